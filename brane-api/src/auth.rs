@@ -0,0 +1,63 @@
+//  AUTH.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:14:42
+//  Last edited:
+//    16 Oct 2026, 01:16:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Authenticates requests that modify the central registry (e.g.,
+//!   annotating datasets) using API tokens.
+//!
+//!   The known tokens are listed in the [`API_TOKENS_FILE`] in the central
+//!   node's certificate directory (see [`brane_cfg::tokens`]).
+//
+
+use std::path::PathBuf;
+
+use brane_cfg::info::Info as _;
+pub use brane_cfg::tokens::{hash_token, ApiToken, ApiTokensFile, API_TOKENS_FILE};
+use log::debug;
+
+pub use crate::errors::AuthError as Error;
+use crate::spec::Context;
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Authenticates the user that sent a request.
+///
+/// # Arguments
+/// - `context`: The [`Context`] of the request, which carries the token it presented (if any).
+///
+/// # Returns
+/// The name of the user that the token belongs to.
+///
+/// # Errors
+/// This function errors if the request did not present a token, if the token is unknown or if we failed to load the [`API_TOKENS_FILE`].
+pub fn authenticate(context: &Context) -> Result<String, Error> {
+    let token: &str = match &context.token {
+        Some(token) => token,
+        None => return Err(Error::MissingToken),
+    };
+
+    // Load the tokens (a missing file simply means no one is allowed)
+    let path: PathBuf = context.certs_path.join(API_TOKENS_FILE);
+    if !path.exists() {
+        debug!("No API tokens file '{}' found; refusing all tokens", path.display());
+        return Err(Error::UnknownToken);
+    }
+    let tokens: ApiTokensFile = match ApiTokensFile::from_path(&path) {
+        Ok(tokens) => tokens,
+        Err(err) => return Err(Error::TokensFileLoadError { path, err }),
+    };
+
+    // Find the matching one
+    match tokens.user(token) {
+        Some(user) => Ok(user.into()),
+        None => Err(Error::UnknownToken),
+    }
+}
+
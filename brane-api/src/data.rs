@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 17:20:55
 //  Last edited:
-//    07 Jun 2023, 16:29:39
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -15,12 +15,11 @@
 
 use std::collections::HashMap;
 
-use brane_cfg::info::Info as _;
 use brane_cfg::infra::InfraFile;
-use brane_cfg::node::NodeConfig;
 use brane_prx::spec::NewPathRequestTlsOptions;
 use log::{debug, error};
 use reqwest::StatusCode;
+use scylla::Session;
 use specifications::data::{AssetInfo, DataInfo};
 use warp::http::{HeaderValue, Response};
 use warp::hyper::Body;
@@ -43,6 +42,36 @@ macro_rules! fail {
 
 
 /***** LIBRARY *****/
+/// Ensures that the table with the annotations of datasets (see the `annotateDataset`-mutation) exists in the given Scylla database.
+///
+/// # Arguments
+/// - `scylla`: The Scylla database session that allows us to talk to it.
+///
+/// # Errors
+/// This function errors if the communication with the given database failed.
+pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
+    if let Err(err) = scylla
+        .query(
+            "CREATE TABLE IF NOT EXISTS brane.dataset_annotations (
+              name text
+            , description text
+            , tags list<text>
+            , contact text
+            , updated bigint
+            , updated_by text
+            , PRIMARY KEY (name)
+        )",
+            &[],
+        )
+        .await
+    {
+        return Err(Error::AnnotationsTableDefineError { err });
+    }
+    Ok(())
+}
+
+
+
 /// Lists the datasets that are known in the instance.
 ///
 /// # Arguments
@@ -56,27 +85,8 @@ macro_rules! fail {
 pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/data/info` (i.e., list all datasets)...");
 
-    // Load the node config file
-    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
-        Ok(config) => config,
-        Err(err) => {
-            error!("Failed to load NodeConfig file: {}", err);
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
-    if !node_config.node.is_central() {
-        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
-        return Err(warp::reject::custom(Error::SecretError));
-    }
-
-    // Load the infrastructure file
-    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            error!("{}", Error::InfrastructureOpenError { path: node_config.node.central().paths.infra.clone(), err });
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
 
     // Iterate through all the locations (each of which have their own registry service)
     let mut datasets: HashMap<String, DataInfo> = HashMap::new();
@@ -164,27 +174,8 @@ pub async fn list(context: Context) -> Result<impl Reply, Rejection> {
 pub async fn get(name: String, context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/data/info/{}` (i.e., get dataset info)...", name);
 
-    // Load the node config file
-    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
-        Ok(config) => config,
-        Err(err) => {
-            error!("Failed to load NodeConfig file: {}", err);
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
-    if !node_config.node.is_central() {
-        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
-        return Err(warp::reject::custom(Error::SecretError));
-    }
-
-    // Load the infrastructure file
-    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            error!("{}", Error::InfrastructureOpenError { path: node_config.node.central().paths.infra.clone(), err });
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
 
     // Iterate through all the locations (each of which have their own registry service)
     let mut dataset: Option<DataInfo> = None;
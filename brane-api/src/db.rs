@@ -0,0 +1,236 @@
+//  DB.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:36:04
+//  Last edited:
+//    15 Oct 2026, 23:26:52
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the prepared statements that `brane-api` sends to the
+//!   Scylla database, as well as a writer that batches concurrent package
+//!   inserts.
+//!
+//!   Statements are prepared once at startup, such that Scylla does not
+//!   have to parse the queries that the CLI and compiler issue constantly
+//!   on every request.
+//
+
+use std::sync::Arc;
+
+use log::debug;
+use scylla::batch::{Batch, BatchType};
+use scylla::prepared_statement::PreparedStatement;
+use scylla::Session;
+use tokio::sync::{mpsc, oneshot};
+
+pub use crate::errors::DbError as Error;
+use crate::packages::PackageUdt;
+
+
+/***** CONSTANTS *****/
+/// The maximum number of package inserts that the [`PackageWriter`] sends in a single batch.
+const MAX_BATCH_SIZE: usize = 64;
+/// The number of package inserts that may be waiting for the [`PackageWriter`] before callers have to wait.
+const QUEUE_SIZE: usize = 256;
+
+/// Lists the versions of a package.
+const PACKAGE_VERSIONS: &str = "SELECT version FROM brane.packages WHERE name = ?";
+/// Gets the image file and the package itself of a specific version of a package.
+const PACKAGE_FILE: &str = "SELECT file, package FROM brane.packages WHERE name = ? AND version = ?";
+/// Searches packages by name.
+const PACKAGE_SEARCH: &str = "SELECT package FROM brane.packages WHERE name LIKE ? ALLOW FILTERING";
+/// Inserts (or replaces) a package.
+const PACKAGE_INSERT: &str = "INSERT INTO brane.packages (name, version, file, package) VALUES (?, ?, ?, ?)";
+/// Deletes a specific version of a package.
+const PACKAGE_DELETE: &str = "DELETE FROM brane.packages WHERE name = ? AND version = ?";
+/// Lists the annotations of all datasets.
+const ANNOTATIONS_LIST: &str = "SELECT name, description, tags, contact, updated, updated_by FROM brane.dataset_annotations";
+/// Gets the annotation of a dataset.
+const ANNOTATION_GET: &str = "SELECT name, description, tags, contact, updated, updated_by FROM brane.dataset_annotations WHERE name = ?";
+/// Inserts (or replaces) the annotation of a dataset.
+const ANNOTATION_INSERT: &str =
+    "INSERT INTO brane.dataset_annotations (name, description, tags, contact, updated, updated_by) VALUES (?, ?, ?, ?, ?, ?)";
+/// Deletes the annotation of a dataset.
+const ANNOTATION_DELETE: &str = "DELETE FROM brane.dataset_annotations WHERE name = ?";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Prepares a single statement.
+///
+/// # Arguments
+/// - `scylla`: The Scylla database session to prepare the statement with.
+/// - `query`: The query to prepare.
+///
+/// # Returns
+/// The [`PreparedStatement`].
+///
+/// # Errors
+/// This function errors if the database failed to prepare the statement, e.g., because it refers to tables that do not exist.
+async fn prepare(scylla: &Session, query: &'static str) -> Result<PreparedStatement, Error> {
+    debug!("Preparing statement '{}'...", query);
+    match scylla.prepare(query).await {
+        Ok(statement) => Ok(statement),
+        Err(err) => Err(Error::StatementPrepareError { query, err }),
+    }
+}
+
+/// Writes batches of package inserts until all [`PackageWriter`]s are dropped.
+///
+/// # Arguments
+/// - `scylla`: The Scylla database session to write to.
+/// - `insert`: The prepared statement that inserts a single package.
+/// - `queue`: The queue of inserts to write, together with where to report their result.
+async fn write_packages(scylla: Arc<Session>, insert: PreparedStatement, mut queue: mpsc::Receiver<PackageInsert>) {
+    while let Some(first) = queue.recv().await {
+        // Take whatever else is waiting already, such that concurrent uploads share a round trip
+        let mut inserts: Vec<PackageInsert> = vec![first];
+        while inserts.len() < MAX_BATCH_SIZE {
+            match queue.try_recv() {
+                Ok(insert) => inserts.push(insert),
+                Err(_) => break,
+            }
+        }
+
+        // Write them as one batch. It is unlogged, as the inserts are independent and (usually) for different partitions.
+        debug!("Writing batch of {} package(s)...", inserts.len());
+        let mut batch: Batch = Batch::new(BatchType::Unlogged);
+        for _ in 0..inserts.len() {
+            batch.append_statement(insert.clone());
+        }
+        let (values, replies): (Vec<_>, Vec<_>) = inserts.into_iter().map(|insert| (insert.values, insert.reply)).unzip();
+        let size: usize = values.len();
+        let res: Result<(), Error> = match scylla.batch(&batch, values).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::BatchWriteError { size, err }),
+        };
+
+        // Tell everyone how it went (they may have given up waiting, which is fine)
+        for reply in replies {
+            let _ = reply.send(res.clone());
+        }
+    }
+    debug!("All package writers dropped; stopping");
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A single package insert waiting for the [`PackageWriter`].
+struct PackageInsert {
+    /// The values to insert, i.e., the name, version, image file and package itself.
+    values: (String, String, String, PackageUdt),
+    /// Where to report the result of the insert.
+    reply:  oneshot::Sender<Result<(), Error>>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Collects the prepared statements that `brane-api` sends to the Scylla database.
+#[derive(Debug)]
+pub struct Statements {
+    /// Lists the versions of a package by name.
+    pub package_versions:  PreparedStatement,
+    /// Gets the image file and the package itself by name and version.
+    pub package_file:      PreparedStatement,
+    /// Searches packages of which the name is LIKE the given pattern.
+    pub package_search:    PreparedStatement,
+    /// Deletes a package by name and version.
+    pub package_delete:    PreparedStatement,
+    /// Lists the annotations of all datasets.
+    pub annotations_list:  PreparedStatement,
+    /// Gets the annotation of a dataset by name.
+    pub annotation_get:    PreparedStatement,
+    /// Inserts (or replaces) the annotation of a dataset.
+    pub annotation_insert: PreparedStatement,
+    /// Deletes the annotation of a dataset by name.
+    pub annotation_delete: PreparedStatement,
+}
+
+impl Statements {
+    /// Prepares all statements.
+    ///
+    /// Note that the tables they refer to must exist (see [`crate::packages::ensure_db_table()`] and [`crate::data::ensure_db_table()`]).
+    ///
+    /// # Arguments
+    /// - `scylla`: The Scylla database session to prepare the statements with.
+    ///
+    /// # Returns
+    /// A new Statements.
+    ///
+    /// # Errors
+    /// This function errors if the database failed to prepare any of the statements.
+    pub async fn prepare(scylla: &Session) -> Result<Self, Error> {
+        Ok(Self {
+            package_versions:  prepare(scylla, PACKAGE_VERSIONS).await?,
+            package_file:      prepare(scylla, PACKAGE_FILE).await?,
+            package_search:    prepare(scylla, PACKAGE_SEARCH).await?,
+            package_delete:    prepare(scylla, PACKAGE_DELETE).await?,
+            annotations_list:  prepare(scylla, ANNOTATIONS_LIST).await?,
+            annotation_get:    prepare(scylla, ANNOTATION_GET).await?,
+            annotation_insert: prepare(scylla, ANNOTATION_INSERT).await?,
+            annotation_delete: prepare(scylla, ANNOTATION_DELETE).await?,
+        })
+    }
+}
+
+
+
+/// Inserts packages into the Scylla database in batches.
+///
+/// Inserts from concurrent uploads (e.g., a CI pipeline pushing many packages at once) are collected by a background task and written to the
+/// database in a single batch, instead of every upload doing its own round trip.
+#[derive(Clone, Debug)]
+pub struct PackageWriter {
+    /// The queue of the background task.
+    queue: mpsc::Sender<PackageInsert>,
+}
+
+impl PackageWriter {
+    /// Prepares the insert statement and spawns the background task that writes the batches.
+    ///
+    /// # Arguments
+    /// - `scylla`: The Scylla database session to write to.
+    ///
+    /// # Returns
+    /// A new PackageWriter. The background task stops once it (and all its clones) are dropped.
+    ///
+    /// # Errors
+    /// This function errors if the database failed to prepare the insert statement.
+    pub async fn spawn(scylla: Arc<Session>) -> Result<Self, Error> {
+        let insert: PreparedStatement = prepare(&scylla, PACKAGE_INSERT).await?;
+        let (queue, receiver): (mpsc::Sender<PackageInsert>, mpsc::Receiver<PackageInsert>) = mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(write_packages(scylla, insert, receiver));
+        Ok(Self { queue })
+    }
+
+    /// Inserts (or replaces) a package, and waits until it is written.
+    ///
+    /// # Arguments
+    /// - `package`: The [`PackageUdt`] to insert.
+    /// - `file`: The path to the image file of the package.
+    ///
+    /// # Errors
+    /// This function errors if the batch with the package failed to be written, or if the background task has stopped.
+    pub async fn insert(&self, package: PackageUdt, file: String) -> Result<(), Error> {
+        let (reply, result): (oneshot::Sender<Result<(), Error>>, oneshot::Receiver<Result<(), Error>>) = oneshot::channel();
+        let values: (String, String, String, PackageUdt) = (package.name.clone(), package.version.clone(), file, package);
+        if self.queue.send(PackageInsert { values, reply }).await.is_err() {
+            return Err(Error::WriterStopped);
+        }
+        match result.await {
+            Ok(res) => res,
+            Err(_) => Err(Error::WriterStopped),
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    04 Feb 2022, 10:35:12
 //  Last edited:
-//    07 Jun 2023, 16:29:32
+//    16 Oct 2026, 00:40:59
 //  Auto updated?
 //    Yes
 //
@@ -45,6 +45,32 @@ impl Error for ApiError {}
 
 
 
+/// Contains errors relating to talking to the Scylla database (see [`crate::db`]).
+#[derive(Clone, Debug)]
+pub enum DbError {
+    /// Failed to prepare a statement.
+    StatementPrepareError { query: &'static str, err: scylla::transport::errors::QueryError },
+    /// Failed to write a batch of packages.
+    BatchWriteError { size: usize, err: scylla::transport::errors::QueryError },
+    /// The background task that writes packages has stopped.
+    WriterStopped,
+}
+
+impl Display for DbError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DbError::*;
+        match self {
+            StatementPrepareError { query, err } => write!(f, "Failed to prepare statement '{query}' in the Scylla database: {err}"),
+            BatchWriteError { size, err } => write!(f, "Failed to write batch of {size} package(s) to the Scylla database: {err}"),
+            WriterStopped => write!(f, "The task that writes packages to the Scylla database has stopped"),
+        }
+    }
+}
+
+impl Error for DbError {}
+
+
+
 /// Contains errors relating to the `/infra` path (and nested).
 #[derive(Debug)]
 pub enum InfraError {
@@ -102,6 +128,60 @@ impl warp::reject::Reject for InfraError {}
 
 
 
+/// Contains errors relating to the `/federation` path (and nested).
+#[derive(Debug)]
+pub enum FederationError {
+    /// Failed to load the file with peer instances.
+    PeersFileLoadError { path: PathBuf, err: brane_cfg::peers::Error },
+
+    /// Failed to send a request to a peer.
+    RequestError { peer: String, address: String, err: reqwest::Error },
+    /// The peer did not reply with an OK
+    RequestFailure { peer: String, address: String, code: StatusCode },
+    /// Failed to read the body sent by a peer.
+    ResponseBodyError { peer: String, address: String, err: reqwest::Error },
+    /// Failed to parse the body sent by a peer.
+    ResponseParseError { peer: String, address: String, err: serde_json::Error },
+    /// The peer replied with GraphQL errors instead of data.
+    GraphQlError { peer: String, address: String, errors: String },
+    /// Failed to serialize the response body.
+    SerializeError { what: &'static str, err: serde_json::Error },
+
+    /// An internal error occurred that we would not like to divulge.
+    SecretError,
+}
+
+impl Display for FederationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use FederationError::*;
+        match self {
+            PeersFileLoadError { path, err } => write!(f, "Failed to load peers file '{}': {}", path.display(), err),
+
+            RequestError { peer, address, err } => write!(f, "Failed to send request to peer '{peer}' at '{address}': {err}"),
+            RequestFailure { peer, address, code } => write!(
+                f,
+                "Request to peer '{}' at '{}' failed with status code {} ({})",
+                peer,
+                address,
+                code,
+                code.canonical_reason().unwrap_or("???")
+            ),
+            ResponseBodyError { peer, address, err } => write!(f, "Failed to get the body of the response of peer '{peer}' at '{address}': {err}"),
+            ResponseParseError { peer, address, err } => write!(f, "Failed to parse response of peer '{peer}' at '{address}' as JSON: {err}"),
+            GraphQlError { peer, address, errors } => write!(f, "Peer '{peer}' at '{address}' returned errors: {errors}"),
+            SerializeError { what, err } => write!(f, "Failed to serialize {what}: {err}"),
+
+            SecretError => write!(f, "An internal error has occurred"),
+        }
+    }
+}
+
+impl Error for FederationError {}
+
+impl warp::reject::Reject for FederationError {}
+
+
+
 /// Contains errors relating to the `/data` path (and nested).
 #[derive(Debug)]
 pub enum DataError {
@@ -123,6 +203,9 @@ pub enum DataError {
     /// Failed to serialize the response body.
     SerializeError { what: &'static str, err: serde_json::Error },
 
+    /// Failed to define the `brane.dataset_annotations` table.
+    AnnotationsTableDefineError { err: scylla::transport::errors::QueryError },
+
     /// An internal error occurred that we would not like to divulge.
     SecretError,
 }
@@ -143,6 +226,8 @@ impl Display for DataError {
             ResponseParseError { address, err } => write!(f, "Failed to parse response from '{address}' as JSON: {err}"),
             SerializeError { what, err } => write!(f, "Failed to serialize {what}: {err}"),
 
+            AnnotationsTableDefineError { err } => write!(f, "Failed to define the 'brane.dataset_annotations' table in the Scylla database: {err}"),
+
             SecretError => write!(f, "An internal error has occurred"),
         }
     }
@@ -161,15 +246,23 @@ pub enum PackageError {
     FunctionsSerializeError { name: String, err: serde_json::Error },
     /// Failed to serialize the types in a PackageInfo.
     TypesSerializeError { name: String, err: serde_json::Error },
+    /// Failed to serialize the minimum resources in a PackageInfo.
+    ResourcesSerializeError { name: String, err: serde_json::Error },
+    /// Failed to serialize the vulnerability scan report in a PackageInfo.
+    ScanSerializeError { name: String, err: serde_json::Error },
     /// The given PackageInfo did not have a digest registered.
     MissingDigest { name: String },
 
     /// Failed to define the `brane.package` type in the Scylla database.
     PackageTypeDefineError { err: scylla::transport::errors::QueryError },
+    /// Failed to query the fields of the `brane.package` type in the Scylla database.
+    PackageTypeQueryError { err: scylla::transport::errors::QueryError },
+    /// Failed to add a field to an existing `brane.package` type in the Scylla database.
+    PackageTypeUpgradeError { field: String, err: scylla::transport::errors::QueryError },
     /// Failed to define the package table in the Scylla database.
     PackageTableDefineError { err: scylla::transport::errors::QueryError },
     /// Failed to insert a new package in the database.
-    PackageInsertError { name: String, err: scylla::transport::errors::QueryError },
+    PackageInsertError { name: String, err: DbError },
 
     /// Failed to query for the given package in the Scylla database.
     VersionsQueryError { name: String, err: scylla::transport::errors::QueryError },
@@ -179,6 +272,8 @@ pub enum PackageError {
     NoVersionsFound { name: String },
     /// Failed to query the database for the file of the given package.
     PathQueryError { name: String, version: Version, err: scylla::transport::errors::QueryError },
+    /// Failed to parse the row of a package in the Scylla database.
+    PackageRowParseError { name: String, version: Version, err: scylla::cql_to_rust::FromRowError },
     /// The given package was unknown.
     UnknownPackage { name: String, version: Version },
     /// Failed to get the metadata of a file.
@@ -228,6 +323,16 @@ pub enum PackageError {
     PackageInfoParseError { path: PathBuf, err: serde_yaml::Error },
     /// Failed to move the temporary image to its final destination.
     FileMoveError { from: PathBuf, to: PathBuf, err: std::io::Error },
+
+    /// Failed to unpack the submitted build context.
+    ContextUnpackError { tarball: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to launch the command that builds the image.
+    BuildLaunchError { command: String, err: std::io::Error },
+    /// Failed to read the file with the ID of the built image.
+    ImageIdReadError { path: PathBuf, err: std::io::Error },
+
+    /// Failed to use the OCI registry that distributes a package.
+    OciError { reference: String, err: brane_shr::oci::Error },
 }
 
 impl Display for PackageError {
@@ -236,9 +341,15 @@ impl Display for PackageError {
         match self {
             FunctionsSerializeError { name, err } => write!(f, "Failed to serialize functions in package '{name}': {err}"),
             TypesSerializeError { name, err } => write!(f, "Failed to serialize types in package '{name}': {err}"),
+            ResourcesSerializeError { name, err } => write!(f, "Failed to serialize minimum resources in package '{name}': {err}"),
+            ScanSerializeError { name, err } => write!(f, "Failed to serialize vulnerability scan report of package '{name}': {err}"),
             MissingDigest { name } => write!(f, "Package '{name}' does not have a digest specified"),
 
             PackageTypeDefineError { err } => write!(f, "Failed to define the 'brane.package' type in the Scylla database: {err}"),
+            PackageTypeQueryError { err } => write!(f, "Failed to query the fields of the 'brane.package' type in the Scylla database: {err}"),
+            PackageTypeUpgradeError { field, err } => {
+                write!(f, "Failed to add field '{field}' to the 'brane.package' type in the Scylla database: {err}")
+            },
             PackageTableDefineError { err } => write!(f, "Failed to define the 'brane.packages' table in the Scylla database: {err}"),
             PackageInsertError { name, err } => write!(f, "Failed to insert package '{name}' into the Scylla database: {err}"),
 
@@ -246,6 +357,7 @@ impl Display for PackageError {
             VersionParseError { raw, err } => write!(f, "Failed to parse '{raw}' as a valid version string: {err}"),
             NoVersionsFound { name } => write!(f, "No versions found for package '{name}'"),
             PathQueryError { name, version, err } => write!(f, "Failed to get path of package '{name}', version {version}: {err}"),
+            PackageRowParseError { name, version, err } => write!(f, "Failed to parse database row of package '{name}', version {version}: {err}"),
             UnknownPackage { name, version } => write!(f, "No package '{name}' exists (or has version {version})"),
             FileMetadataError { path, err } => write!(f, "Failed to get metadata of file '{}': {}", path.display(), err),
             FileOpenError { path, err } => write!(f, "Failed to open file '{}': {}", path.display(), err),
@@ -281,8 +393,93 @@ impl Display for PackageError {
             PackageInfoReadError { path, err } => write!(f, "Failed to read extracted package info file '{}': {}", path.display(), err),
             PackageInfoParseError { path, err } => write!(f, "Failed to parse extracted package info file '{}' as YAML: {}", path.display(), err),
             FileMoveError { from, to, err } => write!(f, "Failed to move '{}' to '{}': {}", from.display(), to.display(), err),
+
+            ContextUnpackError { tarball, target, err } => {
+                write!(f, "Failed to unpack build context '{}' to '{}': {}", tarball.display(), target.display(), err)
+            },
+            BuildLaunchError { command, err } => write!(f, "Failed to launch build command '{command}': {err}"),
+            ImageIdReadError { path, err } => write!(f, "Failed to read image ID file '{}': {}", path.display(), err),
+
+            OciError { reference, err } => write!(f, "Failed to use OCI image '{reference}': {err}"),
         }
     }
 }
 
 impl Error for PackageError {}
+
+
+
+/// Contains errors relating to authenticating requests that modify the central registry.
+#[derive(Debug)]
+pub enum AuthError {
+    /// The request did not carry a token.
+    MissingToken,
+    /// The request carried a token that we don't know.
+    UnknownToken,
+    /// Failed to load the file with the known tokens.
+    TokensFileLoadError { path: PathBuf, err: brane_cfg::info::YamlError },
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AuthError::*;
+        match self {
+            MissingToken => write!(f, "This operation requires an API token (give it as 'Authorization: Bearer <TOKEN>')"),
+            UnknownToken => write!(f, "Invalid API token"),
+            TokensFileLoadError { path, err } => write!(f, "Failed to load API tokens file '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for AuthError {}
+
+
+
+/// Contains errors relating to scanning package images for vulnerabilities (see [`crate::scan`]).
+#[derive(Debug)]
+pub enum ScanError {
+    /// The given scanner is not one we know.
+    UnknownScanner { raw: String },
+    /// Failed to launch the scanner.
+    CommandLaunchError { command: String, err: std::io::Error },
+    /// The scanner exited with a non-zero exit code.
+    CommandFailure { command: String, code: Option<i32>, stderr: String },
+    /// Failed to open the image to send it to a remote scanner.
+    ImageOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to send the image to a remote scanner.
+    RequestError { endpoint: String, err: reqwest::Error },
+    /// The remote scanner failed to scan the image.
+    RequestFailure { endpoint: String, code: StatusCode, message: Option<String> },
+    /// Failed to parse the report of the scanner.
+    ReportParseError { scanner: &'static str, err: serde_json::Error },
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ScanError::*;
+        match self {
+            UnknownScanner { raw } => write!(f, "Unknown vulnerability scanner '{raw}' (options are 'trivy' or 'grype')"),
+            CommandLaunchError { command, err } => write!(f, "Failed to launch scanner command '{command}': {err}"),
+            CommandFailure { command, code, stderr } => write!(
+                f,
+                "Scanner command '{}' failed with exit code {}:\n{}",
+                command,
+                code.map(|code| code.to_string()).unwrap_or_else(|| "???".into()),
+                stderr
+            ),
+            ImageOpenError { path, err } => write!(f, "Failed to open image '{}' to scan: {}", path.display(), err),
+            RequestError { endpoint, err } => write!(f, "Failed to send image to scanner at '{endpoint}': {err}"),
+            RequestFailure { endpoint, code, message } => write!(
+                f,
+                "Scanner at '{}' failed with status code {} ({}){}",
+                endpoint,
+                code,
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
+            ReportParseError { scanner, err } => write!(f, "Failed to parse report of scanner '{scanner}': {err}"),
+        }
+    }
+}
+
+impl Error for ScanError {}
@@ -0,0 +1,294 @@
+//  FEDERATION.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:16:02
+//  Last edited:
+//    15 Oct 2026, 22:16:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `/federation` paths, which aggregate the packages and
+//!   datasets of the peer instances listed in the peers file (see
+//!   [`brane_cfg::peers`]).
+//!
+//!   Resources of peers are namespaced by the name under which the peer
+//!   is listed. Peers that cannot be reached are skipped, such that one
+//!   partner being down does not hide the others.
+//
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use brane_cfg::info::Info as _;
+use brane_cfg::peers::PeersFile;
+use log::{debug, error};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use specifications::data::DataInfo;
+use specifications::registering::FederatedPackage;
+use warp::http::{HeaderValue, Response};
+use warp::hyper::Body;
+use warp::{Rejection, Reply};
+
+pub use crate::errors::FederationError as Error;
+use crate::spec::Context;
+
+
+/***** CONSTANTS *****/
+/// The GraphQL query that we send to peers to search their packages.
+const PACKAGES_QUERY: &str = "query SearchPackages($term: String) { packages(term: $term) { name, version, kind, description } }";
+
+/// The time we give a peer to respond.
+const PEER_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+
+
+
+/***** HELPER MACROS *****/
+/// Quits a path callback with a SecretError.
+macro_rules! fail {
+    () => {
+        return Err(warp::reject::custom(Error::SecretError))
+    };
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Loads the peers file.
+///
+/// # Arguments
+/// - `context`: The Context that carries the path to the peers file.
+///
+/// # Returns
+/// The loaded [`PeersFile`].
+///
+/// # Errors
+/// This function errors (i.e., rejects) with a 404 if federation is not enabled, or with a SecretError if we failed to load the peers file.
+fn load_peers(context: &Context) -> Result<PeersFile, Rejection> {
+    let path: &PathBuf = match &context.peers_path {
+        Some(path) => path,
+        None => {
+            debug!("Federation is not enabled (no peers file given)");
+            return Err(warp::reject::not_found());
+        },
+    };
+    match PeersFile::from_path(path) {
+        Ok(peers) => Ok(peers),
+        Err(err) => {
+            error!("{}", Error::PeersFileLoadError { path: path.clone(), err });
+            fail!();
+        },
+    }
+}
+
+/// Sends a GET- or POST-request to a peer and parses its response as JSON.
+///
+/// # Arguments
+/// - `client`: The [`Client`] to send the request with.
+/// - `peer`: The name of the peer (used for debugging).
+/// - `address`: The URL to send the request to.
+/// - `body`: If given, sends a POST-request with this JSON body instead of a GET-request.
+///
+/// # Returns
+/// The parsed response.
+///
+/// # Errors
+/// This function errors if we failed to send the request, if the peer did not reply with 200 OK or if we failed to parse its reply.
+async fn request_peer<T: for<'de> Deserialize<'de>>(client: &Client, peer: &str, address: String, body: Option<Value>) -> Result<T, Error> {
+    let req = match body {
+        Some(body) => client.post(&address).header("Content-Type", "application/json").body(body.to_string()),
+        None => client.get(&address),
+    };
+    let res: reqwest::Response = match req.send().await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestError { peer: peer.into(), address, err }),
+    };
+    if res.status() != StatusCode::OK {
+        return Err(Error::RequestFailure { peer: peer.into(), address, code: res.status() });
+    }
+    let body: String = match res.text().await {
+        Ok(body) => body,
+        Err(err) => return Err(Error::ResponseBodyError { peer: peer.into(), address, err }),
+    };
+    match serde_json::from_str(&body) {
+        Ok(res) => Ok(res),
+        Err(err) => {
+            debug!("Received body: \"\"\"{}\"\"\"", body);
+            Err(Error::ResponseParseError { peer: peer.into(), address, err })
+        },
+    }
+}
+
+/// Serializes the given value to a JSON response.
+///
+/// # Arguments
+/// - `what`: A description of the value (used for debugging).
+/// - `value`: The value to serialize.
+///
+/// # Returns
+/// A response that can be send to client.
+///
+/// # Errors
+/// This function errors (i.e., rejects) if we failed to serialize the value.
+fn reply_json(what: &'static str, value: &impl serde::Serialize) -> Result<Response<Body>, Rejection> {
+    let body: String = match serde_json::to_string(value) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError { what, err });
+            fail!();
+        },
+    };
+    let body_len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body_len));
+    Ok(response)
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the query parameters of `/federation/packages`.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SearchOptions {
+    /// Only returns packages whose name contains this term.
+    pub term: Option<String>,
+}
+
+/// Defines the (relevant part of the) GraphQL response of a peer.
+#[derive(Deserialize)]
+struct GraphQlResponse {
+    /// The data returned, if any.
+    data:   Option<PackagesData>,
+    /// The errors returned, if any.
+    errors: Option<Value>,
+}
+
+/// Defines the data in the GraphQL response of a peer.
+#[derive(Deserialize)]
+struct PackagesData {
+    /// The packages found.
+    packages: Vec<PeerPackage>,
+}
+
+/// Defines a package as returned by a peer.
+#[derive(Deserialize)]
+struct PeerPackage {
+    /// The name of the package.
+    name:        String,
+    /// The version of the package.
+    version:     String,
+    /// The kind of the package.
+    kind:        String,
+    /// The description of the package, if any.
+    description: Option<String>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the packages of all peer instances.
+///
+/// # Arguments
+/// - `options`: The [`SearchOptions`] given as query parameters.
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a list of [`FederatedPackage`]s.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if federation is disabled or if we failed to load the peers file.
+pub async fn packages(options: SearchOptions, context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/federation/packages` (i.e., list packages of peers)...");
+    let peers: PeersFile = load_peers(&context)?;
+    let client: Client = match Client::builder().timeout(PEER_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            error!("Failed to build HTTP client: {}", err);
+            fail!();
+        },
+    };
+
+    // Ask every peer's GraphQL endpoint
+    let mut packages: Vec<FederatedPackage> = vec![];
+    for (name, peer) in peers.peers {
+        let address: String = format!("{}/graphql", peer.api);
+        let query: Value = serde_json::json!({ "query": PACKAGES_QUERY, "variables": { "term": options.term } });
+        let res: GraphQlResponse = match request_peer(&client, &name, address.clone(), Some(query)).await {
+            Ok(res) => res,
+            Err(err) => {
+                error!("{} (skipping peer)", err);
+                continue;
+            },
+        };
+        let data: PackagesData = match res.data {
+            Some(data) => data,
+            None => {
+                error!("{} (skipping peer)", Error::GraphQlError { peer: name, address, errors: res.errors.unwrap_or_default().to_string() });
+                continue;
+            },
+        };
+        debug!("Peer '{}' has {} package(s)", name, data.packages.len());
+        packages.extend(data.packages.into_iter().map(|package| FederatedPackage {
+            instance:    name.clone(),
+            name:        package.name,
+            version:     package.version,
+            kind:        package.kind,
+            description: package.description,
+        }));
+    }
+
+    reply_json("list of federated packages", &packages)
+}
+
+
+
+/// Lists the datasets of all peer instances.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a map of [`DataInfo`]s, mapped by `<PEER>/<NAME>`.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if federation is disabled or if we failed to load the peers file.
+pub async fn datasets(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/federation/data/info` (i.e., list datasets of peers)...");
+    let peers: PeersFile = load_peers(&context)?;
+    let client: Client = match Client::builder().timeout(PEER_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            error!("Failed to build HTTP client: {}", err);
+            fail!();
+        },
+    };
+
+    // Ask every peer's data endpoint
+    let mut datasets: HashMap<String, DataInfo> = HashMap::new();
+    for (name, peer) in peers.peers {
+        let address: String = format!("{}/data/info", peer.api);
+        let infos: HashMap<String, DataInfo> = match request_peer(&client, &name, address, None).await {
+            Ok(infos) => infos,
+            Err(err) => {
+                error!("{} (skipping peer)", err);
+                continue;
+            },
+        };
+        debug!("Peer '{}' has {} dataset(s)", name, infos.len());
+        datasets.extend(infos.into_iter().map(|(dataset, info)| (format!("{name}/{dataset}"), info)));
+    }
+
+    reply_json("list of federated datasets", &datasets)
+}
@@ -6,7 +6,7 @@
  * Created:
  *   12 Jan 2022, 13:29:01
  * Last edited:
- *   08 May 2022, 14:40:04
+ *   16 Oct 2026, 02:08:06
  * Auto updated?
  *   Yes
  *
@@ -14,16 +14,24 @@
  *   Contains code for the health part of the brane API.
 **/
 
-use warp::http::HeaderValue;
+use brane_cfg::certs::ExpiryMonitor;
+use warp::http::{HeaderValue, StatusCode};
 use warp::hyper::Body;
 use warp::reply::Response;
 use warp::{Rejection, Reply};
 
 
-pub async fn handle() -> Result<impl Reply, Rejection> {
-    let mut response = Response::new(Body::from("OK!\n"));
+pub async fn handle(cert_monitor: ExpiryMonitor) -> Result<impl Reply, Rejection> {
+    // Report a degraded health if any of our certificates are about to expire, and that we're unavailable if they have
+    let (expired, body): (bool, String) = cert_monitor.health();
 
-    response.headers_mut().insert("Content-Length", HeaderValue::from(4));
+    let len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+
+    response.headers_mut().insert("Content-Length", HeaderValue::from(len));
+    if expired {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
 
     Ok(response)
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    02 Nov 2022, 16:21:33
 //  Last edited:
-//    13 Jul 2023, 13:58:57
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -14,11 +14,10 @@
 
 use std::collections::{HashMap, HashSet};
 
-use brane_cfg::info::Info as _;
-use brane_cfg::infra::{InfraFile, InfraLocation};
-use brane_cfg::node::NodeConfig;
+use brane_cfg::infra::{InfraFile, InfraLocation, LocationCapabilities};
 use brane_prx::spec::NewPathRequestTlsOptions;
 use log::{debug, error};
+use serde::Serialize;
 use specifications::address::Address;
 use specifications::package::Capability;
 use warp::hyper::header::HeaderValue;
@@ -29,6 +28,20 @@ pub use crate::errors::InfraError as Error;
 use crate::spec::Context;
 
 
+/***** AUXILLARY *****/
+/// Describes the labels and capabilities of a location as they are returned by `/infra/capabilities`.
+#[derive(Clone, Debug, Serialize)]
+struct LocationDescription<'a> {
+    /// The labels of the location.
+    labels: &'a HashMap<String, String>,
+    /// The structured capabilities of the location.
+    capabilities: &'a LocationCapabilities,
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Lists the registries at each location.
 ///
@@ -43,27 +56,8 @@ use crate::spec::Context;
 pub async fn registries(context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/infra/registries` (i.e., list all registry endpoints)...");
 
-    // Load the node config file
-    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
-        Ok(config) => config,
-        Err(err) => {
-            error!("Failed to load NodeConfig file: {}", err);
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
-    if !node_config.node.is_central() {
-        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
-        return Err(warp::reject::custom(Error::SecretError));
-    }
-
-    // Load the infrastructure file
-    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            error!("{}", Error::InfrastructureOpenError { path: node_config.node.central().paths.infra.clone(), err });
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
 
     // Iterate through all of the regitries
     let mut locations: HashMap<String, Address> = HashMap::new();
@@ -105,27 +99,8 @@ pub async fn registries(context: Context) -> Result<impl Reply, Rejection> {
 pub async fn get_registry(loc: String, context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/infra/registries/{}` (i.e., get location registry address)...", loc);
 
-    // Load the node config file
-    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
-        Ok(config) => config,
-        Err(err) => {
-            error!("Failed to load NodeConfig file: {}", err);
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
-    if !node_config.node.is_central() {
-        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
-        return Err(warp::reject::custom(Error::SecretError));
-    }
-
-    // Load the infrastructure file
-    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            error!("{}", Error::InfrastructureOpenError { path: node_config.node.central().paths.infra.clone(), err });
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
 
     // Find the location requested
     let info: &InfraLocation = match infra.get(&loc) {
@@ -149,6 +124,46 @@ pub async fn get_registry(loc: String, context: Context) -> Result<impl Reply, R
 
 
 
+/// Lists the labels and (structured) capabilities of all locations, as declared in the infrastructure file.
+///
+/// # Arguments
+/// - `context`: The Context that contains stuff we need to run.
+///
+/// # Returns
+/// A response that can be send to client. Specifically, it will contain a map of location names to their labels and capabilities.
+///
+/// # Errors
+/// This function may error (i.e., reject the request) if we failed to load the infrastructure file.
+pub async fn list_capabilities(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Handling GET on `/infra/capabilities` (i.e., list all location capabilities)...");
+
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
+
+    // Collect the descriptions
+    let locations: HashMap<&String, LocationDescription> =
+        infra.iter().map(|(name, loc)| (name, LocationDescription { labels: &loc.labels, capabilities: &loc.capabilities })).collect();
+
+    // Now serialize this map
+    let body: String = match serde_json::to_string(&locations) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", Error::SerializeError { what: "list of all location capabilities", err });
+            return Err(warp::reject::custom(Error::SecretError));
+        },
+    };
+    let body_len: usize = body.len();
+
+    // Create the respones around it
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body_len));
+
+    // Done
+    Ok(response)
+}
+
+
+
 /// Returns the capabilities for the requested location.
 ///
 /// # Arguments
@@ -163,27 +178,8 @@ pub async fn get_registry(loc: String, context: Context) -> Result<impl Reply, R
 pub async fn get_capabilities(loc: String, context: Context) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/infra/capabilities/{}` (i.e., get location capabilities)...", loc);
 
-    // Load the node config file
-    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
-        Ok(config) => config,
-        Err(err) => {
-            error!("Failed to load NodeConfig file: {}", err);
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
-    if !node_config.node.is_central() {
-        error!("Provided node config file '{}' is not for a central node", context.node_config_path.display());
-        return Err(warp::reject::custom(Error::SecretError));
-    }
-
-    // Load the infrastructure file
-    let infra: InfraFile = match InfraFile::from_path(&node_config.node.central().paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            error!("{}", Error::InfrastructureOpenError { path: node_config.node.central().paths.infra.clone(), err });
-            return Err(warp::reject::custom(Error::SecretError));
-        },
-    };
+    // Get the most recent version of the infrastructure file
+    let infra: InfraFile = InfraFile::clone(&context.infra.current());
 
     // Find the location requested
     let info: &InfraLocation = match infra.get(&loc) {
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 15:15:06
 //  Last edited:
-//    02 Nov 2022, 16:21:44
+//    16 Oct 2026, 00:40:59
 //  Auto updated?
 //    Yes
 //
@@ -14,11 +14,15 @@
 //
 
 // Declare modules
+pub mod auth;
 pub mod data;
+pub mod db;
 pub mod errors;
+pub mod federation;
 pub mod health;
 pub mod infra;
 pub mod packages;
+pub mod scan;
 pub mod schema;
 pub mod spec;
 pub mod version;
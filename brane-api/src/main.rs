@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 15:15:36
 //  Last edited:
-//    03 Jan 2024, 14:37:08
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -12,23 +12,32 @@
 //!   Entrypoint to the `brane-job` service.
 //
 
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
+use brane_api::db::{PackageWriter, Statements};
 use brane_api::errors::ApiError;
+use brane_api::scan::{Scanner, ScannerKind};
 use brane_api::schema::{Mutations, Query, Schema};
 use brane_api::spec::Context;
-use brane_api::{data, health, infra, packages, version};
-use brane_cfg::info::Info as _;
+use brane_api::{data, federation, health, infra, packages, version};
+use brane_cfg::certs::ExpiryMonitor;
+use brane_cfg::info::{Info as _, InfoWatcher};
+use brane_cfg::infra::InfraFile;
 use brane_cfg::node::{CentralConfig, NodeConfig};
+use brane_cfg::tokens::bearer_token;
 use brane_prx::client::ProxyClient;
+use brane_shr::logging::{self, LogFormat};
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::trace;
 use juniper::EmptySubscription;
 use log::{debug, error, info, warn, LevelFilter};
-use scylla::{Session, SessionBuilder};
+use scylla::transport::session::PoolSize;
+use scylla::{ExecutionProfile, Session, SessionBuilder};
+use specifications::package::Severity;
 use tokio::signal::unix::{signal, Signal, SignalKind};
 use warp::Filter;
 
@@ -51,6 +60,82 @@ struct Opts {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+
+    /// The number of days before certificate expiry at which we start warning.
+    #[clap(
+        long,
+        default_value = "30",
+        help = "The number of days before a certificate expires at which the service starts warning about it and reports a degraded health.",
+        env = "CERT_EXPIRY_WINDOW"
+    )]
+    cert_expiry_window: u64,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
+
+    /// The file with peer instances to federate with.
+    #[clap(
+        long,
+        help = "If given, enables federation with the peer instances listed in the given YAML file. Their packages and datasets are then available \
+                under the `/federation` paths, namespaced by the name of the peer.",
+        env = "PEERS_PATH"
+    )]
+    peers: Option<PathBuf>,
+
+    /// Whether to build packages for clients.
+    #[clap(
+        long,
+        help = "If given, clients may build packages on this node using `brane build --remote`. This requires Docker (with BuildX) to be reachable \
+                from this service, e.g., by mounting the Docker socket.",
+        env = "ENABLE_BUILDS"
+    )]
+    builds: bool,
+
+    /// The scanner to scan uploaded packages with.
+    #[clap(
+        long,
+        help = "If given, scans the image of every uploaded or built package for vulnerabilities with the given scanner ('trivy' or 'grype') and \
+                stores the findings with the package. Unless '--scanner-endpoint' is given, the scanner must be installed alongside this service. \
+                Packages imported from OCI registries are not scanned.",
+        env = "SCANNER"
+    )]
+    scanner: Option<ScannerKind>,
+    /// The endpoint of a remote scanner.
+    #[clap(
+        long,
+        requires = "scanner",
+        help = "If given, POSTs package images to this endpoint for scanning instead of running the scanner locally. It should reply with the JSON \
+                report of the scanner given with '--scanner'.",
+        env = "SCANNER_ENDPOINT"
+    )]
+    scanner_endpoint: Option<String>,
+    /// The severity from which to reject packages.
+    #[clap(
+        long,
+        requires = "scanner",
+        help = "If given, rejects packages with vulnerabilities of at least this severity ('negligible', 'low', 'medium', 'high' or 'critical').",
+        env = "SCAN_REJECT"
+    )]
+    scan_reject: Option<Severity>,
+
+    /// The number of connections to the Scylla database per shard.
+    #[clap(
+        long,
+        default_value = "2",
+        help = "The number of connections that we keep open to every shard of every Scylla node. Increase this if many clients query the registry \
+                concurrently.",
+        env = "SCYLLA_POOL_SIZE"
+    )]
+    scylla_pool_size: NonZeroUsize,
+    /// The timeout for queries to the Scylla database.
+    #[clap(long, default_value = "5", help = "The number of seconds after which a query to the Scylla database times out.", env = "SCYLLA_TIMEOUT")]
+    scylla_timeout:   u64,
 }
 
 
@@ -64,13 +149,8 @@ async fn main() {
     let opts = Opts::parse();
 
     // Configure logger.
-    let mut logger = env_logger::builder();
-    logger.format_module_path(false);
-
-    if opts.debug {
-        logger.filter_level(LevelFilter::Debug).init();
-    } else {
-        logger.filter_level(LevelFilter::Info).init();
+    if let Err(err) = logging::init("brane-api", opts.log_format, if opts.debug { LevelFilter::Debug } else { LevelFilter::Info }) {
+        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
     }
     info!("Initializing brane-job v{}...", env!("CARGO_PKG_VERSION"));
 
@@ -91,11 +171,29 @@ async fn main() {
         },
     };
 
+    // Watch the infrastructure file for changes. Endpoints read the current version from the watcher, so changes are picked up without a restart.
+    let infra: InfoWatcher<InfraFile> = match InfraFile::watch(central.paths.infra.clone(), Duration::from_secs(5)).await {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            error!("{}", trace!(("Failed to watch infrastructure file"), err));
+            std::process::exit(1);
+        },
+    };
+    let mut watcher: InfoWatcher<InfraFile> = infra.clone();
+    tokio::spawn(async move {
+        while let Some(infra) = watcher.changed().await {
+            info!("Reloaded infrastructure file '{}' ({} location(s))", watcher.path().display(), infra.len());
+        }
+    });
+
     // Configure Scylla.
     debug!("Connecting to scylla...");
+    let profile: ExecutionProfile = ExecutionProfile::builder().request_timeout(Some(Duration::from_secs(opts.scylla_timeout))).build();
     let scylla = match SessionBuilder::new()
         .known_node(&central.services.aux_scylla.address.to_string())
         .connection_timeout(Duration::from_secs(3))
+        .pool_size(PoolSize::PerShard(opts.scylla_pool_size))
+        .default_execution_profile_handle(profile.into_handle())
         .build()
         .await
     {
@@ -114,15 +212,55 @@ async fn main() {
     if let Err(err) = packages::ensure_db_table(&scylla).await {
         error!("Failed to ensure database table: {}", err)
     };
+    if let Err(err) = data::ensure_db_table(&scylla).await {
+        error!("Failed to ensure database table: {}", err)
+    };
+
+    debug!("Preparing statements...");
+    let statements: Arc<Statements> = match Statements::prepare(&scylla).await {
+        Ok(statements) => Arc::new(statements),
+        Err(err) => {
+            error!("{}", trace!(("Failed to prepare database statements"), err));
+            std::process::exit(1);
+        },
+    };
+    let scylla = Arc::new(scylla);
+    let packages_writer: PackageWriter = match PackageWriter::spawn(scylla.clone()).await {
+        Ok(writer) => writer,
+        Err(err) => {
+            error!("{}", trace!(("Failed to start package writer"), err));
+            std::process::exit(1);
+        },
+    };
 
     // Configure Juniper.
     let node_config_path: PathBuf = opts.node_config_path;
-    let scylla = Arc::new(scylla);
+    let certs_path: PathBuf = central.paths.certs.clone();
+    let peers_path: Option<PathBuf> = opts.peers;
+    let builds: bool = opts.builds;
+    let scanner: Option<Scanner> = opts.scanner.map(|kind| Scanner { kind, endpoint: opts.scanner_endpoint, reject: opts.scan_reject });
     let proxy: Arc<ProxyClient> = Arc::new(ProxyClient::new(central.services.prx.address()));
-    let context = warp::any().map(move || Context { node_config_path: node_config_path.clone(), scylla: scylla.clone(), proxy: proxy.clone() });
+    let context = warp::any().map(move || Context {
+        node_config_path: node_config_path.clone(),
+        infra: infra.clone(),
+        scylla: scylla.clone(),
+        statements: statements.clone(),
+        packages_writer: packages_writer.clone(),
+        proxy: proxy.clone(),
+        certs_path: certs_path.clone(),
+        token: None,
+        peers_path: peers_path.clone(),
+        builds,
+        scanner: scanner.clone(),
+    });
 
+    // The GraphQL context also carries the API token given with the request, if any
+    let graphql_context = context
+        .clone()
+        .and(warp::header::optional::<String>("authorization"))
+        .map(|context: Context, auth: Option<String>| Context { token: auth.as_deref().and_then(bearer_token).map(String::from), ..context });
     let schema = Schema::new(Query {}, Mutations {}, EmptySubscription::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema, context.clone().boxed());
+    let graphql_filter = juniper_warp::make_graphql_filter(schema, graphql_context.boxed());
     let graphql = warp::path("graphql").and(graphql_filter);
 
     // Configure Warp.
@@ -151,7 +289,22 @@ async fn main() {
         .and(warp::filters::body::stream())
         .and(context.clone())
         .and_then(packages::upload);
-    let packages = download_package.or(upload_package);
+    let build_package = warp::path("packages")
+        .and(warp::path("build"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::query::<packages::BuildOptions>())
+        .and(warp::filters::body::stream())
+        .and(context.clone())
+        .and_then(packages::build);
+    let import_package = warp::path("packages")
+        .and(warp::path("oci"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(context.clone())
+        .and_then(packages::import);
+    let packages = download_package.or(upload_package.or(build_package.or(import_package)));
 
     // Configure infra
     let list_registries =
@@ -170,14 +323,39 @@ async fn main() {
         .and(warp::path::end())
         .and(context.clone())
         .and_then(infra::get_capabilities);
-    let infra = get_registry.or(list_registries.or(get_capabilities));
+    let list_capabilities = warp::get()
+        .and(warp::path("infra"))
+        .and(warp::path("capabilities"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(infra::list_capabilities);
+    let infra = get_registry.or(list_registries.or(get_capabilities.or(list_capabilities)));
+
+    // Configure federation
+    let federated_packages = warp::get()
+        .and(warp::path("federation"))
+        .and(warp::path("packages"))
+        .and(warp::path::end())
+        .and(warp::query::<federation::SearchOptions>())
+        .and(context.clone())
+        .and_then(federation::packages);
+    let federated_datasets = warp::get()
+        .and(warp::path("federation"))
+        .and(warp::path("data"))
+        .and(warp::path("info"))
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(federation::datasets);
+    let federation = federated_packages.or(federated_datasets);
 
     // Configure the health & version
-    let health = warp::path("health").and(warp::path::end()).and_then(health::handle);
+    let cert_monitor: ExpiryMonitor =
+        ExpiryMonitor::spawn(central.paths.certs.clone(), Duration::from_secs(opts.cert_expiry_window * 24 * 3600), Duration::from_secs(12 * 3600));
+    let health = warp::path("health").and(warp::path::end()).and(warp::any().map(move || cert_monitor.clone())).and_then(health::handle);
     let version = warp::path("version").and(warp::path::end()).and_then(version::handle);
 
     // Construct the final routes
-    let routes = data.or(packages.or(infra.or(health.or(version.or(graphql))))).with(warp::log("brane-api"));
+    let routes = data.or(packages.or(infra.or(federation.or(health.or(version.or(graphql)))))).with(warp::log("brane-api"));
 
     // Run the server
     let handle = warp::serve(routes).try_bind_with_graceful_shutdown(central.services.api.bind, async {
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 15:18:32
 //  Last edited:
-//    08 Feb 2024, 16:16:22
+//    16 Oct 2026, 02:39:20
 //  Auto updated?
 //    Yes
 //
@@ -16,23 +16,27 @@ use std::borrow::Cow;
 use std::convert::{TryFrom, TryInto};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
 
 use async_compression::tokio::bufread::GzipDecoder;
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{CentralConfig, NodeConfig, NodeKind};
+use brane_shr::oci;
 use bytes::Buf;
 use log::{debug, error, info, warn};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use scylla::macros::{FromUserType, IntoUserType};
-use scylla::{SerializeCql, Session};
-use specifications::package::PackageInfo;
+use scylla::{IntoTypedRows, SerializeCql, Session};
+use serde::Deserialize;
+use specifications::arch::Arch;
+use specifications::errors::{ErrorCode, ERROR_CODE_HEADER};
+use specifications::package::{PackageInfo, Severity, Vulnerability};
 use specifications::version::Version;
 // use tar::Archive;
 use tempfile::TempDir;
 use tokio::fs as tfs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
 use tokio_stream::StreamExt;
 use tokio_tar::{Archive, Entries, Entry};
 use uuid::Uuid;
@@ -42,7 +46,9 @@ use warp::hyper::Body;
 use warp::reply::Response;
 use warp::{Rejection, Reply};
 
+use crate::db::PackageWriter;
 pub use crate::errors::PackageError as Error;
+use crate::scan::Scanner;
 use crate::spec::Context;
 
 
@@ -85,6 +91,23 @@ macro_rules! fail {
 
 
 
+/***** CONSTANTS *****/
+/// The fields that package manifest v2, OCI distribution and vulnerability scanning added to the `brane.package` type, together with their types.
+/// These are added to the type if it was created by an older version.
+const PACKAGE_ADDED_FIELDS: [(&str, &str); 7] = [
+    ("license", "text"),
+    ("maintainers", "list<text>"),
+    ("homepage", "text"),
+    ("resources_as_json", "text"),
+    ("tags", "list<text>"),
+    ("oci", "text"),
+    ("scan_as_json", "text"),
+];
+
+
+
+
+
 /***** AUXILLARY STRUCTS *****/
 /// Defines the contents of a single Scylla database row that describes a package.
 #[derive(Clone, IntoUserType, FromUserType, SerializeCql)]
@@ -100,6 +123,16 @@ pub struct PackageUdt {
     pub owners: Vec<String>,
     pub types_as_json: String,
     pub version: String,
+    // Added in package manifest v2, hence optional (and at the end, since that's where they end up in upgraded databases)
+    pub license: Option<String>,
+    pub maintainers: Option<Vec<String>>,
+    pub homepage: Option<String>,
+    pub resources_as_json: Option<String>,
+    pub tags: Option<Vec<String>>,
+    // Added for OCI distribution
+    pub oci: Option<String>,
+    // Added for vulnerability scanning
+    pub scan_as_json: Option<String>,
 }
 
 impl TryFrom<PackageInfo> for PackageUdt {
@@ -120,6 +153,19 @@ impl TryFrom<PackageInfo> for PackageUdt {
             },
         };
 
+        let resources_as_json: Option<String> = match package.resources.as_ref().map(serde_json::to_string).transpose() {
+            Ok(resources) => resources,
+            Err(err) => {
+                return Err(Error::ResourcesSerializeError { name: package.name, err });
+            },
+        };
+        let scan_as_json: Option<String> = match package.scan.as_ref().map(serde_json::to_string).transpose() {
+            Ok(scan) => scan,
+            Err(err) => {
+                return Err(Error::ScanSerializeError { name: package.name, err });
+            },
+        };
+
         // Assert that there is a digest
         let digest: String = match package.digest {
             Some(digest) => digest,
@@ -141,12 +187,35 @@ impl TryFrom<PackageInfo> for PackageUdt {
             owners: package.owners,
             types_as_json,
             version: package.version.to_string(),
+            license: package.license,
+            maintainers: Some(package.maintainers),
+            homepage: package.homepage,
+            resources_as_json,
+            tags: Some(package.tags),
+            oci: package.oci,
+            scan_as_json,
         })
     }
 }
 
 
 
+/// Defines the query parameters of `/packages/build`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BuildOptions {
+    /// The architecture to build the image for (e.g., `x86_64` or `aarch64`).
+    pub arch: String,
+}
+
+/// Defines the body of `/packages/oci`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ImportRequest {
+    /// The OCI reference of the package image, e.g., `harbor.example.com/brane/hello-world:1.0.0`.
+    pub reference: String,
+}
+
+
+
 
 
 /***** AUXILLARY FUNCTIONS *****/
@@ -176,6 +245,13 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
             , owners list<text>
             , types_as_json text
             , version text
+            , license text
+            , maintainers list<text>
+            , homepage text
+            , resources_as_json text
+            , tags list<text>
+            , oci text
+            , scan_as_json text
         )",
             &[],
         )
@@ -184,6 +260,24 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
         return Err(Error::PackageTypeDefineError { err });
     }
 
+    // If the type already existed, it may be missing the fields added since; add them
+    let fields: Vec<String> =
+        match scylla.query("SELECT field_names FROM system_schema.types WHERE keyspace_name = 'brane' AND type_name = 'package'", &[]).await {
+            Ok(res) => res.rows_typed_or_empty::<(Vec<String>,)>().filter_map(Result::ok).flat_map(|(fields,)| fields).collect(),
+            Err(err) => {
+                return Err(Error::PackageTypeQueryError { err });
+            },
+        };
+    for (field, data_type) in PACKAGE_ADDED_FIELDS {
+        if fields.iter().any(|f| f == field) {
+            continue;
+        }
+        debug!("Adding missing field '{}' to the 'brane.package' type...", field);
+        if let Err(err) = scylla.query(format!("ALTER TYPE brane.package ADD {field} {data_type}"), &[]).await {
+            return Err(Error::PackageTypeUpgradeError { field: field.into(), err });
+        }
+    }
+
     // Define  the `brane.packages` table
     if let Err(err) = scylla
         .query(
@@ -210,7 +304,7 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
 /// Inserts the given package into the given Scylla database.
 ///
 /// # Arguments
-/// - `scylla`: The Scylla database session that allows us to talk to it.
+/// - `writer`: The [`PackageWriter`] that writes packages to the Scylla database.
 /// - `package`: The PackageInfo struct that describes the package, and is what we will insert. Note, however, that not _all_ information will make it; only the info present in a `PackageUdt` struct will.
 /// - `path`: The Path where the container image may be found.
 ///
@@ -219,33 +313,218 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
 ///
 /// # Errors
 /// This function errors if the communication with the given database failed too or if the given PackageInfo could not be converted to a PackageUdt for some reason.
-async fn insert_package_into_db(scylla: &Arc<Session>, package: &PackageInfo, path: impl AsRef<Path>) -> Result<(), Error> {
+async fn insert_package_into_db(writer: &PackageWriter, package: &PackageInfo, path: impl AsRef<Path>) -> Result<(), Error> {
     let path: &Path = path.as_ref();
 
     // Attempt to convert the package
     let package: PackageUdt = package.clone().try_into()?;
 
-    // Insert it
-    if let Err(err) = scylla
-        .query(
-            "INSERT INTO brane.packages (
-              name
-            , version
-            , file
-            , package
-        ) VALUES(?, ?, ?, ?)
-        ",
-            (&package.name, &package.version, path.to_string_lossy().to_string(), &package),
-        )
-        .await
-    {
-        return Err(Error::PackageInsertError { name: package.name, err });
+    // Insert it (together with any other packages being uploaded right now)
+    let name: String = package.name.clone();
+    if let Err(err) = writer.insert(package, path.to_string_lossy().to_string()).await {
+        return Err(Error::PackageInsertError { name, err });
     }
 
     // Done
     Ok(())
 }
 
+/// Writes the body of a request to a file.
+///
+/// # Arguments
+/// - `body`: The stream of body chunks to write.
+/// - `path`: The path of the file to write to. Will be created if it does not exist yet.
+///
+/// # Errors
+/// This function errors if we failed to read the body or to create or write the file.
+async fn write_body<S, B>(body: &mut S, path: &Path) -> Result<(), Error>
+where
+    S: StreamExt<Item = Result<B, warp::Error>> + Unpin,
+    B: Buf,
+{
+    // Attempt to open a new file
+    let mut handle = match tfs::File::create(path).await {
+        Ok(handle) => handle,
+        Err(err) => {
+            return Err(Error::TarCreateError { path: path.into(), err });
+        },
+    };
+
+    // Start writing the stream to it
+    while let Some(chunk) = body.next().await {
+        // Unwrap the chunk
+        let mut chunk: B = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                return Err(Error::BodyReadError { err });
+            },
+        };
+
+        // Write the chunk to the Tokio file
+        if let Err(err) = handle.write_all_buf(&mut chunk).await {
+            return Err(Error::TarWriteError { path: path.into(), err });
+        }
+    }
+
+    // Wait until the handle is finished writing
+    if let Err(err) = handle.shutdown().await {
+        return Err(Error::TarFlushError { path: path.into(), err });
+    }
+    Ok(())
+}
+
+/// Moves the image of a new package to its final location in the packages directory and inserts the package into the database.
+///
+/// If this fails, the image is removed again.
+///
+/// # Arguments
+/// - `writer`: The [`PackageWriter`] to insert the package with.
+/// - `packages_dir`: The directory where we store package images.
+/// - `info`: The [`PackageInfo`] describing the new package.
+/// - `image_path`: The path to the image of the package. Should be in `packages_dir` already, to avoid copying it across filesystems.
+///
+/// # Errors
+/// This function errors if we failed to move the image or to insert the package into the database.
+async fn store_package(writer: &PackageWriter, packages_dir: &Path, info: &PackageInfo, image_path: &Path) -> Result<(), Error> {
+    // Copy the image tar to the proper location
+    let result_path: PathBuf = packages_dir.join(format!("{}-{}.tar", info.name, info.version));
+    debug!("Moving image '{}' to '{}'...", image_path.display(), result_path.display());
+    if let Err(err) = tfs::rename(image_path, &result_path).await {
+        if let Err(err) = tfs::remove_file(image_path).await {
+            warn!("Failed to remove temporary image '{}': {}", image_path.display(), err);
+        }
+        return Err(Error::FileMoveError { from: image_path.into(), to: result_path, err });
+    }
+
+    // Call the insert function to store the dataset in the registry
+    debug!("Inserting package '{}' (version {}) into Scylla DB...", info.name, info.version);
+    if let Err(err) = insert_package_into_db(writer, info, &result_path).await {
+        if let Err(err) = tfs::remove_file(&result_path).await {
+            warn!("Failed to remove image '{}': {}", result_path.display(), err);
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Scans the image of a new package for vulnerabilities, if this registry is configured to do so.
+///
+/// If the package is not accepted, its image is removed again.
+///
+/// # Arguments
+/// - `scanner`: The [`Scanner`] to scan the image with, if any.
+/// - `info`: The [`PackageInfo`] describing the new package. The findings of the scanner are stored in it.
+/// - `image_path`: The path to the image of the package.
+///
+/// # Returns
+/// [`None`] if the package may be stored, or else a response that tells the client why it may not.
+async fn scan_package(scanner: Option<&Scanner>, info: &mut PackageInfo, image_path: &Path) -> Option<Response> {
+    let scanner: &Scanner = scanner?;
+    debug!("Scanning image of package '{}' (version {}) for vulnerabilities...", info.name, info.version);
+    let reply: Response = match scanner.scan(image_path).await {
+        Ok(report) => {
+            let rejected: Vec<&Vulnerability> = scanner.rejected(&report);
+            if rejected.is_empty() {
+                info!("Scanned package '{}' (version {}): {}", info.name, info.version, report);
+                info.scan = Some(report);
+                return None;
+            }
+
+            // Tell the client exactly what's wrong
+            info!("Rejecting package '{}' (version {}): {}", info.name, info.version, report);
+            let mut body: String = format!(
+                "Package '{}' (version {}) is rejected because its image has {} {} of severity '{}' or higher:\n",
+                info.name,
+                info.version,
+                rejected.len(),
+                if rejected.len() == 1 { "vulnerability" } else { "vulnerabilities" },
+                scanner.reject.unwrap_or(Severity::Unknown),
+            );
+            for vuln in rejected {
+                body.push_str(&format!(" - {} ({}) in {} {}", vuln.id, vuln.severity, vuln.package, vuln.version));
+                if let Some(fixed_in) = &vuln.fixed_in {
+                    body.push_str(&format!(" (fixed in {fixed_in})"));
+                }
+                body.push('\n');
+            }
+            error_reply(StatusCode::FORBIDDEN, ErrorCode::ApiVulnerablePackage, body)
+        },
+        Err(err) => {
+            error!("Failed to scan image of package '{}' (version {}): {}", info.name, info.version, err);
+            error_reply(StatusCode::SERVICE_UNAVAILABLE, ErrorCode::ApiScanFailed, format!("Failed to scan package image: {err}"))
+        },
+    };
+    if let Err(err) = tfs::remove_file(image_path).await {
+        warn!("Failed to remove image '{}' of rejected package: {}", image_path.display(), err);
+    }
+    Some(reply)
+}
+
+/// Pulls the image of a package that is distributed through an OCI registry.
+///
+/// The image is pulled to a temporary file next to `path` first, such that concurrent downloads never see a partial image.
+///
+/// # Arguments
+/// - `reference`: The OCI reference of the image, as stored in the package.
+/// - `path`: The path to pull the image to.
+///
+/// # Errors
+/// This function errors if we failed to pull the image or to move it into place.
+async fn pull_package(reference: &str, path: &Path) -> Result<(), Error> {
+    let reference: oci::Reference = match oci::Reference::from_str(reference) {
+        Ok(reference) => reference,
+        Err(err) => return Err(Error::OciError { reference: reference.into(), err }),
+    };
+    let creds: oci::Credentials = match oci::Credentials::from_docker_config() {
+        Ok(creds) => creds,
+        Err(err) => return Err(Error::OciError { reference: reference.to_string(), err }),
+    };
+
+    // Pull it next to the final location, then move it in place
+    let id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(8).map(char::from).collect();
+    let temp_path: PathBuf = path.with_extension(format!("{id}.part"));
+    debug!("Pulling image '{}' to '{}'...", reference, temp_path.display());
+    if let Err(err) = oci::Client::new(creds).pull_archive(&reference, oci::host_arch(), &temp_path).await {
+        if temp_path.exists() {
+            if let Err(err) = tfs::remove_file(&temp_path).await {
+                warn!("Failed to remove partial image '{}': {}", temp_path.display(), err);
+            }
+        }
+        return Err(Error::OciError { reference: reference.to_string(), err });
+    }
+    if let Err(err) = tfs::rename(&temp_path, path).await {
+        return Err(Error::FileMoveError { from: temp_path, to: path.into(), err });
+    }
+    Ok(())
+}
+
+/// Creates the response that tells the client that the package they requested does not exist.
+///
+/// # Returns
+/// A response with status code 404 and [`ErrorCode::ApiUnknownPackage`] as error code.
+fn unknown_package() -> Response {
+    let mut response: Response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::NOT_FOUND;
+    response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::ApiUnknownPackage.as_str()));
+    response
+}
+
+/// Creates a response with the given status code, error code and (plain text) body.
+///
+/// # Arguments
+/// - `status`: The HTTP status code of the response.
+/// - `code`: The [`ErrorCode`] to attach to the response.
+/// - `body`: The body of the response, explaining what went wrong.
+///
+/// # Returns
+/// A new response that can be send to the client.
+fn error_reply(status: StatusCode, code: ErrorCode, body: impl Into<Body>) -> Response {
+    let mut response: Response = Response::new(body.into());
+    *response.status_mut() = status;
+    response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(code.as_str()));
+    response
+}
+
 
 
 
@@ -269,7 +548,7 @@ pub async fn download(name: String, version: String, context: Context) -> Result
     // Attempt to resolve the version from the Scylla database in the context
     debug!("Resolving version '{}'...", version);
     let version: Version = if version.to_lowercase() == "latest" {
-        let versions = match context.scylla.query("SELECT version FROM brane.packages WHERE name=?", vec![&name]).await {
+        let versions = match context.scylla.execute(&context.statements.package_versions, (&name,)).await {
             Ok(versions) => versions,
             Err(err) => {
                 fail!(Error::VersionsQueryError { name, err });
@@ -290,7 +569,7 @@ pub async fn download(name: String, version: String, context: Context) -> Result
                 };
 
                 // Finally, find the most recent one
-                if latest.is_none() || version > *latest.as_ref().unwrap() {
+                if latest.is_none() || version.cmp_release(latest.as_ref().unwrap()).is_gt() {
                     latest = Some(version);
                 }
             }
@@ -301,7 +580,7 @@ pub async fn download(name: String, version: String, context: Context) -> Result
             Some(version) => version,
             None => {
                 error!("{}", Error::NoVersionsFound { name });
-                return Err(warp::reject::not_found());
+                return Ok(unknown_package());
             },
         }
     } else {
@@ -315,27 +594,42 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 
     // With the version resolved, query the filename
     debug!("Retrieving filename for package '{}'@{}", name, version);
-    let file: PathBuf =
-        match context.scylla.query("SELECT file FROM brane.packages WHERE name=? AND version=?", vec![&name, &version.to_string()]).await {
-            Ok(file) => {
-                if let Some(rows) = file.rows {
-                    if rows.is_empty() {
-                        error!("{}", Error::UnknownPackage { name, version });
-                        return Err(warp::reject::not_found());
-                    }
-                    if rows.len() > 1 {
-                        panic!("Database contains {} entries with the same name & version ('{}' & '{}')", rows.len(), name, version);
-                    }
-                    rows[0].columns[0].as_ref().unwrap().as_text().unwrap().into()
-                } else {
+    let (file, package): (String, PackageUdt) = match context.scylla.execute(&context.statements.package_file, (&name, version.to_string())).await {
+        Ok(file) => {
+            if let Some(rows) = file.rows {
+                if rows.is_empty() {
                     error!("{}", Error::UnknownPackage { name, version });
-                    return Err(warp::reject::not_found());
+                    return Ok(unknown_package());
                 }
-            },
-            Err(err) => {
-                fail!(Error::PathQueryError { name, version, err });
-            },
-        };
+                if rows.len() > 1 {
+                    panic!("Database contains {} entries with the same name & version ('{}' & '{}')", rows.len(), name, version);
+                }
+                match rows.into_typed::<(String, PackageUdt)>().next().unwrap() {
+                    Ok(row) => row,
+                    Err(err) => {
+                        fail!(Error::PackageRowParseError { name, version, err });
+                    },
+                }
+            } else {
+                error!("{}", Error::UnknownPackage { name, version });
+                return Ok(unknown_package());
+            }
+        },
+        Err(err) => {
+            fail!(Error::PathQueryError { name, version, err });
+        },
+    };
+    let file: PathBuf = file.into();
+
+    // Packages distributed through an OCI registry are only pulled the first time they are needed
+    if let Some(reference) = package.oci {
+        if !file.exists() {
+            if let Err(err) = pull_package(&reference, &file).await {
+                error!("{}", err);
+                return Ok(error_reply(StatusCode::BAD_GATEWAY, ErrorCode::ApiOciFailed, err.to_string()));
+            }
+        }
+    }
 
     // Retrieve the size of the file for the content length
     let length: u64 = match tfs::metadata(&file).await {
@@ -398,7 +692,10 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 /// - `context`: The Context that stores properties about the environment, such as the directory where we store the container files.
 ///
 /// # Returns
-/// The Warp reply that contains the status code of the thing (e.g., OK if everything went fine).
+/// The Warp reply that contains the status code of the thing. It is:
+/// - `200 OK` if the package was added to the registry.
+/// - `403 FORBIDDEN` if its image has vulnerabilities that are too severe (with [`ErrorCode::ApiVulnerablePackage`]).
+/// - `503 SERVICE UNAVAILABLE` if its image could not be scanned for vulnerabilities (with [`ErrorCode::ApiScanFailed`]).
 ///
 /// # Errors
 /// This function errors if we fail to either write the package info to the Scylla database or the package archive to the local filesystem.
@@ -447,35 +744,11 @@ where
     // Generate a unique ID for the image name.
     let id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(8).map(char::from).collect();
 
-    // Attempt to open a new file
+    // Write the stream to a new file
     let tar_path: PathBuf = tempdir_path.join(format!("{id}.tar.gz"));
-    let mut handle = match tfs::File::create(&tar_path).await {
-        Ok(handle) => handle,
-        Err(err) => {
-            fail!(Error::TarCreateError { path: tar_path, err });
-        },
-    };
-
-    // Start writing the stream to it
     debug!("Downloading submitted archive to '{}'...", tar_path.display());
-    while let Some(chunk) = package_archive.next().await {
-        // Unwrap the chunk
-        let mut chunk: B = match chunk {
-            Ok(chunk) => chunk,
-            Err(err) => {
-                fail!(Error::BodyReadError { err });
-            },
-        };
-
-        // Write the chunk to the Tokio file
-        if let Err(err) = handle.write_all_buf(&mut chunk).await {
-            fail!(Error::TarWriteError { path: tar_path, err });
-        }
-    }
-
-    // Wait until the handle is finished writing
-    if let Err(err) = handle.shutdown().await {
-        fail!(Error::TarFlushError { path: tar_path, err });
+    if let Err(err) = write_body(&mut package_archive, &tar_path).await {
+        fail!(err);
     }
 
 
@@ -564,24 +837,21 @@ where
             fail!(Error::PackageInfoReadError { path: info_path, err });
         },
     };
-    let info: PackageInfo = match serde_yaml::from_str(&sinfo) {
+    let mut info: PackageInfo = match serde_yaml::from_str(&sinfo) {
         Ok(info) => info,
         Err(err) => {
             fail!(Error::PackageInfoParseError { path: info_path, err });
         },
     };
 
-    // Copy the image tar to the proper location
-    let result_path: PathBuf = central.paths.packages.join(format!("{}-{}.tar", info.name, info.version));
-    debug!("Moving image '{}' to '{}'...", image_path.display(), result_path.display());
-    if let Err(err) = tfs::rename(&image_path, &result_path).await {
-        fail!(image_path, Error::FileMoveError { from: image_path, to: result_path, err });
+    // Scan the image before anything can use it
+    if let Some(response) = scan_package(context.scanner.as_ref(), &mut info, &image_path).await {
+        return Ok(response);
     }
 
-    // Call the insert function to store the dataset in the registry
-    debug!("Inserting package '{}' (version {}) into Scylla DB...", info.name, info.version);
-    if let Err(err) = insert_package_into_db(&context.scylla, &info, &result_path).await {
-        fail!(result_path, err);
+    // Move the image to its final location and register it
+    if let Err(err) = store_package(&context.packages_writer, &central.paths.packages, &info, &image_path).await {
+        fail!(err);
     }
 
 
@@ -589,7 +859,260 @@ where
     /* Step 4: Done */
     // The package has now been added
     debug!("Upload of package '{}' (version {}) complete.", info.name, info.version);
-    Ok(StatusCode::OK)
+    Ok(StatusCode::OK.into_response())
 
     // Note that the temporary directory is automagically removed
 }
+
+
+
+/// Builds a new package (container) on this node and adds it to the central registry.
+///
+/// This allows clients without Docker, or on another architecture than the workers, to build packages. They prepare the build context like they would
+/// for a local build, and then submit it here.
+///
+/// # Arguments
+/// - `options`: The [`BuildOptions`] given as query parameters.
+/// - `build_context`: The Bytes of the build context, which is a (gzipped) tarball with the `Dockerfile`, the `container` directory and the
+///   `package.yml` (without a digest) of the package.
+/// - `context`: The Context that stores properties about the environment, such as the directory where we store the container files.
+///
+/// # Returns
+/// A reply with the output of the build as body. Its status is:
+/// - `200 OK` if the package was built and added to the registry.
+/// - `400 BAD REQUEST` if the given architecture is unknown.
+/// - `422 UNPROCESSABLE ENTITY` if the build failed (with [`ErrorCode::ApiBuildFailed`]).
+/// - `403 FORBIDDEN` if the built image has vulnerabilities that are too severe (with [`ErrorCode::ApiVulnerablePackage`]).
+/// - `503 SERVICE UNAVAILABLE` if the built image could not be scanned for vulnerabilities (with [`ErrorCode::ApiScanFailed`]).
+///
+/// # Errors
+/// This function errors (i.e., rejects) with a 404 if builds are not enabled, or if we fail to unpack the build context, launch the build or store the
+/// resulting package.
+pub async fn build<S, B>(options: BuildOptions, build_context: S, context: Context) -> Result<impl Reply, Rejection>
+where
+    S: StreamExt<Item = Result<B, warp::Error>> + Unpin,
+    B: Buf,
+{
+    info!("Handling POST on '/packages/build' (i.e., build new package)");
+    let mut build_context = build_context;
+    if !context.builds {
+        debug!("Builds are not enabled (see '--builds')");
+        return Err(warp::reject::not_found());
+    }
+    let arch: Arch = match Arch::from_str(&options.arch) {
+        Ok(arch) => arch,
+        Err(err) => {
+            debug!("Refusing build: {}", err);
+            return Ok(error_reply(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, err.to_string()));
+        },
+    };
+
+
+
+    /* Step 0: Load config files */
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            fail!(Error::NodeConfigLoadError { err });
+        },
+    };
+    let central: &CentralConfig = match node_config.node.try_central() {
+        Some(central) => central,
+        None => {
+            fail!(Error::NodeConfigUnexpectedKind {
+                path:     context.node_config_path,
+                got:      node_config.node.kind(),
+                expected: NodeKind::Central,
+            });
+        },
+    };
+
+
+
+    /* Step 1: Download and unpack the build context */
+    debug!("Preparing filesystem...");
+    let tempdir: TempDir = match TempDir::new() {
+        Ok(tempdir) => tempdir,
+        Err(err) => {
+            fail!(Error::TempDirCreateError { err });
+        },
+    };
+    let tar_path: PathBuf = tempdir.path().join("context.tar.gz");
+    debug!("Downloading submitted build context to '{}'...", tar_path.display());
+    if let Err(err) = write_body(&mut build_context, &tar_path).await {
+        fail!(err);
+    }
+
+    let context_path: PathBuf = tempdir.path().join("context");
+    debug!("Unpacking build context to '{}'...", context_path.display());
+    {
+        let handle: tfs::File = match tfs::File::open(&tar_path).await {
+            Ok(handle) => handle,
+            Err(err) => {
+                fail!(Error::TarReopenError { path: tar_path, err });
+            },
+        };
+        let mut tar: Archive<GzipDecoder<BufReader<tfs::File>>> = Archive::new(GzipDecoder::new(BufReader::new(handle)));
+        if let Err(err) = tar.unpack(&context_path).await {
+            fail!(Error::ContextUnpackError { tarball: tar_path, target: context_path, err });
+        }
+    }
+
+    // Read the package info from it
+    let info_path: PathBuf = context_path.join("package.yml");
+    let sinfo: String = match tfs::read_to_string(&info_path).await {
+        Ok(sinfo) => sinfo,
+        Err(err) => {
+            fail!(Error::PackageInfoReadError { path: info_path, err });
+        },
+    };
+    let mut info: PackageInfo = match serde_yaml::from_str(&sinfo) {
+        Ok(info) => info,
+        Err(err) => {
+            fail!(Error::PackageInfoParseError { path: info_path, err });
+        },
+    };
+
+
+
+    /* Step 2: Build the image */
+    // Generate a unique ID for the image name, and write it in the packages directory directly to avoid copying it across filesystems later
+    let id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(8).map(char::from).collect();
+    let image_path: PathBuf = central.paths.packages.join(format!("{id}.tar"));
+    let iid_path: PathBuf = tempdir.path().join("image.id");
+
+    let mut command = Command::new("docker");
+    command.arg("buildx");
+    command.arg("build");
+    command.arg("--output");
+    command.arg(format!("type=docker,dest={}", image_path.display()));
+    command.arg("--iidfile");
+    command.arg(&iid_path);
+    command.arg("--tag");
+    command.arg(format!("{}:{}", info.name, info.version));
+    command.arg("--platform");
+    command.arg(format!("linux/{}", arch.docker()));
+    command.arg("--build-arg");
+    command.arg(format!("BRANELET_ARCH={}", arch.brane()));
+    command.arg("--build-arg");
+    command.arg(format!("JUICEFS_ARCH={}", arch.juicefs()));
+    command.arg(&context_path);
+    debug!("Building image '{}:{}' with {:?}...", info.name, info.version, command);
+    let output = match command.output().await {
+        Ok(output) => output,
+        Err(err) => {
+            fail!(Error::BuildLaunchError { command: format!("{command:?}"), err });
+        },
+    };
+    let log: String = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() {
+        info!("Build of package '{}' (version {}) failed with exit code {}", info.name, info.version, output.status.code().unwrap_or(-1));
+        if image_path.exists() {
+            if let Err(err) = tfs::remove_file(&image_path).await {
+                warn!("Failed to remove partial image '{}': {}", image_path.display(), err);
+            }
+        }
+        return Ok(error_reply(StatusCode::UNPROCESSABLE_ENTITY, ErrorCode::ApiBuildFailed, log));
+    }
+
+    // Note the digest of the built image
+    match tfs::read_to_string(&iid_path).await {
+        Ok(digest) => info.digest = Some(digest.trim().into()),
+        Err(err) => {
+            fail!(image_path, Error::ImageIdReadError { path: iid_path, err });
+        },
+    }
+
+
+
+    /* Step 3: Scan the image */
+    if let Some(response) = scan_package(context.scanner.as_ref(), &mut info, &image_path).await {
+        return Ok(response);
+    }
+
+
+
+    /* Step 4: Insert the package into the DB */
+    if let Err(err) = store_package(&context.packages_writer, &central.paths.packages, &info, &image_path).await {
+        fail!(err);
+    }
+    debug!("Build of package '{}' (version {}) complete.", info.name, info.version);
+    Ok(Response::new(Body::from(log)))
+}
+
+
+
+/// Adds a package that is distributed through an OCI registry to the central registry.
+///
+/// Only the package info is read from the registry now; the image itself is pulled the first time the package is downloaded (see [`download()`]).
+///
+/// # Arguments
+/// - `request`: The [`ImportRequest`] with the reference of the package image.
+/// - `context`: The Context that stores properties about the environment, such as the directory where we store the container files.
+///
+/// # Returns
+/// A reply with the reference pinned to the digest of the imported image as body. Its status is:
+/// - `200 OK` if the package was added to the registry.
+/// - `400 BAD REQUEST` if the given reference is illegal.
+/// - `502 BAD GATEWAY` if the registry could not be used or the image is not a Brane package (with [`ErrorCode::ApiOciFailed`]).
+///
+/// # Errors
+/// This function errors (i.e., rejects) if we failed to load the node config or to insert the package into the database.
+pub async fn import(request: ImportRequest, context: Context) -> Result<impl Reply, Rejection> {
+    info!("Handling POST on '/packages/oci' (i.e., import package from OCI registry)");
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            fail!(Error::NodeConfigLoadError { err });
+        },
+    };
+    let central: &CentralConfig = match node_config.node.try_central() {
+        Some(central) => central,
+        None => {
+            fail!(Error::NodeConfigUnexpectedKind {
+                path:     context.node_config_path,
+                got:      node_config.node.kind(),
+                expected: NodeKind::Central,
+            });
+        },
+    };
+    let reference: oci::Reference = match oci::Reference::from_str(&request.reference) {
+        Ok(reference) => reference,
+        Err(err) => {
+            debug!("Refusing import: {}", err);
+            return Ok(error_reply(StatusCode::BAD_REQUEST, ErrorCode::BadRequest, err.to_string()));
+        },
+    };
+
+    // Read the package info from the registry
+    debug!("Reading package info from '{}'...", reference);
+    let creds: oci::Credentials = match oci::Credentials::from_docker_config() {
+        Ok(creds) => creds,
+        Err(err) => {
+            fail!(Error::OciError { reference: reference.to_string(), err });
+        },
+    };
+    let (mut info, pinned): (PackageInfo, oci::Reference) = match oci::Client::new(creds).package_info(&reference).await {
+        Ok(res) => res,
+        Err(err) => {
+            let err: Error = Error::OciError { reference: reference.to_string(), err };
+            error!("{}", err);
+            return Ok(error_reply(StatusCode::BAD_GATEWAY, ErrorCode::ApiOciFailed, err.to_string()));
+        },
+    };
+    info.oci = Some(pinned.to_string());
+
+    // Register it, pointing to where the image will be cached. Any image cached for an earlier import is outdated.
+    let path: PathBuf = central.paths.packages.join(format!("{}-{}.tar", info.name, info.version));
+    if path.exists() {
+        if let Err(err) = tfs::remove_file(&path).await {
+            warn!("Failed to remove outdated image '{}': {}", path.display(), err);
+        }
+    }
+    debug!("Inserting package '{}' (version {}) into Scylla DB...", info.name, info.version);
+    if let Err(err) = insert_package_into_db(&context.packages_writer, &info, &path).await {
+        fail!(err);
+    }
+    debug!("Import of package '{}' (version {}) from '{}' complete.", info.name, info.version, pinned);
+    Ok(Response::new(Body::from(pinned.to_string())))
+}
@@ -0,0 +1,307 @@
+//  SCAN.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:39:48
+//  Last edited:
+//    16 Oct 2026, 00:40:59
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Scans the images of uploaded packages for known vulnerabilities.
+//!
+//!   The scanning itself is left to an existing scanner, either
+//!   [Trivy](https://trivy.dev) or [Grype](https://github.com/anchore/grype).
+//!   It is run as a subprocess on the image, or reached over HTTP by
+//!   POSTing the image to an endpoint that replies with the scanner's JSON
+//!   report (e.g., a small service wrapping `trivy image --input`).
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::Utc;
+use log::debug;
+use reqwest::Body;
+use serde::Deserialize;
+use specifications::package::{ScanReport, Severity, Vulnerability};
+use tokio::fs as tfs;
+use tokio::process::Command;
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+pub use crate::errors::ScanError as Error;
+
+
+/***** HELPER STRUCTURES *****/
+/// The parts of a Trivy JSON report that we care about.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyReport {
+    /// The results per target (i.e., OS packages and every language-specific lockfile) in the image.
+    #[serde(default)]
+    results: Vec<TrivyResult>,
+}
+
+/// The parts of a single target in a Trivy JSON report that we care about.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyResult {
+    /// The vulnerabilities found in the target. Trivy writes `null` if there are none.
+    #[serde(default)]
+    vulnerabilities: Option<Vec<TrivyVulnerability>>,
+}
+
+/// The parts of a single vulnerability in a Trivy JSON report that we care about.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TrivyVulnerability {
+    #[serde(rename = "VulnerabilityID")]
+    vulnerability_id:  String,
+    pkg_name:          String,
+    installed_version: String,
+    #[serde(default)]
+    fixed_version:     Option<String>,
+    severity:          String,
+}
+
+/// The parts of a Grype JSON report that we care about.
+#[derive(Deserialize)]
+struct GrypeReport {
+    /// The vulnerabilities matched against packages in the image.
+    #[serde(default)]
+    matches: Vec<GrypeMatch>,
+}
+
+/// The parts of a single match in a Grype JSON report that we care about.
+#[derive(Deserialize)]
+struct GrypeMatch {
+    vulnerability: GrypeVulnerability,
+    artifact:      GrypeArtifact,
+}
+
+/// The parts of a vulnerability in a Grype JSON report that we care about.
+#[derive(Deserialize)]
+struct GrypeVulnerability {
+    id:       String,
+    severity: String,
+    #[serde(default)]
+    fix:      Option<GrypeFix>,
+}
+
+/// The parts of the fix of a vulnerability in a Grype JSON report that we care about.
+#[derive(Deserialize)]
+struct GrypeFix {
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+/// The parts of a vulnerable package in a Grype JSON report that we care about.
+#[derive(Deserialize)]
+struct GrypeArtifact {
+    name:    String,
+    version: String,
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the vulnerability scanners that we know how to run (and whose reports we know how to read).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScannerKind {
+    /// [Trivy](https://trivy.dev).
+    Trivy,
+    /// [Grype](https://github.com/anchore/grype).
+    Grype,
+}
+impl ScannerKind {
+    /// Builds the command that scans the given image with this scanner and writes a JSON report to stdout.
+    ///
+    /// # Arguments
+    /// - `image`: The path to the image to scan, as saved by `docker save`.
+    ///
+    /// # Returns
+    /// A [`Command`] that can be spawned.
+    fn command(&self, image: &Path) -> Command {
+        match self {
+            Self::Trivy => {
+                let mut command = Command::new("trivy");
+                command.args(["image", "--quiet", "--format", "json", "--input"]);
+                command.arg(image);
+                command
+            },
+            Self::Grype => {
+                let mut command = Command::new("grype");
+                command.arg(format!("docker-archive:{}", image.display()));
+                command.args(["--quiet", "--output", "json"]);
+                command
+            },
+        }
+    }
+
+    /// Parses a JSON report written by this scanner.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw report.
+    ///
+    /// # Returns
+    /// The [`Vulnerability`]s listed in the report.
+    ///
+    /// # Errors
+    /// This function errors if the report was not valid JSON of the expected shape.
+    fn parse(&self, raw: &[u8]) -> Result<Vec<Vulnerability>, Error> {
+        // Severities we don't know are kept, but as `Unknown`
+        let severity = |raw: &str| Severity::from_str(raw).unwrap_or(Severity::Unknown);
+        match self {
+            Self::Trivy => {
+                let report: TrivyReport = match serde_json::from_slice(raw) {
+                    Ok(report) => report,
+                    Err(err) => return Err(Error::ReportParseError { scanner: "trivy", err }),
+                };
+                Ok(report
+                    .results
+                    .into_iter()
+                    .flat_map(|res| res.vulnerabilities.unwrap_or_default())
+                    .map(|vuln| Vulnerability {
+                        id:       vuln.vulnerability_id,
+                        severity: severity(&vuln.severity),
+                        package:  vuln.pkg_name,
+                        version:  vuln.installed_version,
+                        fixed_in: vuln.fixed_version.filter(|version| !version.is_empty()),
+                    })
+                    .collect())
+            },
+            Self::Grype => {
+                let report: GrypeReport = match serde_json::from_slice(raw) {
+                    Ok(report) => report,
+                    Err(err) => return Err(Error::ReportParseError { scanner: "grype", err }),
+                };
+                Ok(report
+                    .matches
+                    .into_iter()
+                    .map(|m| Vulnerability {
+                        id:       m.vulnerability.id,
+                        severity: severity(&m.vulnerability.severity),
+                        package:  m.artifact.name,
+                        version:  m.artifact.version,
+                        fixed_in: m.vulnerability.fix.and_then(|fix| fix.versions.into_iter().next()),
+                    })
+                    .collect())
+            },
+        }
+    }
+}
+impl Display for ScannerKind {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Trivy => write!(f, "trivy"),
+            Self::Grype => write!(f, "grype"),
+        }
+    }
+}
+impl FromStr for ScannerKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "trivy" => Ok(Self::Trivy),
+            "grype" => Ok(Self::Grype),
+
+            raw => Err(Error::UnknownScanner { raw: raw.into() }),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how images of uploaded packages are scanned, and which findings are reason to reject them.
+#[derive(Clone, Debug)]
+pub struct Scanner {
+    /// The scanner to use.
+    pub kind:     ScannerKind,
+    /// If given, the image is POSTed to this endpoint instead of running the scanner locally. It should reply with the scanner's JSON report.
+    pub endpoint: Option<String>,
+    /// If given, packages with vulnerabilities of at least this severity are rejected.
+    pub reject:   Option<Severity>,
+}
+impl Scanner {
+    /// Scans the given image for vulnerabilities.
+    ///
+    /// # Arguments
+    /// - `image`: The path to the image to scan, as saved by `docker save`.
+    ///
+    /// # Returns
+    /// A [`ScanReport`] with the findings of the scanner.
+    ///
+    /// # Errors
+    /// This function errors if we failed to run or reach the scanner, or if it failed to scan the image.
+    pub async fn scan(&self, image: &Path) -> Result<ScanReport, Error> {
+        let raw: Vec<u8> = match &self.endpoint {
+            Some(endpoint) => {
+                debug!("Sending image '{}' to {} scanner at '{}'...", image.display(), self.kind, endpoint);
+                let handle: tfs::File = match tfs::File::open(image).await {
+                    Ok(handle) => handle,
+                    Err(err) => return Err(Error::ImageOpenError { path: image.into(), err }),
+                };
+                let res = match reqwest::Client::new()
+                    .post(endpoint)
+                    .header("Content-Type", "application/x-tar")
+                    .body(Body::wrap_stream(FramedRead::new(handle, BytesCodec::new())))
+                    .send()
+                    .await
+                {
+                    Ok(res) => res,
+                    Err(err) => return Err(Error::RequestError { endpoint: endpoint.clone(), err }),
+                };
+                if !res.status().is_success() {
+                    return Err(Error::RequestFailure { endpoint: endpoint.clone(), code: res.status(), message: res.text().await.ok() });
+                }
+                match res.bytes().await {
+                    Ok(raw) => raw.to_vec(),
+                    Err(err) => return Err(Error::RequestError { endpoint: endpoint.clone(), err }),
+                }
+            },
+
+            None => {
+                let mut command: Command = self.kind.command(image);
+                debug!("Scanning image '{}' with {:?}...", image.display(), command);
+                let output = match command.output().await {
+                    Ok(output) => output,
+                    Err(err) => return Err(Error::CommandLaunchError { command: format!("{command:?}"), err }),
+                };
+                if !output.status.success() {
+                    return Err(Error::CommandFailure {
+                        command: format!("{command:?}"),
+                        code:    output.status.code(),
+                        stderr:  String::from_utf8_lossy(&output.stderr).into(),
+                    });
+                }
+                output.stdout
+            },
+        };
+
+        // Parse the report
+        Ok(ScanReport { scanner: self.kind.to_string(), scanned: Utc::now(), findings: self.kind.parse(&raw)? })
+    }
+
+    /// Returns the findings in a report that are reason to reject the package.
+    ///
+    /// # Arguments
+    /// - `report`: The [`ScanReport`] to judge.
+    ///
+    /// # Returns
+    /// The [`Vulnerability`]s that are at least as severe as the threshold. Empty if we don't reject packages, or if the report is fine.
+    pub fn rejected<'r>(&self, report: &'r ScanReport) -> Vec<&'r Vulnerability> {
+        match self.reject {
+            Some(threshold) => report.at_least(threshold).collect(),
+            None => vec![],
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 15:17:39
 //  Last edited:
-//    08 Feb 2024, 16:15:57
+//    16 Oct 2026, 02:39:20
 //  Auto updated?
 //    Yes
 //
@@ -21,9 +21,13 @@ use log::{debug, info};
 use scylla::IntoTypedRows;
 use specifications::version::Version;
 
+use crate::auth;
 use crate::packages::PackageUdt;
 use crate::spec::Context;
 
+/// The columns of a row in the `brane.dataset_annotations` table, in order.
+type AnnotationRow = (String, Option<String>, Option<Vec<String>>, Option<String>, i64, String);
+
 pub type Schema = RootNode<'static, Query, Mutations, EmptySubscription<Context>>;
 impl juniper::Context for Context {}
 
@@ -40,6 +44,13 @@ pub struct Package {
     pub version: String,
     pub functions_as_json: Option<String>,
     pub types_as_json: Option<String>,
+    pub license: Option<String>,
+    pub maintainers: Vec<String>,
+    pub homepage: Option<String>,
+    pub resources_as_json: Option<String>,
+    pub tags: Vec<String>,
+    pub oci: Option<String>,
+    pub scan_as_json: Option<String>,
 }
 
 impl From<PackageUdt> for Package {
@@ -58,6 +69,44 @@ impl From<PackageUdt> for Package {
             version: row.version,
             functions_as_json: Some(row.functions_as_json),
             types_as_json: Some(row.types_as_json),
+            license: row.license,
+            maintainers: row.maintainers.unwrap_or_default(),
+            homepage: row.homepage,
+            resources_as_json: row.resources_as_json,
+            tags: row.tags.unwrap_or_default(),
+            oci: row.oci,
+            scan_as_json: row.scan_as_json,
+        }
+    }
+}
+
+/// Metadata about a dataset that is maintained on the central registry, on top of what the domains advertise.
+#[derive(Clone, Debug, GraphQLObject)]
+pub struct DatasetAnnotation {
+    /// The name of the annotated dataset.
+    pub name: String,
+    /// A description of the dataset.
+    pub description: Option<String>,
+    /// Tags to find the dataset by (unrelated to the sensitivity tags that domains attach to their datasets).
+    pub tags: Vec<String>,
+    /// Who to contact about the dataset.
+    pub contact: Option<String>,
+    /// When the annotation was last changed.
+    pub updated: DateTime<Utc>,
+    /// The user that last changed the annotation.
+    pub updated_by: String,
+}
+
+impl From<AnnotationRow> for DatasetAnnotation {
+    fn from(row: AnnotationRow) -> Self {
+        let (name, description, tags, contact, updated, updated_by) = row;
+        DatasetAnnotation {
+            name,
+            description,
+            tags: tags.unwrap_or_default(),
+            contact,
+            updated: Utc.timestamp_millis_opt(updated).unwrap(),
+            updated_by,
         }
     }
 }
@@ -78,11 +127,10 @@ impl Query {
         let scylla = context.scylla.clone();
 
         let like = format!("%{}%", term.unwrap_or_default());
-        let query = "SELECT package FROM brane.packages WHERE name LIKE ? ALLOW FILTERING";
 
         debug!("Querying Scylla database...");
         let mut packages: Vec<Package> = vec![];
-        if let Some(rows) = scylla.query(query, &(like,)).await?.rows {
+        if let Some(rows) = scylla.execute(&context.statements.package_search, (like,)).await?.rows {
             // Search for all matches of this package
             for row in rows.into_typed::<(PackageUdt,)>() {
                 let (package,) = row?;
@@ -114,7 +162,7 @@ impl Query {
                         };
 
                         // Compare
-                        if package.is_none() || &pversion > version.as_ref().unwrap() {
+                        if package.is_none() || pversion.cmp_release(version.as_ref().unwrap()).is_gt() {
                             package = Some(p);
                             version = Some(pversion);
                         }
@@ -146,6 +194,34 @@ impl Query {
         debug!("Returning {} packages", packages.len());
         Ok(packages)
     }
+
+    /// Lists the annotations of datasets, optionally only those of the dataset with the given name or those with the given tag.
+    async fn datasetAnnotations(name: Option<String>, tag: Option<String>, context: &Context) -> FieldResult<Vec<DatasetAnnotation>> {
+        info!("Handling GRAPHQL on '/graphql' (i.e., get dataset annotations)");
+        let scylla = context.scylla.clone();
+
+        debug!("Querying Scylla database...");
+        let mut annotations: Vec<DatasetAnnotation> = vec![];
+        if let Some(rows) = scylla.execute(&context.statements.annotations_list, ()).await?.rows {
+            for row in rows.into_typed::<AnnotationRow>() {
+                let annotation: DatasetAnnotation = row?.into();
+                if let Some(name) = &name {
+                    if name != &annotation.name {
+                        continue;
+                    }
+                }
+                if let Some(tag) = &tag {
+                    if !annotation.tags.contains(tag) {
+                        continue;
+                    }
+                }
+                annotations.push(annotation);
+            }
+        }
+
+        debug!("Returning {} dataset annotations", annotations.len());
+        Ok(annotations)
+    }
 }
 
 pub struct Mutations;
@@ -165,8 +241,7 @@ impl Mutations {
 
         // Get the image file first, tho
         debug!("Querying file path from Scylla database...");
-        let query = "SELECT file FROM brane.packages WHERE name = ? AND version = ?";
-        let file = scylla.query(query, &(&name, &version)).await?;
+        let file = scylla.execute(&context.statements.package_file, (&name, &version)).await?;
         if let Some(rows) = file.rows {
             if rows.is_empty() {
                 return Ok("OK!");
@@ -175,14 +250,79 @@ impl Mutations {
 
             // Delete the thing from the database
             debug!("Deleting package from Scylla database...");
-            let query = "DELETE FROM brane.packages WHERE name = ? AND version = ?";
-            scylla.query(query, &(&name, &version)).await?;
+            scylla.execute(&context.statements.package_delete, (&name, &version)).await?;
 
-            // Delete the file
+            // Delete the file (which packages distributed through an OCI registry may not have cached)
             debug!("Deleting container file '{}'...", file.display());
-            tokio::fs::remove_file(&file).await?;
+            if let Err(err) = tokio::fs::remove_file(&file).await {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    return Err(err.into());
+                }
+            }
         }
 
         Ok("OK!")
     }
+
+    /// Annotates a dataset with a description, tags and/or a contact. Requires an API token.
+    ///
+    /// Fields that are omitted keep their current value; give an empty string or list to clear them.
+    async fn annotate_dataset(
+        name: String,
+        description: Option<String>,
+        tags: Option<Vec<String>>,
+        contact: Option<String>,
+        context: &Context,
+    ) -> FieldResult<DatasetAnnotation> {
+        info!("Handling GRAPHQL on '/graphql' (i.e., annotate dataset)");
+        let user: String = auth::authenticate(context)?;
+        let scylla = context.scylla.clone();
+
+        // Get the current annotation, if any
+        debug!("Querying current annotation of dataset '{}' from Scylla database...", name);
+        let current: Option<DatasetAnnotation> = match scylla.execute(&context.statements.annotation_get, (&name,)).await?.rows {
+            Some(rows) => rows.into_typed::<AnnotationRow>().next().transpose()?.map(DatasetAnnotation::from),
+            None => None,
+        };
+
+        // Merge the new values into it
+        let (cur_description, cur_tags, cur_contact): (Option<String>, Vec<String>, Option<String>) = match current {
+            Some(current) => (current.description, current.tags, current.contact),
+            None => (None, vec![], None),
+        };
+        let annotation: DatasetAnnotation = DatasetAnnotation {
+            description: description.map(|d| if d.is_empty() { None } else { Some(d) }).unwrap_or(cur_description),
+            tags: tags.unwrap_or(cur_tags),
+            contact: contact.map(|c| if c.is_empty() { None } else { Some(c) }).unwrap_or(cur_contact),
+            updated: Utc::now(),
+            updated_by: user,
+            name,
+        };
+
+        // Write it back
+        debug!("Writing annotation of dataset '{}' to Scylla database...", annotation.name);
+        let values = (
+            &annotation.name,
+            &annotation.description,
+            &annotation.tags,
+            &annotation.contact,
+            annotation.updated.timestamp_millis(),
+            &annotation.updated_by,
+        );
+        scylla.execute(&context.statements.annotation_insert, &values).await?;
+        info!("User '{}' annotated dataset '{}'", annotation.updated_by, annotation.name);
+        Ok(annotation)
+    }
+
+    /// Removes all annotations of a dataset. Requires an API token.
+    async fn remove_dataset_annotation(name: String, context: &Context) -> FieldResult<&str> {
+        info!("Handling GRAPHQL on '/graphql' (i.e., remove dataset annotation)");
+        let user: String = auth::authenticate(context)?;
+        let scylla = context.scylla.clone();
+
+        debug!("Deleting annotation of dataset '{}' from Scylla database...", name);
+        scylla.execute(&context.statements.annotation_delete, (&name,)).await?;
+        info!("User '{}' removed the annotation of dataset '{}'", user, name);
+        Ok("OK!")
+    }
 }
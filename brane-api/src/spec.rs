@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 15:16:04
 //  Last edited:
-//    28 Nov 2022, 17:15:19
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -15,9 +15,14 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use brane_cfg::info::InfoWatcher;
+use brane_cfg::infra::InfraFile;
 use brane_prx::client::ProxyClient;
 use scylla::Session;
 
+use crate::db::{PackageWriter, Statements};
+use crate::scan::Scanner;
+
 
 /***** LIBRARY *****/
 /// Defines the context of all the path calls.
@@ -25,8 +30,24 @@ use scylla::Session;
 pub struct Context {
     /// Points to the `node.yml` file we use in warp functions.
     pub node_config_path: PathBuf,
+    /// The infrastructure file, which is reloaded whenever it changes on disk.
+    pub infra: InfoWatcher<InfraFile>,
     /// Points to the Scylla database where we store package information.
     pub scylla: Arc<Session>,
+    /// The statements prepared in the Scylla database.
+    pub statements: Arc<Statements>,
+    /// Inserts packages into the Scylla database in batches.
+    pub packages_writer: PackageWriter,
     /// The proxy client through which we send our requests.
     pub proxy: Arc<ProxyClient>,
+    /// Points to the certificate directory, which contains the file with API tokens (see [`crate::auth`]).
+    pub certs_path: PathBuf,
+    /// The API token that the client presented with this request, if any.
+    pub token: Option<String>,
+    /// Points to the file with peer instances, if federation is enabled (see [`crate::federation`]).
+    pub peers_path: Option<PathBuf>,
+    /// Whether clients may build packages on this node (see [`crate::packages::build()`]).
+    pub builds: bool,
+    /// How to scan the images of new packages for vulnerabilities, if at all (see [`crate::scan`]).
+    pub scanner: Option<Scanner>,
 }
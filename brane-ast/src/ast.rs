@@ -4,7 +4,7 @@
 //  Created:
 //    30 Aug 2022, 11:55:49
 //  Last edited:
-//    06 Feb 2024, 11:38:29
+//    16 Oct 2026, 02:42:53
 //  Auto updated?
 //    Yes
 //
@@ -32,6 +32,7 @@ use serde::{Deserialize, Serialize};
 use serde_json_any_key::any_key_map;
 use specifications::data::{AvailabilityKind, DataName};
 use specifications::package::Capability;
+use specifications::planning::Constraints;
 use specifications::version::Version;
 
 use crate::data_type::DataType;
@@ -83,6 +84,10 @@ pub struct Workflow {
     pub graph: Arc<Vec<Edge>>,
     /// Contains the parts of the graph that are callable.
     pub funcs: Arc<HashMap<usize, Vec<Edge>>>,
+
+    /// The placement constraints on the tasks in this workflow.
+    #[serde(default)]
+    pub constraints: Arc<Constraints>,
 }
 
 impl Workflow {
@@ -98,7 +103,15 @@ impl Workflow {
     /// A new Workflow instance.
     #[inline]
     pub fn new(id: String, table: SymTable, graph: Vec<Edge>, funcs: HashMap<usize, Vec<Edge>>) -> Self {
-        Self { id, table: Arc::new(table), metadata: Arc::new(HashSet::new()), user: Arc::new(None), graph: Arc::new(graph), funcs: Arc::new(funcs) }
+        Self {
+            id,
+            table: Arc::new(table),
+            metadata: Arc::new(HashSet::new()),
+            user: Arc::new(None),
+            graph: Arc::new(graph),
+            funcs: Arc::new(funcs),
+            constraints: Arc::new(Constraints::default()),
+        }
     }
 
     /// Constructor for the Workflow that initializes it to the given contents, but generates a random ID.
@@ -119,7 +132,70 @@ impl Workflow {
             user: Arc::new(None),
             graph: Arc::new(graph),
             funcs: Arc::new(funcs),
+            constraints: Arc::new(Constraints::default()),
+        }
+    }
+
+    /// Describes how an intermediate result is computed, independently of the names that compiling the workflow generated.
+    ///
+    /// The lineage of a result is the task that produced it applied to the lineages of its inputs, where datasets are referred to by name
+    /// (e.g., `epi[1.0.0]::aggregate(data:umc,epi[1.0.0]::clean(data:umc))`). Because it depends neither on the ID of the workflow nor on the
+    /// (random) names of its intermediate results, the same computation has the same lineage every time the workflow is compiled.
+    ///
+    /// Note that the lineage does not cover the arguments of the calls, as these are only known at runtime. Anything that must tell calls with
+    /// different arguments apart (such as the fingerprints that workers record for their results) has to add them itself.
+    ///
+    /// # Arguments
+    /// - `result`: The name of the intermediate result.
+    ///
+    /// # Returns
+    /// The lineage of the result, or [`None`] if no task in this workflow produces it.
+    pub fn lineage(&self, result: &str) -> Option<String> { self.lineage_of(result, &mut HashSet::new()) }
+
+    /// Computes the lineage of an intermediate result (see [`Workflow::lineage()`]).
+    ///
+    /// # Arguments
+    /// - `result`: The name of the intermediate result.
+    /// - `stack`: The results whose lineage we are already computing, such that results that depend on themselves (e.g., in loops) end.
+    ///
+    /// # Returns
+    /// The lineage of the result, or [`None`] if no task in this workflow produces it or if we are already computing it.
+    fn lineage_of(&self, result: &str, stack: &mut HashSet<String>) -> Option<String> {
+        if !stack.insert(result.into()) {
+            return None;
         }
+
+        // Collect every call that produces it (there may be more than one if the result is produced in different branches)
+        let mut calls: Vec<String> = vec![];
+        for edge in self.graph.iter().chain(self.funcs.values().flatten()) {
+            if let Edge::Node { task, input, result: Some(produced), .. } = edge {
+                if produced != result {
+                    continue;
+                }
+                let task: String = match self.table.tasks.get(*task) {
+                    Some(TaskDef::Compute(def)) => format!("{}[{}]::{}", def.package, def.version, def.function.name),
+                    Some(TaskDef::Transfer) => TRANSFER_FUNC.name.clone(),
+                    None => continue,
+                };
+                let mut inputs: Vec<String> = input
+                    .keys()
+                    .map(|name| match name {
+                        DataName::Data(name) => format!("data:{name}"),
+                        DataName::IntermediateResult(name) => self.lineage_of(name, stack).unwrap_or_else(|| "result".into()),
+                    })
+                    .collect();
+                inputs.sort();
+                calls.push(format!("{}({})", task, inputs.join(",")));
+            }
+        }
+        stack.remove(result);
+
+        if calls.is_empty() {
+            return None;
+        }
+        calls.sort();
+        calls.dedup();
+        Some(calls.join("|"))
     }
 
     // /// Returns the edge pointed to by the given PC.
@@ -169,6 +245,8 @@ impl Default for Workflow {
 
             graph: Arc::new(vec![]),
             funcs: Arc::new(HashMap::new()),
+
+            constraints: Arc::new(Constraints::default()),
         }
     }
 }
@@ -547,6 +625,9 @@ pub enum Edge {
         /// Metadata for this call
         #[serde(rename = "m")]
         metadata: HashSet<Metadata>,
+        /// If given, the number of seconds for which workers may reuse an earlier result of an identical call instead of running it again.
+        #[serde(rename = "c", default, skip_serializing_if = "Option::is_none")]
+        cache: Option<u64>,
         /// The next edge to execute (usually the next one)
         #[serde(rename = "n")]
         next: usize,
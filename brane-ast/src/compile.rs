@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 18:12:44
 //  Last edited:
-//    13 Dec 2023, 08:22:16
+//    15 Oct 2026, 23:52:12
 //  Auto updated?
 //    Yes
 //
@@ -12,10 +12,11 @@
 //!   Defines some toplevel functions that run all traversals as desired.
 //
 
+use std::collections::HashSet;
 use std::fmt::{Display, Formatter, Result as FResult};
 
-use brane_dsl::ast::Program;
-use brane_dsl::{Error as ParseError, ParserOptions};
+use brane_dsl::ast::{Block, Program, Stmt};
+use brane_dsl::{Error as ParseError, Language, ParserOptions};
 use log::trace;
 use specifications::data::DataIndex;
 use specifications::package::PackageIndex;
@@ -28,6 +29,58 @@ use crate::traversals;
 pub use crate::warnings::AstWarning as Warning;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{AstError, ResolveError};
+
+
+    /// Tests whether the imports of a snippet are found, including those in nested blocks.
+    #[test]
+    fn test_package_imports() {
+        let code: &str = "import hello_world;\nfunc f() { import copy_result[1.0.0]; }\nif (true) { import hello_world; }";
+        let names: HashSet<String> = package_imports(code, &ParserOptions::bscript()).unwrap().unwrap();
+        assert_eq!(names, HashSet::from(["hello_world".into(), "copy_result".into()]));
+
+        // Bakery may use any package
+        assert!(package_imports("", &ParserOptions::bakery()).unwrap().is_none());
+    }
+
+    /// Tests whether a compile state still compiles follow-up snippets after being serialized and deserialized.
+    #[test]
+    fn test_compile_state_roundtrip() {
+        let (pindex, dindex): (PackageIndex, DataIndex) = (PackageIndex::empty(), DataIndex::new());
+        let mut state: CompileState = CompileState::new();
+        let code: &str = "let answer := 42;\nfunc double(x) { return 2 * x; }";
+        compile_snippet(&mut state, code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()).workflow();
+
+        // Restore it and refer to what the first snippet defined
+        let raw: String = serde_json::to_string(&state).unwrap();
+        let mut state: CompileState = serde_json::from_str(&raw).unwrap();
+        let (workflow, _) =
+            compile_snippet(&mut state, "println(double(answer));".as_bytes(), &pindex, &dindex, &ParserOptions::bscript()).workflow();
+        assert!(workflow.table.vars.iter().any(|var| var.name == "answer"));
+    }
+
+    /// Tests whether `keep_result()` only accepts string literals as the name of the dataset.
+    #[test]
+    fn test_keep_result_literal_name() {
+        let (pindex, dindex): (PackageIndex, DataIndex) = (PackageIndex::empty(), DataIndex::new());
+        let code: &str = "let name := \"out\";\nfunc keep(res) { keep_result(name, res); }";
+        match compile_snippet(&mut CompileState::new(), code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+            CompileResult::Err(errs) => {
+                assert!(errs.iter().any(|err| matches!(err, AstError::ResolveError(ResolveError::KeepResultIncorrectExpr { .. }))))
+            },
+            _ => panic!("Expected compiling a non-literal dataset name to fail"),
+        }
+    }
+}
+
+
+
+
+
 /***** AUXILLARY *****/
 /// Helper enum that defines the compiler stages.
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -190,6 +243,49 @@ impl Display for CompileResult {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Collects the names of the packages imported in the given block (and any nested blocks).
+///
+/// # Arguments
+/// - `block`: The [`Block`] to search.
+/// - `names`: The set to add the names of the imported packages to.
+fn collect_imports(block: &Block, names: &mut HashSet<String>) {
+    for stmt in &block.stmts {
+        match stmt {
+            Stmt::Import { name, .. } => {
+                names.insert(name.value.clone());
+            },
+
+            Stmt::Block { block } => collect_imports(block, names),
+            Stmt::FuncDef { code, .. } => collect_imports(code, names),
+            Stmt::ClassDef { methods, .. } => {
+                for method in methods {
+                    if let Stmt::FuncDef { code, .. } = &**method {
+                        collect_imports(code, names);
+                    }
+                }
+            },
+            Stmt::If { consequent, alternative, .. } => {
+                collect_imports(consequent, names);
+                if let Some(alternative) = alternative {
+                    collect_imports(alternative, names);
+                }
+            },
+            Stmt::For { consequent, .. } | Stmt::While { consequent, .. } => collect_imports(consequent, names),
+            Stmt::Parallel { blocks, .. } => {
+                for block in blocks {
+                    collect_imports(block, names);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Runs the compiler passes in-order, all of them.
 ///
@@ -465,3 +561,29 @@ pub fn compile_snippet_to<R: std::io::Read>(
     trace!("Compilation done (result: Program)");
     CompileResult::Program(program, warnings)
 }
+
+
+
+/// Finds the packages that the given snippet imports, such that only those have to be resolved before compiling it.
+///
+/// # Arguments
+/// - `source`: The source text of the snippet.
+/// - `options`: The ParserOptions with which we parse the snippet.
+///
+/// # Returns
+/// The names of the imported packages, or [`None`] if the snippet may use any package. The latter is the case for Bakery, where patterns are
+/// resolved against the functions of all known packages.
+///
+/// # Errors
+/// This function errors if the snippet could not be parsed.
+pub fn package_imports(source: impl AsRef<str>, options: &ParserOptions) -> Result<Option<HashSet<String>>, ParseError> {
+    if options.lang == Language::Bakery {
+        return Ok(None);
+    }
+
+    // Parse the snippet without any packages; BraneScript only needs them once the imports are resolved
+    let program: Program = brane_dsl::parse(source, &PackageIndex::empty(), options)?;
+    let mut names: HashSet<String> = HashSet::new();
+    collect_imports(&program.block, &mut names);
+    Ok(Some(names))
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    10 Aug 2022, 13:52:37
 //  Last edited:
-//    31 Jan 2024, 11:35:11
+//    15 Oct 2026, 23:52:12
 //  Auto updated?
 //    Yes
 //
@@ -19,8 +19,9 @@ use std::io::Write;
 use brane_dsl::ast::Expr;
 use brane_dsl::spec::MergeStrategy;
 use brane_dsl::{DataType, TextRange};
+use brane_shr::suggest::DidYouMean;
 use console::{style, Style};
-use specifications::version::Version;
+use specifications::version::VersionRange;
 
 use crate::spec::BuiltinClasses;
 
@@ -566,7 +567,7 @@ pub enum ResolveError {
     /// Failed to parse a package version number.
     VersionParseError { err: specifications::version::ParseError, range: TextRange },
     /// The given package/version pair was not found.
-    UnknownPackageError { name: String, version: Version, range: TextRange },
+    UnknownPackageError { name: String, version: VersionRange, suggestions: Vec<String>, range: TextRange },
     /// Failed to declare an imported package function
     FunctionImportError { package_name: String, name: String, err: brane_dsl::errors::SymbolTableError, range: TextRange },
     /// Failed to declare an imported package class
@@ -597,6 +598,8 @@ pub enum ResolveError {
     UndefinedFunction { ident: String, range: TextRange },
     /// A `commit_result()` did not have a string literal as 'name' field.
     CommitResultIncorrectExpr { range: TextRange },
+    /// A `keep_result()` did not have a string literal as 'name' field.
+    KeepResultIncorrectExpr { range: TextRange },
 
     /// A project operator was used on a non-class type.
     NonClassProjection { name: String, got: DataType, range: TextRange },
@@ -606,7 +609,7 @@ pub enum ResolveError {
     /// A data structure did not have a string literal as 'name' field.
     DataIncorrectExpr { range: TextRange },
     /// An unknown dataset was references.
-    UnknownDataError { name: String, range: TextRange },
+    UnknownDataError { name: String, suggestions: Vec<String>, range: TextRange },
 
     /// The given variable was not declared before.
     UndefinedVariable { ident: String, range: TextRange },
@@ -654,6 +657,7 @@ impl ResolveError {
 
             UndefinedFunction { range, .. } => prettywrite_err(writer, file, source, self, range),
             CommitResultIncorrectExpr { range, .. } => prettywrite_err(writer, file, source, self, range),
+            KeepResultIncorrectExpr { range, .. } => prettywrite_err(writer, file, source, self, range),
 
             NonClassProjection { range, .. } => prettywrite_err(writer, file, source, self, range),
             UnknownField { range, .. } => prettywrite_err(writer, file, source, self, range),
@@ -672,11 +676,12 @@ impl Display for ResolveError {
         use ResolveError::*;
         match self {
             VersionParseError { err, .. } => write!(f, "Failed to parse package version: {err}"),
-            UnknownPackageError { name, version, .. } => write!(
+            UnknownPackageError { name, version, suggestions, .. } => write!(
                 f,
-                "Package '{}' does not exist{}",
+                "Package '{}' does not exist{}{}",
                 name,
-                if !version.is_latest() { format!(" or has no version '{version}'") } else { String::new() }
+                if !version.is_any() { format!(" or has no version matching '{version}'") } else { String::new() },
+                DidYouMean::new(suggestions)
             ),
             FunctionImportError { package_name, name, err, .. } => {
                 write!(f, "Could not import function '{name}' from package '{package_name}': {err}")
@@ -699,12 +704,13 @@ impl Display for ResolveError {
 
             UndefinedFunction { ident, .. } => write!(f, "Undefined function or method '{ident}'"),
             CommitResultIncorrectExpr { .. } => write!(f, "Builtin function 'commit_result()' can only accept string literals as data name"),
+            KeepResultIncorrectExpr { .. } => write!(f, "Builtin function 'keep_result()' can only accept string literals as data name"),
 
             NonClassProjection { name, got, .. } => write!(f, "Cannot access field '{name}' of non-class type {got}"),
             UnknownField { class_name, name, .. } => write!(f, "Class '{class_name}' has no field '{name}'"),
 
             DataIncorrectExpr { .. } => write!(f, "Data class can only take String literals as name"),
-            UnknownDataError { name, .. } => write!(f, "No location has access to data asset '{name}'"),
+            UnknownDataError { name, suggestions, .. } => write!(f, "No location has access to data asset '{name}'{}", DidYouMean::new(suggestions)),
 
             UndefinedVariable { ident, .. } => write!(f, "Undefined variable or parameter '{ident}'"),
         }
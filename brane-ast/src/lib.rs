@@ -4,7 +4,7 @@
 //  Created:
 //    10 Aug 2022, 13:51:38
 //  Last edited:
-//    16 Jan 2024, 11:32:14
+//    15 Oct 2026, 22:44:05
 //  Auto updated?
 //    Yes
 //
@@ -40,7 +40,7 @@ pub use ast::{SymTable, Workflow};
 pub use ast_unresolved::UnresolvedWorkflow;
 pub use brane_dsl::spec::{MergeStrategy, TextPos, TextRange};
 pub use brane_dsl::ParserOptions;
-pub use compile::{compile_program, compile_program_to, compile_snippet, compile_snippet_to, CompileResult, CompileStage};
+pub use compile::{compile_program, compile_program_to, compile_snippet, compile_snippet_to, package_imports, CompileResult, CompileStage};
 pub use data_type::DataType;
 // Bring some stuff into the global namespace.
 pub use errors::AstError as Error;
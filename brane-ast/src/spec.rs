@@ -4,7 +4,7 @@
 //  Created:
 //    20 Oct 2022, 14:17:30
 //  Last edited:
-//    07 Nov 2023, 17:15:25
+//    15 Oct 2026, 23:52:12
 //  Auto updated?
 //    Yes
 //
@@ -33,6 +33,8 @@ pub enum BuiltinFunctions {
 
     /// The commit_builtin-function, which turns an IntermediateResult into a Data.
     CommitResult,
+    /// The keep_result-function, which turns an IntermediateResult into a Data once the workflow is done.
+    KeepResult,
 }
 
 impl BuiltinFunctions {
@@ -47,6 +49,7 @@ impl BuiltinFunctions {
             Len => "len",
 
             CommitResult => "commit_result",
+            KeepResult => "keep_result",
         }
     }
 
@@ -64,16 +67,21 @@ impl BuiltinFunctions {
                 vec![DataType::String, DataType::Class(BuiltinClasses::IntermediateResult.name().into())],
                 DataType::Class(BuiltinClasses::Data.name().into()),
             ),
+            KeepResult => {
+                FunctionSignature::new(vec![DataType::String, DataType::Class(BuiltinClasses::IntermediateResult.name().into())], DataType::Void)
+            },
         }
     }
 
     /// Returns an array with all the builtin functions in it.
     #[inline]
-    pub fn all() -> [Self; 4] { [Self::Print, Self::PrintLn, Self::Len, Self::CommitResult] }
+    pub fn all() -> [Self; 5] { [Self::Print, Self::PrintLn, Self::Len, Self::CommitResult, Self::KeepResult] }
 
     /// Returns an Array with all of the builtin functions but already casted to FunctionStates.
     #[inline]
-    pub fn all_into_state() -> [FunctionState; 4] { [Self::Print.into(), Self::PrintLn.into(), Self::Len.into(), Self::CommitResult.into()] }
+    pub fn all_into_state() -> [FunctionState; 5] {
+        [Self::Print.into(), Self::PrintLn.into(), Self::Len.into(), Self::CommitResult.into(), Self::KeepResult.into()]
+    }
 
     /// Checks if the given string is a builtin.
     #[inline]
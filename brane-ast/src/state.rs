@@ -4,7 +4,7 @@
 //  Created:
 //    16 Sep 2022, 08:22:47
 //  Last edited:
-//    13 Dec 2023, 08:20:26
+//    15 Oct 2026, 23:40:58
 //  Auto updated?
 //    Yes
 //
@@ -21,6 +21,7 @@ use brane_dsl::ast::Data;
 use brane_dsl::data_type::{ClassSignature, FunctionSignature};
 use brane_dsl::symbol_table::{ClassEntry, FunctionEntry, SymbolTable, VarEntry};
 use brane_dsl::{DataType, TextRange};
+use serde::{Deserialize, Serialize};
 use specifications::package::Capability;
 use specifications::version::Version;
 
@@ -40,7 +41,7 @@ lazy_static! {
 
 /***** LIBRARY *****/
 /// Defines a 'TableState', which is the CompileState's notion of a symbol table.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TableState {
     /// The functions that are kept for next compilation junks
     pub funcs:   Vec<FunctionState>,
@@ -301,7 +302,7 @@ impl From<&TableState> for SymTable {
 
 
 /// Defines whatever we need to know of a function in between workflow snippet calls.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FunctionState {
     /// The name of the function.
     pub name:      String,
@@ -346,7 +347,7 @@ impl From<FunctionState> for FunctionDef {
 
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TaskState {
     /// The name of the function.
     pub name: String,
@@ -409,7 +410,7 @@ impl From<TaskState> for TaskDef {
 
 
 /// Defines whatever we need to know of a class in between workflow snippet calls.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ClassState {
     /// The name of the class.
     pub name:    String,
@@ -489,7 +490,7 @@ impl From<ClassState> for ClassDef {
 
 
 /// Defines whatever we need to know of a variable in between workflow snippet calls.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct VarState {
     /// The name of the variable.
     pub name:      String,
@@ -530,7 +531,7 @@ impl From<VarState> for VarDef {
 
 
 /// Defines a DataState, which is a bit like a symbol table for data identifiers - except that it's temporal (i.e., has a notion of values being overwritten).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DataState {
     // /// Maps function names (=identifiers) to their current possible list of data identifiers _they return_. Since function bodies are constant, it may be expected the list of possible identifiers is also.
     // funcs : HashMap<*const RefCell<FunctionEntry>, HashSet<Data>>,
@@ -659,7 +660,7 @@ impl Default for DataState {
 
 
 /// Defines whatever we need to remember w.r.t. compile-time in between two submissions of part of a workflow (i.e., repl-runs).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CompileState {
     /// Contains the offset (in lines) of this snippet compared to previous snippets in the source text.
     pub offset: usize,
@@ -689,6 +690,24 @@ impl CompileState {
             data: DataState::new(),
         }
     }
+
+    /// Declares a function that is implemented outside of BraneScript (e.g., by a VM plugin), such that snippets can call it like a builtin.
+    ///
+    /// # Arguments
+    /// - `name`: The name under which BraneScript can call the function.
+    /// - `signature`: The types of the function's arguments and its return type.
+    ///
+    /// # Returns
+    /// Whether the function was declared. It isn't if a function with the same name already exists (e.g., a builtin, or because it was already
+    /// declared for a previous snippet).
+    pub fn declare_external(&mut self, name: impl Into<String>, signature: FunctionSignature) -> bool {
+        let name: String = name.into();
+        if self.table.funcs.iter().any(|f| f.class_name.is_none() && f.name == name) {
+            return false;
+        }
+        self.table.funcs.push(FunctionState { name, signature, class_name: None, range: TextRange::none() });
+        true
+    }
 }
 
 impl Default for CompileState {
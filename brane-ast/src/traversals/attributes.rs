@@ -4,7 +4,7 @@
 //  Created:
 //    08 Dec 2023, 11:35:48
 //  Last edited:
-//    12 Dec 2023, 16:35:31
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -15,6 +15,7 @@
 //
 
 use brane_dsl::ast::{Attribute, Block, Node as _, Program, Stmt};
+use enum_debug::EnumDebug as _;
 
 use crate::errors::AstError;
 use crate::warnings::AstWarning;
@@ -147,6 +148,7 @@ fn pass_stmt(stmt: &mut Stmt, parent_attrs: &mut Vec<Attribute>, mut prev_attrs:
             attrs.extend(prev_attrs);
             vec![]
         },
+        ImportSource { .. } => panic!("Encountered {:?} in attributes traversal", stmt.variant()),
         FuncDef { ident: _, params: _, code, st_entry: _, attrs, range: _ } => {
             // Set the previous attributes
             attrs.extend(prev_attrs);
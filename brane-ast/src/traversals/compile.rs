@@ -4,7 +4,7 @@
 //  Created:
 //    31 Aug 2022, 11:32:04
 //  Last edited:
-//    31 Jan 2024, 11:35:39
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -537,7 +537,7 @@ fn pass_stmt(stmt: dsl::Stmt, edges: &mut EdgeBuffer, f_edges: &mut HashMap<usiz
 
         // We don't care about the rest (or it does not occur anymore)
         Import { .. } | Empty {} => {},
-        Attribute(_) | AttributeInner(_) | For { .. } => panic!("Encountered {:?} in compile traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } | For { .. } => panic!("Encountered {:?} in compile traversal", stmt.variant()),
     }
 }
 
@@ -565,7 +565,7 @@ fn pass_expr(expr: dsl::Expr, edges: &mut EdgeBuffer, _table: &TableState) {
             edges.write(ast::Edge::Linear { instrs: vec![ast::EdgeInstr::Cast { res_type: (&target).into() }], next: usize::MAX });
         },
 
-        Call { expr, args, st_entry, locations, input, result, metadata, range: _ } => {
+        Call { expr, args, st_entry, locations, input, result, metadata, cache, range: _ } => {
             // First, write the arguments followed by the call expression
             for a in args {
                 pass_expr(*a, edges, _table);
@@ -595,6 +595,7 @@ fn pass_expr(expr: dsl::Expr, edges: &mut EdgeBuffer, _table: &TableState) {
                     input: input.into_iter().map(|d| (d.into(), None)).collect(),
                     result,
                     metadata: metadata.into_iter().map(|md| ast::Metadata { owner: md.owner, tag: md.tag, signature: None }).collect(),
+                    cache,
                     next: usize::MAX,
                 });
             } else {
@@ -4,7 +4,7 @@
 //  Created:
 //    25 Oct 2022, 13:34:31
 //  Last edited:
-//    08 Dec 2023, 10:41:31
+//    15 Oct 2026, 23:52:12
 //  Auto updated?
 //    Yes
 //
@@ -264,7 +264,7 @@ fn pass_stmt(stmt: &mut Stmt, table: &mut DataState, is_branch: bool, scope: &Rc
 
         // The rest no matter
         Import { .. } | Empty { .. } => HashSet::new(),
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in data traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in data traversal", stmt.variant()),
     }
 }
 
@@ -344,6 +344,7 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
                     } else if entry.name == BuiltinFunctions::Print.name()
                         || entry.name == BuiltinFunctions::PrintLn.name()
                         || entry.name == BuiltinFunctions::Len.name()
+                        || entry.name == BuiltinFunctions::KeepResult.name()
                     {
                         // Simply return NOTHING :P
                         HashSet::new()
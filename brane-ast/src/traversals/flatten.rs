@@ -4,7 +4,7 @@
 //  Created:
 //    15 Sep 2022, 08:26:20
 //  Last edited:
-//    12 Dec 2023, 15:57:21
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -516,7 +516,7 @@ pub fn pass_stmt(stmt: &mut Stmt, table: &mut TableState, errors: &mut Vec<Error
 
         // The rest neither recurses nor defines
         Empty {} => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in flatten traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in flatten traversal", stmt.variant()),
     }
 }
 
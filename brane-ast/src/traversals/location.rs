@@ -4,7 +4,7 @@
 //  Created:
 //    05 Sep 2022, 16:27:08
 //  Last edited:
-//    08 Dec 2023, 17:16:27
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -130,7 +130,7 @@ fn process_attrs_loc_location(attrs: &[Attribute], locations: &mut AllowedLocati
             },
 
             // Ignore other attributes
-            Attribute::KeyPair { .. } => {},
+            Attribute::KeyPair { .. } | Attribute::Options { .. } => {},
         }
     }
 }
@@ -235,7 +235,7 @@ fn pass_stmt(stmt: &mut Stmt, mut locations: AllowedLocations, mut reasons: Vec<
 
         // The rest no matter
         Import { .. } | Empty { .. } => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in location traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in location traversal", stmt.variant()),
     };
 }
 
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Dec 2023, 16:34:54
 //  Last edited:
-//    13 Dec 2023, 08:21:29
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -79,6 +79,27 @@ mod tests {
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
         });
     }
+
+    /// Tests whether the lifetimes of `cache`-attributes are parsed correctly.
+    #[test]
+    fn test_parse_ttl() {
+        let string = |value: &str| Literal::String { value: value.into(), range: TextRange::none() };
+        assert_eq!(parse_ttl(&string("45s")), Some(45));
+        assert_eq!(parse_ttl(&string("15m")), Some(15 * 60));
+        assert_eq!(parse_ttl(&string("12h")), Some(12 * 60 * 60));
+        assert_eq!(parse_ttl(&string("7d")), Some(7 * 24 * 60 * 60));
+        assert_eq!(parse_ttl(&string("2w")), Some(2 * 7 * 24 * 60 * 60));
+        assert_eq!(parse_ttl(&string("300")), Some(300));
+        assert_eq!(parse_ttl(&Literal::Integer { value: 60, range: TextRange::none() }), Some(60));
+
+        // Some illegal ones
+        assert_eq!(parse_ttl(&string("")), None);
+        assert_eq!(parse_ttl(&string("d")), None);
+        assert_eq!(parse_ttl(&string("0d")), None);
+        assert_eq!(parse_ttl(&string("7y")), None);
+        assert_eq!(parse_ttl(&Literal::Integer { value: -1, range: TextRange::none() }), None);
+        assert_eq!(parse_ttl(&Literal::Boolean { value: true, range: TextRange::none() }), None);
+    }
 }
 
 
@@ -158,7 +179,7 @@ fn process_attrs_loc_location(attrs: &[Attribute], metadata: &mut HashMap<Metada
             },
 
             // Ignore other attributes
-            Attribute::KeyPair { .. } => {},
+            Attribute::KeyPair { .. } | Attribute::Options { .. } => {},
         }
     }
 }
@@ -172,6 +193,7 @@ fn warn_useless_attrs(attrs: &[Attribute], warns: &mut Vec<Warning>) {
     // Collect the attributes
     let mut metadata: HashMap<Metadata, TextRange> = HashMap::new();
     process_attrs_loc_location(attrs, &mut metadata, false, warns);
+    process_attrs_cache(attrs, &mut cache, warns);
 
     // Warn
     for range in metadata.into_values() {
@@ -179,6 +201,69 @@ fn warn_useless_attrs(attrs: &[Attribute], warns: &mut Vec<Warning>) {
     }
 }
 
+/// Parses the lifetime of a `cache`-attribute.
+///
+/// # Arguments
+/// - `value`: The [`Literal`] to parse. Either a string of a number followed by a unit (`s`, `m`, `h`, `d` or `w`), or an integer number of seconds.
+///
+/// # Returns
+/// The parsed lifetime, in seconds, or [`None`] if the literal is not a valid lifetime.
+fn parse_ttl(value: &Literal) -> Option<u64> {
+    let (raw, unit): (&str, u64) = match value {
+        Literal::Integer { value, range: _ } => return if *value > 0 { Some(*value as u64) } else { None },
+        Literal::String { value, range: _ } => match value.char_indices().last() {
+            Some((i, 's')) => (&value[..i], 1),
+            Some((i, 'm')) => (&value[..i], 60),
+            Some((i, 'h')) => (&value[..i], 60 * 60),
+            Some((i, 'd')) => (&value[..i], 24 * 60 * 60),
+            Some((i, 'w')) => (&value[..i], 7 * 24 * 60 * 60),
+            _ => (value.as_str(), 1),
+        },
+        _ => return None,
+    };
+    match raw.trim().parse::<u64>() {
+        Ok(0) | Err(_) => None,
+        Ok(amount) => amount.checked_mul(unit),
+    }
+}
+
+/// Searches the given attributes for `cache`/`no_cache`-attributes and uses that to decide whether calls may be cached, and for how long.
+///
+/// # Arguments
+/// - `attrs`: The list of attributes to search.
+/// - `cache`: The caching lifetime (in seconds) already in scope, if any. A `no_cache`-attribute resets it to [`None`].
+/// - `warns`: A list used to keep track of occurred warns.
+fn process_attrs_cache(attrs: &[Attribute], cache: &mut Option<u64>, warns: &mut Vec<Warning>) {
+    for attr in attrs {
+        match attr {
+            Attribute::Options { key, options, range } => {
+                if key.value == "no_cache" {
+                    *cache = None;
+                } else if key.value == "cache" {
+                    let mut ttl: Option<&Literal> = None;
+                    for (name, value) in options {
+                        if name.value == "ttl" {
+                            ttl = Some(value);
+                        } else {
+                            warns.push(Warning::UnknownCacheOption { name: name.value.clone(), range: name.range.clone() });
+                        }
+                    }
+                    match ttl {
+                        Some(ttl) => match parse_ttl(ttl) {
+                            Some(ttl) => *cache = Some(ttl),
+                            None => warns.push(Warning::IllegalCacheTtl { range: ttl.range().clone() }),
+                        },
+                        None => warns.push(Warning::MissingCacheTtl { range: range.clone() }),
+                    }
+                }
+            },
+
+            // Ignore other attributes
+            Attribute::KeyPair { .. } | Attribute::List { .. } => {},
+        }
+    }
+}
+
 
 
 
@@ -189,14 +274,16 @@ fn warn_useless_attrs(attrs: &[Attribute], warns: &mut Vec<Warning>) {
 /// # Arguments
 /// - `block`: The [`Block`] to traverse.
 /// - `metadata`: The current metadata in scope to apply to applicable things (and where they are defined).
+/// - `cache`: The caching lifetime (in seconds) in scope to apply to external calls, if any.
 /// - `warns`: A list that keeps track of warnings that occurred.
-fn pass_block(block: &mut Block, mut metadata: HashMap<Metadata, TextRange>, warns: &mut Vec<Warning>) {
+fn pass_block(block: &mut Block, mut metadata: HashMap<Metadata, TextRange>, mut cache: Option<u64>, warns: &mut Vec<Warning>) {
     // Process block attributes
     process_attrs_loc_location(&block.attrs, &mut metadata, false, warns);
+    process_attrs_cache(&block.attrs, &mut cache, warns);
 
     // Process the statements
     for stmt in &mut block.stmts {
-        pass_stmt(stmt, metadata.clone(), warns);
+        pass_stmt(stmt, metadata.clone(), cache, warns);
     }
 }
 
@@ -205,13 +292,14 @@ fn pass_block(block: &mut Block, mut metadata: HashMap<Metadata, TextRange>, war
 /// # Arguments
 /// - `stmt`: The [`Stmt`] to traverse.
 /// - `metadata`: The current metadata in scope to apply to applicable things (and where they are defined).
+/// - `cache`: The caching lifetime (in seconds) in scope to apply to external calls, if any.
 /// - `warns`: A list that keeps track of warnings that occurred.
-fn pass_stmt(stmt: &mut Stmt, mut metadata: HashMap<Metadata, TextRange>, warns: &mut Vec<Warning>) {
+fn pass_stmt(stmt: &mut Stmt, mut metadata: HashMap<Metadata, TextRange>, mut cache: Option<u64>, warns: &mut Vec<Warning>) {
     // Match on the statement
     use Stmt::*;
     match stmt {
         Block { block } => {
-            pass_block(block, metadata, warns);
+            pass_block(block, metadata, cache, warns);
         },
 
         Import { name: _, version: _, st_classes: _, st_funcs: _, attrs, range: _ } => {
@@ -221,86 +309,96 @@ fn pass_stmt(stmt: &mut Stmt, mut metadata: HashMap<Metadata, TextRange>, warns:
         FuncDef { ident: _, params: _, code, st_entry: _, attrs, range: _ } => {
             // Remind the user metadata is useless here
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
 
             // Traverse the body
-            pass_block(code, metadata, warns);
+            pass_block(code, metadata, cache, warns);
         },
         ClassDef { ident: _, props: _, methods, st_entry: _, symbol_table: _, attrs, range: _ } => {
             // Remind the user metadata is useless here
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
             // Traverse the methods
             for method in methods {
-                pass_stmt(method, metadata.clone(), warns);
+                pass_stmt(method, metadata.clone(), cache, warns);
             }
         },
         Return { expr, data_type: _, output: _, attrs, range: _ } => {
             // Traverse into the expression if there is any
             if let Some(expr) = expr {
                 process_attrs_loc_location(attrs, &mut metadata, false, warns);
-                pass_expr(expr, &metadata, warns);
+                process_attrs_cache(attrs, &mut cache, warns);
+                pass_expr(expr, &metadata, cache, warns);
             }
         },
 
         If { cond, consequent, alternative, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
 
             // Traverse into the expression, then bodies
-            pass_expr(cond, &metadata, warns);
-            pass_block(consequent, metadata.clone(), warns);
+            pass_expr(cond, &metadata, cache, warns);
+            pass_block(consequent, metadata.clone(), cache, warns);
             if let Some(alternative) = alternative {
-                pass_block(alternative, metadata, warns);
+                pass_block(alternative, metadata, cache, warns);
             }
         },
         For { initializer, condition, increment, consequent, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
 
             // Traverse into the expressions, then bodies
-            pass_stmt(initializer, metadata.clone(), warns);
-            pass_expr(condition, &metadata, warns);
-            pass_stmt(increment, metadata.clone(), warns);
-            pass_block(consequent, metadata, warns);
+            pass_stmt(initializer, metadata.clone(), cache, warns);
+            pass_expr(condition, &metadata, cache, warns);
+            pass_stmt(increment, metadata.clone(), cache, warns);
+            pass_block(consequent, metadata, cache, warns);
         },
         While { condition, consequent, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
 
             // Traverse into the expressions, then bodies
-            pass_expr(condition, &metadata, warns);
-            pass_block(consequent, metadata, warns);
+            pass_expr(condition, &metadata, cache, warns);
+            pass_block(consequent, metadata, cache, warns);
         },
         Parallel { result: _, blocks, merge: _, st_entry: _, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
 
             // Traverse into the bodies
             for block in blocks {
-                pass_block(block, metadata.clone(), warns);
+                pass_block(block, metadata.clone(), cache, warns);
             }
         },
 
         LetAssign { name: _, value, st_entry: _, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
             // Process the expression
-            pass_expr(value, &metadata, warns);
+            pass_expr(value, &metadata, cache, warns);
         },
         Assign { name: _, value, st_entry: _, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
             // Process the expression
-            pass_expr(value, &metadata, warns);
+            pass_expr(value, &metadata, cache, warns);
         },
         Expr { expr, data_type: _, attrs, range: _ } => {
             // Process attributes for the expression
             process_attrs_loc_location(attrs, &mut metadata, false, warns);
+            process_attrs_cache(attrs, &mut cache, warns);
             // Process the expression
-            pass_expr(expr, &metadata, warns);
+            pass_expr(expr, &metadata, cache, warns);
         },
 
         Empty {} => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in metadata traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in metadata traversal", stmt.variant()),
     }
 }
 
@@ -309,48 +407,50 @@ fn pass_stmt(stmt: &mut Stmt, mut metadata: HashMap<Metadata, TextRange>, warns:
 /// # Arguments
 /// - `expr`: The [`Expr`] to traverse.
 /// - `metadata`: The current metadata in scope to apply to applicable things (and where they are defined).
+/// - `cache`: The caching lifetime (in seconds) in scope to apply to external calls, if any.
 /// - `warns`: A list that keeps track of warnings that occurred.
-fn pass_expr(expr: &mut Expr, metadata: &HashMap<Metadata, TextRange>, _warns: &mut Vec<Warning>) {
+fn pass_expr(expr: &mut Expr, metadata: &HashMap<Metadata, TextRange>, cache: Option<u64>, _warns: &mut Vec<Warning>) {
     // Match on the expression
     use Expr::*;
     match expr {
-        Cast { expr, target: _, range: _ } => pass_expr(expr, metadata, _warns),
+        Cast { expr, target: _, range: _ } => pass_expr(expr, metadata, cache, _warns),
 
-        Call { expr, args, st_entry, locations: _, input: _, result: _, metadata: call_metadata, range: _ } => {
+        Call { expr, args, st_entry, locations: _, input: _, result: _, metadata: call_metadata, cache: call_cache, range: _ } => {
             // Examine if it's an external call
             if st_entry.as_ref().map(|entry| entry.borrow().package_name.is_some()).unwrap_or(false) {
                 call_metadata.extend(metadata.keys().cloned());
+                *call_cache = cache;
             }
 
             // Otherwise, recurse into the expressions
-            pass_expr(expr, metadata, _warns);
+            pass_expr(expr, metadata, cache, _warns);
             for arg in args {
-                pass_expr(arg, metadata, _warns);
+                pass_expr(arg, metadata, cache, _warns);
             }
         },
         Array { values, data_type: _, range: _ } => {
             for value in values {
-                pass_expr(value, metadata, _warns);
+                pass_expr(value, metadata, cache, _warns);
             }
         },
         ArrayIndex { array, index, data_type: _, range: _ } => {
-            pass_expr(array, metadata, _warns);
-            pass_expr(index, metadata, _warns);
+            pass_expr(array, metadata, cache, _warns);
+            pass_expr(index, metadata, cache, _warns);
         },
 
-        UnaOp { op: _, expr, range: _ } => pass_expr(expr, metadata, _warns),
+        UnaOp { op: _, expr, range: _ } => pass_expr(expr, metadata, cache, _warns),
         BinOp { op: _, lhs, rhs, range: _ } => {
-            pass_expr(lhs, metadata, _warns);
-            pass_expr(rhs, metadata, _warns);
+            pass_expr(lhs, metadata, cache, _warns);
+            pass_expr(rhs, metadata, cache, _warns);
         },
         Proj { lhs, rhs, st_entry: _, range: _ } => {
-            pass_expr(lhs, metadata, _warns);
-            pass_expr(rhs, metadata, _warns);
+            pass_expr(lhs, metadata, cache, _warns);
+            pass_expr(rhs, metadata, cache, _warns);
         },
 
         Instance { name: _, properties, st_entry: _, range: _ } => {
             for prop in properties {
-                pass_expr(&mut prop.value, metadata, _warns);
+                pass_expr(&mut prop.value, metadata, cache, _warns);
             }
         },
 
@@ -364,7 +464,7 @@ fn pass_expr(expr: &mut Expr, metadata: &HashMap<Metadata, TextRange>, _warns: &
 
 
 /***** LIBRARY *****/
-/// Processes `#[tag(...)]`/`#[metadata(...)]`-annotations into annotations on various things in the AST.
+/// Processes `#[tag(...)]`/`#[metadata(...)]`- and `#[cache(...)]`/`#[no_cache]`-annotations into annotations on various things in the AST.
 ///
 /// The goal of this traversal is to populate `metadata`-fields in various AST elements.
 ///
@@ -385,7 +485,7 @@ pub fn do_traversal(mut root: Program, warnings: &mut Vec<AstWarning>) -> Result
     root.metadata = root_metadata.into_keys().collect();
 
     // Traverse the tree, doin' all the work
-    pass_block(&mut root.block, HashMap::new(), &mut warns);
+    pass_block(&mut root.block, HashMap::new(), None, &mut warns);
 
     // Process the warnings
     warnings.extend(warns.into_iter().map(AstWarning::MetadataWarning));
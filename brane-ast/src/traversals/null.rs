@@ -4,7 +4,7 @@
 //  Created:
 //    19 Dec 2022, 10:04:38
 //  Last edited:
-//    08 Dec 2023, 11:09:15
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -177,7 +177,7 @@ fn pass_stmt(stmt: &mut Stmt, errors: &mut Vec<Error>) {
 
         // The rest we don't care.
         Import { .. } | Empty {} => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in resolve traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in resolve traversal", stmt.variant()),
     }
 }
 
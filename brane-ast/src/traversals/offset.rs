@@ -4,7 +4,7 @@
 //  Created:
 //    12 Dec 2023, 16:33:38
 //  Last edited:
-//    12 Dec 2023, 17:13:38
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -72,6 +72,14 @@ fn pass_stmt(stmt: &mut Stmt, offset: usize) {
                 }
                 offset_range!(range, offset);
             },
+            brane_dsl::ast::Attribute::Options { key, options, range } => {
+                pass_ident(key, offset);
+                for (name, value) in options {
+                    pass_ident(name, offset);
+                    pass_literal(value, offset);
+                }
+                offset_range!(range, offset);
+            },
         },
 
         Block { block } => pass_block(block, offset),
@@ -81,6 +89,9 @@ fn pass_stmt(stmt: &mut Stmt, offset: usize) {
             pass_literal(version, offset);
             offset_range!(range, offset);
         },
+        ImportSource { path: _, range } => {
+            offset_range!(range, offset);
+        },
         FuncDef { ident, params, code, st_entry: _, attrs: _, range } => {
             pass_ident(ident, offset);
             for param in params {
@@ -194,7 +205,7 @@ fn pass_expr(expr: &mut Expr, offset: usize) {
             offset_range!(range, offset);
         },
 
-        Call { expr, args, st_entry: _, locations: _, input: _, result: _, metadata: _, range } => {
+        Call { expr, args, st_entry: _, locations: _, input: _, result: _, metadata: _, cache: _, range } => {
             pass_expr(expr, offset);
             for arg in args {
                 pass_expr(arg, offset);
@@ -4,7 +4,7 @@
 //  Created:
 //    31 Aug 2022, 09:25:11
 //  Last edited:
-//    06 Feb 2024, 11:38:47
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -183,11 +183,11 @@ fn pass_edges(
         // Match on it
         use Edge::*;
         match node {
-            Node { task, locs, at, input, result, metadata, next } => {
+            Node { task, locs, at, input, result, metadata, cache, next } => {
                 // Write the Node as a task call
                 writeln!(
                     writer,
-                    "{} {}Node({}){}{}{}{}",
+                    "{} {}Node({}){}{}{}{}{}",
                     line_number!(i),
                     indent!(indent),
                     match &table.task(*task) {
@@ -242,6 +242,7 @@ fn pass_edges(
                     } else {
                         String::new()
                     },
+                    if let Some(ttl) = cache { format!(" <cache {ttl}s>") } else { String::new() },
                 )?;
 
                 // Move to the next node
@@ -526,7 +527,7 @@ fn pass_edge_instr(writer: &mut impl Write, instr: &EdgeInstr, table: &SymTable)
 /// # Errors
 /// This pass may error if we failed to write to the given writer.
 pub fn do_traversal(root: &Workflow, mut writer: impl Write) -> Result<(), Vec<Error>> {
-    let Workflow { id, table, metadata, user, graph, funcs } = root;
+    let Workflow { id, table, metadata, user, graph, funcs, constraints } = root;
 
     if let Err(err) = writeln!(&mut writer, "Workflow '{id}' {{") {
         return Err(vec![Error::WriteError { err }]);
@@ -573,6 +574,24 @@ pub fn do_traversal(root: &Workflow, mut writer: impl Write) -> Result<(), Vec<E
         };
     }
 
+    // Print the placement constraints
+    if !constraints.is_empty() {
+        for line in constraints.to_string().lines() {
+            if let Err(err) = writeln!(&mut writer, "{}{}", indent!(INDENT_SIZE), line) {
+                return Err(vec![Error::WriteError { err }]);
+            };
+        }
+        if let Err(err) = writeln!(&mut writer) {
+            return Err(vec![Error::WriteError { err }]);
+        };
+        if let Err(err) = writeln!(&mut writer) {
+            return Err(vec![Error::WriteError { err }]);
+        };
+        if let Err(err) = writeln!(&mut writer) {
+            return Err(vec![Error::WriteError { err }]);
+        };
+    }
+
     // First up: print the workflow table
     if let Err(err) = pass_table(&mut writer, table, INDENT_SIZE) {
         return Err(vec![Error::WriteError { err }]);
@@ -0,0 +1,399 @@
+//  CWL.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:08:36
+//  Last edited:
+//    15 Oct 2026, 22:09:09
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Exports the `brane-ast` AST as a [Common Workflow Language](https://www.commonwl.org)
+//!   (CWL) `Workflow`, such that it can be documented, reviewed and
+//!   partially reused on infrastructure that speaks CWL.
+//!
+//!   CWL only describes a DAG of steps, so the export is necessarily
+//!   lossy: every task call becomes a step that runs once, connected to
+//!   the others by the datasets and results they exchange. Steps that
+//!   only run conditionally, run in a loop or are part of a function
+//!   say so in their `doc`. Any arguments that are not datasets or
+//!   results are computed at runtime in Brane, and thus become inputs of
+//!   the CWL workflow.
+//
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+
+use specifications::data::DataName;
+
+use crate::ast::{ComputeTaskDef, Edge, TaskDef, Workflow};
+use crate::data_type::DataType;
+pub use crate::errors::AstError as Error;
+use crate::func_id::FunctionId;
+use crate::spec::BuiltinClasses;
+
+
+/***** CONSTANTS *****/
+/// The version of CWL that we export to.
+pub const CWL_VERSION: &str = "v1.2";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// The control flow around a task call that CWL cannot express.
+#[derive(Clone, Copy, Debug, Default)]
+struct Context {
+    /// The call only runs if some condition holds.
+    conditional: bool,
+    /// The call runs in a loop.
+    repeated:    bool,
+}
+
+/// A task call that becomes a step in the CWL workflow.
+#[derive(Debug)]
+struct Step<'w> {
+    /// The CWL identifier of the step.
+    id:      String,
+    /// The task that is called.
+    def:     &'w ComputeTaskDef,
+    /// The datasets and results that are input to the call.
+    input:   Vec<&'w DataName>,
+    /// The result produced by the call, if any.
+    result:  Option<&'w String>,
+    /// The function in which the call occurs.
+    func:    FunctionId,
+    /// The control flow around the call.
+    context: Context,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Quotes the given string as a YAML string.
+///
+/// # Arguments
+/// - `s`: The string to quote.
+///
+/// # Returns
+/// The string in double quotes, with any quotes, backslashes or newlines in it escaped.
+fn quote(s: impl AsRef<str>) -> String { format!("\"{}\"", s.as_ref().replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")) }
+
+/// Turns the given string into a valid CWL identifier.
+///
+/// # Arguments
+/// - `s`: The string to convert.
+///
+/// # Returns
+/// The string with any character that is not alphanumeric or an underscore replaced by an underscore, and prefixed with one if it started
+/// with a digit.
+fn cwl_id(s: impl AsRef<str>) -> String {
+    let id: String = s.as_ref().chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if id.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(true) {
+        format!("_{id}")
+    } else {
+        id
+    }
+}
+
+/// Returns the CWL type of the given data type.
+///
+/// # Arguments
+/// - `data_type`: The [`DataType`] to convert.
+///
+/// # Returns
+/// The CWL type, in YAML flow style. Datasets and results become `Directory`s, and types that CWL has no equivalent for become `Any`.
+fn cwl_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Boolean => "boolean".into(),
+        DataType::Integer => "long".into(),
+        DataType::Real => "double".into(),
+        DataType::String | DataType::Semver => "string".into(),
+        DataType::Array { elem_type } => format!("{{type: array, items: {}}}", cwl_type(elem_type)),
+        dt if is_data(dt) => "Directory".into(),
+        _ => "Any".into(),
+    }
+}
+
+/// Returns whether the given data type is a dataset or result.
+///
+/// # Arguments
+/// - `data_type`: The [`DataType`] to check.
+///
+/// # Returns
+/// True if values of this type refer to a dataset or result, or false otherwise.
+fn is_data(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Data | DataType::IntermediateResult => true,
+        DataType::Class { name } => name == BuiltinClasses::Data.name() || name == BuiltinClasses::IntermediateResult.name(),
+        _ => false,
+    }
+}
+
+
+
+
+
+/***** TRAVERSAL FUNCTIONS *****/
+/// Collects the task calls in a list of edges, following the control flow from the given edge until it reaches another.
+///
+/// # Arguments
+/// - `root`: The [`Workflow`] to which the edges belong.
+/// - `func`: The [`FunctionId`] of the function that the edges make up.
+/// - `edges`: The edges to traverse.
+/// - `pc`: The edge to start at.
+/// - `stop`: The edge to stop at (exclusive), if any.
+/// - `context`: The control flow around the edges.
+/// - `done`: The edges that we have already traversed, to prevent doing so twice.
+/// - `steps`: The list of steps to add the task calls to.
+#[allow(clippy::too_many_arguments)]
+fn pass_edges<'w>(
+    root: &'w Workflow,
+    func: FunctionId,
+    edges: &'w [Edge],
+    mut pc: usize,
+    stop: Option<usize>,
+    context: Context,
+    done: &mut HashSet<usize>,
+    steps: &mut Vec<Step<'w>>,
+) {
+    while pc < edges.len() && Some(pc) != stop && done.insert(pc) {
+        match &edges[pc] {
+            Edge::Node { task, input, result, next, .. } => {
+                // Transfers are done by the CWL runner itself
+                if let TaskDef::Compute(def) = root.table.task(*task) {
+                    let mut input: Vec<&DataName> = input.keys().collect();
+                    input.sort_by(|lhs, rhs| (lhs.is_data(), lhs.name()).cmp(&(rhs.is_data(), rhs.name())));
+                    let func_name: String = match func {
+                        FunctionId::Main => "main".into(),
+                        FunctionId::Func(id) => format!("f{id}"),
+                    };
+                    steps.push(Step {
+                        id:     cwl_id(format!("{}_{}_{}", def.function.name, func_name, pc)),
+                        def,
+                        input,
+                        result: result.as_ref(),
+                        func,
+                        context,
+                    });
+                }
+                pc = *next;
+            },
+            Edge::Linear { next, .. } | Edge::Join { next, .. } | Edge::Call { next, .. } => pc = *next,
+            Edge::Stop {} | Edge::Return { .. } => break,
+
+            Edge::Branch { true_next, false_next, merge } => {
+                let branch: Context = Context { conditional: true, ..context };
+                pass_edges(root, func, edges, *true_next, *merge, branch, done, steps);
+                if let Some(false_next) = false_next {
+                    pass_edges(root, func, edges, *false_next, *merge, branch, done, steps);
+                }
+                match merge {
+                    Some(merge) => pc = *merge,
+                    None => break,
+                }
+            },
+            Edge::Parallel { branches, merge } => {
+                for branch in branches {
+                    pass_edges(root, func, edges, *branch, Some(*merge), context, done, steps);
+                }
+                pc = *merge;
+            },
+            Edge::Loop { cond, body, next } => {
+                let body_context: Context = Context { conditional: true, repeated: true };
+                pass_edges(root, func, edges, *cond, Some(*body), Context { repeated: true, ..context }, done, steps);
+                pass_edges(root, func, edges, *body, Some(*cond), body_context, done, steps);
+                match next {
+                    Some(next) => pc = *next,
+                    None => break,
+                }
+            },
+        }
+    }
+}
+
+/// Writes the CommandLineTool that describes the task called by a step.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `step`: The [`Step`] to write the tool of.
+///
+/// # Errors
+/// This function errors if we failed to write to the given writer.
+fn pass_tool(writer: &mut impl Write, step: &Step) -> std::io::Result<()> {
+    let def: &ComputeTaskDef = step.def;
+    writeln!(writer, "    run:")?;
+    writeln!(writer, "      class: CommandLineTool")?;
+    writeln!(writer, "      label: {}", quote(format!("{}::{}", def.package, def.function.name)))?;
+    writeln!(writer, "      doc: {}", quote(format!("Function '{}' of package '{}' (version {})", def.function.name, def.package, def.version)))?;
+    writeln!(writer, "      baseCommand: [{}]", quote(&def.function.name))?;
+    writeln!(writer, "      hints:")?;
+    writeln!(writer, "        DockerRequirement:")?;
+    writeln!(writer, "          dockerPull: {}", quote(format!("{}:{}", def.package, def.version)))?;
+    if def.args_names.is_empty() {
+        writeln!(writer, "      inputs: {{}}")?;
+    } else {
+        writeln!(writer, "      inputs:")?;
+        for (name, data_type) in def.args_names.iter().zip(def.function.args.iter()) {
+            writeln!(writer, "        {}: {}", cwl_id(name), cwl_type(data_type))?;
+        }
+    }
+    if step.result.is_some() {
+        writeln!(writer, "      outputs:")?;
+        writeln!(writer, "        result: Directory")?;
+    } else {
+        writeln!(writer, "      outputs: {{}}")?;
+    }
+    Ok(())
+}
+
+/// Exports the given workflow as a CWL `Workflow`.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `root`: The [`Workflow`] to export.
+///
+/// # Errors
+/// This function errors if we failed to write to the given writer.
+fn pass_workflow(writer: &mut impl Write, root: &Workflow) -> std::io::Result<()> {
+    // Collect all task calls, main first and then the functions in order
+    let mut steps: Vec<Step> = vec![];
+    pass_edges(root, FunctionId::Main, &root.graph, 0, None, Context::default(), &mut HashSet::new(), &mut steps);
+    let mut funcs: Vec<&usize> = root.funcs.keys().collect();
+    funcs.sort();
+    for id in funcs {
+        pass_edges(root, FunctionId::Func(*id), &root.funcs[id], 0, None, Context::default(), &mut HashSet::new(), &mut steps);
+    }
+    let producers: HashMap<&String, &String> = steps.iter().filter_map(|step| step.result.map(|result| (result, &step.id))).collect();
+
+    // Resolve where every argument of every step comes from, which may introduce new workflow inputs
+    // (Dataset and result arguments can only be linked to the datasets and results of a call if there is exactly one of each)
+    let mut inputs: BTreeMap<String, (String, String)> = BTreeMap::new();
+    let mut sources: Vec<Vec<(String, String)>> = Vec::with_capacity(steps.len());
+    for step in &steps {
+        let def: &ComputeTaskDef = step.def;
+        let n_data: usize = def.function.args.iter().filter(|data_type| is_data(data_type)).count();
+        let mut step_sources: Vec<(String, String)> = Vec::with_capacity(def.args_names.len());
+        for (name, data_type) in def.args_names.iter().zip(def.function.args.iter()) {
+            let source: String = match step.input.as_slice() {
+                [data] if n_data == 1 && is_data(data_type) => match data {
+                    DataName::IntermediateResult(result) if producers.contains_key(result) => format!("{}/result", producers[result]),
+                    _ => {
+                        let id: String = cwl_id(data.name());
+                        let kind: &str = if data.is_data() { "Dataset" } else { "Result (of an earlier workflow)" };
+                        inputs.insert(id.clone(), ("Directory".into(), format!("{kind} '{}'", data.name())));
+                        id
+                    },
+                },
+                _ => {
+                    let id: String = cwl_id(format!("{}_{}", step.id, name));
+                    inputs.insert(id.clone(), (cwl_type(data_type), format!("Argument '{}' of step '{}'", name, step.id)));
+                    id
+                },
+            };
+            step_sources.push((cwl_id(name), source));
+        }
+        sources.push(step_sources);
+    }
+
+    // Write the header
+    writeln!(writer, "#!/usr/bin/env cwl-runner")?;
+    writeln!(writer, "cwlVersion: {CWL_VERSION}")?;
+    writeln!(writer, "class: Workflow")?;
+    writeln!(writer, "label: {}", quote(&root.id))?;
+    let lossy: bool = steps.iter().any(|step| step.context.conditional || step.context.repeated || step.func != FunctionId::Main);
+    writeln!(
+        writer,
+        "doc: {}",
+        quote(format!(
+            "Exported from Brane workflow '{}'.{}",
+            root.id,
+            if lossy { " Some of its control flow cannot be expressed in CWL; see the docs of the steps for details." } else { "" }
+        ))
+    )?;
+
+    // Write the inputs and outputs
+    if inputs.is_empty() {
+        writeln!(writer, "inputs: {{}}")?;
+    } else {
+        writeln!(writer, "inputs:")?;
+        for (id, (data_type, doc)) in &inputs {
+            writeln!(writer, "  {id}:")?;
+            writeln!(writer, "    type: {data_type}")?;
+            writeln!(writer, "    doc: {}", quote(doc))?;
+        }
+    }
+    let outputs: Vec<(&String, &String)> = steps.iter().filter_map(|step| step.result.map(|result| (result, &step.id))).collect();
+    if outputs.is_empty() {
+        writeln!(writer, "outputs: {{}}")?;
+    } else {
+        writeln!(writer, "outputs:")?;
+        for (result, step) in outputs {
+            writeln!(writer, "  {}:", cwl_id(result))?;
+            writeln!(writer, "    type: Directory")?;
+            writeln!(writer, "    outputSource: {step}/result")?;
+        }
+    }
+
+    // Write the steps
+    if steps.is_empty() {
+        return writeln!(writer, "steps: {{}}");
+    }
+    writeln!(writer, "steps:")?;
+    for (step, step_sources) in steps.iter().zip(sources) {
+        writeln!(writer, "  {}:", step.id)?;
+        let mut notes: Vec<String> = vec![];
+        if let FunctionId::Func(id) = step.func {
+            notes.push(format!(
+                "Part of function '{}' in the original workflow, which may be called any number of times; it runs once here.",
+                root.table.func(FunctionId::Func(id)).name
+            ));
+        }
+        if step.context.repeated {
+            notes.push("Runs in a loop in the original workflow; it runs once here.".into());
+        } else if step.context.conditional {
+            notes.push("Only runs under a condition in the original workflow; it always runs here.".into());
+        }
+        if !notes.is_empty() {
+            writeln!(writer, "    doc: {}", quote(notes.join(" ")))?;
+        }
+        pass_tool(writer, step)?;
+        if step_sources.is_empty() {
+            writeln!(writer, "    in: {{}}")?;
+        } else {
+            writeln!(writer, "    in:")?;
+            for (name, source) in step_sources {
+                writeln!(writer, "      {name}: {source}")?;
+            }
+        }
+        writeln!(writer, "    out: [{}]", if step.result.is_some() { "result" } else { "" })?;
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Starts exporting the root of the AST (i.e., a Workflow) as a CWL `Workflow`, in YAML.
+///
+/// Every task call becomes a step with an inline `CommandLineTool` that describes the task's interface and container image. Transfers are left
+/// to the CWL runner.
+///
+/// # Arguments
+/// - `root`: The root node of the tree on which this compiler pass will be done.
+/// - `writer`: The `Write`r to write to.
+///
+/// # Errors
+/// This pass may error if we failed to write to the given writer.
+pub fn do_traversal(root: &Workflow, mut writer: impl Write) -> Result<(), Vec<Error>> {
+    match pass_workflow(&mut writer, root) {
+        Ok(()) => Ok(()),
+        Err(err) => Err(vec![Error::WriteError { err }]),
+    }
+}
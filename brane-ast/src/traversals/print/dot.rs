@@ -0,0 +1,228 @@
+//  DOT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:19:48
+//  Last edited:
+//    15 Oct 2026, 21:19:48
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Renders the `brane-ast` AST as a [Graphviz](https://graphviz.org)
+//!   graph of its task calls and the datasets transferred to them,
+//!   optionally coloured by the verdict of a checker.
+//
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use specifications::data::DataName;
+
+use crate::ast::{Edge, TaskDef, Workflow};
+pub use crate::errors::AstError as Error;
+use crate::func_id::FunctionId;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Quotes the given string as a DOT identifier.
+///
+/// # Arguments
+/// - `s`: The string to quote.
+///
+/// # Returns
+/// The string in double quotes, with any quotes, backslashes or newlines in it escaped.
+fn quote(s: impl AsRef<str>) -> String { format!("\"{}\"", s.as_ref().replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")) }
+
+/// Returns the DOT identifier of the node representing a task call.
+///
+/// # Arguments
+/// - `func`: The function in which the call occurs.
+/// - `edge`: The index of the call in that function.
+///
+/// # Returns
+/// The (unquoted) identifier of the node.
+#[inline]
+fn task_id(func: FunctionId, edge: usize) -> String { format!("task-{func}-{edge}") }
+
+/// Returns the DOT identifier of the node representing a dataset or result.
+///
+/// # Arguments
+/// - `name`: The [`DataName`] of the dataset or result.
+///
+/// # Returns
+/// The (unquoted) identifier of the node.
+#[inline]
+fn data_id(name: &DataName) -> String {
+    match name {
+        DataName::Data(name) => format!("data-{name}"),
+        DataName::IntermediateResult(name) => format!("result-{name}"),
+    }
+}
+
+/// Returns the DOT attributes that color something with the given verdict, if any.
+///
+/// # Arguments
+/// - `verdict`: The [`Verdict`] to color with.
+/// - `attr`: The attribute to set (e.g., `fillcolor` or `color`).
+///
+/// # Returns
+/// A string with the attribute (preceded by a comma), or an empty string if there is no verdict.
+#[inline]
+fn color(verdict: Option<&Verdict>, attr: &str) -> String {
+    match verdict {
+        Some(verdict) => format!(", {attr}={}", quote(verdict.color())),
+        None => String::new(),
+    }
+}
+
+
+
+
+
+/***** TRAVERSAL FUNCTIONS *****/
+/// Renders the task calls in a list of edges, together with the datasets they take as input and the results they produce.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `root`: The [`Workflow`] to which the edges belong.
+/// - `func`: The [`FunctionId`] of the function that the edges make up.
+/// - `edges`: The edges to render.
+/// - `annotations`: The [`Annotations`] that color the tasks and transfers.
+/// - `data`: The datasets and results that we have seen so far, and which are rendered as nodes later.
+///
+/// # Errors
+/// This function errors if we failed to write to the given writer.
+fn pass_edges(
+    writer: &mut impl Write,
+    root: &Workflow,
+    func: FunctionId,
+    edges: &[Edge],
+    annotations: &Annotations,
+    data: &mut HashSet<DataName>,
+) -> std::io::Result<()> {
+    for (i, edge) in edges.iter().enumerate() {
+        let (task, at, input, result) = if let Edge::Node { task, at, input, result, .. } = edge { (task, at, input, result) } else { continue };
+        let id: String = task_id(func, i);
+
+        // Write the task itself
+        let name: String = match root.table.task(*task) {
+            TaskDef::Compute(def) => format!("{}::{}", def.package, def.function.name),
+            TaskDef::Transfer => "__builtin::transfer".into(),
+        };
+        let label: String = format!("{}\n{}:{}{}", name, func, i, if let Some(at) = at { format!("\n@ {at}") } else { String::new() });
+        writeln!(writer, "    {} [label={}, shape=box{}];", quote(&id), quote(label), color(annotations.tasks.get(&(func, i)), "fillcolor"))?;
+
+        // Write the transfers to it, in a deterministic order
+        let mut inputs: Vec<&DataName> = input.keys().collect();
+        inputs.sort_by(|lhs, rhs| data_id(lhs).cmp(&data_id(rhs)));
+        for name in inputs {
+            let verdict: Option<&Verdict> = annotations.transfers.get(&(func, i, name.clone()));
+            writeln!(writer, "    {} -> {}{};", quote(data_id(name)), quote(&id), match verdict {
+                Some(_) => format!(" [penwidth=2{}]", color(verdict, "color")),
+                None => String::new(),
+            })?;
+            data.insert(name.clone());
+        }
+
+        // Write the result it produces
+        if let Some(result) = result {
+            let name: DataName = DataName::IntermediateResult(result.clone());
+            writeln!(writer, "    {} -> {};", quote(&id), quote(data_id(&name)))?;
+            data.insert(name);
+        }
+    }
+    Ok(())
+}
+
+/// Renders the given workflow as a Graphviz `digraph`.
+///
+/// # Arguments
+/// - `writer`: The `Write`r to write to.
+/// - `root`: The [`Workflow`] to render.
+/// - `annotations`: The [`Annotations`] that color the tasks and transfers.
+///
+/// # Errors
+/// This function errors if we failed to write to the given writer.
+fn pass_workflow(writer: &mut impl Write, root: &Workflow, annotations: &Annotations) -> std::io::Result<()> {
+    writeln!(writer, "digraph {} {{", quote(&root.id))?;
+    writeln!(writer, "    rankdir=LR;")?;
+    writeln!(writer, "    node [style=filled, fillcolor=white];")?;
+
+    // Write all task calls, main first and then the functions in order
+    let mut data: HashSet<DataName> = HashSet::new();
+    pass_edges(writer, root, FunctionId::Main, &root.graph, annotations, &mut data)?;
+    let mut funcs: Vec<&usize> = root.funcs.keys().collect();
+    funcs.sort();
+    for id in funcs {
+        pass_edges(writer, root, FunctionId::Func(*id), &root.funcs[id], annotations, &mut data)?;
+    }
+
+    // Write the datasets and results we've seen
+    let mut data: Vec<DataName> = data.into_iter().collect();
+    data.sort_by(|lhs, rhs| data_id(lhs).cmp(&data_id(rhs)));
+    for name in data {
+        let shape: &str = if name.is_data() { "cylinder" } else { "note" };
+        writeln!(writer, "    {} [label={}, shape={}];", quote(data_id(&name)), quote(name.name()), shape)?;
+    }
+    writeln!(writer, "}}")
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// The verdict of a checker on a task call or transfer, which determines its color.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Verdict {
+    /// The checker allows it (green).
+    Allow,
+    /// The checker denies it (red).
+    Deny,
+    /// The checker allows it, but something needs a closer look (e.g., a violation occurred while reasoning about it) (orange).
+    NeedsReview,
+}
+impl Verdict {
+    /// Returns the Graphviz color of this verdict.
+    #[inline]
+    pub fn color(&self) -> &'static str {
+        match self {
+            Self::Allow => "palegreen",
+            Self::Deny => "lightcoral",
+            Self::NeedsReview => "orange",
+        }
+    }
+}
+
+/// Annotates the task calls and transfers in a workflow with the [`Verdict`]s of a checker.
+///
+/// Task calls and transfers without a verdict are rendered without color.
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    /// The verdicts on task calls, by the function and index of the edge that calls it.
+    pub tasks:     HashMap<(FunctionId, usize), Verdict>,
+    /// The verdicts on transferring a dataset or result as input to a task call.
+    pub transfers: HashMap<(FunctionId, usize, DataName), Verdict>,
+}
+
+
+
+/// Starts rendering the root of the AST (i.e., a Workflow) as a Graphviz `digraph`.
+///
+/// Task calls are rendered as boxes, and datasets and results as cylinders with edges to the tasks that take them as input and from the tasks
+/// that produce them.
+///
+/// # Arguments
+/// - `root`: The root node of the tree on which this compiler pass will be done.
+/// - `annotations`: The [`Annotations`] that color the tasks and transfers.
+/// - `writer`: The `Write`r to write to.
+///
+/// # Errors
+/// This pass may error if we failed to write to the given writer.
+pub fn do_traversal(root: &Workflow, annotations: &Annotations, mut writer: impl Write) -> Result<(), Vec<Error>> {
+    match pass_workflow(&mut writer, root, annotations) {
+        Ok(()) => Ok(()),
+        Err(err) => Err(vec![Error::WriteError { err }]),
+    }
+}
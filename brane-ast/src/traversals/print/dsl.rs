@@ -4,7 +4,7 @@
 //  Created:
 //    18 Aug 2022, 13:46:22
 //  Last edited:
-//    12 Dec 2023, 14:57:30
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -154,6 +154,10 @@ pub fn pass_stmt(writer: &mut impl Write, stmt: &Stmt, indent: usize) -> std::io
             // Do newline
             writeln!(writer)?;
         },
+        ImportSource { path, range: _ } => {
+            // Print as an import statement of a file
+            writeln!(writer, "{}import {:?};", indent!(indent), path)?;
+        },
         FuncDef { ident, params, code, st_entry: _, attrs, range: _ } => {
             // Print the attributes
             for attr in attrs {
@@ -379,6 +383,24 @@ pub fn pass_attr(writer: &mut impl Write, attr: &Attribute, is_inner: bool, inde
             }
             write!(writer, ")")?;
         },
+        Attribute::Options { key, options, range: _ } => {
+            pass_identifier(writer, key)?;
+            if !options.is_empty() {
+                write!(writer, "(")?;
+                let mut first: bool = true;
+                for (name, value) in options {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(writer, ", ")?;
+                    }
+                    pass_identifier(writer, name)?;
+                    write!(writer, " = ")?;
+                    pass_literal(writer, value)?;
+                }
+                write!(writer, ")")?;
+            }
+        },
     }
     // Print the suffixing tokens
     writeln!(writer, "]")?;
@@ -475,7 +497,7 @@ pub fn pass_expr(writer: &mut impl Write, expr: &Expr, indent: usize) -> std::io
             write!(writer, ")")?;
         },
 
-        Call { expr, args, st_entry: _, locations, input: _, result: _, metadata, range: _ } => {
+        Call { expr, args, st_entry: _, locations, input: _, result: _, metadata, cache, range: _ } => {
             // Print the identifying expression
             pass_expr(writer, expr, indent)?;
             // Print the arguments
@@ -512,6 +534,10 @@ pub fn pass_expr(writer: &mut impl Write, expr: &Expr, indent: usize) -> std::io
                         .join(" ")
                 )?;
             }
+            // Print the caching period
+            if let Some(ttl) = cache {
+                write!(writer, " <cache {ttl}s>")?;
+            }
         },
         Array { values, .. } => {
             // Print the values wrapped in '[]'
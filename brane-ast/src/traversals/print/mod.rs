@@ -4,17 +4,20 @@
 //  Created:
 //    18 Aug 2022, 13:45:22
 //  Last edited:
-//    05 Sep 2022, 11:09:41
+//    15 Oct 2026, 22:09:09
 //  Auto updated?
 //    Yes
 //
 //  Description:
 //!   Prints either the `brane-dsl` AST or the `brane-ast` AST in
-//!   BraneScript-like syntax.
+//!   BraneScript-like syntax, or the latter as a Graphviz graph or CWL
+//!   workflow.
 //
 
 // Declare the modules
 pub mod ast;
 pub mod ast_unresolved;
+pub mod cwl;
+pub mod dot;
 pub mod dsl;
 pub mod symbol_tables;
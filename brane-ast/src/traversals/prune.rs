@@ -4,7 +4,7 @@
 //  Created:
 //    31 Aug 2022, 18:00:09
 //  Last edited:
-//    13 Dec 2023, 08:21:57
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -266,7 +266,7 @@ fn pass_stmt(stmt: Stmt, attr_stack: &mut Vec<Vec<Attribute>>, errors: &mut Vec<
 
         // The rest neither recurses nor defines
         Import { .. } | LetAssign { .. } | Assign { .. } | Expr { .. } | Empty {} => (vec![stmt], false),
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in prune traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in prune traversal", stmt.variant()),
     }
 }
 
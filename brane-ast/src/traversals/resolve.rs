@@ -4,7 +4,7 @@
 //  Created:
 //    18 Aug 2022, 15:24:54
 //  Last edited:
-//    12 Dec 2023, 17:13:11
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -22,11 +22,12 @@ use brane_dsl::data_type::{ClassSignature, FunctionSignature};
 use brane_dsl::spec::MergeStrategy;
 use brane_dsl::symbol_table::{ClassEntry, FunctionEntry, SymbolTableEntry, VarEntry};
 use brane_dsl::{DataType, SymbolTable, TextRange};
+use brane_shr::suggest::suggest;
 use enum_debug::EnumDebug as _;
 use log::trace;
 use specifications::data::DataIndex;
 use specifications::package::{PackageIndex, PackageInfo};
-use specifications::version::Version;
+use specifications::version::VersionRange;
 
 use crate::errors::AstError;
 pub use crate::errors::ResolveError as Error;
@@ -215,8 +216,8 @@ fn pass_stmt(
         },
 
         Import { name, version, st_funcs, st_classes, attrs: _, range } => {
-            // First: parse the version
-            let semver: Version = match version.as_version() {
+            // First: parse the version (range)
+            let semver: VersionRange = match version.as_version_range() {
                 Ok(version) => version,
                 Err(err) => {
                     errors.push(Error::VersionParseError { err, range: version.range().clone() });
@@ -224,11 +225,12 @@ fn pass_stmt(
                 },
             };
 
-            // Attempt to resolve this (name, version) pair in the package index.
-            let info: &PackageInfo = match package_index.get(&name.value, if !semver.is_latest() { Some(&semver) } else { None }) {
+            // Attempt to resolve this package to the highest version in range in the package index.
+            let info: &PackageInfo = match package_index.get_range(&name.value, &semver) {
                 Some(info) => info,
                 None => {
-                    errors.push(Error::UnknownPackageError { name: name.value.clone(), version: semver, range: range.clone() });
+                    let suggestions: Vec<String> = suggest(&name.value, package_index.latest.keys().map(String::as_str));
+                    errors.push(Error::UnknownPackageError { name: name.value.clone(), version: semver, suggestions, range: range.clone() });
                     return;
                 },
             };
@@ -545,7 +547,7 @@ fn pass_stmt(
 
         // We ignore the rest
         Empty {} => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in resolve traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in resolve traversal", stmt.variant()),
     }
 
     // We're done here
@@ -575,7 +577,7 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
             pass_expr(state, data_index, expr, symbol_table, errors);
         },
 
-        Call { expr, args, st_entry: _, locations: _, input: _, result: _, metadata: _, range: _ } => {
+        Call { expr, args, st_entry: _, locations: _, input: _, result: _, metadata: _, cache: _, range: _ } => {
             // Simply recurse the called expression
             pass_expr(state, data_index, expr, symbol_table, errors);
             // If it's an identifier, set its entry to which function it is referring
@@ -593,10 +595,15 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
                 }
 
                 // Assertion that is at the incorrect place but jeeeeeez this sucks to place anywhere; if this is a commit, is the name a literal string?
-                if name.value == BuiltinFunctions::CommitResult.name() {
+                if name.value == BuiltinFunctions::CommitResult.name() || name.value == BuiltinFunctions::KeepResult.name() {
                     if let Some(first_arg) = args.iter().next() {
                         if !matches!(&**first_arg, Expr::Literal { literal: brane_dsl::ast::Literal::String { .. } }) {
-                            errors.push(Error::CommitResultIncorrectExpr { range: first_arg.range().clone() });
+                            let range: TextRange = first_arg.range().clone();
+                            errors.push(if name.value == BuiltinFunctions::CommitResult.name() {
+                                Error::CommitResultIncorrectExpr { range }
+                            } else {
+                                Error::KeepResultIncorrectExpr { range }
+                            });
                         }
                     }
                 }
@@ -795,7 +802,8 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
                 //     }
                 // };
                 if data_index.get(sname).is_none() {
-                    errors.push(Error::UnknownDataError { name: sname.into(), range: name.range().clone() });
+                    let suggestions: Vec<String> = suggest(sname, data_index.names());
+                    errors.push(Error::UnknownDataError { name: sname.into(), suggestions, range: name.range().clone() });
                 }
 
                 // With the dataset resolved, we rest easy
@@ -4,7 +4,7 @@
 //  Created:
 //    19 Aug 2022, 16:34:16
 //  Last edited:
-//    08 Dec 2023, 11:09:07
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -186,7 +186,9 @@ fn insert_casts_at_returns(s: &mut Stmt, target: &DataType) {
 
         // We ignore the rest
         Import { .. } | FuncDef { .. } | ClassDef { .. } | LetAssign { .. } | Assign { .. } | Expr { .. } | Empty {} => {},
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in typing traversal's 'insert_casts_at_returns()'", s.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => {
+            panic!("Encountered {:?} in typing traversal's 'insert_casts_at_returns()'", s.variant())
+        },
     }
 }
 
@@ -539,7 +541,7 @@ fn pass_stmt(
 
         // We ignore the rest
         Empty { .. } => None,
-        Attribute(_) | AttributeInner(_) => panic!("Encountered {:?} in typing traversal", stmt.variant()),
+        Attribute(_) | AttributeInner(_) | ImportSource { .. } => panic!("Encountered {:?} in typing traversal", stmt.variant()),
     };
 
     // We're done here
@@ -4,7 +4,7 @@
 //  Created:
 //    05 Sep 2022, 17:36:21
 //  Last edited:
-//    06 Feb 2024, 11:36:49
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -15,13 +15,16 @@
 
 use std::cell::Ref;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use log::debug;
+use specifications::planning::Constraints;
 
 use crate::ast::{Edge, SymTable, Workflow};
 use crate::ast_unresolved::UnresolvedWorkflow;
 use crate::edgebuffer::{EdgeBuffer, EdgeBufferNode, EdgeBufferNodeLink, EdgeBufferNodePtr};
 use crate::errors::AstError;
+use crate::locations::Locations;
 use crate::state::CompileState;
 
 
@@ -146,6 +149,24 @@ fn update_link(edge: &mut Edge, index: usize) {
     }
 }
 
+/// Adds the location restrictions of the task calls in the given edges (i.e., from `On`-structs and location annotations) to the given
+/// placement constraints.
+///
+/// # Arguments
+/// - `edges`: The (resolved) edges to collect the restrictions of.
+/// - `func`: The index of the function that these edges belong to, or [`None`] if they belong to `<main>`.
+/// - `constraints`: The Constraints to add the restrictions to.
+///
+/// # Returns
+/// Nothing, but does add the restrictions to `constraints`.
+fn collect_constraints(edges: &[Edge], func: Option<usize>, constraints: &mut Constraints) {
+    for (i, edge) in edges.iter().enumerate() {
+        if let Edge::Node { locs: Locations::Restricted(locs), .. } = edge {
+            constraints.task_mut(Constraints::task_key(func, i)).allowed = Some(locs.iter().cloned().collect());
+        }
+    }
+}
+
 
 
 
@@ -183,7 +204,7 @@ fn pass_edges(edges: EdgeBuffer, target: &mut Vec<Edge>, map: &mut HashMap<EdgeB
 
             use Edge::*;
             match &e.edge {
-                Node { task, locs, at: _, input, result, metadata, next: _ } => {
+                Node { task, locs, at: _, input, result, metadata, cache, next: _ } => {
                     // The connection must be linear
                     let next: Option<EdgeBufferNodePtr> = match &e.next {
                         EdgeBufferNodeLink::Linear(next) => Some(next.clone()),
@@ -210,6 +231,7 @@ fn pass_edges(edges: EdgeBuffer, target: &mut Vec<Edge>, map: &mut HashMap<EdgeB
                         input: input.clone(),
                         result: result.clone(),
                         metadata: metadata.clone(),
+                        cache: *cache,
                         next: next_idx,
                     });
                     map.insert(edges_start.clone(), index);
@@ -567,9 +589,17 @@ pub fn do_traversal(state: &mut CompileState, mut root: UnresolvedWorkflow) -> R
         }
     }
 
+    // Express the location restrictions as placement constraints too, so the planner and checkers can reason about them
+    let mut constraints: Constraints = Constraints::default();
+    collect_constraints(&graph, None, &mut constraints);
+    for (i, edges) in &funcs {
+        collect_constraints(edges, Some(*i), &mut constraints);
+    }
+
     // Done; create the workflow and return it
     // Note: don't forget to transfer metadata before doing so
     let mut wf: Workflow = Workflow::with_random_id(table, graph, funcs);
     wf.metadata = root.metadata;
+    wf.constraints = Arc::new(constraints);
     Ok(wf)
 }
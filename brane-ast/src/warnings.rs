@@ -4,7 +4,7 @@
 //  Created:
 //    05 Sep 2022, 16:08:42
 //  Last edited:
-//    12 Dec 2023, 14:56:22
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -293,7 +293,8 @@ impl Display for TypeWarning {
 
             ReturningIntermediateResult { .. } => write!(
                 f,
-                "Returning an {} will not let you see the result; consider committing using the builtin `commit_result()` function",
+                "Returning an {} will not let you see the result; consider committing it using the builtin `commit_result()` or `keep_result()` \
+                 functions",
                 BuiltinClasses::IntermediateResult.name()
             ),
         }
@@ -315,6 +316,13 @@ pub enum MetadataWarning {
     TagWithoutDot { raw: String, range: TextRange },
     /// A piece of metadata was applied (directly) to a statement that did not take it.
     UselessTag { range: TextRange },
+
+    /// A `cache`-attribute was given without a lifetime.
+    MissingCacheTtl { range: TextRange },
+    /// The lifetime of a `cache`-attribute was not a valid duration.
+    IllegalCacheTtl { range: TextRange },
+    /// A `cache`-attribute was given an option it does not know.
+    UnknownCacheOption { name: String, range: TextRange },
 }
 impl MetadataWarning {
     /// Prints the warning in a pretty way to stderr.
@@ -345,6 +353,10 @@ impl MetadataWarning {
             NonStringTag { range } => prettywrite_warn(writer, file, source, self, range),
             TagWithoutDot { range, .. } => prettywrite_warn(writer, file, source, self, range),
             UselessTag { range } => prettywrite_warn(writer, file, source, self, range),
+
+            MissingCacheTtl { range } => prettywrite_warn(writer, file, source, self, range),
+            IllegalCacheTtl { range } => prettywrite_warn(writer, file, source, self, range),
+            UnknownCacheOption { range, .. } => prettywrite_warn(writer, file, source, self, range),
         }
     }
 }
@@ -356,6 +368,12 @@ impl Display for MetadataWarning {
             NonStringTag { .. } => write!(f, "Tags must be string literals"),
             TagWithoutDot { raw, .. } => write!(f, "Missing dot in metadata '{raw}' to separate owner and tag"),
             UselessTag { .. } => write!(f, "Applying tag here has no effect (only has effect on entire workflow or external function calls)"),
+
+            MissingCacheTtl { .. } => write!(f, "Missing 'ttl' option in cache attribute (e.g., '#[cache(ttl = \"7d\")]'); ignoring it"),
+            IllegalCacheTtl { .. } => {
+                write!(f, "Cache lifetime must be a string such as \"30s\", \"15m\", \"12h\" or \"7d\", or a positive number of seconds; ignoring it")
+            },
+            UnknownCacheOption { name, .. } => write!(f, "Unknown cache option '{name}' (only 'ttl' is supported)"),
         }
     }
 }
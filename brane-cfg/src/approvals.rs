@@ -0,0 +1,469 @@
+//  APPROVALS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:29:41
+//  Last edited:
+//    16 Oct 2026, 02:42:53
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the downloads of intermediate results that `brane-reg` parks
+//!   until a data steward approves them (see
+//!   [`EgressApprovalConfig`](crate::node::EgressApprovalConfig)).
+//!
+//!   Every parked download is a separate `<ID>.yml` file in the approvals
+//!   directory. The registry creates it when the download is first
+//!   requested, `branectl approvals` decides on it and the registry reads
+//!   the decision when the download is requested again.
+//!
+//!   Which results need approval is decided based on their
+//!   [`ResultProvenance`], which `brane-job` records when it produces them.
+//
+
+use std::fs::{self, DirEntry, File, OpenOptions, ReadDir};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use enum_debug::EnumDebug;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+pub use crate::errors::ApprovalsError as Error;
+use crate::info::{Info as _, YamlInfo};
+
+
+/***** CONSTANTS *****/
+/// Name of the (hidden) directory in the results folder where the [`ResultProvenance`] of every result is recorded.
+pub const PROVENANCE_DIR: &str = ".provenance";
+
+
+
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Computes the identifier of the download of an intermediate result.
+///
+/// The identifier only depends on how the result is computed and by whom it is downloaded on whose behalf. Unlike the ID of a workflow or the
+/// names of its results, these are the same every time a workflow is compiled, such that an approved download stays approved when the user
+/// runs the workflow again.
+///
+/// # Arguments
+/// - `fingerprint`: The fingerprint of the intermediate result, as recorded in its [`ResultProvenance`].
+/// - `requester`: The name of the domain (or user) that downloads it.
+/// - `user`: The user on whose behalf the workflow runs, if known.
+///
+/// # Returns
+/// A short hexadecimal identifier.
+pub fn approval_id(fingerprint: &str, requester: &str, user: Option<&str>) -> String {
+    let hash = Sha256::digest(format!("{fingerprint}\n{requester}\n{}", user.unwrap_or("")).as_bytes());
+    hex::encode(&hash[..8])
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Describes how an intermediate result was computed, as recorded by the worker that produced it.
+///
+/// The registry relies on this record, rather than on the workflow of whoever downloads the result, to find out what the result was computed
+/// from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResultProvenance {
+    /// The name of the intermediate result.
+    pub result: String,
+    /// The ID of the workflow that produced it.
+    pub workflow: String,
+    /// The user on whose behalf that workflow ran, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// The task that produced it, as `package[version]::function`.
+    pub task: String,
+    /// How the result is computed (see `Workflow::lineage()` in `brane-ast`).
+    pub lineage: String,
+    /// Identifies how the result is computed including the arguments of the calls involved (see [`ResultProvenance::fingerprint()`]).
+    pub fingerprint: String,
+    /// The sensitivity tags of the datasets of this domain that the result was computed from, sorted.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The moment the result was produced.
+    pub recorded: DateTime<Utc>,
+}
+impl<'de> YamlInfo<'de> for ResultProvenance {}
+impl ResultProvenance {
+    /// Computes the fingerprint of an intermediate result.
+    ///
+    /// Unlike its lineage, the fingerprint also covers the arguments of the call that produced the result and the fingerprints of the
+    /// intermediate results it was computed from. As such, calls of the same task with other arguments do not share approvals.
+    ///
+    /// # Arguments
+    /// - `lineage`: The lineage of the result (see `Workflow::lineage()` in `brane-ast`).
+    /// - `args`: The names and (serialized) values of the arguments of the call that produced the result, in any order.
+    /// - `inputs`: The fingerprints of the intermediate results that the call read, in any order. Results that were produced elsewhere are
+    ///   given by their lineage instead.
+    ///
+    /// # Returns
+    /// A hexadecimal fingerprint.
+    pub fn fingerprint<'s>(lineage: &str, args: impl IntoIterator<Item = (&'s str, &'s str)>, inputs: impl IntoIterator<Item = &'s str>) -> String {
+        let mut args: Vec<(&str, &str)> = args.into_iter().collect();
+        args.sort();
+        let mut inputs: Vec<&str> = inputs.into_iter().collect();
+        inputs.sort();
+
+        let mut hasher: Sha256 = Sha256::new();
+        hasher.update(lineage.as_bytes());
+        for (name, value) in args {
+            hasher.update(format!("\0{name}={value}").as_bytes());
+        }
+        for input in inputs {
+            hasher.update(format!("\0<{input}").as_bytes());
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Returns the path of the record of the given result.
+    ///
+    /// # Arguments
+    /// - `results`: The results directory of the worker.
+    /// - `result`: The name of the intermediate result.
+    ///
+    /// # Returns
+    /// The path of its `<RESULT>.yml` file in the [`PROVENANCE_DIR`].
+    #[inline]
+    pub fn path(results: impl AsRef<Path>, result: impl AsRef<str>) -> PathBuf {
+        results.as_ref().join(PROVENANCE_DIR).join(format!("{}.yml", result.as_ref()))
+    }
+
+    /// Loads the record of the given result.
+    ///
+    /// # Arguments
+    /// - `results`: The results directory of the worker.
+    /// - `result`: The name of the intermediate result.
+    ///
+    /// # Returns
+    /// The [`ResultProvenance`], or [`None`] if none was recorded for the result.
+    ///
+    /// # Errors
+    /// This function errors if the record exists but we failed to read it.
+    pub fn load(results: impl AsRef<Path>, result: impl AsRef<str>) -> Result<Option<Self>, Error> {
+        let path: PathBuf = Self::path(results, result);
+        if !path.exists() {
+            return Ok(None);
+        }
+        match Self::from_path(&path) {
+            Ok(provenance) => Ok(Some(provenance)),
+            Err(err) => Err(Error::ProvenanceLoad { path, err }),
+        }
+    }
+
+    /// Writes the record to the [`PROVENANCE_DIR`], replacing any earlier record of the same result.
+    ///
+    /// # Arguments
+    /// - `results`: The results directory of the worker.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the directory or to write the file.
+    pub fn save(&self, results: impl AsRef<Path>) -> Result<(), Error> {
+        let dir: PathBuf = results.as_ref().join(PROVENANCE_DIR);
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreate { path: dir, err });
+        }
+        let path: PathBuf = Self::path(results, &self.result);
+        match self.to_path(&path) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::ProvenanceWrite { path, err }),
+        }
+    }
+}
+
+
+
+/// Defines the decision on a parked download.
+#[derive(Clone, Copy, Debug, Deserialize, EnumDebug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    /// No data steward has decided on it yet.
+    Pending,
+    /// A data steward allowed the download.
+    Approved,
+    /// A data steward refused the download.
+    Denied,
+}
+
+/// Defines a single download of an intermediate result that awaits (or awaited) approval.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApprovalRequest {
+    /// The identifier of the request (see [`approval_id()`]).
+    pub id: String,
+    /// The name of the intermediate result to download (as it was named when the download was first requested).
+    pub result: String,
+    /// How the intermediate result is computed, as recorded in its [`ResultProvenance`] (or `result:<NAME>` if it has none).
+    pub lineage: String,
+    /// The ID of the workflow in whose context it was first downloaded.
+    pub workflow: String,
+    /// The user on whose behalf the workflow runs, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// The domain (or user) that downloads the result.
+    pub requester: String,
+    /// The task that needs the result, or [`None`] if it is downloaded as the result of the workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// The sensitivity tags of the datasets that the result was computed from, as recorded in its [`ResultProvenance`].
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The sensitivity level derived from the `tags`.
+    pub sensitivity: u32,
+    /// The moment the download was first requested.
+    pub requested: DateTime<Utc>,
+
+    /// The decision on the download.
+    pub status:  ApprovalStatus,
+    /// The moment the decision was made, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decided: Option<DateTime<Utc>>,
+    /// Why the download was approved or denied, if the data steward said so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason:  Option<String>,
+}
+impl<'de> YamlInfo<'de> for ApprovalRequest {}
+impl ApprovalRequest {
+    /// Returns the path of the file of the request with the given ID.
+    ///
+    /// # Arguments
+    /// - `dir`: The approvals directory.
+    /// - `id`: The identifier of the request.
+    ///
+    /// # Returns
+    /// The path of its `<ID>.yml` file.
+    #[inline]
+    pub fn path(dir: impl AsRef<Path>, id: impl AsRef<str>) -> PathBuf { dir.as_ref().join(format!("{}.yml", id.as_ref())) }
+
+    /// Loads the request with the given ID.
+    ///
+    /// # Arguments
+    /// - `dir`: The approvals directory.
+    /// - `id`: The identifier of the request.
+    ///
+    /// # Returns
+    /// The [`ApprovalRequest`], or [`None`] if there is none with that ID.
+    ///
+    /// # Errors
+    /// This function errors if the ID is not a valid identifier or if we failed to read the request.
+    pub fn load(dir: impl AsRef<Path>, id: impl AsRef<str>) -> Result<Option<Self>, Error> {
+        let id: &str = id.as_ref();
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::IllegalId { id: id.into() });
+        }
+        let path: PathBuf = Self::path(dir, id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        match Self::from_path(&path) {
+            Ok(request) => Ok(Some(request)),
+            Err(err) => Err(Error::RequestLoad { path, err }),
+        }
+    }
+
+    /// Finds the decision on this download, parking it as a new request if it was never requested before.
+    ///
+    /// # Arguments
+    /// - `dir`: The approvals directory.
+    ///
+    /// # Returns
+    /// The request as it is known in the approvals directory. Its status tells whether a data steward decided on it yet.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read an earlier request or to write this one.
+    pub fn submit(self, dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir: &Path = dir.as_ref();
+        if !self.id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::IllegalId { id: self.id });
+        }
+
+        // Only create the file if it doesn't exist yet, such that we never overwrite a decision made in the meantime
+        let path: PathBuf = Self::path(dir, &self.id);
+        let file: File = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => match Self::from_path(&path) {
+                Ok(request) => return Ok(request),
+                Err(err) => return Err(Error::RequestLoad { path, err }),
+            },
+            Err(err) => return Err(Error::RequestCreate { path, err }),
+        };
+        match self.to_writer(file, true) {
+            Ok(_) => Ok(self),
+            Err(err) => Err(Error::RequestWrite { path, err }),
+        }
+    }
+
+    /// Loads all requests in the approvals directory.
+    ///
+    /// # Arguments
+    /// - `dir`: The approvals directory.
+    ///
+    /// # Returns
+    /// The [`ApprovalRequest`]s, oldest first. Files that are not valid requests are skipped (with a warning).
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the directory.
+    pub fn load_all(dir: impl AsRef<Path>) -> Result<Vec<Self>, Error> {
+        let dir: &Path = dir.as_ref();
+        let entries: ReadDir = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => return Err(Error::DirRead { path: dir.into(), err }),
+        };
+        let mut requests: Vec<Self> = vec![];
+        for entry in entries {
+            let entry: DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Err(Error::DirRead { path: dir.into(), err }),
+            };
+            let path: PathBuf = entry.path();
+            if path.extension().map(|ext| ext != "yml").unwrap_or(true) {
+                continue;
+            }
+            match Self::from_path(&path) {
+                Ok(request) => requests.push(request),
+                Err(err) => warn!("Skipping approval request '{}' that we failed to load: {}", path.display(), err),
+            }
+        }
+        requests.sort_by(|lhs, rhs| lhs.requested.cmp(&rhs.requested));
+        Ok(requests)
+    }
+
+    /// Writes the request to its file in the approvals directory.
+    ///
+    /// # Arguments
+    /// - `dir`: The approvals directory.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write the file.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), Error> {
+        let path: PathBuf = Self::path(dir, &self.id);
+        match self.to_path(&path) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::RequestWrite { path, err }),
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::node::EgressApprovalConfig;
+
+    /// Creates a pending request with the given ID.
+    fn request(id: &str) -> ApprovalRequest {
+        ApprovalRequest {
+            id: id.into(),
+            result: "result_123".into(),
+            lineage: "epi[1.0.0]::local_compute(data:Test)".into(),
+            workflow: "workflow-abc".into(),
+            user: Some("amy".into()),
+            requester: "hospital_b".into(),
+            task: Some("copy_result".into()),
+            tags: vec!["pii".into()],
+            sensitivity: 2,
+            requested: Utc::now(),
+            status: ApprovalStatus::Pending,
+            decided: None,
+            reason: None,
+        }
+    }
+
+    /// Tests that identifiers are stable and valid, and that the sensitivity of tags is derived from their level or that of their family.
+    #[test]
+    fn approvals_id_and_sensitivity() {
+        let id: String = approval_id("abcdef", "hospital_b", Some("amy"));
+        assert_eq!(id, approval_id("abcdef", "hospital_b", Some("amy")));
+        assert_ne!(id, approval_id("abcdef", "hospital_c", Some("amy")));
+        assert_ne!(id, approval_id("abcdef", "hospital_b", Some("bob")));
+        assert_ne!(id, approval_id("abcdee", "hospital_b", Some("amy")));
+        assert_eq!(id.len(), 16);
+
+        let config: EgressApprovalConfig = EgressApprovalConfig {
+            path:      PathBuf::from("/approvals"),
+            levels:    HashMap::from([("pii".into(), 2), ("medical".into(), 3)]),
+            threshold: 2,
+        };
+        assert_eq!(config.sensitivity(["public"]), 0);
+        assert_eq!(config.sensitivity(["pii:email", "public"]), 2);
+        assert_eq!(config.sensitivity(["pii", "medical"]), 3);
+        assert!(config.needs_approval(["pii:email"]));
+        assert!(!config.needs_approval(std::iter::empty()));
+    }
+
+    /// Tests that fingerprints cover the arguments and inputs of a call, regardless of their order.
+    #[test]
+    fn provenance_fingerprint() {
+        let lineage: &str = "epi[1.0.0]::aggregate(data:umc)";
+        let fingerprint: String = ResultProvenance::fingerprint(lineage, [("a", "1"), ("b", "\"x\"")], ["abc", "def"]);
+        assert_eq!(fingerprint, ResultProvenance::fingerprint(lineage, [("b", "\"x\""), ("a", "1")], ["def", "abc"]));
+        assert_ne!(fingerprint, ResultProvenance::fingerprint(lineage, [("a", "2"), ("b", "\"x\"")], ["abc", "def"]));
+        assert_ne!(fingerprint, ResultProvenance::fingerprint(lineage, [("a", "1"), ("b", "\"x\"")], ["abc"]));
+        assert_ne!(fingerprint, ResultProvenance::fingerprint("epi[1.0.0]::aggregate(data:lumc)", [("a", "1"), ("b", "\"x\"")], ["abc", "def"]));
+    }
+
+    /// Tests that submitting a request never overwrites an earlier one, and that invalid requests are skipped when listing them.
+    #[test]
+    fn approvals_submit_and_load_all() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-cfg-approvals-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // The first submission parks the request...
+        let mut parked: ApprovalRequest = request("0123456789abcdef").submit(&dir).unwrap();
+        assert_eq!(parked.status, ApprovalStatus::Pending);
+
+        // ...and later submissions find the decision on it
+        parked.status = ApprovalStatus::Approved;
+        parked.decided = Some(Utc::now());
+        parked.save(&dir).unwrap();
+        assert_eq!(request("0123456789abcdef").submit(&dir).unwrap().status, ApprovalStatus::Approved);
+        assert!(matches!(request("../../etc/passwd").submit(&dir), Err(Error::IllegalId { .. })));
+
+        // Files that are no requests are skipped
+        fs::write(dir.join("fedcba9876543210.yml"), "not: [a, request").unwrap();
+        let requests: Vec<ApprovalRequest> = ApprovalRequest::load_all(&dir).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].status, ApprovalStatus::Approved);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Tests that the provenance of a result can be recorded and read back.
+    #[test]
+    fn provenance_roundtrip() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-cfg-provenance-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert!(ResultProvenance::load(&dir, "result_123").unwrap().is_none());
+        let provenance: ResultProvenance = ResultProvenance {
+            result: "result_123".into(),
+            workflow: "workflow-abc".into(),
+            user: Some("amy".into()),
+            task: "epi[1.0.0]::local_compute".into(),
+            lineage: "epi[1.0.0]::local_compute(data:Test)".into(),
+            fingerprint: ResultProvenance::fingerprint("epi[1.0.0]::local_compute(data:Test)", [], []),
+            tags: vec!["pii".into()],
+            recorded: Utc::now(),
+        };
+        provenance.save(&dir).unwrap();
+        let loaded: ResultProvenance = ResultProvenance::load(&dir, "result_123").unwrap().unwrap();
+        assert_eq!(loaded.fingerprint, provenance.fingerprint);
+        assert_eq!(loaded.tags, provenance.tags);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    18 Oct 2022, 13:50:11
 //  Last edited:
-//    23 May 2023, 15:22:15
+//    16 Oct 2026, 02:16:30
 //  Auto updated?
 //    Yes
 //
@@ -13,18 +13,100 @@
 //!   service to connect with its backend.
 //
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
 use specifications::package::Capability;
 
+use crate::errors::ContainerRuntimeParseError;
 pub use crate::info::YamlError as Error;
 use crate::info::YamlInfo;
+use crate::secrets::SecretRef;
 
 
 /***** AUXILLARY *****/
+/// Defines the container runtimes that tasks may be run with.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerRuntime {
+    /// The default runtime of Docker / Kubernetes (no additional isolation).
+    #[default]
+    Runc,
+    /// gVisor's `runsc`, which runs tasks against a user-space kernel.
+    Runsc,
+    /// Kata Containers, which runs every task in a lightweight virtual machine.
+    Kata,
+}
+impl ContainerRuntime {
+    /// Returns the name under which the runtime is registered with the Docker daemon (or the name of the Kubernetes RuntimeClass).
+    ///
+    /// # Returns
+    /// The name of the runtime as a static string.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Runc => "runc",
+            Self::Runsc => "runsc",
+            Self::Kata => "kata-runtime",
+        }
+    }
+}
+impl Display for ContainerRuntime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ContainerRuntime::*;
+        match self {
+            Runc => write!(f, "runc"),
+            Runsc => write!(f, "runsc"),
+            Kata => write!(f, "kata"),
+        }
+    }
+}
+impl FromStr for ContainerRuntime {
+    type Err = ContainerRuntimeParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "runc" => Ok(Self::Runc),
+            "runsc" | "gvisor" => Ok(Self::Runsc),
+            "kata" => Ok(Self::Kata),
+            _ => Err(ContainerRuntimeParseError::UnknownRuntime { raw: s.into() }),
+        }
+    }
+}
+
+/// Defines how strongly task containers are isolated from the host.
+///
+/// The runtime has to be installed and registered with the backend separately; Brane only selects it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SandboxConfig {
+    /// The container runtime to run tasks with.
+    #[serde(default)]
+    pub runtime:  ContainerRuntime,
+    /// If true, runs tasks as an unprivileged user without any capabilities and without the possibility of gaining any.
+    ///
+    /// Note that the data and results directories must then be accessible to that user.
+    #[serde(default)]
+    pub rootless: bool,
+}
+
+
+
+/// Defines the username/password pair used to authenticate with a container registry.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegistryCredentials {
+    /// The username to authenticate with.
+    pub username: String,
+    /// The password (or access token) to authenticate with. See [`SecretRef`] for how to avoid writing it down in plaintext.
+    pub password: SecretRef,
+}
+
+
+
 /// Defines the possible credentials we may encounter.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -54,16 +136,65 @@ pub enum Credentials {
     Kubernetes {
         /// The address of the Docker registry that we push container images to.
         registry_address: Address,
+        /// The credentials to authenticate with at the Docker registry, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        registry_credentials: Option<RegistryCredentials>,
         /// The path to the Kubernetes config file to connect with.
         config: PathBuf,
     },
 }
+impl Credentials {
+    /// Returns the secrets in these credentials, such that they can be resolved. See [`Info::secrets_mut()`](crate::info::Info::secrets_mut()).
+    ///
+    /// # Returns
+    /// Mutable references to every [`SecretRef`] in these credentials.
+    pub fn secrets_mut(&mut self) -> Vec<&mut SecretRef> {
+        match self {
+            Self::Kubernetes { registry_credentials: Some(credentials), .. } => vec![&mut credentials.password],
+            _ => vec![],
+        }
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The name under which the backend that is defined at the toplevel of the `backend.yml` file is known.
+pub const DEFAULT_BACKEND_NAME: &str = "default";
 
 
 
 
 
 /***** LIBRARY *****/
+/// Defines an additional backend that tasks may be routed to, next to the default one defined in the [`BackendFile`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NamedBackend {
+    /// The capabilities provided by this backend. Tasks are only routed here if it provides everything they require.
+    #[serde(default)]
+    pub capabilities: HashSet<Capability>,
+    /// Can be specified to run tasks on this backend with other isolation than the default backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+    /// The method of connecting
+    pub method: Credentials,
+}
+
+/// Defines the backend that a particular task has been routed to.
+#[derive(Clone, Debug)]
+pub struct BackendRoute<'b> {
+    /// The name of the backend. This is [`DEFAULT_BACKEND_NAME`] for the default backend.
+    pub name:    &'b str,
+    /// The method of connecting to the backend.
+    pub method:  &'b Credentials,
+    /// The sandboxing settings with which to run tasks on the backend.
+    pub sandbox: SandboxConfig,
+}
+
+
+
 /// Defines a file that describes how a job service may connect to its backend.
 ///
 /// Note that this struct is designed to act as a "handle"; i.e., keep it only around when using it but otherwise refer to it only by path.
@@ -73,8 +204,14 @@ pub struct BackendFile {
     pub capabilities: Option<HashSet<Capability>>,
     /// Can be specified to disable container hash checking.
     pub hash_containers: Option<bool>,
+    /// Can be specified to run tasks with stronger isolation (e.g., for running untrusted packages).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
     /// The method of connecting
     pub method: Credentials,
+    /// Any additional backends (e.g., one with GPUs) that tasks may be routed to based on their requirements, by name.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub backends: HashMap<String, NamedBackend>,
 }
 
 impl BackendFile {
@@ -84,5 +221,63 @@ impl BackendFile {
     /// Whether container hash security should be enabled (true) or not (false).
     #[inline]
     pub fn hash_containers(&self) -> bool { self.hash_containers.unwrap_or(true) }
+
+    /// Returns the sandboxing settings of the user, generating a default (i.e., no additional isolation) if they didn't specify it.
+    ///
+    /// # Returns
+    /// The [`SandboxConfig`] to run tasks with.
+    #[inline]
+    pub fn sandbox(&self) -> SandboxConfig { self.sandbox.clone().unwrap_or_default() }
+
+    /// Returns the capabilities advertised by this domain, which is the union of those of all its backends.
+    ///
+    /// # Returns
+    /// A set of all [`Capability`]s that at least one backend provides.
+    pub fn all_capabilities(&self) -> HashSet<Capability> {
+        let mut capabilities: HashSet<Capability> = self.capabilities.clone().unwrap_or_default();
+        for backend in self.backends.values() {
+            capabilities.extend(backend.capabilities.iter().cloned());
+        }
+        capabilities
+    }
+
+    /// Finds the backend that a task with the given requirements should be executed on.
+    ///
+    /// The default backend is preferred if it provides everything the task requires. Otherwise, the first named backend that does (in alphabetical
+    /// order) is chosen.
+    ///
+    /// # Arguments
+    /// - `requirements`: The [`Capability`]s required by the task.
+    ///
+    /// # Returns
+    /// A [`BackendRoute`] describing the chosen backend, or [`None`] if no backend provides all requirements.
+    pub fn route(&self, requirements: &HashSet<Capability>) -> Option<BackendRoute> {
+        // Try the default one first
+        if self.capabilities.as_ref().map(|caps| caps.is_superset(requirements)).unwrap_or(requirements.is_empty()) {
+            return Some(BackendRoute { name: DEFAULT_BACKEND_NAME, method: &self.method, sandbox: self.sandbox() });
+        }
+
+        // Otherwise, go through the named ones in a predictable order
+        let mut names: Vec<&String> = self.backends.keys().collect();
+        names.sort();
+        names.into_iter().find_map(|name| {
+            let backend: &NamedBackend = &self.backends[name];
+            if backend.capabilities.is_superset(requirements) {
+                Some(BackendRoute { name, method: &backend.method, sandbox: backend.sandbox.clone().unwrap_or_else(|| self.sandbox()) })
+            } else {
+                None
+            }
+        })
+    }
+}
+impl<'de> YamlInfo<'de> for BackendFile {
+    const OPERATOR_AUTHORED: bool = true;
+
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> {
+        let mut secrets: Vec<&mut SecretRef> = self.method.secrets_mut();
+        for backend in self.backends.values_mut() {
+            secrets.extend(backend.method.secrets_mut());
+        }
+        secrets
+    }
 }
-impl<'de> YamlInfo<'de> for BackendFile {}
@@ -0,0 +1,170 @@
+//  ENROLLMENT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:58:16
+//  Last edited:
+//    15 Oct 2026, 21:58:16
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the one-time tokens with which clients can enroll at a
+//!   domain, i.e., have its registry issue them a client certificate
+//!   instead of receiving one from the domain's administrator.
+//!
+//!   Tokens are minted by `branectl generate enrollment-token`, which
+//!   stores only their hash in the [`ENROLLMENT_TOKENS_FILE`] in the
+//!   worker's certificate directory. The token given to the user also
+//!   contains the fingerprint of the domain's CA certificate, such that
+//!   the client can verify it talks to the right registry before sending
+//!   the token.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+pub use crate::errors::EnrollmentTokenParseError as Error;
+use crate::info::YamlInfo;
+
+
+/***** CONSTANTS *****/
+/// The name of the file in a worker's certificate directory that lists the pending enrollments.
+pub const ENROLLMENT_TOKENS_FILE: &str = "enrollment-tokens.yml";
+
+
+
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Computes the fingerprint of a certificate.
+///
+/// # Arguments
+/// - `der`: The DER-encoded certificate.
+///
+/// # Returns
+/// The SHA-256 hash of the certificate, as a lowercase hexadecimal string.
+#[inline]
+pub fn fingerprint(der: impl AsRef<[u8]>) -> String { hex::encode(Sha256::digest(der.as_ref())) }
+
+/// Computes the hash of a token's secret, which is what is stored in the [`ENROLLMENT_TOKENS_FILE`].
+///
+/// # Arguments
+/// - `secret`: The secret part of an [`EnrollmentToken`].
+///
+/// # Returns
+/// The SHA-256 hash of the secret, as a lowercase hexadecimal string.
+#[inline]
+pub fn hash_secret(secret: impl AsRef<str>) -> String { hex::encode(Sha256::digest(secret.as_ref().as_bytes())) }
+
+
+
+
+
+/***** LIBRARY *****/
+/// A token with which a client can enroll at a domain once.
+///
+/// It is written as `<SECRET>.<CA_FINGERPRINT>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnrollmentToken {
+    /// The secret that the registry checks.
+    pub secret:         String,
+    /// The fingerprint of the domain's CA certificate (see [`fingerprint()`]), which the client checks.
+    pub ca_fingerprint: String,
+}
+impl Display for EnrollmentToken {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}.{}", self.secret, self.ca_fingerprint) }
+}
+impl FromStr for EnrollmentToken {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (secret, ca_fingerprint): (&str, &str) = match s.trim().split_once('.') {
+            Some(parts) => parts,
+            None => return Err(Error::MissingFingerprint { raw: s.into() }),
+        };
+        if secret.is_empty() || !secret.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::IllegalSecret { raw: s.into() });
+        }
+        if ca_fingerprint.len() != 64 || !ca_fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::IllegalFingerprint { raw: s.into() });
+        }
+        Ok(Self { secret: secret.into(), ca_fingerprint: ca_fingerprint.to_ascii_lowercase() })
+    }
+}
+
+
+
+/// A token that was minted but not yet used.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PendingEnrollment {
+    /// The hash of the token's secret (see [`hash_secret()`]).
+    pub hash:    String,
+    /// The name of the client to issue the certificate to, which ends up as its common name.
+    pub client:  String,
+    /// The time at which the token expires, in seconds since the Unix epoch.
+    pub expires: u64,
+}
+
+/// Defines the [`ENROLLMENT_TOKENS_FILE`], which lists the tokens that can still be used.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct EnrollmentTokensFile {
+    /// The tokens that were minted but not yet used.
+    #[serde(default)]
+    pub tokens: Vec<PendingEnrollment>,
+}
+impl EnrollmentTokensFile {
+    /// Uses up the token with the given secret.
+    ///
+    /// Any expired tokens are removed as well.
+    ///
+    /// # Arguments
+    /// - `secret`: The secret part of the [`EnrollmentToken`] that the client presented.
+    /// - `now`: The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// The [`PendingEnrollment`] that belongs to the token, or `None` if there is no such (unexpired) token.
+    pub fn redeem(&mut self, secret: &str, now: u64) -> Option<PendingEnrollment> {
+        self.tokens.retain(|token| token.expires > now);
+        let hash: String = hash_secret(secret);
+        let pos: usize = self.tokens.iter().position(|token| token.hash == hash)?;
+        Some(self.tokens.remove(pos))
+    }
+}
+impl<'de> YamlInfo<'de> for EnrollmentTokensFile {}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that tokens survive a roundtrip through their string representation.
+    #[test]
+    fn enrollmenttoken_roundtrip() {
+        let token: EnrollmentToken = EnrollmentToken { secret: "abc123".into(), ca_fingerprint: fingerprint(b"ca") };
+        assert_eq!(token.to_string().parse::<EnrollmentToken>().unwrap(), token);
+        assert!(matches!("abc123".parse::<EnrollmentToken>(), Err(Error::MissingFingerprint { .. })));
+        assert!(matches!("abc-123.00".parse::<EnrollmentToken>(), Err(Error::IllegalSecret { .. })));
+        assert!(matches!("abc123.00".parse::<EnrollmentToken>(), Err(Error::IllegalFingerprint { .. })));
+    }
+
+    /// Tests that tokens can only be redeemed once and not after they expired.
+    #[test]
+    fn enrollmenttokensfile_redeem() {
+        let once: PendingEnrollment = PendingEnrollment { hash: hash_secret("once"), client: "amy".into(), expires: 100 };
+        let expired: PendingEnrollment = PendingEnrollment { hash: hash_secret("expired"), client: "bob".into(), expires: 10 };
+        let mut file: EnrollmentTokensFile = EnrollmentTokensFile { tokens: vec![once, expired] };
+        assert_eq!(file.redeem("expired", 50), None);
+        assert_eq!(file.redeem("once", 50).map(|pending| pending.client), Some("amy".into()));
+        assert_eq!(file.redeem("once", 50), None);
+        assert!(file.tokens.is_empty());
+    }
+}
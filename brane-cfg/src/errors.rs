@@ -4,7 +4,7 @@
 //  Created:
 //    04 Oct 2022, 11:09:56
 //  Last edited:
-//    07 Jun 2023, 16:27:48
+//    16 Oct 2026, 02:42:53
 //  Auto updated?
 //    Yes
 //
@@ -25,6 +25,8 @@ pub enum CertsError {
     ClientCertParseError { err: x509_parser::nom::Err<x509_parser::error::X509Error> },
     /// A given certificate did not have the `CN`-field specified.
     ClientCertNoCN { subject: String },
+    /// A certificate could not be parsed to find when it expires.
+    CertExpiryParseError { path: PathBuf, err: x509_parser::nom::Err<x509_parser::error::X509Error> },
 
     /// Failed to open a given file.
     FileOpenError { what: &'static str, path: PathBuf, err: std::io::Error },
@@ -32,6 +34,8 @@ pub enum CertsError {
     FileReadError { what: &'static str, path: PathBuf, err: std::io::Error },
     /// Encountered unknown item in the given file.
     UnknownItemError { what: &'static str, path: PathBuf },
+    /// Failed to read a directory with certificates.
+    DirReadError { path: PathBuf, err: std::io::Error },
 
     /// Failed to parse the certificate file.
     CertFileParseError { path: PathBuf, err: std::io::Error },
@@ -42,6 +46,18 @@ pub enum CertsError {
     EmptyCertFile { path: PathBuf },
     /// The given keyfile was empty.
     EmptyKeyFile { path: PathBuf },
+
+    /// A file contained encrypted keys, but no passphrase was given.
+    KeyPassphraseMissing { path: PathBuf },
+    /// Failed to resolve the passphrase of encrypted keys.
+    KeyPassphraseError { err: SecretError },
+    /// Failed to decode an encrypted key's PEM block.
+    EncryptedKeyPemError { path: PathBuf, err: pkcs8::der::pem::Error },
+    /// Failed to decrypt an encrypted key.
+    KeyDecryptError { path: PathBuf, err: pkcs8::Error },
+
+    /// Failed to parse the deadline of the grace period of a previous CA.
+    GraceDeadlineParseError { path: PathBuf, raw: String, err: std::num::ParseIntError },
 }
 impl Display for CertsError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -49,16 +65,33 @@ impl Display for CertsError {
         match self {
             ClientCertParseError { err } => write!(f, "Failed to parse given client certificate file: {err}"),
             ClientCertNoCN { subject } => write!(f, "Certificate subject field '{subject}' does not specify a CN"),
+            CertExpiryParseError { path, err } => write!(f, "Failed to parse certificate in '{}' to find its expiry date: {}", path.display(), err),
 
             FileOpenError { what, path, err } => write!(f, "Failed to open {} file '{}': {}", what, path.display(), err),
             FileReadError { what, path, err } => write!(f, "Failed to read {} file '{}': {}", what, path.display(), err),
             UnknownItemError { what, path } => write!(f, "Encountered non-certificate, non-key item in {} file '{}'", what, path.display()),
+            DirReadError { path, err } => write!(f, "Failed to read certificate directory '{}': {}", path.display(), err),
 
             CertFileParseError { path, err } => write!(f, "Failed to parse certificates in '{}': {}", path.display(), err),
             KeyFileParseError { path, err } => write!(f, "Failed to parse keys in '{}': {}", path.display(), err),
 
             EmptyCertFile { path } => write!(f, "No certificates found in file '{}'", path.display()),
             EmptyKeyFile { path } => write!(f, "No keys found in file '{}'", path.display()),
+
+            KeyPassphraseMissing { path } => write!(
+                f,
+                "File '{}' contains encrypted private keys, but no passphrase was given (set 'key_passphrase' in the node config, '{}' or '{}')",
+                path.display(),
+                crate::certs::KEY_PASSPHRASE_ENV,
+                crate::certs::KEY_PASSPHRASE_FILE_ENV
+            ),
+            KeyPassphraseError { err } => write!(f, "Failed to resolve private key passphrase: {err}"),
+            EncryptedKeyPemError { path, err } => write!(f, "Failed to decode encrypted private key in '{}': {}", path.display(), err),
+            KeyDecryptError { path, err } => write!(f, "Failed to decrypt private key in '{}' (is the passphrase correct?): {}", path.display(), err),
+
+            GraceDeadlineParseError { path, raw, err } => {
+                write!(f, "Failed to parse '{}' in grace period file '{}' as a UNIX timestamp: {}", raw, path.display(), err)
+            },
         }
     }
 }
@@ -120,6 +153,40 @@ impl Display for ProxyProtocolParseError {
 }
 impl Error for ProxyProtocolParseError {}
 
+/// Defines errors that may occur when parsing destination rules.
+#[derive(Debug)]
+pub enum DestinationRuleParseError {
+    /// The rule has no host part.
+    MissingHost { raw: String },
+    /// An IPv6 host was opened with a bracket but never closed.
+    UnclosedBracket { raw: String },
+    /// The part after a bracketed IPv6 host was not a port.
+    TrailingCharacters { raw: String },
+    /// The address part of a CIDR was not an IP address.
+    IllegalCidrAddress { raw: String, err: std::net::AddrParseError },
+    /// The prefix length of a CIDR was not a number or too large for its address.
+    IllegalCidrPrefix { raw: String },
+    /// A port was not a valid port number.
+    IllegalPort { raw: String, err: std::num::ParseIntError },
+    /// A port range ended before it started.
+    EmptyPortRange { raw: String },
+}
+impl Display for DestinationRuleParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DestinationRuleParseError::*;
+        match self {
+            MissingHost { raw } => write!(f, "Destination rule '{raw}' has no host (use '*' to match any host)"),
+            UnclosedBracket { raw } => write!(f, "Destination rule '{raw}' has an unclosed '['"),
+            TrailingCharacters { raw } => write!(f, "Destination rule '{raw}' has trailing characters after ']' (expected ':<PORTS>')"),
+            IllegalCidrAddress { raw, err } => write!(f, "Failed to parse address of CIDR '{raw}' as an IP address: {err}"),
+            IllegalCidrPrefix { raw } => write!(f, "CIDR '{raw}' has an illegal prefix length"),
+            IllegalPort { raw, err } => write!(f, "Failed to parse '{raw}' as a port number: {err}"),
+            EmptyPortRange { raw } => write!(f, "Port range '{raw}' ends before it starts"),
+        }
+    }
+}
+impl Error for DestinationRuleParseError {}
+
 /// Defines errors that may occur when parsing node kind strings.
 #[derive(Debug)]
 pub enum NodeKindParseError {
@@ -135,3 +202,163 @@ impl Display for NodeKindParseError {
     }
 }
 impl Error for NodeKindParseError {}
+
+/// Defines errors that may occur when parsing container runtime strings.
+#[derive(Debug)]
+pub enum ContainerRuntimeParseError {
+    /// The given runtime was unknown to us.
+    UnknownRuntime { raw: String },
+}
+impl Display for ContainerRuntimeParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ContainerRuntimeParseError::*;
+        match self {
+            UnknownRuntime { raw } => write!(f, "Unknown container runtime '{raw}' (options are 'runc', 'runsc' or 'kata')"),
+        }
+    }
+}
+impl Error for ContainerRuntimeParseError {}
+
+/// Defines errors that may occur when parsing [`EnrollmentToken`](crate::enrollment::EnrollmentToken)s.
+#[derive(Debug)]
+pub enum EnrollmentTokenParseError {
+    /// The token did not contain the CA fingerprint.
+    MissingFingerprint { raw: String },
+    /// The secret part of the token was not alphanumeric.
+    IllegalSecret { raw: String },
+    /// The fingerprint part of the token was not a SHA-256 hash.
+    IllegalFingerprint { raw: String },
+}
+impl Display for EnrollmentTokenParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use EnrollmentTokenParseError::*;
+        match self {
+            MissingFingerprint { raw } => write!(f, "Enrollment token '{raw}' is not given as '<SECRET>.<CA_FINGERPRINT>'"),
+            IllegalSecret { raw } => write!(f, "Enrollment token '{raw}' has an illegal secret (expected an alphanumeric string)"),
+            IllegalFingerprint { raw } => write!(f, "Enrollment token '{raw}' has an illegal CA fingerprint (expected a hexadecimal SHA-256 hash)"),
+        }
+    }
+}
+impl Error for EnrollmentTokenParseError {}
+
+/// Defines errors that may occur when resolving [`SecretRef`](crate::secrets::SecretRef)s.
+#[derive(Debug)]
+pub enum SecretError {
+    /// The referenced environment variable was not set.
+    EnvNotSet { name: String },
+    /// The referenced environment variable was not valid UTF-8.
+    EnvNotUnicode { name: String },
+    /// Failed to read the referenced secret file.
+    FileReadError { path: PathBuf, err: std::io::Error },
+
+    /// A Vault key did not specify which field to read.
+    VaultKeyWithoutField { key: String },
+    /// Failed to send the request to Vault.
+    VaultRequestError { url: String, err: reqwest::Error },
+    /// Vault responded with a non-success status code.
+    VaultRequestFailure { url: String, status: reqwest::StatusCode },
+    /// Failed to parse Vault's response as JSON.
+    VaultResponseParseError { url: String, err: reqwest::Error },
+    /// The requested field was not present in the Vault secret.
+    VaultFieldNotFound { path: String, field: String },
+    /// Failed to create the runtime on which to resolve a Vault secret while loading a config file.
+    RuntimeCreateError { err: std::io::Error },
+    /// The thread on which a Vault secret was resolved panicked.
+    VaultThreadPanicked { key: String },
+}
+impl Display for SecretError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SecretError::*;
+        match self {
+            EnvNotSet { name } => write!(f, "Environment variable '{name}' is not set"),
+            EnvNotUnicode { name } => write!(f, "Environment variable '{name}' does not contain valid UTF-8"),
+            FileReadError { path, .. } => write!(f, "Failed to read secret file '{}'", path.display()),
+
+            VaultKeyWithoutField { key } => write!(f, "Vault secret reference '{key}' does not specify a field (use '<path>#<field>')"),
+            VaultRequestError { url, .. } => write!(f, "Failed to send request to Vault at '{url}'"),
+            VaultRequestFailure { url, status } => write!(
+                f,
+                "Request to Vault at '{}' failed with status {} ({})",
+                url,
+                status.as_u16(),
+                status.canonical_reason().unwrap_or("???")
+            ),
+            VaultResponseParseError { url, .. } => write!(f, "Failed to parse response of Vault at '{url}' as JSON"),
+            VaultFieldNotFound { path, field } => write!(f, "Vault secret '{path}' has no (string) field '{field}'"),
+            RuntimeCreateError { .. } => write!(f, "Failed to create runtime to resolve Vault secrets on"),
+            VaultThreadPanicked { key } => write!(f, "Thread resolving Vault secret '{key}' panicked"),
+        }
+    }
+}
+impl Error for SecretError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SecretError::*;
+        match self {
+            FileReadError { err, .. } => Some(err),
+            VaultRequestError { err, .. } => Some(err),
+            VaultResponseParseError { err, .. } => Some(err),
+            RuntimeCreateError { err } => Some(err),
+
+            EnvNotSet { .. }
+            | EnvNotUnicode { .. }
+            | VaultKeyWithoutField { .. }
+            | VaultRequestFailure { .. }
+            | VaultFieldNotFound { .. }
+            | VaultThreadPanicked { .. } => None,
+        }
+    }
+}
+
+/// Defines errors that may occur when reading or writing [`ApprovalRequest`](crate::approvals::ApprovalRequest)s.
+#[derive(Debug)]
+pub enum ApprovalsError {
+    /// The given identifier is not a valid request identifier.
+    IllegalId { id: String },
+    /// Failed to read the approvals directory.
+    DirRead { path: PathBuf, err: std::io::Error },
+    /// Failed to load a request.
+    RequestLoad { path: PathBuf, err: crate::info::YamlError },
+    /// Failed to create the file of a request.
+    RequestCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to write a request.
+    RequestWrite { path: PathBuf, err: crate::info::YamlError },
+
+    /// Failed to create the directory with the provenance of results.
+    DirCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to load the provenance of a result.
+    ProvenanceLoad { path: PathBuf, err: crate::info::YamlError },
+    /// Failed to write the provenance of a result.
+    ProvenanceWrite { path: PathBuf, err: crate::info::YamlError },
+}
+impl Display for ApprovalsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ApprovalsError::*;
+        match self {
+            IllegalId { id } => write!(f, "Illegal approval request ID '{id}' (expected a hexadecimal string)"),
+            DirRead { path, .. } => write!(f, "Failed to read approvals directory '{}'", path.display()),
+            RequestLoad { path, .. } => write!(f, "Failed to load approval request '{}'", path.display()),
+            RequestCreate { path, .. } => write!(f, "Failed to create approval request file '{}'", path.display()),
+            RequestWrite { path, .. } => write!(f, "Failed to write approval request '{}'", path.display()),
+
+            DirCreate { path, .. } => write!(f, "Failed to create result provenance directory '{}'", path.display()),
+            ProvenanceLoad { path, .. } => write!(f, "Failed to load result provenance '{}'", path.display()),
+            ProvenanceWrite { path, .. } => write!(f, "Failed to write result provenance '{}'", path.display()),
+        }
+    }
+}
+impl Error for ApprovalsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ApprovalsError::*;
+        match self {
+            IllegalId { .. } => None,
+            DirRead { err, .. } => Some(err),
+            RequestLoad { err, .. } => Some(err),
+            RequestCreate { err, .. } => Some(err),
+            RequestWrite { err, .. } => Some(err),
+
+            DirCreate { err, .. } => Some(err),
+            ProvenanceLoad { err, .. } => Some(err),
+            ProvenanceWrite { err, .. } => Some(err),
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    28 Feb 2023, 10:07:36
 //  Last edited:
-//    14 Jun 2024, 15:12:07
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -18,24 +18,65 @@ use std::fmt::{Debug, Display, Formatter, Result as FResult};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
+use brane_shr::fs::AtomicFile;
+use log::{debug, warn};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tokio::fs::File as TFile;
 use tokio::io::AsyncReadExt as _;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::errors::SecretError;
+use crate::secrets::SecretRef;
+use crate::strict::{find_unknown_keys, locate_keys, UnknownKey, UnknownKeyPolicy, UNKNOWN_KEYS_ENV};
 
 
 /***** ERRORS *****/
+/// Defines errors that may occur when interpolating environment variables in a raw config file.
+#[derive(Debug)]
+pub enum InterpolateError {
+    /// A `${`-sequence was opened but never closed.
+    UnterminatedReference { pos: usize },
+    /// A reference did not name any variable.
+    EmptyReference { pos: usize },
+    /// A referenced environment variable was not set and had no default.
+    UnsetVariable { name: String },
+    /// A referenced environment variable was set but not valid UTF-8.
+    NonUnicodeVariable { name: String },
+}
+impl Display for InterpolateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use InterpolateError::*;
+        match self {
+            UnterminatedReference { pos } => write!(f, "Unterminated environment variable reference starting at byte {pos}"),
+            EmptyReference { pos } => write!(f, "Environment variable reference at byte {pos} does not name a variable"),
+            UnsetVariable { name } => write!(f, "Environment variable '{name}' is not set and no default is given (use '${{{name}:-<default>}}')"),
+            NonUnicodeVariable { name } => write!(f, "Environment variable '{name}' does not contain valid UTF-8"),
+        }
+    }
+}
+impl Error for InterpolateError {}
+
 /// Defines general errors for configs.
 #[derive(Debug)]
 pub enum InfoError<E: Debug> {
     /// Failed to create the output file.
     OutputCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to move the written output file over the old one.
+    OutputCommitError { path: PathBuf, err: std::io::Error },
     /// Failed to open the input file.
     InputOpenError { path: PathBuf, err: std::io::Error },
     /// Failed to read the input file.
     InputReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to substitute the environment variables in the input file.
+    InputInterpolateError { path: PathBuf, err: InterpolateError },
+    /// Failed to resolve a secret referred to by the input file.
+    InputSecretError { path: PathBuf, err: SecretError },
 
     /// Failed to serialize the config to a string.
     StringSerializeError { err: E },
@@ -50,14 +91,25 @@ pub enum InfoError<E: Debug> {
     ReaderDeserializeError { err: E },
     /// Failed to deserialize a file to the config.
     FileDeserializeError { path: PathBuf, err: E },
+    /// Failed to deserialize an overlay string over the config.
+    OverlayDeserializeError { err: E },
+    /// Failed to deserialize an overlay file over the config.
+    OverlayFileDeserializeError { path: PathBuf, err: E },
+    /// The config contained keys that it does not know.
+    UnknownKeysError { keys: Vec<UnknownKey> },
+    /// The config in a file contained keys that it does not know.
+    FileUnknownKeysError { path: PathBuf, keys: Vec<UnknownKey> },
 }
 impl<E: Error> Display for InfoError<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use InfoError::*;
         match self {
             OutputCreateError { path, .. } => write!(f, "Failed to create output file '{}'", path.display()),
+            OutputCommitError { path, .. } => write!(f, "Failed to replace output file '{}'", path.display()),
             InputOpenError { path, .. } => write!(f, "Failed to open input file '{}'", path.display()),
             InputReadError { path, .. } => write!(f, "Failed to read input file '{}'", path.display()),
+            InputInterpolateError { path, .. } => write!(f, "Failed to substitute environment variables in input file '{}'", path.display()),
+            InputSecretError { path, .. } => write!(f, "Failed to resolve secret referred to by input file '{}'", path.display()),
 
             StringSerializeError { .. } => write!(f, "Failed to serialize to string"),
             WriterSerializeError { .. } => write!(f, "Failed to serialize to a writer"),
@@ -66,6 +118,21 @@ impl<E: Error> Display for InfoError<E> {
             StringDeserializeError { .. } => write!(f, "Failed to deserialize from string"),
             ReaderDeserializeError { .. } => write!(f, "Failed to deserialize from a reader"),
             FileDeserializeError { path, .. } => write!(f, "Failed to deserialize from input file '{}'", path.display()),
+            OverlayDeserializeError { .. } => write!(f, "Failed to deserialize overlay from string"),
+            OverlayFileDeserializeError { path, .. } => write!(f, "Failed to deserialize from overlay file '{}'", path.display()),
+            UnknownKeysError { keys } => write!(
+                f,
+                "Found {}; set {}=warn to only warn about unknown keys",
+                keys.iter().map(UnknownKey::to_string).collect::<Vec<String>>().join("; "),
+                UNKNOWN_KEYS_ENV
+            ),
+            FileUnknownKeysError { path, keys } => write!(
+                f,
+                "Input file '{}' has {}; set {}=warn to only warn about unknown keys",
+                path.display(),
+                keys.iter().map(UnknownKey::to_string).collect::<Vec<String>>().join("; "),
+                UNKNOWN_KEYS_ENV
+            ),
         }
     }
 }
@@ -74,8 +141,11 @@ impl<E: 'static + Error> Error for InfoError<E> {
         use InfoError::*;
         match self {
             OutputCreateError { err, .. } => Some(err),
+            OutputCommitError { err, .. } => Some(err),
             InputOpenError { err, .. } => Some(err),
             InputReadError { err, .. } => Some(err),
+            InputInterpolateError { err, .. } => Some(err),
+            InputSecretError { err, .. } => Some(err),
 
             StringSerializeError { err } => Some(err),
             WriterSerializeError { err } => Some(err),
@@ -84,6 +154,213 @@ impl<E: 'static + Error> Error for InfoError<E> {
             StringDeserializeError { err } => Some(err),
             ReaderDeserializeError { err } => Some(err),
             FileDeserializeError { err, .. } => Some(err),
+            OverlayDeserializeError { err } => Some(err),
+            OverlayFileDeserializeError { err, .. } => Some(err),
+            UnknownKeysError { .. } => None,
+            FileUnknownKeysError { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Substitutes environment variables in the given raw config file.
+///
+/// References are written as `${NAME}`, which is replaced by the value of `NAME`, or as `${NAME:-default}`, which falls back to `default` if `NAME` is unset or empty. A literal `${` may be written as `$${`. Any other `$` is left untouched.
+///
+/// # Arguments
+/// - `raw`: The raw contents of the config file to substitute in.
+///
+/// # Returns
+/// A new string with all references replaced by their values.
+///
+/// # Errors
+/// This function errors if a reference is malformed or if it refers to a variable that is not set and has no default.
+pub fn interpolate_env(raw: &str) -> Result<String, InterpolateError> {
+    let mut res: String = String::with_capacity(raw.len());
+    let mut i: usize = 0;
+    while let Some(offset) = raw[i..].find('$') {
+        let start: usize = i + offset;
+        res.push_str(&raw[i..start]);
+
+        // Check what follows the dollar
+        let rest: &str = &raw[start..];
+        if rest.starts_with("$${") {
+            res.push_str("${");
+            i = start + 3;
+            continue;
+        } else if !rest.starts_with("${") {
+            res.push('$');
+            i = start + 1;
+            continue;
+        }
+
+        // Find the closing brace
+        let end: usize = match rest.find('}') {
+            Some(end) => start + end,
+            None => return Err(InterpolateError::UnterminatedReference { pos: start }),
+        };
+        let body: &str = &raw[start + 2..end];
+
+        // Split it into a name and an optional default
+        let (name, default): (&str, Option<&str>) = match body.find(":-") {
+            Some(sep) => (body[..sep].trim(), Some(&body[sep + 2..])),
+            None => (body.trim(), None),
+        };
+        if name.is_empty() {
+            return Err(InterpolateError::EmptyReference { pos: start });
+        }
+
+        // Resolve it
+        match (std::env::var(name), default) {
+            (Ok(value), Some(default)) if value.is_empty() => res.push_str(default),
+            (Ok(value), _) => res.push_str(&value),
+            (Err(std::env::VarError::NotPresent), Some(default)) => res.push_str(default),
+            (Err(std::env::VarError::NotPresent), None) => return Err(InterpolateError::UnsetVariable { name: name.into() }),
+            (Err(std::env::VarError::NotUnicode(_)), _) => return Err(InterpolateError::NonUnicodeVariable { name: name.into() }),
+        }
+        i = end + 1;
+    }
+    res.push_str(&raw[i..]);
+    Ok(res)
+}
+
+
+/// Returns the path of the overlay file that belongs to the given config file.
+///
+/// This is the same path, but with `.override` inserted before the extension (e.g., `node.yml` becomes `node.override.yml`).
+///
+/// # Arguments
+/// - `path`: The path of the config file to find the overlay of.
+///
+/// # Returns
+/// The path where the overlay file would be. It may not exist.
+pub fn overlay_path(path: impl AsRef<Path>) -> PathBuf {
+    let path: &Path = path.as_ref();
+    let stem: String = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}.override.{}", stem, ext.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}.override")),
+    }
+}
+
+/// Deep-merges the given overlay YAML value over the given base value.
+///
+/// Mappings are merged key-by-key, recursively; any other value in the overlay (scalars, sequences, `null`) replaces the value in the base.
+///
+/// # Arguments
+/// - `base`: The value to merge into.
+/// - `overlay`: The value to merge over `base`.
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Checks a config parsed from a YAML file for keys that it does not know, and handles them according to the [`UnknownKeyPolicy`].
+///
+/// # Arguments
+/// - `config`: The parsed config.
+/// - `input`: The file as a generic YAML value (merged with its overlay, if any).
+/// - `raw`: The raw file, used to find where unknown keys are defined.
+/// - `overlay`: The raw overlay file, if any.
+///
+/// # Errors
+/// This function errors with the unknown keys if there are any and the policy is [`UnknownKeyPolicy::Deny`].
+fn check_unknown_keys<T: Serialize>(config: &T, input: &serde_yaml::Value, raw: &str, overlay: Option<&str>) -> Result<(), Vec<UnknownKey>> {
+    let output: serde_yaml::Value = match serde_yaml::to_value(config) {
+        Ok(output) => output,
+        Err(err) => {
+            debug!("Failed to serialize config to check for unknown keys: {} (skipping check)", err);
+            return Ok(());
+        },
+    };
+    let mut keys: Vec<UnknownKey> = find_unknown_keys(input, &output);
+    if keys.is_empty() {
+        return Ok(());
+    }
+    locate_keys(&mut keys, raw, overlay);
+    match UnknownKeyPolicy::current() {
+        UnknownKeyPolicy::Deny => Err(keys),
+        UnknownKeyPolicy::Warn => {
+            for key in keys {
+                warn!("Ignoring {}", key);
+            }
+            Ok(())
+        },
+    }
+}
+
+/// Returns a cheap fingerprint of a file's state on disk that we use to detect changes.
+///
+/// # Arguments
+/// - `path`: The path of the file to fingerprint.
+///
+/// # Returns
+/// The modification time and size of the file, or `None` if we failed to read its metadata (e.g., because it's (temporarily) missing).
+async fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata: std::fs::Metadata = tokio::fs::metadata(path).await.ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Aborts the task that polls a watched file when dropped.
+#[derive(Debug)]
+struct WatchTask(JoinHandle<()>);
+impl Drop for WatchTask {
+    #[inline]
+    fn drop(&mut self) { self.0.abort(); }
+}
+
+/// A handle to a config file that is being watched for changes on disk. Obtained through [`Info::watch()`].
+///
+/// The handle can be cloned to share it between tasks (e.g., one that serves requests from [`InfoWatcher::current()`] and one that waits for
+/// [`InfoWatcher::changed()`]). The watcher stops polling the file as soon as all of them are dropped.
+#[derive(Clone, Debug)]
+pub struct InfoWatcher<T> {
+    /// The path of the watched file.
+    path:   PathBuf,
+    /// The receiver on which new versions of the file are published.
+    rx:     watch::Receiver<Arc<T>>,
+    /// The task that polls the file.
+    handle: Arc<WatchTask>,
+}
+impl<T> InfoWatcher<T> {
+    /// Returns the path of the file being watched.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the most recently (successfully) loaded version of the file.
+    #[inline]
+    pub fn current(&self) -> Arc<T> { self.rx.borrow().clone() }
+
+    /// Waits until a new version of the file has been loaded.
+    ///
+    /// Note that versions of the file that fail to parse are never published; instead, a warning is logged and the previous version remains current.
+    ///
+    /// # Returns
+    /// The newly loaded version, or `None` if the watcher has stopped.
+    pub async fn changed(&mut self) -> Option<Arc<T>> {
+        match self.rx.changed().await {
+            Ok(_) => Some(self.rx.borrow_and_update().clone()),
+            Err(_) => None,
         }
     }
 }
@@ -99,6 +376,14 @@ pub trait Info: Clone + Debug {
     /// The types of errors that may be thrown by the serialization function(s).
     type Error: Error;
 
+    /// Whether files of this type are written by operators rather than by Brane itself.
+    ///
+    /// Only such files have environment variables substituted and overlay files merged when they are loaded from disk (see
+    /// [`Info::from_path()`]), since files that Brane writes may contain `$` or `${...}` in their (user-chosen) values.
+    ///
+    /// By default, `false`.
+    const OPERATOR_AUTHORED: bool = false;
+
 
     // Child-provided
     /// Serializes this Config to a string.
@@ -144,13 +429,63 @@ pub trait Info: Clone + Debug {
     /// # Errors
     /// This function may fail if we failed to read from the reader or if its contents were invalid for this object.
     fn from_reader(reader: impl Read) -> Result<Self, InfoError<Self::Error>>;
+    /// Deserializes the given string to an instance of ourselves, after deep-merging another string over it.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw string to deserialize.
+    /// - `overlay`: Another raw string who's contents take precedence over those in `raw`.
+    ///
+    /// # Returns
+    /// A new instance of `Self` with its contents read from the merged strings.
+    ///
+    /// # Errors
+    /// This function may fail if either input string was invalid, or if the merged result was invalid for this object.
+    fn from_string_with_overlay(raw: impl AsRef<str>, overlay: impl AsRef<str>) -> Result<Self, InfoError<Self::Error>>;
+
+    /// Returns the [`SecretRef`]s in this config, such that they can be resolved when it is loaded.
+    ///
+    /// By default, returns none.
+    ///
+    /// # Returns
+    /// Mutable references to every [`SecretRef`] in this config.
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> { vec![] }
 
 
     // Globally deduced
+    /// Resolves any [`SecretRef`]s in this config (see [`Info::secrets_mut()`]), such that missing secrets are noticed when it is loaded instead
+    /// of when they are first used.
+    ///
+    /// This function blocks while resolving them; prefer [`Info::resolve_secrets_async()`] from async code.
+    ///
+    /// # Errors
+    /// This function errors if any of the secrets could not be resolved.
+    fn resolve_secrets(&mut self) -> Result<(), SecretError> {
+        for secret in self.secrets_mut() {
+            secret.load()?;
+        }
+        Ok(())
+    }
+    /// Resolves any [`SecretRef`]s in this config (see [`Info::secrets_mut()`]) asynchronously.
+    ///
+    /// # Errors
+    /// This function errors if any of the secrets could not be resolved.
+    async fn resolve_secrets_async(&mut self) -> Result<(), SecretError>
+    where
+        Self: Send,
+    {
+        for secret in self.secrets_mut() {
+            secret.load_async().await?;
+        }
+        Ok(())
+    }
+
     /// Serializes this Config to a file at the given path.
     ///
     /// This will always choose a pretty representation of the serialization (if applicable).
     ///
+    /// The file is replaced atomically (see [`AtomicFile`]), so concurrent readers never see a half-written file and a crash halfway leaves the
+    /// old one intact.
+    ///
     /// # Arguments
     /// - `path`: The path where to write the file to.
     ///
@@ -160,7 +495,7 @@ pub trait Info: Clone + Debug {
         let path: &Path = path.as_ref();
 
         // Attempt to create the new file
-        let handle: File = match File::create(path) {
+        let mut handle: AtomicFile = match AtomicFile::create(path) {
             Ok(handle) => handle,
             Err(err) => {
                 return Err(InfoError::OutputCreateError { path: path.into(), err });
@@ -168,48 +503,114 @@ pub trait Info: Clone + Debug {
         };
 
         // Write it using the child function, wrapping the error that may occur
-        match self.to_writer(handle, true) {
+        match self.to_writer(&mut handle, true) {
+            Ok(_) => {},
+            Err(InfoError::WriterSerializeError { err }) => return Err(InfoError::FileSerializeError { path: path.into(), err }),
+            Err(err) => return Err(err),
+        }
+
+        // Only now replace the old file
+        match handle.commit() {
             Ok(_) => Ok(()),
-            Err(InfoError::WriterSerializeError { err }) => Err(InfoError::FileSerializeError { path: path.into(), err }),
-            Err(err) => Err(err),
+            Err(err) => Err(InfoError::OutputCommitError { path: path.into(), err }),
         }
     }
 
     /// Deserializes this Config from the file at the given path.
     ///
+    /// For files written by operators (see [`Info::OPERATOR_AUTHORED`]), any environment variable references (`${NAME}` or `${NAME:-default}`) in the file are substituted before parsing. See [`interpolate_env()`] for the exact syntax.
+    ///
+    /// For those files, if an overlay file exists next to the given file (e.g., `node.override.yml` for `node.yml`; see [`overlay_path()`]), it is deep-merged over the file before parsing as well. This allows site-specific tweaks to survive regenerating the main file.
+    ///
+    /// After parsing, any secrets that the file refers to are resolved (see [`Info::resolve_secrets()`]).
+    ///
     /// # Arguments
     /// - `path`: The path where to read the file from.
     ///
     /// # Errors
-    /// This function may fail if we failed to open/read from the file or if its contents were invalid for this object.
+    /// This function may fail if we failed to open/read from the file, if it referenced unset environment variables or secrets that could not be
+    /// resolved, or if its contents were invalid for this object.
     fn from_path(path: impl AsRef<Path>) -> Result<Self, InfoError<Self::Error>> {
         let path: &Path = path.as_ref();
 
-        // Attempt to open the given file
-        let handle: File = match File::open(path) {
-            Ok(handle) => handle,
-            Err(err) => {
-                return Err(InfoError::InputOpenError { path: path.into(), err });
-            },
+        // Read the file to a string
+        let raw: String = {
+            // Attempt to open the given file
+            let mut handle: File = match File::open(path) {
+                Ok(handle) => handle,
+                Err(err) => {
+                    return Err(InfoError::InputOpenError { path: path.into(), err });
+                },
+            };
+
+            // Read everything to a string
+            let mut raw: String = String::new();
+            if let Err(err) = handle.read_to_string(&mut raw) {
+                return Err(InfoError::InputReadError { path: path.into(), err });
+            }
+            raw
         };
 
-        // Write it using the child function, wrapping the error that may occur
-        match Self::from_reader(handle) {
-            Ok(config) => Ok(config),
-            Err(InfoError::ReaderDeserializeError { err }) => Err(InfoError::FileDeserializeError { path: path.into(), err }),
-            Err(err) => Err(err),
+        // Substitute any environment variables
+        let raw: String = if Self::OPERATOR_AUTHORED {
+            match interpolate_env(&raw) {
+                Ok(raw) => raw,
+                Err(err) => return Err(InfoError::InputInterpolateError { path: path.into(), err }),
+            }
+        } else {
+            raw
+        };
+
+        // Read the overlay file, if any
+        let overlay_path: PathBuf = overlay_path(path);
+        let overlay: Option<String> = if Self::OPERATOR_AUTHORED {
+            match std::fs::read_to_string(&overlay_path) {
+                Ok(overlay) => match interpolate_env(&overlay) {
+                    Ok(overlay) => Some(overlay),
+                    Err(err) => return Err(InfoError::InputInterpolateError { path: overlay_path, err }),
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(InfoError::InputReadError { path: overlay_path, err }),
+            }
+        } else {
+            None
+        };
+
+        // Parse it using the child function, wrapping the error that may occur
+        let res: Result<Self, InfoError<Self::Error>> = match overlay {
+            Some(overlay) => Self::from_string_with_overlay(raw, overlay),
+            None => Self::from_string(raw),
+        };
+        let mut config: Self = match res {
+            Ok(config) => config,
+            Err(InfoError::StringDeserializeError { err }) => return Err(InfoError::FileDeserializeError { path: path.into(), err }),
+            Err(InfoError::OverlayDeserializeError { err }) => return Err(InfoError::OverlayFileDeserializeError { path: overlay_path, err }),
+            Err(InfoError::UnknownKeysError { keys }) => return Err(InfoError::FileUnknownKeysError { path: path.into(), keys }),
+            Err(err) => return Err(err),
+        };
+
+        // Resolve any secrets it refers to, so missing ones are noticed now
+        if let Err(err) = config.resolve_secrets() {
+            return Err(InfoError::InputSecretError { path: path.into(), err });
         }
+        Ok(config)
     }
     /// Deserializes this Config from the file at the given path, with the reading part done asynchronously.
     ///
     /// Note that the parsing path cannot be done asynchronously. Also, note that, because serde does not support asynchronous deserialization, we have to read the entire file in one go.
     ///
+    /// Like [`Info::from_path()`], environment variable references in operator-authored files are substituted and any overlay file is merged
+    /// before parsing, and any secrets it refers to are resolved after.
+    ///
     /// # Arguments
     /// - `path`: The path where to read the file from.
     ///
     /// # Errors
     /// This function may fail if we failed to open/read from the file or if its contents were invalid for this object.
-    async fn from_path_async(path: impl Send + AsRef<Path>) -> Result<Self, InfoError<Self::Error>> {
+    async fn from_path_async(path: impl Send + AsRef<Path>) -> Result<Self, InfoError<Self::Error>>
+    where
+        Self: Send,
+    {
         let path: &Path = path.as_ref();
 
         // Read the file to a string
@@ -230,22 +631,137 @@ pub trait Info: Clone + Debug {
             raw
         };
 
-        // Write it using the child function, wrapping the error that may occur
-        match Self::from_string(raw) {
-            Ok(config) => Ok(config),
-            Err(InfoError::ReaderDeserializeError { err }) => Err(InfoError::FileDeserializeError { path: path.into(), err }),
-            Err(err) => Err(err),
+        // Substitute any environment variables
+        let raw: String = if Self::OPERATOR_AUTHORED {
+            match interpolate_env(&raw) {
+                Ok(raw) => raw,
+                Err(err) => return Err(InfoError::InputInterpolateError { path: path.into(), err }),
+            }
+        } else {
+            raw
+        };
+
+        // Read the overlay file, if any
+        let overlay_path: PathBuf = overlay_path(path);
+        let overlay: Option<String> = if Self::OPERATOR_AUTHORED {
+            match tokio::fs::read_to_string(&overlay_path).await {
+                Ok(overlay) => match interpolate_env(&overlay) {
+                    Ok(overlay) => Some(overlay),
+                    Err(err) => return Err(InfoError::InputInterpolateError { path: overlay_path, err }),
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(InfoError::InputReadError { path: overlay_path, err }),
+            }
+        } else {
+            None
+        };
+
+        // Parse it using the child function, wrapping the error that may occur
+        let res: Result<Self, InfoError<Self::Error>> = match overlay {
+            Some(overlay) => Self::from_string_with_overlay(raw, overlay),
+            None => Self::from_string(raw),
+        };
+        let mut config: Self = match res {
+            Ok(config) => config,
+            Err(InfoError::StringDeserializeError { err }) => return Err(InfoError::FileDeserializeError { path: path.into(), err }),
+            Err(InfoError::OverlayDeserializeError { err }) => return Err(InfoError::OverlayFileDeserializeError { path: overlay_path, err }),
+            Err(InfoError::UnknownKeysError { keys }) => return Err(InfoError::FileUnknownKeysError { path: path.into(), keys }),
+            Err(err) => return Err(err),
+        };
+
+        // Resolve any secrets it refers to, so missing ones are noticed now
+        if let Err(err) = config.resolve_secrets_async().await {
+            return Err(InfoError::InputSecretError { path: path.into(), err });
         }
+        Ok(config)
+    }
+
+    /// Loads this Config from the file at the given path, and then keeps watching it for changes.
+    ///
+    /// The file (and its overlay file, if any) is polled every `interval` for changes in its modification time or size. If it changed, it is reloaded and the new version is published on the returned [`InfoWatcher`]. Files that fail to load are reported as warnings and otherwise ignored, so services can keep running on the last valid version.
+    ///
+    /// Note that this function spawns a task, and must thus be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    /// - `path`: The path where to read the file from.
+    /// - `interval`: How often to check the file for changes.
+    ///
+    /// # Returns
+    /// An [`InfoWatcher`] that can be used to get the current version of the file or wait for new ones.
+    ///
+    /// # Errors
+    /// This function fails if the initial load of the file failed.
+    async fn watch(path: impl 'static + Send + Into<PathBuf>, interval: Duration) -> Result<InfoWatcher<Self>, InfoError<Self::Error>>
+    where
+        Self: 'static + Send + Sync,
+    {
+        let path: PathBuf = path.into();
+
+        // Load the initial version
+        let overlay: PathBuf = overlay_path(&path);
+        let mut stamp: Option<(SystemTime, u64)> = file_stamp(&path).await;
+        let mut overlay_stamp: Option<(SystemTime, u64)> = file_stamp(&overlay).await;
+        let initial: Self = Self::from_path_async(&path).await?;
+        let (tx, rx): (watch::Sender<Arc<Self>>, watch::Receiver<Arc<Self>>) = watch::channel(Arc::new(initial));
+
+        // Spawn the task that polls it
+        let task_path: PathBuf = path.clone();
+        let handle: JoinHandle<()> = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                // See if the file (or its overlay) has changed
+                let new_stamp: Option<(SystemTime, u64)> = file_stamp(&task_path).await;
+                let new_overlay_stamp: Option<(SystemTime, u64)> = file_stamp(&overlay).await;
+                if new_stamp.is_none() || (new_stamp == stamp && new_overlay_stamp == overlay_stamp) {
+                    continue;
+                }
+                stamp = new_stamp;
+                overlay_stamp = new_overlay_stamp;
+
+                // Reload it
+                debug!("File '{}' changed on disk; reloading...", task_path.display());
+                let config: Self = match Self::from_path_async(&task_path).await {
+                    Ok(config) => config,
+                    Err(err) => {
+                        warn!("Failed to reload changed file '{}' (keeping previous version): {}", task_path.display(), err);
+                        continue;
+                    },
+                };
+                if tx.send(Arc::new(config)).is_err() {
+                    // Nobody is listening anymore
+                    return;
+                }
+            }
+        });
+
+        // Done
+        Ok(InfoWatcher { path, rx, handle: Arc::new(WatchTask(handle)) })
     }
 }
 
 
 
 /// A marker trait that will let the compiler implement `Config` for this object using the `serde_yaml` backend.
-pub trait YamlInfo<'de>: Clone + Debug + Deserialize<'de> + Serialize {}
+pub trait YamlInfo<'de>: Clone + Debug + Deserialize<'de> + Serialize {
+    /// Whether files of this type are written by operators rather than by Brane itself. See [`Info::OPERATOR_AUTHORED`].
+    ///
+    /// By default, `false`.
+    const OPERATOR_AUTHORED: bool = false;
+
+    /// Returns the [`SecretRef`]s in this config. See [`Info::secrets_mut()`].
+    ///
+    /// By default, returns none.
+    ///
+    /// # Returns
+    /// Mutable references to every [`SecretRef`] in this config.
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> { vec![] }
+}
 impl<T: DeserializeOwned + Serialize + for<'de> YamlInfo<'de>> Info for T {
     type Error = serde_yaml::Error;
 
+    const OPERATOR_AUTHORED: bool = <Self as YamlInfo<'static>>::OPERATOR_AUTHORED;
+
     fn to_string(&self, _pretty: bool) -> Result<String, InfoError<Self::Error>> {
         match serde_yaml::to_string(self) {
             Ok(raw) => Ok(raw),
@@ -261,19 +777,138 @@ impl<T: DeserializeOwned + Serialize + for<'de> YamlInfo<'de>> Info for T {
     }
 
     fn from_string(raw: impl AsRef<str>) -> Result<Self, InfoError<Self::Error>> {
-        match serde_yaml::from_str(raw.as_ref()) {
-            Ok(config) => Ok(config),
-            Err(err) => Err(InfoError::StringDeserializeError { err }),
+        let raw: &str = raw.as_ref();
+        let config: Self = match serde_yaml::from_str(raw) {
+            Ok(config) => config,
+            Err(err) => return Err(InfoError::StringDeserializeError { err }),
+        };
+
+        // Parse it generically as well to find keys that the config ignored
+        let input: serde_yaml::Value = match serde_yaml::from_str(raw) {
+            Ok(input) => input,
+            Err(err) => return Err(InfoError::StringDeserializeError { err }),
+        };
+        if let Err(keys) = check_unknown_keys(&config, &input, raw, None) {
+            return Err(InfoError::UnknownKeysError { keys });
         }
+        Ok(config)
     }
 
     fn from_reader(reader: impl Read) -> Result<Self, InfoError<Self::Error>> {
-        match serde_yaml::from_reader(reader) {
-            Ok(config) => Ok(config),
-            Err(err) => Err(InfoError::ReaderDeserializeError { err }),
+        // Parse it generically first, so we can find keys that the config ignored
+        let input: serde_yaml::Value = match serde_yaml::from_reader(reader) {
+            Ok(input) => input,
+            Err(err) => return Err(InfoError::ReaderDeserializeError { err }),
+        };
+        let config: Self = match serde_yaml::from_value(input.clone()) {
+            Ok(config) => config,
+            Err(err) => return Err(InfoError::ReaderDeserializeError { err }),
+        };
+        if let Err(keys) = check_unknown_keys(&config, &input, "", None) {
+            return Err(InfoError::UnknownKeysError { keys });
         }
+        Ok(config)
     }
+
+    fn from_string_with_overlay(raw: impl AsRef<str>, overlay: impl AsRef<str>) -> Result<Self, InfoError<Self::Error>> {
+        // Parse both as generic values first
+        let mut value: serde_yaml::Value = match serde_yaml::from_str(raw.as_ref()) {
+            Ok(value) => value,
+            Err(err) => return Err(InfoError::StringDeserializeError { err }),
+        };
+        let overlay_raw: &str = overlay.as_ref();
+        let overlay: serde_yaml::Value = match serde_yaml::from_str(overlay_raw) {
+            Ok(overlay) => overlay,
+            Err(err) => return Err(InfoError::OverlayDeserializeError { err }),
+        };
+
+        // Merge them, then parse the result as ourselves
+        merge_yaml(&mut value, overlay);
+        let config: Self = match serde_yaml::from_value(value.clone()) {
+            Ok(config) => config,
+            Err(err) => return Err(InfoError::OverlayDeserializeError { err }),
+        };
+        if let Err(keys) = check_unknown_keys(&config, &value, raw.as_ref(), Some(overlay_raw)) {
+            return Err(InfoError::UnknownKeysError { keys });
+        }
+        Ok(config)
+    }
+
+    #[inline]
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> { <Self as YamlInfo<'static>>::secrets_mut(self) }
 }
 
 /// A type alias for the ConfigError for the YamlConfig.
 pub type YamlError = InfoError<serde_yaml::Error>;
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that plain text and lone dollars are left alone
+    #[test]
+    fn interpolateenv_passthrough() {
+        assert_eq!(interpolate_env("port: 50051").unwrap(), "port: 50051");
+        assert_eq!(interpolate_env("cost: $5 and $$").unwrap(), "cost: $5 and $$");
+        assert_eq!(interpolate_env("literal: $${HOME}").unwrap(), "literal: ${HOME}");
+    }
+
+    /// Test that variables and defaults are substituted
+    #[test]
+    fn interpolateenv_substitute() {
+        std::env::set_var("BRANE_CFG_TEST_INTERPOLATE", "8080");
+        assert_eq!(interpolate_env("port: ${BRANE_CFG_TEST_INTERPOLATE}").unwrap(), "port: 8080");
+        assert_eq!(interpolate_env("port: ${BRANE_CFG_TEST_INTERPOLATE:-50051}").unwrap(), "port: 8080");
+        assert_eq!(interpolate_env("port: ${BRANE_CFG_TEST_UNSET:-50051}").unwrap(), "port: 50051");
+        assert_eq!(interpolate_env("path: ${BRANE_CFG_TEST_UNSET:-}").unwrap(), "path: ");
+    }
+
+    /// Test that overlay paths are derived correctly
+    #[test]
+    fn overlaypath_names() {
+        assert_eq!(overlay_path("/config/node.yml"), PathBuf::from("/config/node.override.yml"));
+        assert_eq!(overlay_path("infra.yaml"), PathBuf::from("infra.override.yaml"));
+        assert_eq!(overlay_path("/config/node"), PathBuf::from("/config/node.override"));
+    }
+
+    /// A file as Brane would write it itself.
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    struct MachineWritten {
+        reason: String,
+    }
+    impl<'de> YamlInfo<'de> for MachineWritten {}
+
+    /// Test that files written by Brane itself are read back verbatim, without substituting environment variables
+    #[test]
+    fn frompath_machinewritten_verbatim() {
+        let path: PathBuf = std::env::temp_dir().join(format!("brane-cfg-test-verbatim-{}.yml", std::process::id()));
+        let file = MachineWritten { reason: "costs $5 and ${BRANE_CFG_TEST_UNSET}".into() };
+        file.to_path(&path).unwrap();
+        let read: Result<MachineWritten, _> = MachineWritten::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.unwrap().reason, file.reason);
+    }
+
+    /// Test that YAML values are deep-merged
+    #[test]
+    fn mergeyaml_deep() {
+        let mut base: serde_yaml::Value = serde_yaml::from_str("a:\n  b: 1\n  c: [1, 2]\nd: foo\n").unwrap();
+        let overlay: serde_yaml::Value = serde_yaml::from_str("a:\n  c: [3]\n  e: true\n").unwrap();
+        merge_yaml(&mut base, overlay);
+        let expected: serde_yaml::Value = serde_yaml::from_str("a:\n  b: 1\n  c: [3]\n  e: true\nd: foo\n").unwrap();
+        assert_eq!(base, expected);
+    }
+
+    /// Test that malformed or unresolvable references fail
+    #[test]
+    fn interpolateenv_errors() {
+        assert!(matches!(interpolate_env("port: ${BRANE_CFG_TEST_UNSET}"), Err(InterpolateError::UnsetVariable { .. })));
+        assert!(matches!(interpolate_env("port: ${BRANE_CFG_TEST_UNSET"), Err(InterpolateError::UnterminatedReference { pos: 6 })));
+        assert!(matches!(interpolate_env("port: ${:-5}"), Err(InterpolateError::EmptyReference { pos: 6 })));
+    }
+}
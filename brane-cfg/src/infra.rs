@@ -4,7 +4,7 @@
 //  Created:
 //    04 Oct 2022, 11:04:33
 //  Last edited:
-//    31 Jan 2024, 15:53:29
+//    16 Oct 2026, 02:28:32
 //  Auto updated?
 //    Yes
 //
@@ -12,16 +12,61 @@
 //!   Implements a more up-to-date version of the infrastructure document.
 //
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
+use specifications::package::{Capability, PackageResources};
 
 pub use crate::info::YamlError as Error;
 use crate::info::YamlInfo;
 
 
 /***** AUXILLARY *****/
+/// Defines the (self-declared) resources of a location, which the planner may use to decide where to run tasks.
+///
+/// Any field that is omitted is treated as "unknown", and will not be used to exclude the location.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LocationCapabilities {
+    /// The number of GPUs available at the location.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<usize>,
+    /// The amount of memory available to a single task, in megabytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<u64>,
+    /// The kind of storage backing the location's datasets and results (e.g., `ssd`, `hdd`, `nfs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+}
+impl LocationCapabilities {
+    /// Returns whether these capabilities (may) satisfy the given package requirements and minimum resources.
+    ///
+    /// # Arguments
+    /// - `requirements`: The requirements of the package to check.
+    /// - `resources`: The minimum resources the package needs, if it declared any.
+    ///
+    /// # Returns
+    /// False if these capabilities are known to _not_ satisfy the requirements or resources, or true otherwise.
+    pub fn satisfies(&self, requirements: &HashSet<Capability>, resources: Option<&PackageResources>) -> bool {
+        let meets = |have: Option<u64>, need: Option<u64>| match (have, need) {
+            (Some(have), Some(need)) => have >= need,
+            _ => true,
+        };
+        let capable: bool = requirements.iter().all(|req| match req {
+            Capability::CudaGpu => self.gpus.map(|gpus| gpus > 0).unwrap_or(true),
+        });
+        let resourced: bool =
+            resources.map(|res| meets(self.gpus.map(|gpus| gpus as u64), res.gpus.map(u64::from)) && meets(self.memory, res.memory)).unwrap_or(true);
+        let stored: bool = match (&self.storage_class, resources.and_then(|res| res.storage_class.as_ref())) {
+            (Some(have), Some(need)) => have == need,
+            _ => true,
+        };
+        capable && resourced && stored
+    }
+}
+
+
+
 /// Defines a single Location in the InfraFile.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InfraLocation {
@@ -31,6 +76,17 @@ pub struct InfraLocation {
     pub delegate: Address,
     /// The address of the local registry to query for locally available packages, datasets and more.
     pub registry: Address,
+
+    /// Arbitrary key/value labels for this location (e.g., `region: eu-west`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+    /// The residency zones that this location is part of (e.g., `eu-only` or `on-prem`). Datasets restricted to a zone are never planned to be
+    /// processed on locations outside of it.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub zones: HashSet<String>,
+    /// The structured capabilities of this location.
+    #[serde(default)]
+    pub capabilities: LocationCapabilities,
 }
 
 
@@ -83,7 +139,9 @@ impl InfraFile {
     #[inline]
     pub fn is_empty(&self) -> bool { self.locations.len() == 0 }
 }
-impl<'de> YamlInfo<'de> for InfraFile {}
+impl<'de> YamlInfo<'de> for InfraFile {
+    const OPERATOR_AUTHORED: bool = true;
+}
 
 impl IntoIterator for InfraFile {
     type IntoIter = std::collections::hash_map::IntoIter<String, InfraLocation>;
@@ -106,3 +164,41 @@ impl<'a> IntoIterator for &'a mut InfraFile {
     #[inline]
     fn into_iter(self) -> Self::IntoIter { self.locations.iter_mut() }
 }
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that locations are only excluded by capabilities they are known to lack
+    #[test]
+    fn locationcapabilities_satisfies() {
+        let gpu: HashSet<Capability> = HashSet::from([Capability::CudaGpu]);
+        let res = PackageResources { cpus: None, memory: Some(4096), disk: None, gpus: Some(2), storage_class: None };
+
+        // Unknown capabilities never exclude
+        let unknown = LocationCapabilities::default();
+        assert!(unknown.satisfies(&gpu, Some(&res)));
+
+        // Known ones do
+        let small = LocationCapabilities { gpus: Some(1), memory: Some(2048), storage_class: None };
+        assert!(small.satisfies(&gpu, None));
+        assert!(!small.satisfies(&gpu, Some(&res)));
+        assert!(!small.satisfies(&HashSet::new(), Some(&PackageResources { memory: Some(4096), ..Default::default() })));
+        assert!(!LocationCapabilities { gpus: Some(0), memory: None, storage_class: None }.satisfies(&gpu, None));
+
+        let big = LocationCapabilities { gpus: Some(4), memory: Some(8192), storage_class: Some("ssd".into()) };
+        assert!(big.satisfies(&gpu, Some(&res)));
+
+        // Storage classes must match exactly, if both are known
+        let ssd = PackageResources { storage_class: Some("ssd".into()), ..Default::default() };
+        let nfs = PackageResources { storage_class: Some("nfs".into()), ..Default::default() };
+        assert!(big.satisfies(&HashSet::new(), Some(&ssd)));
+        assert!(!big.satisfies(&HashSet::new(), Some(&nfs)));
+        assert!(small.satisfies(&HashSet::new(), Some(&nfs)));
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    04 Oct 2022, 11:08:37
 //  Last edited:
-//    28 Jun 2023, 19:41:54
+//    16 Oct 2026, 01:29:42
 //  Auto updated?
 //    Yes
 //
@@ -14,11 +14,17 @@
 //
 
 // Declare modules
+pub mod approvals;
 pub mod backend;
 pub mod certs;
+pub mod enrollment;
 pub mod errors;
 pub mod info;
 pub mod infra;
 pub mod node;
+pub mod peers;
 pub mod policies;
 pub mod proxy;
+pub mod secrets;
+pub mod strict;
+pub mod tokens;
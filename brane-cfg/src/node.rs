@@ -4,7 +4,7 @@
 //  Created:
 //    28 Feb 2023, 10:01:27
 //  Last edited:
-//    07 Mar 2024, 09:52:57
+//    16 Oct 2026, 02:38:59
 //  Auto updated?
 //    Yes
 //
@@ -18,14 +18,18 @@ use std::fmt::{Display, Formatter, Result as FResult};
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
+use brane_shr::events::EventPublisher;
 use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
+use specifications::events::DEFAULT_EVENTS_TOPIC;
 
 pub use crate::errors::NodeConfigError as Error;
 use crate::errors::NodeKindParseError;
 use crate::info::YamlInfo;
+use crate::secrets::SecretRef;
 
 
 /***** AUXILLARY *****/
@@ -79,8 +83,105 @@ pub struct NodeConfig {
 
     /// Any node-specific config
     pub node: NodeSpecificConfig,
+
+    /// Defines where the services on this node export their traces to, if anywhere.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracing: Option<TracingConfig>,
+    /// Defines the event bus that the services on this node publish their audit events on, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events:  Option<EventsConfig>,
+
+    /// The passphrase of any encrypted private keys in the node's certificate directories. If omitted, it is read from the
+    /// `BRANE_KEY_PASSPHRASE` or `BRANE_KEY_PASSPHRASE_FILE` environment variables instead (see [`crate::certs::key_passphrase()`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_passphrase: Option<SecretRef>,
+}
+impl<'de> YamlInfo<'de> for NodeConfig {
+    const OPERATOR_AUTHORED: bool = true;
+
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> {
+        let mut secrets: Vec<&mut SecretRef> = self.key_passphrase.iter_mut().collect();
+        if let NodeSpecificConfig::Worker(WorkerConfig { policy_expert_token: Some(token), .. }) = &mut self.node {
+            secrets.push(token);
+        }
+        secrets
+    }
+}
+
+
+
+/// Defines the OpenTelemetry collector that the services of a node export their traces to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TracingConfig {
+    /// The base address of the OTLP/HTTP endpoint of the collector (e.g., `http://jaeger:4318`). Spans are sent to its `/v1/traces` path.
+    pub endpoint: String,
+    /// Any additional headers to send along with the spans (e.g., for authentication).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers:  HashMap<String, String>,
+}
+impl TracingConfig {
+    /// Starts exporting the spans of the calling service to the collector described by this config.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// - `service`: The name of the calling service (e.g., `brane-drv`).
+    #[inline]
+    pub fn init(&self, service: &str) { specifications::telemetry::init(service, &self.endpoint, self.headers.clone()) }
+}
+
+
+
+/// Defines the event bus that the services of a node publish their audit events on.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventsConfig {
+    /// The kind of event bus and how to reach it.
+    #[serde(flatten)]
+    pub broker: EventBroker,
+    /// The topic (Kafka) or subject (NATS) to publish the events on.
+    #[serde(default = "EventsConfig::default_topic")]
+    pub topic:  String,
+}
+impl EventsConfig {
+    /// Returns the default topic to publish on.
+    #[inline]
+    fn default_topic() -> String { DEFAULT_EVENTS_TOPIC.into() }
+
+    /// Creates a publisher that publishes the events of the calling service on the event bus described by this config.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// - `source`: The name of the calling service and node (e.g., `brane-job@hospital_a`).
+    ///
+    /// # Returns
+    /// A new [`EventPublisher`].
+    ///
+    /// # Errors
+    /// This function errors if we failed to connect to the event bus.
+    pub async fn publisher(&self, source: impl Into<String>) -> Result<EventPublisher, brane_shr::events::Error> {
+        match &self.broker {
+            EventBroker::Kafka { brokers } => EventPublisher::kafka(source, brokers, &self.topic),
+            EventBroker::Nats { address } => EventPublisher::nats(source, address, &self.topic).await,
+        }
+    }
+}
+
+/// Defines the kinds of event bus supported.
+#[derive(Clone, Debug, Deserialize, EnumDebug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EventBroker {
+    /// A Kafka cluster.
+    Kafka {
+        /// The (comma-separated list of) brokers to publish to (e.g., `kafka:9092`).
+        brokers: String,
+    },
+    /// A NATS server.
+    Nats {
+        /// The address of the server (e.g., `nats://nats:4222`).
+        address: String,
+    },
 }
-impl<'de> YamlInfo<'de> for NodeConfig {}
 
 
 
@@ -403,6 +504,174 @@ pub struct WorkerConfig {
     pub paths:    WorkerPaths,
     /// Defines the services for this node.
     pub services: WorkerServices,
+
+    /// Defines the maximum number of tasks that this node executes simultaneously. Any more are queued until one of the running tasks completes.
+    ///
+    /// If omitted, defaults to the number of CPUs available on this host.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_tasks: Option<usize>,
+    /// Defines the maximum time (in seconds) that a task may run. After that, it is terminated (and eventually killed) and fails with a timeout.
+    ///
+    /// If omitted, tasks may run indefinitely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_timeout: Option<u64>,
+    /// Defines whether to keep idle containers of recently executed packages around, such that calling the same package again does not have to
+    /// start a new container.
+    ///
+    /// If omitted, every task is executed in a fresh container.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_pool: Option<ContainerPoolConfig>,
+    /// Defines how often (and how patiently) dataset transfers from other domains are retried when they fail halfway.
+    ///
+    /// If omitted, transfers are attempted a few times with an exponential backoff (see [`TransferRetryConfig`]'s defaults).
+    #[serde(default)]
+    pub transfer_retry: TransferRetryConfig,
+    /// Defines whether downloads of sensitive intermediate results must be approved by a data steward first (see `branectl approvals`).
+    ///
+    /// If omitted, downloads are only checked by the checker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress_approval: Option<EgressApprovalConfig>,
+    /// Defines the token with which `branectl policies` authenticates with the checker's policy expert API. See [`SecretRef`] for how to avoid
+    /// writing it down in plaintext.
+    ///
+    /// If omitted, a short-lived token is generated from the secret at `paths.policy_expert_secret` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_expert_token: Option<SecretRef>,
+}
+impl WorkerConfig {
+    /// Returns the maximum number of tasks to execute simultaneously, resolving it to the number of CPUs available if the user didn't specify it.
+    ///
+    /// # Returns
+    /// The number of tasks that may run at the same time. Is always at least 1.
+    #[inline]
+    pub fn max_concurrent_tasks(&self) -> usize {
+        self.max_concurrent_tasks.unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)).max(1)
+    }
+}
+
+/// Defines how the worker keeps idle containers around to execute tasks in.
+///
+/// Containers are only reused for calls of the same user to the same package that read the same datasets, and their results are moved out of
+/// the container's workspace after every call. Anything else a call leaves behind in a container is visible to later calls of that user.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ContainerPoolConfig {
+    /// The maximum number of idle containers to keep per package (and set of inputs).
+    #[serde(default = "ContainerPoolConfig::default_max_idle")]
+    pub max_idle:     usize,
+    /// The maximum number of tasks executed in a single container before it is replaced by a fresh one.
+    #[serde(default = "ContainerPoolConfig::default_max_uses")]
+    pub max_uses:     usize,
+    /// The time (in seconds) after which an idle container is removed if it has not been used.
+    #[serde(default = "ContainerPoolConfig::default_idle_timeout")]
+    pub idle_timeout: u64,
+}
+impl Default for ContainerPoolConfig {
+    #[inline]
+    fn default() -> Self {
+        Self { max_idle: Self::default_max_idle(), max_uses: Self::default_max_uses(), idle_timeout: Self::default_idle_timeout() }
+    }
+}
+impl ContainerPoolConfig {
+    /// Returns the default maximum number of idle containers per package.
+    #[inline]
+    fn default_max_idle() -> usize { 4 }
+
+    /// Returns the default maximum number of tasks per container.
+    #[inline]
+    fn default_max_uses() -> usize { 100 }
+
+    /// Returns the default time after which idle containers are removed.
+    #[inline]
+    fn default_idle_timeout() -> u64 { 300 }
+}
+
+/// Defines how the worker retries dataset transfers that fail because of (presumably) transient network problems.
+///
+/// The backoff between attempts starts at `backoff` and doubles after every failed attempt, up to `max_backoff`. Transfers that fail because
+/// the other domain refused them (e.g., by policy) are never retried.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransferRetryConfig {
+    /// The maximum number of times a transfer is attempted in total. `1` disables retrying.
+    #[serde(default = "TransferRetryConfig::default_max_attempts")]
+    pub max_attempts: u32,
+    /// The time (in milliseconds) to wait before the first retry.
+    #[serde(default = "TransferRetryConfig::default_backoff")]
+    pub backoff:      u64,
+    /// The maximum time (in milliseconds) to wait between two attempts.
+    #[serde(default = "TransferRetryConfig::default_max_backoff")]
+    pub max_backoff:  u64,
+}
+impl Default for TransferRetryConfig {
+    #[inline]
+    fn default() -> Self {
+        Self { max_attempts: Self::default_max_attempts(), backoff: Self::default_backoff(), max_backoff: Self::default_max_backoff() }
+    }
+}
+impl TransferRetryConfig {
+    /// Returns the time to wait before the given retry.
+    ///
+    /// # Arguments
+    /// - `retry`: The number of the retry, where `1` is the first retry (i.e., the second attempt).
+    ///
+    /// # Returns
+    /// The [`Duration`] to wait before starting it.
+    pub fn backoff_for(&self, retry: u32) -> Duration {
+        let backoff: u64 = self.backoff.saturating_mul(1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX));
+        Duration::from_millis(backoff.min(self.max_backoff))
+    }
+
+    /// Returns the default maximum number of attempts per transfer.
+    #[inline]
+    fn default_max_attempts() -> u32 { 3 }
+
+    /// Returns the default backoff before the first retry.
+    #[inline]
+    fn default_backoff() -> u64 { 1000 }
+
+    /// Returns the default maximum backoff between attempts.
+    #[inline]
+    fn default_max_backoff() -> u64 { 30000 }
+}
+
+/// Defines which downloads of intermediate results the registry parks until a data steward approves them.
+///
+/// The sensitivity of a result is the highest level of the sensitivity tags of the datasets of this domain that it was (transitively) computed
+/// from, where a tag like `pii:email` also has the level of its family `pii`. Tags without a level have level 0.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EgressApprovalConfig {
+    /// The directory in which the registry parks the downloads awaiting approval.
+    pub path:      PathBuf,
+    /// The sensitivity level of every tag (e.g., `pii: 2` or `medical: 3`).
+    #[serde(default)]
+    pub levels:    HashMap<String, u32>,
+    /// Downloads of results with at least this sensitivity need approval.
+    pub threshold: u32,
+}
+impl EgressApprovalConfig {
+    /// Computes the sensitivity of data with the given tags.
+    ///
+    /// # Arguments
+    /// - `tags`: The sensitivity tags of the data.
+    ///
+    /// # Returns
+    /// The highest level of any of the tags (or of their family), or 0 if none has a level.
+    pub fn sensitivity<'t>(&self, tags: impl IntoIterator<Item = &'t str>) -> u32 {
+        tags.into_iter()
+            .filter_map(|tag| self.levels.get(tag).or_else(|| tag.split_once(':').and_then(|(family, _)| self.levels.get(family))))
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns whether data with the given tags needs approval before it may leave the domain.
+    ///
+    /// # Arguments
+    /// - `tags`: The sensitivity tags of the data.
+    ///
+    /// # Returns
+    /// True if its sensitivity is at or above the threshold, or false otherwise.
+    #[inline]
+    pub fn needs_approval<'t>(&self, tags: impl IntoIterator<Item = &'t str>) -> bool { self.sensitivity(tags) >= self.threshold }
 }
 
 /// Defines everything we need to know based on a use-case identifier.
@@ -0,0 +1,50 @@
+//  PEERS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:15:09
+//  Last edited:
+//    16 Oct 2026, 02:15:34
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the file with the peer instances of a central registry,
+//!   whose packages and datasets `brane-api` includes in its federated
+//!   view.
+//
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use specifications::address::Address;
+
+pub use crate::info::YamlError as Error;
+use crate::info::YamlInfo;
+
+
+/***** LIBRARY *****/
+/// Defines a single peer instance.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerInfo {
+    /// The address of the peer's API service (i.e., its central registry).
+    pub api: Address,
+}
+
+/// Defines the file with peer instances.
+///
+/// For example:
+/// ```yaml
+/// peers:
+///   partner:
+///     api: https://brane.partner.org:50051
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PeersFile {
+    /// The peers, by the name under which their packages and datasets are namespaced.
+    #[serde(default)]
+    pub peers: HashMap<String, PeerInfo>,
+}
+impl<'de> YamlInfo<'de> for PeersFile {
+    const OPERATOR_AUTHORED: bool = true;
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    01 Dec 2022, 09:20:32
 //  Last edited:
-//    27 Mar 2023, 11:45:02
+//    16 Oct 2026, 02:15:34
 //  Auto updated?
 //    Yes
 //
@@ -29,7 +29,9 @@ pub struct PolicyFile {
     /// The containers to allow
     pub containers: Vec<ContainerPolicy>,
 }
-impl<'de> YamlInfo<'de> for PolicyFile {}
+impl<'de> YamlInfo<'de> for PolicyFile {
+    const OPERATOR_AUTHORED: bool = true;
+}
 
 
 
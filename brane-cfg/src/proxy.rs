@@ -4,16 +4,18 @@
 //  Created:
 //    09 Mar 2023, 15:15:47
 //  Last edited:
-//    16 Mar 2023, 15:39:53
+//    16 Oct 2026, 02:16:30
 //  Auto updated?
 //    Yes
 //
 //  Description:
-//!   Defines the configuration file for additional incoming proxy rules.
+//!   Defines the configuration file for additional incoming proxy rules,
+//!   and for which destinations outgoing paths may be opened to.
 //
 
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::IpAddr;
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 
@@ -23,9 +25,10 @@ use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
 
-use crate::errors::ProxyProtocolParseError;
+use crate::errors::{DestinationRuleParseError, ProxyProtocolParseError};
 pub use crate::info::YamlError as Error;
 use crate::info::YamlInfo;
+use crate::secrets::SecretRef;
 
 
 /***** AUXILLARY *****/
@@ -97,6 +100,240 @@ impl<'de> Deserialize<'de> for ProxyProtocol {
 
 
 
+/// Defines the host part of a [`DestinationRule`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HostPattern {
+    /// Matches any host.
+    Any,
+    /// Matches exactly this hostname (case-insensitive).
+    Domain(String),
+    /// Matches any subdomain of this domain (case-insensitive), but not the domain itself.
+    Subdomains(String),
+    /// Matches any IP address in this network (or a single address, if the prefix covers the whole address).
+    Cidr(IpAddr, u8),
+}
+impl HostPattern {
+    /// Checks whether the given IP address is part of this pattern's network.
+    ///
+    /// # Arguments
+    /// - `ip`: The IP address to check.
+    ///
+    /// # Returns
+    /// True if this is a [`HostPattern::Cidr`] that contains `ip`, or false otherwise (including if their families differ).
+    fn contains_ip(&self, ip: &IpAddr) -> bool {
+        match (self, ip) {
+            (Self::Cidr(IpAddr::V4(net), prefix), IpAddr::V4(ip)) => {
+                let mask: u32 = if *prefix == 0 { 0 } else { u32::MAX << (32 - *prefix as u32) };
+                (u32::from(*net) & mask) == (u32::from(*ip) & mask)
+            },
+            (Self::Cidr(IpAddr::V6(net), prefix), IpAddr::V6(ip)) => {
+                let mask: u128 = if *prefix == 0 { 0 } else { u128::MAX << (128 - *prefix as u32) };
+                (u128::from(*net) & mask) == (u128::from(*ip) & mask)
+            },
+            _ => false,
+        }
+    }
+}
+impl Display for HostPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use HostPattern::*;
+        match self {
+            Any => write!(f, "*"),
+            Domain(domain) => write!(f, "{domain}"),
+            Subdomains(domain) => write!(f, "*.{domain}"),
+            Cidr(ip, prefix) => {
+                if (ip.is_ipv4() && *prefix == 32) || (ip.is_ipv6() && *prefix == 128) {
+                    write!(f, "{ip}")
+                } else {
+                    write!(f, "{ip}/{prefix}")
+                }
+            },
+        }
+    }
+}
+impl FromStr for HostPattern {
+    type Err = DestinationRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(DestinationRuleParseError::MissingHost { raw: s.into() });
+        }
+        if s == "*" {
+            return Ok(Self::Any);
+        }
+        if let Some(domain) = s.strip_prefix("*.") {
+            return Ok(Self::Subdomains(domain.to_lowercase()));
+        }
+
+        // Parse it as an address if it has a prefix or looks like one
+        let (addr, prefix): (&str, Option<&str>) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let ip: IpAddr = match IpAddr::from_str(addr) {
+            Ok(ip) => ip,
+            Err(err) => {
+                if prefix.is_some() {
+                    return Err(DestinationRuleParseError::IllegalCidrAddress { raw: s.into(), err });
+                }
+                return Ok(Self::Domain(s.to_lowercase()));
+            },
+        };
+        let max: u8 = if ip.is_ipv4() { 32 } else { 128 };
+        let prefix: u8 = match prefix {
+            Some(prefix) => match u8::from_str(prefix) {
+                Ok(prefix) if prefix <= max => prefix,
+                _ => return Err(DestinationRuleParseError::IllegalCidrPrefix { raw: s.into() }),
+            },
+            None => max,
+        };
+        Ok(Self::Cidr(ip, prefix))
+    }
+}
+
+
+
+/// Defines a single rule in the [`DestinationRules`], which matches a host and (optionally) a range of ports.
+///
+/// It is written as `<HOST>[:<PORTS>]`, where `<HOST>` is a hostname, `*.<DOMAIN>` for any of its subdomains, an IP address, a CIDR (e.g., `10.0.0.0/8`) or `*` for any host.
+/// `<PORTS>` is either a single port or an inclusive range (e.g., `8000-8100`). If omitted, the rule matches any port. IPv6 hosts must be wrapped in brackets when a port is given (e.g., `[fd00::/8]:443`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DestinationRule {
+    /// The host(s) matched by this rule.
+    pub host:  HostPattern,
+    /// The port(s) matched by this rule, or [`None`] to match any.
+    pub ports: Option<RangeInclusive<u16>>,
+}
+impl DestinationRule {
+    /// Checks whether this rule matches the given destination.
+    ///
+    /// # Arguments
+    /// - `host`: The host (hostname or IP address) of the destination, as given by the requester.
+    /// - `ips`: The IP addresses that `host` resolves to. Only relevant for CIDR rules; may be empty if `host` is an IP address itself.
+    /// - `port`: The port of the destination.
+    ///
+    /// # Returns
+    /// True if the destination matches this rule, or false otherwise.
+    pub fn matches(&self, host: &str, ips: &[IpAddr], port: u16) -> bool {
+        if let Some(ports) = &self.ports {
+            if !ports.contains(&port) {
+                return false;
+            }
+        }
+        let host: String = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+        match &self.host {
+            HostPattern::Any => true,
+            HostPattern::Domain(domain) => host == *domain,
+            HostPattern::Subdomains(domain) => {
+                host.len() > domain.len() + 1 && host.ends_with(domain.as_str()) && host[..host.len() - domain.len()].ends_with('.')
+            },
+            HostPattern::Cidr(_, _) => match IpAddr::from_str(&host) {
+                Ok(ip) => self.host.contains_ip(&ip),
+                Err(_) => ips.iter().any(|ip| self.host.contains_ip(ip)),
+            },
+        }
+    }
+}
+impl Display for DestinationRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let host: String = match &self.host {
+            HostPattern::Cidr(IpAddr::V6(_), _) if self.ports.is_some() => format!("[{}]", self.host),
+            host => host.to_string(),
+        };
+        match &self.ports {
+            Some(ports) if ports.start() == ports.end() => write!(f, "{}:{}", host, ports.start()),
+            Some(ports) => write!(f, "{}:{}-{}", host, ports.start(), ports.end()),
+            None => write!(f, "{host}"),
+        }
+    }
+}
+impl FromStr for DestinationRule {
+    type Err = DestinationRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split the host and the ports
+        let (host, ports): (&str, Option<&str>) = if let Some(rest) = s.strip_prefix('[') {
+            let (host, rest): (&str, &str) = match rest.split_once(']') {
+                Some(res) => res,
+                None => return Err(DestinationRuleParseError::UnclosedBracket { raw: s.into() }),
+            };
+            if rest.is_empty() {
+                (host, None)
+            } else if let Some(ports) = rest.strip_prefix(':') {
+                (host, Some(ports))
+            } else {
+                return Err(DestinationRuleParseError::TrailingCharacters { raw: s.into() });
+            }
+        } else if s.matches(':').count() == 1 {
+            let (host, ports): (&str, &str) = s.split_once(':').unwrap();
+            (host, Some(ports))
+        } else {
+            // Either no port or an unbracketed IPv6 address
+            (s, None)
+        };
+
+        // Parse them
+        let host: HostPattern = HostPattern::from_str(host)?;
+        let ports: Option<RangeInclusive<u16>> = match ports {
+            Some(ports) => {
+                let (start, end): (&str, &str) = ports.split_once('-').unwrap_or((ports, ports));
+                let start: u16 = match u16::from_str(start) {
+                    Ok(start) => start,
+                    Err(err) => return Err(DestinationRuleParseError::IllegalPort { raw: start.into(), err }),
+                };
+                let end: u16 = match u16::from_str(end) {
+                    Ok(end) => end,
+                    Err(err) => return Err(DestinationRuleParseError::IllegalPort { raw: end.into(), err }),
+                };
+                if end < start {
+                    return Err(DestinationRuleParseError::EmptyPortRange { raw: ports.into() });
+                }
+                Some(start..=end)
+            },
+            None => None,
+        };
+        Ok(Self { host, ports })
+    }
+}
+impl Serialize for DestinationRule {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+impl<'de> Deserialize<'de> for DestinationRule {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        /// Visitor for the DestinationRule.
+        struct DestinationRuleVisitor;
+        impl<'de> Visitor<'de> for DestinationRuleVisitor {
+            type Value = DestinationRule;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "a destination rule ('<HOST>[:<PORTS>]')") }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match DestinationRule::from_str(v) {
+                    Ok(rule) => Ok(rule),
+                    Err(err) => Err(E::custom(err)),
+                }
+            }
+        }
+
+        // Call the visitor
+        deserializer.deserialize_str(DestinationRuleVisitor)
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -113,6 +350,10 @@ pub struct ProxyConfig {
 
     /// Whether we have to forward our traffic through some external proxy.
     pub forward: Option<ForwardConfig>,
+
+    /// Restricts the destinations that outgoing paths may be opened to.
+    #[serde(default, skip_serializing_if = "DestinationRules::is_empty")]
+    pub destinations: DestinationRules,
 }
 impl Default for ProxyConfig {
     fn default() -> Self {
@@ -121,10 +362,71 @@ impl Default for ProxyConfig {
             incoming: HashMap::new(),
 
             forward: None,
+
+            destinations: DestinationRules::default(),
+        }
+    }
+}
+impl<'de> YamlInfo<'de> for ProxyConfig {
+    const OPERATOR_AUTHORED: bool = true;
+
+    fn secrets_mut(&mut self) -> Vec<&mut SecretRef> {
+        match &mut self.forward {
+            Some(ForwardConfig { credentials: Some(credentials), .. }) => vec![&mut credentials.password],
+            _ => vec![],
+        }
+    }
+}
+
+
+
+/// Defines which destinations `brane-prx` may open outgoing paths to.
+///
+/// A destination is refused if it matches any of the `deny` rules. Otherwise, it is accepted if there are no `allow` rules or if it matches any of them.
+///
+/// For example:
+/// ```yaml
+/// destinations:
+///   allow:
+///   - "*.surf.nl:443"
+///   - 10.0.0.0/8:50051-50053
+///   deny:
+///   - 10.0.0.13
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DestinationRules {
+    /// If non-empty, only destinations matching any of these rules are accepted.
+    #[serde(default)]
+    pub allow: Vec<DestinationRule>,
+    /// Destinations matching any of these rules are refused, even if they are allowed.
+    #[serde(default)]
+    pub deny:  Vec<DestinationRule>,
+}
+impl DestinationRules {
+    /// Returns whether there are no rules at all (i.e., any destination is accepted).
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.allow.is_empty() && self.deny.is_empty() }
+
+    /// Returns whether any of the rules is a CIDR, meaning that hostnames have to be resolved before calling [`DestinationRules::allows()`].
+    #[inline]
+    pub fn needs_resolve(&self) -> bool { self.allow.iter().chain(self.deny.iter()).any(|rule| matches!(rule.host, HostPattern::Cidr(_, _))) }
+
+    /// Checks whether the given destination is accepted by these rules.
+    ///
+    /// # Arguments
+    /// - `host`: The host (hostname or IP address) of the destination, as given by the requester.
+    /// - `ips`: The IP addresses that `host` resolves to (see [`DestinationRules::needs_resolve()`]).
+    /// - `port`: The port of the destination.
+    ///
+    /// # Returns
+    /// True if an outgoing path to the destination may be opened, or false otherwise.
+    pub fn allows(&self, host: &str, ips: &[IpAddr], port: u16) -> bool {
+        if self.deny.iter().any(|rule| rule.matches(host, ips, port)) {
+            return false;
         }
+        self.allow.is_empty() || self.allow.iter().any(|rule| rule.matches(host, ips, port))
     }
 }
-impl<'de> YamlInfo<'de> for ProxyConfig {}
 
 
 
@@ -132,7 +434,109 @@ impl<'de> YamlInfo<'de> for ProxyConfig {}
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ForwardConfig {
     /// The address of the proxy to proxy itself.
-    pub address:  Address,
+    pub address:     Address,
     /// The protocol that we use to communicate to the proxy.
-    pub protocol: ProxyProtocol,
+    pub protocol:    ProxyProtocol,
+    /// The credentials to authenticate with at the proxy, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<ForwardCredentials>,
+}
+
+/// Defines the username/password pair used to authenticate with a forwarding proxy.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForwardCredentials {
+    /// The username to authenticate with.
+    pub username: String,
+    /// The password to authenticate with. See [`SecretRef`] for how to avoid writing it down in plaintext.
+    pub password: SecretRef,
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that rules are parsed and printed in the same form
+    #[test]
+    fn destinationrule_roundtrip() {
+        for raw in ["*", "example.com", "*.example.com:443", "10.0.0.0/8", "10.0.0.13:8000-8100", "fd00::/8", "[fd00::/8]:443"] {
+            assert_eq!(DestinationRule::from_str(raw).unwrap().to_string(), raw);
+        }
+        assert!(DestinationRule::from_str("").is_err());
+        assert!(DestinationRule::from_str("10.0.0.0/33").is_err());
+        assert!(DestinationRule::from_str("example.com:8100-8000").is_err());
+        assert!(DestinationRule::from_str("[fd00::/8").is_err());
+    }
+
+    /// Test that rules match the hosts, addresses and ports they should
+    #[test]
+    fn destinationrule_matches() {
+        let rule = DestinationRule::from_str("*.example.com:443").unwrap();
+        assert!(rule.matches("api.example.com", &[], 443));
+        assert!(rule.matches("API.Example.com", &[], 443));
+        assert!(!rule.matches("example.com", &[], 443));
+        assert!(!rule.matches("badexample.com", &[], 443));
+        assert!(!rule.matches("api.example.com", &[], 80));
+
+        let rule = DestinationRule::from_str("10.0.0.0/8").unwrap();
+        assert!(rule.matches("10.1.2.3", &[], 1234));
+        assert!(!rule.matches("11.1.2.3", &[], 1234));
+        assert!(rule.matches("internal.example.com", &["10.4.5.6".parse().unwrap()], 1234));
+        assert!(!rule.matches("internal.example.com", &[], 1234));
+
+        let rule = DestinationRule::from_str("[fd00::/8]:50051-50053").unwrap();
+        assert!(rule.matches("[fd12::1]", &[], 50052));
+        assert!(!rule.matches("[fe80::1]", &[], 50052));
+    }
+
+    /// Test that deny rules take precedence over allow rules
+    #[test]
+    fn destinationrules_allows() {
+        let rules = DestinationRules::default();
+        assert!(rules.allows("anywhere.org", &[], 80));
+
+        let rules = DestinationRules {
+            allow: vec![DestinationRule::from_str("10.0.0.0/8").unwrap()],
+            deny:  vec![DestinationRule::from_str("10.0.0.13").unwrap()],
+        };
+        assert!(rules.needs_resolve());
+        assert!(rules.allows("10.0.0.12", &[], 80));
+        assert!(!rules.allows("10.0.0.13", &[], 80));
+        assert!(!rules.allows("anywhere.org", &["93.184.216.34".parse().unwrap()], 80));
+    }
+
+    /// Test that secrets are resolved when the file is loaded, and that missing ones fail it
+    #[test]
+    fn proxyconfig_loads_secrets() {
+        use crate::info::{Info as _, InfoError};
+
+        let dir: std::path::PathBuf = std::env::temp_dir().join(format!("brane-cfg-proxy-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let secret_path: std::path::PathBuf = dir.join("password");
+        let config_path: std::path::PathBuf = dir.join("proxy.yml");
+        let config = |password: &str| {
+            format!(
+                "outgoing_range:\n  start: 4200\n  end: 4299\nforward:\n  address: proxy.example.com:1080\n  protocol: socks5\n  credentials:\n    \
+                 username: brane\n    password: {password}\n"
+            )
+        };
+
+        // A file that exists is resolved immediately
+        std::fs::write(&secret_path, "hunter2\n").unwrap();
+        std::fs::write(&config_path, config(&format!("{{ file: {} }}", secret_path.display()))).unwrap();
+        let proxy: ProxyConfig = ProxyConfig::from_path(&config_path).unwrap();
+        assert_eq!(proxy.forward.unwrap().credentials.unwrap().password.loaded(), Some("hunter2"));
+
+        // A missing file or environment variable fails loading
+        std::fs::remove_file(&secret_path).unwrap();
+        assert!(matches!(ProxyConfig::from_path(&config_path), Err(InfoError::InputSecretError { .. })));
+        std::fs::write(&config_path, config("{ env: BRANE_CFG_TEST_UNSET_PROXY_PASSWORD }")).unwrap();
+        assert!(matches!(ProxyConfig::from_path(&config_path), Err(InfoError::InputSecretError { .. })));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,403 @@
+//  SECRETS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 11:14:20
+//  Last edited:
+//    16 Oct 2026, 02:16:30
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines [`SecretRef`]s, which can be used in configuration files to
+//!   refer to credentials stored elsewhere (the environment, a file or a
+//!   HashiCorp Vault instance) instead of writing them down in plaintext.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::SecretError as Error;
+
+
+/***** CONSTANTS *****/
+/// The environment variable that we read the address of the Vault instance from if none is given in the reference.
+pub const VAULT_ADDR_ENV: &str = "VAULT_ADDR";
+/// The environment variable that we read the token to authenticate with Vault from.
+pub const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+
+
+
+
+
+/***** BACKENDS *****/
+/// Defines an abstraction over something that can resolve secrets by some backend-specific key.
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    /// Returns a human-readable name of this backend for use in errors and logs.
+    fn name(&self) -> &'static str;
+
+    /// Resolves the secret with the given key.
+    ///
+    /// # Arguments
+    /// - `key`: The backend-specific identifier of the secret to resolve.
+    ///
+    /// # Returns
+    /// The resolved secret.
+    ///
+    /// # Errors
+    /// This function errors if the secret did not exist or the backend could not be reached.
+    async fn resolve(&self, key: &str) -> Result<String, Error>;
+}
+
+
+
+/// Resolves secrets by reading environment variables.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvBackend;
+#[async_trait]
+impl SecretBackend for EnvBackend {
+    #[inline]
+    fn name(&self) -> &'static str { "environment" }
+
+    async fn resolve(&self, key: &str) -> Result<String, Error> {
+        match std::env::var(key) {
+            Ok(value) => Ok(value),
+            Err(std::env::VarError::NotPresent) => Err(Error::EnvNotSet { name: key.into() }),
+            Err(std::env::VarError::NotUnicode(_)) => Err(Error::EnvNotUnicode { name: key.into() }),
+        }
+    }
+}
+
+
+
+/// Resolves secrets by reading the entire contents of a file (e.g., a Docker or Kubernetes secret mount).
+///
+/// Any trailing newlines are stripped from the file's contents.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileBackend;
+#[async_trait]
+impl SecretBackend for FileBackend {
+    #[inline]
+    fn name(&self) -> &'static str { "file" }
+
+    async fn resolve(&self, key: &str) -> Result<String, Error> {
+        match tokio::fs::read_to_string(key).await {
+            Ok(value) => Ok(value.trim_end_matches(['\r', '\n']).into()),
+            Err(err) => Err(Error::FileReadError { path: key.into(), err }),
+        }
+    }
+}
+
+
+
+/// Resolves secrets by reading them from a HashiCorp Vault KV secrets engine.
+///
+/// Keys are given as `<path>#<field>`, where `<path>` is the API path of the secret (e.g., `secret/data/brane/registry`) and `<field>` the name of the field in that secret. Both version 1 and version 2 of the KV engine are supported.
+#[derive(Clone, Debug)]
+pub struct VaultBackend {
+    /// The address of the Vault instance (e.g., `https://vault.example.com:8200`).
+    pub address: String,
+    /// The token to authenticate with.
+    pub token:   String,
+}
+impl VaultBackend {
+    /// Constructor for the VaultBackend that reads the address and token from the standard `VAULT_ADDR` and `VAULT_TOKEN` environment variables.
+    ///
+    /// # Arguments
+    /// - `address`: If given, overrides the address read from `VAULT_ADDR`.
+    ///
+    /// # Returns
+    /// A new VaultBackend instance.
+    ///
+    /// # Errors
+    /// This function errors if the address or token could not be found in the environment.
+    pub fn from_env(address: Option<String>) -> Result<Self, Error> {
+        let address: String = match address {
+            Some(address) => address,
+            None => std::env::var(VAULT_ADDR_ENV).map_err(|_| Error::EnvNotSet { name: VAULT_ADDR_ENV.into() })?,
+        };
+        let token: String = std::env::var(VAULT_TOKEN_ENV).map_err(|_| Error::EnvNotSet { name: VAULT_TOKEN_ENV.into() })?;
+        Ok(Self { address, token })
+    }
+}
+#[async_trait]
+impl SecretBackend for VaultBackend {
+    #[inline]
+    fn name(&self) -> &'static str { "vault" }
+
+    async fn resolve(&self, key: &str) -> Result<String, Error> {
+        // Split the key in a path and a field
+        let (path, field): (&str, &str) = match key.rfind('#') {
+            Some(pos) => (&key[..pos], &key[pos + 1..]),
+            None => return Err(Error::VaultKeyWithoutField { key: key.into() }),
+        };
+
+        // Send the request
+        let url: String = format!("{}/v1/{}", self.address.trim_end_matches('/'), path.trim_start_matches('/'));
+        let res: reqwest::Response = match reqwest::Client::new().get(&url).header("X-Vault-Token", &self.token).send().await {
+            Ok(res) => res,
+            Err(err) => return Err(Error::VaultRequestError { url, err }),
+        };
+        if !res.status().is_success() {
+            return Err(Error::VaultRequestFailure { url, status: res.status() });
+        }
+        let body: serde_json::Value = match res.json().await {
+            Ok(body) => body,
+            Err(err) => return Err(Error::VaultResponseParseError { url, err }),
+        };
+
+        // Find the field; KV v2 nests the secret one level deeper than KV v1
+        let data: &serde_json::Value = &body["data"];
+        let value: &serde_json::Value = if data["data"].is_object() { &data["data"][field] } else { &data[field] };
+        match value.as_str() {
+            Some(value) => Ok(value.into()),
+            None => Err(Error::VaultFieldNotFound { path: path.into(), field: field.into() }),
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Refers to a secret (e.g., a password or token) in a configuration file.
+///
+/// In YAML, a secret may be given as:
+/// - A plain string, which is used as-is (not recommended outside of testing);
+/// - `{ env: NAME }`, which reads the secret from the environment variable `NAME`;
+/// - `{ file: /path/to/secret }`, which reads the secret from the given file; or
+/// - `{ vault: "secret/data/brane#password" }`, optionally with `address: ...`, which reads the secret from HashiCorp Vault (see [`VaultBackend`]).
+///
+/// Config files resolve their references once when they are loaded (see [`SecretRef::load()`]), such that a missing secret is noticed at startup
+/// instead of at first use. Rotated secrets are thus only picked up when the file is reloaded.
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SecretRef {
+    /// The secret is read from the environment.
+    Env {
+        /// The name of the environment variable to read.
+        env:   String,
+        /// The resolved secret, if it has been loaded.
+        #[serde(skip)]
+        value: Option<String>,
+    },
+    /// The secret is read from a file.
+    File {
+        /// The path of the file to read.
+        file:  PathBuf,
+        /// The resolved secret, if it has been loaded.
+        #[serde(skip)]
+        value: Option<String>,
+    },
+    /// The secret is read from HashiCorp Vault.
+    Vault {
+        /// The `<path>#<field>` identifier of the secret in Vault.
+        vault:   String,
+        /// The address of the Vault instance. If omitted, uses the `VAULT_ADDR` environment variable.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        address: Option<String>,
+        /// The resolved secret, if it has been loaded.
+        #[serde(skip)]
+        value:   Option<String>,
+    },
+    /// The secret is given directly.
+    Plain(String),
+}
+impl SecretRef {
+    /// Resolves this reference using the default backends and remembers the secret, such that later calls to [`SecretRef::resolve()`] return it
+    /// without consulting the backend again.
+    ///
+    /// This function is synchronous, such that it can be called while config files are parsed synchronously. References to Vault are resolved on
+    /// a separate thread, which blocks the caller; prefer [`SecretRef::load_async()`] from async code.
+    ///
+    /// # Errors
+    /// This function errors if the backend failed to resolve the secret.
+    pub fn load(&mut self) -> Result<(), Error> {
+        match self {
+            Self::Env { env, value } => {
+                *value = Some(match std::env::var(&*env) {
+                    Ok(secret) => secret,
+                    Err(std::env::VarError::NotPresent) => return Err(Error::EnvNotSet { name: env.clone() }),
+                    Err(std::env::VarError::NotUnicode(_)) => return Err(Error::EnvNotUnicode { name: env.clone() }),
+                });
+            },
+            Self::File { file, value } => {
+                *value = Some(match std::fs::read_to_string(&*file) {
+                    Ok(secret) => secret.trim_end_matches(['\r', '\n']).into(),
+                    Err(err) => return Err(Error::FileReadError { path: file.clone(), err }),
+                });
+            },
+            Self::Vault { vault, address, value } => {
+                let backend: VaultBackend = VaultBackend::from_env(address.clone())?;
+                let key: String = vault.clone();
+                let handle = std::thread::spawn(move || -> Result<String, Error> {
+                    let runtime: tokio::runtime::Runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                        Ok(runtime) => runtime,
+                        Err(err) => return Err(Error::RuntimeCreateError { err }),
+                    };
+                    runtime.block_on(backend.resolve(&key))
+                });
+                *value = Some(match handle.join() {
+                    Ok(res) => res?,
+                    Err(_) => return Err(Error::VaultThreadPanicked { key: vault.clone() }),
+                });
+            },
+            Self::Plain(_) => {},
+        }
+        Ok(())
+    }
+
+    /// Resolves this reference using the default backends and remembers the secret, like [`SecretRef::load()`], but without blocking.
+    ///
+    /// # Errors
+    /// This function errors if the backend failed to resolve the secret.
+    pub async fn load_async(&mut self) -> Result<(), Error> {
+        let secret: String = match &*self {
+            Self::Env { env, .. } => EnvBackend.resolve(env).await?,
+            Self::File { file, .. } => FileBackend.resolve(&file.to_string_lossy()).await?,
+            Self::Vault { vault, address, .. } => VaultBackend::from_env(address.clone())?.resolve(vault).await?,
+            Self::Plain(_) => return Ok(()),
+        };
+        match self {
+            Self::Env { value, .. } | Self::File { value, .. } | Self::Vault { value, .. } => *value = Some(secret),
+            Self::Plain(_) => {},
+        }
+        Ok(())
+    }
+
+    /// Resolves this reference to the actual secret using the default backends.
+    ///
+    /// If the reference has already been loaded (see [`SecretRef::load()`]), the remembered secret is returned instead.
+    ///
+    /// # Returns
+    /// The secret this reference refers to.
+    ///
+    /// # Errors
+    /// This function errors if the backend failed to resolve the secret.
+    pub async fn resolve(&self) -> Result<String, Error> {
+        match self {
+            Self::Env { value: Some(value), .. } | Self::File { value: Some(value), .. } | Self::Vault { value: Some(value), .. } => {
+                Ok(value.clone())
+            },
+            Self::Env { env, .. } => EnvBackend.resolve(env).await,
+            Self::File { file, .. } => FileBackend.resolve(&file.to_string_lossy()).await,
+            Self::Vault { vault, address, .. } => VaultBackend::from_env(address.clone())?.resolve(vault).await,
+            Self::Plain(value) => Ok(value.clone()),
+        }
+    }
+
+    /// Resolves this reference to the actual secret using the given backend for any non-plaintext reference.
+    ///
+    /// This can be used to plug in custom backends (e.g., a pre-configured Vault client, or a mock during testing).
+    ///
+    /// # Arguments
+    /// - `backend`: The [`SecretBackend`] to resolve the reference's key with.
+    ///
+    /// # Returns
+    /// The secret this reference refers to.
+    ///
+    /// # Errors
+    /// This function errors if the backend failed to resolve the secret.
+    pub async fn resolve_with(&self, backend: &dyn SecretBackend) -> Result<String, Error> {
+        match self {
+            Self::Env { env, .. } => backend.resolve(env).await,
+            Self::File { file, .. } => backend.resolve(&file.to_string_lossy()).await,
+            Self::Vault { vault, .. } => backend.resolve(vault).await,
+            Self::Plain(value) => Ok(value.clone()),
+        }
+    }
+
+    /// Returns the secret without resolving it, which is only possible if it is given in plaintext or has been loaded (see [`SecretRef::load()`]).
+    ///
+    /// # Returns
+    /// The secret this reference refers to, or [`None`] if it has not been loaded yet.
+    #[inline]
+    pub fn loaded(&self) -> Option<&str> {
+        match self {
+            Self::Env { value, .. } | Self::File { value, .. } | Self::Vault { value, .. } => value.as_deref(),
+            Self::Plain(value) => Some(value),
+        }
+    }
+
+    /// Returns whether this secret is given in plaintext.
+    ///
+    /// # Returns
+    /// True if it is a [`SecretRef::Plain`], or false otherwise.
+    #[inline]
+    pub fn is_plain(&self) -> bool { matches!(self, Self::Plain(_)) }
+}
+impl std::fmt::Debug for SecretRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        // Never leak the plaintext secret in logs
+        match self {
+            Self::Plain(_) => write!(f, "Plain(<redacted>)"),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+impl Display for SecretRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Env { env, .. } => write!(f, "env:{env}"),
+            Self::File { file, .. } => write!(f, "file:{}", file.display()),
+            Self::Vault { vault, .. } => write!(f, "vault:{vault}"),
+            Self::Plain(_) => write!(f, "<plaintext>"),
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that every form of secret reference is parsed as the right variant
+    #[test]
+    fn secretref_parse() {
+        let secret: SecretRef = serde_yaml::from_str("hunter2").unwrap();
+        assert_eq!(secret, SecretRef::Plain("hunter2".into()));
+        let secret: SecretRef = serde_yaml::from_str("env: REGISTRY_PASSWORD").unwrap();
+        assert_eq!(secret, SecretRef::Env { env: "REGISTRY_PASSWORD".into(), value: None });
+        let secret: SecretRef = serde_yaml::from_str("file: /run/secrets/password").unwrap();
+        assert_eq!(secret, SecretRef::File { file: "/run/secrets/password".into(), value: None });
+        let secret: SecretRef = serde_yaml::from_str("vault: secret/data/brane#password").unwrap();
+        assert_eq!(secret, SecretRef::Vault { vault: "secret/data/brane#password".into(), address: None, value: None });
+        let secret: SecretRef = serde_yaml::from_str("vault: secret/data/brane#password\naddress: https://vault.example.com:8200").unwrap();
+        assert_eq!(secret, SecretRef::Vault {
+            vault:   "secret/data/brane#password".into(),
+            address: Some("https://vault.example.com:8200".into()),
+            value:   None,
+        });
+    }
+
+    /// Test that loaded secrets are remembered, and that plaintext ones are never printed
+    #[test]
+    fn secretref_load() {
+        std::env::set_var("BRANE_CFG_TEST_SECRET", "hunter2");
+        let mut secret: SecretRef = serde_yaml::from_str("env: BRANE_CFG_TEST_SECRET").unwrap();
+        assert_eq!(secret.loaded(), None);
+        secret.load().unwrap();
+        assert_eq!(secret.loaded(), Some("hunter2"));
+
+        let path: PathBuf = std::env::temp_dir().join(format!("brane-cfg-test-secret-{}", std::process::id()));
+        std::fs::write(&path, "hunter3\n").unwrap();
+        let mut secret: SecretRef = SecretRef::File { file: path.clone(), value: None };
+        let res: Result<(), Error> = secret.load();
+        std::fs::remove_file(&path).unwrap();
+        res.unwrap();
+        assert_eq!(secret.loaded(), Some("hunter3"));
+
+        let mut secret: SecretRef = serde_yaml::from_str("env: BRANE_CFG_TEST_SECRET_UNSET").unwrap();
+        assert!(matches!(secret.load(), Err(Error::EnvNotSet { .. })));
+        assert_eq!(format!("{:?}", SecretRef::Plain("hunter2".into())), "Plain(<redacted>)");
+    }
+}
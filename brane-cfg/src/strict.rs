@@ -0,0 +1,533 @@
+//  STRICT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:00:08
+//  Last edited:
+//    15 Oct 2026, 23:00:09
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements detecting unknown (e.g., misspelled) keys in YAML config
+//!   files, such that `servies:` does not silently produce a
+//!   half-configured node.
+//!
+//!   Unknown keys are found by comparing the parsed file with the config
+//!   serialized back again: any key that does not survive the round trip
+//!   was ignored by the parser. This also works for keys nested in tagged
+//!   enums, which serde buffers before parsing.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+
+use brane_shr::suggest::{suggest, DidYouMean};
+use log::warn;
+use serde_yaml::{Mapping, Value};
+
+
+/***** CONSTANTS *****/
+/// The environment variable that determines what happens with unknown keys (see [`UnknownKeyPolicy`]).
+pub const UNKNOWN_KEYS_ENV: &str = "BRANE_CFG_UNKNOWN_KEYS";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the string representation of a mapping key.
+///
+/// # Arguments
+/// - `key`: The key to represent.
+///
+/// # Returns
+/// The key as a string, or [`None`] if it is not a scalar.
+fn key_str(key: &Value) -> Option<String> {
+    match key {
+        Value::String(key) => Some(key.clone()),
+        Value::Number(key) => Some(key.to_string()),
+        Value::Bool(key) => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+/// Finds the value of a mapping by the string representation of its key.
+///
+/// # Arguments
+/// - `map`: The mapping to search.
+/// - `key`: The string representation of the key (see [`key_str()`]).
+///
+/// # Returns
+/// The value, or [`None`] if there is no such key.
+fn get<'m>(map: &'m Mapping, key: &str) -> Option<&'m Value> {
+    map.iter().find_map(|(k, v)| if key_str(k).as_deref() == Some(key) { Some(v) } else { None })
+}
+
+/// Returns whether a value is "empty", i.e., whether a config may have left it out when serializing.
+///
+/// # Arguments
+/// - `value`: The value to check.
+///
+/// # Returns
+/// True if the value is null, false, zero or an empty string or collection.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Bool(value) => !value,
+        Value::Number(value) => value.as_f64() == Some(0.0),
+        Value::String(value) => value.is_empty(),
+        Value::Sequence(value) => value.is_empty(),
+        Value::Mapping(value) => value.is_empty(),
+        Value::Tagged(value) => is_empty(&value.value),
+    }
+}
+
+/// Returns whether a value in a serialized config plausibly came from a value in the parsed file.
+///
+/// This is used to recognize aliases: a key in the file that does not survive the round trip, but of which the value shows up under another
+/// name.
+///
+/// # Arguments
+/// - `output`: The value in the serialized config.
+/// - `input`: The value in the parsed file.
+///
+/// # Returns
+/// True if `input` may have been parsed to `output`, i.e., they have the same shape, equal scalars and no unknown keys.
+fn matches(output: &Value, input: &Value) -> bool {
+    match (output, input) {
+        (Value::Mapping(_), Value::Mapping(_)) => {
+            let mut unknown: Vec<UnknownKey> = vec![];
+            diff(input, output, &mut vec![], &mut unknown);
+            unknown.is_empty()
+        },
+        (Value::Sequence(output), Value::Sequence(input)) => output.len() == input.len() && output.iter().zip(input).all(|(o, i)| matches(o, i)),
+        // We cannot say much about tagged values, so we give them the benefit of the doubt
+        (Value::Tagged(_), _) | (_, Value::Tagged(_)) => true,
+        (Value::Mapping(_), _) | (_, Value::Mapping(_)) | (Value::Sequence(_), _) | (_, Value::Sequence(_)) => false,
+        (output, input) => output == input || key_str(output) == key_str(input),
+    }
+}
+
+/// Compares a parsed file with its serialized config and collects the keys that did not survive.
+///
+/// # Arguments
+/// - `input`: The (part of the) parsed file.
+/// - `output`: The (part of the) serialized config that corresponds to it.
+/// - `path`: The path to `input` in the file.
+/// - `unknown`: The list to push unknown keys to.
+fn diff(input: &Value, output: &Value, path: &mut Vec<PathSegment>, unknown: &mut Vec<UnknownKey>) {
+    match (input, output) {
+        (Value::Mapping(input), Value::Mapping(output)) => {
+            // Keys in the output that the input did not have are candidates for aliases
+            let extra: Vec<(String, &Value)> =
+                output.iter().filter_map(|(k, v)| Some((key_str(k)?, v))).filter(|(k, _)| get(input, k).is_none()).collect();
+            for (key, value) in input {
+                let key: String = match key_str(key) {
+                    Some(key) => key,
+                    None => continue,
+                };
+                let counterpart: Option<&Value> = match get(output, &key) {
+                    Some(counterpart) => Some(counterpart),
+                    None => extra.iter().find(|(_, o)| matches(o, value)).map(|(_, o)| *o),
+                };
+
+                path.push(PathSegment::Key(key));
+                match counterpart {
+                    Some(counterpart) => diff(value, counterpart, path, unknown),
+                    // Empty values may simply have been skipped when serializing
+                    None if is_empty(value) => {},
+                    None => {
+                        let mut accepted: Vec<String> = output.keys().filter_map(key_str).collect();
+                        accepted.sort();
+                        unknown.push(UnknownKey { path: path.clone(), line: None, column: None, in_overlay: false, accepted });
+                    },
+                }
+                path.pop();
+            }
+        },
+        (Value::Sequence(input), Value::Sequence(output)) if input.len() == output.len() => {
+            for (i, (input, output)) in input.iter().zip(output).enumerate() {
+                path.push(PathSegment::Index(i));
+                diff(input, output, path, unknown);
+                path.pop();
+            }
+        },
+        (Value::Tagged(input), Value::Tagged(output)) if input.tag == output.tag => diff(&input.value, &output.value, path, unknown),
+        // Externally tagged enums may be written as a single-key mapping but serialized as a YAML tag
+        (Value::Mapping(input), Value::Tagged(output)) if input.len() == 1 => {
+            let tag: String = output.tag.to_string();
+            if let Some(value) = get(input, tag.trim_start_matches('!')) {
+                path.push(PathSegment::Key(tag.trim_start_matches('!').into()));
+                diff(value, &output.value, path, unknown);
+                path.pop();
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Returns whether a parsed file has a value at the given path.
+///
+/// # Arguments
+/// - `value`: The parsed file.
+/// - `path`: The path to check.
+///
+/// # Returns
+/// True if there is a value at `path`.
+fn has_path(value: &Value, path: &[PathSegment]) -> bool {
+    let mut value: &Value = value;
+    for segment in path {
+        let next: Option<&Value> = match (segment, value) {
+            (PathSegment::Key(key), Value::Mapping(map)) => get(map, key),
+            (PathSegment::Index(i), Value::Sequence(seq)) => seq.get(*i),
+            _ => None,
+        };
+        value = match next {
+            Some(next) => next,
+            None => return false,
+        };
+    }
+    true
+}
+
+/// Returns the indentation of a line, and the remainder after any sequence item markers (`- `).
+///
+/// # Arguments
+/// - `line`: The line to analyse.
+///
+/// # Returns
+/// A tuple of the column at which the remainder starts (zero-indexed), the indentation of the line itself and the remainder.
+fn split_indent(line: &str) -> (usize, usize, &str) {
+    let indent: usize = line.len() - line.trim_start().len();
+    let mut column: usize = indent;
+    let mut rest: &str = &line[indent..];
+    while let Some(item) = rest.strip_prefix('-') {
+        if !item.is_empty() && !item.starts_with(' ') {
+            break;
+        }
+        let trimmed: &str = item.trim_start();
+        column += rest.len() - trimmed.len();
+        rest = trimmed;
+    }
+    (column, indent, rest)
+}
+
+/// Returns whether a line (without indentation) starts with the given key.
+///
+/// # Arguments
+/// - `rest`: The line, without indentation and sequence item markers.
+/// - `key`: The key to look for.
+///
+/// # Returns
+/// True if the line defines `key`, in plain or quoted form.
+fn defines_key(rest: &str, key: &str) -> bool {
+    for quote in ["", "\"", "'"] {
+        if let Some(after) = rest.strip_prefix(quote).and_then(|r| r.strip_prefix(key)).and_then(|r| r.strip_prefix(quote)) {
+            if after.trim_start().starts_with(':') {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines what happens when a config file contains unknown keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnknownKeyPolicy {
+    /// Loading the file fails.
+    Deny,
+    /// The keys are logged as warnings, but otherwise ignored.
+    Warn,
+}
+impl UnknownKeyPolicy {
+    /// Returns the policy for this process, as given by the [`UNKNOWN_KEYS_ENV`] environment variable (`deny` or `warn`).
+    ///
+    /// # Returns
+    /// The policy, which is [`UnknownKeyPolicy::Deny`] if the variable is unset or invalid.
+    pub fn current() -> Self {
+        match std::env::var(UNKNOWN_KEYS_ENV) {
+            Ok(raw) if raw.eq_ignore_ascii_case("warn") => Self::Warn,
+            Ok(raw) if raw.is_empty() || raw.eq_ignore_ascii_case("deny") => Self::Deny,
+            Ok(raw) => {
+                warn!("Unknown value '{raw}' for environment variable {UNKNOWN_KEYS_ENV} (expected 'deny' or 'warn'); assuming 'deny'");
+                Self::Deny
+            },
+            Err(_) => Self::Deny,
+        }
+    }
+}
+
+
+
+/// Defines a single step in the path to a value in a config file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PathSegment {
+    /// A key in a mapping.
+    Key(String),
+    /// An index in a sequence.
+    Index(usize),
+}
+
+
+
+/// Defines a key in a config file that is not used by the config.
+#[derive(Clone, Debug)]
+pub struct UnknownKey {
+    /// The path to the key, including the key itself.
+    pub path: Vec<PathSegment>,
+    /// The line (one-indexed) on which the key is defined, if we could find it.
+    pub line: Option<usize>,
+    /// The column (one-indexed) at which the key is defined, if we could find it.
+    pub column: Option<usize>,
+    /// Whether the key is defined in the overlay file instead of the file itself.
+    pub in_overlay: bool,
+    /// The keys that the config does know at the same level.
+    ///
+    /// Note that this may miss optional keys, since it is deduced from the serialized config.
+    pub accepted: Vec<String>,
+}
+impl UnknownKey {
+    /// Returns the name of the unknown key itself.
+    #[inline]
+    pub fn name(&self) -> &str {
+        match self.path.last() {
+            Some(PathSegment::Key(key)) => key,
+            _ => "",
+        }
+    }
+
+    /// Returns the path to the key as a string, e.g., `node.services.api` or `locations[0].name`.
+    pub fn path_str(&self) -> String {
+        let mut res: String = String::new();
+        for segment in &self.path {
+            match segment {
+                PathSegment::Key(key) => {
+                    if !res.is_empty() {
+                        res.push('.');
+                    }
+                    res.push_str(key);
+                },
+                PathSegment::Index(i) => res.push_str(&format!("[{i}]")),
+            }
+        }
+        res
+    }
+}
+impl Display for UnknownKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "unknown key '{}'", self.path_str())?;
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, " at line {line}, column {column}")?,
+            (Some(line), None) => write!(f, " at line {line}")?,
+            _ => {},
+        }
+        if self.in_overlay {
+            write!(f, " of the overlay file")?;
+        }
+        let suggestions: Vec<String> = suggest(self.name(), self.accepted.iter().map(String::as_str));
+        write!(f, "{}", DidYouMean::new(&suggestions))?;
+        if !self.accepted.is_empty() {
+            write!(f, " (expected one of {})", self.accepted.iter().map(|key| format!("'{key}'")).collect::<Vec<String>>().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Finds the keys in a parsed config file that are not used by the config itself.
+///
+/// # Arguments
+/// - `input`: The parsed file (i.e., as a generic YAML [`Value`]).
+/// - `output`: The config parsed from the file, serialized back to a generic YAML [`Value`].
+///
+/// # Returns
+/// The unknown keys, in the order in which they appear in the file. Their location is not yet filled in (see [`locate()`]).
+pub fn find_unknown_keys(input: &Value, output: &Value) -> Vec<UnknownKey> {
+    let mut unknown: Vec<UnknownKey> = vec![];
+    diff(input, output, &mut vec![], &mut unknown);
+    unknown
+}
+
+
+
+/// Finds the line and column at which the value with the given path is defined in a raw YAML file.
+///
+/// This only understands YAML written in block style (which is what our config files use); for values in flow style (`{ ... }`), it gives up.
+///
+/// # Arguments
+/// - `raw`: The raw YAML file.
+/// - `path`: The path to the value to find.
+///
+/// # Returns
+/// The line and column (both one-indexed), or [`None`] if we failed to find the value.
+pub fn locate(raw: &str, path: &[PathSegment]) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = raw.lines().collect();
+
+    // We find every step of the path in the block of the previous one. Blocks are defined by their first line, the minimum indentation of
+    // their lines and the indentation at which sequence items may start (which YAML allows to be that of the parent key).
+    let mut start: usize = 0;
+    let mut min_indent: usize = 0;
+    let mut item_indent: Option<usize> = None;
+    let mut in_item: bool = false;
+    let mut found: Option<(usize, usize)> = None;
+    for segment in path {
+        // The column of the direct children of the block, which is that of its first line
+        let mut child: Option<usize> = None;
+        let mut items: usize = 0;
+        found = None;
+        for (l, line) in lines.iter().enumerate().skip(start) {
+            let (column, indent, rest): (usize, usize, &str) = split_indent(line);
+            if rest.is_empty() || rest.starts_with('#') {
+                continue;
+            }
+            let is_item: bool = column > indent;
+
+            // Stop once we leave the block (the first line of a sequence item is less indented than the rest)
+            if !(in_item && l == start) && indent < min_indent && !(is_item && item_indent == Some(indent)) {
+                break;
+            }
+            match segment {
+                PathSegment::Key(key) => {
+                    let child: usize = *child.get_or_insert(column);
+                    if column == child && defines_key(rest, key) {
+                        found = Some((l, column));
+                        break;
+                    }
+                },
+                PathSegment::Index(i) => {
+                    if !is_item {
+                        continue;
+                    }
+                    let child: usize = *child.get_or_insert(indent);
+                    if indent == child {
+                        if items == *i {
+                            found = Some((l, column));
+                            break;
+                        }
+                        items += 1;
+                    }
+                },
+            }
+        }
+
+        // Move into the block of what we found
+        let (l, column): (usize, usize) = found?;
+        match segment {
+            PathSegment::Key(_) => {
+                start = l + 1;
+                min_indent = column + 1;
+                item_indent = Some(column);
+                in_item = false;
+            },
+            PathSegment::Index(_) => {
+                start = l;
+                min_indent = column;
+                item_indent = None;
+                in_item = true;
+            },
+        }
+    }
+    found.map(|(l, column)| (l + 1, column + 1))
+}
+
+/// Fills in where the given unknown keys are defined.
+///
+/// # Arguments
+/// - `keys`: The keys to locate (see [`find_unknown_keys()`]).
+/// - `raw`: The raw file that was parsed.
+/// - `overlay`: The raw overlay file that was merged over it, if any. Keys that the overlay defines are located in it.
+pub fn locate_keys(keys: &mut [UnknownKey], raw: &str, overlay: Option<&str>) {
+    let overlay: Option<(&str, Value)> = overlay.and_then(|overlay| Some((overlay, serde_yaml::from_str(overlay).ok()?)));
+    for key in keys {
+        let raw: &str = match &overlay {
+            Some((overlay, value)) if has_path(value, &key.path) => {
+                key.in_overlay = true;
+                overlay
+            },
+            _ => raw,
+        };
+        if let Some((line, column)) = locate(raw, &key.path) {
+            key.line = Some(line);
+            key.column = Some(column);
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Deserialize, Serialize)]
+    struct Config {
+        name: String,
+        #[serde(alias = "project")]
+        project_name: String,
+        node: Node,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        optional: Option<String>,
+        #[serde(default)]
+        hosts: HashMap<String, String>,
+    }
+    #[derive(Deserialize, Serialize)]
+    #[serde(tag = "kind", rename_all = "snake_case")]
+    enum Node {
+        Worker { services: Vec<Service> },
+    }
+    #[derive(Deserialize, Serialize)]
+    struct Service {
+        name: String,
+    }
+
+    /// Parses the given raw file as a [`Config`] and returns its unknown keys (as paths).
+    fn unknown(raw: &str) -> Vec<String> {
+        let input: Value = serde_yaml::from_str(raw).unwrap();
+        let config: Config = serde_yaml::from_str(raw).unwrap();
+        let output: Value = serde_yaml::to_value(&config).unwrap();
+        find_unknown_keys(&input, &output).iter().map(UnknownKey::path_str).collect()
+    }
+
+    /// Test that known keys, aliases, maps and skipped empty values are not reported
+    #[test]
+    fn findunknownkeys_known() {
+        let raw: &str = "name: a\nproject: b\nnode:\n  kind: worker\n  services:\n  - name: c\noptional: ~\nhosts:\n  foo: bar\n";
+        assert!(unknown(raw).is_empty());
+    }
+
+    /// Test that unknown keys are reported, also in tagged enums and sequences
+    #[test]
+    fn findunknownkeys_unknown() {
+        let raw: &str =
+            "name: a\nproject_name: b\nnode:\n  kind: worker\n  servies: [x]\n  services:\n  - name: c\n  - name: d\n    nmae: e\noptinal: f\n";
+        assert_eq!(unknown(raw), vec!["node.servies".to_string(), "node.services[1].nmae".to_string(), "optinal".to_string()]);
+    }
+
+    /// Test that keys are located in the raw file
+    #[test]
+    fn locate_paths() {
+        let raw: &str = "name: a\nnode:\n  # comment\n  kind: worker\n  services:\n  - name: c\n  - name: d\n    nmae: e\nnmae: f\n";
+        let key = |s: &str| PathSegment::Key(s.into());
+        assert_eq!(locate(raw, &[key("nmae")]), Some((9, 1)));
+        assert_eq!(locate(raw, &[key("node"), key("kind")]), Some((4, 3)));
+        assert_eq!(locate(raw, &[key("node"), key("services"), PathSegment::Index(1), key("nmae")]), Some((8, 5)));
+        assert_eq!(locate(raw, &[key("node"), key("services"), PathSegment::Index(1), key("name")]), Some((7, 5)));
+        assert_eq!(locate(raw, &[key("node"), key("nmae")]), None);
+    }
+}
@@ -0,0 +1,119 @@
+//  TOKENS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:03:33
+//  Last edited:
+//    16 Oct 2026, 01:16:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the file with the API tokens with which users authenticate
+//!   themselves to the central node's services (`brane-api` and
+//!   `brane-drv`).
+//!
+//!   It lives in the central node's certificate directory and only stores
+//!   the SHA-256 hash of every token, together with the name of the user
+//!   it belongs to. Clients present the token itself as
+//!   `Authorization: Bearer <TOKEN>`.
+//
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+
+pub use crate::info::YamlError as Error;
+use crate::info::YamlInfo;
+
+
+/***** CONSTANTS *****/
+/// The name of the file in the central node's certificate directory that lists the known API tokens.
+pub const API_TOKENS_FILE: &str = "api-tokens.yml";
+
+
+
+
+
+/***** LIBRARY FUNCTIONS *****/
+/// Computes the hash of an API token, which is what is stored in the [`API_TOKENS_FILE`].
+///
+/// # Arguments
+/// - `token`: The token to hash.
+///
+/// # Returns
+/// The SHA-256 hash of the token, as a lowercase hexadecimal string.
+#[inline]
+pub fn hash_token(token: impl AsRef<str>) -> String { hex::encode(Sha256::digest(token.as_ref().as_bytes())) }
+
+/// Extracts the token from the value of an `Authorization` header.
+///
+/// # Arguments
+/// - `header`: The value of the header, e.g., `Bearer <TOKEN>`.
+///
+/// # Returns
+/// The token, or [`None`] if the header does not carry a bearer token.
+#[inline]
+pub fn bearer_token(header: &str) -> Option<&str> { header.strip_prefix("Bearer ").map(str::trim) }
+
+
+
+
+
+/***** LIBRARY *****/
+/// A single token in the [`API_TOKENS_FILE`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ApiToken {
+    /// The name of the user that the token belongs to.
+    pub user: String,
+    /// The hash of the token (see [`hash_token()`]).
+    pub hash: String,
+}
+
+/// Defines the [`API_TOKENS_FILE`].
+///
+/// For example:
+/// ```yaml
+/// tokens:
+/// - user: amy
+///   hash: 2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ApiTokensFile {
+    /// The known tokens.
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+impl<'de> YamlInfo<'de> for ApiTokensFile {}
+impl ApiTokensFile {
+    /// Finds the user that the given token belongs to.
+    ///
+    /// # Arguments
+    /// - `token`: The token as presented by the client (i.e., not hashed).
+    ///
+    /// # Returns
+    /// The name of the user, or [`None`] if the token is unknown.
+    pub fn user(&self, token: impl AsRef<str>) -> Option<&str> {
+        let hash: String = hash_token(token);
+        self.tokens.iter().find(|entry| entry.hash == hash).map(|entry| entry.user.as_str())
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that tokens are matched by their hash and that only bearer tokens are extracted from headers.
+    #[test]
+    fn apitokensfile_user() {
+        let file: ApiTokensFile = ApiTokensFile { tokens: vec![ApiToken { user: "amy".into(), hash: hash_token("secret") }] };
+        assert_eq!(file.user("secret"), Some("amy"));
+        assert_eq!(file.user("guess"), None);
+        assert_eq!(bearer_token("Bearer secret"), Some("secret"));
+        assert_eq!(bearer_token("Basic c2VjcmV0"), None);
+    }
+}
@@ -44,7 +44,7 @@ typedef struct _source_error SourceError;
 
 /* Defines an index of available packages.
  * 
- * In reality, this actually wraps an `Arc<PackageIndex>`, meaning that you can safely deallocate this reference once given to a compiler- or VM-constructor without worrying about segfaults.
+ * In reality, this actually wraps an `Arc<PackageIndexCache>`, meaning that you can safely deallocate this reference once given to a compiler- or VM-constructor without worrying about segfaults.
  * 
  * WARNING: Do not access any internals yourself, since there are no guarantees on the internal layout of this struct.
  */
@@ -143,6 +143,18 @@ struct _functions {
      * This function can panic if the given `err` is a NULL-pointer.
      */
     void (*error_print_err)(Error* err);
+    /* Serializes the machine-readable code of this error (e.g., `BRANE-PLR-001`) to the given buffer.
+     * 
+     * Use this to react to specific errors instead of matching on the error message.
+     * 
+     * # Arguments
+     * - `err`: the [`Error`] to serialize the code of.
+     * - `buffer`: The buffer to serialize to. Will be freshly allocated using `malloc` for the correct size; can be freed using `free()`. Will be [`NULL`] if the error has no code.
+     * 
+     * # Panics
+     * This function can panic if the given `err` or `buffer` are NULL-pointers.
+     */
+    void (*error_serialize_code)(Error* err, char** buffer);
 
 
 
@@ -267,7 +279,9 @@ struct _functions {
 
 
     /***** PACKAGE INDEX *****/
-    /* Constructs a new [`PackageIndex`] that lists the available packages in a remote instance.
+    /* Constructs a new [`PackageIndex`] that resolves the packages of a remote instance.
+     * 
+     * Packages are only downloaded once a snippet that imports them is compiled (see [`compiler_compile()`]).
      * 
      * # Arguments
      * - `endpoint`: The remote API-endpoint to read the packages from. The path (`/graphql`) will be deduced and needn't be given, just the host and port.
@@ -536,6 +550,7 @@ Functions* functions_load(const char* path) {
     LOAD_SYMBOL(error_free, void (*)(Error*));
     LOAD_SYMBOL(error_serialize_err, void (*)(Error*, char**));
     LOAD_SYMBOL(error_print_err, void (*)(Error*));
+    LOAD_SYMBOL(error_serialize_code, void (*)(Error*, char**));
 
     // Load the source error symbols
     LOAD_SYMBOL(serror_free, void (*)(SourceError*));
@@ -70,7 +70,7 @@ void compiler_free(Compiler *compiler);
 ///
 /// # Panics
 /// This function can panic if the given `pindex` or `dindex` points to NULL.
-const Error *compiler_new(const Arc<Mutex<PackageIndex>> *pindex,
+const Error *compiler_new(const Arc<PackageIndexCache<RemotePackageProvider>> *pindex,
                           const Arc<Mutex<DataIndex>> *dindex,
                           Compiler **compiler);
 
@@ -169,9 +169,11 @@ void fvalue_serialize(const FullValue *fvalue,
 ///
 /// # Arguments
 /// - `pindex`: The [`PackageIndex`] to free.
-void pindex_free(Arc<Mutex<PackageIndex>> *pindex);
+void pindex_free(Arc<PackageIndexCache<RemotePackageProvider>> *pindex);
 
-/// Constructs a new [`PackageIndex`] that lists the available packages in a remote instance.
+/// Constructs a new [`PackageIndex`] that resolves the packages of a remote instance.
+///
+/// Packages are only downloaded once a snippet that imports them is compiled (see [`compiler_compile()`]).
 ///
 /// # Arguments
 /// - `endpoint`: The remote API-endpoint to read the packages from. The path (`/graphql`) will be deduced and needn't be given, just the host and port.
@@ -183,7 +185,7 @@ void pindex_free(Arc<Mutex<PackageIndex>> *pindex);
 /// # Panics
 /// This function can panic if the given `endpoint` does not point to a valud UTF-8 string.
 const Error *pindex_new_remote(const char *endpoint,
-                               Arc<Mutex<PackageIndex>> **pindex);
+                               Arc<PackageIndexCache<RemotePackageProvider>> **pindex);
 
 /// Destructor for the Error type.
 ///
@@ -345,7 +347,7 @@ void vm_free(VirtualMachine *vm);
 const Error *vm_new(const char *api_endpoint,
                     const char *drv_endpoint,
                     const char *certs_dir,
-                    const Arc<Mutex<PackageIndex>> *pindex,
+                    const Arc<PackageIndexCache<RemotePackageProvider>> *pindex,
                     const Arc<Mutex<DataIndex>> *dindex,
                     VirtualMachine **vm);
 
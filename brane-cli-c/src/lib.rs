@@ -4,7 +4,7 @@
 //  Created:
 //    14 Jun 2023, 17:38:09
 //  Last edited:
-//    04 Mar 2024, 13:33:55
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -33,14 +33,17 @@ use brane_ast::state::CompileState;
 use brane_ast::traversals::print::ast;
 use brane_ast::{CompileResult, Error as AstError, ParserOptions, Warning as AstWarning};
 use brane_cli::data::download_data;
+use brane_cli::errors::RunError;
 use brane_cli::run::{initialize_instance, run_instance, InstanceVmState};
 use brane_exe::FullValue;
-use brane_tsk::api::{get_data_index, get_package_index};
+use brane_tsk::api::{get_data_index, RemotePackageProvider};
+use brane_tsk::caches::PackageIndexCache;
 use console::style;
 use humanlog::{DebugMode, HumanLogger};
 use log::{debug, error, info, trace, warn};
 use parking_lot::{Mutex, MutexGuard};
 use specifications::data::{AccessKind, DataIndex};
+use specifications::errors::ErrorCode;
 use specifications::package::PackageIndex;
 use tokio::runtime::{Builder, Runtime};
 
@@ -269,7 +272,9 @@ pub extern "C" fn set_force_colour(force: bool) {
 #[derive(Debug)]
 pub struct Error {
     /// The message to print.
-    msg: String,
+    msg:  String,
+    /// The machine-readable code of the error, if the remote told us one.
+    code: Option<ErrorCode>,
 }
 
 
@@ -342,6 +347,37 @@ pub unsafe extern "C" fn error_print_err(err: *const Error) {
     error!("{}", err.msg);
 }
 
+/// Serializes the machine-readable code of this error (e.g., `BRANE-PLR-001`) to the given buffer.
+///
+/// Use this to react to specific errors instead of matching on the error message.
+///
+/// # Arguments
+/// - `err`: the [`Error`] to serialize the code of.
+/// - `buffer`: The buffer to serialize to. Will be freshly allocated using `malloc` for the correct size; can be freed using `free()`. Will be [`NULL`] if the error has no code.
+///
+/// # Panics
+/// This function can panic if the given `err` or `buffer` are NULL-pointers.
+#[allow(clippy::missing_safety_doc)]
+#[no_mangle]
+pub unsafe extern "C" fn error_serialize_code(err: *const Error, buffer: *mut *mut c_char) {
+    *buffer = std::ptr::null_mut();
+
+    // Unwrap the pointers
+    let err: &Error = match err.as_ref() {
+        Some(err) => err,
+        None => {
+            panic!("Given Error is a NULL-pointer");
+        },
+    };
+
+    // Set the C-string equivalent of the code as the result, if any
+    if let Some(code) = err.code {
+        *buffer = rust_to_cstr(code.as_str().into());
+    }
+
+    // OK, done!
+}
+
 
 
 
@@ -685,7 +721,9 @@ pub unsafe extern "C" fn serror_print_err(serr: *const SourceError) {
 
 
 /***** LIBRARY PACKAGEINDEX *****/
-/// Constructs a new [`PackageIndex`] that lists the available packages in a remote instance.
+/// Constructs a new [`PackageIndex`] that resolves the packages of a remote instance.
+///
+/// Packages are only downloaded once a snippet that imports them is compiled (see [`compiler_compile()`]).
 ///
 /// # Arguments
 /// - `endpoint`: The remote API-endpoint to read the packages from. The path (`/graphql`) will be deduced and needn't be given, just the host and port.
@@ -698,36 +736,18 @@ pub unsafe extern "C" fn serror_print_err(serr: *const SourceError) {
 /// This function can panic if the given `endpoint` does not point to a valud UTF-8 string.
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
-pub unsafe extern "C" fn pindex_new_remote(endpoint: *const c_char, pindex: *mut *mut Arc<Mutex<PackageIndex>>) -> *const Error {
+pub unsafe extern "C" fn pindex_new_remote(endpoint: *const c_char, pindex: *mut *mut Arc<PackageIndexCache<RemotePackageProvider>>) -> *const Error {
     init_logger();
     *pindex = std::ptr::null_mut();
-    info!("Collecting package index...");
+    info!("Preparing package index...");
 
     // Read the input string
     let endpoint: &str = cstr_to_rust(endpoint);
 
-    // Create a local threaded tokio context
-    let runtime: Arc<Runtime> = match init_runtime() {
-        Ok(runtime) => runtime,
-        Err(e) => {
-            let err: Error = Error { msg: format!("Failed to create local Tokio context: {e}") };
-            return Box::into_raw(Box::new(err));
-        },
-    };
-
-    // Build the package index around it
+    // Store the provider and we're done
     let addr: String = format!("{endpoint}/graphql");
-    let index: PackageIndex = match runtime.block_on(get_package_index(&addr)) {
-        Ok(index) => index,
-        Err(e) => {
-            let err: Error = Error { msg: format!("Failed to read package index from '{addr}': {e}") };
-            return Box::into_raw(Box::new(err));
-        },
-    };
-
-    // Store it and we're done
-    debug!("Found {} packages", index.packages.len());
-    *pindex = Box::into_raw(Box::new(Arc::new(Mutex::new(index))));
+    debug!("Resolving packages from '{addr}' as they are imported");
+    *pindex = Box::into_raw(Box::new(Arc::new(PackageIndexCache::new(RemotePackageProvider::new(addr)))));
     std::ptr::null()
 }
 
@@ -739,7 +759,7 @@ pub unsafe extern "C" fn pindex_new_remote(endpoint: *const c_char, pindex: *mut
 /// # Arguments
 /// - `pindex`: The [`PackageIndex`] to free.
 #[no_mangle]
-pub unsafe extern "C" fn pindex_free(pindex: *mut Arc<Mutex<PackageIndex>>) {
+pub unsafe extern "C" fn pindex_free(pindex: *mut Arc<PackageIndexCache<RemotePackageProvider>>) {
     init_logger();
     trace!("Destroying PackageIndex...");
 
@@ -778,7 +798,7 @@ pub unsafe extern "C" fn dindex_new_remote(endpoint: *const c_char, dindex: *mut
     let runtime: Arc<Runtime> = match init_runtime() {
         Ok(runtime) => runtime,
         Err(e) => {
-            let err: Error = Error { msg: format!("Failed to create local Tokio context: {e}") };
+            let err: Error = Error { msg: format!("Failed to create local Tokio context: {e}"), code: None };
             return Box::into_raw(Box::new(err));
         },
     };
@@ -788,7 +808,7 @@ pub unsafe extern "C" fn dindex_new_remote(endpoint: *const c_char, dindex: *mut
     let index: DataIndex = match runtime.block_on(get_data_index(&addr)) {
         Ok(index) => index,
         Err(e) => {
-            let err: Error = Error { msg: format!("Failed to read data index from '{addr}': {e}") };
+            let err: Error = Error { msg: format!("Failed to read data index from '{addr}': {e}"), code: None };
             return Box::into_raw(Box::new(err));
         },
     };
@@ -901,7 +921,7 @@ pub unsafe extern "C" fn workflow_disassemble(workflow: *const Workflow, assembl
     // Run the compiler traversal to serialize it
     let mut result: Vec<u8> = Vec::new();
     if let Err(e) = ast::do_traversal(workflow, &mut result) {
-        let err: Error = Error { msg: format!("Failed to print given workflow: {}", e[0]) };
+        let err: Error = Error { msg: format!("Failed to print given workflow: {}", e[0]), code: None };
         return Box::into_raw(Box::new(err));
     };
 
@@ -920,7 +940,7 @@ pub unsafe extern "C" fn workflow_disassemble(workflow: *const Workflow, assembl
 #[derive(Debug)]
 pub struct Compiler {
     /// The package index to use for compilation.
-    pindex: Arc<Mutex<PackageIndex>>,
+    pindex: Arc<PackageIndexCache<RemotePackageProvider>>,
     /// The data index to use for compilation.
     dindex: Arc<Mutex<DataIndex>>,
 
@@ -947,7 +967,7 @@ pub struct Compiler {
 #[allow(clippy::missing_safety_doc)]
 #[no_mangle]
 pub unsafe extern "C" fn compiler_new(
-    pindex: *const Arc<Mutex<PackageIndex>>,
+    pindex: *const Arc<PackageIndexCache<RemotePackageProvider>>,
     dindex: *const Arc<Mutex<DataIndex>>,
     compiler: *mut *mut Compiler,
 ) -> *const Error {
@@ -956,7 +976,7 @@ pub unsafe extern "C" fn compiler_new(
     info!("Constructing BraneScript compiler v{}...", env!("CARGO_PKG_VERSION"));
 
     // Read the indices
-    let pindex: &Arc<Mutex<PackageIndex>> = match pindex.as_ref() {
+    let pindex: &Arc<PackageIndexCache<RemotePackageProvider>> = match pindex.as_ref() {
         Some(index) => index,
         None => {
             panic!("Given PackageIndex is a NULL-pointer");
@@ -1054,6 +1074,24 @@ pub unsafe extern "C" fn compiler_compile(
 
 
     /* COMPILE */
+    // Fetch the packages that the snippet imports
+    debug!("Resolving imported packages...");
+    let options: ParserOptions = ParserOptions::bscript();
+    let runtime: Arc<Runtime> = match init_runtime() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            serr.msg = Some(format!("Failed to create local Tokio context: {e}"));
+            return Box::into_raw(serr);
+        },
+    };
+    let pindex: Arc<PackageIndex> = match runtime.block_on(compiler.pindex.resolve(raw, &options)) {
+        Ok(pindex) => pindex,
+        Err(e) => {
+            serr.msg = Some(format!("Failed to resolve imported packages: {e}"));
+            return Box::into_raw(serr);
+        },
+    };
+
     debug!("Compiling snippet...");
 
     // Append the source we keep track of
@@ -1063,12 +1101,11 @@ pub unsafe extern "C" fn compiler_compile(
     // Compile that using `brane-ast`
     serr.source.clone_from(&compiler.source);
     let wf: Workflow = {
-        // Acquire locks on the indices
-        let pindex: MutexGuard<PackageIndex> = compiler.pindex.lock();
+        // Acquire a lock on the data index
         let dindex: MutexGuard<DataIndex> = compiler.dindex.lock();
 
         // Run the snippet
-        match brane_ast::compile_snippet(&mut compiler.state, raw.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()) {
+        match brane_ast::compile_snippet(&mut compiler.state, raw.as_bytes(), &pindex, &dindex, &options) {
             CompileResult::Workflow(workflow, warns) => {
                 compiler.state.offset += 1 + raw.chars().filter(|c| *c == '\n').count();
                 serr.warns = warns;
@@ -1246,7 +1283,7 @@ pub unsafe extern "C" fn vm_new(
     api_endpoint: *const c_char,
     drv_endpoint: *const c_char,
     certs_dir: *const c_char,
-    pindex: *const Arc<Mutex<PackageIndex>>,
+    pindex: *const Arc<PackageIndexCache<RemotePackageProvider>>,
     dindex: *const Arc<Mutex<DataIndex>>,
     vm: *mut *mut VirtualMachine,
 ) -> *const Error {
@@ -1260,7 +1297,7 @@ pub unsafe extern "C" fn vm_new(
     let certs_dir: &str = cstr_to_rust(certs_dir);
 
     // Read the indices
-    let pindex: &Arc<Mutex<PackageIndex>> = match pindex.as_ref() {
+    let pindex: &Arc<PackageIndexCache<RemotePackageProvider>> = match pindex.as_ref() {
         Some(index) => index,
         None => {
             panic!("Given PackageIndex is a NULL-pointer");
@@ -1277,7 +1314,7 @@ pub unsafe extern "C" fn vm_new(
     let runtime: Arc<Runtime> = match init_runtime() {
         Ok(runtime) => runtime,
         Err(e) => {
-            let err: Error = Error { msg: format!("Failed to create local Tokio context: {e}") };
+            let err: Error = Error { msg: format!("Failed to create local Tokio context: {e}"), code: None };
             return Box::into_raw(Box::new(err));
         },
     };
@@ -1293,11 +1330,12 @@ pub unsafe extern "C" fn vm_new(
         /* TODO: Add user here as well */
         None,
         None,
+        None,
         ParserOptions::bscript(),
     )) {
         Ok(state) => state,
         Err(e) => {
-            let err: Error = Error { msg: format!("Failed to create new InstanceVmState: {e}") };
+            let err: Error = Error { msg: format!("Failed to create new InstanceVmState: {e}"), code: None };
             return Box::into_raw(Box::new(err));
         },
     };
@@ -1383,7 +1421,12 @@ pub unsafe extern "C" fn vm_run(
     let value: FullValue = match vm.runtime.block_on(run_instance(&vm.drv_endpoint, &mut vm.state, workflow, false)) {
         Ok(value) => value,
         Err(e) => {
-            let err: Box<Error> = Box::new(Error { msg: format!("Failed to run workflow on '{}': {}", vm.drv_endpoint, e) });
+            let code: Option<ErrorCode> = match &e {
+                RunError::ExecFailed { code, .. } => Some(*code),
+                RunError::ExecDenied { .. } | RunError::PolicyDenied { .. } => Some(ErrorCode::DrvPolicyDenied),
+                _ => None,
+            };
+            let err: Box<Error> = Box::new(Error { msg: format!("Failed to run workflow on '{}': {}", vm.drv_endpoint, e), code });
             return Box::into_raw(err);
         },
     };
@@ -1450,7 +1493,7 @@ pub unsafe extern "C" fn vm_process(vm: *mut VirtualMachine, result: *const Full
             *dindex = match vm.runtime.block_on(get_data_index(data_endpoint)) {
                 Ok(index) => index,
                 Err(e) => {
-                    let err: Box<Error> = Box::new(Error { msg: format!("Failed to refresh data index: {e}") });
+                    let err: Box<Error> = Box::new(Error { msg: format!("Failed to refresh data index: {e}"), code: None });
                     return Box::into_raw(err);
                 },
             };
@@ -1459,7 +1502,7 @@ pub unsafe extern "C" fn vm_process(vm: *mut VirtualMachine, result: *const Full
             match dindex.get(d) {
                 Some(info) => info.access.clone(),
                 None => {
-                    let err: Box<Error> = Box::new(Error { msg: format!("Resulting dataset '{d}' is not at any location") });
+                    let err: Box<Error> = Box::new(Error { msg: format!("Resulting dataset '{d}' is not at any location"), code: None });
                     return Box::into_raw(err);
                 },
             }
@@ -1469,7 +1512,8 @@ pub unsafe extern "C" fn vm_process(vm: *mut VirtualMachine, result: *const Full
         let res: Option<AccessKind> = match vm.runtime.block_on(download_data(&vm.api_endpoint, &None, &vm.certs_dir, data_dir, d, &access)) {
             Ok(res) => res,
             Err(e) => {
-                let err: Box<Error> = Box::new(Error { msg: format!("Failed to download resulting data from '{}': {}", vm.api_endpoint, e) });
+                let err: Box<Error> =
+                    Box::new(Error { msg: format!("Failed to download resulting data from '{}': {}", vm.api_endpoint, e), code: None });
                 return Box::into_raw(err);
             },
         };
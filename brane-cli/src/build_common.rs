@@ -4,7 +4,7 @@
 //  Created:
 //    21 Feb 2022, 12:32:28
 //  Last edited:
-//    19 Apr 2023, 11:19:54
+//    15 Oct 2026, 22:30:24
 //  Auto updated?
 //    Yes
 //
@@ -13,13 +13,24 @@
 //!   different
 //
 
-use std::fs;
-use std::path::Path;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
+use console::style;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::debug;
+use reqwest::{Body, Client};
 use specifications::arch::Arch;
+use specifications::package::PackageInfo;
+use tokio::fs::File as TokioFile;
+use tokio_util::codec::{BytesCodec, FramedRead};
 
 use crate::errors::BuildError;
+use crate::registry::get_packages_endpoint;
 
 
 /***** COMMON MACROS *****/
@@ -143,3 +154,121 @@ pub fn build_docker_image<P: AsRef<Path>>(arch: Arch, package_dir: P, tag: Strin
     // Done! :D
     Ok(())
 }
+
+
+
+/// Builds the docker image in the given package directory on the active remote instance, which then adds it to its registry.
+///
+/// This sends the prepared package directory (i.e., the `Dockerfile`, the `container` directory and the package info) as build context to the
+/// instance. The resulting image is not available locally, but can be pulled like any other package in the instance.
+///
+/// # Arguments
+///  - `arch`: The architecture for which to build this image.
+///  - `info`: The PackageInfo of the package to build. Its digest is resolved remotely.
+///  - `package_dir`: The build directory for this image. We expect the actual image files to be under ./container.
+///  - `keep_files`: Determines whether or not to keep the build files after building.
+///
+/// # Errors
+/// This function fails if we could not write or send the build context. Note that a failing build is not an error, but reported to the user.
+pub async fn build_docker_image_remote(arch: Arch, info: PackageInfo, package_dir: &Path, keep_files: bool) -> Result<(), BuildError> {
+    // Write the package info as part of the build context
+    if let Err(err) = info.to_path(package_dir.join("package.yml")) {
+        return Err(BuildError::PackageFileCreateError { err });
+    }
+
+    // Compress the build context
+    let context_path: PathBuf = package_dir.join("context.tar.gz");
+    debug!("Compressing build context to '{}'...", context_path.display());
+    {
+        let handle: File = match File::create(&context_path) {
+            Ok(handle) => handle,
+            Err(err) => {
+                return Err(BuildError::ContextArchiveError { path: context_path, err });
+            },
+        };
+        let mut tar = tar::Builder::new(GzEncoder::new(handle, Compression::fast()));
+        let res = tar
+            .append_path_with_name(package_dir.join("Dockerfile"), "Dockerfile")
+            .and_then(|_| tar.append_dir_all("container", package_dir.join("container")))
+            .and_then(|_| tar.append_path_with_name(package_dir.join("package.yml"), "package.yml"))
+            .and_then(|_| tar.into_inner())
+            .and_then(|gz| gz.finish());
+        if let Err(err) = res {
+            return Err(BuildError::ContextArchiveError { path: context_path, err });
+        }
+    }
+
+    // Send it to the instance, which builds it
+    let endpoint: String = match get_packages_endpoint() {
+        Ok(endpoint) => format!("{}/build?arch={}", endpoint, arch.rust()),
+        Err(err) => {
+            return Err(BuildError::RemoteEndpointError { err });
+        },
+    };
+    let handle: TokioFile = match TokioFile::open(&context_path).await {
+        Ok(handle) => handle,
+        Err(err) => {
+            return Err(BuildError::ContextArchiveOpenError { path: context_path, err });
+        },
+    };
+    let content_length: u64 = match handle.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(err) => {
+            return Err(BuildError::ContextArchiveOpenError { path: context_path, err });
+        },
+    };
+    let progress = ProgressBar::new(0);
+    progress.set_style(ProgressStyle::default_bar().template("Building remotely... [{elapsed_precise}]").unwrap());
+    progress.enable_steady_tick(Duration::from_millis(250));
+    debug!("Sending build context '{}' to '{}'...", context_path.display(), endpoint);
+    let request = Client::new()
+        .post(&endpoint)
+        .body(Body::wrap_stream(FramedRead::new(handle, BytesCodec::new())))
+        .header("Content-Type", "application/gzip")
+        .header("Content-Length", content_length);
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(BuildError::RemoteBuildRequestError { endpoint, err });
+        },
+    };
+    progress.finish();
+    let status = response.status();
+    let log: String = match response.text().await {
+        Ok(log) => log,
+        Err(err) => {
+            return Err(BuildError::RemoteBuildResponseError { endpoint, err });
+        },
+    };
+
+    // Report the result
+    if status.is_success() {
+        debug!("Build output:\n{}", log);
+        println!(
+            "Successfully built version {} of {} {} on the remote instance. It is now available there (you do not have to push it).",
+            style(&info.version).bold().cyan(),
+            info.kind.pretty(),
+            style(&info.name).bold().cyan(),
+        );
+    } else if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY {
+        eprintln!("{log}");
+        println!(
+            "Failed to build version {} of {} {} on the remote instance. See build output above.",
+            style(&info.version).bold().cyan(),
+            info.kind.pretty(),
+            style(&info.name).bold().cyan(),
+        );
+    } else {
+        return Err(BuildError::RemoteBuildRequestFailure { endpoint, code: status, message: if log.is_empty() { None } else { Some(log) } });
+    }
+
+    // The image only exists remotely, so the package directory has no use unless we are told to keep it
+    if !keep_files {
+        if let Err(err) = fs::remove_dir_all(package_dir) {
+            return Err(BuildError::CleanupError { path: package_dir.to_path_buf(), err });
+        }
+    }
+
+    // Done
+    Ok(())
+}
@@ -13,7 +13,7 @@ use specifications::arch::Arch;
 use specifications::container::{ContainerInfo, LocalContainerInfo};
 use specifications::package::PackageInfo;
 
-use crate::build_common::{build_docker_image, clean_directory, BRANELET_URL};
+use crate::build_common::{build_docker_image, build_docker_image_remote, clean_directory, BRANELET_URL};
 use crate::errors::BuildError;
 use crate::utils::ensure_package_dir;
 
@@ -26,6 +26,7 @@ use crate::utils::ensure_package_dir;
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
 ///  - `convert_crlf`: If true, will not ask to convert CRLF files but instead just do it.
+///  - `remote`: If true, builds the image on the active remote instance instead of locally.
 ///
 /// # Errors
 /// This function may error for many reasons.
@@ -36,6 +37,7 @@ pub async fn handle(
     branelet_path: Option<PathBuf>,
     keep_files: bool,
     convert_crlf: bool,
+    remote: bool,
 ) -> Result<(), BuildError> {
     debug!("Building ecu package from container file '{}'...", file.display());
     debug!("Using {} as build context", context.display());
@@ -70,7 +72,7 @@ pub async fn handle(
                 return Err(BuildError::LockCreateError { name: document.name, err });
             },
         };
-        build(arch, document, context, &package_dir, branelet_path, keep_files, convert_crlf).await?;
+        build(arch, document, context, &package_dir, branelet_path, keep_files, convert_crlf, remote).await?;
     };
 
     // Done
@@ -89,9 +91,11 @@ pub async fn handle(
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
 ///  - `convert_crlf`: If true, will not ask to convert CRLF files but instead just do it.
+///  - `remote`: If true, builds the image on the active remote instance instead of locally.
 ///
 /// # Errors
 /// This function may error for many reasons.
+#[allow(clippy::too_many_arguments)]
 async fn build(
     arch: Arch,
     document: ContainerInfo,
@@ -100,12 +104,18 @@ async fn build(
     branelet_path: Option<PathBuf>,
     keep_files: bool,
     convert_crlf: bool,
+    remote: bool,
 ) -> Result<(), BuildError> {
     // Prepare the build directory
     let dockerfile = generate_dockerfile(&document, &context, branelet_path.is_some())?;
     prepare_directory(&document, dockerfile, branelet_path, &context, package_dir, convert_crlf)?;
     debug!("Successfully prepared package directory.");
 
+    // Let the instance build it if told to do so
+    if remote {
+        return build_docker_image_remote(arch, PackageInfo::from(document), package_dir, keep_files).await;
+    }
+
     // Build Docker image
     let tag = format!("{}:{}", document.name, document.version);
     debug!("Building image '{}' in directory '{}'", tag, package_dir.display());
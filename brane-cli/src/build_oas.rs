@@ -13,7 +13,7 @@ use specifications::arch::Arch;
 use specifications::package::{PackageInfo, PackageKind};
 use specifications::version::Version;
 
-use crate::build_common::{build_docker_image, clean_directory, BRANELET_URL};
+use crate::build_common::{build_docker_image, build_docker_image_remote, clean_directory, BRANELET_URL};
 use crate::errors::BuildError;
 use crate::utils::ensure_package_dir;
 
@@ -25,10 +25,18 @@ use crate::utils::ensure_package_dir;
 ///  - `file`: Path to the package's main file (a container file, in this case).
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
+///  - `remote`: If true, builds the image on the active remote instance instead of locally.
 ///
 /// # Errors
 /// This function may error for many reasons.
-pub async fn handle(arch: Arch, context: PathBuf, file: PathBuf, branelet_path: Option<PathBuf>, keep_files: bool) -> Result<(), BuildError> {
+pub async fn handle(
+    arch: Arch,
+    context: PathBuf,
+    file: PathBuf,
+    branelet_path: Option<PathBuf>,
+    keep_files: bool,
+    remote: bool,
+) -> Result<(), BuildError> {
     debug!("Building oas package from OAS Document '{}'...", file.display());
     debug!("Using {} as build context", context.display());
 
@@ -57,7 +65,7 @@ pub async fn handle(arch: Arch, context: PathBuf, file: PathBuf, branelet_path:
                 return Err(BuildError::LockCreateError { name: package_info.name, err });
             },
         };
-        build(arch, document, package_info, &package_dir, branelet_path, keep_files).await?;
+        build(arch, document, package_info, &package_dir, branelet_path, keep_files, remote).await?;
     };
 
     // Done
@@ -107,6 +115,7 @@ fn create_package_info(document: &OpenAPI) -> Result<PackageInfo, BuildError> {
 ///  - `package_info`: The PackageInfo document also describing the package, but in a package-kind-oblivious way.
 ///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
 ///  - `keep_files`: Determines whether or not to keep the build files after building.
+///  - `remote`: If true, builds the image on the active remote instance instead of locally.
 ///
 /// # Errors
 /// This function may error for many reasons.
@@ -117,12 +126,18 @@ async fn build(
     package_dir: &Path,
     branelet_path: Option<PathBuf>,
     keep_files: bool,
+    remote: bool,
 ) -> Result<(), BuildError> {
     // Prepare package directory.
     let dockerfile = generate_dockerfile(branelet_path.is_some())?;
     prepare_directory(&document, dockerfile, branelet_path, package_dir)?;
     debug!("Successfully prepared package directory.");
 
+    // Let the instance build it if told to do so
+    if remote {
+        return build_docker_image_remote(arch, package_info, package_dir, keep_files).await;
+    }
+
     // // Build Docker image.
     // let tag = format!("{}:{}", package_info.name, package_info.version);
     // build_docker_image(&package_dir, tag)?;
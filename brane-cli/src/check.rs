@@ -4,7 +4,7 @@
 //  Created:
 //    02 Feb 2024, 11:08:20
 //  Last edited:
-//    08 Feb 2024, 17:18:29
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -19,6 +19,8 @@ use std::{fs, io};
 
 use brane_ast::{CompileResult, Workflow};
 use brane_dsl::{Language, ParserOptions};
+use brane_tsk::api::RemotePackageProvider;
+use brane_tsk::caches::PackageIndexCache;
 use console::style;
 use error_trace::trace;
 use log::{debug, info};
@@ -49,10 +51,12 @@ use crate::instance::InstanceInfo;
 /// # Errors
 /// This function errors if we failed to get remote packages/datasets, or if the input was not valid BraneScript/Bakery.
 async fn compile(instance: &InstanceInfo, input: &str, source: String, language: Language, user: Option<String>) -> Result<Workflow, Error> {
-    // Read the package index from the remote first
+    let options: ParserOptions = ParserOptions::new(language);
+
+    // Retrieve the packages imported by the workflow from the remote first
     let url: String = format!("{}/graphql", instance.api);
-    debug!("Retrieving package index from '{url}'");
-    let pindex: PackageIndex = match brane_tsk::api::get_package_index(&url).await {
+    debug!("Retrieving imported packages from '{url}'");
+    let pindex: Arc<PackageIndex> = match PackageIndexCache::new(RemotePackageProvider::new(&url)).resolve(&source, &options).await {
         Ok(pindex) => pindex,
         Err(err) => {
             return Err(Error::PackageIndexRetrieve { url, err });
@@ -70,7 +74,7 @@ async fn compile(instance: &InstanceInfo, input: &str, source: String, language:
     };
 
     // Hit the Brane compiler
-    match brane_ast::compile_program(source.as_bytes(), &pindex, &dindex, &ParserOptions::new(language)) {
+    match brane_ast::compile_program(source.as_bytes(), &pindex, &dindex, &options) {
         CompileResult::Workflow(mut wf, warns) => {
             // Emit the warnings before continuing
             for warn in warns {
@@ -165,7 +169,7 @@ pub async fn handle(file: String, language: Language, user: Option<String>, prof
     debug!("Connecting to driver '{}'...", instance.drv);
     let rem = prof.time("Driver time");
     let mut client: DriverServiceClient = match DriverServiceClient::connect(instance.drv.to_string()).await {
-        Ok(client) => client,
+        Ok(client) => client.with_token(instance.token.clone()),
         Err(err) => {
             return Err(Error::DriverConnect { address: instance.drv, err });
         },
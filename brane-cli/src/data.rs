@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 17:39:06
 //  Last edited:
-//    26 Jul 2023, 09:36:57
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -13,12 +13,14 @@
 //
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use brane_shr::fs::copy_dir_recursively_async;
+use brane_cfg::certs::load_identity_pem;
+use brane_shr::fs::{copy_dir_recursively_async, FileLock};
+use brane_shr::suggest::suggest;
 use brane_shr::utilities::is_ip_addr;
 use brane_tsk::spec::LOCALHOST;
 use chrono::Utc;
@@ -40,7 +42,7 @@ use tokio_stream::StreamExt;
 
 use crate::errors::DataError;
 use crate::instance::InstanceInfo;
-use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir};
+use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir, lock_datasets_dir};
 
 
 /***** LIBRARY *****/
@@ -116,16 +118,18 @@ pub async fn download_data(
         let idfile: PathBuf = cert_dir.join("client-id.pem");
         let cafile: PathBuf = cert_dir.join("ca.pem");
 
-        // Load the keypair for this location as an Identity file (for which we just smash 'em together and hope that works)
-        let ident: Identity = match tfs::read(&idfile).await {
-            Ok(raw) => match Identity::from_pem(&raw) {
-                Ok(identity) => identity,
-                Err(err) => {
-                    return Err(DataError::IdentityFileError { path: idfile, err });
-                },
+        // Load the keypair for this location as an Identity file (for which we just smash 'em together and hope that works), decrypting any
+        // encrypted keys in memory since reqwest can't handle those
+        let raw: String = match load_identity_pem(&idfile) {
+            Ok(raw) => raw,
+            Err(err) => {
+                return Err(DataError::IdentityLoadError { path: idfile, err });
             },
+        };
+        let ident: Identity = match Identity::from_pem(raw.as_bytes()) {
+            Ok(identity) => identity,
             Err(err) => {
-                return Err(DataError::FileReadError { what: "client identity", path: idfile, err });
+                return Err(DataError::IdentityFileError { path: idfile, err });
             },
         };
 
@@ -252,6 +256,10 @@ pub async fn download_data(
             owners: None,
             description: None,
             created: Utc::now(),
+            schema: None,
+            tags: HashSet::new(),
+            residency: HashSet::new(),
+            provenance: None,
 
             access: HashMap::from([(LOCALHOST.into(), access.clone())]),
         };
@@ -321,11 +329,22 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
             }
             // if !path.is_file() { return Err(DataError::FileNotAFileError{ path: path.clone() }); }
         },
+
+        // Remote datasets are only fetched when they're used
+        AccessKind::S3 { .. } | AccessKind::Url { .. } => {},
     }
 
 
 
     /* Step 2: Prepare the build directory. */
+    // Keep other `brane` invocations out of the datasets directory until we're done
+    let _lock: FileLock = match lock_datasets_dir() {
+        Ok(lock) => lock,
+        Err(err) => {
+            return Err(DataError::DatasetsLockError { err });
+        },
+    };
+
     // Before we create it though, if it happens to exist, then moan about it
     if let Ok(dir) = get_dataset_dir(&info.name) {
         if dir.exists() {
@@ -356,6 +375,9 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
                 // Update the path to the target
                 *path = target;
             },
+
+            // There's nothing to copy for remote datasets
+            AccessKind::S3 { .. } | AccessKind::Url { .. } => {},
         }
     }
 
@@ -426,7 +448,8 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, proxy_addr: &Option
         let info: &DataInfo = match index.get(&name) {
             Some(info) => info,
             None => {
-                return Err(DataError::UnknownDataset { name });
+                let suggestions: Vec<String> = suggest(&name, index.names());
+                return Err(DataError::UnknownDataset { name, suggestions });
             },
         };
 
@@ -498,7 +521,13 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, proxy_addr: &Option
                     },
                 };
 
-                // Get the path to download it to
+                // Get the path to download it to (and keep other `brane` invocations from downloading it at the same time)
+                let _lock: FileLock = match lock_datasets_dir() {
+                    Ok(lock) => lock,
+                    Err(err) => {
+                        return Err(DataError::DatasetsLockError { err });
+                    },
+                };
                 let data_dir: PathBuf = match ensure_dataset_dir(&name, true) {
                     Ok(dir) => dir,
                     Err(err) => {
@@ -520,6 +549,7 @@ pub async fn download(names: Vec<String>, locs: Vec<String>, proxy_addr: &Option
         println!("Download {}", style("success").bold().cyan());
         match access {
             AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+            AccessKind::S3 { .. } | AccessKind::Url { .. } => println!("(It's available at '{access}')"),
         }
     }
 
@@ -575,6 +605,8 @@ pub fn list() -> Result<(), DataError> {
                     // The kind is the name, the access is the path to the file
                     ("File", path.to_string_lossy().into(), is_linked)
                 },
+                access @ AccessKind::S3 { .. } => ("S3", access.to_string(), true),
+                access @ AccessKind::Url { .. } => ("URL", access.to_string(), true),
             };
         let sis_linked: String = if is_linked { String::from("yes") } else { String::from("no") };
         let (kind, access, is_linked): (Cow<str>, Cow<str>, Cow<str>) = (
@@ -635,7 +667,7 @@ pub fn path(datasets: Vec<impl AsRef<str>>) -> Result<(), DataError> {
                         println!("{}", path.display());
                     },
 
-                    #[allow(unreachable_patterns)]
+                    // Remote datasets don't have a path
                     _ => {
                         println!("<none>")
                     },
@@ -644,7 +676,7 @@ pub fn path(datasets: Vec<impl AsRef<str>>) -> Result<(), DataError> {
                 return Err(DataError::UnavailableDataset { name: d.into(), locs: info.access.keys().cloned().collect() });
             }
         } else {
-            return Err(DataError::UnknownDataset { name: d.into() });
+            return Err(DataError::UnknownDataset { name: d.into(), suggestions: suggest(d, index.names()) });
         }
     }
 
@@ -693,6 +725,12 @@ pub fn remove(datasets: Vec<impl AsRef<str>>, force: bool) -> Result<(), DataErr
         }
 
         // Everything checks out so just delete that folder
+        let _lock: FileLock = match lock_datasets_dir() {
+            Ok(lock) => lock,
+            Err(err) => {
+                return Err(DataError::DatasetsLockError { err });
+            },
+        };
         if let Err(err) = fs::remove_dir_all(&dir) {
             return Err(DataError::RemoveError { path: dir, err });
         }
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Feb 2022, 10:27:28
 //  Last edited:
-//    07 Mar 2024, 14:16:08
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -18,10 +18,14 @@ use std::path::PathBuf;
 
 use brane_shr::errors::ErrorTrace as _;
 use brane_shr::formatters::{BlockFormatter, PrettyListFormatter};
+use brane_shr::suggest::DidYouMean;
 use reqwest::StatusCode;
 use specifications::address::Address;
+use specifications::checking::{ApprovalPending, PolicyDenial};
 use specifications::container::{ContainerInfoError, Image, LocalContainerInfoError};
+use specifications::errors::ErrorCode;
 use specifications::package::{PackageInfoError, PackageKindError};
+use specifications::reporting::TaskFailure;
 use specifications::version::{ParseError as VersionParseError, Version};
 
 
@@ -57,6 +61,8 @@ pub enum CliError {
     RegistryError { err: RegistryError },
     /// Errors that occur during the repl command
     ReplError { err: ReplError },
+    /// Errors that occur when inspecting the intermediate results of an execution
+    ResultsError { err: ResultsError },
     /// Errors that occur during the run command
     RunError { err: RunError },
     /// Errors that occur in the test command
@@ -65,8 +71,12 @@ pub enum CliError {
     VerifyError { err: VerifyError },
     /// Errors that occur in the version command
     VersionError { err: VersionError },
+    /// Errors that occur in any of the workflow command(s)
+    WorkflowError { err: WorkflowError },
     /// Errors that occur when upgrading old config files.
     UpgradeError { err: crate::upgrade::Error },
+    /// Errors that occur when preparing the sinks for a workflow's output.
+    SinkError { err: SinkError },
     /// Errors that occur in some inter-subcommand utility
     UtilError { err: UtilError },
     /// Temporary wrapper around any anyhow error
@@ -95,11 +105,14 @@ impl Display for CliError {
             PackageError { err } => write!(f, "{err}"),
             RegistryError { err } => write!(f, "{err}"),
             ReplError { err } => write!(f, "{err}"),
+            ResultsError { err } => write!(f, "{err}"),
             RunError { err } => write!(f, "{err}"),
             TestError { err } => write!(f, "{err}"),
             VerifyError { err } => write!(f, "{err}"),
             VersionError { err } => write!(f, "{err}"),
+            WorkflowError { err } => write!(f, "{err}"),
             UpgradeError { err } => write!(f, "{err}"),
+            SinkError { err } => write!(f, "{err}"),
             UtilError { err } => write!(f, "{err}"),
             OtherError { err } => write!(f, "{err}"),
 
@@ -123,11 +136,14 @@ impl Error for CliError {
             PackageError { err } => err.source(),
             RegistryError { err } => err.source(),
             ReplError { err } => err.source(),
+            ResultsError { err } => err.source(),
             RunError { err } => err.source(),
             TestError { err } => err.source(),
             VerifyError { err } => err.source(),
             VersionError { err } => err.source(),
+            WorkflowError { err } => err.source(),
             UpgradeError { err } => err.source(),
+            SinkError { err } => err.source(),
             UtilError { err } => err.source(),
             OtherError { err } => err.source(),
 
@@ -248,6 +264,18 @@ pub enum BuildError {
     ImageBuildLaunchError { command: String, err: std::io::Error },
     /// The command to build the image returned a non-zero exit code (we don't accept stdout or stderr here, as the command's output itself will be passed to stdout & stderr)
     ImageBuildError { command: String, code: i32 },
+    /// Could not resolve the endpoint of the instance to build remotely on.
+    RemoteEndpointError { err: RegistryError },
+    /// Could not write the build context to send to the instance.
+    ContextArchiveError { path: PathBuf, err: std::io::Error },
+    /// Could not re-open the build context to send it to the instance.
+    ContextArchiveOpenError { path: PathBuf, err: std::io::Error },
+    /// Could not send the build context to the instance.
+    RemoteBuildRequestError { endpoint: String, err: reqwest::Error },
+    /// Could not read the response of the instance to a remote build.
+    RemoteBuildResponseError { endpoint: String, err: reqwest::Error },
+    /// The instance refused to build remotely.
+    RemoteBuildRequestFailure { endpoint: String, code: StatusCode, message: Option<String> },
 
     /// Could not get the digest from the just-built image
     DigestError { err: brane_tsk::docker::Error },
@@ -366,6 +394,19 @@ impl Display for BuildError {
             ),
             ImageBuildLaunchError { command, .. } => write!(f, "Could not run command '{command}' to build the package image"),
             ImageBuildError { command, code } => write!(f, "Command '{command}' to build the package image returned exit code {code}"),
+            RemoteEndpointError { .. } => write!(f, "Could not resolve the packages endpoint of the active instance"),
+            ContextArchiveError { path, .. } => write!(f, "Could not compress build context to '{}'", path.display()),
+            ContextArchiveOpenError { path, .. } => write!(f, "Could not re-open build context '{}'", path.display()),
+            RemoteBuildRequestError { endpoint, .. } => write!(f, "Could not send build context to '{endpoint}'"),
+            RemoteBuildResponseError { endpoint, .. } => write!(f, "Could not read response of '{endpoint}'"),
+            RemoteBuildRequestFailure { endpoint, code, message } => write!(
+                f,
+                "Remote build at '{}' failed with status code {} ({}){}",
+                endpoint,
+                code.as_u16(),
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
 
             DigestError { .. } => write!(f, "Could not get Docker image digest"),
             PackageFileCreateError { .. } => write!(f, "Could not write package info to build directory"),
@@ -445,6 +486,12 @@ impl Error for BuildError {
             BuildKitError { .. } => None,
             ImageBuildLaunchError { err, .. } => Some(err),
             ImageBuildError { .. } => None,
+            RemoteEndpointError { err } => Some(err),
+            ContextArchiveError { err, .. } => Some(err),
+            ContextArchiveOpenError { err, .. } => Some(err),
+            RemoteBuildRequestError { err, .. } => Some(err),
+            RemoteBuildResponseError { err, .. } => Some(err),
+            RemoteBuildRequestFailure { .. } => None,
 
             DigestError { err } => Some(err),
             PackageFileCreateError { err } => Some(err),
@@ -519,6 +566,23 @@ pub enum CertsError {
     /// Failed to write to the given file.
     FileWriteError { what: &'static str, path: PathBuf, err: std::io::Error },
 
+    /// Failed to load the instance's info file.
+    InstanceInfoLoadError { name: String, err: InstanceError },
+    /// Failed to build the HTTP client.
+    ClientBuildError { err: reqwest::Error },
+    /// Failed to send a request.
+    RequestError { address: String, err: reqwest::Error },
+    /// A request returned a non-2xx status code.
+    RequestFailure { address: String, code: StatusCode, message: Option<String> },
+    /// Failed to get the body of a response.
+    ResponseTextError { address: String, err: reqwest::Error },
+    /// The registry sent a CA certificate that we could not parse.
+    CaParseError { address: String, err: openssl::error::ErrorStack },
+    /// The registry sent a different CA certificate than the enrollment token promised.
+    CaFingerprintMismatch { address: String, expected: String, got: String },
+    /// Failed to generate a key or certificate signing request.
+    KeyGenerateError { err: openssl::error::ErrorStack },
+
     /// Failed to load instances directory.
     InstancesDirError { err: UtilError },
     /// Failed to read the directory with instances.
@@ -570,6 +634,26 @@ impl Display for CertsError {
             FileOpenError { what, path, .. } => write!(f, "Failed to open {} file '{}' for appending", what, path.display()),
             FileWriteError { what, path, .. } => write!(f, "Failed to write to {} file '{}'", what, path.display()),
 
+            InstanceInfoLoadError { name, .. } => write!(f, "Failed to load info of instance '{name}'"),
+            ClientBuildError { .. } => write!(f, "Failed to build HTTP client"),
+            RequestError { address, .. } => write!(f, "Failed to send request to '{address}'"),
+            RequestFailure { address, code, message } => write!(
+                f,
+                "Request to '{}' failed with status code {} ({}){}",
+                address,
+                code,
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(msg) = message { format!(": {msg}") } else { String::new() }
+            ),
+            ResponseTextError { address, .. } => write!(f, "Failed to get body from response sent by '{address}'"),
+            CaParseError { address, .. } => write!(f, "Failed to parse CA certificate sent by '{address}'"),
+            CaFingerprintMismatch { address, expected, got } => write!(
+                f,
+                "CA certificate sent by '{address}' has fingerprint '{got}', but the enrollment token expects '{expected}' (are you enrolling at \
+                 the right domain?)"
+            ),
+            KeyGenerateError { .. } => write!(f, "Failed to generate client key and certificate signing request"),
+
             InstancesDirError { .. } => write!(f, "Failed to get instances directory"),
             DirReadError { what, path, .. } => write!(f, "Failed to read {} directory '{}'", what, path.display()),
             DirEntryReadError { what, path, entry, .. } => {
@@ -607,6 +691,15 @@ impl Error for CertsError {
             FileOpenError { err, .. } => Some(err),
             FileWriteError { err, .. } => Some(err),
 
+            InstanceInfoLoadError { err, .. } => Some(err),
+            ClientBuildError { err } => Some(err),
+            RequestError { err, .. } => Some(err),
+            RequestFailure { .. } => None,
+            ResponseTextError { err, .. } => Some(err),
+            CaParseError { err, .. } => Some(err),
+            CaFingerprintMismatch { .. } => None,
+            KeyGenerateError { err } => Some(err),
+
             InstancesDirError { err } => Some(err),
             DirReadError { err, .. } => Some(err),
             DirEntryReadError { err, .. } => Some(err),
@@ -696,6 +789,8 @@ pub enum DataError {
     CertsDirError { err: CertsError },
     /// Failed to parse an identity file.
     IdentityFileError { path: PathBuf, err: reqwest::Error },
+    /// Failed to load an identity file, including decrypting its private key.
+    IdentityLoadError { path: PathBuf, err: brane_cfg::certs::Error },
     /// Failed to parse a certificate.
     CertificateError { path: PathBuf, err: reqwest::Error },
     /// A directory was not a directory but a file.
@@ -732,6 +827,8 @@ pub enum DataError {
 
     /// Failed to get the datasets folder
     DatasetsError { err: UtilError },
+    /// Failed to lock the datasets folder.
+    DatasetsLockError { err: UtilError },
     /// Failed to fetch the local data index.
     LocalDataIndexError { err: brane_tsk::local::Error },
 
@@ -768,7 +865,7 @@ pub enum DataError {
     UnknownLocation { name: String },
 
     /// The given dataset was unknown to us.
-    UnknownDataset { name: String },
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// the given dataset was known but not locally available.
     UnavailableDataset { name: String, locs: Vec<String> },
 
@@ -799,6 +896,7 @@ impl Display for DataError {
             FileReadError { what, path, .. } => write!(f, "Failed to read {} file '{}'", what, path.display()),
             CertsDirError { .. } => write!(f, "Failed to get certificates directory for active instance"),
             IdentityFileError { path, .. } => write!(f, "Failed to parse identity file '{}'", path.display()),
+            IdentityLoadError { path, .. } => write!(f, "Failed to load identity file '{}'", path.display()),
             CertificateError { path, .. } => write!(f, "Failed to parse certificate '{}'", path.display()),
             DirNotADirError { what, path } => write!(f, "{} directory '{}' is not a directory", what, path.display()),
             DirRemoveError { what, path, .. } => write!(f, "Failed to remove {} directory '{}'", what, path.display()),
@@ -817,6 +915,7 @@ impl Display for DataError {
             TarExtractError { .. } => write!(f, "Failed to extract downloaded archive"),
 
             DatasetsError { .. } => write!(f, "Failed to get datasets folder"),
+            DatasetsLockError { .. } => write!(f, "Failed to lock datasets folder"),
             LocalDataIndexError { .. } => write!(f, "Failed to get local data index"),
 
             AssetFileError { path, .. } => write!(f, "Failed to load given asset file '{}'", path.display()),
@@ -836,7 +935,7 @@ impl Display for DataError {
             DataSelectError { .. } => write!(f, "Failed to ask the user (you!) to select a download location"),
             UnknownLocation { name } => write!(f, "Unknown location '{name}'"),
 
-            UnknownDataset { name } => write!(f, "Unknown dataset '{name}'"),
+            UnknownDataset { name, suggestions } => write!(f, "Unknown dataset '{name}'{}", DidYouMean::new(suggestions)),
             UnavailableDataset { name, locs } => write!(
                 f,
                 "Dataset '{}' is unavailable{}",
@@ -866,6 +965,7 @@ impl Error for DataError {
             FileReadError { err, .. } => Some(err),
             CertsDirError { .. } => None,
             IdentityFileError { err, .. } => Some(err),
+            IdentityLoadError { err, .. } => Some(err),
             CertificateError { err, .. } => Some(err),
             DirNotADirError { .. } => None,
             DirRemoveError { err, .. } => Some(err),
@@ -885,6 +985,7 @@ impl Error for DataError {
             TarExtractError { .. } => None,
 
             DatasetsError { .. } => None,
+            DatasetsLockError { err } => Some(err),
             LocalDataIndexError { .. } => None,
 
             AssetFileError { err, .. } => Some(err),
@@ -992,6 +1093,8 @@ pub enum InstanceError {
 
     /// Failed to get the instances directory.
     InstancesDirError { err: UtilError },
+    /// Failed to lock the instances directory.
+    InstancesDirLockError { err: UtilError },
     /// Failed to read the instances directory.
     InstancesDirReadError { path: PathBuf, err: std::io::Error },
     /// Failed to read an entry in the instances directory.
@@ -1014,6 +1117,25 @@ pub enum InstanceError {
     /// Failed to create a new active instance link.
     ActiveInstanceCreateError { path: PathBuf, target: String, err: std::io::Error },
 
+    /// Failed to read an instance's certificates directory.
+    CertsDirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to read an entry in an instance's certificates directory.
+    CertsDirEntryReadError { path: PathBuf, entry: usize, err: std::io::Error },
+    /// Failed to create the certificates directory of a domain.
+    CertsDirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the CA certificate of a domain.
+    CaCertReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to write the CA certificate of a domain.
+    CaCertWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to serialize an instance bundle.
+    BundleSerializeError { err: serde_yaml::Error },
+    /// Failed to write an instance bundle.
+    BundleWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to read an instance bundle.
+    BundleReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse an instance bundle.
+    BundleParseError { path: PathBuf, err: serde_yaml::Error },
+
     /// No instance is active
     NoActiveInstance,
 }
@@ -1052,6 +1174,7 @@ impl Display for InstanceError {
             },
 
             InstancesDirError { .. } => write!(f, "Failed to get the instances directory"),
+            InstancesDirLockError { .. } => write!(f, "Failed to lock the instances directory"),
             InstancesDirReadError { path, .. } => write!(f, "Failed to read instances directory '{}'", path.display()),
             InstancesDirEntryReadError { path, entry, .. } => {
                 write!(f, "Failed to read instances directory '{}' entry {}", path.display(), entry)
@@ -1068,6 +1191,16 @@ impl Display for InstanceError {
                 write!(f, "Failed to create active instance link '{}' to '{}'", path.display(), target)
             },
 
+            CertsDirReadError { path, .. } => write!(f, "Failed to read certificates directory '{}'", path.display()),
+            CertsDirEntryReadError { path, entry, .. } => write!(f, "Failed to read certificates directory '{}' entry {}", path.display(), entry),
+            CertsDirCreateError { path, .. } => write!(f, "Failed to create certificates directory '{}'", path.display()),
+            CaCertReadError { path, .. } => write!(f, "Failed to read CA certificate '{}'", path.display()),
+            CaCertWriteError { path, .. } => write!(f, "Failed to write CA certificate '{}'", path.display()),
+            BundleSerializeError { .. } => write!(f, "Failed to serialize instance bundle"),
+            BundleWriteError { path, .. } => write!(f, "Failed to write instance bundle '{}'", path.display()),
+            BundleReadError { path, .. } => write!(f, "Failed to read instance bundle '{}'", path.display()),
+            BundleParseError { path, .. } => write!(f, "Failed to parse instance bundle '{}' as valid YAML", path.display()),
+
             NoActiveInstance => write!(f, "No active instance is set (run 'brane instance select' first)"),
         }
     }
@@ -1092,6 +1225,7 @@ impl Error for InstanceError {
             ConfirmationError { err, .. } => Some(err),
 
             InstancesDirError { err, .. } => Some(err),
+            InstancesDirLockError { err } => Some(err),
             InstancesDirReadError { err, .. } => Some(err),
             InstancesDirEntryReadError { err, .. } => Some(err),
             ActiveInstanceTargetError { err, .. } => Some(err),
@@ -1104,6 +1238,16 @@ impl Error for InstanceError {
             ActiveInstanceRemoveError { err, .. } => Some(err),
             ActiveInstanceCreateError { err, .. } => Some(err),
 
+            CertsDirReadError { err, .. } => Some(err),
+            CertsDirEntryReadError { err, .. } => Some(err),
+            CertsDirCreateError { err, .. } => Some(err),
+            CaCertReadError { err, .. } => Some(err),
+            CaCertWriteError { err, .. } => Some(err),
+            BundleSerializeError { err } => Some(err),
+            BundleWriteError { err, .. } => Some(err),
+            BundleReadError { err, .. } => Some(err),
+            BundleParseError { err, .. } => Some(err),
+
             NoActiveInstance => None,
         }
     }
@@ -1223,6 +1367,10 @@ pub enum RegistryError {
     FunctionsParseError { url: String, raw: String, err: serde_json::Error },
     /// Could not parse the types as proper PackageInfo types
     TypesParseError { url: String, raw: String, err: serde_json::Error },
+    /// Could not parse the minimum resources as proper PackageInfo resources
+    ResourcesParseError { url: String, raw: String, err: serde_json::Error },
+    /// Could not parse the vulnerability scan report as a proper PackageInfo scan report
+    ScanParseError { url: String, raw: String, err: serde_json::Error },
     /// Could not create a file for the PackageInfo
     PackageInfoCreateError { path: PathBuf, err: std::io::Error },
     /// Could not write the PackageInfo
@@ -1244,6 +1392,19 @@ pub enum RegistryError {
     PackageArchiveOpenError { path: PathBuf, err: std::io::Error },
     /// Failed to upload the compressed file to the instance
     UploadError { path: PathBuf, endpoint: String, err: reqwest::Error },
+
+    /// Failed to read the package info of a local package.
+    PackageInfoReadError { name: String, version: Version, err: PackageInfoError },
+    /// The given OCI repository (combined with a package name and version) is not a valid OCI reference.
+    OciReferenceError { raw: String, err: brane_shr::oci::Error },
+    /// Failed to load the credentials for OCI registries.
+    OciCredentialsError { err: brane_shr::oci::Error },
+    /// Failed to push a package to an OCI registry.
+    OciPushError { name: String, version: Version, reference: String, err: brane_shr::oci::Error },
+    /// Failed to send the request to register a package pushed to an OCI registry with the instance.
+    OciRegisterError { endpoint: String, err: reqwest::Error },
+    /// The instance refused to register a package pushed to an OCI registry.
+    OciRegisterFailure { endpoint: String, status: StatusCode, message: Option<String> },
 }
 impl Display for RegistryError {
     #[inline]
@@ -1278,6 +1439,10 @@ impl Display for RegistryError {
             RequirementParseError { url, raw, err } => write!(f, "Could not parse '{raw}' (received from '{url}') as package requirement: {err}"),
             FunctionsParseError { url, raw, err } => write!(f, "Could not parse '{raw}' (received from '{url}') as package functions: {err}"),
             TypesParseError { url, raw, err } => write!(f, "Could not parse '{raw}' (received from '{url}') as package types: {err}"),
+            ResourcesParseError { url, raw, err } => {
+                write!(f, "Could not parse '{raw}' (received from '{url}') as package minimum resources: {err}")
+            },
+            ScanParseError { url, raw, err } => write!(f, "Could not parse '{raw}' (received from '{url}') as package scan report: {err}"),
             PackageInfoCreateError { path, err } => write!(f, "Could not create PackageInfo file '{}': {}", path.display(), err),
             PackageInfoWriteError { path, err } => write!(f, "Could not write to PackageInfo file '{}': {}", path.display(), err),
             NoPackageInfo { url } => write!(f, "Server '{url}' responded with empty response (is your name/version correct?)"),
@@ -1293,6 +1458,22 @@ impl Display for RegistryError {
             UploadError { path, endpoint, err } => {
                 write!(f, "Could not upload compressed package archive '{}' to '{}': {}", path.display(), endpoint, err)
             },
+
+            PackageInfoReadError { name, version, err } => write!(f, "Could not read package info of package '{name}' (version {version}): {err}"),
+            OciReferenceError { raw, err } => write!(f, "Could not use '{raw}' as OCI reference: {err}"),
+            OciCredentialsError { err } => write!(f, "Could not load OCI registry credentials: {err}"),
+            OciPushError { name, version, reference, err } => {
+                write!(f, "Could not push package '{name}' (version {version}) to '{reference}': {err}")
+            },
+            OciRegisterError { endpoint, err } => write!(f, "Could not send request to register OCI package to '{endpoint}': {err}"),
+            OciRegisterFailure { endpoint, status, message } => write!(
+                f,
+                "Request to register OCI package to '{}' was met with status code {} ({}){}",
+                endpoint,
+                status.as_u16(),
+                status.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
         }
     }
 }
@@ -1312,12 +1493,30 @@ pub enum ReplError {
     /// Failed to load the login file.
     InstanceInfoError { err: InstanceError },
 
+    /// Failed to connect to the remote driver.
+    DriverConnectError { address: String, err: specifications::driving::Error },
+    /// The remote driver failed to list the active sessions.
+    SessionListError { address: String, err: tonic::Status },
+    /// Failed to ask the user which session to attach to.
+    SessionSelectError { err: std::io::Error },
+    /// The remote driver sent a session ID that is not a valid application ID.
+    SessionIdError { raw: String, err: brane_tsk::errors::IdError },
+
     /// Failed to initialize one of the states.
     InitializeError { what: &'static str, err: RunError },
     /// Failed to run one of the VMs/clients.
     RunError { what: &'static str, err: RunError },
     /// Failed to process the VM result.
     ProcessError { what: &'static str, err: RunError },
+
+    /// Failed to read the snapshot of a previous REPL session.
+    SnapshotReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the snapshot of a previous REPL session.
+    SnapshotParseError { path: PathBuf, err: serde_json::Error },
+    /// The snapshot of a previous REPL session was made for another kind of REPL.
+    SnapshotMismatch { path: PathBuf, expected: String, got: String },
+    /// Failed to restore the variables of a previous REPL session.
+    SnapshotRestoreError { path: PathBuf, err: brane_exe::errors::FrameStackError },
 }
 impl Display for ReplError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -1328,9 +1527,19 @@ impl Display for ReplError {
             EditorCreateError { .. } => write!(f, "Failed to create new rustyline editor"),
             InstanceInfoError { .. } => write!(f, "Failed to load instance info file"),
 
+            DriverConnectError { address, .. } => write!(f, "Could not connect to remote Brane instance '{address}'"),
+            SessionListError { address, .. } => write!(f, "Could not list sessions of remote Brane instance '{address}': remote returned status"),
+            SessionSelectError { .. } => write!(f, "Failed to ask the user (you!) to select a session"),
+            SessionIdError { raw, .. } => write!(f, "Could not parse session ID '{raw}' as an application ID"),
+
             InitializeError { what, .. } => write!(f, "Failed to initialize {what} and associated structures"),
             RunError { what, .. } => write!(f, "Failed to execute workflow on {what}"),
             ProcessError { what, .. } => write!(f, "Failed to process {what} workflow results"),
+
+            SnapshotReadError { path, .. } => write!(f, "Failed to read REPL snapshot '{}'", path.display()),
+            SnapshotParseError { path, .. } => write!(f, "Failed to parse REPL snapshot '{}'", path.display()),
+            SnapshotMismatch { path, expected, got } => write!(f, "REPL snapshot '{}' was made for {got}, not {expected}", path.display()),
+            SnapshotRestoreError { path, .. } => write!(f, "Failed to restore variables from REPL snapshot '{}'", path.display()),
         }
     }
 }
@@ -1343,9 +1552,64 @@ impl Error for ReplError {
             EditorCreateError { err } => Some(err),
             InstanceInfoError { err } => Some(err),
 
+            DriverConnectError { err, .. } => Some(err),
+            SessionListError { err, .. } => Some(err),
+            SessionSelectError { err } => Some(err),
+            SessionIdError { err, .. } => Some(err),
+
             InitializeError { err, .. } => Some(err),
             RunError { err, .. } => Some(err),
             ProcessError { err, .. } => Some(err),
+
+            SnapshotReadError { err, .. } => Some(err),
+            SnapshotParseError { err, .. } => Some(err),
+            SnapshotMismatch { .. } => None,
+            SnapshotRestoreError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Collects errors during the `brane workflow results` subcommand.
+#[derive(Debug)]
+pub enum ResultsError {
+    /// Failed to read the info of the instance we're logged-in to.
+    InstanceInfoError { err: InstanceError },
+    /// Failed to connect to the remote driver.
+    DriverConnectError { address: String, err: specifications::driving::Error },
+    /// The remote driver failed to list the intermediate results of an execution.
+    ListError { address: String, id: String, err: tonic::Status },
+    /// The remote driver failed to promote an intermediate result.
+    PromoteError { address: String, id: String, name: String, err: tonic::Status },
+    /// Failed to download a promoted intermediate result.
+    DownloadError { name: String, err: DataError },
+}
+impl Display for ResultsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ResultsError::*;
+        match self {
+            InstanceInfoError { .. } => write!(f, "Could not read active instance info file"),
+            DriverConnectError { address, .. } => write!(f, "Could not connect to remote Brane instance '{address}'"),
+            ListError { address, id, .. } => {
+                write!(f, "Could not list intermediate results of execution '{id}' on remote Brane instance '{address}'")
+            },
+            PromoteError { address, id, name, .. } => {
+                write!(f, "Could not promote intermediate result '{name}' of execution '{id}' on remote Brane instance '{address}'")
+            },
+            DownloadError { name, .. } => write!(f, "Failed to download promoted intermediate result '{name}'"),
+        }
+    }
+}
+impl Error for ResultsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ResultsError::*;
+        match self {
+            InstanceInfoError { err } => Some(err),
+            DriverConnectError { err, .. } => Some(err),
+            ListError { err, .. } => Some(err),
+            PromoteError { err, .. } => Some(err),
+            DownloadError { err, .. } => Some(err),
         }
     }
 }
@@ -1379,6 +1643,8 @@ pub enum RunError {
     RemotePackageIndexError { address: String, err: brane_tsk::errors::ApiError },
     /// Failed to create the remote data index.
     RemoteDataIndexError { address: String, err: brane_tsk::errors::ApiError },
+    /// Failed to resolve the packages imported by a snippet from the remote instance.
+    RemotePackageResolveError { err: brane_tsk::errors::ApiError },
     /// Failed to pull the delegate map from the remote delegate index(ish - `brane-api`)
     RemoteDelegatesError { address: String, err: DelegatesError },
     /// Could not connect to the given address
@@ -1400,11 +1666,23 @@ pub enum RunError {
     ValueParseError { address: String, raw: String, err: serde_json::Error },
     /// The workflow was denied by some checker.
     ExecDenied { err: Box<dyn Error> },
+    /// The workflow was denied by some checker, which explained why.
+    PolicyDenied { denial: PolicyDenial },
+    /// The workflow needed a download that awaits the approval of a domain's data steward.
+    ApprovalPending { pending: ApprovalPending },
     /// Failed to run the workflow
     ExecError { err: Box<dyn Error> },
+    /// Failed to run the workflow, and the remote told us why with an [`ErrorCode`].
+    ExecFailed { code: ErrorCode, err: Box<dyn Error> },
+    /// A task of the workflow failed on the remote, which told us why.
+    TaskFailed { name: String, location: String, failure: Box<TaskFailure> },
+    /// Failed to show the progress view of a workflow in the terminal.
+    ProgressViewError { err: std::io::Error },
+    /// The user closed the progress view before the workflow completed.
+    ProgressViewInterrupted,
 
     /// The returned dataset was unknown.
-    UnknownDataset { name: String },
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// The returend dataset was known but not available locally.
     UnavailableDataset { name: String, locs: Vec<String> },
     /// Failed to download remote dataset.
@@ -1437,6 +1715,7 @@ impl Display for RunError {
             InstancePathError { name, .. } => write!(f, "Could not get path of instance '{name}'"),
             RemotePackageIndexError { address, .. } => write!(f, "Failed to fetch remote package index from '{address}'"),
             RemoteDataIndexError { address, .. } => write!(f, "Failed to fetch remote data index from '{address}'"),
+            RemotePackageResolveError { .. } => write!(f, "Failed to resolve imported packages from remote instance"),
             RemoteDelegatesError { address, .. } => write!(f, "Failed to fetch delegates map from '{address}'"),
             ClientConnectError { address, .. } => write!(f, "Could not connect to remote Brane instance '{address}'"),
             AppIdError { address, raw, .. } => write!(f, "Could not parse '{raw}' send by remote '{address}' as an application ID"),
@@ -1451,9 +1730,21 @@ impl Display for RunError {
             },
             ValueParseError { address, raw, .. } => write!(f, "Could not parse '{raw}' sent by remote '{address}' as a value"),
             ExecDenied { .. } => write!(f, "Workflow was denied"),
+            PolicyDenied { denial } => write!(f, "Workflow was denied by the checker of domain '{}'", denial.domain),
+            ApprovalPending { pending } => write!(f, "Workflow awaits approval by the data steward of domain '{}'", pending.domain),
             ExecError { .. } => write!(f, "Failed to run workflow"),
+            ExecFailed { code: code @ ErrorCode::PlrUnplannable, .. } => {
+                write!(f, "Failed to run workflow ({code}); check that all packages and datasets it uses are available on some domain")
+            },
+            ExecFailed { code: code @ ErrorCode::PlrResidencyViolation, .. } => {
+                write!(f, "Failed to run workflow ({code}); it would move data outside of its residency zones")
+            },
+            ExecFailed { code, .. } => write!(f, "Failed to run workflow ({code})"),
+            TaskFailed { name, location, failure } => write!(f, "Failed to run workflow; task '{name}' at '{location}' failed with {failure}"),
+            ProgressViewError { .. } => write!(f, "Failed to show workflow progress view"),
+            ProgressViewInterrupted => write!(f, "Interrupted while following workflow (it may still be running on the instance)"),
 
-            UnknownDataset { name } => write!(f, "Unknown dataset '{name}'"),
+            UnknownDataset { name, suggestions } => write!(f, "Unknown dataset '{name}'{}", DidYouMean::new(suggestions)),
             UnavailableDataset { name, locs } => write!(
                 f,
                 "Unavailable dataset '{}'{}",
@@ -1489,6 +1780,7 @@ impl Error for RunError {
             InstancePathError { err, .. } => Some(err),
             RemotePackageIndexError { err, .. } => Some(err),
             RemoteDataIndexError { err, .. } => Some(err),
+            RemotePackageResolveError { err } => Some(err),
             RemoteDelegatesError { err, .. } => Some(err),
             ClientConnectError { err, .. } => Some(err),
             AppIdError { err, .. } => Some(err),
@@ -1499,7 +1791,13 @@ impl Error for RunError {
             CommandRequestError { err, .. } => Some(err),
             ValueParseError { err, .. } => Some(err),
             ExecDenied { err } => Some(&**err),
+            PolicyDenied { .. } => None,
+            ApprovalPending { .. } => None,
             ExecError { err } => Some(&**err),
+            ExecFailed { err, .. } => Some(&**err),
+            TaskFailed { .. } => None,
+            ProgressViewError { err } => Some(err),
+            ProgressViewInterrupted => None,
 
             UnknownDataset { .. } => None,
             UnavailableDataset { .. } => None,
@@ -1646,6 +1944,136 @@ impl Error for VersionError {}
 
 
 
+/// Collects errors relating to the workflow command(s).
+#[derive(Debug)]
+pub enum WorkflowError {
+    /// The compile step from `brane_ast` failed.
+    AstCompile { input: String },
+    /// Failed to get the local datasets directory.
+    DatasetsDir { err: UtilError },
+    /// Failed to read the input from the given file.
+    InputFileRead { path: PathBuf, err: std::io::Error },
+    /// Failed to read the input from stdin.
+    InputStdinRead { err: std::io::Error },
+    /// Failed to get the local data index.
+    LocalDataIndex { err: brane_tsk::local::Error },
+    /// Failed to get the local package index.
+    LocalPackageIndex { err: brane_tsk::local::Error },
+    /// Failed to create the file to write a graph to.
+    OutputCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to write a graph.
+    OutputWrite { path: PathBuf, err: brane_ast::Error },
+    /// Failed to get the local packages directory.
+    PackagesDir { err: UtilError },
+    /// The policy file has an extension we don't know.
+    PolicyExtension { path: PathBuf },
+    /// Failed to compile an eFLINT policy file to eFLINT JSON.
+    PolicyCompile { path: PathBuf, err: eflint_to_json::Error },
+    /// Failed to read an eFLINT JSON policy file.
+    PolicyRead { path: PathBuf, err: std::io::Error },
+    /// The (compiled) policy file was not valid eFLINT JSON.
+    PolicyDeserialize { path: PathBuf, err: serde_json::Error },
+    /// Failed to send the request to the reasoner.
+    ReasonerRequest { address: Address, err: reqwest::Error },
+    /// The reasoner responded with a non-2xx status code.
+    ReasonerFailure { address: Address, code: StatusCode, message: Option<String> },
+    /// The reasoner responded with something that isn't an eFLINT JSON response.
+    ReasonerResponse { address: Address, err: reqwest::Error },
+    /// The reasoner could not process the policy and the workflow.
+    ReasonerRejected { address: Address, errors: Vec<String> },
+    /// Failed to create the temporary directory for intermediate results.
+    ResultsDirCreate { err: std::io::Error },
+    /// Failed to load the simulated domains.
+    Simulation { err: SimulationError },
+    /// The policies of some simulated domains did not allow the workflow.
+    SimulationDenied { domains: Vec<String> },
+    /// Failed to convert a workflow to the checker's representation.
+    WorkflowConvert { input: String, err: chk_workflow::compile::Error },
+    /// Failed to serialize a workflow (or its eFLINT representation).
+    WorkflowSerialize { input: String, err: serde_json::Error },
+    /// Failed to plan or run a workflow.
+    WorkflowRun { input: String, err: OfflineVmError },
+}
+impl Display for WorkflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use WorkflowError::*;
+        match self {
+            AstCompile { input } => write!(f, "Failed to compile workflow '{input}' (see output above)"),
+            DatasetsDir { .. } => write!(f, "Failed to get local datasets directory"),
+            InputFileRead { path, .. } => write!(f, "Failed to read input file '{}'", path.display()),
+            InputStdinRead { .. } => write!(f, "Failed to read input from stdin"),
+            LocalDataIndex { .. } => write!(f, "Failed to get local data index"),
+            LocalPackageIndex { .. } => write!(f, "Failed to get local package index"),
+            OutputCreate { path, .. } => write!(f, "Failed to create output file '{}'", path.display()),
+            OutputWrite { path, .. } => write!(f, "Failed to write graph to '{}'", path.display()),
+            PackagesDir { .. } => write!(f, "Failed to get local packages directory"),
+            PolicyExtension { path } => {
+                write!(f, "Cannot derive policy language of '{}' (expected an '.eflint' or '.json' file)", path.display())
+            },
+            PolicyCompile { path, .. } => write!(f, "Failed to compile policy file '{}' to eFLINT JSON", path.display()),
+            PolicyRead { path, .. } => write!(f, "Failed to read policy file '{}'", path.display()),
+            PolicyDeserialize { path, .. } => write!(f, "Policy file '{}' is not valid eFLINT JSON", path.display()),
+            ReasonerRequest { address, .. } => write!(f, "Failed to send request to reasoner '{address}'"),
+            ReasonerFailure { address, code, message } => write!(
+                f,
+                "Request to reasoner '{}' failed with status {} ({}){}",
+                address,
+                code.as_u16(),
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message {
+                    format!("\n\nResponse:\n{}\n{}\n{}\n", *CLI_LINE_SEPARATOR, message, *CLI_LINE_SEPARATOR)
+                } else {
+                    String::new()
+                }
+            ),
+            ReasonerResponse { address, .. } => write!(f, "Failed to parse response of reasoner '{address}' as eFLINT JSON"),
+            ReasonerRejected { address, errors } => {
+                write!(f, "Reasoner '{}' failed to reason about the policy and workflow: {}", address, PrettyListFormatter::new(errors.iter(), "and"))
+            },
+            ResultsDirCreate { .. } => write!(f, "Failed to create temporary results directory"),
+            Simulation { .. } => write!(f, "Failed to load simulated domains"),
+            SimulationDenied { domains } => {
+                write!(f, "Workflow is not allowed by the policy of simulated domain(s) {}", PrettyListFormatter::new(domains.iter(), "and"))
+            },
+            WorkflowConvert { input, .. } => write!(f, "Failed to convert workflow '{input}' to the checker's representation"),
+            WorkflowSerialize { input, .. } => write!(f, "Failed to serialize workflow '{input}'"),
+            WorkflowRun { input, .. } => write!(f, "Failed to run workflow '{input}'"),
+        }
+    }
+}
+impl Error for WorkflowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use WorkflowError::*;
+        match self {
+            AstCompile { .. } => None,
+            DatasetsDir { err } => Some(err),
+            InputFileRead { err, .. } => Some(err),
+            InputStdinRead { err } => Some(err),
+            LocalDataIndex { err } => Some(err),
+            LocalPackageIndex { err } => Some(err),
+            OutputCreate { err, .. } => Some(err),
+            OutputWrite { err, .. } => Some(err),
+            PackagesDir { err } => Some(err),
+            PolicyExtension { .. } => None,
+            PolicyCompile { err, .. } => Some(err),
+            PolicyRead { err, .. } => Some(err),
+            PolicyDeserialize { err, .. } => Some(err),
+            ReasonerRequest { err, .. } => Some(err),
+            ReasonerFailure { .. } => None,
+            ReasonerResponse { err, .. } => Some(err),
+            ReasonerRejected { .. } => None,
+            ResultsDirCreate { err } => Some(err),
+            Simulation { err } => Some(err),
+            SimulationDenied { .. } => None,
+            WorkflowConvert { err, .. } => Some(err),
+            WorkflowSerialize { err, .. } => Some(err),
+            WorkflowRun { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
 /// Collects errors of utilities that don't find an origin in just one subcommand.
 #[derive(Debug)]
 pub enum UtilError {
@@ -1713,7 +2141,7 @@ pub enum UtilError {
     /// Could not create the directory for a package
     PackageDirCreateError { package: String, path: PathBuf, err: std::io::Error },
     /// The target package directory does not exist
-    PackageDirNotFound { package: String, path: PathBuf },
+    PackageDirNotFound { package: String, path: PathBuf, suggestions: Vec<String> },
     /// Could not create a new directory for the given version
     VersionDirCreateError { package: String, version: Version, path: PathBuf, err: std::io::Error },
     /// The target package/version directory does not exist
@@ -1732,6 +2160,8 @@ pub enum UtilError {
     BraneInstanceDirCreateError { path: PathBuf, name: String, err: std::io::Error },
     /// The instance folder for a specific instance did not exist.
     BraneInstanceDirNotFound { path: PathBuf, name: String },
+    /// Could not lock a directory against concurrent modification by other processes.
+    BraneDirLockError { path: PathBuf, err: brane_shr::fs::Error },
 
     /// The given name is not a valid bakery name.
     InvalidBakeryName { name: String },
@@ -1787,7 +2217,9 @@ impl Display for UtilError {
             PackageDirCreateError { package, path, err } => {
                 write!(f, "Could not create directory for package '{}' (path: '{}'): {}", package, path.display(), err)
             },
-            PackageDirNotFound { package, path } => write!(f, "Directory for package '{}' does not exist (path: '{}')", package, path.display()),
+            PackageDirNotFound { package, path, suggestions } => {
+                write!(f, "Directory for package '{}' does not exist (path: '{}'){}", package, path.display(), DidYouMean::new(suggestions))
+            },
             VersionDirCreateError { package, version, path, err } => {
                 write!(f, "Could not create directory for package '{}', version: {} (path: '{}'): {}", package, version, path.display(), err)
             },
@@ -1806,6 +2238,7 @@ impl Display for UtilError {
                 write!(f, "Failed to create directory '{}' for new instance '{}': {}", path.display(), name, err)
             },
             BraneInstanceDirNotFound { path, name } => write!(f, "Brane instance directory '{}' for instance '{}' not found", path.display(), name),
+            BraneDirLockError { path, err } => write!(f, "Could not lock Brane directory '{}': {}", path.display(), err),
 
             InvalidBakeryName { name } => write!(f, "The given name '{name}' is not a valid name; expected alphanumeric or underscore characters"),
         }
@@ -1857,6 +2290,46 @@ impl Error for HostnameParseError {}
 
 
 
+/// Declares errors that relate to parsing `--auto-commit` specifications from a string.
+#[derive(Debug)]
+pub enum AutoCommitParseError {
+    /// There was no `=` separating the variable from the dataset.
+    MissingSeparator { raw: String },
+    /// The variable was not a valid identifier.
+    IllegalVariable { raw: String },
+    /// The dataset name was empty or contained characters that cannot appear in a string literal.
+    IllegalDataset { raw: String },
+}
+impl Display for AutoCommitParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use AutoCommitParseError::*;
+        match self {
+            MissingSeparator { raw } => write!(f, "Missing '=' in '{raw}' (expected '<VARIABLE>=<DATASET>')"),
+            IllegalVariable { raw } => write!(f, "'{raw}' is not a valid variable name"),
+            IllegalDataset { raw } => write!(f, "'{raw}' is not a valid dataset name"),
+        }
+    }
+}
+impl Error for AutoCommitParseError {}
+
+/// Defines errors that relate to parsing the format of `brane run --output`.
+#[derive(Debug)]
+pub enum OutputFormatParseError {
+    /// The format was not one we know.
+    UnknownFormat { raw: String },
+}
+impl Display for OutputFormatParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use OutputFormatParseError::*;
+        match self {
+            UnknownFormat { raw } => write!(f, "Unknown output format '{raw}' (expected 'human' or 'json')"),
+        }
+    }
+}
+impl Error for OutputFormatParseError {}
+
+
+
 /// Declares errors that relate to the offline VM.
 #[derive(Debug)]
 pub enum OfflineVmError {
@@ -1878,6 +2351,72 @@ impl Error for OfflineVmError {}
 
 
 
+/// Declares errors that relate to the sinks that `brane workflow run` writes printed lines to.
+#[derive(Debug)]
+pub enum SinkError {
+    /// Failed to create the log file.
+    LogFileCreateError { path: PathBuf, err: std::io::Error },
+}
+impl Display for SinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SinkError::*;
+        match self {
+            LogFileCreateError { path, .. } => write!(f, "Failed to create log file '{}'", path.display()),
+        }
+    }
+}
+impl Error for SinkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SinkError::*;
+        match self {
+            LogFileCreateError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Declares errors that relate to loading the domains simulated by `brane workflow run --simulate-domains`.
+#[derive(Debug)]
+pub enum SimulationError {
+    /// Failed to read the infrastructure file.
+    InfraFileRead { path: PathBuf, err: brane_cfg::infra::Error },
+    /// The infrastructure file does not define any domains.
+    NoDomains { path: PathBuf },
+    /// Failed to read the datasets of a domain.
+    DataIndex { domain: String, err: brane_tsk::local::Error },
+    /// A dataset of a domain is not stored as a file.
+    IllegalAccess { domain: String, name: String },
+    /// Failed to merge the datasets of all domains.
+    DataIndexMerge { err: specifications::data::DataIndexError },
+}
+impl Display for SimulationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SimulationError::*;
+        match self {
+            InfraFileRead { path, .. } => write!(f, "Failed to read infrastructure file '{}'", path.display()),
+            NoDomains { path } => write!(f, "Infrastructure file '{}' does not define any domains", path.display()),
+            DataIndex { domain, .. } => write!(f, "Failed to read datasets of simulated domain '{domain}'"),
+            IllegalAccess { domain, name } => write!(f, "Dataset '{name}' of simulated domain '{domain}' is not stored as a file"),
+            DataIndexMerge { .. } => write!(f, "Failed to merge datasets of simulated domains"),
+        }
+    }
+}
+impl Error for SimulationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SimulationError::*;
+        match self {
+            InfraFileRead { err, .. } => Some(err),
+            NoDomains { .. } => None,
+            DataIndex { err, .. } => Some(err),
+            IllegalAccess { .. } => None,
+            DataIndexMerge { err } => Some(err),
+        }
+    }
+}
+
+
+
 /// A really specific error enum for errors relating to fetching delegates.
 #[derive(Debug)]
 pub enum DelegatesError {
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Jan 2023, 09:22:13
 //  Last edited:
-//    08 Jan 2024, 10:43:17
+//    16 Oct 2026, 02:28:32
 //  Auto updated?
 //    Yes
 //
@@ -14,27 +14,206 @@
 //
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::OsString;
 use std::fs::{self, DirEntry, File, ReadDir};
 use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs as _};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use brane_cfg::certs::{load_certstore, load_identity};
+use brane_cfg::infra::LocationCapabilities;
 use brane_shr::formatters::PrettyListFormatter;
+use brane_shr::fs::{write_atomic, AtomicFile, FileLock};
 use console::{pad_str, style, Alignment};
 use dialoguer::Confirm;
 use log::{debug, info, warn};
 use prettytable::format::FormatBuilder;
 use prettytable::Table;
+use rustls::{Certificate, ClientConfig, ClientConnection, PrivateKey, RootCertStore, ServerName};
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
+use url::Url;
 
 pub use crate::errors::InstanceError as Error;
 use crate::spec::Hostname;
-use crate::utils::{ensure_instance_dir, ensure_instances_dir, get_active_instance_link, get_instance_dir};
+use crate::utils::{ensure_instance_dir, ensure_instances_dir, get_active_instance_link, get_instance_dir, lock_instances_dir};
+
+
+/***** CONSTANTS *****/
+/// The time we give every step of pinging an endpoint before giving up on it.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What we send to gRPC endpoints to provoke a first byte: the HTTP/2 connection preface, followed by an empty `SETTINGS` frame.
+const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00";
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Describes the labels and capabilities of a location, as returned by `brane-api`'s `/infra/capabilities`.
+#[derive(Clone, Debug, Deserialize)]
+struct LocationDescription {
+    /// The labels of the location.
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    /// The structured capabilities of the location.
+    #[serde(default)]
+    capabilities: LocationCapabilities,
+}
+
+
+
+/// Defines the protocols that we speak to the endpoints we ping.
+#[derive(Clone, Copy, Debug)]
+enum PingProtocol {
+    /// Plain HTTP (or HTTPS), for which we request `/health`.
+    Http,
+    /// gRPC, for which we start an HTTP/2 connection.
+    Grpc,
+}
+
+/// The latencies measured while pinging one endpoint.
+///
+/// Steps that were not reached (or don't apply, such as TLS for plain connections) are [`None`].
+#[derive(Clone, Debug, Default)]
+struct PingReport {
+    /// The time it took to resolve the endpoint's hostname.
+    dns: Option<Duration>,
+    /// The time it took to open a TCP connection.
+    connect: Option<Duration>,
+    /// The time it took to complete the TLS handshake.
+    tls: Option<Duration>,
+    /// The time between sending the first request and receiving the first byte of the response.
+    first_byte: Option<Duration>,
+    /// The reason we failed to complete the ping, if we did.
+    error: Option<String>,
+}
+impl PingReport {
+    /// Returns the total time spent on all steps.
+    ///
+    /// # Returns
+    /// The sum of all measured latencies.
+    #[inline]
+    fn total(&self) -> Duration { [self.dns, self.connect, self.tls, self.first_byte].into_iter().flatten().sum() }
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
+/// Prints a table with the labels and capabilities of all locations in the instance with the given API address.
+///
+/// Failures are reported as warnings, since this is only auxillary information.
+///
+/// # Arguments
+/// - `name`: The name of the instance (for printing).
+/// - `api_addr`: The address of the instance's `brane-api` service.
+async fn print_locations(name: &str, api_addr: &str) {
+    // Fetch the locations
+    let address: String = format!("{api_addr}/infra/capabilities");
+    let res: reqwest::Response = match reqwest::get(&address).await {
+        Ok(res) => res,
+        Err(err) => {
+            warn!("Failed to fetch locations of instance '{}' from '{}': {}", name, address, err);
+            return;
+        },
+    };
+    if !res.status().is_success() {
+        warn!("Failed to fetch locations of instance '{}' from '{}': server returned {}", name, address, res.status());
+        return;
+    }
+    let locations: HashMap<String, LocationDescription> = match res.json().await {
+        Ok(locations) => locations,
+        Err(err) => {
+            warn!("Failed to parse locations of instance '{}' from '{}': {}", name, address, err);
+            return;
+        },
+    };
+
+    // Print them as a table, sorted by name
+    let mut names: Vec<&String> = locations.keys().collect();
+    names.sort();
+    let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["LOCATION", "GPUS", "MEMORY", "STORAGE", "LABELS"]);
+    for loc in names {
+        let desc: &LocationDescription = &locations[loc];
+        let mut labels: Vec<String> = desc.labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        labels.sort();
+        table.add_row(row![
+            loc,
+            desc.capabilities.gpus.map(|g| g.to_string()).unwrap_or_else(|| "-".into()),
+            desc.capabilities.memory.map(|m| format!("{m} MB")).unwrap_or_else(|| "-".into()),
+            desc.capabilities.storage_class.as_deref().unwrap_or("-"),
+            labels.join(", ")
+        ]);
+    }
+    println!();
+    println!("Locations of instance {}:", style(name).bold());
+    table.printstd();
+}
+
+
+
+/// Asserts that the given name is a valid instance name.
+///
+/// # Arguments
+/// - `name`: The name to check.
+///
+/// # Errors
+/// This function errors if the name contains characters other than ASCII letters, digits, underscores, dots or dashes.
+fn assert_instance_name(name: &str) -> Result<(), Error> {
+    debug!("Asserting name validity...");
+    for c in name.chars() {
+        if !c.is_ascii_lowercase() && !c.is_ascii_uppercase() && !c.is_ascii_digit() && c != '_' && c != '.' && c != '-' {
+            return Err(Error::IllegalInstanceName { raw: name.into(), illegal_char: c });
+        }
+    }
+    Ok(())
+}
+
+/// Asks the user whether to overwrite the instance with the given name, if it exists.
+///
+/// # Arguments
+/// - `name`: The name of the instance that is about to be written.
+///
+/// # Returns
+/// True if the instance does not exist or the user agreed to overwrite it, or false otherwise.
+///
+/// # Errors
+/// This function errors if we failed to get the instance's directory or to ask the user.
+fn confirm_overwrite(name: &str) -> Result<bool, Error> {
+    debug!("Checking if instance already exists...");
+    let instance_path: PathBuf = match get_instance_dir(name) {
+        Ok(path) => path,
+        Err(err) => {
+            return Err(Error::InstanceDirError { err });
+        },
+    };
+    if instance_path.exists() {
+        debug!("Asking for confirmation...");
+        println!("An instance with the name {} already exists. Overwrite?", style(name).cyan().bold());
+        let consent: bool = match Confirm::new().interact() {
+            Ok(consent) => consent,
+            Err(err) => {
+                return Err(Error::ConfirmationError { err });
+            },
+        };
+        if !consent {
+            println!("Not overwriting, aborted.");
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
 /// Reads the active instance from the special active_instance file.
 ///
 /// # Returns
@@ -67,6 +246,182 @@ fn read_active_instance_link() -> Result<String, Error> {
 }
 
 
+/// Builds the TLS configuration with which we authenticate ourselves to a domain's registry.
+///
+/// # Arguments
+/// - `certs_dir`: The directory with the certificates for this domain (i.e., `ca.pem` and `client-id.pem`).
+///
+/// # Returns
+/// A new [`ClientConfig`] that trusts the domain's CA and presents our client identity.
+///
+/// # Errors
+/// This function errors if we failed to load the certificates, with a human-readable reason.
+fn registry_tls_config(certs_dir: &Path) -> Result<ClientConfig, String> {
+    if !certs_dir.exists() {
+        return Err("no certificates for this domain (see `brane certs add`)".into());
+    }
+    let roots: RootCertStore = match load_certstore(certs_dir.join("ca.pem")) {
+        Ok(roots) => roots,
+        Err(err) => return Err(format!("failed to load CA certificate: {err}")),
+    };
+    let (certs, key): (Vec<Certificate>, PrivateKey) = match load_identity(certs_dir.join("client-id.pem")) {
+        Ok(identity) => identity,
+        Err(err) => return Err(format!("failed to load client certificate: {err}")),
+    };
+    match ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_client_auth_cert(certs, key) {
+        Ok(config) => Ok(config),
+        Err(err) => Err(format!("failed to load client certificate: {err}")),
+    }
+}
+
+/// Sends a request on the given stream and measures the time until the first byte of the response arrives.
+///
+/// # Arguments
+/// - `stream`: The stream to send the request on.
+/// - `request`: The raw bytes of the request.
+///
+/// # Returns
+/// The time between sending the request and receiving the first byte.
+///
+/// # Errors
+/// This function errors if we failed to write the request, read the response or if the remote closed the connection instead of responding.
+fn time_first_byte(mut stream: impl Read + Write, request: &[u8]) -> Result<Duration, std::io::Error> {
+    let start: Instant = Instant::now();
+    stream.write_all(request)?;
+    stream.flush()?;
+    let mut buf: [u8; 1] = [0; 1];
+    if stream.read(&mut buf)? == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "connection closed by remote"));
+    }
+    Ok(start.elapsed())
+}
+
+/// Pings an endpoint, measuring how long it takes to resolve it, connect to it, perform the TLS handshake (if any) and get the first byte of
+/// a response.
+///
+/// This function blocks until done, which takes at most a few times [`PING_TIMEOUT`].
+///
+/// # Arguments
+/// - `address`: The address of the endpoint, including its scheme (e.g., `http://localhost:50051`). Only `https` endpoints use TLS.
+/// - `protocol`: The [`PingProtocol`] to speak to the endpoint.
+/// - `certs_dir`: The directory with the certificates to authenticate ourselves with for `https` endpoints.
+///
+/// # Returns
+/// A [`PingReport`] with the latencies of every step we completed and, if we didn't complete all of them, why.
+fn ping_endpoint(address: &str, protocol: PingProtocol, certs_dir: Option<&Path>) -> PingReport {
+    let mut report: PingReport = PingReport::default();
+
+    // Find the host and port to connect to
+    let url: Url = match Url::parse(address) {
+        Ok(url) => url,
+        Err(err) => {
+            report.error = Some(format!("invalid address: {err}"));
+            return report;
+        },
+    };
+    let (host, port): (String, u16) = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => (host.trim_start_matches('[').trim_end_matches(']').into(), port),
+        _ => {
+            report.error = Some("address has no host and port".into());
+            return report;
+        },
+    };
+
+    // Resolve it
+    let start: Instant = Instant::now();
+    let addr: SocketAddr = match (host.as_str(), port).to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => addr,
+        Ok(None) => {
+            report.error = Some(format!("'{host}' does not resolve to any address"));
+            return report;
+        },
+        Err(err) => {
+            report.error = Some(format!("failed to resolve '{host}': {err}"));
+            return report;
+        },
+    };
+    report.dns = Some(start.elapsed());
+
+    // Connect to it
+    let start: Instant = Instant::now();
+    let mut tcp: TcpStream = match TcpStream::connect_timeout(&addr, PING_TIMEOUT) {
+        Ok(tcp) => tcp,
+        Err(err) => {
+            report.error = Some(format!("failed to connect to '{addr}': {err}"));
+            return report;
+        },
+    };
+    report.connect = Some(start.elapsed());
+    if let Err(err) = tcp.set_read_timeout(Some(PING_TIMEOUT)).and_then(|_| tcp.set_write_timeout(Some(PING_TIMEOUT))) {
+        report.error = Some(format!("failed to set timeouts: {err}"));
+        return report;
+    }
+
+    // Prepare what to send
+    let request: Cow<[u8]> = match protocol {
+        PingProtocol::Http => format!("GET /health HTTP/1.1\r\nHost: {host}:{port}\r\nConnection: close\r\n\r\n").into_bytes().into(),
+        PingProtocol::Grpc => HTTP2_PREFACE.into(),
+    };
+
+    // Do the handshake if this is a TLS endpoint, then send the request
+    let first_byte: Result<Duration, std::io::Error> = if url.scheme() == "https" {
+        let config: ClientConfig = match registry_tls_config(certs_dir.unwrap_or_else(|| Path::new(""))) {
+            Ok(config) => config,
+            Err(err) => {
+                report.error = Some(err);
+                return report;
+            },
+        };
+        let server_name: ServerName = match ServerName::try_from(host.as_str()) {
+            Ok(name) => name,
+            Err(err) => {
+                report.error = Some(format!("illegal server name '{host}': {err}"));
+                return report;
+            },
+        };
+        let mut conn: ClientConnection = match ClientConnection::new(Arc::new(config), server_name) {
+            Ok(conn) => conn,
+            Err(err) => {
+                report.error = Some(format!("failed to start TLS session: {err}"));
+                return report;
+            },
+        };
+
+        let start: Instant = Instant::now();
+        while conn.is_handshaking() {
+            if let Err(err) = conn.complete_io(&mut tcp) {
+                report.error = Some(format!("TLS handshake failed: {err}"));
+                return report;
+            }
+        }
+        report.tls = Some(start.elapsed());
+        time_first_byte(rustls::Stream::new(&mut conn, &mut tcp), &request)
+    } else {
+        time_first_byte(&mut tcp, &request)
+    };
+    match first_byte {
+        Ok(first_byte) => report.first_byte = Some(first_byte),
+        Err(err) => report.error = Some(format!("no response: {err}")),
+    }
+    report
+}
+
+/// Formats a latency for in the ping table.
+///
+/// # Arguments
+/// - `latency`: The latency to format, or [`None`] if it wasn't measured.
+///
+/// # Returns
+/// The latency in milliseconds, or `-` if it wasn't measured.
+#[inline]
+fn format_latency(latency: Option<Duration>) -> String {
+    match latency {
+        Some(latency) => format!("{:.1} ms", latency.as_secs_f64() * 1000.0),
+        None => "-".into(),
+    }
+}
+
+
 
 
 
@@ -77,11 +432,14 @@ fn read_active_instance_link() -> Result<String, Error> {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InstanceInfo {
     /// The place where we can find the API service for this instance.
-    pub api:  Address,
+    pub api:   Address,
     /// The place where we can find the driver service for this instance.
-    pub drv:  Address,
+    pub drv:   Address,
     /// A username to send with workflow requests as receiver of the final result.
-    pub user: String,
+    pub user:  String,
+    /// The API token with which we authenticate ourselves to the driver, if any. If given, the driver runs our workflows as its user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
 }
 impl InstanceInfo {
     /// Reads this InstanceInfo from the active instance's directory in the local configuration directory.
@@ -208,8 +566,8 @@ impl InstanceInfo {
             },
         };
 
-        // Open a file to write us to
-        let mut handle: File = match File::create(path) {
+        // Open a file to write us to (which only replaces the old one once we're done)
+        let mut handle: AtomicFile = match AtomicFile::create(path) {
             Ok(handle) => handle,
             Err(err) => {
                 return Err(Error::InstanceInfoCreateError { path: path.into(), err });
@@ -217,7 +575,10 @@ impl InstanceInfo {
         };
 
         // Finally write it
-        match write!(handle, "{sself}") {
+        if let Err(err) = write!(handle, "{sself}") {
+            return Err(Error::InstanceInfoWriteError { path: path.into(), err });
+        }
+        match handle.commit() {
             Ok(_) => Ok(()),
             Err(err) => Err(Error::InstanceInfoWriteError { path: path.into(), err }),
         }
@@ -290,6 +651,25 @@ impl InstanceInfo {
 
 
 
+/// Defines the layout of an InstanceBundle, which is what `brane instance export` writes and `brane instance import` reads.
+///
+/// It contains everything needed to connect to an instance that can be shared with others, i.e., its endpoints and the CA certificates of its
+/// domains, but not the user name or any private keys.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InstanceBundle {
+    /// The name of the instance, which is used as default name when importing it.
+    pub name:     String,
+    /// The place where we can find the API service for this instance.
+    pub api:      Address,
+    /// The place where we can find the driver service for this instance.
+    pub drv:      Address,
+    /// The CA certificates of the instance's domains, as PEM, by domain name.
+    #[serde(default)]
+    pub ca_certs: BTreeMap<String, String>,
+}
+
+
+
 
 /***** SUBCOMMANDS *****/
 /// Registers a new instance to which we can hot-swap using switch.
@@ -319,37 +699,10 @@ pub async fn add(
 ) -> Result<(), Error> {
     info!("Creating new instance '{}'...", name);
 
-    // Assert the name is valid
-    debug!("Asserting name validity...");
-    for c in name.chars() {
-        if !c.is_ascii_lowercase() && !c.is_ascii_uppercase() && !c.is_ascii_digit() && c != '_' && c != '.' && c != '-' {
-            return Err(Error::IllegalInstanceName { raw: name, illegal_char: c });
-        }
-    }
-
-    // Attempt to find out if the instance exists
-    if !force {
-        debug!("Checking if instance already exists...");
-        let instance_path: PathBuf = match get_instance_dir(&name) {
-            Ok(path) => path,
-            Err(err) => {
-                return Err(Error::InstanceDirError { err });
-            },
-        };
-        if instance_path.exists() {
-            debug!("Asking for confirmation...");
-            println!("An instance with the name {} already exists. Overwrite?", style(&name).cyan().bold());
-            let consent: bool = match Confirm::new().interact() {
-                Ok(consent) => consent,
-                Err(err) => {
-                    return Err(Error::ConfirmationError { err });
-                },
-            };
-            if !consent {
-                println!("Not overwriting, aborted.");
-                return Ok(());
-            }
-        }
+    // Assert the name is valid and that we may overwrite any existing instance
+    assert_instance_name(&name)?;
+    if !force && !confirm_overwrite(&name)? {
+        return Ok(());
     }
 
     // Convert the hostname and ports to Addresses
@@ -392,10 +745,15 @@ pub async fn add(
 
     // Create a new InstanceInfo
     debug!("Writing InstanceInfo...");
-    let info: InstanceInfo = InstanceInfo { api, drv, user };
+    let info: InstanceInfo = InstanceInfo { api, drv, user, token: None };
 
     // Write it to wherever it wants to be
+    let lock: FileLock = match lock_instances_dir() {
+        Ok(lock) => lock,
+        Err(err) => return Err(Error::InstancesDirLockError { err }),
+    };
     info.to_default_path(&name)?;
+    lock.release();
 
     // If told to do so, call `select()` to immediately make it active
     println!("Successfully added new instance {}", style(&name).cyan().bold());
@@ -446,6 +804,10 @@ pub fn remove(names: Vec<String>, force: bool) -> Result<(), Error> {
     }
 
     // Now loop through the names to remove them
+    let _lock: FileLock = match lock_instances_dir() {
+        Ok(lock) => lock,
+        Err(err) => return Err(Error::InstancesDirLockError { err }),
+    };
     for name in names {
         debug!("Removing instance '{}'...", name);
 
@@ -500,7 +862,7 @@ pub fn remove(names: Vec<String>, force: bool) -> Result<(), Error> {
 ///
 /// # Errors
 /// This function errors if we failed to read the instance directory.
-pub async fn list(show_status: bool) -> Result<(), Error> {
+pub async fn list(show_status: bool, show_locations: bool) -> Result<(), Error> {
     info!("Listing instances...");
 
     // Prepare display table.
@@ -532,6 +894,7 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
 
     // Open up the ol' directory and iterate over its contents
     debug!("Reading '{}'...", instances_dir.display());
+    let mut to_describe: Vec<(String, String)> = vec![];
     let entries: ReadDir = match fs::read_dir(&instances_dir) {
         Ok(entries) => entries,
         Err(err) => {
@@ -595,6 +958,11 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
             pad_str(&user, 25, Alignment::Left, Some("..")),
         );
 
+        // Remember the instance if we want to show its locations later
+        if show_locations {
+            to_describe.push((entry.file_name().to_string_lossy().into_owned(), api_addr.clone()));
+        }
+
         // Either get the reachability and then add the row, or add the row immediately (depending on what the user wants us to do)
         if show_status {
             // Get the status
@@ -610,6 +978,10 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
                 if !res.status().is_success() {
                     break 'reach style("UNHEALTHY").yellow().bold().to_string();
                 }
+                // The instance may still report that it's degraded (e.g., because certificates are about to expire)
+                if res.text().await.map(|body| body.starts_with("DEGRADED")).unwrap_or(false) {
+                    break 'reach style("DEGRADED").yellow().bold().to_string();
+                }
                 style("OK").green().bold().to_string()
             };
 
@@ -624,11 +996,131 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
         }
     }
 
+    // Print the main table, then the locations of every instance if requested
+    table.printstd();
+    for (name, api_addr) in to_describe {
+        print_locations(&name, &api_addr).await;
+    }
+
     // Done
+    Ok(())
+}
+
+/// Pings the services of an instance, printing how long it takes to resolve, connect to, perform the TLS handshake with (if any) and get a
+/// first response from each of them.
+///
+/// Pings the instance's API service and driver, as well as the registries of all its domains if the API service tells us where they are.
+///
+/// # Arguments
+/// - `name`: The name of the instance to ping. If omitted, pings the active instance instead.
+///
+/// # Errors
+/// This function errors if we failed to find or load the instance. Unreachable services are reported in the table instead.
+pub async fn ping(name: Option<String>) -> Result<(), Error> {
+    info!("Pinging instance {}...", name.as_ref().map(|n| format!("'{n}'")).unwrap_or("<active>".into()));
+
+    // Resolve the instance
+    let name: String = match name {
+        Some(name) => name,
+        None => {
+            if !InstanceInfo::active_instance_exists()? {
+                return Err(Error::NoActiveInstance);
+            }
+            read_active_instance_link()?
+        },
+    };
+    let instance_dir: PathBuf = match get_instance_dir(&name) {
+        Ok(dir) => dir,
+        Err(err) => {
+            return Err(Error::InstanceDirError { err });
+        },
+    };
+    if !instance_dir.exists() {
+        return Err(Error::UnknownInstance { name });
+    }
+    let info: InstanceInfo = InstanceInfo::from_default_path(&name)?;
+    let (api_addr, drv_addr): (String, String) = (info.api.to_string(), info.drv.to_string());
+
+    // Collect the endpoints to ping, including the registries if the API tells us where they are
+    let mut targets: Vec<(String, String, PingProtocol, Option<PathBuf>)> =
+        vec![("api".into(), api_addr.clone(), PingProtocol::Http, None), ("driver".into(), drv_addr, PingProtocol::Grpc, None)];
+    let registries_addr: String = format!("{api_addr}/infra/registries");
+    debug!("Fetching registries from '{}'...", registries_addr);
+    match reqwest::Client::new().get(&registries_addr).timeout(PING_TIMEOUT).send().await {
+        Ok(res) if res.status().is_success() => match res.json::<HashMap<String, String>>().await {
+            Ok(registries) => {
+                let mut registries: Vec<(String, String)> = registries.into_iter().collect();
+                registries.sort();
+                for (domain, address) in registries {
+                    let certs_dir: PathBuf = instance_dir.join("certs").join(&domain);
+                    targets.push((format!("registry ({domain})"), address, PingProtocol::Http, Some(certs_dir)));
+                }
+            },
+            Err(err) => warn!("Failed to parse registries from '{}': {} (not pinging registries)", registries_addr, err),
+        },
+        Ok(res) => warn!("Failed to fetch registries from '{}': server returned {} (not pinging registries)", registries_addr, res.status()),
+        Err(err) => warn!("Failed to fetch registries from '{}': {} (not pinging registries)", registries_addr, err),
+    }
+
+    // Ping them all in parallel
+    println!("Pinging instance {}...", style(&name).bold().cyan());
+    let handles: Vec<_> = targets
+        .into_iter()
+        .map(|(what, address, protocol, certs_dir)| {
+            tokio::task::spawn_blocking(move || {
+                let report: PingReport = ping_endpoint(&address, protocol, certs_dir.as_deref());
+                (what, address, report)
+            })
+        })
+        .collect();
+
+    // Print the results as a table
+    let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["SERVICE", "ADDRESS", "DNS", "CONNECT", "TLS", "FIRST BYTE", "TOTAL", "STATUS"]);
+    let mut failures: Vec<(String, String)> = vec![];
+    for handle in handles {
+        let (what, address, report): (String, String, PingReport) = match handle.await {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("Failed to wait for ping to complete: {err}");
+                continue;
+            },
+        };
+        let status: String = if let Some(err) = report.error.as_ref() {
+            failures.push((what.clone(), err.clone()));
+            style("FAILED").red().bold().to_string()
+        } else {
+            style("OK").green().bold().to_string()
+        };
+        table.add_row(row![
+            what,
+            address,
+            format_latency(report.dns),
+            format_latency(report.connect),
+            format_latency(report.tls),
+            format_latency(report.first_byte),
+            format_latency(Some(report.total())),
+            status
+        ]);
+    }
     table.printstd();
+
+    // Explain any failures
+    if !failures.is_empty() {
+        println!();
+        for (what, err) in failures {
+            println!("{} {}: {}", style("Failed to ping").red().bold(), style(what).bold(), err);
+        }
+    }
+
+    // Done
     Ok(())
 }
 
+
+
 /// Changes the active instance to the current one.
 ///
 /// # Arguments
@@ -666,7 +1158,11 @@ pub fn select(name: String) -> Result<(), Error> {
 
     // Simply write a new link, which overwrites the previous file
     debug!("Generating new active link...");
-    if let Err(err) = fs::write(&link_path, &name) {
+    let _lock: FileLock = match lock_instances_dir() {
+        Ok(lock) => lock,
+        Err(err) => return Err(Error::InstancesDirLockError { err }),
+    };
+    if let Err(err) = write_atomic(&link_path, &name) {
         return Err(Error::ActiveInstanceCreateError { path: link_path, target: name, err });
     }
 
@@ -685,6 +1181,7 @@ pub fn select(name: String) -> Result<(), Error> {
 /// - `api_port`: Whether to change the API service port of the instance and, if so, what to change it to.
 /// - `drv_port`: Whether to change the driver service port of the instance and, if so, what to change it to.
 /// - `user`: Whether to change the user name which the user presents as receiver of the final result.
+/// - `token`: Whether to change the API token with which we authenticate to the driver and, if so, what to change it to (an empty one removes it).
 ///
 /// # Errors
 /// This function errors if we failed to find the instance or failed to update its file.
@@ -694,6 +1191,7 @@ pub fn edit(
     api_port: Option<u16>,
     drv_port: Option<u16>,
     user: Option<String>,
+    token: Option<String>,
 ) -> Result<(), Error> {
     info!("Editing instance {}...", name.as_ref().map(|n| format!("'{n}'")).unwrap_or("<active>".into()));
 
@@ -719,8 +1217,12 @@ pub fn edit(
             InstanceInfo::get_default_path(active_name)
         })?;
 
-    // With the path confirmed, load the info.yml
+    // With the path confirmed, load the info.yml (and keep others from changing it until we wrote it back)
     debug!("Loading instance file...");
+    let _lock: FileLock = match lock_instances_dir() {
+        Ok(lock) => lock,
+        Err(err) => return Err(Error::InstancesDirLockError { err }),
+    };
     let mut info: InstanceInfo = InstanceInfo::from_path(instance_path.as_path())?;
 
     // Adapt whatever is necessary
@@ -743,6 +1245,15 @@ pub fn edit(
         println!("Updating username to {}...", style(&user).cyan().bold());
         info.user = user;
     }
+    if let Some(token) = token {
+        if token.is_empty() {
+            println!("Removing API token...");
+            info.token = None;
+        } else {
+            println!("Updating API token...");
+            info.token = Some(token);
+        }
+    }
 
     // Write the modified file back
     debug!("Writing instance file back...");
@@ -756,3 +1267,171 @@ pub fn edit(
     }
     Ok(())
 }
+
+
+
+/// Exports an instance to a bundle that can be shared with others, who can then import it with `brane instance import`.
+///
+/// The bundle contains the instance's endpoints and the CA certificates of its domains, but not the user name or any client certificates or keys.
+///
+/// # Arguments
+/// - `name`: The name of the instance to export. If omitted, exports the active instance instead.
+/// - `output`: The path to write the bundle to. If omitted, writes it to stdout.
+///
+/// # Errors
+/// This function errors if we failed to find or load the instance, failed to read its certificates or failed to write the bundle.
+pub fn export(name: Option<String>, output: Option<PathBuf>) -> Result<(), Error> {
+    info!("Exporting instance {}...", name.as_ref().map(|n| format!("'{n}'")).unwrap_or("<active>".into()));
+
+    // Resolve the instance
+    let name: String = match name {
+        Some(name) => name,
+        None => {
+            if !InstanceInfo::active_instance_exists()? {
+                return Err(Error::NoActiveInstance);
+            }
+            read_active_instance_link()?
+        },
+    };
+    let instance_dir: PathBuf = match get_instance_dir(&name) {
+        Ok(dir) => dir,
+        Err(err) => {
+            return Err(Error::InstanceDirError { err });
+        },
+    };
+    if !instance_dir.exists() {
+        return Err(Error::UnknownInstance { name });
+    }
+    let info: InstanceInfo = InstanceInfo::from_default_path(&name)?;
+
+    // Collect the CA certificates of all domains (and only those; client certificates and keys stay here)
+    let mut ca_certs: BTreeMap<String, String> = BTreeMap::new();
+    let certs_dir: PathBuf = instance_dir.join("certs");
+    if certs_dir.exists() {
+        debug!("Collecting CA certificates from '{}'...", certs_dir.display());
+        let entries: ReadDir = match fs::read_dir(&certs_dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                return Err(Error::CertsDirReadError { path: certs_dir, err });
+            },
+        };
+        for (i, entry) in entries.enumerate() {
+            let entry: DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    return Err(Error::CertsDirEntryReadError { path: certs_dir, entry: i, err });
+                },
+            };
+            let ca_path: PathBuf = entry.path().join("ca.pem");
+            if !ca_path.is_file() {
+                debug!("Skipping '{}' (no CA certificate)", entry.path().display());
+                continue;
+            }
+            let ca: String = match fs::read_to_string(&ca_path) {
+                Ok(ca) => ca,
+                Err(err) => {
+                    return Err(Error::CaCertReadError { path: ca_path, err });
+                },
+            };
+            ca_certs.insert(entry.file_name().to_string_lossy().into(), ca);
+        }
+    }
+
+    // Write the bundle
+    let bundle: InstanceBundle = InstanceBundle { name: name.clone(), api: info.api, drv: info.drv, ca_certs };
+    let sbundle: String = match serde_yaml::to_string(&bundle) {
+        Ok(sbundle) => sbundle,
+        Err(err) => {
+            return Err(Error::BundleSerializeError { err });
+        },
+    };
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, sbundle) {
+                return Err(Error::BundleWriteError { path, err });
+            }
+            println!(
+                "Successfully exported instance {} to {} (with CA certificates for {} domain{})",
+                style(&name).cyan().bold(),
+                style(path.display()).bold(),
+                bundle.ca_certs.len(),
+                if bundle.ca_certs.len() != 1 { "s" } else { "" }
+            );
+        },
+        None => print!("{sbundle}"),
+    }
+    Ok(())
+}
+
+/// Imports an instance from a bundle written by `brane instance export`.
+///
+/// # Arguments
+/// - `path`: The path to the bundle.
+/// - `name`: The name to give the instance. If omitted, uses the name in the bundle.
+/// - `user`: The name of the user to login as.
+/// - `use_immediately`: Whether to switch to it or not.
+/// - `force`: Whether to ask for permission before overwriting an existing instance.
+///
+/// # Errors
+/// This function errors if we failed to read the bundle or failed to write the instance's files.
+pub fn import(path: PathBuf, name: Option<String>, user: String, use_immediately: bool, force: bool) -> Result<(), Error> {
+    info!("Importing instance from '{}'...", path.display());
+
+    // Read the bundle
+    let raw: String = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) => {
+            return Err(Error::BundleReadError { path, err });
+        },
+    };
+    let bundle: InstanceBundle = match serde_yaml::from_str(&raw) {
+        Ok(bundle) => bundle,
+        Err(err) => {
+            return Err(Error::BundleParseError { path, err });
+        },
+    };
+
+    // Assert the name is valid and that we may overwrite any existing instance
+    let name: String = name.unwrap_or(bundle.name);
+    assert_instance_name(&name)?;
+    if !force && !confirm_overwrite(&name)? {
+        return Ok(());
+    }
+
+    // Write the instance info, then the CA certificates
+    debug!("Writing InstanceInfo...");
+    let lock: FileLock = match lock_instances_dir() {
+        Ok(lock) => lock,
+        Err(err) => return Err(Error::InstancesDirLockError { err }),
+    };
+    let info: InstanceInfo = InstanceInfo { api: bundle.api, drv: bundle.drv, user, token: None };
+    info.to_default_path(&name)?;
+    let certs_dir: PathBuf = InstanceInfo::get_instance_path(&name)?.join("certs");
+    for (domain, ca) in &bundle.ca_certs {
+        let domain_dir: PathBuf = certs_dir.join(domain);
+        debug!("Writing CA certificate for domain '{}' to '{}'...", domain, domain_dir.display());
+        if let Err(err) = fs::create_dir_all(&domain_dir) {
+            return Err(Error::CertsDirCreateError { path: domain_dir, err });
+        }
+        let ca_path: PathBuf = domain_dir.join("ca.pem");
+        if let Err(err) = write_atomic(&ca_path, ca) {
+            return Err(Error::CaCertWriteError { path: ca_path, err });
+        }
+    }
+    lock.release();
+
+    // Done
+    println!("Successfully imported instance {}", style(&name).cyan().bold());
+    if !bundle.ca_certs.is_empty() {
+        println!(
+            "The bundle only contains the domains' CA certificates; use {} or {} to get a client certificate for {}.",
+            style("brane certs enroll").bold(),
+            style("brane certs add").bold(),
+            PrettyListFormatter::new(bundle.ca_certs.keys().map(|d| style(d).bold().cyan()), "and")
+        );
+    }
+    if use_immediately {
+        select(name)?;
+    }
+    Ok(())
+}
@@ -19,9 +19,13 @@ pub mod instance;
 pub mod old_configs;
 pub mod packages;
 pub mod planner;
+pub mod progress;
 pub mod registry;
 pub mod repl;
+pub mod results;
 pub mod run;
+pub mod simulation;
+pub mod sinks;
 pub mod spec;
 pub mod test;
 pub mod upgrade;
@@ -29,6 +33,7 @@ pub mod utils;
 pub mod verify;
 pub mod version;
 pub mod vm;
+pub mod workflow;
 
 
 
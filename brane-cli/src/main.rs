@@ -4,7 +4,7 @@
 //  Created:
 //    21 Sep 2022, 14:34:28
 //  Last edited:
-//    08 Feb 2024, 17:15:18
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -18,11 +18,16 @@ extern crate human_panic;
 use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::Result;
+use brane_cfg::enrollment::EnrollmentToken;
 use brane_cli::errors::{CliError, ImportError};
-use brane_cli::spec::{Hostname, VersionFix, API_DEFAULT_VERSION};
-use brane_cli::{build_ecu, build_oas, certs, check, data, instance, packages, registry, repl, run, test, upgrade, verify, version};
+use brane_cli::sinks::OutputSinks;
+use brane_cli::spec::{AutoCommit, Hostname, OutputFormat, VersionFix, API_DEFAULT_VERSION};
+use brane_cli::{
+    build_ecu, build_oas, certs, check, data, instance, packages, registry, repl, results, run, test, upgrade, verify, version, workflow,
+};
 use brane_dsl::Language;
 use brane_shr::fs::DownloadSecurity;
 use brane_tsk::docker::{ClientVersion, DockerOptions};
@@ -31,8 +36,10 @@ use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::ErrorTrace as _;
 use humanlog::{DebugMode, HumanLogger};
+use humantime::Duration as HumanDuration;
 // use git2::Repository;
 use log::{error, info};
+use specifications::address::Address;
 use specifications::arch::Arch;
 use specifications::package::PackageKind;
 use specifications::version::Version as SemVersion;
@@ -78,6 +85,13 @@ enum SubCommand {
                     it."
         )]
         crlf_ok: bool,
+        #[clap(
+            long,
+            action,
+            help = "If given, builds the image on the active remote instance instead of locally, which then adds the package to its registry. Use \
+                    this if you do not have Docker or if your machine has another architecture than the instance's workers (see '--arch')."
+        )]
+        remote: bool,
     },
 
     #[clap(name = "certs", about = "Manage certificates for connecting to remote instances.")]
@@ -155,7 +169,8 @@ enum SubCommand {
             short,
             long,
             default_value = "custom",
-            help = "Any alternative syntax to use for printed classes and functions. Can be 'bscript', 'bakery' or 'custom'."
+            help = "The syntax to print the package in. Can be 'custom' (a human-readable overview), 'bscript' (BraneScript stubs that import the \
+                    package), 'json', 'markdown' or 'bakery'."
         )]
         syntax: String,
     },
@@ -201,6 +216,13 @@ enum SubCommand {
                     assumed to be 'latest' if omitted."
         )]
         packages: Vec<String>,
+        #[clap(
+            long,
+            help = "If given, pushes the packages to this OCI registry repository (e.g., 'harbor.example.com/brane') instead, as \
+                    '<REPOSITORY>/<NAME>:<VERSION>'. They are then registered with the active instance, if any, whose workers pull them from the \
+                    registry directly. Credentials are read from the Docker config (see 'docker login')."
+        )]
+        oci:      Option<String>,
     },
 
     #[clap(name = "remove", about = "Remove a local package.")]
@@ -248,16 +270,31 @@ enum SubCommand {
 
         #[clap(short, long, help = "Create a remote REPL session to the instance you are currently logged-in to (see `brane login`)")]
         remote: bool,
-        #[clap(short, long, value_names = &["uid"], help = "Attach to an existing remote session")]
-        attach: Option<AppId>,
+        #[clap(
+            short,
+            long,
+            value_names = &["uid"],
+            help = "Attach to an existing remote session. If no ID is given, lets you pick one of your active sessions."
+        )]
+        attach: Option<Option<AppId>>,
+        #[clap(long, conflicts_with = "attach", help = "List your active remote sessions (to use with `--attach`) and exit.")]
+        list_sessions: bool,
 
         #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
         bakery: bool,
-        #[clap(short, long, action, help = "Clear history before session")]
+        #[clap(short, long, action, help = "Clear history before session (only that of the active instance if `--remote` is given)")]
         clear:  bool,
 
         #[clap(long, help = "If given, shows profile times if they are available.")]
         profile: bool,
+        #[clap(
+            long,
+            value_names = &["file"],
+            conflicts_with = "attach",
+            help = "Restores the session from the snapshot at the given path (if any), and keeps it updated after every snippet so you can pick up \
+                    where you left off next time."
+        )]
+        persist: Option<PathBuf>,
 
         /// The Docker socket location.
         #[cfg(unix)]
@@ -313,6 +350,22 @@ enum SubCommand {
             help = "Create a remote session to the instance you are currently logged-in to (see `brane login`)"
         )]
         remote:  bool,
+        #[clap(
+            long,
+            value_names = &["variable=dataset"],
+            conflicts_with = "dry_run",
+            help = "Promotes the intermediate result in the given global variable to a dataset with the given name once the workflow is done, \
+                    instead of losing it when the workflow's results are cleaned up. May be given multiple times."
+        )]
+        auto_commit: Vec<AutoCommit>,
+        #[clap(
+            short,
+            long,
+            default_value = "human",
+            help = "How to report the outcome of the workflow. Can be 'human' or 'json', which prints its result (or the exit code, signal, last \
+                    lines of output and resource usage of the task that made it fail) as a single line of JSON at the end of stdout."
+        )]
+        output: OutputFormat,
 
         #[clap(long, help = "If given, shows profile times if they are available.")]
         profile: bool,
@@ -394,7 +447,9 @@ enum SubCommand {
     #[clap(name = "search", about = "Search a registry for packages")]
     Search {
         #[clap(name = "TERM", help = "Term to use as search criteria")]
-        term: Option<String>,
+        term:      Option<String>,
+        #[clap(long, help = "If given, searches the packages of the peer instances that the active instance federates with instead.")]
+        federated: bool,
     },
 
     #[clap(name = "unpublish", about = "Remove a package from a registry")]
@@ -442,6 +497,13 @@ enum SubCommand {
         )]
         remote: bool,
     },
+
+    #[clap(name = "workflow", about = "Commands that work on workflows without running them.")]
+    Workflow {
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand: WorkflowSubcommand,
+    },
 }
 
 /// Defines the subcommands for the `instance certs` subommand
@@ -482,6 +544,35 @@ enum CertsSubcommand {
         #[clap(short, long, help = "If given, does not ask for permission before overwriting old certificates. Use at your own risk.")]
         force: bool,
     },
+    #[clap(
+        name = "enroll",
+        about = "Obtains a CA/client certificate pair for this instance from a domain, using a one-time token from that domain's administrator."
+    )]
+    Enroll {
+        /// The domain at which to enroll.
+        #[clap(name = "DOMAIN", help = "The name of the domain at which to enroll. Its registry is looked up through the instance's API.")]
+        domain: String,
+        /// The enrollment token.
+        #[clap(
+            short,
+            long,
+            help = "The one-time enrollment token that the domain's administrator generated with `branectl generate enrollment-token`."
+        )]
+        token:  EnrollmentToken,
+
+        /// The instance for which to enroll.
+        #[clap(
+            short,
+            long,
+            help = "The name of the instance to add the certificates to. If omitted, will add to the active instance instead (i.e., the one set \
+                    with `brane instance select`). Use 'brane instance list' for an overview."
+        )]
+        instance: Option<String>,
+
+        /// Whether to ask for permission before overwriting old certificates (but negated).
+        #[clap(short, long, help = "If given, does not ask for permission before overwriting old certificates. Use at your own risk.")]
+        force: bool,
+    },
     #[clap(name = "remove", about = "Removes the certificates for a certain domain within this instance.")]
     Remove {
         /// The name(s) of the certificate(s) to remove.
@@ -655,6 +746,22 @@ enum InstanceSubcommand {
         /// If given, shows an additional column in the table that shows whether this instance is online or not.
         #[clap(short, long, help = "If given, shows an additional column in the table that shows whether this instance is online or not.")]
         show_status: bool,
+        /// If given, shows the labels and capabilities of the locations in every instance.
+        #[clap(short = 'l', long, help = "If given, shows the labels and capabilities of the locations in every instance.")]
+        show_locations: bool,
+    },
+    #[clap(
+        name = "ping",
+        about = "Measures the latency to the services of an instance, broken down in resolving, connecting, the TLS handshake and the first \
+                 response."
+    )]
+    Ping {
+        /// The instance's name to ping.
+        #[clap(
+            name = "NAME",
+            help = "The name of the instance to ping if you don't want to ping the active instance. If in doubt, consult `brane instance list`."
+        )]
+        name: Option<String>,
     },
     #[clap(name = "select", about = "Switches to the registered instance with the given name.")]
     Select {
@@ -663,6 +770,51 @@ enum InstanceSubcommand {
         name: String,
     },
 
+    #[clap(
+        name = "export",
+        about = "Exports an instance's endpoints and the CA certificates of its domains to a bundle that others can import with `brane instance \
+                 import`. Does not export your user name, client certificates or keys."
+    )]
+    Export {
+        /// The instance's name to export.
+        #[clap(
+            name = "NAME",
+            help = "The name of the instance to export if you don't want to export the active instance. If in doubt, consult `brane instance list`."
+        )]
+        name:   Option<String>,
+        /// The file to write the bundle to.
+        #[clap(short, long, help = "The file to write the bundle to. If omitted, writes it to stdout.")]
+        output: Option<PathBuf>,
+    },
+    #[clap(name = "import", about = "Defines a new instance from a bundle written by `brane instance export`.")]
+    Import {
+        /// The bundle to import.
+        #[clap(name = "PATH", help = "The path to the bundle to import.")]
+        path: PathBuf,
+
+        /// Any custom name for this instance.
+        #[clap(short, long, help = "Some name to set for this instance. If omitted, will use the name in the bundle instead.")]
+        name: Option<String>,
+        /// The name of the user as which we login.
+        #[clap(
+            short = 'U',
+            long,
+            help = "The name as which to login to the instance. This is used to tell checkers who will download the result, but only tentatively; a \
+                    final check happens using domain-specific credentials. Will default to a random name when omitted."
+        )]
+        user: Option<String>,
+        /// Whether to use this instance immediately or not.
+        #[clap(
+            short,
+            long = "use",
+            help = "If given, immediately uses this instance (i.e., acts as if `brane instance switch <name>` is called for this instance)"
+        )]
+        use_immediately: bool,
+        /// Whether to ask for permission before overwriting an existing instance (but negated).
+        #[clap(short, long, help = "If given, does not ask for permission before overwriting an existing instance. Use at your own risk.")]
+        force: bool,
+    },
+
     #[clap(name = "edit", about = "Changes some properties of an instance.")]
     Edit {
         /// The instance's name to edit.
@@ -689,6 +841,14 @@ enum InstanceSubcommand {
                     only tentatively; a final check happens using domain-specific credentials."
         )]
         user:     Option<String>,
+        /// The API token with which we authenticate ourselves.
+        #[clap(
+            short,
+            long,
+            help = "If given, changes the API token with which to authenticate to the instance's driver. The driver then runs workflows as the \
+                    user that the token belongs to. Give an empty string to remove the token again."
+        )]
+        token:    Option<String>,
     },
 }
 
@@ -739,6 +899,226 @@ enum VerifySubcommand {
     },
 }
 
+/// Defines the subcommands for the workflow subcommand.
+#[derive(Parser)]
+enum WorkflowSubcommand {
+    #[clap(
+        name = "check",
+        about = "Checks a workflow against a local policy file instead of the policy of the current remote instance. The workflow is compiled using \
+                 only local packages and datasets, and no domain is contacted."
+    )]
+    Check {
+        #[clap(name = "FILE", help = "Path to the file to check. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        #[clap(short, long, help = "The policy to check against. Can be eFLINT (`*.eflint`) or eFLINT JSON (`*.json`).")]
+        against:  PathBuf,
+        #[clap(
+            short,
+            long,
+            default_value = "http://localhost:8080",
+            help = "The address of the eFLINT reasoner to check with (e.g., one that you started locally with `docker run`)."
+        )]
+        reasoner: Address,
+        #[clap(short, long, help = "If given, uses the given user as end user of the workflow.")]
+        user:     Option<String>,
+    },
+
+    #[clap(
+        name = "run",
+        about = "Runs a workflow locally while simulating the domains of a multi-site instance. Every domain has its own datasets and policy, and \
+                 data is transferred between them by copying it. This is useful to test data locality and policies without access to an instance. \
+                 Use `--remote` to run it on the instance you are logged-in to instead."
+    )]
+    Run {
+        #[clap(name = "FILE", help = "Path to the file to run. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        #[clap(
+            long,
+            required_unless_present = "remote",
+            help = "The `infra.yml` file that defines the domains to simulate. Every domain has a directory with its name next to this file, with \
+                    its datasets in a `data` directory and, optionally, its policy in a `policy.eflint` or `policy.json` file."
+        )]
+        simulate_domains: Option<PathBuf>,
+        #[clap(long, conflicts_with = "simulate_domains", help = "If given, runs the workflow on the instance you are logged-in to instead.")]
+        remote: bool,
+        #[clap(
+            long,
+            requires = "remote",
+            help = "If given, follows the workflow in an interactive view that shows the state of every task, how long it takes and the output sent \
+                    by the instance."
+        )]
+        tui: bool,
+        #[clap(
+            long,
+            requires = "remote",
+            help = "If given, the time that the workflow may take in total (e.g., `30m` or `2h`). If it takes longer, the instance cancels its \
+                    outstanding tasks and reports which ones did complete."
+        )]
+        deadline: Option<HumanDuration>,
+        #[clap(
+            long,
+            value_names = &["address[:port]"],
+            requires = "remote",
+            help = "If given, proxies any data transfers to this machine through the proxy at the given address."
+        )]
+        proxy_addr: Option<String>,
+        #[clap(
+            short,
+            long,
+            default_value = "http://localhost:8080",
+            help = "The address of the eFLINT reasoner to check the domains' policies with (e.g., one that you started locally with `docker run`)."
+        )]
+        reasoner:         Address,
+        #[clap(short, long, help = "If given, uses the given user as end user of the workflow.")]
+        user:             Option<String>,
+        #[clap(
+            long,
+            help = "If given, also writes what the workflow prints to this file. Every line is tagged with the time it was printed and the function \
+                    that printed it."
+        )]
+        log_file:         Option<PathBuf>,
+        #[clap(
+            long,
+            help = "If given, also POSTs every line that the workflow prints to this address, as JSON with the line (`text`), the time it was \
+                    printed (`timestamp`) and the function that printed it (`function`)."
+        )]
+        webhook:          Option<String>,
+
+        /// The Docker socket location.
+        #[cfg(unix)]
+        #[clap(
+            short = 's',
+            long,
+            default_value = "/var/run/docker.sock",
+            help = "The path to the Docker socket with which we communicate with the dameon."
+        )]
+        docker_socket:   PathBuf,
+        /// The Docker socket location.
+        #[cfg(windows)]
+        #[clap(
+            short = 's',
+            long,
+            default_value = "//./pipe/docker_engine",
+            help = "The path to the Docker socket with which we communicate with the dameon."
+        )]
+        docker_socket:   PathBuf,
+        /// The Docker socket location.
+        #[cfg(not(any(unix, windows)))]
+        #[clap(short = 's', long, help = "The path to the Docker socket with which we communicate with the dameon.")]
+        docker_socket:   PathBuf,
+        /// The Docker client version.
+        #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
+        client_version:  ClientVersion,
+        /// Whether to keep container after running or not.
+        #[clap(short = 'k', long, help = "If given, does not remove containers after execution. This is useful for debugging them.")]
+        keep_containers: bool,
+    },
+
+    #[clap(
+        name = "visualize",
+        about = "Renders a workflow as a Graphviz graph of its tasks and the datasets transferred to them. If a policy is given, every task and \
+                 transfer is colored by its verdict (green if allowed, red if denied and orange if it needs review)."
+    )]
+    Visualize {
+        #[clap(name = "FILE", help = "Path to the file to visualize. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        #[clap(short, long, help = "If given, colors the graph by the verdicts of this policy. Can be eFLINT (`*.eflint`) or eFLINT JSON (`*.json`).")]
+        against:  Option<PathBuf>,
+        #[clap(
+            short,
+            long,
+            default_value = "http://localhost:8080",
+            help = "The address of the eFLINT reasoner to check with (e.g., one that you started locally with `docker run`)."
+        )]
+        reasoner: Address,
+        #[clap(short, long, help = "If given, uses the given user as end user of the workflow.")]
+        user:     Option<String>,
+        #[clap(short, long, help = "If given, writes the graph (in DOT format) to this file instead of stdout.")]
+        output:   Option<PathBuf>,
+    },
+
+    #[clap(
+        name = "export",
+        about = "Exports a workflow to the Common Workflow Language (CWL), so it can be documented, reviewed or partially reused on infrastructure \
+                 that speaks CWL. Control flow that CWL cannot express (conditions, loops and functions) is noted in the steps' documentation."
+    )]
+    Export {
+        #[clap(name = "FILE", help = "Path to the file to export. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        #[clap(short, long, help = "If given, uses the given user as end user of the workflow.")]
+        user:   Option<String>,
+        #[clap(short, long, help = "If given, writes the CWL workflow (in YAML) to this file instead of stdout.")]
+        output: Option<PathBuf>,
+    },
+
+    #[clap(
+        name = "resume",
+        about = "Resumes a workflow that was interrupted on the instance you are currently logged-in to, skipping the tasks that it already \
+                 completed. This requires that the instance's driver checkpoints workflows."
+    )]
+    Resume {
+        #[clap(name = "EXECUTION_ID", help = "The identifier of the workflow to resume, as reported when it was interrupted.")]
+        execution_id: String,
+
+        #[clap(short, long, value_names = &["address[:port]"], help = "If given, proxies any data transfers to this machine through the proxy at the given address.")]
+        proxy_addr: Option<String>,
+        #[clap(long, help = "If given, shows profile times if they are available.")]
+        profile:    bool,
+    },
+
+    #[clap(
+        name = "results",
+        about = "Lists the intermediate results that a workflow produced on the instance you are currently logged-in to, including the task that \
+                 produced them, their size and until when they are kept. Use the subcommands to download or promote one of them. This requires that \
+                 the instance's driver keeps an execution history."
+    )]
+    Results {
+        #[clap(name = "EXECUTION_ID", help = "The identifier of the workflow, as reported when it completed.")]
+        execution_id: String,
+
+        // We subcommand further
+        #[clap(subcommand)]
+        subcommand: Option<ResultsSubcommand>,
+    },
+}
+
+/// Defines the subcommands for the `workflow results` subcommand.
+#[derive(Parser)]
+enum ResultsSubcommand {
+    #[clap(name = "download", about = "Downloads an intermediate result, by promoting it to a dataset and then downloading that dataset.")]
+    Download {
+        #[clap(name = "NAME", help = "The name of the intermediate result to download.")]
+        name:    String,
+        #[clap(short, long, help = "The name of the dataset to promote the result to. Defaults to the name of the result.")]
+        dataset: Option<String>,
+
+        #[clap(short, long, help = "If given, proxies the transfer through the given proxy.")]
+        proxy_addr: Option<String>,
+        #[clap(short, long, action, help = "If given, will always attempt to transfer data remotely, even if it's already available locally.")]
+        force:      bool,
+    },
+
+    #[clap(name = "promote", about = "Promotes an intermediate result to a dataset that lives where the result was produced.")]
+    Promote {
+        #[clap(name = "NAME", help = "The name of the intermediate result to promote.")]
+        name:    String,
+        #[clap(name = "DATASET", help = "The name of the dataset to promote the result to.")]
+        dataset: String,
+    },
+}
+
 
 
 
@@ -803,7 +1183,7 @@ async fn main() -> Result<()> {
 async fn run(options: Cli) -> Result<(), CliError> {
     use SubCommand::*;
     match options.sub_command {
-        Build { arch, workdir, file, kind, init, keep_files, crlf_ok } => {
+        Build { arch, workdir, file, kind, init, keep_files, crlf_ok, remote } => {
             // Resolve the working directory
             let workdir = match workdir {
                 Some(workdir) => workdir,
@@ -840,10 +1220,12 @@ async fn run(options: Cli) -> Result<(), CliError> {
 
             // Build a new package with it
             match kind {
-                PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, keep_files, crlf_ok)
-                    .await
-                    .map_err(|err| CliError::BuildError { err })?,
-                PackageKind::Oas => build_oas::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, keep_files)
+                PackageKind::Ecu | PackageKind::Wasm => {
+                    build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, keep_files, crlf_ok, remote)
+                        .await
+                        .map_err(|err| CliError::BuildError { err })?
+                },
+                PackageKind::Oas => build_oas::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, keep_files, remote)
                     .await
                     .map_err(|err| CliError::BuildError { err })?,
                 _ => eprintln!("Unsupported package kind: {kind}"),
@@ -857,6 +1239,11 @@ async fn run(options: Cli) -> Result<(), CliError> {
                         return Err(CliError::CertsError { err });
                     }
                 },
+                Enroll { domain, token, instance, force } => {
+                    if let Err(err) = certs::enroll(instance, domain, token, force).await {
+                        return Err(CliError::CertsError { err });
+                    }
+                },
                 Remove { domains, instance, force } => {
                     if let Err(err) = certs::remove(domains, instance, force) {
                         return Err(CliError::CertsError { err });
@@ -995,12 +1382,12 @@ async fn run(options: Cli) -> Result<(), CliError> {
 
             // Build a new package with it
             match kind {
-                PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, false, crlf_ok)
+                PackageKind::Ecu | PackageKind::Wasm => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, false, crlf_ok, false)
+                    .await
+                    .map_err(|err| CliError::BuildError { err })?,
+                PackageKind::Oas => build_oas::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, false, false)
                     .await
                     .map_err(|err| CliError::BuildError { err })?,
-                PackageKind::Oas => {
-                    build_oas::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, false).await.map_err(|err| CliError::BuildError { err })?
-                },
                 _ => eprintln!("Unsupported package kind: {kind}"),
             }
         },
@@ -1035,8 +1422,13 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     }
                 },
 
-                List { show_status } => {
-                    if let Err(err) = instance::list(show_status).await {
+                List { show_status, show_locations } => {
+                    if let Err(err) = instance::list(show_status, show_locations).await {
+                        return Err(CliError::InstanceError { err });
+                    }
+                },
+                Ping { name } => {
+                    if let Err(err) = instance::ping(name).await {
                         return Err(CliError::InstanceError { err });
                     }
                 },
@@ -1046,8 +1438,21 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     }
                 },
 
-                Edit { name, hostname, api_port, drv_port, user } => {
-                    if let Err(err) = instance::edit(name, hostname, api_port, drv_port, user) {
+                Export { name, output } => {
+                    if let Err(err) = instance::export(name, output) {
+                        return Err(CliError::InstanceError { err });
+                    }
+                },
+                Import { path, name, user, use_immediately, force } => {
+                    if let Err(err) =
+                        instance::import(path, name, user.unwrap_or_else(|| names::three::lowercase::rand().into()), use_immediately, force)
+                    {
+                        return Err(CliError::InstanceError { err });
+                    }
+                },
+
+                Edit { name, hostname, api_port, drv_port, user, token } => {
+                    if let Err(err) = instance::edit(name, hostname, api_port, drv_port, user, token) {
                         return Err(CliError::InstanceError { err });
                     }
                 },
@@ -1084,7 +1489,7 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 return Err(CliError::RegistryError { err });
             };
         },
-        Push { packages } => {
+        Push { packages, oci } => {
             // Parse the NAME:VERSION pairs into a name and a version
             if packages.is_empty() {
                 println!("Nothing to do.");
@@ -1101,7 +1506,11 @@ async fn run(options: Cli) -> Result<(), CliError> {
             }
 
             // Now delegate the parsed pairs to the actual push() function
-            if let Err(err) = registry::push(parsed).await {
+            let res = match oci {
+                Some(repository) => registry::push_oci(parsed, repository).await,
+                None => registry::push(parsed).await,
+            };
+            if let Err(err) = res {
                 return Err(CliError::RegistryError { err });
             };
         },
@@ -1126,23 +1535,25 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 return Err(CliError::PackageError { err });
             };
         },
-        Repl { proxy_addr, bakery, clear, remote, attach, profile, docker_socket, client_version, keep_containers } => {
+        Repl { proxy_addr, bakery, clear, remote, attach, list_sessions, profile, persist, docker_socket, client_version, keep_containers } => {
             if let Err(err) = repl::start(
                 proxy_addr,
                 remote,
                 attach,
+                list_sessions,
                 if bakery { Language::Bakery } else { Language::BraneScript },
                 clear,
                 profile,
                 DockerOptions { socket: docker_socket, version: client_version },
                 keep_containers,
+                persist,
             )
             .await
             {
                 return Err(CliError::ReplError { err });
             };
         },
-        Run { proxy_addr, bakery, file, dry_run, remote, profile, docker_socket, client_version, keep_containers } => {
+        Run { proxy_addr, bakery, file, dry_run, remote, auto_commit, output, profile, docker_socket, client_version, keep_containers } => {
             if let Err(err) = run::handle(
                 proxy_addr,
                 if bakery { Language::Bakery } else { Language::BraneScript },
@@ -1152,6 +1563,11 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 profile,
                 DockerOptions { socket: docker_socket, version: client_version },
                 keep_containers,
+                false,
+                None,
+                auto_commit,
+                output,
+                None,
             )
             .await
             {
@@ -1165,8 +1581,9 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 return Err(CliError::TestError { err });
             };
         },
-        Search { term } => {
-            if let Err(err) = registry::search(term).await {
+        Search { term, federated } => {
+            let res = if federated { registry::search_federated(term).await } else { registry::search(term).await };
+            if let Err(err) = res {
                 return Err(CliError::OtherError { err });
             };
         },
@@ -1233,6 +1650,103 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 }
             }
         },
+        Workflow { subcommand } => {
+            // Match the subcommand in question
+            use WorkflowSubcommand::*;
+            match subcommand {
+                Check { file, bakery, against, reasoner, user } => {
+                    if let Err(err) =
+                        workflow::check(file, if bakery { Language::Bakery } else { Language::BraneScript }, against, reasoner, user).await
+                    {
+                        return Err(CliError::WorkflowError { err });
+                    }
+                },
+                Run {
+                    file,
+                    bakery,
+                    simulate_domains,
+                    remote: _,
+                    tui,
+                    deadline,
+                    proxy_addr,
+                    reasoner,
+                    user,
+                    log_file,
+                    webhook,
+                    docker_socket,
+                    client_version,
+                    keep_containers,
+                } => {
+                    let language: Language = if bakery { Language::Bakery } else { Language::BraneScript };
+                    let docker_opts: DockerOptions = DockerOptions { socket: docker_socket, version: client_version };
+                    let sinks: Option<Arc<OutputSinks>> = match OutputSinks::new(log_file, webhook) {
+                        Ok(sinks) => sinks.map(Arc::new),
+                        Err(err) => return Err(CliError::SinkError { err }),
+                    };
+                    match simulate_domains {
+                        Some(simulate_domains) => {
+                            if let Err(err) =
+                                workflow::run(file, language, simulate_domains, reasoner, user, docker_opts, keep_containers, sinks).await
+                            {
+                                return Err(CliError::WorkflowError { err });
+                            }
+                        },
+                        // Otherwise, `--remote` was given
+                        None => {
+                            if let Err(err) = run::handle(
+                                proxy_addr,
+                                language,
+                                PathBuf::from(file),
+                                false,
+                                true,
+                                false,
+                                docker_opts,
+                                keep_containers,
+                                tui,
+                                deadline.map(Into::into),
+                                vec![],
+                                OutputFormat::Human,
+                                sinks,
+                            )
+                            .await
+                            {
+                                return Err(CliError::RunError { err });
+                            }
+                        },
+                    }
+                },
+                Visualize { file, bakery, against, reasoner, user, output } => {
+                    if let Err(err) =
+                        workflow::visualize(file, if bakery { Language::Bakery } else { Language::BraneScript }, against, reasoner, user, output)
+                            .await
+                    {
+                        return Err(CliError::WorkflowError { err });
+                    }
+                },
+                Export { file, bakery, user, output } => {
+                    if let Err(err) = workflow::export(file, if bakery { Language::Bakery } else { Language::BraneScript }, user, output) {
+                        return Err(CliError::WorkflowError { err });
+                    }
+                },
+                Resume { execution_id, proxy_addr, profile } => {
+                    if let Err(err) = run::resume(proxy_addr, execution_id, profile).await {
+                        return Err(CliError::RunError { err });
+                    }
+                },
+                Results { execution_id, subcommand } => {
+                    let res: Result<(), results::Error> = match subcommand {
+                        None => results::list(execution_id).await,
+                        Some(ResultsSubcommand::Download { name, dataset, proxy_addr, force }) => {
+                            results::download(execution_id, name, dataset, proxy_addr, force).await
+                        },
+                        Some(ResultsSubcommand::Promote { name, dataset }) => results::promote(execution_id, name, dataset).await.map(|_| ()),
+                    };
+                    if let Err(err) = res {
+                        return Err(CliError::ResultsError { err });
+                    }
+                },
+            }
+        },
     }
 
     Ok(())
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::str::FromStr;
 use std::time::Duration;
@@ -19,6 +20,8 @@ use hyper::Body;
 use indicatif::{DecimalBytes, HumanDuration};
 use prettytable::format::FormatBuilder;
 use prettytable::Table;
+use serde::Serialize;
+use specifications::common::{Function, Type};
 use specifications::container::Image;
 use specifications::package::PackageInfo;
 use specifications::version::Version;
@@ -45,7 +48,7 @@ fn insert_package_in_list(infos: &mut Vec<PackageInfo>, info: PackageInfo) {
         if info.name.eq(&pkg.name) {
             // Only add if the new version is higher
             debug!(" > Version '{}' vs '{}'", info.version, pkg.version.to_string());
-            if info.version > pkg.version {
+            if info.version.cmp_release(&pkg.version).is_gt() {
                 *pkg = info;
             }
             // Always stop tho
@@ -56,19 +59,187 @@ fn insert_package_in_list(infos: &mut Vec<PackageInfo>, info: PackageInfo) {
     // Simply add to the list
     infos.push(info);
 }
+
+/// Renders a data type as it is written in BraneScript.
+///
+/// # Arguments
+/// - `data_type`: The raw data type as given in a package's `package.yml` (e.g., `int[]`).
+///
+/// # Returns
+/// The BraneScript spelling of the type (e.g., `int[]`).
+fn bscript_type(data_type: &str) -> String {
+    fn render(data_type: &DataType) -> String {
+        match data_type {
+            DataType::Any => "any".into(),
+            DataType::Void => "void".into(),
+            DataType::Boolean => "bool".into(),
+            DataType::Integer => "int".into(),
+            DataType::Real => "real".into(),
+            DataType::String => "string".into(),
+            DataType::Semver => "semver".into(),
+            DataType::Array(elem) => format!("{}[]", render(elem)),
+            DataType::Function(_) => "func".into(),
+            DataType::Class(name) => name.clone(),
+        }
+    }
+    render(&DataType::from(data_type))
+}
+
+/// Renders the typed signature of a package function in BraneScript syntax.
+///
+/// # Arguments
+/// - `name`: The name of the function.
+/// - `func`: The [`Function`] to render the signature of.
+///
+/// # Returns
+/// A signature like `func hello_world(name: string) -> string`.
+fn bscript_signature(name: &str, func: &Function) -> String {
+    format!(
+        "func {}({}) -> {}",
+        name,
+        func.parameters.iter().map(|p| format!("{}: {}", p.name, bscript_type(&p.data_type))).collect::<Vec<String>>().join(", "),
+        bscript_type(&func.return_type)
+    )
+}
+
+/// Renders a package's classes and functions as BraneScript stubs, which import the package and document how to call its functions.
+///
+/// # Arguments
+/// - `info`: The [`PackageInfo`] of the package to render.
+///
+/// # Returns
+/// A BraneScript snippet.
+fn bscript_stubs(info: &PackageInfo) -> String {
+    let mut res: String = format!("// Package `{}` ({} package, version {})\n", info.name, info.kind, info.version);
+    for line in info.description.lines().filter(|l| !l.trim().is_empty()) {
+        res.push_str(&format!("// {line}\n"));
+    }
+    res.push_str(&format!("import {}[{}];\n", info.name, info.version));
+
+    // Classes are defined by the import, so we only document them
+    for (name, class) in info.types.iter().collect::<BTreeMap<&String, &Type>>() {
+        res.push_str(&format!("\n// class {name} {{\n"));
+        for p in &class.properties {
+            res.push_str(&format!("//     {}: {};\n", p.name, bscript_type(&p.data_type)));
+        }
+        res.push_str("// }\n");
+    }
+
+    // Functions are documented with their signature and a call to paste
+    for (name, func) in info.functions.iter().collect::<BTreeMap<&String, &Function>>() {
+        res.push_str(&format!("\n// {}\n", bscript_signature(name, func)));
+        if let Some(requirements) = &func.requirements {
+            let mut requirements: Vec<String> = requirements.iter().map(|c| format!("{c:?}")).collect();
+            requirements.sort();
+            res.push_str(&format!("// Requires: {}\n", requirements.join(", ")));
+        }
+        res.push_str(&format!("// {}({});\n", name, func.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>().join(", ")));
+    }
+    res
+}
+
+/// Renders a package's classes and functions as a Markdown document.
+///
+/// # Arguments
+/// - `info`: The [`PackageInfo`] of the package to render.
+///
+/// # Returns
+/// A Markdown document.
+fn markdown_doc(info: &PackageInfo) -> String {
+    let mut res: String = format!("# `{}` {}\n\n", info.name, info.version);
+    if !info.description.trim().is_empty() {
+        res.push_str(&format!("{}\n\n", info.description.trim()));
+    }
+    res.push_str(&format!("- **Kind:** {}\n", info.kind));
+    if !info.owners.is_empty() {
+        res.push_str(&format!("- **Owners:** {}\n", info.owners.join(", ")));
+    }
+    if let Some(license) = &info.license {
+        res.push_str(&format!("- **License:** {license}\n"));
+    }
+    if let Some(homepage) = &info.homepage {
+        res.push_str(&format!("- **Homepage:** <{homepage}>\n"));
+    }
+    res.push_str(&format!("\nImport it in BraneScript with:\n\n```branescript\nimport {}[{}];\n```\n", info.name, info.version));
+
+    // Document the classes
+    if !info.types.is_empty() {
+        res.push_str("\n## Classes\n");
+        for (name, class) in info.types.iter().collect::<BTreeMap<&String, &Type>>() {
+            res.push_str(&format!("\n### `{name}`\n\n| Property | Type |\n|----------|------|\n"));
+            for p in &class.properties {
+                res.push_str(&format!("| `{}` | `{}` |\n", p.name, bscript_type(&p.data_type)));
+            }
+        }
+    }
+
+    // Document the functions
+    if !info.functions.is_empty() {
+        res.push_str("\n## Functions\n");
+        for (name, func) in info.functions.iter().collect::<BTreeMap<&String, &Function>>() {
+            res.push_str(&format!("\n### `{}`\n\n```branescript\n{}\n```\n", name, bscript_signature(name, func)));
+            if !func.parameters.is_empty() {
+                res.push_str("\n| Parameter | Type |\n|-----------|------|\n");
+                for p in &func.parameters {
+                    res.push_str(&format!("| `{}` | `{}` |\n", p.name, bscript_type(&p.data_type)));
+                }
+            }
+            res.push_str(&format!("\n**Returns:** `{}`\n", bscript_type(&func.return_type)));
+            if let Some(requirements) = &func.requirements {
+                let mut requirements: Vec<String> = requirements.iter().map(|c| format!("`{c:?}`")).collect();
+                requirements.sort();
+                res.push_str(&format!("\n**Requires:** {}\n", requirements.join(", ")));
+            }
+        }
+    }
+    res
+}
 /*******/
 
 
 
 
 
+/***** AUXILLARY *****/
+/// The signatures of a package, as printed by `brane package inspect --syntax json`.
+#[derive(Serialize)]
+struct PackageSignatures<'i> {
+    /// The name of the package.
+    name:        &'i str,
+    /// The version of the package.
+    version:     &'i Version,
+    /// The kind of the package.
+    kind:        String,
+    /// The description of the package.
+    description: &'i str,
+    /// The classes defined by the package.
+    types:       BTreeMap<&'i String, &'i Type>,
+    /// The functions defined by the package.
+    functions:   BTreeMap<&'i String, FunctionSignature<'i>>,
+}
+
+/// The signature of a single function in [`PackageSignatures`].
+#[derive(Serialize)]
+struct FunctionSignature<'i> {
+    /// The signature of the function in BraneScript syntax.
+    signature: String,
+    /// The function itself, i.e., its parameters, return type and requirements.
+    #[serde(flatten)]
+    function:  &'i Function,
+}
+
+
+
+
+
 /***** SUBCOMMANDS *****/
 /// Inspects the given package, pretty-printing its details.
 ///
 /// # Arguments
 /// - `name`: The name of the package to inspect.
 /// - `version`: The version of the package to inspect.
-/// - `syntax`: The mode of syntax to use for classes & functions. Can be 'bscript', 'bakery' or 'custom'.
+/// - `syntax`: The syntax to print the package in. Can be 'custom' (a human-readable overview), 'bscript' (BraneScript stubs), 'json', 'markdown'
+///   or 'bakery'.
 ///
 /// # Returns
 /// Nothing
@@ -77,6 +248,35 @@ pub fn inspect(name: String, version: Version, syntax: String) -> Result<()> {
     let package_file = package_dir.join("package.yml");
 
     if let Ok(info) = PackageInfo::from_path(package_file) {
+        // Print the machine- or paste-friendly syntaxes as-is
+        match syntax.as_str() {
+            "bscript" => {
+                print!("{}", bscript_stubs(&info));
+                return Ok(());
+            },
+            "json" => {
+                let sigs: PackageSignatures = PackageSignatures {
+                    name:        &info.name,
+                    version:     &info.version,
+                    kind:        info.kind.to_string(),
+                    description: &info.description,
+                    types:       info.types.iter().collect(),
+                    functions:   info
+                        .functions
+                        .iter()
+                        .map(|(name, func)| (name, FunctionSignature { signature: bscript_signature(name, func), function: func }))
+                        .collect(),
+                };
+                println!("{}", serde_json::to_string_pretty(&sigs)?);
+                return Ok(());
+            },
+            "markdown" | "md" => {
+                print!("{}", markdown_doc(&info));
+                return Ok(());
+            },
+            _ => {},
+        }
+
         // _Neatly_ print it
         println!();
         println!(
@@ -104,6 +304,35 @@ pub fn inspect(name: String, version: Version, syntax: String) -> Result<()> {
         println!("{}", if !info.description.trim().is_empty() { &info.description } else { "<no description>" });
         println!();
 
+        // Print the additional metadata, if any
+        if !info.maintainers.is_empty() {
+            println!("Maintainers: {}", PrettyListFormatter::new(info.maintainers.iter().map(|m| format!("{}", style(&m).bold())), "and"));
+        }
+        if let Some(license) = &info.license {
+            println!("License: {}", style(license).bold());
+        }
+        if let Some(homepage) = &info.homepage {
+            println!("Homepage: {}", style(homepage).bold());
+        }
+        if let Some(resources) = &info.resources {
+            println!("Minimum resources: {}", style(resources).bold());
+        }
+        if !info.tags.is_empty() {
+            println!("Tags: {}", info.tags.iter().map(|t| format!("{}", style(t).bold())).collect::<Vec<String>>().join(", "));
+        }
+        if let Some(scan) = &info.scan {
+            println!("Vulnerability scan: {} (at {})", style(scan).bold(), scan.scanned.with_timezone(&Local));
+        }
+        if !info.maintainers.is_empty()
+            || info.license.is_some()
+            || info.homepage.is_some()
+            || info.resources.is_some()
+            || !info.tags.is_empty()
+            || info.scan.is_some()
+        {
+            println!();
+        }
+
         // Now print the types
         println!("Classes provided by this package:");
         let mut types: Vec<&String> = info.types.keys().collect();
@@ -111,14 +340,6 @@ pub fn inspect(name: String, version: Version, syntax: String) -> Result<()> {
         for name in types {
             let info = info.types.get(name).unwrap();
             match syntax.as_str() {
-                "bscript" => {
-                    println!("  - class {} {{", style(&name).bold().cyan());
-                    for p in &info.properties {
-                        println!("        {}: {};", style(&p.name).bold(), DataType::from(&p.data_type));
-                    }
-                    println!("    }}");
-                },
-
                 "bakery" => {
                     return Err(anyhow!("Bakery syntax is not yet implemented"));
                 },
@@ -147,19 +368,6 @@ pub fn inspect(name: String, version: Version, syntax: String) -> Result<()> {
         for name in funcs {
             let func = info.functions.get(name).unwrap();
             match syntax.as_str() {
-                "bscript" => {
-                    println!(
-                        "  - func {}({}) -> {}",
-                        style(&name).bold().cyan(),
-                        func.parameters
-                            .iter()
-                            .map(|p| format!("{}: {}", style(&p.name).bold(), DataType::from(&p.data_type)))
-                            .collect::<Vec<String>>()
-                            .join(", "),
-                        DataType::from(&func.return_type)
-                    );
-                },
-
                 "bakery" => {
                     return Err(anyhow!("Bakery syntax is not yet implemented"));
                 },
@@ -4,13 +4,15 @@
 //  Created:
 //    24 Oct 2022, 16:40:21
 //  Last edited:
-//    31 Jan 2024, 14:47:01
+//    15 Oct 2026, 22:55:10
 //  Auto updated?
 //    Yes
 //
 //  Description:
 //!   A very trivial planner, that simple plans every dataset to run on
-//!   'localhost'.
+//!   'localhost'. When simulating multiple domains, it instead plans
+//!   every task on the domain that has most of its inputs, and plans
+//!   transfers for the rest.
 //
 
 use std::collections::{HashMap, HashSet};
@@ -19,21 +21,70 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use brane_ast::ast::{Edge, SymTable};
+use brane_ast::locations::{Location, Locations};
 use brane_ast::Workflow;
+use brane_shr::suggest::suggest;
 use brane_tsk::errors::PlanError;
 use brane_tsk::spec::{Planner, LOCALHOST};
 use log::debug;
 use parking_lot::Mutex;
-use specifications::data::{AccessKind, AvailabilityKind, DataIndex, DataName};
+use specifications::data::{AccessKind, AvailabilityKind, DataIndex, DataName, PreprocessKind};
 
 
 /***** HELPER FUNCTIONS *****/
+/// Helper function that decides on which of the simulated domains to run a task.
+///
+/// # Arguments
+/// - `name`: The name of the task (used for debugging).
+/// - `locs`: The locations to which the task is restricted (e.g., by an On-struct).
+/// - `input`: The datasets/intermediate results that are input to the task.
+/// - `dindex`: The DataIndex we use to find where datasets are.
+/// - `results`: The map of results that are known in this workflow to where they are.
+/// - `domains`: The domains that are simulated.
+///
+/// # Returns
+/// The domain that has the most of the task's inputs (i.e., the one that needs the fewest transfers), preferring earlier domains if there is a
+/// tie.
+///
+/// # Errors
+/// This function errors if the task is restricted to locations that are not simulated.
+fn place(
+    name: &str,
+    locs: &Locations,
+    input: &HashMap<DataName, Option<AvailabilityKind>>,
+    dindex: &DataIndex,
+    results: &HashMap<String, String>,
+    domains: &[Location],
+) -> Result<Location, PlanError> {
+    let candidates: Vec<&Location> = match locs {
+        Locations::All => domains.iter().collect(),
+        Locations::Restricted(locs) => domains.iter().filter(|domain| locs.contains(domain)).collect(),
+    };
+
+    // Count the inputs that each candidate already has
+    let local = |domain: &Location| -> usize {
+        input
+            .keys()
+            .filter(|data| match data {
+                DataName::Data(data) => dindex.get(data).map(|info| info.access.contains_key(domain)).unwrap_or(false),
+                DataName::IntermediateResult(data) => results.get(data) == Some(domain),
+            })
+            .count()
+    };
+    // NOTE: `max_by_key()` returns the last maximum, so we iterate in reverse to prefer earlier domains
+    match candidates.into_iter().rev().max_by_key(|domain| local(domain)) {
+        Some(domain) => Ok(domain.clone()),
+        None => Err(PlanError::UnknownLocationError { name: name.into(), locs: locs.clone() }),
+    }
+}
+
 /// Helper function that plans the given list of edges.
 ///
 /// # Arguments
 /// - `table`: The SymbolTable these edges live in.
 /// - `edges`: The given list to plan.
 /// - `dindex`: The DataIndex we use to resolve data references.
+/// - `domains`: If given, the domains that are simulated. Tasks are then planned on those instead of on 'localhost'.
 /// - `pc`: The started index for the program counter. Should be '0' when called manually, the rest is handled during recursion.
 /// - `merge`: If given, then we will stop analysing once we reach that point.
 /// - `deferred`: Whether or not to show errors when an intermediate result is not generated yet (false) or not (true).
@@ -44,10 +95,12 @@ use specifications::data::{AccessKind, AvailabilityKind, DataIndex, DataName};
 ///
 /// # Errors
 /// This function may error if the given list of edges was malformed (usually due to unknown or inaccessible datasets or results).
+#[allow(clippy::too_many_arguments)]
 fn plan_edges(
     table: &mut SymTable,
     edges: &mut [Edge],
     dindex: &Arc<DataIndex>,
+    domains: Option<&[Location]>,
     pc: usize,
     merge: Option<usize>,
     deferred: bool,
@@ -64,21 +117,25 @@ fn plan_edges(
         done.insert(pc);
         match edge {
             // This is the node where it all revolves around, in the end
-            Edge::Node { task, at, input, result, next, .. } => {
-                // We simply assign all locations to localhost
-                *at = Some(LOCALHOST.into());
-                debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), LOCALHOST);
-
-                // For all dataset/intermediate result inputs, we assert they are available on the local location
+            Edge::Node { task, locs, at, input, result, next, .. } => {
+                // We simply assign all locations to localhost (unless we simulate domains)
+                let loc: Location = match domains {
+                    Some(domains) => place(table.tasks[*task].name(), locs, input, dindex, &table.results, domains)?,
+                    None => LOCALHOST.into(),
+                };
+                *at = Some(loc.clone());
+                debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), loc);
+
+                // For all dataset/intermediate result inputs, we assert they are available on that location (or transfer them if simulating)
                 for (name, avail) in input {
-                    OfflinePlanner::plan_data(name, avail, dindex, &table.results, deferred)?;
+                    OfflinePlanner::plan_data(name, avail, dindex, &table.results, &loc, domains.is_some(), deferred)?;
                 }
 
                 // Then, we make the intermediate result available at the location where the function is being run (if there is any)
                 if let Some(name) = result {
                     // Insert an entry in the list detailling where to access it and how
-                    debug!("Making intermediate result '{}' accessible after execution of '{}' on '{}'", name, table.tasks[*task].name(), LOCALHOST);
-                    table.results.insert(name.clone(), LOCALHOST.into());
+                    debug!("Making intermediate result '{}' accessible after execution of '{}' on '{}'", name, table.tasks[*task].name(), loc);
+                    table.results.insert(name.clone(), loc);
                 }
 
                 // Finally, don't forget to move to the next one
@@ -100,10 +157,10 @@ fn plan_edges(
                 let merge: Option<usize> = *merge;
 
                 // First analyse the true_next branch, until it reaches the merge (or quits)
-                plan_edges(table, edges, dindex, true_next, merge, deferred, done)?;
+                plan_edges(table, edges, dindex, domains, true_next, merge, deferred, done)?;
                 // If there is a false branch, do that one too
                 if let Some(false_next) = false_next {
-                    plan_edges(table, edges, dindex, false_next, merge, deferred, done)?;
+                    plan_edges(table, edges, dindex, domains, false_next, merge, deferred, done)?;
                 }
 
                 // If there is a merge, continue there; otherwise, we can assume that we've returned fully in the branch
@@ -121,7 +178,7 @@ fn plan_edges(
                 // Analyse any of the branches
                 for b in branches {
                     // No merge needed since we can be safe in assuming parallel branches end with returns
-                    plan_edges(table, edges, dindex, b, None, deferred, done)?;
+                    plan_edges(table, edges, dindex, domains, b, None, deferred, done)?;
                 }
 
                 // Continue at the merge
@@ -139,12 +196,12 @@ fn plan_edges(
                 let next: Option<usize> = *next;
 
                 // Run the conditions and body in a first pass, with deferation enabled, to do as much as we can
-                plan_edges(table, edges, dindex, cond, Some(body), true, done)?;
-                plan_edges(table, edges, dindex, body, Some(cond), true, done)?;
+                plan_edges(table, edges, dindex, domains, cond, Some(body), true, done)?;
+                plan_edges(table, edges, dindex, domains, body, Some(cond), true, done)?;
 
                 // Then we run through the condition and body again to resolve any unknown things
-                plan_deferred(table, edges, cond, Some(body), &mut HashSet::new())?;
-                plan_deferred(table, edges, cond, Some(cond), &mut HashSet::new())?;
+                plan_deferred(table, edges, domains.is_some(), cond, Some(body), &mut HashSet::new())?;
+                plan_deferred(table, edges, domains.is_some(), cond, Some(cond), &mut HashSet::new())?;
 
                 // When done, move to the next if there is any (otherwise, the body returns and then so can we)
                 if let Some(next) = next {
@@ -198,6 +255,7 @@ fn plan_edges(
 /// # Arguments
 /// - `table`: The SymbolTable these edges live in.
 /// - `edges`: The given list to plan.
+/// - `transfer`: Whether to plan transfers for results that are not available where a task runs (i.e., if we simulate domains).
 /// - `pc`: The started index for the program counter. Should be '0' when called manually, the rest is handled during recursion.
 /// - `merge`: If given, then we will stop analysing once we reach that point.
 ///
@@ -206,7 +264,14 @@ fn plan_edges(
 ///
 /// # Errors
 /// This function may error if there were still results that couldn't be populated even after we've seen all edges.
-fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<usize>, done: &mut HashSet<usize>) -> Result<(), PlanError> {
+fn plan_deferred(
+    table: &SymTable,
+    edges: &mut [Edge],
+    transfer: bool,
+    pc: usize,
+    merge: Option<usize>,
+    done: &mut HashSet<usize>,
+) -> Result<(), PlanError> {
     // We cannot get away simply examining all edges in-order; we have to follow their execution structure
     let mut pc: usize = pc;
     while pc < edges.len() && (merge.is_none() || pc != merge.unwrap()) {
@@ -218,7 +283,9 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
         done.insert(pc);
         match edge {
             // This is the node where it all revolves around, in the end
-            Edge::Node { input, next, .. } => {
+            Edge::Node { at, input, next, .. } => {
+                let at: &str = at.as_deref().unwrap_or(LOCALHOST);
+
                 // This next trick involves checking if the node has any unresolved results as input, then trying to resolve them
                 for (name, avail) in input {
                     // Continue if it already has a resolved availability
@@ -231,9 +298,17 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
                         // We have to know of it, i.e., it has to be declared somewhere where it makes sense
                         if let Some(loc) = table.results.get(name) {
                             // Match on whether it is available locally or not
-                            if LOCALHOST == loc {
+                            if at == loc {
                                 debug!("Input intermediate result '{}' is locally available", name);
                                 *avail = Some(AvailabilityKind::Available { how: AccessKind::File { path: PathBuf::from(name) } });
+                            } else if transfer {
+                                debug!("Input intermediate result '{}' will be transferred from '{}' to '{}'", name, loc, at);
+                                *avail = Some(AvailabilityKind::Unavailable {
+                                    how: PreprocessKind::TransferRegistryTar {
+                                        location: loc.clone(),
+                                        dataname: DataName::IntermediateResult(name.clone()),
+                                    },
+                                });
                             } else {
                                 // We don't download, so always unavailable
                                 return Err(PlanError::IntermediateResultUnavailable { name: name.clone(), locs: vec![] });
@@ -265,10 +340,10 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
                 let merge: Option<usize> = *merge;
 
                 // First analyse the true_next branch, until it reaches the merge (or quits)
-                plan_deferred(table, edges, true_next, merge, done)?;
+                plan_deferred(table, edges, transfer, true_next, merge, done)?;
                 // If there is a false branch, do that one too
                 if let Some(false_next) = false_next {
-                    plan_deferred(table, edges, false_next, merge, done)?;
+                    plan_deferred(table, edges, transfer, false_next, merge, done)?;
                 }
 
                 // If there is a merge, continue there; otherwise, we can assume that we've returned fully in the branch
@@ -286,7 +361,7 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
                 // Analyse any of the branches
                 for b in branches {
                     // No merge needed since we can be safe in assuming parallel branches end with returns
-                    plan_deferred(table, edges, b, None, done)?;
+                    plan_deferred(table, edges, transfer, b, None, done)?;
                 }
 
                 // Continue at the merge
@@ -304,8 +379,8 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
                 let next: Option<usize> = *next;
 
                 // We only have to analyse further deferrence; the actual planning should have been done before `plan_deferred()` is called
-                plan_deferred(table, edges, cond, Some(body), done)?;
-                plan_deferred(table, edges, cond, Some(cond), done)?;
+                plan_deferred(table, edges, transfer, cond, Some(body), done)?;
+                plan_deferred(table, edges, transfer, cond, Some(cond), done)?;
 
                 // When done, move to the next if there is any (otherwise, the body returns and then so can we)
                 if let Some(next) = next {
@@ -337,10 +412,14 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
 
 /***** LIBRARY *****/
 /// The planner is in charge of assigning locations to tasks in a workflow. This one is very simple, assigning 'localhost' to whatever it sees.
+///
+/// If it simulates domains, it instead assigns every task to the domain that has most of its inputs, and plans transfers for the others.
 #[derive(Debug)]
 pub struct OfflinePlanner {
     /// The local data index to resolve datasets with.
     data_index:  Arc<DataIndex>,
+    /// The domains to plan on if we simulate them.
+    domains:     Option<Vec<Location>>,
     /// The results we planned last time (or whatever).
     pub results: Arc<Mutex<HashMap<String, String>>>,
 }
@@ -355,7 +434,21 @@ impl OfflinePlanner {
     /// # Returns
     /// A new OfflinePlanner instance.
     #[inline]
-    pub fn new(data_index: Arc<DataIndex>, results: Arc<Mutex<HashMap<String, String>>>) -> Self { Self { data_index, results } }
+    pub fn new(data_index: Arc<DataIndex>, results: Arc<Mutex<HashMap<String, String>>>) -> Self { Self { data_index, domains: None, results } }
+
+    /// Constructor for an OfflinePlanner that plans on simulated domains instead of on 'localhost'.
+    ///
+    /// # Arguments
+    /// - `data_index`: The DataIndex that is used to resolve datasets at plantime. Datasets should be available at the simulated domains.
+    /// - `results`: A map of results to where they are, which we planned last time around.
+    /// - `domains`: The simulated domains to plan on. Ties are broken in favour of the earlier domains.
+    ///
+    /// # Returns
+    /// A new OfflinePlanner instance.
+    #[inline]
+    pub fn simulating(data_index: Arc<DataIndex>, results: Arc<Mutex<HashMap<String, String>>>, domains: Vec<Location>) -> Self {
+        Self { data_index, domains: Some(domains), results }
+    }
 
     /// Plans the given task offline.
     ///
@@ -364,6 +457,8 @@ impl OfflinePlanner {
     /// - `avail`: The availability for this dataset that we will be updating.
     /// - `dindex`: The DataIndex we use to see what datasets are actually available where.
     /// - `results`: The map of results that are known in this workflow.
+    /// - `loc`: The location where the task that uses the data is planned.
+    /// - `transfer`: If `true`, plans a transfer if the data is not available at `loc` instead of erroring (i.e., if we simulate domains).
     /// - `deferred`: If `true`, then will not error if we failed to find a result yet (its declaration might come later, in that case).
     ///
     /// # Returns
@@ -373,31 +468,44 @@ impl OfflinePlanner {
         avail: &mut Option<AvailabilityKind>,
         dindex: &Arc<DataIndex>,
         results: &HashMap<String, String>,
+        loc: &str,
+        transfer: bool,
         deferred: bool,
     ) -> Result<(), PlanError> {
         match name {
             DataName::Data(name) => {
                 if let Some(info) = dindex.get(name) {
                     // Check if it is local or remote
-                    if let Some(access) = info.access.get(LOCALHOST) {
+                    if let Some(access) = info.access.get(loc) {
                         debug!("Input dataset '{}' is locally available", name);
                         *avail = Some(AvailabilityKind::Available { how: access.clone() });
+                    } else if let (true, Some(from)) = (transfer, info.access.keys().min()) {
+                        // Transfer it from the first domain that has it, to be deterministic
+                        debug!("Input dataset '{}' will be transferred from '{}' to '{}'", name, from, loc);
+                        *avail = Some(AvailabilityKind::Unavailable {
+                            how: PreprocessKind::TransferRegistryTar { location: from.clone(), dataname: DataName::Data(name.clone()) },
+                        });
                     } else {
                         // We don't download, so always unavailable
                         return Err(PlanError::DatasetUnavailable { name: name.clone(), locs: vec![] });
                     }
                 } else {
-                    return Err(PlanError::UnknownDataset { name: name.clone() });
+                    return Err(PlanError::UnknownDataset { name: name.clone(), suggestions: suggest(name, dindex.names()) });
                 }
             },
 
             DataName::IntermediateResult(name) => {
                 // We have to know of it, i.e., it has to be declared somewhere where it makes sense
-                if let Some(loc) = results.get(name) {
+                if let Some(from) = results.get(name) {
                     // Match on whether it is available locally or not
-                    if LOCALHOST == loc {
+                    if loc == from {
                         debug!("Input intermediate result '{}' is locally available", name);
                         *avail = Some(AvailabilityKind::Available { how: AccessKind::File { path: PathBuf::from(name) } });
+                    } else if transfer {
+                        debug!("Input intermediate result '{}' will be transferred from '{}' to '{}'", name, from, loc);
+                        *avail = Some(AvailabilityKind::Unavailable {
+                            how: PreprocessKind::TransferRegistryTar { location: from.clone(), dataname: DataName::IntermediateResult(name.clone()) },
+                        });
                     } else {
                         // We don't download, so always unavailable
                         return Err(PlanError::IntermediateResultUnavailable { name: name.clone(), locs: vec![] });
@@ -437,7 +545,7 @@ impl Planner for OfflinePlanner {
 
             // Plan them
             debug!("Planning main edges...");
-            plan_edges(&mut table, &mut edges, &self.data_index, 0, None, false, &mut HashSet::new())?;
+            plan_edges(&mut table, &mut edges, &self.data_index, self.domains.as_deref(), 0, None, false, &mut HashSet::new())?;
 
             // Move the edges back
             let mut edges: Arc<Vec<Edge>> = Arc::new(edges);
@@ -454,7 +562,7 @@ impl Planner for OfflinePlanner {
             // Iterate through all of the edges
             for (idx, edges) in &mut funcs {
                 debug!("Planning '{}' edges...", table.funcs[*idx].name);
-                plan_edges(&mut table, edges, &self.data_index, 0, None, false, &mut HashSet::new())?;
+                plan_edges(&mut table, edges, &self.data_index, self.domains.as_deref(), 0, None, false, &mut HashSet::new())?;
             }
 
             // Put the map back
@@ -0,0 +1,649 @@
+//  PROGRESS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:35:26
+//  Last edited:
+//    16 Oct 2026, 00:46:40
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements an interactive terminal view that follows a workflow
+//!   running on a remote instance, showing the state of its tasks and
+//!   the output sent by the driver as it arrives.
+//
+
+use std::collections::HashMap;
+use std::io::{Stdout, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use brane_ast::ast::{Edge, TaskDef};
+use brane_ast::func_id::FunctionId;
+use brane_ast::Workflow;
+use brane_exe::pc::ProgramCounter;
+use brane_exe::FullValue;
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+use specifications::data::DataName;
+use specifications::driving::{ExecuteEvent, ExecuteReply, TaskState};
+use tonic::{Status, Streaming};
+
+pub use crate::errors::RunError as Error;
+use crate::run::status_error;
+use crate::sinks::OutputSinks;
+
+
+/***** CONSTANTS *****/
+/// The interval at which the view is redrawn (and thus the elapsed times updated) if nothing else happens.
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The number of lines that the logs scroll per PageUp/PageDown.
+const LOG_PAGE: usize = 10;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Formats an elapsed time for humans.
+///
+/// # Arguments
+/// - `elapsed`: The [`Duration`] to format.
+///
+/// # Returns
+/// The duration as, e.g., `4.2s` or `3m05s`.
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs: u64 = elapsed.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Returns the style with which to show a task in the given state.
+///
+/// # Arguments
+/// - `state`: The [`TaskState`] of the task.
+///
+/// # Returns
+/// A [`Style`] to show the state with.
+fn state_style(state: TaskState) -> Style {
+    match state {
+        TaskState::Queued => Style::default().fg(Color::DarkGray),
+        TaskState::Planning => Style::default().fg(Color::Blue),
+        TaskState::Transferring => Style::default().fg(Color::Magenta),
+        TaskState::Running => Style::default().fg(Color::Yellow),
+        TaskState::Done => Style::default().fg(Color::Green),
+        TaskState::Failed => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        TaskState::Cancelled => Style::default().fg(Color::Red),
+    }
+}
+
+/// Collects the task calls in a workflow, together with the calls whose results they depend on.
+///
+/// # Arguments
+/// - `workflow`: The [`Workflow`] to collect the task calls of.
+///
+/// # Returns
+/// A list of [`TaskRow`]s, one per task call, in the order in which they appear in the workflow.
+fn collect_tasks(workflow: &Workflow) -> Vec<TaskRow> {
+    let mut funcs: Vec<(FunctionId, &[Edge])> = vec![(FunctionId::Main, workflow.graph.as_slice())];
+    let mut ids: Vec<&usize> = workflow.funcs.keys().collect();
+    ids.sort();
+    funcs.extend(ids.into_iter().map(|id| (FunctionId::Func(*id), workflow.funcs[id].as_slice())));
+
+    // Collect the calls themselves first, remembering who produces which result
+    let mut tasks: Vec<TaskRow> = vec![];
+    let mut inputs: Vec<Vec<String>> = vec![];
+    let mut producers: HashMap<&str, String> = HashMap::new();
+    for (func_id, edges) in funcs {
+        for (edge_idx, edge) in edges.iter().enumerate() {
+            let (task, at, input, result) = if let Edge::Node { task, at, input, result, .. } = edge { (task, at, input, result) } else { continue };
+            let name: String = match workflow.table.task(*task) {
+                TaskDef::Compute(def) => format!("{}::{}", def.package, def.function.name),
+                TaskDef::Transfer => "__builtin::transfer".into(),
+            };
+            if let Some(result) = result {
+                producers.insert(result.as_str(), name.clone());
+            }
+
+            tasks.push(TaskRow {
+                pc: ProgramCounter { func_id, edge_idx }.to_string(),
+                name,
+                location: at.clone(),
+                after: vec![],
+                state: TaskState::Queued,
+                started: None,
+                finished: None,
+            });
+            inputs.push(
+                input.keys().filter_map(|name| if let DataName::IntermediateResult(name) = name { Some(name.clone()) } else { None }).collect(),
+            );
+        }
+    }
+
+    // Then resolve the results they take to the calls producing them
+    for (task, inputs) in tasks.iter_mut().zip(inputs) {
+        task.after = inputs.iter().filter_map(|name| producers.get(name.as_str()).cloned()).collect();
+        task.after.sort();
+        task.after.dedup();
+    }
+    tasks
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Puts the terminal in raw mode on an alternate screen for as long as it lives.
+struct TerminalGuard;
+impl TerminalGuard {
+    /// Enters raw mode and the alternate screen.
+    ///
+    /// # Errors
+    /// This function errors if we failed to change the mode of the terminal.
+    fn enter() -> Result<Self, std::io::Error> {
+        enable_raw_mode()?;
+        if let Err(err) = execute!(std::io::stdout(), EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(err);
+        }
+        Ok(Self)
+    }
+}
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+
+
+/// A single task call as shown in the view.
+#[derive(Clone, Debug)]
+struct TaskRow {
+    /// The program counter of the call (as `<func>:<edge>`).
+    pc:       String,
+    /// The name of the task called (as `<package>::<function>`).
+    name:     String,
+    /// The location where it runs, once known.
+    location: Option<String>,
+    /// The names of the tasks whose results it takes as input.
+    after:    Vec<String>,
+
+    /// The state that it is in.
+    state:    TaskState,
+    /// When it started transferring or running, if it did.
+    started:  Option<Instant>,
+    /// When it finished, if it did.
+    finished: Option<Instant>,
+}
+impl TaskRow {
+    /// Returns how long this task has been going, if it started at all.
+    #[inline]
+    fn elapsed(&self) -> Option<Duration> { self.started.map(|started| self.finished.unwrap_or_else(Instant::now) - started) }
+}
+
+
+
+/// Describes how following a workflow ended.
+enum Outcome {
+    /// The driver closed the stream, optionally having sent the (raw) result of the workflow.
+    Completed(Option<String>),
+    /// The driver reported an error.
+    Failed(Status),
+    /// The user closed the view before the workflow completed.
+    Interrupted,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Keeps track of the state of a workflow followed in the terminal.
+#[derive(Debug)]
+pub struct ProgressView {
+    /// The task calls in the workflow.
+    tasks:  Vec<TaskRow>,
+    /// The selected task call.
+    table:  TableState,
+    /// The lines logged so far.
+    logs:   Vec<String>,
+    /// How many lines the logs are scrolled up from the bottom.
+    scroll: usize,
+
+    /// When we started following the workflow.
+    started:  Instant,
+    /// Whether the driver is still planning the workflow.
+    planning: bool,
+    /// Whether the workflow completed, and if so, the reason it failed (if it did).
+    finished: Option<Option<String>>,
+
+    /// The stdout written by the workflow, to be printed once the view closes.
+    stdout:  String,
+    /// The function that printed the next stdout and when it did, as reported by the driver.
+    printed: Option<(String, DateTime<Utc>)>,
+    /// The sinks to write printed lines to on top of stdout, if any.
+    sinks:   Option<Arc<OutputSinks>>,
+    /// The latest (raw) value returned by the workflow.
+    value:   Option<String>,
+}
+
+impl ProgressView {
+    /// Constructor for the ProgressView.
+    ///
+    /// # Arguments
+    /// - `workflow`: The (compiled) [`Workflow`] that will be followed.
+    ///
+    /// # Returns
+    /// A new ProgressView with all of the workflow's tasks queued.
+    pub fn new(workflow: &Workflow) -> Self {
+        let tasks: Vec<TaskRow> = collect_tasks(workflow);
+        let mut table: TableState = TableState::default();
+        if !tasks.is_empty() {
+            table.select(Some(0));
+        }
+        Self {
+            tasks,
+            table,
+            logs: vec![],
+            scroll: 0,
+
+            started: Instant::now(),
+            planning: false,
+            finished: None,
+
+            stdout: String::new(),
+            printed: None,
+            sinks: None,
+            value: None,
+        }
+    }
+
+    /// Makes this ProgressView write what the workflow prints to the given sinks, on top of stdout.
+    ///
+    /// # Arguments
+    /// - `sinks`: The [`OutputSinks`] to write printed lines to.
+    ///
+    /// # Returns
+    /// The same ProgressView, for chaining.
+    #[inline]
+    pub fn with_sinks(mut self, sinks: Arc<OutputSinks>) -> Self {
+        self.sinks = Some(sinks);
+        self
+    }
+
+    /// Updates the view with a reply sent by the driver.
+    ///
+    /// # Arguments
+    /// - `reply`: The [`ExecuteReply`] to process.
+    ///
+    /// # Returns
+    /// Whether the driver indicated that this was its last reply.
+    pub fn handle_reply(&mut self, reply: ExecuteReply) -> bool {
+        if let Some(event) = reply.event {
+            match serde_json::from_str::<ExecuteEvent>(&event) {
+                Ok(event) => self.handle_event(event),
+                Err(err) => self.log(format!("[brane] Failed to parse progress event '{event}': {err}")),
+            }
+        }
+        if let Some(profile) = reply.profile {
+            self.log(format!("[profile] {profile}"));
+        }
+        if let Some(debug) = reply.debug {
+            self.log(debug);
+        }
+        if let Some(stdout) = reply.stdout {
+            self.log(stdout.trim_end());
+            if let Some(sinks) = &self.sinks {
+                // Older drivers don't say who printed it
+                let (function, timestamp): (String, DateTime<Utc>) = self.printed.take().unwrap_or_else(|| ("<unknown>".into(), Utc::now()));
+                sinks.write(&function, timestamp, &stdout);
+            }
+            self.stdout.push_str(&stdout);
+        }
+        if let Some(stderr) = reply.stderr {
+            self.log(format!("[stderr] {}", stderr.trim_end()));
+        }
+        if let Some(value) = reply.value {
+            self.value = Some(value);
+        }
+        reply.close
+    }
+
+    /// Updates the view with a progress event sent by the driver.
+    ///
+    /// # Arguments
+    /// - `event`: The [`ExecuteEvent`] to process.
+    pub fn handle_event(&mut self, event: ExecuteEvent) {
+        match event {
+            ExecuteEvent::Planning => {
+                self.planning = true;
+                for task in &mut self.tasks {
+                    task.state = TaskState::Planning;
+                }
+            },
+            ExecuteEvent::Planned { locations } => {
+                self.planning = false;
+                for task in &mut self.tasks {
+                    task.state = TaskState::Queued;
+                    if let Some(location) = locations.get(&task.pc) {
+                        task.location = Some(location.clone());
+                    }
+                }
+            },
+            ExecuteEvent::Task { pc, state, message } => {
+                let task: &mut TaskRow = match self.tasks.iter_mut().find(|task| task.pc == pc) {
+                    Some(task) => task,
+                    None => {
+                        self.log(format!("[brane] Progress reported for unknown task call '{pc}'"));
+                        return;
+                    },
+                };
+                match state {
+                    TaskState::Queued | TaskState::Planning => {},
+                    TaskState::Transferring | TaskState::Running => {
+                        task.started.get_or_insert_with(Instant::now);
+                        task.finished = None;
+                    },
+                    TaskState::Done | TaskState::Failed | TaskState::Cancelled => {
+                        task.started.get_or_insert_with(Instant::now);
+                        task.finished = Some(Instant::now());
+                    },
+                }
+                task.state = state;
+
+                let line: String = match message {
+                    Some(message) => format!("[brane] Task '{}' ({}) is {}: {}", task.name, task.pc, state, message),
+                    None => format!("[brane] Task '{}' ({}) is {}", task.name, task.pc, state),
+                };
+                self.log(line);
+            },
+            ExecuteEvent::TaskFailed { pc, name, location, failure } => {
+                if let Some(usage) = &failure.usage {
+                    self.log(format!("[brane] Task '{name}' ({pc}) used {usage} on '{location}'"));
+                }
+                for line in &failure.stderr {
+                    self.log(format!("[{name} stderr] {line}"));
+                }
+            },
+            ExecuteEvent::Printed { function, timestamp, .. } => self.printed = Some((function, timestamp)),
+            ExecuteEvent::TimedOut { deadline, partial } => {
+                for task in &mut self.tasks {
+                    if matches!(task.state, TaskState::Queued | TaskState::Transferring | TaskState::Running) {
+                        task.state = TaskState::Cancelled;
+                        task.finished = task.started.map(|_| Instant::now());
+                    }
+                }
+                self.log(format!("[brane] Workflow exceeded its deadline of {deadline}s; {} task(s) completed before it", partial.len()));
+                let mut partial: Vec<(String, String)> = partial.into_iter().collect();
+                partial.sort();
+                for (pc, value) in partial {
+                    self.log(format!("[brane] Task call {pc} returned {value}"));
+                }
+            },
+        }
+    }
+
+    /// Marks the workflow as completed.
+    ///
+    /// Any task still in progress is considered to have failed if the workflow did.
+    ///
+    /// # Arguments
+    /// - `error`: The reason the workflow failed, if it did.
+    pub fn finish(&mut self, error: Option<String>) {
+        if let Some(error) = &error {
+            for task in &mut self.tasks {
+                if matches!(task.state, TaskState::Transferring | TaskState::Running) {
+                    task.state = TaskState::Failed;
+                    task.finished = Some(Instant::now());
+                }
+            }
+            self.log(format!("[brane] Workflow failed: {error}"));
+        }
+        self.planning = false;
+        self.finished = Some(error);
+    }
+
+    /// Adds a line (or multiple) to the logs.
+    ///
+    /// # Arguments
+    /// - `text`: The text to add.
+    pub fn log(&mut self, text: impl AsRef<str>) {
+        for line in text.as_ref().lines() {
+            self.logs.push(line.into());
+            // Keep the same lines in view if the user scrolled up
+            if self.scroll > 0 {
+                self.scroll += 1;
+            }
+        }
+    }
+
+    /// Handles a key pressed by the user.
+    ///
+    /// # Arguments
+    /// - `code`: The [`KeyCode`] of the key pressed.
+    fn handle_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                let selected: usize = self.table.selected().unwrap_or(0);
+                self.table.select(Some(selected.saturating_sub(1)));
+            },
+            KeyCode::Down | KeyCode::Char('j') => {
+                let selected: usize = self.table.selected().map(|i| i + 1).unwrap_or(0);
+                self.table.select(Some(selected.min(self.tasks.len().saturating_sub(1))));
+            },
+            KeyCode::PageUp => self.scroll = (self.scroll + LOG_PAGE).min(self.logs.len()),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(LOG_PAGE),
+            KeyCode::Home => self.scroll = self.logs.len(),
+            KeyCode::End => self.scroll = 0,
+            _ => {},
+        }
+    }
+
+    /// Renders the view.
+    ///
+    /// # Arguments
+    /// - `frame`: The [`Frame`] to render to.
+    fn draw(&mut self, frame: &mut Frame) {
+        let area: Rect = frame.size();
+        let table_height: u16 = (self.tasks.len() as u16).saturating_add(3).min(area.height / 2).max(4);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(table_height), Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+
+        // The status line
+        let done: usize = self.tasks.iter().filter(|task| task.state == TaskState::Done).count();
+        let elapsed: String = format_elapsed(self.started.elapsed());
+        let status: Span = match &self.finished {
+            Some(None) => Span::styled(format!("Workflow completed ({done}/{} tasks, {elapsed})", self.tasks.len()), state_style(TaskState::Done)),
+            Some(Some(err)) => Span::styled(format!("Workflow failed ({elapsed}): {err}"), state_style(TaskState::Failed)),
+            None if self.planning => Span::styled(format!("Planning workflow... ({elapsed})"), state_style(TaskState::Planning)),
+            None => Span::styled(format!("Running workflow... ({done}/{} tasks done, {elapsed})", self.tasks.len()), state_style(TaskState::Running)),
+        };
+        frame.render_widget(Paragraph::new(Line::from(status)), chunks[0]);
+
+        // The tasks
+        let rows: Vec<Row> = self
+            .tasks
+            .iter()
+            .map(|task| {
+                Row::new(vec![
+                    Span::raw(task.name.clone()),
+                    Span::raw(task.pc.clone()),
+                    Span::raw(task.location.clone().unwrap_or_else(|| "-".into())),
+                    Span::raw(if task.after.is_empty() { "-".into() } else { task.after.join(", ") }),
+                    Span::styled(task.state.to_string(), state_style(task.state)),
+                    Span::raw(task.elapsed().map(format_elapsed).unwrap_or_else(|| "-".into())),
+                ])
+            })
+            .collect();
+        let table: Table = Table::new(rows, [
+            Constraint::Percentage(30),
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Percentage(25),
+            Constraint::Length(13),
+            Constraint::Length(9),
+        ])
+        .header(Row::new(vec!["Task", "Call", "Location", "After", "State", "Elapsed"]).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title(" Tasks "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, chunks[1], &mut self.table);
+
+        // The logs, showing the bottom lines unless scrolled up
+        let height: usize = chunks[2].height.saturating_sub(2) as usize;
+        let end: usize = self.logs.len().saturating_sub(self.scroll);
+        let start: usize = end.saturating_sub(height);
+        let lines: Vec<Line> = self.logs[start..end].iter().map(|line| Line::from(line.as_str())).collect();
+        let title: String = if self.scroll > 0 { format!(" Logs (scrolled up {} lines) ", self.scroll) } else { " Logs ".into() };
+        frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), chunks[2]);
+
+        // The keys
+        let help: &str = if self.finished.is_some() {
+            "↑/↓ select task · PgUp/PgDn/Home/End scroll logs · q close"
+        } else {
+            "↑/↓ select task · PgUp/PgDn/Home/End scroll logs · Ctrl+C stop following"
+        };
+        frame.render_widget(Paragraph::new(Line::from(Span::styled(help, Style::default().fg(Color::DarkGray)))), chunks[3]);
+    }
+
+    /// Follows the workflow until the driver is done with it and the user closed the view.
+    ///
+    /// # Arguments
+    /// - `terminal`: The [`Terminal`] to draw the view on.
+    /// - `stream`: The stream of [`ExecuteReply`]s sent by the driver.
+    ///
+    /// # Returns
+    /// How following the workflow ended.
+    ///
+    /// # Errors
+    /// This function errors if we failed to draw to or read from the terminal.
+    async fn follow(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        mut stream: Streaming<ExecuteReply>,
+    ) -> Result<Outcome, std::io::Error> {
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+        let mut outcome: Option<Outcome> = None;
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Handle any keys pressed in the meantime
+            while event::poll(Duration::ZERO)? {
+                let key = if let Event::Key(key) = event::read()? { key } else { continue };
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(outcome.unwrap_or(Outcome::Interrupted)),
+                    KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => {
+                        if let Some(outcome) = outcome.take() {
+                            return Ok(outcome);
+                        }
+                    },
+                    code => self.handle_key(code),
+                }
+            }
+
+            // Wait for the driver to say something (or for the time to redraw)
+            if outcome.is_some() {
+                ticker.tick().await;
+                continue;
+            }
+            tokio::select! {
+                _ = ticker.tick() => {},
+                message = stream.message() => match message {
+                    Ok(Some(reply)) => {
+                        if self.handle_reply(reply) {
+                            self.finish(None);
+                            outcome = Some(Outcome::Completed(self.value.take()));
+                        }
+                    },
+                    Ok(None) => {
+                        self.finish(None);
+                        outcome = Some(Outcome::Completed(self.value.take()));
+                    },
+                    Err(status) => {
+                        self.finish(Some(status.message().into()));
+                        outcome = Some(Outcome::Failed(status));
+                    },
+                },
+            }
+        }
+    }
+}
+
+
+
+/// Follows a workflow running on a remote instance in an interactive terminal view.
+///
+/// The view shows the workflow's tasks with their state, where they run and how long they take, together with the output sent by the
+/// driver. It stays open after the workflow completed until the user closes it.
+///
+/// # Arguments
+/// - `drv_endpoint`: The `brane-drv` endpoint that runs the workflow (used for debugging only).
+/// - `workflow`: The (compiled) [`Workflow`] that runs.
+/// - `stream`: The stream of [`ExecuteReply`]s sent by the driver for it.
+/// - `stdout`: Some [`Write`]-handle to write the workflow's stdout to once the view is closed.
+/// - `sinks`: If given, the [`OutputSinks`] to write what the workflow prints to as it does so.
+///
+/// # Returns
+/// A [`FullValue`] carrying the result of the workflow (or [`FullValue::Void`]).
+///
+/// # Errors
+/// This function errors if we failed to draw the view, if the workflow failed remotely or if the user closed the view before it completed.
+pub async fn follow(
+    drv_endpoint: &str,
+    workflow: &Workflow,
+    stream: Streaming<ExecuteReply>,
+    mut stdout: impl Write,
+    sinks: Option<Arc<OutputSinks>>,
+) -> Result<FullValue, Error> {
+    let mut view: ProgressView = ProgressView::new(workflow);
+    if let Some(sinks) = sinks {
+        view = view.with_sinks(sinks);
+    }
+    let outcome: Result<Outcome, std::io::Error> = {
+        let _guard: TerminalGuard = match TerminalGuard::enter() {
+            Ok(guard) => guard,
+            Err(err) => return Err(Error::ProgressViewError { err }),
+        };
+        match Terminal::new(CrosstermBackend::new(std::io::stdout())) {
+            Ok(mut terminal) => view.follow(&mut terminal, stream).await,
+            Err(err) => Err(err),
+        }
+    };
+
+    // Now that the terminal is back to normal, write what the workflow wrote
+    if let Err(err) = write!(&mut stdout, "{}", view.stdout) {
+        return Err(Error::WriteError { err });
+    }
+    match outcome {
+        Ok(Outcome::Completed(Some(raw))) => match serde_json::from_str(&raw) {
+            Ok(value) => Ok(value),
+            Err(err) => Err(Error::ValueParseError { address: drv_endpoint.into(), raw, err }),
+        },
+        Ok(Outcome::Completed(None)) => Ok(FullValue::Void),
+        Ok(Outcome::Failed(status)) => Err(status_error(status)),
+        Ok(Outcome::Interrupted) => Err(Error::ProgressViewInterrupted),
+        Err(err) => Err(Error::ProgressViewError { err }),
+    }
+}
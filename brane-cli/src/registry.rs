@@ -5,6 +5,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use anyhow::Result;
+use brane_shr::oci;
 use brane_tsk::local::get_package_versions;
 use chrono::{DateTime, Utc};
 use console::{pad_str, style, Alignment};
@@ -16,7 +17,8 @@ use indicatif::{ProgressBar, ProgressStyle};
 use prettytable::format::FormatBuilder;
 use prettytable::Table;
 use reqwest::{self, Body, Client};
-use specifications::package::{PackageInfo, PackageKind};
+use specifications::package::{PackageInfo, PackageKind, PackageResources, ScanReport};
+use specifications::registering::FederatedPackage;
 use specifications::version::Version;
 use tokio::fs::File as TokioFile;
 use tokio_util::codec::{BytesCodec, FramedRead};
@@ -221,17 +223,44 @@ pub async fn pull(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
                 None => HashMap::new(),
             };
 
+            // Don't forget the minimum resources, if any
+            let resources: Option<PackageResources> = match package.resources_as_json.as_ref() {
+                Some(resources) => match serde_json::from_str(resources) {
+                    Ok(resources) => Some(resources),
+                    Err(err) => {
+                        return Err(RegistryError::ResourcesParseError { url, raw: resources.clone(), err });
+                    },
+                },
+                None => None,
+            };
+            let scan: Option<ScanReport> = match package.scan_as_json.as_ref() {
+                Some(scan) => match serde_json::from_str(scan) {
+                    Ok(scan) => Some(scan),
+                    Err(err) => {
+                        return Err(RegistryError::ScanParseError { url, raw: scan.clone(), err });
+                    },
+                },
+                None => None,
+            };
+
             // Finally, combine everything in a fully-fledged PackageInfo
             let package_info = PackageInfo {
                 created: package.created,
                 description: package.description.clone().unwrap_or_default(),
                 detached: package.detached,
                 digest: package.digest.clone(),
+                oci: package.oci.clone(),
                 functions,
                 id: package.id,
                 kind,
                 name: package.name.clone(),
                 owners: package.owners.clone(),
+                license: package.license.clone(),
+                maintainers: package.maintainers.clone(),
+                homepage: package.homepage.clone(),
+                resources,
+                tags: package.tags.clone(),
+                scan,
                 types,
                 version,
             };
@@ -309,8 +338,8 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
                 },
             };
 
-            // Sort the versions and return the last one
-            versions.sort();
+            // Sort the versions (such that releases come after pre-releases) and return the last one
+            versions.sort_by(Version::cmp_release);
             versions[versions.len() - 1]
         } else {
             // Simply use the version given
@@ -406,6 +435,160 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
 }
 /*******/
 
+/// Pushes the given packages to an OCI registry, and registers them with the active instance (if any).
+///
+/// Every package is pushed as `<REPOSITORY>/<NAME>:<VERSION>`, with its package info attached to the image as an annotation. The instance then
+/// reads the package info from the registry, and its workers pull the image from there directly.
+///
+/// # Arguments
+/// - `packages`: A list with name/ID / version pairs of the packages to push.
+/// - `repository`: The repository to push to, e.g., `harbor.example.com/brane`. Credentials are read from the Docker config (see `docker login`).
+///
+/// # Errors
+/// This function errors if we failed to read a package, push it to the registry or register it with the instance.
+pub async fn push_oci(packages: Vec<(String, Version)>, repository: String) -> Result<(), RegistryError> {
+    let packages_dir = match ensure_packages_dir(false) {
+        Ok(dir) => dir,
+        Err(err) => {
+            return Err(RegistryError::PackagesDirError { err });
+        },
+    };
+    let creds: oci::Credentials = match oci::Credentials::from_docker_config() {
+        Ok(creds) => creds,
+        Err(err) => {
+            return Err(RegistryError::OciCredentialsError { err });
+        },
+    };
+    let client: oci::Client = oci::Client::new(creds);
+    let api: Option<String> = match InstanceInfo::active_instance_exists() {
+        Ok(true) => Some(InstanceInfo::from_active_path().map_err(|err| RegistryError::InstanceInfoError { err })?.api.to_string()),
+        Ok(false) => None,
+        Err(err) => {
+            return Err(RegistryError::InstanceInfoError { err });
+        },
+    };
+
+    for (name, version) in packages {
+        // Resolve the version number
+        let version = if version.is_latest() {
+            let mut versions = match get_package_versions(&name, &packages_dir.join(&name)) {
+                Ok(versions) => versions,
+                Err(err) => {
+                    return Err(RegistryError::VersionsError { name, err });
+                },
+            };
+            versions.sort_by(Version::cmp_release);
+            versions[versions.len() - 1]
+        } else {
+            version
+        };
+        let package_dir = match ensure_package_dir(&name, Some(&version), false) {
+            Ok(dir) => dir,
+            Err(err) => {
+                return Err(RegistryError::PackageDirError { name, version, err });
+            },
+        };
+        let info: PackageInfo = match PackageInfo::from_path(package_dir.join("package.yml")) {
+            Ok(info) => info,
+            Err(err) => {
+                return Err(RegistryError::PackageInfoReadError { name, version, err });
+            },
+        };
+
+        // Push the image with the package info attached
+        let raw: String = format!("{}/{}:{}", repository.trim_end_matches('/'), name, version);
+        let reference: oci::Reference = match oci::Reference::from_str(&raw) {
+            Ok(reference) => reference,
+            Err(err) => {
+                return Err(RegistryError::OciReferenceError { raw, err });
+            },
+        };
+        let progress = ProgressBar::new(0);
+        progress.set_style(ProgressStyle::default_bar().template("Pushing...     [{elapsed_precise}]").unwrap());
+        progress.enable_steady_tick(Duration::from_millis(250));
+        let pinned: oci::Reference = match client.push_archive(package_dir.join("image.tar"), &info, &reference).await {
+            Ok(pinned) => pinned,
+            Err(err) => {
+                return Err(RegistryError::OciPushError { name, version, reference: raw, err });
+            },
+        };
+        progress.finish();
+        println!(
+            "\nSuccessfully pushed version {} of package {} to {}.",
+            style(&version).bold().cyan(),
+            style(&name).bold().cyan(),
+            style(&pinned).bold()
+        );
+
+        // Let the instance know where to find it
+        if let Some(api) = &api {
+            let endpoint: String = format!("{api}/packages/oci");
+            debug!("Registering '{}' with '{}'...", pinned, endpoint);
+            let res = match Client::new().post(&endpoint).json(&HashMap::from([("reference", pinned.to_string())])).send().await {
+                Ok(res) => res,
+                Err(err) => {
+                    return Err(RegistryError::OciRegisterError { endpoint, err });
+                },
+            };
+            if !res.status().is_success() {
+                return Err(RegistryError::OciRegisterFailure { endpoint, status: res.status(), message: res.text().await.ok() });
+            }
+            println!("Registered version {} of package {} with the active instance.", style(&version).bold().cyan(), style(&name).bold().cyan());
+        }
+    }
+    if api.is_none() {
+        println!("No instance is active, so the packages have not been registered with one.");
+    }
+
+    // Done!
+    Ok(())
+}
+
+/// Searches the packages of the peer instances that the active instance federates with.
+///
+/// # Arguments
+/// - `term`: If given, only shows packages whose name contains this term.
+///
+/// # Errors
+/// This function errors if we failed to reach the instance, or if it does not federate with any peers.
+pub async fn search_federated(term: Option<String>) -> Result<()> {
+    let api: String = InstanceInfo::from_active_path().map_err(|err| RegistryError::InstanceInfoError { err })?.api.to_string();
+    let url: String = format!("{api}/federation/packages");
+
+    // Send the request
+    let client = reqwest::Client::new();
+    let mut request = client.get(&url);
+    if let Some(term) = term {
+        request = request.query(&[("term", term)]);
+    }
+    let response = request.send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow::anyhow!("Instance at '{api}' does not federate with any peers"));
+    }
+    let packages: Vec<FederatedPackage> = response.error_for_status()?.json().await?;
+
+    // Present results in a table.
+    let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["INSTANCE", "NAME", "VERSION", "KIND", "DESCRIPTION"]);
+
+    for package in packages {
+        let instance = pad_str(&package.instance, 15, Alignment::Left, Some(".."));
+        let name = pad_str(&package.name, 20, Alignment::Left, Some(".."));
+        let version = pad_str(&package.version, 10, Alignment::Left, Some(".."));
+        let kind = pad_str(&package.kind, 10, Alignment::Left, Some(".."));
+        let description = package.description.clone().unwrap_or_default();
+        let description = pad_str(&description, 50, Alignment::Left, Some(".."));
+
+        table.add_row(row![instance, name, version, kind, description]);
+    }
+
+    table.printstd();
+    Ok(())
+}
+
 pub async fn search(term: Option<String>) -> Result<()> {
     #[derive(GraphQLQuery)]
     #[graphql(schema_path = "src/graphql/api_schema.json", query_path = "src/graphql/search_packages.graphql", response_derives = "Debug")]
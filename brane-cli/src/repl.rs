@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 16:42:47
 //  Last edited:
-//    08 Jan 2024, 10:23:14
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -13,23 +13,37 @@
 //
 
 use std::borrow::Cow::{self, Borrowed, Owned};
+use std::collections::HashMap;
 use std::fs;
 use std::io::{Stderr, Stdout};
+use std::path::{Path, PathBuf};
+use std::str::FromStr as _;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use brane_ast::ParserOptions;
+use brane_ast::locations::Location;
+use brane_ast::state::CompileState;
+use brane_ast::{ParserOptions, SymTable};
 use brane_dsl::Language;
 use brane_exe::FullValue;
 use brane_tsk::docker::DockerOptions;
 use brane_tsk::spec::AppId;
-use log::warn;
+use console::{style, Term};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Select;
+use indicatif::HumanDuration;
+use log::{debug, warn};
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
 use rustyline::completion::{Completer, FilenameCompleter, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::{Highlighter, MatchingBracketHighlighter};
 use rustyline::hint::{Hinter, HistoryHinter};
 use rustyline::history::DefaultHistory;
 use rustyline::validate::{self, MatchingBracketValidator, Validator};
-use rustyline::{CompletionType, Config, Context, EditMode, Editor};
+use rustyline::{Cmd, CompletionType, Config, Context, EditMode, Editor, KeyEvent};
 use rustyline_derive::Helper;
+use serde::{Deserialize, Serialize};
+use specifications::driving::{DriverServiceClient, ListSessionsRequest, SessionInfo};
 
 pub use crate::errors::ReplError as Error;
 use crate::instance::InstanceInfo;
@@ -37,23 +51,55 @@ use crate::run::{
     initialize_instance_vm, initialize_offline_vm, process_instance_result, process_offline_result, run_instance_vm, run_offline_vm, InstanceVmState,
     OfflineVmState,
 };
-use crate::utils::{ensure_config_dir, get_history_file};
+use crate::utils::{ensure_config_dir, get_history_file, get_instance_history_file};
+use crate::vm::OfflineVm;
 
 
 /***** HELPER FUNCTIONS *****/
+/// Prints (part of) the REPL history for the `:history` magick.
+///
+/// # Arguments
+/// - `history`: The history to print.
+/// - `query`: If not empty, only entries containing this (case-insensitive) are printed.
+fn print_history(history: &DefaultHistory, query: &str) {
+    let query: String = query.to_lowercase();
+    let entries: Vec<(usize, &String)> =
+        history.iter().enumerate().filter(|(_, entry)| query.is_empty() || entry.to_lowercase().contains(&query)).collect();
+    if entries.is_empty() {
+        if query.is_empty() {
+            println!("The history is empty.");
+        } else {
+            println!("No history entries contain '{query}'.");
+        }
+        println!();
+        return;
+    }
+
+    // Number the entries like a shell would, aligned on the largest index
+    let width: usize = history.iter().count().to_string().len();
+    for (i, entry) in entries {
+        println!("  {:>width$}  {}", i + 1, entry);
+    }
+    println!();
+}
+
 /// Handles magicks in the REPL.
 ///
 /// # Arguments
 /// - `line`: The line given by the user.
+/// - `history`: The history of the REPL, for the magicks that show it.
 ///
 /// # Returns
 /// If a magics was triggered, returns if that trigger should break the REPL (i.e., returns `Some(true)` if so or `Some(false)` if the REPL can continue but not with this line). If the line was not a REPL magick, then `None` is returned.
-fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
+fn repl_magicks(line: impl AsRef<str>, history: &DefaultHistory) -> Option<bool> {
     let line: &str = line.as_ref();
 
     // Switch on the command given
     if line == "exit" || line == "quit" || line == "q" {
         Some(true)
+    } else if line == ":history" || line.starts_with(":history ") {
+        print_history(history, line[":history".len()..].trim());
+        Some(false)
     } else if line == "help" {
         println!("You found the secret REPL-commands!");
         println!(
@@ -64,6 +110,9 @@ fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
         println!("Supported commands:");
         println!("  `exit`, `quit` or `q`   Exits the REPL. The same can be achieved by hitting `Ctrl+C` or `Ctrl+D`.");
         println!("  `help`                  Prints this overview.");
+        println!("  `:history [QUERY]`      Lists the REPL history (of this instance, if remote), or only the entries containing QUERY.");
+        println!();
+        println!("Press `Ctrl+R` to search backwards through the history as you type, and `Ctrl+S` to search forwards again.");
         println!();
         println!("Any other statement that is not one of the commands above is interpreted as the language you're REPLing.");
         println!();
@@ -73,6 +122,292 @@ fn repl_magicks(line: impl AsRef<str>) -> Option<bool> {
     }
 }
 
+/// Formats a Unix timestamp as the time elapsed since then.
+///
+/// # Arguments
+/// - `timestamp`: The timestamp, as seconds since the Unix epoch.
+///
+/// # Returns
+/// A string like `3 hours ago`.
+fn format_ago(timestamp: u64) -> String {
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{} ago", HumanDuration(Duration::from_secs(now.saturating_sub(timestamp))))
+}
+
+/// Fetches the active sessions of the current user from the remote driver.
+///
+/// # Arguments
+/// - `info`: An [`InstanceInfo`] that describes how to connect to the driver and as which user.
+///
+/// # Returns
+/// The user's active sessions, most recently used first.
+///
+/// # Errors
+/// This function errors if we failed to connect to the driver or if it failed to list the sessions.
+async fn fetch_sessions(info: &InstanceInfo) -> Result<Vec<SessionInfo>, Error> {
+    let drv_address: String = info.drv.to_string();
+
+    // Connect to the driver
+    debug!("Connecting to driver '{}'...", drv_address);
+    let mut client: DriverServiceClient = match DriverServiceClient::connect(drv_address.clone()).await {
+        Ok(client) => client.with_token(info.token.clone()),
+        Err(err) => return Err(Error::DriverConnectError { address: drv_address, err }),
+    };
+
+    // Ask it for the sessions of this user
+    debug!("Listing sessions of user '{}'...", info.user);
+    match client.list_sessions(ListSessionsRequest { user: Some(info.user.clone()) }).await {
+        Ok(reply) => Ok(reply.into_inner().sessions),
+        Err(err) => Err(Error::SessionListError { address: drv_address, err }),
+    }
+}
+
+/// Asks the user to pick one of their active sessions to attach to.
+///
+/// # Arguments
+/// - `info`: An [`InstanceInfo`] that describes how to connect to the driver and as which user.
+///
+/// # Returns
+/// The ID of the picked session, or [`None`] if the user has no sessions or chose to start a new one.
+///
+/// # Errors
+/// This function errors if we failed to fetch the sessions, failed to ask the user or if the driver sent an invalid session ID.
+async fn pick_session(info: &InstanceInfo) -> Result<Option<AppId>, Error> {
+    let sessions: Vec<SessionInfo> = fetch_sessions(info).await?;
+    if sessions.is_empty() {
+        println!("You have no active sessions on this instance; starting a new one.\n");
+        return Ok(None);
+    }
+
+    // Prepare the prompt with one item per session, and one to start a new session
+    let mut items: Vec<String> =
+        sessions.iter().map(|s| format!("{} (created {}, last used {})", s.uuid, format_ago(s.created), format_ago(s.last_activity))).collect();
+    items.push("Start a new session".into());
+    let colorful = ColorfulTheme::default();
+    let mut prompt = Select::with_theme(&colorful);
+    prompt.items(&items).with_prompt("Select session to attach to").default(0usize);
+
+    // Ask the user
+    let choice: Option<usize> = match prompt.interact_on_opt(&Term::stderr()) {
+        Ok(choice) => choice,
+        Err(err) => return Err(Error::SessionSelectError { err }),
+    };
+    match choice.and_then(|i| sessions.get(i)) {
+        Some(session) => match AppId::from_str(&session.uuid) {
+            Ok(app_id) => Ok(Some(app_id)),
+            Err(err) => Err(Error::SessionIdError { raw: session.uuid.clone(), err }),
+        },
+        None => Ok(None),
+    }
+}
+
+
+
+
+
+/***** SNAPSHOTS *****/
+/// Defines what `brane repl --persist` remembers of a REPL session between restarts.
+#[derive(Deserialize, Serialize)]
+struct Snapshot {
+    /// The language in which the snippets were written.
+    language: String,
+    /// The number of the next snippet, so the prompt continues where it left off.
+    count:    u32,
+    /// The state of the compiler after the last snippet.
+    state:    CompileState,
+    /// The source of all snippets compiled so far, which we use for debugging.
+    source:   String,
+    /// The part of the snapshot that depends on where the snippets run.
+    #[serde(flatten)]
+    vm:       VmSnapshot,
+}
+
+impl Snapshot {
+    /// Describes the kind of REPL this snapshot was made for.
+    ///
+    /// # Returns
+    /// A string like `a local BraneScript REPL`.
+    fn describe(&self) -> String {
+        match &self.vm {
+            VmSnapshot::Local { .. } => describe_repl(&self.language, None),
+            VmSnapshot::Remote { instance, .. } => describe_repl(&self.language, Some(instance)),
+        }
+    }
+}
+
+/// Defines the part of a [`Snapshot`] that depends on where the snippets run.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum VmSnapshot {
+    /// The snippets ran on the local machine, so we keep the values ourselves.
+    Local {
+        /// The directory with the intermediate results produced so far. It is kept alive after the REPL exits.
+        results_dir: PathBuf,
+        /// The intermediate results produced so far, mapped to the location where they live.
+        results:     HashMap<String, Location>,
+        /// The values of the global variables, mapped by their definition index.
+        variables:   HashMap<usize, FullValue>,
+    },
+    /// The snippets ran on a remote instance, whose driver session keeps the values for us.
+    Remote {
+        /// The name of the instance.
+        instance: String,
+        /// The ID of the session on that instance.
+        session:  String,
+    },
+}
+
+
+
+/// Describes a kind of REPL for when a snapshot does not match it.
+///
+/// # Arguments
+/// - `language`: The language of the REPL.
+/// - `instance`: The name of the instance the REPL runs on, or [`None`] if it runs locally.
+///
+/// # Returns
+/// A string like `a remote BraneScript REPL on instance 'foo'`.
+fn describe_repl(language: &str, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("a remote {language} REPL on instance '{instance}'"),
+        None => format!("a local {language} REPL"),
+    }
+}
+
+/// Loads the snapshot of a previous REPL session.
+///
+/// # Arguments
+/// - `path`: The path of the snapshot file.
+///
+/// # Returns
+/// The [`Snapshot`], or [`None`] if there is no file at `path` yet.
+///
+/// # Errors
+/// This function errors if the file exists but could not be read or parsed.
+fn load_snapshot(path: &Path) -> Result<Option<Snapshot>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw: String = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::SnapshotReadError { path: path.into(), err }),
+    };
+    match serde_json::from_str(&raw) {
+        Ok(snapshot) => Ok(Some(snapshot)),
+        Err(err) => Err(Error::SnapshotParseError { path: path.into(), err }),
+    }
+}
+
+/// Saves the snapshot of the current REPL session.
+///
+/// The snapshot is first written next to `path` and then moved over it, so a crash halfway through never leaves a broken snapshot behind. Failures are only logged, since they should not interrupt the session.
+///
+/// # Arguments
+/// - `path`: The path of the snapshot file.
+/// - `snapshot`: The [`Snapshot`] to write.
+fn save_snapshot(path: &Path, snapshot: &Snapshot) {
+    let raw: String = match serde_json::to_string(snapshot) {
+        Ok(raw) => raw,
+        Err(err) => {
+            warn!("Failed to serialize REPL snapshot: {}", err);
+            return;
+        },
+    };
+    let part: PathBuf = PathBuf::from(format!("{}.part", path.display()));
+    if let Err(err) = fs::write(&part, raw) {
+        warn!("Failed to write REPL snapshot '{}': {}", part.display(), err);
+        return;
+    }
+    if let Err(err) = fs::rename(&part, path) {
+        warn!("Failed to move REPL snapshot '{}' to '{}': {}", part.display(), path.display(), err);
+    }
+}
+
+/// Collects the datasets and intermediate results referenced by a value.
+///
+/// # Arguments
+/// - `value`: The [`FullValue`] to search.
+/// - `datasets`: The list to which the names of referenced datasets are added.
+/// - `results`: The list to which the names of referenced intermediate results are added.
+fn collect_references<'v>(value: &'v FullValue, datasets: &mut Vec<&'v str>, results: &mut Vec<&'v str>) {
+    match value {
+        FullValue::Array(values) => values.iter().for_each(|v| collect_references(v, datasets, results)),
+        FullValue::Instance(_, fields) => fields.values().for_each(|v| collect_references(v, datasets, results)),
+        FullValue::Data(name) => datasets.push(name.as_ref()),
+        FullValue::IntermediateResult(name) => results.push(name.as_ref()),
+        _ => {},
+    }
+}
+
+/// Restores the variables and intermediate results of a local REPL snapshot into a freshly initialized offline VM.
+///
+/// Results are moved from the results directory of the previous session to that of the current one. Variables that refer to datasets or results that no longer exist are dropped.
+///
+/// # Arguments
+/// - `path`: The path of the snapshot file, for debugging.
+/// - `state`: The [`OfflineVmState`] to restore into. Its compile state must already be restored.
+/// - `results_dir`: The results directory of the previous session.
+/// - `results`: The intermediate results of the previous session, mapped to the location where they live.
+/// - `variables`: The values of the global variables of the previous session, mapped by their definition index.
+///
+/// # Errors
+/// This function errors if the VM refused to restore the variables.
+fn restore_local(
+    path: &Path,
+    state: &mut OfflineVmState,
+    results_dir: &Path,
+    results: HashMap<String, Location>,
+    variables: HashMap<usize, FullValue>,
+) -> Result<(), Error> {
+    // Move the results that still exist over to this session
+    let new_dir: &Path = state.results_dir.path();
+    let mut restored: HashMap<String, Location> = HashMap::with_capacity(results.len());
+    for (name, loc) in results {
+        if let Err(err) = fs::rename(results_dir.join(&name), new_dir.join(&name)) {
+            warn!("Could not restore intermediate result '{}' from '{}': {}", name, results_dir.display(), err);
+            continue;
+        }
+        restored.insert(name, loc);
+    }
+    if results_dir.exists() {
+        if let Err(err) = fs::remove_dir_all(results_dir) {
+            warn!("Failed to remove previous results directory '{}': {}", results_dir.display(), err);
+        }
+    }
+
+    // Only keep the variables of which everything they refer to is still there
+    let n_vars: usize = state.state.table.vars.len();
+    let variables: HashMap<usize, FullValue> = variables
+        .into_iter()
+        .filter(|(def, value)| {
+            if *def >= n_vars {
+                warn!("Ignoring unknown variable {} in REPL snapshot '{}'", def, path.display());
+                return false;
+            }
+            let name: &str = &state.state.table.var(*def).name;
+            let (mut datasets, mut results): (Vec<&str>, Vec<&str>) = (vec![], vec![]);
+            collect_references(value, &mut datasets, &mut results);
+            if let Some(data) = datasets.into_iter().find(|d| state.dindex.get(d).is_none()) {
+                println!("Not restoring variable '{name}', since dataset '{data}' no longer exists.");
+                return false;
+            }
+            if let Some(result) = results.into_iter().find(|r| !restored.contains_key(*r)) {
+                println!("Not restoring variable '{name}', since intermediate result '{result}' no longer exists.");
+                return false;
+            }
+            true
+        })
+        .collect();
+
+    // Hand them to the VM
+    let n_restored: usize = variables.len();
+    let vm: &mut OfflineVm = state.vm.as_mut().expect("Offline VM not initialized");
+    if let Err(err) = vm.restore(SymTable::from(&state.state.table), variables, restored) {
+        return Err(Error::SnapshotRestoreError { path: path.into(), err });
+    }
+    println!("Restored {} variable(s) from '{}'.\n", n_restored, path.display());
+    Ok(())
+}
 
 
 
@@ -137,26 +472,41 @@ impl Validator for ReplHelper {
 /// # Arguments
 /// - `proxy_addr`: The address to proxy any data transfers through if they occur.
 /// - `remote`: Whether to use the remote Brane instance in the login file to run the on instead.
-/// - `attach`: If not None, defines the session ID of an existing session to connect to.
+/// - `attach`: If not None, defines the session ID of an existing session to connect to. If that ID is None, asks the user to pick one of their active sessions instead.
+/// - `list_sessions`: If true, lists the user's active sessions on the remote Brane instance instead of starting a REPL.
 /// - `language`: The language with which to compile the file.
-/// - `clear`: Whether or not to clear the history of the REPL before beginning.
+/// - `clear`: Whether or not to clear the history of the REPL before beginning. For remote REPLs, only clears the history of the active instance.
 /// - `profile`: If given, prints the profile timings to stdout if available.
 /// - `docker_opts`: The DockerOpts that determines how we connect to the local Docker dameon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `persist`: If given, restores the session from the snapshot at this path (if any) and keeps it up-to-date after every snippet.
 ///
 /// # Errors
-/// This function errors if we could not properly read from/write to the terminal. Additionally, it may error if any of the given statements fails for whatever reason.
+/// This function errors if we could not properly read from/write to the terminal, or if the snapshot could not be restored. Additionally, it may error if any of the given statements fails for whatever reason.
 #[allow(clippy::too_many_arguments)]
 pub async fn start(
     proxy_addr: Option<String>,
     remote: bool,
-    attach: Option<AppId>,
+    attach: Option<Option<AppId>>,
+    list_sessions: bool,
     language: Language,
     clear: bool,
     profile: bool,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    persist: Option<PathBuf>,
 ) -> Result<(), Error> {
+    // If we're only listing sessions, do so and quit
+    if list_sessions {
+        let info: InstanceInfo = match InstanceInfo::from_active_path() {
+            Ok(info) => info,
+            Err(err) => {
+                return Err(Error::InstanceInfoError { err });
+            },
+        };
+        return show_sessions(&info).await;
+    }
+
     // Build the config for the rustyline REPL.
     let config = Config::builder().history_ignore_space(true).completion_type(CompletionType::Circular).edit_mode(EditMode::Emacs).build();
 
@@ -169,11 +519,29 @@ pub async fn start(
         validator: MatchingBracketValidator::new(),
     };
 
-    // Get the history file, clearing it if necessary
+    // Open the login file to find the remote location, if any
+    let instance: Option<(String, InstanceInfo)> = if remote {
+        let name: String = match InstanceInfo::get_active_name() {
+            Ok(name) => name,
+            Err(err) => {
+                return Err(Error::InstanceInfoError { err });
+            },
+        };
+        match InstanceInfo::from_default_path(&name) {
+            Ok(info) => Some((name, info)),
+            Err(err) => {
+                return Err(Error::InstanceInfoError { err });
+            },
+        }
+    } else {
+        None
+    };
+
+    // Get the history file (which is per-instance for remote REPLs), clearing it if necessary
     if let Err(err) = ensure_config_dir(true) {
         return Err(Error::ConfigDirCreateError { err });
     };
-    let history_file = match get_history_file() {
+    let history_file = match instance.as_ref().map(|(name, _)| get_instance_history_file(name)).unwrap_or_else(get_history_file) {
         Ok(file) => file,
         Err(err) => {
             return Err(Error::HistoryFileError { err });
@@ -193,6 +561,8 @@ pub async fn start(
         },
     };
     rl.set_helper(Some(repl_helper));
+    rl.bind_sequence(KeyEvent::ctrl('R'), Cmd::ReverseSearchHistory);
+    rl.bind_sequence(KeyEvent::ctrl('S'), Cmd::ForwardSearchHistory);
     if let Err(err) = rl.load_history(&history_file) {
         warn!("Could not load REPL history from '{}': {}", history_file.display(), err);
     }
@@ -200,21 +570,46 @@ pub async fn start(
     // Prepare the parser options
     let options: ParserOptions = ParserOptions::new(language);
 
+    // Load the snapshot of a previous session, if any, and make sure it was made for this kind of REPL
+    let mut snapshot: Option<Snapshot> = match &persist {
+        Some(path) => load_snapshot(path)?,
+        None => None,
+    };
+    if let (Some(path), Some(snapshot)) = (&persist, &snapshot) {
+        let expected: String = describe_repl(&language.to_string(), instance.as_ref().map(|(name, _)| name.as_str()));
+        let got: String = snapshot.describe();
+        if got != expected {
+            return Err(Error::SnapshotMismatch { path: path.clone(), expected, got });
+        }
+    }
+
     // Initialization done; run the REPL
     println!("Welcome to the Brane REPL, press Ctrl+D to exit.\n");
-    if remote {
-        // Open the login file to find the remote location
-        let info: InstanceInfo = match InstanceInfo::from_active_path() {
-            Ok(info) => info,
-            Err(err) => {
-                return Err(Error::InstanceInfoError { err });
-            },
+    if let Some((name, info)) = instance {
+        // Resolve which session to attach to, if any
+        let mut attach: Option<AppId> = match attach {
+            Some(Some(app_id)) => Some(app_id),
+            Some(None) => pick_session(&info).await?,
+            None => None,
         };
 
+        // A snapshot only makes sense if its session still lives on the driver
+        if let Some(Snapshot { vm: VmSnapshot::Remote { session, .. }, .. }) = &snapshot {
+            if fetch_sessions(&info).await?.iter().any(|s| &s.uuid == session) {
+                attach = match AppId::from_str(session) {
+                    Ok(app_id) => Some(app_id),
+                    Err(err) => return Err(Error::SessionIdError { raw: session.clone(), err }),
+                };
+            } else {
+                println!("Session '{session}' from the snapshot no longer exists on this instance; starting a new one.\n");
+                snapshot = None;
+            }
+        }
+
         // Run the thing
-        remote_repl(&mut rl, info, proxy_addr, attach, options, profile).await?;
+        remote_repl(&mut rl, name, info, proxy_addr, attach, options, profile, persist.as_deref(), snapshot).await?;
     } else {
-        local_repl(&mut rl, options, docker_opts, keep_containers).await?;
+        local_repl(&mut rl, options, docker_opts, keep_containers, persist.as_deref(), snapshot).await?;
     }
 
     // Try to save the history if we exited cleanly
@@ -228,6 +623,36 @@ pub async fn start(
 
 
 
+/// Prints the user's active sessions on the remote instance, so they can attach to one of them.
+///
+/// # Arguments
+/// - `info`: An [`InstanceInfo`] that describes how to connect to the driver and as which user.
+///
+/// # Errors
+/// This function errors if we failed to fetch the sessions.
+async fn show_sessions(info: &InstanceInfo) -> Result<(), Error> {
+    let sessions: Vec<SessionInfo> = fetch_sessions(info).await?;
+    if sessions.is_empty() {
+        println!("You have no active sessions on this instance.");
+        return Ok(());
+    }
+
+    // Prepare display table.
+    let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["ID", "CREATED", "LAST ACTIVITY"]);
+    for session in &sessions {
+        table.add_row(row![session.uuid, format_ago(session.created), format_ago(session.last_activity)]);
+    }
+
+    // Write to stdout and done!
+    table.printstd();
+    println!();
+    println!("Run {} to attach to one of them.", style("brane repl --remote --attach <ID>").bold().cyan());
+    Ok(())
+}
+
 /// Runs the given file on the remote instance.
 ///
 /// # Arguments
@@ -237,31 +662,45 @@ pub async fn start(
 /// - `attach`: If given, uses the given ID to attach to an existing session instead of creating a new one.
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+/// - `persist`: If given, writes a snapshot of the session to this path after every snippet.
+/// - `snapshot`: If given, restores the compile state of a previous session. `attach` must then point to that session.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
+#[allow(clippy::too_many_arguments)]
 async fn remote_repl(
     rl: &mut Editor<ReplHelper, DefaultHistory>,
+    name: String,
     info: InstanceInfo,
     proxy_addr: Option<String>,
     attach: Option<AppId>,
     options: ParserOptions,
     profile: bool,
+    persist: Option<&Path>,
+    snapshot: Option<Snapshot>,
 ) -> Result<(), Error> {
     let api_address: String = info.api.to_string();
     let drv_address: String = info.drv.to_string();
 
     // First we initialize the remote thing
     let mut state: InstanceVmState<Stdout, Stderr> =
-        match initialize_instance_vm(&api_address, &drv_address, Some(info.user.clone()), attach, options).await {
+        match initialize_instance_vm(&api_address, &drv_address, Some(info.user.clone()), info.token.clone(), attach, options).await {
             Ok(state) => state,
             Err(err) => {
                 return Err(Error::InitializeError { what: "remote instance client", err });
             },
         };
 
-    // Next, enter the L in REPL
+    // Continue where the snapshot left off, if any
     let mut count: u32 = 1;
+    if let (Some(path), Some(snapshot)) = (persist, snapshot) {
+        state.state = snapshot.state;
+        state.source = snapshot.source;
+        count = snapshot.count;
+        println!("Restored session '{}' from '{}'.\n", state.session, path.display());
+    }
+
+    // Next, enter the L in REPL
     loop {
         // Prepare the prompt with the current iteration number
         let p = format!("{count}> ");
@@ -278,7 +717,7 @@ async fn remote_repl(
                 }
 
                 // Fetch REPL magicks
-                if let Some(quit) = repl_magicks(&line) {
+                if let Some(quit) = repl_magicks(&line, rl.history()) {
                     if quit {
                         break;
                     } else {
@@ -303,6 +742,15 @@ async fn remote_repl(
                 // Go to the next iteration
                 count += 1;
                 state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
+                if let Some(path) = persist {
+                    save_snapshot(path, &Snapshot {
+                        language: state.options.lang.to_string(),
+                        count,
+                        state: state.state.clone(),
+                        source: state.source.clone(),
+                        vm: VmSnapshot::Remote { instance: name.clone(), session: state.session.to_string() },
+                    });
+                }
             },
             Err(ReadlineError::Interrupted) => {
                 println!("Keyboard interrupt received, exiting...");
@@ -331,6 +779,8 @@ async fn remote_repl(
 /// - `parse_opts`: The ParseOptions that specify how to parse the incoming source.
 /// - `docker_opts`: The DockerOpts that determines how we connect to the local Docker dameon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `persist`: If given, writes a snapshot of the session to this path after every snippet and keeps the intermediate results around on exit.
+/// - `snapshot`: If given, restores the compile state, variables and intermediate results of a previous session.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
@@ -339,6 +789,8 @@ async fn local_repl(
     parse_opts: ParserOptions,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    persist: Option<&Path>,
+    snapshot: Option<Snapshot>,
 ) -> Result<(), Error> {
     // First we initialize the remote thing
     let mut state: OfflineVmState = match initialize_offline_vm(parse_opts, docker_opts, keep_containers) {
@@ -348,8 +800,18 @@ async fn local_repl(
         },
     };
 
-    // With the VM setup, enter the L in the REPL
+    // Continue where the snapshot left off, if any
     let mut count: u32 = 1;
+    if let (Some(path), Some(snapshot)) = (persist, snapshot) {
+        if let VmSnapshot::Local { results_dir, results, variables } = snapshot.vm {
+            state.state = snapshot.state;
+            state.source = snapshot.source;
+            count = snapshot.count;
+            restore_local(path, &mut state, &results_dir, results, variables)?;
+        }
+    }
+
+    // With the VM setup, enter the L in the REPL
     loop {
         // Prepare the prompt with the current iteration number
         let p = format!("{count}> ");
@@ -366,7 +828,7 @@ async fn local_repl(
                 }
 
                 // Fetch REPL magicks
-                if let Some(quit) = repl_magicks(&line) {
+                if let Some(quit) = repl_magicks(&line, rl.history()) {
                     if quit {
                         break;
                     } else {
@@ -391,6 +853,20 @@ async fn local_repl(
                 // Go to the next iteration
                 count += 1;
                 state.state.offset += 1 + line.chars().filter(|c| *c == '\n').count();
+                if let Some(path) = persist {
+                    let vm: &OfflineVm = state.vm.as_ref().expect("Offline VM not initialized");
+                    save_snapshot(path, &Snapshot {
+                        language: state.options.lang.to_string(),
+                        count,
+                        state: state.state.clone(),
+                        source: state.source.clone(),
+                        vm: VmSnapshot::Local {
+                            results_dir: state.results_dir.path().into(),
+                            results:     vm.results(),
+                            variables:   vm.variables(),
+                        },
+                    });
+                }
             },
             Err(ReadlineError::Interrupted) => {
                 println!("Keyboard interrupt received, exiting...");
@@ -406,6 +882,11 @@ async fn local_repl(
         }
     }
 
+    // The snapshot refers to the intermediate results, so they have to outlive this session
+    if persist.is_some() {
+        let _ = state.results_dir.into_path();
+    }
+
     // Done
     Ok(())
 }
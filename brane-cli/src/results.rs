@@ -0,0 +1,168 @@
+//  RESULTS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:54:38
+//  Last edited:
+//    16 Oct 2026, 01:16:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements `brane workflow results`, which inspects the intermediate
+//!   results that an execution produced on the instance (as recorded in
+//!   the driver's execution history), and downloads or promotes them.
+//
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use console::style;
+use indicatif::{DecimalBytes, HumanDuration};
+use log::debug;
+use prettytable::format::FormatBuilder;
+use prettytable::Table;
+use specifications::driving::{DriverServiceClient, ListResultsRequest, PromoteResultRequest, ResultInfo};
+
+pub use crate::errors::ResultsError as Error;
+use crate::instance::InstanceInfo;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Connects to the driver of the instance we're logged-in to.
+///
+/// # Returns
+/// A tuple of the [`DriverServiceClient`] and the address of the driver (for debugging).
+///
+/// # Errors
+/// This function errors if we failed to read the instance info or to connect to the driver.
+async fn connect() -> Result<(DriverServiceClient, String), Error> {
+    let info: InstanceInfo = match InstanceInfo::from_active_path() {
+        Ok(info) => info,
+        Err(err) => return Err(Error::InstanceInfoError { err }),
+    };
+    let drv_address: String = info.drv.to_string();
+
+    debug!("Connecting to driver '{}'...", drv_address);
+    match DriverServiceClient::connect(drv_address.clone()).await {
+        Ok(client) => Ok((client.with_token(info.token), drv_address)),
+        Err(err) => Err(Error::DriverConnectError { address: drv_address, err }),
+    }
+}
+
+/// Formats a Unix timestamp relative to now.
+///
+/// # Arguments
+/// - `timestamp`: The timestamp, as seconds since the Unix epoch.
+///
+/// # Returns
+/// A string like `3 hours ago` or `in 2 days`.
+fn format_relative(timestamp: u64) -> String {
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    if timestamp <= now {
+        format!("{} ago", HumanDuration(Duration::from_secs(now - timestamp)))
+    } else {
+        format!("in {}", HumanDuration(Duration::from_secs(timestamp - now)))
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the intermediate results that an execution produced.
+///
+/// # Arguments
+/// - `execution_id`: The identifier of the execution, as reported when it completed.
+///
+/// # Errors
+/// This function errors if we failed to reach the driver or if it failed to list the results (e.g., because it does not know the execution).
+pub async fn list(execution_id: String) -> Result<(), Error> {
+    let (mut client, address): (DriverServiceClient, String) = connect().await?;
+    debug!("Listing intermediate results of execution '{}'...", execution_id);
+    let results: Vec<ResultInfo> = match client.list_results(ListResultsRequest { execution_id: execution_id.clone() }).await {
+        Ok(reply) => reply.into_inner().results,
+        Err(err) => return Err(Error::ListError { address, id: execution_id, err }),
+    };
+    if results.is_empty() {
+        println!("Execution '{}' did not produce any intermediate results.", execution_id);
+        return Ok(());
+    }
+
+    // Prepare display table.
+    let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+    let mut table = Table::new();
+    table.set_format(format);
+    table.add_row(row!["NAME", "TASK", "LOCATION", "SIZE", "PRODUCED", "EXPIRES"]);
+    for result in &results {
+        let size: String = match result.size {
+            Some(size) => DecimalBytes(size).to_string(),
+            None => "gone".into(),
+        };
+        table.add_row(row![
+            result.name,
+            result.task.as_deref().unwrap_or("-"),
+            result.location,
+            size,
+            format_relative(result.produced),
+            format_relative(result.expires)
+        ]);
+    }
+
+    // Write to stdout and done!
+    table.printstd();
+    println!();
+    println!(
+        "Run {} or {} to keep one of them.",
+        style(format!("brane workflow results {execution_id} download <NAME>")).bold().cyan(),
+        style(format!("brane workflow results {execution_id} promote <NAME> <DATASET>")).bold().cyan()
+    );
+    Ok(())
+}
+
+/// Promotes an intermediate result that an execution produced to a dataset.
+///
+/// # Arguments
+/// - `execution_id`: The identifier of the execution, as reported when it completed.
+/// - `name`: The name of the intermediate result to promote.
+/// - `dataset`: The name of the dataset to promote it to.
+///
+/// # Returns
+/// The location where the new dataset lives.
+///
+/// # Errors
+/// This function errors if we failed to reach the driver or if it failed to promote the result.
+pub async fn promote(execution_id: String, name: String, dataset: String) -> Result<String, Error> {
+    let (mut client, address): (DriverServiceClient, String) = connect().await?;
+    debug!("Promoting intermediate result '{}' of execution '{}' to dataset '{}'...", name, execution_id, dataset);
+    let request: PromoteResultRequest = PromoteResultRequest { execution_id: execution_id.clone(), name: name.clone(), dataset: dataset.clone() };
+    let location: String = match client.promote_result(request).await {
+        Ok(reply) => reply.into_inner().location,
+        Err(err) => return Err(Error::PromoteError { address, id: execution_id, name, err }),
+    };
+
+    println!("Promoted intermediate result {} to dataset {} at {}", style(&name).bold().cyan(), style(&dataset).bold().cyan(), location);
+    Ok(location)
+}
+
+/// Downloads an intermediate result that an execution produced, by promoting it to a dataset first.
+///
+/// # Arguments
+/// - `execution_id`: The identifier of the execution, as reported when it completed.
+/// - `name`: The name of the intermediate result to download.
+/// - `dataset`: The name of the dataset to promote it to. Defaults to the name of the result.
+/// - `proxy_addr`: If given, proxies the transfer through the given proxy.
+/// - `force`: If true, always downloads the dataset, even if it's already available locally.
+///
+/// # Errors
+/// This function errors if we failed to promote the result or to download the resulting dataset.
+pub async fn download(execution_id: String, name: String, dataset: Option<String>, proxy_addr: Option<String>, force: bool) -> Result<(), Error> {
+    let dataset: String = dataset.unwrap_or_else(|| name.clone());
+    let location: String = promote(execution_id, name, dataset.clone()).await?;
+
+    // Download it from where we just put it
+    if let Err(err) = crate::data::download(vec![dataset.clone()], vec![format!("{dataset}={location}")], &proxy_addr, force).await {
+        return Err(Error::DownloadError { name: dataset, err });
+    }
+    Ok(())
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 16:42:57
 //  Last edited:
-//    07 Mar 2024, 14:14:56
+//    16 Oct 2026, 01:29:42
 //  Auto updated?
 //    Yes
 //
@@ -13,34 +13,47 @@
 //
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{Read, Stderr, Stdout, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use brane_ast::state::CompileState;
 use brane_ast::{compile_snippet, CompileResult, ParserOptions, Workflow};
 use brane_dsl::Language;
 use brane_exe::dummy::{DummyVm, Error as DummyVmError};
-use brane_exe::FullValue;
+use brane_exe::{Error as VmError, FullValue};
+use brane_shr::suggest::suggest;
+use brane_tsk::api::RemotePackageProvider;
+use brane_tsk::caches::PackageIndexCache;
 use brane_tsk::docker::DockerOptions;
-use brane_tsk::errors::StringError;
+use brane_tsk::errors::{ExecuteError, StringError};
+use brane_tsk::local::LocalPackageProvider;
 use brane_tsk::spec::{AppId, LOCALHOST};
+use chrono::{DateTime, Utc};
 use console::style;
 use parking_lot::{Mutex, MutexGuard};
+use serde::Serialize;
+use specifications::checking::{ApprovalPending, PolicyDenial};
 use specifications::data::{AccessKind, DataIndex, DataInfo};
-use specifications::driving::{CreateSessionRequest, DriverServiceClient, ExecuteRequest};
+use specifications::driving::{CreateSessionRequest, DriverServiceClient, ExecuteEvent, ExecuteRequest};
+use specifications::errors::ErrorCode;
 use specifications::package::PackageIndex;
+use specifications::reporting::TaskFailure;
 use tempfile::{tempdir, TempDir};
-use tonic::Code;
+use tonic::{Code, Status};
 
-use crate::data;
 use crate::errors::OfflineVmError;
 pub use crate::errors::RunError as Error;
 use crate::instance::InstanceInfo;
+use crate::sinks::OutputSinks;
+use crate::spec::{AutoCommit, OutputFormat};
 use crate::utils::{ensure_datasets_dir, ensure_packages_dir, get_datasets_dir, get_packages_dir};
 use crate::vm::OfflineVm;
+use crate::{data, progress};
 
 
 /***** HELPER FUNCTIONS *****/
@@ -128,6 +141,181 @@ fn compile(
 }
 
 
+/// Prints why a checker denied (part of) a workflow in a human-friendly way.
+///
+/// # Arguments
+/// - `denial`: The [`PolicyDenial`] sent by the remote driver.
+fn print_denial(denial: &PolicyDenial) {
+    println!();
+    print!("Checker of domain {} {} ", style(&denial.domain).bold().cyan(), style("denied").bold().red());
+    match (&denial.task, &denial.dataset) {
+        (Some(task), Some(dataset)) => println!("transfer of dataset {} for task {}", style(dataset).bold(), style(task).bold()),
+        (None, Some(dataset)) => println!("transfer of dataset {}", style(dataset).bold()),
+        (Some(task), None) => println!("execution of task {}", style(task).bold()),
+        (None, None) => println!("execution of the workflow"),
+    }
+    if !denial.reasons.is_empty() {
+        println!(" > Reasons for denial:");
+        for reason in &denial.reasons {
+            println!("    - {}", style(reason).bold());
+        }
+    }
+    println!();
+}
+
+/// Prints that a download needed by a workflow awaits the approval of a data steward, and what to do about it.
+///
+/// # Arguments
+/// - `pending`: The [`ApprovalPending`] sent by the remote driver.
+fn print_pending(pending: &ApprovalPending) {
+    println!();
+    println!(
+        "Download of intermediate result {} {} by the data steward of domain {}",
+        style(&pending.result).bold(),
+        style("awaits approval").bold().yellow(),
+        style(&pending.domain).bold().cyan()
+    );
+    println!(" > Ask them to run {}, then run the workflow again", style(format!("branectl approvals approve {}", pending.id)).bold());
+    println!();
+}
+
+/// Writes which tasks completed (and what they returned) before a workflow exceeded its deadline.
+///
+/// # Arguments
+/// - `out`: The [`Write`]r to write to.
+/// - `deadline`: The deadline that the workflow exceeded, in seconds.
+/// - `partial`: The values returned by the tasks that did complete (as JSON), by program counter.
+///
+/// # Errors
+/// This function errors if we failed to write to `out`.
+fn write_partial(mut out: impl Write, deadline: u64, partial: HashMap<String, String>) -> Result<(), std::io::Error> {
+    writeln!(out, "Workflow exceeded its deadline of {deadline}s; its outstanding tasks were cancelled")?;
+    if partial.is_empty() {
+        return writeln!(out, " > No task completed before the deadline");
+    }
+    writeln!(out, " > Tasks that completed before the deadline:")?;
+    let mut partial: Vec<(String, String)> = partial.into_iter().collect();
+    partial.sort();
+    for (pc, value) in partial {
+        match serde_json::from_str::<FullValue>(&value) {
+            Ok(FullValue::Void) => writeln!(out, "    - {}", style(pc).bold())?,
+            Ok(value) => writeln!(out, "    - {}: {}", style(pc).bold(), value)?,
+            Err(_) => writeln!(out, "    - {}: {}", style(pc).bold(), value)?,
+        }
+    }
+    Ok(())
+}
+
+
+/// Converts an error sent by the driver while running a workflow into a [`RunError`](Error).
+///
+/// If the workflow was denied by a checker that explained why, the reasons are printed to stdout.
+///
+/// # Arguments
+/// - `status`: The [`Status`] sent by the driver.
+///
+/// # Returns
+/// A new [`Error`] describing why the workflow failed.
+pub(crate) fn status_error(status: Status) -> Error {
+    match (status.code(), ErrorCode::from_status(&status)) {
+        (Code::PermissionDenied, _) => {
+            if let Some(denial) = PolicyDenial::from_bytes(status.details()) {
+                print_denial(&denial);
+                return Error::PolicyDenied { denial };
+            }
+            Error::ExecDenied { err: Box::new(StringError(status.message().into())) }
+        },
+        (Code::FailedPrecondition, Some(ErrorCode::RegApprovalPending)) => {
+            if let Some(pending) = ApprovalPending::from_bytes(status.details()) {
+                print_pending(&pending);
+                return Error::ApprovalPending { pending };
+            }
+            Error::ExecFailed { code: ErrorCode::RegApprovalPending, err: Box::new(StringError(status.message().into())) }
+        },
+        (_, Some(code)) => Error::ExecFailed { code, err: Box::new(StringError(status.message().into())) },
+        (_, None) => Error::ExecError { err: Box::new(StringError(status.message().into())) },
+    }
+}
+
+/// Generates the BraneScript snippet that promotes the intermediate results in the variables given with `--auto-commit`.
+///
+/// # Arguments
+/// - `auto_commit`: The variables to promote, together with the dataset to promote each of them to.
+///
+/// # Returns
+/// The snippet as source text.
+fn auto_commit_snippet(auto_commit: &[AutoCommit]) -> String {
+    auto_commit.iter().map(|ac| format!("keep_result(\"{}\", {});", ac.dataset, ac.variable)).collect::<Vec<String>>().join("\n")
+}
+
+/// Prints which variables were promoted with `--auto-commit`.
+///
+/// # Arguments
+/// - `auto_commit`: The variables that were promoted, together with the dataset each of them was promoted to.
+fn print_auto_commit(auto_commit: &[AutoCommit]) {
+    println!();
+    for ac in auto_commit {
+        println!("Promoted {} to dataset {}", style(&ac.variable).bold(), style(format!("'{}'", ac.dataset)).bold().cyan());
+    }
+}
+
+/// Finds the task that made a workflow fail, if it failed because a task did.
+///
+/// # Arguments
+/// - `err`: The error with which the workflow failed.
+///
+/// # Returns
+/// The name of the task, where it ran (if not locally) and its [`TaskFailure`], or [`None`] if no task failed.
+fn failed_task(err: &Error) -> Option<(&str, Option<&str>, &TaskFailure)> {
+    match err {
+        Error::TaskFailed { name, location, failure } => Some((name.as_str(), Some(location.as_str()), &**failure)),
+        // Local tasks fail somewhere deep down the VM
+        Error::ExecError { err } => match err.downcast_ref::<OfflineVmError>() {
+            Some(OfflineVmError::ExecError { err: VmError::Custom { err, .. } }) => match err.downcast_ref::<ExecuteError>() {
+                Some(ExecuteError::ExternalCallFailed { name, failure, .. }) => Some((name.as_str(), None, &**failure)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Prints the outcome of a workflow as a single line of JSON (i.e., for `--output json`).
+///
+/// # Arguments
+/// - `res`: The value returned by the workflow, or the error with which it failed.
+fn print_json(res: &Result<FullValue, Error>) {
+    /// The task that made a workflow fail.
+    #[derive(Serialize)]
+    struct FailedTask<'a> {
+        task:     &'a str,
+        location: Option<&'a str>,
+        #[serde(flatten)]
+        failure:  &'a TaskFailure,
+    }
+    /// The outcome of a workflow.
+    #[derive(Serialize)]
+    struct Outcome<'a> {
+        status:  &'static str,
+        value:   Option<&'a FullValue>,
+        error:   Option<String>,
+        failure: Option<FailedTask<'a>>,
+    }
+
+    let outcome: Outcome = match res {
+        Ok(value) => Outcome { status: "succeeded", value: Some(value), error: None, failure: None },
+        Err(err) => Outcome {
+            status:  "failed",
+            value:   None,
+            error:   Some(err.to_string()),
+            failure: failed_task(err).map(|(task, location, failure)| FailedTask { task, location, failure }),
+        },
+    };
+    println!("{}", serde_json::to_string(&outcome).unwrap());
+}
+
+
 
 
 
@@ -140,9 +328,10 @@ fn compile(
 /// - `stdout_writer`: Some [`Write`]-handle that we use to write stdout to.
 /// - `stderr_writer`: Some [`Write`]-handle that we use to write stderr to.
 /// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff.
-/// - `pindex`: The [`PackageIndex`] that contains the remote's available packages.
+/// - `pindex`: The [`PackageIndexCache`] that resolves the remote's packages imported by the snippets.
 /// - `dindex`: The [`DataIndex`] that contains the remote's available datasets.
 /// - `user`: Some (tentative) identifier of the user who might receive the end result.
+/// - `token`: If given, the API token with which we authenticate ourselves to the driver (which then runs our workflows as its user).
 /// - `attach`: If given, we will try to attach to a session with that ID. Otherwise, we start a new session.
 /// - `options`: The ParserOptions that describe how to parse the given source.
 ///
@@ -156,9 +345,10 @@ pub async fn initialize_instance<O: Write, E: Write>(
     stdout_writer: O,
     stderr_writer: E,
     drv_endpoint: impl AsRef<str>,
-    pindex: Arc<Mutex<PackageIndex>>,
+    pindex: Arc<PackageIndexCache<RemotePackageProvider>>,
     dindex: Arc<Mutex<DataIndex>>,
     user: Option<String>,
+    token: Option<String>,
     attach: Option<AppId>,
     options: ParserOptions,
 ) -> Result<InstanceVmState<O, E>, Error> {
@@ -167,7 +357,7 @@ pub async fn initialize_instance<O: Write, E: Write>(
     // Connect to the server with gRPC
     debug!("Connecting to driver '{}'...", drv_endpoint);
     let mut client: DriverServiceClient = match DriverServiceClient::connect(drv_endpoint.to_string()).await {
-        Ok(client) => client,
+        Ok(client) => client.with_token(token),
         Err(err) => {
             return Err(Error::ClientConnectError { address: drv_endpoint.into(), err });
         },
@@ -179,7 +369,7 @@ pub async fn initialize_instance<O: Write, E: Write>(
         attach
     } else {
         // Setup a new session
-        let request = CreateSessionRequest {};
+        let request = CreateSessionRequest { user: user.clone() };
         let reply = match client.create_session(request).await {
             Ok(reply) => reply,
             Err(err) => {
@@ -213,6 +403,8 @@ pub async fn initialize_instance<O: Write, E: Write>(
 
         session,
         client,
+        deadline: None,
+        sinks: None,
     })
 }
 
@@ -247,9 +439,106 @@ pub async fn run_instance<O: Write, E: Write>(
         },
     };
 
-    // Prepare the request to execute this command
-    let request = ExecuteRequest { uuid: state.session.to_string(), input: sworkflow };
+    // Prepare the request to execute this command, and run it
+    let request = ExecuteRequest {
+        uuid:     state.session.to_string(),
+        input:    sworkflow,
+        resume:   None,
+        deadline: state.deadline.map(|deadline| deadline.as_secs()),
+    };
+    execute_instance(drv_endpoint, state, request, profile).await
+}
+
+/// Runs the given compiled workflow on the remote instance while following its progress in an interactive terminal view.
+///
+/// # Arguments
+/// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `workflow`: The already compiled [`Workflow`] to execute.
+///
+/// # Returns
+/// A [`FullValue`] carrying the result of the snippet (or [`FullValue::Void`]).
+///
+/// # Errors
+/// This function may error for any reason that [`run_instance()`] may error, or if we failed to show the view.
+pub async fn run_instance_tui<O: Write, E: Write>(
+    drv_endpoint: impl AsRef<str>,
+    state: &mut InstanceVmState<O, E>,
+    workflow: &Workflow,
+) -> Result<FullValue, Error> {
+    let drv_endpoint: &str = drv_endpoint.as_ref();
+
+    // Serialize the workflow
+    let sworkflow: String = match serde_json::to_string(&workflow) {
+        Ok(sworkflow) => sworkflow,
+        Err(err) => {
+            return Err(Error::WorkflowSerializeError { err });
+        },
+    };
+
+    // Send it, then leave following the replies to the view
+    let request = ExecuteRequest {
+        uuid:     state.session.to_string(),
+        input:    sworkflow,
+        resume:   None,
+        deadline: state.deadline.map(|deadline| deadline.as_secs()),
+    };
+    let response = match state.client.execute(request).await {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(Error::CommandRequestError { address: drv_endpoint.into(), err });
+        },
+    };
+    progress::follow(drv_endpoint, workflow, response.into_inner(), &mut state.stdout, state.sinks.clone()).await
+}
+
+/// Resumes an interrupted workflow on the remote instance from its last completed task.
+///
+/// # Arguments
+/// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `execution_id`: The identifier of the workflow to resume, as reported by the driver when it was interrupted.
+/// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+///
+/// # Returns
+/// A [`FullValue`] carrying the result of the workflow (or [`FullValue::Void`]).
+///
+/// # Errors
+/// This function may error if the driver has no checkpoint for the workflow, or for any reason that [`run_instance()`] may error.
+pub async fn resume_instance<O: Write, E: Write>(
+    drv_endpoint: impl AsRef<str>,
+    state: &mut InstanceVmState<O, E>,
+    execution_id: impl Into<String>,
+    profile: bool,
+) -> Result<FullValue, Error> {
+    let request = ExecuteRequest {
+        uuid:     state.session.to_string(),
+        input:    String::new(),
+        resume:   Some(execution_id.into()),
+        deadline: state.deadline.map(|deadline| deadline.as_secs()),
+    };
+    execute_instance(drv_endpoint.as_ref(), state, request, profile).await
+}
 
+/// Sends an [`ExecuteRequest`] to the driver and processes its replies until the workflow completes.
+///
+/// # Arguments
+/// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff (used for debugging only).
+/// - `state`: The InstanceVmState that we use to connect to the driver.
+/// - `request`: The [`ExecuteRequest`] to send.
+/// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+///
+/// # Returns
+/// A [`FullValue`] carrying the result of the workflow (or [`FullValue::Void`]).
+///
+/// # Errors
+/// This function may error if we failed to reach the driver or if the workflow failed remotely.
+async fn execute_instance<O: Write, E: Write>(
+    drv_endpoint: &str,
+    state: &mut InstanceVmState<O, E>,
+    request: ExecuteRequest,
+    profile: bool,
+) -> Result<FullValue, Error> {
     // Run it
     let response = match state.client.execute(request).await {
         Ok(response) => response,
@@ -261,25 +550,55 @@ pub async fn run_instance<O: Write, E: Write>(
 
     // Switch on the type of message that the remote returned
     let mut res: FullValue = FullValue::Void;
+    let mut failed: Option<(String, String, TaskFailure)> = None;
+    let mut printed: Option<(String, DateTime<Utc>)> = None;
     loop {
         // Match on the message
         match stream.message().await {
             // The message itself went alright
             Ok(Some(reply)) => {
-                // Show profile times
-                if profile { /* TODO */ }
+                // Show profile information
+                if let Some(prof) = reply.profile {
+                    if profile {
+                        if let Err(err) = writeln!(&mut state.stdout, "{prof}") {
+                            return Err(Error::WriteError { err });
+                        }
+                    } else {
+                        debug!("Remote profile: {}", prof);
+                    }
+                }
 
                 // The remote send us some debug message
                 if let Some(debug) = reply.debug {
                     debug!("Remote: {}", debug);
                 }
 
+                // The remote reports progress, which we only show if the workflow timed out (to tell what did complete) and only remember
+                // if a task failed (to tell why once the workflow fails because of it)
+                if let Some(event) = reply.event {
+                    match serde_json::from_str(&event) {
+                        Ok(ExecuteEvent::TimedOut { deadline, partial }) => {
+                            if let Err(err) = write_partial(&mut state.stderr, deadline, partial) {
+                                return Err(Error::WriteError { err });
+                            }
+                        },
+                        Ok(ExecuteEvent::TaskFailed { name, location, failure, .. }) => failed = Some((name, location, failure)),
+                        Ok(ExecuteEvent::Printed { function, timestamp, .. }) => printed = Some((function, timestamp)),
+                        _ => {},
+                    }
+                }
+
                 // The remote send us a normal text message
                 if let Some(stdout) = reply.stdout {
                     debug!("Remote returned stdout");
                     if let Err(err) = write!(&mut state.stdout, "{stdout}") {
                         return Err(Error::WriteError { err });
                     }
+                    if let Some(sinks) = &state.sinks {
+                        // Older drivers don't say who printed it
+                        let (function, timestamp): (String, DateTime<Utc>) = printed.take().unwrap_or_else(|| ("<unknown>".into(), Utc::now()));
+                        sinks.write(&function, timestamp, &stdout);
+                    }
                 }
 
                 // The remote send us an error
@@ -312,9 +631,11 @@ pub async fn run_instance<O: Write, E: Write>(
                     break;
                 }
             },
-            Err(status) => match status.code() {
-                Code::PermissionDenied => return Err(Error::ExecDenied { err: Box::new(StringError(status.message().into())) }),
-                _ => return Err(Error::ExecError { err: Box::new(StringError(status.message().into())) }),
+            Err(status) => {
+                return Err(match failed {
+                    Some((name, location, failure)) => Error::TaskFailed { name, location, failure: Box::new(failure) },
+                    None => status_error(status),
+                });
             },
             Ok(None) => {
                 // Stream closed by the remote for some rason
@@ -383,7 +704,8 @@ pub async fn process_instance(
                 let info: &DataInfo = match index.get(&name) {
                     Some(info) => info,
                     None => {
-                        return Err(Error::UnknownDataset { name: name.into() });
+                        let suggestions: Vec<String> = suggest(name.as_ref(), index.names());
+                        return Err(Error::UnknownDataset { name: name.into(), suggestions });
                     },
                 };
                 let access: AccessKind = match info.access.get(LOCALHOST) {
@@ -405,6 +727,7 @@ pub async fn process_instance(
                 // Write the method of access
                 match access {
                     AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+                    AccessKind::S3 { .. } | AccessKind::Url { .. } => println!("(It's available at '{access}')"),
                 }
             },
 
@@ -424,8 +747,8 @@ pub async fn process_instance(
 /***** AUXILLARY *****/
 /// A helper struct that contains what we need to know about a compiler + VM state for the dummy use-case.
 pub struct DummyVmState {
-    /// The package index for this session.
-    pub pindex: Arc<PackageIndex>,
+    /// The package index for this session, which resolves local packages as they are imported.
+    pub pindex: PackageIndexCache<LocalPackageProvider>,
     /// The data index for this session.
     pub dindex: Arc<DataIndex>,
 
@@ -467,8 +790,8 @@ pub struct InstanceVmState<O, E> {
     /// A stderr to write outgoing stdout messages on.
     pub stderr: E,
 
-    /// The package index for this session.
-    pub pindex: Arc<Mutex<PackageIndex>>,
+    /// The package index for this session, which resolves remote packages as they are imported.
+    pub pindex: Arc<PackageIndexCache<RemotePackageProvider>>,
     /// The data index for this session.
     pub dindex: Arc<Mutex<DataIndex>>,
     /// A username of the person doing everything rn.
@@ -482,9 +805,13 @@ pub struct InstanceVmState<O, E> {
     pub options: ParserOptions,
 
     /// The ID for this session.
-    pub session: AppId,
+    pub session:  AppId,
     /// The client which we use to communicate to the VM.
-    pub client:  DriverServiceClient,
+    pub client:   DriverServiceClient,
+    /// The time that workflows may take in total before the driver cancels them, if limited.
+    pub deadline: Option<Duration>,
+    /// The sinks to write what workflows print to on top of stdout, if any.
+    pub sinks:    Option<Arc<OutputSinks>>,
 }
 
 
@@ -517,13 +844,8 @@ pub fn initialize_dummy_vm(options: ParserOptions) -> Result<DummyVmState, Error
         },
     };
 
-    // Get the package index for the local repository
-    let package_index: Arc<PackageIndex> = match brane_tsk::local::get_package_index(packages_dir) {
-        Ok(index) => Arc::new(index),
-        Err(err) => {
-            return Err(Error::LocalPackageIndexError { err });
-        },
-    };
+    // Only read local packages once they are imported, since the dummy VM doesn't need them to run
+    let package_index: PackageIndexCache<LocalPackageProvider> = PackageIndexCache::new(LocalPackageProvider::new(packages_dir));
     // Get the data index for the local repository
     let data_index: Arc<DataIndex> = match brane_tsk::local::get_data_index(datasets_dir) {
         Ok(index) => Arc::new(index),
@@ -641,6 +963,7 @@ pub fn initialize_offline_vm(parse_opts: ParserOptions, docker_opts: DockerOptio
 /// - `api_endpoint`: The `brane-api` endpoint that we download indices from.
 /// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff.
 /// - `user`: If given, then this is some tentative identifier of the user receiving the final workflow result.
+/// - `token`: If given, the API token with which we authenticate ourselves to the driver (which then runs our workflows as its user).
 /// - `attach`: If given, we will try to attach to a session with that ID. Otherwise, we start a new session.
 /// - `options`: The ParserOptions that describe how to parse the given source.
 ///
@@ -653,21 +976,17 @@ pub async fn initialize_instance_vm(
     api_endpoint: impl AsRef<str>,
     drv_endpoint: impl AsRef<str>,
     user: Option<String>,
+    token: Option<String>,
     attach: Option<AppId>,
     options: ParserOptions,
 ) -> Result<InstanceVmState<Stdout, Stderr>, Error> {
     let api_endpoint: &str = api_endpoint.as_ref();
     let drv_endpoint: &str = drv_endpoint.as_ref();
 
-    // We fetch a local copy of the indices for compiling
-    debug!("Fetching global package & data indices from '{}'...", api_endpoint);
-    let package_addr: String = format!("{api_endpoint}/graphql");
-    let pindex: Arc<Mutex<PackageIndex>> = match brane_tsk::api::get_package_index(&package_addr).await {
-        Ok(pindex) => Arc::new(Mutex::new(pindex)),
-        Err(err) => {
-            return Err(Error::RemotePackageIndexError { address: package_addr, err });
-        },
-    };
+    // We fetch a local copy of the data index for compiling; packages are only fetched once imported
+    debug!("Fetching global data index from '{}'...", api_endpoint);
+    let pindex: Arc<PackageIndexCache<RemotePackageProvider>> =
+        Arc::new(PackageIndexCache::new(RemotePackageProvider::new(format!("{api_endpoint}/graphql"))));
     let data_addr: String = format!("{api_endpoint}/data/info");
     let dindex: Arc<Mutex<DataIndex>> = match brane_tsk::api::get_data_index(&data_addr).await {
         Ok(dindex) => Arc::new(Mutex::new(dindex)),
@@ -677,7 +996,7 @@ pub async fn initialize_instance_vm(
     };
 
     // Pass the rest to `initialize_instance`
-    initialize_instance(std::io::stdout(), std::io::stderr(), drv_endpoint, pindex, dindex, user, attach, options).await
+    initialize_instance(std::io::stdout(), std::io::stderr(), drv_endpoint, pindex, dindex, user, token, attach, options).await
 }
 
 
@@ -698,8 +1017,14 @@ pub async fn run_dummy_vm(state: &mut DummyVmState, what: impl AsRef<str>, snipp
     let what: &str = what.as_ref();
     let snippet: &str = snippet.as_ref();
 
-    // Compile the workflow
-    let workflow: Workflow = compile(&mut state.state, &mut state.source, &state.pindex, &state.dindex, None, &state.options, what, snippet)?;
+    // Resolve the packages it imports, then compile the workflow
+    let pindex: Arc<PackageIndex> = match state.pindex.resolve(snippet, &state.options).await {
+        Ok(pindex) => pindex,
+        Err(err) => {
+            return Err(Error::LocalPackageIndexError { err });
+        },
+    };
+    let workflow: Workflow = compile(&mut state.state, &mut state.source, &pindex, &state.dindex, None, &state.options, what, snippet)?;
 
     // Run it in the local VM (which is a bit ugly do to the need to consume the VM itself)
     let res: (DummyVm, Result<FullValue, DummyVmError>) = state.vm.take().unwrap().exec(workflow).await;
@@ -774,16 +1099,38 @@ pub async fn run_instance_vm(
     snippet: impl AsRef<str>,
     profile: bool,
 ) -> Result<FullValue, Error> {
-    // Compile the workflow
-    let workflow: Workflow = {
-        // Acquire the locks
-        let pindex: MutexGuard<PackageIndex> = state.pindex.lock();
-        let dindex: MutexGuard<DataIndex> = state.dindex.lock();
-        compile(&mut state.state, &mut state.source, &pindex, &dindex, state.user.as_deref(), &state.options, what, snippet)?
+    let workflow: Workflow = compile_instance(state, what, snippet).await?;
+    run_instance(drv_endpoint, state, &workflow, profile).await
+}
+
+/// Compiles the given workflow snippet against the packages and datasets of the Brane instance.
+///
+/// # Arguments
+/// - `state`: The InstanceVmState that we use to resolve packages and datasets.
+/// - `what`: The thing we're compiling. Either a filename, or something like '<stdin>'.
+/// - `snippet`: The snippet (as raw text) to compile.
+///
+/// # Returns
+/// The compiled [`Workflow`].
+///
+/// # Errors
+/// This function errors if we failed to resolve the packages imported by the snippet or to compile it.
+async fn compile_instance<O: Write, E: Write>(
+    state: &mut InstanceVmState<O, E>,
+    what: impl AsRef<str>,
+    snippet: impl AsRef<str>,
+) -> Result<Workflow, Error> {
+    // Resolve the packages it imports first
+    let pindex: Arc<PackageIndex> = match state.pindex.resolve(snippet.as_ref(), &state.options).await {
+        Ok(pindex) => pindex,
+        Err(err) => {
+            return Err(Error::RemotePackageResolveError { err });
+        },
     };
 
-    // Run the thing using the other function
-    run_instance(drv_endpoint, state, &workflow, profile).await
+    // Compile the workflow (acquiring the lock)
+    let dindex: MutexGuard<DataIndex> = state.dindex.lock();
+    compile(&mut state.state, &mut state.source, &pindex, &dindex, state.user.as_deref(), &state.options, what, snippet)
 }
 
 
@@ -865,7 +1212,8 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
                 let info: &DataInfo = match index.get(&name) {
                     Some(info) => info,
                     None => {
-                        return Err(Error::UnknownDataset { name: name.into() });
+                        let suggestions: Vec<String> = suggest(name.as_ref(), index.names());
+                        return Err(Error::UnknownDataset { name: name.into(), suggestions });
                     },
                 };
                 let access: &AccessKind = match info.access.get(LOCALHOST) {
@@ -878,6 +1226,7 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
                 // Write the method of access
                 match access {
                     AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+                    AccessKind::S3 { .. } | AccessKind::Url { .. } => println!("(It's available at '{access}')"),
                 }
             },
 
@@ -945,6 +1294,11 @@ pub async fn process_instance_result(api_endpoint: impl AsRef<str>, proxy_addr:
 /// - `profile`: If given, prints the profile timings to stdout if available.
 /// - `docker_opts`: The options with which we connect to the local Docker daemon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `tui`: Whether to follow the workflow in an interactive terminal view. Only relevant if running remotely.
+/// - `deadline`: The time that the workflow may take in total before the driver cancels it, if limited. Only relevant if running remotely.
+/// - `auto_commit`: The variables holding intermediate results to promote to datasets once the workflow is done. Irrelevant for dummy runs.
+/// - `output`: How to report the outcome of the workflow.
+/// - `sinks`: If given, the [`OutputSinks`] to write what the workflow prints to on top of stdout. Only relevant if running remotely.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
@@ -958,6 +1312,11 @@ pub async fn handle(
     profile: bool,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    tui: bool,
+    deadline: Option<Duration>,
+    auto_commit: Vec<AutoCommit>,
+    output: OutputFormat,
+    sinks: Option<Arc<OutputSinks>>,
 ) -> Result<(), Error> {
     // Either read the file or read stdin
     let (what, source_code): (Cow<str>, String) = if file == PathBuf::from("-") {
@@ -975,11 +1334,16 @@ pub async fn handle(
         }
     };
 
-    // Prepare the parser options
-    let options: ParserOptions = ParserOptions::new(language);
+    // Prepare the parser options (resolving imported files relative to the input file)
+    let mut options: ParserOptions = ParserOptions::new(language);
+    if file != PathBuf::from("-") {
+        if let Some(dir) = file.parent() {
+            options = options.with_base_dir(dir);
+        }
+    }
 
     // Now switch on dummy, local or remote mode
-    if !dummy {
+    let res: Result<FullValue, Error> = if !dummy {
         if remote {
             // Open the login file to find the remote location
             let info: InstanceInfo = match InstanceInfo::from_active_path() {
@@ -990,13 +1354,19 @@ pub async fn handle(
             };
 
             // Run the thing
-            remote_run(info, proxy_addr, options, what, source_code, profile).await
+            remote_run(info, proxy_addr, options, what, source_code, profile, tui, deadline, auto_commit, output, sinks).await
         } else {
-            local_run(options, docker_opts, what, source_code, keep_containers).await
+            local_run(options, docker_opts, what, source_code, keep_containers, auto_commit, output).await
         }
     } else {
-        dummy_run(options, what, source_code).await
+        dummy_run(options, what, source_code, output).await
+    };
+
+    // Report the outcome for scripts, if they asked for it
+    if output == OutputFormat::Json {
+        print_json(&res);
     }
+    res.map(|_| ())
 }
 
 
@@ -1007,10 +1377,11 @@ pub async fn handle(
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `what`: A description of the source we're reading (e.g., the filename or `<stdin>`)
 /// - `source`: The source code to read.
+/// - `output`: How to report the outcome of the workflow. Only if it's [`OutputFormat::Human`] is the result printed here.
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Does not produce new datasets.
-async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>) -> Result<(), Error> {
+/// The value returned by the workflow. Does not produce new datasets.
+async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>, output: OutputFormat) -> Result<FullValue, Error> {
     let what: &str = what.as_ref();
     let source: &str = source.as_ref();
 
@@ -1019,10 +1390,12 @@ async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl A
     // Next, we run the VM (one snippet only ayway)
     let res: FullValue = run_dummy_vm(&mut state, what, source).await?;
     // Then, we collect and process the result
-    process_dummy_result(res);
+    if output == OutputFormat::Human {
+        process_dummy_result(res.clone());
+    }
 
     // Done
-    Ok(())
+    Ok(res)
 }
 
 /// Runs the given file on the local machine.
@@ -1033,16 +1406,20 @@ async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl A
 /// - `what`: A description of the source we're reading (e.g., the filename or `<stdin>`)
 /// - `source`: The source code to read.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `auto_commit`: The variables holding intermediate results to promote to datasets once the workflow is done.
+/// - `output`: How to report the outcome of the workflow. Only if it's [`OutputFormat::Human`] is the result printed here.
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Might also produce new datasets.
+/// The value returned by the workflow. Might also produce new datasets.
 async fn local_run(
     parse_opts: ParserOptions,
     docker_opts: DockerOptions,
     what: impl AsRef<str>,
     source: impl AsRef<str>,
     keep_containers: bool,
-) -> Result<(), Error> {
+    auto_commit: Vec<AutoCommit>,
+    output: OutputFormat,
+) -> Result<FullValue, Error> {
     let what: &str = what.as_ref();
     let source: &str = source.as_ref();
 
@@ -1051,10 +1428,22 @@ async fn local_run(
     // Next, we run the VM (one snippet only ayway)
     let res: FullValue = run_offline_vm(&mut state, what, source).await?;
     // Then, we collect and process the result
-    process_offline_result(res)?;
+    if output == OutputFormat::Human {
+        process_offline_result(res.clone())?;
+    }
+
+    // Finally, promote the results the user wants to keep before the results directory is cleaned up (always in BraneScript)
+    if !auto_commit.is_empty() {
+        state.state.offset += 1 + source.chars().filter(|c| *c == '\n').count();
+        state.options = ParserOptions::bscript();
+        run_offline_vm(&mut state, "<auto-commit>", auto_commit_snippet(&auto_commit)).await?;
+        if output == OutputFormat::Human {
+            print_auto_commit(&auto_commit);
+        }
+    }
 
     // Done
-    Ok(())
+    Ok(res)
 }
 
 /// Runs the given file on the remote instance.
@@ -1066,9 +1455,15 @@ async fn local_run(
 /// - `what`: A description of the source we're reading (e.g., the filename or `<stdin>`)
 /// - `source`: The source code to read.
 /// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+/// - `tui`: Whether to follow the workflow in an interactive terminal view.
+/// - `deadline`: The time that the workflow may take in total before the driver cancels it, if limited.
+/// - `auto_commit`: The variables holding intermediate results to promote to datasets once the workflow is done.
+/// - `output`: How to report the outcome of the workflow. Only if it's [`OutputFormat::Human`] is the result printed (and downloaded) here.
+/// - `sinks`: If given, the [`OutputSinks`] to write what the workflow prints to on top of stdout.
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Might also produce new datasets.
+/// The value returned by the workflow. Might also produce new datasets.
+#[allow(clippy::too_many_arguments)]
 async fn remote_run(
     info: InstanceInfo,
     proxy_addr: Option<String>,
@@ -1076,7 +1471,12 @@ async fn remote_run(
     what: impl AsRef<str>,
     source: impl AsRef<str>,
     profile: bool,
-) -> Result<(), Error> {
+    tui: bool,
+    deadline: Option<Duration>,
+    auto_commit: Vec<AutoCommit>,
+    output: OutputFormat,
+    sinks: Option<Arc<OutputSinks>>,
+) -> Result<FullValue, Error> {
     let api_endpoint: String = info.api.to_string();
     let drv_endpoint: String = info.drv.to_string();
     let what: &str = what.as_ref();
@@ -1084,10 +1484,73 @@ async fn remote_run(
 
     // First we initialize the remote thing
     let mut state: InstanceVmState<Stdout, Stderr> =
-        initialize_instance_vm(&api_endpoint, &drv_endpoint, Some(info.user.clone()), None, options).await?;
+        initialize_instance_vm(&api_endpoint, &drv_endpoint, Some(info.user.clone()), info.token.clone(), None, options).await?;
+    state.deadline = deadline;
+    state.sinks = sinks;
     // Next, we run the VM (one snippet only ayway)
-    let res: FullValue = run_instance_vm(drv_endpoint, &mut state, what, source, profile).await?;
+    let res: Result<FullValue, Error> = if tui {
+        match compile_instance(&mut state, what, source).await {
+            Ok(workflow) => run_instance_tui(&drv_endpoint, &mut state, &workflow).await,
+            Err(err) => Err(err),
+        }
+    } else {
+        run_instance_vm(&drv_endpoint, &mut state, what, source, profile).await
+    };
+    // Whether it succeeded or not, make sure everything it printed reaches the sinks
+    if let Some(sinks) = &state.sinks {
+        sinks.close().await;
+    }
+    let res: FullValue = res?;
     // Then, we collect and process the result
+    if output == OutputFormat::Human {
+        process_instance_result(api_endpoint, &proxy_addr, res.clone()).await?;
+    }
+
+    // Finally, promote the results the user wants to keep in the same session (always in BraneScript)
+    if !auto_commit.is_empty() {
+        state.state.offset += 1 + source.chars().filter(|c| *c == '\n').count();
+        state.options = ParserOptions::bscript();
+        run_instance_vm(&drv_endpoint, &mut state, "<auto-commit>", auto_commit_snippet(&auto_commit), profile).await?;
+        if output == OutputFormat::Human {
+            print_auto_commit(&auto_commit);
+        }
+    }
+
+    // Done
+    Ok(res)
+}
+
+
+
+/// Resumes an interrupted workflow on the instance we are currently logged-in to.
+///
+/// The driver skips the tasks that completed before the workflow was interrupted (as long as their results still exist) and continues from
+/// there. This requires that the driver checkpoints workflows (see its `--checkpoint-dir`).
+///
+/// # Arguments
+/// - `proxy_addr`: The address to proxy any data transfers through if they occur.
+/// - `execution_id`: The identifier of the workflow to resume, as reported by the driver when it was interrupted.
+/// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+///
+/// # Returns
+/// Nothing, but does print results and such to stdout. Might also produce new datasets.
+///
+/// # Errors
+/// This function errors if we failed to reach the instance, if it has no checkpoint for the workflow or if the workflow failed (again).
+pub async fn resume(proxy_addr: Option<String>, execution_id: String, profile: bool) -> Result<(), Error> {
+    let info: InstanceInfo = match InstanceInfo::from_active_path() {
+        Ok(config) => config,
+        Err(err) => {
+            return Err(Error::InstanceInfoError { err });
+        },
+    };
+    let api_endpoint: String = info.api.to_string();
+    let drv_endpoint: String = info.drv.to_string();
+
+    // Open a new session and resume the workflow in it
+    let mut state: InstanceVmState<Stdout, Stderr> =
+        initialize_instance_vm(&api_endpoint, &drv_endpoint, Some(info.user.clone()), info.token.clone(), None, ParserOptions::bscript()).await?;
+    let res: FullValue = resume_instance(drv_endpoint, &mut state, execution_id, profile).await?;
     process_instance_result(api_endpoint, &proxy_addr, res).await?;
 
     // Done
@@ -0,0 +1,146 @@
+//  SIMULATION.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:52:31
+//  Last edited:
+//    15 Oct 2026, 21:52:31
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the domains that `brane workflow run --simulate-domains`
+//!   emulates on the local machine.
+//!
+//!   The domains are read from an `infra.yml` file. Every domain has its
+//!   own directory next to that file (named after the domain), which
+//!   contains its datasets in a `data` directory (laid out like the local
+//!   datasets directory) and, optionally, its policy in a
+//!   `policy.eflint` or `policy.json` file.
+//
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use brane_ast::locations::Location;
+use brane_cfg::info::Info as _;
+use brane_cfg::infra::InfraFile;
+use log::debug;
+use specifications::data::{AccessKind, DataIndex, DataInfo};
+
+pub use crate::errors::SimulationError as Error;
+
+
+/***** CONSTANTS *****/
+/// The files that we look for (in order) as a domain's policy.
+const POLICY_FILES: [&str; 2] = ["policy.eflint", "policy.json"];
+
+
+
+
+
+/***** LIBRARY *****/
+/// A single simulated domain.
+#[derive(Clone, Debug)]
+pub struct Domain {
+    /// The directory with the domain's datasets.
+    pub data_dir: PathBuf,
+    /// The domain's policy, if it has any.
+    pub policy:   Option<PathBuf>,
+}
+
+
+
+/// The domains that are simulated on the local machine.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// The domains, by name.
+    pub domains: BTreeMap<Location, Domain>,
+}
+impl Simulation {
+    /// Constructor for the Simulation that reads the domains from an `infra.yml` file.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the `infra.yml` file. The domain directories are resolved relative to its parent directory.
+    ///
+    /// # Returns
+    /// A new Simulation instance.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the infrastructure file or if it does not define any domains.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Read the infrastructure file
+        debug!("Reading simulated domains from '{}'...", path.display());
+        let infra: InfraFile = match InfraFile::from_path(path) {
+            Ok(infra) => infra,
+            Err(err) => return Err(Error::InfraFileRead { path: path.into(), err }),
+        };
+        if infra.is_empty() {
+            return Err(Error::NoDomains { path: path.into() });
+        }
+
+        // Find the directories of each domain
+        let root: &Path = path.parent().unwrap_or_else(|| Path::new("."));
+        let domains: BTreeMap<Location, Domain> = infra
+            .into_iter()
+            .map(|(name, _)| {
+                let dir: PathBuf = root.join(&name);
+                let policy: Option<PathBuf> = POLICY_FILES.iter().map(|file| dir.join(file)).find(|path| path.is_file());
+                debug!("Simulating domain '{}' in '{}' ({})", name, dir.display(), if policy.is_some() { "with policy" } else { "without policy" });
+                (name, Domain { data_dir: dir.join("data"), policy })
+            })
+            .collect();
+        Ok(Self { domains })
+    }
+
+    /// Returns the names of the simulated domains, in alphabetical order.
+    #[inline]
+    pub fn locations(&self) -> Vec<Location> { self.domains.keys().cloned().collect() }
+
+    /// Builds a [`DataIndex`] of the datasets of all domains.
+    ///
+    /// Every dataset is available at the domain(s) in whose data directory it is found, regardless of the location listed in its `data.yml`.
+    ///
+    /// # Returns
+    /// A new [`DataIndex`] with the datasets of all domains.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read a domain's datasets, or if a dataset is not stored as a file.
+    pub fn data_index(&self) -> Result<DataIndex, Error> {
+        let mut infos: HashMap<String, DataInfo> = HashMap::new();
+        for (name, domain) in &self.domains {
+            if !domain.data_dir.exists() {
+                debug!("Domain '{}' has no datasets", name);
+                continue;
+            }
+            let index: DataIndex = match brane_tsk::local::get_data_index(&domain.data_dir) {
+                Ok(index) => index,
+                Err(err) => return Err(Error::DataIndex { domain: name.clone(), err }),
+            };
+
+            // Move every dataset to this domain, resolving its path relative to its own directory
+            // (Datasets with the same name in multiple domains are merged into one that is available at all of them)
+            for mut info in index {
+                let path: PathBuf = match info.access.into_values().next() {
+                    Some(AccessKind::File { path }) => domain.data_dir.join(&info.name).join(path),
+                    _ => return Err(Error::IllegalAccess { domain: name.clone(), name: info.name }),
+                };
+                match infos.get_mut(&info.name) {
+                    Some(einfo) => {
+                        einfo.access.insert(name.clone(), AccessKind::File { path });
+                    },
+                    None => {
+                        info.access = HashMap::from([(name.clone(), AccessKind::File { path })]);
+                        infos.insert(info.name.clone(), info);
+                    },
+                }
+            }
+        }
+        match DataIndex::from_infos(infos.into_values().collect()) {
+            Ok(index) => Ok(index),
+            Err(err) => Err(Error::DataIndexMerge { err }),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//  SINKS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:46:40
+//  Last edited:
+//    16 Oct 2026, 00:46:40
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the places (besides stdout) where `brane workflow run` writes
+//!   what a workflow prints.
+//!
+//!   Every line is tagged with the function that printed it and when it
+//!   did. Lines are appended to a log file and/or POSTed (as JSON) to a
+//!   webhook. Both are best-effort: failing to write a line is reported as
+//!   a warning, but does not fail the workflow.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs::File;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::{debug, warn};
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+pub use crate::errors::SinkError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// POSTs the lines sent on the given channel to a webhook, one at a time and in order.
+///
+/// # Arguments
+/// - `address`: The address of the webhook.
+/// - `rx`: The channel on which the lines are sent. We stop once it is closed.
+async fn post_lines(address: String, mut rx: UnboundedReceiver<PrintedLine>) {
+    let client: reqwest::Client = reqwest::Client::new();
+    while let Some(line) = rx.recv().await {
+        match client.post(&address).json(&line).send().await {
+            Ok(res) => {
+                if !res.status().is_success() {
+                    warn!("Webhook '{}' refused printed line: status code {}", address, res.status());
+                }
+            },
+            Err(err) => warn!("Failed to send printed line to webhook '{}': {}", address, err),
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A single line printed by a workflow, tagged with where and when it was printed.
+#[derive(Clone, Debug, Serialize)]
+pub struct PrintedLine {
+    /// The name of the function that printed it (`<main>` if it was printed outside of any function).
+    pub function:  String,
+    /// The moment it was printed. For lines printed in parts, this is the moment the first part was printed.
+    pub timestamp: DateTime<Utc>,
+    /// The line itself, without the closing newline.
+    pub text:      String,
+}
+impl Display for PrintedLine {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "[{}] [{}] {}", self.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true), self.function, self.text)
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Writes what a workflow prints to a log file and/or a webhook, on top of stdout.
+#[derive(Debug)]
+pub struct OutputSinks {
+    /// The log file to append lines to, if any.
+    log_file: Option<(PathBuf, Mutex<File>)>,
+    /// The channel on which lines are sent to the task that POSTs them to the webhook, if any.
+    webhook:  Mutex<Option<UnboundedSender<PrintedLine>>>,
+    /// The task that POSTs lines to the webhook, if any.
+    worker:   Mutex<Option<JoinHandle<()>>>,
+
+    /// The line that is currently being printed, i.e., that was printed in parts that did not yet end in a newline.
+    pending: Mutex<Option<PrintedLine>>,
+}
+impl OutputSinks {
+    /// Constructor for the OutputSinks.
+    ///
+    /// Note that this must be called from within a Tokio runtime if a webhook is given.
+    ///
+    /// # Arguments
+    /// - `log_file`: If given, the path of a file to write the lines to. It is created if it does not exist, and truncated otherwise.
+    /// - `webhook`: If given, the address of a webhook to POST every line to.
+    ///
+    /// # Returns
+    /// A new OutputSinks, or [`None`] if neither a log file nor a webhook is given.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the log file.
+    pub fn new(log_file: Option<PathBuf>, webhook: Option<String>) -> Result<Option<Self>, Error> {
+        if log_file.is_none() && webhook.is_none() {
+            return Ok(None);
+        }
+
+        // Open the log file
+        let log_file: Option<(PathBuf, Mutex<File>)> = match log_file {
+            Some(path) => {
+                debug!("Writing workflow output to log file '{}'", path.display());
+                match File::create(&path) {
+                    Ok(handle) => Some((path, Mutex::new(handle))),
+                    Err(err) => return Err(Error::LogFileCreateError { path, err }),
+                }
+            },
+            None => None,
+        };
+
+        // Start POSTing to the webhook
+        let (webhook, worker): (Option<UnboundedSender<PrintedLine>>, Option<JoinHandle<()>>) = match webhook {
+            Some(address) => {
+                debug!("Sending workflow output to webhook '{}'", address);
+                let (tx, rx): (UnboundedSender<PrintedLine>, UnboundedReceiver<PrintedLine>) = mpsc::unbounded_channel();
+                (Some(tx), Some(tokio::spawn(post_lines(address, rx))))
+            },
+            None => (None, None),
+        };
+
+        Ok(Some(Self { log_file, webhook: Mutex::new(webhook), worker: Mutex::new(worker), pending: Mutex::new(None) }))
+    }
+
+    /// Writes text printed by the workflow to the sinks.
+    ///
+    /// Only complete lines are written; text that does not (yet) end in a newline is kept until the rest of its line is printed.
+    ///
+    /// # Arguments
+    /// - `function`: The name of the function that printed the text.
+    /// - `timestamp`: The moment it was printed.
+    /// - `text`: The text itself, including any newlines.
+    pub fn write(&self, function: &str, timestamp: DateTime<Utc>, text: &str) {
+        let mut pending: MutexGuard<Option<PrintedLine>> = self.pending.lock().unwrap();
+        let mut rest: &str = text;
+        while let Some(pos) = rest.find('\n') {
+            let line: PrintedLine = match pending.take() {
+                Some(mut line) => {
+                    line.text.push_str(&rest[..pos]);
+                    line
+                },
+                None => PrintedLine { function: function.into(), timestamp, text: rest[..pos].into() },
+            };
+            self.emit(line);
+            rest = &rest[pos + 1..];
+        }
+        if !rest.is_empty() {
+            match &mut *pending {
+                Some(line) => line.text.push_str(rest),
+                None => *pending = Some(PrintedLine { function: function.into(), timestamp, text: rest.into() }),
+            }
+        }
+    }
+
+    /// Writes a complete line to the sinks.
+    ///
+    /// # Arguments
+    /// - `line`: The [`PrintedLine`] to write.
+    fn emit(&self, line: PrintedLine) {
+        if let Some((path, handle)) = &self.log_file {
+            if let Err(err) = writeln!(handle.lock().unwrap(), "{line}") {
+                warn!("Failed to write printed line to log file '{}': {}", path.display(), err);
+            }
+        }
+        if let Some(tx) = &*self.webhook.lock().unwrap() {
+            // The worker only quits once we close the channel, so this never fails
+            let _ = tx.send(line);
+        }
+    }
+
+    /// Writes any incomplete line to the sinks, and waits until every line has been sent to the webhook.
+    ///
+    /// Lines written after this are only written to the log file.
+    pub async fn close(&self) {
+        if let Some(line) = self.pending.lock().unwrap().take() {
+            self.emit(line);
+        }
+
+        // Close the channel to have the worker quit once it sent what is left
+        self.webhook.lock().unwrap().take();
+        let worker: Option<JoinHandle<()>> = self.worker.lock().unwrap().take();
+        if let Some(worker) = worker {
+            if let Err(err) = worker.await {
+                warn!("Failed to wait for webhook to receive all printed lines: {}", err);
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    28 Nov 2022, 15:56:23
 //  Last edited:
-//    07 Nov 2023, 16:29:39
+//    16 Oct 2026, 00:46:40
 //  Auto updated?
 //    Yes
 //
@@ -25,7 +25,9 @@ use specifications::data::DataIndex;
 use specifications::package::PackageIndex;
 use specifications::version::Version;
 
-use crate::errors::HostnameParseError;
+use crate::errors::{AutoCommitParseError, HostnameParseError, OutputFormatParseError};
+use crate::simulation::Simulation;
+use crate::sinks::OutputSinks;
 
 
 /***** STATICS *****/
@@ -140,6 +142,75 @@ impl FromStr for VersionFix {
 
 
 
+/// Names a variable holding an intermediate result that is promoted to a dataset once a workflow is done (i.e., `--auto-commit <VARIABLE>=<DATASET>`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoCommit {
+    /// The name of the (global) variable that holds the intermediate result.
+    pub variable: String,
+    /// The name of the dataset to promote it to.
+    pub dataset:  String,
+}
+impl Display for AutoCommit {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}={}", self.variable, self.dataset) }
+}
+impl FromStr for AutoCommit {
+    type Err = AutoCommitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (variable, dataset): (&str, &str) = match s.split_once('=') {
+            Some(parts) => parts,
+            None => return Err(AutoCommitParseError::MissingSeparator { raw: s.into() }),
+        };
+
+        // The variable is referred to in BraneScript, so it must be an identifier
+        let mut chars = variable.chars();
+        if !matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_') || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AutoCommitParseError::IllegalVariable { raw: variable.into() });
+        }
+        // The dataset is given as a string literal, so it cannot break out of one
+        if dataset.is_empty() || dataset.chars().any(|c| c == '"' || c == '\\' || c.is_control()) {
+            return Err(AutoCommitParseError::IllegalDataset { raw: dataset.into() });
+        }
+
+        Ok(Self { variable: variable.into(), dataset: dataset.into() })
+    }
+}
+
+
+
+/// Defines how `brane run` reports the outcome of a workflow (i.e., `--output <FORMAT>`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Readable text for users, which may also download a dataset that the workflow returned.
+    #[default]
+    Human,
+    /// A single line of JSON with the result of the workflow or why it failed, for scripts.
+    Json,
+}
+impl Display for OutputFormat {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Human => write!(f, "human"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+impl FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            raw => Err(OutputFormatParseError::UnknownFormat { raw: raw.into() }),
+        }
+    }
+}
+
+
+
 /// The global state for the OfflineVm.
 #[derive(Clone, Debug)]
 pub struct GlobalState {
@@ -161,6 +232,11 @@ pub struct GlobalState {
     pub dindex:  Arc<DataIndex>,
     /// A list of results we planned in the previous timestep.
     pub results: Arc<Mutex<HashMap<String, String>>>,
+
+    /// The domains we simulate, if any. If omitted, everything runs on 'localhost'.
+    pub simulation: Option<Arc<Simulation>>,
+    /// The sinks to write printed lines to on top of stdout, if any.
+    pub sinks:      Option<Arc<OutputSinks>>,
 }
 impl CustomGlobalState for GlobalState {}
 
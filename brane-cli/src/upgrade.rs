@@ -4,7 +4,7 @@
 //  Created:
 //    03 Oct 2023, 10:52:44
 //  Last edited:
-//    03 Oct 2023, 11:30:53
+//    16 Oct 2026, 00:18:28
 //  Auto updated?
 //    Yes
 //
@@ -306,6 +306,8 @@ fn upgrade<T: Serialize>(
 /// # Errors
 /// This function may error if we failed to read from disk.
 pub fn data(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: VersionFix) -> Result<(), Error> {
+    use std::collections::HashSet;
+
     use specifications::data::{AccessKind, DataInfo};
     use v1_0_0::data as v1_0_0;
 
@@ -335,6 +337,10 @@ pub fn data(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
                     owners: cfg.owners,
                     description: cfg.description,
                     created: cfg.created,
+                    schema: None,
+                    tags: HashSet::new(),
+                    residency: HashSet::new(),
+                    provenance: None,
                     access: cfg
                         .access
                         .into_iter()
@@ -4,7 +4,7 @@
 //  Created:
 //    21 Feb 2022, 14:43:30
 //  Last edited:
-//    11 Apr 2023, 15:35:16
+//    16 Oct 2026, 02:39:20
 //  Auto updated?
 //    Yes
 //
@@ -18,6 +18,8 @@ use std::fs::{self, File};
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
+use brane_shr::fs::FileLock;
+use brane_shr::suggest::suggest;
 use specifications::package::PackageKind;
 use specifications::version::Version;
 
@@ -236,6 +238,22 @@ pub fn get_history_file() -> Result<PathBuf, UtilError> {
     Ok(config_dir.join("repl_history.txt"))
 }
 
+/// Returns the location of the REPL history file of the given instance.
+///
+/// Every instance has its own history, such that snippets written for one instance (e.g., that refer to its datasets) are not suggested
+/// when connected to another. The global history file (see [`get_history_file()`]) is only used by the local REPL.
+///
+/// # Arguments
+/// - `name`: The name of the instance for which to get the history file.
+///
+/// # Returns
+/// The path of the history file. Note that no guarantees are made about its existance.
+///
+/// # Errors
+/// This function may error if we failed to get the Brane configuration directory.
+#[inline]
+pub fn get_instance_history_file(name: impl AsRef<str>) -> Result<PathBuf, UtilError> { Ok(get_instance_dir(name)?.join("repl_history.txt")) }
+
 /// Makes sure that the history file exists and then returns its path.
 ///
 /// **Arguments**
@@ -448,8 +466,8 @@ pub fn get_package_dir(name: &str, version: Option<&Version>) -> Result<PathBuf,
             },
         };
 
-        // Sort the versions and return the last one
-        versions.sort();
+        // Sort the versions (such that releases come after pre-releases) and return the last one
+        versions.sort_by(Version::cmp_release);
         versions[versions.len() - 1]
     } else {
         // Simply use the given version
@@ -505,7 +523,13 @@ pub fn ensure_package_dir(name: &str, version: Option<&Version>, create: bool) -
                         return Err(UtilError::PackageDirCreateError { package: name.to_string(), path: package_dir, err });
                     }
                 } else {
-                    return Err(UtilError::PackageDirNotFound { package: name.to_string(), path: package_dir });
+                    // Suggest the packages that do exist, in case of a typo
+                    let names: Vec<String> = match package_dir.parent().map(fs::read_dir) {
+                        Some(Ok(entries)) => entries.filter_map(|entry| entry.ok()?.file_name().into_string().ok()).collect(),
+                        _ => vec![],
+                    };
+                    let suggestions: Vec<String> = suggest(name, names.iter().map(String::as_str));
+                    return Err(UtilError::PackageDirNotFound { package: name.to_string(), path: package_dir, suggestions });
                 }
             },
         }
@@ -699,6 +723,38 @@ pub fn get_active_instance_link() -> Result<PathBuf, UtilError> {
     Ok(config_dir.join("active_instance"))
 }
 
+/// Locks the directory where we store instance definitions, such that concurrent `brane` invocations do not modify the instances (or the
+/// active instance link) at the same time.
+///
+/// # Returns
+/// A [`FileLock`] that holds the lock until it is dropped.
+///
+/// # Errors
+/// This function errors if we failed to get or create the instances directory, or if we failed to lock it.
+pub fn lock_instances_dir() -> Result<FileLock, UtilError> {
+    let instances_dir: PathBuf = ensure_instances_dir(true)?;
+    match FileLock::lock_dir("instances", &instances_dir) {
+        Ok(lock) => Ok(lock),
+        Err(err) => Err(UtilError::BraneDirLockError { path: instances_dir, err }),
+    }
+}
+
+/// Locks the directory where we store datasets, such that concurrent `brane` invocations do not add or remove the same datasets at the
+/// same time.
+///
+/// # Returns
+/// A [`FileLock`] that holds the lock until it is dropped.
+///
+/// # Errors
+/// This function errors if we failed to get or create the datasets directory, or if we failed to lock it.
+pub fn lock_datasets_dir() -> Result<FileLock, UtilError> {
+    let datasets_dir: PathBuf = ensure_datasets_dir(true)?;
+    match FileLock::lock_dir("datasets", &datasets_dir) {
+        Ok(lock) => Ok(lock),
+        Err(err) => Err(UtilError::BraneDirLockError { path: datasets_dir, err }),
+    }
+}
+
 
 
 /// Returns an equivalent string to the given one, except that the first letter is capitalized.
@@ -4,7 +4,7 @@
 //  Created:
 //    24 Oct 2022, 15:34:05
 //  Last edited:
-//    31 Jan 2024, 14:23:06
+//    16 Oct 2026, 00:46:40
 //  Auto updated?
 //    Yes
 //
@@ -12,42 +12,90 @@
 //!   The VM for the local use-case is one that simply directly
 //!   interacts with the planner and worker without any complicated
 //!   networking.
+//!
+//!   It can also simulate multiple domains, in which case transfers
+//!   between them are simulated by copying the data.
 //
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine as _;
 use brane_ast::locations::Location;
-use brane_ast::Workflow;
-use brane_exe::errors::VmError;
+use brane_ast::{SymTable, Workflow};
+use brane_exe::errors::{FrameStackError, VmError};
 use brane_exe::pc::ProgramCounter;
-use brane_exe::spec::{RunState, TaskInfo, VmPlugin};
+use brane_exe::spec::{PrintInfo, RunState, TaskInfo, VmPlugin};
 use brane_exe::value::FullValue;
 use brane_exe::Vm;
 use brane_shr::formatters::BlockFormatter;
-use brane_shr::fs::copy_dir_recursively_async;
+use brane_shr::fs::{copy_dir_recursively_async, Error as FsError};
 use brane_tsk::docker::{self, DockerOptions, ExecuteInfo, ImageSource, Network};
 use brane_tsk::errors::{CommitError, ExecuteError, PreprocessError, StdoutError};
 use brane_tsk::spec::{Planner as _, LOCALHOST};
 use brane_tsk::tools::decode_base64;
 use chrono::Utc;
+use console::style;
 use log::{debug, info};
 use parking_lot::Mutex;
 use specifications::container::{Image, VolumeBind};
-use specifications::data::{AccessKind, DataIndex, DataInfo, DataName, PreprocessKind};
+use specifications::data::{AccessKind, DataIndex, DataInfo, DataName, PreprocessKind, Provenance};
 use specifications::package::{PackageIndex, PackageInfo};
 use specifications::profiling::ProfileScopeHandle;
+use specifications::reporting::TaskFailure;
+use tempfile::TempDir;
 use tokio::fs as tfs;
 use tokio::io::AsyncWriteExt;
 
 pub use crate::errors::OfflineVmError as Error;
 use crate::planner::OfflinePlanner;
+use crate::simulation::Simulation;
+use crate::sinks::OutputSinks;
 use crate::spec::{GlobalState, LocalState};
 
 
+/***** CONSTANTS *****/
+/// The directory in the results directory where data transferred to simulated domains ends up.
+const TRANSFERS_DIR: &str = ".transfers";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Simulates transferring a dataset or intermediate result to another domain by copying it.
+///
+/// # Arguments
+/// - `source`: The file or directory to transfer.
+/// - `target`: The directory to transfer it to.
+///
+/// # Returns
+/// The path of the transferred data, which is `target` if `source` is a directory or the file in `target` if it is a file.
+///
+/// # Errors
+/// This function errors if we failed to copy the data.
+async fn simulate_transfer(source: &Path, target: &Path) -> Result<PathBuf, FsError> {
+    if let Err(err) = tfs::create_dir_all(target).await {
+        return Err(FsError::DirCreateError { what: "transfer", path: target.into(), err });
+    }
+    if source.is_file() {
+        let file: PathBuf = target.join(source.file_name().unwrap_or_default());
+        if let Err(err) = tfs::copy(source, &file).await {
+            return Err(FsError::FileCopyError { source: source.into(), target: file, err });
+        }
+        Ok(file)
+    } else {
+        copy_dir_recursively_async(source, target).await?;
+        Ok(target.into())
+    }
+}
+
+
+
+
+
 /***** AUXILLARY *****/
 /// Defines the plugins used that implement offline task execution.
 pub struct OfflinePlugin;
@@ -62,10 +110,10 @@ impl VmPlugin for OfflinePlugin {
     type StdoutError = StdoutError;
 
     async fn preprocess(
-        _global: Arc<RwLock<Self::GlobalState>>,
+        global: Arc<RwLock<Self::GlobalState>>,
         _local: Self::LocalState,
         pc: ProgramCounter,
-        _loc: Location,
+        loc: Location,
         name: DataName,
         preprocess: PreprocessKind,
         _prof: ProfileScopeHandle<'_>,
@@ -75,8 +123,40 @@ impl VmPlugin for OfflinePlugin {
 
         // Match on the type of preprocessing
         match preprocess {
-            // Anything that requires transfers, fails
-            PreprocessKind::TransferRegistryTar { .. } => Err(PreprocessError::UnavailableData { name }),
+            // Anything that requires transfers fails, unless we're simulating domains
+            PreprocessKind::TransferRegistryTar { location, dataname } => {
+                let (simulating, dindex, results_dir): (bool, Arc<DataIndex>, PathBuf) = {
+                    let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+                    (state.simulation.is_some(), state.dindex.clone(), state.results_dir.clone())
+                };
+                if !simulating {
+                    return Err(PreprocessError::UnavailableData { name });
+                }
+
+                // Find the data at the domain that has it
+                let source: PathBuf = match &dataname {
+                    DataName::Data(data) => match dindex.get(data).and_then(|info| info.access.get(&location)) {
+                        Some(AccessKind::File { path }) => path.clone(),
+                        _ => return Err(PreprocessError::UnavailableData { name }),
+                    },
+                    DataName::IntermediateResult(result) => results_dir.join(result),
+                };
+
+                // Copy it to the domain that needs it
+                eprintln!(
+                    "{} Transferring {} '{}' from '{}' to '{}'",
+                    style("[simulation]").dim(),
+                    if dataname.is_data() { "dataset" } else { "intermediate result" },
+                    style(dataname.name()).bold(),
+                    location,
+                    loc
+                );
+                let target: PathBuf = results_dir.join(TRANSFERS_DIR).join(&loc).join(dataname.name());
+                match simulate_transfer(&source, &target).await {
+                    Ok(path) => Ok(AccessKind::File { path }),
+                    Err(err) => Err(PreprocessError::SimulatedTransferError { name, err }),
+                }
+            },
         }
     }
 
@@ -97,10 +177,27 @@ impl VmPlugin for OfflinePlugin {
 
         // First, we query the global state to find the result directory and required indices
         let get = prof.time("Information retrieval");
-        let (docker_opts, package_dir, results_dir, pindex, keep_container): (DockerOptions, PathBuf, PathBuf, Arc<PackageIndex>, bool) = {
+        let (docker_opts, package_dir, results_dir, pindex, keep_container, simulating): (
+            DockerOptions,
+            PathBuf,
+            PathBuf,
+            Arc<PackageIndex>,
+            bool,
+            bool,
+        ) = {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
-            (state.docker_opts.clone(), state.package_dir.clone(), state.results_dir.clone(), state.pindex.clone(), state.keep_containers)
+            (
+                state.docker_opts.clone(),
+                state.package_dir.clone(),
+                state.results_dir.clone(),
+                state.pindex.clone(),
+                state.keep_containers,
+                state.simulation.is_some(),
+            )
         };
+        if simulating {
+            eprintln!("{} Running task '{}' on '{}'", style("[simulation]").dim(), style(info.name).bold(), info.location);
+        }
 
         // Next, we resolve the package
         let pinfo: &PackageInfo =
@@ -110,6 +207,20 @@ impl VmPlugin for OfflinePlugin {
             };
         get.stop();
 
+        // Fetch any inputs that are stored remotely (e.g., in S3), which are removed again once the task is done
+        let _remote_dir: Option<TempDir> = if info.input.values().any(AccessKind::is_remote) {
+            let dir: TempDir = match TempDir::new() {
+                Ok(dir) => dir,
+                Err(err) => {
+                    return Err(ExecuteError::RemoteDirCreateError { err });
+                },
+            };
+            prof.time_fut("remote data fetching", docker::fetch_remote_inputs(&mut info.input, dir.path())).await?;
+            Some(dir)
+        } else {
+            None
+        };
+
         // Resolve the input arguments, generating the folders we have to bind
         let binds: Vec<VolumeBind> = prof
             .time_fut("argument preprocessing", docker::preprocess_args(&mut info.args, &info.input, info.result, None::<String>, results_dir))
@@ -143,6 +254,7 @@ impl VmPlugin for OfflinePlugin {
             binds,
             network: Network::None,
             capabilities: info.requirements.clone(),
+            sandbox: Default::default(),
         };
 
         // We can now execute the task on the local Docker daemon
@@ -158,7 +270,8 @@ impl VmPlugin for OfflinePlugin {
 
         // If the return code is no bueno, error and show stderr
         if code != 0 {
-            return Err(ExecuteError::ExternalCallFailed { name: info.name.into(), image: Box::new(image), code, stdout, stderr });
+            let failure: TaskFailure = TaskFailure::from_output(code, &stdout, &stderr);
+            return Err(ExecuteError::ExternalCallFailed { name: info.name.into(), image: Box::new(image), failure: Box::new(failure) });
         }
 
         // Otherwise, decode the output of branelet to the value returned
@@ -179,8 +292,9 @@ impl VmPlugin for OfflinePlugin {
     }
 
     async fn stdout(
-        _global: &Arc<RwLock<Self::GlobalState>>,
+        global: &Arc<RwLock<Self::GlobalState>>,
         _local: &Self::LocalState,
+        info: &PrintInfo,
         text: &str,
         newline: bool,
         _prof: ProfileScopeHandle<'_>,
@@ -194,6 +308,11 @@ impl VmPlugin for OfflinePlugin {
             println!("{text}");
         }
 
+        // Also write it to the sinks, if any
+        if let Some(sinks) = &global.read().unwrap().sinks {
+            sinks.write(&info.function, info.timestamp, &format!("{}{}", text, if newline { "\n" } else { "" }));
+        }
+
         // Done
         Ok(())
     }
@@ -218,28 +337,34 @@ impl VmPlugin for OfflinePlugin {
     async fn commit(
         global: &Arc<RwLock<Self::GlobalState>>,
         _local: &Self::LocalState,
-        _loc: &Location,
+        loc: &Location,
         name: &str,
         path: &Path,
         data_name: &str,
+        provenance: &Provenance,
         prof: ProfileScopeHandle<'_>,
     ) -> Result<(), Self::CommitError> {
         info!("Committing intermediate result '{}' to '{}' in an offline environment...", name, data_name);
         debug!("Physical file(s): {}", path.display());
 
         // Check the data index to check if it exists or not
+        // (If we're simulating domains, the dataset is committed in the domain where the result is)
         let inf = prof.time("Information retrieval");
-        let (results_dir, dataset_dir, info): (PathBuf, PathBuf, Option<DataInfo>) = {
+        let (results_dir, dataset_dir, location, info): (PathBuf, PathBuf, Location, Option<DataInfo>) = {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
-            (state.results_dir.clone(), state.dataset_dir.clone(), state.dindex.get(data_name).cloned())
+            let (dataset_dir, location): (PathBuf, Location) = match state.simulation.as_ref().and_then(|sim| sim.domains.get(loc)) {
+                Some(domain) => (domain.data_dir.clone(), loc.clone()),
+                None => (state.dataset_dir.clone(), LOCALHOST.into()),
+            };
+            (state.results_dir.clone(), dataset_dir, location, state.dindex.get(data_name).cloned())
         };
         inf.stop();
 
         // Match on whether it already exists or not
         let copy = prof.time("Copying");
-        if let Some(info) = info {
+        if let Some(mut info) = info {
             // Make sure that it has the current location (probably so)
-            if let Some(access) = info.access.get(LOCALHOST) {
+            if let Some(access) = info.access.get(&location) {
                 debug!("Dataset '{}' already exists; overwriting file...", data_name);
 
                 // Copy the source to the target destination (file, in this case)
@@ -250,6 +375,24 @@ impl VmPlugin for OfflinePlugin {
                             return Err(CommitError::DataCopyError { err });
                         }
                     },
+                    AccessKind::S3 { .. } | AccessKind::Url { .. } => {
+                        return Err(CommitError::RemoteDataset { name: data_name.into() });
+                    },
+                }
+
+                // The dataset now has a new origin, so update that if we manage its info file
+                let info_path: PathBuf = dataset_dir.join(data_name).join("data.yml");
+                if info_path.is_file() {
+                    info.provenance = Some(provenance.clone());
+                    let sinfo: String = match serde_yaml::to_string(&info) {
+                        Ok(sinfo) => sinfo,
+                        Err(err) => {
+                            return Err(CommitError::DataInfoSerializeError { err });
+                        },
+                    };
+                    if let Err(err) = tfs::write(&info_path, sinfo).await {
+                        return Err(CommitError::DataInfoWriteError { path: info_path, err });
+                    }
                 }
             } else {
                 return Err(CommitError::UnavailableDataError { name: data_name.into(), locs: info.access.keys().cloned().collect() });
@@ -274,8 +417,12 @@ impl VmPlugin for OfflinePlugin {
                 owners: None,      // TODO: Merge parent datasets??
                 description: None, // TODO: Add parents & algorithm in description??
                 created: Utc::now(),
+                schema: None,
+                tags: HashSet::new(),
+                residency: HashSet::new(),
+                provenance: Some(provenance.clone()),
 
-                access: HashMap::from([("localhost".into(), AccessKind::File { path: dir.join("data") })]),
+                access: HashMap::from([(location, AccessKind::File { path: dir.join("data") })]),
             };
 
             // Write it to the target folder
@@ -361,10 +508,68 @@ impl OfflineVm {
                 pindex: package_index,
                 dindex: data_index,
                 results: Arc::new(Mutex::new(HashMap::new())),
+
+                simulation: None,
+                sinks: None,
             }),
         }
     }
 
+    /// Makes this OfflineVm simulate multiple domains instead of running everything on 'localhost'.
+    ///
+    /// Tasks are then planned on the domain that has most of their inputs, and transfers between domains are simulated by copying the data.
+    /// Note that the VM's data index should contain the datasets of the simulated domains (see [`Simulation::data_index()`]).
+    ///
+    /// # Arguments
+    /// - `simulation`: The [`Simulation`] that describes the domains to simulate.
+    ///
+    /// # Returns
+    /// The same OfflineVm, for chaining.
+    #[inline]
+    pub fn with_simulation(self, simulation: Arc<Simulation>) -> Self {
+        self.state.global.write().unwrap().simulation = Some(simulation);
+        self
+    }
+
+    /// Makes this OfflineVm write what workflows print to the given sinks, on top of stdout.
+    ///
+    /// # Arguments
+    /// - `sinks`: The [`OutputSinks`] to write printed lines to.
+    ///
+    /// # Returns
+    /// The same OfflineVm, for chaining.
+    #[inline]
+    pub fn with_sinks(self, sinks: Arc<OutputSinks>) -> Self {
+        self.state.global.write().unwrap().sinks = Some(sinks);
+        self
+    }
+
+    /// Plans the given workflow for this VM.
+    ///
+    /// # Arguments
+    /// - `workflow`: The Workflow to plan.
+    ///
+    /// # Returns
+    /// The planned workflow, i.e., with every task assigned a location and every input its availability.
+    ///
+    /// # Errors
+    /// This function errors if the workflow could not be planned (e.g., because some dataset is unavailable).
+    pub async fn plan(&self, workflow: Workflow) -> Result<Workflow, Error> {
+        let planner: OfflinePlanner = {
+            let state: RwLockReadGuard<GlobalState> = self.state.global.read().unwrap();
+
+            // Plan with the previous results
+            match &state.simulation {
+                Some(simulation) => OfflinePlanner::simulating(state.dindex.clone(), state.results.clone(), simulation.locations()),
+                None => OfflinePlanner::new(state.dindex.clone(), state.results.clone()),
+            }
+        };
+        match planner.plan(workflow).await {
+            Ok(plan) => Ok(plan),
+            Err(err) => Err(Error::PlanError { err }),
+        }
+    }
+
     /// Runs the given workflow on this VM.
     ///
     /// There is a bit of ownership awkwardness going on, but that's due to the need for the struct to outlive threads.
@@ -376,26 +581,23 @@ impl OfflineVm {
     /// The result of the workflow, if any. It also returns `self` again for subsequent runs.
     pub async fn exec(self, workflow: Workflow) -> (Self, Result<FullValue, Error>) {
         // Step 1: Plan
-        let plan: Result<Workflow, Error> = {
-            let planner: OfflinePlanner = {
-                let state: RwLockReadGuard<GlobalState> = self.state.global.read().unwrap();
-
-                // Plan with the previous results
-                OfflinePlanner::new(state.dindex.clone(), state.results.clone())
-            };
-            match planner.plan(workflow).await {
-                Ok(plan) => Ok(plan),
-                Err(err) => Err(Error::PlanError { err }),
-            }
-        };
-        // Unwrap the result (necessary to avoid borrowing conflicts with the lock)
-        let plan: Workflow = match plan {
+        let plan: Workflow = match self.plan(workflow).await {
             Ok(plan) => plan,
             Err(err) => return (self, Err(err)),
         };
 
+        // Step 2 & 3: Execution & result
+        self.exec_plan(plan).await
+    }
 
-
+    /// Runs the given, already planned workflow on this VM.
+    ///
+    /// # Arguments
+    /// - `plan`: The Workflow to execute, as returned by [`OfflineVm::plan()`].
+    ///
+    /// # Returns
+    /// The result of the workflow, if any. It also returns `self` again for subsequent runs.
+    pub async fn exec_plan(self, plan: Workflow) -> (Self, Result<FullValue, Error>) {
         // Step 2: Execution
         // Now wrap ourselves in a lock so that we can run the internal vm
         let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
@@ -427,6 +629,50 @@ impl OfflineVm {
     /// Returns the path to the internal temporary folder for results.
     #[inline]
     pub fn results_dir(&self) -> PathBuf { self.state.global.read().unwrap().results_dir.clone() }
+
+    /// Returns the values of the global variables defined by the workflows run so far.
+    ///
+    /// # Returns
+    /// A map of the definition index of every variable with a value to that value.
+    pub fn variables(&self) -> HashMap<usize, FullValue> {
+        let table: &SymTable = self.state.fstack.table();
+        self.state.fstack.globals().map(|(def, value)| (def, value.to_full(table))).collect()
+    }
+
+    /// Returns the intermediate results produced by the workflows run so far.
+    ///
+    /// # Returns
+    /// A map of the names of the results to the location where they live.
+    #[inline]
+    pub fn results(&self) -> HashMap<String, Location> { self.state.global.read().unwrap().results.lock().clone() }
+
+    /// Restores the global variables and intermediate results of a previous session with this VM.
+    ///
+    /// # Arguments
+    /// - `table`: The [`SymTable`] that defines the variables (i.e., the one of the restored compile state).
+    /// - `variables`: The values of the variables to restore, mapped by their definition index.
+    /// - `results`: The intermediate results to restore, mapped to the location where they live. They are expected to be in the results directory of this VM.
+    ///
+    /// # Errors
+    /// This function errors if any of the variables is already declared or if its value does not match its type.
+    ///
+    /// # Panics
+    /// This function panics if any of the variables is unknown to the given table.
+    pub fn restore(
+        &mut self,
+        table: SymTable,
+        variables: HashMap<usize, FullValue>,
+        results: HashMap<String, Location>,
+    ) -> Result<(), FrameStackError> {
+        let table: Arc<SymTable> = Arc::new(table);
+        self.state.fstack.update_table(table.clone());
+        for (def, value) in variables {
+            self.state.fstack.declare(def)?;
+            self.state.fstack.set(def, value.into_value(&table))?;
+        }
+        self.state.global.read().unwrap().results.lock().extend(results);
+        Ok(())
+    }
 }
 
 impl Vm for OfflineVm {
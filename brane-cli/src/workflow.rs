@@ -0,0 +1,703 @@
+//  WORKFLOW.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:18:04
+//  Last edited:
+//    16 Oct 2026, 00:46:40
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `brane workflow`-subcommands, which work on compiled
+//!   workflows without running them on an instance (e.g., checking or
+//!   visualizing them against a local policy, exporting them to CWL or
+//!   running them locally on simulated domains).
+//
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+use brane_ast::ast::{Edge, TaskDef};
+use brane_ast::func_id::FunctionId;
+use brane_ast::locations::Location;
+use brane_ast::traversals::print::cwl;
+use brane_ast::traversals::print::dot::{self, Annotations, Verdict};
+use brane_ast::{CompileResult, Workflow};
+use brane_dsl::{Language, ParserOptions};
+use brane_exe::FullValue;
+use brane_tsk::docker::DockerOptions;
+use console::style;
+use error_trace::trace;
+use log::{debug, info, warn};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use specifications::address::Address;
+use specifications::data::{AvailabilityKind, DataIndex, DataName, PreprocessKind};
+use specifications::package::PackageIndex;
+use tempfile::TempDir;
+
+pub use crate::errors::WorkflowError as Error;
+use crate::simulation::Simulation;
+use crate::sinks::OutputSinks;
+use crate::utils::{ensure_datasets_dir, ensure_packages_dir};
+use crate::vm::OfflineVm;
+
+
+/***** CONSTANTS *****/
+/// The version of the eFLINT JSON specification that we talk to the reasoner.
+const EFLINT_JSON_VERSION: &str = "0.1.0";
+
+/// The user that is injected as end user of workflows when none is given.
+const DEFAULT_USER: &str = "local";
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A task call in a (planned) workflow.
+struct TaskCall<'w> {
+    /// The program counter of the call (as `<func>:<edge>`).
+    node:  String,
+    /// The name of the called task.
+    name:  String,
+    /// Where the call is planned, if it is.
+    at:    Option<&'w Location>,
+    /// The inputs of the call, together with how they are made available.
+    input: &'w HashMap<DataName, Option<AvailabilityKind>>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads the input source text from a file or stdin.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+///
+/// # Returns
+/// A pair of a description of the input (used for debugging) and the source text itself.
+///
+/// # Errors
+/// This function errors if we failed to read the input.
+fn read_input(file: String) -> Result<(String, String), Error> {
+    debug!("Loading input from '{file}'...");
+    if file == "-" {
+        let mut source: String = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut source) {
+            return Err(Error::InputStdinRead { err });
+        }
+        Ok(("<stdin>".into(), source))
+    } else {
+        match fs::read_to_string(&file) {
+            Ok(source) => Ok((file, source)),
+            Err(err) => Err(Error::InputFileRead { path: file.into(), err }),
+        }
+    }
+}
+
+/// Reads the indices of the locally available packages and datasets.
+///
+/// # Returns
+/// A pair of the local [`PackageIndex`] and [`DataIndex`].
+///
+/// # Errors
+/// This function errors if we failed to find or read the local packages or datasets.
+fn local_indices() -> Result<(PackageIndex, DataIndex), Error> {
+    let packages_dir: PathBuf = match ensure_packages_dir(false) {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::PackagesDir { err }),
+    };
+    let datasets_dir: PathBuf = match ensure_datasets_dir(false) {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::DatasetsDir { err }),
+    };
+    let pindex: PackageIndex = match brane_tsk::local::get_package_index(packages_dir) {
+        Ok(index) => index,
+        Err(err) => return Err(Error::LocalPackageIndex { err }),
+    };
+    let dindex: DataIndex = match brane_tsk::local::get_data_index(datasets_dir) {
+        Ok(index) => index,
+        Err(err) => return Err(Error::LocalDataIndex { err }),
+    };
+    Ok((pindex, dindex))
+}
+
+/// Compiles the given source text against the given packages and datasets.
+///
+/// # Arguments
+/// - `input`: Some description of where the input comes from (used for debugging). If it is a path, imported files are resolved relative to it.
+/// - `source`: The raw source text.
+/// - `language`: The [`Language`] as which to parse the `source` text.
+/// - `user`: The end user of the workflow result.
+/// - `pindex`: The [`PackageIndex`] with the packages that the workflow may use.
+/// - `dindex`: The [`DataIndex`] with the datasets that the workflow may use.
+///
+/// # Returns
+/// A compiled [`Workflow`].
+///
+/// Note that this already printed any warnings or errors.
+///
+/// # Errors
+/// This function errors if the input was not valid BraneScript/Bakery.
+fn compile(input: &str, source: String, language: Language, user: String, pindex: &PackageIndex, dindex: &DataIndex) -> Result<Workflow, Error> {
+    let mut options: ParserOptions = ParserOptions::new(language);
+    if input != "<stdin>" {
+        if let Some(dir) = Path::new(input).parent() {
+            options = options.with_base_dir(dir);
+        }
+    }
+    match brane_ast::compile_program(source.as_bytes(), pindex, dindex, &options) {
+        CompileResult::Workflow(mut wf, warns) => {
+            for warn in warns {
+                warn.prettyprint(input, &source);
+            }
+            wf.user = Arc::new(Some(user));
+            Ok(wf)
+        },
+        CompileResult::Err(errs) => {
+            for err in errs {
+                err.prettyprint(input, &source);
+            }
+            Err(Error::AstCompile { input: input.into() })
+        },
+        CompileResult::Eof(err) => {
+            err.prettyprint(input, source);
+            Err(Error::AstCompile { input: input.into() })
+        },
+
+        // The rest does not occur for this variation of the function
+        CompileResult::Program(_, _) | CompileResult::Unresolved(_, _) => unreachable!(),
+    }
+}
+
+/// Reads a policy file as a list of eFLINT JSON phrases, compiling it first if it's written in eFLINT.
+///
+/// # Arguments
+/// - `path`: The path to the policy file. Its language is derived from its extension (`.eflint` or `.json`).
+///
+/// # Returns
+/// The phrases in the policy.
+///
+/// # Errors
+/// This function errors if we failed to read or compile the policy.
+async fn read_policy(path: &Path) -> Result<Vec<Value>, Error> {
+    let json: Vec<u8> = match path.extension().and_then(OsStr::to_str) {
+        Some("eflint") => {
+            debug!("Compiling eFLINT policy '{}' to eFLINT JSON...", path.display());
+            let mut json: Vec<u8> = Vec::new();
+            if let Err(err) = eflint_to_json::compile_async(path, &mut json, None).await {
+                return Err(Error::PolicyCompile { path: path.into(), err });
+            }
+            json
+        },
+        Some("json") => {
+            debug!("Reading eFLINT JSON policy '{}'...", path.display());
+            match tokio::fs::read(path).await {
+                Ok(json) => json,
+                Err(err) => return Err(Error::PolicyRead { path: path.into(), err }),
+            }
+        },
+        _ => return Err(Error::PolicyExtension { path: path.into() }),
+    };
+
+    // A policy is a full request, of which we only need the phrases
+    #[derive(serde::Deserialize)]
+    struct Phrases {
+        phrases: Vec<Value>,
+    }
+    match serde_json::from_slice::<Phrases>(&json) {
+        Ok(policy) => Ok(policy.phrases),
+        Err(err) => Err(Error::PolicyDeserialize { path: path.into(), err }),
+    }
+}
+
+/// Compiles a workflow to eFLINT JSON phrases the same way a checker does.
+///
+/// # Arguments
+/// - `input`: Some description of where the workflow comes from (used for debugging).
+/// - `workflow`: The [`Workflow`] to compile.
+///
+/// # Returns
+/// The phrases that state the workflow as facts.
+///
+/// # Errors
+/// This function errors if the workflow could not be represented in the checker's format.
+fn compile_workflow(input: &str, workflow: &Workflow) -> Result<Vec<Value>, Error> {
+    // The checker's compiler is built against upstream's AST, so we hand it our workflow through its (stable) serialized form
+    let wir: chk_ast::Workflow = match serde_json::to_value(workflow).and_then(serde_json::from_value) {
+        Ok(wir) => wir,
+        Err(err) => return Err(Error::WorkflowSerialize { input: input.into(), err }),
+    };
+    let wf: chk_workflow::Workflow = match chk_workflow::Workflow::try_from(wir) {
+        Ok(wf) => wf,
+        Err(err) => return Err(Error::WorkflowConvert { input: input.into(), err }),
+    };
+    match serde_json::to_value(wf.to_eflint()) {
+        Ok(Value::Array(phrases)) => Ok(phrases),
+        Ok(_) => unreachable!(),
+        Err(err) => Err(Error::WorkflowSerialize { input: input.into(), err }),
+    }
+}
+
+/// Creates the eFLINT JSON query for a fact about the given workflow.
+///
+/// # Arguments
+/// - `fact`: The fact to query (e.g., `workflow-to-execute`).
+/// - `workflow`: The ID of the workflow.
+/// - `node`: The program counter of a task call in the workflow (as `<func>:<edge>`) if the fact is about one.
+/// - `data`: The name of a dataset or result if the fact is about transferring one to `node`.
+///
+/// # Returns
+/// A `bquery`-phrase for the fact.
+fn query(fact: &str, workflow: &str, node: Option<&str>, data: Option<&str>) -> Value {
+    let mut operand: Value = json!({ "identifier": "workflow", "operands": [workflow] });
+    if let Some(node) = node {
+        operand = json!({ "identifier": "node", "operands": [operand, node] });
+    }
+    if let Some(data) = data {
+        operand = json!({ "identifier": "node-input", "operands": [operand, { "identifier": "asset", "operands": [data] }] });
+    }
+    json!({ "kind": "bquery", "expression": { "identifier": fact, "operands": [operand] } })
+}
+
+/// Asks the reasoner about the given phrases and interprets its answer.
+///
+/// # Arguments
+/// - `reasoner`: The address of the eFLINT reasoner to ask.
+/// - `phrases`: The phrases to send it, of which the last is expected to be a query.
+///
+/// # Returns
+/// Whether the query holds, together with any violations that occurred while reasoning.
+///
+/// # Errors
+/// This function errors if we failed to reach the reasoner, or if it failed to reason about the phrases.
+async fn reason(reasoner: &Address, phrases: Vec<Value>) -> Result<(bool, Vec<String>), Error> {
+    let body: Value = json!({ "version": EFLINT_JSON_VERSION, "kind": "phrases", "phrases": phrases, "updates": true });
+    debug!("Sending {} phrase(s) to reasoner '{}'...", body["phrases"].as_array().map(Vec::len).unwrap_or(0), reasoner);
+    let res: reqwest::Response = match reqwest::Client::new().post(reasoner.to_string()).json(&body).send().await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::ReasonerRequest { address: reasoner.clone(), err }),
+    };
+    let code: StatusCode = res.status();
+    if !code.is_success() {
+        return Err(Error::ReasonerFailure { address: reasoner.clone(), code, message: res.text().await.ok() });
+    }
+    let res: Value = match res.json().await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::ReasonerResponse { address: reasoner.clone(), err }),
+    };
+
+    // Interpret it
+    if res["success"] == Value::Bool(false) {
+        let errors: Vec<String> = res["errors"].as_array().map(|errs| errs.iter().map(Value::to_string).collect()).unwrap_or_default();
+        return Err(Error::ReasonerRejected { address: reasoner.clone(), errors });
+    }
+    let results: &[Value] = res["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut reasons: Vec<String> = vec![];
+    for result in results {
+        if result["violated"] == Value::Bool(true) {
+            match result["violations"].as_array() {
+                Some(violations) => reasons.extend(violations.iter().map(|v| format!("Violated {v}"))),
+                None => reasons.push("A violation occurred".into()),
+            }
+        }
+    }
+    Ok((results.last().map(|result| result["result"] == Value::Bool(true)).unwrap_or(false), reasons))
+}
+
+/// Finds the verdict of the reasoner on the given query.
+///
+/// # Arguments
+/// - `reasoner`: The address of the eFLINT reasoner to ask.
+/// - `phrases`: The policy and workflow phrases that the query is about.
+/// - `query`: The query to ask.
+///
+/// # Returns
+/// The [`Verdict`] of the reasoner. Queries the reasoner could not reason about need review.
+///
+/// # Errors
+/// This function errors if we failed to reach the reasoner.
+async fn verdict(reasoner: &Address, phrases: &[Value], query: Value) -> Result<Verdict, Error> {
+    let mut phrases: Vec<Value> = phrases.to_vec();
+    phrases.push(query);
+    match reason(reasoner, phrases).await {
+        Ok((true, violations)) if violations.is_empty() => Ok(Verdict::Allow),
+        Ok((true, _)) => Ok(Verdict::NeedsReview),
+        Ok((false, _)) => Ok(Verdict::Deny),
+        Err(err @ Error::ReasonerRejected { .. }) => {
+            warn!("{}", trace!(("Reasoner could not reason about query"), err));
+            Ok(Verdict::NeedsReview)
+        },
+        Err(err) => Err(err),
+    }
+}
+
+/// Collects the task calls in a (planned) workflow.
+///
+/// # Arguments
+/// - `workflow`: The [`Workflow`] to collect the task calls of.
+///
+/// # Returns
+/// A list of every [`TaskCall`] in the workflow.
+fn task_calls(workflow: &Workflow) -> Vec<TaskCall> {
+    let mut calls: Vec<TaskCall> = vec![];
+    let funcs = workflow.funcs.iter().map(|(id, edges)| (FunctionId::Func(*id), edges));
+    for (func, edges) in std::iter::once((FunctionId::Main, &*workflow.graph)).chain(funcs) {
+        for (i, edge) in edges.iter().enumerate() {
+            if let Edge::Node { task, at, input, .. } = edge {
+                let name: String = match workflow.table.task(*task) {
+                    TaskDef::Compute(def) => format!("{}::{}", def.package, def.function.name),
+                    TaskDef::Transfer => "__builtin::transfer".into(),
+                };
+                calls.push(TaskCall { node: format!("{func}:{i}"), name, at: at.as_ref(), input });
+            }
+        }
+    }
+    calls
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Handles the `brane workflow check`-subcommand, which validates a workflow against a local policy file instead of the policies of an
+/// instance.
+///
+/// This compiles the workflow using only locally available packages and datasets, and states it as facts in the same way that checkers do.
+/// Together with the policy, these are then given to an eFLINT reasoner (e.g., one started locally) to find whether the policy allows it.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `against`: The policy file to check the workflow against. May be eFLINT (`.eflint`) or eFLINT JSON (`.json`).
+/// - `reasoner`: The address of the eFLINT reasoner to use.
+/// - `user`: The end user of the workflow result, if any.
+///
+/// # Errors
+/// This function errors if we failed to perform the check. Note that a denied workflow is not an error.
+pub async fn check(file: String, language: Language, against: PathBuf, reasoner: Address, user: Option<String>) -> Result<(), Error> {
+    info!("Handling 'brane workflow check {}' against '{}'", if file == "-" { "<stdin>" } else { file.as_str() }, against.display());
+
+    // Compile both the policy and the workflow
+    let (input, source): (String, String) = read_input(file)?;
+    let (pindex, dindex): (PackageIndex, DataIndex) = local_indices()?;
+    let workflow: Workflow = compile(&input, source, language, user.unwrap_or_else(|| DEFAULT_USER.into()), &pindex, &dindex)?;
+    let mut phrases: Vec<Value> = read_policy(&against).await?;
+    phrases.extend(compile_workflow(&input, &workflow)?);
+
+    // Ask if the workflow may be executed
+    phrases.push(query("workflow-to-execute", &workflow.id, None, None));
+    let (holds, mut reasons): (bool, Vec<String>) = reason(&reasoner, phrases).await?;
+    if !holds {
+        reasons.push("The workflow is not permitted to be executed".into());
+    }
+    match if reasons.is_empty() { None } else { Some(reasons) } {
+        None => {
+            println!("Workflow {} is {} by policy {}", style(&workflow.id).bold().cyan(), style("accepted").bold().green(), against.display());
+        },
+        Some(reasons) => {
+            println!("Workflow {} is {} by policy {}", style(&workflow.id).bold().cyan(), style("rejected").bold().red(), against.display());
+            println!("   Reasons for denial:");
+            for reason in reasons {
+                println!("    - {}", style(reason).bold());
+            }
+        },
+    }
+    println!();
+
+    // Either way, the check itself was a success
+    Ok(())
+}
+
+
+
+/// Handles the `brane workflow visualize`-subcommand, which renders a workflow as a Graphviz graph of its tasks and the datasets transferred to
+/// them.
+///
+/// If a policy is given, every task call and transfer is colored by the verdict of the policy on it, in the same way as in
+/// [`check()`]: green if allowed, red if denied and orange if it needs review (i.e., it is allowed but a violation occurred, or the reasoner
+/// could not reason about it).
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `against`: The policy file to color the workflow with, if any. May be eFLINT (`.eflint`) or eFLINT JSON (`.json`).
+/// - `reasoner`: The address of the eFLINT reasoner to use.
+/// - `user`: The end user of the workflow result, if any.
+/// - `output`: The file to write the graph to. If omitted, writes it to stdout.
+///
+/// # Errors
+/// This function errors if we failed to compile the workflow, ask the reasoner or write the graph.
+pub async fn visualize(
+    file: String,
+    language: Language,
+    against: Option<PathBuf>,
+    reasoner: Address,
+    user: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<(), Error> {
+    info!("Handling 'brane workflow visualize {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+
+    // Compile the workflow
+    let (input, source): (String, String) = read_input(file)?;
+    let (pindex, dindex): (PackageIndex, DataIndex) = local_indices()?;
+    let workflow: Workflow = compile(&input, source, language, user.unwrap_or_else(|| DEFAULT_USER.into()), &pindex, &dindex)?;
+
+    // Ask the reasoner about every task call and transfer in it if there is a policy
+    let mut annotations: Annotations = Annotations::default();
+    if let Some(against) = against {
+        let mut phrases: Vec<Value> = read_policy(&against).await?;
+        phrases.extend(compile_workflow(&input, &workflow)?);
+
+        let funcs = workflow.funcs.iter().map(|(id, edges)| (FunctionId::Func(*id), edges));
+        for (func, edges) in std::iter::once((FunctionId::Main, &*workflow.graph)).chain(funcs) {
+            for (i, edge) in edges.iter().enumerate() {
+                if let Edge::Node { input, .. } = edge {
+                    let node: String = format!("{func}:{i}");
+                    debug!("Asking reasoner about task call {node}...");
+                    let task: Verdict = verdict(&reasoner, &phrases, query("task-to-execute", &workflow.id, Some(&node), None)).await?;
+                    annotations.tasks.insert((func, i), task);
+                    for name in input.keys() {
+                        let fact: &str = if name.is_data() { "dataset-to-transfer" } else { "result-to-transfer" };
+                        let transfer: Verdict = verdict(&reasoner, &phrases, query(fact, &workflow.id, Some(&node), Some(name.name()))).await?;
+                        annotations.transfers.insert((func, i, name.clone()), transfer);
+                    }
+                }
+            }
+        }
+    }
+
+    // Render it
+    let res: Result<(), Vec<brane_ast::Error>> = match &output {
+        Some(path) => match fs::File::create(path) {
+            Ok(handle) => dot::do_traversal(&workflow, &annotations, handle),
+            Err(err) => return Err(Error::OutputCreate { path: path.clone(), err }),
+        },
+        None => dot::do_traversal(&workflow, &annotations, io::stdout()),
+    };
+    if let Err(mut errs) = res {
+        let path: PathBuf = output.unwrap_or_else(|| "<stdout>".into());
+        return Err(Error::OutputWrite { path, err: errs.swap_remove(0) });
+    }
+    Ok(())
+}
+
+
+
+/// Handles the `brane workflow export`-subcommand, which exports a workflow to the Common Workflow Language (CWL).
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `user`: The end user of the workflow result, if any.
+/// - `output`: The file to write the CWL workflow to. If omitted, writes it to stdout.
+///
+/// # Errors
+/// This function errors if we failed to compile the workflow or write the CWL workflow.
+pub fn export(file: String, language: Language, user: Option<String>, output: Option<PathBuf>) -> Result<(), Error> {
+    info!("Handling 'brane workflow export {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+
+    // Compile the workflow
+    let (input, source): (String, String) = read_input(file)?;
+    let (pindex, dindex): (PackageIndex, DataIndex) = local_indices()?;
+    let workflow: Workflow = compile(&input, source, language, user.unwrap_or_else(|| DEFAULT_USER.into()), &pindex, &dindex)?;
+
+    // Export it
+    let res: Result<(), Vec<brane_ast::Error>> = match &output {
+        Some(path) => match fs::File::create(path) {
+            Ok(handle) => cwl::do_traversal(&workflow, handle),
+            Err(err) => return Err(Error::OutputCreate { path: path.clone(), err }),
+        },
+        None => cwl::do_traversal(&workflow, io::stdout()),
+    };
+    if let Err(mut errs) = res {
+        let path: PathBuf = output.unwrap_or_else(|| "<stdout>".into());
+        return Err(Error::OutputWrite { path, err: errs.swap_remove(0) });
+    }
+    Ok(())
+}
+
+
+
+/// Handles the `brane workflow run --simulate-domains`-subcommand, which runs a workflow locally while emulating multiple domains.
+///
+/// Every domain has its own datasets and, optionally, its own policy (see [`Simulation`]). Tasks are planned on the domain that has most of
+/// their inputs, and inputs that live in other domains are transferred by copying them. Before running anything, the policy of every
+/// domain involved is asked (in the same way as [`check()`]) whether it allows the workflow, its task calls on that domain and the
+/// transfers from it. The workflow is only run if no domain denies it.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `simulate_domains`: The `infra.yml` file that defines the domains to simulate.
+/// - `reasoner`: The address of the eFLINT reasoner to use for the domains' policies.
+/// - `user`: The end user of the workflow result, if any.
+/// - `docker_opts`: The configuration of our Docker client.
+/// - `keep_containers`: Whether to keep the containers after execution or not.
+/// - `sinks`: If given, the [`OutputSinks`] to write what the workflow prints to on top of stdout.
+///
+/// # Errors
+/// This function errors if we failed to load the domains, compile the workflow, ask the reasoner or run the workflow, or if any domain denies
+/// the workflow.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    file: String,
+    language: Language,
+    simulate_domains: PathBuf,
+    reasoner: Address,
+    user: Option<String>,
+    docker_opts: DockerOptions,
+    keep_containers: bool,
+    sinks: Option<Arc<OutputSinks>>,
+) -> Result<(), Error> {
+    info!(
+        "Handling 'brane workflow run {}' on simulated domains '{}'",
+        if file == "-" { "<stdin>" } else { file.as_str() },
+        simulate_domains.display()
+    );
+
+    // Load the domains and their datasets
+    let simulation: Arc<Simulation> = match Simulation::from_path(&simulate_domains) {
+        Ok(simulation) => Arc::new(simulation),
+        Err(err) => return Err(Error::Simulation { err }),
+    };
+    let dindex: DataIndex = match simulation.data_index() {
+        Ok(dindex) => dindex,
+        Err(err) => return Err(Error::Simulation { err }),
+    };
+
+    // Compile the workflow against the local packages and the domains' datasets
+    let (input, source): (String, String) = read_input(file)?;
+    let (pindex, _): (PackageIndex, DataIndex) = local_indices()?;
+    let workflow: Workflow = compile(&input, source, language, user.unwrap_or_else(|| DEFAULT_USER.into()), &pindex, &dindex)?;
+
+    // Prepare a VM that simulates the domains, and plan the workflow with it
+    let packages_dir: PathBuf = match ensure_packages_dir(false) {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::PackagesDir { err }),
+    };
+    let datasets_dir: PathBuf = match ensure_datasets_dir(false) {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::DatasetsDir { err }),
+    };
+    let results_dir: TempDir = match TempDir::new() {
+        Ok(dir) => dir,
+        Err(err) => return Err(Error::ResultsDirCreate { err }),
+    };
+    let mut vm: OfflineVm =
+        OfflineVm::new(docker_opts, keep_containers, packages_dir, datasets_dir, results_dir.path(), Arc::new(pindex), Arc::new(dindex))
+            .with_simulation(simulation.clone());
+    if let Some(sinks) = &sinks {
+        vm = vm.with_sinks(sinks.clone());
+    }
+    let plan: Workflow = match vm.plan(workflow).await {
+        Ok(plan) => plan,
+        Err(err) => return Err(Error::WorkflowRun { input, err }),
+    };
+    let calls: Vec<TaskCall> = task_calls(&plan);
+    println!("Planned workflow {} on {} simulated domain(s):", style(&plan.id).bold().cyan(), simulation.domains.len());
+    for call in &calls {
+        println!("  - {} ({}) @ {}", style(&call.name).bold(), call.node, style(call.at.map(String::as_str).unwrap_or("<unplanned>")).bold().cyan());
+        for (data, avail) in call.input {
+            if let Some(AvailabilityKind::Unavailable { how: PreprocessKind::TransferRegistryTar { location, .. } }) = avail {
+                println!("      transfers {} '{}' from {}", if data.is_data() { "dataset" } else { "result" }, data.name(), location);
+            }
+        }
+    }
+    println!();
+
+    // Ask every domain's policy about the parts of the workflow that involve it
+    let mut denied: Vec<String> = vec![];
+    for (domain, info) in &simulation.domains {
+        // Find what the domain is asked to do
+        let tasks: Vec<&str> = calls.iter().filter(|call| call.at == Some(domain)).map(|call| call.node.as_str()).collect();
+        let mut transfers: Vec<(&str, &DataName)> = vec![];
+        for call in &calls {
+            for (data, avail) in call.input {
+                if let Some(AvailabilityKind::Unavailable { how: PreprocessKind::TransferRegistryTar { location, .. } }) = avail {
+                    if location == domain {
+                        transfers.push((call.node.as_str(), data));
+                    }
+                }
+            }
+        }
+        if tasks.is_empty() && transfers.is_empty() {
+            debug!("Simulated domain '{domain}' is not involved in the workflow");
+            continue;
+        }
+        let policy: &Path = match &info.policy {
+            Some(policy) => policy,
+            None => {
+                println!("Domain {} has no policy; allowing everything", style(domain).bold().cyan());
+                continue;
+            },
+        };
+
+        // Ask the reasoner
+        let mut phrases: Vec<Value> = read_policy(policy).await?;
+        phrases.extend(compile_workflow(&input, &plan)?);
+        let mut reasons: Vec<String> = vec![];
+        if verdict(&reasoner, &phrases, query("workflow-to-execute", &plan.id, None, None)).await? == Verdict::Deny {
+            reasons.push("The workflow is not permitted to be executed".into());
+        }
+        for node in tasks {
+            if verdict(&reasoner, &phrases, query("task-to-execute", &plan.id, Some(node), None)).await? == Verdict::Deny {
+                reasons.push(format!("Task call {node} is not permitted to be executed"));
+            }
+        }
+        for (node, data) in transfers {
+            let fact: &str = if data.is_data() { "dataset-to-transfer" } else { "result-to-transfer" };
+            if verdict(&reasoner, &phrases, query(fact, &plan.id, Some(node), Some(data.name()))).await? == Verdict::Deny {
+                reasons.push(format!("Transferring '{}' for task call {} is not permitted", data.name(), node));
+            }
+        }
+        if reasons.is_empty() {
+            println!("Domain {} {} the workflow", style(domain).bold().cyan(), style("accepts").bold().green());
+        } else {
+            println!("Domain {} {} the workflow", style(domain).bold().cyan(), style("rejects").bold().red());
+            println!("   Reasons for denial:");
+            for reason in reasons {
+                println!("    - {}", style(reason).bold());
+            }
+            denied.push(domain.clone());
+        }
+    }
+    println!();
+    if !denied.is_empty() {
+        return Err(Error::SimulationDenied { domains: denied });
+    }
+
+    // Run it
+    let (_, res): (OfflineVm, Result<FullValue, _>) = vm.exec_plan(plan).await;
+    if let Some(sinks) = &sinks {
+        sinks.close().await;
+    }
+    let result: FullValue = match res {
+        Ok(result) => result,
+        Err(err) => return Err(Error::WorkflowRun { input, err }),
+    };
+    if result != FullValue::Void {
+        println!("\nWorkflow returned value {}", style(format!("'{result}'")).bold().cyan());
+        match result {
+            FullValue::IntermediateResult(_) => println!("(Intermediate results are not available; promote it using 'commit_result()')"),
+            FullValue::Data(name) => {
+                if let Some(info) = simulation.data_index().ok().and_then(|index| index.get(&name).cloned()) {
+                    for (domain, access) in info.access {
+                        println!("(It's available in domain '{domain}' as '{access}')");
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,387 @@
+//  CLIENT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:17:04
+//  Last edited:
+//    15 Oct 2026, 23:17:04
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the [`Client`], which is the entrypoint to talking to a
+//!   Brane instance, and the [`ClientBuilder`] that configures it.
+//
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr as _;
+
+use brane_ast::ParserOptions;
+use brane_tsk::spec::{AppId, LOCALHOST};
+use log::debug;
+use reqwest::{Proxy, Response};
+use specifications::data::{AccessKind, DataIndex, DataInfo};
+use specifications::driving::{CreateSessionRequest, DriverServiceClient, ListSessionsRequest, SessionInfo};
+use specifications::errors::ErrorCode;
+use specifications::package::PackageIndex;
+
+use crate::data;
+use crate::errors::{ApiError, BuildError, DownloadError, SessionError};
+use crate::session::Session;
+
+
+/***** LIBRARY *****/
+/// Configures a new [`Client`].
+///
+/// Only the endpoints of `brane-api` and `brane-drv` are required; see [`Client::builder()`] for an example.
+#[derive(Clone, Debug)]
+pub struct ClientBuilder {
+    /// The address of the `brane-api` service.
+    api:       Option<String>,
+    /// The address of the `brane-drv` service.
+    drv:       Option<String>,
+    /// The directory with the certificates of the domains, if any.
+    certs_dir: Option<PathBuf>,
+    /// The address to proxy data transfers through, if any.
+    proxy:     Option<String>,
+    /// The user that receives workflow results, if known.
+    user:      Option<String>,
+    /// The options with which to compile snippets.
+    options:   ParserOptions,
+}
+
+impl Default for ClientBuilder {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl ClientBuilder {
+    /// Constructor for the ClientBuilder.
+    ///
+    /// # Returns
+    /// A new ClientBuilder without any endpoints, that compiles BraneScript.
+    #[inline]
+    pub fn new() -> Self { Self { api: None, drv: None, certs_dir: None, proxy: None, user: None, options: ParserOptions::bscript() } }
+
+    /// Sets the address of the instance's `brane-api` service (e.g., `http://localhost:50051`).
+    #[inline]
+    pub fn api(mut self, endpoint: impl Into<String>) -> Self {
+        self.api = Some(endpoint.into());
+        self
+    }
+
+    /// Sets the address of the instance's `brane-drv` service (e.g., `grpc://localhost:50053`).
+    #[inline]
+    pub fn drv(mut self, endpoint: impl Into<String>) -> Self {
+        self.drv = Some(endpoint.into());
+        self
+    }
+
+    /// Sets the directory with the certificates of the instance's domains, which is needed to download datasets.
+    ///
+    /// It is expected to contain a directory per domain with a `ca.pem` and `client-id.pem`, like the ones generated by `brane certs add`.
+    #[inline]
+    pub fn certs_dir(mut self, path: impl Into<PathBuf>) -> Self {
+        self.certs_dir = Some(path.into());
+        self
+    }
+
+    /// Sets a proxy to send data transfers through.
+    #[inline]
+    pub fn proxy(mut self, address: impl Into<String>) -> Self {
+        self.proxy = Some(address.into());
+        self
+    }
+
+    /// Sets the (tentative) identifier of the user who receives the results of workflows.
+    #[inline]
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Sets the options with which snippets are compiled. Defaults to [`ParserOptions::bscript()`].
+    #[inline]
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Builds the [`Client`].
+    ///
+    /// This doesn't connect to anything yet; that happens when the client is used.
+    ///
+    /// # Returns
+    /// A new [`Client`] with this configuration.
+    ///
+    /// # Errors
+    /// This function errors if either endpoint was not given, or if the proxy address is invalid.
+    pub fn build(self) -> Result<Client, BuildError> {
+        let api: String = match self.api {
+            Some(api) => api.trim_end_matches('/').into(),
+            None => return Err(BuildError::MissingEndpoint { what: "brane-api" }),
+        };
+        let drv: String = match self.drv {
+            Some(drv) => drv,
+            None => return Err(BuildError::MissingEndpoint { what: "brane-drv" }),
+        };
+        let proxy: Option<Proxy> = match self.proxy {
+            Some(address) => match Proxy::all(&address) {
+                Ok(proxy) => Some(proxy),
+                Err(err) => return Err(BuildError::ProxyCreateError { address, err }),
+            },
+            None => None,
+        };
+        Ok(Client { api, drv, certs_dir: self.certs_dir, proxy, user: self.user, options: self.options })
+    }
+}
+
+
+
+/// A client for a Brane instance.
+///
+/// It is cheap to clone, and every method creates its own connections, so it can be shared freely between tasks.
+#[derive(Clone, Debug)]
+pub struct Client {
+    /// The address of the `brane-api` service, without trailing slashes.
+    api:       String,
+    /// The address of the `brane-drv` service.
+    drv:       String,
+    /// The directory with the certificates of the domains, if any.
+    certs_dir: Option<PathBuf>,
+    /// The proxy to send data transfers through, if any.
+    proxy:     Option<Proxy>,
+    /// The user that receives workflow results, if known.
+    user:      Option<String>,
+    /// The options with which to compile snippets.
+    options:   ParserOptions,
+}
+
+impl Client {
+    /// Returns a new [`ClientBuilder`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use brane_client::Client;
+    ///
+    /// let client = Client::builder().api("http://localhost:50051").drv("grpc://localhost:50053").certs_dir("./certs").build()?;
+    /// let mut session = client.session().await?;
+    /// let output = session.run("println(\"Hello, world!\");").await?;
+    /// assert_eq!(output.stdout, "Hello, world!\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn builder() -> ClientBuilder { ClientBuilder::new() }
+
+    /// Returns the address of the `brane-api` service.
+    #[inline]
+    pub fn api_endpoint(&self) -> &str { &self.api }
+
+    /// Returns the address of the `brane-drv` service.
+    #[inline]
+    pub fn drv_endpoint(&self) -> &str { &self.drv }
+
+
+
+    /// Downloads the packages known to the instance.
+    ///
+    /// # Returns
+    /// A [`PackageIndex`] with all packages at the time of the call.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-api` or its response was ill-formed.
+    pub async fn packages(&self) -> Result<PackageIndex, ApiError> {
+        let address: String = format!("{}/graphql", self.api);
+        debug!("Fetching package index from '{}'...", address);
+        match brane_tsk::api::get_package_index(&address).await {
+            Ok(index) => Ok(index),
+            Err(err) => Err(ApiError::PackageIndexError { address, err }),
+        }
+    }
+
+    /// Downloads the datasets known to the instance.
+    ///
+    /// # Returns
+    /// A [`DataIndex`] with all datasets at the time of the call.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-api` or its response was ill-formed.
+    pub async fn datasets(&self) -> Result<DataIndex, ApiError> {
+        let address: String = format!("{}/data/info", self.api);
+        debug!("Fetching data index from '{}'...", address);
+        match brane_tsk::api::get_data_index(&address).await {
+            Ok(index) => Ok(index),
+            Err(err) => Err(ApiError::DataIndexError { address, err }),
+        }
+    }
+
+    /// Resolves a domain to the address of its registry.
+    ///
+    /// # Arguments
+    /// - `location`: The identifier of the domain.
+    ///
+    /// # Returns
+    /// The address of the domain's `brane-reg` service.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-api`, or if it does not know the domain.
+    pub async fn registry(&self, location: impl AsRef<str>) -> Result<String, ApiError> {
+        let address: String = format!("{}/infra/registries/{}", self.api, location.as_ref());
+        let res: Response = match reqwest::get(&address).await {
+            Ok(res) => res,
+            Err(err) => return Err(ApiError::RequestError { address, err }),
+        };
+        if !res.status().is_success() {
+            return Err(ApiError::RequestFailure {
+                address,
+                status: res.status(),
+                code: ErrorCode::from_headers(res.headers()),
+                message: res.text().await.ok(),
+            });
+        }
+        match res.text().await {
+            Ok(registry) => Ok(registry),
+            Err(err) => Err(ApiError::ResponseTextError { address, err }),
+        }
+    }
+
+
+
+    /// Lists the sessions that are active on the driver.
+    ///
+    /// # Returns
+    /// The sessions of this client's user if it has one, or all of them otherwise, ordered by most recent activity first.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-drv`.
+    pub async fn sessions(&self) -> Result<Vec<SessionInfo>, SessionError> {
+        let mut client: DriverServiceClient = self.connect().await?;
+        match client.list_sessions(ListSessionsRequest { user: self.user.clone() }).await {
+            Ok(reply) => Ok(reply.into_inner().sessions),
+            Err(err) => Err(SessionError::ListError { address: self.drv.clone(), err }),
+        }
+    }
+
+    /// Creates a new session on the driver.
+    ///
+    /// # Returns
+    /// A new [`Session`] in which snippets can be compiled and run.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-drv` or `brane-api` (from which the session downloads the data index).
+    pub async fn session(&self) -> Result<Session, SessionError> {
+        let mut client: DriverServiceClient = self.connect().await?;
+        let raw: String = match client.create_session(CreateSessionRequest { user: self.user.clone() }).await {
+            Ok(reply) => reply.into_inner().uuid,
+            Err(err) => return Err(SessionError::CreateError { address: self.drv.clone(), err }),
+        };
+        let id: AppId = match AppId::from_str(&raw) {
+            Ok(id) => id,
+            Err(err) => return Err(SessionError::SessionIdError { address: self.drv.clone(), raw, err }),
+        };
+        debug!("Created new session '{}'", id);
+        self.open(client, id).await
+    }
+
+    /// Attaches to an existing session on the driver, such that its variables and functions can be used again.
+    ///
+    /// Note that this client won't know the definitions made in the session before, so snippets that refer to them only compile if they
+    /// are (re-)declared. Use a fresh [`Session`] (see [`Client::session()`]) unless you need the remote state.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session to attach to.
+    ///
+    /// # Returns
+    /// A [`Session`] for the given session.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach `brane-drv` or `brane-api`.
+    pub async fn attach(&self, id: AppId) -> Result<Session, SessionError> {
+        let client: DriverServiceClient = self.connect().await?;
+        debug!("Attaching to session '{}'", id);
+        self.open(client, id).await
+    }
+
+    /// Connects to the driver.
+    async fn connect(&self) -> Result<DriverServiceClient, SessionError> {
+        debug!("Connecting to driver '{}'...", self.drv);
+        match DriverServiceClient::connect(self.drv.clone()).await {
+            Ok(client) => Ok(client),
+            Err(err) => Err(SessionError::ConnectError { address: self.drv.clone(), err }),
+        }
+    }
+
+    /// Creates a [`Session`] for the given session ID, downloading the data index it compiles against.
+    async fn open(&self, client: DriverServiceClient, id: AppId) -> Result<Session, SessionError> {
+        let dindex: DataIndex = match self.datasets().await {
+            Ok(dindex) => dindex,
+            Err(err) => return Err(SessionError::DataIndexError { err }),
+        };
+        Ok(Session::new(id, self.drv.clone(), client, &self.api, dindex, self.user.clone(), self.options.clone()))
+    }
+
+
+
+    /// Downloads a dataset from a domain that has it.
+    ///
+    /// The domains are tried in alphabetical order until one succeeds.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the dataset to download.
+    /// - `target`: The directory to extract the dataset to. Anything in it already is removed first.
+    ///
+    /// # Returns
+    /// How the dataset can be accessed locally. If the instance advertises it as available on `localhost`, nothing is downloaded and that
+    /// access is returned instead.
+    ///
+    /// # Errors
+    /// This function errors if the dataset is unknown, or if we failed to download it from every domain that advertises it.
+    pub async fn download(&self, name: impl AsRef<str>, target: impl AsRef<Path>) -> Result<AccessKind, DownloadError> {
+        let name: &str = name.as_ref();
+        let target: &Path = target.as_ref();
+
+        // Find out who has it
+        let dindex: DataIndex = match self.datasets().await {
+            Ok(dindex) => dindex,
+            Err(err) => return Err(DownloadError::DataIndexError { err }),
+        };
+        let info: &DataInfo = match dindex.get(name) {
+            Some(info) => info,
+            None => return Err(DownloadError::UnknownDataset { name: name.into() }),
+        };
+        if let Some(access) = info.access.get(LOCALHOST) {
+            return Ok(access.clone());
+        }
+        if info.access.is_empty() {
+            return Err(DownloadError::UnavailableDataset { name: name.into() });
+        }
+        let certs_dir: &Path = match &self.certs_dir {
+            Some(certs_dir) => certs_dir,
+            None => return Err(DownloadError::MissingCertsDir),
+        };
+
+        // Try them one by one
+        let mut locations: Vec<&String> = info.access.keys().collect();
+        locations.sort();
+        let mut errs: Vec<(String, DownloadError)> = Vec::with_capacity(locations.len());
+        for location in locations {
+            let registry: String = match self.registry(location).await {
+                Ok(registry) => registry,
+                Err(err) => {
+                    errs.push((location.clone(), DownloadError::RegistryError { location: location.clone(), err }));
+                    continue;
+                },
+            };
+            match data::download(&registry, self.proxy.as_ref(), &certs_dir.join(location), name, target).await {
+                Ok(()) => return Ok(AccessKind::File { path: target.into() }),
+                Err(err) => {
+                    debug!("Failed to download dataset '{}' from '{}': {}", name, location, err);
+                    errs.push((location.clone(), err));
+                },
+            }
+        }
+        Err(DownloadError::LocationsFailed { name: name.into(), errs })
+    }
+}
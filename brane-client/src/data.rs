@@ -0,0 +1,155 @@
+//  DATA.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:17:04
+//  Last edited:
+//    16 Oct 2026, 02:28:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements downloading datasets from the registry of a domain.
+//
+
+use std::path::{Path, PathBuf};
+
+use brane_cfg::certs::load_identity_pem;
+use brane_shr::utilities::is_ip_addr;
+use futures_util::StreamExt as _;
+use log::debug;
+use reqwest::tls::{Certificate, Identity};
+use reqwest::{Client, ClientBuilder, Proxy, Response};
+use specifications::errors::ErrorCode;
+use tempfile::TempDir;
+use tokio::fs as tfs;
+use tokio::io::AsyncWriteExt as _;
+
+use crate::errors::{ApiError, DownloadError};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Loads the client identity and CA certificate for a domain.
+///
+/// # Arguments
+/// - `cert_dir`: The directory with the certificates of the domain.
+///
+/// # Returns
+/// The client [`Identity`] and the CA [`Certificate`] of the domain.
+///
+/// # Errors
+/// This function errors if either file could not be read or parsed.
+async fn load_certs(cert_dir: &Path) -> Result<(Identity, Certificate), DownloadError> {
+    let idfile: PathBuf = cert_dir.join("client-id.pem");
+    let cafile: PathBuf = cert_dir.join("ca.pem");
+
+    // Load the keypair as an Identity, decrypting any encrypted keys in memory since reqwest can't handle those
+    let raw: String = match load_identity_pem(&idfile) {
+        Ok(raw) => raw,
+        Err(err) => return Err(DownloadError::IdentityLoadError { path: idfile, err }),
+    };
+    let identity: Identity = match Identity::from_pem(raw.as_bytes()) {
+        Ok(identity) => identity,
+        Err(err) => return Err(DownloadError::IdentityFileError { path: idfile, err }),
+    };
+
+    // Load the root certificate
+    let root: Certificate = match tfs::read(&cafile).await {
+        Ok(raw) => match Certificate::from_pem(&raw) {
+            Ok(root) => root,
+            Err(err) => return Err(DownloadError::CertificateError { path: cafile, err }),
+        },
+        Err(err) => return Err(DownloadError::FileReadError { what: "server cert root", path: cafile, err }),
+    };
+    Ok((identity, root))
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Downloads a dataset from the registry of a single domain.
+///
+/// # Arguments
+/// - `registry`: The address of the domain's `brane-reg` service.
+/// - `proxy`: The proxy to send the transfer through, if any.
+/// - `cert_dir`: The directory with the certificates of the domain.
+/// - `name`: The name of the dataset to download.
+/// - `target`: The directory to extract the dataset to. Anything in it already is removed first.
+///
+/// # Errors
+/// This function errors if we failed to authenticate to the registry, download the dataset or extract it.
+pub(crate) async fn download(registry: &str, proxy: Option<&Proxy>, cert_dir: &Path, name: &str, target: &Path) -> Result<(), DownloadError> {
+    let (identity, root): (Identity, Certificate) = load_certs(cert_dir).await?;
+
+    // Build the client
+    let address: String = format!("{registry}/data/download/{name}");
+    let mut client: ClientBuilder = Client::builder().use_rustls_tls().add_root_certificate(root).identity(identity).tls_sni(!is_ip_addr(&address));
+    if let Some(proxy) = proxy {
+        client = client.proxy(proxy.clone());
+    }
+    let client: Client = match client.build() {
+        Ok(client) => client,
+        Err(err) => return Err(DownloadError::ClientCreateError { err }),
+    };
+
+    // Send the request
+    debug!("Sending download request to '{}'...", address);
+    let res: Response = match client.get(&address).send().await {
+        Ok(res) => res,
+        Err(err) => return Err(DownloadError::RequestError { err: ApiError::RequestError { address, err } }),
+    };
+    if !res.status().is_success() {
+        return Err(DownloadError::RequestError {
+            err: ApiError::RequestFailure {
+                address,
+                status: res.status(),
+                code: ErrorCode::from_headers(res.headers()),
+                message: res.text().await.ok(),
+            },
+        });
+    }
+
+    // Stream the archive to a temporary file
+    let tar_dir: TempDir = match TempDir::new() {
+        Ok(tar_dir) => tar_dir,
+        Err(err) => return Err(DownloadError::TempDirError { err }),
+    };
+    let tar_path: PathBuf = tar_dir.path().join(format!("data_{name}.tar.gz"));
+    debug!("Downloading archive to '{}'...", tar_path.display());
+    {
+        let mut handle: tfs::File = match tfs::File::create(&tar_path).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(DownloadError::TarCreateError { path: tar_path, err }),
+        };
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let mut chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => return Err(DownloadError::DownloadStreamError { address, err }),
+            };
+            if let Err(err) = handle.write_all_buf(&mut chunk).await {
+                return Err(DownloadError::TarWriteError { path: tar_path, err });
+            }
+        }
+        if let Err(err) = handle.flush().await {
+            return Err(DownloadError::TarWriteError { path: tar_path, err });
+        }
+    }
+
+    // Replace the target with the archive's contents
+    if target.exists() {
+        if !target.is_dir() {
+            return Err(DownloadError::TargetNotADirError { path: target.into() });
+        }
+        if let Err(err) = tfs::remove_dir_all(target).await {
+            return Err(DownloadError::TargetRemoveError { path: target.into(), err });
+        }
+    }
+    debug!("Unpacking '{}' to '{}'...", tar_path.display(), target.display());
+    match brane_shr::fs::unarchive_async(&tar_path, target).await {
+        Ok(()) => Ok(()),
+        Err(err) => Err(DownloadError::TarExtractError { err }),
+    }
+}
@@ -0,0 +1,344 @@
+//  ERRORS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:17:04
+//  Last edited:
+//    16 Oct 2026, 02:28:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the errors that may occur in the `brane-client` crate.
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
+
+use reqwest::StatusCode;
+use specifications::checking::{ApprovalPending, PolicyDenial};
+use specifications::driving::DriverServiceError;
+use specifications::errors::ErrorCode;
+
+
+/***** LIBRARY *****/
+/// Defines errors that occur when building a [`Client`](crate::Client).
+#[derive(Debug)]
+pub enum BuildError {
+    /// The builder was not given one of the endpoints.
+    MissingEndpoint { what: &'static str },
+    /// Failed to create a proxy for the given address.
+    ProxyCreateError { address: String, err: reqwest::Error },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use BuildError::*;
+        match self {
+            MissingEndpoint { what } => write!(f, "No {what} endpoint given"),
+            ProxyCreateError { address, .. } => write!(f, "Failed to create proxy to '{address}'"),
+        }
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use BuildError::*;
+        match self {
+            MissingEndpoint { .. } => None,
+            ProxyCreateError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Defines errors that occur when talking to the `brane-api` service.
+#[derive(Debug)]
+pub enum ApiError {
+    /// Failed to download the package index.
+    PackageIndexError { address: String, err: brane_tsk::api::Error },
+    /// Failed to download the data index.
+    DataIndexError { address: String, err: brane_tsk::api::Error },
+    /// Failed to send a request.
+    RequestError { address: String, err: reqwest::Error },
+    /// The service responded with a non-success status code, and possibly a Brane [`ErrorCode`].
+    RequestFailure { address: String, status: StatusCode, code: Option<ErrorCode>, message: Option<String> },
+    /// Failed to read the body of a response.
+    ResponseTextError { address: String, err: reqwest::Error },
+}
+
+impl ApiError {
+    /// Returns the Brane error code sent by the service, if any.
+    ///
+    /// # Returns
+    /// The [`ErrorCode`] if this is a [`ApiError::RequestFailure`] that carried one, or [`None`] otherwise.
+    #[inline]
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::RequestFailure { code, .. } => *code,
+            _ => None,
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ApiError::*;
+        match self {
+            PackageIndexError { address, .. } => write!(f, "Failed to fetch package index from '{address}'"),
+            DataIndexError { address, .. } => write!(f, "Failed to fetch data index from '{address}'"),
+            RequestError { address, .. } => write!(f, "Failed to send request to '{address}'"),
+            RequestFailure { address, status, code, message } => write!(
+                f,
+                "Request to '{}' failed with status {} ({}){}{}",
+                address,
+                status.as_u16(),
+                status.canonical_reason().unwrap_or("???"),
+                if let Some(code) = code { format!(" [{}]", code.as_str()) } else { String::new() },
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
+            ResponseTextError { address, .. } => write!(f, "Failed to read response body from '{address}'"),
+        }
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ApiError::*;
+        match self {
+            PackageIndexError { err, .. } => Some(err),
+            DataIndexError { err, .. } => Some(err),
+            RequestError { err, .. } => Some(err),
+            RequestFailure { .. } => None,
+            ResponseTextError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Defines errors that occur when managing sessions on, or running workflows in, the `brane-drv` service.
+#[derive(Debug)]
+pub enum SessionError {
+    /// Failed to connect to the driver.
+    ConnectError { address: String, err: DriverServiceError },
+    /// The driver failed to create a new session.
+    CreateError { address: String, err: tonic::Status },
+    /// The driver failed to list the sessions.
+    ListError { address: String, err: tonic::Status },
+    /// The driver sent a session identifier we could not parse.
+    SessionIdError { address: String, raw: String, err: brane_tsk::errors::IdError },
+
+    /// Failed to download the data index to compile against.
+    DataIndexError { err: ApiError },
+    /// Failed to resolve the packages imported by a snippet.
+    PackageResolveError { err: brane_tsk::api::Error },
+    /// The snippet did not compile. Use [`SessionError::prettywrite()`] to show the errors with source context.
+    ///
+    /// `source` is everything compiled in the session so far (including the failed snippet), since that is what the errors' ranges refer to.
+    CompileError { source: String, errs: Vec<brane_ast::Error> },
+    /// Failed to serialize the compiled workflow.
+    WorkflowSerializeError { err: serde_json::Error },
+
+    /// The driver failed to check the workflow.
+    CheckError { address: String, err: tonic::Status },
+    /// The driver failed to start executing the workflow.
+    ExecuteRequestError { address: String, err: tonic::Status },
+    /// A checker denied the workflow, and shared why.
+    PolicyDenied { denial: PolicyDenial },
+    /// The workflow needed a download that awaits the approval of a domain's data steward.
+    ApprovalPending { pending: ApprovalPending },
+    /// The workflow was denied without an explanation.
+    ExecDenied { message: String },
+    /// The workflow failed with a known Brane error code.
+    ExecFailed { code: ErrorCode, message: String },
+    /// The workflow failed otherwise.
+    ExecError { message: String },
+    /// The driver sent a value we could not parse.
+    ValueParseError { address: String, raw: String, err: serde_json::Error },
+}
+
+impl SessionError {
+    /// Writes the errors of a [`SessionError::CompileError`] with source context, the same way `brane run` shows them.
+    ///
+    /// # Arguments
+    /// - `writer`: The [`Write`](std::io::Write)r to write to.
+    /// - `what`: A name for the snippet (e.g., a filename) to show in the errors.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write to the given writer.
+    pub fn prettywrite(&self, mut writer: impl std::io::Write, what: impl AsRef<str>) -> Result<(), std::io::Error> {
+        match self {
+            Self::CompileError { source, errs } => {
+                for err in errs {
+                    err.prettywrite(&mut writer, what.as_ref(), source)?;
+                }
+                Ok(())
+            },
+            err => writeln!(writer, "{err}"),
+        }
+    }
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SessionError::*;
+        match self {
+            ConnectError { address, .. } => write!(f, "Failed to connect to driver '{address}'"),
+            CreateError { address, err } => write!(f, "Failed to create new session on driver '{}': {}", address, err.message()),
+            ListError { address, err } => write!(f, "Failed to list sessions on driver '{}': {}", address, err.message()),
+            SessionIdError { address, raw, .. } => write!(f, "Could not parse '{raw}' sent by driver '{address}' as a session ID"),
+
+            DataIndexError { .. } => write!(f, "Failed to fetch data index to compile against"),
+            PackageResolveError { .. } => write!(f, "Failed to resolve imported packages"),
+            CompileError { errs, .. } => {
+                write!(f, "Failed to compile snippet ({} error{})", errs.len(), if errs.len() == 1 { "" } else { "s" })
+            },
+            WorkflowSerializeError { .. } => write!(f, "Failed to serialize the compiled workflow"),
+
+            CheckError { address, err } => write!(f, "Failed to check workflow on driver '{}': {}", address, err.message()),
+            ExecuteRequestError { address, err } => write!(f, "Failed to execute workflow on driver '{}': {}", address, err.message()),
+            PolicyDenied { denial } => write!(f, "Workflow was denied by the checker of domain '{}'", denial.domain),
+            ApprovalPending { pending } => write!(f, "{pending}"),
+            ExecDenied { message } => write!(f, "Workflow was denied: {message}"),
+            ExecFailed { code, message } => write!(f, "Workflow failed ({code}): {message}"),
+            ExecError { message } => write!(f, "Workflow failed: {message}"),
+            ValueParseError { address, raw, .. } => write!(f, "Could not parse '{raw}' sent by driver '{address}' as a value"),
+        }
+    }
+}
+
+impl Error for SessionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SessionError::*;
+        match self {
+            ConnectError { err, .. } => Some(err),
+            CreateError { .. } | ListError { .. } => None,
+            SessionIdError { err, .. } => Some(err),
+
+            DataIndexError { err } => Some(err),
+            PackageResolveError { err } => Some(err),
+            CompileError { .. } => None,
+            WorkflowSerializeError { err } => Some(err),
+
+            CheckError { .. }
+            | ExecuteRequestError { .. }
+            | PolicyDenied { .. }
+            | ApprovalPending { .. }
+            | ExecDenied { .. }
+            | ExecFailed { .. }
+            | ExecError { .. } => None,
+            ValueParseError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Defines errors that occur when downloading datasets.
+#[derive(Debug)]
+pub enum DownloadError {
+    /// Failed to download the data index.
+    DataIndexError { err: ApiError },
+    /// The instance does not know the dataset.
+    UnknownDataset { name: String },
+    /// No domain advertises the dataset.
+    UnavailableDataset { name: String },
+    /// The client was built without a certificate directory, which is needed to download from domains.
+    MissingCertsDir,
+    /// Failed to download the dataset from any of the domains that have it.
+    LocationsFailed { name: String, errs: Vec<(String, DownloadError)> },
+
+    /// Failed to resolve a domain to the address of its registry.
+    RegistryError { location: String, err: ApiError },
+    /// Failed to read a certificate file.
+    FileReadError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to load a client identity file, including decrypting its keys.
+    IdentityLoadError { path: PathBuf, err: brane_cfg::certs::Error },
+    /// Failed to parse a client identity file.
+    IdentityFileError { path: PathBuf, err: reqwest::Error },
+    /// Failed to parse a CA certificate.
+    CertificateError { path: PathBuf, err: reqwest::Error },
+    /// Failed to create the HTTP client for downloading.
+    ClientCreateError { err: reqwest::Error },
+    /// Failed to download the dataset from the registry.
+    RequestError { err: ApiError },
+    /// Failed to read the next chunk of the download.
+    DownloadStreamError { address: String, err: reqwest::Error },
+
+    /// Failed to create a temporary directory for the archive.
+    TempDirError { err: std::io::Error },
+    /// Failed to create the archive file.
+    TarCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to write to the archive file.
+    TarWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to extract the archive.
+    TarExtractError { err: brane_shr::fs::Error },
+    /// The target directory exists but is not a directory.
+    TargetNotADirError { path: PathBuf },
+    /// Failed to remove the previous contents of the target directory.
+    TargetRemoveError { path: PathBuf, err: std::io::Error },
+}
+
+impl Display for DownloadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DownloadError::*;
+        match self {
+            DataIndexError { .. } => write!(f, "Failed to fetch data index"),
+            UnknownDataset { name } => write!(f, "Unknown dataset '{name}'"),
+            UnavailableDataset { name } => write!(f, "Dataset '{name}' is not advertised by any domain"),
+            MissingCertsDir => write!(f, "Client has no certificate directory to authenticate to domains with"),
+            LocationsFailed { name, errs } => {
+                write!(f, "Failed to download dataset '{name}' from any domain:")?;
+                for (location, err) in errs {
+                    write!(f, "\n - {location}: {err}")?;
+                }
+                Ok(())
+            },
+
+            RegistryError { location, .. } => write!(f, "Failed to resolve registry of domain '{location}'"),
+            FileReadError { what, path, .. } => write!(f, "Failed to read {} file '{}'", what, path.display()),
+            IdentityLoadError { path, .. } => write!(f, "Failed to load client identity file '{}'", path.display()),
+            IdentityFileError { path, .. } => write!(f, "Failed to parse client identity file '{}'", path.display()),
+            CertificateError { path, .. } => write!(f, "Failed to parse CA certificate '{}'", path.display()),
+            ClientCreateError { .. } => write!(f, "Failed to create HTTP client"),
+            RequestError { .. } => write!(f, "Failed to download dataset"),
+            DownloadStreamError { address, .. } => write!(f, "Failed to download from '{address}'"),
+
+            TempDirError { .. } => write!(f, "Failed to create temporary directory"),
+            TarCreateError { path, .. } => write!(f, "Failed to create archive '{}'", path.display()),
+            TarWriteError { path, .. } => write!(f, "Failed to write to archive '{}'", path.display()),
+            TarExtractError { .. } => write!(f, "Failed to extract downloaded archive"),
+            TargetNotADirError { path } => write!(f, "Target '{}' exists but is not a directory", path.display()),
+            TargetRemoveError { path, .. } => write!(f, "Failed to remove previous contents of '{}'", path.display()),
+        }
+    }
+}
+
+impl Error for DownloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use DownloadError::*;
+        match self {
+            DataIndexError { err } => Some(err),
+            UnknownDataset { .. } | UnavailableDataset { .. } | MissingCertsDir | LocationsFailed { .. } => None,
+
+            RegistryError { err, .. } => Some(err),
+            FileReadError { err, .. } => Some(err),
+            IdentityLoadError { err, .. } => Some(err),
+            IdentityFileError { err, .. } => Some(err),
+            CertificateError { err, .. } => Some(err),
+            ClientCreateError { err } => Some(err),
+            RequestError { err } => Some(err),
+            DownloadStreamError { err, .. } => Some(err),
+
+            TempDirError { err } => Some(err),
+            TarCreateError { err, .. } => Some(err),
+            TarWriteError { err, .. } => Some(err),
+            TarExtractError { err } => Some(err),
+            TargetNotADirError { .. } => None,
+            TargetRemoveError { err, .. } => Some(err),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:17:05
+//  Last edited:
+//    15 Oct 2026, 23:17:05
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   The `brane-client` library provides an async client for Brane
+//!   instances. It wraps the gRPC interface of `brane-drv`, the REST and
+//!   GraphQL interfaces of `brane-api` and the dataset downloads of the
+//!   domain registries, such that Rust programs can run workflows without
+//!   going through the internals of `brane-cli`.
+//!
+//!   Start by building a [`Client`] with [`Client::builder()`].
+//
+
+// Declare modules
+mod client;
+mod data;
+pub mod errors;
+mod session;
+
+// Bring some of it into the crate namespace, together with the types that appear in the client's interface
+pub use brane_ast::{ParserOptions, Workflow};
+pub use brane_exe::FullValue;
+pub use brane_tsk::spec::AppId;
+pub use specifications::data::{AccessKind, DataIndex, DataInfo};
+pub use specifications::driving::SessionInfo;
+pub use specifications::package::{PackageIndex, PackageInfo};
+
+pub use crate::client::{Client, ClientBuilder};
+pub use crate::errors::{ApiError, BuildError, DownloadError, SessionError};
+pub use crate::session::{Event, Execution, Output, Session, Verdict};
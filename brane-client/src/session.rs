@@ -0,0 +1,403 @@
+//  SESSION.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:17:05
+//  Last edited:
+//    16 Oct 2026, 01:29:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements [`Session`]s, in which snippets are compiled and run on the
+//!   driver, and the [`Execution`]s that stream their progress back.
+//
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use brane_ast::state::CompileState;
+use brane_ast::{compile_snippet, CompileResult, ParserOptions, Workflow};
+use brane_exe::FullValue;
+use brane_tsk::api::RemotePackageProvider;
+use brane_tsk::caches::PackageIndexCache;
+use brane_tsk::spec::AppId;
+use log::{debug, warn};
+use specifications::checking::{ApprovalPending, PolicyDenial};
+use specifications::data::DataIndex;
+use specifications::driving::{CheckRequest, DriverServiceClient, ExecuteReply, ExecuteRequest};
+use specifications::errors::ErrorCode;
+use specifications::package::PackageIndex;
+use tonic::{Code, Status, Streaming};
+
+use crate::errors::SessionError;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Converts an error sent by the driver while executing a workflow to a [`SessionError`].
+///
+/// # Arguments
+/// - `status`: The [`Status`] sent by the driver.
+///
+/// # Returns
+/// A [`SessionError`] that distinguishes policy denials and known Brane error codes from other failures.
+fn exec_error(status: Status) -> SessionError {
+    match (status.code(), ErrorCode::from_status(&status)) {
+        (Code::PermissionDenied, _) => match PolicyDenial::from_bytes(status.details()) {
+            Some(denial) => SessionError::PolicyDenied { denial },
+            None => SessionError::ExecDenied { message: status.message().into() },
+        },
+        (Code::FailedPrecondition, Some(ErrorCode::RegApprovalPending)) => match ApprovalPending::from_bytes(status.details()) {
+            Some(pending) => SessionError::ApprovalPending { pending },
+            None => SessionError::ExecFailed { code: ErrorCode::RegApprovalPending, message: status.message().into() },
+        },
+        (_, Some(code)) => SessionError::ExecFailed { code, message: status.message().into() },
+        (_, None) => SessionError::ExecError { message: status.message().into() },
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines something that happened while executing a workflow.
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// The driver has debug information.
+    Debug(String),
+    /// The workflow printed something.
+    Stdout(String),
+    /// The workflow printed something to stderr.
+    Stderr(String),
+    /// The driver has profiling information (e.g., the resources used by a task).
+    Profile(String),
+    /// The workflow returned a value.
+    Value(FullValue),
+}
+
+
+
+/// Defines everything a workflow produced, as collected by [`Execution::wait()`].
+#[derive(Clone, Debug)]
+pub struct Output {
+    /// The value the workflow returned, or [`FullValue::Void`] if it returned nothing.
+    pub value:  FullValue,
+    /// Everything the workflow printed.
+    pub stdout: String,
+    /// Everything the workflow printed to stderr.
+    pub stderr: String,
+}
+
+
+
+/// Defines the verdict of the checkers on a workflow.
+#[derive(Clone, Debug)]
+pub struct Verdict {
+    /// Whether all checkers allowed the workflow.
+    pub allowed: bool,
+    /// The first checker that denied it, if any.
+    pub who:     Option<String>,
+    /// Why that checker denied it, if it wants to share.
+    pub reasons: Vec<String>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A session on the driver, in which snippets can be compiled and run.
+///
+/// Like the REPL, snippets may refer to the variables and functions defined by previous snippets in the same session.
+#[derive(Debug)]
+pub struct Session {
+    /// The identifier of the session.
+    id:      AppId,
+    /// The address of the driver.
+    address: String,
+    /// The connection to the driver.
+    client:  DriverServiceClient,
+
+    /// Resolves the packages imported by snippets.
+    pindex:  PackageIndexCache<RemotePackageProvider>,
+    /// The datasets that snippets may refer to.
+    dindex:  DataIndex,
+    /// The user that receives workflow results, if known.
+    user:    Option<String>,
+    /// The options with which to compile snippets.
+    options: ParserOptions,
+    /// The state of the compiler in between snippets.
+    state:   CompileState,
+    /// Everything compiled so far, which error ranges refer to.
+    source:  String,
+}
+
+impl Session {
+    /// Constructor for the Session.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session on the driver.
+    /// - `address`: The address of the driver.
+    /// - `client`: The connection to the driver.
+    /// - `api`: The address of `brane-api`, from which packages are resolved.
+    /// - `dindex`: The datasets that snippets may refer to.
+    /// - `user`: The user that receives workflow results, if known.
+    /// - `options`: The options with which to compile snippets.
+    ///
+    /// # Returns
+    /// A new Session.
+    pub(crate) fn new(
+        id: AppId,
+        address: String,
+        client: DriverServiceClient,
+        api: &str,
+        dindex: DataIndex,
+        user: Option<String>,
+        options: ParserOptions,
+    ) -> Self {
+        Self {
+            id,
+            address,
+            client,
+
+            pindex: PackageIndexCache::new(RemotePackageProvider::new(format!("{api}/graphql"))),
+            dindex,
+            user,
+            options,
+            state: CompileState::new(),
+            source: String::new(),
+        }
+    }
+
+    /// Returns the identifier of this session, with which it can be attached to later (see [`Client::attach()`](crate::Client::attach())).
+    #[inline]
+    pub fn id(&self) -> &AppId { &self.id }
+
+    /// Replaces the datasets that snippets may refer to, e.g., after a workflow committed a new one.
+    ///
+    /// # Arguments
+    /// - `dindex`: The new [`DataIndex`] (see [`Client::datasets()`](crate::Client::datasets())).
+    #[inline]
+    pub fn set_datasets(&mut self, dindex: DataIndex) { self.dindex = dindex; }
+
+
+
+    /// Compiles a snippet in the context of this session.
+    ///
+    /// Any warnings are logged.
+    ///
+    /// # Arguments
+    /// - `snippet`: The BraneScript (or Bakery) source to compile.
+    ///
+    /// # Returns
+    /// The compiled [`Workflow`].
+    ///
+    /// # Errors
+    /// This function errors if we failed to resolve the imported packages or if the snippet did not compile.
+    pub async fn compile(&mut self, snippet: impl AsRef<str>) -> Result<Workflow, SessionError> {
+        let snippet: &str = snippet.as_ref();
+        let pindex: Arc<PackageIndex> = match self.pindex.resolve(snippet, &self.options).await {
+            Ok(pindex) => pindex,
+            Err(err) => return Err(SessionError::PackageResolveError { err }),
+        };
+
+        self.source.push_str(snippet);
+        self.source.push('\n');
+        match compile_snippet(&mut self.state, snippet.as_bytes(), &pindex, &self.dindex, &self.options) {
+            CompileResult::Workflow(mut workflow, warns) => {
+                for warn in warns {
+                    let mut buf: Vec<u8> = vec![];
+                    if warn.prettywrite(&mut buf, "<snippet>", &self.source).is_ok() {
+                        warn!("{}", String::from_utf8_lossy(&buf).trim_end());
+                    }
+                }
+                if let Some(user) = &self.user {
+                    workflow.user = Arc::new(Some(user.clone()));
+                }
+                Ok(workflow)
+            },
+
+            CompileResult::Eof(err) => {
+                self.state.offset += 1 + snippet.chars().filter(|c| *c == '\n').count();
+                Err(SessionError::CompileError { source: self.source.clone(), errs: vec![err] })
+            },
+            CompileResult::Err(errs) => {
+                self.state.offset += 1 + snippet.chars().filter(|c| *c == '\n').count();
+                Err(SessionError::CompileError { source: self.source.clone(), errs })
+            },
+
+            // `compile_snippet()` compiles all the way
+            _ => unreachable!(),
+        }
+    }
+
+    /// Asks the checkers whether they allow a workflow, without running it.
+    ///
+    /// # Arguments
+    /// - `workflow`: The [`Workflow`] to check.
+    ///
+    /// # Returns
+    /// The [`Verdict`] of the checkers.
+    ///
+    /// # Errors
+    /// This function errors if we failed to reach the driver.
+    pub async fn check(&mut self, workflow: &Workflow) -> Result<Verdict, SessionError> {
+        let workflow: String = match serde_json::to_string(workflow) {
+            Ok(workflow) => workflow,
+            Err(err) => return Err(SessionError::WorkflowSerializeError { err }),
+        };
+        match self.client.check(CheckRequest { workflow }).await {
+            Ok(reply) => {
+                let reply = reply.into_inner();
+                Ok(Verdict { allowed: reply.verdict, who: reply.who, reasons: reply.reasons })
+            },
+            Err(err) => Err(SessionError::CheckError { address: self.address.clone(), err }),
+        }
+    }
+
+    /// Starts executing a workflow in this session.
+    ///
+    /// # Arguments
+    /// - `workflow`: The [`Workflow`] to execute.
+    ///
+    /// # Returns
+    /// An [`Execution`] that streams the progress of the workflow.
+    ///
+    /// # Errors
+    /// This function errors if the driver refused to execute the workflow.
+    pub async fn execute(&mut self, workflow: &Workflow) -> Result<Execution, SessionError> {
+        let input: String = match serde_json::to_string(workflow) {
+            Ok(input) => input,
+            Err(err) => return Err(SessionError::WorkflowSerializeError { err }),
+        };
+        self.send(ExecuteRequest { uuid: self.id.to_string(), input, resume: None, deadline: None }).await
+    }
+
+    /// Resumes an interrupted workflow from its last completed task.
+    ///
+    /// # Arguments
+    /// - `execution_id`: The identifier of the workflow, as reported by the driver when it was interrupted.
+    ///
+    /// # Returns
+    /// An [`Execution`] that streams the progress of the workflow.
+    ///
+    /// # Errors
+    /// This function errors if the driver has no checkpoint for the workflow.
+    pub async fn resume(&mut self, execution_id: impl Into<String>) -> Result<Execution, SessionError> {
+        self.send(ExecuteRequest { uuid: self.id.to_string(), input: String::new(), resume: Some(execution_id.into()), deadline: None }).await
+    }
+
+    /// Compiles and runs a snippet to completion.
+    ///
+    /// # Arguments
+    /// - `snippet`: The BraneScript (or Bakery) source to run.
+    ///
+    /// # Returns
+    /// The [`Output`] of the workflow.
+    ///
+    /// # Errors
+    /// This function errors if the snippet did not compile, or if the workflow failed.
+    pub async fn run(&mut self, snippet: impl AsRef<str>) -> Result<Output, SessionError> {
+        let workflow: Workflow = self.compile(snippet).await?;
+        self.execute(&workflow).await?.wait().await
+    }
+
+    /// Sends an [`ExecuteRequest`] to the driver.
+    async fn send(&mut self, request: ExecuteRequest) -> Result<Execution, SessionError> {
+        match self.client.execute(request).await {
+            Ok(response) => Ok(Execution { address: self.address.clone(), stream: response.into_inner(), pending: VecDeque::new(), done: false }),
+            Err(err) => Err(SessionError::ExecuteRequestError { address: self.address.clone(), err }),
+        }
+    }
+}
+
+
+
+/// A workflow that is executing on the driver.
+#[derive(Debug)]
+pub struct Execution {
+    /// The address of the driver.
+    address: String,
+    /// The replies of the driver.
+    stream:  Streaming<ExecuteReply>,
+    /// Events of a reply that have not been returned yet.
+    pending: VecDeque<Event>,
+    /// Whether the driver has closed the stream.
+    done:    bool,
+}
+
+impl Execution {
+    /// Waits for the next thing that happens in the workflow.
+    ///
+    /// # Returns
+    /// The next [`Event`], or [`None`] if the workflow has completed.
+    ///
+    /// # Errors
+    /// This function errors if the workflow failed, or if the driver sent something we could not parse.
+    pub async fn next(&mut self) -> Result<Option<Event>, SessionError> {
+        while self.pending.is_empty() && !self.done {
+            let reply: ExecuteReply = match self.stream.message().await {
+                Ok(Some(reply)) => reply,
+                Ok(None) => {
+                    self.done = true;
+                    break;
+                },
+                Err(status) => {
+                    self.done = true;
+                    return Err(exec_error(status));
+                },
+            };
+
+            // Split the reply into events, in the order in which `brane run` handles them
+            if let Some(profile) = reply.profile {
+                self.pending.push_back(Event::Profile(profile));
+            }
+            if let Some(debug) = reply.debug {
+                self.pending.push_back(Event::Debug(debug));
+            }
+            if let Some(stdout) = reply.stdout {
+                self.pending.push_back(Event::Stdout(stdout));
+            }
+            if let Some(stderr) = reply.stderr {
+                self.pending.push_back(Event::Stderr(stderr));
+            }
+            if let Some(raw) = reply.value {
+                debug!("Driver returned value '{}'", raw);
+                match serde_json::from_str(&raw) {
+                    Ok(value) => self.pending.push_back(Event::Value(value)),
+                    Err(err) => {
+                        self.done = true;
+                        return Err(SessionError::ValueParseError { address: self.address.clone(), raw, err });
+                    },
+                }
+            }
+            self.done = reply.close;
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Waits for the workflow to complete.
+    ///
+    /// # Returns
+    /// Everything the workflow produced as an [`Output`]. Debug and profiling information is logged.
+    ///
+    /// # Errors
+    /// This function errors if the workflow failed, or if the driver sent something we could not parse.
+    pub async fn wait(mut self) -> Result<Output, SessionError> {
+        let mut output: Output = Output { value: FullValue::Void, stdout: String::new(), stderr: String::new() };
+        while let Some(event) = self.next().await? {
+            match event {
+                Event::Debug(debug) => debug!("Driver: {}", debug),
+                Event::Stdout(stdout) => output.stdout.push_str(&stdout),
+                Event::Stderr(stderr) => {
+                    output.stderr.push_str(&stderr);
+                    output.stderr.push('\n');
+                },
+                Event::Profile(profile) => debug!("Driver profile: {}", profile),
+                Event::Value(value) => output.value = value,
+            }
+        }
+        Ok(output)
+    }
+}
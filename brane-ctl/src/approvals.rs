@@ -0,0 +1,185 @@
+//  APPROVALS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:29:41
+//  Last edited:
+//    16 Oct 2026, 01:29:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements subcommands with which a data steward decides on downloads
+//!   of sensitive intermediate results that the registry parked.
+//
+
+use std::path::PathBuf;
+
+use brane_cfg::approvals::{ApprovalRequest, ApprovalStatus};
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{NodeConfig, NodeKind, NodeSpecificConfig};
+use chrono::Utc;
+use console::style;
+use enum_debug::EnumDebug as _;
+use log::{debug, info};
+
+pub use crate::errors::ApprovalsError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Resolves the approvals directory from the given node config file.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `what`: What we want to do, used in errors.
+///
+/// # Returns
+/// The path of the approvals directory.
+///
+/// # Errors
+/// This function errors if we failed to load the node config, it is not a worker node or it does not have approvals enabled.
+fn approvals_dir(node_config_path: PathBuf, what: &'static str) -> Result<PathBuf, Error> {
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+    match node_config.node {
+        NodeSpecificConfig::Worker(node) => match node.egress_approval {
+            Some(approval) => Ok(approval.path),
+            None => Err(Error::NotConfigured { path: node_config_path }),
+        },
+        NodeSpecificConfig::Central(_) => Err(Error::UnsupportedNode { what, kind: NodeKind::Central }),
+        NodeSpecificConfig::Proxy(_) => Err(Error::UnsupportedNode { what, kind: NodeKind::Proxy }),
+    }
+}
+
+/// Records a decision on a parked download.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `id`: The identifier of the request to decide on.
+/// - `status`: The decision to record.
+/// - `reason`: Why the data steward decided this way, if they said so.
+///
+/// # Errors
+/// This function errors if we failed to find the approvals directory, the request does not exist or has already been decided on, or if we
+/// failed to write the decision.
+fn decide(node_config_path: PathBuf, id: String, status: ApprovalStatus, reason: Option<String>) -> Result<(), Error> {
+    let dir: PathBuf = approvals_dir(node_config_path, "decide on downloads")?;
+
+    // Find the request
+    let mut request: ApprovalRequest = match ApprovalRequest::load(&dir, &id) {
+        Ok(Some(request)) => request,
+        Ok(None) => return Err(Error::UnknownRequest { id }),
+        Err(err) => return Err(Error::Approvals { err }),
+    };
+    if request.status != ApprovalStatus::Pending {
+        return Err(Error::AlreadyDecided { id, status: request.status });
+    }
+
+    // Write the decision
+    request.status = status;
+    request.decided = Some(Utc::now());
+    request.reason = reason;
+    if let Err(err) = request.save(&dir) {
+        return Err(Error::Approvals { err });
+    }
+
+    let decision = match status {
+        ApprovalStatus::Approved => style("approved").bold().green(),
+        _ => style("denied").bold().red(),
+    };
+    println!("Download of result {} by {} {}", style(&request.result).bold().cyan(), style(&request.requester).bold(), decision);
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the downloads that await (or awaited) approval.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `all`: Whether to also list downloads that have already been decided on.
+///
+/// # Returns
+/// Nothing directly, but does print the requests to `stdout`.
+///
+/// # Errors
+/// This function errors if we failed to find the approvals directory or failed to read the requests in it.
+pub fn list(node_config_path: impl Into<PathBuf>, all: bool) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Listing downloads awaiting approval...");
+
+    let dir: PathBuf = approvals_dir(node_config_path, "list downloads awaiting approval")?;
+    let requests: Vec<ApprovalRequest> = match ApprovalRequest::load_all(&dir) {
+        Ok(requests) => requests.into_iter().filter(|r| all || r.status == ApprovalStatus::Pending).collect(),
+        Err(err) => return Err(Error::Approvals { err }),
+    };
+
+    // Print them in a table
+    let result_width: usize = requests.iter().map(|r| r.result.len()).max().unwrap_or(0).max("RESULT".len());
+    let requester_width: usize = requests.iter().map(|r| r.requester.len()).max().unwrap_or(0).max("REQUESTER".len());
+    println!(
+        "{:<16} {:<result_width$} {:<requester_width$} {:<11} {:<9} {:<20} {}",
+        style("ID").bold(),
+        style("RESULT").bold(),
+        style("REQUESTER").bold(),
+        style("SENSITIVITY").bold(),
+        style("STATUS").bold(),
+        style("REQUESTED").bold(),
+        style("TAGS").bold(),
+    );
+    for request in requests {
+        let styled_status = match request.status {
+            ApprovalStatus::Pending => style(request.status.variant().to_string()).yellow(),
+            ApprovalStatus::Approved => style(request.status.variant().to_string()).green(),
+            ApprovalStatus::Denied => style(request.status.variant().to_string()).red(),
+        };
+        println!(
+            "{:<16} {:<result_width$} {:<requester_width$} {:<11} {:<9} {:<20} {}",
+            request.id,
+            request.result,
+            request.requester,
+            request.sensitivity,
+            styled_status,
+            request.requested.format("%Y-%m-%d %H:%M:%S"),
+            request.tags.join(", "),
+        );
+    }
+
+    Ok(())
+}
+
+/// Approves a parked download, such that the registry serves it when it is requested again.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `id`: The identifier of the request to approve.
+/// - `reason`: Why the download is approved, if given.
+///
+/// # Errors
+/// This function errors if we failed to find the request or failed to write the decision.
+pub fn approve(node_config_path: impl Into<PathBuf>, id: impl Into<String>, reason: Option<String>) -> Result<(), Error> {
+    let id: String = id.into();
+    info!("Approving download '{}'...", id);
+    decide(node_config_path.into(), id, ApprovalStatus::Approved, reason)
+}
+
+/// Denies a parked download, such that the registry refuses it when it is requested again.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that contains environment settings for this node.
+/// - `id`: The identifier of the request to deny.
+/// - `reason`: Why the download is denied, if given. It is shown to the requesting user.
+///
+/// # Errors
+/// This function errors if we failed to find the request or failed to write the decision.
+pub fn deny(node_config_path: impl Into<PathBuf>, id: impl Into<String>, reason: Option<String>) -> Result<(), Error> {
+    let id: String = id.into();
+    info!("Denying download '{}'...", id);
+    decide(node_config_path.into(), id, ApprovalStatus::Denied, reason)
+}
@@ -0,0 +1,503 @@
+//  BACKUP.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 18:24:51
+//  Last edited:
+//    16 Oct 2026, 02:37:31
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `branectl backup` subcommands, which snapshot the
+//!   state of a node into a single archive and restore it again.
+//
+
+use std::collections::HashMap;
+use std::fs::Permissions;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use brane_cfg::info::{interpolate_env, Info as _};
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig};
+use brane_shr::fs::{archive_async, copy_dir_recursively_async, unarchive_async};
+use console::style;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use tokio::fs as tfs;
+
+pub use crate::errors::BackupError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the manifest file in a backup archive.
+const MANIFEST_NAME: &str = "manifest.yml";
+/// The name of the node config file in a backup archive.
+const NODE_CONFIG_NAME: &str = "node.yml";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes how an entry in a backup is stored.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntryKind {
+    /// The entry is a single file.
+    File,
+    /// The entry is a directory that is backed up in its entirety.
+    Dir,
+    /// The entry is a dataset directory of which only the `data.yml` metadata files are backed up.
+    DatasetMetadata,
+}
+
+/// Describes a single file or directory stored in a backup.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct BackupEntry {
+    /// The name of the entry in the archive.
+    name: String,
+    /// The path where the entry originally lived (and where it is restored to).
+    path: PathBuf,
+    /// How the entry is stored.
+    kind: EntryKind,
+}
+
+/// Describes the contents of a backup archive.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct BackupManifest {
+    /// The version of `branectl` that created the backup.
+    version: String,
+    /// When the backup was created, as a UNIX timestamp (in seconds).
+    created: u64,
+    /// The kind of node that was backed up.
+    kind:    String,
+    /// The entries in the backup.
+    entries: Vec<BackupEntry>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Collects the files and directories that make up the state of the given node.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// A list of [`BackupEntry`]s to store in the backup. Entries that are optional and not configured are omitted.
+fn collect_entries(node_config: &NodeConfig) -> Vec<BackupEntry> {
+    let entry = |name: &str, path: &Path, kind: EntryKind| BackupEntry { name: name.into(), path: path.into(), kind };
+
+    match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            let mut res: Vec<BackupEntry> = vec![
+                entry("certs", &central.paths.certs, EntryKind::Dir),
+                entry("packages", &central.paths.packages, EntryKind::Dir),
+                entry("infra.yml", &central.paths.infra, EntryKind::File),
+            ];
+            res.extend(central.paths.proxy.as_ref().map(|p| entry("proxy.yml", p, EntryKind::File)));
+            res
+        },
+
+        NodeSpecificConfig::Worker(worker) => {
+            let mut res: Vec<BackupEntry> = vec![
+                entry("certs", &worker.paths.certs, EntryKind::Dir),
+                entry("packages", &worker.paths.packages, EntryKind::Dir),
+                entry("backend.yml", &worker.paths.backend, EntryKind::File),
+                entry("policies.db", &worker.paths.policy_database, EntryKind::File),
+                entry("policy_deliberation_secret.json", &worker.paths.policy_deliberation_secret, EntryKind::File),
+                entry("policy_expert_secret.json", &worker.paths.policy_expert_secret, EntryKind::File),
+                entry("data", &worker.paths.data, EntryKind::DatasetMetadata),
+            ];
+            res.extend(worker.paths.policy_audit_log.as_ref().map(|p| entry("audit.log", p, EntryKind::File)));
+            res.extend(worker.paths.proxy.as_ref().map(|p| entry("proxy.yml", p, EntryKind::File)));
+            res
+        },
+
+        NodeSpecificConfig::Proxy(proxy) => {
+            vec![entry("certs", &proxy.paths.certs, EntryKind::Dir), entry("proxy.yml", &proxy.paths.proxy, EntryKind::File)]
+        },
+    }
+}
+
+/// Resolves where to restore the entries of a backup to, based on the node config stored in that backup.
+///
+/// The paths in the manifest are only used as a sanity check: an entry is only restored if the node config defines it at the same path. This
+/// prevents a tampered manifest from having us overwrite (or, with `--force`, remove) arbitrary paths.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] stored in the backup.
+/// - `manifest`: The [`BackupManifest`] of the backup.
+///
+/// # Returns
+/// The entries to restore, with the paths and kinds as defined by the node config.
+///
+/// # Errors
+/// This function errors if the manifest lists an entry that the node config does not define (or lists it twice), or defines at another path.
+fn restore_targets(node_config: &NodeConfig, manifest: &BackupManifest) -> Result<Vec<BackupEntry>, Error> {
+    let mut known: HashMap<String, BackupEntry> = collect_entries(node_config).into_iter().map(|entry| (entry.name.clone(), entry)).collect();
+    let mut res: Vec<BackupEntry> = Vec::with_capacity(manifest.entries.len());
+    for entry in &manifest.entries {
+        let target: BackupEntry = match known.remove(&entry.name) {
+            Some(target) => target,
+            None => return Err(Error::UnknownEntry { name: entry.name.clone() }),
+        };
+        if target.path != entry.path {
+            return Err(Error::EntryPathMismatch { name: entry.name.clone(), path: entry.path.clone(), expected: target.path });
+        }
+        res.push(target);
+    }
+    Ok(res)
+}
+
+/// Copies only the `data.yml` files of every dataset in the given data directory.
+///
+/// # Arguments
+/// - `source`: The data directory to read the datasets from.
+/// - `target`: The directory to write the metadata to. Every dataset gets its own nested directory.
+///
+/// # Errors
+/// This function errors if we failed to read the source directory or write any of the files.
+async fn copy_dataset_metadata(source: &Path, target: &Path) -> Result<(), Error> {
+    if let Err(err) = tfs::create_dir_all(target).await {
+        return Err(Error::DirCreateError { path: target.into(), err });
+    }
+    let mut entries: tfs::ReadDir = match tfs::read_dir(source).await {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::DirReadError { path: source.into(), err }),
+    };
+    loop {
+        let entry: tfs::DirEntry = match entries.next_entry().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(err) => return Err(Error::DirReadError { path: source.into(), err }),
+        };
+
+        // Only consider datasets with metadata
+        let info_path: PathBuf = entry.path().join("data.yml");
+        if !info_path.is_file() {
+            continue;
+        }
+        let target_dir: PathBuf = target.join(entry.file_name());
+        if let Err(err) = tfs::create_dir_all(&target_dir).await {
+            return Err(Error::DirCreateError { path: target_dir, err });
+        }
+        let target_path: PathBuf = target_dir.join("data.yml");
+        debug!("Copying '{}' to '{}'...", info_path.display(), target_path.display());
+        if let Err(err) = tfs::copy(&info_path, &target_path).await {
+            return Err(Error::CopyError { source: info_path, target: target_path, err });
+        }
+    }
+    Ok(())
+}
+
+/// Copies a single backup entry from the given source to the given target.
+///
+/// # Arguments
+/// - `kind`: How the entry is stored.
+/// - `source`: The path to copy from.
+/// - `target`: The path to copy to.
+///
+/// # Errors
+/// This function errors if we failed to copy the entry.
+async fn copy_entry(kind: EntryKind, source: &Path, target: &Path) -> Result<(), Error> {
+    // Make sure the parent exists
+    if let Some(parent) = target.parent() {
+        if let Err(err) = tfs::create_dir_all(parent).await {
+            return Err(Error::DirCreateError { path: parent.into(), err });
+        }
+    }
+
+    match kind {
+        EntryKind::File => {
+            debug!("Copying file '{}' to '{}'...", source.display(), target.display());
+            if let Err(err) = tfs::copy(source, target).await {
+                return Err(Error::CopyError { source: source.into(), target: target.into(), err });
+            }
+        },
+        EntryKind::Dir => {
+            if let Err(err) = copy_dir_recursively_async(source, target).await {
+                return Err(Error::DirCopyError { source: source.into(), target: target.into(), err: Box::new(err) });
+            }
+        },
+        EntryKind::DatasetMetadata => copy_dataset_metadata(source, target).await?,
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Creates a backup of the local node in a single archive.
+///
+/// The backup contains the `node.yml` file, the certificates, the package store, dataset metadata and (for workers) the policy database and secrets.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file of the node to back up.
+/// - `output`: The path of the `.tar.gz` archive to write.
+///
+/// # Errors
+/// This function errors if we failed to read the node config, copy any of the node's files or write the archive.
+pub async fn create(node_config_path: impl Into<PathBuf>, output: impl Into<PathBuf>) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    let output: PathBuf = output.into();
+    info!("Backing up node defined by '{}' to '{}'...", node_config_path.display(), output.display());
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+
+    // Prepare a staging directory
+    let staging: TempDir = match TempDir::new() {
+        Ok(staging) => staging,
+        Err(err) => return Err(Error::TempDirError { err }),
+    };
+
+    // Copy the node config and all entries into it
+    copy_entry(EntryKind::File, &node_config_path, &staging.path().join(NODE_CONFIG_NAME)).await?;
+    let mut entries: Vec<BackupEntry> = Vec::new();
+    for entry in collect_entries(&node_config) {
+        if !entry.path.exists() {
+            warn!("Skipping {} ('{}' does not exist)", entry.name, entry.path.display());
+            continue;
+        }
+        copy_entry(entry.kind, &entry.path, &staging.path().join(&entry.name)).await?;
+        entries.push(entry);
+    }
+
+    // Write the manifest
+    let manifest: BackupManifest = BackupManifest {
+        version: env!("CARGO_PKG_VERSION").into(),
+        created: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        kind: node_config.node.kind().to_string(),
+        entries,
+    };
+    let manifest_path: PathBuf = staging.path().join(MANIFEST_NAME);
+    let raw: String = match serde_yaml::to_string(&manifest) {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::ManifestSerializeError { err }),
+    };
+    if let Err(err) = tfs::write(&manifest_path, raw).await {
+        return Err(Error::FileWriteError { what: "manifest", path: manifest_path, err });
+    }
+
+    // Archive the lot, in a file that only we may read since it contains private keys and secrets
+    if let Err(err) = tfs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(&output).await {
+        return Err(Error::ArchiveCreateError { path: output, err });
+    }
+    if let Err(err) = tfs::set_permissions(&output, Permissions::from_mode(0o600)).await {
+        return Err(Error::ArchiveCreateError { path: output, err });
+    }
+    if let Err(err) = archive_async(staging.path(), &output, true).await {
+        return Err(Error::ArchiveError { path: output, err: Box::new(err) });
+    }
+
+    // Done
+    println!(
+        "Successfully backed up {} node to {} ({} entries)",
+        manifest.kind,
+        style(output.display().to_string()).bold().green(),
+        manifest.entries.len() + 1
+    );
+    Ok(())
+}
+
+
+
+/// Restores a backup of a node that was created with [`create()`].
+///
+/// Every entry is restored to the path that the `node.yml` file in the backup defines for it, and the `node.yml` file itself to the given path.
+/// Backups whose manifest lists other paths are rejected.
+///
+/// # Arguments
+/// - `node_config_path`: The path to write the restored node configuration file to.
+/// - `input`: The path of the `.tar.gz` archive to restore.
+/// - `force`: If true, overwrites existing files. Otherwise, refuses to restore if any of the targets already exist.
+///
+/// # Errors
+/// This function errors if we failed to read the archive, if its manifest does not match its node config, or if we failed to restore any of its
+/// entries.
+pub async fn restore(node_config_path: impl Into<PathBuf>, input: impl Into<PathBuf>, force: bool) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    let input: PathBuf = input.into();
+    info!("Restoring backup '{}' to node defined by '{}'...", input.display(), node_config_path.display());
+
+    // Unpack the archive
+    let staging: TempDir = match TempDir::new() {
+        Ok(staging) => staging,
+        Err(err) => return Err(Error::TempDirError { err }),
+    };
+    if let Err(err) = unarchive_async(&input, staging.path()).await {
+        return Err(Error::UnarchiveError { path: input, err: Box::new(err) });
+    }
+
+    // Read the manifest
+    let manifest_path: PathBuf = staging.path().join(MANIFEST_NAME);
+    let raw: String = match tfs::read_to_string(&manifest_path).await {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::FileReadError { what: "manifest", path: manifest_path, err }),
+    };
+    let manifest: BackupManifest = match serde_yaml::from_str(&raw) {
+        Ok(manifest) => manifest,
+        Err(err) => return Err(Error::ManifestDeserializeError { path: input, err }),
+    };
+    if manifest.version != env!("CARGO_PKG_VERSION") {
+        warn!("Backup was created by branectl v{}, but this is v{}", manifest.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    // Read the node config in the backup to find where to restore everything to (without resolving its secrets, which we don't need)
+    let staged_config_path: PathBuf = staging.path().join(NODE_CONFIG_NAME);
+    let raw: String = match tfs::read_to_string(&staged_config_path).await {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::FileReadError { what: "node config", path: staged_config_path, err }),
+    };
+    let raw: String = match interpolate_env(&raw) {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::NodeConfigInterpolateError { path: staged_config_path, err }),
+    };
+    let node_config: NodeConfig = match NodeConfig::from_string(raw) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigParseError { path: staged_config_path, err }),
+    };
+    let entries: Vec<BackupEntry> = restore_targets(&node_config, &manifest)?;
+
+    // Refuse to overwrite anything unless told otherwise
+    if !force {
+        for target in std::iter::once(&node_config_path).chain(entries.iter().map(|e| &e.path)) {
+            if target.exists() {
+                return Err(Error::TargetExists { path: target.clone() });
+            }
+        }
+    }
+
+    // Restore everything
+    copy_entry(EntryKind::File, &staged_config_path, &node_config_path).await?;
+    for entry in &entries {
+        debug!("Restoring {} to '{}'...", entry.name, entry.path.display());
+        if entry.kind == EntryKind::Dir && entry.path.is_dir() {
+            // Only reachable if `force` is given; clear it to avoid mixing old and restored files
+            debug!("Removing existing directory '{}'...", entry.path.display());
+            if let Err(err) = tfs::remove_dir_all(&entry.path).await {
+                return Err(Error::DirRemoveError { path: entry.path.clone(), err });
+            }
+        }
+        copy_entry(entry.kind, &staging.path().join(&entry.name), &entry.path).await?;
+    }
+
+    // Done
+    println!(
+        "Successfully restored {} node from {} ({} entries)",
+        manifest.kind,
+        style(input.display().to_string()).bold().green(),
+        manifest.entries.len() + 1
+    );
+    Ok(())
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+    use brane_cfg::node::{ProxyConfig, ProxyPaths, ProxyServices, PublicService};
+    use specifications::address::Address;
+
+    use super::*;
+
+
+    /// Creates a proxy [`NodeConfig`] whose files live in the given directory.
+    fn proxy_config(dir: &Path) -> NodeConfig {
+        NodeConfig {
+            hostnames: HashMap::new(),
+            namespace: "brane".into(),
+            node:      NodeSpecificConfig::Proxy(ProxyConfig {
+                paths:    ProxyPaths { certs: dir.join("certs"), proxy: dir.join("proxy.yml") },
+                services: ProxyServices {
+                    prx: PublicService {
+                        name: "brane-prx".into(),
+                        address: Address::Hostname("test.com".into(), 42),
+                        bind: SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(0, 0, 0, 0), 42)),
+                        external_address: Address::Hostname("test.com".into(), 42),
+                    },
+                },
+            }),
+            tracing:   None,
+            events:    None,
+
+            key_passphrase: None,
+        }
+    }
+
+    /// Creates a fresh, empty directory in the system temp folder.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-ctl-backup-{}-{}", name, std::process::id()));
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+
+    #[tokio::test]
+    async fn backup_roundtrip() {
+        let dir: PathBuf = test_dir("roundtrip");
+        let node_config_path: PathBuf = dir.join("node.yml");
+        let archive_path: PathBuf = dir.join("backup.tar.gz");
+        proxy_config(&dir).to_path(&node_config_path).unwrap();
+        std::fs::create_dir_all(dir.join("certs")).unwrap();
+        std::fs::write(dir.join("certs").join("ca.pem"), "ca").unwrap();
+        std::fs::write(dir.join("proxy.yml"), "proxy").unwrap();
+
+        create(&node_config_path, &archive_path).await.unwrap();
+        assert_eq!(std::fs::metadata(&archive_path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // Without `force`, existing files are left alone
+        assert!(matches!(restore(&node_config_path, &archive_path, false).await, Err(Error::TargetExists { .. })));
+
+        // Restore after removing everything
+        let node_config: String = std::fs::read_to_string(&node_config_path).unwrap();
+        std::fs::remove_file(&node_config_path).unwrap();
+        std::fs::remove_dir_all(dir.join("certs")).unwrap();
+        std::fs::remove_file(dir.join("proxy.yml")).unwrap();
+        restore(&node_config_path, &archive_path, false).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&node_config_path).unwrap(), node_config);
+        assert_eq!(std::fs::read_to_string(dir.join("certs").join("ca.pem")).unwrap(), "ca");
+        assert_eq!(std::fs::read_to_string(dir.join("proxy.yml")).unwrap(), "proxy");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restore_targets_rejects_foreign_paths() {
+        let dir: PathBuf = std::env::temp_dir().join("brane-ctl-backup-targets");
+        let node_config: NodeConfig = proxy_config(&dir);
+        let manifest = |entries: Vec<BackupEntry>| BackupManifest { version: "test".into(), created: 0, kind: "proxy".into(), entries };
+
+        // Entries matching the config are restored where the config says
+        let targets: Vec<BackupEntry> = restore_targets(&node_config, &manifest(collect_entries(&node_config))).unwrap();
+        assert_eq!(targets.iter().map(|e| e.path.clone()).collect::<Vec<_>>(), vec![dir.join("certs"), dir.join("proxy.yml")]);
+
+        // Entries elsewhere are not
+        let moved: BackupEntry = BackupEntry { name: "certs".into(), path: "/etc".into(), kind: EntryKind::Dir };
+        assert!(matches!(restore_targets(&node_config, &manifest(vec![moved])), Err(Error::EntryPathMismatch { .. })));
+
+        // Neither are entries that the config does not define
+        let unknown: BackupEntry = BackupEntry { name: "infra.yml".into(), path: dir.join("infra.yml"), kind: EntryKind::File };
+        assert!(matches!(restore_targets(&node_config, &manifest(vec![unknown])), Err(Error::UnknownEntry { .. })));
+    }
+}
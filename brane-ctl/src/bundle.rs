@@ -0,0 +1,386 @@
+//  BUNDLE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:02:37
+//  Last edited:
+//    15 Oct 2026, 21:02:37
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `branectl bundle` subcommands, which collect
+//!   everything needed to install a node into a single archive on a
+//!   connected machine and import it again on an air-gapped one.
+//
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use brane_shr::fs::{archive_async, download_file_async, move_path_async, recurse_in_only_child_async, unarchive_async, DownloadSecurity};
+use brane_tsk::docker::ClientVersion;
+use console::{style, Style};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use specifications::arch::Arch;
+use specifications::version::Version;
+use tempfile::TempDir;
+use tokio::fs as tfs;
+
+use crate::download;
+pub use crate::errors::BundleError as Error;
+use crate::spec::{BundleContent, DownloadServicesSubcommand};
+
+
+/***** CONSTANTS *****/
+/// The name of the manifest file in a bundle.
+const MANIFEST_NAME: &str = "bundle.yml";
+/// The name of the directory with images in a bundle.
+const IMAGES_DIR: &str = "images";
+/// The name of the policy reasoner archive in a bundle.
+const POLICY_REASONER_NAME: &str = "policy-reasoner.tar.gz";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes a single file stored in a bundle.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct BundleEntry {
+    /// The path of the entry in the bundle.
+    name:   String,
+    /// The hex-encoded SHA-256 checksum of the entry.
+    sha256: String,
+}
+
+/// Describes the contents of a bundle.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct BundleManifest {
+    /// The version of `branectl` that created the bundle.
+    version: String,
+    /// The version of the Brane services in the bundle.
+    brane:   Version,
+    /// The architecture of the images in the bundle.
+    arch:    Arch,
+    /// When the bundle was created, as a UNIX timestamp (in seconds).
+    created: u64,
+    /// The kinds of artifacts in the bundle.
+    content: Vec<String>,
+    /// The files in the bundle.
+    entries: Vec<BundleEntry>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the SHA-256 checksum of the given file.
+///
+/// # Arguments
+/// - `path`: The path of the file to hash.
+///
+/// # Returns
+/// The hex-encoded checksum.
+///
+/// # Errors
+/// This function errors if we failed to read the file.
+fn hash_file(path: &Path) -> Result<String, Error> {
+    let mut handle: File = match File::open(path) {
+        Ok(handle) => handle,
+        Err(err) => return Err(Error::FileOpenError { path: path.into(), err }),
+    };
+    let mut hasher: Sha256 = Sha256::new();
+    if let Err(err) = std::io::copy(&mut handle, &mut hasher) {
+        return Err(Error::FileHashError { path: path.into(), err });
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Lists the names of all files in the given directory of a bundle, relative to the bundle's root.
+///
+/// # Arguments
+/// - `root`: The root of the bundle.
+/// - `dir`: The name of the directory in the bundle to list.
+///
+/// # Errors
+/// This function errors if we failed to read the directory.
+async fn list_files(root: &Path, dir: &str) -> Result<Vec<String>, Error> {
+    let path: PathBuf = root.join(dir);
+    let mut entries: tfs::ReadDir = match tfs::read_dir(&path).await {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::DirReadError { path, err }),
+    };
+    let mut res: Vec<String> = Vec::new();
+    loop {
+        match entries.next_entry().await {
+            Ok(Some(entry)) => {
+                if entry.path().is_file() {
+                    res.push(format!("{}/{}", dir, entry.file_name().to_string_lossy()));
+                }
+            },
+            Ok(None) => break,
+            Err(err) => return Err(Error::DirReadError { path, err }),
+        }
+    }
+    res.sort();
+    Ok(res)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Collects everything needed to install a node without internet access into a single archive.
+///
+/// # Arguments
+/// - `output`: The path of the `.tar.gz` archive to write.
+/// - `arch`: The architecture for which to collect the images.
+/// - `version`: The version of the service images to collect.
+/// - `content`: The kinds of artifacts to collect. If empty, collects all of them.
+/// - `mirrors`: Any alternative mirrors to download the service images from (see [`download::services()`]).
+/// - `no_verify`: Whether to skip verifying the service images against their published checksums.
+/// - `branch`: The branch of the policy reasoner repository to collect the policy database migrations from.
+/// - `socket`: The Docker socket to use when pulling the auxillary images.
+/// - `client_version`: The Docker client API version to use when pulling the auxillary images.
+///
+/// # Errors
+/// This function errors if we failed to download any of the artifacts or write the archive.
+#[allow(clippy::too_many_arguments)]
+pub async fn export(
+    output: impl Into<PathBuf>,
+    arch: Arch,
+    version: Version,
+    content: Vec<BundleContent>,
+    mirrors: Vec<String>,
+    no_verify: bool,
+    branch: String,
+    socket: PathBuf,
+    client_version: ClientVersion,
+) -> Result<(), Error> {
+    let output: PathBuf = output.into();
+    let content: Vec<BundleContent> = if content.is_empty() { BundleContent::ALL.into() } else { content };
+    info!("Exporting bundle with {} to '{}'...", content.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(", "), output.display());
+
+    // Prepare a staging directory
+    let staging: TempDir = match TempDir::new() {
+        Ok(staging) => staging,
+        Err(err) => return Err(Error::TempDirError { err }),
+    };
+    let images_dir: PathBuf = staging.path().join(IMAGES_DIR);
+    if let Err(err) = tfs::create_dir_all(&images_dir).await {
+        return Err(Error::DirCreateError { path: images_dir, err });
+    }
+
+    // Collect the artifacts
+    for what in &content {
+        let kind: DownloadServicesSubcommand = match what {
+            BundleContent::Central => DownloadServicesSubcommand::Central,
+            BundleContent::Worker => DownloadServicesSubcommand::Worker,
+            BundleContent::Auxillary => DownloadServicesSubcommand::Auxillary { socket: socket.clone(), client_version },
+            BundleContent::PolicyReasoner => {
+                let url: String = format!("https://api.github.com/repos/epi-project/policy-reasoner/tarball/{branch}");
+                let path: PathBuf = staging.path().join(POLICY_REASONER_NAME);
+                println!("Downloading policy reasoner artifacts ({})...", style(&branch).bold().green());
+                if let Err(err) = download_file_async(&url, &path, DownloadSecurity::https(), Some(Style::new().green().bold())).await {
+                    return Err(Error::RepoDownloadError { repo: url, path, err: Box::new(err) });
+                }
+                continue;
+            },
+        };
+        if let Err(err) = download::services(false, &images_dir, arch, version, true, mirrors.clone(), None, no_verify, kind).await {
+            return Err(Error::ImagesDownloadError { what: what.to_string(), err: Box::new(err) });
+        }
+    }
+
+    // Hash everything we collected
+    let mut names: Vec<String> = list_files(staging.path(), IMAGES_DIR).await?;
+    if content.contains(&BundleContent::PolicyReasoner) {
+        names.push(POLICY_REASONER_NAME.into());
+    }
+    let mut entries: Vec<BundleEntry> = Vec::with_capacity(names.len());
+    for name in names {
+        debug!("Computing checksum of '{name}'...");
+        let sha256: String = hash_file(&staging.path().join(&name))?;
+        entries.push(BundleEntry { name, sha256 });
+    }
+
+    // Write the manifest
+    let manifest: BundleManifest = BundleManifest {
+        version: env!("CARGO_PKG_VERSION").into(),
+        brane: version,
+        arch,
+        created: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        content: content.iter().map(|c| c.to_string()).collect(),
+        entries,
+    };
+    let manifest_path: PathBuf = staging.path().join(MANIFEST_NAME);
+    let raw: String = match serde_yaml::to_string(&manifest) {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::ManifestSerializeError { err }),
+    };
+    if let Err(err) = tfs::write(&manifest_path, raw).await {
+        return Err(Error::FileWriteError { what: "manifest", path: manifest_path, err });
+    }
+
+    // Archive the lot
+    println!("Archiving bundle...");
+    if let Err(err) = archive_async(staging.path(), &output, true).await {
+        return Err(Error::ArchiveError { path: output, err: Box::new(err) });
+    }
+
+    // Done
+    println!(
+        "Successfully exported bundle for Brane {} ({}) to {} ({} files)",
+        manifest.brane,
+        manifest.arch,
+        style(output.display().to_string()).bold().green(),
+        manifest.entries.len()
+    );
+    Ok(())
+}
+
+
+
+/// Imports a bundle that was created with [`export()`] on an air-gapped machine.
+///
+/// Every file is verified against the checksum in the bundle's manifest before anything is installed.
+///
+/// # Arguments
+/// - `input`: The path of the `.tar.gz` archive to import.
+/// - `fix_dirs`: Whether to create missing directories or error instead.
+/// - `path`: The directory to write the service images to (i.e., what is given as `--image-dir` to `branectl start`).
+/// - `policy_reasoner`: The directory to unpack the policy reasoner artifacts to.
+/// - `force`: If true, overwrites existing files. Otherwise, refuses to import if any of the targets already exist.
+///
+/// # Errors
+/// This function errors if we failed to read the archive, any of its files did not match its checksum or we failed to install any of them.
+pub async fn import(
+    input: impl Into<PathBuf>,
+    fix_dirs: bool,
+    path: impl Into<PathBuf>,
+    policy_reasoner: impl Into<PathBuf>,
+    force: bool,
+) -> Result<(), Error> {
+    let input: PathBuf = input.into();
+    let path: PathBuf = path.into();
+    let policy_reasoner: PathBuf = policy_reasoner.into();
+    info!("Importing bundle '{}' to '{}'...", input.display(), path.display());
+
+    // Unpack the archive
+    let staging: TempDir = match TempDir::new() {
+        Ok(staging) => staging,
+        Err(err) => return Err(Error::TempDirError { err }),
+    };
+    println!("Unpacking {}...", style(input.display()).bold().green());
+    if let Err(err) = unarchive_async(&input, staging.path()).await {
+        return Err(Error::UnarchiveError { path: input, err: Box::new(err) });
+    }
+
+    // Read the manifest
+    let manifest_path: PathBuf = staging.path().join(MANIFEST_NAME);
+    let raw: String = match tfs::read_to_string(&manifest_path).await {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::FileReadError { what: "manifest", path: manifest_path, err }),
+    };
+    let manifest: BundleManifest = match serde_yaml::from_str(&raw) {
+        Ok(manifest) => manifest,
+        Err(err) => return Err(Error::ManifestDeserializeError { path: input, err }),
+    };
+    if manifest.version != env!("CARGO_PKG_VERSION") {
+        warn!("Bundle was created by branectl v{}, but this is v{}", manifest.version, env!("CARGO_PKG_VERSION"));
+    }
+    if manifest.arch != Arch::HOST {
+        warn!("Bundle contains images for {}, but this machine is {}", manifest.arch, Arch::HOST);
+    }
+
+    // Verify everything before touching the target directories
+    for entry in &manifest.entries {
+        let entry_path: PathBuf = staging.path().join(&entry.name);
+        if !entry_path.is_file() {
+            return Err(Error::EntryMissing { name: entry.name.clone() });
+        }
+        debug!("Verifying checksum of '{}'...", entry.name);
+        let got: String = hash_file(&entry_path)?;
+        if !got.eq_ignore_ascii_case(&entry.sha256) {
+            return Err(Error::ChecksumMismatch { name: entry.name.clone(), expected: entry.sha256.clone(), got });
+        }
+    }
+
+    // Prepare the targets
+    let has_policy_reasoner: bool = manifest.entries.iter().any(|e| e.name == POLICY_REASONER_NAME);
+    let images: Vec<&BundleEntry> = manifest.entries.iter().filter(|e| e.name.starts_with(&format!("{IMAGES_DIR}/"))).collect();
+    if !images.is_empty() && !path.exists() {
+        if !fix_dirs {
+            return Err(Error::DirNotFound { what: "image", path });
+        }
+        if let Err(err) = tfs::create_dir_all(&path).await {
+            return Err(Error::DirCreateError { path, err });
+        }
+    }
+    if !force {
+        for entry in &images {
+            let target: PathBuf = path.join(&entry.name[IMAGES_DIR.len() + 1..]);
+            if target.exists() {
+                return Err(Error::TargetExists { path: target });
+            }
+        }
+        if has_policy_reasoner && policy_reasoner.exists() {
+            return Err(Error::TargetExists { path: policy_reasoner });
+        }
+    }
+
+    // Install the images
+    for entry in &images {
+        let source: PathBuf = staging.path().join(&entry.name);
+        let target: PathBuf = path.join(&entry.name[IMAGES_DIR.len() + 1..]);
+        debug!("Moving '{}' to '{}'...", source.display(), target.display());
+        if let Err(err) = move_path_async(&source, &target).await {
+            return Err(Error::MoveError { source, target, err: Box::new(err) });
+        }
+    }
+
+    // Install the policy reasoner artifacts
+    if has_policy_reasoner {
+        let tar_path: PathBuf = staging.path().join(POLICY_REASONER_NAME);
+        let dir_path: PathBuf = staging.path().join("policy-reasoner");
+        if let Err(err) = unarchive_async(&tar_path, &dir_path).await {
+            return Err(Error::RepoUnpackError { tar: tar_path, target: dir_path, err: Box::new(err) });
+        }
+        // Resolve that one weird folder in there
+        let dir_path: PathBuf = match recurse_in_only_child_async(&dir_path).await {
+            Ok(path) => path,
+            Err(err) => return Err(Error::RepoRecurseError { target: dir_path, err: Box::new(err) }),
+        };
+        if policy_reasoner.is_dir() {
+            // Only reachable if `force` is given; clear it to avoid mixing old and imported files
+            debug!("Removing existing directory '{}'...", policy_reasoner.display());
+            if let Err(err) = tfs::remove_dir_all(&policy_reasoner).await {
+                return Err(Error::DirRemoveError { path: policy_reasoner, err });
+            }
+        }
+        debug!("Moving '{}' to '{}'...", dir_path.display(), policy_reasoner.display());
+        if let Err(err) = move_path_async(&dir_path, &policy_reasoner).await {
+            return Err(Error::MoveError { source: dir_path, target: policy_reasoner, err: Box::new(err) });
+        }
+        println!(
+            "Policy reasoner artifacts imported to {}; use 'branectl generate policy_database --migrations {}' to create a policy database",
+            style(policy_reasoner.display()).bold().green(),
+            policy_reasoner.display()
+        );
+    }
+
+    // Done
+    println!(
+        "Successfully imported bundle for Brane {} ({}) from {} ({} files)",
+        manifest.brane,
+        manifest.arch,
+        style(input.display().to_string()).bold().green(),
+        manifest.entries.len()
+    );
+    Ok(())
+}
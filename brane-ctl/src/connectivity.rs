@@ -0,0 +1,554 @@
+//  CONNECTIVITY.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:34:17
+//  Last edited:
+//    15 Oct 2026, 21:34:17
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements `branectl test connectivity`, which attempts real
+//!   connections to the services of the other domains in an instance to
+//!   diagnose networking and certificate problems.
+//
+
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs as _};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use brane_cfg::certs::{cert_expiry, load_certstore, load_identity, CertExpiry};
+use brane_cfg::info::Info as _;
+use brane_cfg::infra::InfraFile;
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig};
+use console::style;
+use log::{debug, info};
+use rustls::{
+    AlertDescription, Certificate, CertificateError, ClientConfig, ClientConnection, ConfigBuilder, PrivateKey, RootCertStore, ServerName,
+    StreamOwned,
+};
+use specifications::address::Address;
+
+pub use crate::errors::ConnectivityError as Error;
+
+
+/***** CONSTANTS *****/
+/// Certificates that expire within this window are reported as a warning.
+const EXPIRY_WARN_WINDOW: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes a single endpoint to test.
+#[derive(Clone, Debug)]
+struct Target {
+    /// The domain (i.e., location or use-case) the endpoint belongs to.
+    domain:  String,
+    /// The service we expect at the endpoint (e.g., `registry`).
+    service: &'static str,
+    /// The address of the endpoint. Its hostname may be prefixed by a scheme (e.g., `https://`).
+    address: Address,
+    /// Whether the service has a `/health` endpoint that we can query.
+    health:  bool,
+}
+
+/// Describes why a [`Target`] could not be reached, and what to do about it.
+#[derive(Clone, Debug)]
+struct Failure {
+    /// What went wrong.
+    reason: String,
+    /// What the user can do to fix it.
+    fix:    String,
+}
+
+/// Describes the outcome of testing a single [`Target`].
+#[derive(Debug)]
+struct Outcome {
+    /// The time it took to establish the TCP connection, which approximates a round-trip.
+    latency: Option<Duration>,
+    /// The expiry of the certificate presented by the server, if it used TLS.
+    peer:    Option<CertExpiry>,
+    /// Either a short description of the successful check, or why it failed.
+    result:  Result<String, Failure>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Splits the (optional) scheme from the hostname in an address.
+///
+/// # Arguments
+/// - `address`: The [`Address`] to split.
+///
+/// # Returns
+/// A tuple of the scheme (if any) and the bare host.
+fn split_scheme(address: &Address) -> (Option<String>, String) {
+    let domain: String = address.domain().into_owned();
+    match domain.split_once("://") {
+        Some((scheme, host)) => (Some(scheme.to_lowercase()), host.into()),
+        None => (None, domain),
+    }
+}
+
+/// Collects the endpoints that this node should be able to reach.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] of this node.
+/// - `infra`: The [`InfraFile`] describing the other domains, if any.
+///
+/// # Returns
+/// The list of [`Target`]s to test.
+fn collect_targets(node_config: &NodeConfig, infra: Option<&InfraFile>) -> Vec<Target> {
+    let mut targets: Vec<Target> = vec![];
+    let own_name: Option<&str> = match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            // Check our own public services are reachable from the outside
+            targets.push(Target {
+                domain:  "<this node>".into(),
+                service: "api",
+                address: central.services.api.external_address.clone(),
+                health:  true,
+            });
+            targets.push(Target {
+                domain:  "<this node>".into(),
+                service: "driver",
+                address: central.services.drv.external_address.clone(),
+                health:  false,
+            });
+            None
+        },
+        NodeSpecificConfig::Worker(worker) => {
+            // Workers must be able to reach the central registry of every use-case they participate in
+            let mut usecases: Vec<(&String, &brane_cfg::node::WorkerUsecase)> = worker.usecases.iter().collect();
+            usecases.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+            for (name, usecase) in usecases {
+                targets.push(Target { domain: name.clone(), service: "api", address: usecase.api.clone(), health: true });
+            }
+            Some(worker.name.as_str())
+        },
+        NodeSpecificConfig::Proxy(_) => None,
+    };
+
+    // Then add the services of the other domains
+    if let Some(infra) = infra {
+        let mut locations: Vec<(&String, &brane_cfg::infra::InfraLocation)> = infra.iter().collect();
+        locations.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        for (name, location) in locations {
+            if Some(name.as_str()) == own_name {
+                continue;
+            }
+            targets.push(Target { domain: name.clone(), service: "registry", address: location.registry.clone(), health: true });
+            // Only the central node talks to the delegates
+            if own_name.is_none() {
+                targets.push(Target { domain: name.clone(), service: "delegate", address: location.delegate.clone(), health: false });
+            }
+        }
+    }
+    targets
+}
+
+/// Resolves a host to the socket addresses to try.
+///
+/// # Arguments
+/// - `host`: The host to resolve.
+/// - `port`: The port to connect to.
+/// - `node_config`: The [`NodeConfig`] of which the `hostnames` take precedence over DNS, like they do for the services.
+///
+/// # Errors
+/// This function errors if the host could not be resolved.
+fn resolve(host: &str, port: u16, node_config: &NodeConfig) -> Result<Vec<SocketAddr>, Failure> {
+    if let Some(ip) = node_config.hostnames.get(host) {
+        debug!("Resolved '{host}' to '{ip}' using the node config's hostnames");
+        return Ok(vec![SocketAddr::new(*ip, port)]);
+    }
+    match (host, port).to_socket_addrs() {
+        Ok(addrs) => Ok(addrs.collect()),
+        Err(err) => Err(Failure {
+            reason: format!("failed to resolve '{host}': {err}"),
+            fix:    format!("Fix the DNS record of '{host}', or map it to an IP address in the 'hostnames' section of your node.yml"),
+        }),
+    }
+}
+
+/// Opens a TCP connection to any of the given addresses.
+///
+/// # Arguments
+/// - `addrs`: The addresses to try, in order.
+/// - `timeout`: How long to wait for every attempt.
+///
+/// # Returns
+/// The connection, together with the time it took to establish it.
+///
+/// # Errors
+/// This function errors if none of the addresses could be connected to.
+fn connect(addrs: &[SocketAddr], timeout: Duration) -> Result<(TcpStream, Duration), Failure> {
+    let mut last: Option<(SocketAddr, io::Error)> = None;
+    for addr in addrs {
+        let start: Instant = Instant::now();
+        match TcpStream::connect_timeout(addr, timeout) {
+            Ok(sock) => {
+                let latency: Duration = start.elapsed();
+                // Don't let the rest of the test hang on a misbehaving server either
+                if let Err(err) = sock.set_read_timeout(Some(timeout)).and_then(|_| sock.set_write_timeout(Some(timeout))) {
+                    debug!("Failed to set timeouts on connection to '{addr}': {err}");
+                }
+                return Ok((sock, latency));
+            },
+            Err(err) => last = Some((*addr, err)),
+        }
+    }
+
+    // Explain the last error
+    let Some((addr, err)) = last else {
+        return Err(Failure { reason: "host resolved to no addresses".into(), fix: "Check the DNS records of the host".into() });
+    };
+    let fix: String = match err.kind() {
+        io::ErrorKind::ConnectionRefused => format!(
+            "Nothing is listening on {addr}; check that the service is running on the remote domain and that the port is exposed (and forwarded by \
+             its proxy, if any)"
+        ),
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+            format!("The connection to {addr} timed out; check the firewalls between this node and the remote domain")
+        },
+        _ => format!("Check the network route from this node to {addr}"),
+    };
+    Err(Failure { reason: format!("failed to connect to {addr}: {err}"), fix })
+}
+
+/// Builds the TLS client configuration for talking to the given domain.
+///
+/// # Arguments
+/// - `certs_dir`: The node's certificate directory.
+/// - `domain`: The domain to build the config for. Its certificates are expected in `<certs_dir>/<domain>`.
+///
+/// # Returns
+/// The [`ClientConfig`], and the expiry of our client certificate if we have one.
+///
+/// # Errors
+/// This function errors if the certificates for the domain are missing or invalid.
+fn tls_config(certs_dir: &Path, domain: &str) -> Result<(ClientConfig, Option<CertExpiry>), Failure> {
+    let ca_path: PathBuf = certs_dir.join(domain).join("ca.pem");
+    let ca: RootCertStore = match load_certstore(&ca_path) {
+        Ok(ca) => ca,
+        Err(err) => {
+            return Err(Failure {
+                reason: format!("failed to load CA certificate '{}': {}", ca_path.display(), err),
+                fix:    format!("Obtain the CA certificate ('ca.pem') of domain '{}' and place it at '{}'", domain, ca_path.display()),
+            });
+        },
+    };
+    let config: ConfigBuilder<_, _> = ClientConfig::builder().with_safe_defaults().with_root_certificates(ca);
+
+    // Add the client certificate if we have one
+    let client_path: PathBuf = certs_dir.join(domain).join("client-id.pem");
+    if !client_path.exists() {
+        return Ok((config.with_no_client_auth(), None));
+    }
+    let (certs, key): (Vec<Certificate>, PrivateKey) = match load_identity(&client_path) {
+        Ok(identity) => identity,
+        Err(err) => {
+            return Err(Failure {
+                reason: format!("failed to load client certificate '{}': {}", client_path.display(), err),
+                fix:    format!("Ask domain '{}' to issue a new client certificate for this node ('branectl generate certs client')", domain),
+            });
+        },
+    };
+    let expiry: Option<CertExpiry> = certs.first().and_then(|cert| cert_expiry(&client_path, cert).ok());
+    match config.with_client_auth_cert(certs, key) {
+        Ok(config) => Ok((config, expiry)),
+        Err(err) => Err(Failure {
+            reason: format!("invalid client certificate '{}': {}", client_path.display(), err),
+            fix:    format!("Ask domain '{}' to issue a new client certificate for this node ('branectl generate certs client')", domain),
+        }),
+    }
+}
+
+/// Explains a TLS error.
+///
+/// # Arguments
+/// - `err`: The [`io::Error`] returned by the TLS connection.
+/// - `target`: The [`Target`] we were connecting to.
+/// - `host`: The host we were connecting to.
+///
+/// # Returns
+/// A [`Failure`] with a suggested fix for the error.
+fn explain_tls(err: io::Error, target: &Target, host: &str) -> Failure {
+    let reason: String = format!("TLS handshake failed: {err}");
+    let domain: &str = &target.domain;
+    let fix: String = match err.get_ref().and_then(|err| err.downcast_ref::<rustls::Error>()) {
+        Some(rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer | CertificateError::BadSignature)) => {
+            format!("The server certificate is not signed by the CA we have for domain '{domain}'; ask the domain for its current 'ca.pem'")
+        },
+        Some(rustls::Error::InvalidCertificate(CertificateError::NotValidForName)) => format!(
+            "The server certificate is not issued for '{host}'; regenerate it on domain '{domain}' with 'branectl generate certs server --hostname \
+             {host}', or use the hostname it was issued for in infra.yml"
+        ),
+        Some(rustls::Error::InvalidCertificate(CertificateError::Expired | CertificateError::NotValidYet)) => format!(
+            "The server certificate is not (or no longer) valid; check the clocks of both machines and renew the certificate on domain '{domain}' \
+             with 'branectl generate certs server'"
+        ),
+        Some(rustls::Error::AlertReceived(
+            AlertDescription::BadCertificate
+            | AlertDescription::UnknownCA
+            | AlertDescription::CertificateRequired
+            | AlertDescription::CertificateExpired
+            | AlertDescription::AccessDenied,
+        )) => {
+            format!("Domain '{domain}' rejected our client certificate; ask it to issue a new one for this node ('branectl generate certs client')")
+        },
+        _ => format!("Check that the {} of domain '{}' indeed serves TLS on this port", target.service, domain),
+    };
+    Failure { reason, fix }
+}
+
+/// Sends a request to the `/health` endpoint over the given stream.
+///
+/// # Arguments
+/// - `stream`: The (possibly TLS) stream to send the request on.
+/// - `host`: The host we are connecting to, used for the `Host`-header.
+///
+/// # Returns
+/// The status code returned by the server.
+///
+/// # Errors
+/// This function errors if we failed to send the request or if the server did not respond with HTTP.
+fn health<S: io::Read + io::Write>(stream: &mut S, host: &str) -> Result<u16, io::Error> {
+    write!(stream, "GET /health HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n")?;
+    stream.flush()?;
+
+    // Read until we have the status line
+    let mut res: Vec<u8> = Vec::with_capacity(64);
+    let mut buf: [u8; 64] = [0; 64];
+    while !res.windows(2).any(|w| w == b"\r\n") {
+        let n: usize = stream.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        res.extend_from_slice(&buf[..n]);
+    }
+    let line: String = String::from_utf8_lossy(&res).lines().next().unwrap_or_default().to_string();
+    match line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()) {
+        Some(code) if line.starts_with("HTTP/") => Ok(code),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("not an HTTP response: '{line}'"))),
+    }
+}
+
+/// Tests a single target.
+///
+/// # Arguments
+/// - `target`: The [`Target`] to test.
+/// - `node_config`: The [`NodeConfig`] of this node.
+/// - `certs_dir`: The certificate directory of this node.
+/// - `timeout`: How long to wait for every network operation.
+///
+/// # Returns
+/// An [`Outcome`] describing how it went.
+fn probe(target: &Target, node_config: &NodeConfig, certs_dir: &Path, timeout: Duration) -> Outcome {
+    let (scheme, host): (Option<String>, String) = split_scheme(&target.address);
+    let port: u16 = target.address.port();
+    let mut outcome: Outcome = Outcome { latency: None, peer: None, result: Err(Failure { reason: String::new(), fix: String::new() }) };
+
+    // Connect
+    let addrs: Vec<SocketAddr> = match resolve(&host, port, node_config) {
+        Ok(addrs) => addrs,
+        Err(failure) => {
+            outcome.result = Err(failure);
+            return outcome;
+        },
+    };
+    let (mut sock, latency): (TcpStream, Duration) = match connect(&addrs, timeout) {
+        Ok(res) => res,
+        Err(failure) => {
+            outcome.result = Err(failure);
+            return outcome;
+        },
+    };
+    outcome.latency = Some(latency);
+
+    // Do TLS if the scheme tells us to
+    if scheme.as_deref() != Some("https") {
+        if !target.health {
+            outcome.result = Ok("reachable".into());
+            return outcome;
+        }
+        outcome.result = match health(&mut sock, &host) {
+            Ok(code) if (200..300).contains(&code) => Ok(format!("healthy (HTTP {code})")),
+            Ok(code) => Err(Failure {
+                reason: format!("health check returned HTTP {code}"),
+                fix:    format!("Inspect the logs of the {} on domain '{}' ('branectl logs')", target.service, target.domain),
+            }),
+            Err(err) => Err(Failure {
+                reason: format!("health check failed: {err}"),
+                fix:    format!("Check that port {} of domain '{}' belongs to its {}", port, target.domain, target.service),
+            }),
+        };
+        return outcome;
+    }
+    let (config, client): (ClientConfig, Option<CertExpiry>) = match tls_config(certs_dir, &target.domain) {
+        Ok(res) => res,
+        Err(failure) => {
+            outcome.result = Err(failure);
+            return outcome;
+        },
+    };
+    let name: ServerName = match host.parse::<IpAddr>() {
+        Ok(ip) => ServerName::IpAddress(ip),
+        Err(_) => match ServerName::try_from(host.as_str()) {
+            Ok(name) => name,
+            Err(err) => {
+                outcome.result = Err(Failure {
+                    reason: format!("'{host}' is not a valid TLS server name: {err}"),
+                    fix:    "Use a valid hostname or IP address in infra.yml".into(),
+                });
+                return outcome;
+            },
+        },
+    };
+    let conn: ClientConnection = match ClientConnection::new(Arc::new(config), name) {
+        Ok(conn) => conn,
+        Err(err) => {
+            outcome.result = Err(explain_tls(io::Error::new(io::ErrorKind::InvalidData, err), target, &host));
+            return outcome;
+        },
+    };
+    let mut stream: StreamOwned<ClientConnection, TcpStream> = StreamOwned::new(conn, sock);
+    while stream.conn.is_handshaking() {
+        if let Err(err) = stream.conn.complete_io(&mut stream.sock) {
+            outcome.result = Err(explain_tls(err, target, &host));
+            return outcome;
+        }
+    }
+    outcome.peer = stream.conn.peer_certificates().and_then(|certs| certs.first()).and_then(|cert| cert_expiry(&host, cert).ok());
+
+    // Warn about certificates that are about to expire
+    if let Some(expiry) = outcome.peer.iter().chain(client.iter()).find(|expiry| expiry.expires_within(EXPIRY_WARN_WINDOW)) {
+        outcome.result = Err(Failure {
+            reason: format!("certificate '{}' expires soon", expiry.subject),
+            fix:    format!("Renew the certificate from '{}' before it expires ('branectl generate certs')", expiry.path.display()),
+        });
+        return outcome;
+    }
+
+    // The server only checks our client certificate after the handshake, so do a request to find out whether it was accepted
+    outcome.result = if target.health {
+        match health(&mut stream, &host) {
+            Ok(code) if (200..300).contains(&code) => Ok(format!("healthy over TLS (HTTP {code})")),
+            Ok(code) => Err(Failure {
+                reason: format!("health check returned HTTP {code}"),
+                fix:    format!("Inspect the logs of the {} on domain '{}' ('branectl logs')", target.service, target.domain),
+            }),
+            Err(err) => Err(explain_tls(err, target, &host)),
+        }
+    } else {
+        Ok("TLS handshake succeeded".into())
+    };
+    outcome
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Tests whether this node can reach the services of the other domains in the instance.
+///
+/// For every endpoint, we resolve its host (honouring the node config's `hostnames`), open a TCP connection, and, if the endpoint uses
+/// `https`, perform a TLS handshake with the certificates in `<certs>/<domain>`. Services with a health endpoint are queried as well.
+/// Note that the connections are made directly from this machine, not through the node's proxy service.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file of this node.
+/// - `infra_path`: The path to the `infra.yml` describing the other domains. Defaults to the one in the node config for central nodes.
+/// - `timeout`: How long to wait for every network operation.
+///
+/// # Errors
+/// This function errors if we failed to load the config files, or if any of the endpoints could not be reached properly.
+pub fn connectivity(node_config_path: impl Into<PathBuf>, infra_path: Option<PathBuf>, timeout: Duration) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Testing connectivity of node defined by '{}'...", node_config_path.display());
+
+    // Load the node config and (if any) the infra file
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+    let (certs_dir, infra_path): (&Path, Option<PathBuf>) = match &node_config.node {
+        NodeSpecificConfig::Central(central) => (&central.paths.certs, Some(infra_path.unwrap_or_else(|| central.paths.infra.clone()))),
+        NodeSpecificConfig::Worker(worker) => (&worker.paths.certs, infra_path),
+        NodeSpecificConfig::Proxy(proxy) => (&proxy.paths.certs, infra_path),
+    };
+    let infra: Option<InfraFile> = match infra_path {
+        Some(path) => match InfraFile::from_path(&path) {
+            Ok(infra) => Some(infra),
+            Err(err) => return Err(Error::InfraLoadError { path, err }),
+        },
+        None => None,
+    };
+
+    // Test every target
+    let targets: Vec<Target> = collect_targets(&node_config, infra.as_ref());
+    if targets.is_empty() {
+        println!("Nothing to test; give an infra.yml file with '--infra' to test the connectivity to other domains.");
+        return Ok(());
+    }
+    let mut fixes: Vec<String> = vec![];
+    for target in &targets {
+        debug!("Testing {} of '{}' at '{}'...", target.service, target.domain, target.address);
+        let outcome: Outcome = probe(target, &node_config, certs_dir, timeout);
+
+        // Report it
+        let latency: String = outcome.latency.map(|l| format!("{} ms", l.as_millis())).unwrap_or_else(|| "-".into());
+        let validity: String = match &outcome.peer {
+            Some(peer) => match peer.remaining() {
+                Some(remaining) => format!(", certificate valid for {} days", remaining.as_secs() / (24 * 60 * 60)),
+                None => ", certificate expired".into(),
+            },
+            None => String::new(),
+        };
+        match outcome.result {
+            Ok(what) => println!(
+                "{} {} {} ({}): {} ({}{})",
+                style("[ OK ]").bold().green(),
+                style(&target.domain).bold(),
+                target.service,
+                target.address,
+                what,
+                latency,
+                validity
+            ),
+            Err(failure) => {
+                println!(
+                    "{} {} {} ({}): {} ({}{})",
+                    style("[FAIL]").bold().red(),
+                    style(&target.domain).bold(),
+                    target.service,
+                    target.address,
+                    failure.reason,
+                    latency,
+                    validity
+                );
+                fixes.push(format!("{} {}: {}", style(&target.domain).bold(), target.service, failure.fix));
+            },
+        }
+    }
+
+    // Summarize
+    println!();
+    if fixes.is_empty() {
+        println!("All {} endpoints are reachable.", style(targets.len()).bold().green());
+        return Ok(());
+    }
+    println!("Suggested fixes:");
+    for fix in &fixes {
+        println!("  - {fix}");
+    }
+    println!();
+    Err(Error::Unreachable { failed: fixes.len(), total: targets.len() })
+}
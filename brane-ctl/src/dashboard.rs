@@ -0,0 +1,70 @@
+//  DASHBOARD.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:33
+//  Last edited:
+//    16 Oct 2026, 01:39:25
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the optional dashboard (`brane-web`) that can be deployed
+//!   alongside a central node.
+//
+
+
+/***** CONSTANTS *****/
+/// The Docker Compose service that serves the dashboard.
+///
+/// This is inserted in the central node's Compose file by [`add_to_compose()`]. Because the dashboard does not authenticate its users, it
+/// is only exposed on the host's loopback interface unless `WEB_HOST` says otherwise.
+const COMPOSE_SERVICE: &str = r#"
+  ###############
+  ## DASHBOARD ##
+  ###############
+
+  # Shows the packages, datasets, active workflows and domain health of the instance in a browser
+  brane-web:
+    image: brane-web:${BRANE_VERSION:-latest}
+    container_name: ${WEB_NAME:-brane-web}
+    restart: always
+    environment:
+      REFRESH: ${WEB_REFRESH:-30}
+      DRV_TOKEN: ${WEB_DRV_TOKEN:-}
+    ports:
+    - "${WEB_HOST:-127.0.0.1}:${WEB_PORT:-50070}:50070"
+    volumes:
+    - ${NODE_CONFIG_PATH}:/node.yml
+    depends_on:
+    - brane-prx
+    - brane-api
+    - brane-drv
+"#;
+
+
+
+
+
+/***** LIBRARY *****/
+/// Inserts the dashboard service in the given Docker Compose file of a central node.
+///
+/// # Arguments
+/// - `compose`: The contents of the Docker Compose file to extend.
+///
+/// # Returns
+/// The contents of the Docker Compose file with the `brane-web` service added.
+pub fn add_to_compose(compose: &str) -> String {
+    // Insert the service right before the toplevel `networks`-section, or at the end if there is none
+    let (services, rest): (&str, &str) = match compose.find("\nnetworks:") {
+        Some(pos) => compose.split_at(pos + 1),
+        None => (compose, ""),
+    };
+    let mut res: String = String::with_capacity(compose.len() + COMPOSE_SERVICE.len() + 2);
+    res.push_str(services.trim_end_matches('\n'));
+    res.push('\n');
+    res.push_str(COMPOSE_SERVICE);
+    res.push('\n');
+    res.push_str(rest);
+    res
+}
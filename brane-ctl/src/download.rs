@@ -4,7 +4,7 @@
 //  Created:
 //    20 Feb 2023, 14:59:16
 //  Last edited:
-//    13 Apr 2023, 09:56:02
+//    16 Oct 2026, 02:38:11
 //  Auto updated?
 //    Yes
 //
@@ -21,6 +21,7 @@ use brane_shr::fs::{download_file_async, move_path_async, unarchive_async, Downl
 use brane_tsk::docker::{connect_local, ensure_image, save_image, Docker, DockerOptions, ImageSource};
 use console::{style, Style};
 use enum_debug::EnumDebug as _;
+use error_trace::ErrorTrace as _;
 use log::{debug, info, warn};
 use specifications::arch::Arch;
 use specifications::container::Image;
@@ -32,6 +33,12 @@ use crate::spec::DownloadServicesSubcommand;
 
 
 /***** CONSTANTS *****/
+/// The location where releases are published by default. Mirrors are expected to follow the same layout (i.e., `<BASE>/latest/download/<FILE>` and `<BASE>/download/v<VERSION>/<FILE>`).
+pub const DEFAULT_RELEASE_BASE: &str = "https://github.com/epi-project/brane/releases";
+
+/// The name of the file published alongside every release that lists the SHA-256 checksums of its artifacts (in `sha256sum` format).
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+
 /// Defines the auxillary images that we want to download from Docker.
 const AUXILLARY_DOCKER_IMAGES: [(&str, &str); 3] =
     [("aux-scylla", "scylladb/scylla:4.6.3"), ("aux-kafka", "ubuntu/kafka:3.1-22.04_beta"), ("aux-zookeeper", "ubuntu/zookeeper:3.1-22.04_beta")];
@@ -41,20 +48,165 @@ const AUXILLARY_DOCKER_IMAGES: [(&str, &str); 3] =
 
 
 /***** HELPER FUNCTIONS *****/
+/// Resolves the address of a release file on the given mirror.
+///
+/// # Arguments
+/// - `base`: The base address of the mirror (e.g., [`DEFAULT_RELEASE_BASE`]).
+/// - `version`: The version of the release to get the file from. May be 'latest'.
+/// - `file`: The name of the file to resolve.
+///
+/// # Returns
+/// The full address of the file.
+fn release_address(base: &str, version: &Version, file: &str) -> String {
+    let base: &str = base.trim_end_matches('/');
+    if version.is_latest() { format!("{base}/latest/download/{file}") } else { format!("{base}/download/v{version}/{file}") }
+}
+
+/// Parses a hexadecimal SHA-256 checksum.
+///
+/// # Arguments
+/// - `raw`: The hexadecimal string to parse.
+///
+/// # Returns
+/// The raw bytes of the checksum.
+///
+/// # Errors
+/// This function errors if the given string is not valid hex or does not encode exactly 32 bytes.
+fn parse_checksum(raw: &str) -> Result<Vec<u8>, Error> {
+    let checksum: Vec<u8> = match hex::decode(raw.trim()) {
+        Ok(checksum) => checksum,
+        Err(err) => return Err(Error::InvalidChecksum { raw: raw.into(), err }),
+    };
+    if checksum.len() != 32 {
+        return Err(Error::InvalidChecksumLength { raw: raw.into(), got: checksum.len() });
+    }
+    Ok(checksum)
+}
+
+/// Finds the checksum of the given file in the contents of a checksums file.
+///
+/// The file is expected to be in the format emitted by `sha256sum`, i.e., lines of `<HEX>  <FILE>` (optionally with a `*` before the filename).
+///
+/// # Arguments
+/// - `sums`: The contents of the checksums file.
+/// - `file`: The name of the file to find.
+///
+/// # Returns
+/// The hexadecimal checksum of the file, or [`None`] if it wasn't listed.
+fn find_checksum<'s>(sums: &'s str, file: &str) -> Option<&'s str> {
+    for line in sums.lines() {
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Split it into the hash and the filename
+        let mut parts = line.split_whitespace();
+        let (Some(hash), Some(name)) = (parts.next(), parts.next()) else {
+            debug!("Skipping malformed checksums line '{line}'");
+            continue;
+        };
+        let name: &str = name.trim_start_matches('*').trim_start_matches("./");
+        if name == file {
+            return Some(hash);
+        }
+    }
+    None
+}
+
+/// Downloads the published checksums file from the canonical release page (i.e., [`DEFAULT_RELEASE_BASE`]) and finds the checksum of the given
+/// file in it.
+///
+/// The checksums are never taken from a mirror, since a mirror serving tampered artifacts could then simply serve matching checksums too.
+///
+/// # Arguments
+/// - `version`: The version of the release to get the checksum for.
+/// - `file`: The name of the file to find the checksum of.
+/// - `dir`: A (temporary) directory to download the checksums file to.
+///
+/// # Returns
+/// The raw bytes of the checksum.
+///
+/// # Errors
+/// This function errors if we failed to download or read the checksums file, or if it did not list (a valid checksum for) the given file.
+async fn fetch_checksum(version: &Version, file: &str, dir: &Path) -> Result<Vec<u8>, Error> {
+    let address: String = release_address(DEFAULT_RELEASE_BASE, version, CHECKSUMS_FILE);
+    let sums_path: PathBuf = dir.join(CHECKSUMS_FILE);
+    debug!("Downloading checksums from '{}'...", address);
+
+    // Download the file
+    let security: DownloadSecurity = DownloadSecurity { checksum: None, https: true };
+    if let Err(err) = download_file_async(&address, &sums_path, security, None).await {
+        return Err(Error::ChecksumsDownloadError { address, path: sums_path, err: Box::new(err) });
+    }
+    let sums: String = match fs::read_to_string(&sums_path) {
+        Ok(sums) => sums,
+        Err(err) => return Err(Error::ChecksumsReadError { path: sums_path, err }),
+    };
+
+    // Find the file's entry in it
+    match find_checksum(&sums, file) {
+        Some(hash) => parse_checksum(hash),
+        None => Err(Error::ChecksumNotFound { address, file: file.into() }),
+    }
+}
+
+/// Downloads a release file from a single mirror, verifying it against the given checksum.
+///
+/// # Arguments
+/// - `base`: The base address of the mirror to download from.
+/// - `version`: The version of the release to download.
+/// - `file`: The name of the file to download.
+/// - `checksum`: If given, a checksum to verify the file against.
+/// - `dir`: The (temporary) directory to download the file to.
+///
+/// # Returns
+/// The path of the downloaded file.
+///
+/// # Errors
+/// This function errors if we failed to download the file or if the downloaded file's checksum does not match.
+async fn download_release_file(base: &str, version: &Version, file: &str, checksum: Option<&[u8]>, dir: &Path) -> Result<PathBuf, Error> {
+    let address: String = release_address(base, version, file);
+    let https: bool = address.starts_with("https://");
+    if !https {
+        warn!("Mirror '{base}' does not use HTTPS; the integrity of '{file}' relies on its checksum only");
+    }
+
+    // Download the file itself
+    debug!("Will download from: {}", address);
+    let path: PathBuf = dir.join(file);
+    match download_file_async(&address, &path, DownloadSecurity { checksum, https }, Some(Style::new().green().bold())).await {
+        Ok(_) => Ok(path),
+        Err(err @ brane_shr::fs::Error::FileChecksumError { .. }) => Err(Error::ChecksumMismatch { address, err: Box::new(err) }),
+        Err(err) => Err(Error::DownloadError { address, path, err: Box::new(err) }),
+    }
+}
+
 /// Downloads either the central or the worker images (which depends solely on the tar name).
 ///
 /// # Arguments
-/// - `address`: The address of the file to download.
+/// - `mirrors`: The base addresses of the mirrors to try, in order.
+/// - `version`: The version of the images to download.
 /// - `path`: The path to the directory where the image files will _eventually_ end up in.
 /// - `tar_name`: The base name of the tarball file, which is also the name if the directory inside it etc.
+/// - `checksum`: If given, a checksum to verify the tarball against instead of the one published alongside the release.
+/// - `verify`: Whether to verify the tarball against the checksum published on [`DEFAULT_RELEASE_BASE`] at all. Ignored if `checksum` is given.
 /// - `force`: If given, overwrites images if they are already there.
 ///
 /// # Errors
-/// This function may error if we failed to reach GitHub, we failed to establish HTTPS or we failed to somehow write the file / create missing directories (if enabled).
-async fn download_brane_services(address: impl AsRef<str>, path: impl AsRef<Path>, tar_name: impl AsRef<str>, force: bool) -> Result<(), Error> {
-    let address: &str = address.as_ref();
+/// This function may error if we failed to obtain the published checksum, we failed to reach any of the mirrors, we failed to establish HTTPS, the downloaded tarball does not match its checksum or we failed to somehow write the file / create missing directories (if enabled).
+async fn download_brane_services(
+    mirrors: &[String],
+    version: &Version,
+    path: impl AsRef<Path>,
+    tar_name: impl AsRef<str>,
+    checksum: Option<&[u8]>,
+    verify: bool,
+    force: bool,
+) -> Result<(), Error> {
     let path: &Path = path.as_ref();
     let tar_name: &str = tar_name.as_ref();
+    let file: String = format!("{tar_name}.tar.gz");
 
     // Create a temporary directory to download the tar file to.
     debug!("Creating temporary directory...");
@@ -64,19 +216,48 @@ async fn download_brane_services(address: impl AsRef<str>, path: impl AsRef<Path
             return Err(Error::TempDirError { err });
         },
     };
-    let tar_path: PathBuf = temp.path().join(format!("{tar_name}.tar.gz"));
 
-    // Download it
-    if let Err(err) = download_file_async(address, &tar_path, DownloadSecurity::https(), Some(Style::new().green().bold())).await {
-        // Don't call the destructor of `TempDir`, since it's much easier to debug if it lives after creation
-        // SAFETY: This is OK because for our committed version, the destructor of `TempDir` only destroys the directory itself using a normal `std::fs::remove_dir_all()` call, and so nothing will explode if that does not happen.
-        // (see https://docs.rs/tempfile/3.3.0/src/tempfile/dir.rs.html#403-407)
-        std::mem::forget(temp);
-        return Err(Error::DownloadError { address: address.into(), path: tar_path, err: Box::new(err) });
+    // Resolve the checksum to verify against, which is always fetched from the canonical release page if not given
+    let published: Option<Vec<u8>> = if checksum.is_none() && verify { Some(fetch_checksum(version, &file, temp.path()).await?) } else { None };
+    let checksum: Option<&[u8]> = checksum.or(published.as_deref());
+
+    // Download it, trying the mirrors in order
+    let mut tar_path: Option<PathBuf> = None;
+    for base in mirrors {
+        match download_release_file(base, version, &file, checksum, temp.path()).await {
+            Ok(path) => {
+                tar_path = Some(path);
+                break;
+            },
+            Err(err @ Error::ChecksumMismatch { .. }) => {
+                // Never fall back to another mirror if the file is wrong; that's something the user should know about
+                // Don't call the destructor of `TempDir`, since it's much easier to debug if it lives after creation
+                // SAFETY: This is OK because for our committed version, the destructor of `TempDir` only destroys the directory itself using a normal `std::fs::remove_dir_all()` call, and so nothing will explode if that does not happen.
+                // (see https://docs.rs/tempfile/3.3.0/src/tempfile/dir.rs.html#403-407)
+                std::mem::forget(temp);
+                return Err(err);
+            },
+            Err(err) => {
+                // With only one mirror, just report the error directly (again without destroying the temporary directory)
+                if mirrors.len() == 1 {
+                    std::mem::forget(temp);
+                    return Err(err);
+                }
+                warn!("{}", err.trace());
+            },
+        }
     }
+    let tar_path: PathBuf = match tar_path {
+        Some(tar_path) => tar_path,
+        None => {
+            // Don't call the destructor of `TempDir` (see above)
+            std::mem::forget(temp);
+            return Err(Error::NoMirrorSucceeded { file, mirrors: mirrors.into() });
+        },
+    };
 
     // Extract the folder to the same temporary directory
-    println!("Unpacking {}...", style(format!("{tar_name}.tar.gz")).bold().green());
+    println!("Unpacking {}...", style(&file).bold().green());
     let dir_path: PathBuf = temp.path().join("services");
     if let Err(err) = unarchive_async(&tar_path, &dir_path).await {
         // Don't call the destructor of `TempDir`, since it's much easier to debug if it lives after creation
@@ -166,21 +347,35 @@ async fn download_brane_services(address: impl AsRef<str>, path: impl AsRef<Path
 /// - `version`: The version of the images to download.
 /// - `arch`: The architecture for which to download the images.
 /// - `force`: If given, overwrites images if they are already there.
+/// - `mirrors`: The base addresses of alternative mirrors to download the images from, tried in order. If empty, defaults to [`DEFAULT_RELEASE_BASE`].
+/// - `sha256`: If given, the hexadecimal SHA-256 checksum to verify the downloaded images against instead of the one published with the release.
+/// - `no_verify`: If given, does not verify the downloaded images against their published checksums at all.
 /// - `kind`: The kind of images to download (e.g., central, worker or auxillary).
 ///
 /// # Errors
-/// This function may error if we failed to reach GitHub, we failed to establish HTTPS or we failed to somehow write the file / create missing directories (if enabled).
+/// This function may error if we failed to reach GitHub (or the mirrors), we failed to establish HTTPS, the downloaded images did not match their checksum or we failed to somehow write the file / create missing directories (if enabled).
+#[allow(clippy::too_many_arguments)]
 pub async fn services(
     fix_dirs: bool,
     path: impl AsRef<Path>,
     arch: Arch,
     version: Version,
     force: bool,
+    mirrors: Vec<String>,
+    sha256: Option<String>,
+    no_verify: bool,
     kind: DownloadServicesSubcommand,
 ) -> Result<(), Error> {
     let path: &Path = path.as_ref();
     info!("Downloading {} service images...", kind.variant());
 
+    // Resolve the verification settings
+    let checksum: Option<Vec<u8>> = sha256.as_deref().map(parse_checksum).transpose()?;
+    if no_verify && checksum.is_none() && !matches!(kind, DownloadServicesSubcommand::Auxillary { .. }) {
+        warn!("Not verifying downloaded images against their published checksums; corrupted or tampered images will NOT be detected");
+    }
+    let mirrors: Vec<String> = if mirrors.is_empty() { vec![DEFAULT_RELEASE_BASE.into()] } else { mirrors };
+
     // Fix the missing directories, if any.
     if !path.exists() {
         if !fix_dirs {
@@ -197,29 +392,14 @@ pub async fn services(
     // Now match on what we are downloading
     match &kind {
         DownloadServicesSubcommand::Central => {
-            // Resolve the address to use
-            let address: String = if version.is_latest() {
-                format!("https://github.com/epi-project/brane/releases/latest/download/instance-{}.tar.gz", arch.brane())
-            } else {
-                format!("https://github.com/epi-project/brane/releases/download/v{}/instance-{}.tar.gz", version, arch.brane())
-            };
-            debug!("Will download from: {}", address);
-
             // Hand it over the shared code
-            download_brane_services(address, path, format!("instance-{}", arch.brane()), force).await?;
+            download_brane_services(&mirrors, &version, path, format!("instance-{}", arch.brane()), checksum.as_deref(), !no_verify, force).await?;
         },
 
         DownloadServicesSubcommand::Worker => {
-            // Resolve the address to use
-            let address: String = if version.is_latest() {
-                format!("https://github.com/epi-project/brane/releases/latest/download/worker-instance-{}.tar.gz", arch.brane())
-            } else {
-                format!("https://github.com/epi-project/brane/releases/download/v{}/worker-instance-{}.tar.gz", version, arch.brane())
-            };
-            debug!("Will download from: {}", address);
-
             // Hand it over the shared code
-            download_brane_services(address, path, format!("worker-instance-{}", arch.brane()), force).await?;
+            download_brane_services(&mirrors, &version, path, format!("worker-instance-{}", arch.brane()), checksum.as_deref(), !no_verify, force)
+                .await?;
         },
 
         DownloadServicesSubcommand::Auxillary { socket, client_version } => {
@@ -259,3 +439,50 @@ pub async fn services(
     println!("Successfully downloaded {} services to {}", kind.variant().to_string().to_lowercase(), style(path.display()).bold().green());
     Ok(())
 }
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// A checksum that is valid hex of the correct length.
+    const HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+
+    #[test]
+    fn parse_checksum_valid() {
+        let checksum: Vec<u8> = parse_checksum(HASH).unwrap();
+        assert_eq!(checksum.len(), 32);
+        assert_eq!(hex::encode(checksum), HASH);
+        // Surrounding whitespace is allowed
+        assert_eq!(parse_checksum(&format!("  {HASH}\n")).unwrap(), parse_checksum(HASH).unwrap());
+    }
+
+    #[test]
+    fn parse_checksum_invalid() {
+        assert!(matches!(parse_checksum("not-hex"), Err(Error::InvalidChecksum { .. })));
+        assert!(matches!(parse_checksum("abcd"), Err(Error::InvalidChecksumLength { got: 2, .. })));
+        assert!(matches!(parse_checksum(&format!("{HASH}00")), Err(Error::InvalidChecksumLength { got: 33, .. })));
+    }
+
+    #[test]
+    fn find_checksum_sha256sum_format() {
+        let sums: String = format!(
+            "# Checksums of the release\n\n{HASH}  instance-x86_64.tar.gz\n{}  *worker-instance-x86_64.tar.gz\nmalformed\n{}  \
+             ./instance-aarch64.tar.gz\n",
+            "1".repeat(64),
+            "2".repeat(64)
+        );
+        assert_eq!(find_checksum(&sums, "instance-x86_64.tar.gz"), Some(HASH));
+        assert_eq!(find_checksum(&sums, "worker-instance-x86_64.tar.gz"), Some("1".repeat(64).as_str()));
+        assert_eq!(find_checksum(&sums, "instance-aarch64.tar.gz"), Some("2".repeat(64).as_str()));
+        assert_eq!(find_checksum(&sums, "worker-instance-aarch64.tar.gz"), None);
+        assert_eq!(find_checksum(&sums, "malformed"), None);
+        assert_eq!(find_checksum("", "instance-x86_64.tar.gz"), None);
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    21 Nov 2022, 15:46:26
 //  Last edited:
-//    01 May 2024, 15:19:09
+//    16 Oct 2026, 02:37:31
 //  Auto updated?
 //    Yes
 //
@@ -17,6 +17,7 @@ use std::fmt::{Debug, Display, Formatter, Result as FResult};
 use std::path::PathBuf;
 use std::process::{Command, ExitStatus};
 
+use brane_cfg::approvals::ApprovalStatus;
 use brane_cfg::node::NodeKind;
 use brane_shr::formatters::Capitalizeable;
 use brane_tsk::docker::ImageSource;
@@ -28,6 +29,241 @@ use specifications::version::Version;
 
 
 /***** LIBRARY *****/
+/// Errors that relate to creating or restoring backups.
+///
+/// Note: we box `brane_shr::fs::Error` to avoid the error enum growing too large (see `clippy::result_large_err`).
+#[derive(Debug)]
+pub enum BackupError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// Failed to substitute environment variables in the node config file of a backup.
+    NodeConfigInterpolateError { path: PathBuf, err: brane_cfg::info::InterpolateError },
+    /// Failed to parse the node config file of a backup.
+    NodeConfigParseError { path: PathBuf, err: brane_cfg::info::YamlError },
+    /// Failed to create a temporary directory.
+    TempDirError { err: std::io::Error },
+
+    /// Failed to create a directory.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a directory.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to remove an existing directory.
+    DirRemoveError { path: PathBuf, err: std::io::Error },
+    /// Failed to copy a directory.
+    DirCopyError { source: PathBuf, target: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to copy a file.
+    CopyError { source: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to read a file.
+    FileReadError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to write a file.
+    FileWriteError { what: &'static str, path: PathBuf, err: std::io::Error },
+
+    /// Failed to serialize the backup manifest.
+    ManifestSerializeError { err: serde_yaml::Error },
+    /// Failed to deserialize the manifest of the given backup.
+    ManifestDeserializeError { path: PathBuf, err: serde_yaml::Error },
+    /// Failed to create the backup archive with restricted permissions.
+    ArchiveCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to archive the backup.
+    ArchiveError { path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to unarchive the backup.
+    UnarchiveError { path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// A file or directory we are restoring to already exists.
+    TargetExists { path: PathBuf },
+    /// The manifest of a backup lists an entry that the node config in the backup does not define.
+    UnknownEntry { name: String },
+    /// The manifest of a backup lists an entry at another path than the node config in the backup defines for it.
+    EntryPathMismatch { name: String, path: PathBuf, expected: PathBuf },
+}
+impl Display for BackupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use BackupError::*;
+        match self {
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            NodeConfigInterpolateError { path, .. } => {
+                write!(f, "Failed to substitute environment variables in node config file '{}'", path.display())
+            },
+            NodeConfigParseError { path, .. } => write!(f, "Failed to parse node config file '{}'", path.display()),
+            TempDirError { .. } => write!(f, "Failed to create temporary directory in system temp folder"),
+
+            DirCreateError { path, .. } => write!(f, "Failed to create directory '{}'", path.display()),
+            DirReadError { path, .. } => write!(f, "Failed to read directory '{}'", path.display()),
+            DirRemoveError { path, .. } => write!(f, "Failed to remove existing directory '{}'", path.display()),
+            DirCopyError { source, target, .. } => write!(f, "Failed to copy directory '{}' to '{}'", source.display(), target.display()),
+            CopyError { source, target, .. } => write!(f, "Failed to copy file '{}' to '{}'", source.display(), target.display()),
+            FileReadError { what, path, .. } => write!(f, "Failed to read {} file '{}'", what, path.display()),
+            FileWriteError { what, path, .. } => write!(f, "Failed to write {} file '{}'", what, path.display()),
+
+            ManifestSerializeError { .. } => write!(f, "Failed to serialize backup manifest"),
+            ManifestDeserializeError { path, .. } => write!(f, "Failed to parse manifest of backup '{}' (is it a backup?)", path.display()),
+            ArchiveCreateError { path, .. } => write!(f, "Failed to create backup archive '{}'", path.display()),
+            ArchiveError { path, .. } => write!(f, "Failed to write backup archive '{}'", path.display()),
+            UnarchiveError { path, .. } => write!(f, "Failed to unpack backup archive '{}'", path.display()),
+            TargetExists { path } => {
+                write!(f, "Restore target '{}' already exists (re-run with '--force' to overwrite it)", path.display())
+            },
+            UnknownEntry { name } => write!(f, "Backup contains entry '{name}', which its node config does not define"),
+            EntryPathMismatch { name, path, expected } => {
+                write!(f, "Backup restores entry '{}' to '{}', but its node config places it at '{}'", name, path.display(), expected.display())
+            },
+        }
+    }
+}
+impl Error for BackupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use BackupError::*;
+        match self {
+            NodeConfigLoadError { err } => Some(err),
+            NodeConfigInterpolateError { err, .. } => Some(err),
+            NodeConfigParseError { err, .. } => Some(err),
+            TempDirError { err } => Some(err),
+
+            DirCreateError { err, .. } => Some(err),
+            DirReadError { err, .. } => Some(err),
+            DirRemoveError { err, .. } => Some(err),
+            DirCopyError { err, .. } => Some(err),
+            CopyError { err, .. } => Some(err),
+            FileReadError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+
+            ManifestSerializeError { err } => Some(err),
+            ManifestDeserializeError { err, .. } => Some(err),
+            ArchiveCreateError { err, .. } => Some(err),
+            ArchiveError { err, .. } => Some(err),
+            UnarchiveError { err, .. } => Some(err),
+            TargetExists { .. } => None,
+            UnknownEntry { .. } => None,
+            EntryPathMismatch { .. } => None,
+        }
+    }
+}
+
+
+
+/// Errors that relate to exporting or importing air-gapped installation bundles.
+///
+/// Note: we box `brane_shr::fs::Error` to avoid the error enum growing too large (see `clippy::result_large_err`).
+#[derive(Debug)]
+pub enum BundleError {
+    /// Failed to create a temporary directory.
+    TempDirError { err: std::io::Error },
+    /// The given directory does not exist.
+    DirNotFound { what: &'static str, path: PathBuf },
+    /// Failed to create a directory.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a directory.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to remove an existing directory.
+    DirRemoveError { path: PathBuf, err: std::io::Error },
+
+    /// Failed to download the images of some kind.
+    ImagesDownloadError { what: String, err: Box<DownloadError> },
+    /// Failed to download the policy reasoner repository.
+    RepoDownloadError { repo: String, path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to unpack the policy reasoner repository.
+    RepoUnpackError { tar: PathBuf, target: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to recurse into the downloaded repo archive's only folder.
+    RepoRecurseError { target: PathBuf, err: Box<brane_shr::fs::Error> },
+
+    /// Failed to open a file for hashing.
+    FileOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to hash a file.
+    FileHashError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a file.
+    FileReadError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to write a file.
+    FileWriteError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to move a file or directory.
+    MoveError { source: PathBuf, target: PathBuf, err: Box<brane_shr::fs::Error> },
+
+    /// Failed to serialize the bundle manifest.
+    ManifestSerializeError { err: serde_yaml::Error },
+    /// Failed to deserialize the manifest of the given bundle.
+    ManifestDeserializeError { path: PathBuf, err: serde_yaml::Error },
+    /// Failed to archive the bundle.
+    ArchiveError { path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to unarchive the bundle.
+    UnarchiveError { path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// A file in the bundle is listed in the manifest but missing.
+    EntryMissing { name: String },
+    /// A file in the bundle does not match the checksum in its manifest.
+    ChecksumMismatch { name: String, expected: String, got: String },
+    /// A file or directory we are importing to already exists.
+    TargetExists { path: PathBuf },
+}
+impl Display for BundleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use BundleError::*;
+        match self {
+            TempDirError { .. } => write!(f, "Failed to create temporary directory in system temp folder"),
+            DirNotFound { what, path } => write!(f, "{} directory '{}' not found", what.capitalize(), path.display()),
+            DirCreateError { path, .. } => write!(f, "Failed to create directory '{}'", path.display()),
+            DirReadError { path, .. } => write!(f, "Failed to read directory '{}'", path.display()),
+            DirRemoveError { path, .. } => write!(f, "Failed to remove existing directory '{}'", path.display()),
+
+            ImagesDownloadError { what, .. } => write!(f, "Failed to download {what} images"),
+            RepoDownloadError { repo, path, .. } => write!(f, "Failed to download repository archive '{}' to '{}'", repo, path.display()),
+            RepoUnpackError { tar, target, .. } => write!(f, "Failed to unpack repository archive '{}' to '{}'", tar.display(), target.display()),
+            RepoRecurseError { target, .. } => {
+                write!(f, "Failed to recurse into only directory of unpacked repository archive '{}'", target.display())
+            },
+
+            FileOpenError { path, .. } => write!(f, "Failed to open file '{}'", path.display()),
+            FileHashError { path, .. } => write!(f, "Failed to compute checksum of file '{}'", path.display()),
+            FileReadError { what, path, .. } => write!(f, "Failed to read {} file '{}'", what, path.display()),
+            FileWriteError { what, path, .. } => write!(f, "Failed to write {} file '{}'", what, path.display()),
+            MoveError { source, target, .. } => write!(f, "Failed to move '{}' to '{}'", source.display(), target.display()),
+
+            ManifestSerializeError { .. } => write!(f, "Failed to serialize bundle manifest"),
+            ManifestDeserializeError { path, .. } => write!(f, "Failed to parse manifest of bundle '{}' (is it a bundle?)", path.display()),
+            ArchiveError { path, .. } => write!(f, "Failed to write bundle archive '{}'", path.display()),
+            UnarchiveError { path, .. } => write!(f, "Failed to unpack bundle archive '{}'", path.display()),
+            EntryMissing { name } => write!(f, "Bundle manifest lists '{name}', but it is not in the bundle (is the bundle incomplete?)"),
+            ChecksumMismatch { name, expected, got } => write!(
+                f,
+                "Refusing to import '{name}' because its checksum does not match the bundle manifest (corrupted in transfer?): expected \
+                 '{expected}', got '{got}'"
+            ),
+            TargetExists { path } => {
+                write!(f, "Import target '{}' already exists (re-run with '--force' to overwrite it)", path.display())
+            },
+        }
+    }
+}
+impl Error for BundleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use BundleError::*;
+        match self {
+            TempDirError { err } => Some(err),
+            DirNotFound { .. } => None,
+            DirCreateError { err, .. } => Some(err),
+            DirReadError { err, .. } => Some(err),
+            DirRemoveError { err, .. } => Some(err),
+
+            ImagesDownloadError { err, .. } => Some(err),
+            RepoDownloadError { err, .. } => Some(err),
+            RepoUnpackError { err, .. } => Some(err),
+            RepoRecurseError { err, .. } => Some(err),
+
+            FileOpenError { err, .. } => Some(err),
+            FileHashError { err, .. } => Some(err),
+            FileReadError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+            MoveError { err, .. } => Some(err),
+
+            ManifestSerializeError { err } => Some(err),
+            ManifestDeserializeError { err, .. } => Some(err),
+            ArchiveError { err, .. } => Some(err),
+            UnarchiveError { err, .. } => Some(err),
+            EntryMissing { .. } => None,
+            ChecksumMismatch { .. } => None,
+            TargetExists { .. } => None,
+        }
+    }
+}
+
+
+
 /// Errors that relate to downloading stuff (the subcommand, specifically).
 ///
 /// Note: we box `brane_shr::fs::Error` to avoid the error enum growing too large (see `clippy::result_large_err`).
@@ -53,6 +289,21 @@ pub enum DownloadError {
     /// Failed to move something.
     MoveError { source: PathBuf, target: PathBuf, err: Box<brane_shr::fs::Error> },
 
+    /// A checksum given by the user (or found in a checksums file) was not valid hexadecimal SHA-256.
+    InvalidChecksum { raw: String, err: hex::FromHexError },
+    /// A checksum given by the user (or found in a checksums file) was valid hex, but not of a SHA-256 hash length.
+    InvalidChecksumLength { raw: String, got: usize },
+    /// Failed to download the published checksums file.
+    ChecksumsDownloadError { address: String, path: PathBuf, err: Box<brane_shr::fs::Error> },
+    /// Failed to read the downloaded checksums file.
+    ChecksumsReadError { path: PathBuf, err: std::io::Error },
+    /// The checksums file did not list the file we're downloading.
+    ChecksumNotFound { address: String, file: String },
+    /// The downloaded file did not match its expected checksum.
+    ChecksumMismatch { address: String, err: Box<brane_shr::fs::Error> },
+    /// None of the given mirrors had the file available.
+    NoMirrorSucceeded { file: String, mirrors: Vec<String> },
+
     /// Failed to connect to local Docker client.
     DockerConnectError { err: brane_tsk::docker::Error },
     /// Failed to pull an image.
@@ -75,6 +326,29 @@ impl Display for DownloadError {
             ReadEntryError { path, entry, err } => write!(f, "Failed to read entry {} in directory '{}': {}", entry, path.display(), err),
             MoveError { source, target, err } => write!(f, "Failed to move '{}' to '{}': {}", source.display(), target.display(), err),
 
+            InvalidChecksum { raw, err } => write!(f, "Invalid SHA-256 checksum '{raw}': {err}"),
+            InvalidChecksumLength { raw, got } => write!(f, "Invalid SHA-256 checksum '{raw}': expected 32 bytes, got {got}"),
+            ChecksumsDownloadError { address, path, err } => write!(
+                f,
+                "Failed to download checksums file '{}' to '{}': {} (use '--sha256' to give the checksum manually, or '--no-verify' to skip \
+                 verification at your own risk)",
+                address,
+                path.display(),
+                err
+            ),
+            ChecksumsReadError { path, err } => write!(f, "Failed to read checksums file '{}': {}", path.display(), err),
+            ChecksumNotFound { address, file } => write!(f, "Checksums file '{address}' does not list a checksum for '{file}'"),
+            ChecksumMismatch { address, err } => {
+                write!(f, "Refusing to install '{address}' because it does not match its published checksum (corrupted or tampered with?): {err}")
+            },
+            NoMirrorSucceeded { file, mirrors } => write!(
+                f,
+                "Failed to download '{}' from any of the {} given mirrors ({}); see the warnings above for details",
+                file,
+                mirrors.len(),
+                mirrors.iter().map(|m| format!("'{m}'")).collect::<Vec<String>>().join(", ")
+            ),
+
             DockerConnectError { err } => write!(f, "Failed to connect to local Docker daemon: {err}"),
             PullError { name, image, err } => write!(f, "Failed to pull '{image}' as '{name}': {err}"),
             SaveError { name, path, err, .. } => write!(f, "Failed to save image '{}' to '{}': {}", name, path.display(), err),
@@ -167,6 +441,22 @@ pub enum GenerateError {
     UnsupportedKeyAlgorithm { key_alg: KeyAlgorithm },
     /// Failed to generate a new policy token.
     TokenGenerate { err: specifications::policy::Error },
+
+    /// Failed to load the node.yml file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// The node.yml file is not of the node kind we expected.
+    UnsupportedNode { what: &'static str, kind: NodeKind },
+    /// Failed to load the CA certificate.
+    CaCertLoadError { path: PathBuf, err: brane_cfg::certs::Error },
+    /// The CA certificate file did not contain any certificates.
+    CaCertEmpty { path: PathBuf },
+    /// Failed to read the enrollment tokens file.
+    EnrollmentTokensLoadError { path: PathBuf, err: brane_cfg::info::YamlError },
+    /// Failed to write the enrollment tokens file.
+    EnrollmentTokensWriteError { path: PathBuf, err: brane_cfg::info::YamlError },
+
+    /// Failed to write the monitoring stack configuration.
+    MonitoringConfigError { path: PathBuf, err: MonitoringError },
 }
 impl Display for GenerateError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -226,6 +516,15 @@ impl Display for GenerateError {
                 write!(f, "Policy key algorithm {key_alg} is unsupported")
             },
             TokenGenerate { .. } => write!(f, "Failed to generate new policy token"),
+
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            UnsupportedNode { what, kind } => write!(f, "Cannot {what} on a {} node", kind.variant()),
+            CaCertLoadError { path, .. } => write!(f, "Failed to load certificate authority's certificate '{}'", path.display()),
+            CaCertEmpty { path } => write!(f, "Certificate authority's certificate file '{}' does not contain any certificates", path.display()),
+            EnrollmentTokensLoadError { path, .. } => write!(f, "Failed to read enrollment tokens file '{}'", path.display()),
+            EnrollmentTokensWriteError { path, .. } => write!(f, "Failed to write enrollment tokens file '{}'", path.display()),
+
+            MonitoringConfigError { path, .. } => write!(f, "Failed to write monitoring configuration to '{}'", path.display()),
         }
     }
 }
@@ -275,6 +574,15 @@ impl Error for GenerateError {
 
             UnsupportedKeyAlgorithm { .. } => None,
             TokenGenerate { err, .. } => Some(err),
+
+            NodeConfigLoadError { err } => Some(err),
+            UnsupportedNode { .. } => None,
+            CaCertLoadError { err, .. } => Some(err),
+            CaCertEmpty { .. } => None,
+            EnrollmentTokensLoadError { err, .. } => Some(err),
+            EnrollmentTokensWriteError { err, .. } => Some(err),
+
+            MonitoringConfigError { err, .. } => Some(err),
         }
     }
 }
@@ -304,6 +612,8 @@ pub enum LifetimeError {
 
     /// Failed to touch the audit log into existance.
     AuditLogCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to create the directory in which the registry parks downloads awaiting approval.
+    ApprovalsDirCreate { path: PathBuf, err: std::io::Error },
 
     /// Failed to read the `proxy.yml` file.
     ProxyReadError { err: brane_cfg::info::YamlError },
@@ -350,6 +660,7 @@ impl Display for LifetimeError {
             DockerComposeWriteError { path, .. } => write!(f, "Failed to write to Docker Compose file '{}'", path.display()),
 
             AuditLogCreate { path, .. } => write!(f, "Failed to touch audit log '{}' into existance", path.display()),
+            ApprovalsDirCreate { path, .. } => write!(f, "Failed to create approvals directory '{}'", path.display()),
 
             ProxyReadError { .. } => write!(f, "Failed to read proxy config file"),
             HostsFileCreateError { path, .. } => write!(f, "Failed to create extra hosts file '{}'", path.display()),
@@ -401,6 +712,7 @@ impl Error for LifetimeError {
             DockerComposeWriteError { err, .. } => Some(err),
 
             AuditLogCreate { err, .. } => Some(err),
+            ApprovalsDirCreate { err, .. } => Some(err),
 
             ProxyReadError { err } => Some(err),
             HostsFileCreateError { err, .. } => Some(err),
@@ -424,6 +736,134 @@ impl Error for LifetimeError {
 
 
 
+/// Errors that relate to reporting the status of the node.
+#[derive(Debug)]
+pub enum StatusError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// Failed to connect to the local Docker daemon.
+    DockerConnectError { err: brane_tsk::errors::DockerError },
+    /// Failed to inspect the container of the given service.
+    ContainerInspectError { name: String, err: bollard::errors::Error },
+}
+impl Display for StatusError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use StatusError::*;
+        match self {
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            DockerConnectError { .. } => write!(f, "Failed to connect to local Docker socket"),
+            ContainerInspectError { name, .. } => write!(f, "Failed to inspect container '{name}'"),
+        }
+    }
+}
+impl Error for StatusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use StatusError::*;
+        match self {
+            NodeConfigLoadError { err } => Some(err),
+            DockerConnectError { err } => Some(err),
+            ContainerInspectError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Errors that relate to showing the logs of the node's services.
+#[derive(Debug)]
+pub enum LogsError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// The user asked for a service that this node does not host.
+    UnknownService { name: String, known: Vec<String> },
+    /// Failed to connect to the local Docker daemon.
+    DockerConnectError { err: brane_tsk::errors::DockerError },
+    /// Failed to read the logs of the given container.
+    LogReadError { name: String, err: bollard::errors::Error },
+}
+impl Display for LogsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use LogsError::*;
+        match self {
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            UnknownService { name, known } => write!(f, "Unknown service '{}' (this node hosts: {})", name, known.join(", ")),
+            DockerConnectError { .. } => write!(f, "Failed to connect to local Docker socket"),
+            LogReadError { name, .. } => write!(f, "Failed to read logs of container '{name}'"),
+        }
+    }
+}
+impl Error for LogsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use LogsError::*;
+        match self {
+            NodeConfigLoadError { err } => Some(err),
+            UnknownService { .. } => None,
+            DockerConnectError { err } => Some(err),
+            LogReadError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Errors that relate to generating and managing systemd units.
+#[derive(Debug)]
+pub enum SystemdError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// Failed to resolve the given path to an absolute one.
+    CanonicalizeError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// The directory to write the units to does not exist.
+    DirNotFound { path: PathBuf },
+    /// The directory to write the units to exists but is not a directory.
+    DirNotADir { path: PathBuf },
+    /// Failed to create the directory to write the units to.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to write a unit file.
+    FileWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to launch `systemctl`.
+    JobLaunchError { command: Command, err: std::io::Error },
+    /// `systemctl` returned a non-zero exit code.
+    JobFailure { command: Command, status: ExitStatus },
+}
+impl Display for SystemdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SystemdError::*;
+        match self {
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            CanonicalizeError { what, path, .. } => write!(f, "Failed to resolve {} path '{}'", what, path.display()),
+            DirNotFound { path } => write!(f, "Unit directory '{}' not found", path.display()),
+            DirNotADir { path } => write!(f, "Unit directory '{}' exists but is not a directory", path.display()),
+            DirCreateError { path, .. } => write!(f, "Failed to create unit directory '{}'", path.display()),
+            FileWriteError { path, .. } => write!(f, "Failed to write unit file '{}'", path.display()),
+            JobLaunchError { command, .. } => write!(f, "Failed to launch command '{command:?}'"),
+            JobFailure { command, status } => write!(
+                f,
+                "Command '{}' failed with exit code {} (see output above)",
+                style(format!("{command:?}")).bold(),
+                style(status.code().map(|c| c.to_string()).unwrap_or_else(|| "non-zero".into())).bold()
+            ),
+        }
+    }
+}
+impl Error for SystemdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use SystemdError::*;
+        match self {
+            NodeConfigLoadError { err } => Some(err),
+            CanonicalizeError { err, .. } => Some(err),
+            DirNotFound { .. } => None,
+            DirNotADir { .. } => None,
+            DirCreateError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+            JobLaunchError { err, .. } => Some(err),
+            JobFailure { .. } => None,
+        }
+    }
+}
+
+
+
 /// Errors that relate to package subcommands.
 #[derive(Debug)]
 pub enum PackagesError {
@@ -467,6 +907,82 @@ impl Error for PackagesError {}
 
 
 
+/// Errors that relate to deciding on downloads awaiting approval.
+#[derive(Debug)]
+pub enum ApprovalsError {
+    /// Failed to load the given node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// The given node type is not supported for this operation.
+    ///
+    /// The `what` should fill in the `<WHAT>` in: "Cannot <WHAT> on a ... node"
+    UnsupportedNode { what: &'static str, kind: NodeKind },
+    /// The worker does not have approvals enabled.
+    NotConfigured { path: PathBuf },
+    /// There is no request with the given ID.
+    UnknownRequest { id: String },
+    /// The request with the given ID has already been decided on.
+    AlreadyDecided { id: String, status: ApprovalStatus },
+    /// Failed to read or write the requests.
+    Approvals { err: brane_cfg::approvals::Error },
+}
+impl Display for ApprovalsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ApprovalsError::*;
+        match self {
+            NodeConfigLoadError { .. } => write!(f, "Failed to load node.yml file"),
+            UnsupportedNode { what, kind } => write!(f, "Cannot {what} on a {} node", kind.variant()),
+            NotConfigured { path } => write!(f, "Node config file '{}' does not enable `egress_approval`", path.display()),
+            UnknownRequest { id } => write!(f, "No download awaiting approval with ID '{id}'"),
+            AlreadyDecided { id, status } => write!(f, "Download '{id}' has already been {}", status.variant().to_string().to_lowercase()),
+            Approvals { .. } => write!(f, "Failed to access downloads awaiting approval"),
+        }
+    }
+}
+impl Error for ApprovalsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ApprovalsError::*;
+        match self {
+            NodeConfigLoadError { err } => Some(err),
+            UnsupportedNode { .. } => None,
+            NotConfigured { .. } => None,
+            UnknownRequest { .. } => None,
+            AlreadyDecided { .. } => None,
+            Approvals { err } => Some(err),
+        }
+    }
+}
+
+
+
+/// Errors that relate to writing the configuration of the monitoring stack.
+#[derive(Debug)]
+pub enum MonitoringError {
+    /// Failed to create a directory.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to write a file.
+    FileWriteError { what: &'static str, path: PathBuf, err: std::io::Error },
+}
+impl Display for MonitoringError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use MonitoringError::*;
+        match self {
+            DirCreateError { path, .. } => write!(f, "Failed to create directory '{}'", path.display()),
+            FileWriteError { what, path, .. } => write!(f, "Failed to write {} file '{}'", what, path.display()),
+        }
+    }
+}
+impl Error for MonitoringError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use MonitoringError::*;
+        match self {
+            DirCreateError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
 /// Errors that relate to unpacking files.
 #[derive(Debug)]
 pub enum UnpackError {
@@ -480,6 +996,10 @@ pub enum UnpackError {
     TargetDirNotFound { path: PathBuf },
     /// The target directory was not a directory.
     TargetDirNotADir { path: PathBuf },
+    /// Failed to render the Kubernetes manifests.
+    K8sRenderError { err: K8sError },
+    /// The dashboard was requested for a node that is not a central node.
+    DashboardNotCentral { kind: NodeKind },
 }
 impl Display for UnpackError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -492,6 +1012,8 @@ impl Display for UnpackError {
                 write!(f, "Target directory '{}' not found (you can create it by re-running this command with '-f')", path.display())
             },
             TargetDirNotADir { path } => write!(f, "Target directory '{}' exists but is not a directory", path.display()),
+            K8sRenderError { err } => write!(f, "Failed to render Kubernetes manifests: {err}"),
+            DashboardNotCentral { kind } => write!(f, "Cannot add the dashboard to a {kind} node (it can only be deployed on a central node)"),
         }
     }
 }
@@ -499,6 +1021,103 @@ impl Error for UnpackError {}
 
 
 
+/// Errors that relate to testing the connectivity of a node.
+#[derive(Debug)]
+pub enum ConnectivityError {
+    /// Failed to load the node config file.
+    NodeConfigLoadError { err: brane_cfg::info::YamlError },
+    /// Failed to load the infra file.
+    InfraLoadError { path: PathBuf, err: brane_cfg::info::YamlError },
+    /// Some of the endpoints could not be reached.
+    Unreachable { failed: usize, total: usize },
+}
+impl Display for ConnectivityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConnectivityError::*;
+        match self {
+            NodeConfigLoadError { err } => write!(f, "Failed to load node config file: {err}"),
+            InfraLoadError { path, err } => write!(f, "Failed to load infrastructure file '{}': {}", path.display(), err),
+            Unreachable { failed, total } => {
+                write!(f, "{failed} out of {total} endpoints could not be reached properly (see the suggested fixes above)")
+            },
+        }
+    }
+}
+impl Error for ConnectivityError {}
+
+
+
+/// Errors that relate to rendering Kubernetes manifests.
+#[derive(Debug)]
+pub enum K8sError {
+    /// Failed to read a file referenced by the node config.
+    FileReadError { what: &'static str, path: PathBuf, err: std::io::Error },
+    /// Failed to read a directory referenced by the node config.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to load the proxy config file.
+    ProxyConfigLoadError { path: PathBuf, err: brane_cfg::info::YamlError },
+    /// Failed to serialize a manifest.
+    SerializeError { what: &'static str, err: serde_yaml::Error },
+}
+impl Display for K8sError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use K8sError::*;
+        match self {
+            FileReadError { what, path, err } => write!(f, "Failed to read {} file '{}': {}", what, path.display(), err),
+            DirReadError { path, err } => write!(f, "Failed to read directory '{}': {}", path.display(), err),
+            ProxyConfigLoadError { path, err } => write!(f, "Failed to load proxy config file '{}': {}", path.display(), err),
+            SerializeError { what, err } => write!(f, "Failed to serialize {what} manifest: {err}"),
+        }
+    }
+}
+impl Error for K8sError {}
+
+
+
+/// Errors that relate to migrating config files to newer versions.
+#[derive(Debug)]
+pub enum MigrateError {
+    /// The given path was not found.
+    PathNotFound { path: PathBuf },
+    /// Could not deduce the kind of the given file.
+    UnknownKind { path: PathBuf },
+    /// Failed to read a directory.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a file.
+    FileReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse a file as YAML.
+    FileParseError { path: PathBuf, err: serde_yaml::Error },
+    /// Failed to serialize a migrated file.
+    SerializeError { path: PathBuf, err: serde_yaml::Error },
+    /// The migrated file is not a valid file of its kind.
+    ValidateError { path: PathBuf, kind: crate::spec::ConfigKind, err: brane_cfg::info::YamlError },
+    /// Failed to back up the original file.
+    BackupError { path: PathBuf, backup: PathBuf, err: std::io::Error },
+    /// Failed to write the migrated file.
+    FileWriteError { path: PathBuf, err: std::io::Error },
+}
+impl Display for MigrateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use MigrateError::*;
+        match self {
+            PathNotFound { path } => write!(f, "Path '{}' not found", path.display()),
+            UnknownKind { path } => {
+                write!(f, "Cannot deduce what kind of config file '{}' is from its name (specify it using '--kind')", path.display())
+            },
+            DirReadError { path, err } => write!(f, "Failed to read directory '{}': {}", path.display(), err),
+            FileReadError { path, err } => write!(f, "Failed to read file '{}': {}", path.display(), err),
+            FileParseError { path, err } => write!(f, "Failed to parse file '{}' as YAML: {}", path.display(), err),
+            SerializeError { path, err } => write!(f, "Failed to serialize migrated file '{}': {}", path.display(), err),
+            ValidateError { path, kind, err } => write!(f, "Migrated file '{}' is not a valid {}.yml file: {}", path.display(), kind, err),
+            BackupError { path, backup, err } => write!(f, "Failed to back up '{}' to '{}': {}", path.display(), backup.display(), err),
+            FileWriteError { path, err } => write!(f, "Failed to write migrated file '{}': {}", path.display(), err),
+        }
+    }
+}
+impl Error for MigrateError {}
+
+
+
 /// Errors that relate to parsing Docker client version numbers.
 #[derive(Debug)]
 pub enum DockerClientVersionParseError {
@@ -586,6 +1205,80 @@ impl Error for PolicyInputLanguageParseError {}
 
 
 
+/// Errors that relate to parsing [`PolicyTemplate`](crate::spec::PolicyTemplate)s.
+#[derive(Debug)]
+pub enum PolicyTemplateParseError {
+    /// The given identifier was not recognized.
+    Unknown { raw: String },
+}
+impl Display for PolicyTemplateParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use PolicyTemplateParseError::*;
+        match self {
+            Unknown { raw } => {
+                write!(f, "Unknown policy template '{raw}' (options are 'allow-all', 'dataset-allowlist' or 'purpose-binding')")
+            },
+        }
+    }
+}
+impl Error for PolicyTemplateParseError {}
+
+
+
+/// Errors that relate to parsing [`DeployTarget`](crate::spec::DeployTarget)s.
+#[derive(Debug)]
+pub enum DeployTargetParseError {
+    /// The given identifier was not recognized.
+    Unknown { raw: String },
+}
+impl Display for DeployTargetParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DeployTargetParseError::*;
+        match self {
+            Unknown { raw } => write!(f, "Unknown deployment target '{raw}' (options are 'compose' or 'systemd')"),
+        }
+    }
+}
+impl Error for DeployTargetParseError {}
+
+
+
+/// Errors that relate to parsing [`BundleContent`](crate::spec::BundleContent)s.
+#[derive(Debug)]
+pub enum BundleContentParseError {
+    /// The given identifier was not recognized.
+    Unknown { raw: String },
+}
+impl Display for BundleContentParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use BundleContentParseError::*;
+        match self {
+            Unknown { raw } => write!(f, "Unknown bundle content '{raw}' (options are 'central', 'worker', 'auxillary' or 'policy-reasoner')"),
+        }
+    }
+}
+impl Error for BundleContentParseError {}
+
+
+
+/// Errors that relate to parsing [`ConfigKind`](crate::spec::ConfigKind)s.
+#[derive(Debug)]
+pub enum ConfigKindParseError {
+    /// The given identifier was not recognized.
+    Unknown { raw: String },
+}
+impl Display for ConfigKindParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConfigKindParseError::*;
+        match self {
+            Unknown { raw } => write!(f, "Unknown config file kind '{raw}' (options are 'node', 'infra', 'backend' or 'proxy')"),
+        }
+    }
+}
+impl Error for ConfigKindParseError {}
+
+
+
 /// Errors that relate to parsing architecture iDs.
 #[derive(Debug)]
 pub enum ArchParseError {
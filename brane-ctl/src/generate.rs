@@ -4,7 +4,7 @@
 //  Created:
 //    21 Nov 2022, 15:40:47
 //  Last edited:
-//    01 May 2024, 15:20:56
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -12,7 +12,7 @@
 //!   Handles commands relating to node.yml generation.
 //
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::fs::{self, File};
 use std::io::Write;
@@ -20,22 +20,28 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddrV4};
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64ct::Encoding as _;
-use brane_cfg::backend::{BackendFile, Credentials};
+use bollard::container::KillContainerOptions;
+use bollard::Docker;
+use brane_cfg::backend::{BackendFile, Credentials, SandboxConfig};
+use brane_cfg::certs::previous_ca_paths;
+use brane_cfg::enrollment::{self, EnrollmentToken, EnrollmentTokensFile, PendingEnrollment, ENROLLMENT_TOKENS_FILE};
 use brane_cfg::info::Info as _;
 use brane_cfg::infra::{InfraFile, InfraLocation};
 use brane_cfg::node::{
     self, CentralConfig, CentralPaths, CentralServices, ExternalService, NodeConfig, NodeSpecificConfig, PrivateOrExternalService, PrivateService,
     ProxyPaths, ProxyServices, PublicService, WorkerConfig, WorkerPaths, WorkerServices, WorkerUsecase,
 };
-use brane_cfg::proxy::{self, ForwardConfig};
+use brane_cfg::proxy::{self, DestinationRules, ForwardConfig};
 use brane_shr::fs::{set_executable, DownloadSecurity};
+use brane_tsk::docker::{connect_local, ClientVersion, DockerOptions, API_DEFAULT_VERSION};
 use console::style;
 use diesel::{Connection as _, SqliteConnection};
 use diesel_migrations::{FileBasedMigrations, MigrationHarness as _};
 use enum_debug::EnumDebug as _;
+use error_trace::trace;
 use jsonwebtoken::jwk::{self, Jwk, JwkSet, KeyAlgorithm, OctetKeyParameters, OctetKeyType, PublicKeyUse};
 use log::{debug, info, warn};
 use rand::distributions::Alphanumeric;
@@ -48,6 +54,7 @@ use specifications::policy::generate_policy_token;
 use tempfile::TempDir;
 
 pub use crate::errors::GenerateError as Error;
+use crate::monitoring;
 use crate::spec::{GenerateBackendSubcommand, GenerateCertsSubcommand, GenerateNodeSubcommand, Pair};
 use crate::utils::resolve_config_path;
 
@@ -496,6 +503,75 @@ fn generate_client_server_cert(
 
 
 
+/// Generates a private key and a certificate signing request given a CSR configuration file, to be signed by some external CA.
+///
+/// # Arguments
+/// - `exes`: The paths to the cfssl and cfssljson binaries.
+/// - `csr_file`: The path to the file that describes the new certificate.
+/// - `path`: The path to write the resulting files to (without extension; `-key.pem` and `.csr` are appended).
+///
+/// # Returns
+/// Nothing, except that when it does you can assume the key and CSR files exist at the given location.
+///
+/// # Errors
+/// This function may error if we failed to call the command or the command itself fails.
+fn generate_csr(exes: CfsslExecutables<impl AsRef<Path>, impl AsRef<Path>>, csr_file: impl AsRef<Path>, path: impl AsRef<Path>) -> Result<(), Error> {
+    let cfssl: &Path = exes.cfssl.as_ref();
+    let cfssljson: &Path = exes.cfssljson.as_ref();
+    let csr_file: &Path = csr_file.as_ref();
+    let path: &Path = path.as_ref();
+    info!("Generating certificate signing request...");
+
+    // Prepare the command to run
+    let mut cmd: Command = Command::new("bash");
+    cmd.arg("-c");
+    cmd.arg(format!("\"{}\" genkey \"{}\" | \"{}\" -bare \"{}\"", cfssl.display(), csr_file.display(), cfssljson.display(), path.display()));
+
+    // Run it
+    debug!("CSR generation command: {:?}", cmd);
+    let output: Output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) => {
+            return Err(Error::SpawnError { cmd, err });
+        },
+    };
+    if !output.status.success() {
+        return Err(Error::SpawnFailure { cmd, status: output.status, err: String::from_utf8_lossy(&output.stderr).into() });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Appends the certificate(s) in the given CA file to the given certificate file, such that it presents the full chain.
+///
+/// # Arguments
+/// - `cert_path`: The path to the certificate file to append to.
+/// - `ca_path`: The path to the CA certificate (chain) to append.
+///
+/// # Errors
+/// This function errors if we failed to open either of the files or failed to copy between them.
+fn append_chain(cert_path: &Path, ca_path: &Path) -> Result<(), Error> {
+    debug!("Appending CA chain '{}' to '{}'...", ca_path.display(), cert_path.display());
+    let mut cert: File = match fs::OpenOptions::new().append(true).open(cert_path) {
+        Ok(cert) => cert,
+        Err(err) => {
+            return Err(Error::FileOpenError { what: "server certificate", path: cert_path.into(), err });
+        },
+    };
+    let mut ca: File = match File::open(ca_path) {
+        Ok(ca) => ca,
+        Err(err) => {
+            return Err(Error::FileOpenError { what: "CA certificate", path: ca_path.into(), err });
+        },
+    };
+    if let Err(err) = std::io::copy(&mut ca, &mut cert) {
+        return Err(Error::CopyError { source: ca_path.into(), target: cert_path.into(), err });
+    }
+    Ok(())
+}
+
+
 
 
 /***** HELPER STRUCTS *****/
@@ -585,6 +661,101 @@ struct CfsslCsrKey {
 
 
 
+/// Prepares a certificate directory for rotation by backing up the certificates that are about to be overwritten.
+///
+/// The current CA certificate is moved to its "previous" location (see [`previous_ca_paths()`]), where services will keep trusting it until the given grace period ends.
+///
+/// # Arguments
+/// - `path`: The directory with the certificates to rotate.
+/// - `kind`: The kind of certificates that are being rotated.
+/// - `grace_period`: How long to keep trusting the previous CA.
+///
+/// # Errors
+/// This function errors if we failed to back up a file or write the grace period deadline.
+fn rotate_existing_certs(path: &Path, kind: &GenerateCertsSubcommand, grace_period: Duration) -> Result<(), Error> {
+    let ca_path: PathBuf = path.join("ca.pem");
+    if !ca_path.exists() {
+        debug!("Nothing to rotate in '{}' (no existing CA certificate)", path.display());
+        return Ok(());
+    }
+    info!("Rotating existing certificates in '{}' (grace period: {})...", path.display(), humantime::format_duration(grace_period));
+
+    // Back up the files that are about to be replaced
+    let files: &[&str] = match kind {
+        GenerateCertsSubcommand::Server { .. } => &["ca-key.pem", "server.pem", "server-key.pem"],
+        GenerateCertsSubcommand::Client { .. } => &["client-id.pem"],
+    };
+    for file in files {
+        let source: PathBuf = path.join(file);
+        if !source.exists() {
+            continue;
+        }
+        let target: PathBuf = path.join(file.replace(".pem", ".previous.pem"));
+        debug!("Backing up '{}' to '{}'...", source.display(), target.display());
+        if let Err(err) = fs::copy(&source, &target) {
+            return Err(Error::CopyError { source, target, err });
+        }
+    }
+
+    // Keep the CA around as a trusted, previous CA
+    let (prev_path, deadline_path): (PathBuf, PathBuf) = previous_ca_paths(&ca_path);
+    debug!("Keeping '{}' trusted as '{}'...", ca_path.display(), prev_path.display());
+    if let Err(err) = fs::copy(&ca_path, &prev_path) {
+        return Err(Error::CopyError { source: ca_path, target: prev_path, err });
+    }
+    let deadline: u64 = (SystemTime::now() + grace_period).duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    if let Err(err) = fs::write(&deadline_path, format!("{deadline}\n")) {
+        return Err(Error::FileWriteError { what: "grace period", path: deadline_path, err });
+    }
+
+    // Done
+    Ok(())
+}
+
+/// Asks the services of the local node to reload their certificates, if they hold on to them.
+///
+/// Failures are reported as warnings only, since the certificates themselves have been generated successfully at this point.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node.yml file of the local node.
+async fn reload_certs(node_config_path: &Path) {
+    // Find which services to reload
+    let node_config: NodeConfig = match NodeConfig::from_path(node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            warn!("{}", trace!(("Failed to load node config '{}'; not reloading any services", node_config_path.display()), err));
+            return;
+        },
+    };
+    // Note: `brane-prx` loads its certificates per connection and `brane-job` does not use any, so only the registry needs to be told
+    let name: &str = match &node_config.node {
+        NodeSpecificConfig::Worker(worker) => &worker.services.reg.name,
+        NodeSpecificConfig::Central(_) | NodeSpecificConfig::Proxy(_) => {
+            debug!("No services on a {} node hold on to their certificates", node_config.node.variant());
+            return;
+        },
+    };
+
+    // Send the signal
+    debug!("Sending SIGHUP to container '{name}'...");
+    let docker_opts: DockerOptions = DockerOptions { socket: PathBuf::from("/var/run/docker.sock"), version: ClientVersion(*API_DEFAULT_VERSION) };
+    let docker: Docker = match connect_local(docker_opts) {
+        Ok(docker) => docker,
+        Err(err) => {
+            warn!("{}", trace!(("Failed to connect to local Docker daemon; reload '{name}' manually (send it SIGHUP)"), err));
+            return;
+        },
+    };
+    match docker.kill_container(name, Some(KillContainerOptions { signal: "SIGHUP" })).await {
+        Ok(_) => println!("Reloaded certificates of {}", style(name).bold().green()),
+        Err(err) => warn!("{}", trace!(("Failed to signal container '{name}'; reload it manually (send it SIGHUP)"), err)),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Handles generating a new `node.yml` config file for a central _or_ worker node.
 ///
@@ -593,6 +764,7 @@ struct CfsslCsrKey {
 /// - `hosts`: List of additional hostnames to set in the launched containers.
 /// - `fix_dirs`: if true, will generate missing directories instead of complaining.
 /// - `config_path`: The path to the config directory that other paths may use as their base.
+/// - `monitoring`: If true, also writes the Prometheus and Grafana configuration for the optional monitoring stack to `<config_path>/monitoring`.
 /// - `command`: The GenerateSubcommand that contains the specific values to write, as well as whether to write a central or worker node.
 ///
 /// # Returns
@@ -605,6 +777,7 @@ pub fn node(
     hosts: Vec<Pair<String, ':', IpAddr>>,
     fix_dirs: bool,
     config_path: impl Into<PathBuf>,
+    monitoring: bool,
     command: GenerateNodeSubcommand,
 ) -> Result<(), Error> {
     let path: PathBuf = path.into();
@@ -723,6 +896,11 @@ pub fn node(
                         },
                     },
                 }),
+
+                tracing: None,
+                events:  None,
+
+                key_passphrase: None,
             }
         },
 
@@ -752,6 +930,8 @@ pub fn node(
             reg_port,
             job_port,
             chk_port,
+            max_concurrent_tasks,
+            task_timeout,
         } => {
             // Remove any scheme, paths, ports, whatever from the hostname
             let mut hostname: &str = &hostname;
@@ -848,7 +1028,19 @@ pub fn node(
                             })
                         },
                     },
+
+                    max_concurrent_tasks,
+                    task_timeout,
+                    container_pool: None,
+                    transfer_retry: Default::default(),
+                    egress_approval: None,
+                    policy_expert_token: None,
                 }),
+
+                tracing: None,
+                events:  None,
+
+                key_passphrase: None,
             }
         },
 
@@ -888,6 +1080,11 @@ pub fn node(
                         },
                     },
                 }),
+
+                tracing: None,
+                events:  None,
+
+                key_passphrase: None,
             }
         },
     };
@@ -909,9 +1106,22 @@ pub fn node(
     if let Err(err) = node_config.to_writer(handle, true) {
         return Err(Error::FileBodyWriteError { what: "infra.yml", path, err });
     }
+    println!("Successfully generated {}", style(path.display().to_string()).bold().green());
+
+    // Write the monitoring configuration, if asked
+    if monitoring {
+        let monitoring_path: PathBuf = config_path.join("monitoring");
+        debug!("Writing monitoring configuration to '{}'...", monitoring_path.display());
+        if let Err(err) = monitoring::write_config(&monitoring_path, &node_config) {
+            return Err(Error::MonitoringConfigError { path: monitoring_path, err });
+        }
+        println!(
+            "Successfully generated monitoring configuration in {} (use 'branectl unpack compose --monitoring' to deploy it)",
+            style(monitoring_path.display().to_string()).bold().green()
+        );
+    }
 
     // Done
-    println!("Successfully generated {}", style(path.display().to_string()).bold().green());
     Ok(())
 }
 
@@ -919,10 +1129,14 @@ pub fn node(
 
 /// Handles generating root & server certificates for the current domain.
 ///
+/// For server certificates, the root can also be an existing (intermediate) CA, or a certificate signing request can be generated instead to let an external CA sign it.
+///
 /// # Arguments
+/// - `node_config_path`: The path to the node.yml file of the local node. Only used to find which services to reload when rotating.
 /// - `fix_dirs`: if true, will generate missing directories instead of complaining.
 /// - `path`: The path of the directory to write the new certificate files to.
 /// - `temp_dir`: The path of the directory where we store the temporary scripts.
+/// - `rotate`: If given, replaces existing certificates in `path` while keeping the previous CA trusted for the given grace period, and reloads the local node's services afterwards.
 /// - `kind`: The kind of certificate(s) to generate.
 ///
 /// # Returns
@@ -930,7 +1144,15 @@ pub fn node(
 ///
 /// # Errors
 /// This function may error if I/O errors occur while downloading the auxillary scripts or while writing the files.
-pub async fn certs(fix_dirs: bool, path: impl Into<PathBuf>, temp_dir: impl Into<PathBuf>, mut kind: GenerateCertsSubcommand) -> Result<(), Error> {
+pub async fn certs(
+    node_config_path: impl Into<PathBuf>,
+    fix_dirs: bool,
+    path: impl Into<PathBuf>,
+    temp_dir: impl Into<PathBuf>,
+    rotate: Option<Duration>,
+    mut kind: GenerateCertsSubcommand,
+) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
     let path: PathBuf = path.into();
     let temp_dir: PathBuf = temp_dir.into();
 
@@ -1010,24 +1232,17 @@ pub async fn certs(fix_dirs: bool, path: impl Into<PathBuf>, temp_dir: impl Into
     // Generate a random ID to avoid* conflicting* repeated files
     let id: String = rand::thread_rng().sample_iter(Alphanumeric).map(char::from).take(3).collect::<String>();
 
+    // If we're rotating, keep the current certificates around before they are overwritten
+    if let Some(grace_period) = rotate {
+        rotate_existing_certs(&path, &kind, grace_period)?;
+    }
+
 
 
     /* KIND-SPECIFIC */
     match &kind {
-        GenerateCertsSubcommand::Server { location_id, hostname } => {
-            // Then write the CA config itself (always, since it contains call-specific information)
-            let ca_csr_path: PathBuf = temp_dir.join(format!("ca-csr-{id}.json"));
-            debug!("Generating '{}'...", ca_csr_path.display());
-            generate_config(
-                "CA CSR config",
-                CfsslCaCsr {
-                    cn:    location_id.clone(),
-                    key:   CfsslCsrKey { algo: "rsa".into(), size: 4096 },
-                    names: vec![HashMap::from([("".into(), "".into())])],
-                },
-                &ca_csr_path,
-            )?;
-            // And the server config
+        GenerateCertsSubcommand::Server { location_id, hostname, ca_cert, ca_key, csr } => {
+            // Write the server config
             let server_csr_path: PathBuf = temp_dir.join(format!("server-csr-{id}.json"));
             debug!("Generating '{}'...", server_csr_path.display());
             generate_config(
@@ -1041,17 +1256,82 @@ pub async fn certs(fix_dirs: bool, path: impl Into<PathBuf>, temp_dir: impl Into
                 &server_csr_path,
             )?;
 
-            // Now call the `cfssl` binary twice to generate the certificates
-            generate_ca_cert(&cfssl_path, &cfssljson_path, ca_csr_path, path.join("ca"))?;
-            generate_client_server_cert(
-                "server",
-                CfsslExecutables { cfssl: &cfssl_path, cfssljson: &cfssljson_path },
-                path.join("ca.pem"),
-                path.join("ca-key.pem"),
-                ca_config_path,
-                server_csr_path,
-                path.join("server"),
-            )?;
+            // Decide how to obtain a signed certificate
+            match (ca_cert, ca_key) {
+                (Some(ca_cert), Some(ca_key)) => {
+                    // Assert the input certificate and key are there
+                    if !ca_cert.exists() {
+                        return Err(Error::CaCertNotFound { path: ca_cert.clone() });
+                    }
+                    if !ca_cert.is_file() {
+                        return Err(Error::CaCertNotAFile { path: ca_cert.clone() });
+                    }
+                    if !ca_key.exists() {
+                        return Err(Error::CaKeyNotFound { path: ca_key.clone() });
+                    }
+                    if !ca_key.is_file() {
+                        return Err(Error::CaKeyNotAFile { path: ca_key.clone() });
+                    }
+
+                    // Sign the server certificate with the existing CA, and have it present the chain up to that CA
+                    generate_client_server_cert(
+                        "server",
+                        CfsslExecutables { cfssl: &cfssl_path, cfssljson: &cfssljson_path },
+                        ca_cert,
+                        ca_key,
+                        ca_config_path,
+                        server_csr_path,
+                        path.join("server"),
+                    )?;
+                    append_chain(&path.join("server.pem"), ca_cert)?;
+
+                    // Trust the existing CA (but keep its key out of the node)
+                    let out_ca_path: PathBuf = path.join("ca.pem");
+                    debug!("Copying existing CA certificate to '{}'...", out_ca_path.display());
+                    if let Err(err) = fs::copy(ca_cert, &out_ca_path) {
+                        return Err(Error::CopyError { source: ca_cert.clone(), target: out_ca_path, err });
+                    }
+                },
+
+                _ if *csr => {
+                    // Only generate the key and the request; the organisation's PKI does the rest
+                    generate_csr(CfsslExecutables { cfssl: &cfssl_path, cfssljson: &cfssljson_path }, server_csr_path, path.join("server"))?;
+                    println!(
+                        "Generated certificate signing request {}; have it signed by your organisation's CA, then place the certificate as {} and \
+                         the CA (chain) as {}",
+                        style(path.join("server.csr").display()).bold().green(),
+                        style(path.join("server.pem").display()).bold(),
+                        style(path.join("ca.pem").display()).bold()
+                    );
+                },
+
+                _ => {
+                    // Write the CA config itself (always, since it contains call-specific information)
+                    let ca_csr_path: PathBuf = temp_dir.join(format!("ca-csr-{id}.json"));
+                    debug!("Generating '{}'...", ca_csr_path.display());
+                    generate_config(
+                        "CA CSR config",
+                        CfsslCaCsr {
+                            cn:    location_id.clone(),
+                            key:   CfsslCsrKey { algo: "rsa".into(), size: 4096 },
+                            names: vec![HashMap::from([("".into(), "".into())])],
+                        },
+                        &ca_csr_path,
+                    )?;
+
+                    // Now call the `cfssl` binary twice to generate the certificates
+                    generate_ca_cert(&cfssl_path, &cfssljson_path, ca_csr_path, path.join("ca"))?;
+                    generate_client_server_cert(
+                        "server",
+                        CfsslExecutables { cfssl: &cfssl_path, cfssljson: &cfssljson_path },
+                        path.join("ca.pem"),
+                        path.join("ca-key.pem"),
+                        ca_config_path,
+                        server_csr_path,
+                        path.join("server"),
+                    )?;
+                },
+            }
         },
 
         GenerateCertsSubcommand::Client { location_id, hostname, ca_cert, ca_key } => {
@@ -1147,6 +1427,84 @@ pub async fn certs(fix_dirs: bool, path: impl Into<PathBuf>, temp_dir: impl Into
         kind.variant().to_string().to_lowercase(),
         style(kind.location_id()).green().bold()
     );
+
+    // Let the running services pick up the new certificates
+    if rotate.is_some() {
+        reload_certs(&node_config_path).await;
+    }
+    Ok(())
+}
+
+/// Handles minting a one-time token with which a client can enroll at this domain.
+///
+/// The registry of this domain issues a client certificate (signed by the domain's CA) to whoever presents the token first, as long as it has
+/// not expired. Only the hash of the token is stored on the node.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node.yml file of the local node, which must be a worker node.
+/// - `client`: The name of the client to issue the certificate to, which ends up as the certificate's common name.
+/// - `exp`: The duration for which the token may be used.
+///
+/// # Returns
+/// Nothing, but does write the token's hash to the enrollment tokens file in the worker's certificate directory and prints the token on stdout.
+///
+/// # Errors
+/// This function errors if the node is not a worker node, if it does not have its CA's key (e.g., because the CA is managed externally), or if
+/// we failed to update the enrollment tokens file.
+pub fn enrollment_token(node_config_path: impl AsRef<Path>, client: String, exp: Duration) -> Result<(), Error> {
+    info!("Generating enrollment token for client '{client}'...");
+
+    // Find the certificate directory of this worker
+    let node_config: NodeConfig = match NodeConfig::from_path(node_config_path.as_ref()) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+    let certs_dir: PathBuf = match node_config.node.try_worker() {
+        Some(worker) => worker.paths.certs.clone(),
+        None => return Err(Error::UnsupportedNode { what: "mint enrollment tokens", kind: node_config.node.kind() }),
+    };
+
+    // The registry can only issue certificates if it can sign them
+    let ca_path: PathBuf = certs_dir.join("ca.pem");
+    let ca_key_path: PathBuf = certs_dir.join("ca-key.pem");
+    if !ca_key_path.is_file() {
+        return Err(Error::CaKeyNotFound { path: ca_key_path });
+    }
+    let ca_fingerprint: String = match brane_cfg::certs::load_cert(&ca_path) {
+        Ok(certs) => match certs.first() {
+            Some(cert) => enrollment::fingerprint(&cert.0),
+            None => return Err(Error::CaCertEmpty { path: ca_path }),
+        },
+        Err(err) => return Err(Error::CaCertLoadError { path: ca_path, err }),
+    };
+
+    // Mint the token and remember its hash
+    let secret: String = OsRng.sample_iter(Alphanumeric).map(char::from).take(32).collect();
+    let expires: u64 = (SystemTime::now() + exp).duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let tokens_path: PathBuf = certs_dir.join(ENROLLMENT_TOKENS_FILE);
+    let mut tokens: EnrollmentTokensFile = if tokens_path.exists() {
+        match EnrollmentTokensFile::from_path(&tokens_path) {
+            Ok(tokens) => tokens,
+            Err(err) => return Err(Error::EnrollmentTokensLoadError { path: tokens_path, err }),
+        }
+    } else {
+        EnrollmentTokensFile::default()
+    };
+    tokens.tokens.push(PendingEnrollment { hash: enrollment::hash_secret(&secret), client: client.clone(), expires });
+    debug!("Writing enrollment tokens to '{}'...", tokens_path.display());
+    if let Err(err) = tokens.to_path(&tokens_path) {
+        return Err(Error::EnrollmentTokensWriteError { path: tokens_path, err });
+    }
+
+    // Show it to the user
+    let token: EnrollmentToken = EnrollmentToken { secret, ca_fingerprint };
+    println!("Generated enrollment token for client {} (valid for {}):", style(&client).bold().cyan(), humantime::format_duration(exp));
+    println!("{}", style(token).bold().green());
+    println!();
+    println!(
+        "Give it to the client, who can then run 'brane certs enroll {} --token <TOKEN>'. It can only be used once.",
+        node_config.node.worker().name
+    );
     Ok(())
 }
 
@@ -1186,6 +1544,10 @@ pub fn infra(
             name:     beautify_id(loc.0),
             registry: Address::hostname(format!("https://{}", loc.1), 50051),
             delegate: Address::hostname(format!("grpc://{}", loc.1), 50052),
+
+            labels: HashMap::new(),
+            zones: HashSet::new(),
+            capabilities: Default::default(),
         });
     }
 
@@ -1253,6 +1615,7 @@ pub fn infra(
 /// - `path`: The path to write the `creds.yml` to.
 /// - `capabilities`: A list of Capabilities to advertise for this domain.
 /// - `hash_container`: Whether the hashing-containers feature should be enabled or not.
+/// - `sandbox`: The runtime and privileges with which task containers are run. Only written if it deviates from the default.
 /// - `command`: The command with the type of backend (and associated properties) encoded in it.
 ///
 /// # Returns
@@ -1265,8 +1628,10 @@ pub fn backend(
     path: impl Into<PathBuf>,
     capabilities: Vec<Capability>,
     hash_containers: bool,
+    sandbox: SandboxConfig,
     command: GenerateBackendSubcommand,
 ) -> Result<(), Error> {
+    let sandbox: Option<SandboxConfig> = if sandbox != SandboxConfig::default() { Some(sandbox) } else { None };
     let path: PathBuf = path.into();
     info!("Generating backend.yml for a {} backend...", command.variant());
 
@@ -1279,6 +1644,22 @@ pub fn backend(
                 capabilities: Some(capabilities.into_iter().collect()),
                 hash_containers: Some(hash_containers),
                 method: Credentials::Local { path: Some(socket), version: client_version.map(|v| (v.0.major_version, v.0.minor_version)) },
+                sandbox,
+                backends: HashMap::new(),
+            }
+        },
+        GenerateBackendSubcommand::Kubernetes { registry_address, config } => {
+            // Default to the current user's Kubernetes config
+            let config: PathBuf = match config {
+                Some(config) => config,
+                None => dirs_2::home_dir().unwrap_or_default().join(".kube").join("config"),
+            };
+            BackendFile {
+                capabilities: Some(capabilities.into_iter().collect()),
+                hash_containers: Some(hash_containers),
+                method: Credentials::Kubernetes { registry_address, registry_credentials: None, config },
+                sandbox,
+                backends: HashMap::new(),
             }
         },
     };
@@ -1314,13 +1695,15 @@ pub fn backend(
 /// # Arguments
 /// - `fix_dirs`: if true, will generate missing directories instead of complaining.
 /// - `path`: The path to write the `policies.db` to.
+/// - `branch`: The branch of the policy reasoner repository to pull the migrations from.
+/// - `local`: If given, reads the migrations from this local copy of the policy reasoner repository instead (e.g., one imported with `branectl bundle import`).
 ///
 /// # Returns
 /// Nothing, but does write a new file to the given path and updates the user on stdout on success.
 ///
 /// # Errors
 /// This function may error if I/O errors occur while writing the file.
-pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> Result<(), Error> {
+pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String, local: Option<PathBuf>) -> Result<(), Error> {
     info!("Generating policies.db at '{}'...", path.display());
 
     // First, touch the file alive
@@ -1331,9 +1714,16 @@ pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> R
     }
 
     // Next, fetch the migrations to run
-    debug!("Retrieving up-to-date mitigations from 'https://github.com/epi-project/policy-reasoner ({branch})...");
     // NOTE: We're not using `_dir`, but keep it to prevent the directory from being removed once the objects gets dropped
-    let (_dir, migrations): (TempDir, FileBasedMigrations) = {
+    let (_dir, migrations): (Option<TempDir>, FileBasedMigrations) = if let Some(dir_path) = local {
+        debug!("Retrieving mitigations from local policy reasoner repository '{}'...", dir_path.display());
+        match FileBasedMigrations::find_migrations_directory_in_path(&dir_path) {
+            Ok(migrations) => (None, migrations),
+            Err(err) => return Err(Error::MigrationsRetrieve { path: dir_path, err }),
+        }
+    } else {
+        debug!("Retrieving up-to-date mitigations from 'https://github.com/epi-project/policy-reasoner ({branch})...");
+
         // Prepare the input URL and output directory
         let url = format!("https://api.github.com/repos/epi-project/policy-reasoner/tarball/{branch}");
         let dir = match TempDir::new() {
@@ -1365,7 +1755,7 @@ pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> R
             Ok(migrations) => migrations,
             Err(err) => return Err(Error::MigrationsRetrieve { path: dir_path, err }),
         };
-        (dir, migrations)
+        (Some(dir), migrations)
     };
 
     // Apply that with diesel
@@ -1509,7 +1899,7 @@ pub fn proxy(
 
     // Create the BackendFile
     debug!("Generating proxy information...");
-    let proxy: proxy::ProxyConfig = proxy::ProxyConfig { outgoing_range, incoming, forward };
+    let proxy: proxy::ProxyConfig = proxy::ProxyConfig { outgoing_range, incoming, forward, destinations: DestinationRules::default() };
 
     // Make sure its directory exists
     debug!("Writing to '{}'...", path.display());
@@ -0,0 +1,517 @@
+//  K8S.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 19:41:18
+//  Last edited:
+//    15 Oct 2026, 19:41:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Renders Kubernetes manifests (or a Helm chart) for a node from its
+//!   `node.yml` file, as an alternative to the Docker Compose files.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64ct::Encoding as _;
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, PrivateOrExternalService};
+use brane_cfg::proxy::ProxyConfig;
+use log::debug;
+use serde_json::{json, Value};
+use specifications::version::Version;
+
+pub use crate::errors::K8sError as Error;
+
+
+/***** CONSTANTS *****/
+/// The size of the persistent volume claims we generate when not rendering a Helm chart.
+const DEFAULT_STORAGE_SIZE: &str = "10Gi";
+
+/// The type of Kubernetes Service we use for services that must be reachable from outside the cluster when not rendering a Helm chart.
+const DEFAULT_SERVICE_TYPE: &str = "NodePort";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes a single pod-level volume and where it is mounted in the container.
+#[derive(Clone, Debug)]
+struct Mount {
+    /// The volume definition in the pod spec.
+    volume: Value,
+    /// The volume mount definition in the container spec.
+    mount:  Value,
+}
+
+/// Describes a single service to render a Deployment and a Service for.
+#[derive(Clone, Debug)]
+struct Workload {
+    /// The short identifier of the service (e.g., `api`).
+    id:     &'static str,
+    /// The name of the service, which is used as the name of the Deployment and the Service (and thus its hostname in the cluster).
+    name:   String,
+    /// The name of the image (without registry or tag) to run.
+    image:  &'static str,
+    /// The arguments to pass to the container.
+    args:   Vec<String>,
+    /// The environment variables to set in the container.
+    env:    Vec<(String, String)>,
+    /// The ports the service listens on.
+    ports:  Vec<u16>,
+    /// Whether the service must be reachable from outside the cluster.
+    public: bool,
+    /// The volumes to mount.
+    mounts: Vec<Mount>,
+}
+
+
+
+/// Defines the options that determine how the manifests are rendered.
+#[derive(Clone, Debug)]
+pub struct K8sOpts {
+    /// The Brane version of the images to run.
+    pub version:  Version,
+    /// The registry to pull the images from, if not the default one.
+    pub registry: Option<String>,
+    /// Whether to render a Helm chart instead of plain manifests.
+    pub helm:     bool,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the path at which the given path from the node config is mounted in the containers.
+#[inline]
+fn container_path(path: &Path) -> String { Path::new("/").join(path).display().to_string() }
+
+/// Turns a (relative) file path into something that is a valid ConfigMap or Secret key.
+#[inline]
+fn to_key(path: &str) -> String { path.replace(['/', '\\'], "__") }
+
+/// Reads the given file.
+///
+/// # Errors
+/// This function errors if we failed to read the file.
+fn read_file(what: &'static str, path: &Path) -> Result<Vec<u8>, Error> {
+    debug!("Reading {what} file '{}'...", path.display());
+    fs::read(path).map_err(|err| Error::FileReadError { what, path: path.into(), err })
+}
+
+/// Reads all files in the given directory, recursively.
+///
+/// # Arguments
+/// - `dir`: The directory to read.
+/// - `prefix`: The path of `dir` relative to the root we started reading at.
+/// - `res`: The list of `(relative path, contents)` pairs to add the files to.
+///
+/// # Errors
+/// This function errors if we failed to read the directory or any of its files.
+fn read_dir(dir: &Path, prefix: &str, res: &mut Vec<(String, Vec<u8>)>) -> Result<(), Error> {
+    debug!("Reading directory '{}'...", dir.display());
+    let entries = fs::read_dir(dir).map_err(|err| Error::DirReadError { path: dir.into(), err })?;
+    let mut paths: Vec<PathBuf> = vec![];
+    for entry in entries {
+        paths.push(entry.map_err(|err| Error::DirReadError { path: dir.into(), err })?.path());
+    }
+    paths.sort();
+    for path in paths {
+        let name: String = format!("{}{}", prefix, path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default());
+        if path.is_dir() {
+            read_dir(&path, &format!("{name}/"), res)?;
+        } else {
+            res.push((name, read_file("certificate", &path)?));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the standard labels for a resource belonging to the given service.
+#[inline]
+fn labels(name: &str, component: &str) -> Value {
+    json!({ "app.kubernetes.io/name": name, "app.kubernetes.io/component": component, "app.kubernetes.io/part-of": "brane" })
+}
+
+/// Returns the metadata of a resource with the given name.
+fn metadata(name: &str, component: &str, namespace: Option<&str>) -> Value {
+    let mut res: Value = json!({ "name": name, "labels": labels(name, component) });
+    if let Some(namespace) = namespace {
+        res["namespace"] = json!(namespace);
+    }
+    res
+}
+
+/// Renders a ConfigMap with the given (textual) files.
+fn config_map(name: &str, namespace: Option<&str>, files: Vec<(String, Vec<u8>)>) -> Value {
+    let data: serde_json::Map<String, Value> =
+        files.into_iter().map(|(key, contents)| (to_key(&key), json!(String::from_utf8_lossy(&contents).into_owned()))).collect();
+    json!({ "apiVersion": "v1", "kind": "ConfigMap", "metadata": metadata(name, "config", namespace), "data": data })
+}
+
+/// Renders an opaque Secret with the given files.
+fn secret(name: &str, namespace: Option<&str>, files: Vec<(String, Vec<u8>)>) -> Value {
+    let data: serde_json::Map<String, Value> =
+        files.into_iter().map(|(key, contents)| (to_key(&key), json!(base64ct::Base64::encode_string(&contents)))).collect();
+    json!({ "apiVersion": "v1", "kind": "Secret", "type": "Opaque", "metadata": metadata(name, "config", namespace), "data": data })
+}
+
+/// Renders a PersistentVolumeClaim.
+fn pvc(name: &str, namespace: Option<&str>, size: &str) -> Value {
+    json!({
+        "apiVersion": "v1",
+        "kind": "PersistentVolumeClaim",
+        "metadata": metadata(name, "storage", namespace),
+        "spec": { "accessModes": ["ReadWriteOnce"], "resources": { "requests": { "storage": size } } },
+    })
+}
+
+/// Mounts a single key of a ConfigMap or Secret as a file.
+fn mount_file(volume: &str, secret: bool, key: &str, path: String) -> Mount {
+    let volume_def: Value = if secret {
+        json!({ "name": volume, "secret": { "secretName": volume } })
+    } else {
+        json!({ "name": volume, "configMap": { "name": volume } })
+    };
+    Mount { volume: volume_def, mount: json!({ "name": volume, "mountPath": path, "subPath": to_key(key), "readOnly": true }) }
+}
+
+/// Mounts all files of a Secret as a directory, restoring their original (nested) paths.
+fn mount_secret_dir(volume: &str, files: &[String], path: String) -> Mount {
+    let items: Vec<Value> = files.iter().map(|f| json!({ "key": to_key(f), "path": f })).collect();
+    Mount {
+        volume: json!({ "name": volume, "secret": { "secretName": volume, "items": items } }),
+        mount:  json!({ "name": volume, "mountPath": path, "readOnly": true }),
+    }
+}
+
+/// Mounts a PersistentVolumeClaim as a directory.
+fn mount_pvc(claim: &str, path: String) -> Mount {
+    Mount {
+        volume: json!({ "name": claim, "persistentVolumeClaim": { "claimName": claim } }),
+        mount:  json!({ "name": claim, "mountPath": path }),
+    }
+}
+
+/// Renders the Deployment and Service for the given workload.
+fn workload(wl: Workload, namespace: Option<&str>, node_config: &NodeConfig, opts: &K8sOpts) -> Vec<Value> {
+    // Resolve the image and the service type
+    let (image, service_type): (String, String) = if opts.helm {
+        (format!("{{{{ .Values.image.registry }}}}{}:{{{{ .Values.image.tag }}}}", wl.image), "{{ .Values.service.type }}".into())
+    } else {
+        let registry: String = opts.registry.as_ref().map(|r| format!("{}/", r.trim_end_matches('/'))).unwrap_or_default();
+        (format!("{}{}:{}", registry, wl.image, opts.version), DEFAULT_SERVICE_TYPE.into())
+    };
+
+    // Build the container
+    let mut container: Value = json!({
+        "name": wl.image,
+        "image": image,
+        "imagePullPolicy": if opts.helm { "{{ .Values.image.pullPolicy }}" } else { "IfNotPresent" },
+        "ports": wl.ports.iter().map(|p| json!({ "containerPort": p })).collect::<Vec<Value>>(),
+        "volumeMounts": wl.mounts.iter().map(|m| m.mount.clone()).collect::<Vec<Value>>(),
+    });
+    if !wl.args.is_empty() {
+        container["args"] = json!(wl.args);
+    }
+    if !wl.env.is_empty() {
+        container["env"] = json!(wl.env.iter().map(|(k, v)| json!({ "name": k, "value": v })).collect::<Vec<Value>>());
+    }
+
+    // Build the pod spec; a volume may be mounted more than once (e.g., multiple files of the same Secret), but only defined once
+    let mut volumes: Vec<Value> = vec![];
+    for m in &wl.mounts {
+        if !volumes.iter().any(|v| v["name"] == m.volume["name"]) {
+            volumes.push(m.volume.clone());
+        }
+    }
+    let mut pod: Value = json!({ "containers": [container], "volumes": volumes });
+    if !node_config.hostnames.is_empty() {
+        let mut aliases: Vec<(&String, String)> = node_config.hostnames.iter().map(|(h, ip)| (h, ip.to_string())).collect();
+        aliases.sort();
+        pod["hostAliases"] = json!(aliases.into_iter().map(|(h, ip)| json!({ "ip": ip, "hostnames": [h] })).collect::<Vec<Value>>());
+    }
+
+    let selector: Value = json!({ "app.kubernetes.io/name": wl.name });
+    let mut res: Vec<Value> = vec![json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": metadata(&wl.name, wl.id, namespace),
+        "spec": {
+            "replicas": 1,
+            "strategy": { "type": "Recreate" },
+            "selector": { "matchLabels": selector },
+            "template": { "metadata": { "labels": labels(&wl.name, wl.id) }, "spec": pod },
+        },
+    })];
+    if !wl.ports.is_empty() {
+        res.push(json!({
+            "apiVersion": "v1",
+            "kind": "Service",
+            "metadata": metadata(&wl.name, wl.id, namespace),
+            "spec": {
+                "type": if wl.public { service_type } else { "ClusterIP".into() },
+                "selector": selector,
+                "ports": wl.ports.iter().map(|p| json!({ "name": format!("port-{p}"), "port": p, "targetPort": p })).collect::<Vec<Value>>(),
+            },
+        }));
+    }
+    res
+}
+
+/// Serializes the given resources as a multi-document YAML file.
+///
+/// # Errors
+/// This function errors if we failed to serialize any of the resources.
+fn to_yaml(what: &'static str, docs: &[Value]) -> Result<String, Error> {
+    let mut res: String = String::new();
+    for doc in docs {
+        let raw: String = serde_yaml::to_string(doc).map_err(|err| Error::SerializeError { what, err })?;
+        res.push_str("---\n");
+        res.push_str(raw.trim_start_matches("---\n"));
+    }
+    Ok(res)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Renders the Kubernetes manifests for the node described by the given node config.
+///
+/// The rendered manifests contain a Deployment and Service per hosted service (named like the containers in the Compose files, so the
+/// addresses in `node.yml` keep working), ConfigMaps and Secrets for the configuration files and certificates, and PersistentVolumeClaims
+/// for the data directories. Every file is mounted on the same path as given in `node.yml`.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the `node.yml` file, which is embedded in the manifests and used to resolve relative paths.
+/// - `node_config`: The [`NodeConfig`] loaded from `node_config_path`.
+/// - `opts`: The [`K8sOpts`] that determine how to render the manifests.
+///
+/// # Returns
+/// A list of `(path, contents)` pairs of the files to write, relative to the output directory.
+///
+/// # Errors
+/// This function errors if we failed to read any of the files referenced by the node config or serialize the manifests.
+pub fn render(node_config_path: &Path, node_config: &NodeConfig, opts: &K8sOpts) -> Result<Vec<(PathBuf, String)>, Error> {
+    let node_config_dir: &Path = node_config_path.parent().unwrap_or_else(|| Path::new("."));
+    let namespace: Option<&str> = if opts.helm { None } else { Some(node_config.namespace.as_str()) };
+    let size: &str = if opts.helm { "{{ .Values.storage.size }}" } else { DEFAULT_STORAGE_SIZE };
+    let kind: String = node_config.node.kind().to_string();
+
+    // Resolve the (shared) configuration and workloads per node kind
+    let mut config: Vec<Value> = vec![config_map("node-config", namespace, vec![("node.yml".into(), read_file("node config", node_config_path)?)])];
+    let mut volumes: Vec<Value> = vec![];
+    let mut workloads: Vec<Workload> = vec![];
+    let node_mount: Mount = mount_file("node-config", false, "node.yml", "/node.yml".into());
+    let (certs, proxy_path, prx): (&Path, Option<&Path>, Option<(String, u16, bool)>) = match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            let (paths, svcs) = (&central.paths, &central.services);
+
+            // Configuration
+            let infra: Vec<u8> = read_file("infrastructure", &node_config_dir.join(&paths.infra))?;
+            config.push(config_map("infra", namespace, vec![("infra.yml".into(), infra)]));
+            volumes.push(pvc("packages", namespace, size));
+            let infra: Mount = mount_file("infra", false, "infra.yml", container_path(&paths.infra));
+            let packages: Mount = mount_pvc("packages", container_path(&paths.packages));
+
+            // Workloads
+            workloads.push(Workload {
+                id:     "scylla",
+                name:   "aux-scylla".into(),
+                image:  "aux-scylla",
+                args:   vec!["--smp".into(), "1".into()],
+                env:    vec![],
+                ports:  vec![9042],
+                public: false,
+                mounts: vec![],
+            });
+            workloads.push(Workload {
+                id:     "api",
+                name:   svcs.api.name.clone(),
+                image:  "brane-api",
+                args:   vec!["--debug".into()],
+                env:    vec![],
+                ports:  vec![svcs.api.bind.port()],
+                public: true,
+                mounts: vec![node_mount.clone(), infra.clone(), packages],
+            });
+            workloads.push(Workload {
+                id:     "drv",
+                name:   svcs.drv.name.clone(),
+                image:  "brane-drv",
+                args:   vec!["--debug".into()],
+                env:    vec![],
+                ports:  vec![svcs.drv.bind.port()],
+                public: true,
+                mounts: vec![node_mount.clone(), infra.clone()],
+            });
+            workloads.push(Workload {
+                id:     "plr",
+                name:   svcs.plr.name.clone(),
+                image:  "brane-plr",
+                args:   vec![],
+                env:    vec![],
+                ports:  vec![svcs.plr.bind.port()],
+                public: false,
+                mounts: vec![node_mount.clone(), infra],
+            });
+
+            let prx = if let PrivateOrExternalService::Private(prx) = &svcs.prx { Some((prx.name.clone(), prx.bind.port(), false)) } else { None };
+            (paths.certs.as_path(), paths.proxy.as_deref(), prx)
+        },
+
+        NodeSpecificConfig::Worker(worker) => {
+            let (paths, svcs) = (&worker.paths, &worker.services);
+
+            // Configuration
+            config.push(secret("backend", namespace, vec![("backend.yml".into(), read_file("backend", &node_config_dir.join(&paths.backend))?)]));
+            config.push(secret("policy-secrets", namespace, vec![
+                ("deliberation.json".into(), read_file("policy deliberation secret", &node_config_dir.join(&paths.policy_deliberation_secret))?),
+                ("expert.json".into(), read_file("policy expert secret", &node_config_dir.join(&paths.policy_expert_secret))?),
+            ]));
+            for claim in ["packages", "data", "results", "temp-data", "temp-results", "policy-db"] {
+                volumes.push(pvc(claim, namespace, size));
+            }
+            let backend: Mount = mount_file("backend", true, "backend.yml", container_path(&paths.backend));
+            let delib: Mount = mount_file("policy-secrets", true, "deliberation.json", container_path(&paths.policy_deliberation_secret));
+            let data: Mount = mount_pvc("data", container_path(&paths.data));
+            let results: Mount = mount_pvc("results", container_path(&paths.results));
+
+            // Workloads
+            workloads.push(Workload {
+                id:     "chk",
+                name:   svcs.chk.name.clone(),
+                image:  "brane-chk",
+                args:   vec!["-s".into(), "node-file-path=/node.yml".into()],
+                env:    vec![("ADDRESS".into(), format!("0.0.0.0:{}", svcs.chk.bind.port()))],
+                ports:  vec![svcs.chk.bind.port()],
+                public: false,
+                mounts: vec![
+                    node_mount.clone(),
+                    mount_pvc("policy-db", "/data".into()),
+                    mount_file("policy-secrets", true, "deliberation.json", "/examples/config/jwk_set_delib.json".into()),
+                    mount_file("policy-secrets", true, "expert.json", "/examples/config/jwk_set_expert.json".into()),
+                ],
+            });
+            workloads.push(Workload {
+                id:     "reg",
+                name:   svcs.reg.name.clone(),
+                image:  "brane-reg",
+                args:   vec!["--debug".into()],
+                env:    vec![],
+                ports:  vec![svcs.reg.bind.port()],
+                public: true,
+                mounts: vec![node_mount.clone(), backend.clone(), delib.clone(), data.clone(), results.clone()],
+            });
+            workloads.push(Workload {
+                id:     "job",
+                name:   svcs.job.name.clone(),
+                image:  "brane-job",
+                args:   vec!["--debug".into()],
+                env:    vec![],
+                ports:  vec![svcs.job.bind.port()],
+                public: true,
+                mounts: vec![
+                    node_mount.clone(),
+                    backend,
+                    delib,
+                    mount_pvc("packages", container_path(&paths.packages)),
+                    data,
+                    results,
+                    mount_pvc("temp-data", container_path(&paths.temp_data)),
+                    mount_pvc("temp-results", container_path(&paths.temp_results)),
+                    // The job service launches task containers through the node's Docker daemon
+                    Mount {
+                        volume: json!({ "name": "docker-socket", "hostPath": { "path": "/var/run/docker.sock", "type": "Socket" } }),
+                        mount:  json!({ "name": "docker-socket", "mountPath": "/var/run/docker.sock" }),
+                    },
+                ],
+            });
+
+            let prx = if let PrivateOrExternalService::Private(prx) = &svcs.prx { Some((prx.name.clone(), prx.bind.port(), false)) } else { None };
+            (paths.certs.as_path(), paths.proxy.as_deref(), prx)
+        },
+
+        NodeSpecificConfig::Proxy(proxy) => {
+            let prx = &proxy.services.prx;
+            (proxy.paths.certs.as_path(), Some(proxy.paths.proxy.as_path()), Some((prx.name.clone(), prx.bind.port(), true)))
+        },
+    };
+
+    // Every service gets the certificates
+    let mut cert_files: Vec<(String, Vec<u8>)> = vec![];
+    read_dir(&node_config_dir.join(certs), "", &mut cert_files)?;
+    let cert_names: Vec<String> = cert_files.iter().map(|(name, _)| name.clone()).collect();
+    config.push(secret("certs", namespace, cert_files));
+    let certs_mount: Mount = mount_secret_dir("certs", &cert_names, container_path(certs));
+    for wl in &mut workloads {
+        if wl.id != "scylla" && wl.id != "chk" && wl.id != "plr" {
+            wl.mounts.push(certs_mount.clone());
+        }
+    }
+
+    // Add the proxy service if we host it
+    if let Some((name, port, public)) = prx {
+        let mut ports: Vec<u16> = vec![port];
+        let mut mounts: Vec<Mount> = vec![node_mount, certs_mount];
+        if let Some(proxy_path) = proxy_path {
+            let host_path: PathBuf = node_config_dir.join(proxy_path);
+            let proxy: ProxyConfig = ProxyConfig::from_path(&host_path).map_err(|err| Error::ProxyConfigLoadError { path: host_path.clone(), err })?;
+            // The other services connect to the proxy on the outgoing ports, and external parties on the incoming ones
+            ports.extend(proxy.outgoing_range.clone());
+            let mut incoming: Vec<u16> = proxy.incoming.keys().copied().collect();
+            incoming.sort();
+            ports.extend(incoming);
+            ports.sort();
+            ports.dedup();
+            config.push(secret("proxy", namespace, vec![("proxy.yml".into(), read_file("proxy", &host_path)?)]));
+            mounts.push(mount_file("proxy", true, "proxy.yml", container_path(proxy_path)));
+        }
+        workloads.insert(0, Workload { id: "prx", name, image: "brane-prx", args: vec!["--debug".into()], env: vec![], ports, public, mounts });
+    }
+
+    // Serialize everything
+    let mut files: Vec<(PathBuf, String)> = vec![];
+    let templates: PathBuf = if opts.helm { PathBuf::from("templates") } else { PathBuf::new() };
+    if opts.helm {
+        files.push((
+            "Chart.yaml".into(),
+            format!(
+                "apiVersion: v2\nname: brane-{kind}\ndescription: A Brane {kind} node\ntype: application\nversion: {}\nappVersion: \"{}\"\n",
+                opts.version, opts.version
+            ),
+        ));
+        files.push((
+            "values.yaml".into(),
+            format!(
+                "image:\n  # The registry to pull the images from, including a trailing slash (e.g., 'registry.example.com/brane/')\n  registry: \
+                 \"{}\"\n  tag: \"{}\"\n  pullPolicy: IfNotPresent\n\nservice:\n  # The type of Service used for services reachable from outside the \
+                 cluster\n  type: {DEFAULT_SERVICE_TYPE}\n\nstorage:\n  # The size of every persistent volume claim\n  size: \
+                 {DEFAULT_STORAGE_SIZE}\n",
+                opts.registry.as_ref().map(|r| format!("{}/", r.trim_end_matches('/'))).unwrap_or_default(),
+                opts.version
+            ),
+        ));
+    } else {
+        files.push((
+            "namespace.yml".into(),
+            to_yaml("namespace", &[json!({ "apiVersion": "v1", "kind": "Namespace", "metadata": { "name": node_config.namespace } })])?,
+        ));
+    }
+    files.push((templates.join("config.yml"), to_yaml("configuration", &config)?));
+    if !volumes.is_empty() {
+        files.push((templates.join("volumes.yml"), to_yaml("volume", &volumes)?));
+    }
+    for wl in workloads {
+        let path: PathBuf = templates.join(format!("{}.yml", wl.name));
+        files.push((path, to_yaml("workload", &workload(wl, namespace, node_config, opts))?));
+    }
+    Ok(files)
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    21 Nov 2022, 15:40:12
 //  Last edited:
-//    10 Jan 2024, 15:49:42
+//    16 Oct 2026, 01:29:42
 //  Auto updated?
 //    Yes
 //
@@ -15,14 +15,25 @@
 
 // Declare modules
 // pub mod args;
+pub mod approvals;
+pub mod backup;
+pub mod bundle;
+pub mod connectivity;
+pub mod dashboard;
 pub mod download;
 pub mod errors;
 pub mod generate;
+pub mod k8s;
 pub mod lifetime;
+pub mod logs;
+pub mod migrations;
+pub mod monitoring;
 pub mod old_configs;
 pub mod packages;
 pub mod policies;
 pub mod spec;
+pub mod status;
+pub mod systemd;
 pub mod unpack;
 pub mod upgrade;
 pub mod utils;
@@ -4,7 +4,7 @@
 //  Created:
 //    22 Nov 2022, 11:19:22
 //  Last edited:
-//    07 Mar 2024, 09:55:58
+//    16 Oct 2026, 01:52:35
 //  Auto updated?
 //    Yes
 //
@@ -16,7 +16,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fmt::Display;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
@@ -319,6 +319,12 @@ fn prepare_host(node_config: &NodeConfig) -> Result<(), Error> {
                         temp_results: _,
                     },
                 services: WorkerServices { reg: _, job: _, chk: _, prx: _ },
+                max_concurrent_tasks: _,
+                task_timeout: _,
+                container_pool: _,
+                transfer_retry: _,
+                egress_approval,
+                policy_expert_token: _,
             } = worker;
 
             // Generate an empty log if it doesn't exist
@@ -331,6 +337,16 @@ fn prepare_host(node_config: &NodeConfig) -> Result<(), Error> {
                 }
             }
 
+            // Create the directory in which the registry parks downloads awaiting approval
+            if let Some(egress_approval) = egress_approval {
+                if !egress_approval.path.exists() {
+                    debug!("Creating approvals directory '{}'...", egress_approval.path.display());
+                    if let Err(err) = fs::create_dir_all(&egress_approval.path) {
+                        return Err(Error::ApprovalsDirCreate { path: egress_approval.path.clone(), err });
+                    }
+                }
+            }
+
             // Done
             Ok(())
         },
@@ -351,13 +367,17 @@ fn prepare_host(node_config: &NodeConfig) -> Result<(), Error> {
 /// - `profile_dir`: The profile directory to mount (or not).
 ///
 /// # Returns
-/// The path to the generated compose file if it was necessary. If not (i.e., no hosts given), returns `None`.
+/// The path to the generated compose file if it was necessary. If not (i.e., no hosts given and no approvals directory to mount), returns `None`.
 ///
 /// # Errors
 /// This function errors if we failed to write the file.
 fn generate_override_file(node_config: &NodeConfig, hosts: &HashMap<String, IpAddr>, profile_dir: Option<PathBuf>) -> Result<Option<PathBuf>, Error> {
     // Early quit if there's nothing to do
-    if hosts.is_empty() {
+    let approvals: Option<&PathBuf> = match &node_config.node {
+        NodeSpecificConfig::Worker(node) => node.egress_approval.as_ref().map(|config| &config.path),
+        NodeSpecificConfig::Central(_) | NodeSpecificConfig::Proxy(_) => None,
+    };
+    if hosts.is_empty() && approvals.is_none() {
         return Ok(None);
     }
 
@@ -432,10 +452,16 @@ fn generate_override_file(node_config: &NodeConfig, hosts: &HashMap<String, IpAd
                 chk_svc.volumes.push(format!("{}:/audit-log.log", policy_audit_log.display()));
             }
 
+            // The registry parks downloads awaiting approval in the approvals directory
+            let mut reg_svc: ComposeOverrideFileService = svc.clone();
+            if let Some(approvals) = approvals {
+                reg_svc.volumes.push(format!("{}:{}", approvals.display(), approvals.display()));
+            }
+
             // Generate the override file for this node
             ComposeOverrideFile {
                 version:  "3.6",
-                services: HashMap::from([("brane-reg", svc.clone()), ("brane-job", svc), ("brane-chk", chk_svc), ("brane-prx", prx_svc)]),
+                services: HashMap::from([("brane-reg", reg_svc), ("brane-job", svc), ("brane-chk", chk_svc), ("brane-prx", prx_svc)]),
             }
         },
 
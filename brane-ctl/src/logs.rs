@@ -0,0 +1,144 @@
+//  LOGS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 18:47:03
+//  Last edited:
+//    15 Oct 2026, 18:47:03
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `branectl logs` subcommand, which shows (and
+//!   optionally follows) the logs of the services of the local node.
+//
+
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bollard::container::{LogOutput, LogsOptions};
+use bollard::Docker;
+use brane_cfg::info::Info as _;
+use brane_cfg::node::NodeConfig;
+use brane_tsk::docker::DockerOptions;
+use console::{style, Color};
+use futures_util::stream::{select_all, Stream, StreamExt as _};
+use log::{debug, info};
+use regex::Regex;
+
+pub use crate::errors::LogsError as Error;
+use crate::utils::node_services;
+
+
+/***** CONSTANTS *****/
+/// The colours we cycle through to distinguish services in the output.
+const COLOURS: [Color; 6] = [Color::Cyan, Color::Magenta, Color::Yellow, Color::Green, Color::Blue, Color::Red];
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the options for showing logs.
+#[derive(Clone, Debug)]
+pub struct LogsOpts {
+    /// The services (by identifier, e.g., `job`, or by container name) to show. Shows all of them if empty.
+    pub services: Vec<String>,
+    /// Whether to keep following the logs as new lines come in.
+    pub follow:   bool,
+    /// If given, only shows lines produced in this much time before now.
+    pub since:    Option<Duration>,
+    /// If given, only shows this many lines per service (before following).
+    pub tail:     Option<usize>,
+    /// If given, only shows lines that match this expression.
+    pub grep:     Option<Regex>,
+}
+
+
+
+/// Shows the logs of the services of the local node.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file that determines which services exist.
+/// - `docker_opts`: Configuration for connecting to the local Docker daemon. See `DockerOptions` for more information.
+/// - `opts`: The [`LogsOpts`] that determine which lines to show.
+///
+/// # Errors
+/// This function errors if we failed to read the node config, connect to Docker, or read the logs of any service.
+pub async fn logs(node_config_path: impl Into<PathBuf>, docker_opts: DockerOptions, opts: LogsOpts) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Showing logs of node defined by '{}'", node_config_path.display());
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+
+    // Resolve which services to show
+    let all: Vec<(&'static str, String)> = node_services(&node_config);
+    let services: Vec<(&'static str, String)> = if opts.services.is_empty() {
+        all
+    } else {
+        let mut res: Vec<(&'static str, String)> = Vec::with_capacity(opts.services.len());
+        for svc in &opts.services {
+            match all.iter().find(|(id, name)| svc == id || svc == name || svc.strip_prefix("brane-") == Some(*id)) {
+                Some(target) => res.push(target.clone()),
+                None => {
+                    return Err(Error::UnknownService { name: svc.clone(), known: all.iter().map(|(id, _)| (*id).into()).collect() });
+                },
+            }
+        }
+        res
+    };
+
+    // Connect to the Docker client
+    let docker: Docker = match brane_tsk::docker::connect_local(docker_opts) {
+        Ok(docker) => docker,
+        Err(err) => return Err(Error::DockerConnectError { err }),
+    };
+
+    // Open a log stream for every service
+    let since: i64 = match opts.since {
+        Some(since) => SystemTime::now().checked_sub(since).and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs() as i64).unwrap_or(0),
+        None => 0,
+    };
+    let tail: String = opts.tail.map(|t| t.to_string()).unwrap_or_else(|| "all".into());
+    let width: usize = services.iter().map(|(_, name)| name.len()).max().unwrap_or(0);
+    let mut streams: Vec<Pin<Box<dyn Send + Stream<Item = (usize, String, Result<LogOutput, bollard::errors::Error>)>>>> =
+        Vec::with_capacity(services.len());
+    for (i, (_, name)) in services.into_iter().enumerate() {
+        debug!("Opening log stream of container '{name}'...");
+        let stream = docker.logs(&name, Some(LogsOptions::<String> {
+            follow: opts.follow,
+            stdout: true,
+            stderr: true,
+            since,
+            tail: tail.clone(),
+            ..Default::default()
+        }));
+        streams.push(Box::pin(stream.map(move |line| (i, name.clone(), line))));
+    }
+
+    // Print the lines as they come in
+    let mut lines = select_all(streams);
+    while let Some((i, name, line)) = lines.next().await {
+        let line: LogOutput = match line {
+            Ok(line) => line,
+            Err(err) => return Err(Error::LogReadError { name, err }),
+        };
+        let text: String = String::from_utf8_lossy(&line.into_bytes()).into_owned();
+        for text in text.lines() {
+            if let Some(grep) = &opts.grep {
+                if !grep.is_match(text) {
+                    continue;
+                }
+            }
+            println!("{} | {}", style(format!("{name:<width$}")).fg(COLOURS[i % COLOURS.len()]).bold(), text);
+        }
+    }
+
+    // Done
+    Ok(())
+}
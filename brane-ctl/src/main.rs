@@ -4,7 +4,7 @@
 //  Created:
 //    15 Nov 2022, 09:18:40
 //  Last edited:
-//    01 May 2024, 15:20:07
+//    16 Oct 2026, 02:38:11
 //  Auto updated?
 //    Yes
 //
@@ -15,12 +15,18 @@
 use std::net::IpAddr;
 use std::path::PathBuf;
 
+use brane_cfg::backend::{ContainerRuntime, SandboxConfig};
 use brane_cfg::proxy::{ForwardConfig, ProxyProtocol};
+use brane_ctl::k8s::K8sOpts;
+use brane_ctl::logs::LogsOpts;
 use brane_ctl::spec::{
-    DownloadServicesSubcommand, GenerateBackendSubcommand, GenerateCertsSubcommand, GenerateNodeSubcommand, InclusiveRange, Pair,
-    PolicyInputLanguage, ResolvableNodeKind, StartOpts, StartSubcommand, VersionFix, API_DEFAULT_VERSION,
+    BundleContent, ConfigKind, DeployTarget, DownloadServicesSubcommand, GenerateBackendSubcommand, GenerateCertsSubcommand, GenerateNodeSubcommand,
+    InclusiveRange, Pair, PolicyInputLanguage, PolicyTemplate, ResolvableNodeKind, StartOpts, StartSubcommand, VersionFix, API_DEFAULT_VERSION,
+};
+use brane_ctl::{
+    approvals, backup, bundle, connectivity, download, generate, lifetime, logs, migrations, packages, policies, status, systemd, unpack, upgrade,
+    wizard,
 };
-use brane_ctl::{download, generate, lifetime, packages, policies, unpack, upgrade, wizard};
 use brane_tsk::docker::{ClientVersion, DockerOptions};
 use clap::{Parser, Subcommand};
 use dotenvy::dotenv;
@@ -29,6 +35,7 @@ use humanlog::{DebugMode, HumanLogger};
 use humantime::Duration as HumanDuration;
 use jsonwebtoken::jwk::KeyAlgorithm;
 use log::error;
+use regex::Regex;
 use specifications::address::{Address, AddressOpt};
 use specifications::arch::Arch;
 use specifications::package::Capability;
@@ -82,6 +89,14 @@ enum CtlSubcommand {
     Data(Box<DataSubcommand>),
     #[clap(subcommand)]
     Policies(Box<PolicySubcommand>),
+    #[clap(subcommand)]
+    Approvals(Box<ApprovalSubcommand>),
+    #[clap(subcommand)]
+    Backup(Box<BackupSubcommand>),
+    #[clap(subcommand)]
+    Bundle(Box<BundleSubcommand>),
+    #[clap(subcommand)]
+    Test(Box<TestSubcommand>),
 
     #[clap(name = "start", about = "Starts the local node by loading and then launching (already compiled) image files.")]
     Start {
@@ -134,12 +149,30 @@ enum CtlSubcommand {
         )]
         profile_dir: Option<PathBuf>,
 
+        /// How the services are deployed on this host.
+        #[clap(
+            long = "kind",
+            global = true,
+            default_value = "compose",
+            help = "How the services are deployed on this host. Can be 'compose' to run them as containers using Docker Compose, or 'systemd' to \
+                    start the units generated by 'branectl generate systemd' (in which case the image flags are ignored)."
+        )]
+        target: DeployTarget,
+
         /// Defines the possible nodes and associated flags to start.
         #[clap(subcommand)]
         kind: Box<StartSubcommand>,
     },
     #[clap(name = "stop", about = "Stops the local node if it is running.")]
     Stop {
+        /// How the services are deployed on this host.
+        #[clap(
+            long = "kind",
+            default_value = "compose",
+            help = "How the services are deployed on this host. Can be 'compose' to stop the Docker Compose containers, or 'systemd' to stop the \
+                    units generated by 'branectl generate systemd'."
+        )]
+        target: DeployTarget,
         /// The docker-compose command we run.
         #[clap(short, long, default_value = "docker compose", help = "The command to use to run Docker Compose.")]
         exe:  String,
@@ -148,6 +181,43 @@ enum CtlSubcommand {
         file: Option<PathBuf>,
     },
 
+    #[clap(name = "logs", about = "Shows the logs of the services of the local node.")]
+    Logs {
+        #[clap(short = 'S', long, default_value = "/var/run/docker.sock", help = "The path of the Docker socket to connect to.")]
+        docker_socket:  PathBuf,
+        #[clap(short = 'V', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The version of the Docker client API that we use to connect to the engine.")]
+        docker_version: ClientVersion,
+
+        /// The services to show the logs of.
+        #[clap(
+            short,
+            long = "service",
+            help = "The service to show the logs of, either by identifier (e.g., 'job') or container name (e.g., 'brane-job'). Can be given multiple \
+                    times. If omitted, shows the logs of all services of the node."
+        )]
+        services: Vec<String>,
+        /// Whether to keep following the logs.
+        #[clap(short, long, help = "If given, keeps following the logs as new lines come in.")]
+        follow:   bool,
+        /// How far back to show the logs.
+        #[clap(long, help = "If given, only shows lines produced this long ago or later (e.g., '10min' or '1h 30min').")]
+        since:    Option<HumanDuration>,
+        /// The number of lines to show.
+        #[clap(short, long, help = "If given, only shows the last this many lines of every service (before following).")]
+        tail:     Option<usize>,
+        /// The filter to apply.
+        #[clap(short, long, help = "If given, only shows lines that match this regular expression.")]
+        grep:     Option<Regex>,
+    },
+
+    #[clap(name = "status", about = "Reports the status, version, uptime and last error of every service of the local node.")]
+    Status {
+        #[clap(short = 'S', long, default_value = "/var/run/docker.sock", help = "The path of the Docker socket to connect to.")]
+        docker_socket:  PathBuf,
+        #[clap(short = 'V', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The version of the Docker client API that we use to connect to the engine.")]
+        docker_version: ClientVersion,
+    },
+
     #[clap(name = "version", about = "Returns the version of this CTL tool and/or the local node.")]
     Version {
         #[clap(short, long, help = "If given, shows the architecture instead of the version when using '--ctl' or '--node'.")]
@@ -215,6 +285,35 @@ enum DownloadSubcommand {
         )]
         force:   bool,
 
+        /// Any alternative mirrors to download the services from.
+        #[clap(
+            short,
+            long = "mirror",
+            global = true,
+            help = "The base address of an alternative mirror to download the images from (e.g., for air-gapped or geo-restricted sites). It should \
+                    have the same layout as the GitHub releases page, i.e., '<MIRROR>/download/v<VERSION>/<FILE>' and \
+                    '<MIRROR>/latest/download/<FILE>'. Can be given multiple times to try them in order. If omitted, downloads from GitHub. Note \
+                    that the published checksum is always fetched from GitHub; use '--sha256' if it cannot be reached."
+        )]
+        mirrors:   Vec<String>,
+        /// A manual checksum to verify against.
+        #[clap(
+            long,
+            global = true,
+            conflicts_with = "no_verify",
+            help = "The hexadecimal SHA-256 checksum that the downloaded images tarball should have. If omitted, uses the checksum published \
+                    alongside the release on GitHub in its 'SHA256SUMS' file (even when downloading from a mirror)."
+        )]
+        sha256:    Option<String>,
+        /// Whether to skip checksum verification.
+        #[clap(
+            long,
+            global = true,
+            help = "If given, does not verify the downloaded images against their published checksum. Only use this for releases that do not \
+                    publish a 'SHA256SUMS' file, and at your own risk."
+        )]
+        no_verify: bool,
+
         /// Whether to download the central or the worker VMs.
         #[clap(subcommand)]
         kind: DownloadServicesSubcommand,
@@ -252,6 +351,13 @@ enum GenerateSubcommand {
             help = "A common ancestor for --infra-path, --secrets-path and --certs-path. See their descriptions for more info."
         )]
         config_path: PathBuf,
+        /// Whether to also generate the monitoring stack configuration.
+        #[clap(
+            long,
+            help = "If given, also generates the Prometheus and Grafana configuration for the optional monitoring stack in \
+                    '<CONFIG_PATH>/monitoring'. Deploy it using 'branectl unpack compose --monitoring'."
+        )]
+        monitoring:  bool,
 
         /// Defines the possible nodes to generate a new node.yml file for.
         #[clap(subcommand)]
@@ -262,10 +368,10 @@ enum GenerateSubcommand {
     Certs {
         /// If given, will generate missing directories instead of throwing errors.
         #[clap(short = 'f', long, global = true, help = "If given, will generate any missing directories.")]
-        fix_dirs: bool,
+        fix_dirs:     bool,
         /// The directory to write to.
         #[clap(short, long, default_value = "./", global = true, help = "The path of the directory to write the generated certificate files.")]
-        path:     PathBuf,
+        path:         PathBuf,
         /// The directory to write temporary scripts to.
         #[clap(
             short,
@@ -274,13 +380,48 @@ enum GenerateSubcommand {
             global = true,
             help = "The path of the directory to write the temporary scripts to we use for certificate generation."
         )]
-        temp_dir: PathBuf,
+        temp_dir:     PathBuf,
+        /// Whether to rotate existing certificates.
+        #[clap(
+            long,
+            global = true,
+            help = "If given, rotates the certificates already in '--path': the previous CA remains trusted for '--grace-period', and the local \
+                    node's services are told to reload their certificates without being restarted."
+        )]
+        rotate:       bool,
+        /// How long to keep trusting the previous CA when rotating.
+        #[clap(
+            long,
+            default_value = "7d",
+            global = true,
+            help = "The time for which the previous CA remains trusted after rotating (e.g., '7d' or '12h'). Only relevant when using '--rotate'."
+        )]
+        grace_period: HumanDuration,
 
         /// The type of certificate to generate.
         #[clap(subcommand)]
         kind: Box<GenerateCertsSubcommand>,
     },
 
+    #[clap(
+        name = "enrollment-token",
+        about = "Mints a one-time token with which a client can enroll at this (worker) domain using 'brane certs enroll', i.e., have this domain's \
+                 registry issue it a client certificate."
+    )]
+    EnrollmentToken {
+        /// The name of the client.
+        #[clap(name = "CLIENT", help = "The name of the client (user) to issue the certificate to. It ends up as the certificate's common name.")]
+        client: String,
+        /// The expiry time.
+        #[clap(
+            short,
+            long,
+            default_value = "24h",
+            help = "The duration for which this token can be used. You can use freeform syntax like '5min', '7d' or even '1h 30min'"
+        )]
+        exp:    HumanDuration,
+    },
+
     #[clap(name = "infra", about = "Generates a new 'infra.yml' file.")]
     Infra {
         /// Defines the list of domains
@@ -343,6 +484,18 @@ enum GenerateSubcommand {
                     on a domain)"
         )]
         disable_hashing: bool,
+        /// The container runtime to run tasks with.
+        #[clap(
+            short,
+            long,
+            default_value = "runc",
+            help = "The container runtime to run tasks with. Can be 'runc' (Docker's default), 'runsc' (gVisor) or 'kata' (Kata Containers). \
+                    Note that the chosen runtime must be installed and registered with the Docker daemon."
+        )]
+        runtime:         ContainerRuntime,
+        /// Whether to run tasks rootless.
+        #[clap(long, help = "If given, runs task containers as an unprivileged user with all capabilities dropped.")]
+        rootless:        bool,
 
         /// Defines the possible backends to generate a new backend.yml file for.
         #[clap(subcommand)]
@@ -365,6 +518,15 @@ enum GenerateSubcommand {
             help = "The branch of the `https://github.com/epi-project/policy-reasoner` repository from which to pull the Diesel migrations."
         )]
         branch:   String,
+        /// A local copy of the policy reasoner repository to use instead.
+        #[clap(
+            short,
+            long,
+            conflicts_with = "branch",
+            help = "If given, reads the Diesel migrations from this local copy of the policy reasoner repository instead of downloading them \
+                    (e.g., one imported with 'branectl bundle import')."
+        )]
+        migrations: Option<PathBuf>,
     },
 
     #[clap(name = "policy_secret", about = "Generates a new JWT key for use in the `brane-chk` service.")]
@@ -452,6 +614,26 @@ enum GenerateSubcommand {
         )]
         forward_protocol: ProxyProtocol,
     },
+
+    #[clap(
+        name = "systemd",
+        about = "Generates systemd unit files that run the services of the node defined by --node-config directly on the host, instead of in \
+                 containers."
+    )]
+    Systemd {
+        /// If given, will generate missing directories instead of throwing errors.
+        #[clap(short, long, help = "If given, will generate any missing directories.")]
+        fix_dirs: bool,
+        /// The directory to write the units to.
+        #[clap(short, long, default_value = "/etc/systemd/system", help = "The directory to write the unit files to.")]
+        path:     PathBuf,
+        /// The directory where the service binaries are installed.
+        #[clap(short, long, default_value = "/usr/local/bin", help = "The directory where the 'brane-<svc>' service binaries are installed.")]
+        bin_dir:  PathBuf,
+        /// The user to run the services as.
+        #[clap(short, long, help = "If given, runs the services as this user instead of root.")]
+        user:     Option<String>,
+    },
 }
 
 /// Defines subcommands that allow us to unpack baked-in files.
@@ -481,10 +663,59 @@ enum UnpackSubcommand {
             help = "Defines the kind of node for which to unpack the Docker Compose file. You can use '$NODECFG' to refer to the node kind defined \
                     in the `node.yml` file (see 'branectl -n')."
         )]
-        kind:     ResolvableNodeKind,
+        kind:       ResolvableNodeKind,
+        /// Whether to fix missing directories (true) or throw errors (false).
+        #[clap(short, long, help = "If given, will create missing directories instead of throwing an error.")]
+        fix_dirs:   bool,
+        /// Whether to add the monitoring stack.
+        #[clap(
+            long,
+            help = "If given, adds Prometheus and Grafana services to the unpacked file. They read their configuration from '$MONITORING_DIR' \
+                    (default: './config/monitoring'), which can be generated with 'branectl generate node --monitoring'."
+        )]
+        monitoring: bool,
+        /// Whether to add the dashboard.
+        #[clap(
+            long,
+            help = "If given, adds the dashboard service (brane-web) to the unpacked file, which shows the packages, datasets, active workflows and \
+                    domain health of the instance in a browser. It is served on '$WEB_HOST:$WEB_PORT' (default: '127.0.0.1:50070'). Only valid for \
+                    central nodes."
+        )]
+        dashboard:  bool,
+    },
+
+    #[clap(
+        name = "k8s",
+        alias = "kubernetes",
+        about = "Renders Kubernetes manifests (Deployments, Services, Secrets and PersistentVolumeClaims) for the node defined by \
+                 --node-config, as an alternative to the Docker Compose file. The files referenced by the node config are embedded in the \
+                 manifests, so re-run this command if they change."
+    )]
+    K8s {
+        /// The location to which to write the manifests.
+        #[clap(
+            name = "PATH",
+            default_value = "./k8s-$NODE",
+            help = "Defines the directory to which we write the manifests. You can use '$NODE' to refer to the node kind defined in the \
+                    `node.yml` file."
+        )]
+        path: PathBuf,
+
         /// Whether to fix missing directories (true) or throw errors (false).
         #[clap(short, long, help = "If given, will create missing directories instead of throwing an error.")]
         fix_dirs: bool,
+        /// Whether to render a Helm chart.
+        #[clap(
+            long,
+            help = "If given, renders a Helm chart (with the image, service type and storage size as values) instead of plain manifests."
+        )]
+        helm:     bool,
+        /// The registry to pull the images from.
+        #[clap(short, long, help = "If given, pulls the images from this registry (e.g., 'registry.example.com/brane') instead of the default one.")]
+        registry: Option<String>,
+        /// The Brane version of the images to run.
+        #[clap(short, long, default_value = env!("CARGO_PKG_VERSION"), help = "The Brane version of the images to run.")]
+        version:  Version,
     },
 }
 
@@ -524,6 +755,69 @@ enum UpgradeSubcommand {
         )]
         version:   VersionFix,
     },
+    #[clap(
+        name = "config",
+        about = "Migrates node.yml, infra.yml, backend.yml and proxy.yml files to the layout of this BRANE version by running every applicable \
+                 migration step in order."
+    )]
+    Config {
+        /// The file or folder to migrate.
+        #[clap(
+            name = "PATH",
+            default_value = "./",
+            help = "The path to the file to migrate, or a folder of which all 'node.yml', 'infra.yml', 'backend.yml' and 'proxy.yml' files \
+                    (non-recursively) are migrated."
+        )]
+        path: PathBuf,
+        /// The kind of the file(s) to migrate.
+        #[clap(
+            short,
+            long,
+            help = "The kind of config file to migrate ('node', 'infra', 'backend' or 'proxy'). If omitted, it is deduced from the filename. If \
+                    PATH is a folder, only files of this kind are migrated."
+        )]
+        kind: Option<ConfigKind>,
+
+        /// Whether to run dryly or not
+        #[clap(short, long, help = "If given, does not write anything but instead shows a diff of every change that would be made.")]
+        dry_run:   bool,
+        /// Whether to skip the backups
+        #[clap(long, help = "If given, does not back up files to '<FILE>.<TIMESTAMP>.bak' before overwriting them.")]
+        no_backup: bool,
+        /// Fixes the version from which we are migrating.
+        #[clap(
+            short,
+            long,
+            default_value = "all",
+            help = "The BRANE version the files are currently compatible with. Only migration steps for newer versions are run. Use 'all' to run \
+                    every step that applies."
+        )]
+        from:      VersionFix,
+    },
+}
+
+/// Defines the subcommands for the test subcommand
+#[derive(Debug, Subcommand)]
+#[clap(name = "test", about = "Runs diagnostics on the local node.")]
+enum TestSubcommand {
+    #[clap(
+        name = "connectivity",
+        about = "Attempts real (TLS) connections to the services of the other domains in the instance, and reports reachability, certificate \
+                 validity and latency, together with suggested fixes."
+    )]
+    Connectivity {
+        /// The infra file describing the other domains.
+        #[clap(
+            short,
+            long,
+            help = "The 'infra.yml' file describing the other domains to test. Defaults to the one in the node config for central nodes; if \
+                    omitted for worker nodes, only the registries of their use-cases are tested."
+        )]
+        infra:   Option<PathBuf>,
+        /// The timeout for every network operation.
+        #[clap(short, long, default_value = "5s", help = "The time to wait for every connection attempt or response (e.g., '5s').")]
+        timeout: HumanDuration,
+    },
 }
 
 /// Defines subcommands relating to the wizard
@@ -556,9 +850,135 @@ enum PackageSubcommand {
 #[clap(name = "data", about = "Manage data and intermediate results stored on this node.")]
 enum DataSubcommand {}
 
+/// Defines backup-related subcommands for the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "backup", about = "Create or restore backups of the local node's configuration and state.")]
+enum BackupSubcommand {
+    #[clap(
+        name = "create",
+        about = "Snapshots the node's configuration, certificates, package store, dataset metadata and policy database into a single archive."
+    )]
+    Create {
+        /// The archive to write.
+        #[clap(name = "OUTPUT", default_value = "./brane-backup.tar.gz", help = "The path of the '.tar.gz' archive to write the backup to.")]
+        output: PathBuf,
+    },
+
+    #[clap(
+        name = "restore",
+        about = "Restores a backup created with 'branectl backup create'. The node.yml file is restored to the path given by '--node-config', \
+                 everything else to the path it was backed up from."
+    )]
+    Restore {
+        /// The archive to restore.
+        #[clap(name = "INPUT", help = "The path of the '.tar.gz' archive to restore.")]
+        input: PathBuf,
+        /// Whether to overwrite existing files.
+        #[clap(short, long, help = "If given, overwrites any existing files and directories instead of refusing to restore.")]
+        force: bool,
+    },
+}
+
+/// Defines subcommands for installing nodes without internet access.
+#[derive(Debug, Subcommand)]
+#[clap(name = "bundle", about = "Export or import everything needed to install a node on a machine without internet access.")]
+enum BundleSubcommand {
+    #[clap(
+        name = "export",
+        about = "Collects the service images, auxillary images and policy reasoner artifacts into a single archive. Run this on a machine with \
+                 internet access."
+    )]
+    Export {
+        /// The archive to write.
+        #[clap(name = "OUTPUT", default_value = "./brane-bundle.tar.gz", help = "The path of the '.tar.gz' archive to write the bundle to.")]
+        output:  PathBuf,
+        /// The architecture for which to collect the services.
+        #[clap(
+            short,
+            long,
+            default_value = "$LOCAL",
+            help = "The processor architecture for which to collect the images. Specify '$LOCAL' to use the architecture of the current machine \
+                    (which may differ from the air-gapped one)."
+        )]
+        arch:    Arch,
+        /// The version of the services to collect.
+        #[clap(short, long, default_value = env!("CARGO_PKG_VERSION"), help = "The version of the images to collect.")]
+        version: Version,
+        /// What to collect.
+        #[clap(
+            short,
+            long,
+            help = "The artifacts to collect. Options are 'central', 'worker', 'auxillary' and 'policy-reasoner'. Can be given multiple times. If \
+                    omitted, collects all of them."
+        )]
+        content: Vec<BundleContent>,
+
+        /// Any alternative mirrors to download the services from.
+        #[clap(
+            short,
+            long = "mirror",
+            help = "The base address of an alternative mirror to download the images from (see 'branectl download services'). Can be given \
+                    multiple times to try them in order."
+        )]
+        mirrors:   Vec<String>,
+        /// Whether to skip checksum verification.
+        #[clap(long, help = "If given, does not verify the downloaded images against their published checksum.")]
+        no_verify: bool,
+        /// The branch to pull the policy reasoner artifacts from.
+        #[clap(
+            short,
+            long,
+            default_value = "main",
+            help = "The branch of the `https://github.com/epi-project/policy-reasoner` repository from which to collect the Diesel migrations."
+        )]
+        branch:    String,
+
+        /// The path of the Docker socket.
+        #[clap(short, long, default_value = "/var/run/docker.sock", help = "The path of the Docker socket to use for pulling the auxillary images.")]
+        socket: PathBuf,
+        /// The client version to connect with.
+        #[clap(long, default_value = API_DEFAULT_VERSION.as_str(), help = "The client version to connect to the Docker instance with.")]
+        client_version: ClientVersion,
+    },
+
+    #[clap(
+        name = "import",
+        about = "Verifies and installs a bundle created with 'branectl bundle export'. Run this on the machine without internet access, then use \
+                 'branectl start' as usual."
+    )]
+    Import {
+        /// The archive to import.
+        #[clap(name = "INPUT", help = "The path of the '.tar.gz' archive to import.")]
+        input:    PathBuf,
+        /// Whether to create missing directories.
+        #[clap(short = 'f', long, help = "If given, will automatically create missing directories.")]
+        fix_dirs: bool,
+        /// The directory to write the images to.
+        #[clap(
+            short,
+            long,
+            default_value = "./target/release",
+            help = "The directory to write the images to. Note: if you leave it at the default, then you won't have to manually specify anything \
+                    when running 'branectl start'."
+        )]
+        path:     PathBuf,
+        /// The directory to write the policy reasoner artifacts to.
+        #[clap(
+            long,
+            default_value = "./policy-reasoner",
+            help = "The directory to write the policy reasoner artifacts to. Give it to 'branectl generate policy_database --migrations' \
+                    afterwards."
+        )]
+        policy_reasoner: PathBuf,
+        /// Whether to overwrite existing files.
+        #[clap(short = 'F', long, help = "If given, overwrites any existing images and policy reasoner artifacts instead of refusing to import.")]
+        force: bool,
+    },
+}
+
 /// Defines policy-related subcommands for the `branectl` tool.
 #[derive(Debug, Subcommand)]
-#[clap(name = "policies", alias = "policy", about = "Manage the checker's policies by adding them or setting different active versions.")]
+#[clap(name = "policies", alias = "policy", about = "Manage the checker's policies by adding, inspecting, (de)activating or removing them.")]
 enum PolicySubcommand {
     /// Activates a policy in the remote checker.
     #[clap(name = "activate", about = "Activates an already added policy in the remote checker.")]
@@ -585,9 +1005,9 @@ enum PolicySubcommand {
             short,
             long,
             env,
-            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the one from the `policy_expert_secret` file \
-                    in the given `node.yml` when found. Note that you can also just set an environment variable named 'TOKEN' with the value if you \
-                    don't want to give it everytime."
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
         )]
         token:   Option<String>,
     },
@@ -625,9 +1045,9 @@ enum PolicySubcommand {
             short,
             long,
             env,
-            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the one from the `policy_expert_secret` file \
-                    in the given `node.yml` when found. Note that you can also just set an environment variable named 'TOKEN' with the value if you \
-                    don't want to give it everytime."
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
         )]
         token:   Option<String>,
     },
@@ -648,12 +1068,188 @@ enum PolicySubcommand {
             short,
             long,
             env,
-            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the one from the `policy_expert_secret` file \
-                    in the given `node.yml` when found. Note that you can also just set an environment variable named 'TOKEN' with the value if you \
-                    don't want to give it everytime."
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
+        )]
+        token:   Option<String>,
+    },
+
+    /// Deactivates the active policy in the remote checker.
+    #[clap(name = "deactivate", about = "Deactivates the active policy in the remote checker, such that no policy is active anymore.")]
+    Deactivate {
+        /// Address on which to find the checker.
+        #[clap(
+            short,
+            long,
+            default_value = "localhost",
+            help = "The address on which to reach the checker service, given as '<HOSTNAME>[:<PORT>]'. If you omit the port, the one from the \
+                    `node.yml` file is read."
+        )]
+        address: AddressOpt,
+        /// The JWT to use to authenticate with the remote checker.
+        #[clap(
+            short,
+            long,
+            env,
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
+        )]
+        token:   Option<String>,
+    },
+
+    /// Removes a policy from the remote checker.
+    #[clap(name = "remove", alias = "rm", about = "Removes a policy from the remote checker. The active policy cannot be removed.")]
+    Remove {
+        /// The policy to remove.
+        #[clap(name = "VERSION", help = "The version of the policy to remove.")]
+        version: i64,
+
+        /// Address on which to find the checker.
+        #[clap(
+            short,
+            long,
+            default_value = "localhost",
+            help = "The address on which to reach the checker service, given as '<HOSTNAME>[:<PORT>]'. If you omit the port, the one from the \
+                    `node.yml` file is read."
+        )]
+        address: AddressOpt,
+        /// The JWT to use to authenticate with the remote checker.
+        #[clap(
+            short,
+            long,
+            env,
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
+        )]
+        token:   Option<String>,
+    },
+
+    /// Shows a policy in the remote checker.
+    #[clap(name = "show", about = "Shows a policy in the remote checker, including a diff against the active policy.")]
+    Show {
+        /// The policy to show.
+        #[clap(name = "VERSION", help = "The version of the policy to show.")]
+        version: i64,
+
+        /// Address on which to find the checker.
+        #[clap(
+            short,
+            long,
+            default_value = "localhost",
+            help = "The address on which to reach the checker service, given as '<HOSTNAME>[:<PORT>]'. If you omit the port, the one from the \
+                    `node.yml` file is read."
+        )]
+        address: AddressOpt,
+        /// The JWT to use to authenticate with the remote checker.
+        #[clap(
+            short,
+            long,
+            env,
+            help = "A JSON Web Token (JWT) to use to authenticate to the checker. If omitted, will use the `policy_expert_token` in the given \
+                    `node.yml`, or else generate one from its `policy_expert_secret` file. Note that you can also just set an environment variable \
+                    named 'TOKEN' with the value if you don't want to give it everytime."
         )]
         token:   Option<String>,
     },
+
+    /// Validates a policy file locally.
+    #[clap(name = "validate", about = "Checks a policy file for syntax errors locally, without sending it to the checker.")]
+    Validate {
+        /// The path to the policy file to validate, but with stdout capabilities.
+        #[clap(
+            name = "INPUT",
+            help = "The input policy to validate. Given as a path to a file, or '-' to read from stdin (end you policy with Ctrl+D)."
+        )]
+        input:    String,
+        /// The language of the input.
+        #[clap(
+            short,
+            long,
+            help = "The language of the input policy. Options are 'eflint' and 'eflint-json', where the former will be compiled to the latter \
+                    to check it. If omitted, will attempt to deduce it based on the 'INPUT'."
+        )]
+        language: Option<PolicyInputLanguage>,
+    },
+
+    /// Generates a new policy set from a template.
+    #[clap(name = "init", about = "Generates a starter eFLINT policy set from a template, together with sample workflows to check it against.")]
+    Init {
+        /// The template to generate the policy set from.
+        #[clap(
+            name = "TEMPLATE",
+            help = "The template to generate the policy set from. Options are 'allow-all' (allows everything), 'dataset-allowlist' (only allows \
+                    listed datasets to be transferred) and 'purpose-binding' (only allows datasets to be used by workflows tagged with a purpose \
+                    they may be used for)."
+        )]
+        template: PolicyTemplate,
+        /// The directory to generate the policy set in.
+        #[clap(short, long, default_value = "./policy", help = "The directory to generate the policy set in. Will be created if it does not exist.")]
+        path:     PathBuf,
+        /// Whether to overwrite existing files.
+        #[clap(short = 'F', long, help = "If given, overwrites any existing files of the policy set instead of refusing to generate it.")]
+        force:    bool,
+    },
+
+    /// Checks a policy set against its sample workflows locally.
+    #[clap(
+        name = "check",
+        about = "Checks a policy set against its sample workflows using a local eFLINT reasoner, without sending anything to the checker."
+    )]
+    Check {
+        /// The directory of the policy set to check.
+        #[clap(
+            name = "DIR",
+            help = "The directory of the policy set to check. It should contain a 'policy.eflint' (or 'policy.json') file, and sample workflows \
+                    that the policy should allow in 'samples/allow/' and deny in 'samples/deny/' (see 'branectl policies init')."
+        )]
+        dir:      PathBuf,
+        /// The address of the reasoner to check with.
+        #[clap(
+            short,
+            long,
+            default_value = "http://localhost:8080",
+            help = "The address of the eFLINT reasoner to check with (e.g., one that you started locally with `docker run`)."
+        )]
+        reasoner: Address,
+    },
+}
+
+/// Defines subcommands for deciding on downloads that await approval in the `branectl` tool.
+#[derive(Debug, Subcommand)]
+#[clap(name = "approvals", about = "Decide on downloads of sensitive intermediate results awaiting approval.")]
+enum ApprovalSubcommand {
+    /// Lists the downloads awaiting approval.
+    #[clap(name = "list", about = "Lists the downloads of intermediate results that await approval on this node.")]
+    List {
+        /// Whether to also list decided downloads.
+        #[clap(short, long, help = "If given, also lists downloads that have already been approved or denied.")]
+        all: bool,
+    },
+
+    /// Approves a download.
+    #[clap(name = "approve", about = "Approves a download, such that the result is served when the requester tries again.")]
+    Approve {
+        /// The download to approve.
+        #[clap(name = "ID", help = "The identifier of the download to approve, as shown by `branectl approvals list`.")]
+        id:     String,
+        /// Why the download is approved.
+        #[clap(short, long, help = "An optional reason for the approval, kept with the request for auditing.")]
+        reason: Option<String>,
+    },
+
+    /// Denies a download.
+    #[clap(name = "deny", about = "Denies a download, such that the requester is refused when they try again.")]
+    Deny {
+        /// The download to deny.
+        #[clap(name = "ID", help = "The identifier of the download to deny, as shown by `branectl approvals list`.")]
+        id:     String,
+        /// Why the download is denied.
+        #[clap(short, long, help = "An optional reason for the denial, which is shown to the requesting user.")]
+        reason: Option<String>,
+    },
 }
 
 
@@ -711,26 +1307,35 @@ async fn main() {
     // Now match on the command
     match args.subcommand {
         CtlSubcommand::Download(subcommand) => match *subcommand {
-            DownloadSubcommand::Services { fix_dirs, path, arch, version, force, kind } => {
+            DownloadSubcommand::Services { fix_dirs, path, arch, version, force, mirrors, sha256, no_verify, kind } => {
                 // Run the subcommand
-                if let Err(err) = download::services(fix_dirs, path, arch, version, force, kind).await {
+                if let Err(err) = download::services(fix_dirs, path, arch, version, force, mirrors, sha256, no_verify, kind).await {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
             },
         },
         CtlSubcommand::Generate(subcommand) => match *subcommand {
-            GenerateSubcommand::Node { hosts, fix_dirs, config_path, kind } => {
+            GenerateSubcommand::Node { hosts, fix_dirs, config_path, monitoring, kind } => {
+                // Call the thing
+                if let Err(err) = generate::node(args.node_config, hosts, fix_dirs, config_path, monitoring, *kind) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            GenerateSubcommand::Certs { fix_dirs, path, temp_dir, rotate, grace_period, kind } => {
                 // Call the thing
-                if let Err(err) = generate::node(args.node_config, hosts, fix_dirs, config_path, *kind) {
+                let rotate: Option<std::time::Duration> = if rotate { Some(*grace_period) } else { None };
+                if let Err(err) = generate::certs(args.node_config, fix_dirs, path, temp_dir, rotate, *kind).await {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
             },
 
-            GenerateSubcommand::Certs { fix_dirs, path, temp_dir, kind } => {
+            GenerateSubcommand::EnrollmentToken { client, exp } => {
                 // Call the thing
-                if let Err(err) = generate::certs(fix_dirs, path, temp_dir, *kind).await {
+                if let Err(err) = generate::enrollment_token(args.node_config, client, *exp) {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -744,17 +1349,17 @@ async fn main() {
                 }
             },
 
-            GenerateSubcommand::Backend { fix_dirs, path, capabilities, disable_hashing, kind } => {
+            GenerateSubcommand::Backend { fix_dirs, path, capabilities, disable_hashing, runtime, rootless, kind } => {
                 // Call the thing
-                if let Err(err) = generate::backend(fix_dirs, path, capabilities, !disable_hashing, *kind) {
+                if let Err(err) = generate::backend(fix_dirs, path, capabilities, !disable_hashing, SandboxConfig { runtime, rootless }, *kind) {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
             },
 
-            GenerateSubcommand::PolicyDatabase { fix_dirs, path, branch } => {
+            GenerateSubcommand::PolicyDatabase { fix_dirs, path, branch, migrations } => {
                 // Call the thing
-                if let Err(err) = generate::policy_database(fix_dirs, path, branch).await {
+                if let Err(err) = generate::policy_database(fix_dirs, path, branch, migrations).await {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -774,6 +1379,14 @@ async fn main() {
                 }
             },
 
+            GenerateSubcommand::Systemd { fix_dirs, path, bin_dir, user } => {
+                // Call the thing
+                if let Err(err) = systemd::generate(args.node_config, fix_dirs, path, bin_dir, user) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
             GenerateSubcommand::Proxy { fix_dirs, path, outgoing_range, incoming, forward, forward_protocol } => {
                 // Call the thing
                 if let Err(err) = generate::proxy(
@@ -781,7 +1394,7 @@ async fn main() {
                     path,
                     outgoing_range.0,
                     incoming.into_iter().map(|p| (p.0, p.1)).collect(),
-                    forward.map(|a| ForwardConfig { address: a, protocol: forward_protocol }),
+                    forward.map(|a| ForwardConfig { address: a, protocol: forward_protocol, credentials: None }),
                 ) {
                     error!("{}", err.trace());
                     std::process::exit(1);
@@ -795,10 +1408,30 @@ async fn main() {
                     std::process::exit(1);
                 }
             },
+            UpgradeSubcommand::Config { path, kind, dry_run, no_backup, from } => {
+                if let Err(err) = migrations::migrate(path, kind, dry_run, !no_backup, from) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+        },
+        CtlSubcommand::Test(subcommand) => match *subcommand {
+            TestSubcommand::Connectivity { infra, timeout } => {
+                if let Err(err) = connectivity::connectivity(args.node_config, infra, *timeout) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
         },
         CtlSubcommand::Unpack(subcommand) => match *subcommand {
-            UnpackSubcommand::Compose { kind, path, fix_dirs } => {
-                if let Err(err) = unpack::compose(kind, fix_dirs, path, args.node_config) {
+            UnpackSubcommand::Compose { kind, path, fix_dirs, monitoring, dashboard } => {
+                if let Err(err) = unpack::compose(kind, fix_dirs, path, args.node_config, monitoring, dashboard) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+            UnpackSubcommand::K8s { path, fix_dirs, helm, registry, version } => {
+                if let Err(err) = unpack::k8s(fix_dirs, path, args.node_config, K8sOpts { version, registry, helm }) {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -806,7 +1439,7 @@ async fn main() {
         },
         CtlSubcommand::Wizard(subcommand) => match *subcommand {
             WizardSubcommand::Setup {} => {
-                if let Err(err) = wizard::setup() {
+                if let Err(err) = wizard::setup().await {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -847,9 +1480,126 @@ async fn main() {
                     std::process::exit(1);
                 }
             },
+
+            PolicySubcommand::Deactivate { address, token } => {
+                // Call the thing
+                if let Err(err) = policies::deactivate(args.node_config, address, token).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            PolicySubcommand::Remove { version, address, token } => {
+                // Call the thing
+                if let Err(err) = policies::remove(args.node_config, version, address, token).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            PolicySubcommand::Show { version, address, token } => {
+                // Call the thing
+                if let Err(err) = policies::show(args.node_config, version, address, token).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            PolicySubcommand::Validate { input, language } => {
+                // Call the thing
+                if let Err(err) = policies::validate(input, language).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            PolicySubcommand::Init { template, path, force } => {
+                // Call the thing
+                if let Err(err) = policies::init(template, path, force).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+
+            PolicySubcommand::Check { dir, reasoner } => {
+                // Call the thing
+                if let Err(err) = policies::check(dir, reasoner).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
         },
 
-        CtlSubcommand::Start { exe, file, docker_socket, docker_version, version, image_dir, local_aux, skip_import, profile_dir, kind } => {
+        CtlSubcommand::Approvals(subcommand) => match *subcommand {
+            ApprovalSubcommand::List { all } => {
+                if let Err(err) = approvals::list(args.node_config, all) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+            ApprovalSubcommand::Approve { id, reason } => {
+                if let Err(err) = approvals::approve(args.node_config, id, reason) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+            ApprovalSubcommand::Deny { id, reason } => {
+                if let Err(err) = approvals::deny(args.node_config, id, reason) {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+        },
+
+        CtlSubcommand::Backup(subcommand) => match *subcommand {
+            BackupSubcommand::Create { output } => {
+                if let Err(err) = backup::create(args.node_config, output).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+            BackupSubcommand::Restore { input, force } => {
+                if let Err(err) = backup::restore(args.node_config, input, force).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+        },
+
+        CtlSubcommand::Bundle(subcommand) => match *subcommand {
+            BundleSubcommand::Export { output, arch, version, content, mirrors, no_verify, branch, socket, client_version } => {
+                if let Err(err) = bundle::export(output, arch, version, content, mirrors, no_verify, branch, socket, client_version).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+            BundleSubcommand::Import { input, fix_dirs, path, policy_reasoner, force } => {
+                if let Err(err) = bundle::import(input, fix_dirs, path, policy_reasoner, force).await {
+                    error!("{}", err.trace());
+                    std::process::exit(1);
+                }
+            },
+        },
+
+        CtlSubcommand::Start { target: DeployTarget::Systemd, .. } => {
+            if let Err(err) = systemd::start(args.node_config) {
+                error!("{}", err.trace());
+                std::process::exit(1);
+            }
+        },
+        CtlSubcommand::Start {
+            exe,
+            file,
+            docker_socket,
+            docker_version,
+            version,
+            image_dir,
+            local_aux,
+            skip_import,
+            profile_dir,
+            target: _,
+            kind,
+        } => {
             if let Err(err) = lifetime::start(
                 exe,
                 file,
@@ -864,13 +1614,33 @@ async fn main() {
                 std::process::exit(1);
             }
         },
-        CtlSubcommand::Stop { exe, file } => {
+        CtlSubcommand::Stop { target: DeployTarget::Systemd, .. } => {
+            if let Err(err) = systemd::stop(args.node_config) {
+                error!("{}", err.trace());
+                std::process::exit(1);
+            }
+        },
+        CtlSubcommand::Stop { exe, file, target: _ } => {
             if let Err(err) = lifetime::stop(args.debug || args.trace, exe, file, args.node_config) {
                 error!("{}", err.trace());
                 std::process::exit(1);
             }
         },
 
+        CtlSubcommand::Logs { docker_socket, docker_version, services, follow, since, tail, grep } => {
+            let opts: LogsOpts = LogsOpts { services, follow, since: since.map(|since| *since), tail, grep };
+            if let Err(err) = logs::logs(args.node_config, DockerOptions { socket: docker_socket, version: docker_version }, opts).await {
+                error!("{}", err.trace());
+                std::process::exit(1);
+            }
+        },
+        CtlSubcommand::Status { docker_socket, docker_version } => {
+            if let Err(err) = status::status(args.node_config, DockerOptions { socket: docker_socket, version: docker_version }).await {
+                error!("{}", err.trace());
+                std::process::exit(1);
+            }
+        },
+
         CtlSubcommand::Version { arch: _, kind: _, ctl: _, node: _ } => {},
     }
 }
@@ -0,0 +1,447 @@
+//  MIGRATIONS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 20:41:03
+//  Last edited:
+//    15 Oct 2026, 17:58:23
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a framework of ordered, versioned migration steps that
+//!   bring config files written for older BRANE versions up-to-date.
+//!
+//!   Contrary to the converters in [`crate::upgrade`], the steps here do
+//!   not parse the file with an old layout but instead operate on its raw
+//!   YAML tree. That way, every step only has to know about the change it
+//!   migrates, and steps for subsequent versions can simply be chained.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use brane_cfg::backend::BackendFile;
+use brane_cfg::info::Info as _;
+use brane_cfg::infra::InfraFile;
+use brane_cfg::node::NodeConfig;
+use brane_cfg::proxy::ProxyConfig;
+use console::style;
+use log::{debug, info, warn};
+use serde_yaml::value::{Tag, TaggedValue};
+use serde_yaml::{Mapping, Value};
+use specifications::version::Version;
+
+pub use crate::errors::MigrateError as Error;
+use crate::spec::{ConfigKind, VersionFix};
+use crate::utils::diff_lines;
+
+
+/***** CONSTANTS *****/
+/// The migration steps known to `branectl`.
+///
+/// Steps are applied in order, so this list must be sorted by version.
+const MIGRATIONS: &[Migration] = &[
+    // node.yml
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(3, 0, 0),
+        description: "Write the node kind as a YAML tag instead of a map key",
+        apply:       node_kind_to_tag,
+    },
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(3, 0, 0),
+        description: "Rename the 'control' node kind to 'central'",
+        apply:       node_control_to_central,
+    },
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(3, 0, 0),
+        description: "Rename deprecated keys (e.g., 'project' to 'namespace')",
+        apply:       node_rename_keys,
+    },
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(4, 0, 0),
+        description: "Replace the worker's 'policies' file with a policy database and its secrets",
+        apply:       node_policy_database,
+    },
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(4, 0, 0),
+        description: "Add the (now mandatory) worker 'usecases' map",
+        apply:       node_usecases,
+    },
+    Migration {
+        kind:        ConfigKind::Node,
+        version:     Version::new(4, 0, 0),
+        description: "Remove the external address of the (now private) checker service",
+        apply:       node_chk_private,
+    },
+    // backend.yml
+    Migration {
+        kind:        ConfigKind::Backend,
+        version:     Version::new(3, 0, 0),
+        description: "Write the connection method as a YAML tag instead of a map key",
+        apply:       backend_method_to_tag,
+    },
+];
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Defines a single migration step for a particular kind of config file.
+struct Migration {
+    /// The kind of config file this step applies to.
+    kind:        ConfigKind,
+    /// The BRANE version that introduced the layout this step migrates to.
+    version:     Version,
+    /// A short, human-readable description of what the step does.
+    description: &'static str,
+    /// The function that performs the step on the raw YAML tree. Returns whether it changed anything.
+    ///
+    /// Steps must be idempotent, i.e., running them on a file that is already migrated should not do anything.
+    apply:       fn(&mut Value) -> bool,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Renames a key in a map, keeping its position.
+///
+/// # Arguments
+/// - `map`: The [`Mapping`] to rename the key in.
+/// - `from`: The old name of the key.
+/// - `to`: The new name of the key.
+///
+/// # Returns
+/// Whether the key was renamed. If `to` already exists, nothing is renamed.
+fn rename_key(map: &mut Mapping, from: &str, to: &str) -> bool {
+    if !map.contains_key(from) || map.contains_key(to) {
+        return false;
+    }
+    let old: Mapping = std::mem::take(map);
+    *map = old.into_iter().map(|(key, value)| if key.as_str() == Some(from) { (Value::from(to), value) } else { (key, value) }).collect();
+    true
+}
+
+/// Converts an externally tagged enum written as a single-key map (`{ kind: ... }`) to a YAML tag (`!kind ...`).
+///
+/// # Arguments
+/// - `value`: The [`Value`] to convert.
+/// - `variants`: The names of the variants we accept as key.
+///
+/// # Returns
+/// Whether the value was converted.
+fn map_to_tag(value: &mut Value, variants: &[&str]) -> bool {
+    let (variant, body): (String, Value) = match value {
+        Value::Mapping(map) if map.len() == 1 => match map.iter().next() {
+            Some((Value::String(variant), body)) if variants.contains(&variant.as_str()) => (variant.clone(), body.clone()),
+            _ => return false,
+        },
+        _ => return false,
+    };
+    *value = Value::Tagged(Box::new(TaggedValue { tag: Tag::new(variant), value: body }));
+    true
+}
+
+/// Returns the kind and body of the node-specific part of a `node.yml` file.
+///
+/// # Arguments
+/// - `root`: The root of the `node.yml` file.
+///
+/// # Returns
+/// The kind of the node (e.g., `worker`) and the map describing it, or [`None`] if the file has no (tagged) node-specific part.
+fn node_body(root: &mut Value) -> Option<(String, &mut Mapping)> {
+    match root.get_mut("node")? {
+        Value::Tagged(tagged) => {
+            let kind: String = tagged.tag.to_string().trim_start_matches('!').into();
+            tagged.value.as_mapping_mut().map(|body| (kind, body))
+        },
+        _ => None,
+    }
+}
+
+
+
+/// Migrates `node: { central: ... }` to `node: !central ...`.
+fn node_kind_to_tag(root: &mut Value) -> bool {
+    match root.get_mut("node") {
+        Some(node) => map_to_tag(node, &["central", "control", "worker", "proxy"]),
+        None => false,
+    }
+}
+
+/// Migrates `node: !control ...` to `node: !central ...`.
+fn node_control_to_central(root: &mut Value) -> bool {
+    match root.get_mut("node") {
+        Some(Value::Tagged(tagged)) if tagged.tag == "control" => {
+            tagged.tag = Tag::new("central");
+            true
+        },
+        _ => false,
+    }
+}
+
+/// Migrates the keys that were renamed when the services got their three-letter identifiers.
+fn node_rename_keys(root: &mut Value) -> bool {
+    let mut changed: bool = false;
+    if let Some(map) = root.as_mapping_mut() {
+        changed |= rename_key(map, "project", "namespace");
+    }
+
+    let Some((kind, body)) = node_body(root) else { return changed };
+    let services: &[(&str, &str)] = match kind.as_str() {
+        "central" => &[("registry", "api"), ("driver", "drv"), ("planner", "plr"), ("proxy", "prx"), ("scylla", "aux_scylla")],
+        "worker" => {
+            changed |= rename_key(body, "location_id", "name");
+            changed |= rename_key(body, "use_cases", "usecases");
+            if let Some(paths) = body.get_mut("paths").and_then(Value::as_mapping_mut) {
+                changed |= rename_key(paths, "policy_db", "policy_database");
+            }
+            if let Some(usecases) = body.get_mut("usecases").and_then(Value::as_mapping_mut) {
+                for (_, usecase) in usecases.iter_mut() {
+                    if let Some(usecase) = usecase.as_mapping_mut() {
+                        changed |= rename_key(usecase, "registry", "api");
+                    }
+                }
+            }
+            &[("registry", "reg"), ("delegate", "job"), ("checker", "chk"), ("proxy", "prx")]
+        },
+        "proxy" => &[("proxy", "prx")],
+        _ => &[],
+    };
+    if let Some(svcs) = body.get_mut("services").and_then(Value::as_mapping_mut) {
+        for (from, to) in services {
+            changed |= rename_key(svcs, from, to);
+        }
+    }
+    changed
+}
+
+/// Migrates the worker's `paths.policies` file to a `policies.db` database and the secrets used to access it.
+fn node_policy_database(root: &mut Value) -> bool {
+    let Some((kind, body)) = node_body(root) else { return false };
+    if kind != "worker" {
+        return false;
+    }
+    let Some(paths) = body.get_mut("paths").and_then(Value::as_mapping_mut) else { return false };
+    let Some(policies) = paths.get("policies").and_then(Value::as_str).map(PathBuf::from) else { return false };
+
+    // Put the new files next to the old one
+    let dir: &Path = policies.parent().unwrap_or_else(|| Path::new("."));
+    if paths.contains_key("policy_database") {
+        paths.shift_remove("policies");
+    } else {
+        rename_key(paths, "policies", "policy_database");
+        paths.insert("policy_database".into(), dir.join("policies.db").display().to_string().into());
+    }
+    for (key, file) in [("policy_deliberation_secret", "policy_deliberation_secret.json"), ("policy_expert_secret", "policy_expert_secret.json")] {
+        if !paths.contains_key(key) {
+            paths.insert(key.into(), dir.join(file).display().to_string().into());
+        }
+    }
+
+    warn!(
+        "Policies are no longer read from '{}' but from a policy database; run 'branectl generate policy_database' and 'branectl generate \
+         policy_secret' to create it and its secrets",
+        policies.display()
+    );
+    true
+}
+
+/// Migrates worker nodes without `usecases` to ones with an empty map.
+fn node_usecases(root: &mut Value) -> bool {
+    let Some((kind, body)) = node_body(root) else { return false };
+    if kind != "worker" || body.contains_key("usecases") {
+        return false;
+    }
+    body.insert("usecases".into(), Value::Mapping(Mapping::new()));
+    warn!("Worker node has no use-cases defined; add the registries of the use-cases this worker participates in to 'usecases'");
+    true
+}
+
+/// Migrates the checker service from a public service to a private one.
+fn node_chk_private(root: &mut Value) -> bool {
+    let Some((kind, body)) = node_body(root) else { return false };
+    if kind != "worker" {
+        return false;
+    }
+    match body.get_mut("services").and_then(|svcs| svcs.get_mut("chk")).and_then(Value::as_mapping_mut) {
+        Some(chk) => chk.shift_remove("external_address").is_some(),
+        None => false,
+    }
+}
+
+
+
+/// Migrates `method: { local: ... }` to `method: !local ...`.
+fn backend_method_to_tag(root: &mut Value) -> bool {
+    match root.get_mut("method") {
+        Some(method) => map_to_tag(method, &["local", "ssh", "slurm", "kubernetes"]),
+        None => false,
+    }
+}
+
+
+
+/// Checks whether the given raw file is a valid file of the given kind.
+///
+/// # Arguments
+/// - `kind`: The [`ConfigKind`] to validate as.
+/// - `raw`: The raw contents of the file.
+///
+/// # Errors
+/// This function errors if the file failed to parse as its kind.
+fn validate(kind: ConfigKind, raw: &str) -> Result<(), brane_cfg::info::YamlError> {
+    match kind {
+        ConfigKind::Node => NodeConfig::from_string(raw).map(|_| ()),
+        ConfigKind::Infra => InfraFile::from_string(raw).map(|_| ()),
+        ConfigKind::Backend => BackendFile::from_string(raw).map(|_| ()),
+        ConfigKind::Proxy => ProxyConfig::from_string(raw).map(|_| ()),
+    }
+}
+
+/// Migrates a single file.
+///
+/// # Arguments
+/// - `path`: The path of the file to migrate.
+/// - `kind`: The [`ConfigKind`] of the file.
+/// - `dry_run`: Whether to only print the changes instead of writing them.
+/// - `backup`: Whether to back up the original file before overwriting it.
+/// - `from`: If given, only runs the steps for versions newer than this one.
+///
+/// # Errors
+/// This function errors if we failed to read, parse, validate or write the file.
+fn migrate_file(path: &Path, kind: ConfigKind, dry_run: bool, backup: bool, from: Option<Version>) -> Result<(), Error> {
+    debug!("Migrating '{}' as a {}.yml file...", path.display(), kind);
+
+    // Read the file as a raw tree
+    let raw: String = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) => return Err(Error::FileReadError { path: path.into(), err }),
+    };
+    let mut tree: Value = match serde_yaml::from_str(&raw) {
+        Ok(tree) => tree,
+        Err(err) => return Err(Error::FileParseError { path: path.into(), err }),
+    };
+
+    // Run the steps
+    let mut applied: Vec<&Migration> = vec![];
+    for step in MIGRATIONS.iter().filter(|step| step.kind == kind && from.map(|from| step.version > from).unwrap_or(true)) {
+        debug!("Running v{} step '{}'...", step.version, step.description);
+        if (step.apply)(&mut tree) {
+            applied.push(step);
+        }
+    }
+    if applied.is_empty() {
+        println!("File {} is up-to-date", style(path.display()).bold().green());
+        if let Err(err) = validate(kind, &raw) {
+            warn!("{}", Error::ValidateError { path: path.into(), kind, err });
+        }
+        return Ok(());
+    }
+
+    // Serialize and check the result
+    let new: String = match serde_yaml::to_string(&tree) {
+        Ok(new) => new,
+        Err(err) => return Err(Error::SerializeError { path: path.into(), err }),
+    };
+    if let Err(err) = validate(kind, &new) {
+        return Err(Error::ValidateError { path: path.into(), kind, err });
+    }
+
+    // Report what we (would) do
+    println!("{} {} {}.yml file {}:", if dry_run { "Would migrate" } else { "Migrating" }, applied.len(), kind, style(path.display()).bold().green());
+    for step in &applied {
+        println!("  - {} {}", style(format!("v{}", step.version)).bold(), step.description);
+    }
+    if dry_run {
+        println!();
+        print!("{}", diff_lines(&raw, &new, 3));
+        println!();
+        return Ok(());
+    }
+
+    // Back up the original (which also keeps its comments around) and write the new one
+    if backup {
+        let stamp: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let backup: PathBuf = path.with_file_name(format!("{}.{}.bak", path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(), stamp));
+        debug!("Backing up '{}' to '{}'...", path.display(), backup.display());
+        if let Err(err) = fs::copy(path, &backup) {
+            return Err(Error::BackupError { path: path.into(), backup, err });
+        }
+        println!("  (original backed up to {})", style(backup.display()).bold());
+    }
+    if let Err(err) = fs::write(path, new) {
+        return Err(Error::FileWriteError { path: path.into(), err });
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Migrates the config files at the given path to the layout of this BRANE version.
+///
+/// Note that comments in the files are not preserved; use the backups to recover them.
+///
+/// # Arguments
+/// - `path`: The file to migrate, or a directory of which all `node.yml`, `infra.yml`, `backend.yml` and `proxy.yml` files are migrated.
+/// - `kind`: If given, treats a file as this kind of config file (or, for a directory, only considers files of this kind).
+/// - `dry_run`: Whether to only print the changes instead of writing them.
+/// - `backup`: Whether to back up every file before overwriting it.
+/// - `from`: If not `all`, assumes the files are already up-to-date with this version and only runs the steps for newer ones.
+///
+/// # Errors
+/// This function errors if the path does not exist or if we failed to migrate any of the files.
+pub fn migrate(path: impl Into<PathBuf>, kind: Option<ConfigKind>, dry_run: bool, backup: bool, from: VersionFix) -> Result<(), Error> {
+    let path: PathBuf = path.into();
+    info!("Migrating config files in '{}'...", path.display());
+
+    // Collect the files to migrate
+    let mut files: Vec<(PathBuf, ConfigKind)> = vec![];
+    if path.is_file() {
+        match kind.or_else(|| ConfigKind::from_path(&path)) {
+            Some(kind) => files.push((path, kind)),
+            None => return Err(Error::UnknownKind { path }),
+        }
+    } else if path.is_dir() {
+        let entries: fs::ReadDir = match fs::read_dir(&path) {
+            Ok(entries) => entries,
+            Err(err) => return Err(Error::DirReadError { path, err }),
+        };
+        for entry in entries {
+            let entry: PathBuf = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => return Err(Error::DirReadError { path, err }),
+            };
+            if let Some(entry_kind) = ConfigKind::from_path(&entry) {
+                if entry.is_file() && kind.map(|kind| kind == entry_kind).unwrap_or(true) {
+                    files.push((entry, entry_kind));
+                }
+            }
+        }
+        files.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    } else {
+        return Err(Error::PathNotFound { path });
+    }
+    if files.is_empty() {
+        println!("No config files found in {}", style(path.display()).bold());
+        return Ok(());
+    }
+
+    // Migrate them one-by-one
+    for (file, kind) in files {
+        migrate_file(&file, kind, dry_run, backup, from.0)?;
+    }
+    Ok(())
+}
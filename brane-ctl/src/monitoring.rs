@@ -0,0 +1,298 @@
+//  MONITORING.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 17:51:37
+//  Last edited:
+//    15 Oct 2026, 18:26:27
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the optional monitoring stack (Prometheus + Grafana) that
+//!   can be deployed alongside a node.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, PrivateOrExternalService};
+use log::debug;
+
+pub use crate::errors::MonitoringError as Error;
+
+
+/***** CONSTANTS *****/
+/// The port on which `brane-job` serves its metrics (see its `--metrics-address`), as it cannot serve them on its gRPC port.
+const JOB_METRICS_PORT: u16 = 9102;
+
+/// The Docker Compose services that make up the monitoring stack.
+///
+/// These are inserted in the node's Compose file by [`add_to_compose()`].
+const COMPOSE_SERVICES: &str = r#"
+  ################
+  ## MONITORING ##
+  ################
+
+  # Scrapes the metrics endpoints of the services on this node
+  aux-prometheus:
+    image: prom/prometheus:v2.47.2
+    container_name: ${PROMETHEUS_NAME:-aux-prometheus}
+    restart: always
+    command:
+    - --config.file=/etc/prometheus/prometheus.yml
+    - --storage.tsdb.retention.time=${PROMETHEUS_RETENTION:-15d}
+    ports:
+    - "127.0.0.1:${PROMETHEUS_PORT:-9090}:9090"
+    volumes:
+    - ${MONITORING_DIR:-./config/monitoring}/prometheus.yml:/etc/prometheus/prometheus.yml:ro
+    - prometheus-data:/prometheus
+
+  # Visualizes the scraped metrics using pre-provisioned dashboards
+  aux-grafana:
+    image: grafana/grafana:10.2.0
+    container_name: ${GRAFANA_NAME:-aux-grafana}
+    restart: always
+    environment:
+      GF_SECURITY_ADMIN_PASSWORD: ${GRAFANA_ADMIN_PASSWORD:-admin}
+      GF_USERS_ALLOW_SIGN_UP: "false"
+    ports:
+    - "0.0.0.0:${GRAFANA_PORT:-3000}:3000"
+    volumes:
+    - ${MONITORING_DIR:-./config/monitoring}/grafana/provisioning:/etc/grafana/provisioning:ro
+    - ${MONITORING_DIR:-./config/monitoring}/grafana/dashboards:/var/lib/grafana/dashboards:ro
+    - grafana-data:/var/lib/grafana
+    depends_on:
+    - aux-prometheus
+"#;
+
+/// The Docker Compose volumes used by the monitoring stack.
+const COMPOSE_VOLUMES: &str = "volumes:\n  prometheus-data:\n  grafana-data:\n";
+
+/// The Grafana datasource provisioning file, pointing to the Prometheus service.
+const GRAFANA_DATASOURCE: &str = r#"apiVersion: 1
+
+datasources:
+- name: Prometheus
+  type: prometheus
+  access: proxy
+  url: http://aux-prometheus:9090
+  isDefault: true
+"#;
+
+/// The Grafana dashboard provisioning file, loading every dashboard in the dashboards directory.
+const GRAFANA_DASHBOARD_PROVIDER: &str = r#"apiVersion: 1
+
+providers:
+- name: Brane
+  folder: Brane
+  type: file
+  disableDeletion: true
+  options:
+    path: /var/lib/grafana/dashboards
+"#;
+
+/// The pre-provisioned Grafana dashboard giving an overview of the node's services.
+const GRAFANA_DASHBOARD: &str = r#"{
+  "title": "Brane node overview",
+  "uid": "brane-node",
+  "schemaVersion": 38,
+  "refresh": "30s",
+  "time": { "from": "now-6h", "to": "now" },
+  "panels": [
+    {
+      "type": "stat",
+      "title": "Services up",
+      "gridPos": { "x": 0, "y": 0, "w": 24, "h": 4 },
+      "targets": [{ "expr": "up{brane_node!=\"\"}", "legendFormat": "{{service}}" }]
+    },
+    {
+      "type": "timeseries",
+      "title": "Requests per second",
+      "gridPos": { "x": 0, "y": 4, "w": 12, "h": 8 },
+      "targets": [{ "expr": "sum by (service) (rate(brane_requests_total[5m]))", "legendFormat": "{{service}}" }]
+    },
+    {
+      "type": "timeseries",
+      "title": "Request latency (p95)",
+      "gridPos": { "x": 12, "y": 4, "w": 12, "h": 8 },
+      "targets": [{ "expr": "histogram_quantile(0.95, sum by (service, le) (rate(brane_request_duration_seconds_bucket[5m])))", "legendFormat": "{{service}}" }]
+    },
+    {
+      "type": "timeseries",
+      "title": "Resident memory",
+      "gridPos": { "x": 0, "y": 12, "w": 12, "h": 8 },
+      "targets": [{ "expr": "process_resident_memory_bytes", "legendFormat": "{{service}}" }]
+    },
+    {
+      "type": "timeseries",
+      "title": "CPU usage",
+      "gridPos": { "x": 12, "y": 12, "w": 12, "h": 8 },
+      "targets": [{ "expr": "rate(process_cpu_seconds_total[5m])", "legendFormat": "{{service}}" }]
+    },
+    {
+      "type": "timeseries",
+      "title": "Worker tasks",
+      "gridPos": { "x": 0, "y": 20, "w": 12, "h": 8 },
+      "targets": [
+        { "expr": "brane_job_tasks_running", "legendFormat": "running" },
+        { "expr": "brane_job_tasks_queued", "legendFormat": "queued" }
+      ]
+    },
+    {
+      "type": "timeseries",
+      "title": "Worker data transfers",
+      "gridPos": { "x": 12, "y": 20, "w": 12, "h": 8 },
+      "targets": [{ "expr": "sum by (kind) (rate(brane_job_transfer_bytes_total[5m]))", "legendFormat": "{{kind}}" }]
+    }
+  ]
+}
+"#;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Collects the services that Prometheus should scrape for the node described by the given node config.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// A list of pairs of the short service identifier (e.g., `api`) and the `<container>:<port>` to scrape.
+fn scrape_targets(node_config: &NodeConfig) -> Vec<(&'static str, String)> {
+    // Only scrape the proxy if we host it ourselves
+    let prx = |prx: &PrivateOrExternalService| -> Option<(&'static str, String)> {
+        if let PrivateOrExternalService::Private(prx) = prx { Some(("prx", format!("{}:{}", prx.name, prx.bind.port()))) } else { None }
+    };
+
+    match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            let svcs = &central.services;
+            let mut res: Vec<(&'static str, String)> = vec![
+                ("api", format!("{}:{}", svcs.api.name, svcs.api.bind.port())),
+                ("drv", format!("{}:{}", svcs.drv.name, svcs.drv.bind.port())),
+                ("plr", format!("{}:{}", svcs.plr.name, svcs.plr.bind.port())),
+            ];
+            res.extend(prx(&svcs.prx));
+            res
+        },
+
+        NodeSpecificConfig::Worker(worker) => {
+            let svcs = &worker.services;
+            let mut res: Vec<(&'static str, String)> = vec![
+                ("reg", format!("{}:{}", svcs.reg.name, svcs.reg.bind.port())),
+                ("job", format!("{}:{}", svcs.job.name, JOB_METRICS_PORT)),
+                ("chk", format!("{}:{}", svcs.chk.name, svcs.chk.bind.port())),
+            ];
+            res.extend(prx(&svcs.prx));
+            res
+        },
+
+        NodeSpecificConfig::Proxy(proxy) => vec![("prx", format!("{}:{}", proxy.services.prx.name, proxy.services.prx.bind.port()))],
+    }
+}
+
+/// Writes the given contents to the given file, creating its parent directory if necessary.
+///
+/// # Arguments
+/// - `what`: A description of the file for use in errors.
+/// - `path`: The path of the file to write.
+/// - `contents`: The contents to write.
+///
+/// # Errors
+/// This function errors if we failed to create the parent directory or write the file.
+fn write_file(what: &'static str, path: PathBuf, contents: impl AsRef<[u8]>) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            if let Err(err) = fs::create_dir_all(parent) {
+                return Err(Error::DirCreateError { path: parent.into(), err });
+            }
+        }
+    }
+    debug!("Writing {what} to '{}'...", path.display());
+    if let Err(err) = fs::write(&path, contents) {
+        return Err(Error::FileWriteError { what, path, err });
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates the Prometheus configuration that scrapes every service hosted on the given node.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// The contents of a `prometheus.yml` file.
+pub fn prometheus_config(node_config: &NodeConfig) -> String {
+    let node: String = match &node_config.node {
+        NodeSpecificConfig::Central(_) => "central".into(),
+        NodeSpecificConfig::Worker(worker) => worker.name.clone(),
+        NodeSpecificConfig::Proxy(_) => "proxy".into(),
+    };
+
+    let mut res: String = String::from("global:\n  scrape_interval: 15s\n  evaluation_interval: 15s\n\nscrape_configs:\n");
+    for (id, target) in scrape_targets(node_config) {
+        res.push_str(&format!(
+            "- job_name: brane-{id}\n  metrics_path: /metrics\n  static_configs:\n  - targets: ['{target}']\n    labels:\n      service: {id}\n      \
+             brane_node: {node}\n"
+        ));
+    }
+    res
+}
+
+/// Inserts the monitoring stack services in the given Docker Compose file.
+///
+/// # Arguments
+/// - `compose`: The contents of the Docker Compose file to extend.
+///
+/// # Returns
+/// The contents of the Docker Compose file with the Prometheus and Grafana services (and their volumes) added.
+pub fn add_to_compose(compose: &str) -> String {
+    // Insert the services right before the toplevel `networks`-section, or at the end if there is none
+    let (services, rest): (&str, &str) = match compose.find("\nnetworks:") {
+        Some(pos) => compose.split_at(pos + 1),
+        None => (compose, ""),
+    };
+    let mut res: String = String::with_capacity(compose.len() + COMPOSE_SERVICES.len() + COMPOSE_VOLUMES.len() + 2);
+    res.push_str(services.trim_end_matches('\n'));
+    res.push('\n');
+    res.push_str(COMPOSE_SERVICES);
+    res.push('\n');
+    res.push_str(rest);
+    if !res.ends_with('\n') {
+        res.push('\n');
+    }
+    res.push('\n');
+    res.push_str(COMPOSE_VOLUMES);
+    res
+}
+
+/// Writes the Prometheus and Grafana configuration for the given node to the given directory.
+///
+/// The resulting directory can be mounted by the services inserted by [`add_to_compose()`] (see `MONITORING_DIR`).
+///
+/// # Arguments
+/// - `dir`: The directory to write the configuration to. Will be created if it does not exist.
+/// - `node_config`: The [`NodeConfig`] describing the node to monitor.
+///
+/// # Errors
+/// This function errors if we failed to create any directory or write any file.
+pub fn write_config(dir: impl AsRef<Path>, node_config: &NodeConfig) -> Result<(), Error> {
+    let dir: &Path = dir.as_ref();
+    write_file("Prometheus config", dir.join("prometheus.yml"), prometheus_config(node_config))?;
+    write_file("Grafana datasource", dir.join("grafana").join("provisioning").join("datasources").join("prometheus.yml"), GRAFANA_DATASOURCE)?;
+    write_file(
+        "Grafana dashboard provider",
+        dir.join("grafana").join("provisioning").join("dashboards").join("brane.yml"),
+        GRAFANA_DASHBOARD_PROVIDER,
+    )?;
+    write_file("Grafana dashboard", dir.join("grafana").join("dashboards").join("brane-node.json"), GRAFANA_DASHBOARD)?;
+    Ok(())
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    06 Dec 2022, 11:57:11
 //  Last edited:
-//    10 Mar 2023, 16:49:17
+//    16 Oct 2026, 02:39:20
 //  Auto updated?
 //    Yes
 //
@@ -126,7 +126,7 @@ pub async fn hash(node_config_path: impl Into<PathBuf>, image: impl Into<String>
                 // Only write it if the version makes sense
                 if version.is_latest() {
                     // Check if it's 'latest' too or the highest
-                    if eversion.is_latest() || file.is_none() || eversion > file.as_ref().unwrap().1 {
+                    if eversion.is_latest() || file.is_none() || eversion.cmp_release(&file.as_ref().unwrap().1).is_gt() {
                         let is_latest: bool = eversion.is_latest();
                         file = Some((entry.path(), eversion));
                         if is_latest {
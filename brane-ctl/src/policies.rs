@@ -4,7 +4,7 @@
 //  Created:
 //    10 Jan 2024, 15:57:54
 //  Last edited:
-//    24 Jun 2024, 17:40:43
+//    16 Oct 2026, 01:52:35
 //  Auto updated?
 //    Yes
 //
@@ -20,6 +20,7 @@ use std::time::Duration;
 
 use brane_cfg::info::Info;
 use brane_cfg::node::{NodeConfig, NodeSpecificConfig, WorkerConfig};
+use brane_cfg::secrets::SecretRef;
 use brane_shr::formatters::BlockFormatter;
 use console::style;
 use dialoguer::theme::ColorfulTheme;
@@ -31,14 +32,41 @@ use rand::distributions::Alphanumeric;
 use rand::Rng;
 use reqwest::{Client, Request, Response, StatusCode};
 use serde_json::value::RawValue;
+use serde_json::{json, Value};
 use specifications::address::{Address, AddressOpt};
 use specifications::checking::{
-    POLICY_API_ADD_VERSION, POLICY_API_GET_ACTIVE_VERSION, POLICY_API_GET_VERSION, POLICY_API_LIST_POLICIES, POLICY_API_SET_ACTIVE_VERSION,
+    POLICY_API_ADD_VERSION, POLICY_API_DEACTIVATE_VERSION, POLICY_API_GET_ACTIVE_VERSION, POLICY_API_GET_VERSION, POLICY_API_LIST_POLICIES,
+    POLICY_API_REMOVE_VERSION, POLICY_API_SET_ACTIVE_VERSION,
 };
 use srv::models::{AddPolicyPostModel, PolicyContentPostModel, SetVersionPostModel};
 use tokio::fs::{self as tfs, File as TFile};
 
-use crate::spec::PolicyInputLanguage;
+use crate::spec::{PolicyInputLanguage, PolicyTemplate};
+use crate::utils::diff_lines;
+
+
+/***** CONSTANTS *****/
+/// The version of the eFLINT JSON specification that we talk to a local reasoner.
+const EFLINT_JSON_VERSION: &str = "0.1.0";
+
+/// The eFLINT file declaring the facts that Brane states about workflows, required by every template.
+const TEMPLATE_INTERFACE: &str = include_str!("../policies/interface.eflint");
+/// The policy of the [`PolicyTemplate::AllowAll`] template.
+const TEMPLATE_ALLOW_ALL: &str = include_str!("../policies/allow-all.eflint");
+/// The policy of the [`PolicyTemplate::DatasetAllowlist`] template.
+const TEMPLATE_DATASET_ALLOWLIST: &str = include_str!("../policies/dataset-allowlist.eflint");
+/// The policy of the [`PolicyTemplate::PurposeBinding`] template.
+const TEMPLATE_PURPOSE_BINDING: &str = include_str!("../policies/purpose-binding.eflint");
+
+/// A sample workflow that every template allows.
+const SAMPLE_EXAMPLE: &str = include_str!("../policies/samples/example.eflint");
+/// A sample workflow that uses a dataset not on the allowlist of [`PolicyTemplate::DatasetAllowlist`].
+const SAMPLE_UNLISTED: &str = include_str!("../policies/samples/unlisted.eflint");
+/// A sample workflow with a purpose that [`PolicyTemplate::PurposeBinding`] does not allow.
+const SAMPLE_WRONG_PURPOSE: &str = include_str!("../policies/samples/wrong-purpose.eflint");
+
+
+
 
 
 /***** ERRORS *****/
@@ -47,6 +75,14 @@ use crate::spec::PolicyInputLanguage;
 pub enum Error {
     /// Failed to get the active version of the policy.
     ActiveVersionGet { addr: Address, err: Box<Self> },
+    /// Some samples of a policy set did not get the verdict they expected.
+    ChecksFailed { dir: PathBuf, failed: usize, total: usize },
+    /// Failed to create a directory of a new policy set.
+    DirCreate { path: PathBuf, err: std::io::Error },
+    /// A file of a new policy set already exists.
+    FileExists { path: PathBuf },
+    /// Failed to write a file of a new policy set.
+    FileWrite { path: PathBuf, err: std::io::Error },
     /// Failed to deserialize the read input file as JSON.
     InputDeserialize { path: PathBuf, raw: String, err: serde_json::Error },
     /// Failed to read the input file.
@@ -57,6 +93,10 @@ pub enum Error {
     InvalidPolicyActivated { addr: Address, got: Option<i64>, expected: Option<i64> },
     /// A policy language was attempted to derive from a path without extension.
     MissingExtension { path: PathBuf },
+    /// A compiled policy or sample did not have any phrases.
+    MissingPhrases { path: PathBuf },
+    /// A policy set did not have a policy file.
+    MissingPolicy { dir: PathBuf },
     /// The given node config file was not a worker config file.
     NodeConfigIncompatible { path: PathBuf, got: String },
     /// Failed to load the node configuration file for this node.
@@ -65,6 +105,8 @@ pub enum Error {
     PolicyWithoutVersion { addr: Address, which: String },
     /// Failed to prompt the user for version selection.
     PromptVersions { err: Box<Self> },
+    /// Attempted to remove the active policy.
+    RemoveActive { addr: Address, version: i64 },
     /// Failed to build a request.
     RequestBuild { kind: &'static str, addr: String, err: reqwest::Error },
     /// A request failed for some reason.
@@ -75,6 +117,8 @@ pub enum Error {
     ResponseDeserialize { addr: String, raw: String, err: serde_json::Error },
     /// Failed to download the body of the checker's response.
     ResponseDownload { addr: String, err: reqwest::Error },
+    /// Failed to read the samples directory of a policy set.
+    SamplesDirRead { path: PathBuf, err: std::io::Error },
     /// Failed to create a temporary file.
     TempFileCreate { path: PathBuf, err: std::io::Error },
     /// Failed to write to a temporary file from stdin.
@@ -97,6 +141,10 @@ impl Display for Error {
         use Error::*;
         match self {
             ActiveVersionGet { addr, .. } => write!(f, "Failed to get active version of checker '{addr}'"),
+            ChecksFailed { dir, failed, total } => write!(f, "{} out of {} sample(s) of policy set '{}' failed", failed, total, dir.display()),
+            DirCreate { path, .. } => write!(f, "Failed to create directory '{}'", path.display()),
+            FileExists { path } => write!(f, "File '{}' already exists (give '--force' to overwrite it)", path.display()),
+            FileWrite { path, .. } => write!(f, "Failed to write file '{}'", path.display()),
             InputDeserialize { path, raw, .. } => {
                 write!(f, "Failed to deserialize contents of '{}' to JSON\n\nRaw value:\n{}\n", path.display(), BlockFormatter::new(raw))
             },
@@ -112,12 +160,19 @@ impl Display for Error {
             MissingExtension { path } => {
                 write!(f, "Cannot derive input language from '{}' that has no extension; manually specify it using '--language'", path.display())
             },
+            MissingPhrases { path } => write!(f, "Compiled eFLINT JSON of '{}' does not have any phrases", path.display()),
+            MissingPolicy { dir } => write!(f, "Policy set '{}' does not have a 'policy.eflint' or 'policy.json' file", dir.display()),
             NodeConfigIncompatible { path, got } => {
                 write!(f, "Given node configuration file '{}' is for a {} node, but expected a Worker node", path.display(), got)
             },
             NodeConfigLoad { path, .. } => write!(f, "Failed to load node configuration file '{}'", path.display()),
             PolicyWithoutVersion { addr, which } => write!(f, "{which} policy return by checker '{addr}' has no version number set"),
             PromptVersions { .. } => write!(f, "Failed to prompt the user (you!) to select a version"),
+            RemoveActive { addr, version } => write!(
+                f,
+                "Cannot remove policy '{version}' from checker '{addr}' because it is the active policy; deactivate it first using 'branectl \
+                 policies deactivate'"
+            ),
             RequestBuild { kind, addr, .. } => write!(f, "Failed to build new {kind}-request to '{addr}'"),
             RequestFailure { addr, code, response } => write!(
                 f,
@@ -132,6 +187,7 @@ impl Display for Error {
                 write!(f, "Failed to deserialize response from '{}' as JSON\n\nResponse:\n{}\n", addr, BlockFormatter::new(raw))
             },
             ResponseDownload { addr, .. } => write!(f, "Failed to download response from '{addr}'"),
+            SamplesDirRead { path, .. } => write!(f, "Failed to read samples directory '{}'", path.display()),
             TempFileCreate { path, .. } => write!(f, "Failed to create temporary file '{}'", path.display()),
             TempFileWrite { path, .. } => write!(f, "Failed to copy stdin to temporary file '{}'", path.display()),
             TokenGenerate { secret, .. } => write!(
@@ -157,20 +213,28 @@ impl error::Error for Error {
         use Error::*;
         match self {
             ActiveVersionGet { err, .. } => Some(&**err),
+            ChecksFailed { .. } => None,
+            DirCreate { err, .. } => Some(err),
+            FileExists { .. } => None,
+            FileWrite { err, .. } => Some(err),
             InputDeserialize { err, .. } => Some(err),
             InputRead { err, .. } => Some(err),
             InputToJson { err, .. } => Some(err),
             InvalidPolicyActivated { .. } => None,
             MissingExtension { .. } => None,
+            MissingPhrases { .. } => None,
+            MissingPolicy { .. } => None,
             NodeConfigIncompatible { .. } => None,
             NodeConfigLoad { err, .. } => Some(err),
             PolicyWithoutVersion { .. } => None,
             PromptVersions { err } => Some(err),
+            RemoveActive { .. } => None,
             RequestBuild { err, .. } => Some(err),
             RequestFailure { .. } => None,
             RequestSend { err, .. } => Some(err),
             ResponseDeserialize { err, .. } => Some(err),
             ResponseDownload { err, .. } => Some(err),
+            SamplesDirRead { err, .. } => Some(err),
             TempFileCreate { err, .. } => Some(err),
             TempFileWrite { err, .. } => Some(err),
             TokenGenerate { err, .. } => Some(err),
@@ -219,7 +283,8 @@ fn resolve_worker_config(node_config_path: impl AsRef<Path>, worker: Option<Work
 
 /// Resolves a token by either using the given one or generating a new one.
 ///
-/// When generating a new one, the token in the given [`WorkerConfig`] is used. This, too, will be resolved in that case.
+/// When none is given, the `policy_expert_token` in the given [`WorkerConfig`] is used, or else a new one is generated from its expert secret.
+/// The [`WorkerConfig`], too, will be resolved in that case.
 ///
 /// # Arguments
 /// - `node_config_path`: The path to load the worker config from if `worker_config` if [`None`].
@@ -239,6 +304,14 @@ fn resolve_token(node_config_path: impl AsRef<Path>, worker: &mut Option<WorkerC
         // Resolve the worker
         let worker_cfg: WorkerConfig = resolve_worker_config(&node_config_path, worker.take())?;
 
+        // Use the configured token if there is any
+        if let Some(token) = worker_cfg.policy_expert_token.as_ref().and_then(SecretRef::loaded) {
+            debug!("Using token from node config file");
+            let token: String = token.into();
+            *worker = Some(worker_cfg);
+            return Ok(token);
+        }
+
         // Attempt to generate a new token based on the secret in the `node.yml` file
         match specifications::policy::generate_policy_token(
             names::three::lowercase::rand(),
@@ -272,7 +345,7 @@ fn resolve_token(node_config_path: impl AsRef<Path>, worker: &mut Option<WorkerC
 /// This function may error if we have to load a new worker config but fail to do so.
 fn resolve_addr_opt(node_config_path: impl AsRef<Path>, worker: &mut Option<WorkerConfig>, mut address: AddressOpt) -> Result<Address, Error> {
     // Resolve the address port if needed
-    if address.port().is_none() {
+    if address.port().is_none() && !address.is_unix() {
         // Resolve the worker and store the port of the checker
         let worker_cfg: WorkerConfig = resolve_worker_config(&node_config_path, worker.take())?;
         *address.port_mut() = Some(worker_cfg.services.chk.address.port());
@@ -434,6 +507,163 @@ async fn get_active_version_on_checker(address: &Address, token: &str) -> Result
     }
 }
 
+/// Resolves the policy input given by the user to a path to read from.
+///
+/// # Arguments
+/// - `input`: The input given by the user. This is a path, or `-` to read from stdin.
+///
+/// # Returns
+/// A tuple of the path to read the input from and whether it was written there from stdin.
+///
+/// # Errors
+/// This function may error if we failed to copy stdin to a temporary file.
+async fn resolve_input(input: String) -> Result<(PathBuf, bool), Error> {
+    if input == "-" {
+        // Create a temporary file to write stdin to
+        let id: String = rand::thread_rng().sample_iter(Alphanumeric).take(4).map(char::from).collect::<String>();
+        let temp_path: PathBuf = std::env::temp_dir().join(format!("branectl-stdin-{id}.txt"));
+        debug!("Writing stdin to temporary file '{}'...", temp_path.display());
+        let mut temp: TFile = match TFile::create(&temp_path).await {
+            Ok(temp) => temp,
+            Err(err) => return Err(Error::TempFileCreate { path: temp_path, err }),
+        };
+
+        // Perform the write
+        if let Err(err) = tokio::io::copy(&mut tokio::io::stdin(), &mut temp).await {
+            return Err(Error::TempFileWrite { path: temp_path, err });
+        }
+
+        // Done
+        Ok((temp_path, true))
+    } else {
+        Ok((input.into(), false))
+    }
+}
+
+/// Reads a policy input file and compiles it to eFLINT JSON if necessary.
+///
+/// # Arguments
+/// - `input`: The path to the input file.
+/// - `from_stdin`: Whether the input file was written from stdin (used for error messages only).
+/// - `language`: The language of the input. If omitted, it is derived from the file's extension.
+///
+/// # Returns
+/// The policy as raw JSON, together with the [`TargetReasoner`] it is meant for.
+///
+/// # Errors
+/// This function may error if we failed to derive the language, read the input or compile it. Note that this effectively makes this
+/// function a syntax check of the input.
+async fn read_input(input: &Path, from_stdin: bool, language: Option<PolicyInputLanguage>) -> Result<(Box<RawValue>, TargetReasoner), Error> {
+    // If the language is not given, resolve it from the file extension
+    let language: PolicyInputLanguage = if let Some(language) = language {
+        debug!("Interpreting input as {language}");
+        language
+    } else if let Some(ext) = input.extension() {
+        debug!("Attempting to derive input language from extension '{}' (part of '{}')", ext.to_string_lossy(), input.display());
+
+        // Else, attempt to resolve from the extension
+        if ext == OsStr::new("eflint") {
+            PolicyInputLanguage::EFlint
+        } else if ext == OsStr::new("json") {
+            PolicyInputLanguage::EFlintJson
+        } else if from_stdin {
+            return Err(Error::UnspecifiedInputLanguage);
+        } else {
+            let ext: String = ext.to_string_lossy().into();
+            return Err(Error::UnknownExtension { path: input.into(), ext });
+        }
+    } else if from_stdin {
+        return Err(Error::UnspecifiedInputLanguage);
+    } else {
+        return Err(Error::MissingExtension { path: input.into() });
+    };
+
+    // Read the input file
+    let (json, target_reasoner): (String, TargetReasoner) = match language {
+        PolicyInputLanguage::EFlint => {
+            // We read it as eFLINT to JSON
+            debug!("Compiling eFLINT input file '{}' to eFLINT JSON", input.display());
+            let mut json: Vec<u8> = Vec::new();
+            if let Err(err) = eflint_to_json::compile_async(input, &mut json, None).await {
+                return Err(Error::InputToJson { path: input.into(), err });
+            }
+
+            // Serialize it to a string
+            match String::from_utf8(json) {
+                Ok(json) => (json, TargetReasoner::EFlintJson(EFlintJsonVersion::V0_1_0)),
+                Err(err) => panic!("{}", trace!(("eflint_to_json::compile_async() did not return valid UTF-8"), err)),
+            }
+        },
+        PolicyInputLanguage::EFlintJson => {
+            // Read the file in one go
+            debug!("Reading eFLINT JSON input file '{}'", input.display());
+            match tfs::read_to_string(input).await {
+                Ok(json) => (json, TargetReasoner::EFlintJson(EFlintJsonVersion::V0_1_0)),
+                Err(err) => return Err(Error::InputRead { path: input.into(), err }),
+            }
+        },
+    };
+
+    // Ensure it is JSON
+    debug!("Deserializing input as JSON...");
+    match serde_json::from_str(&json) {
+        Ok(json) => Ok((json, target_reasoner)),
+        Err(err) => Err(Error::InputDeserialize { path: input.into(), raw: json, err }),
+    }
+}
+
+/// Renders the contents of a policy as pretty-printed JSON.
+///
+/// # Arguments
+/// - `policy`: The [`Policy`] to render the contents of.
+///
+/// # Returns
+/// A string with, for every reasoner the policy targets, a header and the pretty-printed JSON of the policy body.
+fn render_policy_contents(policy: &Policy) -> String {
+    let mut res: String = String::new();
+    for content in &policy.content {
+        res.push_str(&format!("# {} v{}\n", content.reasoner, content.reasoner_version));
+        match serde_json::from_str::<serde_json::Value>(content.content.get()).and_then(|value| serde_json::to_string_pretty(&value)) {
+            Ok(pretty) => res.push_str(&pretty),
+            Err(_) => res.push_str(content.content.get()),
+        }
+        res.push('\n');
+    }
+    res
+}
+
+/// Prints a policy to stdout, together with the changes it makes compared to the active policy.
+///
+/// # Arguments
+/// - `policy`: The [`Policy`] to show.
+/// - `active`: The currently active [`Policy`], if any.
+fn show_policy(policy: &Policy, active: Option<&Policy>) {
+    let version: Option<i64> = policy.version.version;
+    println!("Policy {}", style(version.map(|v| v.to_string()).unwrap_or_else(|| "<unknown>".into())).bold().green());
+    println!("  Created at  : {}", policy.version.created_at.format("%H:%M:%S %d-%m-%Y"));
+    println!("  Created by  : {}", policy.version.creator.as_deref().unwrap_or("<unknown>"));
+    if !policy.version.version_description.is_empty() {
+        println!("  Description : {}", policy.version.version_description);
+    }
+    println!();
+    println!("{}", render_policy_contents(policy));
+
+    // Compare it to the active one
+    match active {
+        Some(active) if active.version.version == version => println!("This is the {} version.", style("active").bold()),
+        Some(active) => {
+            println!(
+                "Changes compared to the active version {}:",
+                style(active.version.version.map(|v| v.to_string()).unwrap_or_else(|| "<unknown>".into())).bold().green()
+            );
+            println!("{}", diff_lines(&render_policy_contents(active), &render_policy_contents(policy), 3));
+        },
+        None => println!("No version is currently active."),
+    }
+}
+
+
+
 /// Prompts the user to select one of the given list of versions.
 ///
 /// # Arguments
@@ -512,7 +742,94 @@ fn prompt_user_version(
 
 
 
+/// Reads a policy or sample file and returns its eFLINT JSON phrases.
+///
+/// # Arguments
+/// - `path`: The path to the file. Its language is derived from its extension.
+///
+/// # Returns
+/// The phrases in the file.
+///
+/// # Errors
+/// This function may error if we failed to read or compile the file, or if it did not compile to a list of phrases.
+async fn read_phrases(path: &Path) -> Result<Vec<Value>, Error> {
+    let (json, _): (Box<RawValue>, TargetReasoner) = read_input(path, false, None).await?;
+    match serde_json::from_str::<Value>(json.get()) {
+        Ok(Value::Object(mut json)) => match json.remove("phrases") {
+            Some(Value::Array(phrases)) => Ok(phrases),
+            _ => Err(Error::MissingPhrases { path: path.into() }),
+        },
+        _ => Err(Error::MissingPhrases { path: path.into() }),
+    }
+}
+
+/// Asks an eFLINT reasoner about the given phrases.
+///
+/// # Arguments
+/// - `reasoner`: The address of the eFLINT reasoner to ask.
+/// - `phrases`: The phrases to send it, of which the last is expected to be a query.
+///
+/// # Returns
+/// The [`Verdict`] of the reasoner.
+///
+/// # Errors
+/// This function errors if we failed to reach the reasoner or to parse its response.
+async fn reason(reasoner: &Address, phrases: Vec<Value>) -> Result<Verdict, Error> {
+    let url: String = reasoner.to_string();
+    let body: Value = json!({ "version": EFLINT_JSON_VERSION, "kind": "phrases", "phrases": phrases, "updates": true });
+    debug!("Sending {} phrase(s) to reasoner '{}'...", body["phrases"].as_array().map(Vec::len).unwrap_or(0), url);
+    let res: Response = match Client::new().post(&url).header("Content-Type", "application/json").body(body.to_string()).send().await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestSend { kind: "POST", addr: url, err }),
+    };
+    debug!("Reasoner responded with {}", res.status());
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure { addr: url, code: res.status(), response: res.text().await.ok() });
+    }
+    let res: Value = match res.text().await {
+        Ok(body) => match serde_json::from_str(&body) {
+            Ok(res) => res,
+            Err(err) => return Err(Error::ResponseDeserialize { addr: url, raw: body, err }),
+        },
+        Err(err) => return Err(Error::ResponseDownload { addr: url, err }),
+    };
+
+    // Interpret it
+    if res["success"] == Value::Bool(false) {
+        return Ok(Verdict::Error(res["errors"].as_array().map(|errs| errs.iter().map(Value::to_string).collect()).unwrap_or_default()));
+    }
+    let results: &[Value] = res["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut reasons: Vec<String> = vec![];
+    for result in results {
+        if result["violated"] == Value::Bool(true) {
+            match result["violations"].as_array() {
+                Some(violations) => reasons.extend(violations.iter().map(|v| format!("Violated {v}"))),
+                None => reasons.push("A violation occurred".into()),
+            }
+        }
+    }
+    if results.last().map(|result| result["result"] != Value::Bool(true)).unwrap_or(true) {
+        reasons.push("The question asked by the sample does not hold".into());
+    }
+    Ok(if reasons.is_empty() { Verdict::Allow } else { Verdict::Deny(reasons) })
+}
+
+
+
+
+
 /***** AUXILLARY *****/
+/// Defines the verdict of a local reasoner on a sample workflow.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Verdict {
+    /// The question asked by the sample holds and no violations occurred.
+    Allow,
+    /// The question asked by the sample does not hold or violations occurred, with the reasons why.
+    Deny(Vec<String>),
+    /// The reasoner could not reason about the sample, with the errors it gave.
+    Error(Vec<String>),
+}
+
 /// Defines supported reasoners in the checker.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum TargetReasoner {
@@ -673,84 +990,9 @@ pub async fn add(
     let token: String = resolve_token(&node_config_path, &mut worker, token)?;
     let address: Address = resolve_addr_opt(&node_config_path, &mut worker, address)?;
 
-    // Next stop: resolve the input to a path to read from
-    let (input, from_stdin): (PathBuf, bool) = if input == "-" {
-        // Create a temporary file to write stdin to
-        let id: String = rand::thread_rng().sample_iter(Alphanumeric).take(4).map(char::from).collect::<String>();
-        let temp_path: PathBuf = std::env::temp_dir().join(format!("branectl-stdin-{id}.txt"));
-        debug!("Writing stdin to temporary file '{}'...", temp_path.display());
-        let mut temp: TFile = match TFile::create(&temp_path).await {
-            Ok(temp) => temp,
-            Err(err) => return Err(Error::TempFileCreate { path: temp_path, err }),
-        };
-
-        // Perform the write
-        if let Err(err) = tokio::io::copy(&mut tokio::io::stdin(), &mut temp).await {
-            return Err(Error::TempFileWrite { path: temp_path, err });
-        }
-
-        // Done
-        (temp_path, true)
-    } else {
-        (input.into(), false)
-    };
-
-    // If the language is not given, resolve it from the file extension
-    let language: PolicyInputLanguage = if let Some(language) = language {
-        debug!("Interpreting input as {language}");
-        language
-    } else if let Some(ext) = input.extension() {
-        debug!("Attempting to derive input language from extension '{}' (part of '{}')", ext.to_string_lossy(), input.display());
-
-        // Else, attempt to resolve from the extension
-        if ext == OsStr::new("eflint") {
-            PolicyInputLanguage::EFlint
-        } else if ext == OsStr::new("json") {
-            PolicyInputLanguage::EFlintJson
-        } else if from_stdin {
-            return Err(Error::UnspecifiedInputLanguage);
-        } else {
-            let ext: String = ext.to_string_lossy().into();
-            return Err(Error::UnknownExtension { path: input, ext });
-        }
-    } else if from_stdin {
-        return Err(Error::UnspecifiedInputLanguage);
-    } else {
-        return Err(Error::MissingExtension { path: input });
-    };
-
-    // Read the input file
-    let (json, target_reasoner): (String, TargetReasoner) = match language {
-        PolicyInputLanguage::EFlint => {
-            // We read it as eFLINT to JSON
-            debug!("Compiling eFLINT input file '{}' to eFLINT JSON", input.display());
-            let mut json: Vec<u8> = Vec::new();
-            if let Err(err) = eflint_to_json::compile_async(&input, &mut json, None).await {
-                return Err(Error::InputToJson { path: input, err });
-            }
-
-            // Serialize it to a string
-            match String::from_utf8(json) {
-                Ok(json) => (json, TargetReasoner::EFlintJson(EFlintJsonVersion::V0_1_0)),
-                Err(err) => panic!("{}", trace!(("eflint_to_json::compile_async() did not return valid UTF-8"), err)),
-            }
-        },
-        PolicyInputLanguage::EFlintJson => {
-            // Read the file in one go
-            debug!("Reading eFLINT JSON input file '{}'", input.display());
-            match tfs::read_to_string(&input).await {
-                Ok(json) => (json, TargetReasoner::EFlintJson(EFlintJsonVersion::V0_1_0)),
-                Err(err) => return Err(Error::InputRead { path: input, err }),
-            }
-        },
-    };
-
-    // Ensure it is JSON
-    debug!("Deserializing input as JSON...");
-    let json: Box<RawValue> = match serde_json::from_str(&json) {
-        Ok(json) => json,
-        Err(err) => return Err(Error::InputDeserialize { path: input, raw: json, err }),
-    };
+    // Read the input as eFLINT JSON
+    let (input, from_stdin): (PathBuf, bool) = resolve_input(input).await?;
+    let (json, target_reasoner): (Box<RawValue>, TargetReasoner) = read_input(&input, from_stdin, language).await?;
 
     // Finally, construct a request for the checker
     let url: String = format!("http://{}/{}", address, POLICY_API_ADD_VERSION.1);
@@ -821,15 +1063,16 @@ pub async fn list(node_config_path: PathBuf, address: AddressOpt, token: Option<
     let address: Address = resolve_addr_opt(&node_config_path, &mut worker, address)?;
 
     // Send the request to the reasoner to fetch the active versions
-    let mut versions: Vec<PolicyVersion> = match get_versions_on_checker(&address, &token).await {
+    let versions: Vec<PolicyVersion> = match get_versions_on_checker(&address, &token).await {
         Ok(versions) => versions,
         Err(err) => return Err(Error::VersionsGet { addr: address, err: Box::new(err) }),
     };
     // Then fetch the already active version
-    let active_version: Option<i64> = match get_active_version_on_checker(&address, &token).await {
-        Ok(version) => version.and_then(|v| v.version.version),
+    let active: Option<Policy> = match get_active_version_on_checker(&address, &token).await {
+        Ok(active) => active,
         Err(err) => return Err(Error::ActiveVersionGet { addr: address, err: Box::new(err) }),
     };
+    let active_version: Option<i64> = active.as_ref().and_then(|v| v.version.version);
 
     // Enter a loop where we let the user decide for themselves
     loop {
@@ -839,16 +1082,333 @@ pub async fn list(node_config_path: PathBuf, address: AddressOpt, token: Option<
             Ok(None) => break,
             Err(err) => return Err(Error::PromptVersions { err: Box::new(err) }),
         };
-        let version: i64 = versions.swap_remove(idx).version.unwrap();
+        let version: i64 = versions[idx].version.unwrap();
 
         // Attempt to pull this version from the remote
-        let _version: Policy = match get_version_body_from_checker(&address, &token, version).await {
+        let policy: Policy = match get_version_body_from_checker(&address, &token, version).await {
             Ok(version) => version,
             Err(err) => return Err(Error::VersionGetBody { addr: address, version, err: Box::new(err) }),
         };
+
+        // Show it to the user, then re-enter the loop until they quit
+        println!();
+        show_policy(&policy, active.as_ref());
+        println!();
+    }
+
+    // Done
+    Ok(())
+}
+
+
+
+/// Deactivates the active policy in the checker, such that no policy is active anymore.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file that determines which node we're working for.
+/// - `address`: The address on which to reach the checker. May be missing a port, to be resolved in the node.yml.
+/// - `token`: A token used for authentication with the remote checker. If omitted, will attempt to generate one based on the secret file in the node.yml file.
+///
+/// # Errors
+/// This function may error if we failed to read configs, contact the checker of if the checker errored.
+pub async fn deactivate(node_config_path: PathBuf, address: AddressOpt, token: Option<String>) -> Result<(), Error> {
+    info!("Deactivating policy on checker of node defined by '{}'", node_config_path.display());
+
+    // See if we need to resolve the token & address
+    let mut worker: Option<WorkerConfig> = None;
+    let token: String = resolve_token(&node_config_path, &mut worker, token)?;
+    let address: Address = resolve_addr_opt(&node_config_path, &mut worker, address)?;
+
+    // Build the request
+    let url: String = format!("http://{}/{}", address, POLICY_API_DEACTIVATE_VERSION.1);
+    debug!("Building DELETE-request to '{url}'...");
+    let client: Client = Client::new();
+    let req: Request = match client.request(POLICY_API_DEACTIVATE_VERSION.0, &url).bearer_auth(token).build() {
+        Ok(req) => req,
+        Err(err) => return Err(Error::RequestBuild { kind: "DELETE", addr: url, err }),
+    };
+
+    // Send it
+    debug!("Sending request to '{url}'...");
+    let res: Response = match client.execute(req).await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestSend { kind: "DELETE", addr: url, err }),
+    };
+    debug!("Server responded with {}", res.status());
+    match res.status() {
+        code if code.is_success() => {},
+        // No policy was active to begin with
+        StatusCode::NOT_FOUND => {
+            println!("Checker {} has no active policy.", style(address).bold().green());
+            return Ok(());
+        },
+        code => return Err(Error::RequestFailure { addr: url, code, response: res.text().await.ok() }),
+    }
+
+    // Done!
+    println!("Successfully deactivated the active policy on checker {}.", style(address).bold().green());
+    Ok(())
+}
+
+
+
+/// Removes a (non-active) policy from the checker.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file that determines which node we're working for.
+/// - `version`: The version to remove from the checker.
+/// - `address`: The address on which to reach the checker. May be missing a port, to be resolved in the node.yml.
+/// - `token`: A token used for authentication with the remote checker. If omitted, will attempt to generate one based on the secret file in the node.yml file.
+///
+/// # Errors
+/// This function may error if we failed to read configs, contact the checker, if the checker errored or if the given version is the active one.
+pub async fn remove(node_config_path: PathBuf, version: i64, address: AddressOpt, token: Option<String>) -> Result<(), Error> {
+    info!("Removing policy '{}' from checker of node defined by '{}'", version, node_config_path.display());
+
+    // See if we need to resolve the token & address
+    let mut worker: Option<WorkerConfig> = None;
+    let token: String = resolve_token(&node_config_path, &mut worker, token)?;
+    let address: Address = resolve_addr_opt(&node_config_path, &mut worker, address)?;
+
+    // Refuse to remove the active version from under the checker's feet
+    match get_active_version_on_checker(&address, &token).await {
+        Ok(Some(active)) if active.version.version == Some(version) => return Err(Error::RemoveActive { addr: address, version }),
+        Ok(_) => {},
+        Err(err) => return Err(Error::ActiveVersionGet { addr: address, err: Box::new(err) }),
     }
 
-    // TODO: Finish this. The idea is show a particular version to the user, then re-enter the loop until they quit
-    //       (empty version, as above)
-    todo!();
+    // Build the request
+    let url: String = format!("http://{}/{}", address, POLICY_API_REMOVE_VERSION.1(version));
+    debug!("Building DELETE-request to '{url}'...");
+    let client: Client = Client::new();
+    let req: Request = match client.request(POLICY_API_REMOVE_VERSION.0, &url).bearer_auth(token).build() {
+        Ok(req) => req,
+        Err(err) => return Err(Error::RequestBuild { kind: "DELETE", addr: url, err }),
+    };
+
+    // Send it
+    debug!("Sending request to '{url}'...");
+    let res: Response = match client.execute(req).await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestSend { kind: "DELETE", addr: url, err }),
+    };
+    debug!("Server responded with {}", res.status());
+    if !res.status().is_success() {
+        return Err(Error::RequestFailure { addr: url, code: res.status(), response: res.text().await.ok() });
+    }
+
+    // Done!
+    println!("Successfully removed policy {} from checker {}.", style(version).bold().green(), style(address).bold().green());
+    Ok(())
+}
+
+
+
+/// Shows a policy in the checker, including the changes it makes compared to the active version.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file that determines which node we're working for.
+/// - `version`: The version to show.
+/// - `address`: The address on which to reach the checker. May be missing a port, to be resolved in the node.yml.
+/// - `token`: A token used for authentication with the remote checker. If omitted, will attempt to generate one based on the secret file in the node.yml file.
+///
+/// # Errors
+/// This function may error if we failed to read configs, contact the checker of if the checker errored.
+pub async fn show(node_config_path: PathBuf, version: i64, address: AddressOpt, token: Option<String>) -> Result<(), Error> {
+    info!("Showing policy '{}' on checker of node defined by '{}'", version, node_config_path.display());
+
+    // See if we need to resolve the token & address
+    let mut worker: Option<WorkerConfig> = None;
+    let token: String = resolve_token(&node_config_path, &mut worker, token)?;
+    let address: Address = resolve_addr_opt(&node_config_path, &mut worker, address)?;
+
+    // Fetch both the requested and the active version
+    let policy: Policy = match get_version_body_from_checker(&address, &token, version).await {
+        Ok(policy) => policy,
+        Err(err) => return Err(Error::VersionGetBody { addr: address, version, err: Box::new(err) }),
+    };
+    let active: Option<Policy> = match get_active_version_on_checker(&address, &token).await {
+        Ok(active) => active,
+        Err(err) => return Err(Error::ActiveVersionGet { addr: address, err: Box::new(err) }),
+    };
+
+    // Show it
+    show_policy(&policy, active.as_ref());
+    Ok(())
+}
+
+
+
+/// Validates a policy file locally, without sending it to a checker.
+///
+/// For eFLINT policies, this compiles them to eFLINT JSON; for eFLINT JSON policies, this checks they are valid JSON.
+///
+/// # Arguments
+/// - `input`: The policy (or rather, a path thereto) to validate. Can be `-` to read from stdin.
+/// - `language`: The language of the input.
+///
+/// # Errors
+/// This function errors if the policy is invalid, or if we failed to read or compile it.
+pub async fn validate(input: String, language: Option<PolicyInputLanguage>) -> Result<(), Error> {
+    info!("Validating policy '{input}'");
+
+    // Read the input, which does all the checks for us
+    let (input, from_stdin): (PathBuf, bool) = resolve_input(input).await?;
+    let (_, target_reasoner): (Box<RawValue>, TargetReasoner) = read_input(&input, from_stdin, language).await?;
+
+    // Done!
+    println!(
+        "Policy {} is valid for reasoner {} v{}.",
+        style(if from_stdin { "<stdin>".into() } else { input.display().to_string() }).bold().green(),
+        style(target_reasoner.id()).bold(),
+        target_reasoner.version()
+    );
+    Ok(())
+}
+
+
+
+/// Generates a new policy set from a template.
+///
+/// The policy set is a directory with the policy itself (`policy.eflint`), the interface it requires (`interface.eflint`) and sample
+/// workflows that the policy is expected to allow (`samples/allow/`) or deny (`samples/deny/`). These can be checked with [`check()`].
+///
+/// # Arguments
+/// - `template`: The [`PolicyTemplate`] to generate the policy set from.
+/// - `path`: The directory to generate the policy set in. Will be created if it does not exist.
+/// - `force`: Whether to overwrite existing files.
+///
+/// # Errors
+/// This function may error if any of the files already exist (and `force` is false) or if we failed to write them.
+pub async fn init(template: PolicyTemplate, path: PathBuf, force: bool) -> Result<(), Error> {
+    info!("Generating '{}' policy set in '{}'", template, path.display());
+
+    // Collect the files to write
+    let policy: &str = match template {
+        PolicyTemplate::AllowAll => TEMPLATE_ALLOW_ALL,
+        PolicyTemplate::DatasetAllowlist => TEMPLATE_DATASET_ALLOWLIST,
+        PolicyTemplate::PurposeBinding => TEMPLATE_PURPOSE_BINDING,
+    };
+    let mut files: Vec<(PathBuf, &str)> = vec![
+        (path.join("interface.eflint"), TEMPLATE_INTERFACE),
+        (path.join("policy.eflint"), policy),
+        (path.join("samples").join("allow").join("example.eflint"), SAMPLE_EXAMPLE),
+    ];
+    match template {
+        PolicyTemplate::AllowAll => {},
+        PolicyTemplate::DatasetAllowlist => files.push((path.join("samples").join("deny").join("unlisted.eflint"), SAMPLE_UNLISTED)),
+        PolicyTemplate::PurposeBinding => files.push((path.join("samples").join("deny").join("wrong-purpose.eflint"), SAMPLE_WRONG_PURPOSE)),
+    }
+    if !force {
+        if let Some((file, _)) = files.iter().find(|(file, _)| file.exists()) {
+            return Err(Error::FileExists { path: file.clone() });
+        }
+    }
+
+    // Write them
+    for (file, contents) in files {
+        if let Some(dir) = file.parent() {
+            if let Err(err) = tfs::create_dir_all(dir).await {
+                return Err(Error::DirCreate { path: dir.into(), err });
+            }
+        }
+        debug!("Writing '{}'...", file.display());
+        if let Err(err) = tfs::write(&file, contents).await {
+            return Err(Error::FileWrite { path: file, err });
+        }
+    }
+
+    // Done!
+    println!("Generated {} policy set in {}.", style(template).bold().green(), style(path.display()).bold());
+    println!("Edit {} to your needs, and check it with:", style(path.join("policy.eflint").display()).bold());
+    println!("    branectl policies check {}", path.display());
+    Ok(())
+}
+
+/// Checks a policy set against its sample workflows using a local reasoner.
+///
+/// Every sample in the policy set's `samples/allow/` and `samples/deny/` directories is given to the reasoner together with the policy. A
+/// sample states a workflow as facts and ends with the question that a checker would ask about it (e.g., whether a dataset may be
+/// transferred). Its verdict is then compared to the directory it is in.
+///
+/// # Arguments
+/// - `dir`: The directory of the policy set (e.g., as generated by [`init()`]).
+/// - `reasoner`: The address of the eFLINT reasoner to use.
+///
+/// # Errors
+/// This function may error if we failed to read or compile the policy set, failed to reach the reasoner or if any sample did not get the
+/// expected verdict.
+pub async fn check(dir: PathBuf, reasoner: Address) -> Result<(), Error> {
+    info!("Checking policy set '{}' against reasoner '{}'", dir.display(), reasoner);
+
+    // Compile the policy
+    let policy_path: PathBuf = match ["policy.eflint", "policy.json"].into_iter().map(|file| dir.join(file)).find(|path| path.exists()) {
+        Some(path) => path,
+        None => return Err(Error::MissingPolicy { dir }),
+    };
+    let policy: Vec<Value> = read_phrases(&policy_path).await?;
+
+    // Run every sample
+    let (mut total, mut failed): (usize, usize) = (0, 0);
+    for expect_allow in [true, false] {
+        let samples_dir: PathBuf = dir.join("samples").join(if expect_allow { "allow" } else { "deny" });
+        if !samples_dir.exists() {
+            debug!("No samples directory '{}'; skipping", samples_dir.display());
+            continue;
+        }
+        let mut samples: Vec<PathBuf> = vec![];
+        let mut entries: tfs::ReadDir = match tfs::read_dir(&samples_dir).await {
+            Ok(entries) => entries,
+            Err(err) => return Err(Error::SamplesDirRead { path: samples_dir, err }),
+        };
+        loop {
+            match entries.next_entry().await {
+                Ok(Some(entry)) => {
+                    let path: PathBuf = entry.path();
+                    if path.extension().map(|ext| ext == OsStr::new("eflint") || ext == OsStr::new("json")).unwrap_or(false) {
+                        samples.push(path);
+                    }
+                },
+                Ok(None) => break,
+                Err(err) => return Err(Error::SamplesDirRead { path: samples_dir, err }),
+            }
+        }
+        samples.sort();
+
+        for sample in samples {
+            let mut phrases: Vec<Value> = policy.clone();
+            phrases.extend(read_phrases(&sample).await?);
+            let verdict: Verdict = reason(&reasoner, phrases).await?;
+
+            // Report it
+            total += 1;
+            let name: String = sample.strip_prefix(&dir).unwrap_or(&sample).display().to_string();
+            let (ok, reasons): (bool, Vec<String>) = match verdict {
+                Verdict::Allow => (expect_allow, vec![]),
+                Verdict::Deny(reasons) => (!expect_allow, reasons),
+                Verdict::Error(errors) => (false, errors),
+            };
+            if ok {
+                println!(" {} {}", style("PASS").bold().green(), name);
+            } else {
+                failed += 1;
+                println!(" {} {} (expected it to be {})", style("FAIL").bold().red(), name, if expect_allow { "allowed" } else { "denied" });
+                for reason in reasons {
+                    println!("    - {}", style(reason).bold());
+                }
+            }
+        }
+    }
+    println!();
+
+    // Done!
+    if total == 0 {
+        println!("Policy set {} has no samples to check.", style(dir.display()).bold());
+        return Ok(());
+    }
+    if failed > 0 {
+        return Err(Error::ChecksFailed { dir, failed, total });
+    }
+    println!("All {} sample(s) of policy set {} got the expected verdict.", total, style(dir.display()).bold().green());
+    Ok(())
 }
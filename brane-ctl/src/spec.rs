@@ -4,7 +4,7 @@
 //  Created:
 //    21 Nov 2022, 17:27:52
 //  Last edited:
-//    08 Feb 2024, 17:08:25
+//    15 Oct 2026, 22:34:10
 //  Auto updated?
 //    Yes
 //
@@ -14,7 +14,7 @@
 
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::ops::RangeInclusive;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use brane_cfg::node::NodeKind;
@@ -24,7 +24,10 @@ use enum_debug::EnumDebug;
 use specifications::address::Address;
 use specifications::version::Version;
 
-use crate::errors::{InclusiveRangeParseError, PairParseError, PolicyInputLanguageParseError};
+use crate::errors::{
+    BundleContentParseError, ConfigKindParseError, DeployTargetParseError, InclusiveRangeParseError, PairParseError, PolicyInputLanguageParseError,
+    PolicyTemplateParseError,
+};
 
 
 /***** STATICS *****/
@@ -273,6 +276,167 @@ impl FromStr for PolicyInputLanguage {
     }
 }
 
+/// Defines the starter policy sets that `branectl policies init` can generate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PolicyTemplate {
+    /// Allows every workflow, task and transfer.
+    AllowAll,
+    /// Only allows the datasets on an allowlist to be transferred.
+    DatasetAllowlist,
+    /// Only allows datasets to be transferred to workflows that are tagged with a purpose they may be used for.
+    PurposeBinding,
+}
+impl Display for PolicyTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use PolicyTemplate::*;
+        match self {
+            AllowAll => write!(f, "allow-all"),
+            DatasetAllowlist => write!(f, "dataset-allowlist"),
+            PurposeBinding => write!(f, "purpose-binding"),
+        }
+    }
+}
+impl FromStr for PolicyTemplate {
+    type Err = PolicyTemplateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow-all" => Ok(Self::AllowAll),
+            "dataset-allowlist" => Ok(Self::DatasetAllowlist),
+            "purpose-binding" => Ok(Self::PurposeBinding),
+            raw => Err(PolicyTemplateParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+
+
+
+/// Defines the ways in which a node's services can be deployed on the host.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DeployTarget {
+    /// The services run as containers managed by Docker Compose.
+    Compose,
+    /// The services run as binaries on the host, managed by systemd.
+    Systemd,
+}
+impl Display for DeployTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DeployTarget::*;
+        match self {
+            Compose => write!(f, "compose"),
+            Systemd => write!(f, "systemd"),
+        }
+    }
+}
+impl FromStr for DeployTarget {
+    type Err = DeployTargetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compose" => Ok(Self::Compose),
+            "systemd" => Ok(Self::Systemd),
+            raw => Err(DeployTargetParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+
+
+
+/// Defines the kinds of configuration files that `branectl upgrade config` knows how to migrate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConfigKind {
+    /// A `node.yml` file.
+    Node,
+    /// An `infra.yml` file.
+    Infra,
+    /// A `backend.yml` file.
+    Backend,
+    /// A `proxy.yml` file.
+    Proxy,
+}
+impl ConfigKind {
+    /// Attempts to deduce the kind of a config file from its filename.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to deduce the kind of.
+    ///
+    /// # Returns
+    /// The kind of the file, or [`None`] if it isn't named after any of the known kinds.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let stem: &str = path.file_stem()?.to_str()?;
+        match path.extension()?.to_str()? {
+            "yml" | "yaml" => Self::from_str(stem).ok(),
+            _ => None,
+        }
+    }
+}
+impl Display for ConfigKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConfigKind::*;
+        match self {
+            Node => write!(f, "node"),
+            Infra => write!(f, "infra"),
+            Backend => write!(f, "backend"),
+            Proxy => write!(f, "proxy"),
+        }
+    }
+}
+impl FromStr for ConfigKind {
+    type Err = ConfigKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node" => Ok(Self::Node),
+            "infra" => Ok(Self::Infra),
+            "backend" => Ok(Self::Backend),
+            "proxy" => Ok(Self::Proxy),
+            raw => Err(ConfigKindParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+
+/// Defines the kinds of artifacts that `branectl bundle export` can collect.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BundleContent {
+    /// The central node's service images.
+    Central,
+    /// The worker node's service images.
+    Worker,
+    /// The auxillary images (Scylla, Kafka, ...).
+    Auxillary,
+    /// The policy reasoner's artifacts (i.e., the migrations for the policy database).
+    PolicyReasoner,
+}
+impl BundleContent {
+    /// All the kinds of artifacts there are.
+    pub const ALL: [Self; 4] = [Self::Central, Self::Worker, Self::Auxillary, Self::PolicyReasoner];
+}
+impl Display for BundleContent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use BundleContent::*;
+        match self {
+            Central => write!(f, "central"),
+            Worker => write!(f, "worker"),
+            Auxillary => write!(f, "auxillary"),
+            PolicyReasoner => write!(f, "policy-reasoner"),
+        }
+    }
+}
+impl FromStr for BundleContent {
+    type Err = BundleContentParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "central" => Ok(Self::Central),
+            "worker" => Ok(Self::Worker),
+            "auxillary" | "aux" => Ok(Self::Auxillary),
+            "policy-reasoner" | "policy_reasoner" => Ok(Self::PolicyReasoner),
+            raw => Err(BundleContentParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+
+
 
 
 /// Defines a collection of options to pass to the `start`-subcommand handler.
@@ -517,6 +681,21 @@ pub enum GenerateNodeSubcommand {
         /// The port of the proxy service.
         #[clap(short, long, default_value = "50050", help = "The port on which the local proxy service is available.")]
         prx_port: u16,
+
+        /// The maximum number of tasks to run at the same time.
+        #[clap(
+            long,
+            help = "The maximum number of tasks that the worker executes simultaneously. Any more are queued until a running task completes. If \
+                    omitted, defaults to the number of CPUs of the host."
+        )]
+        max_concurrent_tasks: Option<usize>,
+        /// The maximum time a task may run.
+        #[clap(
+            long,
+            help = "The maximum time (in seconds) that a task may run on the worker before it is terminated and fails with a timeout. If omitted, \
+                    tasks may run indefinitely."
+        )]
+        task_timeout: Option<u64>,
     },
 
     /// Starts a proxy node.
@@ -556,7 +735,7 @@ pub enum GenerateNodeSubcommand {
 /// A bit awkward here, but defines the generate subcommand for certificates. This basically defines the possible certificate kinds to generate.
 #[derive(Debug, EnumDebug, Subcommand)]
 pub enum GenerateCertsSubcommand {
-    /// It's a server certificate (which includes generating the CA).
+    /// It's a server certificate (which includes generating the CA, unless an existing one is given).
     Server {
         /// The domain name for which to generate the certificates.
         #[clap(name = "LOCATION_ID", help = "The name of the location for which we are generating server certificates.")]
@@ -570,6 +749,35 @@ pub enum GenerateCertsSubcommand {
                     given for the location ID."
         )]
         hostname:    String,
+
+        /// The certificate of an existing (intermediate) CA to sign with instead of generating a new one.
+        #[clap(
+            short,
+            long,
+            requires = "ca_key",
+            conflicts_with = "csr",
+            help = "If given, does not generate a new CA but instead signs the server certificate with the existing (intermediate) CA certificate \
+                    at the given path (e.g., one issued by your organisation's PKI). May contain the full chain up to the root. Requires \
+                    '--ca-key'. Note that client certificates for this domain must then also be signed with this CA."
+        )]
+        ca_cert: Option<PathBuf>,
+        /// The key of an existing (intermediate) CA to sign with instead of generating a new one.
+        #[clap(
+            short = 'k',
+            long,
+            requires = "ca_cert",
+            help = "The path to the private key of the CA given with '--ca-cert'. It is only used for signing and NOT copied to the output \
+                    directory."
+        )]
+        ca_key:  Option<PathBuf>,
+        /// Whether to only generate a key and CSR instead of a certificate.
+        #[clap(
+            long,
+            help = "If given, does not sign anything but only generates the server's private key and a certificate signing request ('server.csr') \
+                    to submit to your organisation's PKI. Place the resulting certificate as 'server.pem' and the CA (chain) as 'ca.pem' in the \
+                    output directory afterwards."
+        )]
+        csr:     bool,
     },
 
     /// It's a client certificate.
@@ -660,6 +868,17 @@ pub enum GenerateBackendSubcommand {
         #[clap(short, long, help = "If given, fixes the Docker client version to the given one.")]
         client_version: Option<ClientVersion>,
     },
+
+    /// A backend on a Kubernetes cluster.
+    #[clap(name = "kubernetes", alias = "k8s", about = "Generate a backend.yml for a Kubernetes cluster backend.")]
+    Kubernetes {
+        /// The address of the registry to push container images to.
+        #[clap(name = "REGISTRY", help = "The address of the Docker registry that the cluster pulls container images from.")]
+        registry_address: Address,
+        /// The location of the Kubernetes config file.
+        #[clap(short, long, help = "The location of the Kubernetes config file to connect to the cluster with. If omitted, uses '~/.kube/config'.")]
+        config: Option<PathBuf>,
+    },
 }
 
 
@@ -0,0 +1,427 @@
+//  STATUS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 17:32:10
+//  Last edited:
+//    16 Oct 2026, 02:07:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `branectl status` subcommand, which reports the
+//!   health of every service of the local node.
+//
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use bollard::Docker;
+use bollard::models::{ContainerInspectResponse, ContainerState, HealthStatusEnum};
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig};
+use brane_tsk::docker::DockerOptions;
+use console::style;
+use log::{debug, info};
+use reqwest::StatusCode;
+use specifications::checking::POLICY_API_GET_ACTIVE_VERSION;
+use tonic::transport::{Channel, Endpoint};
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+
+pub use crate::errors::StatusError as Error;
+use crate::utils::node_services;
+
+
+/***** CONSTANTS *****/
+/// The time we wait for a service's health endpoints before we consider them unreachable.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(3);
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes how to query a service for its health and version.
+#[derive(Clone, Debug)]
+enum Probe {
+    /// The service exposes plain HTTP `/health` and `/version` endpoints on the given port.
+    Http { port: u16 },
+    /// The service exposes `/health` and `/version` endpoints over HTTPS on the given port.
+    Https {
+        /// The port on which the service listens.
+        port: u16,
+        /// The hostname for which the service's certificate is issued.
+        host: String,
+        /// The path to the CA certificate that signed the service's certificate.
+        ca:   PathBuf,
+    },
+    /// The service implements the standard gRPC health service on the given port.
+    Grpc { port: u16 },
+    /// The service has no health endpoint, but any HTTP response on the given path and port tells us it is alive.
+    Alive { port: u16, path: &'static str },
+}
+
+/// Describes a single service we want to report the status of.
+#[derive(Clone, Debug)]
+struct ServiceTarget {
+    /// The short identifier of the service (e.g., `api`).
+    id:    &'static str,
+    /// The name of the service's container.
+    name:  String,
+    /// How to query the service for its health and version, if at all.
+    probe: Option<Probe>,
+}
+
+/// Describes the health of a service as reported by the service itself.
+#[derive(Clone, Debug)]
+enum Health {
+    /// The service reports that all is well.
+    Healthy,
+    /// The service is running but degraded, for the given reason(s).
+    Degraded(String),
+    /// The service reports that it is not healthy.
+    Unhealthy(String),
+    /// We failed to reach the service.
+    Unreachable(String),
+}
+
+/// Describes the collected status of a single service.
+#[derive(Clone, Debug)]
+struct ServiceStatus {
+    /// The short identifier of the service (e.g., `api`).
+    id:      &'static str,
+    /// The name of the service's container.
+    name:    String,
+    /// The status of the service (e.g., `running`, `healthy`, `degraded`, `not found`).
+    status:  String,
+    /// The version of the service, if known.
+    version: Option<String>,
+    /// The time the service has been running, if it is running.
+    uptime:  Option<Duration>,
+    /// The last error reported by the service, if any.
+    error:   Option<String>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Collects the services that are hosted on the node described by the given node config.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// A list of [`ServiceTarget`]s, one for every service hosted by this node.
+fn collect_services(node_config: &NodeConfig) -> Vec<ServiceTarget> {
+    let probe = |id: &str| -> Option<Probe> {
+        match (&node_config.node, id) {
+            (NodeSpecificConfig::Central(central), "api") => Some(Probe::Http { port: central.services.api.bind.port() }),
+            (NodeSpecificConfig::Central(central), "drv") => Some(Probe::Grpc { port: central.services.drv.bind.port() }),
+            (NodeSpecificConfig::Central(central), "plr") => Some(Probe::Http { port: central.services.plr.bind.port() }),
+            (NodeSpecificConfig::Central(central), "prx") => Some(Probe::Http { port: central.services.prx.private().bind.port() }),
+
+            (NodeSpecificConfig::Worker(worker), "reg") => Some(Probe::Https {
+                port: worker.services.reg.bind.port(),
+                host: worker.services.reg.external_address.domain().into_owned(),
+                ca:   worker.paths.certs.join("ca.pem"),
+            }),
+            (NodeSpecificConfig::Worker(worker), "job") => Some(Probe::Grpc { port: worker.services.job.bind.port() }),
+            (NodeSpecificConfig::Worker(worker), "chk") => {
+                Some(Probe::Alive { port: worker.services.chk.bind.port(), path: POLICY_API_GET_ACTIVE_VERSION.1 })
+            },
+            (NodeSpecificConfig::Worker(worker), "prx") => Some(Probe::Http { port: worker.services.prx.private().bind.port() }),
+
+            (NodeSpecificConfig::Proxy(proxy), "prx") => Some(Probe::Http { port: proxy.services.prx.bind.port() }),
+            _ => None,
+        }
+    };
+    node_services(node_config).into_iter().map(|(id, name)| ServiceTarget { id, name, probe: probe(id) }).collect()
+}
+
+/// Finds the IP address of the given container on its Docker network(s).
+///
+/// # Arguments
+/// - `info`: The [`ContainerInspectResponse`] of the container.
+///
+/// # Returns
+/// The IP address of the container, or the local host if it has none (e.g., because it runs in host networking mode).
+fn container_ip(info: &ContainerInspectResponse) -> IpAddr {
+    info.network_settings
+        .as_ref()
+        .and_then(|settings| settings.networks.as_ref())
+        .and_then(|networks| networks.values().filter_map(|net| net.ip_address.as_ref()).find_map(|ip| ip.parse::<IpAddr>().ok()))
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+}
+
+/// Extracts the last failing healthcheck output from the given container state, if any.
+///
+/// # Arguments
+/// - `state`: The [`ContainerState`] to search.
+///
+/// # Returns
+/// The (trimmed) output of the most recent healthcheck that failed, or [`None`] if there is none.
+fn last_health_error(state: &ContainerState) -> Option<String> {
+    let log = state.health.as_ref()?.log.as_ref()?;
+    log.iter().rev().find(|res| res.exit_code.unwrap_or(0) != 0).and_then(|res| res.output.as_ref()).map(|out| out.trim().to_string())
+}
+
+/// Queries the `/health` and `/version` endpoints of a service.
+///
+/// # Arguments
+/// - `client`: The [`reqwest::Client`] to send the requests with.
+/// - `base`: The base URL of the service (e.g., `http://172.18.0.2:50051`).
+///
+/// # Returns
+/// A tuple of the service's [`Health`] and the reported version, if any.
+async fn query_http(client: &reqwest::Client, base: &str) -> (Health, Option<String>) {
+    // Query the health first
+    let url: String = format!("{base}/health");
+    debug!("Querying '{url}'...");
+    let health: Health = match client.get(&url).send().await {
+        Ok(res) => {
+            let status: StatusCode = res.status();
+            match res.text().await {
+                // Degraded services report why in the body, whatever the status code
+                Ok(body) if body.starts_with("DEGRADED") => {
+                    Health::Degraded(body.lines().skip(1).map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<&str>>().join("; "))
+                },
+                Ok(_) if status.is_success() => Health::Healthy,
+                Ok(_) => Health::Unhealthy(format!("'{url}' returned {status}")),
+                Err(err) => Health::Unreachable(format!("Failed to download response of '{url}': {err}")),
+            }
+        },
+        Err(err) => Health::Unreachable(format!("Failed to reach '{url}': {err}")),
+    };
+
+    // Then the version
+    let url: String = format!("{base}/version");
+    debug!("Querying '{url}'...");
+    let version: Option<String> = match client.get(&url).send().await {
+        Ok(res) if res.status().is_success() => res.text().await.ok().map(|v| v.trim().to_string()),
+        _ => None,
+    };
+
+    (health, version)
+}
+
+/// Queries the standard gRPC health service of a service.
+///
+/// # Arguments
+/// - `addr`: The address of the service.
+///
+/// # Returns
+/// The [`Health`] of the service.
+async fn query_grpc(addr: SocketAddr) -> Health {
+    let url: String = format!("http://{addr}");
+    debug!("Querying gRPC health service at '{url}'...");
+    let endpoint: Endpoint = match Endpoint::from_shared(url.clone()) {
+        Ok(endpoint) => endpoint.connect_timeout(HTTP_TIMEOUT).timeout(HTTP_TIMEOUT),
+        Err(err) => return Health::Unreachable(format!("Invalid gRPC address '{url}': {err}")),
+    };
+    let channel: Channel = match endpoint.connect().await {
+        Ok(channel) => channel,
+        Err(err) => return Health::Unreachable(format!("Failed to reach '{url}': {err}")),
+    };
+    match HealthClient::new(channel).check(HealthCheckRequest { service: String::new() }).await {
+        Ok(res) => match res.into_inner().status() {
+            ServingStatus::Serving => Health::Healthy,
+            status => Health::Unhealthy(format!("'{url}' reports {}", status.as_str_name())),
+        },
+        Err(err) => Health::Unreachable(format!("Failed to query health of '{url}': {err}")),
+    }
+}
+
+/// Queries a service for its health and version.
+///
+/// # Arguments
+/// - `probe`: The [`Probe`] describing how to query the service.
+/// - `ip`: The IP address at which the service's container is reachable.
+///
+/// # Returns
+/// A tuple of the service's [`Health`] and the reported version, if any.
+async fn query(probe: &Probe, ip: IpAddr) -> (Health, Option<String>) {
+    let builder: reqwest::ClientBuilder = reqwest::Client::builder().timeout(HTTP_TIMEOUT);
+    match probe {
+        Probe::Http { port } => match builder.build() {
+            Ok(client) => query_http(&client, &format!("http://{}", SocketAddr::new(ip, *port))).await,
+            Err(err) => (Health::Unreachable(format!("Failed to build HTTP client: {err}")), None),
+        },
+
+        Probe::Https { port, host, ca } => {
+            // Trust the node's own CA, and verify the certificate against the hostname it was issued for
+            let root: reqwest::Certificate = match std::fs::read(ca)
+                .map_err(|err| err.to_string())
+                .and_then(|pem| reqwest::Certificate::from_pem(&pem).map_err(|err| err.to_string()))
+            {
+                Ok(root) => root,
+                Err(err) => return (Health::Unreachable(format!("Failed to load CA certificate '{}': {}", ca.display(), err)), None),
+            };
+            let builder: reqwest::ClientBuilder = builder.use_rustls_tls().tls_built_in_root_certs(false).add_root_certificate(root);
+            let (builder, base): (reqwest::ClientBuilder, String) = match host.parse::<IpAddr>() {
+                Ok(host) => (builder, format!("https://{}", SocketAddr::new(host, *port))),
+                Err(_) => (builder.resolve(host, SocketAddr::new(ip, *port)), format!("https://{host}:{port}")),
+            };
+            match builder.build() {
+                Ok(client) => query_http(&client, &base).await,
+                Err(err) => (Health::Unreachable(format!("Failed to build HTTPS client: {err}")), None),
+            }
+        },
+
+        Probe::Grpc { port } => (query_grpc(SocketAddr::new(ip, *port)).await, None),
+
+        Probe::Alive { port, path } => {
+            let url: String = format!("http://{}/{}", SocketAddr::new(ip, *port), path);
+            debug!("Querying '{url}'...");
+            let client: reqwest::Client = match builder.build() {
+                Ok(client) => client,
+                Err(err) => return (Health::Unreachable(format!("Failed to build HTTP client: {err}")), None),
+            };
+            match client.get(&url).send().await {
+                Ok(res) if res.status().is_server_error() => (Health::Unhealthy(format!("'{url}' returned {}", res.status())), None),
+                Ok(_) => (Health::Healthy, None),
+                Err(err) => (Health::Unreachable(format!("Failed to reach '{url}': {err}")), None),
+            }
+        },
+    }
+}
+
+/// Collects the status of a single service.
+///
+/// # Arguments
+/// - `docker`: The [`Docker`] client to inspect the service's container with.
+/// - `target`: The [`ServiceTarget`] describing the service.
+///
+/// # Returns
+/// The [`ServiceStatus`] of the service.
+///
+/// # Errors
+/// This function errors if we failed to inspect the container for any other reason than it not existing.
+async fn service_status(docker: &Docker, target: ServiceTarget) -> Result<ServiceStatus, Error> {
+    // Inspect the container
+    debug!("Inspecting container '{}'...", target.name);
+    let info: ContainerInspectResponse = match docker.inspect_container(&target.name, None).await {
+        Ok(info) => info,
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => {
+            return Ok(ServiceStatus { id: target.id, name: target.name, status: "not found".into(), version: None, uptime: None, error: None });
+        },
+        Err(err) => return Err(Error::ContainerInspectError { name: target.name, err }),
+    };
+    let ip: IpAddr = container_ip(&info);
+    let state: ContainerState = info.state.unwrap_or_default();
+    let running: bool = state.running.unwrap_or(false);
+
+    // Derive the status from Docker's view on things
+    let mut status: String = match state.health.as_ref().and_then(|h| h.status) {
+        Some(health) if running && health != HealthStatusEnum::EMPTY && health != HealthStatusEnum::NONE => health.to_string(),
+        _ => state.status.as_ref().map(|s| s.to_string()).unwrap_or_else(|| "unknown".into()),
+    };
+    let mut error: Option<String> = state.error.clone().filter(|e| !e.is_empty()).or_else(|| last_health_error(&state));
+
+    // Compute the uptime
+    let uptime: Option<Duration> = if running {
+        state
+            .started_at
+            .as_ref()
+            .and_then(|started| humantime::parse_rfc3339_weak(started).ok())
+            .and_then(|started| SystemTime::now().duration_since(started).ok())
+    } else {
+        None
+    };
+
+    // Use the image tag as the version, unless the service can tell us itself
+    let mut version: Option<String> = info.config.and_then(|c| c.image).and_then(|i| i.rsplit_once(':').map(|(_, tag)| tag.to_string()));
+    if let (true, Some(probe)) = (running, &target.probe) {
+        let (health, reported) = query(probe, ip).await;
+        match health {
+            Health::Healthy => {},
+            Health::Degraded(reason) => {
+                status = "degraded".into();
+                error = Some(reason);
+            },
+            Health::Unhealthy(reason) => {
+                status = "unhealthy".into();
+                error = Some(reason);
+            },
+            Health::Unreachable(err) => {
+                status = "unreachable".into();
+                error = Some(err);
+            },
+        }
+        version = reported.or(version);
+    }
+
+    Ok(ServiceStatus { id: target.id, name: target.name, status, version, uptime, error })
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Reports the status of every service of the local node.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node configuration file that determines which services we report on.
+/// - `docker_opts`: Configuration for connecting to the local Docker daemon. See `DockerOptions` for more information.
+///
+/// # Errors
+/// This function errors if we failed to read the node config, connect to Docker or inspect a container.
+pub async fn status(node_config_path: impl Into<PathBuf>, docker_opts: DockerOptions) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    info!("Reporting status of node defined by '{}'", node_config_path.display());
+
+    // Load the node config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+
+    // Connect to the Docker client
+    let docker: Docker = match brane_tsk::docker::connect_local(docker_opts) {
+        Ok(docker) => docker,
+        Err(err) => return Err(Error::DockerConnectError { err }),
+    };
+
+    // Collect the status of all services
+    let mut statuses: Vec<ServiceStatus> = vec![];
+    for target in collect_services(&node_config) {
+        statuses.push(service_status(&docker, target).await?);
+    }
+
+    // Print them in a table
+    let name_width: usize = statuses.iter().map(|s| s.name.len()).max().unwrap_or(0).max("CONTAINER".len());
+    println!(
+        "{:<5} {:<name_width$} {:<12} {:<10} {:<16} {}",
+        style("SVC").bold(),
+        style("CONTAINER").bold(),
+        style("STATUS").bold(),
+        style("VERSION").bold(),
+        style("UPTIME").bold(),
+        style("LAST ERROR").bold()
+    );
+    for status in statuses {
+        let uptime: String =
+            status.uptime.map(|u| humantime::format_duration(Duration::from_secs(u.as_secs())).to_string()).unwrap_or_else(|| "-".into());
+        let styled_status = match status.status.as_str() {
+            "healthy" | "running" => style(status.status.clone()).green(),
+            "starting" | "degraded" => style(status.status.clone()).yellow(),
+            _ => style(status.status.clone()).red(),
+        };
+        println!(
+            "{:<5} {:<name_width$} {:<12} {:<10} {:<16} {}",
+            status.id,
+            status.name,
+            styled_status,
+            status.version.as_deref().unwrap_or("-"),
+            uptime,
+            status.error.as_deref().unwrap_or("-"),
+        );
+    }
+
+    Ok(())
+}
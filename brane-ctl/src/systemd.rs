@@ -0,0 +1,322 @@
+//  SYSTEMD.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 19:12:40
+//  Last edited:
+//    15 Oct 2026, 19:12:40
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the systemd deployment target, which runs the services of
+//!   a node as binaries directly on the host instead of as containers.
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, PrivateOrExternalService};
+use console::style;
+use log::{debug, info, warn};
+use specifications::address::Address;
+
+pub use crate::errors::SystemdError as Error;
+
+
+/***** HELPER STRUCTS *****/
+/// Describes a single service for which we generate a unit.
+#[derive(Clone, Debug)]
+struct UnitService {
+    /// The short identifier of the service (e.g., `api`).
+    id:      &'static str,
+    /// The name of the service, which we use as the unit name.
+    name:    String,
+    /// The address on which other services on this node reach this service.
+    address: Address,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the name of the systemd target that groups all services of the given node.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// The name of the target, including the `.target` suffix.
+fn target_name(node_config: &NodeConfig) -> String {
+    match &node_config.node {
+        NodeSpecificConfig::Central(_) => "brane-central.target".into(),
+        NodeSpecificConfig::Worker(_) => "brane-worker.target".into(),
+        NodeSpecificConfig::Proxy(_) => "brane-proxy.target".into(),
+    }
+}
+
+/// Collects the services that are hosted on the node described by the given node config.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// A list of [`UnitService`]s, one for every service hosted by this node.
+fn collect_services(node_config: &NodeConfig) -> Vec<UnitService> {
+    // Only include the proxy if we host it ourselves
+    let prx = |prx: &PrivateOrExternalService| -> Option<UnitService> {
+        if let PrivateOrExternalService::Private(prx) = prx {
+            Some(UnitService { id: "prx", name: prx.name.clone(), address: prx.address.clone() })
+        } else {
+            None
+        }
+    };
+
+    match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            let svcs = &central.services;
+            let mut res: Vec<UnitService> = vec![
+                UnitService { id: "api", name: svcs.api.name.clone(), address: svcs.api.address.clone() },
+                UnitService { id: "drv", name: svcs.drv.name.clone(), address: svcs.drv.address.clone() },
+                UnitService { id: "plr", name: svcs.plr.name.clone(), address: svcs.plr.address.clone() },
+            ];
+            res.extend(prx(&svcs.prx));
+            res
+        },
+
+        NodeSpecificConfig::Worker(worker) => {
+            let svcs = &worker.services;
+            let mut res: Vec<UnitService> = vec![
+                UnitService { id: "reg", name: svcs.reg.name.clone(), address: svcs.reg.address.clone() },
+                UnitService { id: "job", name: svcs.job.name.clone(), address: svcs.job.address.clone() },
+                UnitService { id: "chk", name: svcs.chk.name.clone(), address: svcs.chk.address.clone() },
+            ];
+            res.extend(prx(&svcs.prx));
+            res
+        },
+
+        NodeSpecificConfig::Proxy(proxy) => {
+            let prx = &proxy.services.prx;
+            vec![UnitService { id: "prx", name: prx.name.clone(), address: prx.address.clone() }]
+        },
+    }
+}
+
+/// Generates the unit file for a single service.
+///
+/// # Arguments
+/// - `svc`: The [`UnitService`] to generate the unit for.
+/// - `target`: The name of the target that groups the node's services.
+/// - `after`: Any other units that this service should be started after.
+/// - `bin_dir`: The (absolute) directory where the service binaries live.
+/// - `node_config_path`: The (absolute) path to the node config file.
+/// - `user`: The user to run the service as, if not root.
+///
+/// # Returns
+/// The contents of the unit file.
+fn service_unit(svc: &UnitService, target: &str, after: &[String], bin_dir: &Path, node_config_path: &Path, user: Option<&str>) -> String {
+    let mut res: String = format!(
+        "[Unit]\nDescription=Brane {} service ({})\nWants=network-online.target\nAfter=network-online.target{}\nPartOf={}\n\n",
+        svc.id,
+        svc.name,
+        after.iter().map(|a| format!(" {a}")).collect::<String>(),
+        target
+    );
+    res.push_str("[Service]\nType=simple\n");
+    res.push_str(&format!("ExecStart={}\n", bin_dir.join(format!("brane-{}", svc.id)).display()));
+    res.push_str(&format!("Environment=NODE_CONFIG_PATH={}\n", node_config_path.display()));
+    if let Some(user) = user {
+        res.push_str(&format!("User={user}\n"));
+    }
+    match svc.id {
+        // The job service launches containers for the tasks it runs, so it needs the Docker socket
+        "job" => res.push_str("SupplementaryGroups=docker\n"),
+        // The registry reloads its certificates on SIGHUP
+        "reg" => res.push_str("ExecReload=/bin/kill -HUP $MAINPID\n"),
+        _ => {},
+    }
+    res.push_str("Restart=always\nRestartSec=5\n\n");
+    res.push_str(&format!("[Install]\nWantedBy={target}\n"));
+    res
+}
+
+/// Generates the unit file for the target that groups all services of a node.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+/// - `services`: The [`UnitService`]s grouped by this target.
+///
+/// # Returns
+/// The contents of the target file.
+fn target_unit(node_config: &NodeConfig, services: &[UnitService]) -> String {
+    format!(
+        "[Unit]\nDescription=Brane {} node\nWants={}\n\n[Install]\nWantedBy=multi-user.target\n",
+        node_config.node.kind(),
+        services.iter().map(|svc| format!("{}.service", svc.name)).collect::<Vec<String>>().join(" ")
+    )
+}
+
+/// Runs `systemctl` with the given action on the target of the given node.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that determines which target to act upon.
+/// - `action`: The `systemctl` action to perform (e.g., `start`).
+///
+/// # Errors
+/// This function errors if we failed to load the node config or if `systemctl` failed.
+fn systemctl(node_config_path: PathBuf, action: &'static str) -> Result<(), Error> {
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+    let target: String = target_name(&node_config);
+
+    // Run the command
+    let mut cmd: Command = Command::new("systemctl");
+    cmd.args([action, target.as_str()]);
+    cmd.stdin(Stdio::inherit());
+    cmd.stdout(Stdio::inherit());
+    cmd.stderr(Stdio::inherit());
+    println!("Running 'systemctl' {} on {}...", style(action).bold().green(), style(&target).bold());
+    debug!("Command: {:?}", cmd);
+    let output: Output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) => return Err(Error::JobLaunchError { command: cmd, err }),
+    };
+    if !output.status.success() {
+        return Err(Error::JobFailure { command: cmd, status: output.status });
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates systemd unit files that run the services of the node directly on the host.
+///
+/// One `.service` unit is generated per hosted service (named after the service's name in the node config), plus a `brane-<kind>.target`
+/// that groups them.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file describing the node.
+/// - `fix_dirs`: Whether to create the output directory if it does not exist.
+/// - `path`: The directory to write the unit files to.
+/// - `bin_dir`: The directory where the `brane-<svc>` binaries are installed.
+/// - `user`: If given, the services are run as this user instead of root.
+///
+/// # Errors
+/// This function errors if we failed to load the node config, resolve any path or write any unit file.
+pub fn generate(
+    node_config_path: impl Into<PathBuf>,
+    fix_dirs: bool,
+    path: impl Into<PathBuf>,
+    bin_dir: impl Into<PathBuf>,
+    user: Option<String>,
+) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    let path: PathBuf = path.into();
+    let bin_dir: PathBuf = bin_dir.into();
+    info!("Generating systemd units for node defined by '{}' in '{}'...", node_config_path.display(), path.display());
+
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
+        Ok(config) => config,
+        Err(err) => return Err(Error::NodeConfigLoadError { err }),
+    };
+
+    // Units are started from the root, so make the paths absolute
+    let node_config_path: PathBuf = match fs::canonicalize(&node_config_path) {
+        Ok(path) => path,
+        Err(err) => return Err(Error::CanonicalizeError { what: "node config", path: node_config_path, err }),
+    };
+    let bin_dir: PathBuf = match fs::canonicalize(&bin_dir) {
+        Ok(path) => path,
+        Err(err) => return Err(Error::CanonicalizeError { what: "binary directory", path: bin_dir, err }),
+    };
+
+    // Ensure the output directory exists
+    if !path.exists() {
+        if !fix_dirs {
+            return Err(Error::DirNotFound { path });
+        }
+        if let Err(err) = fs::create_dir_all(&path) {
+            return Err(Error::DirCreateError { path, err });
+        }
+    }
+    if !path.is_dir() {
+        return Err(Error::DirNotADir { path });
+    }
+
+    // Warn about things that the containers used to do for us
+    let services: Vec<UnitService> = collect_services(&node_config);
+    for svc in &services {
+        if svc.address.domain() == svc.name {
+            warn!(
+                "Service '{}' is reachable at '{}', which only resolves within the Docker network; consider changing it to 'localhost' in '{}'",
+                svc.id,
+                svc.address,
+                node_config_path.display()
+            );
+        }
+    }
+    if let NodeSpecificConfig::Central(_) = &node_config.node {
+        warn!("The central node depends on Scylla, which is not managed by the generated units; make sure it runs on the host separately");
+    }
+
+    // Write the service units
+    let target: String = target_name(&node_config);
+    let prx: Option<String> = services.iter().find(|svc| svc.id == "prx").map(|svc| format!("{}.service", svc.name));
+    for svc in &services {
+        let after: Vec<String> = if svc.id != "prx" { prx.iter().cloned().collect() } else { vec![] };
+        let unit_path: PathBuf = path.join(format!("{}.service", svc.name));
+        debug!("Writing unit '{}'...", unit_path.display());
+        if let Err(err) = fs::write(&unit_path, service_unit(svc, &target, &after, &bin_dir, &node_config_path, user.as_deref())) {
+            return Err(Error::FileWriteError { path: unit_path, err });
+        }
+    }
+
+    // Write the target
+    let target_path: PathBuf = path.join(&target);
+    debug!("Writing target '{}'...", target_path.display());
+    if let Err(err) = fs::write(&target_path, target_unit(&node_config, &services)) {
+        return Err(Error::FileWriteError { path: target_path, err });
+    }
+
+    // Done
+    println!(
+        "Successfully generated {} systemd units in {}; run 'systemctl daemon-reload' and 'systemctl enable --now {}' to start them",
+        services.len() + 1,
+        style(path.display()).bold().green(),
+        target
+    );
+    Ok(())
+}
+
+
+
+/// Starts the services of the local node through systemd.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that determines which target to start.
+///
+/// # Errors
+/// This function errors if we failed to load the node config or if `systemctl` failed.
+#[inline]
+pub fn start(node_config_path: impl Into<PathBuf>) -> Result<(), Error> { systemctl(node_config_path.into(), "start") }
+
+/// Stops the services of the local node through systemd.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that determines which target to stop.
+///
+/// # Errors
+/// This function errors if we failed to load the node config or if `systemctl` failed.
+#[inline]
+pub fn stop(node_config_path: impl Into<PathBuf>) -> Result<(), Error> { systemctl(node_config_path.into(), "stop") }
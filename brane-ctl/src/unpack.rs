@@ -4,7 +4,7 @@
 //  Created:
 //    28 Mar 2023, 10:26:05
 //  Last edited:
-//    28 Mar 2023, 10:58:36
+//    16 Oct 2026, 01:02:43
 //  Auto updated?
 //    Yes
 //
@@ -12,6 +12,7 @@
 //!   Implements functions that can unpack internal files.
 //
 
+use std::borrow::Cow;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -20,7 +21,9 @@ use brane_cfg::node::{NodeConfig, NodeKind};
 use log::{debug, info};
 
 pub use crate::errors::UnpackError as Error;
+use crate::k8s::{self, K8sOpts};
 use crate::spec::ResolvableNodeKind;
+use crate::{dashboard, monitoring};
 
 
 /***** LIBRARY *****/
@@ -31,10 +34,20 @@ use crate::spec::ResolvableNodeKind;
 /// - `fix_dirs`: Whether to fix missing directories.
 /// - `path`: The path to write the new file to.
 /// - `node_config_path`: The path to the `node.yml` file.
+/// - `monitoring`: If true, adds the Prometheus and Grafana services of the optional monitoring stack to the unpacked file.
+/// - `dashboard`: If true, adds the optional dashboard service (`brane-web`) to the unpacked file. Only valid for central nodes.
 ///
 /// # Errors
-/// This function errors if we failed to read the `node.yml` file, or failed to write the builtin one.
-pub fn compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>, node_config_path: impl AsRef<Path>) -> Result<(), Error> {
+/// This function errors if we failed to read the `node.yml` file, failed to write the builtin one or if the dashboard is requested for a
+/// non-central node.
+pub fn compose(
+    kind: ResolvableNodeKind,
+    fix_dirs: bool,
+    path: impl AsRef<Path>,
+    node_config_path: impl AsRef<Path>,
+    monitoring: bool,
+    dashboard: bool,
+) -> Result<(), Error> {
     let path: &Path = path.as_ref();
     let node_config_path: &Path = node_config_path.as_ref();
     info!("Extracting Docker Compose file for '{}' to '{}'", kind, path.display());
@@ -58,6 +71,10 @@ pub fn compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>,
         },
     };
 
+    if dashboard && kind != NodeKind::Central {
+        return Err(Error::DashboardNotCentral { kind });
+    }
+
     // Resolve the path
     let path: PathBuf = path.to_string_lossy().replace("$NODE", &kind.to_string()).into();
 
@@ -90,12 +107,97 @@ pub fn compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>,
         NodeKind::Proxy => include_str!("../../docker-compose-proxy.yml"),
     };
 
+    // Add the monitoring stack if asked
+    let compose: Cow<str> = if monitoring {
+        debug!("Adding monitoring stack...");
+        Cow::Owned(monitoring::add_to_compose(compose))
+    } else {
+        Cow::Borrowed(compose)
+    };
+
+    // Add the dashboard if asked
+    let compose: Cow<str> = if dashboard {
+        debug!("Adding dashboard...");
+        Cow::Owned(dashboard::add_to_compose(&compose))
+    } else {
+        compose
+    };
+
     // Attempt to write it
     debug!("Writing file to '{}'...", path.display());
-    if let Err(err) = fs::write(&path, compose) {
+    if let Err(err) = fs::write(&path, compose.as_bytes()) {
         return Err(Error::FileWriteError { what: "Docker Compose", path, err });
     }
 
     // OK, done
     Ok(())
 }
+
+
+
+/// Renders Kubernetes manifests (or a Helm chart) for the node defined by the given `node.yml` file.
+///
+/// # Arguments
+/// - `fix_dirs`: Whether to fix missing directories.
+/// - `path`: The path of the directory to write the manifests to. Any '$NODE' is replaced with the kind of the node.
+/// - `node_config_path`: The path to the `node.yml` file.
+/// - `opts`: The [`K8sOpts`] that determine how to render the manifests.
+///
+/// # Errors
+/// This function errors if we failed to read the `node.yml` file (or any file it references), or failed to write the manifests.
+pub fn k8s(fix_dirs: bool, path: impl AsRef<Path>, node_config_path: impl AsRef<Path>, opts: K8sOpts) -> Result<(), Error> {
+    let path: &Path = path.as_ref();
+    let node_config_path: &Path = node_config_path.as_ref();
+    info!("Rendering Kubernetes {} for '{}' to '{}'", if opts.helm { "chart" } else { "manifests" }, node_config_path.display(), path.display());
+
+    // Load the node config file
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            return Err(Error::NodeConfigError { err });
+        },
+    };
+
+    // Resolve the path
+    let path: PathBuf = path.to_string_lossy().replace("$NODE", &node_config.node.kind().to_string()).into();
+
+    // Make sure the target directory exists
+    if !path.exists() {
+        if fix_dirs {
+            if let Err(err) = fs::create_dir_all(&path) {
+                return Err(Error::TargetDirCreateError { path, err });
+            }
+        } else {
+            return Err(Error::TargetDirNotFound { path });
+        }
+    }
+    if !path.is_dir() {
+        return Err(Error::TargetDirNotADir { path });
+    }
+
+    // Render the files
+    let files: Vec<(PathBuf, String)> = match k8s::render(node_config_path, &node_config, &opts) {
+        Ok(files) => files,
+        Err(err) => return Err(Error::K8sRenderError { err }),
+    };
+
+    // Write them
+    for (file, contents) in files {
+        let file: PathBuf = path.join(file);
+        if let Some(parent) = file.parent() {
+            if !parent.exists() {
+                if let Err(err) = fs::create_dir_all(parent) {
+                    return Err(Error::TargetDirCreateError { path: parent.into(), err });
+                }
+            }
+        }
+        debug!("Writing file to '{}'...", file.display());
+        if let Err(err) = fs::write(&file, contents) {
+            return Err(Error::FileWriteError { what: "Kubernetes manifest", path: file, err });
+        }
+    }
+
+    // OK, done
+    Ok(())
+}
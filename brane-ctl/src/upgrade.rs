@@ -4,7 +4,7 @@
 //  Created:
 //    03 Jul 2023, 13:01:31
 //  Last edited:
-//    07 Mar 2024, 09:54:40
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -316,7 +316,7 @@ pub fn node(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
         CentralConfig, CentralPaths, CentralServices, NodeConfig, NodeSpecificConfig, PrivateOrExternalService, PrivateService, PublicService,
         WorkerConfig, WorkerPaths, WorkerServices,
     };
-    use brane_cfg::proxy::{ForwardConfig, ProxyConfig, ProxyProtocol};
+    use brane_cfg::proxy::{DestinationRules, ForwardConfig, ProxyConfig, ProxyProtocol};
     use specifications::address::Address;
     use v1_0_0::node as v1_0_0;
 
@@ -368,7 +368,8 @@ pub fn node(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
                     incoming: HashMap::new(),
                     forward: cfg
                         .proxy
-                        .map(|a| ForwardConfig { address: Address::from_str(&a.to_string()).unwrap(), protocol: ProxyProtocol::Socks6 }),
+                        .map(|a| ForwardConfig { address: Address::from_str(&a.to_string()).unwrap(), protocol: ProxyProtocol::Socks6, credentials: None }),
+                    destinations: DestinationRules::default(),
                 };
                 let proxy_cfg: String = match serde_yaml::to_string(&proxy_cfg) {
                     Ok(cfg) => cfg,
@@ -469,6 +470,13 @@ pub fn node(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
                                 bind:    cfg.ports.prx,
                             }),
                         },
+
+                        max_concurrent_tasks: None,
+                        task_timeout: None,
+                        container_pool: None,
+                        transfer_retry: Default::default(),
+                        egress_approval: None,
+                        policy_expert_token: None,
                     }),
                 };
 
@@ -480,7 +488,7 @@ pub fn node(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
                 };
 
                 // Write to the config
-                Ok(NodeConfig { namespace, hostnames: cfg.hosts, node })
+                Ok(NodeConfig { namespace, hostnames: cfg.hosts, node, tracing: None, events: None, key_passphrase: None })
             }))
         }),
     )];
@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 14:15:54
 //  Last edited:
-//    28 Nov 2022, 13:23:15
+//    15 Oct 2026, 17:58:23
 //  Auto updated?
 //    Yes
 //
@@ -15,6 +15,24 @@
 use std::ffi::OsStr;
 use std::path::{Component, Path, PathBuf};
 
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, PrivateOrExternalService};
+use console::style;
+
+
+/***** HELPER STRUCTS *****/
+/// Defines a single line in a diff.
+enum DiffLine<'s> {
+    /// The line is the same in both files.
+    Keep(&'s str),
+    /// The line only occurs in the old file.
+    Remove(&'s str),
+    /// The line only occurs in the new file.
+    Add(&'s str),
+}
+
+
+
+
 
 /***** LIBRARY *****/
 /// Function that resolves the given config path.
@@ -42,3 +60,98 @@ pub fn resolve_config_path(path: PathBuf, config_path: impl AsRef<Path>) -> Path
     // Done
     result
 }
+
+
+
+/// Returns the services hosted by the node described by the given node config.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] describing the node.
+///
+/// # Returns
+/// A list of pairs of the short identifier of every service (e.g., `api`) and the name of its container. External services (e.g., a proxy hosted elsewhere) are omitted.
+pub fn node_services(node_config: &NodeConfig) -> Vec<(&'static str, String)> {
+    // Only include the proxy if we host it ourselves
+    let prx = |prx: &PrivateOrExternalService| -> Option<(&'static str, String)> {
+        if let PrivateOrExternalService::Private(prx) = prx { Some(("prx", prx.name.clone())) } else { None }
+    };
+
+    match &node_config.node {
+        NodeSpecificConfig::Central(central) => {
+            let mut res: Vec<(&'static str, String)> =
+                vec![("api", central.services.api.name.clone()), ("drv", central.services.drv.name.clone()), ("plr", central.services.plr.name.clone())];
+            res.extend(prx(&central.services.prx));
+            res
+        },
+
+        NodeSpecificConfig::Worker(worker) => {
+            let mut res: Vec<(&'static str, String)> =
+                vec![("reg", worker.services.reg.name.clone()), ("job", worker.services.job.name.clone()), ("chk", worker.services.chk.name.clone())];
+            res.extend(prx(&worker.services.prx));
+            res
+        },
+
+        NodeSpecificConfig::Proxy(proxy) => vec![("prx", proxy.services.prx.name.clone())],
+    }
+}
+
+
+
+/// Computes a line-based diff between two files.
+///
+/// # Arguments
+/// - `old`: The old file.
+/// - `new`: The new file.
+/// - `context`: The number of unchanged lines to show around every change.
+///
+/// # Returns
+/// The diff, formatted for printing to the terminal. Only changed lines and the `context` lines around them are included.
+pub fn diff_lines(old: &str, new: &str, context: usize) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Compute the longest common subsequence of every suffix pair
+    let mut lcs: Vec<Vec<usize>> = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    // Walk it to find the edits
+    let mut lines: Vec<DiffLine> = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j): (usize, usize) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Keep(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Remove(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Add(new[j]));
+            j += 1;
+        }
+    }
+
+    // Render only the lines near a change
+    let changes: Vec<usize> = lines.iter().enumerate().filter(|(_, line)| !matches!(line, DiffLine::Keep(_))).map(|(i, _)| i).collect();
+    let mut res: String = String::new();
+    let mut last: Option<usize> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if !changes.iter().any(|c| c.abs_diff(i) <= context) {
+            continue;
+        }
+        if last.map(|last| i > last + 1).unwrap_or(false) {
+            res.push_str(&format!("{}\n", style("   ...").dim()));
+        }
+        last = Some(i);
+        match line {
+            DiffLine::Keep(line) => res.push_str(&format!("     {line}\n")),
+            DiffLine::Remove(line) => res.push_str(&format!("   {}\n", style(format!("- {line}")).red())),
+            DiffLine::Add(line) => res.push_str(&format!("   {}\n", style(format!("+ {line}")).green())),
+        }
+    }
+    res
+}
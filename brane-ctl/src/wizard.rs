@@ -4,7 +4,7 @@
 //  Created:
 //    01 Jun 2023, 12:43:20
 //  Last edited:
-//    07 Mar 2024, 09:54:57
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -19,19 +19,31 @@ use std::error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::{self, File};
 use std::io::Write as _;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs as _};
+use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use brane_cfg::backend::{BackendFile, Credentials, SandboxConfig};
 use brane_cfg::info::Info;
-use brane_cfg::node::{self, NodeConfig, NodeKind, NodeSpecificConfig};
-use brane_cfg::proxy::{ForwardConfig, ProxyConfig, ProxyProtocol};
+use brane_cfg::node::{self, NodeConfig, NodeKind, NodeSpecificConfig, PrivateOrExternalService};
+use brane_cfg::proxy::{DestinationRules, ForwardConfig, ProxyConfig, ProxyProtocol};
 use brane_shr::input::{confirm, input, input_map, input_path, select, FileHistory};
 use console::style;
 use dirs_2::config_dir;
 use enum_debug::EnumDebug as _;
+use jsonwebtoken::jwk::KeyAlgorithm;
 use log::{debug, info};
 use specifications::address::Address;
+use specifications::package::Capability;
 
-use crate::spec::InclusiveRange;
+use crate::generate;
+use crate::spec::{GenerateBackendSubcommand, GenerateNodeSubcommand, InclusiveRange, Pair};
+
+
+/***** CONSTANTS *****/
+/// The time we wait for a remote address to accept a connection during the self-test.
+const SELF_TEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 
 /***** HELPER MACROS *****/
@@ -80,6 +92,13 @@ pub enum Error {
     ProxyConfigQuery { err: Box<Self> },
     /// Failed to write the proxy config file.
     ProxyConfigWrite { err: Box<Self> },
+    /// Failed to query the user for the backend config file.
+    BackendConfigQuery { err: Box<Self> },
+
+    /// Failed to generate a configuration file using the `branectl generate` machinery.
+    Generate { what: &'static str, err: Box<crate::errors::GenerateError> },
+    /// Failed to load a configuration file we just generated.
+    ConfigLoad { path: PathBuf, err: brane_cfg::info::YamlError },
 
     /// Failed to create a new file.
     ConfigCreate { path: PathBuf, err: std::io::Error },
@@ -102,6 +121,10 @@ impl Display for Error {
             NodeConfigWrite { .. } => write!(f, "Failed to write node config file"),
             ProxyConfigQuery { .. } => write!(f, "Failed to query proxy service configuration"),
             ProxyConfigWrite { .. } => write!(f, "Failed to write proxy service config file"),
+            BackendConfigQuery { .. } => write!(f, "Failed to query backend configuration"),
+
+            Generate { what, .. } => write!(f, "Failed to generate {what} file"),
+            ConfigLoad { path, .. } => write!(f, "Failed to load generated config file '{}'", path.display()),
 
             ConfigCreate { path, .. } => write!(f, "Failed to create config file '{}'", path.display()),
             ConfigSerialize { path, .. } => write!(f, "Failed to serialize config to '{}'", path.display()),
@@ -119,6 +142,10 @@ impl error::Error for Error {
             NodeConfigWrite { err } => Some(err),
             ProxyConfigQuery { err } => Some(err),
             ProxyConfigWrite { err } => Some(err),
+            BackendConfigQuery { err } => Some(err),
+
+            Generate { err, .. } => Some(err),
+            ConfigLoad { err, .. } => Some(err),
 
             ConfigCreate { err, .. } => Some(err),
             ConfigSerialize { err, .. } => Some(err),
@@ -236,6 +263,135 @@ where
     Ok(())
 }
 
+/// Prints the result of a single self-test check.
+///
+/// # Arguments
+/// - `what`: A description of what we checked.
+/// - `res`: Either [`Ok`] if the check succeeded, or [`Err`] with the reason why it failed.
+///
+/// # Returns
+/// Whether the check succeeded.
+fn report_check(what: impl Display, res: Result<(), String>) -> bool {
+    match res {
+        Ok(()) => {
+            println!(" {} {}", style("OK  ").bold().green(), what);
+            true
+        },
+        Err(reason) => {
+            println!(" {} {} ({})", style("FAIL").bold().red(), what, reason);
+            false
+        },
+    }
+}
+
+/// Attempts to open a TCP connection (or Unix socket connection) to the given address.
+///
+/// # Arguments
+/// - `address`: The [`Address`] to connect to. Any scheme in its hostname is ignored.
+///
+/// # Errors
+/// This function errors with a human-readable reason if the address could not be resolved or connected to.
+fn check_reachable(address: &Address) -> Result<(), String> {
+    if let Some(path) = address.unix_path() {
+        return UnixStream::connect(path).map(|_| ()).map_err(|err| err.to_string());
+    }
+    let domain = address.domain();
+    let host: &str = domain.split_once("://").map(|(_, host)| host).unwrap_or(&domain);
+    let host: &str = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+    let addrs: Vec<SocketAddr> = match (host, address.port()).to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(err) => return Err(format!("failed to resolve: {err}")),
+    };
+    let mut last_err: String = "no addresses found".into();
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, SELF_TEST_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(err) => last_err = err.to_string(),
+        }
+    }
+    Err(last_err)
+}
+
+/// Runs a few sanity checks on a freshly generated worker node to catch common mistakes early.
+///
+/// Failed checks are only reported, since the node may still be started once the problem is fixed (e.g., when the central node is not up yet).
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] of the worker node to check.
+///
+/// # Errors
+/// This function errors if we failed to load the backend file referenced by the node config.
+fn self_test_worker(node_config: &NodeConfig) -> Result<(), Error> {
+    let worker: &node::WorkerConfig = match &node_config.node {
+        NodeSpecificConfig::Worker(worker) => worker,
+        _ => return Ok(()),
+    };
+    let mut ok: bool = true;
+
+    // The ports we are about to bind must be free
+    let mut ports: Vec<(&str, u16)> = vec![
+        ("registry", worker.services.reg.bind.port()),
+        ("delegate", worker.services.job.bind.port()),
+        ("checker", worker.services.chk.bind.port()),
+    ];
+    match &worker.services.prx {
+        PrivateOrExternalService::Private(prx) => ports.push(("proxy", prx.bind.port())),
+        PrivateOrExternalService::External(prx) => {
+            ok &= report_check(format!("External proxy service at '{}' is reachable", prx.address), check_reachable(&prx.address));
+        },
+    }
+    for (what, port) in ports {
+        ok &= report_check(
+            format!("Port {port} for the {what} service is free"),
+            TcpListener::bind(("0.0.0.0", port)).map(|_| ()).map_err(|err| err.to_string()),
+        );
+    }
+
+    // The backend must be there
+    let backend: BackendFile = match BackendFile::from_path(&worker.paths.backend) {
+        Ok(backend) => backend,
+        Err(err) => return Err(Error::ConfigLoad { path: worker.paths.backend.clone(), err }),
+    };
+    match &backend.method {
+        Credentials::Local { path, .. } => {
+            let socket: PathBuf = path.clone().unwrap_or_else(|| "/var/run/docker.sock".into());
+            ok &= report_check(
+                format!("Docker socket '{}' exists", socket.display()),
+                if socket.exists() { Ok(()) } else { Err("not found".into()) },
+            );
+        },
+        Credentials::Kubernetes { registry_address, config, .. } => {
+            ok &= report_check(
+                format!("Kubernetes config '{}' exists", config.display()),
+                if config.exists() { Ok(()) } else { Err("not found".into()) },
+            );
+            ok &= report_check(format!("Registry at '{registry_address}' is reachable"), check_reachable(registry_address));
+        },
+        Credentials::Ssh { address, .. } => {
+            ok &= report_check(format!("SSH host at '{address}' is reachable"), check_reachable(address));
+        },
+        Credentials::Slurm {} => {},
+    }
+
+    // Finally, the central nodes of the use-cases must be reachable
+    let mut usecases: Vec<(&String, &node::WorkerUsecase)> = worker.usecases.iter().collect();
+    usecases.sort_by_key(|(name, _)| *name);
+    for (name, usecase) in usecases {
+        ok &= report_check(format!("Central node of use-case '{}' at '{}' is reachable", name, usecase.api), check_reachable(&usecase.api));
+    }
+
+    println!();
+    if ok {
+        println!("{}", style("All checks passed.").bold().green());
+    } else {
+        println!(
+            "{}",
+            style("Some checks failed; you can still start the node, but it may not work until the problems above are resolved.").bold().yellow()
+        );
+    }
+    Ok(())
+}
+
 
 
 
@@ -312,7 +468,7 @@ pub fn query_proxy_config() -> Result<ProxyConfig, Error> {
             };
 
         // Construct the config
-        Some(ForwardConfig { address, protocol })
+        Some(ForwardConfig { address, protocol, credentials: None })
     } else {
         None
     };
@@ -320,7 +476,7 @@ pub fn query_proxy_config() -> Result<ProxyConfig, Error> {
     println!();
 
     // Construct the ProxyConfig to return it
-    Ok(ProxyConfig { outgoing_range: range.0, incoming, forward })
+    Ok(ProxyConfig { outgoing_range: range.0, incoming, forward, destinations: DestinationRules::default() })
 }
 
 /// Queries the user for the node file configuration.
@@ -346,6 +502,212 @@ pub fn query_proxy_node_config() -> Result<NodeConfig, Error> {
                 },
             },
         }),
+        tracing:   None,
+        events:    None,
+
+        key_passphrase: None,
+    })
+}
+
+
+
+
+
+/// Queries the user for the address of a proxy service hosted elsewhere.
+///
+/// # Returns
+/// The [`Address`] of the proxy service.
+///
+/// # Errors
+/// This function may error if we failed to query the user.
+pub fn query_external_proxy() -> Result<Address, Error> {
+    match input("address", "P1. Enter the address (including port) of the proxy service to use", None::<Address>, Some(hist!("prx-external.hist"))) {
+        Ok(address) => Ok(address),
+        Err(err) => Err(Error::Input { what: "external proxy address", err }),
+    }
+}
+
+/// Queries the user for the backend configuration of a worker node.
+///
+/// # Returns
+/// A tuple of the capabilities of the backend, whether to hash containers and the kind of backend, which can be passed to [`generate::backend()`].
+///
+/// # Errors
+/// This function may error if we failed to query the user.
+pub fn query_backend_config() -> Result<(Vec<Capability>, bool, GenerateBackendSubcommand), Error> {
+    // Query the kind of backend first
+    let kind: &str = match select("B1. Select how tasks are executed on this node", ["docker", "kubernetes"], Some(0)) {
+        Ok(kind) => kind,
+        Err(err) => {
+            return Err(Error::Input { what: "backend kind", err });
+        },
+    };
+    debug!("Backend kind: {kind}");
+    println!();
+    let command: GenerateBackendSubcommand = if kind == "docker" {
+        let socket: PathBuf =
+            match input_path("B1a. Enter the path of the Docker socket", Some("/var/run/docker.sock"), Some(hist!("bck-socket.hist"))) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    return Err(Error::Input { what: "Docker socket path", err });
+                },
+            };
+        GenerateBackendSubcommand::Local { socket, client_version: None }
+    } else {
+        let registry_address: Address = match input(
+            "address",
+            "B1a. Enter the address (including port) of the registry that the cluster pulls images from",
+            None::<Address>,
+            Some(hist!("bck-registry.hist")),
+        ) {
+            Ok(address) => address,
+            Err(err) => {
+                return Err(Error::Input { what: "registry address", err });
+            },
+        };
+        let config: PathBuf = match input_path(
+            "B1b. Enter the path of the Kubernetes config file",
+            Some(dirs_2::home_dir().unwrap_or_default().join(".kube").join("config")),
+            Some(hist!("bck-kubeconfig.hist")),
+        ) {
+            Ok(config) => config,
+            Err(err) => {
+                return Err(Error::Input { what: "Kubernetes config path", err });
+            },
+        };
+        GenerateBackendSubcommand::Kubernetes { registry_address, config: Some(config) }
+    };
+    println!();
+
+    // Query the capabilities
+    let gpu: bool = match confirm("B2. Does this backend provide access to a CUDA GPU?", Some(false)) {
+        Ok(gpu) => gpu,
+        Err(err) => {
+            return Err(Error::Input { what: "GPU capability", err });
+        },
+    };
+    let capabilities: Vec<Capability> = if gpu { vec![Capability::CudaGpu] } else { vec![] };
+    debug!("Capabilities: {capabilities:?}");
+    println!();
+
+    // Query whether to hash containers
+    let hash_containers: bool = match confirm("B3. Do you want to verify container hashes before running them?", Some(true)) {
+        Ok(hash) => hash,
+        Err(err) => {
+            return Err(Error::Input { what: "container hashing", err });
+        },
+    };
+    println!();
+
+    Ok((capabilities, hash_containers, command))
+}
+
+/// Queries the user for the worker-specific parts of the node file configuration.
+///
+/// # Arguments
+/// - `cfg_dir`: The directory where the other configuration files live.
+/// - `external_proxy`: The address of the proxy service to use if we do not host our own.
+///
+/// # Returns
+/// A [`GenerateNodeSubcommand::Worker`] that reflects the user's choices, which can be passed to [`generate::node()`].
+///
+/// # Errors
+/// This function may error if we failed to query the user.
+pub fn query_worker_node_config(cfg_dir: &Path, external_proxy: Option<Address>) -> Result<GenerateNodeSubcommand, Error> {
+    // Query the identity of the node
+    let location_id: String =
+        match input("location ID", "W1. Enter the location ID of this node", None::<String>, Some(hist!("wrk-location_id.hist"))) {
+            Ok(id) => id,
+            Err(err) => {
+                return Err(Error::Input { what: "location ID", err });
+            },
+        };
+    println!();
+    let hostname: String = match input(
+        "hostname",
+        "W2. Enter the hostname on which other nodes can reach this node",
+        None::<String>,
+        Some(hist!("wrk-hostname.hist")),
+    ) {
+        Ok(hostname) => hostname,
+        Err(err) => {
+            return Err(Error::Input { what: "hostname", err });
+        },
+    };
+    println!();
+
+    // Query the use-cases
+    let use_cases: HashMap<String, Address> = match input_map(
+        "use-case",
+        "address",
+        "W3.1. Enter the central node of a use-case as '<use-case>=<API address>' (or leave empty to specify none)",
+        "W3.%I. Enter an additional use-case as '<use-case>=<API address>' (or leave empty to finish)",
+        "=",
+        Some(hist!("wrk-use_cases.hist")),
+    ) {
+        Ok(use_cases) => use_cases,
+        Err(err) => {
+            return Err(Error::Input { what: "use-cases", err });
+        },
+    };
+    debug!("Use-cases:\n{:#?}", use_cases);
+    println!();
+
+    // Query the data paths
+    let mut paths: Vec<PathBuf> = Vec::with_capacity(5);
+    for (i, (what, default, hist)) in [
+        ("package", "./packages", "wrk-packages.hist"),
+        ("dataset", "./data", "wrk-data.hist"),
+        ("intermediate result", "./results", "wrk-results.hist"),
+        ("temporary dataset", "/tmp/data", "wrk-temp_data.hist"),
+        ("temporary result", "/tmp/results", "wrk-temp_results.hist"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let path: PathBuf = match input_path(
+            format!("W4.{}. Enter the path of the {} directory", i + 1, what),
+            Some(default),
+            Some(FileHistory::new(config_dir().unwrap().join("branectl").join("history").join(hist))),
+        ) {
+            Ok(path) => path,
+            Err(err) => {
+                return Err(Error::Input { what: "data path", err });
+            },
+        };
+        paths.push(path);
+    }
+    println!();
+    let [packages, data, results, temp_data, temp_results]: [PathBuf; 5] = paths.try_into().unwrap();
+
+    // The rest uses the defaults, except that we pin everything to the config directory
+    Ok(GenerateNodeSubcommand::Worker {
+        hostname,
+        location_id,
+        use_cases: use_cases.into_iter().map(|(name, address)| Pair(name, address)).collect(),
+        backend: cfg_dir.join("backend.yml"),
+        policy_database: cfg_dir.join("policies.db"),
+        policy_deliberation_secret: cfg_dir.join("policy_deliberation_secret.json"),
+        policy_expert_secret: cfg_dir.join("policy_expert_secret.json"),
+        policy_audit_log: None,
+        proxy: cfg_dir.join("proxy.yml"),
+        certs: cfg_dir.join("certs"),
+        packages,
+        data,
+        results,
+        temp_data,
+        temp_results,
+        external_proxy,
+        reg_name: "brane-reg-$LOCATION".into(),
+        job_name: "brane-job-$LOCATION".into(),
+        chk_name: "brane-chk-$LOCATION".into(),
+        prx_name: "brane-prx-$LOCATION".into(),
+        reg_port: 50051,
+        job_port: 50052,
+        chk_port: 50053,
+        prx_port: 50050,
+        max_concurrent_tasks: None,
+        task_timeout: None,
     })
 }
 
@@ -360,7 +722,7 @@ pub fn query_proxy_node_config() -> Result<NodeConfig, Error> {
 ///
 /// # Errors
 /// This function may error if any of the wizard steps fail.
-pub fn setup() -> Result<(), Error> {
+pub async fn setup() -> Result<(), Error> {
     info!("Running wizard to setup a new node...");
 
     // Let us setup the history structure
@@ -440,7 +802,111 @@ pub fn setup() -> Result<(), Error> {
     match kind {
         NodeKind::Central => {},
 
-        NodeKind::Worker => {},
+        NodeKind::Worker => {
+            println!(" - {}", style(config_dir.join("proxy.yml").display()).bold());
+            println!(" - {}", style(config_dir.join("backend.yml").display()).bold());
+            println!(" - {}", style(config_dir.join("policy_deliberation_secret.json").display()).bold());
+            println!(" - {}", style(config_dir.join("policy_expert_secret.json").display()).bold());
+            println!(" - {}", style(config_dir.join("policies.db").display()).bold());
+            println!(" - {}", style(path.join("node.yml").display()).bold());
+            println!();
+
+            // Decide whether we host our own proxy, and if so, generate its configuration
+            println!("=== proxy.yml ===");
+            let own_proxy: bool = match confirm("Do you want to host a proxy service on this node? (recommended)", Some(true)) {
+                Ok(own) => own,
+                Err(err) => {
+                    return Err(Error::Input { what: "proxy hosting confirmation", err });
+                },
+            };
+            let external_proxy: Option<Address> = if own_proxy {
+                let cfg: ProxyConfig = match query_proxy_config() {
+                    Ok(cfg) => cfg,
+                    Err(err) => {
+                        return Err(Error::ProxyConfigQuery { err: Box::new(err) });
+                    },
+                };
+                let proxy_path: PathBuf = config_dir.join("proxy.yml");
+                let url: &str = "https://wiki.enablingpersonalizedinterventions.nl/user-guide/config/admins/proxy.html";
+                if let Err(err) = write_config(cfg, proxy_path, url) {
+                    return Err(Error::ProxyConfigWrite { err: Box::new(err) });
+                }
+                None
+            } else {
+                match query_external_proxy() {
+                    Ok(address) => Some(address),
+                    Err(err) => {
+                        return Err(Error::ProxyConfigQuery { err: Box::new(err) });
+                    },
+                }
+            };
+            println!();
+
+            // Generate the backend file
+            println!("=== backend.yml ===");
+            let (capabilities, hash_containers, command): (Vec<Capability>, bool, GenerateBackendSubcommand) = match query_backend_config() {
+                Ok(res) => res,
+                Err(err) => {
+                    return Err(Error::BackendConfigQuery { err: Box::new(err) });
+                },
+            };
+            if let Err(err) =
+                generate::backend(true, config_dir.join("backend.yml"), capabilities, hash_containers, SandboxConfig::default(), command)
+            {
+                return Err(Error::Generate { what: "backend", err: Box::new(err) });
+            }
+            println!();
+
+            // Generate the policy secrets and database
+            println!("=== policy secrets ===");
+            for (name, id) in [("policy_deliberation_secret.json", "deliberation"), ("policy_expert_secret.json", "expert")] {
+                let secret_path: PathBuf = config_dir.join(name);
+                if secret_path.exists() {
+                    println!("Keeping existing {}", style(secret_path.display()).bold());
+                    continue;
+                }
+                if let Err(err) = generate::policy_secret(true, secret_path, id.into(), KeyAlgorithm::HS256) {
+                    return Err(Error::Generate { what: "policy secret", err: Box::new(err) });
+                }
+            }
+            let db_path: PathBuf = config_dir.join("policies.db");
+            if db_path.exists() {
+                println!("Keeping existing {}", style(db_path.display()).bold());
+            } else if let Err(err) = generate::policy_database(true, db_path, "main".into(), None).await {
+                return Err(Error::Generate { what: "policy database", err: Box::new(err) });
+            }
+            println!();
+
+            // Now we generate the node.yml file
+            println!("=== node.yml ===");
+            let command: GenerateNodeSubcommand = match query_worker_node_config(&config_dir, external_proxy) {
+                Ok(command) => command,
+                Err(err) => {
+                    return Err(Error::NodeConfigQuery { err: Box::new(err) });
+                },
+            };
+            let node_path: PathBuf = path.join("node.yml");
+            if let Err(err) = generate::node(&node_path, vec![], true, &config_dir, false, command) {
+                return Err(Error::Generate { what: "node", err: Box::new(err) });
+            }
+            println!();
+
+            // Finally, check whether the node stands a chance of working
+            println!("=== self-test ===");
+            let node: NodeConfig = match NodeConfig::from_path(&node_path) {
+                Ok(node) => node,
+                Err(err) => {
+                    return Err(Error::ConfigLoad { path: node_path, err });
+                },
+            };
+            self_test_worker(&node)?;
+            println!();
+            println!(
+                "Don't forget to generate the node's certificates (see {}) before starting it with {}.",
+                style("branectl generate certs").bold(),
+                style("branectl start worker").bold()
+            );
+        },
 
         NodeKind::Proxy => {
             println!(" - {}", style(config_dir.join("proxy.yml").display()).bold());
@@ -4,7 +4,7 @@
 //  Created:
 //    06 Feb 2024, 11:46:14
 //  Last edited:
-//    08 Feb 2024, 14:39:13
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -339,7 +339,7 @@ fn traverse_and_request(
         log::trace!("Spawning requests in {:?}", edge.variant());
         use Edge::*;
         match edge {
-            Node { task: _, locs: _, at, input, result: _, metadata: _, next } => {
+            Node { task: _, locs: _, at, input, result: _, metadata: _, cache: _, next } => {
                 // Get the checker that is scheduled to execute the node
                 let at: &String = match at {
                     Some(at) => at,
@@ -0,0 +1,239 @@
+//  CHECKPOINT.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:21:18
+//  Last edited:
+//    15 Oct 2026, 22:21:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements checkpoints of running workflows, such that a workflow
+//!   that was interrupted (e.g., because the driver restarted or a task
+//!   failed) can be resumed from the last completed task.
+//!
+//!   Every checkpoint is a directory `<CHECKPOINT_DIR>/<WORKFLOW_ID>`,
+//!   containing the submitted workflow (`workflow.json`) and a journal
+//!   of completed tasks (`journal.jsonl`). The checkpoint is removed once
+//!   the workflow completes successfully.
+//
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use brane_ast::locations::Location;
+use brane_exe::spec::TaskInfo;
+use brane_exe::FullValue;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::CheckpointError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the file with the submitted workflow in a checkpoint directory.
+const WORKFLOW_FILE: &str = "workflow.json";
+/// The name of the file with the completed tasks in a checkpoint directory.
+const JOURNAL_FILE: &str = "journal.jsonl";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Resolves the directory of the checkpoint of a workflow.
+///
+/// # Arguments
+/// - `root`: The directory in which the driver keeps its checkpoints.
+/// - `id`: The identifier of the workflow.
+///
+/// # Returns
+/// The path to the checkpoint directory.
+///
+/// # Errors
+/// This function errors if the identifier is not a valid workflow identifier, e.g., because it would escape the `root`.
+fn checkpoint_dir(root: &Path, id: &str) -> Result<PathBuf, Error> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::IllegalId { id: id.into() });
+    }
+    Ok(root.join(id))
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a single task that completed, as recorded in the journal.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JournalEntry {
+    /// Identifies the call of the task (see [`Checkpoint::task_key()`]).
+    pub key:      String,
+    /// The location where the task was executed.
+    pub location: Location,
+    /// The value that the task returned, if any.
+    pub value:    Option<FullValue>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Represents the checkpoint of a single running workflow.
+#[derive(Debug)]
+pub struct Checkpoint {
+    /// The directory of this checkpoint.
+    dir:       PathBuf,
+    /// The tasks completed by a previous run, by [`Checkpoint::task_key()`] and in the order they completed.
+    completed: Mutex<HashMap<String, VecDeque<JournalEntry>>>,
+    /// The journal to which we append newly completed tasks.
+    journal:   Mutex<File>,
+}
+
+impl Checkpoint {
+    /// Creates a new, empty checkpoint for a workflow that is just submitted.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which the driver keeps its checkpoints.
+    /// - `id`: The identifier of the workflow.
+    /// - `workflow`: The serialized workflow as submitted by the client.
+    ///
+    /// # Returns
+    /// A new Checkpoint without any completed tasks.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the checkpoint directory or its files.
+    pub fn create(root: impl AsRef<Path>, id: &str, workflow: &str) -> Result<Self, Error> {
+        let dir: PathBuf = checkpoint_dir(root.as_ref(), id)?;
+        debug!("Creating checkpoint '{}'...", dir.display());
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreateError { path: dir, err });
+        }
+
+        let workflow_path: PathBuf = dir.join(WORKFLOW_FILE);
+        if let Err(err) = fs::write(&workflow_path, workflow) {
+            return Err(Error::WorkflowWriteError { path: workflow_path, err });
+        }
+        let journal_path: PathBuf = dir.join(JOURNAL_FILE);
+        let journal: File = match File::create(&journal_path) {
+            Ok(journal) => journal,
+            Err(err) => return Err(Error::JournalOpenError { path: journal_path, err }),
+        };
+
+        Ok(Self { dir, completed: Mutex::new(HashMap::new()), journal: Mutex::new(journal) })
+    }
+
+    /// Loads the checkpoint of an interrupted workflow.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which the driver keeps its checkpoints.
+    /// - `id`: The identifier of the workflow to resume.
+    ///
+    /// # Returns
+    /// A tuple of the loaded Checkpoint and the serialized workflow that was originally submitted.
+    ///
+    /// # Errors
+    /// This function errors if there is no checkpoint for the given workflow or if we failed to read it.
+    pub fn load(root: impl AsRef<Path>, id: &str) -> Result<(Self, String), Error> {
+        let dir: PathBuf = checkpoint_dir(root.as_ref(), id)?;
+        debug!("Loading checkpoint '{}'...", dir.display());
+        let workflow_path: PathBuf = dir.join(WORKFLOW_FILE);
+        if !workflow_path.exists() {
+            return Err(Error::UnknownCheckpoint { id: id.into(), path: dir });
+        }
+        let workflow: String = match fs::read_to_string(&workflow_path) {
+            Ok(workflow) => workflow,
+            Err(err) => return Err(Error::WorkflowReadError { path: workflow_path, err }),
+        };
+
+        // Read the journal, and then keep it open to record the tasks of this run
+        let journal_path: PathBuf = dir.join(JOURNAL_FILE);
+        let journal: File = match OpenOptions::new().create(true).read(true).append(true).open(&journal_path) {
+            Ok(journal) => journal,
+            Err(err) => return Err(Error::JournalOpenError { path: journal_path, err }),
+        };
+        let mut completed: HashMap<String, VecDeque<JournalEntry>> = HashMap::new();
+        for (i, line) in BufReader::new(&journal).lines().enumerate() {
+            let line: String = match line {
+                Ok(line) => line,
+                Err(err) => return Err(Error::JournalReadError { path: journal_path, err }),
+            };
+            // A half-written line means we were interrupted while recording; that task simply runs again
+            let entry: JournalEntry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    warn!("Failed to parse line {} of checkpoint journal '{}': {} (skipping entry)", i + 1, journal_path.display(), err);
+                    continue;
+                },
+            };
+            completed.entry(entry.key.clone()).or_default().push_back(entry);
+        }
+        debug!("Checkpoint '{}' has {} completed task(s)", dir.display(), completed.values().map(VecDeque::len).sum::<usize>());
+
+        Ok((Self { dir, completed: Mutex::new(completed), journal: Mutex::new(journal) }, workflow))
+    }
+
+    /// Computes the key that identifies a call of a task in the journal.
+    ///
+    /// Calls are identified by where they occur in the workflow, which task they call, with which arguments and the name of their result.
+    /// Calls at the same place with the same arguments (e.g., in a loop) are told apart by the order in which they complete.
+    ///
+    /// # Arguments
+    /// - `info`: The [`TaskInfo`] describing the call.
+    ///
+    /// # Returns
+    /// The key of the call.
+    pub fn task_key(info: &TaskInfo) -> String {
+        let args: BTreeMap<&String, &FullValue> = info.args.iter().collect();
+        format!("{}/{}/{}/{}", info.pc, info.def, serde_json::to_string(&args).unwrap_or_default(), info.result.as_deref().unwrap_or(""))
+    }
+
+    /// Takes the next completed call with the given key from a previous run, if any.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the call (see [`Checkpoint::task_key()`]).
+    ///
+    /// # Returns
+    /// The [`JournalEntry`] of the completed call, or [`None`] if the previous run did not complete (this many calls of) it.
+    pub fn take(&self, key: &str) -> Option<JournalEntry> { self.completed.lock().unwrap().get_mut(key).and_then(VecDeque::pop_front) }
+
+    /// Records that a call has completed in this run.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the call (see [`Checkpoint::task_key()`]).
+    /// - `location`: The location where the call was executed.
+    /// - `value`: The value returned by the call, if any.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write the journal.
+    pub fn record(&self, key: String, location: Location, value: Option<FullValue>) -> Result<(), Error> {
+        let mut line: String = match serde_json::to_string(&JournalEntry { key, location, value }) {
+            Ok(line) => line,
+            Err(err) => return Err(Error::JournalSerializeError { err }),
+        };
+        line.push('\n');
+
+        let mut journal = self.journal.lock().unwrap();
+        if let Err(err) = journal.write_all(line.as_bytes()).and_then(|_| journal.sync_data()) {
+            return Err(Error::JournalWriteError { path: self.dir.join(JOURNAL_FILE), err });
+        }
+        Ok(())
+    }
+
+    /// Removes the checkpoint, because its workflow completed successfully.
+    ///
+    /// # Errors
+    /// This function errors if we failed to remove the checkpoint directory.
+    pub fn finish(&self) -> Result<(), Error> {
+        debug!("Removing checkpoint '{}'...", self.dir.display());
+        match fs::remove_dir_all(&self.dir) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::DirRemoveError { path: self.dir.clone(), err }),
+        }
+    }
+}
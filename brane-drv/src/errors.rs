@@ -4,7 +4,7 @@
 //  Created:
 //    01 Feb 2022, 16:13:53
 //  Last edited:
-//    08 Feb 2024, 16:49:47
+//    16 Oct 2026, 00:55:26
 //  Auto updated?
 //    Yes
 //
@@ -15,6 +15,7 @@
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::PathBuf;
+use std::time::Duration;
 
 
 /***** ERRORS *****/
@@ -25,6 +26,8 @@ pub enum RemoteVmError {
     PlanError { err: brane_tsk::errors::PlanError },
     /// Failed to run a workflow.
     ExecError { err: brane_exe::Error },
+    /// The workflow did not complete within its deadline.
+    DeadlineExceeded { deadline: Duration },
 
     /// The given node config was not for this type of node.
     IllegalNodeConfig { path: PathBuf, got: String },
@@ -40,6 +43,7 @@ impl Display for RemoteVmError {
         match self {
             PlanError { .. } => write!(f, "Failed to plan workflow"),
             ExecError { .. } => write!(f, "Failed to execute workflow"),
+            DeadlineExceeded { deadline } => write!(f, "Workflow did not complete within its deadline of {}s", deadline.as_secs()),
 
             IllegalNodeConfig { path, got } => {
                 write!(f, "Illegal node config kind in node config '{}'; expected Central, got {}", path.display(), got)
@@ -56,6 +60,7 @@ impl Error for RemoteVmError {
         match self {
             PlanError { err } => Some(err),
             ExecError { err } => Some(err),
+            DeadlineExceeded { .. } => None,
 
             IllegalNodeConfig { .. } => None,
             InfraFileLoad { err, .. } => Some(err),
@@ -63,3 +68,184 @@ impl Error for RemoteVmError {
         }
     }
 }
+
+
+
+/// Defines errors that relate to checkpointing workflows.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The given workflow identifier cannot be used as a checkpoint.
+    IllegalId { id: String },
+    /// Failed to create the directory of a checkpoint.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to remove the directory of a finished checkpoint.
+    DirRemoveError { path: PathBuf, err: std::io::Error },
+    /// There is no checkpoint for the given workflow.
+    UnknownCheckpoint { id: String, path: PathBuf },
+    /// Failed to write the workflow of a checkpoint.
+    WorkflowWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the workflow of a checkpoint.
+    WorkflowReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to open the journal of a checkpoint.
+    JournalOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the journal of a checkpoint.
+    JournalReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to serialize an entry for the journal of a checkpoint.
+    JournalSerializeError { err: serde_json::Error },
+    /// Failed to write an entry to the journal of a checkpoint.
+    JournalWriteError { path: PathBuf, err: std::io::Error },
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use CheckpointError::*;
+        match self {
+            IllegalId { id } => write!(f, "Illegal workflow identifier '{id}' for a checkpoint"),
+            DirCreateError { path, .. } => write!(f, "Failed to create checkpoint directory '{}'", path.display()),
+            DirRemoveError { path, .. } => write!(f, "Failed to remove checkpoint directory '{}'", path.display()),
+            UnknownCheckpoint { id, path } => write!(f, "No checkpoint found for workflow '{}' (looked in '{}')", id, path.display()),
+            WorkflowWriteError { path, .. } => write!(f, "Failed to write checkpointed workflow '{}'", path.display()),
+            WorkflowReadError { path, .. } => write!(f, "Failed to read checkpointed workflow '{}'", path.display()),
+            JournalOpenError { path, .. } => write!(f, "Failed to open checkpoint journal '{}'", path.display()),
+            JournalReadError { path, .. } => write!(f, "Failed to read checkpoint journal '{}'", path.display()),
+            JournalSerializeError { .. } => write!(f, "Failed to serialize checkpoint journal entry"),
+            JournalWriteError { path, .. } => write!(f, "Failed to write to checkpoint journal '{}'", path.display()),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use CheckpointError::*;
+        match self {
+            IllegalId { .. } => None,
+            DirCreateError { err, .. } => Some(err),
+            DirRemoveError { err, .. } => Some(err),
+            UnknownCheckpoint { .. } => None,
+            WorkflowWriteError { err, .. } => Some(err),
+            WorkflowReadError { err, .. } => Some(err),
+            JournalOpenError { err, .. } => Some(err),
+            JournalReadError { err, .. } => Some(err),
+            JournalSerializeError { err } => Some(err),
+            JournalWriteError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Defines errors that relate to the history of executed workflows.
+#[derive(Debug)]
+pub enum HistoryError {
+    /// The given workflow identifier cannot be used as an execution in the history.
+    IllegalId { id: String },
+    /// Failed to create the directory of an execution.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the directory with the history.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// There is no history for the given execution.
+    UnknownExecution { id: String, path: PathBuf },
+    /// Failed to open the journal of results of an execution.
+    ResultsOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the journal of results of an execution.
+    ResultsReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to serialize an entry for the journal of results of an execution.
+    ResultsSerializeError { err: serde_json::Error },
+    /// Failed to write an entry to the journal of results of an execution.
+    ResultsWriteError { path: PathBuf, err: std::io::Error },
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use HistoryError::*;
+        match self {
+            IllegalId { id } => write!(f, "Illegal workflow identifier '{id}' for an execution"),
+            DirCreateError { path, .. } => write!(f, "Failed to create execution history directory '{}'", path.display()),
+            DirReadError { path, .. } => write!(f, "Failed to read execution history directory '{}'", path.display()),
+            UnknownExecution { id, path } => write!(f, "No history found for execution '{}' (looked in '{}')", id, path.display()),
+            ResultsOpenError { path, .. } => write!(f, "Failed to open execution history '{}'", path.display()),
+            ResultsReadError { path, .. } => write!(f, "Failed to read execution history '{}'", path.display()),
+            ResultsSerializeError { .. } => write!(f, "Failed to serialize execution history entry"),
+            ResultsWriteError { path, .. } => write!(f, "Failed to write to execution history '{}'", path.display()),
+        }
+    }
+}
+
+impl Error for HistoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use HistoryError::*;
+        match self {
+            IllegalId { .. } => None,
+            DirCreateError { err, .. } => Some(err),
+            DirReadError { err, .. } => Some(err),
+            UnknownExecution { .. } => None,
+            ResultsOpenError { err, .. } => Some(err),
+            ResultsReadError { err, .. } => Some(err),
+            ResultsSerializeError { err } => Some(err),
+            ResultsWriteError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+/// Defines errors that relate to the store of sessions shared by replicas of the driver.
+#[derive(Debug)]
+pub enum StoreError {
+    /// Failed to create the directory of the store or of a session in it.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the directory with the sessions in the store.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to remove the directory of a session.
+    DirRemoveError { path: PathBuf, err: std::io::Error },
+    /// Failed to serialize the record of a session.
+    RecordSerializeError { id: String, err: serde_json::Error },
+    /// Failed to write the record of a session.
+    RecordWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the record of a session.
+    RecordReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the record of a session.
+    RecordParseError { path: PathBuf, err: serde_json::Error },
+    /// Failed to create the lock file of a session.
+    LockCreateError { path: PathBuf, err: std::io::Error },
+    /// The session is locked by (another) replica that is executing a workflow in it.
+    SessionLocked { id: String, holder: String },
+    /// Failed to restore the variables of a session in a new VM.
+    RestoreError { id: String, err: brane_exe::errors::FrameStackError },
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use StoreError::*;
+        match self {
+            DirCreateError { path, .. } => write!(f, "Failed to create session store directory '{}'", path.display()),
+            DirReadError { path, .. } => write!(f, "Failed to read session store directory '{}'", path.display()),
+            DirRemoveError { path, .. } => write!(f, "Failed to remove session directory '{}'", path.display()),
+            RecordSerializeError { id, .. } => write!(f, "Failed to serialize record of session '{id}'"),
+            RecordWriteError { path, .. } => write!(f, "Failed to write session record '{}'", path.display()),
+            RecordReadError { path, .. } => write!(f, "Failed to read session record '{}'", path.display()),
+            RecordParseError { path, .. } => write!(f, "Failed to parse session record '{}'", path.display()),
+            LockCreateError { path, .. } => write!(f, "Failed to create session lock '{}'", path.display()),
+            SessionLocked { id, holder } => write!(f, "Session '{id}' is busy executing a workflow on driver replica '{holder}'"),
+            RestoreError { id, .. } => write!(f, "Failed to restore the variables of session '{id}'"),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use StoreError::*;
+        match self {
+            DirCreateError { err, .. } => Some(err),
+            DirReadError { err, .. } => Some(err),
+            DirRemoveError { err, .. } => Some(err),
+            RecordSerializeError { err, .. } => Some(err),
+            RecordWriteError { err, .. } => Some(err),
+            RecordReadError { err, .. } => Some(err),
+            RecordParseError { err, .. } => Some(err),
+            LockCreateError { err, .. } => Some(err),
+            SessionLocked { .. } => None,
+            RestoreError { err, .. } => Some(err),
+        }
+    }
+}
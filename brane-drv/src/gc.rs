@@ -4,23 +4,27 @@
 //  Created:
 //    12 Jul 2023, 16:31:40
 //  Last edited:
-//    13 Jul 2023, 10:26:03
+//    16 Oct 2026, 00:55:26
 //  Auto updated?
 //    Yes
 //
 //  Description:
-//!   Implements a small function that can be used as a "garbage
-//!   collector" for `brane-drv` sessions.
+//!   Implements small functions that can be used as a "garbage
+//!   collector" for `brane-drv` sessions and execution history.
 //
 
-use std::sync::Weak;
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 use brane_tsk::spec::AppId;
 use dashmap::DashMap;
+use error_trace::trace;
 use log::{debug, info, warn};
 
-use crate::vm::InstanceVm;
+use crate::history::ExecutionHistory;
+use crate::spec::Session;
+use crate::store::{SessionRecord, SessionStore};
 
 
 /***** CONSTANTS *****/
@@ -41,10 +45,11 @@ const SESSION_TIMEOUT: u64 = 24 * 3600;
 ///
 /// # Arguments
 /// - `sessions`: The [`DashMap`] of weak sessions. Note that, to avoid memory leaks because its destructor would not be run when this task is cancelled, we assume a [`Weak`] reference.
+/// - `store`: The [`SessionStore`] shared with other replicas, if any. Sessions that none of the replicas used recently are removed from it too.
 ///
 /// # Returns
 /// Never, unless the referred `sessions` is free'd.
-pub async fn sessions(sessions: Weak<DashMap<AppId, (InstanceVm, Instant)>>) {
+pub async fn sessions(sessions: Weak<DashMap<AppId, Session>>, store: Option<Arc<SessionStore>>) {
     // Loop indefinitely
     debug!("Starting sessions garbage collector");
     loop {
@@ -58,14 +63,16 @@ pub async fn sessions(sessions: Weak<DashMap<AppId, (InstanceVm, Instant)>>) {
             // Remove the required things
             sessions.retain(|k, v| {
                 // Only keep those with recent enough usage
-                if v.1.elapsed() < Duration::from_secs(SESSION_TIMEOUT) {
+                // NOTE: If the clock went backwards, we consider the session to be used just now
+                let idle: Duration = v.last_activity.elapsed().unwrap_or_default();
+                if idle < Duration::from_secs(SESSION_TIMEOUT) {
                     true
                 } else {
                     info!(
                         "Removing session '{}' because it has not been used for {} seconds (last use {} seconds ago)",
                         k,
                         SESSION_TIMEOUT,
-                        v.1.elapsed().as_secs()
+                        idle.as_secs()
                     );
                     false
                 }
@@ -74,5 +81,47 @@ pub async fn sessions(sessions: Weak<DashMap<AppId, (InstanceVm, Instant)>>) {
             warn!("Garbage collector attempted to run after `sessions` has been deallocated; quitting garbage collector");
             break;
         }
+
+        // Do the same for the sessions in the store (which any replica may do)
+        if let Some(store) = &store {
+            let records: Vec<(String, SessionRecord)> = match store.list() {
+                Ok(records) => records,
+                Err(err) => {
+                    warn!("{}", trace!(("Failed to list sessions in store"), err));
+                    continue;
+                },
+            };
+            for (id, record) in records {
+                let idle: Duration = record.last_activity.elapsed().unwrap_or_default();
+                if idle >= Duration::from_secs(SESSION_TIMEOUT) {
+                    info!("Removing session '{}' from store because it has not been used for {} seconds", id, idle.as_secs());
+                    if let Err(err) = store.remove(&id) {
+                        warn!("{}", trace!(("Failed to remove session '{id}' from store"), err));
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+
+/// Can be run as a `tokio` background task to periodically remove the history of old executions.
+///
+/// # Arguments
+/// - `root`: The directory in which the driver keeps its execution history.
+/// - `retention`: How long the history of an execution is kept after it was last written to.
+///
+/// # Returns
+/// Never.
+pub async fn history(root: PathBuf, retention: Duration) {
+    debug!("Starting execution history garbage collector");
+    loop {
+        debug!("Running execution history garbage collector");
+        match ExecutionHistory::prune(&root, retention) {
+            Ok(removed) => debug!("Removed the history of {} execution(s)", removed),
+            Err(err) => warn!("{}", trace!(("Failed to prune execution history"), err)),
+        }
+        tokio::time::sleep(Duration::from_secs(GC_POLL_TIMEOUT)).await;
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 16:18:11
 //  Last edited:
-//    07 Mar 2024, 14:20:06
+//    16 Oct 2026, 01:42:13
 //  Auto updated?
 //    Yes
 //
@@ -12,46 +12,71 @@
 //!   Implements the command handler from the client.
 //
 
+use std::collections::{HashSet, VecDeque};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use brane_ast::locations::Location;
 use brane_ast::Workflow;
 use brane_cfg::info::Info;
 use brane_cfg::infra::InfraFile;
 use brane_cfg::node::{CentralConfig, NodeConfig, NodeSpecificConfig};
-use brane_exe::FullValue;
+use brane_cfg::tokens::{bearer_token, ApiTokensFile, API_TOKENS_FILE};
+use brane_exe::{Error as VmError, FullValue};
 use brane_prx::client::ProxyClient;
-use brane_tsk::errors::PlanError;
+use brane_prx::spec::NewPathRequestTlsOptions;
+use brane_shr::chaos;
+use brane_shr::events::EventPublisher;
+use brane_tsk::errors::{ExecuteError, PlanError, PreprocessError};
 use brane_tsk::spec::AppId;
+use chrono::Utc;
 use dashmap::DashMap;
 use enum_debug::EnumDebug as _;
 use error_trace::{trace, ErrorTrace as _};
-use log::{debug, error, info};
-use specifications::driving::{CheckReply, CheckRequest, CreateSessionReply, CreateSessionRequest, DriverService, ExecuteReply, ExecuteRequest};
+use log::{debug, error, info, warn};
+use reqwest::StatusCode;
+use specifications::address::Address;
+use specifications::checking::{ApprovalPending, PolicyDenial};
+use specifications::data::Provenance;
+use specifications::driving::{
+    CheckReply, CheckRequest, CreateSessionReply, CreateSessionRequest, DriverService, ExecuteReply, ExecuteRequest, ListResultsReply,
+    ListResultsRequest, ListSessionsReply, ListSessionsRequest, PromoteResultReply, PromoteResultRequest, ResultInfo, SessionInfo,
+    AUTHORIZATION_METADATA,
+};
+use specifications::errors::ErrorCode;
+use specifications::events::EventKind;
 use specifications::profiling::ProfileReport;
+use specifications::registering::ResultInfoReply;
+use specifications::telemetry::{self, Span, TraceContext};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Request, Response, Status};
+use tonic::{Code, Request, Response, Status};
 
 use crate::check::RequestOutput;
-use crate::errors::RemoteVmError;
+use crate::checkpoint::Checkpoint;
+use crate::errors::{HistoryError, RemoteVmError, StoreError};
+use crate::history::{ExecutionHistory, ResultRecord};
 use crate::planner::InstancePlanner;
-use crate::vm::InstanceVm;
+use crate::spec::Session;
+use crate::store::{SessionLock, SessionStore};
+use crate::vm::{commit_remote, InstanceVm};
 use crate::{check, gc};
 
 
 /***** HELPER MACROS *****/
 /// Sends an error back to the client, also logging it here. Is like `err!` but returning the stream.
+///
+/// When given a `Status` constructor, also expects the [`ErrorCode`] to attach to the status.
 macro_rules! fatal_err {
-    ($tx:ident,Status:: $status:ident, $err:expr) => {{
+    ($tx:ident,Status:: $status:ident, $code:expr, $err:expr) => {{
         // Always log to stderr
         log::error!("{}", $err.trace());
         // Attempt to log on tx
         let serr: String = $err.to_string();
-        if let Err(err) = $tx.send(Err(Status::$status(serr))).await {
+        if let Err(err) = $tx.send(Err($code.attach(Status::$status(serr)))).await {
             log::error!("{}", trace!(("Failed to notify client of error"), err));
         }
         // Return
@@ -68,11 +93,11 @@ macro_rules! fatal_err {
         return;
     }};
 
-    ($tx:ident, $rx:ident,Status:: $status:ident, $err:expr) => {{
+    ($tx:ident, $rx:ident,Status:: $status:ident, $code:expr, $err:expr) => {{
         // Always log to stderr
         log::error!("{}", $err.trace());
         // Attempt to log on tx
-        if let Err(err) = $tx.send(Err(Status::$status($err.to_string()))).await {
+        if let Err(err) = $tx.send(Err($code.attach(Status::$status($err.to_string())))).await {
             log::error!("{}", trace!(("Failed to notify client of error"), err));
         }
         // Return
@@ -94,6 +119,139 @@ macro_rules! fatal_err {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Finds the [`PolicyDenial`] that caused a workflow to fail, if it failed because a checker denied it.
+///
+/// # Arguments
+/// - `err`: The [`RemoteVmError`] that the workflow failed with.
+///
+/// # Returns
+/// The [`PolicyDenial`] explaining which checker denied what and why.
+///
+/// # Errors
+/// This function returns the given `err` back if the workflow failed for another reason.
+fn policy_denial(err: RemoteVmError) -> Result<PolicyDenial, RemoteVmError> {
+    match err {
+        RemoteVmError::PlanError { err: PlanError::CheckerDenied { domain, reasons } } => {
+            Ok(PolicyDenial { domain, task: None, dataset: None, reasons })
+        },
+        RemoteVmError::ExecError { err: VmError::Custom { pc, err } } => {
+            if let Some(ExecuteError::PolicyDenied { denial }) = err.downcast_ref::<ExecuteError>() {
+                return Ok(denial.clone());
+            }
+            if let Some(PreprocessError::PolicyDenied { denial }) = err.downcast_ref::<PreprocessError>() {
+                return Ok(denial.clone());
+            }
+            Err(RemoteVmError::ExecError { err: VmError::Custom { pc, err } })
+        },
+        err => Err(err),
+    }
+}
+
+/// Finds the [`ApprovalPending`] that caused a workflow to fail, if it failed because a download awaits the approval of a data steward.
+///
+/// # Arguments
+/// - `err`: The [`RemoteVmError`] that the workflow failed with.
+///
+/// # Returns
+/// The [`ApprovalPending`] describing which download awaits approval where, or [`None`] if the workflow failed for another reason.
+fn approval_pending(err: &RemoteVmError) -> Option<ApprovalPending> {
+    match err {
+        RemoteVmError::ExecError { err: VmError::Custom { err, .. } } => match err.downcast_ref::<PreprocessError>() {
+            Some(PreprocessError::ApprovalPending { pending }) => Some(pending.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Asks the registry of a location how large an intermediate result is.
+///
+/// # Arguments
+/// - `proxy`: The [`ProxyClient`] that we use to reach the registry.
+/// - `registry`: The address of the registry of the location where the result lives.
+/// - `location`: The location where the result lives.
+/// - `name`: The name of the intermediate result.
+///
+/// # Returns
+/// The size of the result in bytes, or [`None`] if the registry does not (or no longer) know it. If we failed to ask it, we assume it doesn't.
+async fn result_size(proxy: &ProxyClient, registry: &Address, location: &Location, name: &str) -> Option<u64> {
+    let address: String = format!("{registry}/results/info/{name}");
+    let tls: NewPathRequestTlsOptions = NewPathRequestTlsOptions { location: location.clone(), use_client_auth: false };
+    let res: reqwest::Response = match proxy.get(&address, Some(tls)).await {
+        Ok(Ok(res)) => res,
+        Ok(Err(err)) => {
+            warn!("{}", trace!(("Failed to send request to '{address}'"), err));
+            return None;
+        },
+        Err(err) => {
+            warn!("{}", trace!(("Failed to send request to '{address}'"), err));
+            return None;
+        },
+    };
+    if res.status() != StatusCode::OK {
+        return None;
+    }
+    match res.text().await.map(|body| serde_json::from_str::<ResultInfoReply>(&body)) {
+        Ok(Ok(info)) => Some(info.size),
+        Ok(Err(err)) => {
+            warn!("{}", trace!(("Failed to parse response of '{address}'"), err));
+            None
+        },
+        Err(err) => {
+            warn!("{}", trace!(("Failed to download response of '{address}'"), err));
+            None
+        },
+    }
+}
+
+/// Decides which [`ErrorCode`] to return to the client when a workflow failed.
+///
+/// # Arguments
+/// - `err`: The [`RemoteVmError`] that the workflow failed with.
+///
+/// # Returns
+/// The code that the planner returned if it failed to plan the workflow, or [`ErrorCode::DrvExecutionFailed`] otherwise.
+fn error_code(err: &RemoteVmError) -> ErrorCode {
+    match err {
+        RemoteVmError::PlanError { err: PlanError::PlanningFailure { error_code: Some(code), .. } } => *code,
+        RemoteVmError::PlanError { err: PlanError::ResidencyViolation { .. } } => ErrorCode::PlrResidencyViolation,
+        _ => ErrorCode::DrvExecutionFailed,
+    }
+}
+
+
+
+
+
+/// Collects the lineage of an intermediate result from the history of the execution that produced it.
+///
+/// # Arguments
+/// - `records`: The [`ResultRecord`]s of the execution.
+/// - `record`: The [`ResultRecord`] of the result.
+///
+/// # Returns
+/// The names of the datasets and intermediate results that the result was (directly or indirectly) computed from, closest first.
+fn lineage(records: &[ResultRecord], record: &ResultRecord) -> Vec<String> {
+    let mut lineage: Vec<String> = vec![];
+    let mut todo: VecDeque<&str> = record.inputs.iter().map(String::as_str).collect();
+    while let Some(input) = todo.pop_front() {
+        if input == record.name || lineage.iter().any(|known| known == input) {
+            continue;
+        }
+        lineage.push(input.into());
+        // Intermediate results produced by the same execution have a lineage of their own
+        if let Some(producer) = records.iter().find(|record| record.name == input) {
+            todo.extend(producer.inputs.iter().map(String::as_str));
+        }
+    }
+    lineage
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// The DriverHandler handles incoming gRPC requests. This is effectively what 'drives' the driver.
 #[derive(Clone)]
@@ -102,9 +260,23 @@ pub struct DriverHandler {
     node_config_path: PathBuf,
     /// The ProxyClient that we use to connect to/through `brane-prx`.
     proxy: Arc<ProxyClient>,
+    /// The EventPublisher that we use to publish audit events about the workflows we run.
+    events: EventPublisher,
+    /// The maximum number of bytes of values that a session's workflows may keep in memory, if limited.
+    memory_limit: Option<usize>,
+    /// The directory in which to checkpoint running workflows, if enabled.
+    checkpoint_dir: Option<PathBuf>,
+    /// The directory in which to keep the history of executions, if enabled.
+    history_dir: Option<PathBuf>,
+    /// How long the history of an execution (and thus the knowledge of its intermediate results) is kept.
+    history_retention: Duration,
+    /// The store of sessions shared with other replicas of the driver, if any.
+    store: Option<Arc<SessionStore>>,
+    /// Whether clients must authenticate themselves with an API token.
+    require_auth: bool,
 
     /// Current sessions and active VMs. Note that this only concerns states if connected via a REPL-session; any in-statement state (i.e., calling nodes) is handled by virtue of the VM being implemented as `async`.
-    sessions: Arc<DashMap<AppId, (InstanceVm, Instant)>>,
+    sessions: Arc<DashMap<AppId, Session>>,
 }
 
 impl DriverHandler {
@@ -113,18 +285,246 @@ impl DriverHandler {
     /// # Arguments
     /// - `node_config_path`: The path to the `node.yml` file that describes this node's environment. For the handler, this is the path to the `infra.yml` file (and an optional `secrets.yml`) and the topic to send commands to the planner on.
     /// - `proxy`: The (shared) ProxyClient that we use to connect to/through `brane-prx`.
+    /// - `events`: The [`EventPublisher`] that we use to publish audit events about the workflows we run.
+    /// - `memory_limit`: The maximum number of bytes of values that a session's workflows may keep in memory, or [`None`] to not limit it.
+    /// - `checkpoint_dir`: The directory in which to checkpoint running workflows such that they can be resumed, or [`None`] to disable checkpointing.
+    /// - `history_dir`: The directory in which to record the intermediate results of executions such that they can be inspected later, or [`None`] to disable the execution history.
+    /// - `history_retention`: How long the history of an execution is kept after it was last written to.
+    /// - `store`: The [`SessionStore`] in which to keep sessions such that other replicas of the driver can take them over, or [`None`] to only keep them in memory.
+    /// - `require_auth`: Whether clients must authenticate themselves with an API token. If not, clients may still do so to have their workflows run as their user.
     /// - `planner`: The InstancePlanner that handles our side of planning.
     ///
     /// # Returns
     /// A new DriverHandler instance.
     #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, proxy: Arc<ProxyClient>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        node_config_path: impl Into<PathBuf>,
+        proxy: Arc<ProxyClient>,
+        events: EventPublisher,
+        memory_limit: Option<usize>,
+        checkpoint_dir: Option<PathBuf>,
+        history_dir: Option<PathBuf>,
+        history_retention: Duration,
+        store: Option<SessionStore>,
+        require_auth: bool,
+    ) -> Self {
         // Create the new sessions list with its Garbage Collector (GC)
-        let sessions: Arc<DashMap<AppId, (InstanceVm, Instant)>> = Arc::new(DashMap::new());
-        tokio::spawn(gc::sessions(Arc::downgrade(&sessions)));
+        let sessions: Arc<DashMap<AppId, Session>> = Arc::new(DashMap::new());
+        let store: Option<Arc<SessionStore>> = store.map(Arc::new);
+        tokio::spawn(gc::sessions(Arc::downgrade(&sessions), store.clone()));
+        // Old executions are forgotten by a GC too
+        if let Some(dir) = &history_dir {
+            tokio::spawn(gc::history(dir.clone(), history_retention));
+        }
 
         // Now use that as this handler's sessions
-        Self { node_config_path: node_config_path.into(), proxy, sessions }
+        Self {
+            node_config_path: node_config_path.into(),
+            proxy,
+            events,
+            memory_limit,
+            checkpoint_dir,
+            history_dir,
+            history_retention,
+            store,
+            require_auth,
+            sessions,
+        }
+    }
+
+    /// Loads the configuration of this (central) node.
+    ///
+    /// # Returns
+    /// The loaded [`CentralConfig`].
+    ///
+    /// # Errors
+    /// This function errors with an (already logged) internal error [`Status`] if we failed to load the node config file or if it is not for a
+    /// central node.
+    async fn central(&self) -> Result<CentralConfig, Status> {
+        debug!("Loading node config file '{}'...", self.node_config_path.display());
+        match NodeConfig::from_path_async(&self.node_config_path).await {
+            Ok(cfg) => match cfg.node {
+                NodeSpecificConfig::Central(central) => Ok(central),
+                NodeSpecificConfig::Worker(_) | NodeSpecificConfig::Proxy(_) => {
+                    error!("Given node config file '{}' is for a {}, but expected a Central", self.node_config_path.display(), cfg.node.variant());
+                    Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")))
+                },
+            },
+            Err(err) => {
+                error!("{}", trace!(("Failed to read node config file '{}'", self.node_config_path.display()), err));
+                Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")))
+            },
+        }
+    }
+
+    /// Loads the infrastructure file of this node, i.e., the locations that it knows.
+    ///
+    /// # Returns
+    /// The loaded [`InfraFile`].
+    ///
+    /// # Errors
+    /// This function errors with an (already logged) internal error [`Status`] if we failed to load the node config file or the infra file.
+    async fn infra(&self) -> Result<InfraFile, Status> {
+        let central_cfg: CentralConfig = self.central().await?;
+
+        debug!("Loading infra file '{}'...", central_cfg.paths.infra.display());
+        match InfraFile::from_path_async(&central_cfg.paths.infra).await {
+            Ok(infra) => Ok(infra),
+            Err(err) => {
+                error!("{}", trace!(("Failed to read infra file '{}'", central_cfg.paths.infra.display()), err));
+                Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")))
+            },
+        }
+    }
+
+    /// Authenticates the client that sent the given request by the API token in its metadata.
+    ///
+    /// # Arguments
+    /// - `request`: The [`Request`] of which to authenticate the sender.
+    ///
+    /// # Returns
+    /// The name of the user that the client authenticated as, or [`None`] if it did not present a token (and we don't require it to).
+    ///
+    /// # Errors
+    /// This function errors with a [`Status`] to send back to the client if it presented an unknown token, if it presented none while we require
+    /// one or if we failed to load the known tokens.
+    async fn authenticate<T>(&self, request: &Request<T>) -> Result<Option<String>, Status> {
+        let token: Option<&str> = request.metadata().get(AUTHORIZATION_METADATA).and_then(|value| value.to_str().ok()).and_then(bearer_token);
+        let token: &str = match token {
+            Some(token) => token,
+            None if self.require_auth => {
+                debug!("Rejecting request without an API token");
+                return Err(ErrorCode::DrvUnauthenticated.attach(Status::unauthenticated("Missing API token")));
+            },
+            None => return Ok(None),
+        };
+
+        // Resolve the token to its user using the list of known ones
+        let central_cfg: CentralConfig = self.central().await?;
+        let path: PathBuf = central_cfg.paths.certs.join(API_TOKENS_FILE);
+        let tokens: ApiTokensFile = match ApiTokensFile::from_path_async(&path).await {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                error!("{}", trace!(("Failed to read API tokens file '{}'", path.display()), err));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
+            },
+        };
+        match tokens.user(token) {
+            Some(user) => {
+                debug!("Authenticated request as user '{}'", user);
+                Ok(Some(user.into()))
+            },
+            None => {
+                debug!("Rejecting request with an unknown API token");
+                Err(ErrorCode::DrvUnauthenticated.attach(Status::unauthenticated("Unknown API token")))
+            },
+        }
+    }
+
+    /// Checks that an authenticated client may access something owned by the given user.
+    ///
+    /// Clients that did not authenticate themselves (which is only possible if we don't require it) may access anything, as before
+    /// authentication existed.
+    ///
+    /// # Arguments
+    /// - `auth`: The user that the client authenticated as, if any.
+    /// - `owner`: The user that owns the thing, if any.
+    /// - `what`: A description of the thing for in errors (e.g., "session 'abc'").
+    ///
+    /// # Errors
+    /// This function errors with a [`Status`] to send back to the client if the thing belongs to another user, or if it belongs to nobody while
+    /// we require authentication.
+    fn authorize(&self, auth: &Option<String>, owner: &Option<String>, what: &str) -> Result<(), Status> {
+        match (auth, owner) {
+            (Some(auth), Some(owner)) if auth != owner => {
+                debug!("Rejecting access by user '{}' to {} of user '{}'", auth, what, owner);
+                Err(ErrorCode::DrvUserMismatch
+                    .attach(Status::permission_denied(format!("Authenticated as user '{auth}', but {what} belongs to user '{owner}'"))))
+            },
+            (Some(auth), None) if self.require_auth => {
+                debug!("Rejecting access by user '{}' to {} without an owner", auth, what);
+                Err(ErrorCode::DrvUserMismatch
+                    .attach(Status::permission_denied(format!("Authenticated as user '{auth}', but {what} belongs to no user"))))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads the intermediate results recorded in the history of an execution.
+    ///
+    /// A resumed execution may have produced the same result more than once; only its latest version is returned.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the execution (i.e., of its workflow).
+    /// - `auth`: The user that the client authenticated as, if any. They may only access their own executions.
+    ///
+    /// # Returns
+    /// A tuple of the [`ResultRecord`]s of the execution, in the order they were produced, and until when they are kept (as seconds since the
+    /// Unix epoch).
+    ///
+    /// # Errors
+    /// This function errors with a [`Status`] to send back to the client if the history is disabled, if the execution is unknown or belongs to
+    /// another user, or if we failed to read its history.
+    fn history(&self, id: &str, auth: &Option<String>) -> Result<(Vec<ResultRecord>, u64), Status> {
+        let dir: &PathBuf = match &self.history_dir {
+            Some(dir) => dir,
+            None => return Err(ErrorCode::BadRequest.attach(Status::failed_precondition("Execution history is not enabled on this driver"))),
+        };
+        let (mut records, modified): (Vec<ResultRecord>, SystemTime) = match ExecutionHistory::load(dir, id) {
+            Ok(res) => res,
+            Err(err @ (HistoryError::IllegalId { .. } | HistoryError::UnknownExecution { .. })) => {
+                debug!("{}", err);
+                return Err(ErrorCode::BadRequest.attach(Status::not_found(err.to_string())));
+            },
+            Err(err) => {
+                error!("{}", trace!(("Failed to load history of execution '{id}'"), err));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
+            },
+        };
+
+        let owner: Option<String> = records.iter().find_map(|record| record.user.clone());
+        self.authorize(auth, &owner, &format!("execution '{id}'"))?;
+
+        let mut seen: HashSet<String> = HashSet::new();
+        records.reverse();
+        records.retain(|record| seen.insert(record.name.clone()));
+        records.reverse();
+        let expires: u64 = (modified + self.history_retention).duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Ok((records, expires))
+    }
+
+    /// Finds the session with the given ID, taking it over from the [`SessionStore`] if another replica executed a workflow in it since we did
+    /// (or if we never served it).
+    ///
+    /// # Arguments
+    /// - `app_id`: The ID of the session.
+    ///
+    /// # Returns
+    /// The [`Session`] with its latest state, or [`None`] if no replica knows it.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or restore the session from the store.
+    fn session(&self, app_id: &AppId) -> Result<Option<Session>, StoreError> {
+        let local: Option<Session> = self.sessions.get(app_id).map(|session| session.clone());
+        let store: &SessionStore = match &self.store {
+            Some(store) => store,
+            None => return Ok(local),
+        };
+        match (local, store.load(app_id)?) {
+            (Some(local), Some(record)) if record.revision <= local.revision => Ok(Some(local)),
+            (_, Some(record)) => {
+                info!("Taking over session '{}' (revision {}) last served by replica '{}'", app_id, record.revision, record.replica);
+                let vm: InstanceVm = InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone(), self.memory_limit);
+                let session: Session = match Session::restore(vm, record) {
+                    Ok(session) => session,
+                    Err(err) => return Err(StoreError::RestoreError { id: app_id.to_string(), err }),
+                };
+                self.sessions.insert(app_id.clone(), session.clone());
+                Ok(Some(session))
+            },
+            (local, None) => Ok(local),
+        }
     }
 }
 
@@ -141,14 +541,35 @@ impl DriverService for DriverHandler {
     /// The response to the request, which only contains a new AppId.
     ///
     /// # Errors
-    /// This function doesn't typically error.
-    async fn create_session(&self, _request: Request<CreateSessionRequest>) -> Result<Response<CreateSessionReply>, Status> {
+    /// This function errors if the client failed to authenticate itself, or if it did but claims the session for another user.
+    async fn create_session(&self, request: Request<CreateSessionRequest>) -> Result<Response<CreateSessionReply>, Status> {
         let report = ProfileReport::auto_reporting_file("brane-drv DriverHandler::create_session", "brane-drv_create-session");
         let _guard = report.time("Total");
+        let auth: Option<String> = self.authenticate(&request).await?;
+        let request: CreateSessionRequest = request.into_inner();
+
+        // An authenticated client always owns its sessions
+        let user: Option<String> = match (auth, request.user) {
+            (Some(auth), Some(user)) if auth != user => {
+                debug!("Rejecting session for user '{}' requested by user '{}'", user, auth);
+                return Err(ErrorCode::DrvUserMismatch
+                    .attach(Status::permission_denied(format!("Authenticated as user '{auth}', cannot create a session for user '{user}'"))));
+            },
+            (Some(auth), _) => Some(auth),
+            (None, user) => user,
+        };
 
         // Create a new VM for this session
         let app_id: AppId = AppId::generate();
-        self.sessions.insert(app_id.clone(), (InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone()), Instant::now()));
+        let vm: InstanceVm = InstanceVm::new(&self.node_config_path, app_id.clone(), self.proxy.clone(), self.memory_limit);
+        let session: Session = Session::new(vm, user);
+        if let Some(store) = &self.store {
+            if let Err(err) = store.save(&app_id, &session.record(store.replica())) {
+                error!("{}", trace!(("Failed to save new session '{app_id}'"), err));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
+            }
+        }
+        self.sessions.insert(app_id.clone(), session);
 
         // Now return the ID to the user for future reference
         debug!("Created new session '{}'", app_id);
@@ -156,6 +577,146 @@ impl DriverService for DriverHandler {
         Ok(Response::new(reply))
     }
 
+    /// Lists the active BraneScript sessions.
+    ///
+    /// # Arguments
+    /// - `request`: The request to create a response to, which may limit the sessions to those of a particular user. Authenticated clients
+    ///   only see their own sessions.
+    ///
+    /// # Returns
+    /// The response to the request, which contains the (matching) sessions, most recently used first.
+    ///
+    /// # Errors
+    /// This function errors if the client failed to authenticate itself, or if it did but asks for the sessions of another user.
+    async fn list_sessions(&self, request: Request<ListSessionsRequest>) -> Result<Response<ListSessionsReply>, Status> {
+        let auth: Option<String> = self.authenticate(&request).await?;
+        let mut request: ListSessionsRequest = request.into_inner();
+        if let Some(auth) = auth {
+            if let Some(user) = &request.user {
+                self.authorize(&Some(auth.clone()), &Some(user.clone()), &format!("the sessions of user '{user}'"))?;
+            }
+            request.user = Some(auth);
+        }
+
+        // Collect the sessions matching the user (of all replicas, if we share them)
+        let unix = |time: SystemTime| -> u64 { time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) };
+        let mut sessions: Vec<SessionInfo> = match &self.store {
+            Some(store) => match store.list() {
+                Ok(records) => records
+                    .into_iter()
+                    .filter(|(_, record)| request.user.is_none() || record.user == request.user)
+                    .map(|(uuid, record)| SessionInfo {
+                        uuid,
+                        user: record.user,
+                        created: unix(record.created),
+                        last_activity: unix(record.last_activity),
+                    })
+                    .collect(),
+                Err(err) => {
+                    error!("{}", trace!(("Failed to list sessions in store"), err));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
+                },
+            },
+            None => self
+                .sessions
+                .iter()
+                .filter(|entry| request.user.is_none() || entry.value().user == request.user)
+                .map(|entry| SessionInfo {
+                    uuid: entry.key().to_string(),
+                    user: entry.value().user.clone(),
+                    created: unix(entry.value().created),
+                    last_activity: unix(entry.value().last_activity),
+                })
+                .collect(),
+        };
+        sessions.sort_by(|lhs, rhs| rhs.last_activity.cmp(&lhs.last_activity));
+
+        // Return them
+        debug!("Listing {} session(s){}", sessions.len(), if let Some(user) = &request.user { format!(" of user '{user}'") } else { String::new() });
+        Ok(Response::new(ListSessionsReply { sessions }))
+    }
+
+    /// Lists the intermediate results produced by an execution.
+    ///
+    /// # Arguments
+    /// - `request`: The request to create a response to, which identifies the execution.
+    ///
+    /// # Returns
+    /// The response to the request, which contains the results in the order they were produced, including their size if they still exist.
+    ///
+    /// # Errors
+    /// This function errors if the client failed to authenticate itself, if the execution history is disabled, if the execution is unknown or
+    /// belongs to another user, or if we failed to read its history.
+    async fn list_results(&self, request: Request<ListResultsRequest>) -> Result<Response<ListResultsReply>, Status> {
+        let auth: Option<String> = self.authenticate(&request).await?;
+        let request: ListResultsRequest = request.into_inner();
+        let (records, expires): (Vec<ResultRecord>, u64) = self.history(&request.execution_id, &auth)?;
+        let infra: InfraFile = self.infra().await?;
+
+        // Ask the registries where the results live whether they still have them
+        let mut results: Vec<ResultInfo> = Vec::with_capacity(records.len());
+        for record in records {
+            let size: Option<u64> = match infra.get(&record.location) {
+                Some(info) => result_size(&self.proxy, &info.registry, &record.location, &record.name).await,
+                None => None,
+            };
+            results.push(ResultInfo { name: record.name, task: record.task, location: record.location, size, produced: record.produced, expires });
+        }
+
+        // Return them
+        debug!("Listing {} intermediate result(s) of execution '{}'", results.len(), request.execution_id);
+        Ok(Response::new(ListResultsReply { results }))
+    }
+
+    /// Promotes an intermediate result produced by an execution to a dataset.
+    ///
+    /// # Arguments
+    /// - `request`: The request to create a response to, which identifies the execution, the result and the dataset to promote it to.
+    ///
+    /// # Returns
+    /// The response to the request, which contains the location where the new dataset lives.
+    ///
+    /// # Errors
+    /// This function errors if the client failed to authenticate itself, if the execution (or its result) is unknown or belongs to another
+    /// user, or if the delegate failed to promote the result.
+    async fn promote_result(&self, request: Request<PromoteResultRequest>) -> Result<Response<PromoteResultReply>, Status> {
+        let auth: Option<String> = self.authenticate(&request).await?;
+        let PromoteResultRequest { execution_id, name, dataset } = request.into_inner();
+        info!("Promoting intermediate result '{}' of execution '{}' to dataset '{}'...", name, execution_id, dataset);
+        let (records, _): (Vec<ResultRecord>, u64) = self.history(&execution_id, &auth)?;
+        let record: &ResultRecord = match records.iter().find(|record| record.name == name) {
+            Some(record) => record,
+            None => {
+                return Err(ErrorCode::BadRequest
+                    .attach(Status::not_found(format!("Execution '{execution_id}' did not produce an intermediate result '{name}'"))));
+            },
+        };
+
+        // Find the delegate of the location where it lives
+        let infra: InfraFile = self.infra().await?;
+        let delegate: Address = match infra.get(&record.location) {
+            Some(info) => info.delegate.clone(),
+            None => {
+                let msg: String = format!("Intermediate result '{}' lives at unknown location '{}'", name, record.location);
+                return Err(ErrorCode::BadRequest.attach(Status::failed_precondition(msg)));
+            },
+        };
+
+        // Have it promote the result, together with everything it was computed from
+        let provenance: Provenance = Provenance {
+            result:    name.clone(),
+            task:      record.task.clone(),
+            inputs:    lineage(&records, record),
+            location:  record.location.clone(),
+            committed: Utc::now(),
+        };
+        if let Err(err) = commit_remote(&self.proxy, delegate, &TraceContext::for_workflow(&execution_id), &name, &dataset, &provenance).await {
+            error!("{}", trace!(("Failed to promote intermediate result '{name}' of execution '{execution_id}'"), err));
+            return Err(ErrorCode::Internal.attach(Status::internal(err.to_string())));
+        }
+        Ok(Response::new(PromoteResultReply { location: record.location.clone() }))
+    }
+
     /// Checks a workflow's validity in the backing instance.
     ///
     /// # Arguments
@@ -167,22 +728,37 @@ impl DriverService for DriverHandler {
     /// # Errors
     /// This function may error for any reason a job might fail.
     async fn check(&self, request: Request<CheckRequest>) -> Result<Response<CheckReply>, Status> {
+        // Inject faults if in chaos mode
+        chaos::delay().await;
+        if chaos::should_drop() {
+            return Err(ErrorCode::Unavailable.attach(Status::unavailable("Request dropped (chaos mode)")));
+        }
+
         let report = ProfileReport::auto_reporting_file("brane-drv DriverHandler::check", "brane-drv_check");
         let overhead = report.time("Handle overhead");
 
+        let auth: Option<String> = self.authenticate(&request).await?;
         let CheckRequest { workflow } = request.into_inner();
         debug!("Receiving check request");
 
         // Deserialize the workflow
         debug!("Deserializing input workflow...");
-        let workflow: Workflow = match serde_json::from_str(&workflow) {
+        let mut workflow: Workflow = match serde_json::from_str(&workflow) {
             Ok(workflow) => workflow,
             Err(err) => {
                 debug!("{}", trace!(("Incoming request has invalid workflow"), err));
-                return Err(Status::invalid_argument("Invalid workflow"));
+                return Err(ErrorCode::DrvInvalidWorkflow.attach(Status::invalid_argument("Invalid workflow")));
             },
         };
 
+        // An authenticated client may only check workflows as itself, like it may only run them as itself
+        if let Some(auth) = auth {
+            if let Some(user) = &*workflow.user {
+                self.authorize(&Some(auth.clone()), &Some(user.clone()), &format!("workflow '{}'", workflow.id))?;
+            }
+            workflow.user = Arc::new(Some(auth));
+        }
+
         // Read the node file
         debug!("Loading node config file '{}'...", self.node_config_path.display());
         let central_cfg: CentralConfig = match NodeConfig::from_path_async(&self.node_config_path).await {
@@ -190,12 +766,12 @@ impl DriverService for DriverHandler {
                 NodeSpecificConfig::Central(central) => central,
                 NodeSpecificConfig::Worker(_) | NodeSpecificConfig::Proxy(_) => {
                     error!("Given node config file '{}' is for a {}, but expected a Central", self.node_config_path.display(), cfg.node.variant());
-                    return Err(Status::internal("An internal error has occurred"));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
                 },
             },
             Err(err) => {
                 error!("{}", trace!(("Failed to read node config file '{}'", self.node_config_path.display()), err));
-                return Err(Status::internal("An internal error has occurred"));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
             },
         };
 
@@ -205,7 +781,7 @@ impl DriverService for DriverHandler {
             Ok(infra) => infra,
             Err(err) => {
                 error!("{}", trace!(("Failed to read infra file '{}'", central_cfg.paths.infra.display()), err));
-                return Err(Status::internal("An internal error has occurred"));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
             },
         };
         overhead.stop();
@@ -213,8 +789,11 @@ impl DriverService for DriverHandler {
         // Plan the workflow first
         debug!("Planning workflow on instance `brane-plr`...");
         let wf_id: String = workflow.id.clone();
+        let trace: TraceContext = TraceContext::new();
+        let mut span: Span = Span::root("brane-drv check", trace);
+        span.set_attribute("brane.workflow", &wf_id);
         let workflow: Workflow =
-            match InstancePlanner::plan(&central_cfg.services.plr.address, AppId::generate(), workflow, report.nest("Planning")).await {
+            match InstancePlanner::plan(&central_cfg.services.plr.address, AppId::generate(), workflow, &trace, report.nest("Planning")).await {
                 Ok(wf) => wf,
                 Err(PlanError::CheckerDenied { domain, reasons }) => {
                     debug!("Checker denied workflow during planning already");
@@ -226,8 +805,13 @@ impl DriverService for DriverHandler {
                     }));
                 },
                 Err(err) => {
+                    let code: ErrorCode = match &err {
+                        PlanError::PlanningFailure { error_code: Some(code), .. } => *code,
+                        PlanError::ResidencyViolation { .. } => ErrorCode::PlrResidencyViolation,
+                        _ => ErrorCode::Internal,
+                    };
                     error!("{}", trace!(("Failed to plan workflow '{wf_id}'"), err));
-                    return Err(Status::internal("An internal error has occurred"));
+                    return Err(code.attach(Status::internal("An internal error has occurred")));
                 },
             };
 
@@ -238,7 +822,7 @@ impl DriverService for DriverHandler {
             Ok(reqs) => reqs,
             Err(err) => {
                 error!("{}", trace!(("Failed to spawn requests for workflow '{}'", workflow.id), err));
-                return Err(Status::internal("An internal error has occurred"));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
             },
         };
         req_gen.stop();
@@ -253,7 +837,7 @@ impl DriverService for DriverHandler {
                 Ok(res) => res,
                 Err(err) => {
                     error!("{}", trace!(("Failed to await JoinHandle for workflow '{}'", workflow.id), err));
-                    return Err(Status::internal("An internal error has occurred"));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
                 },
             };
 
@@ -269,7 +853,7 @@ impl DriverService for DriverHandler {
                 Err(err) => {
                     // Stop if any request failed
                     error!("{}", trace!(("Failed to ask checker '{checker}' for permission for workflow '{}'", workflow.id), err));
-                    return Err(Status::internal("An internal error has occurred"));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error has occurred")));
                 },
             }
         }
@@ -295,9 +879,16 @@ impl DriverService for DriverHandler {
     /// # Errors
     /// This function may error for any reason a job might fail.
     async fn execute(&self, request: Request<ExecuteRequest>) -> Result<Response<Self::ExecuteStream>, Status> {
+        // Inject faults if in chaos mode
+        chaos::delay().await;
+        if chaos::should_drop() {
+            return Err(ErrorCode::Unavailable.attach(Status::unavailable("Request dropped (chaos mode)")));
+        }
+
         let report = ProfileReport::auto_reporting_file("brane-drv DriverHandler::execute", "brane-drv_execute");
         let overhead = report.time("Handle overhead");
 
+        let auth: Option<String> = self.authenticate(&request).await?;
         let request = request.into_inner();
         debug!("Receiving execute request for session '{}'", request.uuid);
 
@@ -308,51 +899,198 @@ impl DriverService for DriverHandler {
         let app_id: AppId = match AppId::from_str(&request.uuid) {
             Ok(app_id) => app_id,
             Err(err) => {
-                fatal_err!(tx, rx, Status::invalid_argument, err);
+                fatal_err!(tx, rx, Status::invalid_argument, ErrorCode::BadRequest, err);
             },
         };
 
-        // Fetch the VM
-        let sessions: Arc<DashMap<AppId, (InstanceVm, Instant)>> = self.sessions.clone();
-        let vm: InstanceVm = match sessions.get(&app_id) {
-            Some(vm) => vm.0.clone(),
-            None => {
-                fatal_err!(tx, rx, Status::internal(format!("No session with ID '{app_id}' found")));
+        // Claim the session if we share it with other replicas, such that only we execute a workflow in it...
+        let lock: Option<SessionLock> = match self.store.as_ref().map(|store| store.lock(&app_id)).transpose() {
+            Ok(lock) => lock,
+            Err(err @ StoreError::SessionLocked { .. }) => {
+                fatal_err!(tx, rx, Status::unavailable, ErrorCode::DrvSessionBusy, err);
+            },
+            Err(err) => {
+                fatal_err!(tx, rx, Status::internal, ErrorCode::Internal, err);
             },
         };
 
+        // ...and fetch its VM
+        let sessions: Arc<DashMap<AppId, Session>> = self.sessions.clone();
+        let session: Session = match self.session(&app_id) {
+            Ok(Some(session)) => session,
+            Ok(None) => {
+                fatal_err!(tx, rx, ErrorCode::DrvUnknownSession.attach(Status::not_found(format!("No session with ID '{app_id}' found"))));
+            },
+            Err(err) => {
+                fatal_err!(tx, rx, Status::internal, ErrorCode::Internal, err);
+            },
+        };
+        if let Err(status) = self.authorize(&auth, &session.user, &format!("session '{app_id}'")) {
+            fatal_err!(tx, rx, status);
+        }
+
         // We're gonna run the rest asynchronous, to allow the client to earlier receive callbacks
         overhead.stop();
+        let events: EventPublisher = self.events.clone();
+        let checkpoint_dir: Option<PathBuf> = self.checkpoint_dir.clone();
+        let history_dir: Option<PathBuf> = self.history_dir.clone();
+        let store: Option<Arc<SessionStore>> = self.store.clone();
         tokio::spawn(async move {
             debug!("Executing workflow for session '{}'", app_id);
 
+            // If we resume a workflow, its input comes from its checkpoint instead
+            let (input, mut checkpoint): (String, Option<Arc<Checkpoint>>) = match (request.resume, &checkpoint_dir) {
+                (Some(id), Some(dir)) => match Checkpoint::load(dir, &id) {
+                    Ok((checkpoint, input)) => {
+                        info!("Resuming workflow '{}' from its checkpoint", id);
+                        (input, Some(Arc::new(checkpoint)))
+                    },
+                    Err(err) => {
+                        fatal_err!(tx, Status::not_found, ErrorCode::BadRequest, err);
+                    },
+                },
+                (Some(_), None) => {
+                    fatal_err!(tx, ErrorCode::BadRequest.attach(Status::failed_precondition("Checkpointing is not enabled on this driver")));
+                },
+                (None, _) => (request.input, None),
+            };
+
             // We assume that the input is an already compiled workflow; so no need to fire up any parsers/compilers
 
             // We only have to use JSON magic
             let par = report.time("Workflow parsing");
-            debug!("Parsing workflow of {} characters", request.input.len());
-            let workflow: Workflow = match serde_json::from_str(&request.input) {
+            debug!("Parsing workflow of {} characters", input.len());
+            let mut workflow: Workflow = match serde_json::from_str(&input) {
                 Ok(workflow) => workflow,
                 Err(err) => {
-                    debug!(
-                        "Workflow:\n{}\n{}\n{}\n\n",
-                        (0..80).map(|_| '-').collect::<String>(),
-                        request.input,
-                        (0..80).map(|_| '-').collect::<String>()
-                    );
-                    fatal_err!(tx, Status::invalid_argument, err);
+                    debug!("Workflow:\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), input, (0..80).map(|_| '-').collect::<String>());
+                    fatal_err!(tx, Status::invalid_argument, ErrorCode::DrvInvalidWorkflow, err);
                 },
             };
+
+            // An authenticated client may only run workflows as itself, which we stamp in the workflow for the workers to see
+            if let Some(auth) = auth {
+                match &*workflow.user {
+                    Some(user) if *user != auth => {
+                        fatal_err!(
+                            tx,
+                            ErrorCode::DrvUserMismatch.attach(Status::permission_denied(format!(
+                                "Authenticated as user '{auth}', cannot run workflow '{}' as user '{user}'",
+                                workflow.id
+                            )))
+                        );
+                    },
+                    Some(_) => {},
+                    None => workflow.user = Arc::new(Some(auth)),
+                }
+            }
+            if let (None, Some(dir)) = (&checkpoint, &checkpoint_dir) {
+                match Checkpoint::create(dir, &workflow.id, &input) {
+                    Ok(new) => checkpoint = Some(Arc::new(new)),
+                    Err(err) => error!("{} (running without checkpoint)", trace!(("Failed to checkpoint workflow '{}'", workflow.id), err)),
+                }
+            }
+            let history: Option<Arc<ExecutionHistory>> = match &history_dir {
+                Some(dir) => match ExecutionHistory::create(dir, &workflow.id, (*workflow.user).clone()) {
+                    Ok(history) => Some(Arc::new(history)),
+                    Err(err) => {
+                        error!("{} (running without execution history)", trace!(("Failed to open history of workflow '{}'", workflow.id), err));
+                        None
+                    },
+                },
+                None => None,
+            };
+            report.set_trace_id(workflow.id.clone());
+            let mut span: Span = Span::root("brane-drv execute", TraceContext::for_workflow(&workflow.id));
+            span.set_attribute("brane.app_id", &app_id);
+            span.set_attribute("brane.workflow", &workflow.id);
             par.stop();
+            let wf_id: String = workflow.id.clone();
+            let wf_user: Option<String> = (*workflow.user).clone();
+            events.publish(EventKind::WorkflowStarted { workflow: wf_id.clone(), app_id: app_id.to_string(), user: wf_user.clone() });
 
             // We now have a runnable plan ( ͡° ͜ʖ ͡°), so run it
             debug!("Executing workflow of {} edges", workflow.graph.len());
-            let (vm, res): (InstanceVm, Result<FullValue, RemoteVmError>) =
-                report.nest_fut("VM execution", |scope| vm.exec(tx.clone(), app_id.clone(), workflow, scope)).await;
+            let deadline: Option<Duration> = request.deadline.map(Duration::from_secs);
+            let (vm, res): (InstanceVm, Result<FullValue, RemoteVmError>) = report
+                .nest_fut("VM execution", |scope| {
+                    telemetry::in_trace(
+                        *span.context(),
+                        session.vm.exec(tx.clone(), app_id.clone(), workflow, checkpoint.clone(), history.clone(), deadline, scope),
+                    )
+                })
+                .await;
+
+            if let Err(RemoteVmError::DeadlineExceeded { deadline }) = &res {
+                events.publish(EventKind::WorkflowTimedOut {
+                    workflow: wf_id.clone(),
+                    app_id:   app_id.to_string(),
+                    user:     wf_user.clone(),
+                    deadline: deadline.as_secs(),
+                });
+            } else {
+                events.publish(EventKind::WorkflowFinished {
+                    workflow: wf_id.clone(),
+                    app_id:   app_id.to_string(),
+                    user:     wf_user.clone(),
+                    success:  res.is_ok(),
+                });
+            }
 
-            // Insert the VM again
+            // Only keep the checkpoint if there is something to resume
+            if let Some(checkpoint) = &checkpoint {
+                if res.is_ok() {
+                    if let Err(err) = checkpoint.finish() {
+                        error!("{}", trace!(("Failed to remove checkpoint of workflow '{wf_id}'"), err));
+                    }
+                } else {
+                    let reply = ExecuteReply {
+                        close:   false,
+                        debug:   None,
+                        stderr:  Some(format!("Workflow '{wf_id}' was checkpointed; resume it with 'brane workflow resume {wf_id}'\n")),
+                        stdout:  None,
+                        value:   None,
+                        profile: None,
+                        event:   None,
+                    };
+                    if let Err(err) = tx.send(Ok(reply)).await {
+                        error!("{}", trace!(("Failed to send checkpoint hint back to client"), err));
+                    }
+                }
+            }
+
+            // Tell the client where to find the intermediate results the workflow produced
+            if let Some(history) = &history {
+                if res.is_ok() && history.recorded() > 0 {
+                    let reply = ExecuteReply {
+                        close:   false,
+                        debug:   None,
+                        stderr:  Some(format!(
+                            "Workflow '{wf_id}' produced {} intermediate result(s); inspect them with 'brane workflow results {wf_id}'\n",
+                            history.recorded()
+                        )),
+                        stdout:  None,
+                        value:   None,
+                        profile: None,
+                        event:   None,
+                    };
+                    if let Err(err) = tx.send(Ok(reply)).await {
+                        error!("{}", trace!(("Failed to send history hint back to client"), err));
+                    }
+                }
+            }
+
+            // Insert the VM again (claiming the session for the workflow's user if it was created anonymously), and share it with other replicas
             debug!("Saving state session state");
-            sessions.insert(app_id, (vm, Instant::now()));
+            let session: Session =
+                Session { vm, user: session.user.or(wf_user), last_activity: SystemTime::now(), revision: session.revision + 1, ..session };
+            if let Some(store) = &store {
+                if let Err(err) = store.save(&app_id, &session.record(store.replica())) {
+                    error!("{}", trace!(("Failed to save session '{app_id}' (other replicas cannot take it over)"), err));
+                }
+            }
+            sessions.insert(app_id, session);
+            drop(lock);
 
             // Switch on the actual result and send that back to the user
             match res {
@@ -364,34 +1102,42 @@ impl DriverService for DriverHandler {
                     let sres: String = match serde_json::to_string(&res) {
                         Ok(sres) => sres,
                         Err(err) => {
-                            fatal_err!(tx, Status::internal, err);
+                            fatal_err!(tx, Status::internal, ErrorCode::Internal, err);
                         },
                     };
 
                     // Create the reply text
                     let msg = String::from("Driver completed execution.");
-                    let reply = ExecuteReply { close: true, debug: Some(msg.clone()), stderr: None, stdout: None, value: Some(sres) };
+                    let reply = ExecuteReply {
+                        close:   true,
+                        debug:   Some(msg.clone()),
+                        stderr:  None,
+                        stdout:  None,
+                        value:   Some(sres),
+                        profile: None,
+                        event:   None,
+                    };
 
                     // Send it
                     if let Err(err) = tx.send(Ok(reply)).await {
                         error!("{}", trace!(("Failed to send workflow result back to client"), err));
                     }
                 },
-                Err(RemoteVmError::PlanError { err: PlanError::CheckerDenied { domain, reasons } }) => {
-                    fatal_err!(
-                        tx,
-                        Status::permission_denied(format!(
-                            "Checker of domain '{domain}' denied execution{}",
-                            if !reasons.is_empty() {
-                                format!("\n\nReasons:\n{}\n", reasons.iter().map(|r| format!(" - {r}")).collect::<Vec<String>>().join("\n"))
-                            } else {
-                                String::new()
-                            }
-                        ))
-                    );
-                },
-                Err(err) => {
-                    fatal_err!(tx, Status::internal, err);
+                Err(err) => match policy_denial(err) {
+                    Ok(denial) => {
+                        let status: Status = Status::with_details(Code::PermissionDenied, denial.to_string(), denial.to_bytes().into());
+                        fatal_err!(tx, ErrorCode::DrvPolicyDenied.attach(status));
+                    },
+                    Err(err @ RemoteVmError::DeadlineExceeded { .. }) => {
+                        fatal_err!(tx, Status::deadline_exceeded, ErrorCode::DrvDeadlineExceeded, err);
+                    },
+                    Err(err) => {
+                        if let Some(pending) = approval_pending(&err) {
+                            let status: Status = Status::with_details(Code::FailedPrecondition, pending.to_string(), pending.to_bytes().into());
+                            fatal_err!(tx, ErrorCode::RegApprovalPending.attach(status));
+                        }
+                        fatal_err!(tx, Status::internal, error_code(&err), err);
+                    },
                 },
             };
         });
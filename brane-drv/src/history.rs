@@ -0,0 +1,249 @@
+//  HISTORY.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:51:30
+//  Last edited:
+//    16 Oct 2026, 01:42:13
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the execution history of workflows, which remembers the
+//!   intermediate results that every execution produced such that they
+//!   can be inspected (and downloaded or promoted to a dataset) after the
+//!   workflow completed.
+//!
+//!   Every execution has a directory `<HISTORY_DIR>/<WORKFLOW_ID>`,
+//!   containing a journal of produced results (`results.jsonl`). Unlike
+//!   checkpoints, the history is kept after the workflow completes, until
+//!   it is older than the retention period of the driver.
+//
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead as _, BufReader, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use brane_ast::locations::Location;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::HistoryError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the file with the produced results in a history directory.
+const RESULTS_FILE: &str = "results.jsonl";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Resolves the directory of the history of an execution.
+///
+/// # Arguments
+/// - `root`: The directory in which the driver keeps its execution history.
+/// - `id`: The identifier of the execution (i.e., of its workflow).
+///
+/// # Returns
+/// The path to the history directory.
+///
+/// # Errors
+/// This function errors if the identifier is not a valid workflow identifier, e.g., because it would escape the `root`.
+fn history_dir(root: &Path, id: &str) -> Result<PathBuf, Error> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::IllegalId { id: id.into() });
+    }
+    Ok(root.join(id))
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a single intermediate result that was produced, as recorded in the history.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResultRecord {
+    /// The name of the intermediate result.
+    pub name:     String,
+    /// The task that produced it (as `<package>[<version>]::<function>`), if it was produced by one.
+    pub task:     Option<String>,
+    /// The program counter (as `<func>:<edge>`) of the call that produced it.
+    pub pc:       String,
+    /// The datasets and intermediate results that the call that produced it may have computed on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs:   Vec<String>,
+    /// The location where the result lives.
+    pub location: Location,
+    /// When the result was produced, as seconds since the Unix epoch.
+    pub produced: u64,
+    /// The user on whose behalf the workflow that produced it ran, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user:     Option<String>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Represents the history of a single execution, to which it records the intermediate results it produces.
+#[derive(Debug)]
+pub struct ExecutionHistory {
+    /// The directory of this history.
+    dir:      PathBuf,
+    /// The journal to which we append newly produced results.
+    results:  Mutex<File>,
+    /// The user on whose behalf the execution runs, if known.
+    user:     Option<String>,
+    /// The number of results recorded by this run.
+    recorded: Mutex<usize>,
+}
+
+impl ExecutionHistory {
+    /// Opens the history of an execution that is just submitted (or resumed) to record its results.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which the driver keeps its execution history.
+    /// - `id`: The identifier of the execution (i.e., of its workflow).
+    /// - `user`: The user on whose behalf the execution runs, if known. Only they may inspect its results later.
+    ///
+    /// # Returns
+    /// A new ExecutionHistory that appends to any results recorded by a previous run of the same workflow.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the history directory or its journal.
+    pub fn create(root: impl AsRef<Path>, id: &str, user: Option<String>) -> Result<Self, Error> {
+        let dir: PathBuf = history_dir(root.as_ref(), id)?;
+        debug!("Opening execution history '{}'...", dir.display());
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreateError { path: dir, err });
+        }
+
+        let results_path: PathBuf = dir.join(RESULTS_FILE);
+        let results: File = match OpenOptions::new().create(true).append(true).open(&results_path) {
+            Ok(results) => results,
+            Err(err) => return Err(Error::ResultsOpenError { path: results_path, err }),
+        };
+
+        Ok(Self { dir, results: Mutex::new(results), user, recorded: Mutex::new(0) })
+    }
+
+    /// Loads the results recorded in the history of an execution.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which the driver keeps its execution history.
+    /// - `id`: The identifier of the execution (i.e., of its workflow).
+    ///
+    /// # Returns
+    /// A tuple of the recorded [`ResultRecord`]s, in the order they were produced, and when the history was last written to.
+    ///
+    /// # Errors
+    /// This function errors if there is no history for the given execution or if we failed to read it.
+    pub fn load(root: impl AsRef<Path>, id: &str) -> Result<(Vec<ResultRecord>, SystemTime), Error> {
+        let dir: PathBuf = history_dir(root.as_ref(), id)?;
+        debug!("Loading execution history '{}'...", dir.display());
+        let results_path: PathBuf = dir.join(RESULTS_FILE);
+        if !results_path.exists() {
+            return Err(Error::UnknownExecution { id: id.into(), path: dir });
+        }
+
+        let results: File = match File::open(&results_path) {
+            Ok(results) => results,
+            Err(err) => return Err(Error::ResultsOpenError { path: results_path, err }),
+        };
+        let modified: SystemTime = match results.metadata().and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(err) => return Err(Error::ResultsReadError { path: results_path, err }),
+        };
+        let mut records: Vec<ResultRecord> = vec![];
+        for (i, line) in BufReader::new(results).lines().enumerate() {
+            let line: String = match line {
+                Ok(line) => line,
+                Err(err) => return Err(Error::ResultsReadError { path: results_path, err }),
+            };
+            // A half-written line means the driver was interrupted while recording; we just don't know about that result
+            match serde_json::from_str(&line) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!("Failed to parse line {} of execution history '{}': {} (skipping entry)", i + 1, results_path.display(), err),
+            }
+        }
+        Ok((records, modified))
+    }
+
+    /// Removes the history of every execution that has not been written to for the given period.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which the driver keeps its execution history.
+    /// - `retention`: How long the history of an execution is kept after it was last written to.
+    ///
+    /// # Returns
+    /// The number of executions whose history was removed.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the `root` directory. Failing to remove a single history is only logged.
+    pub fn prune(root: impl AsRef<Path>, retention: Duration) -> Result<usize, Error> {
+        let root: &Path = root.as_ref();
+        let entries: fs::ReadDir = match fs::read_dir(root) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(Error::DirReadError { path: root.into(), err }),
+        };
+
+        let mut removed: usize = 0;
+        for entry in entries {
+            let dir: PathBuf = match entry {
+                Ok(entry) => entry.path(),
+                Err(err) => return Err(Error::DirReadError { path: root.into(), err }),
+            };
+            // NOTE: If the clock went backwards, we consider the history to be written just now
+            let age: Duration = match fs::metadata(dir.join(RESULTS_FILE)).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified.elapsed().unwrap_or_default(),
+                Err(_) => continue,
+            };
+            if age >= retention {
+                info!("Removing execution history '{}' because it is {} seconds old", dir.display(), age.as_secs());
+                match fs::remove_dir_all(&dir) {
+                    Ok(_) => removed += 1,
+                    Err(err) => warn!("Failed to remove execution history '{}': {}", dir.display(), err),
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Records that an intermediate result has been produced.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the intermediate result.
+    /// - `task`: The task that produced it (as `<package>[<version>]::<function>`), if any.
+    /// - `pc`: The program counter (as `<func>:<edge>`) of the call that produced it.
+    /// - `inputs`: The names of the datasets and intermediate results that the call may have computed on.
+    /// - `location`: The location where the result lives.
+    ///
+    /// # Errors
+    /// This function errors if we failed to write the journal.
+    pub fn record(&self, name: String, task: Option<String>, pc: String, inputs: Vec<String>, location: Location) -> Result<(), Error> {
+        let produced: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let mut line: String = match serde_json::to_string(&ResultRecord { name, task, pc, inputs, location, produced, user: self.user.clone() }) {
+            Ok(line) => line,
+            Err(err) => return Err(Error::ResultsSerializeError { err }),
+        };
+        line.push('\n');
+
+        let mut results = self.results.lock().unwrap();
+        if let Err(err) = results.write_all(line.as_bytes()).and_then(|_| results.sync_data()) {
+            return Err(Error::ResultsWriteError { path: self.dir.join(RESULTS_FILE), err });
+        }
+        *self.recorded.lock().unwrap() += 1;
+        Ok(())
+    }
+
+    /// Returns the number of intermediate results recorded by this run.
+    #[inline]
+    pub fn recorded(&self) -> usize { *self.recorded.lock().unwrap() }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 12:00:46
 //  Last edited:
-//    06 Feb 2024, 11:46:27
+//    16 Oct 2026, 00:55:26
 //  Auto updated?
 //    Yes
 //
@@ -17,9 +17,12 @@
 
 // Declare the modules
 pub mod check;
+pub mod checkpoint;
 pub mod errors;
 pub mod gc;
 pub mod handler;
+pub mod history;
 pub mod planner;
 pub mod spec;
+pub mod store;
 pub mod vm;
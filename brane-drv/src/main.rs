@@ -4,7 +4,7 @@
 //  Created:
 //    30 Sep 2022, 11:59:58
 //  Last edited:
-//    08 Feb 2024, 17:08:36
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -19,12 +19,18 @@ use std::time::Duration;
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{CentralConfig, NodeConfig};
 use brane_drv::handler::DriverHandler;
+use brane_drv::store::SessionStore;
 use brane_prx::client::ProxyClient;
+use brane_shr::chaos::{self, ChaosConfig};
+use brane_shr::events::EventPublisher;
+use brane_shr::logging::{self, LogFormat};
+use brane_tsk::spec::AppId;
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::trace;
 use log::{debug, error, info, warn, LevelFilter};
 use specifications::driving::DriverServiceServer;
+use specifications::reflection;
 use tokio::signal::unix::{signal, Signal, SignalKind};
 use tonic::transport::Server;
 
@@ -51,6 +57,86 @@ struct Opts {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
+
+    /// The faults to inject in chaos mode, if any.
+    #[clap(
+        long,
+        help = "If given, injects faults to test resilience, e.g., 'latency=250ms@0.5,drop=0.1,fail=0.05,seed=42' (see \
+                `brane_shr::chaos::ChaosConfig`). Only has an effect in debug builds.",
+        env = "BRANE_CHAOS"
+    )]
+    chaos: Option<ChaosConfig>,
+
+    /// The maximum memory that a single workflow may use for its values, in MiB.
+    #[clap(
+        long,
+        help = "If given, limits the memory that a single workflow (or REPL session) may use for its variables and intermediate values to this many \
+                MiB. Workflows that exceed it fail instead of exhausting the driver's memory.",
+        env = "MAX_WORKFLOW_MEMORY"
+    )]
+    max_workflow_memory: Option<usize>,
+
+    /// The directory in which to checkpoint running workflows.
+    #[clap(
+        long,
+        help = "If given, checkpoints running workflows in this directory, such that workflows that are interrupted can be resumed from their last \
+                completed task with `brane workflow resume`.",
+        env = "CHECKPOINT_DIR"
+    )]
+    checkpoint_dir: Option<PathBuf>,
+
+    /// The directory in which to keep the history of executions.
+    #[clap(
+        long,
+        help = "If given, records the intermediate results that every workflow produces in this directory, such that they can be inspected, \
+                downloaded or promoted to a dataset later with `brane workflow results`.",
+        env = "HISTORY_DIR"
+    )]
+    history_dir: Option<PathBuf>,
+    /// How long to keep the history of an execution.
+    #[clap(
+        long,
+        default_value = "7",
+        help = "The number of days that the history of an execution is kept after it last produced an intermediate result.",
+        env = "HISTORY_RETENTION"
+    )]
+    history_retention: u64,
+
+    /// The directory in which to keep sessions, shared with other replicas.
+    #[clap(
+        long,
+        help = "If given, keeps sessions in this directory such that multiple replicas of brane-drv behind a load balancer can serve them. Share it \
+                between the replicas (e.g., on a network file system); any replica then takes over a session that another one used last, and a \
+                session executes one workflow at a time across all of them.",
+        env = "STATE_DIR"
+    )]
+    state_dir:  Option<PathBuf>,
+    /// The identifier of this replica.
+    #[clap(
+        long,
+        help = "The identifier of this replica, which is shown when another one holds a session. Defaults to the hostname, or a random identifier \
+                if that is unknown.",
+        env = "REPLICA_ID"
+    )]
+    replica_id: Option<String>,
+
+    /// Whether clients must authenticate themselves.
+    #[clap(
+        long,
+        help = "If given, rejects clients that do not authenticate themselves with an API token (see `brane instance edit --token`). Otherwise, \
+                clients may still do so to have their workflows run as their user.",
+        env = "REQUIRE_AUTH"
+    )]
+    require_auth: bool,
 }
 
 
@@ -64,14 +150,13 @@ async fn main() {
     let opts = Opts::parse();
 
     // Configure logger.
-    let mut logger = env_logger::builder();
-    logger.format_module_path(false);
-    if opts.debug {
-        logger.filter_level(LevelFilter::Debug).init();
-    } else {
-        logger.filter_level(LevelFilter::Info).init();
+    if let Err(err) = logging::init("brane-drv", opts.log_format, if opts.debug { LevelFilter::Debug } else { LevelFilter::Info }) {
+        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
     }
     info!("Initializing brane-drv v{}...", env!("CARGO_PKG_VERSION"));
+    if let Some(chaos) = opts.chaos {
+        chaos::init("brane-drv", chaos);
+    }
 
     // Load the config, making sure it's a central config
     debug!("Loading node.yml file '{}'...", opts.node_config_path.display());
@@ -82,6 +167,19 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    if let Some(tracing) = &node_config.tracing {
+        tracing.init("brane-drv");
+    }
+    let events: EventPublisher = match &node_config.events {
+        Some(events) => match events.publisher("brane-drv").await {
+            Ok(events) => events,
+            Err(err) => {
+                error!("{}", trace!(("Failed to connect to event bus"), err));
+                std::process::exit(1);
+            },
+        },
+        None => EventPublisher::disabled(),
+    };
     let central: CentralConfig = match node_config.node.try_into_central() {
         Some(central) => central,
         None => {
@@ -90,14 +188,58 @@ async fn main() {
         },
     };
 
+    // Open the store shared with other replicas, if any
+    let store: Option<SessionStore> = match opts.state_dir {
+        Some(dir) => {
+            let replica: String = opts.replica_id.or_else(|| std::env::var("HOSTNAME").ok()).unwrap_or_else(|| AppId::generate().to_string());
+            info!("Sharing sessions with other replicas in '{}' as replica '{}'", dir.display(), replica);
+            match SessionStore::new(&dir, replica) {
+                Ok(store) => Some(store),
+                Err(err) => {
+                    error!("{}", trace!(("Failed to open session store"), err));
+                    std::process::exit(1);
+                },
+            }
+        },
+        None => None,
+    };
+
     // Start the DriverHandler
-    let handler = DriverHandler::new(&opts.node_config_path, Arc::new(ProxyClient::new(central.services.prx.address())));
+    let memory_limit: Option<usize> = opts.max_workflow_memory.map(|mib| mib.saturating_mul(1024 * 1024));
+    let handler = DriverHandler::new(
+        &opts.node_config_path,
+        Arc::new(ProxyClient::new(central.services.prx.address())),
+        events,
+        memory_limit,
+        opts.checkpoint_dir,
+        opts.history_dir,
+        Duration::from_secs(opts.history_retention.saturating_mul(24 * 3600)),
+        store,
+        opts.require_auth,
+    );
+
+    // Prepare the standard health and reflection services alongside it, for probes and tools like `grpcurl`
+    let (mut health, health_service) = tonic_health::server::health_reporter();
+    health.set_serving::<DriverServiceServer<DriverHandler>>().await;
+    let reflection_service = match tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .register_file_descriptor_set(reflection::descriptor_set())
+        .build()
+    {
+        Ok(service) => service,
+        Err(err) => {
+            error!("{}", trace!(("Failed to build gRPC reflection service"), err));
+            std::process::exit(1);
+        },
+    };
 
     // Start gRPC server with callback service.
     debug!("gRPC server ready to serve on '{}'", central.services.drv.bind);
     if let Err(err) = Server::builder()
         .add_service(DriverServiceServer::new(handler))
-        .serve_with_shutdown(central.services.drv.bind, async {
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .serve_with_shutdown(central.services.drv.bind, async move {
             // Register a SIGTERM handler to be Docker-friendly
             let mut handler: Signal = match signal(SignalKind::terminate()) {
                 Ok(handler) => handler,
@@ -113,6 +255,7 @@ async fn main() {
             // Wait until we receive such a signal after which we terminate the server
             handler.recv().await;
             info!("Received SIGTERM, shutting down gracefully...");
+            health.set_not_serving::<DriverServiceServer<DriverHandler>>().await;
         })
         .await
     {
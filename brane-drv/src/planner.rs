@@ -4,7 +4,7 @@
 //  Created:
 //    25 Oct 2022, 11:35:00
 //  Last edited:
-//    08 Feb 2024, 17:27:11
+//    16 Oct 2026, 00:18:28
 //  Auto updated?
 //    Yes
 //
@@ -21,8 +21,10 @@ use log::debug;
 use reqwest::{Client, Request, Response, StatusCode};
 use serde_json::Value;
 use specifications::address::Address;
-use specifications::planning::{PlanningDeniedReply, PlanningReply, PlanningRequest};
+use specifications::errors::ErrorCode;
+use specifications::planning::{PlanningDeniedReply, PlanningReply, PlanningRequest, ResidencyViolation};
 use specifications::profiling::ProfileScopeHandle;
+use specifications::telemetry::{Span, TraceContext};
 
 
 /***** LIBRARY *****/
@@ -37,11 +39,18 @@ impl InstancePlanner {
     /// - `plr`: The address of the remote planner to connect to.
     /// - `app_id`: The session ID for this workflow.
     /// - `workflow`: The Workflow to plan.
+    /// - `trace`: The [`TraceContext`] of the workflow, which is propagated to the planner.
     /// - `prof`: The ProfileScope that can be used to provide additional information about the timings of the planning (driver-side).
     ///
     /// # Returns
     /// The same workflow as given, but now with all tasks and data transfers planned.
-    pub async fn plan(plr: &Address, app_id: AppId, workflow: Workflow, prof: ProfileScopeHandle<'_>) -> Result<Workflow, PlanError> {
+    pub async fn plan(
+        plr: &Address,
+        app_id: AppId,
+        workflow: Workflow,
+        trace: &TraceContext,
+        prof: ProfileScopeHandle<'_>,
+    ) -> Result<Workflow, PlanError> {
         // Generate the ID
         let task_id: String = format!("{}", TaskId::generate());
 
@@ -69,7 +78,8 @@ impl InstancePlanner {
         let remote = prof.time(format!("workflow '{task_id}' on brane-plr"));
         let url: String = format!("{plr}/plan");
         let client: Client = Client::new();
-        let req: Request = match client.post(&url).body(sreq).build() {
+        let span: Span = Span::child("brane-drv plan", trace);
+        let req: Request = match span.context().inject_http(client.post(&url)).body(sreq).build() {
             Ok(req) => req,
             Err(err) => return Err(PlanError::PlanningRequest { id: workflow.id, url, err }),
         };
@@ -79,23 +89,39 @@ impl InstancePlanner {
             Err(err) => return Err(PlanError::PlanningRequestSend { id: workflow.id, url, err }),
         };
         let status: StatusCode = res.status();
+        let error_code: Option<ErrorCode> = ErrorCode::from_headers(res.headers());
         if status == StatusCode::UNAUTHORIZED {
             // Attempt to parse the response
             let res: String = match res.text().await {
                 Ok(res) => res,
                 // If errored, default to the other error
-                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, response: None }),
+                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, error_code, response: None }),
             };
             let res: PlanningDeniedReply = match serde_json::from_str(&res) {
                 Ok(res) => res,
                 // If errored, default to the other error
-                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, response: Some(res) }),
+                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, error_code, response: Some(res) }),
             };
 
             // Return it
             return Err(PlanError::CheckerDenied { domain: res.domain, reasons: res.reasons });
+        } else if error_code == Some(ErrorCode::PlrResidencyViolation) {
+            // Attempt to parse the response
+            let res: String = match res.text().await {
+                Ok(res) => res,
+                // If errored, default to the other error
+                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, error_code, response: None }),
+            };
+            let violation: ResidencyViolation = match serde_json::from_str(&res) {
+                Ok(violation) => violation,
+                // If errored, default to the other error
+                Err(_) => return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, error_code, response: Some(res) }),
+            };
+
+            // Return it
+            return Err(PlanError::ResidencyViolation { violation });
         } else if !status.is_success() {
-            return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, response: res.text().await.ok() });
+            return Err(PlanError::PlanningFailure { id: workflow.id, url, code: status, error_code, response: res.text().await.ok() });
         }
         remote.stop();
 
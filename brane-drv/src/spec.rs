@@ -4,7 +4,7 @@
 //  Created:
 //    28 Nov 2022, 16:08:36
 //  Last edited:
-//    08 Feb 2024, 16:54:16
+//    16 Oct 2026, 00:55:26
 //  Auto updated?
 //    Yes
 //
@@ -12,17 +12,29 @@
 //!   Defines (public) interfaces and structs for the `brane-drv` crate.
 //
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::SystemTime;
 
+use brane_ast::SymTable;
 use brane_cfg::infra::InfraFile;
+use brane_exe::errors::FrameStackError;
 use brane_exe::spec::CustomGlobalState;
+use brane_exe::FullValue;
 use brane_prx::client::ProxyClient;
 use brane_tsk::spec::AppId;
 use specifications::driving::ExecuteReply;
+use specifications::telemetry::TraceContext;
 use tokio::sync::mpsc::Sender;
+use tokio::time::Instant;
 use tonic::Status;
 
+use crate::checkpoint::Checkpoint;
+use crate::history::ExecutionHistory;
+use crate::store::SessionRecord;
+use crate::vm::InstanceVm;
+
 
 /***** LIBRARY *****/
 /// The global state for the RemoteVm.
@@ -36,9 +48,19 @@ pub struct GlobalState {
     pub proxy: Arc<ProxyClient>,
 
     /// The infra file for this session, which will be loaded when a new snippet is executed.
-    pub infra:    Option<InfraFile>,
+    pub infra:      Option<InfraFile>,
     /// The workflow for this session, which will be updated when a new one is received.
-    pub workflow: Option<String>,
+    pub workflow:   Option<String>,
+    /// The trace context of the workflow for this session, which is propagated to the workers we send requests to.
+    pub trace:      Option<TraceContext>,
+    /// The checkpoint of the workflow for this session, if checkpointing is enabled.
+    pub checkpoint: Option<Arc<Checkpoint>>,
+    /// The history of the execution of the workflow for this session, in which its intermediate results are recorded, if enabled.
+    pub history:    Option<Arc<ExecutionHistory>>,
+    /// The moment by which the workflow for this session must have completed, if it has a deadline.
+    pub deadline:   Option<Instant>,
+    /// The values returned by the tasks of the workflow for this session that completed so far, by program counter (as `<func>:<edge>`).
+    pub partial:    HashMap<String, FullValue>,
 
     /// The callback for the client to receive prints and other status updates on (such as the final result).
     ///
@@ -49,3 +71,71 @@ impl CustomGlobalState for GlobalState {}
 
 /// The local state for the RemoteVm is unused.
 pub type LocalState = ();
+
+
+
+/// An active REPL-session, which keeps the state of its VM in between workflows.
+#[derive(Clone)]
+pub struct Session {
+    /// The VM that carries the state of the session.
+    pub vm: InstanceVm,
+    /// The user who created the session, if known.
+    pub user: Option<String>,
+    /// When the session was created.
+    pub created: SystemTime,
+    /// When the session was last used, i.e., when it was created or last executed a workflow.
+    pub last_activity: SystemTime,
+    /// The number of workflows executed in the session, which tells whether another replica used it since (see [`SessionRecord`]).
+    pub revision: u64,
+}
+impl Session {
+    /// Constructor for a Session that has just been created.
+    ///
+    /// # Arguments
+    /// - `vm`: The [`InstanceVm`] that carries the state of the session.
+    /// - `user`: The user who created the session, if known.
+    ///
+    /// # Returns
+    /// A new Session instance.
+    #[inline]
+    pub fn new(vm: InstanceVm, user: Option<String>) -> Self {
+        let now: SystemTime = SystemTime::now();
+        Self { vm, user, created: now, last_activity: now, revision: 0 }
+    }
+
+    /// Restores a Session from its record in the [`SessionStore`](crate::store::SessionStore).
+    ///
+    /// # Arguments
+    /// - `vm`: A new [`InstanceVm`] to restore the state of the session in.
+    /// - `record`: The [`SessionRecord`] to restore.
+    ///
+    /// # Returns
+    /// A new Session instance.
+    ///
+    /// # Errors
+    /// This function errors if we failed to restore the variables of the session in the `vm`.
+    pub fn restore(mut vm: InstanceVm, record: SessionRecord) -> Result<Self, FrameStackError> {
+        vm.restore(record.table, record.variables)?;
+        Ok(Self { vm, user: record.user, created: record.created, last_activity: record.last_activity, revision: record.revision })
+    }
+
+    /// Creates the record of this Session to keep in the [`SessionStore`](crate::store::SessionStore).
+    ///
+    /// # Arguments
+    /// - `replica`: The identifier of this replica.
+    ///
+    /// # Returns
+    /// A new [`SessionRecord`] with the state of this session.
+    pub fn record(&self, replica: impl Into<String>) -> SessionRecord {
+        let (table, variables): (SymTable, HashMap<usize, FullValue>) = self.vm.snapshot();
+        SessionRecord {
+            user: self.user.clone(),
+            created: self.created,
+            last_activity: self.last_activity,
+            revision: self.revision,
+            replica: replica.into(),
+            table,
+            variables,
+        }
+    }
+}
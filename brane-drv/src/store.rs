@@ -0,0 +1,299 @@
+//  STORE.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:26:42
+//  Last edited:
+//    16 Oct 2026, 00:26:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a store of sessions in a directory that may be shared by
+//!   multiple replicas of the driver (e.g., on a network file system),
+//!   such that any of them can serve any session.
+//
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use brane_ast::SymTable;
+use brane_exe::FullValue;
+use brane_shr::fs::write_atomic;
+use brane_tsk::spec::AppId;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+pub use crate::errors::StoreError as Error;
+
+
+/***** CONSTANTS *****/
+/// The name of the directory in the store with a directory per session.
+const SESSIONS_DIR: &str = "sessions";
+/// The name of the file with the record of a session in its directory.
+const RECORD_FILE: &str = "session.json";
+/// The name of the file that locks a session in its directory.
+const LOCK_FILE: &str = "lock";
+
+/// The interval at which the holder of a session lock shows that it is still alive.
+const LOCK_RENEW_INTERVAL: Duration = Duration::from_secs(10);
+/// The time after which a session lock that has not been renewed is considered abandoned (e.g., because its replica crashed).
+const LOCK_TIMEOUT: Duration = Duration::from_secs(60);
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the state of a session as it is kept in the [`SessionStore`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SessionRecord {
+    /// The user who created the session, if known.
+    pub user: Option<String>,
+    /// When the session was created.
+    pub created: SystemTime,
+    /// When the session was last used, i.e., when it was created or last executed a workflow.
+    pub last_activity: SystemTime,
+    /// The number of workflows executed in the session. Replicas with an older revision of the session have to take it over again.
+    pub revision: u64,
+    /// The replica that last served the session.
+    pub replica: String,
+
+    /// The symbol table that defines the variables of the session.
+    pub table:     SymTable,
+    /// The values of the global variables of the session, by definition index.
+    pub variables: HashMap<usize, FullValue>,
+}
+
+
+
+/// Guards the exclusive right of a replica to execute workflows in a session. Releases it when dropped.
+#[derive(Debug)]
+pub struct SessionLock {
+    /// The path to the lock file.
+    path:  PathBuf,
+    /// The background task that renews the lock while we hold it.
+    renew: JoinHandle<()>,
+}
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        self.renew.abort();
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!("Failed to remove session lock '{}': {}", self.path.display(), err);
+        }
+    }
+}
+
+
+
+/// Keeps sessions in a directory that is shared by the replicas of the driver.
+#[derive(Debug)]
+pub struct SessionStore {
+    /// The directory with the sessions.
+    dir:     PathBuf,
+    /// The identifier of this replica.
+    replica: String,
+}
+impl SessionStore {
+    /// Constructor for the SessionStore.
+    ///
+    /// # Arguments
+    /// - `root`: The directory in which to keep the sessions. Replicas that share it can take over each other's sessions.
+    /// - `replica`: The identifier of this replica, which is used to show who holds a session.
+    ///
+    /// # Returns
+    /// A new SessionStore instance.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the directory.
+    pub fn new(root: impl AsRef<Path>, replica: impl Into<String>) -> Result<Self, Error> {
+        let dir: PathBuf = root.as_ref().join(SESSIONS_DIR);
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreateError { path: dir, err });
+        }
+        Ok(Self { dir, replica: replica.into() })
+    }
+
+    /// Returns the identifier of this replica.
+    #[inline]
+    pub fn replica(&self) -> &str { &self.replica }
+
+    /// Writes the record of a session, replacing any previous one.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session.
+    /// - `record`: The [`SessionRecord`] to write.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize or write the record.
+    pub fn save(&self, id: &AppId, record: &SessionRecord) -> Result<(), Error> {
+        let dir: PathBuf = self.dir.join(id.to_string());
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreateError { path: dir, err });
+        }
+        let raw: String = match serde_json::to_string(record) {
+            Ok(raw) => raw,
+            Err(err) => return Err(Error::RecordSerializeError { id: id.to_string(), err }),
+        };
+        let path: PathBuf = dir.join(RECORD_FILE);
+        debug!("Saving session record '{}' (revision {})...", path.display(), record.revision);
+        match write_atomic(&path, raw) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::RecordWriteError { path, err }),
+        }
+    }
+
+    /// Reads the record of a session.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session.
+    ///
+    /// # Returns
+    /// The [`SessionRecord`] of the session, or [`None`] if the store does not know it.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the record.
+    pub fn load(&self, id: &AppId) -> Result<Option<SessionRecord>, Error> { Self::read(&self.dir.join(id.to_string()).join(RECORD_FILE)) }
+
+    /// Lists the records of all sessions in the store.
+    ///
+    /// Records that cannot be read are skipped (they may be written by another replica at the same time).
+    ///
+    /// # Returns
+    /// The identifiers of the sessions together with their [`SessionRecord`]s, in no particular order.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the directory of the store.
+    pub fn list(&self) -> Result<Vec<(String, SessionRecord)>, Error> {
+        let entries: fs::ReadDir = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) => return Err(Error::DirReadError { path: self.dir.clone(), err }),
+        };
+        let mut records: Vec<(String, SessionRecord)> = vec![];
+        for entry in entries {
+            let entry: fs::DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err) => return Err(Error::DirReadError { path: self.dir.clone(), err }),
+            };
+            match Self::read(&entry.path().join(RECORD_FILE)) {
+                Ok(Some(record)) => records.push((entry.file_name().to_string_lossy().into_owned(), record)),
+                Ok(None) => {},
+                Err(err) => warn!("{err} (skipping session)"),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Removes a session from the store.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session.
+    ///
+    /// # Errors
+    /// This function errors if we failed to remove its directory.
+    pub fn remove(&self, id: &str) -> Result<(), Error> {
+        let dir: PathBuf = self.dir.join(id);
+        debug!("Removing session '{}' from store...", dir.display());
+        match fs::remove_dir_all(&dir) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::DirRemoveError { path: dir, err }),
+        }
+    }
+
+    /// Locks a session such that only we execute workflows in it.
+    ///
+    /// Locks held by replicas that stopped renewing them (e.g., because they crashed) are taken over.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the session.
+    ///
+    /// # Returns
+    /// A [`SessionLock`] that holds the lock until it is dropped.
+    ///
+    /// # Errors
+    /// This function errors if another replica holds the lock, or if we failed to create the lock file.
+    pub fn lock(&self, id: &AppId) -> Result<SessionLock, Error> {
+        let dir: PathBuf = self.dir.join(id.to_string());
+        if let Err(err) = fs::create_dir_all(&dir) {
+            return Err(Error::DirCreateError { path: dir, err });
+        }
+        let path: PathBuf = dir.join(LOCK_FILE);
+
+        // Try at most twice, since we may have to remove an abandoned lock first
+        let mut holder: String = String::new();
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    if let Err(err) = file.write_all(self.replica.as_bytes()) {
+                        let _ = fs::remove_file(&path);
+                        return Err(Error::LockCreateError { path, err });
+                    }
+                    debug!("Locked session '{}'", id);
+                    return Ok(SessionLock { path: path.clone(), renew: tokio::spawn(renew(path, self.replica.clone())) });
+                },
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                    holder = fs::read_to_string(&path).unwrap_or_default();
+                    let age: Option<Duration> = fs::metadata(&path).and_then(|meta| meta.modified()).ok().and_then(|time| time.elapsed().ok());
+                    if age.map(|age| age < LOCK_TIMEOUT).unwrap_or(true) {
+                        break;
+                    }
+                    warn!("Taking over session '{}' from replica '{}', which has not renewed its lock for {}s", id, holder, LOCK_TIMEOUT.as_secs());
+                    if let Err(err) = fs::remove_file(&path) {
+                        if err.kind() != ErrorKind::NotFound {
+                            return Err(Error::LockCreateError { path, err });
+                        }
+                    }
+                },
+                Err(err) => return Err(Error::LockCreateError { path, err }),
+            }
+        }
+        Err(Error::SessionLocked { id: id.to_string(), holder })
+    }
+
+    /// Reads a session record from the given path.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the record.
+    ///
+    /// # Returns
+    /// The [`SessionRecord`], or [`None`] if it does not exist.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the record.
+    fn read(path: &Path) -> Result<Option<SessionRecord>, Error> {
+        let raw: String = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::RecordReadError { path: path.into(), err }),
+        };
+        match serde_json::from_str(&raw) {
+            Ok(record) => Ok(Some(record)),
+            Err(err) => Err(Error::RecordParseError { path: path.into(), err }),
+        }
+    }
+}
+
+
+
+/// Can be run as a `tokio` background task to periodically show that we still hold a session lock.
+///
+/// # Arguments
+/// - `path`: The path of the lock file.
+/// - `replica`: The identifier of this replica, which is written to the lock file.
+///
+/// # Returns
+/// Never, until the task is aborted when the [`SessionLock`] is dropped.
+async fn renew(path: PathBuf, replica: String) {
+    loop {
+        tokio::time::sleep(LOCK_RENEW_INTERVAL).await;
+        // Rewriting the file updates its modification time
+        if let Err(err) = fs::write(&path, &replica) {
+            warn!("Failed to renew session lock '{}': {}", path.display(), err);
+        }
+    }
+}
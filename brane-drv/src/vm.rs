@@ -4,7 +4,7 @@
 //  Created:
 //    27 Oct 2022, 10:14:26
 //  Last edited:
-//    07 Mar 2024, 14:18:12
+//    16 Oct 2026, 01:42:13
 //  Auto updated?
 //    Yes
 //
@@ -15,34 +15,48 @@
 //!   complicating the `stdout()` function.
 //
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use brane_ast::ast::{Edge, SymTable};
 use brane_ast::func_id::FunctionId;
 use brane_ast::locations::Location;
 use brane_ast::Workflow;
 use brane_cfg::info::Info as _;
 use brane_cfg::infra::InfraFile;
 use brane_cfg::node::{CentralConfig, NodeConfig, NodeSpecificConfig};
+use brane_exe::errors::FrameStackError;
 use brane_exe::pc::ProgramCounter;
-use brane_exe::spec::{TaskInfo, VmPlugin};
+use brane_exe::spec::{PrintInfo, TaskInfo, VmPlugin};
 use brane_exe::{Error as VmError, FullValue, RunState, Vm};
 use brane_prx::client::ProxyClient;
+use brane_prx::spec::NewPathRequestTlsOptions;
 use brane_tsk::errors::{CommitError, ExecuteError, PreprocessError, StdoutError, StringError};
 use brane_tsk::spec::{AppId, JobStatus};
 use enum_debug::EnumDebug as _;
+use error_trace::trace;
 use log::{debug, info, warn};
+use reqwest::StatusCode;
 use serde_json_any_key::MapIterToJson;
 use specifications::address::Address;
-use specifications::data::{AccessKind, DataName, PreprocessKind};
+use specifications::checking::{ApprovalPending, PolicyDenial};
+use specifications::data::{AccessKind, DataName, PreprocessKind, Provenance};
+use specifications::driving::{ExecuteEvent, TaskState};
 use specifications::profiling::ProfileScopeHandle;
-use specifications::working::TransferRegistryTar;
+use specifications::reporting::ResourceUsage;
+use specifications::telemetry::{Span, TraceContext};
+use specifications::working::{TransferRegistryTar, TASK_HEARTBEAT_TIMEOUT};
 use specifications::{driving as driving_grpc, working as working_grpc};
 use tokio::sync::mpsc::Sender;
-use tonic::{Response, Status, Streaming};
+use tokio::time::Instant;
+use tonic::{Code, Response, Status, Streaming};
 
+use crate::checkpoint::Checkpoint;
 pub use crate::errors::RemoteVmError as Error;
+use crate::history::ExecutionHistory;
 use crate::planner::InstancePlanner;
 use crate::spec::{GlobalState, LocalState};
 
@@ -61,6 +75,467 @@ macro_rules! mundane_status_update {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Sends a progress event to the client.
+///
+/// # Arguments
+/// - `tx`: The transmission channel to send the event on.
+/// - `event`: The [`ExecuteEvent`] to send.
+async fn send_event(tx: &Sender<Result<driving_grpc::ExecuteReply, Status>>, event: ExecuteEvent) {
+    let reply: driving_grpc::ExecuteReply = driving_grpc::ExecuteReply {
+        close:   false,
+        debug:   None,
+        stdout:  None,
+        stderr:  None,
+        value:   None,
+        profile: None,
+        event:   Some(serde_json::to_string(&event).unwrap()),
+    };
+    if let Err(err) = tx.send(Ok(reply)).await {
+        warn!("Failed to send progress event to client: {}", err);
+    }
+}
+
+/// Sends a progress event about a task call to the client, if there is one listening.
+///
+/// # Arguments
+/// - `global`: The [`GlobalState`] of the VM, which carries the channel to the client.
+/// - `pc`: The [`ProgramCounter`] of the task call.
+/// - `state`: The [`TaskState`] that the call is now in.
+/// - `message`: Some additional information to send along, if any.
+async fn send_task_event(global: &Arc<RwLock<GlobalState>>, pc: ProgramCounter, state: TaskState, message: Option<String>) {
+    let tx: Option<Arc<Sender<Result<driving_grpc::ExecuteReply, Status>>>> = global.read().unwrap().tx.clone();
+    if let Some(tx) = tx {
+        send_event(&tx, ExecuteEvent::Task { pc: pc.to_string(), state, message }).await;
+    }
+}
+
+/// Collects where every task call in a planned workflow will run.
+///
+/// # Arguments
+/// - `plan`: The planned [`Workflow`] to collect the locations of.
+///
+/// # Returns
+/// A map of the program counter of every task call (as `<func>:<edge>`) to the location where it runs.
+fn task_locations(plan: &Workflow) -> HashMap<String, String> {
+    let mut funcs: Vec<(FunctionId, &[Edge])> = vec![(FunctionId::Main, plan.graph.as_slice())];
+    funcs.extend(plan.funcs.iter().map(|(id, edges)| (FunctionId::Func(*id), edges.as_slice())));
+
+    let mut locations: HashMap<String, String> = HashMap::new();
+    for (func_id, edges) in funcs {
+        for (edge_idx, edge) in edges.iter().enumerate() {
+            if let Edge::Node { at: Some(at), .. } = edge {
+                locations.insert(ProgramCounter { func_id, edge_idx }.to_string(), at.clone());
+            }
+        }
+    }
+    locations
+}
+
+/// Executes a task on the delegate of the location it has been planned on.
+///
+/// # Arguments
+/// - `global`: The [`GlobalState`] of the VM, which carries the infrastructure, the proxy and the workflow.
+/// - `info`: The [`TaskInfo`] describing the task to execute.
+/// - `prof`: The ProfileScope that can be used to provide additional information about the timings of the execution.
+///
+/// # Returns
+/// The value returned by the task, or [`None`] if it returned nothing.
+///
+/// # Errors
+/// This function errors if we failed to reach the delegate or if the task failed.
+async fn execute_remote(
+    global: &Arc<RwLock<GlobalState>>,
+    info: &TaskInfo<'_>,
+    prof: ProfileScopeHandle<'_>,
+) -> Result<Option<FullValue>, ExecuteError> {
+    info!("Executing task '{}' at '{}' in a distributed environment...", info.name, info.location);
+    debug!("Package: '{}' v{}", info.package_name, info.package_version);
+    debug!("Input data: {:?}", info.input.keys().map(|k| format!("{k}")).collect::<Vec<String>>());
+    debug!("Result: {:?}", info.result);
+    debug!("Input arguments: {:#?}", info.args);
+    debug!("Requirements: {:?}", info.requirements);
+
+    // Resolve the location to an address (and get the proxy and the workflow while we have a lock anyway)
+    let disk = prof.time("File loading");
+    let (proxy, delegate_address, workflow): (Arc<ProxyClient>, Address, String) = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+
+        // Resolve to an address and return that with the other addresses
+        (
+            state.proxy.clone(),
+            match state.infra.as_ref().unwrap().get(info.location) {
+                Some(info) => info.delegate.clone(),
+                None => {
+                    return Err(ExecuteError::UnknownLocationError { loc: info.location.clone() });
+                },
+            },
+            state.workflow.as_ref().unwrap().clone(),
+        )
+    };
+    disk.stop();
+
+    // Prepare the request to send to the delegate node
+    debug!("Sending execute request to job node '{}'...", delegate_address);
+    let job = prof.time(format!("on {delegate_address}"));
+    let message: working_grpc::ExecuteRequest = working_grpc::ExecuteRequest {
+        // NOTE: For now, we hardcode the central orchestrator as only "use-case" (registry)
+        use_case: "central".into(),
+
+        workflow,
+        call_pc: specifications::working::ProgramCounter {
+            func_id:  if let FunctionId::Func(id) = info.pc.func_id { id as u64 } else { u64::MAX },
+            edge_idx: info.pc.edge_idx as u64,
+        },
+        task_def: info.def as u64,
+
+        input: info.input.to_json_map().unwrap(),
+        result: info.result.clone(),
+        args: serde_json::to_string(&info.args).unwrap(),
+
+        stream_output: Some(true),
+    };
+
+    // Create the client
+    let mut client: working_grpc::JobServiceClient = match proxy.connect_to_job(delegate_address.to_string()).await {
+        Ok(result) => match result {
+            Ok(client) => client,
+            Err(err) => {
+                return Err(ExecuteError::GrpcConnectError { endpoint: delegate_address, err });
+            },
+        },
+        Err(err) => {
+            return Err(ExecuteError::ProxyError { err: Box::new(err) });
+        },
+    };
+
+    // Send the request to the job node, as part of the workflow's trace
+    let mut span: Span = Span::child("brane-drv execute", &global.read().unwrap().trace.unwrap_or_default());
+    span.set_attribute("brane.location", &delegate_address);
+    let mut request: tonic::Request<working_grpc::ExecuteRequest> = tonic::Request::new(message);
+    span.context().inject_grpc(&mut request);
+    let response: Response<Streaming<working_grpc::ExecuteReply>> = match client.execute(request).await {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(ExecuteError::GrpcRequestError { what: "ExecuteRequest", endpoint: delegate_address, err });
+        },
+    };
+    let mut stream: Streaming<working_grpc::ExecuteReply> = response.into_inner();
+    // Get the TX to forward task output on (so that the lock does not live over an `.await`)
+    let tx: Option<Arc<Sender<Result<driving_grpc::ExecuteReply, Status>>>> = global.read().unwrap().tx.clone();
+
+    // Now we tick off incoming messages
+    let mut state: JobStatus = JobStatus::Unknown;
+    // let mut error : Option<String> = None;
+    let mut result: Result<FullValue, String> = Err("No response".into());
+    let mut heartbeats: bool = false;
+    loop {
+        // Once the delegate has shown that it sends heartbeats, we expect to hear from it regularly
+        let message: Result<Option<working_grpc::ExecuteReply>, Status> = if heartbeats {
+            match tokio::time::timeout(TASK_HEARTBEAT_TIMEOUT, stream.message()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    return Err(ExecuteError::HeartbeatTimeout {
+                        endpoint: delegate_address,
+                        name:     info.name.into(),
+                        status:   state.into(),
+                        timeout:  TASK_HEARTBEAT_TIMEOUT,
+                    });
+                },
+            }
+        } else {
+            stream.message().await
+        };
+        match message {
+            // The message itself went alright
+            Ok(Some(reply)) => {
+                // Pass any output of the task on to the client as debug information
+                if let Some(output) = reply.output {
+                    let channel: &str = match working_grpc::OutputChannel::try_from(output.channel) {
+                        Ok(working_grpc::OutputChannel::Stderr) => "stderr",
+                        _ => "stdout",
+                    };
+                    debug!("Task '{}' @ '{}' ({}): {}", info.name, info.location, channel, output.data.trim_end());
+                    if let Some(tx) = &tx {
+                        let reply: driving_grpc::ExecuteReply = driving_grpc::ExecuteReply {
+                            close:   false,
+                            debug:   Some(format!("[{}@{} {}] {}", info.name, info.location, channel, output.data.trim_end())),
+                            stdout:  None,
+                            stderr:  None,
+                            value:   None,
+                            profile: None,
+                            event:   None,
+                        };
+                        if let Err(err) = tx.send(Ok(reply)).await {
+                            warn!("Failed to forward task output to client: {}", err);
+                        }
+                    }
+                }
+
+                // Pass the resources used by the task on to the client as profiling information
+                if let Some(usage) = reply.usage {
+                    match serde_json::from_str::<ResourceUsage>(&usage) {
+                        Ok(usage) => {
+                            debug!("Task '{}' @ '{}' used: {}", info.name, info.location, usage);
+                            if let Some(tx) = &tx {
+                                let reply: driving_grpc::ExecuteReply = driving_grpc::ExecuteReply {
+                                    close:   false,
+                                    debug:   None,
+                                    stdout:  None,
+                                    stderr:  None,
+                                    value:   None,
+                                    profile: Some(format!("Task '{}' @ '{}' used: {}", info.name, info.location, usage)),
+                                    event:   None,
+                                };
+                                if let Err(err) = tx.send(Ok(reply)).await {
+                                    warn!("Failed to forward task resource usage to client: {}", err);
+                                }
+                            }
+                        },
+                        Err(err) => warn!("Failed to parse resource usage of task '{}' @ '{}': {}", info.name, info.location, err),
+                    }
+                }
+
+                // Create a JobStatus based on the given ExecuteStatus
+                let status: JobStatus = match JobStatus::from_status(
+                    match working_grpc::TaskStatus::try_from(reply.status) {
+                        Ok(status) => status,
+                        Err(_) => {
+                            warn!("Unknown job status '{}' (skipping message)", reply.status);
+                            continue;
+                        },
+                    },
+                    reply.value,
+                ) {
+                    Ok(status) => status,
+                    Err(err) => {
+                        warn!("Incoming message does not have a parseable job status: {} (skipping message)", err);
+                        continue;
+                    },
+                };
+
+                // Match it
+                debug!("Received status update: {:?}", working_grpc::TaskStatus::from(&status));
+                match &status {
+                    JobStatus::Unknown => {
+                        warn!("Received JobStatus::Unknown, which doesn't make a whole lot of sense");
+                    },
+
+                    JobStatus::Received => {
+                        mundane_status_update!(state, status);
+                    },
+
+                    JobStatus::Authorized => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::Denied(denial) => {
+                        return Err(ExecuteError::PolicyDenied { denial: denial.clone() });
+                    },
+                    JobStatus::AuthorizationFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+
+                    JobStatus::Created => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::CreationFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+
+                    JobStatus::Ready => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::Initialized => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::InitializationFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+                    JobStatus::Started => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::StartingFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+
+                    JobStatus::Heartbeat => {
+                        // Heartbeats only tell us that the task is still alive, not how far along it is
+                        heartbeats = true;
+                    },
+                    JobStatus::Completed => {
+                        mundane_status_update!(state, status);
+                    },
+                    JobStatus::CompletionFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+
+                    JobStatus::Finished(value) => {
+                        result = Ok(value.clone());
+                        state = status;
+                        break;
+                    },
+                    JobStatus::Stopped => {
+                        result = Err("Job was stopped".into());
+                        state = status;
+                        break;
+                    },
+                    JobStatus::DecodingFailed(err) => {
+                        result = Err(err.clone());
+                        state = status;
+                        break;
+                    },
+                    JobStatus::Failed(failure) => {
+                        return Err(ExecuteError::TaskFailed {
+                            name:     info.name.into(),
+                            location: info.location.clone(),
+                            failure:  Box::new(failure.clone()),
+                        });
+                    },
+                }
+            },
+            Ok(None) => {
+                // Stream closed
+                break;
+            },
+
+            Err(status) => {
+                // Something went wrong
+                result = Err(format!("Status error: {status}"));
+                break;
+            },
+        }
+    }
+    job.stop();
+
+    // Now we simply match on the value to see if we got something
+    let result: FullValue = match result {
+        Ok(result) => result,
+        Err(err) => {
+            return Err(ExecuteError::ExecuteError {
+                endpoint: delegate_address,
+                name:     info.name.into(),
+                status:   state.into(),
+                err:      StringError(err),
+            });
+        },
+    };
+
+    // That's it!
+    debug!("Task '{}' result: {:?}", info.name, result);
+    Ok(if let FullValue::Void = result { None } else { Some(result) })
+}
+
+/// Checks whether an intermediate result still exists on the registry of the given location.
+///
+/// # Arguments
+/// - `global`: The [`GlobalState`] of the VM, which carries the infrastructure and the proxy.
+/// - `location`: The location where the result is supposed to be.
+/// - `name`: The name of the intermediate result.
+///
+/// # Returns
+/// Whether the registry knows the result. If we failed to ask it, we assume it doesn't.
+async fn result_exists(global: &Arc<RwLock<GlobalState>>, location: &Location, name: &str) -> bool {
+    let (proxy, registry): (Arc<ProxyClient>, Address) = {
+        let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
+        match state.infra.as_ref().unwrap().get(location) {
+            Some(info) => (state.proxy.clone(), info.registry.clone()),
+            None => return false,
+        }
+    };
+
+    let address: String = format!("{registry}/results/info/{name}");
+    match proxy.get(&address, Some(NewPathRequestTlsOptions { location: location.clone(), use_client_auth: false })).await {
+        Ok(Ok(res)) => res.status() == StatusCode::OK,
+        Ok(Err(err)) => {
+            warn!("{}", trace!(("Failed to send request to '{address}'"), err));
+            false
+        },
+        Err(err) => {
+            warn!("{}", trace!(("Failed to send request to '{address}'"), err));
+            false
+        },
+    }
+}
+
+
+
+
+
+/// Promotes an intermediate result to a dataset on the delegate of the location where it lives.
+///
+/// # Arguments
+/// - `proxy`: The [`ProxyClient`] that we use to connect to the delegate.
+/// - `delegate_address`: The address of the delegate of the location where the result lives.
+/// - `trace`: The [`TraceContext`] of the workflow in which the result is promoted.
+/// - `name`: The name of the intermediate result.
+/// - `data_name`: The name of the dataset to promote it to.
+/// - `provenance`: The [`Provenance`] to record with the dataset.
+///
+/// # Errors
+/// This function errors if we failed to reach the delegate or if it failed to promote the result.
+pub async fn commit_remote(
+    proxy: &ProxyClient,
+    delegate_address: Address,
+    trace: &TraceContext,
+    name: &str,
+    data_name: &str,
+    provenance: &Provenance,
+) -> Result<(), CommitError> {
+    // Prepare the request to send to the delegate node
+    debug!("Sending commit request to job node '{}'...", delegate_address);
+    let provenance: Option<String> = match serde_json::to_string(provenance) {
+        Ok(provenance) => Some(provenance),
+        Err(err) => {
+            warn!("{}", trace!(("Failed to serialize provenance of intermediate result '{name}'; committing without"), err));
+            None
+        },
+    };
+    let message: working_grpc::CommitRequest = working_grpc::CommitRequest { result_name: name.into(), data_name: data_name.into(), provenance };
+
+    // Create the client
+    let mut client: working_grpc::JobServiceClient = match proxy.connect_to_job(delegate_address.to_string()).await {
+        Ok(result) => match result {
+            Ok(client) => client,
+            Err(err) => {
+                return Err(CommitError::GrpcConnectError { endpoint: delegate_address, err });
+            },
+        },
+        Err(err) => {
+            return Err(CommitError::ProxyError { err: Box::new(err) });
+        },
+    };
+
+    // Send the request to the job node, as part of the workflow's trace
+    let mut span: Span = Span::child("brane-drv commit", trace);
+    span.set_attribute("brane.location", &delegate_address);
+    let mut request: tonic::Request<working_grpc::CommitRequest> = tonic::Request::new(message);
+    span.context().inject_grpc(&mut request);
+    let response: Response<working_grpc::CommitReply> = match client.commit(request).await {
+        Ok(response) => response,
+        Err(err) => {
+            return Err(CommitError::GrpcRequestError { what: "CommitRequest", endpoint: delegate_address, err });
+        },
+    };
+    let _: working_grpc::CommitReply = response.into_inner();
+
+    // Done (nothing to return)
+    Ok(())
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// The InstancePlugin provides `brane-exe` functions for task execution.
 pub struct InstancePlugin;
@@ -85,6 +560,7 @@ impl VmPlugin for InstancePlugin {
     ) -> Result<AccessKind, Self::PreprocessError> {
         info!("Preprocessing {} '{}' on '{}' in a distributed environment...", name.variant(), name.name(), loc);
         debug!("Preprocessing to be done: {:?}", preprocess);
+        send_task_event(&global, pc, TaskState::Transferring, Some(format!("{} '{}'", name.variant(), name.name()))).await;
 
         // Resolve the location to an address (and get the proxy while we have a lock anyway)
         let disk = prof.time("File loading");
@@ -140,9 +616,25 @@ impl VmPlugin for InstancePlugin {
             },
         };
 
-        // Send the request to the job node
-        let response: Response<working_grpc::PreprocessReply> = match client.preprocess(message).await {
+        // Send the request to the job node, as part of the workflow's trace
+        let mut span: Span = Span::child("brane-drv preprocess", &global.read().unwrap().trace.unwrap_or_default());
+        span.set_attribute("brane.location", &delegate_address);
+        let mut request: tonic::Request<working_grpc::PreprocessRequest> = tonic::Request::new(message);
+        span.context().inject_grpc(&mut request);
+        let response: Response<working_grpc::PreprocessReply> = match client.preprocess(request).await {
             Ok(response) => response,
+            Err(err) if err.code() == Code::PermissionDenied => {
+                return Err(match PolicyDenial::from_bytes(err.details()) {
+                    Some(denial) => PreprocessError::PolicyDenied { denial },
+                    None => PreprocessError::GrpcRequestError { what: "PreprocessRequest", endpoint: delegate_address, err },
+                });
+            },
+            Err(err) if err.code() == Code::FailedPrecondition => {
+                return Err(match ApprovalPending::from_bytes(err.details()) {
+                    Some(pending) => PreprocessError::ApprovalPending { pending },
+                    None => PreprocessError::GrpcRequestError { what: "PreprocessRequest", endpoint: delegate_address, err },
+                });
+            },
             Err(err) => {
                 return Err(PreprocessError::GrpcRequestError { what: "PreprocessRequest", endpoint: delegate_address, err });
             },
@@ -170,232 +662,83 @@ impl VmPlugin for InstancePlugin {
         info: TaskInfo<'_>,
         prof: ProfileScopeHandle<'_>,
     ) -> Result<Option<FullValue>, Self::ExecuteError> {
-        info!("Executing task '{}' at '{}' in a distributed environment...", info.name, info.location);
-        debug!("Package: '{}' v{}", info.package_name, info.package_version);
-        debug!("Input data: {:?}", info.input.keys().map(|k| format!("{k}")).collect::<Vec<String>>());
-        debug!("Result: {:?}", info.result);
-        debug!("Input arguments: {:#?}", info.args);
-        debug!("Requirements: {:?}", info.requirements);
-
-        // Resolve the location to an address (and get the proxy and the workflow while we have a lock anyway)
-        let disk = prof.time("File loading");
-        let (proxy, delegate_address, workflow): (Arc<ProxyClient>, Address, String) = {
-            let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
-
-            // Resolve to an address and return that with the other addresses
-            (
-                state.proxy.clone(),
-                match state.infra.as_ref().unwrap().get(info.location) {
-                    Some(info) => info.delegate.clone(),
-                    None => {
-                        return Err(ExecuteError::UnknownLocationError { loc: info.location.clone() });
-                    },
-                },
-                state.workflow.as_ref().unwrap().clone(),
-            )
-        };
-        disk.stop();
-
-        // Prepare the request to send to the delegate node
-        debug!("Sending execute request to job node '{}'...", delegate_address);
-        let job = prof.time(format!("on {delegate_address}"));
-        let message: working_grpc::ExecuteRequest = working_grpc::ExecuteRequest {
-            // NOTE: For now, we hardcode the central orchestrator as only "use-case" (registry)
-            use_case: "central".into(),
+        // Skip the task if a previous run of this workflow already completed it (and its result is still there)
+        let checkpoint: Option<Arc<Checkpoint>> = global.read().unwrap().checkpoint.clone();
+        let key: Option<String> = checkpoint.as_ref().map(|_| Checkpoint::task_key(&info));
+        if let (Some(checkpoint), Some(key)) = (&checkpoint, &key) {
+            if let Some(entry) = checkpoint.take(key) {
+                let exists: bool = match info.result {
+                    Some(name) => result_exists(global, info.location, name).await,
+                    None => true,
+                };
+                if entry.location == *info.location && exists {
+                    info!("Skipping task '{}' at '{}' (completed by a previous run)", info.name, info.location);
+                    send_task_event(global, info.pc, TaskState::Done, Some("completed by a previous run".into())).await;
+                    return Ok(entry.value);
+                }
+                debug!("Re-running task '{}' completed by a previous run (it was planned elsewhere or its result is gone)", info.name);
+            }
+        }
 
-            workflow,
-            call_pc: specifications::working::ProgramCounter {
-                func_id:  if let FunctionId::Func(id) = info.pc.func_id { id as u64 } else { u64::MAX },
-                edge_idx: info.pc.edge_idx as u64,
+        // Otherwise, run it (cancelling it if the workflow exceeds its deadline) and remember that we did
+        send_task_event(global, info.pc, TaskState::Running, Some(info.location.clone())).await;
+        let deadline: Option<Instant> = global.read().unwrap().deadline;
+        let res: Result<Option<FullValue>, ExecuteError> = match deadline {
+            Some(deadline) => match tokio::time::timeout_at(deadline, execute_remote(global, &info, prof)).await {
+                Ok(res) => res,
+                Err(_) => Err(ExecuteError::DeadlineExceeded { name: info.name.into(), location: info.location.clone() }),
             },
-            task_def: info.def as u64,
-
-            input: info.input.to_json_map().unwrap(),
-            result: info.result.clone(),
-            args: serde_json::to_string(&info.args).unwrap(),
+            None => execute_remote(global, &info, prof).await,
         };
-
-        // Create the client
-        let mut client: working_grpc::JobServiceClient = match proxy.connect_to_job(delegate_address.to_string()).await {
-            Ok(result) => match result {
-                Ok(client) => client,
-                Err(err) => {
-                    return Err(ExecuteError::GrpcConnectError { endpoint: delegate_address, err });
-                },
-            },
-            Err(err) => {
-                return Err(ExecuteError::ProxyError { err: Box::new(err) });
-            },
-        };
-
-        // Send the request to the job node
-        let response: Response<Streaming<working_grpc::ExecuteReply>> = match client.execute(message).await {
-            Ok(response) => response,
+        let value: Option<FullValue> = match res {
+            Ok(value) => value,
             Err(err) => {
-                return Err(ExecuteError::GrpcRequestError { what: "ExecuteRequest", endpoint: delegate_address, err });
+                let state: TaskState = if matches!(err, ExecuteError::DeadlineExceeded { .. }) { TaskState::Cancelled } else { TaskState::Failed };
+                let message: String = match &err {
+                    ExecuteError::TaskFailed { failure, .. } => failure.summary(),
+                    err => err.to_string(),
+                };
+                send_task_event(global, info.pc, state, Some(message)).await;
+                // Also send why it failed in a structured way, such that the client can render or export it
+                if let ExecuteError::TaskFailed { name, location, failure } = &err {
+                    let tx: Option<Arc<Sender<Result<driving_grpc::ExecuteReply, Status>>>> = global.read().unwrap().tx.clone();
+                    if let Some(tx) = tx {
+                        let event: ExecuteEvent = ExecuteEvent::TaskFailed {
+                            pc: info.pc.to_string(),
+                            name: name.clone(),
+                            location: location.clone(),
+                            failure: (**failure).clone(),
+                        };
+                        send_event(&tx, event).await;
+                    }
+                }
+                return Err(err);
             },
         };
-        let mut stream: Streaming<working_grpc::ExecuteReply> = response.into_inner();
-
-        // Now we tick off incoming messages
-        let mut state: JobStatus = JobStatus::Unknown;
-        // let mut error : Option<String> = None;
-        let mut result: Result<FullValue, String> = Err("No response".into());
-        #[allow(irrefutable_let_patterns)]
-        while let message = stream.message().await {
-            match message {
-                // The message itself went alright
-                Ok(Some(reply)) => {
-                    // Create a JobStatus based on the given ExecuteStatus
-                    let status: JobStatus = match JobStatus::from_status(
-                        match working_grpc::TaskStatus::try_from(reply.status) {
-                            Ok(status) => status,
-                            Err(_) => {
-                                warn!("Unknown job status '{}' (skipping message)", reply.status);
-                                continue;
-                            },
-                        },
-                        reply.value,
-                    ) {
-                        Ok(status) => status,
-                        Err(err) => {
-                            warn!("Incoming message does not have a parseable job status: {} (skipping message)", err);
-                            continue;
-                        },
-                    };
-
-                    // Match it
-                    debug!("Received status update: {:?}", working_grpc::TaskStatus::from(&status));
-                    match &status {
-                        JobStatus::Unknown => {
-                            warn!("Received JobStatus::Unknown, which doesn't make a whole lot of sense");
-                        },
-
-                        JobStatus::Received => {
-                            mundane_status_update!(state, status);
-                        },
-
-                        JobStatus::Authorized => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::Denied => {
-                            result = Err("Permission denied".into());
-                            state = status;
-                            break;
-                        },
-                        JobStatus::AuthorizationFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-
-                        JobStatus::Created => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::CreationFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-
-                        JobStatus::Ready => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::Initialized => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::InitializationFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-                        JobStatus::Started => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::StartingFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-
-                        JobStatus::Heartbeat => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::Completed => {
-                            mundane_status_update!(state, status);
-                        },
-                        JobStatus::CompletionFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-
-                        JobStatus::Finished(value) => {
-                            result = Ok(value.clone());
-                            state = status;
-                            break;
-                        },
-                        JobStatus::Stopped => {
-                            result = Err("Job was stopped".into());
-                            state = status;
-                            break;
-                        },
-                        JobStatus::DecodingFailed(err) => {
-                            result = Err(err.clone());
-                            state = status;
-                            break;
-                        },
-                        JobStatus::Failed(code, stdout, stderr) => {
-                            result = Err(format!(
-                                "Job failed with exit code {}\n\nstdout:\n{}\n{}\n{}\n\nstderr:\n{}\n{}\n{}\n",
-                                code,
-                                (0..80).map(|_| '-').collect::<String>(),
-                                stdout,
-                                (0..80).map(|_| '-').collect::<String>(),
-                                (0..80).map(|_| '-').collect::<String>(),
-                                stderr,
-                                (0..80).map(|_| '-').collect::<String>()
-                            ));
-                            state = status;
-                            break;
-                        },
-                    }
-                },
-                Ok(None) => {
-                    // Stream closed
-                    break;
-                },
-
-                Err(status) => {
-                    // Something went wrong
-                    result = Err(format!("Status error: {status}"));
-                    break;
-                },
+        send_task_event(global, info.pc, TaskState::Done, None).await;
+        global.write().unwrap().partial.insert(info.pc.to_string(), value.clone().unwrap_or(FullValue::Void));
+        if let (Some(checkpoint), Some(key)) = (checkpoint, key) {
+            if let Err(err) = checkpoint.record(key, info.location.clone(), value.clone()) {
+                warn!("{}", trace!(("Failed to record task '{}' in checkpoint", info.name), err));
             }
         }
-        job.stop();
-
-        // Now we simply match on the value to see if we got something
-        let result: FullValue = match result {
-            Ok(result) => result,
-            Err(err) => {
-                return Err(ExecuteError::ExecuteError {
-                    endpoint: delegate_address,
-                    name:     info.name.into(),
-                    status:   state.into(),
-                    err:      StringError(err),
-                });
-            },
-        };
-
-        // That's it!
-        debug!("Task '{}' result: {:?}", info.name, result);
-        Ok(if let FullValue::Void = result { None } else { Some(result) })
+        // Remember the intermediate result it produced, such that it can be inspected after the workflow completes
+        let history: Option<Arc<ExecutionHistory>> = global.read().unwrap().history.clone();
+        if let (Some(history), Some(name)) = (history, info.result) {
+            let task: String = format!("{}[{}]::{}", info.package_name, info.package_version, info.name);
+            let mut inputs: Vec<String> = info.input.keys().map(|input| input.name().into()).collect();
+            inputs.sort();
+            if let Err(err) = history.record(name.clone(), Some(task), info.pc.to_string(), inputs, info.location.clone()) {
+                warn!("{}", trace!(("Failed to record intermediate result '{name}' in execution history"), err));
+            }
+        }
+        Ok(value)
     }
 
     async fn stdout(
         global: &Arc<RwLock<Self::GlobalState>>,
         _local: &Self::LocalState,
+        info: &PrintInfo,
         text: &str,
         newline: bool,
         _prof: ProfileScopeHandle<'_>,
@@ -409,13 +752,16 @@ impl VmPlugin for InstancePlugin {
             state.tx.as_ref().expect("Missing `tx` in GlobalState; did you forget to update it before this poll?").clone()
         };
 
-        // Write stdout to the tx
+        // Write stdout to the tx, tagged with where and when it was printed
+        let event: ExecuteEvent = ExecuteEvent::Printed { pc: info.pc.to_string(), function: info.function.clone(), timestamp: info.timestamp };
         if let Err(err) = tx
             .send(Ok(driving_grpc::ExecuteReply {
-                stdout: Some(format!("{}{}", text, if newline { "\n" } else { "" })),
-                stderr: None,
-                debug:  None,
-                value:  None,
+                stdout:  Some(format!("{}{}", text, if newline { "\n" } else { "" })),
+                stderr:  None,
+                debug:   None,
+                value:   None,
+                profile: None,
+                event:   Some(serde_json::to_string(&event).unwrap()),
 
                 close: false,
             }))
@@ -451,21 +797,20 @@ impl VmPlugin for InstancePlugin {
         name: &str,
         path: &Path,
         data_name: &str,
+        provenance: &Provenance,
         prof: ProfileScopeHandle<'_>,
     ) -> Result<(), Self::CommitError> {
         info!("Committing intermediate result '{}' living at '{}' as '{}' in a distributed environment...", name, loc, data_name);
         debug!("File: '{}'", path.display());
 
-        // We submit a commit request to the job node
-
-        // Resolve the location to an address (and get the proxy client while at it)
+        // Resolve the location to an address (and get the proxy client and trace while at it)
         let disk = prof.time("File loading");
-        let (proxy, delegate_address): (Arc<ProxyClient>, Address) = {
+        let (proxy, delegate_address, trace): (Arc<ProxyClient>, Address, TraceContext) = {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
 
             // Resolve to an address
             match state.infra.as_ref().unwrap().get(loc) {
-                Some(info) => (state.proxy.clone(), info.delegate.clone()),
+                Some(info) => (state.proxy.clone(), info.delegate.clone(), state.trace.unwrap_or_default()),
                 None => {
                     return Err(CommitError::UnknownLocationError { loc: loc.clone() });
                 },
@@ -473,35 +818,10 @@ impl VmPlugin for InstancePlugin {
         };
         disk.stop();
 
-        // Prepare the request to send to the delegate node
-        debug!("Sending commit request to job node '{}'...", delegate_address);
+        // We submit a commit request to the job node
         let job = prof.time(format!("on {delegate_address}"));
-        let message: working_grpc::CommitRequest = working_grpc::CommitRequest { result_name: name.into(), data_name: data_name.into() };
-
-        // Create the client
-        let mut client: working_grpc::JobServiceClient = match proxy.connect_to_job(delegate_address.to_string()).await {
-            Ok(result) => match result {
-                Ok(client) => client,
-                Err(err) => {
-                    return Err(CommitError::GrpcConnectError { endpoint: delegate_address, err });
-                },
-            },
-            Err(err) => {
-                return Err(CommitError::ProxyError { err: Box::new(err) });
-            },
-        };
-
-        // Send the request to the job node
-        let response: Response<working_grpc::CommitReply> = match client.commit(message).await {
-            Ok(response) => response,
-            Err(err) => {
-                return Err(CommitError::GrpcRequestError { what: "CommitRequest", endpoint: delegate_address, err });
-            },
-        };
-        let _: working_grpc::CommitReply = response.into_inner();
+        commit_remote(&proxy, delegate_address, &trace, name, data_name, provenance).await?;
         job.stop();
-
-        // Done (nothing to return)
         Ok(())
     }
 }
@@ -522,16 +842,58 @@ impl InstanceVm {
     /// - `node_config_path`: The path to the configuration for this node's environment. For us, contains the path to the infra.yml and (optional) secrets.yml files.
     /// - `app_id`: The application ID for this session.
     /// - `proxy`: The ProxyClient that we use to connect to/through `brane-prx`.
+    /// - `memory_limit`: The maximum number of bytes of values that workflows may keep in memory, or [`None`] to not limit it.
     /// - `planner`: The client-side of a planner that we use to plan.
     ///
     /// # Returns
     /// A new InstanceVm instance.
     #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, app_id: AppId, proxy: Arc<ProxyClient>) -> Self {
-        Self {
-            // InfraPath::new(&node_config.node.central().paths.infra, &node_config.node.central().paths.secrets)
-            state: Self::new_state(GlobalState { node_config_path: node_config_path.into(), app_id, proxy, infra: None, workflow: None, tx: None }),
+    pub fn new(node_config_path: impl Into<PathBuf>, app_id: AppId, proxy: Arc<ProxyClient>, memory_limit: Option<usize>) -> Self {
+        // InfraPath::new(&node_config.node.central().paths.infra, &node_config.node.central().paths.secrets)
+        let state: RunState<GlobalState> = Self::new_state(GlobalState {
+            node_config_path: node_config_path.into(),
+            app_id,
+            proxy,
+            infra: None,
+            workflow: None,
+            trace: None,
+            checkpoint: None,
+            history: None,
+            deadline: None,
+            partial: HashMap::new(),
+            tx: None,
+        });
+        Self { state: if let Some(limit) = memory_limit { state.with_memory_limit(limit) } else { state } }
+    }
+
+    /// Returns the symbol table and the values of the global variables defined by the workflows run so far.
+    ///
+    /// # Returns
+    /// A tuple of the [`SymTable`] that defines the variables, and a map of the definition index of every variable with a value to that value.
+    pub fn snapshot(&self) -> (SymTable, HashMap<usize, FullValue>) {
+        let table: &SymTable = self.state.fstack.table();
+        (table.clone(), self.state.fstack.globals().map(|(def, value)| (def, value.to_full(table))).collect())
+    }
+
+    /// Restores the global variables of a session with another VM (see [`InstanceVm::snapshot()`]).
+    ///
+    /// # Arguments
+    /// - `table`: The [`SymTable`] that defines the variables.
+    /// - `variables`: The values of the variables to restore, mapped by their definition index.
+    ///
+    /// # Errors
+    /// This function errors if any of the variables is already declared or if its value does not match its type.
+    ///
+    /// # Panics
+    /// This function panics if any of the variables is unknown to the given table.
+    pub fn restore(&mut self, table: SymTable, variables: HashMap<usize, FullValue>) -> Result<(), FrameStackError> {
+        let table: Arc<SymTable> = Arc::new(table);
+        self.state.fstack.update_table(table.clone());
+        for (def, value) in variables {
+            self.state.fstack.declare(def)?;
+            self.state.fstack.set(def, value.into_value(&table))?;
         }
+        Ok(())
     }
 
     /// Runs the given workflow on this VM.
@@ -542,17 +904,26 @@ impl InstanceVm {
     /// - `tx`: The transmission channel to send feedback to the client on.
     /// - `id`: The identifier of the workflow this session is part of.
     /// - `workflow`: The Workflow to execute.
+    /// - `checkpoint`: The [`Checkpoint`] in which to record completed tasks (and from which to skip tasks completed by a previous run), if any.
+    /// - `history`: The [`ExecutionHistory`] in which to record the intermediate results produced by the workflow, if any.
+    /// - `deadline`: The time that the workflow may take in total (including planning) before its outstanding tasks are cancelled, if any.
     /// - `prof`: The ProfileScope that can be used to provide additional information about the timings of the VM.
     ///
     /// # Returns
     /// The result of the workflow, if any. It also returns `self` again for subsequent runs.
+    #[allow(clippy::too_many_arguments)]
     pub async fn exec(
         self,
         tx: Sender<Result<driving_grpc::ExecuteReply, Status>>,
         id: AppId,
         workflow: Workflow,
+        checkpoint: Option<Arc<Checkpoint>>,
+        history: Option<Arc<ExecutionHistory>>,
+        deadline: Option<Duration>,
         prof: ProfileScopeHandle<'_>,
     ) -> (Self, Result<FullValue, Error>) {
+        let deadline_at: Option<Instant> = deadline.map(|deadline| Instant::now() + deadline);
+
         // Step 0: Load files
         let plr_addr: Address = {
             let mut global = self.state.global.write().unwrap();
@@ -595,17 +966,26 @@ impl InstanceVm {
 
         // Step 1: Plan
         debug!("Planning workflow on Kafka planner...");
-        let plan: Workflow = match prof.nest_fut("planning (brane-drv)", |scope| InstancePlanner::plan(&plr_addr, id, workflow, scope)).await {
+        let trace: TraceContext = TraceContext::for_workflow(&workflow.id);
+        send_event(&tx, ExecuteEvent::Planning).await;
+        let plan: Workflow = match prof.nest_fut("planning (brane-drv)", |scope| InstancePlanner::plan(&plr_addr, id, workflow, &trace, scope)).await
+        {
             Ok(plan) => plan,
             Err(err) => {
                 return (self, Err(Error::PlanError { err }));
             },
         };
+        send_event(&tx, ExecuteEvent::Planned { locations: task_locations(&plan) }).await;
 
-        // Also update the TX & workflow in the internal state
+        // Also update the TX, workflow & trace in the internal state
         {
             let mut state: RwLockWriteGuard<GlobalState> = self.state.global.write().unwrap();
             state.workflow = Some(serde_json::to_string(&plan).unwrap());
+            state.trace = Some(trace);
+            state.checkpoint = checkpoint;
+            state.history = history;
+            state.deadline = deadline_at;
+            state.partial.clear();
             state.tx = Some(Arc::new(tx));
         }
 
@@ -615,8 +995,12 @@ impl InstanceVm {
         // Now wrap ourselves in a lock so that we can run the internal vm
         let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
 
-        // Run the VM and get self back
-        let result: Result<FullValue, VmError> = prof.nest_fut("execution", |scope| Self::run::<InstancePlugin>(this.clone(), plan, scope)).await;
+        // Run the VM (dropping it, and thus the tasks it waits for, if it exceeds its deadline) and get self back
+        let run = prof.nest_fut("execution", |scope| Self::run::<InstancePlugin>(this.clone(), plan, scope));
+        let result: Option<Result<FullValue, VmError>> = match deadline_at {
+            Some(deadline_at) => tokio::time::timeout_at(deadline_at, run).await.ok(),
+            None => Some(run.await),
+        };
         let this: Self = match Arc::try_unwrap(this) {
             Ok(this) => this.into_inner().unwrap(),
             Err(_) => {
@@ -627,12 +1011,34 @@ impl InstanceVm {
 
 
         // Step 3: Result
-        // Match the result to potentially error
+        // Match the result to potentially error (where tasks failing because they were cancelled still means the workflow timed out)
+        let expired: bool = deadline_at.map(|deadline_at| Instant::now() >= deadline_at).unwrap_or(false);
         let value: FullValue = match result {
-            Ok(value) => value,
-            Err(err) => {
+            Some(Ok(value)) => value,
+            Some(Err(err)) if !expired => {
                 return (this, Err(Error::ExecError { err }));
             },
+            _ => {
+                let deadline: Duration = deadline.unwrap_or_default();
+                warn!("Workflow exceeded its deadline of {}s; cancelled its outstanding tasks", deadline.as_secs());
+
+                // Tell the client which tasks did complete, and what they returned
+                let (tx, partial): (Option<Arc<Sender<Result<driving_grpc::ExecuteReply, Status>>>>, HashMap<String, String>) = {
+                    let state: RwLockReadGuard<GlobalState> = this.state.global.read().unwrap();
+                    (
+                        state.tx.clone(),
+                        state
+                            .partial
+                            .iter()
+                            .map(|(pc, value)| (pc.clone(), serde_json::to_string(value).unwrap_or_else(|_| "null".into())))
+                            .collect(),
+                    )
+                };
+                if let Some(tx) = tx {
+                    send_event(&tx, ExecuteEvent::TimedOut { deadline: deadline.as_secs(), partial }).await;
+                }
+                return (this, Err(Error::DeadlineExceeded { deadline }));
+            },
         };
 
         // Done, return
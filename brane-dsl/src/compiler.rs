@@ -4,7 +4,7 @@
 //  Created:
 //    18 Aug 2022, 09:51:07
 //  Last edited:
-//    31 Oct 2023, 10:30:23
+//    16 Oct 2026, 01:41:42
 //  Auto updated?
 //    Yes
 //
@@ -13,14 +13,18 @@
 //!   (incomplete) AST.
 //
 
-use log::trace;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, trace};
 use nom::error::VerboseErrorKind;
 use nom::InputLength;
 use specifications::package::PackageIndex;
 
 use crate::errors;
 pub use crate::errors::ParseError as Error;
-use crate::parser::ast::Program;
+use crate::parser::ast::{Block, Program, Stmt};
 use crate::parser::{bakery, bscript};
 use crate::scanner::{self, Span, Token, Tokens};
 use crate::spec::Language;
@@ -85,6 +89,155 @@ pub mod tests {
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
         });
     }
+
+    /// Tests importing the definitions in other files.
+    #[test]
+    fn test_import_source() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-dsl-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("helpers.bs"), "import hello_world;\nfunc greet() { return hello_world(); }\nprintln(\"ignored\");\n").unwrap();
+        fs::write(dir.join("cycle.bs"), "import \"cycle.bs\";\n").unwrap();
+        fs::write(dir.join("base.bs"), "func base() { return 42; }\n").unwrap();
+        fs::write(dir.join("left.bs"), "import \"base.bs\";\nfunc left() { return base(); }\n").unwrap();
+        fs::write(dir.join("right.bs"), "import \"base.bs\";\nfunc right() { return base(); }\n").unwrap();
+        fs::write(dir.join("recipe.bk"), "").unwrap();
+        let pindex: PackageIndex = create_package_index();
+        let options: ParserOptions = ParserOptions::bscript().with_base_dir(&dir);
+
+        // The definitions (and package imports) should be copied, the rest ignored
+        let res: Program = parse("import \"helpers.bs\";\nprintln(greet());\n", &pindex, &options).unwrap();
+        assert_eq!(res.block.stmts.len(), 3);
+        assert!(matches!(res.block.stmts[0], Stmt::Import { .. }));
+        assert!(matches!(res.block.stmts[1], Stmt::FuncDef { .. }));
+
+        // A file imported by more than one other file should only be defined once
+        let res: Program = parse("import \"left.bs\";\nimport \"right.bs\";\n", &pindex, &options).unwrap();
+        let names: Vec<String> =
+            res.block.stmts.iter().filter_map(|stmt| if let Stmt::FuncDef { ident, .. } = stmt { Some(ident.value.clone()) } else { None }).collect();
+        assert_eq!(names, vec!["base".to_string(), "left".into(), "right".into()]);
+
+        // Cycles, missing files and files we cannot parse should be detected
+        assert!(matches!(parse("import \"recipe.bk\";", &pindex, &options), Err(Error::ImportLanguageError { lang: Language::Bakery, .. })));
+        assert!(matches!(parse("import \"cycle.bs\";", &pindex, &options), Err(Error::ImportParseError { .. })));
+        assert!(matches!(parse("import \"missing.bs\";", &pindex, &options), Err(Error::ImportReadError { .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads and parses a file imported with `import "...";`.
+///
+/// # Arguments
+/// - `path`: The path of the file, as written in the import statement.
+/// - `pindex`: The PackageIndex that we use to resolve patterns.
+/// - `options`: The ParserOptions of the importing file.
+/// - `imports`: The files that are being or have been imported, to detect cycles and to import every file only once.
+///
+/// # Returns
+/// The function and class definitions in the file (with their attributes), as well as its package imports. Any other statements are ignored.
+/// If the file was already imported elsewhere in the program, returns nothing, as its definitions are already there.
+///
+/// # Errors
+/// This function errors if we failed to read or parse the file, if it is not written in BraneScript or if it (indirectly) imports itself.
+fn import_source(path: &str, pindex: &PackageIndex, options: &ParserOptions, imports: &mut Imports) -> Result<Vec<Stmt>, Error> {
+    let path: PathBuf = match &options.base_dir {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    };
+    let canonical: PathBuf = path.canonicalize().unwrap_or_else(|_| path.clone());
+    if imports.stack.contains(&canonical) {
+        return Err(Error::ImportCycleError { path });
+    }
+    if imports.done.contains(&canonical) {
+        debug!("Skipping import of '{}' (already imported)", path.display());
+        return Ok(vec![]);
+    }
+    // NOTE: The Bakery parser is not implemented yet, so importing it would silently yield nothing
+    let lang: Language = path.extension().and_then(|ext| ext.to_str()).and_then(Language::from_extension).unwrap_or(options.lang);
+    if lang != Language::BraneScript {
+        return Err(Error::ImportLanguageError { path, lang });
+    }
+
+    // Read the file and parse it in its own language
+    debug!("Importing definitions from '{}'...", path.display());
+    let source: String = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(err) => return Err(Error::ImportReadError { path, err }),
+    };
+    let options: ParserOptions = ParserOptions { lang, base_dir: path.parent().map(PathBuf::from) };
+    imports.stack.push(canonical.clone());
+    let program: Program = match parse_nested(source, pindex, &options, imports) {
+        Ok(program) => program,
+        Err(err) => return Err(Error::ImportParseError { path, err: Box::new(err) }),
+    };
+    imports.stack.pop();
+    imports.done.insert(canonical);
+
+    // Only keep the definitions and the attributes preceding them
+    let mut stmts: Vec<Stmt> = Vec::with_capacity(program.block.stmts.len());
+    let mut attrs: Vec<Stmt> = vec![];
+    for stmt in program.block.stmts {
+        match stmt {
+            Stmt::Attribute(_) => attrs.push(stmt),
+            Stmt::Import { .. } | Stmt::FuncDef { .. } | Stmt::ClassDef { .. } => {
+                stmts.append(&mut attrs);
+                stmts.push(stmt);
+            },
+            _ => attrs.clear(),
+        }
+    }
+    Ok(stmts)
+}
+
+/// Replaces the `import "...";`-statements in the given block (and any nested blocks) with the definitions in the files they import.
+///
+/// # Arguments
+/// - `block`: The [`Block`] to resolve the imports in.
+/// - `pindex`: The PackageIndex that we use to resolve patterns.
+/// - `options`: The ParserOptions of the file that contains the block.
+/// - `imports`: The files that are being or have been imported, to detect cycles and to import every file only once.
+///
+/// # Errors
+/// This function errors if we failed to import any of the files.
+fn resolve_imports(block: &mut Block, pindex: &PackageIndex, options: &ParserOptions, imports: &mut Imports) -> Result<(), Error> {
+    let stmts: Vec<Stmt> = std::mem::take(&mut block.stmts);
+    for mut stmt in stmts {
+        match &mut stmt {
+            Stmt::ImportSource { path, .. } => {
+                block.stmts.extend(import_source(path, pindex, options, imports)?);
+                continue;
+            },
+
+            Stmt::Block { block } => resolve_imports(block, pindex, options, imports)?,
+            Stmt::FuncDef { code, .. } => resolve_imports(code, pindex, options, imports)?,
+            Stmt::ClassDef { methods, .. } => {
+                for method in methods {
+                    if let Stmt::FuncDef { code, .. } = &mut **method {
+                        resolve_imports(code, pindex, options, imports)?;
+                    }
+                }
+            },
+            Stmt::If { consequent, alternative, .. } => {
+                resolve_imports(consequent, pindex, options, imports)?;
+                if let Some(alternative) = alternative {
+                    resolve_imports(alternative, pindex, options, imports)?;
+                }
+            },
+            Stmt::For { consequent, .. } | Stmt::While { consequent, .. } => resolve_imports(consequent, pindex, options, imports)?,
+            Stmt::Parallel { blocks, .. } => {
+                for block in blocks {
+                    resolve_imports(block, pindex, options, imports)?;
+                }
+            },
+            _ => {},
+        }
+        block.stmts.push(stmt);
+    }
+    Ok(())
 }
 
 
@@ -92,11 +245,25 @@ pub mod tests {
 
 
 /***** AUXILLARY STRUCTS *****/
+/// Keeps track of the files imported with `import "...";` while parsing a program.
+#[derive(Debug, Default)]
+struct Imports {
+    /// The files that are currently being imported, to detect cycles.
+    stack: Vec<PathBuf>,
+    /// The files that have been imported completely, such that a file imported by more than one other file is only imported once.
+    done:  HashSet<PathBuf>,
+}
+
+
+
 /// Defines options that configure the compiler before we use it.
 #[derive(Clone, Debug)]
 pub struct ParserOptions {
     /// The language the compiler will parse (i.e., BraneScript or Bakery).
-    pub lang: Language,
+    pub lang:     Language,
+    /// The directory against which the paths in `import "...";`-statements are resolved. If omitted, they are resolved against the current
+    /// working directory.
+    pub base_dir: Option<PathBuf>,
 }
 
 impl ParserOptions {
@@ -108,21 +275,34 @@ impl ParserOptions {
     /// # Returns
     /// A new ParserOptions with the given settings.
     #[inline]
-    pub fn new(lang: Language) -> Self { Self { lang } }
+    pub fn new(lang: Language) -> Self { Self { lang, base_dir: None } }
 
     /// Constructor for the ParserOptions that defaults it to a BraneScript setup.
     ///
     /// # Returns
     /// A new ParserOptions that will make the compiler compile BraneScript.
     #[inline]
-    pub fn bscript() -> Self { Self { lang: Language::BraneScript } }
+    pub fn bscript() -> Self { Self { lang: Language::BraneScript, base_dir: None } }
 
     /// Constructor for the ParserOptions that defaults it to a Bakery setup.
     ///
     /// # Returns
     /// A new ParserOptions that will make the compiler compile Bakery.
     #[inline]
-    pub fn bakery() -> Self { Self { lang: Language::Bakery } }
+    pub fn bakery() -> Self { Self { lang: Language::Bakery, base_dir: None } }
+
+    /// Sets the directory against which the paths in `import "...";`-statements are resolved.
+    ///
+    /// # Arguments
+    /// - `base_dir`: The directory to resolve against, typically the one containing the file that is parsed.
+    ///
+    /// # Returns
+    /// The same ParserOptions but with the directory set.
+    #[inline]
+    pub fn with_base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(base_dir.into());
+        self
+    }
 }
 
 
@@ -146,8 +326,23 @@ impl ParserOptions {
 /// # Errors
 /// This function may error if we could not read the reader or if the source code was somehow malformed.
 pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOptions) -> Result<Program, Error> {
-    let source: &str = source.as_ref();
+    parse_nested(source.as_ref(), pindex, options, &mut Imports::default())
+}
 
+/// Implements [`parse()`], keeping track of the files that are being imported.
+///
+/// # Arguments
+/// - `source`: The source text to parse from.
+/// - `pindex`: The PackageIndex that we use to resolve patterns.
+/// - `options`: Some auxillary ParserOptions that finetune its behaviour.
+/// - `imports`: The files that are being or have been imported, to detect cycles and to import every file only once.
+///
+/// # Returns
+/// A new Program that is the parsed source code, with its `import "...";`-statements resolved.
+///
+/// # Errors
+/// This function may error if the source code was somehow malformed or if we failed to import another file.
+fn parse_nested(source: &str, pindex: &PackageIndex, options: &ParserOptions, imports: &mut Imports) -> Result<Program, Error> {
     // Run that through the scanner
     trace!("Parsing input to tokens...");
     let (remain, tokens): (Span, Vec<Token>) = match scanner::scan_tokens(Span::from(source)) {
@@ -166,7 +361,7 @@ pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOp
     // Run the tokens through the parser (depending on the selected language)
     trace!("Parsing tokens to Program...");
     let tks = Tokens::new(&tokens);
-    let (remain, ast): (Tokens, Program) = match options.lang {
+    let (remain, mut ast): (Tokens, Program) = match options.lang {
         Language::BraneScript => match bscript::parse_ast(tks) {
             Ok(ast) => ast,
 
@@ -201,6 +396,9 @@ pub fn parse<S: AsRef<str>>(source: S, pindex: &PackageIndex, options: &ParserOp
         return Err(Error::LeftoverTokensError { lang: options.lang });
     }
 
+    // Replace imports of other files with their definitions
+    resolve_imports(&mut ast.block, pindex, options, imports)?;
+
     // Alright, that's a parsed program
     Ok(ast)
 }
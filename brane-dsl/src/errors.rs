@@ -4,7 +4,7 @@
 //  Created:
 //    17 Aug 2022, 11:29:00
 //  Last edited:
-//    16 Nov 2022, 16:39:28
+//    16 Oct 2026, 01:41:42
 //  Auto updated?
 //    Yes
 //
@@ -15,6 +15,7 @@
 
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
 
 use nom::error::{VerboseError, VerboseErrorKind};
 
@@ -306,6 +307,15 @@ pub enum ParseError {
     Eof { lang: Language, err: String },
     /// Not all tokens were parsed (indicating an error).
     LeftoverTokensError { lang: Language },
+
+    /// Failed to read a file imported with `import "...";`.
+    ImportReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse a file imported with `import "...";`.
+    ImportParseError { path: PathBuf, err: Box<Self> },
+    /// A file (indirectly) imports itself.
+    ImportCycleError { path: PathBuf },
+    /// A file imported with `import "...";` is written in a language that cannot be imported (yet).
+    ImportLanguageError { path: PathBuf, lang: Language },
 }
 
 impl Display for ParseError {
@@ -321,6 +331,11 @@ impl Display for ParseError {
             ParserError { lang, err } => write!(f, "{lang} parse error: {err}"),
             Eof { lang, err } => write!(f, "{lang} parse error: reached end-of-file unexpectedly ({err})"),
             LeftoverTokensError { lang } => write!(f, "{lang} parse error: not all input could be parsed"),
+
+            ImportReadError { path, err } => write!(f, "Failed to read imported file '{}': {}", path.display(), err),
+            ImportParseError { path, err } => write!(f, "In imported file '{}': {}", path.display(), err),
+            ImportCycleError { path } => write!(f, "File '{}' (indirectly) imports itself", path.display()),
+            ImportLanguageError { path, lang } => write!(f, "Cannot import file '{}': {} imports are not supported", path.display(), lang),
         }
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    10 Aug 2022, 14:00:59
 //  Last edited:
-//    31 Jan 2024, 11:30:40
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -19,7 +19,8 @@ use std::rc::Rc;
 use std::str::FromStr as _;
 
 use enum_debug::EnumDebug;
-use specifications::version::{ParseError, Version};
+use serde::{Deserialize, Serialize};
+use specifications::version::{ParseError, Version, VersionRange};
 
 use crate::data_type::DataType;
 use crate::location::AllowedLocations;
@@ -105,6 +106,15 @@ pub enum Attribute {
         /// The range of the attribute in the source text.
         range:  TextRange,
     },
+    /// It's a key with named options, which may be omitted altogether (e.g., `no_cache` or `cache(ttl = "7d")`).
+    Options {
+        /// The given key.
+        key:     Identifier,
+        /// The named options we parsed, in the order given.
+        options: Vec<(Identifier, Literal)>,
+        /// The range of the attribute in the source text.
+        range:   TextRange,
+    },
 }
 impl Attribute {
     /// Constructor for the attribute that initializes it as a key/pair value.
@@ -139,6 +149,20 @@ impl Attribute {
     pub fn list(key: impl Into<Identifier>, values: impl Into<Vec<Literal>>, range: impl Into<TextRange>) -> Self {
         Self::List { key: key.into(), values: values.into(), range: range.into() }
     }
+
+    /// Constructor for the attribute that initializes it as a key with named options.
+    ///
+    /// # Arguments
+    /// - `key`: The given key, as a BraneScript [`Identifier`].
+    /// - `options`: The given options, as a list of BraneScript [`Identifier`]/[`Literal`] pairs. May be empty.
+    /// - `range`: The [`TextRange`] linking this attribute to the source text.
+    ///
+    /// # Returns
+    /// A new Attribute.
+    #[inline]
+    pub fn options(key: impl Into<Identifier>, options: impl Into<Vec<(Identifier, Literal)>>, range: impl Into<TextRange>) -> Self {
+        Self::Options { key: key.into(), options: options.into(), range: range.into() }
+    }
 }
 impl Node for Attribute {
     #[inline]
@@ -146,6 +170,7 @@ impl Node for Attribute {
         match self {
             Self::KeyPair { range, .. } => range,
             Self::List { range, .. } => range,
+            Self::Options { range, .. } => range,
         }
     }
 }
@@ -236,6 +261,17 @@ pub enum Stmt {
         /// The range of the import statement in the source text.
         range: TextRange,
     },
+    /// Defines an import of the functions and classes defined in another BraneScript or Bakery file.
+    ///
+    /// This is resolved by [`parse()`](crate::compiler::parse()), which replaces it with the definitions themselves, so it never reaches the
+    /// compiler.
+    ImportSource {
+        /// The path of the file to import, as written in the source text.
+        path: String,
+
+        /// The range of the import statement in the source text.
+        range: TextRange,
+    },
     /// Defines a function definition.
     FuncDef {
         /// The name of the function, as an identifier.
@@ -524,6 +560,7 @@ impl Node for Stmt {
             Block { block, .. } => block.range(),
 
             Import { range, .. } => range,
+            ImportSource { range, .. } => range,
             FuncDef { range, .. } => range,
             ClassDef { range, .. } => range,
             Return { range, .. } => range,
@@ -612,6 +649,8 @@ pub enum Expr {
         result:    HashSet<Data>,
         /// Metadata for this call. Only used for external calls.
         metadata:  HashSet<Metadata>,
+        /// If given, the number of seconds for which workers may reuse an earlier result of this call. Only used for external calls.
+        cache:     Option<u64>,
 
         /// The range of the call-expression in the source text.
         range: TextRange,
@@ -752,7 +791,17 @@ impl Expr {
     /// A new `Expr::Call` instance.
     #[inline]
     pub fn new_call(expr: Box<Expr>, args: Vec<Box<Expr>>, range: TextRange, locations: AllowedLocations) -> Self {
-        Self::Call { expr, args, st_entry: None, locations, input: HashSet::new(), result: HashSet::new(), metadata: HashSet::new(), range }
+        Self::Call {
+            expr,
+            args,
+            st_entry: None,
+            locations,
+            input: HashSet::new(),
+            result: HashSet::new(),
+            metadata: HashSet::new(),
+            cache: None,
+            range,
+        }
     }
 
     /// Creates a new Array expression with some auxillary fields set to empty.
@@ -874,7 +923,7 @@ impl Node for Expr {
 
 
 /// Defines a simple enum that is either a Data or an IntermediateResult.
-#[derive(Clone, Debug, EnumDebug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Deserialize, EnumDebug, Eq, Hash, PartialEq, Serialize)]
 pub enum Data {
     /// It's a dataset (with the given name)
     Data(String),
@@ -1349,7 +1398,7 @@ pub enum Literal {
         range: TextRange,
     },
 
-    /// Defines a SemVer literal.
+    /// Defines a SemVer literal, which may also be a range of versions (e.g., `^1.2` or `>=1.0.0, <2.0.0`).
     Semver {
         /// We did not parse the semver _yet_.
         value: String,
@@ -1572,6 +1621,26 @@ impl Literal {
         }
     }
 
+    /// Returns a (parsed) range of semantic versions from the Literal as if it is a Semver (e.g., `^1.2`).
+    ///
+    /// # Returns
+    /// A freshly parsed (i.e., non-trivial retrieval) of a VersionRange.
+    ///
+    /// # Errors
+    /// This function errors if we could not parse the Semver as a VersionRange.
+    ///
+    /// # Panics
+    /// This function panics if the Literal is not a Semver.
+    #[inline]
+    pub fn as_version_range(&self) -> Result<VersionRange, ParseError> {
+        use Literal::*;
+        if let Semver { value, .. } = self {
+            VersionRange::from_str(value)
+        } else {
+            panic!("Attempted to get Literal of type '{}' as 'Semver'", self.data_type());
+        }
+    }
+
     /// Returns the data type of this Literal.
     #[inline]
     pub fn data_type(&self) -> DataType {
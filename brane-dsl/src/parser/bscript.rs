@@ -4,7 +4,7 @@
 //  Created:
 //    17 Aug 2022, 16:01:41
 //  Last edited:
-//    08 Dec 2023, 17:30:47
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -190,6 +190,7 @@ pub fn parse_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(inpu
         declare_func_stmt,
         expr_stmt,
         if_stmt,
+        import_source_stmt,
         import_stmt,
         let_assign_stmt,
         return_stmt,
@@ -210,6 +211,14 @@ pub fn parse_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(inpu
 /// ```branescript
 /// foo("bar", "baz")
 /// ```
+/// or
+/// ```branescript
+/// foo(bar = "baz")
+/// ```
+/// or
+/// ```branescript
+/// foo
+/// ```
 ///
 /// # Arguments
 /// - `input`: The token stream that will be parsed.
@@ -231,6 +240,28 @@ pub fn attribute<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input
                 Attribute::KeyPair { key, value, range }
             },
         ),
+        // Note that this one has to come before the list, since that one cuts after the opening parenthesis
+        comb::map(
+            seq::tuple((
+                identifier::parse,
+                tag_token!(Token::LeftParen),
+                seq::separated_pair(identifier::parse, tag_token!(Token::Equal), comb::cut(literal::parse)),
+                comb::cut(seq::pair(
+                    multi::many0(seq::preceded(
+                        tag_token!(Token::Comma),
+                        seq::separated_pair(identifier::parse, tag_token!(Token::Equal), literal::parse),
+                    )),
+                    tag_token!(Token::RightParen),
+                )),
+            )),
+            |(key, lparen, first, (rest, rparen)): (Identifier, Tokens, (Identifier, Literal), (Vec<(Identifier, Literal)>, Tokens))| {
+                let range: TextRange = TextRange::new(TextPos::from(lparen.tok[0].inner()), TextPos::end_of(rparen.tok[0].inner()));
+                let mut options: Vec<(Identifier, Literal)> = Vec::with_capacity(1 + rest.len());
+                options.push(first);
+                options.extend(rest);
+                Attribute::Options { key, options, range }
+            },
+        ),
         comb::map(
             seq::tuple((
                 identifier::parse,
@@ -242,6 +273,10 @@ pub fn attribute<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input
                 Attribute::List { key, values, range }
             },
         ),
+        comb::map(identifier::parse, |key: Identifier| {
+            let range: TextRange = key.range.clone();
+            Attribute::Options { key, options: vec![], range }
+        }),
     )))
     .parse(input)
 }
@@ -273,6 +308,7 @@ pub fn attribute_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(
     match &mut attr {
         Attribute::KeyPair { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
         Attribute::List { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
+        Attribute::Options { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
     }
 
     // Return the parsed attribute
@@ -307,6 +343,7 @@ pub fn attribute_inner_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<
     match &mut attr {
         Attribute::KeyPair { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
         Attribute::List { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
+        Attribute::Options { range, .. } => *range = TextRange::new(TextPos::from(p.tok[0].inner()), TextPos::end_of(b.tok[0].inner())),
     }
 
     // Return the parsed attribute
@@ -602,6 +639,9 @@ pub fn if_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input:
 /// For example:
 /// ```branescript
 /// import hello_world;
+/// import hello_world[1.0.0];
+/// import hello_world[^1.2];
+/// import hello_world[>=1.0.0, <2.0.0];
 /// ```
 ///
 /// # Arguments
@@ -617,29 +657,89 @@ pub fn import_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(inp
 
     // Parse the import token first
     let (r, i) = nom::error::context("'import' statement", tag_token!(Token::Import)).parse(input)?;
-    // Parse the identifier followed by an optional version number
+    // Parse the identifier followed by an optional version (range), which is a list of versions optionally preceded by a comparison
     let (r, (package, version)) = nom::error::context(
         "'import' statement",
         comb::cut(seq::pair(
             identifier::parse,
-            comb::opt(seq::delimited(tag_token!(Token::LeftBracket), tag_token!(Token::SemVer), tag_token!(Token::RightBracket))),
+            comb::opt(seq::delimited(
+                tag_token!(Token::LeftBracket),
+                multi::separated_list1(
+                    tag_token!(Token::Comma),
+                    seq::pair(
+                        comb::opt(branch::alt((
+                            tag_token!(Token::GreaterOrEqual),
+                            tag_token!(Token::LessOrEqual),
+                            tag_token!(Token::Greater),
+                            tag_token!(Token::Less),
+                        ))),
+                        branch::alt((tag_token!(Token::SemVer), tag_token!(Token::Real), tag_token!(Token::Integer))),
+                    ),
+                ),
+                tag_token!(Token::RightBracket),
+            )),
         )),
     )
     .parse(r)?;
     // Parse the closing semicolon
     let (r, s) = nom::error::context("'import' statement", tag_token!(Token::Semicolon)).parse(r)?;
 
+    // Merge the version tokens back into one literal
+    let version: Literal = match version {
+        Some(comps) => {
+            let value: String = comps
+                .iter()
+                .map(|(op, v)| {
+                    format!("{}{}", op.map(|op| op.tok[0].inner().fragment().to_string()).unwrap_or_default(), v.tok[0].inner().fragment())
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            let (first_op, first_v) = &comps[0];
+            let start = first_op.unwrap_or(*first_v);
+            let end = comps[comps.len() - 1].1;
+            Literal::Semver { value, range: TextRange::from((start.tok[0].inner(), end.tok[0].inner())) }
+        },
+        None => Literal::Semver { value: "latest".into(), range: TextRange::none() },
+    };
+
     // Put it in an Import and done
-    Ok((
-        r,
-        Stmt::new_import(
-            package,
-            version
-                .map(|t| Literal::Semver { value: t.tok[0].inner().fragment().to_string(), range: t.tok[0].inner().into() })
-                .unwrap_or(Literal::Semver { value: "latest".into(), range: TextRange::none() }),
-            TextRange::from((i.tok[0].inner(), s.tok[0].inner())),
-        ),
-    ))
+    Ok((r, Stmt::new_import(package, version, TextRange::from((i.tok[0].inner(), s.tok[0].inner())))))
+}
+
+/// Parses an import-statement that imports the definitions in another file.
+///
+/// For example:
+/// ```branescript
+/// import "helpers.bs";
+/// import "../shared/cleaning.bakery";
+/// ```
+///
+/// # Arguments
+/// - `input`: The token stream that will be parsed.
+///
+/// # Returns
+/// A pair of remaining tokens and a parsed `Stmt::ImportSource`.
+///
+/// # Errors
+/// This function may error if the tokens do not comprise a valid statement.
+pub fn import_source_stmt<'a, E: ParseError<Tokens<'a>> + ContextError<Tokens<'a>>>(input: Tokens<'a>) -> IResult<Tokens, Stmt, E> {
+    trace!("Attempting to parse ImportSource-statement");
+
+    // Parse the import token followed by a string (no cut, since it may still be a package import)
+    let (r, (i, path)) = nom::error::context(
+        "'import' statement",
+        seq::pair(tag_token!(Token::Import), comb::verify(literal::parse, |l: &Literal| matches!(l, Literal::String { .. }))),
+    )
+    .parse(input)?;
+    // Parse the closing semicolon
+    let (r, s) = nom::error::context("'import' statement", comb::cut(tag_token!(Token::Semicolon))).parse(r)?;
+
+    // Put it in an ImportSource and done
+    let path: String = match path {
+        Literal::String { value, .. } => value,
+        _ => unreachable!(),
+    };
+    Ok((r, Stmt::ImportSource { path, range: TextRange::from((i.tok[0].inner(), s.tok[0].inner())) }))
 }
 
 /// Parses a for-loop.
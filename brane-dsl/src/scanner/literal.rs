@@ -4,7 +4,7 @@
 //  Created:
 //    25 Aug 2022, 11:12:17
 //  Last edited:
-//    17 Jan 2023, 14:58:53
+//    15 Oct 2026, 23:07:51
 //  Auto updated?
 //    Yes
 //
@@ -64,6 +64,10 @@ fn integer<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span<'a>
 
 /// Parses a semver token off of the head of the given input.
 ///
+/// Besides full versions (e.g., `1.2.3`), this also accepts partial versions prefixed with `^` or `~` (e.g., `^1.2`) and versions with
+/// wildcards (e.g., `1.2.x`), such that version ranges can be given. Full versions may have a pre-release label and build metadata (e.g.,
+/// `1.2.3-rc.1+build.42`); whether these are valid is left to the version parser.
+///
 /// # Arguments
 /// - `input`: The input text to scan.
 ///
@@ -75,11 +79,41 @@ fn integer<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span<'a>
 fn semver<'a, E: ParseError<Span<'a>> + ContextError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
     const NUMBERS: &str = "0123456789";
 
-    comb::recognize(seq::tuple((
-        multi::many1(cc::one_of(NUMBERS)),
-        seq::delimited(cc::char('.'), multi::many1(cc::one_of(NUMBERS)), cc::char('.')),
-        multi::many1(cc::one_of(NUMBERS)),
-    )))
+    const WILDCARDS: &str = "xX*";
+    let is_label = |c: char| c.is_ascii_alphanumeric() || c == '.' || c == '-';
+
+    branch::alt((
+        // Partial versions with a caret or tilde
+        comb::recognize(seq::tuple((
+            cc::one_of("^~"),
+            multi::many1(cc::one_of(NUMBERS)),
+            multi::many_m_n(
+                0,
+                2,
+                seq::preceded(
+                    cc::char('.'),
+                    branch::alt((comb::recognize(multi::many1(cc::one_of(NUMBERS))), comb::recognize(cc::one_of(WILDCARDS)))),
+                ),
+            ),
+            comb::opt(seq::preceded(cc::char('-'), bc::take_while1(is_label))),
+            comb::opt(seq::preceded(cc::char('+'), bc::take_while1(is_label))),
+        ))),
+        // Full versions
+        comb::recognize(seq::tuple((
+            multi::many1(cc::one_of(NUMBERS)),
+            seq::delimited(cc::char('.'), multi::many1(cc::one_of(NUMBERS)), cc::char('.')),
+            multi::many1(cc::one_of(NUMBERS)),
+            comb::opt(seq::preceded(cc::char('-'), bc::take_while1(is_label))),
+            comb::opt(seq::preceded(cc::char('+'), bc::take_while1(is_label))),
+        ))),
+        // Versions with wildcards
+        comb::recognize(seq::tuple((
+            multi::many1(cc::one_of(NUMBERS)),
+            comb::opt(seq::preceded(cc::char('.'), multi::many1(cc::one_of(NUMBERS)))),
+            multi::many1(seq::preceded(cc::char('.'), cc::one_of("xX"))),
+            comb::not(cc::satisfy(|c| c.is_alphanumeric() || c == '_')),
+        ))),
+    ))
     .parse(input)
 }
 
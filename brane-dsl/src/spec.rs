@@ -4,7 +4,7 @@
 //  Created:
 //    10 Aug 2022, 14:03:04
 //  Last edited:
-//    16 Nov 2022, 16:40:19
+//    15 Oct 2026, 22:12:51
 //  Auto updated?
 //    Yes
 //
@@ -223,6 +223,24 @@ impl Display for Language {
     }
 }
 
+impl Language {
+    /// Determines the language of a source file from its extension.
+    ///
+    /// # Arguments
+    /// - `ext`: The extension of the file, without the dot.
+    ///
+    /// # Returns
+    /// The Language of the file (`bs` or `bscript` for BraneScript, `bk` or `bakery` for Bakery), or [`None`] if we don't recognize the
+    /// extension.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "bs" | "bscript" => Some(Self::BraneScript),
+            "bk" | "bakery" => Some(Self::Bakery),
+            _ => None,
+        }
+    }
+}
+
 impl FromStr for Language {
     type Err = LanguageParseError;
 
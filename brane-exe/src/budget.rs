@@ -0,0 +1,93 @@
+//  BUDGET.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:05:08
+//  Last edited:
+//    15 Oct 2026, 22:39:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a memory budget that limits how many bytes of values
+//!   a single workflow may keep in memory at once.
+//!
+//!   The budget is shared by all threads of a workflow. The [`Stack`] and
+//!   [`FrameStack`] charge it for every value they hold and release it
+//!   again once the value leaves them, such that a workflow building a
+//!   giant array fails with an error instead of taking down the process
+//!   that runs it.
+//!
+//!   Note that the sizes are estimates (see [`Value::size()`]); they do
+//!   not include allocator overhead or spare capacity.
+//!
+//!   [`Value::size()`]: crate::value::Value::size()
+//!   [`Stack`]: crate::stack::Stack
+//!   [`FrameStack`]: crate::frame_stack::FrameStack
+//
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+
+/***** LIBRARY *****/
+/// Keeps track of the number of bytes that a workflow has in memory.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    /// The maximum number of bytes that may be in use.
+    limit: usize,
+    /// The number of bytes currently in use.
+    used:  AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Constructor for the MemoryBudget.
+    ///
+    /// # Arguments
+    /// - `limit`: The maximum number of bytes that may be in use at once.
+    ///
+    /// # Returns
+    /// A new MemoryBudget instance with nothing in use.
+    #[inline]
+    pub fn new(limit: usize) -> Self { Self { limit, used: AtomicUsize::new(0) } }
+
+    /// Attempts to reserve the given number of bytes.
+    ///
+    /// # Arguments
+    /// - `bytes`: The number of bytes to reserve.
+    ///
+    /// # Returns
+    /// True if the bytes were reserved, or false if that would exceed the limit (in which case nothing is reserved).
+    pub fn charge(&self, bytes: usize) -> bool {
+        self.used.fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| used.checked_add(bytes).filter(|new| *new <= self.limit)).is_ok()
+    }
+
+    /// Reserves the given number of bytes, even if that exceeds the limit.
+    ///
+    /// Used for copies of values that already exist (e.g., when cloning a stack), which we cannot refuse without losing state.
+    ///
+    /// # Arguments
+    /// - `bytes`: The number of bytes to reserve.
+    #[inline]
+    pub fn force_charge(&self, bytes: usize) {
+        // NOTE: The closure never returns `None`, so this cannot fail
+        let _ = self.used.fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| Some(used.saturating_add(bytes)));
+    }
+
+    /// Releases the given number of bytes that were previously reserved.
+    ///
+    /// # Arguments
+    /// - `bytes`: The number of bytes to release.
+    #[inline]
+    pub fn release(&self, bytes: usize) {
+        // NOTE: The closure never returns `None`, so this cannot fail
+        let _ = self.used.fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| Some(used.saturating_sub(bytes)));
+    }
+
+    /// Returns the maximum number of bytes that may be in use at once.
+    #[inline]
+    pub fn limit(&self) -> usize { self.limit }
+
+    /// Returns the number of bytes currently in use.
+    #[inline]
+    pub fn used(&self) -> usize { self.used.load(Ordering::Acquire) }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    13 Sep 2022, 16:43:11
 //  Last edited:
-//    31 Jan 2024, 11:36:37
+//    16 Oct 2026, 00:46:40
 //  Auto updated?
 //    Yes
 //
@@ -22,13 +22,13 @@ use brane_ast::ast::{Edge, SymTable};
 use brane_ast::locations::Location;
 use brane_ast::{DataType, Workflow};
 use log::info;
-use specifications::data::{AccessKind, AvailabilityKind, DataName};
+use specifications::data::{AccessKind, AvailabilityKind, DataName, Provenance};
 use specifications::profiling::ProfileScopeHandle;
 
 pub use crate::errors::DummyVmError as Error;
 use crate::errors::VmError;
 use crate::pc::ProgramCounter;
-use crate::spec::{CustomGlobalState, RunState, TaskInfo, VmPlugin};
+use crate::spec::{CustomGlobalState, ExternalFunctions, PrintInfo, RunState, TaskInfo, VmPlugin};
 use crate::value::FullValue;
 use crate::vm::Vm;
 
@@ -119,6 +119,9 @@ pub struct DummyState {
     ///
     /// It looks overkill to have a mutex here, but this is required in the test of `thread.rs` due to it not using a wrapping VM.
     pub text: Arc<Mutex<String>>,
+
+    /// The external functions that workflows may call.
+    pub externals: ExternalFunctions,
 }
 impl CustomGlobalState for DummyState {}
 
@@ -177,6 +180,7 @@ impl VmPlugin for DummyPlugin {
     async fn stdout(
         global: &Arc<RwLock<Self::GlobalState>>,
         _local: &Self::LocalState,
+        _info: &PrintInfo,
         text: &str,
         newline: bool,
         _prof: ProfileScopeHandle<'_>,
@@ -213,6 +217,7 @@ impl VmPlugin for DummyPlugin {
         name: &str,
         path: &Path,
         data_name: &str,
+        _provenance: &Provenance,
         _prof: ProfileScopeHandle<'_>,
     ) -> Result<(), Self::CommitError> {
         info!("Processing dummy commit for result '{}' @ '{:?}' to '{}'...", name, path.display(), data_name,);
@@ -220,6 +225,9 @@ impl VmPlugin for DummyPlugin {
         // We don't really do anything, unfortunately
         Ok(())
     }
+
+    #[inline]
+    fn externals(global: &Self::GlobalState) -> Option<&ExternalFunctions> { Some(&global.externals) }
 }
 
 
@@ -343,9 +351,10 @@ impl DummyVm {
     pub fn new() -> Self {
         Self {
             state: Self::new_state(DummyState {
-                workflow: None,
-                text:     Arc::new(Mutex::new(String::new())),
-                results:  Arc::new(Mutex::new(HashMap::new())),
+                workflow:  None,
+                text:      Arc::new(Mutex::new(String::new())),
+                results:   Arc::new(Mutex::new(HashMap::new())),
+                externals: ExternalFunctions::new(),
             }),
         }
     }
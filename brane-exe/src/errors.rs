@@ -4,7 +4,7 @@
 //  Created:
 //    26 Aug 2022, 18:01:09
 //  Last edited:
-//    31 Jan 2024, 11:36:09
+//    15 Oct 2026, 22:06:56
 //  Auto updated?
 //    Yes
 //
@@ -152,6 +152,8 @@ impl Error for ValueError {}
 pub enum StackError {
     /// The stack overflowed :(
     StackOverflowError { size: usize },
+    /// Pushing a value would exceed the workflow's memory budget.
+    MemoryBudgetExceeded { limit: usize, size: usize },
 }
 
 impl Display for StackError {
@@ -160,6 +162,9 @@ impl Display for StackError {
         use StackError::*;
         match self {
             StackOverflowError { size } => write!(f, "Stack overflow occurred (has space for {size} values)"),
+            MemoryBudgetExceeded { limit, size } => {
+                write!(f, "Cannot push value of {size} bytes onto the stack without exceeding the workflow's memory budget of {limit} bytes")
+            },
         }
     }
 }
@@ -188,6 +193,8 @@ pub enum FrameStackError {
     VarTypeError { name: String, got: DataType, expected: DataType },
     /// The given variable was not known in the FrameStack.
     VariableNotInScope { name: String },
+    /// Assigning a value to a variable would exceed the workflow's memory budget.
+    MemoryBudgetExceeded { name: String, limit: usize, size: usize },
 }
 
 impl Display for FrameStackError {
@@ -204,6 +211,9 @@ impl Display for FrameStackError {
             UninitializedVariable { name } => write!(f, "Uninitialized variable '{name}'"),
             VarTypeError { name, got, expected } => write!(f, "Cannot assign value of type {got} to variable '{name}' of type {expected}"),
             VariableNotInScope { name } => write!(f, "Variable '{name}' is declared but not currently in scope"),
+            MemoryBudgetExceeded { name, limit, size } => {
+                write!(f, "Cannot assign value of {size} bytes to variable '{name}' without exceeding the workflow's memory budget of {limit} bytes")
+            },
         }
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    12 Sep 2022, 10:45:50
 //  Last edited:
-//    16 Jan 2024, 15:23:14
+//    15 Oct 2026, 23:40:58
 //  Auto updated?
 //    Yes
 //
@@ -20,19 +20,42 @@ use brane_ast::ast::{SymTable, VarDef};
 use brane_ast::func_id::FunctionId;
 use brane_ast::DataType;
 
+use crate::budget::MemoryBudget;
 pub use crate::errors::FrameStackError as Error;
 use crate::pc::ProgramCounter;
 use crate::value::Value;
 
 
 /***** HELPER SRUCTS *****/
+/// Defines the value of a single variable.
+///
+/// Registers are shared (by [`Arc`]) between the FrameStack of a thread and those forked from it, until either of them overwrites the variable.
+/// As such, forking a thread does not copy the values of its variables.
+#[derive(Debug)]
+struct Register {
+    /// The value of the variable.
+    value:  Value,
+    /// The memory budget that was charged for the value, if any. It is released once the last thread sharing this register drops it.
+    budget: Option<Arc<MemoryBudget>>,
+}
+
+impl Drop for Register {
+    fn drop(&mut self) {
+        if let Some(budget) = &self.budget {
+            budget.release(self.value.size());
+        }
+    }
+}
+
+
+
 /// Defines a single Frame on the FrameStack.
 #[derive(Clone, Debug)]
 struct Frame {
     /// The function definition of the calling function. If usize::MAX, that means it's the main.
     def:  usize,
     /// The variables that live within this frame, mapped by their definition index.
-    vars: HashMap<usize, Option<Value>>,
+    vars: HashMap<usize, Option<Arc<Register>>>,
     /// The return address to return to after returning from this frame.
     ret:  ProgramCounter,
 }
@@ -57,12 +80,16 @@ impl Frame {
 
 /***** LIBRARY *****/
 /// Implements a FrameStack, which is used to keep track of function calls and their expected return types.
+///
+/// Cloning a FrameStack (or forking it) does not copy the values of its variables; they are shared until either overwrites them.
 #[derive(Clone, Debug)]
 pub struct FrameStack {
     /// The stack itself
-    data:  Vec<Frame>,
+    data:   Vec<Frame>,
     /// The virtual table that is also a stack but for scopes.
-    table: Arc<SymTable>,
+    table:  Arc<SymTable>,
+    /// The memory budget that is charged for the values of variables, if any.
+    budget: Option<Arc<MemoryBudget>>,
 }
 
 impl FrameStack {
@@ -81,24 +108,63 @@ impl FrameStack {
         data.push(Frame { def: usize::MAX, vars: HashMap::new(), ret: ProgramCounter::new(FunctionId::Main, usize::MAX) });
 
         // Run it
-        Self { data, table }
+        Self { data, table, budget: None }
     }
 
-    /// Forks the framestack, which copies the existing variables in-scope into a single frame that is the new main.
+    /// Forks the framestack, which puts the existing variables in-scope into a single frame that is the new main.
+    ///
+    /// The values of the variables are shared with this FrameStack until either overwrites them, so forking is cheap even for large values.
     ///
     /// # Returns
     /// A new FrameStack instance that can be used in a forked thread.
     pub fn fork(&self) -> Self {
         // Collect all variables into one thingamabob
-        let vars: HashMap<usize, Option<Value>> =
-            self.table.vars.iter().enumerate().map(|(i, _)| (i, Some(self.get(i).unwrap_or(&Value::Void).clone()))).collect();
+        let vars: HashMap<usize, Option<Arc<Register>>> = (0..self.table.vars.len())
+            .map(|i| (i, Some(self.register(i).cloned().unwrap_or_else(|| Arc::new(Register { value: Value::Void, budget: None })))))
+            .collect();
 
-        // Now manually create the stack with a custom frame
-        let mut data: Vec<Frame> = Vec::with_capacity(self.data.capacity());
-        data.push(Frame { def: usize::MAX, vars, ret: ProgramCounter::new(FunctionId::Main, usize::MAX) });
-        Self { data, table: self.table.clone() }
+        // Now manually create the stack with a custom frame (the rest of the frames are allocated when the forked thread needs them)
+        let data: Vec<Frame> = vec![Frame { def: usize::MAX, vars, ret: ProgramCounter::new(FunctionId::Main, usize::MAX) }];
+        Self { data, table: self.table.clone(), budget: self.budget.clone() }
     }
 
+    /// Changes the memory budget that is charged for the values of variables.
+    ///
+    /// Any values that are already stored are moved from the old budget to the new one (even if they exceed it).
+    ///
+    /// # Arguments
+    /// - `budget`: The new [`MemoryBudget`] to charge, or [`None`] to no longer limit memory usage.
+    ///
+    /// # Returns
+    /// Nothing, but does update the internal budget.
+    pub fn set_budget(&mut self, budget: Option<Arc<MemoryBudget>>) {
+        for reg in self.data.iter_mut().flat_map(|frame| frame.vars.values_mut().flatten()) {
+            match Arc::get_mut(reg) {
+                Some(reg) => {
+                    if let Some(old) = &reg.budget {
+                        old.release(reg.value.size());
+                    }
+                    if let Some(new) = &budget {
+                        new.force_charge(reg.value.size());
+                    }
+                    reg.budget = budget.clone();
+                },
+                None => {
+                    // It's shared with another thread that keeps using the old budget, so we take our own copy
+                    if let Some(new) = &budget {
+                        new.force_charge(reg.value.size());
+                    }
+                    *reg = Arc::new(Register { value: reg.value.clone(), budget: budget.clone() });
+                },
+            }
+        }
+        self.budget = budget;
+    }
+
+    /// Returns the memory budget that is charged for the values of variables, if any.
+    #[inline]
+    pub fn budget(&self) -> Option<&Arc<MemoryBudget>> { self.budget.as_ref() }
+
     /// Updates the internal table to be the same as the given one.
     ///
     /// This is useful if the workflow is updating its own states.
@@ -196,6 +262,7 @@ impl FrameStack {
 
         // Search the frames (in reverse order)
         if let Some(frame) = self.data.last_mut() {
+            // Dropping the register releases its value from the budget (if no other thread shares it)
             if frame.vars.remove(&def).is_none() {
                 return Err(Error::UndeclaredUndeclaration { name: self.table.var(def).name.clone() });
             }
@@ -215,7 +282,8 @@ impl FrameStack {
     /// Nothing, but does update the given variable's value.
     ///
     /// # Errors
-    /// This function may error if there was nothing left on the stack or if the given variable was not declared.
+    /// This function may error if there was nothing left on the stack, if the given variable was not declared or if the value does not fit in the
+    /// memory budget.
     pub fn set(&mut self, def: usize, value: Value) -> Result<(), Error> {
         // Throw a special error if the stack is empty
         if self.data.is_empty() {
@@ -232,7 +300,15 @@ impl FrameStack {
         // Search the frames (in reverse order)
         for f in self.data.iter_mut().rev() {
             if let Some(v) = f.vars.get_mut(&def) {
-                *v = Some(value);
+                // Charge the new value; the old one is released when its register is dropped (if no other thread shares it)
+                if let Some(budget) = &self.budget {
+                    let size: usize = value.size();
+                    if !budget.charge(size) {
+                        return Err(Error::MemoryBudgetExceeded { name: var.name.clone(), limit: budget.limit(), size });
+                    }
+                }
+
+                *v = Some(Arc::new(Register { value, budget: self.budget.clone() }));
                 return Ok(());
             }
         }
@@ -262,7 +338,7 @@ impl FrameStack {
             if let Some(v) = f.vars.get(&def) {
                 match v {
                     Some(v) => {
-                        return Ok(v);
+                        return Ok(&v.value);
                     },
                     None => return Err(Error::UninitializedVariable { name: self.table.var(def).name.clone() }),
                 }
@@ -273,6 +349,24 @@ impl FrameStack {
         Err(Error::UndeclaredVariable { name: self.table.var(def).name.clone() })
     }
 
+    /// Returns the variables in the main frame (i.e., the global variables of the workflow) that have a value.
+    ///
+    /// # Returns
+    /// An iterator over the definition indices of the variables and their values, in no particular order.
+    pub fn globals(&self) -> impl Iterator<Item = (usize, &Value)> {
+        self.data.first().into_iter().flat_map(|frame| frame.vars.iter().filter_map(|(def, reg)| reg.as_ref().map(|reg| (*def, &reg.value))))
+    }
+
+    /// Returns the register of the variable with the given index, if it is declared and initialized.
+    ///
+    /// # Arguments
+    /// - `def`: The variable to get the register of.
+    ///
+    /// # Returns
+    /// The [`Register`] of the variable, or [`None`] if it is not declared or not yet initialized.
+    #[inline]
+    fn register(&self, def: usize) -> Option<&Arc<Register>> { self.data.iter().rev().find_map(|f| f.vars.get(&def)).and_then(Option::as_ref) }
+
     /// Returns the total capacity of the FrameStack. Using any more than this will result in overflows.
     #[inline]
     pub fn capacity(&self) -> usize { self.data.capacity() }
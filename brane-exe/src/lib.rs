@@ -4,7 +4,7 @@
 //  Created:
 //    09 Sep 2022, 11:54:53
 //  Last edited:
-//    16 Jan 2024, 15:15:43
+//    15 Oct 2026, 22:06:56
 //  Auto updated?
 //    Yes
 //
@@ -13,6 +13,7 @@
 //
 
 // Define some modules
+pub mod budget;
 pub mod errors;
 pub mod spec;
 // pub mod vtable;
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Aug 2022, 18:26:40
 //  Last edited:
-//    31 Jan 2024, 11:36:19
+//    16 Oct 2026, 00:46:40
 //  Auto updated?
 //    Yes
 //
@@ -15,16 +15,21 @@
 
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::fmt::{Debug, Formatter, Result as FResult};
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 
 use brane_ast::ast::SymTable;
 use brane_ast::locations::Location;
-use specifications::data::{AccessKind, DataName, PreprocessKind};
+use brane_ast::state::CompileState;
+use brane_dsl::data_type::FunctionSignature;
+use chrono::{DateTime, Utc};
+use specifications::data::{AccessKind, DataName, PreprocessKind, Provenance};
 use specifications::package::Capability;
 use specifications::profiling::ProfileScopeHandle;
 use specifications::version::Version;
 
+use crate::budget::MemoryBudget;
 use crate::frame_stack::FrameStack;
 use crate::pc::ProgramCounter;
 use crate::value::FullValue;
@@ -53,6 +58,88 @@ impl CustomLocalState for () {
 
 
 
+/// A function implemented in Rust that BraneScript can call like a builtin, without packaging it as a container.
+///
+/// Embedders register these in an [`ExternalFunctions`] that their [`VmPlugin`] returns in [`VmPlugin::externals()`].
+#[async_trait::async_trait]
+pub trait ExternalFunction: 'static + Send + Sync {
+    /// Returns the types of the function's arguments and its return type, which the compiler uses to check calls to it.
+    fn signature(&self) -> FunctionSignature;
+
+    /// Calls the function.
+    ///
+    /// # Arguments
+    /// - `args`: The values of the arguments, which are guaranteed to match the types in the function's [`signature()`](ExternalFunction::signature()).
+    ///
+    /// # Returns
+    /// The value that the function returns, which should match the return type in its signature (use [`FullValue::Void`] for functions that
+    /// don't return anything).
+    ///
+    /// # Errors
+    /// This function may error whenever it likes, which fails the workflow.
+    async fn call(&self, args: Vec<FullValue>) -> Result<FullValue, Box<dyn 'static + Send + Sync + Error>>;
+}
+
+/// A set of [`ExternalFunction`]s, by the name under which BraneScript calls them.
+#[derive(Clone, Default)]
+pub struct ExternalFunctions {
+    /// The functions, by name.
+    funcs: HashMap<String, Arc<dyn ExternalFunction>>,
+}
+impl ExternalFunctions {
+    /// Constructor for the ExternalFunctions that initializes it without any functions.
+    ///
+    /// # Returns
+    /// A new ExternalFunctions instance.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Registers a new function, replacing any function that was registered under the same name.
+    ///
+    /// # Arguments
+    /// - `name`: The name under which BraneScript can call the function.
+    /// - `func`: The [`ExternalFunction`] to call.
+    ///
+    /// # Returns
+    /// Self for chaining.
+    #[inline]
+    pub fn register(&mut self, name: impl Into<String>, func: impl ExternalFunction) -> &mut Self {
+        self.funcs.insert(name.into(), Arc::new(func));
+        self
+    }
+
+    /// Returns the function with the given name, if any.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the function.
+    ///
+    /// # Returns
+    /// The [`ExternalFunction`] registered under that name, or [`None`] if there is none.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn ExternalFunction>> { self.funcs.get(name) }
+
+    /// Declares all functions in the given compile state, such that snippets compiled with it may call them.
+    ///
+    /// Functions that clash with a builtin or a function that was declared before are skipped.
+    ///
+    /// # Arguments
+    /// - `state`: The [`CompileState`] to declare the functions in.
+    pub fn declare(&self, state: &mut CompileState) {
+        for (name, func) in &self.funcs {
+            state.declare_external(name, func.signature());
+        }
+    }
+}
+impl Debug for ExternalFunctions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let mut names: Vec<&String> = self.funcs.keys().collect();
+        names.sort();
+        f.debug_struct("ExternalFunctions").field("funcs", &names).finish()
+    }
+}
+
+
+
 /// A trait that implements various missing pieces in task execution. See the `brane-tsk` crate for implementations.
 #[async_trait::async_trait]
 pub trait VmPlugin: 'static + Send + Sync {
@@ -139,6 +226,7 @@ pub trait VmPlugin: 'static + Send + Sync {
     /// # Arguments
     /// - `global`: The custom global state for keeping track of your own things during execution.
     /// - `local`: The custom local state for keeping track of your own things faster but only local to this (execution) thread.
+    /// - `info`: A [`PrintInfo`] that describes where and when the workflow printed the text, e.g., to tag it with.
     /// - `text`: The text to write to your version of stdout.
     /// - `newline`: Whether or not to print a closing newline after the text (i.e., whether to use `println` or `print`).
     /// - `prof`: A ProfileScopeHandle that can be used to prove additional details about the timings of this function.
@@ -148,6 +236,7 @@ pub trait VmPlugin: 'static + Send + Sync {
     async fn stdout(
         global: &Arc<RwLock<Self::GlobalState>>,
         local: &Self::LocalState,
+        info: &PrintInfo,
         text: &str,
         newline: bool,
         prof: ProfileScopeHandle<'_>,
@@ -195,10 +284,12 @@ pub trait VmPlugin: 'static + Send + Sync {
     /// - `name`: The name of the intermediate result to promoto (you'll typically use this for debugging only).
     /// - `path`: The path where the intermediate result is available. You'll probably want to archive this somewhere else before continuing. **Note**: Be aware that this path is relative to some directory you still have to prepend.
     /// - `data_name`: The identifier of the dataset once the intermediate result is promoted. If it already exists, you'll probably want to override the old value with the new one.
+    /// - `provenance`: Describes where the intermediate result came from. You'll probably want to record this with the dataset.
     /// - `prof`: A ProfileScopeHandle that can be used to prove additional details about the timings of this function.
     ///
     /// # Errors
     /// This function may error whenever it likes.
+    #[allow(clippy::too_many_arguments)]
     async fn commit(
         global: &Arc<RwLock<Self::GlobalState>>,
         local: &Self::LocalState,
@@ -206,8 +297,27 @@ pub trait VmPlugin: 'static + Send + Sync {
         name: &str,
         path: &Path,
         data_name: &str,
+        provenance: &Provenance,
         prof: ProfileScopeHandle<'_>,
     ) -> Result<(), Self::CommitError>;
+
+
+
+    /// Returns the [`ExternalFunction`]s that workflows may call in addition to the builtins.
+    ///
+    /// By default, there are none. Note that the compiler only accepts calls to them if they are declared in its state first (see
+    /// [`ExternalFunctions::declare()`]).
+    ///
+    /// # Arguments
+    /// - `global`: The custom global state, which is where you'll typically keep them.
+    ///
+    /// # Returns
+    /// The [`ExternalFunctions`], or [`None`] if this plugin does not provide any.
+    #[inline]
+    fn externals(global: &Self::GlobalState) -> Option<&ExternalFunctions> {
+        let _ = global;
+        None
+    }
 }
 
 
@@ -236,6 +346,21 @@ impl<G: CustomGlobalState> RunState<G> {
     /// A new RunState instance.
     #[inline]
     pub fn new(table: Arc<SymTable>, global: Arc<RwLock<G>>) -> Self { Self { fstack: FrameStack::new(512, table), global } }
+
+    /// Limits the number of bytes of values that workflows run with this state may keep in memory at once.
+    ///
+    /// Workflows that exceed the limit fail with an error instead of allocating more.
+    ///
+    /// # Arguments
+    /// - `limit`: The maximum number of bytes, shared across all threads of the workflow (see [`MemoryBudget`]).
+    ///
+    /// # Returns
+    /// The same RunState but with the limit applied.
+    #[inline]
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.fstack.set_budget(Some(Arc::new(MemoryBudget::new(limit))));
+        self
+    }
 }
 
 
@@ -266,3 +391,16 @@ pub struct TaskInfo<'a> {
     /// If this task returns an intermediate result, then this specifies the name it should have.
     pub result:   &'a Option<String>,
 }
+
+
+
+/// Defines where and when a workflow printed something, so that the stdout closure can tag what it writes with it.
+#[derive(Clone, Debug)]
+pub struct PrintInfo {
+    /// The program counter of the call to `print` or `println`.
+    pub pc:        ProgramCounter,
+    /// The name of the function that called it (`<main>` if it was called outside of any function).
+    pub function:  String,
+    /// The moment it was called.
+    pub timestamp: DateTime<Utc>,
+}
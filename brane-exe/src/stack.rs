@@ -4,7 +4,7 @@
 //  Created:
 //    26 Aug 2022, 18:34:47
 //  Last edited:
-//    14 Nov 2022, 10:40:58
+//    15 Oct 2026, 22:39:18
 //  Auto updated?
 //    Yes
 //
@@ -15,9 +15,11 @@
 use std::borrow::{Borrow, BorrowMut};
 use std::mem;
 use std::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+use std::sync::Arc;
 
 use log::warn;
 
+use crate::budget::MemoryBudget;
 pub use crate::errors::StackError as Error;
 use crate::value::Value;
 
@@ -327,10 +329,14 @@ impl<'a> IntoIterator for &'a mut StackSlice {
 
 
 /// The stack itself.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct Stack {
     /// The slots on the stack.
-    slots: Vec<StackSlot>,
+    slots:  Vec<StackSlot>,
+    /// The maximum number of slots on the stack. They are only allocated once used, such that (many) forked threads are cheap.
+    limit:  usize,
+    /// The memory budget that is charged for the values on the stack, if any.
+    budget: Option<Arc<MemoryBudget>>,
 }
 
 impl Stack {
@@ -342,7 +348,18 @@ impl Stack {
     /// # Returns
     /// A new instance of a Stack with `size` slots available.
     #[inline]
-    pub fn new(size: usize) -> Self { Self { slots: Vec::with_capacity(size) } }
+    pub fn new(size: usize) -> Self { Self { slots: Vec::new(), limit: size, budget: None } }
+
+    /// Constructor for the Stack that charges the values pushed to it to the given memory budget.
+    ///
+    /// # Arguments
+    /// - `size`: The size of the stack. This is actually non-configurable during execution.
+    /// - `budget`: The [`MemoryBudget`] to charge, or [`None`] to not limit memory usage.
+    ///
+    /// # Returns
+    /// A new instance of a Stack with `size` slots available.
+    #[inline]
+    pub fn with_budget(size: usize, budget: Option<Arc<MemoryBudget>>) -> Self { Self { slots: Vec::new(), limit: size, budget } }
 
     /// Constructor for the Slack that takes a raw StackSlot slice.
     ///
@@ -352,7 +369,39 @@ impl Stack {
     /// # Returns
     /// A new instance of a Stack with the given slots.
     #[inline]
-    fn from_slice(slice: &[StackSlot]) -> Self { Self { slots: slice.to_vec() } }
+    fn from_slice(slice: &[StackSlot]) -> Self { Self { slots: slice.to_vec(), limit: slice.len(), budget: None } }
+
+    /// Returns the estimated number of bytes occupied by the values on the stack (see [`Value::size()`]).
+    #[inline]
+    fn size(&self) -> usize { self.slots.iter().filter(|s| s.is_value()).map(|s| s.as_value().size()).sum() }
+
+    /// Charges the given value to the memory budget, if any.
+    ///
+    /// # Arguments
+    /// - `value`: The value that is about to be put on the stack.
+    ///
+    /// # Errors
+    /// This function errors if the value does not fit in the budget anymore.
+    fn charge(&self, value: &Value) -> Result<(), Error> {
+        if let Some(budget) = &self.budget {
+            let size: usize = value.size();
+            if !budget.charge(size) {
+                return Err(Error::MemoryBudgetExceeded { limit: budget.limit(), size });
+            }
+        }
+        Ok(())
+    }
+
+    /// Releases the given value from the memory budget, if any.
+    ///
+    /// # Arguments
+    /// - `value`: The value that was taken off the stack.
+    #[inline]
+    fn release(&self, value: &Value) {
+        if let Some(budget) = &self.budget {
+            budget.release(value.size());
+        }
+    }
 
     /// Returns the top value of the stack, popping it.
     ///
@@ -363,7 +412,9 @@ impl Stack {
         while let Some(v) = self.slots.pop() {
             // Stop if it is a value
             if v.is_value() {
-                return Some(v.into_value());
+                let value: Value = v.into_value();
+                self.release(&value);
+                return Some(value);
             }
             // Otherwise, warn
             warn!("Popping {:?} in a non-dynamic pop situation", v);
@@ -384,7 +435,9 @@ impl Stack {
         while let Some(v) = self.slots.pop() {
             // Stop if it is a value
             if v.is_value() {
-                res.push(v.into_value());
+                let value: Value = v.into_value();
+                self.release(&value);
+                res.push(value);
                 continue;
             }
             // Otherwise, stop
@@ -404,15 +457,17 @@ impl Stack {
     /// - `value`: The value to push onto the stack.
     ///
     /// # Errors
-    /// This function may error if the stack is growing too large.
+    /// This function may error if the stack is growing too large or if the value does not fit in the memory budget.
     pub fn push<V: Into<Value>>(&mut self, value: V) -> Result<(), Error> {
         // Make sure there is enough space first
-        if self.slots.len() == self.slots.capacity() {
-            return Err(Error::StackOverflowError { size: self.slots.capacity() });
+        if self.slots.len() >= self.limit {
+            return Err(Error::StackOverflowError { size: self.limit });
         }
+        let value: Value = value.into();
+        self.charge(&value)?;
 
         // Push the value next
-        self.slots.push(StackSlot::from(value.into()));
+        self.slots.push(StackSlot::from(value));
         Ok(())
     }
 
@@ -422,8 +477,8 @@ impl Stack {
     /// This function may error if the stack is growing too large.
     pub fn push_pop_marker(&mut self) -> Result<(), Error> {
         // Make sure there is enough space first
-        if self.slots.len() == self.slots.capacity() {
-            return Err(Error::StackOverflowError { size: self.slots.capacity() });
+        if self.slots.len() >= self.limit {
+            return Err(Error::StackOverflowError { size: self.limit });
         }
 
         // Push the value next
@@ -441,19 +496,39 @@ impl Stack {
     /// - `value`: The value to push onto the stack.
     ///
     /// # Errors
-    /// This function may error if the stack is growing too large.
+    /// This function may error if the stack is growing too large or if the value does not fit in the memory budget.
     pub fn insert<V: Into<Value>>(&mut self, index: usize, value: V) -> Result<(), Error> {
         // Make sure there is enough space first
-        if self.slots.len() == self.slots.capacity() {
-            return Err(Error::StackOverflowError { size: self.slots.capacity() });
+        if self.slots.len() >= self.limit {
+            return Err(Error::StackOverflowError { size: self.limit });
         }
+        let value: Value = value.into();
+        self.charge(&value)?;
 
         // Insert the value next
-        self.slots.insert(index, StackSlot::from(value.into()));
+        self.slots.insert(index, StackSlot::from(value));
         Ok(())
     }
 }
 
+impl Clone for Stack {
+    fn clone(&self) -> Self {
+        // The copies take up memory too
+        if let Some(budget) = &self.budget {
+            budget.force_charge(self.size());
+        }
+        Self { slots: self.slots.clone(), limit: self.limit, budget: self.budget.clone() }
+    }
+}
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        if let Some(budget) = &self.budget {
+            budget.release(self.size());
+        }
+    }
+}
+
 impl Index<usize> for Stack {
     type Output = Value;
 
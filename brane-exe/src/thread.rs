@@ -4,7 +4,7 @@
 //  Created:
 //    09 Sep 2022, 13:23:41
 //  Last edited:
-//    31 Jan 2024, 11:36:30
+//    16 Oct 2026, 00:46:40
 //  Auto updated?
 //    Yes
 //
@@ -16,7 +16,7 @@
 use std::any::type_name;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
 use async_recursion::async_recursion;
 use brane_ast::ast::{ClassDef, ComputeTaskDef, Edge, EdgeInstr, FunctionDef, TaskDef};
@@ -24,11 +24,12 @@ use brane_ast::func_id::FunctionId;
 use brane_ast::locations::Location;
 use brane_ast::spec::{BuiltinClasses, BuiltinFunctions};
 use brane_ast::{DataType, MergeStrategy, Workflow};
+use chrono::Utc;
 use enum_debug::EnumDebug as _;
 use futures::future::{BoxFuture, FutureExt};
 use log::debug;
-use specifications::data::{AccessKind, AvailabilityKind, DataName};
-use specifications::profiling::{ProfileScopeHandle, ProfileScopeHandleOwned};
+use specifications::data::{AccessKind, AvailabilityKind, DataName, Provenance};
+use specifications::profiling::{ProfileScope, ProfileScopeHandle, ProfileScopeHandleOwned};
 use tokio::spawn;
 use tokio::task::JoinHandle;
 
@@ -37,7 +38,7 @@ use crate::errors::ReturnEdge;
 pub use crate::errors::VmError as Error;
 use crate::frame_stack::FrameStack;
 use crate::pc::ProgramCounter;
-use crate::spec::{CustomGlobalState, CustomLocalState, RunState, TaskInfo, VmPlugin};
+use crate::spec::{CustomGlobalState, CustomLocalState, ExternalFunction, PrintInfo, RunState, TaskInfo, VmPlugin};
 use crate::stack::Stack;
 use crate::value::{FullValue, Value};
 
@@ -47,14 +48,18 @@ use crate::value::{FullValue, Value};
 mod tests {
     use std::sync::Mutex;
 
+    use brane_ast::state::CompileState;
     use brane_ast::traversals::print::ast;
-    use brane_ast::{compile_program, CompileResult, ParserOptions};
+    use brane_ast::{compile_program, compile_snippet, CompileResult, ParserOptions};
+    use brane_dsl::data_type::FunctionSignature;
     use brane_shr::utilities::{create_data_index, create_package_index, test_on_dsl_files_async};
     use specifications::data::DataIndex;
     use specifications::package::PackageIndex;
 
     use super::*;
     use crate::dummy::{DummyPlanner, DummyPlugin, DummyState};
+    use crate::errors::{FrameStackError, StackError};
+    use crate::spec::ExternalFunctions;
 
 
     /// Tests the traversal by generating symbol tables for every file.
@@ -116,9 +121,10 @@ mod tests {
                 // Run the program
                 let text: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
                 let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState {
-                    workflow: Some(workflow.clone()),
-                    results:  Arc::new(Mutex::new(HashMap::new())),
-                    text:     text.clone(),
+                    workflow:  Some(workflow.clone()),
+                    results:   Arc::new(Mutex::new(HashMap::new())),
+                    text:      text.clone(),
+                    externals: ExternalFunctions::new(),
                 });
                 match main.run::<DummyPlugin>(ProfileScopeHandleOwned::dummy()).await {
                     Ok(value) => {
@@ -137,6 +143,104 @@ mod tests {
         })
         .await;
     }
+
+
+
+    /// An external function that greets whoever it is given.
+    struct Greet;
+    #[async_trait::async_trait]
+    impl ExternalFunction for Greet {
+        fn signature(&self) -> FunctionSignature { FunctionSignature::new(vec![brane_dsl::DataType::String], brane_dsl::DataType::String) }
+
+        async fn call(&self, args: Vec<FullValue>) -> Result<FullValue, Box<dyn 'static + Send + Sync + std::error::Error>> {
+            match args.into_iter().next() {
+                Some(FullValue::String(name)) => Ok(FullValue::String(format!("Hello, {name}!"))),
+                _ => Err("Expected a string".into()),
+            }
+        }
+    }
+
+    /// Compiles and plans the given snippet for the tests below, panicking if it fails.
+    fn compile_test_snippet(state: &mut CompileState, code: &str) -> Arc<Workflow> {
+        let workflow: Workflow =
+            match compile_snippet(state, code.as_bytes(), &PackageIndex::empty(), &DataIndex::from_infos(vec![]).unwrap(), &ParserOptions::bscript())
+            {
+                CompileResult::Workflow(wf, _) => wf,
+                CompileResult::Eof(err) => {
+                    err.prettyprint("<test>", code);
+                    panic!("Failed to compile to workflow (see output above)");
+                },
+                CompileResult::Err(errs) => {
+                    for e in errs {
+                        e.prettyprint("<test>", code);
+                    }
+                    panic!("Failed to compile to workflow (see output above)");
+                },
+                _ => unreachable!(),
+            };
+        Arc::new(DummyPlanner::plan(&mut HashMap::new(), workflow))
+    }
+
+    /// Tests that workflows can call external functions provided by the plugin.
+    #[tokio::test]
+    async fn test_thread_externals() {
+        let mut externals: ExternalFunctions = ExternalFunctions::new();
+        externals.register("greet", Greet);
+
+        // Compile a snippet that calls it
+        let mut state: CompileState = CompileState::new();
+        externals.declare(&mut state);
+        let workflow: Arc<Workflow> = compile_test_snippet(&mut state, "println(greet(\"world\"));");
+
+        // Run it
+        let text: Arc<Mutex<String>> = Arc::new(Mutex::new(String::new()));
+        let main: Thread<DummyState, ()> = Thread::new(&workflow, DummyState {
+            workflow: Some(workflow.clone()),
+            results:  Arc::new(Mutex::new(HashMap::new())),
+            text:     text.clone(),
+            externals,
+        });
+        if let Err(err) = main.run::<DummyPlugin>(ProfileScopeHandleOwned::dummy()).await {
+            err.prettyprint();
+            panic!("Failed to execute workflow (see output above)");
+        }
+        assert_eq!(*text.lock().unwrap(), "Hello, world!\n");
+    }
+
+    /// Tests that workflows cannot keep more values in memory than their budget allows.
+    #[tokio::test]
+    async fn test_thread_memory_limit() {
+        let code: &str = &format!("let big := \"{}\"; let arr := [big, big, big, big]; println(\"done\");", "x".repeat(1024));
+        let workflow: Arc<Workflow> = compile_test_snippet(&mut CompileState::new(), code);
+
+        // Run it with the given limit
+        let run = |limit: usize| {
+            let workflow: Arc<Workflow> = workflow.clone();
+            async move {
+                let global: DummyState = DummyState {
+                    workflow:  Some(workflow.clone()),
+                    results:   Arc::new(Mutex::new(HashMap::new())),
+                    text:      Arc::new(Mutex::new(String::new())),
+                    externals: ExternalFunctions::new(),
+                };
+                let state: RunState<DummyState> = RunState::new(workflow.table.clone(), Arc::new(RwLock::new(global))).with_memory_limit(limit);
+                let main: Thread<DummyState, ()> = Thread::from_state(&workflow, state);
+                main.run::<DummyPlugin>(ProfileScopeHandleOwned::dummy()).await
+            }
+        };
+
+        // The string and its four copies fit in 16 KiB, but not in 4 KiB
+        if let Err(err) = run(16 * 1024).await {
+            err.prettyprint();
+            panic!("Failed to execute workflow (see output above)");
+        }
+        match run(4 * 1024).await {
+            Err(Error::StackError { err: StackError::MemoryBudgetExceeded { .. }, .. })
+            | Err(Error::VarSetError { err: FrameStackError::MemoryBudgetExceeded { .. }, .. }) => {},
+            Err(err) => panic!("Expected a memory budget error, got: {err}"),
+            Ok(res) => panic!("Expected a memory budget error, got value: {res:?}"),
+        }
+    }
 }
 
 
@@ -1058,6 +1162,8 @@ pub struct Thread<G: CustomGlobalState, L: CustomLocalState> {
 
     /// The threads that we're blocking on.
     threads: Vec<(usize, JoinHandle<Result<Value, Error>>)>,
+    /// The intermediate results to promote once the snippet is done (as `(result, dataset)` pairs). Shared with forks of this thread.
+    kept:    Arc<Mutex<Vec<(String, String)>>>,
 
     /// The thread-global custom part of the RunState.
     global: Arc<RwLock<G>>,
@@ -1089,6 +1195,7 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
             fstack: FrameStack::new(512, workflow.table.clone()),
 
             threads: vec![],
+            kept:    Arc::new(Mutex::new(vec![])),
 
             global: global.clone(),
             local:  L::new(&global),
@@ -1108,10 +1215,11 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
             pc: ProgramCounter::start(),
 
-            stack:  Stack::new(2048),
+            stack:  Stack::with_budget(2048, state.fstack.budget().cloned()),
             fstack: state.fstack,
 
             threads: vec![],
+            kept:    Arc::new(Mutex::new(vec![])),
 
             global: state.global.clone(),
             local:  L::new(&state.global),
@@ -1124,7 +1232,7 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
     /// - `offset`: The offset (as a `(body, idx)` pair) where the thread will begin computation in the edges list.
     ///
     /// # Returns
-    /// A new Thread that is partly cloned of this one.
+    /// A new Thread that is partly cloned of this one. Note that variable values are shared with this thread until either of them assigns them.
     #[inline]
     pub fn fork(&self, offset: ProgramCounter) -> Self {
         Self {
@@ -1133,10 +1241,11 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
             pc: offset,
 
-            stack:  Stack::new(2048),
+            stack:  Stack::with_budget(2048, self.fstack.budget().cloned()),
             fstack: self.fstack.fork(),
 
             threads: vec![],
+            kept:    self.kept.clone(),
 
             global: self.global.clone(),
             local:  L::new(&self.global),
@@ -1153,6 +1262,104 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
         }
     }
 
+    /// Looks up the external function with the given name in the plugin.
+    ///
+    /// # Generic arguments
+    /// - `P`: The [`VmPlugin`] that provides the external functions.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the function to look up.
+    ///
+    /// # Returns
+    /// The [`ExternalFunction`] with that name, or [`None`] if the plugin does not provide one.
+    fn external<P: VmPlugin<GlobalState = G>>(&self, name: &str) -> Option<Arc<dyn ExternalFunction>> {
+        match self.global.read() {
+            Ok(global) => P::externals(&global).and_then(|externals| externals.get(name).cloned()),
+            Err(_) => None,
+        }
+    }
+
+    /// Describes where the given intermediate result came from by looking up the task call that produces it.
+    ///
+    /// # Arguments
+    /// - `result`: The name of the intermediate result.
+    /// - `loc`: The location where the intermediate result lives.
+    ///
+    /// # Returns
+    /// A [`Provenance`] for the result. Its task and inputs are left empty if no call in this workflow produces it (e.g., because a previous snippet did).
+    fn provenance(&self, result: &str, loc: &Location) -> Provenance {
+        let mut provenance: Provenance =
+            Provenance { result: result.into(), task: None, inputs: vec![], location: loc.clone(), committed: Utc::now() };
+        for edge in self.graph.iter().chain(self.funcs.values().flatten()) {
+            if let Edge::Node { task, input, result: Some(res), .. } = edge {
+                if res != result {
+                    continue;
+                }
+                if let TaskDef::Compute(ComputeTaskDef { package, version, function, .. }) = self.fstack.table().task(*task) {
+                    provenance.task = Some(format!("{}[{}]::{}", package, version, function.name));
+                }
+                provenance.inputs = input.keys().map(|name| name.name().into()).collect();
+                provenance.inputs.sort();
+                break;
+            }
+        }
+        provenance
+    }
+
+    /// Describes where and when the workflow prints something by resolving the function that does so.
+    ///
+    /// # Arguments
+    /// - `pc`: The program counter of the call to `print` or `println`.
+    ///
+    /// # Returns
+    /// A [`PrintInfo`] with the name of the function and the current time.
+    fn print_info(&self, pc: ProgramCounter) -> PrintInfo {
+        let function: String = pc.resolved(self.fstack.table()).func_name().cloned().unwrap_or_else(|| pc.func_id.to_string());
+        PrintInfo { pc, function, timestamp: Utc::now() }
+    }
+
+    /// Promotes the given intermediate result to a dataset using the plugin.
+    ///
+    /// # Generic arguments
+    /// - `P`: The [`VmPlugin`] that does the actual promoting.
+    ///
+    /// # Arguments
+    /// - `pc`: The program counter of the edge that promotes the result, for debugging.
+    /// - `res_name`: The name of the intermediate result to promote.
+    /// - `data_name`: The name of the dataset to promote it to.
+    /// - `prof`: A ProfileScope that is used to provide more details about the time it takes to promote the result.
+    ///
+    /// # Errors
+    /// This function errors if the result is unknown or if the plugin failed to promote it.
+    async fn commit<P: VmPlugin<GlobalState = G, LocalState = L>>(
+        &self,
+        pc: ProgramCounter,
+        res_name: &str,
+        data_name: &str,
+        prof: &ProfileScope,
+    ) -> Result<(), Error> {
+        // Try to find out where this res lives, currently
+        let loc: &String = match self.fstack.table().results.get(res_name) {
+            Some(loc) => loc,
+            None => {
+                return Err(Error::UnknownResult { pc, name: res_name.into() });
+            },
+        };
+        let provenance: Provenance = self.provenance(res_name, loc);
+
+        // Call the external data committer
+        let res_path: PathBuf = res_name.into();
+        if let Err(err) = prof
+            .nest_fut(format!("{}::commit()", type_name::<P>()), |scope| {
+                P::commit(&self.global, &self.local, loc, res_name, &res_path, data_name, &provenance, scope)
+            })
+            .await
+        {
+            return Err(Error::Custom { pc, err: Box::new(err) });
+        };
+        Ok(())
+    }
+
     /// Retrieves the current edge based on the given program counter.
     ///
     /// # Arguments
@@ -1841,10 +2048,13 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
 
                 // Either run as a builtin (if it is defined as one) or else run the call
                 if sig.name == BuiltinFunctions::Print.name() {
-                    // We have one variable that is a string; so print it
+                    // We have one variable that is a string; so print it, tagged with where and when we did
                     let text: String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let info: PrintInfo = self.print_info(pc);
                     if let Err(err) = prof
-                        .nest_fut(format!("{}::stdout(false)", type_name::<P>()), |scope| P::stdout(&self.global, &self.local, &text, false, scope))
+                        .nest_fut(format!("{}::stdout(false)", type_name::<P>()), |scope| {
+                            P::stdout(&self.global, &self.local, &info, &text, false, scope)
+                        })
                         .await
                     {
                         return EdgeResult::Err(Error::Custom { pc, err: Box::new(err) });
@@ -1853,10 +2063,13 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                     // Done, go to the next immediately
                     pc.jump(*next)
                 } else if sig.name == BuiltinFunctions::PrintLn.name() {
-                    // We have one variable that is a string; so print it
+                    // We have one variable that is a string; so print it, tagged with where and when we did
                     let text: String = self.stack.pop().unwrap().try_as_string().unwrap();
+                    let info: PrintInfo = self.print_info(pc);
                     if let Err(err) = prof
-                        .nest_fut(format!("{}::stdout(true)", type_name::<P>()), |scope| P::stdout(&self.global, &self.local, &text, true, scope))
+                        .nest_fut(format!("{}::stdout(true)", type_name::<P>()), |scope| {
+                            P::stdout(&self.global, &self.local, &info, &text, true, scope)
+                        })
                         .await
                     {
                         return EdgeResult::Err(Error::Custom { pc, err: Box::new(err) });
@@ -1880,30 +2093,56 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                     let res_name: String = self.stack.pop().unwrap().try_as_intermediate_result().unwrap();
                     let data_name: String = self.stack.pop().unwrap().try_as_string().unwrap();
 
-                    // Try to find out where this res lives, currently
-                    let loc: &String = match self.fstack.table().results.get(&res_name) {
-                        Some(loc) => loc,
-                        None => {
-                            return EdgeResult::Err(Error::UnknownResult { pc, name: res_name });
-                        },
-                    };
-
-                    // Call the external data committer
-                    let res_path: PathBuf = res_name.as_str().into();
-                    if let Err(err) = prof
-                        .nest_fut(format!("{}::commit()", type_name::<P>()), |scope| {
-                            P::commit(&self.global, &self.local, loc, &res_name, &res_path, &data_name, scope)
-                        })
-                        .await
-                    {
-                        return EdgeResult::Err(Error::Custom { pc, err: Box::new(err) });
-                    };
+                    // Promote it right away
+                    if let Err(err) = self.commit::<P>(pc, &res_name, &data_name, &prof).await {
+                        return EdgeResult::Err(err);
+                    }
 
                     // Push the resulting data onto the stack
                     if let Err(err) = self.stack.push(Value::Data { name: data_name }) {
                         return EdgeResult::Err(Error::StackError { pc, instr: None, err });
                     }
 
+                    // We can then go to the next one immediately
+                    pc.jump(*next)
+                } else if sig.name == BuiltinFunctions::KeepResult.name() {
+                    // Fetch the arguments
+                    let res_name: String = self.stack.pop().unwrap().try_as_intermediate_result().unwrap();
+                    let data_name: String = self.stack.pop().unwrap().try_as_string().unwrap();
+
+                    // Note it down for when the snippet is done (the last result kept under a name wins)
+                    let mut kept = self.kept.lock().unwrap();
+                    kept.retain(|(_, name)| name != &data_name);
+                    kept.push((res_name, data_name));
+
+                    // We can then go to the next one immediately
+                    pc.jump(*next)
+                } else if let Some(func) = self.external::<P>(&sig.name) {
+                    // Pop the arguments (in reverse order) and call the plugin's function with them
+                    let mut args: Vec<FullValue> = Vec::with_capacity(sig.args.len());
+                    for _ in 0..sig.args.len() {
+                        args.push(self.stack.pop().unwrap().into_full(self.fstack.table()));
+                    }
+                    args.reverse();
+                    let name: String = sig.name.clone();
+                    let ret_type: DataType = sig.ret.clone();
+                    let value: FullValue = match prof.nest_fut(format!("external {name}()"), |_| func.call(args)).await {
+                        Ok(value) => value,
+                        Err(err) => return EdgeResult::Err(Error::Custom { pc, err }),
+                    };
+
+                    // Push the result back onto the stack (if any)
+                    let value: Value = value.into_value(self.fstack.table());
+                    let value_type: DataType = value.data_type(self.fstack.table());
+                    if !value_type.allowed_by(&ret_type) {
+                        return EdgeResult::Err(Error::ReturnTypeError { pc, got: value_type, expected: ret_type });
+                    }
+                    if !ret_type.is_void() {
+                        if let Err(err) = self.stack.push(value) {
+                            return EdgeResult::Err(Error::StackError { pc, instr: None, err });
+                        }
+                    }
+
                     // We can then go to the next one immediately
                     pc.jump(*next)
                 } else {
@@ -2023,6 +2262,11 @@ impl<G: CustomGlobalState, L: CustomLocalState> Thread<G, L> {
                     // Either quit or continue, noting down the time taken
                     // Return not just the value, but also the VmState part of this thread to keep.
                     EdgeResult::Ok(value) => {
+                        // Promote the results the snippet asked to keep, now that it is done
+                        let kept: Vec<(String, String)> = std::mem::take(&mut *self.kept.lock().unwrap());
+                        for (res_name, data_name) in kept {
+                            self.commit::<P>(self.pc, &res_name, &data_name, &prof).await?;
+                        }
                         return Ok((value, self.into_state()));
                     },
                     EdgeResult::Pending(next) => next,
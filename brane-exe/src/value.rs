@@ -4,7 +4,7 @@
 //  Created:
 //    20 Sep 2022, 13:44:07
 //  Last edited:
-//    16 Jan 2024, 15:13:18
+//    15 Oct 2026, 22:06:56
 //  Auto updated?
 //    Yes
 //
@@ -787,6 +787,26 @@ impl Value {
         }
     }
 
+    /// Estimates the number of bytes that this Value occupies in memory, including any nested values.
+    ///
+    /// This is used to enforce the [`MemoryBudget`](crate::budget::MemoryBudget) of a workflow. It does not account for allocator overhead or spare
+    /// capacity.
+    ///
+    /// # Returns
+    /// The estimated size of this Value, in bytes.
+    pub fn size(&self) -> usize {
+        use Value::*;
+        let heap: usize = match self {
+            Boolean { .. } | Integer { .. } | Real { .. } | Function { .. } | Void => 0,
+            String { value } => value.len(),
+
+            Array { values } => values.iter().map(Self::size).sum(),
+            Instance { values, .. } | Method { values, .. } => values.iter().map(|(name, value)| name.len() + value.size()).sum(),
+            Data { name } | IntermediateResult { name } => name.len(),
+        };
+        std::mem::size_of::<Self>() + heap
+    }
+
     /// Allows the Value to be displayed with resolved definitions.
     ///
     /// # Arguments
@@ -4,7 +4,7 @@
 //  Created:
 //    28 Nov 2022, 16:21:40
 //  Last edited:
-//    31 Jan 2024, 12:07:18
+//    15 Oct 2026, 18:26:27
 //  Auto updated?
 //    Yes
 //
@@ -15,4 +15,6 @@
 
 // Declare modules
 pub mod errors;
+pub mod metrics;
+pub mod prefetch;
 pub mod worker;
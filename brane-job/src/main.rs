@@ -4,7 +4,7 @@
 //  Created:
 //    18 Oct 2022, 13:47:17
 //  Last edited:
-//    14 Jun 2024, 15:14:12
+//    16 Oct 2026, 02:25:27
 //  Auto updated?
 //    Yes
 //
@@ -12,18 +12,24 @@
 //!   Entrypoint to the `brane-job` service.
 //
 
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use brane_cfg::info::Info as _;
-use brane_cfg::node::{NodeConfig, WorkerConfig};
+use brane_cfg::info::{Info as _, InfoWatcher};
+use brane_cfg::node::{EventsConfig, NodeConfig, WorkerConfig};
+use brane_job::metrics::{self, DEFAULT_METRICS_ADDRESS};
 use brane_job::worker::WorkerServer;
 use brane_prx::client::ProxyClient;
+use brane_shr::chaos::{self, ChaosConfig};
+use brane_shr::events::EventPublisher;
+use brane_shr::logging::{self, LogFormat};
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::trace;
 use log::{debug, error, info, warn, LevelFilter};
+use specifications::reflection;
 use specifications::working::JobServiceServer;
 use tokio::signal::unix::{signal, Signal, SignalKind};
 use tonic::transport::Server;
@@ -39,6 +45,15 @@ struct Opts {
     /// Whether to keep containers after execution or not.
     #[clap(long, action, help = "If given, will not remove job containers after removing them.", env = "KEEP_CONTAINERS")]
     keep_containers: bool,
+    /// Whether to transfer datasets ahead of time or not (but inverted).
+    #[clap(
+        long,
+        action,
+        help = "If given, will not start transferring the datasets of planned tasks as soon as the plan arrives, but only right before each task \
+                is executed.",
+        env = "DISABLE_PREFETCH"
+    )]
+    disable_prefetch: bool,
 
     /// Node environment metadata store.
     #[clap(
@@ -50,6 +65,32 @@ struct Opts {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+    /// The address on which to serve Prometheus metrics.
+    #[clap(
+        long,
+        default_value = DEFAULT_METRICS_ADDRESS,
+        help = "The address on which to serve Prometheus metrics (at '/metrics').",
+        env = "METRICS_ADDRESS"
+    )]
+    metrics_address: SocketAddr,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
+
+    /// The faults to inject in chaos mode, if any.
+    #[clap(
+        long,
+        help = "If given, injects faults to test resilience, e.g., 'latency=250ms@0.5,drop=0.1,fail=0.05,seed=42' (see \
+                `brane_shr::chaos::ChaosConfig`). Only has an effect in debug builds.",
+        env = "BRANE_CHAOS"
+    )]
+    chaos: Option<ChaosConfig>,
 }
 
 
@@ -63,15 +104,13 @@ async fn main() {
     let opts = Opts::parse();
 
     // Configure logger.
-    let mut logger = env_logger::builder();
-    logger.format_module_path(false);
-
-    if opts.debug {
-        logger.filter_level(LevelFilter::Debug).init();
-    } else {
-        logger.filter_level(LevelFilter::Info).init();
+    if let Err(err) = logging::init("brane-job", opts.log_format, if opts.debug { LevelFilter::Debug } else { LevelFilter::Info }) {
+        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
     }
     info!("Initializing brane-job v{}...", env!("CARGO_PKG_VERSION"));
+    if let Some(chaos) = opts.chaos {
+        chaos::init("brane-job", chaos);
+    }
 
     // Load the config, making sure it's a worker config
     debug!("Loading node.yml file '{}'...", opts.node_config_path.display());
@@ -82,6 +121,11 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    if let Some(tracing) = &node_config.tracing {
+        tracing.init("brane-job");
+    }
+    let tracing: Option<String> = node_config.tracing.as_ref().map(|tracing| tracing.endpoint.clone());
+    let events: Option<EventsConfig> = node_config.events.clone();
     let worker: WorkerConfig = match node_config.node.try_into_worker() {
         Some(worker) => worker,
         None => {
@@ -90,13 +134,63 @@ async fn main() {
         },
     };
 
+    let events: EventPublisher = match &events {
+        Some(events) => match events.publisher(format!("brane-job@{}", worker.name)).await {
+            Ok(events) => events,
+            Err(err) => {
+                error!("{}", trace!(("Failed to connect to event bus"), err));
+                std::process::exit(1);
+            },
+        },
+        None => EventPublisher::disabled(),
+    };
+
+    // Watch the node.yml for changes. The server reads the most recent version on every request, but we'd like to tell the user if they changed something that requires a restart.
+    let node_config: InfoWatcher<NodeConfig> = match NodeConfig::watch(opts.node_config_path.clone(), Duration::from_secs(5)).await {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            error!("{}", trace!(("Failed to watch NodeConfig file"), err));
+            std::process::exit(1);
+        },
+    };
+    let bind: std::net::SocketAddr = worker.services.job.bind;
+    let max_tasks: Option<usize> = worker.max_concurrent_tasks;
+    let mut checker: String = worker.services.chk.address.to_string();
+    let mut watcher: InfoWatcher<NodeConfig> = node_config.clone();
+    tokio::spawn(async move {
+        while let Some(config) = watcher.changed().await {
+            info!("Reloaded node config file '{}'", watcher.path().display());
+            if let Some(chk) = config.node.try_worker().map(|w| w.services.chk.address.to_string()) {
+                if chk != checker {
+                    info!("Consulting policy checker '{chk}' from now on");
+                    checker = chk;
+                }
+            }
+            if config.node.try_worker().map(|w| w.services.job.bind) != Some(bind) {
+                warn!("The bind address of this service has changed in '{}'; restart the service to apply it", watcher.path().display());
+            }
+            if config.node.try_worker().map(|w| w.max_concurrent_tasks) != Some(max_tasks) {
+                warn!("The maximum number of concurrent tasks has changed in '{}'; restart the service to apply it", watcher.path().display());
+            }
+            if config.tracing.as_ref().map(|tracing| &tracing.endpoint) != tracing.as_ref() {
+                warn!("The tracing endpoint has changed in '{}'; restart the service to apply it", watcher.path().display());
+            }
+        }
+    });
+
     // Initialize the Xenon thingy
     // debug!("Initializing Xenon...");
     // let xenon_schedulers = Arc::new(DashMap::<String, Arc<RwLock<Scheduler>>>::new());
     // let xenon_endpoint = utilities::ensure_http_schema(&opts.xenon, !opts.debug)?;
 
     // Start the JobHandler
-    let server = match WorkerServer::new(opts.node_config_path, opts.keep_containers, Arc::new(ProxyClient::new(worker.services.prx.address()))) {
+    let server = match WorkerServer::new(
+        node_config,
+        opts.keep_containers,
+        !opts.disable_prefetch,
+        Arc::new(ProxyClient::new(worker.services.prx.address())),
+        events,
+    ) {
         Ok(svr) => svr,
         Err(err) => {
             error!("{}", trace!(("Failed to create WorkerServer"), err));
@@ -104,11 +198,37 @@ async fn main() {
         },
     };
 
+    // Serve the metrics alongside the gRPC server
+    let metrics_address: SocketAddr = opts.metrics_address;
+    tokio::spawn(async move {
+        if let Err(err) = metrics::serve(metrics_address).await {
+            error!("{}", trace!(("Failed to serve metrics on '{metrics_address}'"), err));
+            warn!("Service will NOT expose any metrics");
+        }
+    });
+
+    // Prepare the standard health and reflection services alongside it, for probes and tools like `grpcurl`
+    let (mut health, health_service) = tonic_health::server::health_reporter();
+    health.set_serving::<JobServiceServer<WorkerServer>>().await;
+    let reflection_service = match tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .register_file_descriptor_set(reflection::descriptor_set())
+        .build()
+    {
+        Ok(service) => service,
+        Err(err) => {
+            error!("{}", trace!(("Failed to build gRPC reflection service"), err));
+            std::process::exit(1);
+        },
+    };
+
     // Start gRPC server with callback service.
     debug!("gRPC server ready to serve on '{}'", worker.services.job.bind);
     if let Err(err) = Server::builder()
         .add_service(JobServiceServer::new(server))
-        .serve_with_shutdown(worker.services.job.bind, async {
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .serve_with_shutdown(worker.services.job.bind, async move {
             // Register a SIGTERM handler to be Docker-friendly
             let mut handler: Signal = match signal(SignalKind::terminate()) {
                 Ok(handler) => handler,
@@ -124,6 +244,7 @@ async fn main() {
             // Wait until we receive such a signal after which we terminate the server
             handler.recv().await;
             info!("Received SIGTERM, shutting down gracefully...");
+            health.set_not_serving::<JobServiceServer<WorkerServer>>().await;
         })
         .await
     {
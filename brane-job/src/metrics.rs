@@ -0,0 +1,122 @@
+//  METRICS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 18:32:05
+//  Last edited:
+//    16 Oct 2026, 00:37:16
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the Prometheus metrics exposed by the `brane-job` service,
+//!   together with the HTTP endpoint that serves them.
+//
+
+use std::net::SocketAddr;
+
+use lazy_static::lazy_static;
+use log::{debug, error};
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge, Encoder as _, Histogram, HistogramVec, IntCounterVec,
+    IntGauge, TextEncoder,
+};
+use warp::http::header::CONTENT_TYPE;
+use warp::reply::Response;
+use warp::Filter as _;
+
+
+/***** CONSTANTS *****/
+/// The default address on which the metrics are served.
+pub const DEFAULT_METRICS_ADDRESS: &str = "0.0.0.0:9102";
+
+/// The histogram buckets (in seconds) used for task execution durations, which may take anywhere from seconds to hours.
+const TASK_DURATION_BUCKETS: &[f64] = &[1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 1800.0, 3600.0, 7200.0, 21600.0];
+
+
+
+
+
+/***** METRICS *****/
+lazy_static! {
+    /// The number of tasks that have reached a particular status (e.g., `received`, `created`, `finished`, `failed`).
+    pub static ref TASKS: IntCounterVec =
+        register_int_counter_vec!("brane_job_tasks_total", "The number of tasks that have reached a particular status.", &["status"]).unwrap();
+    /// The number of tasks that are waiting for a free task slot.
+    pub static ref TASKS_QUEUED: IntGauge =
+        register_int_gauge!("brane_job_tasks_queued", "The number of tasks that are waiting for a free task slot.").unwrap();
+    /// The number of tasks that are currently being executed.
+    pub static ref TASKS_RUNNING: IntGauge =
+        register_int_gauge!("brane_job_tasks_running", "The number of tasks that are currently being executed.").unwrap();
+    /// How long it takes to execute a task, from acquiring its slot until it has completed.
+    pub static ref TASK_DURATION: Histogram = register_histogram!(
+        "brane_job_task_duration_seconds",
+        "How long it takes to execute a task, including downloading its container and authorizing it.",
+        TASK_DURATION_BUCKETS.to_vec()
+    )
+    .unwrap();
+    /// How long it takes to create and start a task container.
+    pub static ref CONTAINER_START: Histogram =
+        register_histogram!("brane_job_container_start_seconds", "How long it takes to create and start a task container.").unwrap();
+    /// The number of bytes of datasets (`data`) and intermediate results (`result`) transferred to this worker.
+    pub static ref TRANSFER_BYTES: IntCounterVec = register_int_counter_vec!(
+        "brane_job_transfer_bytes_total",
+        "The number of bytes of datasets and intermediate results transferred to this worker.",
+        &["kind"]
+    )
+    .unwrap();
+    /// The number of times that a transfer of a dataset (`data`) or intermediate result (`result`) to this worker was retried.
+    pub static ref TRANSFER_RETRIES: IntCounterVec = register_int_counter_vec!(
+        "brane_job_transfer_retries_total",
+        "The number of times that a transfer of a dataset or intermediate result to this worker was retried.",
+        &["kind"]
+    )
+    .unwrap();
+    /// How long it takes to have the checker validate a workflow (`workflow`), a task (`task`) or a task right before execution (`execute`).
+    pub static ref POLICY_CHECK: HistogramVec = register_histogram_vec!(
+        "brane_job_policy_check_seconds",
+        "How long it takes to have the checker validate a workflow or task.",
+        &["kind"]
+    )
+    .unwrap();
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Renders all registered metrics in the Prometheus text exposition format.
+///
+/// # Returns
+/// A [`Response`] that carries the rendered metrics.
+pub fn render() -> Response {
+    let encoder: TextEncoder = TextEncoder::new();
+    let mut buffer: Vec<u8> = Vec::new();
+    if let Err(err) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("Failed to encode metrics: {err}");
+        let mut res: Response = Response::new(Vec::new().into());
+        *res.status_mut() = warp::http::StatusCode::INTERNAL_SERVER_ERROR;
+        return res;
+    }
+
+    // Return it with the proper content type
+    let mut res: Response = Response::new(buffer.into());
+    res.headers_mut().insert(CONTENT_TYPE, encoder.format_type().parse().unwrap());
+    res
+}
+
+/// Serves the metrics on the given address at `/metrics`.
+///
+/// # Arguments
+/// - `address`: The address to serve the metrics on.
+///
+/// # Errors
+/// This function errors if we failed to bind to the given address.
+pub async fn serve(address: SocketAddr) -> Result<(), warp::Error> {
+    let metrics = warp::get().and(warp::path("metrics")).and(warp::path::end()).map(render);
+    let (address, server) = warp::serve(metrics).try_bind_ephemeral(address)?;
+    debug!("Serving metrics on 'http://{address}/metrics'");
+    server.await;
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//  PREFETCH.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 17:40:12
+//  Last edited:
+//    15 Oct 2026, 21:24:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements prefetching of the datasets that tasks planned on this
+//!   worker need. Instead of transferring them one-by-one right before a
+//!   task is launched, all of them are transferred in parallel as soon as
+//!   the plan arrives, such that they overlap with the execution of
+//!   earlier tasks.
+//
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{Debug, Formatter, Result as FResult};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use brane_ast::ast::Edge;
+use brane_ast::func_id::FunctionId;
+use brane_ast::locations::Location;
+use brane_ast::Workflow;
+use brane_cfg::node::WorkerConfig;
+use brane_exe::pc::ProgramCounter;
+use brane_prx::client::ProxyClient;
+use brane_tsk::caches::DomainRegistryCache;
+use enum_debug::EnumDebug as _;
+use futures_util::future::{BoxFuture, FutureExt as _, Shared};
+use log::{debug, warn};
+use specifications::data::{AccessKind, AvailabilityKind, DataName, PreprocessKind};
+use specifications::profiling::ProfileReport;
+use specifications::telemetry::TraceContext;
+
+use crate::worker::preprocess_transfer_tar;
+
+
+/***** CONSTANTS *****/
+/// The default time (in seconds) after which prefetched datasets are forgotten about.
+pub const DEFAULT_PREFETCH_TIMEOUT: u64 = 3600;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Collects all dataset transfers that are planned for tasks on the given location.
+///
+/// Intermediate results are ignored, as these typically do not exist yet by the time the plan arrives.
+///
+/// # Arguments
+/// - `location`: The location for which to collect the transfers (i.e., our location).
+/// - `workflow`: The planned [`Workflow`] to collect the transfers from.
+///
+/// # Returns
+/// A list of the program counter of the first task that needs a dataset, the location to download it from and the name of the dataset.
+fn collect_transfers(location: &str, workflow: &Workflow) -> Vec<(ProgramCounter, Location, DataName)> {
+    let mut seen: HashSet<(Location, DataName)> = HashSet::new();
+    let mut transfers: Vec<(ProgramCounter, Location, DataName)> = vec![];
+    let funcs = std::iter::once((FunctionId::Main, workflow.graph.as_slice()))
+        .chain(workflow.funcs.iter().map(|(id, edges)| (FunctionId::Func(*id), edges.as_slice())));
+    for (func_id, edges) in funcs {
+        for (i, edge) in edges.iter().enumerate() {
+            // Only consider tasks planned on us
+            let input: &HashMap<DataName, Option<AvailabilityKind>> = match edge {
+                Edge::Node { at: Some(at), input, .. } if at == location => input,
+                _ => continue,
+            };
+
+            // Collect any datasets that still have to be transferred
+            for avail in input.values() {
+                if let Some(AvailabilityKind::Unavailable { how: PreprocessKind::TransferRegistryTar { location, dataname } }) = avail {
+                    if dataname.is_data() && seen.insert((location.clone(), dataname.clone())) {
+                        transfers.push((ProgramCounter::new(func_id, i), location.clone(), dataname.clone()));
+                    }
+                }
+            }
+        }
+    }
+    transfers
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The result of a prefetch as it may be awaited by multiple preprocess requests.
+pub type PrefetchResult = Result<AccessKind, Arc<dyn 'static + Send + Sync + Error>>;
+
+/// Identifies a single prefetched dataset.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct PrefetchKey {
+    /// The use-case (registry) for which the dataset was fetched.
+    use_case: String,
+    /// The identifier of the workflow for which the dataset was fetched.
+    workflow: String,
+    /// The location from which the dataset was fetched.
+    location: Location,
+    /// The name of the dataset.
+    dataname: DataName,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Keeps track of datasets that are being (or have been) transferred ahead of the tasks that need them.
+pub struct Prefetcher {
+    /// The timeout (in seconds) after which prefetched datasets are forgotten about.
+    timeout:   u64,
+    /// The transfers that are running or have completed, together with when they were started.
+    transfers: Mutex<HashMap<PrefetchKey, (Shared<BoxFuture<'static, PrefetchResult>>, Instant)>>,
+}
+impl Default for Prefetcher {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+impl Prefetcher {
+    /// Constructor for the Prefetcher that uses the default timeout.
+    ///
+    /// See [`DEFAULT_PREFETCH_TIMEOUT`] to find what the current default is.
+    ///
+    /// # Returns
+    /// A new Prefetcher instance that doesn't know of any transfers yet.
+    #[inline]
+    pub fn new() -> Self { Self::with_timeout(DEFAULT_PREFETCH_TIMEOUT) }
+
+    /// Constructor for the Prefetcher.
+    ///
+    /// # Arguments
+    /// - `timeout`: A timeout (in seconds) that determines after how long prefetched datasets are forgotten about.
+    ///
+    /// # Returns
+    /// A new Prefetcher instance that doesn't know of any transfers yet.
+    #[inline]
+    pub fn with_timeout(timeout: u64) -> Self { Self { timeout, transfers: Mutex::new(HashMap::with_capacity(16)) } }
+
+    /// Starts transferring all datasets needed by tasks planned on this worker in the given workflow.
+    ///
+    /// All transfers run in parallel in the background. Datasets that are already being prefetched for this workflow are not transferred again.
+    ///
+    /// # Arguments
+    /// - `worker_cfg`: The configuration for this node's environment.
+    /// - `registries`: The caches that resolve location IDs to registry addresses, mapped by use-case.
+    /// - `proxy`: The proxy client we use to proxy the data transfers.
+    /// - `use_case`: A string denoting which use-case (registry) we're using.
+    /// - `workflow`: The planned [`Workflow`] to prefetch the datasets of.
+    ///
+    /// # Returns
+    /// The number of transfers that were started.
+    pub fn prefetch(
+        &self,
+        worker_cfg: &WorkerConfig,
+        registries: &Arc<HashMap<String, DomainRegistryCache>>,
+        proxy: &Arc<ProxyClient>,
+        use_case: &str,
+        workflow: &Workflow,
+    ) -> usize {
+        // We can only prefetch for use-cases that we know
+        if !registries.contains_key(use_case) {
+            debug!("Not prefetching for unknown use-case '{use_case}'");
+            return 0;
+        }
+
+        // Throw away stale transfers first
+        let mut transfers = self.transfers.lock().unwrap();
+        let timeout: u64 = self.timeout;
+        transfers.retain(|_, (_, started)| started.elapsed().as_secs() < timeout);
+
+        // Start a background transfer for every dataset we don't know yet
+        let mut started: usize = 0;
+        for (pc, location, dataname) in collect_transfers(&worker_cfg.name, workflow) {
+            let key: PrefetchKey =
+                PrefetchKey { use_case: use_case.into(), workflow: workflow.id.clone(), location: location.clone(), dataname: dataname.clone() };
+            if transfers.contains_key(&key) {
+                continue;
+            }
+            debug!("Prefetching {} '{}' from '{}' for task {}...", dataname.variant(), dataname.name(), location, pc);
+
+            // Spawn the transfer such that it starts immediately, regardless of whether anyone awaits it
+            let handle = tokio::spawn({
+                let worker_cfg: WorkerConfig = worker_cfg.clone();
+                let registries: Arc<HashMap<String, DomainRegistryCache>> = registries.clone();
+                let proxy: Arc<ProxyClient> = proxy.clone();
+                let use_case: String = use_case.into();
+                let workflow: Workflow = workflow.clone();
+                async move {
+                    let report = ProfileReport::auto_reporting_file("brane-job prefetch", format!("brane-job_{}_prefetch", worker_cfg.name));
+                    let _total = report.time("Total");
+                    let registry: &DomainRegistryCache = &registries[&use_case];
+                    let trace: TraceContext = TraceContext::for_workflow(&workflow.id);
+                    report
+                        .nest_fut("TransferTar prefetching", |scope| {
+                            preprocess_transfer_tar(registry, &worker_cfg, proxy, &use_case, Some(pc), workflow, location, dataname, &trace, scope)
+                        })
+                        .await
+                }
+            });
+            let fut: BoxFuture<'static, PrefetchResult> = async move {
+                match handle.await {
+                    Ok(Ok(access)) => Ok(access),
+                    Ok(Err(err)) => Err(Arc::new(err) as Arc<dyn 'static + Send + Sync + Error>),
+                    Err(err) => Err(Arc::new(err) as Arc<dyn 'static + Send + Sync + Error>),
+                }
+            }
+            .boxed();
+            transfers.insert(key, (fut.shared(), Instant::now()));
+            started += 1;
+        }
+        started
+    }
+
+    /// Claims the result of a prefetched dataset, waiting for its transfer to complete if it is still running.
+    ///
+    /// A failed transfer is forgotten about, such that the caller may simply retry it.
+    ///
+    /// # Arguments
+    /// - `use_case`: A string denoting which use-case (registry) we're using.
+    /// - `workflow_id`: The identifier of the workflow that needs the dataset.
+    /// - `location`: The location where the dataset is downloaded from.
+    /// - `dataname`: The name of the dataset.
+    ///
+    /// # Returns
+    /// The result of the transfer, or [`None`] if the dataset wasn't prefetched.
+    pub async fn claim(&self, use_case: &str, workflow_id: &str, location: &Location, dataname: &DataName) -> Option<PrefetchResult> {
+        let key: PrefetchKey =
+            PrefetchKey { use_case: use_case.into(), workflow: workflow_id.into(), location: location.clone(), dataname: dataname.clone() };
+        let fut: Shared<BoxFuture<'static, PrefetchResult>> = self.transfers.lock().unwrap().get(&key)?.0.clone();
+
+        // Wait for it to complete
+        let res: PrefetchResult = fut.await;
+        if let Err(err) = &res {
+            warn!("Failed to prefetch {} '{}' from '{}': {}", dataname.variant(), dataname.name(), location, err);
+            self.transfers.lock().unwrap().remove(&key);
+        }
+        Some(res)
+    }
+}
+impl Debug for Prefetcher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        f.debug_struct("Prefetcher").field("timeout", &self.timeout).field("transfers", &self.transfers.lock().unwrap().len()).finish()
+    }
+}
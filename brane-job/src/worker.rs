@@ -4,7 +4,7 @@
 //  Created:
 //    31 Oct 2022, 11:21:14
 //  Last edited:
-//    01 May 2024, 10:39:39
+//    16 Oct 2026, 02:42:53
 //  Auto updated?
 //    Yes
 //
@@ -20,28 +20,32 @@ use std::ffi::OsStr;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::{Path, PathBuf};
 use std::str::FromStr as _;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine as _;
 use bollard::API_DEFAULT_VERSION;
-use brane_ast::ast::{ComputeTaskDef, TaskDef};
+use brane_ast::ast::{ComputeTaskDef, Edge, TaskDef};
 use brane_ast::func_id::FunctionId;
 use brane_ast::locations::Location;
 use brane_ast::Workflow;
-use brane_cfg::backend::{BackendFile, Credentials};
-use brane_cfg::info::Info as _;
-use brane_cfg::node::{NodeConfig, NodeSpecificConfig, WorkerConfig};
+use brane_cfg::approvals::ResultProvenance;
+use brane_cfg::backend::{BackendFile, BackendRoute, Credentials, SandboxConfig};
+use brane_cfg::info::{Info as _, InfoWatcher};
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, TransferRetryConfig, WorkerConfig};
 use brane_exe::pc::ProgramCounter;
 use brane_exe::FullValue;
 use brane_prx::client::ProxyClient;
 use brane_prx::spec::NewPathRequestTlsOptions;
+use brane_shr::events::EventPublisher;
 use brane_shr::formatters::BlockFormatter;
-use brane_shr::fs::{copy_dir_recursively_async, unarchive_async};
-use brane_tsk::caches::DomainRegistryCache;
+use brane_shr::fs::{copy_dir_recursively_async, deduplicate_dir_async, unarchive_async};
+use brane_shr::{chaos, oci};
+use brane_tsk::caches::{CheckQuestion, DomainRegistryCache, ResultCache, VerdictCache, VerdictKey};
 use brane_tsk::docker::{self, ClientVersion, DockerOptions, ExecuteInfo, ImageSource, Network};
 use brane_tsk::errors::{AuthorizeError, CommitError, ExecuteError, PreprocessError};
+use brane_tsk::pool::{ContainerPool, PooledContainer};
 use brane_tsk::spec::JobStatus;
 use brane_tsk::tools::decode_base64;
 use chrono::Utc;
@@ -54,32 +58,49 @@ use hyper::body::Bytes;
 use hyper::header;
 // use kube::config::Kubeconfig;
 use log::{debug, error, info, warn};
-use reqwest::Method;
+use reqwest::{Method, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json_any_key::json_to_map;
 use specifications::address::Address;
-// use brane_tsk::k8s::{self, K8sOptions};
-use specifications::checking::{DELIBERATION_API_EXECUTE_TASK, DELIBERATION_API_WORKFLOW};
+// use brane_tsk::k8s::{self, BasicAuth, K8sOptions, RegistryAuth};
+use specifications::checking::{ApprovalPending, PolicyDenial, DELIBERATION_API_EXECUTE_TASK, DELIBERATION_API_WORKFLOW};
 use specifications::container::{Image, VolumeBind};
-use specifications::data::{AccessKind, AssetInfo, DataName};
+use specifications::data::{AccessKind, AssetInfo, DataName, Provenance};
+use specifications::errors::ErrorCode;
+use specifications::events::EventKind;
 use specifications::package::{Capability, PackageIndex, PackageInfo, PackageKind};
 use specifications::profiling::{ProfileReport, ProfileScopeHandle};
-use specifications::registering::DownloadAssetRequest;
+use specifications::registering::{ArchiveCompression, DownloadAssetRequest};
+use specifications::reporting::{BraneletReport, ResourceUsage, TaskFailure, LIVENESS_INTERVAL, LIVENESS_TIMEOUT};
+use specifications::telemetry::{self, Span, TraceContext};
 use specifications::version::Version;
 use specifications::working::{
-    CheckReply, CheckTaskRequest, CheckWorkflowRequest, CommitReply, CommitRequest, ExecuteReply, ExecuteRequest, JobService, PreprocessReply,
-    PreprocessRequest, TaskStatus,
+    CheckReply, CheckTaskRequest, CheckWorkflowRequest, CommitReply, CommitRequest, ExecuteReply, ExecuteRequest, JobService, OutputChannel,
+    OutputChunk, PreprocessReply, PreprocessRequest, TaskStatus, TASK_HEARTBEAT_INTERVAL,
 };
+use tempfile::TempDir;
 use tokio::fs as tfs;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::{AcquireError, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Request, Response, Status};
+use tonic::{Code, Request, Response, Status};
+
+use crate::metrics;
+use crate::prefetch::Prefetcher;
 
 
 /***** CONSTANTS *****/
 /// Path to the temporary folder.
 pub const TEMPORARY_DIR: &str = "/tmp";
+/// Name of the (hidden) directory in the results folder where results are stored by their hash. The results themselves link to it.
+pub const RESULT_OBJECTS_DIR: &str = ".objects";
+/// Name of the (hidden) directory in the results folder where the results of cacheable calls are remembered.
+pub const RESULT_CACHE_DIR: &str = ".cache";
+/// Name of the directory in the temporary results folder where the workspaces of pooled containers live.
+pub const POOL_DIR: &str = "pool";
 
 
 
@@ -118,9 +139,10 @@ macro_rules! err {
 async fn update_client(tx: &Sender<Result<ExecuteReply, Status>>, status: JobStatus) -> Result<(), ExecuteError> {
     // Convert the JobStatus into a code and (possible) value
     let (status, value): (TaskStatus, Option<String>) = status.into();
+    metrics::TASKS.with_label_values(&[&format!("{status:?}").to_lowercase()]).inc();
 
     // Put that in an ExecuteReply
-    let reply: ExecuteReply = ExecuteReply { status: status as i32, value };
+    let reply: ExecuteReply = ExecuteReply { status: status as i32, value, output: None, usage: None };
 
     // Send it over the wire
     debug!("Updating client on '{:?}'...", status);
@@ -132,6 +154,270 @@ async fn update_client(tx: &Sender<Result<ExecuteReply, Status>>, status: JobSta
     Ok(())
 }
 
+/// Sends a chunk of task output to the client.
+///
+/// The chunk is sent as a [`TaskStatus::Heartbeat`], such that clients that don't know about output simply ignore it.
+///
+/// # Arguments
+/// - `tx`: The channel to update the client on.
+/// - `channel`: The output channel to which the task wrote the chunk.
+/// - `data`: The chunk of output itself.
+async fn update_client_output(tx: &Sender<Result<ExecuteReply, Status>>, channel: OutputChannel, data: String) {
+    let reply: ExecuteReply = ExecuteReply {
+        status: TaskStatus::Heartbeat as i32,
+        value:  None,
+        output: Some(OutputChunk { channel: channel as i32, data }),
+        usage:  None,
+    };
+    if let Err(err) = tx.send(Ok(reply)).await {
+        // Not fatal; the output is still reported when the task fails
+        warn!("{}", trace!(("Failed to send task output to client"), err));
+    }
+}
+
+/// Sends the resources that a task used to the client.
+///
+/// Like task output, this is sent as a [`TaskStatus::Heartbeat`] such that clients that don't know about it simply ignore it.
+///
+/// # Arguments
+/// - `tx`: The channel to update the client on.
+/// - `usage`: The [`ResourceUsage`] reported by the task's `branelet`.
+async fn update_client_usage(tx: &Sender<Result<ExecuteReply, Status>>, usage: &ResourceUsage) {
+    let reply: ExecuteReply =
+        ExecuteReply { status: TaskStatus::Heartbeat as i32, value: None, output: None, usage: Some(serde_json::to_string(usage).unwrap()) };
+    if let Err(err) = tx.send(Ok(reply)).await {
+        warn!("{}", trace!(("Failed to send task resource usage to client"), err));
+    }
+}
+
+/// Removes any [`BraneletReport`]s from a chunk of a container's stderr, remembering what they told us.
+///
+/// Reports meant for the user (see [`BraneletReport::user_message()`]) are replaced by a human-readable version instead.
+///
+/// # Arguments
+/// - `data`: The chunk of stderr to filter.
+/// - `state`: The [`BraneletState`] to update with the reports in the chunk.
+///
+/// # Returns
+/// The chunk without the lines that were reports.
+fn filter_reports(data: &str, state: &Mutex<BraneletState>) -> String {
+    let mut res: String = String::with_capacity(data.len());
+    for line in data.split_inclusive('\n') {
+        match BraneletReport::from_line(line) {
+            Some(report) => {
+                debug!("Received report from branelet: {report:?}");
+                if let Some(msg) = report.user_message() {
+                    res.push_str(&msg);
+                    res.push('\n');
+                }
+                let mut state = state.lock().unwrap();
+                state.last_report = Some(Instant::now());
+                match report {
+                    BraneletReport::Usage(usage) => state.usage = Some(usage),
+                    BraneletReport::Stopped { signal } => state.signal = Some(signal),
+                    BraneletReport::TimedOut { timeout_s } => state.timed_out = Some(timeout_s),
+                    BraneletReport::Retrying { .. } => {
+                        state.timed_out = None;
+                        state.retries += 1;
+                    },
+                    _ => {},
+                }
+            },
+            None => res.push_str(line),
+        }
+    }
+    res
+}
+
+/// Spawns a background task that periodically sends a [`TaskStatus::Heartbeat`] to the client.
+///
+/// This allows the client to detect that we crashed or became unreachable while a task is queued or running.
+///
+/// # Arguments
+/// - `tx`: The channel to update the client on.
+///
+/// # Returns
+/// A [`JoinHandle`] to the background task, which should be aborted once the task completes. It stops by itself if the client disconnects.
+fn spawn_heartbeat(tx: Sender<Result<ExecuteReply, Status>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval: Interval = tokio::time::interval(TASK_HEARTBEAT_INTERVAL);
+        // The first tick completes immediately
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let reply: ExecuteReply = ExecuteReply { status: TaskStatus::Heartbeat as i32, value: None, output: None, usage: None };
+            if tx.send(Ok(reply)).await.is_err() {
+                debug!("Client disconnected; stopping heartbeats");
+                break;
+            }
+        }
+    })
+}
+
+/// Decides whether a failed attempt at downloading a tarball is worth retrying.
+///
+/// # Arguments
+/// - `err`: The [`PreprocessError`] that the attempt failed with.
+///
+/// # Returns
+/// True if the error is (likely) caused by the network or an overloaded registry, or false if retrying would just fail again (e.g., because the registry denied the transfer).
+fn is_transient_transfer_error(err: &PreprocessError) -> bool {
+    match err {
+        PreprocessError::ProxyError { .. } | PreprocessError::DownloadRequestError { .. } | PreprocessError::DownloadStreamError { .. } => true,
+        PreprocessError::DownloadRequestFailure { code, .. } => {
+            code.is_server_error() || *code == StatusCode::REQUEST_TIMEOUT || *code == StatusCode::TOO_MANY_REQUESTS
+        },
+        _ => false,
+    }
+}
+
+/// Serializes an argument of a call for the fingerprint of its result (see [`ResultProvenance::fingerprint()`]).
+///
+/// References to intermediate results are replaced by their lineage, as their names are different every time the workflow is compiled.
+///
+/// # Arguments
+/// - `value`: The value of the argument.
+/// - `workflow`: The [`Workflow`] that the call is part of.
+///
+/// # Returns
+/// The serialized argument.
+fn fingerprint_arg(value: &FullValue, workflow: &Workflow) -> String {
+    fn canonicalize(value: &FullValue, workflow: &Workflow) -> FullValue {
+        match value {
+            FullValue::Array(values) => FullValue::Array(values.iter().map(|value| canonicalize(value, workflow)).collect()),
+            FullValue::Instance(name, fields) => {
+                FullValue::Instance(name.clone(), fields.iter().map(|(field, value)| (field.clone(), canonicalize(value, workflow))).collect())
+            },
+            FullValue::IntermediateResult(name) => {
+                FullValue::IntermediateResult(workflow.lineage(name.as_ref()).unwrap_or_else(|| format!("result:{name}")).into())
+            },
+            value => value.clone(),
+        }
+    }
+
+    // NOTE: Going through `serde_json::Value` sorts the fields of instances
+    serde_json::to_value(canonicalize(value, workflow)).map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Collects the sensitivity tags of the given datasets of this domain.
+///
+/// # Arguments
+/// - `data_path`: The directory with the datasets of this domain.
+/// - `names`: The names of the datasets to collect the tags of. Names of datasets that this domain does not have are ignored.
+///
+/// # Returns
+/// The tags, or [`None`] if we failed to read (any of) the datasets.
+fn local_dataset_tags(data_path: &Path, names: &HashSet<&str>) -> Option<HashSet<String>> {
+    let mut tags: HashSet<String> = HashSet::new();
+    if names.is_empty() {
+        return Some(tags);
+    }
+    let entries: std::fs::ReadDir = match std::fs::read_dir(data_path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!("Failed to read data directory '{}': {}", data_path.display(), err);
+            return None;
+        },
+    };
+    for entry in entries {
+        let info_path: PathBuf = match entry {
+            Ok(entry) => entry.path().join("data.yml"),
+            Err(err) => {
+                warn!("Failed to read entry in data directory '{}': {}", data_path.display(), err);
+                return None;
+            },
+        };
+        if !info_path.is_file() {
+            continue;
+        }
+        match AssetInfo::from_path(&info_path) {
+            Ok(info) if names.contains(info.name.as_str()) => tags.extend(info.all_tags().into_iter().map(String::from)),
+            Ok(_) => {},
+            Err(err) => {
+                warn!("{}", trace!(("Failed to read dataset info '{}'", info_path.display()), err));
+                return None;
+            },
+        }
+    }
+    Some(tags)
+}
+
+/// Describes how the given call computes its intermediate result, such that the registry can decide whether it may leave this domain.
+///
+/// The result inherits the tags of the datasets of this domain that the call reads, and those recorded for the intermediate results of this
+/// domain that it reads. Inputs of other domains are unknown to us, and thus contribute no tags.
+///
+/// # Arguments
+/// - `worker_cfg`: The configuration for this node's environment. For us, contains where to find data & intermediate results.
+/// - `workflow`: The [`Workflow`] that the call is part of.
+/// - `tinfo`: The [`TaskInfo`] describing the call.
+/// - `result`: The name of the intermediate result that the call produces.
+///
+/// # Returns
+/// The [`ResultProvenance`] of the result, or [`None`] if we could not find out what it inherits (in which case none should be recorded, such
+/// that the registry considers it sensitive).
+fn result_provenance(worker_cfg: &WorkerConfig, workflow: &Workflow, tinfo: &TaskInfo, result: &str) -> Option<ResultProvenance> {
+    let lineage: String = workflow.lineage(result).unwrap_or_else(|| format!("result:{result}"));
+
+    // Collect what the inputs contribute
+    let datasets: HashSet<&str> =
+        tinfo.input.keys().filter_map(|name| if let DataName::Data(name) = name { Some(name.as_str()) } else { None }).collect();
+    let mut tags: HashSet<String> = local_dataset_tags(&worker_cfg.paths.data, &datasets)?;
+    let mut inputs: Vec<String> = vec![];
+    for name in tinfo.input.keys() {
+        let DataName::IntermediateResult(name) = name else { continue };
+        match ResultProvenance::load(&worker_cfg.paths.results, name) {
+            Ok(Some(provenance)) => {
+                tags.extend(provenance.tags);
+                inputs.push(provenance.fingerprint);
+            },
+            Ok(None) if !worker_cfg.paths.results.join(name).exists() => {
+                inputs.push(workflow.lineage(name).unwrap_or_else(|| format!("result:{name}")));
+            },
+            Ok(None) => {
+                warn!("Local intermediate result '{}' has no recorded provenance; cannot tell what '{}' inherits from it", name, result);
+                return None;
+            },
+            Err(err) => {
+                warn!("{}", trace!(("Failed to load provenance of intermediate result '{name}'"), err));
+                return None;
+            },
+        }
+    }
+
+    // Fingerprint the call
+    let args: Vec<(&str, String)> = tinfo.args.iter().map(|(name, value)| (name.as_str(), fingerprint_arg(value, workflow))).collect();
+    let fingerprint: String =
+        ResultProvenance::fingerprint(&lineage, args.iter().map(|(name, value)| (*name, value.as_str())), inputs.iter().map(String::as_str));
+    let mut tags: Vec<String> = tags.into_iter().collect();
+    tags.sort();
+    Some(ResultProvenance {
+        result: result.into(),
+        workflow: workflow.id.clone(),
+        user: (*workflow.user).clone(),
+        task: format!("{}[{}]::{}", tinfo.package_name, tinfo.package_version, tinfo.name),
+        lineage,
+        fingerprint,
+        tags,
+        recorded: Utc::now(),
+    })
+}
+
+/// Records the provenance of an intermediate result, if any. Failures are only logged, as the registry considers results without provenance
+/// sensitive anyway.
+///
+/// # Arguments
+/// - `worker_cfg`: The configuration for this node's environment. For us, contains where to find intermediate results.
+/// - `provenance`: The [`ResultProvenance`] to record, if any.
+fn record_provenance(worker_cfg: &WorkerConfig, provenance: Option<&ResultProvenance>) {
+    if let Some(provenance) = provenance {
+        match provenance.save(&worker_cfg.paths.results) {
+            Ok(_) => debug!("Recorded provenance of intermediate result '{}' (tags: {:?})", provenance.result, provenance.tags),
+            Err(err) => warn!("{}", trace!(("Failed to record provenance of intermediate result '{}'", provenance.result), err)),
+        }
+    }
+}
+
 
 
 
@@ -163,8 +449,6 @@ async fn update_client(tx: &Sender<Result<ExecuteReply, Status>>, status: JobSta
 /// Defines errors occuring from the [`JobServer`] itself.
 #[derive(Debug)]
 pub enum Error {
-    /// Failed to load the node.yml file from disk
-    NodeConfigLoad { path: PathBuf, err: brane_cfg::info::YamlError },
     /// The given node.yml file was not of the correct kind.
     NodeConfigWrongKind { path: PathBuf, got: String, expected: String },
 }
@@ -172,7 +456,6 @@ impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use Error::*;
         match self {
-            NodeConfigLoad { path, .. } => write!(f, "Failed to load node config file '{}'", path.display()),
             NodeConfigWrongKind { path, got, expected } => {
                 write!(f, "Given node config file '{}' is of the wrong kind; got a {} config, expected a {} config", path.display(), got, expected)
             },
@@ -183,7 +466,6 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         use Error::*;
         match self {
-            NodeConfigLoad { err, .. } => Some(err),
             NodeConfigWrongKind { .. } => None,
         }
     }
@@ -222,6 +504,21 @@ struct PolicyValidateRequest {
     pub workflow: Workflow,
 }
 
+/// Keeps track of what a task's `branelet` has reported to us (see [`BraneletReport`]).
+#[derive(Clone, Debug, Default)]
+struct BraneletState {
+    /// When we last received any report, or [`None`] if the `branelet` never sent one (e.g., because it's too old).
+    last_report: Option<Instant>,
+    /// The resources used by the task, once it has completed.
+    usage: Option<ResourceUsage>,
+    /// The timeout (in seconds) that the (last attempt of the) task exceeded, if it did.
+    timed_out: Option<u64>,
+    /// How often the task has been retried.
+    retries: u32,
+    /// The signal that stopped the package, if it was stopped by one.
+    signal: Option<i32>,
+}
+
 
 
 
@@ -271,6 +568,8 @@ pub struct TaskInfo {
     pub args: HashMap<String, FullValue>,
     /// The requirements for this task.
     pub requirements: HashSet<Capability>,
+    /// If given, the number of seconds for which the result of an earlier, identical call may be reused. Note: won't be populated until later.
+    pub cache: Option<u64>,
 }
 impl TaskInfo {
     /// Constructor for the TaskInfo.
@@ -313,6 +612,7 @@ impl TaskInfo {
 
             args,
             requirements,
+            cache: None,
         }
     }
 }
@@ -322,6 +622,115 @@ impl TaskInfo {
 
 
 /***** PLANNING FUNCTIONS *****/
+/// Performs a single attempt at downloading a tarball from the registry of another domain.
+///
+/// If earlier attempts already downloaded part of the tarball, the registry is asked to only send the remainder. Registries that cannot do
+/// so (e.g., because they archive the dataset anew for every download) send the whole tarball instead, in which case we start over.
+///
+/// # Arguments
+/// - `proxy`: The proxy client we use to proxy the data transfer.
+/// - `url`: The URL of the download endpoint of the registry.
+/// - `body`: The [`DownloadAssetRequest`] to send to the registry.
+/// - `location`: The location to download the tarball from.
+/// - `dataname`: The name of the dataset to download.
+/// - `tar_path`: The path of the tarball to download to.
+/// - `written`: The number of bytes of the tarball downloaded by earlier attempts. Is kept up-to-date while downloading, such that it is accurate even if this attempt fails.
+/// - `trace`: The [`TraceContext`] of the request, which is propagated to the registry.
+///
+/// # Errors
+/// This function errors if we failed to reach the registry, if the registry refused the download or if we failed to write the tarball.
+#[allow(clippy::too_many_arguments)]
+async fn download_tar(
+    proxy: &ProxyClient,
+    url: &str,
+    body: &DownloadAssetRequest,
+    location: &Location,
+    dataname: &DataName,
+    tar_path: &Path,
+    written: &mut u64,
+    trace: &TraceContext,
+) -> Result<(), PreprocessError> {
+    // Send a reqwest, asking for the remainder only if we already have part of it
+    debug!("Sending download request...");
+    let client: reqwest::Client = reqwest::Client::new();
+    let mut req: reqwest::RequestBuilder = trace.inject_http(client.get(url)).json(body);
+    if *written > 0 {
+        req = req.header(header::RANGE, format!("bytes={}-", *written));
+    }
+    let req: reqwest::Request = match req.build() {
+        Ok(req) => req,
+        Err(err) => return Err(PreprocessError::DownloadRequestError { address: url.into(), err }),
+    };
+    let res = match proxy.execute(client, req, Some(NewPathRequestTlsOptions { location: location.clone(), use_client_auth: true })).await {
+        Ok(result) => match result {
+            Ok(res) => res,
+            Err(err) => {
+                return Err(PreprocessError::DownloadRequestError { address: url.into(), err });
+            },
+        },
+        Err(err) => {
+            return Err(PreprocessError::ProxyError { err: Box::new(err) });
+        },
+    };
+    if !res.status().is_success() {
+        let code: StatusCode = res.status();
+        let message: Option<String> = res.text().await.ok();
+        if code == StatusCode::FORBIDDEN {
+            if let Some(denial) = message.as_deref().and_then(|message| PolicyDenial::from_bytes(message.as_bytes())) {
+                return Err(PreprocessError::PolicyDenied { denial });
+            }
+        }
+        if code == StatusCode::LOCKED {
+            if let Some(pending) = message.as_deref().and_then(|message| ApprovalPending::from_bytes(message.as_bytes())) {
+                return Err(PreprocessError::ApprovalPending { pending });
+            }
+        }
+        return Err(PreprocessError::DownloadRequestFailure { address: url.into(), code, message });
+    }
+
+    // Either continue where the previous attempt left off or start over, depending on what the registry sent
+    let mut handle: tfs::File = if *written > 0 && res.status() == StatusCode::PARTIAL_CONTENT {
+        debug!("Resuming download to '{}' after {} bytes...", tar_path.display(), *written);
+        let handle: tfs::File = match tfs::OpenOptions::new().append(true).open(tar_path).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(PreprocessError::TarOpenError { path: tar_path.into(), err }),
+        };
+        // Drop anything beyond what we know was written completely
+        if let Err(err) = handle.set_len(*written).await {
+            return Err(PreprocessError::TarWriteError { path: tar_path.into(), err });
+        }
+        handle
+    } else {
+        debug!("Downloading file to '{}'...", tar_path.display());
+        *written = 0;
+        match tfs::File::create(tar_path).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(PreprocessError::TarCreateError { path: tar_path.into(), err }),
+        }
+    };
+
+    // Download it in parts
+    let mut stream = res.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        // Unwrap the chunk
+        let mut chunk: Bytes = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                return Err(PreprocessError::DownloadStreamError { address: url.into(), err });
+            },
+        };
+
+        // Write it to the file
+        let len: u64 = chunk.len() as u64;
+        metrics::TRANSFER_BYTES.with_label_values(&[if dataname.is_data() { "data" } else { "result" }]).inc_by(len);
+        if let Err(err) = handle.write_all_buf(&mut chunk).await {
+            return Err(PreprocessError::TarWriteError { path: tar_path.into(), err });
+        }
+        *written += len;
+    }
+    Ok(())
+}
+
 /// Function that preprocesses the given tar by downloading it to the local machine and extracting it.
 ///
 /// # Arguments
@@ -333,6 +742,7 @@ impl TaskInfo {
 /// - `workflow`: A [`Workflow`] that is given as context to the registry.
 /// - `location`: The location to download the tarball from.
 /// - `dataname`: The name of the dataset to preprocess.
+/// - `trace`: The [`TraceContext`] of the request, which is propagated to the registry.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to preprocess a TAR-file.
 ///
 /// # Returns
@@ -350,6 +760,7 @@ async fn preprocess_transfer_tar_local(
     workflow: Workflow,
     location: Location,
     dataname: DataName,
+    trace: &TraceContext,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<AccessKind, PreprocessError> {
     debug!("Preprocessing by executing a data transfer");
@@ -410,7 +821,7 @@ async fn preprocess_transfer_tar_local(
             }
 
             // Add the name of the file as the final result path
-            (tar_path.join(format!("data_{name}.tar.gz")), data_path)
+            (tar_path.join(format!("data_{name}.tar.zst")), data_path)
         },
 
         DataName::IntermediateResult(name) => {
@@ -426,64 +837,47 @@ async fn preprocess_transfer_tar_local(
             }
 
             // Add the name of the file as the final result path
-            (tar_path.join(format!("res_{name}.tar.gz")), res_path)
+            (tar_path.join(format!("res_{name}.tar.zst")), res_path)
         },
     };
     pre.stop();
 
 
 
-    // Send a reqwest
-    debug!("Sending download request...");
+    // Download the tarball, retrying (and resuming, if the registry supports it) when the transfer fails halfway
     let download = prof.time("Downloading");
     let url: String = format!("{}/{}/download/{}", address, if dataname.is_data() { "data" } else { "results" }, dataname.name());
-    let res = match proxy
-        .get_with_body(&url, Some(NewPathRequestTlsOptions { location: location.clone(), use_client_auth: true }), &DownloadAssetRequest {
-            use_case: use_case.into(),
-            workflow: serde_json::to_value(&workflow).unwrap(),
-            task:     pc.map(|pc| (if let FunctionId::Func(id) = pc.func_id { Some(id as u64) } else { None }, pc.edge_idx as u64)),
-        })
-        .await
-    {
-        Ok(result) => match result {
-            Ok(res) => res,
-            Err(err) => {
-                return Err(PreprocessError::DownloadRequestError { address: url, err });
-            },
-        },
-        Err(err) => {
-            return Err(PreprocessError::ProxyError { err: Box::new(err) });
-        },
+    let body: DownloadAssetRequest = DownloadAssetRequest {
+        use_case: use_case.into(),
+        workflow: serde_json::to_value(&workflow).unwrap(),
+        task: pc.map(|pc| (if let FunctionId::Func(id) = pc.func_id { Some(id as u64) } else { None }, pc.edge_idx as u64)),
+        compression: ArchiveCompression::Zstd,
     };
-    if !res.status().is_success() {
-        return Err(PreprocessError::DownloadRequestFailure { address: url, code: res.status(), message: res.text().await.ok() });
-    }
-
-
-
-    // With the request success, download it in parts
-    debug!("Downloading file to '{}'...", tar_path.display());
-    {
-        let mut handle: tfs::File = match tfs::File::create(&tar_path).await {
-            Ok(handle) => handle,
-            Err(err) => {
-                return Err(PreprocessError::TarCreateError { path: tar_path, err });
+    let retry: &TransferRetryConfig = &worker_cfg.transfer_retry;
+    let max_attempts: u32 = retry.max_attempts.max(1);
+    let mut written: u64 = 0;
+    let mut attempt: u32 = 1;
+    loop {
+        match download_tar(&proxy, &url, &body, &location, &dataname, &tar_path, &mut written, trace).await {
+            Ok(_) => break,
+            Err(err) if attempt < max_attempts && is_transient_transfer_error(&err) => {
+                let backoff: Duration = retry.backoff_for(attempt);
+                warn!(
+                    "Attempt {}/{} to download {} '{}' from '{}' failed; retrying in {:.1}s{}\n{}",
+                    attempt,
+                    max_attempts,
+                    dataname.variant(),
+                    dataname.name(),
+                    location,
+                    backoff.as_secs_f64(),
+                    if written > 0 { format!(" (resuming after {written} bytes)") } else { String::new() },
+                    err.trace()
+                );
+                metrics::TRANSFER_RETRIES.with_label_values(&[if dataname.is_data() { "data" } else { "result" }]).inc();
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
             },
-        };
-        let mut stream = res.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            // Unwrap the chunk
-            let mut chunk: Bytes = match chunk {
-                Ok(chunk) => chunk,
-                Err(err) => {
-                    return Err(PreprocessError::DownloadStreamError { address: url, err });
-                },
-            };
-
-            // Write it to the file
-            if let Err(err) = handle.write_all_buf(&mut chunk).await {
-                return Err(PreprocessError::TarWriteError { path: tar_path, err });
-            }
+            Err(err) => return Err(err),
         }
     }
     download.stop();
@@ -537,6 +931,7 @@ async fn preprocess_transfer_tar_local(
 /// - `workflow`: A [`Workflow`] that is given as context to the registry.
 /// - `location`: The location to download the tarball from.
 /// - `dataname`: The name of the dataset to download.
+/// - `trace`: The [`TraceContext`] of the request, which is propagated to the registry.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to preprocess a TAR-file.
 ///
 /// # Returns
@@ -554,6 +949,7 @@ pub async fn preprocess_transfer_tar(
     workflow: Workflow,
     location: Location,
     dataname: DataName,
+    trace: &TraceContext,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<AccessKind, PreprocessError> {
     debug!("Preprocessing tar...");
@@ -570,16 +966,19 @@ pub async fn preprocess_transfer_tar(
     match backend.method {
         Credentials::Local { .. } => {
             // Download the container locally
-            preprocess_transfer_tar_local(location_cache, worker_cfg, proxy, use_case, pc, workflow, location, dataname, prof).await
+            preprocess_transfer_tar_local(location_cache, worker_cfg, proxy, use_case, pc, workflow, location, dataname, trace, prof).await
         },
 
         Credentials::Ssh { .. } => Err(PreprocessError::UnsupportedBackend { what: "SSH" }),
 
-        Credentials::Kubernetes { registry_address: _, config: _ } => {
+        Credentials::Kubernetes { .. } => {
             Err(PreprocessError::UnsupportedBackend { what: "Kubernetes" })
 
-            // // Prepare the Kubernetes options
-            // let kinfo: K8sOptions = K8sOptions { registry_address, config };
+            // // Prepare the Kubernetes options (the registry password has already been resolved when loading the backend file)
+            // let registry_auth: Option<RegistryAuth> = registry_credentials.map(|creds| {
+            //     RegistryAuth::Basic(BasicAuth { username: creds.username, password: creds.password.loaded().unwrap_or_default().into() })
+            // });
+            // let kinfo: K8sOptions = K8sOptions { registry_address, registry_auth, config };
 
             // // Call the function
             // preprocess_transfer_tar_k8s(kinfo, location, address, prof).await
@@ -597,23 +996,33 @@ pub async fn preprocess_transfer_tar(
 ///
 /// # Arguments
 /// - `worker_cfg`: The configuration for this node's environment. For us, contains if and where we should proxy the request through and where we may find the checker.
+/// - `verdicts`: The [`VerdictCache`] with the checker's earlier verdicts, which is consulted before the checker itself.
 /// - `use_case`: A string denoting which use-case (registry) we're using.
 /// - `workflow`: The workflow to check.
 /// - `call`: A program counter that identifies which call in the workflow we'll be checkin'.
 ///
 /// # Returns
-/// Whether the workflow has been accepted or not.
+/// [`None`] if the task has been accepted, or else the reasons for denial given by the checker (if it shared any).
 ///
 /// # Errors
 /// This function errors if we failed to reach the checker, or the checker itself crashed.
 async fn assert_task_permission(
     worker_cfg: &WorkerConfig,
+    verdicts: &VerdictCache,
     use_case: &str,
     workflow: &Workflow,
     call: ProgramCounter,
-) -> Result<bool, AuthorizeError> {
+) -> Result<Option<Vec<String>>, AuthorizeError> {
     info!("Checking task '{}' execution permission with checker '{}'...", call, worker_cfg.services.chk.address);
 
+    // Re-use the checker's earlier verdict if it's still valid
+    let key: Option<VerdictKey> =
+        verdicts.key(&worker_cfg.services.chk.address, &worker_cfg.paths.policy_expert_secret, use_case, workflow, CheckQuestion::Task(call)).await;
+    if let Some(verdict) = key.as_ref().and_then(|key| verdicts.get(key)) {
+        info!("Checker {} execution of task {} (cached)", if verdict.is_none() { "ALLOWED" } else { "DENIED" }, call);
+        return Ok(verdict);
+    }
+
     // Alrighty tighty, let's begin by building the request for the checker
     debug!("Constructing checker request...");
     let body: PolicyExecuteRequest = PolicyExecuteRequest { use_case: use_case.into(), workflow: workflow.clone(), task_id: call };
@@ -665,51 +1074,48 @@ async fn assert_task_permission(
     };
 
     // Now match the checker's response
-    match res {
+    let verdict: Option<Vec<String>> = match res {
         Verdict::Allow(_) => {
             info!("Checker ALLOWED execution of task {}", call);
-            Ok(true)
+            None
         },
 
-        Verdict::Deny(_) => {
+        Verdict::Deny(deny) => {
             info!("Checker DENIED execution of task {}", call);
-            Ok(false)
+            Some(deny.reasons_for_denial.unwrap_or_else(Vec::new))
         },
+    };
+    if let Some(key) = key {
+        verdicts.insert(key, verdict.clone());
     }
+    Ok(verdict)
 }
 
 /// Abstracts over the kinds of questions this worker handles ~ workflow-global validation requests or task-specific.
 ///
 /// # Arguments
-/// -` node_config_path`: The path to a `node.yml` file that defines the environment (such as checker location).
+/// - `node_config`: The watched `node.yml` file that defines the environment (such as checker location).
+/// - `verdicts`: The [`VerdictCache`] with the checker's earlier verdicts, which is consulted before the checker itself.
 /// - `request`: The body of the request, which is either a [`CheckWorkflowRequest`] or a [`CheckTaskRequest`].
 ///
 /// # Returns
 /// A [`CheckReply`] containing the checker's response (wrapped in a [`tonic::Response`]).
 ///
 /// # Errors
-/// This function may error if we failed to read the `node.yml` file or if we failed to contact the checker.
-async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest) -> Result<Response<CheckReply>, Status> {
+/// This function may error if the `node.yml` file is not that of a worker or if we failed to contact the checker.
+async fn check_workflow_or_task(
+    node_config: &InfoWatcher<NodeConfig>,
+    verdicts: &VerdictCache,
+    request: CheckRequest,
+) -> Result<Response<CheckReply>, Status> {
     let (use_case, workflow, task_id): (String, String, Option<String>) = match request {
         CheckRequest::Workflow(CheckWorkflowRequest { use_case, workflow }) => (use_case, workflow, None),
         CheckRequest::Task(CheckTaskRequest { use_case, workflow, task_id }) => (use_case, workflow, Some(task_id)),
     };
     debug!("Consulting checker to find validity for use-case '{use_case}'");
 
-    // Load the worker config from the node config to setup the profiler
-    let worker_cfg: WorkerConfig = match NodeConfig::from_path(node_config_path) {
-        Ok(node_config) => match node_config.node.try_into_worker() {
-            Some(node) => node,
-            None => {
-                error!("Provided a non-worker `node.yml` file; please change to include worker services");
-                return Err(Status::internal("An internal error occurred"));
-            },
-        },
-        Err(err) => {
-            error!("{}", trace!(("Could not load `node.yml` file '{}'", node_config_path.display()), err));
-            return Err(Status::internal("An internal error occurred"));
-        },
-    };
+    // Get the most recent worker config to setup the profiler
+    let worker_cfg: WorkerConfig = current_worker_config(node_config)?;
     let report =
         ProfileReport::auto_reporting_file("brane-job WorkerServer::check-workflow", format!("brane-job_{}_check-workflow", worker_cfg.name));
 
@@ -720,24 +1126,35 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             error!("{}", trace!(("Failed to deserialize workflow"), err));
             debug!("Workflow:\n{}\n{}\n{}\n", (0..80).map(|_| '-').collect::<String>(), workflow, (0..80).map(|_| '-').collect::<String>());
-            return Err(Status::invalid_argument(format!("{}", trace!(("Failed to deserialize workflow"), err))));
+            return Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!("{}", trace!(("Failed to deserialize workflow"), err)))));
         },
     };
-    par.stop();
-
-    // Alrighty tighty, let's begin by building the request for the checker
-    let send = report.time("Checker request");
-    debug!("Constructing checker request...");
-    let (method, url, body): (Method, String, String) = if let Some(task_id) = task_id {
+    let question: CheckQuestion = match task_id {
         // Parse the task ID as a ProgramCounter
-        let pc: ProgramCounter = match ProgramCounter::from_str(&task_id) {
-            Ok(pc) => pc,
+        Some(task_id) => match ProgramCounter::from_str(&task_id) {
+            Ok(pc) => CheckQuestion::Task(pc),
             Err(err) => {
                 debug!("{}", trace!(("Failed to parse '{task_id}' as program counter"), err));
-                return Err(Status::invalid_argument(format!("{}", trace!(("Failed to parse '{task_id}' as program counter"), err))));
+                return Err(ErrorCode::JobInvalidRequest
+                    .attach(Status::invalid_argument(format!("{}", trace!(("Failed to parse '{task_id}' as program counter"), err)))));
             },
-        };
+        },
+        None => CheckQuestion::Workflow,
+    };
+    par.stop();
 
+    // Re-use the checker's earlier verdict if it's still valid
+    let key: Option<VerdictKey> =
+        verdicts.key(&worker_cfg.services.chk.address, &worker_cfg.paths.policy_expert_secret, &use_case, &workflow, question.clone()).await;
+    if let Some(verdict) = key.as_ref().and_then(|key| verdicts.get(key)) {
+        info!("Checker {} execution of workflow (cached)", if verdict.is_none() { "ALLOWED" } else { "DENIED" });
+        return Ok(Response::new(CheckReply { verdict: verdict.is_none(), reasons: verdict.unwrap_or_default() }));
+    }
+
+    // Alrighty tighty, let's begin by building the request for the checker
+    let send = report.time("Checker request");
+    debug!("Constructing checker request...");
+    let (method, url, body): (Method, String, String) = if let CheckQuestion::Task(pc) = question {
         // It's a task request
         (
             DELIBERATION_API_EXECUTE_TASK.0,
@@ -746,7 +1163,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
                 Ok(req) => req,
                 Err(err) => {
                     error!("{}", trace!(("Could not deserialize PolicyExecuteRequest"), err));
-                    return Err(Status::internal("An internal error occurred"));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
                 },
             },
         )
@@ -759,7 +1176,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
                 Ok(req) => req,
                 Err(err) => {
                     error!("{}", trace!(("Could not deserialize PolicyExecuteRequest"), err));
-                    return Err(Status::internal("An internal error occurred"));
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
                 },
             },
         )
@@ -776,7 +1193,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             let err = AuthorizeError::TokenGenerate { secret: worker_cfg.paths.policy_deliberation_secret.clone(), err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
 
@@ -786,7 +1203,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             let err = AuthorizeError::ClientBuild { err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
     let req: reqwest::Request = match client.request(method, &url).header(header::AUTHORIZATION, format!("Bearer {jwt}")).body(body).build() {
@@ -794,7 +1211,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             let err = AuthorizeError::ExecuteRequestBuild { addr: url, err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
 
@@ -805,7 +1222,7 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             let err = AuthorizeError::ExecuteRequestSend { addr: url, err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
 
@@ -814,14 +1231,14 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
     if !res.status().is_success() {
         let err = AuthorizeError::ExecuteRequestFailure { addr: url, code: res.status(), err: res.text().await.ok() };
         error!("{}", err.trace());
-        return Err(Status::internal("An internal error occurred"));
+        return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
     }
     let res: String = match res.text().await {
         Ok(res) => res,
         Err(err) => {
             let err = AuthorizeError::ExecuteBodyDownload { addr: url, err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
     let res: Verdict = match serde_json::from_str(&res) {
@@ -829,23 +1246,27 @@ async fn check_workflow_or_task(node_config_path: &Path, request: CheckRequest)
         Err(err) => {
             let err = AuthorizeError::ExecuteBodyDeserialize { addr: url, raw: res, err };
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         },
     };
     send.stop();
 
     // Now match the checker's response
-    match res {
+    let verdict: Option<Vec<String>> = match res {
         Verdict::Allow(_) => {
             info!("Checker ALLOWED execution of workflow");
-            Ok(Response::new(CheckReply { verdict: true, reasons: vec![] }))
+            None
         },
 
         Verdict::Deny(deny) => {
             info!("Checker DENIED execution of workflow");
-            Ok(Response::new(CheckReply { verdict: false, reasons: deny.reasons_for_denial.unwrap_or_else(Vec::new) }))
+            Some(deny.reasons_for_denial.unwrap_or_else(Vec::new))
         },
+    };
+    if let Some(key) = key {
+        verdicts.insert(key, verdict.clone());
     }
+    Ok(Response::new(CheckReply { verdict: verdict.is_none(), reasons: verdict.unwrap_or_default() }))
 }
 
 
@@ -874,6 +1295,7 @@ fn get_cached_container(worker_cfg: &WorkerConfig, image: &Image) -> Option<Path
 /// - `proxy`: The proxy client we use to proxy the data transfer.
 /// - `endpoint`: The address where to download the container from.
 /// - `image`: The image name (including digest, for caching) to download.
+/// - `oci`: The OCI reference of the image if the package is distributed through an OCI registry, in which case it is pulled from there instead.
 ///
 /// # Returns
 /// The path of the downloaded image file combined with the hash of the image. It's very good practise to use this one, since the actual path is subject to change.
@@ -887,8 +1309,35 @@ async fn get_container(
     proxy: Arc<ProxyClient>,
     endpoint: impl AsRef<str>,
     image: &Image,
+    oci: Option<&str>,
 ) -> Result<PathBuf, ExecuteError> {
     let endpoint: &str = endpoint.as_ref();
+    let image_path: PathBuf = worker_cfg.paths.packages.join(format!("{}-{}.tar", image.name, image.version.as_ref().unwrap_or(&"latest".into())));
+
+    // Packages distributed through an OCI registry are pulled from there directly
+    if let Some(reference) = oci {
+        debug!("Pulling image '{}' from '{}'...", image, reference);
+        let client: oci::Client = match oci::Credentials::from_docker_config() {
+            Ok(creds) => oci::Client::new(creds),
+            Err(err) => {
+                return Err(ExecuteError::OciPullError { reference: reference.into(), err });
+            },
+        };
+        let res: Result<(), oci::Error> = match oci::Reference::from_str(reference) {
+            Ok(reference) => client.pull_archive(&reference, oci::host_arch(), &image_path).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = res {
+            // Don't leave a partial image behind, since it would be taken for a cached one
+            if image_path.exists() {
+                if let Err(err) = tfs::remove_file(&image_path).await {
+                    warn!("Failed to remove partial image '{}': {}", image_path.display(), err);
+                }
+            }
+            return Err(ExecuteError::OciPullError { reference: reference.into(), err });
+        }
+        return Ok(image_path);
+    }
     debug!("Downloading image '{}' from '{}'...", image, endpoint);
 
     // Send a GET-request to the correct location
@@ -910,7 +1359,6 @@ async fn get_container(
     }
 
     // With the request success, download it in parts
-    let image_path: PathBuf = worker_cfg.paths.packages.join(format!("{}-{}.tar", image.name, image.version.as_ref().unwrap_or(&"latest".into())));
     debug!("Writing request stream to '{}'...", image_path.display());
     {
         let mut handle: tfs::File = match tfs::File::create(&image_path).await {
@@ -1059,6 +1507,7 @@ async fn get_container_ids(
 /// - `proxy`: The proxy client we use to proxy the data transfer.
 /// - `endpoint`: The address where to download the container from.
 /// - `image`: The image name (including digest, for caching) to download.
+/// - `oci`: The OCI reference of the image if the package is distributed through an OCI registry.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to ensure a container exists.
 ///
 /// # Returns
@@ -1077,12 +1526,13 @@ async fn ensure_container(
     proxy: Arc<ProxyClient>,
     endpoint: impl AsRef<str>,
     image: &Image,
+    oci: Option<&str>,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<(PathBuf, String, Option<String>), ExecuteError> {
     // Download the file if we don't have it locally already
     let image_path: PathBuf = match prof.time_func("cache checking", || get_cached_container(worker_cfg, image)) {
         Some(path) => path,
-        None => prof.time_fut("container downloading", get_container(worker_cfg, proxy, endpoint, image)).await?,
+        None => prof.time_fut("container downloading", get_container(worker_cfg, proxy, endpoint, image, oci)).await?,
     };
 
     // Compute the ID and hash for it
@@ -1103,7 +1553,11 @@ async fn ensure_container(
 /// - `tx`: The transmission channel over which we should update the client of our progress.
 /// - `container_path`: The path of the downloaded container that we should execute.
 /// - `tinfo`: The TaskInfo that describes the task itself to execute.
-/// - `keep_container`: Whether to keep the container after execution or not.
+/// - `keep_container`: Whether to keep the container after execution or not. If so, the task is never executed in a pooled container.
+/// - `stream_output`: Whether to send the task's output to the client while it is being written.
+/// - `sandbox`: The container runtime and privileges with which to run the task, as configured in the `backend.yml` file.
+/// - `pool`: The pool of idle containers to execute the task in, if enabled.
+/// - `user`: The user on whose behalf the task is executed, if known. Pooled containers are never shared between users.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to execute a local task.
 ///
 /// # Returns
@@ -1111,6 +1565,7 @@ async fn ensure_container(
 ///
 /// # Errors
 /// This function errors if the task fails for whatever reason or we didn't even manage to launch it.
+#[allow(clippy::too_many_arguments)]
 async fn execute_task_local(
     worker_cfg: &WorkerConfig,
     dinfo: DockerOptions,
@@ -1118,14 +1573,38 @@ async fn execute_task_local(
     container_path: impl AsRef<Path>,
     tinfo: TaskInfo,
     keep_container: bool,
+    stream_output: bool,
+    sandbox: SandboxConfig,
+    pool: Option<&ContainerPool>,
+    user: Option<&str>,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<FullValue, JobStatus> {
     let container_path: &Path = container_path.as_ref();
+    let pool: Option<&ContainerPool> = if keep_container { None } else { pool };
     let mut tinfo: TaskInfo = tinfo;
     let image: Image = tinfo.image.clone().unwrap();
     debug!("Spawning container '{}' as a local container...", image);
+    if chaos::should_fail() {
+        return Err(JobStatus::Failed(TaskFailure::new(-1, None, "", "Task failed (injected by chaos mode)", None)));
+    }
+
+    // Fetch any inputs that are stored remotely, which are removed again once the task is done
+    let _remote_dir: Option<TempDir> = if tinfo.input.values().any(AccessKind::is_remote) {
+        let dir: TempDir = match TempDir::new_in(&worker_cfg.paths.temp_data) {
+            Ok(dir) => dir,
+            Err(err) => {
+                return Err(JobStatus::CreationFailed(format!("Failed to create directory for remote inputs: {err}")));
+            },
+        };
+        if let Err(err) = prof.time_fut("remote data fetching", docker::fetch_remote_inputs(&mut tinfo.input, dir.path())).await {
+            return Err(JobStatus::CreationFailed(format!("Failed to fetch remote input: {err}")));
+        }
+        Some(dir)
+    } else {
+        None
+    };
 
-    // First, we preprocess the arguments
+    // Then, we preprocess the arguments
     let binds: Vec<VolumeBind> = match prof
         .time_fut(
             "preprocessing",
@@ -1150,36 +1629,43 @@ async fn execute_task_local(
     ser.stop();
 
     // Prepare the ExecuteInfo
-    let info: ExecuteInfo = ExecuteInfo::new(
-        &tinfo.name,
-        image,
-        ImageSource::Path(container_path.into()),
-        vec![
-            "-d".into(),
-            "--application-id".into(),
-            "unspecified".into(),
-            "--location-id".into(),
-            worker_cfg.name.clone(),
-            "--job-id".into(),
-            "unspecified".into(),
-            tinfo.kind.unwrap().into(),
-            tinfo.name.clone(),
-            STANDARD.encode(params),
-        ],
-        binds,
-        tinfo.requirements,
-        Network::None,
-    );
-
-    // Now we can launch the container...
+    let mut command: Vec<String> = vec![
+        "-d".into(),
+        "--application-id".into(),
+        "unspecified".into(),
+        "--location-id".into(),
+        worker_cfg.name.clone(),
+        "--job-id".into(),
+        "unspecified".into(),
+    ];
+    if let Some(timeout) = worker_cfg.task_timeout {
+        command.extend(["--timeout".into(), timeout.to_string()]);
+    }
+    command.extend([tinfo.kind.unwrap().into(), tinfo.name.clone(), STANDARD.encode(params)]);
+    let mut info: ExecuteInfo =
+        ExecuteInfo::new(&tinfo.name, image, ImageSource::Path(container_path.into()), command, binds, tinfo.requirements, Network::None);
+    info.sandbox = sandbox;
+    let command: Vec<String> = info.command.clone();
+
+    // Now we can launch the container (or take an idle one from the pool)...
     let exec = prof.nest("execution");
     let total = prof.time("Total");
-    let name: String = match exec.time_fut("spawn overhead", docker::launch(&dinfo, info)).await {
-        Ok(name) => name,
-        Err(err) => {
-            return Err(JobStatus::CreationFailed(format!("Failed to spawn container: {err}")));
+    let launch = metrics::CONTAINER_START.start_timer();
+    let (name, pooled): (String, Option<PooledContainer>) = match pool {
+        Some(pool) => match exec.time_fut("spawn overhead", pool.acquire(&dinfo, &info, user)).await {
+            Ok(container) => (container.name.clone(), Some(container)),
+            Err(err) => {
+                return Err(JobStatus::CreationFailed(format!("Failed to acquire pooled container: {err}")));
+            },
+        },
+        None => match exec.time_fut("spawn overhead", docker::launch(&dinfo, info)).await {
+            Ok(name) => (name, None),
+            Err(err) => {
+                return Err(JobStatus::CreationFailed(format!("Failed to spawn container: {err}")));
+            },
         },
     };
+    launch.observe_duration();
     if let Err(err) = update_client(tx, JobStatus::Created).await {
         error!("{}", err.trace());
     }
@@ -1187,16 +1673,102 @@ async fn execute_task_local(
         error!("{}", err.trace());
     }
 
-    // ...and wait for it to complete
-    let (code, stdout, stderr): (i32, String, String) = match exec.time_fut("join overhead", docker::join(dinfo, name, keep_container)).await {
-        Ok(name) => name,
-        Err(err) => {
+    // ...and wait for it to complete (passing its output on as we go, if asked, and filtering out branelet's reports)
+    let bstate: Mutex<BraneletState> = Mutex::new(BraneletState::default());
+    let on_output = |channel: OutputChannel, data: String| {
+        let data: String = if channel == OutputChannel::Stderr { filter_reports(&data, &bstate) } else { data };
+        async move {
+            if stream_output && !data.is_empty() {
+                update_client_output(tx, channel, data).await;
+            }
+        }
+    };
+    let join = async {
+        match (pool, &pooled) {
+            (Some(pool), Some(container)) => pool.exec_streaming(&dinfo, container, &command, on_output).await.map_err(|err| err.to_string()),
+            _ => docker::join_streaming(&dinfo, &name, keep_container, on_output).await.map_err(|err| err.to_string()),
+        }
+    };
+    // Only tasks whose branelet has reported at least once are timed out, such that older branelets still work
+    let watchdog = async {
+        let mut interval: Interval = tokio::time::interval(LIVENESS_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some(last) = bstate.lock().unwrap().last_report {
+                if last.elapsed() > LIVENESS_TIMEOUT {
+                    break last.elapsed();
+                }
+            }
+        }
+    };
+    // The driver drops its end of the stream if it cancels the task (e.g., because the workflow exceeded its deadline)
+    let join = async {
+        tokio::select! {
+            res = join => Ok(res),
+            silence = watchdog => Err(Some(silence)),
+            _ = tx.closed() => Err(None),
+        }
+    };
+    let (code, stdout, stderr): (i32, String, String) = match exec.time_fut("join overhead", join).await {
+        Ok(Ok(name)) => name,
+        Ok(Err(err)) => {
+            if let (Some(pool), Some(container)) = (pool, pooled) {
+                pool.discard(&dinfo, container).await;
+            }
             return Err(JobStatus::CompletionFailed(format!("Failed to join container: {err}")));
         },
+        Err(None) => {
+            info!("Client of task '{}' went away; cancelling container '{}'", tinfo.name, name);
+            if let (Some(pool), Some(container)) = (pool, pooled) {
+                pool.discard(&dinfo, container).await;
+            } else if let Err(err) = docker::remove(&dinfo, &name).await {
+                warn!("{}", trace!(("Failed to remove cancelled container '{name}'"), err));
+            }
+            return Err(JobStatus::CompletionFailed("Task was cancelled by its client".into()));
+        },
+        Err(Some(silence)) => {
+            warn!("Container '{}' has not reported being alive for {}s; assuming it hung", name, silence.as_secs());
+            if let (Some(pool), Some(container)) = (pool, pooled) {
+                pool.discard(&dinfo, container).await;
+            } else if let Err(err) = docker::remove(&dinfo, &name).await {
+                warn!("{}", trace!(("Failed to remove hung container '{name}'"), err));
+            }
+            return Err(JobStatus::CompletionFailed(format!(
+                "Task has not reported being alive for {}s (timeout is {}s); assuming it hung",
+                silence.as_secs(),
+                LIVENESS_TIMEOUT.as_secs()
+            )));
+        },
     };
+    // Give a pooled container back, which moves the task's result to where it would have been written by a fresh container
+    if let (Some(pool), Some(container)) = (pool, pooled) {
+        let result: Option<PathBuf> = tinfo.result.as_ref().map(|result| worker_cfg.paths.results.join(result));
+        if let Err(err) = pool.release(&dinfo, container, result.as_deref()).await {
+            return Err(JobStatus::CompletionFailed(format!("Failed to collect result of pooled container: {err}")));
+        }
+    }
+    let stderr: String = filter_reports(&stderr, &bstate);
     total.stop();
     exec.finish();
 
+    // Pass on what the task used (and how often it was attempted), if its branelet told us
+    let bstate: BraneletState = bstate.into_inner().unwrap();
+    let mut usage: Option<ResourceUsage> = bstate.usage;
+    if bstate.retries > 0 {
+        usage.get_or_insert_with(ResourceUsage::default).attempts = Some(bstate.retries + 1);
+    }
+    if let Some(usage) = usage {
+        info!("Task '{}' used: {}", tinfo.name, usage);
+        update_client_usage(tx, &usage).await;
+    }
+
+    // If the task took too long, say so instead of showing it as any other failure
+    if let Some(timeout) = bstate.timed_out {
+        if code != 0 {
+            return Err(JobStatus::CompletionFailed(format!("Task exceeded its timeout of {timeout}s and was terminated")));
+        }
+    }
+
     // Let the client know it was done
     debug!("Container return code: {}", code);
     debug!("Container stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
@@ -1206,7 +1778,7 @@ async fn execute_task_local(
 
     // If the return code is no bueno, error and show stderr
     if code != 0 {
-        return Err(JobStatus::Failed(code, stdout, stderr));
+        return Err(JobStatus::Failed(TaskFailure::new(code, bstate.signal, &stdout, &stderr, usage)));
     }
 
     // Otherwise, decode the output of branelet to the value returned
@@ -1226,6 +1798,16 @@ async fn execute_task_local(
     };
     decode.stop();
 
+    // Store the result by its contents, such that identical results of repeated runs only take up disk space once
+    if let Some(result) = &tinfo.result {
+        let _dedup = prof.time("Deduplication");
+        let results_path: &Path = &worker_cfg.paths.results;
+        match deduplicate_dir_async(results_path.join(result), results_path.join(RESULT_OBJECTS_DIR)).await {
+            Ok(hash) => debug!("Stored result '{}' as object '{}'", result, hash),
+            Err(err) => warn!("{}", trace!(("Failed to deduplicate result '{result}'; keeping it as-is"), err)),
+        }
+    }
+
     // Done
     debug!("Task '{}' returned value: '{:?}'", tinfo.name, value);
     Ok(value)
@@ -1275,7 +1857,7 @@ async fn execute_task_local(
 //     ser.stop();
 
 //     // Prepare the ExecuteInfo
-//     let info: ExecuteInfo = ExecuteInfo::new(
+//     let mut info: ExecuteInfo = ExecuteInfo::new(
 //         &tinfo.name,
 //         image,
 //         ImageSource::Path(container_path.into()),
@@ -1296,18 +1878,35 @@ async fn execute_task_local(
 //         Network::None,
 //     );
 
+//     // Push the image to the registry, and allow the cluster to pull it from there with the same credentials
+//     let client: k8s::Client = match k8s::Client::new(config) {
+//         Ok(client) => client,
+//         Err(err)   => { return Err(JobStatus::CreationFailed(format!("Failed to create Kubernetes client: {err}"))); },
+//     };
+//     info.image_source = match k8s::resolve_image_source(&info.image, info.image_source, &kinfo.registry_address, kinfo.registry_auth.clone(), false).await {
+//         Ok(source) => source,
+//         Err(err)   => { return Err(JobStatus::CreationFailed(format!("Failed to push image to registry: {err}"))); },
+//     };
+//     let registry_secret: Option<k8s::Handle<k8s::Secret>> = match kinfo.registry_auth {
+//         Some(auth) => match client.scope::<k8s::Secret>(&worker_cfg.name).create_registry_secret(&kinfo.registry_address, auth).await {
+//             Ok(secret) => Some(secret),
+//             Err(err)   => { return Err(JobStatus::CreationFailed(format!("Failed to create registry pull secret: {err}"))); },
+//         },
+//         None => None,
+//     };
+
 //     // Now we can launch the container...
 //     let exec = prof.nest("execution");
 //     let total = prof.time("Total");
-//     let name: String = match exec.time_fut("spawn overhead", docker::launch(&dinfo, info)).await {
-//         Ok(name) => name,
+//     let pod: k8s::Handle<k8s::Pod> = match exec.time_fut("spawn overhead", client.scope::<k8s::Pod>(&worker_cfg.name).spawn(info, registry_secret.as_ref())).await {
+//         Ok(pod)  => pod,
 //         Err(err) => { return Err(JobStatus::CreationFailed(format!("Failed to spawn container: {err}"))); },
 //     };
 //     if let Err(err) = update_client(tx, JobStatus::Created).await { error!("{}", err.trace()); }
 //     if let Err(err) = update_client(tx, JobStatus::Started).await { error!("{}", err.trace()); }
 
 //     // ...and wait for it to complete
-//     let (code, stdout, stderr): (i32, String, String) = match exec.time_fut("join overhead", docker::join(dinfo, name, keep_container)).await {
+//     let (code, stdout, stderr): (i32, String, String) = match exec.time_fut("join overhead", pod.join()).await {
 //         Ok(name) => name,
 //         Err(err) => { return Err(JobStatus::CompletionFailed(format!("Failed to join container: {err}"))); },
 //     };
@@ -1355,6 +1954,8 @@ async fn execute_task_local(
 /// - `cinfo`: The ControlNodeInfo that specifies where to find services over at the control node.
 /// - `tinfo`: The TaskInfo that describes the task itself to execute.
 /// - `keep_container`: Whether to keep the container after execution or not.
+/// - `stream_output`: Whether to send the task's output to the client while it is being written.
+/// - `pool`: The pool of idle containers to execute the task in, if enabled.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to execute a task.
 ///
 /// # Returns
@@ -1365,6 +1966,7 @@ async fn execute_task_local(
 #[allow(clippy::too_many_arguments)]
 async fn execute_task(
     worker_cfg: &WorkerConfig,
+    verdicts: &VerdictCache,
     proxy: Arc<ProxyClient>,
     tx: Sender<Result<ExecuteReply, Status>>,
     use_case: &str,
@@ -1372,6 +1974,8 @@ async fn execute_task(
     cinfo: ControlNodeInfo,
     tinfo: TaskInfo,
     keep_container: bool,
+    stream_output: bool,
+    pool: Option<&ContainerPool>,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<(), ExecuteError> {
     let mut tinfo = tinfo;
@@ -1425,7 +2029,7 @@ async fn execute_task(
     // Download the container from the central node
     let (container_path, container_id, container_hash): (PathBuf, String, Option<String>) = prof
         .nest_fut(format!("container {:?} downloading", tinfo.image.as_ref()), |scope| {
-            ensure_container(worker_cfg, proxy, &cinfo.api_endpoint, tinfo.image.as_ref().unwrap(), scope)
+            ensure_container(worker_cfg, proxy, &cinfo.api_endpoint, tinfo.image.as_ref().unwrap(), info.oci.as_deref(), scope)
         })
         .await?;
     tinfo.image.as_mut().unwrap().digest = Some(container_id);
@@ -1438,19 +2042,23 @@ async fn execute_task(
         let _auth = prof.time("Authorization");
 
         // First: make sure that the workflow is allowed by the checker
-        match assert_task_permission(worker_cfg, use_case, &workflow, tinfo.pc).await {
-            Ok(true) => {
+        let check = metrics::POLICY_CHECK.with_label_values(&["execute"]).start_timer();
+        let verdict: Result<Option<Vec<String>>, AuthorizeError> = assert_task_permission(worker_cfg, verdicts, use_case, &workflow, tinfo.pc).await;
+        check.observe_duration();
+        match verdict {
+            Ok(None) => {
                 debug!("Checker accepted incoming workflow");
                 if let Err(err) = update_client(&tx, JobStatus::Authorized).await {
                     error!("{}", err.trace());
                 }
             },
-            Ok(false) => {
+            Ok(Some(reasons)) => {
                 debug!("Checker rejected incoming workflow");
-                if let Err(err) = update_client(&tx, JobStatus::Denied).await {
+                let denial: PolicyDenial = PolicyDenial { domain: worker_cfg.name.clone(), task: Some(tinfo.name.clone()), dataset: None, reasons };
+                if let Err(err) = update_client(&tx, JobStatus::Denied(denial.clone())).await {
                     error!("{}", err.trace());
                 }
-                return Err(ExecuteError::AuthorizationFailure { checker: worker_cfg.services.reg.address.clone() });
+                return Err(ExecuteError::PolicyDenied { denial });
             },
 
             Err(err) => {
@@ -1464,9 +2072,50 @@ async fn execute_task(
 
 
 
+    /* CACHE */
+    // Find out how the result is computed now that we still have the whole call, to record it once we have it
+    let provenance: Option<ResultProvenance> = tinfo.result.as_ref().and_then(|result| result_provenance(worker_cfg, &workflow, &tinfo, result));
+
+    // See if we can reuse the result of an earlier, identical call
+    let results_path: &Path = &worker_cfg.paths.results;
+    let result: Option<String> = tinfo.result.clone();
+    let cache: Option<(ResultCache, String, u64)> = tinfo.cache.map(|ttl| {
+        let digest: Option<&str> = tinfo.image.as_ref().and_then(|image| image.digest.as_deref());
+        let key: String = ResultCache::key(&tinfo.package_name, &tinfo.package_version, digest, &tinfo.name, &tinfo.args, tinfo.input.keys());
+        (ResultCache::new(results_path.join(RESULT_CACHE_DIR)), key, ttl)
+    });
+    if let Some((cache, key, ttl)) = &cache {
+        let result_path: Option<PathBuf> = result.as_ref().map(|result| results_path.join(result));
+        let objects_path: PathBuf = results_path.join(RESULT_OBJECTS_DIR);
+        match prof.time_fut("result cache lookup", cache.get(key, *ttl, result_path.as_deref().map(|path| (path, objects_path.as_path())))).await {
+            Ok(Some(value)) => {
+                info!("Reusing cached result of task '{}'", tinfo.name);
+                record_provenance(worker_cfg, provenance.as_ref());
+                if let Err(err) = update_client(&tx, JobStatus::Finished(value)).await {
+                    error!("{}", err.trace());
+                }
+                return Ok(());
+            },
+            Ok(None) => debug!("No cached result of task '{}' to reuse", tinfo.name),
+            Err(err) => warn!("{}", trace!(("Failed to look up cached result of task '{}'; running it instead", tinfo.name), err)),
+        }
+    }
+
+
+
     /* SCHEDULE */
+    // Find the backend that provides everything the task requires
+    let route: BackendRoute = match creds.route(&tinfo.requirements) {
+        Some(route) => route,
+        None => {
+            return err!(tx, ExecuteError::NoSuitableBackend { name: tinfo.name.clone(), requirements: tinfo.requirements.clone() });
+        },
+    };
+    debug!("Routing task '{}' to backend '{}'", tinfo.name, route.name);
+
     // Match on the specific type to find the specific backend
-    let value: FullValue = match creds.method {
+    let sandbox: SandboxConfig = route.sandbox;
+    let value: FullValue = match route.method.clone() {
         Credentials::Local { path, version } => {
             // Prepare the DockerInfo
             let dinfo: DockerOptions = DockerOptions {
@@ -1480,7 +2129,21 @@ async fn execute_task(
 
             // Do the call
             match prof
-                .nest_fut("execution (local)", |scope| execute_task_local(worker_cfg, dinfo, &tx, container_path, tinfo, keep_container, scope))
+                .nest_fut("execution (local)", |scope| {
+                    execute_task_local(
+                        worker_cfg,
+                        dinfo,
+                        &tx,
+                        container_path,
+                        tinfo,
+                        keep_container,
+                        stream_output,
+                        sandbox,
+                        pool,
+                        workflow.user.as_deref(),
+                        scope,
+                    )
+                })
                 .await
             {
                 Ok(value) => value,
@@ -1502,16 +2165,20 @@ async fn execute_task(
             return Ok(());
         },
 
-        Credentials::Kubernetes { registry_address: _, config: _ } => {
+        Credentials::Kubernetes { .. } => {
             error!("Kubernetes backend is not yet supported");
             if let Err(err) = update_client(&tx, JobStatus::CreationFailed("Kubernetes backend is not yet supported".into())).await {
                 error!("{}", err.trace());
             }
             return Ok(());
 
-            // // Prepare the options for the Kubernetes client
+            // // Prepare the options for the Kubernetes client (the registry password has already been resolved when loading the backend file)
+            // let registry_auth: Option<RegistryAuth> = registry_credentials.map(|creds| {
+            //     RegistryAuth::Basic(BasicAuth { username: creds.username, password: creds.password.loaded().unwrap_or_default().into() })
+            // });
             // let kinfo: K8sOptions = K8sOptions {
             //     registry_address,
+            //     registry_auth,
             //     config,
             // };
 
@@ -1534,6 +2201,25 @@ async fn execute_task(
         },
     };
     debug!("Job completed");
+    record_provenance(worker_cfg, provenance.as_ref());
+
+    // Remember the result for identical calls later on, but only if its intermediate result (if any) is stored as an object that can be shared
+    if let Some((cache, key, _)) = cache {
+        let object: Option<String> = match &result {
+            Some(result) => match tfs::read_link(results_path.join(result)).await {
+                Ok(target) => target.file_name().map(|name| name.to_string_lossy().into()),
+                Err(_) => None,
+            },
+            None => None,
+        };
+        if result.is_none() || object.is_some() {
+            if let Err(err) = cache.insert(&key, value.clone(), object).await {
+                warn!("{}", trace!(("Failed to cache result of call '{key}'"), err));
+            }
+        } else {
+            debug!("Not caching result of call '{key}', since its intermediate result is not stored as an object");
+        }
+    }
 
 
 
@@ -1554,6 +2240,7 @@ async fn execute_task(
 /// - `results_path`: Path to the shared data results directory. This is where the results live.
 /// - `name`: The name of the intermediate result to promote.
 /// - `data_name`: The name of the intermediate result to promote it as.
+/// - `provenance`: If given, describes where the intermediate result came from. It is recorded in the dataset's `data.yml`.
 /// - `prof`: A ProfileScope to provide more detailled information about the time it takes to commit a result.
 ///
 /// # Errors
@@ -1562,6 +2249,7 @@ async fn commit_result(
     worker_cfg: &WorkerConfig,
     name: impl AsRef<str>,
     data_name: impl AsRef<str>,
+    provenance: Option<Provenance>,
     prof: ProfileScopeHandle<'_>,
 ) -> Result<(), CommitError> {
     let name: &str = name.as_ref();
@@ -1572,7 +2260,7 @@ async fn commit_result(
 
     // Step 1: Check if the dataset already exists (locally)
     let data_path: &Path = &worker_cfg.paths.data;
-    let info: Option<AssetInfo> = {
+    let info: Option<(AssetInfo, PathBuf)> = {
         let _reg = prof.time("Local registry scan");
 
         // Get the entries in the dataset directory
@@ -1584,7 +2272,7 @@ async fn commit_result(
         };
 
         // Iterate through them
-        let mut found_info: Option<AssetInfo> = None;
+        let mut found_info: Option<(AssetInfo, PathBuf)> = None;
         let mut i: usize = 0;
         #[allow(irrefutable_let_patterns)]
         while let entry = entries.next_entry().await {
@@ -1628,11 +2316,12 @@ async fn commit_result(
                             *path = entry_path.join(&path);
                         }
                     },
+                    AccessKind::S3 { .. } | AccessKind::Url { .. } => {},
                 }
 
                 // Keep it if it has the target name
                 if info.name == data_name {
-                    found_info = Some(info);
+                    found_info = Some((info, info_path));
                     break;
                 }
             }
@@ -1650,7 +2339,7 @@ async fn commit_result(
     // Step 2: Match on whether it already exists or not and copy the file
     let copy = prof.time("Data copying");
     let results_path: &Path = &worker_cfg.paths.results;
-    if let Some(info) = info {
+    if let Some((info, info_path)) = info {
         debug!("Dataset '{}' already exists; overwriting file...", data_name);
 
         // Copy the source to the target destination (file, in this case)
@@ -1676,6 +2365,27 @@ async fn commit_result(
                 if let Err(err) = copy_dir_recursively_async(results_path.join(name), data_path).await {
                     return Err(CommitError::DataCopyError { err });
                 };
+
+                // The dataset now has a new origin, so update that (from the file as-is, to not write the canonicalized path)
+                let mut info: AssetInfo = match AssetInfo::from_path(&info_path) {
+                    Ok(info) => info,
+                    Err(err) => {
+                        return Err(CommitError::AssetInfoReadError { path: info_path, err });
+                    },
+                };
+                info.provenance = provenance;
+                let sinfo: String = match serde_yaml::to_string(&info) {
+                    Ok(sinfo) => sinfo,
+                    Err(err) => {
+                        return Err(CommitError::DataInfoSerializeError { err });
+                    },
+                };
+                if let Err(err) = tfs::write(&info_path, sinfo).await {
+                    return Err(CommitError::DataInfoWriteError { path: info_path, err });
+                }
+            },
+            AccessKind::S3 { .. } | AccessKind::Url { .. } => {
+                return Err(CommitError::RemoteDataset { name: data_name.into() });
             },
         }
     } else {
@@ -1703,6 +2413,11 @@ async fn commit_result(
             owners: None,      // TODO: Merge parent datasets??
             description: None, // TODO: Add parents & algorithm in description??
             created: Utc::now(),
+            schema: None,
+            tags: HashSet::new(),
+            residency: HashSet::new(),
+            immutable: false,
+            provenance,
 
             access: AccessKind::File { path: dir.join("data") },
         };
@@ -1738,6 +2453,37 @@ async fn commit_result(
 
 
 /***** HELPERS *****/
+/// Returns the most recent version of this worker's part of the watched `node.yml` file.
+///
+/// # Arguments
+/// - `node_config`: The [`InfoWatcher`] that keeps the `node.yml` file up-to-date.
+///
+/// # Returns
+/// A copy of the [`WorkerConfig`] in the file's current version.
+///
+/// # Errors
+/// This function errors with an internal [`Status`] if the file has been changed to describe a non-worker node.
+fn current_worker_config(node_config: &InfoWatcher<NodeConfig>) -> Result<WorkerConfig, Status> {
+    match node_config.current().node.try_worker() {
+        Some(worker) => Ok(worker.clone()),
+        None => {
+            error!("Provided a non-worker `node.yml` file '{}'; please change to include worker services", node_config.path().display());
+            Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")))
+        },
+    }
+}
+
+/// Builds the registry caches for all of the use-cases in the given worker config.
+///
+/// # Arguments
+/// - `worker`: The [`WorkerConfig`] that lists the use-cases and their central registries.
+///
+/// # Returns
+/// A map of use-case IDs to a (fresh) [`DomainRegistryCache`] for that use-case.
+fn registry_caches(worker: &WorkerConfig) -> Arc<HashMap<String, DomainRegistryCache>> {
+    Arc::new(worker.usecases.iter().map(|(usecase, reg)| (usecase.clone(), DomainRegistryCache::new(reg.api.clone()))).collect())
+}
+
 /// Abstracts over either a workflow validation request ([`CheckWorkflowRequest`]) or task validation request ([`CheckTaskRequest`]).
 enum CheckRequest {
     /// It's a workflow validation request
@@ -1754,57 +2500,143 @@ enum CheckRequest {
 /// Defines a server for incoming worker requests.
 #[derive(Clone, Debug)]
 pub struct WorkerServer {
-    /// The path to the node config file that we store.
-    node_config_path: PathBuf,
+    /// The node config file, which is reloaded whenever it changes on disk.
+    node_config:     InfoWatcher<NodeConfig>,
     /// Whether to remove containers after execution or not (but negated).
-    keep_containers:  bool,
+    keep_containers: bool,
 
     /// The proxy client to connect to the proxy service with.
-    proxy:      Arc<ProxyClient>,
+    proxy: Arc<ProxyClient>,
     /// The cache that is responsible for learning location ID -> registry mappings.
     ///
-    /// They are mapped by use-case ID.
-    registries: Arc<HashMap<String, DomainRegistryCache>>,
+    /// They are mapped by use-case ID, and rebuilt whenever the use-cases in the node config change.
+    registries: Arc<RwLock<Arc<HashMap<String, DomainRegistryCache>>>>,
+    /// Keeps track of the datasets we transfer ahead of time, if enabled.
+    prefetcher: Option<Arc<Prefetcher>>,
+    /// Limits how many tasks may run simultaneously. Tasks that cannot acquire a slot are queued until one frees up.
+    task_slots: Arc<Semaphore>,
+    /// Remembers the checker's verdicts, such that repeatedly executed workflows need not be reasoned about again.
+    verdicts: Arc<VerdictCache>,
+    /// Publishes audit events about the tasks we execute.
+    events: EventPublisher,
+    /// Keeps idle containers around to execute tasks in, if enabled.
+    pool: Option<Arc<ContainerPool>>,
 }
 
 impl WorkerServer {
     /// Constructor for the JobHandler.
     ///
     /// # Arguments
-    /// - `node_config_path`: The path to the `node.yml` file that describes this node's environment.
+    /// - `node_config`: The watched `node.yml` file that describes this node's environment.
     /// - `keep_containers`: If true, then we will not remove containers after execution (useful for debugging).
+    /// - `prefetch`: If true, then we will start transferring the datasets of planned tasks as soon as a plan arrives instead of right before they are executed.
     /// - `proxy`: The proxy client to connect to the proxy service with.
+    /// - `events`: The [`EventPublisher`] to publish audit events about executed tasks with.
     ///
     /// # Returns
     /// A new JobHandler instance.
     ///
     /// # Errors
-    /// This function could error if it failed to load the node config file at `node_config_path`.
-    #[inline]
-    pub fn new(node_config_path: impl Into<PathBuf>, keep_containers: bool, proxy: Arc<ProxyClient>) -> Result<Self, Error> {
+    /// This function could error if the node config file does not describe a worker node.
+    pub fn new(
+        node_config: InfoWatcher<NodeConfig>,
+        keep_containers: bool,
+        prefetch: bool,
+        proxy: Arc<ProxyClient>,
+        events: EventPublisher,
+    ) -> Result<Self, Error> {
         // Read the node config to construct a map of caches
-        let node_config_path: PathBuf = node_config_path.into();
-        let node: NodeConfig = match NodeConfig::from_path(&node_config_path) {
-            Ok(node) => node,
-            Err(err) => return Err(Error::NodeConfigLoad { path: node_config_path, err }),
-        };
-        let worker: WorkerConfig = match node.node {
-            NodeSpecificConfig::Worker(worker) => worker,
+        let worker: WorkerConfig = match &node_config.current().node {
+            NodeSpecificConfig::Worker(worker) => worker.clone(),
             kind => {
                 return Err(Error::NodeConfigWrongKind {
-                    path:     node_config_path,
+                    path:     node_config.path().into(),
                     got:      kind.variant().to_string(),
                     expected: "Worker".into(),
                 });
             },
         };
 
-        // Build a map to do the thing
-        let registries: HashMap<String, DomainRegistryCache> =
-            worker.usecases.into_iter().map(|(usecase, reg)| (usecase, DomainRegistryCache::new(reg.api))).collect();
+        // Prepare the slots for running tasks
+        let max_tasks: usize = worker.max_concurrent_tasks();
+        debug!("Allowing at most {max_tasks} task(s) to run simultaneously");
+
+        // Prepare the pool of idle containers, if any, of which the workspaces live next to the temporary results
+        let pool: Option<Arc<ContainerPool>> = worker.container_pool.map(|config| {
+            debug!("Keeping at most {} idle container(s) per package", config.max_idle);
+            Arc::new(ContainerPool::new(config, worker.paths.temp_results.join(POOL_DIR)))
+        });
+
+        // Build a map to do the thing, which we rebuild whenever the use-cases change
+        let registries: Arc<RwLock<Arc<HashMap<String, DomainRegistryCache>>>> = Arc::new(RwLock::new(registry_caches(&worker)));
+        let mut watcher: InfoWatcher<NodeConfig> = node_config.clone();
+        let caches: Arc<RwLock<Arc<HashMap<String, DomainRegistryCache>>>> = registries.clone();
+        let apis = |worker: &WorkerConfig| -> HashMap<String, String> {
+            worker.usecases.iter().map(|(usecase, reg)| (usecase.clone(), reg.api.to_string())).collect()
+        };
+        let mut usecases: HashMap<String, String> = apis(&worker);
+        tokio::spawn(async move {
+            while let Some(config) = watcher.changed().await {
+                if let Some(worker) = config.node.try_worker() {
+                    if apis(worker) != usecases {
+                        info!("Use-cases in '{}' have changed; rebuilding registry caches", watcher.path().display());
+                        *caches.write().unwrap() = registry_caches(worker);
+                        usecases = apis(worker);
+                    }
+                }
+            }
+        });
 
         // OK, return self
-        Ok(Self { node_config_path, keep_containers, proxy, registries: Arc::new(registries) })
+        Ok(Self {
+            node_config,
+            keep_containers,
+            proxy,
+            registries,
+            prefetcher: if prefetch { Some(Arc::new(Prefetcher::new())) } else { None },
+            task_slots: Arc::new(Semaphore::new(max_tasks)),
+            verdicts: Arc::new(VerdictCache::new()),
+            events,
+            pool,
+        })
+    }
+
+    /// Starts prefetching the datasets needed by the tasks planned on this worker in the given workflow.
+    ///
+    /// Does nothing if prefetching is disabled. Any errors are only logged, as prefetching is merely an optimization.
+    ///
+    /// # Arguments
+    /// - `use_case`: A string denoting which use-case (registry) we're using.
+    /// - `workflow`: The (serialized) planned workflow to prefetch the datasets of.
+    fn start_prefetch(&self, use_case: &str, workflow: &str) {
+        let prefetcher: &Prefetcher = match &self.prefetcher {
+            Some(prefetcher) => prefetcher,
+            None => return,
+        };
+
+        // Get the most recent worker config
+        let worker: WorkerConfig = match self.node_config.current().node.try_worker() {
+            Some(worker) => worker.clone(),
+            None => {
+                warn!("Provided a non-worker `node.yml`; not prefetching any datasets");
+                return;
+            },
+        };
+
+        // Parse the workflow
+        let workflow: Workflow = match serde_json::from_str(workflow) {
+            Ok(workflow) => workflow,
+            Err(err) => {
+                warn!("{}", trace!(("Could not parse planned workflow; not prefetching any datasets"), err));
+                return;
+            },
+        };
+
+        // Start the transfers
+        let started: usize = prefetcher.prefetch(&worker, &self.registries.read().unwrap().clone(), &self.proxy, use_case, &workflow);
+        if started > 0 {
+            info!("Prefetching {started} dataset(s) for workflow '{}'", workflow.id);
+        }
     }
 }
 
@@ -1814,39 +2646,47 @@ impl JobService for WorkerServer {
 
     async fn check_workflow(&self, request: Request<CheckWorkflowRequest>) -> Result<Response<CheckReply>, Status> {
         info!("Receiving check request for workflow validity...");
+        let _span: Span = Span::child("brane-job check_workflow", &TraceContext::extract_grpc(&request).unwrap_or_default());
 
         // Pass to the abstracted version
-        check_workflow_or_task(&self.node_config_path, CheckRequest::Workflow(request.into_inner())).await
+        let request: CheckWorkflowRequest = request.into_inner();
+        let (use_case, workflow): (String, String) = (request.use_case.clone(), request.workflow.clone());
+        let check = metrics::POLICY_CHECK.with_label_values(&["workflow"]).start_timer();
+        let res: Response<CheckReply> = check_workflow_or_task(&self.node_config, &self.verdicts, CheckRequest::Workflow(request)).await?;
+        check.observe_duration();
+
+        // If we're OK with the plan, then we can already start transferring the datasets it needs
+        if res.get_ref().verdict {
+            self.start_prefetch(&use_case, &workflow);
+        }
+        Ok(res)
     }
 
     async fn check_task(&self, request: Request<CheckTaskRequest>) -> Result<Response<CheckReply>, Status> {
         info!("Receiving check request for task validity...");
+        let _span: Span = Span::child("brane-job check_task", &TraceContext::extract_grpc(&request).unwrap_or_default());
 
         // Pass to the abstracted version
-        check_workflow_or_task(&self.node_config_path, CheckRequest::Task(request.into_inner())).await
+        let _check = metrics::POLICY_CHECK.with_label_values(&["task"]).start_timer();
+        check_workflow_or_task(&self.node_config, &self.verdicts, CheckRequest::Task(request.into_inner())).await
     }
 
     async fn preprocess(&self, request: Request<PreprocessRequest>) -> Result<Response<PreprocessReply>, Status> {
+        // Inject faults if in chaos mode
+        chaos::delay().await;
+        if chaos::should_drop() {
+            return Err(ErrorCode::Unavailable.attach(Status::unavailable("Request dropped (chaos mode)")));
+        }
+
         // let PreprocessRequest { use_case, kind, workflow, pc } = request.into_inner();
+        let trace: Option<TraceContext> = TraceContext::extract_grpc(&request);
         let req: PreprocessRequest = request.into_inner();
         println!("{req:?}");
         let PreprocessRequest { use_case, kind, workflow, pc } = req;
         debug!("Receiving preprocess request for use-case '{use_case}'");
 
         // Load the location ID from the node config
-        let location_id: String = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(node_config) => match node_config.node.try_into_worker() {
-                Some(node) => node.name,
-                None => {
-                    error!("Provided a non-worker `node.yml` file; please change to include worker services");
-                    return Err(Status::internal("An internal error occurred"));
-                },
-            },
-            Err(err) => {
-                error!("{}", trace!(("Could not load `node.yml` file '{}'", self.node_config_path.display()), err));
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
+        let location_id: String = current_worker_config(&self.node_config)?.name;
 
         // Do the profiling (F the first function)
         let report = ProfileReport::auto_reporting_file("brane-job WorkerServer::preprocess", format!("brane-job_{location_id}_preprocess"));
@@ -1860,27 +2700,12 @@ impl JobService for WorkerServer {
             Some(dataname) => dataname.into(),
             None => {
                 error!("Failed to parse dataname in incoming request");
-                return Err(Status::invalid_argument("Invalid request: could not parse dataname".to_string()));
+                return Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument("Invalid request: could not parse dataname".to_string())));
             },
         };
 
-        // Load the node config file
-        let disk = report.time("File loading");
-        let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(config) => config,
-            Err(err) => {
-                error!("{}", err.trace());
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
-        let worker: WorkerConfig = match node_config.node.try_into_worker() {
-            Some(worker) => worker,
-            None => {
-                error!("Provided a non-worker `node.yml`; please provide one for a worker node");
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
-        disk.stop();
+        // Get the most recent version of the node config
+        let worker: WorkerConfig = current_worker_config(&self.node_config)?;
 
         // Parse the workflow
         let workflow: Workflow = match report.time_func("Workflow deserialization", || {
@@ -1890,46 +2715,76 @@ impl JobService for WorkerServer {
             Ok(wf) => wf,
             Err(err) => {
                 debug!("{}", trace!(("Incoming workflow couldn't be deserialized"), err));
-                return Err(Status::invalid_argument("Invalid workflow"));
+                return Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument("Invalid workflow")));
             },
         };
+        let mut span: Span = Span::child("brane-job preprocess", &trace.unwrap_or_else(|| TraceContext::for_workflow(&workflow.id)));
+        span.set_attribute("brane.location", &location_id);
 
         // Resolve the use-case
-        let registries: &DomainRegistryCache = match self.registries.get(&use_case) {
+        let caches: Arc<HashMap<String, DomainRegistryCache>> = self.registries.read().unwrap().clone();
+        let registries: &DomainRegistryCache = match caches.get(&use_case) {
             Some(regs) => regs,
             None => {
                 debug!("Received unknown use-case identifier '{use_case}'");
-                return Err(Status::invalid_argument("Invalid use-case"));
+                return Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument("Invalid use-case")));
             },
         };
 
-        // Run the function that way
+        // Use the prefetched dataset if we have it
         let location: Location = kind.location;
-        let access: AccessKind = match report
-            .nest_fut("TransferTar preprocessing", |scope| {
-                preprocess_transfer_tar(
-                    registries,
-                    &worker,
-                    self.proxy.clone(),
-                    &use_case,
-                    pc.map(|pc| {
-                        ProgramCounter::new(
-                            if pc.func_id == u64::MAX { FunctionId::Main } else { FunctionId::Func(pc.func_id as usize) },
-                            pc.edge_idx as usize,
-                        )
-                    }),
-                    workflow,
-                    location,
-                    dataname,
-                    scope,
-                )
-            })
-            .await
-        {
-            Ok(access) => access,
-            Err(err) => {
-                error!("{}", err.trace());
-                return Err(Status::internal("An internal error occurred"));
+        let prefetched: Option<AccessKind> = match &self.prefetcher {
+            Some(prefetcher) => match report.time_fut("Prefetch waiting", prefetcher.claim(&use_case, &workflow.id, &location, &dataname)).await {
+                Some(Ok(access)) => {
+                    debug!("Using prefetched {} '{}'", dataname.variant(), dataname.name());
+                    Some(access)
+                },
+                // The claim already reported the error, so we just transfer it again
+                Some(Err(_)) | None => None,
+            },
+            None => None,
+        };
+
+        // Run the function that way
+        let access: AccessKind = match prefetched {
+            Some(access) => access,
+            None => match report
+                .nest_fut("TransferTar preprocessing", |scope| {
+                    preprocess_transfer_tar(
+                        registries,
+                        &worker,
+                        self.proxy.clone(),
+                        &use_case,
+                        pc.map(|pc| {
+                            ProgramCounter::new(
+                                if pc.func_id == u64::MAX { FunctionId::Main } else { FunctionId::Func(pc.func_id as usize) },
+                                pc.edge_idx as usize,
+                            )
+                        }),
+                        workflow,
+                        location,
+                        dataname,
+                        span.context(),
+                        scope,
+                    )
+                })
+                .await
+            {
+                Ok(access) => access,
+                Err(PreprocessError::PolicyDenied { denial }) => {
+                    info!("{denial}");
+                    let status: Status = Status::with_details(Code::PermissionDenied, denial.to_string(), Bytes::from(denial.to_bytes()));
+                    return Err(ErrorCode::JobPolicyDenied.attach(status));
+                },
+                Err(PreprocessError::ApprovalPending { pending }) => {
+                    info!("{pending}");
+                    let status: Status = Status::with_details(Code::FailedPrecondition, pending.to_string(), Bytes::from(pending.to_bytes()));
+                    return Err(ErrorCode::RegApprovalPending.attach(status));
+                },
+                Err(err) => {
+                    error!("{}", err.trace());
+                    return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
+                },
             },
         };
 
@@ -1939,7 +2794,7 @@ impl JobService for WorkerServer {
             Ok(saccess) => saccess,
             Err(err) => {
                 error!("{}", PreprocessError::AccessKindSerializeError { err });
-                return Err(Status::internal("An internal error occurred"));
+                return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
             },
         };
         ser.stop();
@@ -1957,23 +2812,18 @@ impl JobService for WorkerServer {
     }
 
     async fn execute(&self, request: Request<ExecuteRequest>) -> Result<Response<Self::ExecuteStream>, Status> {
-        let ExecuteRequest { use_case, workflow, call_pc, task_def, input, result, args } = request.into_inner();
+        // Inject faults if in chaos mode
+        chaos::delay().await;
+        if chaos::should_drop() {
+            return Err(ErrorCode::Unavailable.attach(Status::unavailable("Request dropped (chaos mode)")));
+        }
+
+        let trace: Option<TraceContext> = TraceContext::extract_grpc(&request);
+        let ExecuteRequest { use_case, workflow, call_pc, task_def, input, result, args, stream_output } = request.into_inner();
         debug!("Receiving execute request");
 
         // Load the location ID from the node config
-        let location_id: String = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(node_config) => match node_config.node.try_into_worker() {
-                Some(node) => node.name,
-                None => {
-                    error!("Provided a non-worker `node.yml` file; please change to include worker services");
-                    return Err(Status::internal("An internal error occurred"));
-                },
-            },
-            Err(err) => {
-                error!("{}", trace!(("Could not load `node.yml` file '{}'", self.node_config_path.display()), err));
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
+        let location_id: String = current_worker_config(&self.node_config)?.name;
 
         // Do the profiling
         let report = ProfileReport::auto_reporting_file("brane-job WorkerServer::execute", format!("brane-job_{location_id}_execute"));
@@ -1990,22 +2840,30 @@ impl JobService for WorkerServer {
             Err(err) => {
                 error!("{}", trace!(("Failed to deserialize workflow"), err));
                 debug!("Workflow:\n{}\n{}\n{}\n", (0..80).map(|_| '-').collect::<String>(), workflow, (0..80).map(|_| '-').collect::<String>());
-                if let Err(err) = tx.send(Err(Status::invalid_argument(format!("{}", trace!(("Failed to deserialize workflow"), err))))).await {
+                if let Err(err) = tx
+                    .send(Err(
+                        ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!("{}", trace!(("Failed to deserialize workflow"), err))))
+                    ))
+                    .await
+                {
                     error!("{}", err.trace());
                 }
                 return Ok(Response::new(ReceiverStream::new(rx)));
             },
         };
+        report.set_trace_id(workflow.id.clone());
+        let mut span: Span = Span::child("brane-job execute", &trace.unwrap_or_else(|| TraceContext::for_workflow(&workflow.id)));
+        span.set_attribute("brane.location", &location_id);
 
         // Fetch the task ID
         if task_def as usize >= workflow.table.tasks.len() {
             error!("Given task ID '{}' is out-of-bounds for workflow with {} tasks", task_def, workflow.table.tasks.len());
             if let Err(err) = tx
-                .send(Err(Status::invalid_argument(format!(
+                .send(Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!(
                     "Given task ID '{}' is out-of-bounds for workflow with {} tasks",
                     task_def,
                     workflow.table.tasks.len()
-                ))))
+                )))))
                 .await
             {
                 error!("{}", err.trace());
@@ -2017,10 +2875,10 @@ impl JobService for WorkerServer {
             _ => {
                 error!("A task of type '{}' is not yet supported", workflow.table.tasks[task_def as usize].variant());
                 if let Err(err) = tx
-                    .send(Err(Status::invalid_argument(format!(
+                    .send(Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!(
                         "A task of type '{}' is not yet supported",
                         workflow.table.tasks[task_def as usize].variant()
-                    ))))
+                    )))))
                     .await
                 {
                     error!("{}", err.trace());
@@ -2034,7 +2892,12 @@ impl JobService for WorkerServer {
             Ok(input) => input,
             Err(err) => {
                 error!("{}", trace!(("Failed to deserialize input '{}'", input), err));
-                if let Err(err) = tx.send(Err(Status::invalid_argument(format!("Failed to deserialize input '{}': {}", input, err)))).await {
+                if let Err(err) = tx
+                    .send(Err(
+                        ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!("Failed to deserialize input '{}': {}", input, err)))
+                    ))
+                    .await
+                {
                     error!("{}", err.trace());
                 }
                 return Ok(Response::new(ReceiverStream::new(rx)));
@@ -2046,7 +2909,12 @@ impl JobService for WorkerServer {
             Ok(args) => args,
             Err(err) => {
                 error!("{}", trace!(("Failed to deserialize arguments '{args}'"), err));
-                if let Err(err) = tx.send(Err(Status::invalid_argument(format!("Failed to deserialize arguments '{args}': {err}")))).await {
+                if let Err(err) = tx
+                    .send(Err(
+                        ErrorCode::JobInvalidRequest.attach(Status::invalid_argument(format!("Failed to deserialize arguments '{args}': {err}")))
+                    ))
+                    .await
+                {
                     error!("{}", err.trace());
                 }
                 return Ok(Response::new(ReceiverStream::new(rx)));
@@ -2054,36 +2922,21 @@ impl JobService for WorkerServer {
         };
         par.stop();
 
-        // Load the node config file
-        let disk = overhead.time("File loading");
-        let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(config) => config,
-            Err(err) => {
-                error!("{}", err.trace());
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
-        let worker: WorkerConfig = match node_config.node.try_into_worker() {
-            Some(worker) => worker,
-            None => {
-                error!("Provided a non-worker `node.yml`; please provide one for a worker node");
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
-        disk.stop();
+        // Get the most recent version of the node config
+        let worker: WorkerConfig = current_worker_config(&self.node_config)?;
 
         // Fetch the use-case's API address
         let api: &Address = match worker.usecases.get(&use_case) {
             Some(usecase) => &usecase.api,
             None => {
                 debug!("Received unknown use-case identifier '{use_case}'");
-                return Err(Status::invalid_argument("Invalid use-case"));
+                return Err(ErrorCode::JobInvalidRequest.attach(Status::invalid_argument("Invalid use-case")));
             },
         };
 
         // Collect some request data into ControlNodeInfo's and TaskInfo's.
         let cinfo: ControlNodeInfo = ControlNodeInfo::new(api.to_string());
-        let tinfo: TaskInfo = TaskInfo::new(
+        let mut tinfo: TaskInfo = TaskInfo::new(
             task.function.name.clone(),
             ProgramCounter::new(
                 if call_pc.func_id == u64::MAX { FunctionId::Main } else { FunctionId::Func(call_pc.func_id as usize) },
@@ -2096,15 +2949,80 @@ impl JobService for WorkerServer {
             args,
             task.requirements.clone(),
         );
+        let edges: Option<&Vec<Edge>> = if tinfo.pc.func_id.is_main() { Some(&*workflow.graph) } else { workflow.funcs.get(&tinfo.pc.func_id.id()) };
+        if let Some(Edge::Node { cache, .. }) = edges.and_then(|edges| edges.get(tinfo.pc.edge_idx)) {
+            tinfo.cache = *cache;
+        }
         total.stop();
         overhead.finish();
 
         // Now move the rest to a separate task so we can return the start of the stream
         let keep_containers: bool = self.keep_containers;
+        let stream_output: bool = stream_output.unwrap_or(false);
         let proxy: Arc<ProxyClient> = self.proxy.clone();
+        let verdicts: Arc<VerdictCache> = self.verdicts.clone();
+        let task_slots: Arc<Semaphore> = self.task_slots.clone();
+        let events: EventPublisher = self.events.clone();
+        let pool: Option<Arc<ContainerPool>> = self.pool.clone();
+        span.set_attribute("brane.task", &tinfo.name);
         tokio::spawn(async move {
             let worker: WorkerConfig = worker;
-            report.nest_fut("execution", |scope| execute_task(&worker, proxy, tx, &use_case, workflow, cinfo, tinfo, keep_containers, scope)).await
+            let span: Span = span;
+
+            // Let the client know we're alive while the task is queued or running
+            let heartbeat: JoinHandle<()> = spawn_heartbeat(tx.clone());
+
+            // Wait until there's room for another task
+            if task_slots.available_permits() == 0 {
+                info!("All task slots are occupied; queueing task '{}'", tinfo.name);
+            }
+            metrics::TASKS_QUEUED.inc();
+            let slot: Result<OwnedSemaphorePermit, AcquireError> = report.time_fut("queueing", task_slots.acquire_owned()).await;
+            metrics::TASKS_QUEUED.dec();
+            let _slot: OwnedSemaphorePermit = match slot {
+                Ok(slot) => slot,
+                Err(err) => {
+                    error!("{}", trace!(("Failed to acquire a slot for task '{}'", tinfo.name), err));
+                    if let Err(err) = tx.send(Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")))).await {
+                        error!("{}", err.trace());
+                    }
+                    heartbeat.abort();
+                    return Ok(());
+                },
+            };
+
+            // Run it
+            let (workflow_id, task_name, pc): (String, String, String) = (workflow.id.clone(), tinfo.name.clone(), tinfo.pc.to_string());
+            let user: Option<String> = (*workflow.user).clone();
+            events.publish(EventKind::TaskStarted { workflow: workflow_id.clone(), task: task_name.clone(), pc: pc.clone(), user: user.clone() });
+            metrics::TASKS_RUNNING.inc();
+            let duration = metrics::TASK_DURATION.start_timer();
+            let res: Result<(), ExecuteError> = report
+                .nest_fut("execution", |scope| {
+                    telemetry::in_trace(
+                        *span.context(),
+                        execute_task(
+                            &worker,
+                            &verdicts,
+                            proxy,
+                            tx,
+                            &use_case,
+                            workflow,
+                            cinfo,
+                            tinfo,
+                            keep_containers,
+                            stream_output,
+                            pool.as_deref(),
+                            scope,
+                        ),
+                    )
+                })
+                .await;
+            duration.observe_duration();
+            metrics::TASKS_RUNNING.dec();
+            heartbeat.abort();
+            events.publish(EventKind::TaskFinished { workflow: workflow_id, task: task_name, pc, user, success: res.is_ok() });
+            res
         });
 
         // Return the stream so the user can get updates
@@ -2112,50 +3030,42 @@ impl JobService for WorkerServer {
     }
 
     async fn commit(&self, request: Request<CommitRequest>) -> Result<Response<CommitReply>, Status> {
+        // Inject faults if in chaos mode
+        chaos::delay().await;
+        if chaos::should_drop() {
+            return Err(ErrorCode::Unavailable.attach(Status::unavailable("Request dropped (chaos mode)")));
+        }
+
+        let _span: Span = Span::child("brane-job commit", &TraceContext::extract_grpc(&request).unwrap_or_default());
         let request = request.into_inner();
         debug!("Receiving commit request");
 
         // Load the location ID from the node config
-        let location_id: String = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(node_config) => match node_config.node.try_into_worker() {
-                Some(node) => node.name,
-                None => {
-                    error!("Provided a non-worker `node.yml` file; please change to include worker services");
-                    return Err(Status::internal("An internal error occurred"));
-                },
-            },
-            Err(err) => {
-                error!("{}", trace!(("Could not load `node.yml` file '{}'", self.node_config_path.display()), err));
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
+        let location_id: String = current_worker_config(&self.node_config)?.name;
 
         // Do the profiling
         let report = ProfileReport::auto_reporting_file("brane-job WorkerServer::commit", format!("brane-job_{location_id}_commit"));
         let _guard = report.time("Total");
 
-        // Load the node config file
-        let disk = report.time("File loading");
-        let node_config: NodeConfig = match NodeConfig::from_path(&self.node_config_path) {
-            Ok(config) => config,
-            Err(err) => {
-                error!("{}", err.trace());
-                return Err(Status::internal("An internal error occurred"));
-            },
-        };
-        let worker: WorkerConfig = match node_config.node.try_into_worker() {
-            Some(worker) => worker,
-            None => {
-                error!("Provided a non-worker `node.yml`; please provide one for a worker node");
-                return Err(Status::internal("An internal error occurred"));
+        // Get the most recent version of the node config
+        let worker: WorkerConfig = current_worker_config(&self.node_config)?;
+
+        // Parse where the result came from, if the driver told us
+        let provenance: Option<Provenance> = match request.provenance.as_deref().map(serde_json::from_str::<Provenance>) {
+            Some(Ok(provenance)) => Some(provenance),
+            Some(Err(err)) => {
+                warn!("{}", trace!(("Failed to parse provenance of intermediate result '{}'; committing without", request.result_name), err));
+                None
             },
+            None => None,
         };
-        disk.stop();
 
         // Run the function
-        if let Err(err) = report.nest_fut("committing", |scope| commit_result(&worker, &request.result_name, &request.data_name, scope)).await {
+        if let Err(err) =
+            report.nest_fut("committing", |scope| commit_result(&worker, &request.result_name, &request.data_name, provenance, scope)).await
+        {
             error!("{}", err.trace());
-            return Err(Status::internal("An internal error occurred"));
+            return Err(ErrorCode::Internal.attach(Status::internal("An internal error occurred")));
         }
 
         // Be done without any error
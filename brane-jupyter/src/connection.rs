@@ -0,0 +1,94 @@
+//  CONNECTION.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:42
+//  Last edited:
+//    15 Oct 2026, 23:12:42
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the connection file with which Jupyter launches a kernel,
+//!   which describes where to bind the kernel's sockets and how to sign
+//!   its messages.
+//
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub use crate::errors::ConnectionError as Error;
+
+
+/***** LIBRARY *****/
+/// Defines the contents of the connection file that Jupyter passes to the kernel (typically as `{connection_file}` in the kernel's `argv`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConnectionInfo {
+    /// The ZeroMQ transport to use (typically `tcp`).
+    pub transport: String,
+    /// The IP address to bind the sockets on.
+    pub ip: String,
+
+    /// The port of the socket on which requests are received.
+    pub shell_port:   u16,
+    /// The port of the socket on which high-priority requests (e.g., shutdowns) are received.
+    pub control_port: u16,
+    /// The port of the socket on which outputs and status updates are broadcasted.
+    pub iopub_port:   u16,
+    /// The port of the socket on which the kernel may ask for input from the frontend.
+    pub stdin_port:   u16,
+    /// The port of the socket that echoes heartbeats.
+    pub hb_port:      u16,
+
+    /// The key with which to sign messages. If empty, messages are not signed.
+    pub key: String,
+    /// The scheme with which to sign messages.
+    pub signature_scheme: String,
+    /// The name of the kernel, if Jupyter told us.
+    #[serde(default)]
+    pub kernel_name: Option<String>,
+}
+
+impl ConnectionInfo {
+    /// Reads the ConnectionInfo from the given file.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the connection file to read.
+    ///
+    /// # Returns
+    /// A new ConnectionInfo with the file's contents.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the file, or if it asks for a signature scheme other than `hmac-sha256`.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Read the file
+        let raw: String = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) => return Err(Error::FileReadError { path: path.into(), err }),
+        };
+        let info: Self = match serde_json::from_str(&raw) {
+            Ok(info) => info,
+            Err(err) => return Err(Error::FileParseError { path: path.into(), err }),
+        };
+
+        // Assert we can sign with it
+        if !info.key.is_empty() && info.signature_scheme != "hmac-sha256" {
+            return Err(Error::UnsupportedScheme { path: path.into(), scheme: info.signature_scheme });
+        }
+        Ok(info)
+    }
+
+    /// Returns the ZeroMQ endpoint for the given port.
+    ///
+    /// # Arguments
+    /// - `port`: One of the ports in this ConnectionInfo.
+    ///
+    /// # Returns
+    /// An endpoint like `tcp://127.0.0.1:5555`.
+    #[inline]
+    pub fn endpoint(&self, port: u16) -> String { format!("{}://{}:{}", self.transport, self.ip, port) }
+}
@@ -0,0 +1,187 @@
+//  DISPLAY.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Renders the [`FullValue`]s returned by workflows as Jupyter MIME
+//!   bundles, such that notebooks can show them as rich output (e.g.,
+//!   arrays of structs as tables).
+//
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use brane_exe::FullValue;
+use serde_json::{Map, Value};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// Test the HTML rendering of the various kinds of values
+    #[test]
+    fn test_html() {
+        let point = |x: i64, name: &str| {
+            FullValue::Instance(
+                "Point".into(),
+                HashMap::from([("x".to_string(), FullValue::Integer(x)), ("name".to_string(), FullValue::String(name.into()))]),
+            )
+        };
+
+        // Scalars are escaped
+        assert_eq!(html(&FullValue::String("<b>".into())), "<code>&lt;b&gt;</code>");
+        // Instances are tables of their fields, sorted by name
+        assert_eq!(
+            html(&point(1, "a")),
+            "<table><tr><th \
+             colspan=\"2\">Point</th></tr><tr><td>name</td><td><code>a</code></td></tr><tr><td>x</td><td><code>1</code></td></tr></table>"
+        );
+        // Arrays of instances are tables with a row per instance
+        assert_eq!(
+            html(&FullValue::Array(vec![point(1, "a"), point(2, "b")])),
+            "<table><tr><th>name</th><th>x</th></tr><tr><td><code>a</code></td><td><code>1</code></td></tr><tr><td><code>b</code></td><td><code>2</\
+             code></td></tr></table>"
+        );
+        // Other arrays are lists
+        assert_eq!(html(&FullValue::Array(vec![FullValue::Boolean(true)])), "<ol start=\"0\"><li><code>true</code></li></ol>");
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes the given text for use in HTML.
+///
+/// # Arguments
+/// - `text`: The text to escape.
+///
+/// # Returns
+/// The escaped text.
+fn escape(text: &str) -> String {
+    let mut res: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Renders a value as HTML.
+///
+/// # Arguments
+/// - `value`: The [`FullValue`] to render.
+///
+/// # Returns
+/// The HTML, which is a table for instances and arrays of instances of the same class, a list for other arrays and inline code for anything else.
+fn html(value: &FullValue) -> String {
+    let mut res: String = String::new();
+    match value {
+        FullValue::Instance(name, fields) => {
+            write!(&mut res, "<table><tr><th colspan=\"2\">{}</th></tr>", escape(name)).unwrap();
+            let names: BTreeSet<&String> = fields.keys().collect();
+            for field in names {
+                write!(&mut res, "<tr><td>{}</td><td>{}</td></tr>", escape(field), html(&fields[field])).unwrap();
+            }
+            res.push_str("</table>");
+        },
+
+        FullValue::Array(values) => {
+            // Show the array as a table if all elements are instances of the same class
+            let class: Option<&String> = match values.first() {
+                Some(FullValue::Instance(name, _)) => Some(name),
+                _ => None,
+            };
+            let is_table: bool = class.is_some() && values.iter().all(|v| matches!(v, FullValue::Instance(name, _) if Some(name) == class));
+            if is_table {
+                let columns: BTreeSet<&String> =
+                    values.iter().flat_map(|v| if let FullValue::Instance(_, fields) = v { fields.keys().collect() } else { vec![] }).collect();
+                res.push_str("<table><tr>");
+                for column in &columns {
+                    write!(&mut res, "<th>{}</th>", escape(column)).unwrap();
+                }
+                res.push_str("</tr>");
+                for value in values {
+                    if let FullValue::Instance(_, fields) = value {
+                        res.push_str("<tr>");
+                        for column in &columns {
+                            write!(&mut res, "<td>{}</td>", fields.get(*column).map(html).unwrap_or_default()).unwrap();
+                        }
+                        res.push_str("</tr>");
+                    }
+                }
+                res.push_str("</table>");
+            } else {
+                // BraneScript arrays are zero-indexed, so number them that way
+                res.push_str("<ol start=\"0\">");
+                for value in values {
+                    write!(&mut res, "<li>{}</li>", html(value)).unwrap();
+                }
+                res.push_str("</ol>");
+            }
+        },
+
+        FullValue::Data(name) => write!(&mut res, "Dataset <code>{}</code>", escape(name.as_ref())).unwrap(),
+        FullValue::IntermediateResult(name) => write!(&mut res, "Intermediate result <code>{}</code>", escape(name.as_ref())).unwrap(),
+        value => write!(&mut res, "<code>{}</code>", escape(&value.to_string())).unwrap(),
+    }
+    res
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Renders a workflow result as a MIME bundle (the `data` of an `execute_result` message).
+///
+/// # Arguments
+/// - `value`: The [`FullValue`] returned by the workflow.
+/// - `location`: Where the value was downloaded to if it's a dataset, if at all.
+///
+/// # Returns
+/// A JSON object mapping MIME types to representations of the value. It always has a `text/plain` and `text/html` representation, and an
+/// `application/json` one if the value can be serialized.
+pub fn mime_bundle(value: &FullValue, location: Option<&Path>) -> Value {
+    let mut bundle: Map<String, Value> = Map::new();
+
+    // The plain representation mirrors what `brane run` prints
+    let mut plain: String = value.to_string();
+    let mut rich: String = html(value);
+    match (value, location) {
+        (FullValue::Data(_), Some(location)) => {
+            write!(&mut plain, " (available under '{}')", location.display()).unwrap();
+            write!(&mut rich, " (available under <code>{}</code>)", escape(&location.display().to_string())).unwrap();
+        },
+        (FullValue::IntermediateResult(_), _) => {
+            plain.push_str(" (intermediate results are not available locally; promote it using 'commit_result()')");
+            rich.push_str(" (intermediate results are not available locally; promote it using <code>commit_result()</code>)");
+        },
+        _ => {},
+    }
+    bundle.insert("text/plain".into(), Value::String(plain));
+    bundle.insert("text/html".into(), Value::String(rich));
+
+    // Also give the raw value, for frontends (or widgets) that want to process it
+    if let Ok(json) = serde_json::to_value(value) {
+        bundle.insert("application/json".into(), json);
+    }
+    Value::Object(bundle)
+}
@@ -0,0 +1,114 @@
+//  ERRORS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the errors that may occur in the `brane-jupyter` crate.
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
+
+
+/***** LIBRARY *****/
+/// Defines errors that relate to the connection file that Jupyter launches a kernel with.
+#[derive(Debug)]
+pub enum ConnectionError {
+    /// Failed to read the connection file.
+    FileReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the connection file.
+    FileParseError { path: PathBuf, err: serde_json::Error },
+    /// The connection file asks for a signature scheme we don't support.
+    UnsupportedScheme { path: PathBuf, scheme: String },
+}
+
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConnectionError::*;
+        match self {
+            FileReadError { path, err } => write!(f, "Failed to read connection file '{}': {}", path.display(), err),
+            FileParseError { path, err } => write!(f, "Failed to parse connection file '{}' as JSON: {}", path.display(), err),
+            UnsupportedScheme { path, scheme } => {
+                write!(f, "Connection file '{}' uses unsupported signature scheme '{}' (only 'hmac-sha256' is supported)", path.display(), scheme)
+            },
+        }
+    }
+}
+
+impl Error for ConnectionError {}
+
+
+
+/// Defines errors that relate to encoding or decoding Jupyter messages.
+#[derive(Debug)]
+pub enum MessageError {
+    /// The message did not have the delimiter that separates the identities from the rest.
+    MissingDelimiter,
+    /// The message had fewer frames after the delimiter than required.
+    MissingFrames { got: usize },
+    /// The signature of the message did not match its contents.
+    InvalidSignature,
+    /// Failed to parse one of the JSON-frames of a message.
+    FrameParseError { what: &'static str, err: serde_json::Error },
+    /// Failed to serialize one of the JSON-frames of a message.
+    FrameSerializeError { what: &'static str, err: serde_json::Error },
+}
+
+impl Display for MessageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use MessageError::*;
+        match self {
+            MissingDelimiter => write!(f, "Message has no '<IDS|MSG>' delimiter"),
+            MissingFrames { got } => write!(f, "Message has too few frames after the delimiter (got {got}, expected at least 5)"),
+            InvalidSignature => write!(f, "Message has an invalid signature"),
+            FrameParseError { what, err } => write!(f, "Failed to parse message {what}: {err}"),
+            FrameSerializeError { what, err } => write!(f, "Failed to serialize message {what}: {err}"),
+        }
+    }
+}
+
+impl Error for MessageError {}
+
+
+
+/// Defines errors that stop the kernel.
+#[derive(Debug)]
+pub enum KernelError {
+    /// Failed to bind one of the sockets.
+    SocketBindError { what: &'static str, endpoint: String, err: zeromq::ZmqError },
+    /// Failed to receive a message on one of the sockets.
+    SocketRecvError { what: &'static str, err: zeromq::ZmqError },
+    /// Failed to send a message on one of the sockets.
+    SocketSendError { what: &'static str, err: zeromq::ZmqError },
+    /// Failed to encode a message we wanted to send.
+    MessageEncodeError { msg_type: String, err: MessageError },
+
+    /// Failed to read the data index of the instance.
+    DataIndexError { endpoint: String, err: brane_tsk::errors::ApiError },
+    /// Failed to connect to the instance.
+    InstanceInitializeError { endpoint: String, err: brane_cli::errors::RunError },
+}
+
+impl Display for KernelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use KernelError::*;
+        match self {
+            SocketBindError { what, endpoint, err } => write!(f, "Failed to bind {what} socket to '{endpoint}': {err}"),
+            SocketRecvError { what, err } => write!(f, "Failed to receive message on {what} socket: {err}"),
+            SocketSendError { what, err } => write!(f, "Failed to send message on {what} socket: {err}"),
+            MessageEncodeError { msg_type, err } => write!(f, "Failed to encode '{msg_type}' message: {err}"),
+
+            DataIndexError { endpoint, err } => write!(f, "Failed to read data index from '{endpoint}': {err}"),
+            InstanceInitializeError { endpoint, err } => write!(f, "Failed to connect to instance '{endpoint}': {err}"),
+        }
+    }
+}
+
+impl Error for KernelError {}
@@ -0,0 +1,628 @@
+//  KERNEL.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    16 Oct 2026, 01:16:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the kernel itself, which binds the sockets described by
+//!   the connection file and answers the frontend's requests by compiling
+//!   and running BraneScript cells on a remote instance.
+//!
+//!   Cells share one compile state and driver session, exactly like
+//!   snippets in `brane repl --remote`.
+//
+
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use brane_ast::{compile_snippet, CompileResult, ParserOptions, Workflow};
+use brane_cli::data::download_data;
+use brane_cli::errors::RunError;
+use brane_cli::run::{initialize_instance, run_instance, InstanceVmState};
+use brane_exe::FullValue;
+use brane_tsk::api::{get_data_index, RemotePackageProvider};
+use brane_tsk::caches::PackageIndexCache;
+use log::{debug, info, warn};
+use parking_lot::{Mutex, MutexGuard};
+use serde_json::{json, Value};
+use specifications::data::{AccessKind, DataIndex};
+use specifications::package::PackageIndex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use zeromq::{PubSocket, RepSocket, RouterSocket, Socket as _, SocketRecv as _, SocketSend as _, ZmqMessage};
+
+use crate::connection::ConnectionInfo;
+use crate::display::mime_bundle;
+pub use crate::errors::KernelError as Error;
+use crate::message::{Message, Signer, PROTOCOL_VERSION};
+use crate::stream::{Iopub, StreamWriter};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Binds a socket to the given endpoint.
+///
+/// # Arguments
+/// - `socket`: The socket to bind.
+/// - `what`: The name of the socket, for debugging purposes.
+/// - `endpoint`: The endpoint to bind to.
+///
+/// # Errors
+/// This function errors if we failed to bind the socket.
+async fn bind(socket: &mut impl zeromq::Socket, what: &'static str, endpoint: String) -> Result<(), Error> {
+    debug!("Binding {} socket to '{}'...", what, endpoint);
+    match socket.bind(&endpoint).await {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::SocketBindError { what, endpoint, err }),
+    }
+}
+
+/// Echoes heartbeats until the socket fails.
+///
+/// # Arguments
+/// - `socket`: The heartbeat socket.
+async fn heartbeat(mut socket: RepSocket) {
+    loop {
+        let msg: ZmqMessage = match socket.recv().await {
+            Ok(msg) => msg,
+            Err(err) => {
+                warn!("{}", Error::SocketRecvError { what: "heartbeat", err });
+                return;
+            },
+        };
+        if let Err(err) = socket.send(msg).await {
+            warn!("{}", Error::SocketSendError { what: "heartbeat", err });
+        }
+    }
+}
+
+/// Publishes messages on the IOPub socket until all [`Iopub`] handles are dropped.
+///
+/// # Arguments
+/// - `socket`: The IOPub socket.
+/// - `queue`: The queue of messages to publish.
+/// - `signer`: The [`Signer`] to sign the messages with.
+async fn publish(mut socket: PubSocket, mut queue: UnboundedReceiver<Message>, signer: Signer) {
+    while let Some(msg) = queue.recv().await {
+        let msg_type: String = msg.header.msg_type.clone();
+        let frames = match msg.into_frames(&signer) {
+            Ok(frames) => frames,
+            Err(err) => {
+                warn!("{}", Error::MessageEncodeError { msg_type, err });
+                continue;
+            },
+        };
+        // There is always at least a delimiter, so the message is never empty
+        let msg: ZmqMessage = ZmqMessage::try_from(frames).expect("Encoded message should not be empty");
+        if let Err(err) = socket.send(msg).await {
+            warn!("{}", Error::SocketSendError { what: "IOPub", err });
+        }
+    }
+    debug!("All IOPub handles dropped; stopping");
+}
+
+/// Guesses whether the given code is a complete BraneScript snippet, for the `is_complete_request`.
+///
+/// This only looks at whether brackets are balanced (outside of strings and comments), which is what decides whether the frontend submits
+/// the cell or inserts a newline when the user presses enter.
+///
+/// # Arguments
+/// - `code`: The code to check.
+///
+/// # Returns
+/// `complete`, `incomplete` or `invalid`.
+fn is_complete(code: &str) -> &'static str {
+    let mut depth: i64 = 0;
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                // Skip the string, minding escapes
+                let mut closed: bool = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        },
+                        '"' => {
+                            closed = true;
+                            break;
+                        },
+                        _ => {},
+                    }
+                }
+                if !closed {
+                    return "incomplete";
+                }
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                // Skip the comment until the end of the line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev: char = '\0';
+                let mut closed: bool = false;
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        closed = true;
+                        break;
+                    }
+                    prev = c;
+                }
+                if !closed {
+                    return "incomplete";
+                }
+            },
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return "invalid";
+                }
+            },
+            _ => {},
+        }
+    }
+    if depth > 0 {
+        "incomplete"
+    } else {
+        "complete"
+    }
+}
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes why a cell failed, in the shape of Jupyter's `error` messages.
+#[derive(Debug)]
+struct CellError {
+    /// The name of the error.
+    ename:     &'static str,
+    /// A one-line description of the error.
+    evalue:    String,
+    /// The (possibly coloured) lines to show the user.
+    traceback: Vec<String>,
+}
+
+impl CellError {
+    /// Constructor for a CellError that only has a message.
+    ///
+    /// # Arguments
+    /// - `ename`: The name of the error.
+    /// - `evalue`: The message of the error, which is also used as traceback.
+    ///
+    /// # Returns
+    /// A new CellError.
+    #[inline]
+    fn new(ename: &'static str, evalue: String) -> Self { Self { ename, traceback: vec![evalue.clone()], evalue } }
+
+    /// Returns the contents of the `error` message for this CellError.
+    #[inline]
+    fn content(&self) -> Value { json!({ "ename": self.ename, "evalue": self.evalue, "traceback": self.traceback }) }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how the kernel connects to a Brane instance.
+#[derive(Clone, Debug)]
+pub struct KernelOptions {
+    /// The address of the instance's `brane-api` service.
+    pub api_endpoint: String,
+    /// The address of the instance's `brane-drv` service.
+    pub drv_endpoint: String,
+    /// The directory with the certificates to download datasets with.
+    pub certs_dir: PathBuf,
+    /// The directory to download datasets returned by cells to.
+    pub data_dir: PathBuf,
+    /// The user to run the workflows as, if any.
+    pub user: Option<String>,
+    /// The API token with which to authenticate to the driver, if any.
+    pub token: Option<String>,
+}
+
+
+
+/// Implements a Jupyter kernel that runs BraneScript cells on a Brane instance.
+#[derive(Debug)]
+pub struct Kernel {
+    /// Where to bind the sockets and how to sign messages.
+    info: ConnectionInfo,
+    /// Which instance to run the cells on.
+    opts: KernelOptions,
+
+    /// Signs and verifies messages.
+    signer: Signer,
+    /// The session of the kernel, which it sends with every message.
+    session: String,
+    /// The number of cells executed so far.
+    execution_count: u64,
+}
+
+impl Kernel {
+    /// Constructor for the Kernel.
+    ///
+    /// # Arguments
+    /// - `info`: The [`ConnectionInfo`] that Jupyter launched the kernel with.
+    /// - `opts`: The [`KernelOptions`] that describe which instance to run cells on.
+    ///
+    /// # Returns
+    /// A new Kernel. It does nothing until [`Kernel::run()`] is called.
+    #[inline]
+    pub fn new(info: ConnectionInfo, opts: KernelOptions) -> Self {
+        let signer: Signer = Signer::new(&info.key);
+        Self { info, opts, signer, session: uuid::Uuid::new_v4().to_string(), execution_count: 0 }
+    }
+
+    /// Runs the kernel until the frontend asks it to shut down.
+    ///
+    /// # Errors
+    /// This function errors if we failed to bind or use any of the sockets, or if we failed to connect to the instance. Errors in cells
+    /// are reported to the frontend instead.
+    pub async fn run(mut self) -> Result<(), Error> {
+        info!("Starting BraneScript kernel v{} for instance '{}'...", env!("CARGO_PKG_VERSION"), self.opts.api_endpoint);
+
+        // Bind the sockets
+        let mut shell: RouterSocket = RouterSocket::new();
+        bind(&mut shell, "shell", self.info.endpoint(self.info.shell_port)).await?;
+        let mut control: RouterSocket = RouterSocket::new();
+        bind(&mut control, "control", self.info.endpoint(self.info.control_port)).await?;
+        // We never ask for input, but the frontend expects to be able to connect to it
+        let mut stdin: RouterSocket = RouterSocket::new();
+        bind(&mut stdin, "stdin", self.info.endpoint(self.info.stdin_port)).await?;
+        let mut iopub_socket: PubSocket = PubSocket::new();
+        bind(&mut iopub_socket, "IOPub", self.info.endpoint(self.info.iopub_port)).await?;
+        let mut hb: RepSocket = RepSocket::new();
+        bind(&mut hb, "heartbeat", self.info.endpoint(self.info.hb_port)).await?;
+
+        // Spawn the tasks for the sockets that work independently of requests
+        tokio::spawn(heartbeat(hb));
+        let (queue, receiver): (UnboundedSender<Message>, UnboundedReceiver<Message>) = mpsc::unbounded_channel();
+        let publisher = tokio::spawn(publish(iopub_socket, receiver, self.signer.clone()));
+        let iopub: Iopub = Iopub::new(&self.session, queue);
+        iopub.status("starting");
+
+        // Connect to the instance
+        let mut state: InstanceVmState<StreamWriter, StreamWriter> = self.connect(&iopub).await?;
+        iopub.status("idle");
+
+        // Handle requests until we're asked to stop
+        loop {
+            let (what, res) = tokio::select! {
+                res = shell.recv() => ("shell", res),
+                res = control.recv() => ("control", res),
+            };
+            let frames = match res {
+                Ok(msg) => msg.into_vec(),
+                Err(err) => return Err(Error::SocketRecvError { what, err }),
+            };
+            let request: Message = match Message::from_frames(frames, &self.signer) {
+                Ok(request) => request,
+                Err(err) => {
+                    warn!("Ignoring invalid message on {} socket: {}", what, err);
+                    continue;
+                },
+            };
+            debug!("Received '{}' on {} socket", request.msg_type(), what);
+
+            // Handle it, telling the frontend we're busy meanwhile
+            iopub.set_parent(Some(request.header.clone()));
+            iopub.status("busy");
+            let (content, stop): (Option<Value>, bool) = self.handle(&request, &iopub, &mut state).await;
+            if let Some(content) = content {
+                let reply: Message = request.reply(&self.session, request.msg_type().replace("_request", "_reply"), content);
+                let socket: &mut RouterSocket = if what == "shell" { &mut shell } else { &mut control };
+                self.send(socket, what, reply).await?;
+            }
+            iopub.status("idle");
+            iopub.set_parent(None);
+            if stop {
+                break;
+            }
+        }
+
+        // Let the publisher flush before we return
+        drop(state);
+        drop(iopub);
+        if let Err(err) = publisher.await {
+            warn!("IOPub task failed: {}", err);
+        }
+        drop(stdin);
+        info!("Kernel shut down");
+        Ok(())
+    }
+
+    /// Connects to the instance.
+    ///
+    /// # Arguments
+    /// - `iopub`: The [`Iopub`] handle on which to stream the prints of cells.
+    ///
+    /// # Returns
+    /// The state of the session on the instance, which is shared by all cells.
+    ///
+    /// # Errors
+    /// This function errors if we failed to get the instance's data index or to create a session on it.
+    async fn connect(&self, iopub: &Iopub) -> Result<InstanceVmState<StreamWriter, StreamWriter>, Error> {
+        // Packages are resolved as cells import them
+        let pindex: Arc<PackageIndexCache<RemotePackageProvider>> =
+            Arc::new(PackageIndexCache::new(RemotePackageProvider::new(format!("{}/graphql", self.opts.api_endpoint))));
+        let data_endpoint: String = format!("{}/data/info", self.opts.api_endpoint);
+        let dindex: DataIndex = match get_data_index(&data_endpoint).await {
+            Ok(dindex) => dindex,
+            Err(err) => return Err(Error::DataIndexError { endpoint: data_endpoint, err }),
+        };
+
+        // Create the session
+        match initialize_instance(
+            StreamWriter::new("stdout", iopub.clone()),
+            StreamWriter::new("stderr", iopub.clone()),
+            &self.opts.drv_endpoint,
+            pindex,
+            Arc::new(Mutex::new(dindex)),
+            self.opts.user.clone(),
+            self.opts.token.clone(),
+            None,
+            ParserOptions::bscript(),
+        )
+        .await
+        {
+            Ok(state) => Ok(state),
+            Err(err) => Err(Error::InstanceInitializeError { endpoint: self.opts.drv_endpoint.clone(), err }),
+        }
+    }
+
+    /// Sends a message on one of the request sockets.
+    ///
+    /// # Arguments
+    /// - `socket`: The socket to send on.
+    /// - `what`: The name of the socket, for debugging purposes.
+    /// - `msg`: The [`Message`] to send.
+    ///
+    /// # Errors
+    /// This function errors if we failed to encode or send the message.
+    async fn send(&self, socket: &mut RouterSocket, what: &'static str, msg: Message) -> Result<(), Error> {
+        let msg_type: String = msg.header.msg_type.clone();
+        let frames = match msg.into_frames(&self.signer) {
+            Ok(frames) => frames,
+            Err(err) => return Err(Error::MessageEncodeError { msg_type, err }),
+        };
+        // There is always at least a delimiter, so the message is never empty
+        let msg: ZmqMessage = ZmqMessage::try_from(frames).expect("Encoded message should not be empty");
+        match socket.send(msg).await {
+            Ok(_) => Ok(()),
+            Err(err) => Err(Error::SocketSendError { what, err }),
+        }
+    }
+
+    /// Handles a single request.
+    ///
+    /// # Arguments
+    /// - `request`: The request to handle.
+    /// - `iopub`: The [`Iopub`] handle on which to publish outputs.
+    /// - `state`: The state of the session on the instance.
+    ///
+    /// # Returns
+    /// The content of the reply (or [`None`] if the request should be ignored), and whether the kernel should stop.
+    async fn handle(&mut self, request: &Message, iopub: &Iopub, state: &mut InstanceVmState<StreamWriter, StreamWriter>) -> (Option<Value>, bool) {
+        match request.msg_type() {
+            "kernel_info_request" => (
+                Some(json!({
+                    "status": "ok",
+                    "protocol_version": PROTOCOL_VERSION,
+                    "implementation": "brane",
+                    "implementation_version": env!("CARGO_PKG_VERSION"),
+                    "language_info": {
+                        "name": "bscript",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "mimetype": "text/x-bscript",
+                        "file_extension": ".bs",
+                    },
+                    "banner": format!("BraneScript kernel v{} for instance '{}'", env!("CARGO_PKG_VERSION"), self.opts.api_endpoint),
+                    "help_links": [],
+                })),
+                false,
+            ),
+            "execute_request" => (Some(self.execute(request, iopub, state).await), false),
+            "is_complete_request" => {
+                let code: &str = request.content.get("code").and_then(Value::as_str).unwrap_or_default();
+                let status: &str = is_complete(code);
+                (Some(if status == "incomplete" { json!({ "status": status, "indent": "    " }) } else { json!({ "status": status }) }), false)
+            },
+            "comm_info_request" => (Some(json!({ "status": "ok", "comms": {} })), false),
+            "history_request" => (Some(json!({ "status": "ok", "history": [] })), false),
+            // Cells run to completion before we read the next request anyway
+            "interrupt_request" => (Some(json!({ "status": "ok" })), false),
+            "shutdown_request" => {
+                let restart: bool = request.content.get("restart").and_then(Value::as_bool).unwrap_or(false);
+                (Some(json!({ "status": "ok", "restart": restart })), true)
+            },
+            msg_type => {
+                warn!("Ignoring unsupported '{}' message", msg_type);
+                (None, false)
+            },
+        }
+    }
+
+    /// Executes a cell.
+    ///
+    /// # Arguments
+    /// - `request`: The `execute_request` with the cell.
+    /// - `iopub`: The [`Iopub`] handle on which to publish the cell's input, prints and result.
+    /// - `state`: The state of the session on the instance.
+    ///
+    /// # Returns
+    /// The content of the `execute_reply`.
+    async fn execute(&mut self, request: &Message, iopub: &Iopub, state: &mut InstanceVmState<StreamWriter, StreamWriter>) -> Value {
+        let code: &str = request.content.get("code").and_then(Value::as_str).unwrap_or_default();
+        let silent: bool = request.content.get("silent").and_then(Value::as_bool).unwrap_or(false);
+        let store_history: bool = request.content.get("store_history").and_then(Value::as_bool).unwrap_or(!silent);
+        if store_history {
+            self.execution_count += 1;
+        }
+        let count: u64 = self.execution_count;
+        if !silent {
+            iopub.publish("execute_input", json!({ "code": code, "execution_count": count }));
+        }
+
+        // Run the cell, then fetch its result if necessary
+        let res: Result<(FullValue, Option<PathBuf>), CellError> = match self.run_cell(&format!("<cell {count}>"), code, state).await {
+            Ok(value) => self.process(&value, state).await.map(|location| (value, location)),
+            Err(err) => Err(err),
+        };
+        match res {
+            Ok((value, location)) => {
+                if !silent && value != FullValue::Void {
+                    iopub.publish(
+                        "execute_result",
+                        json!({ "execution_count": count, "data": mime_bundle(&value, location.as_deref()), "metadata": {} }),
+                    );
+                }
+                json!({ "status": "ok", "execution_count": count, "user_expressions": {}, "payload": [] })
+            },
+            Err(err) => {
+                let mut content: Value = err.content();
+                if !silent {
+                    iopub.publish("error", content.clone());
+                }
+                content["status"] = json!("error");
+                content["execution_count"] = json!(count);
+                content
+            },
+        }
+    }
+
+    /// Compiles and runs a cell on the instance.
+    ///
+    /// # Arguments
+    /// - `what`: The name of the cell, as used in compile errors.
+    /// - `code`: The code of the cell.
+    /// - `state`: The state of the session on the instance.
+    ///
+    /// # Returns
+    /// The value returned by the cell's workflow.
+    ///
+    /// # Errors
+    /// This function errors if the cell failed to compile or run.
+    async fn run_cell(&self, what: &str, code: &str, state: &mut InstanceVmState<StreamWriter, StreamWriter>) -> Result<FullValue, CellError> {
+        // Fetch the packages that the cell imports
+        let pindex: Arc<PackageIndex> = match state.pindex.resolve(code, &state.options).await {
+            Ok(pindex) => pindex,
+            Err(err) => return Err(CellError::new("ResolveError", format!("Failed to resolve imported packages: {err}"))),
+        };
+
+        // Compile it
+        state.source.push_str(code);
+        state.source.push('\n');
+        let res: CompileResult = {
+            let dindex: MutexGuard<DataIndex> = state.dindex.lock();
+            compile_snippet(&mut state.state, code.as_bytes(), &pindex, &dindex, &state.options)
+        };
+        state.state.offset += 1 + code.chars().filter(|c| *c == '\n').count();
+        let mut workflow: Workflow = match res {
+            CompileResult::Workflow(workflow, warns) => {
+                for warning in warns {
+                    let mut buf: Vec<u8> = vec![];
+                    if warning.prettywrite(&mut buf, what, &state.source).is_ok() {
+                        let _ = state.stderr.write_all(&buf);
+                    }
+                }
+                workflow
+            },
+            CompileResult::Eof(err) => return Err(Self::compile_error(what, &state.source, vec![err])),
+            CompileResult::Err(errs) => return Err(Self::compile_error(what, &state.source, errs)),
+            CompileResult::Program(_, _) | CompileResult::Unresolved(_, _) => unreachable!(),
+        };
+        if let Some(user) = &state.user {
+            workflow.user = Arc::new(Some(user.clone()));
+        }
+
+        // Run it
+        match run_instance(&self.opts.drv_endpoint, state, &workflow, false).await {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                let ename: &'static str = match &err {
+                    RunError::ExecDenied { .. } | RunError::PolicyDenied { .. } => "PolicyDenied",
+                    _ => "RunError",
+                };
+                Err(CellError::new(ename, err.to_string()))
+            },
+        }
+    }
+
+    /// Builds the [`CellError`] for a cell that failed to compile.
+    ///
+    /// # Arguments
+    /// - `what`: The name of the cell.
+    /// - `source`: The source of all cells so far, which the errors refer to.
+    /// - `errs`: The errors that occurred.
+    ///
+    /// # Returns
+    /// A new CellError with the prettyprinted errors as traceback.
+    fn compile_error(what: &str, source: &str, errs: Vec<brane_ast::Error>) -> CellError {
+        let mut traceback: Vec<String> = Vec::with_capacity(errs.len());
+        for err in &errs {
+            let mut buf: Vec<u8> = vec![];
+            match err.prettywrite(&mut buf, what, source) {
+                Ok(_) => traceback.push(String::from_utf8_lossy(&buf).into_owned()),
+                Err(_) => traceback.push(err.to_string()),
+            }
+        }
+        CellError { ename: "CompileError", evalue: format!("Failed to compile {what} ({} error(s))", errs.len()), traceback }
+    }
+
+    /// Downloads the result of a cell if it's a dataset.
+    ///
+    /// # Arguments
+    /// - `value`: The value returned by the cell.
+    /// - `state`: The state of the session on the instance.
+    ///
+    /// # Returns
+    /// Where the dataset was downloaded to, or [`None`] if it's not a dataset (or not a file).
+    ///
+    /// # Errors
+    /// This function errors if we failed to download the dataset.
+    async fn process(&self, value: &FullValue, state: &mut InstanceVmState<StreamWriter, StreamWriter>) -> Result<Option<PathBuf>, CellError> {
+        let name: &str = match value {
+            FullValue::Data(name) => name.as_ref(),
+            _ => return Ok(None),
+        };
+
+        // Refresh the data index to find where the new dataset lives
+        let data_endpoint: String = format!("{}/data/info", self.opts.api_endpoint);
+        let dindex: DataIndex = match get_data_index(&data_endpoint).await {
+            Ok(dindex) => dindex,
+            Err(err) => return Err(CellError::new("DownloadError", format!("Failed to refresh data index: {err}"))),
+        };
+        let access: HashMap<String, AccessKind> = {
+            let mut lock: MutexGuard<DataIndex> = state.dindex.lock();
+            *lock = dindex;
+            match lock.get(name) {
+                Some(info) => info.access.clone(),
+                None => return Err(CellError::new("DownloadError", format!("Resulting dataset '{name}' is not at any location"))),
+            }
+        };
+
+        // Download it
+        match download_data(&self.opts.api_endpoint, &None, &self.opts.certs_dir, &self.opts.data_dir, name, &access).await {
+            Ok(Some(AccessKind::File { path })) => Ok(Some(path)),
+            Ok(_) => Ok(None),
+            Err(err) => {
+                Err(CellError::new("DownloadError", format!("Failed to download resulting dataset from '{}': {}", self.opts.api_endpoint, err)))
+            },
+        }
+    }
+}
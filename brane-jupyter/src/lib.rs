@@ -0,0 +1,34 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   The `brane-jupyter` library implements the Jupyter kernel protocol
+//!   on top of the same compiler and instance VM state that `brane-cli`
+//!   and `brane-cli-c` use. This allows notebooks (e.g., those of
+//!   `brane-ide`) to run BraneScript cells natively, with prints streamed
+//!   as they arrive and workflow results rendered as rich output.
+//!
+//!   The protocol itself is described at:
+//!   https://jupyter-client.readthedocs.io/en/stable/messaging.html
+//
+
+// Declare modules
+pub mod connection;
+pub mod display;
+pub mod errors;
+pub mod kernel;
+pub mod message;
+pub mod spec;
+mod stream;
+
+// Bring some of it into the crate namespace
+pub use connection::ConnectionInfo;
+pub use kernel::{Kernel, KernelOptions};
+pub use spec::KernelSpec;
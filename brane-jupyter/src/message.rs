@@ -0,0 +1,350 @@
+//  MESSAGE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the Jupyter wire protocol, i.e., how messages are split
+//!   over ZeroMQ frames and how they are signed.
+//
+
+use std::fmt::{Debug, Formatter, Result as FResult};
+
+use bytes::Bytes;
+use hmac::{Hmac, Mac as _};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+pub use crate::errors::MessageError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that messages survive being split over frames and parsed again, and that tampering is detected
+    #[test]
+    fn test_frames() {
+        let signer: Signer = Signer::new("secret");
+        let mut request: Message = Message::new("client", "execute_request", json!({ "code": "println(\"Hello\");" }));
+        request.identities = vec![Bytes::from_static(b"frontend")];
+
+        // Roundtrip the request
+        let frames: Vec<Bytes> = request.clone().into_frames(&signer).unwrap();
+        assert_eq!(frames[0], Bytes::from_static(b"frontend"));
+        assert_eq!(frames[1], Bytes::from_static(DELIMITER));
+        let parsed: Message = Message::from_frames(frames.clone(), &signer).unwrap();
+        assert_eq!(parsed.identities, request.identities);
+        assert_eq!(parsed.header.msg_id, request.header.msg_id);
+        assert!(parsed.parent.is_none());
+        assert_eq!(parsed.content, request.content);
+
+        // Replies refer to their request
+        let reply: Message = parsed.reply("kernel", "execute_reply", json!({ "status": "ok" }));
+        assert_eq!(reply.identities, request.identities);
+        assert_eq!(reply.parent.as_ref().map(|p| p.msg_id.as_str()), Some(request.header.msg_id.as_str()));
+
+        // Tampering with the content invalidates the signature
+        let mut tampered: Vec<Bytes> = frames.clone();
+        tampered[6] = Bytes::from_static(b"{\"code\":\"rm -rf /\"}");
+        assert!(matches!(Message::from_frames(tampered, &signer), Err(Error::InvalidSignature)));
+        assert!(matches!(Message::from_frames(frames, &Signer::new("other")), Err(Error::InvalidSignature)));
+
+        // Unsigned messages are accepted when there is no key
+        let frames: Vec<Bytes> = request.into_frames(&Signer::new("")).unwrap();
+        assert!(frames[2].is_empty());
+        assert!(Message::from_frames(frames, &Signer::new("")).is_ok());
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The frame that separates the ZeroMQ identities from the message itself.
+pub const DELIMITER: &[u8] = b"<IDS|MSG>";
+/// The version of the messaging protocol we implement.
+pub const PROTOCOL_VERSION: &str = "5.3";
+
+
+
+
+
+/***** LIBRARY *****/
+/// Signs and verifies messages with the key from the connection file.
+#[derive(Clone)]
+pub struct Signer {
+    /// The key to sign with, or [`None`] if messages aren't signed.
+    key: Option<Vec<u8>>,
+}
+
+impl Signer {
+    /// Constructor for the Signer.
+    ///
+    /// # Arguments
+    /// - `key`: The key to sign with. If empty, messages are neither signed nor verified.
+    ///
+    /// # Returns
+    /// A new Signer.
+    #[inline]
+    pub fn new(key: &str) -> Self { Self { key: if key.is_empty() { None } else { Some(key.as_bytes().to_vec()) } } }
+
+    /// Computes the HMAC over the given frames.
+    ///
+    /// # Arguments
+    /// - `frames`: The header, parent header, metadata and content frames, in that order.
+    ///
+    /// # Returns
+    /// The MAC, or [`None`] if this Signer has no key.
+    fn mac(&self, frames: &[&[u8]]) -> Option<Hmac<Sha256>> {
+        let key: &[u8] = self.key.as_ref()?;
+        // HMAC accepts keys of any length, so this never fails
+        let mut mac: Hmac<Sha256> = Hmac::new_from_slice(key).expect("HMAC should accept keys of any length");
+        for frame in frames {
+            mac.update(frame);
+        }
+        Some(mac)
+    }
+
+    /// Signs the given frames.
+    ///
+    /// # Arguments
+    /// - `frames`: The header, parent header, metadata and content frames, in that order.
+    ///
+    /// # Returns
+    /// The hex-encoded signature, which is empty if this Signer has no key.
+    pub fn sign(&self, frames: &[&[u8]]) -> String {
+        match self.mac(frames) {
+            Some(mac) => hex::encode(mac.finalize().into_bytes()),
+            None => String::new(),
+        }
+    }
+
+    /// Verifies the signature of the given frames.
+    ///
+    /// # Arguments
+    /// - `signature`: The hex-encoded signature that came with the frames.
+    /// - `frames`: The header, parent header, metadata and content frames, in that order.
+    ///
+    /// # Returns
+    /// Whether the signature is valid. Always true if this Signer has no key.
+    pub fn verify(&self, signature: &[u8], frames: &[&[u8]]) -> bool {
+        match self.mac(frames) {
+            Some(mac) => match hex::decode(signature) {
+                Ok(signature) => mac.verify_slice(&signature).is_ok(),
+                Err(_) => false,
+            },
+            None => true,
+        }
+    }
+}
+
+impl Debug for Signer {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        // Never show the key itself
+        f.debug_struct("Signer").field("key", &self.key.as_ref().map(|_| "<redacted>")).finish()
+    }
+}
+
+
+
+/// Defines the header of a message.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Header {
+    /// A unique identifier for this message.
+    pub msg_id:   String,
+    /// The session of whoever sent the message.
+    pub session:  String,
+    /// The user who sent the message.
+    pub username: String,
+    /// When the message was created, as an ISO 8601 timestamp.
+    pub date:     String,
+    /// The type of the message (e.g., `execute_request`).
+    pub msg_type: String,
+    /// The version of the protocol with which the message was sent.
+    pub version:  String,
+}
+
+impl Header {
+    /// Constructor for a Header for a new message.
+    ///
+    /// # Arguments
+    /// - `session`: The session of the sender of the message.
+    /// - `msg_type`: The type of the message.
+    ///
+    /// # Returns
+    /// A new Header with a fresh message ID.
+    pub fn new(session: impl Into<String>, msg_type: impl Into<String>) -> Self {
+        Self {
+            msg_id:   uuid::Uuid::new_v4().to_string(),
+            session:  session.into(),
+            username: "brane".into(),
+            date:     chrono::Utc::now().to_rfc3339(),
+            msg_type: msg_type.into(),
+            version:  PROTOCOL_VERSION.into(),
+        }
+    }
+}
+
+
+
+/// Defines a single Jupyter message.
+#[derive(Clone, Debug)]
+pub struct Message {
+    /// The ZeroMQ identities of the peer that sent the message (for `ROUTER` sockets), or the topic (for the `PUB` socket).
+    pub identities: Vec<Bytes>,
+    /// The header of the message.
+    pub header:     Header,
+    /// The header of the message that caused this one, if any.
+    pub parent:     Option<Header>,
+    /// Any metadata of the message.
+    pub metadata:   Value,
+    /// The contents of the message, which depend on its type.
+    pub content:    Value,
+}
+
+impl Message {
+    /// Constructor for a new Message that is not caused by another one.
+    ///
+    /// # Arguments
+    /// - `session`: The session of the sender of the message.
+    /// - `msg_type`: The type of the message.
+    /// - `content`: The contents of the message.
+    ///
+    /// # Returns
+    /// A new Message without identities.
+    #[inline]
+    pub fn new(session: impl Into<String>, msg_type: impl Into<String>, content: Value) -> Self {
+        Self { identities: vec![], header: Header::new(session, msg_type), parent: None, metadata: json!({}), content }
+    }
+
+    /// Creates a reply to this message, which is sent to the same peer.
+    ///
+    /// # Arguments
+    /// - `session`: The session of the kernel.
+    /// - `msg_type`: The type of the reply (e.g., `execute_reply`).
+    /// - `content`: The contents of the reply.
+    ///
+    /// # Returns
+    /// A new Message with this one as parent.
+    #[inline]
+    pub fn reply(&self, session: impl Into<String>, msg_type: impl Into<String>, content: Value) -> Self {
+        Self { identities: self.identities.clone(), parent: Some(self.header.clone()), ..Self::new(session, msg_type, content) }
+    }
+
+    /// Returns the type of this message.
+    #[inline]
+    pub fn msg_type(&self) -> &str { &self.header.msg_type }
+
+    /// Parses a Message from the frames received on a socket.
+    ///
+    /// # Arguments
+    /// - `frames`: The frames of the message.
+    /// - `signer`: The [`Signer`] to verify the message's signature with.
+    ///
+    /// # Returns
+    /// The parsed Message.
+    ///
+    /// # Errors
+    /// This function errors if the frames are not a valid message, or if its signature is invalid.
+    pub fn from_frames(mut frames: Vec<Bytes>, signer: &Signer) -> Result<Self, Error> {
+        // Everything up to the delimiter are identities
+        let delim: usize = match frames.iter().position(|frame| frame.as_ref() == DELIMITER) {
+            Some(delim) => delim,
+            None => return Err(Error::MissingDelimiter),
+        };
+        let rest: Vec<Bytes> = frames.split_off(delim + 1);
+        frames.truncate(delim);
+        if rest.len() < 5 {
+            return Err(Error::MissingFrames { got: rest.len() });
+        }
+
+        // Verify the signature before we parse anything
+        if !signer.verify(&rest[0], &[&rest[1], &rest[2], &rest[3], &rest[4]]) {
+            return Err(Error::InvalidSignature);
+        }
+
+        // Parse the JSON frames
+        let header: Header = match serde_json::from_slice(&rest[1]) {
+            Ok(header) => header,
+            Err(err) => return Err(Error::FrameParseError { what: "header", err }),
+        };
+        // The parent header is an empty object if there is no parent
+        let parent: Value = match serde_json::from_slice(&rest[2]) {
+            Ok(parent) => parent,
+            Err(err) => return Err(Error::FrameParseError { what: "parent header", err }),
+        };
+        let parent: Option<Header> = match parent {
+            Value::Object(map) if map.is_empty() => None,
+            parent => match serde_json::from_value(parent) {
+                Ok(parent) => Some(parent),
+                Err(err) => return Err(Error::FrameParseError { what: "parent header", err }),
+            },
+        };
+        let metadata: Value = match serde_json::from_slice(&rest[3]) {
+            Ok(metadata) => metadata,
+            Err(err) => return Err(Error::FrameParseError { what: "metadata", err }),
+        };
+        let content: Value = match serde_json::from_slice(&rest[4]) {
+            Ok(content) => content,
+            Err(err) => return Err(Error::FrameParseError { what: "content", err }),
+        };
+
+        // Done
+        Ok(Self { identities: frames, header, parent, metadata, content })
+    }
+
+    /// Serializes this Message to frames to send on a socket.
+    ///
+    /// # Arguments
+    /// - `signer`: The [`Signer`] to sign the message with.
+    ///
+    /// # Returns
+    /// The frames of the message.
+    ///
+    /// # Errors
+    /// This function errors if we failed to serialize any of the parts of the message.
+    pub fn into_frames(self, signer: &Signer) -> Result<Vec<Bytes>, Error> {
+        let header: Vec<u8> = match serde_json::to_vec(&self.header) {
+            Ok(header) => header,
+            Err(err) => return Err(Error::FrameSerializeError { what: "header", err }),
+        };
+        let parent: Vec<u8> = match &self.parent {
+            Some(parent) => match serde_json::to_vec(parent) {
+                Ok(parent) => parent,
+                Err(err) => return Err(Error::FrameSerializeError { what: "parent header", err }),
+            },
+            None => b"{}".to_vec(),
+        };
+        let metadata: Vec<u8> = match serde_json::to_vec(&self.metadata) {
+            Ok(metadata) => metadata,
+            Err(err) => return Err(Error::FrameSerializeError { what: "metadata", err }),
+        };
+        let content: Vec<u8> = match serde_json::to_vec(&self.content) {
+            Ok(content) => content,
+            Err(err) => return Err(Error::FrameSerializeError { what: "content", err }),
+        };
+        let signature: String = signer.sign(&[&header, &parent, &metadata, &content]);
+
+        // Put it together
+        let mut frames: Vec<Bytes> = self.identities;
+        frames.extend([
+            Bytes::from_static(DELIMITER),
+            Bytes::from(signature),
+            Bytes::from(header),
+            Bytes::from(parent),
+            Bytes::from(metadata),
+            Bytes::from(content),
+        ]);
+        Ok(frames)
+    }
+}
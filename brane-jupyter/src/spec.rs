@@ -0,0 +1,52 @@
+//  SPEC.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the kernel specification (`kernel.json`) with which a
+//!   BraneScript kernel is installed into Jupyter.
+//
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+
+/***** LIBRARY *****/
+/// Defines the `kernel.json` file that tells Jupyter how to launch the kernel.
+///
+/// Install it by writing it to `<jupyter data dir>/kernels/<name>/kernel.json`, or with `jupyter kernelspec install`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KernelSpec {
+    /// The command (and arguments) that launch the kernel. Jupyter replaces `{connection_file}` with the path of the [`ConnectionInfo`](crate::ConnectionInfo) file.
+    pub argv: Vec<String>,
+    /// The name of the kernel as shown to users.
+    pub display_name: String,
+    /// The language of the kernel.
+    pub language: String,
+    /// Whether the kernel wants to be interrupted with a signal or a message.
+    pub interrupt_mode: String,
+    /// Any environment variables to set when launching the kernel.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl KernelSpec {
+    /// Constructor for the KernelSpec for a BraneScript kernel.
+    ///
+    /// # Arguments
+    /// - `argv`: The command that launches the kernel, which should contain `{connection_file}` somewhere.
+    ///
+    /// # Returns
+    /// A new KernelSpec.
+    #[inline]
+    pub fn bscript(argv: Vec<String>) -> Self {
+        Self { argv, display_name: "BraneScript".into(), language: "bscript".into(), interrupt_mode: "message".into(), env: HashMap::new() }
+    }
+}
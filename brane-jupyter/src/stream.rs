@@ -0,0 +1,122 @@
+//  STREAM.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:12:43
+//  Last edited:
+//    15 Oct 2026, 23:12:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the handle with which the kernel broadcasts messages on
+//!   its IOPub socket, and writers that stream prints to the notebook as
+//!   they come in.
+//
+
+use std::io::Write;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::message::{Header, Message};
+
+
+/***** LIBRARY *****/
+/// A cloneable handle to the task that owns the IOPub socket.
+///
+/// Messages are published as children of the request that is currently being handled (see [`Iopub::set_parent()`]), such that the frontend
+/// shows them in the right cell.
+#[derive(Clone, Debug)]
+pub struct Iopub {
+    /// The kernel's session.
+    session: String,
+    /// The request that is currently being handled, if any.
+    parent:  Arc<Mutex<Option<Header>>>,
+    /// The queue of the task that owns the socket.
+    queue:   UnboundedSender<Message>,
+}
+
+impl Iopub {
+    /// Constructor for the Iopub.
+    ///
+    /// # Arguments
+    /// - `session`: The kernel's session.
+    /// - `queue`: The queue of the task that owns the socket.
+    ///
+    /// # Returns
+    /// A new Iopub handle.
+    #[inline]
+    pub fn new(session: impl Into<String>, queue: UnboundedSender<Message>) -> Self {
+        Self { session: session.into(), parent: Arc::new(Mutex::new(None)), queue }
+    }
+
+    /// Sets the request that is currently being handled.
+    ///
+    /// # Arguments
+    /// - `parent`: The header of the request, or [`None`] if the kernel is done with it.
+    #[inline]
+    pub fn set_parent(&self, parent: Option<Header>) { *self.parent.lock() = parent; }
+
+    /// Publishes a message.
+    ///
+    /// # Arguments
+    /// - `msg_type`: The type of the message, which is also used as its topic.
+    /// - `content`: The contents of the message.
+    ///
+    /// # Returns
+    /// Whether the message was queued, which is only false if the IOPub task has stopped.
+    pub fn publish(&self, msg_type: &str, content: Value) -> bool {
+        let mut msg: Message = Message::new(&self.session, msg_type, content);
+        msg.identities = vec![Bytes::copy_from_slice(msg_type.as_bytes())];
+        msg.parent.clone_from(&self.parent.lock());
+        self.queue.send(msg).is_ok()
+    }
+
+    /// Publishes a change in the kernel's execution state.
+    ///
+    /// # Arguments
+    /// - `state`: The new state (`starting`, `busy` or `idle`).
+    #[inline]
+    pub fn status(&self, state: &str) { self.publish("status", json!({ "execution_state": state })); }
+}
+
+
+
+/// A [`Write`]r that streams everything written to it to the notebook as `stream` messages.
+#[derive(Clone, Debug)]
+pub struct StreamWriter {
+    /// The name of the stream (`stdout` or `stderr`).
+    name:  &'static str,
+    /// The handle to publish with.
+    iopub: Iopub,
+}
+
+impl StreamWriter {
+    /// Constructor for the StreamWriter.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the stream (`stdout` or `stderr`).
+    /// - `iopub`: The handle to publish with.
+    ///
+    /// # Returns
+    /// A new StreamWriter.
+    #[inline]
+    pub fn new(name: &'static str, iopub: Iopub) -> Self { Self { name, iopub } }
+}
+
+impl Write for StreamWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Prints from the driver are whole messages, so there's no need to buffer until a newline
+        if !buf.is_empty() && !self.iopub.publish("stream", json!({ "name": self.name, "text": String::from_utf8_lossy(buf) })) {
+            return Err(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "IOPub task has stopped"));
+        }
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
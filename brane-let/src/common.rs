@@ -4,7 +4,7 @@
 //  Created:
 //    14 Feb 2022, 14:21:21
 //  Last edited:
-//    22 May 2023, 10:23:31
+//    15 Oct 2026, 20:47:32
 //  Auto updated?
 //    Yes
 //
@@ -12,6 +12,8 @@
 //!   Contains common definitions across all executions.
 //
 
+use std::time::Duration;
+
 use brane_ast::DataType;
 use brane_exe::FullValue;
 use log::debug;
@@ -48,6 +50,8 @@ pub enum PackageReturnState {
     Failed { code: i32, stdout: String, stderr: String },
     /// The package completed successfully
     Finished { stdout: String },
+    /// The package exceeded its timeout and was terminated
+    TimedOut { timeout: Duration },
 }
 
 
@@ -60,6 +64,8 @@ pub enum PackageResult {
     Failed { code: i32, stdout: String, stderr: String },
     /// The package completed successfully
     Finished { result: FullValue },
+    /// The package exceeded its timeout and was terminated
+    TimedOut { timeout: Duration },
 }
 
 
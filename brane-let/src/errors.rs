@@ -4,7 +4,7 @@
 //  Created:
 //    11 Feb 2022, 13:09:23
 //  Last edited:
-//    22 May 2023, 10:12:51
+//    15 Oct 2026, 20:45:14
 //  Auto updated?
 //    Yes
 //
@@ -82,10 +82,27 @@ pub enum LetError {
     IllegalNestedURL { name: String, field: String },
     /// We got an error launching the package
     PackageLaunchError { command: String, err: std::io::Error },
+    /// Failed to serialize the JSON-RPC request for the package
+    RpcRequestSerializeError { err: serde_json::Error },
+    /// The subprocess' stdin wasn't opened successfully
+    ClosedStdin,
+    /// Could not write the JSON-RPC request to the package's stdin
+    StdinWriteError { err: std::io::Error },
 
     /// The given Open API Standard file does not parse as OAS
     IllegalOasDocument { path: PathBuf, err: anyhow::Error },
 
+    /// Failed to load the WebAssembly module of a WASM package
+    WasmModuleError { path: PathBuf, err: anyhow::Error },
+    /// Failed to prepare the (WASI) environment for a WASM package
+    WasmEnvironmentError { err: anyhow::Error },
+    /// Failed to instantiate the WebAssembly module of a WASM package
+    WasmInstantiateError { path: PathBuf, err: anyhow::Error },
+    /// The WebAssembly module of a WASM package trapped while running
+    WasmTrap { path: PathBuf, err: anyhow::Error },
+    /// The (blocking) task running a WASM package panicked or was cancelled
+    WasmJoinError { err: tokio::task::JoinError },
+
     /// Somehow, we got an error while waiting for the subprocess
     PackageRunError { err: std::io::Error },
     /// The subprocess' stdout wasn't opened successfully
@@ -212,9 +229,18 @@ impl Display for LetError {
                 write!(f, "Field '{field}' of struct '{name}' is a Directory or a File struct, but misses the 'URL' field")
             },
             PackageLaunchError { command, err } => write!(f, "Could not run nested package call '{command}': {err}"),
+            RpcRequestSerializeError { err } => write!(f, "Could not serialize JSON-RPC request for package: {err}"),
+            ClosedStdin => write!(f, "Could not open subprocess stdin"),
+            StdinWriteError { err } => write!(f, "Could not write JSON-RPC request to subprocess stdin: {err}"),
 
             IllegalOasDocument { path, err } => write!(f, "Could not parse OpenAPI specification '{}': {}", path.display(), err),
 
+            WasmModuleError { path, err } => write!(f, "Could not load WebAssembly module '{}': {}", path.display(), err),
+            WasmEnvironmentError { err } => write!(f, "Could not prepare WASI environment for WebAssembly module: {err}"),
+            WasmInstantiateError { path, err } => write!(f, "Could not instantiate WebAssembly module '{}': {}", path.display(), err),
+            WasmTrap { path, err } => write!(f, "WebAssembly module '{}' trapped: {}", path.display(), err),
+            WasmJoinError { err } => write!(f, "Failed to wait for WebAssembly module to complete: {err}"),
+
             ClosedStdout => write!(f, "Could not open subprocess stdout"),
             ClosedStderr => write!(f, "Could not open subprocess stdout"),
             StdoutReadError { err } => write!(f, "Could not read from stdout: {err}"),
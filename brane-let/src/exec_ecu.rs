@@ -4,7 +4,7 @@
 //  Created:
 //    20 Sep 2022, 13:55:30
 //  Last edited:
-//    25 May 2023, 20:43:21
+//    15 Oct 2026, 20:49:19
 //  Auto updated?
 //    Yes
 //
@@ -19,26 +19,36 @@ use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use brane_exe::FullValue;
-use log::{debug, info};
-use specifications::container::{Action, ActionCommand, LocalContainerInfo};
-use tokio::io::AsyncReadExt as _;
+use log::{debug, info, warn};
+use specifications::container::{Action, ActionCommand, CommandProtocol, LocalContainerInfo};
+use specifications::reporting::{BraneletReport, ResourceUsage, LIVENESS_INTERVAL};
+use tokio::io::{self as tio, AsyncBufReadExt as _, AsyncRead, AsyncWrite, AsyncWriteExt as _, BufReader};
 use tokio::process::{Child as TokioChild, Command as TokioCommand};
+use tokio::sync::Mutex;
 use tokio::time::{self, Duration};
 
 // use crate::callback::Callback;
-use crate::common::{assert_input, Map, PackageResult, PackageReturnState, HEARTBEAT_DELAY};
+use crate::common::{assert_input, Map, PackageResult, PackageReturnState};
 use crate::errors::LetError;
+use crate::retry::with_retries;
+use crate::rpc::{Message, RunRequest};
 
 
 /***** CONSTANTS *****/
 /// Initial capacity for the buffers for stdout and stderr
 const DEFAULT_STD_BUFFER_SIZE: usize = 2048;
+/// The maximum number of bytes of a single line of output that is streamed; longer lines are truncated (but still returned in full)
+const MAX_STREAMED_LINE_SIZE: usize = 64 * 1024;
+/// The maximum number of bytes of stdout and stderr (each) that is streamed; any more output is only returned when the package completes
+const MAX_STREAMED_SIZE: usize = 16 * 1024 * 1024;
 /// The start marker of a capture area
 const MARK_START: &str = "--> START CAPTURE";
 /// The end marker of a capture area
 const MARK_END: &str = "--> END CAPTURE";
 /// The single-line marker of a capture line
 const PREFIX: &str = "~~>";
+/// The time we give a package to terminate after sending it SIGTERM (because it exceeded its timeout) before we kill it
+const TERMINATION_GRACE_PERIOD: Duration = Duration::from_secs(10);
 
 
 
@@ -53,6 +63,7 @@ const PREFIX: &str = "~~>";
 ///  * `function`: The function name to execute in the package.
 ///  * `arguments`: The arguments, as a map of argument name / value pairs.
 ///  * `working_dir`: The wokring directory for this package.
+///  * `timeout`: If given, the maximum time the package may run before it is terminated.
 ///  * `callback`: The callback object we use to keep in touch with the driver.
 ///
 /// **Returns**  
@@ -61,12 +72,13 @@ pub async fn handle(
     function: String,
     arguments: Map<FullValue>,
     working_dir: PathBuf,
+    timeout: Option<Duration>,
     // callback: &mut Option<&mut Callback>,
 ) -> Result<PackageResult, LetError> {
     debug!("Executing '{}' (ecu) using arguments:\n{:#?}", function, arguments);
 
     // Initialize the package
-    let (container_info, function) = match initialize(&function, &arguments, &working_dir) {
+    let (container_info, action) = match initialize(&function, &arguments, &working_dir) {
         Ok(results) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.initialized().await { warn!("Could not update driver on Initialized: {}", err); }
@@ -83,8 +95,32 @@ pub async fn handle(
         },
     };
 
+    // Run the package, retrying it if its policy tells us to
+    with_retries(container_info.retry.as_ref(), || call(&container_info, &action, &function, &arguments, &working_dir, timeout)).await
+}
+
+/// Performs a single attempt at calling the package function.
+///
+/// **Arguments**
+///  * `container_info`: The LocalContainerInfo representing the container.yml of this package.
+///  * `action`: The function to call.
+///  * `function`: The name of the function to call.
+///  * `arguments`: The arguments, as a map of argument name / value pairs.
+///  * `working_dir`: The wokring directory for this package.
+///  * `timeout`: If given, the maximum time the package may run before it is terminated.
+///
+/// **Returns**  
+/// The return state of the package call on success, or a LetError otherwise.
+async fn call(
+    container_info: &LocalContainerInfo,
+    action: &Action,
+    function: &str,
+    arguments: &Map<FullValue>,
+    working_dir: &Path,
+    timeout: Option<Duration>,
+) -> Result<PackageResult, LetError> {
     // Launch the job
-    let (command, process) = match start(&container_info, &function, &arguments, &working_dir) {
+    let (command, mut process) = match start(container_info, action, arguments, working_dir) {
         Ok(result) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.started().await { warn!("Could not update driver on Started: {}", err); }
@@ -101,8 +137,14 @@ pub async fn handle(
         },
     };
 
+    // If the package speaks JSON-RPC, send it what to do
+    let rpc: bool = command.protocol.unwrap_or_default() == CommandProtocol::JsonRpc;
+    if rpc {
+        send_request(&mut process, function, arguments).await?;
+    }
+
     // Wait until the job is completed
-    let result = match complete(process).await {
+    let (result, response) = match complete(process, command.ready.as_deref(), rpc, timeout).await {
         Ok(result) => {
             // if let Some(callback) = callback {
             //     if let Err(err) = callback.completed().await { warn!("Could not update driver on Completed: {}", err); }
@@ -119,8 +161,15 @@ pub async fn handle(
         },
     };
 
-    // Convert the call to a PackageReturn value instead of state
-    let result = match decode(result, &command.capture) {
+    // Convert the call to a PackageReturn value instead of state (which packages speaking JSON-RPC may have already given us)
+    let result = match (result, response) {
+        (PackageReturnState::Finished { .. }, Some(Message::Result { value })) => Ok(PackageResult::Finished { result: value }),
+        (PackageReturnState::Finished { stdout }, Some(Message::Error { err })) => {
+            Ok(PackageResult::Failed { code: i32::try_from(err.code).ok().filter(|code| *code != 0).unwrap_or(1), stdout, stderr: err.message })
+        },
+        (result, _) => decode(result, &command.capture),
+    };
+    let result = match result {
         Ok(result) => result,
         Err(err) => {
             // if let Some(callback) = callback {
@@ -156,7 +205,7 @@ pub async fn handle(
 ///    * A list of Parmaters describing the function's _output_
 ///  * On failure:
 ///    * A LetError describing what went wrong.
-fn initialize(function: &str, arguments: &Map<FullValue>, working_dir: &Path) -> Result<(LocalContainerInfo, Action), LetError> {
+pub(crate) fn initialize(function: &str, arguments: &Map<FullValue>, working_dir: &Path) -> Result<(LocalContainerInfo, Action), LetError> {
     debug!("Reading local_container.yml...");
     // Get the container info from the path
     let container_info_path = working_dir.join("local_container.yml");
@@ -235,7 +284,8 @@ fn start(
 ) -> Result<(ActionCommand, TokioChild), LetError> {
     // Determine entrypoint and, optionally, command and arguments
     let entrypoint = &container_info.entrypoint.exec;
-    let command = function.command.clone().unwrap_or_else(|| ActionCommand { args: Default::default(), capture: None });
+    let command =
+        function.command.clone().unwrap_or_else(|| ActionCommand { args: Default::default(), capture: None, ready: None, protocol: None });
     let entrypoint_path = working_dir.join(entrypoint);
     let entrypoint_path = match entrypoint_path.canonicalize() {
         Ok(entrypoint_path) => entrypoint_path,
@@ -266,6 +316,9 @@ fn start(
     exec_command.envs(envs);
     exec_command.stdout(Stdio::piped());
     exec_command.stderr(Stdio::piped());
+    if command.protocol.unwrap_or_default() == CommandProtocol::JsonRpc {
+        exec_command.stdin(Stdio::piped());
+    }
     let process = match exec_command.spawn() {
         Ok(process) => process,
         Err(err) => {
@@ -277,6 +330,39 @@ fn start(
     Ok((command, process))
 }
 
+/// Sends the JSON-RPC request to run the given function to a package that speaks JSON-RPC (see [`crate::rpc`]).
+///
+/// Closes the package's stdin afterwards.
+///
+/// **Arguments**
+///  * `process`: The handle to the package's process, which must have been spawned with a piped stdin.
+///  * `function`: The name of the function to run.
+///  * `arguments`: The arguments to run it with.
+///
+/// **Returns**  
+/// Nothing on success, or a LetError on failure.
+async fn send_request(process: &mut TokioChild, function: &str, arguments: &Map<FullValue>) -> Result<(), LetError> {
+    let mut request: Vec<u8> = match serde_json::to_vec(&RunRequest::new(function, arguments)) {
+        Ok(request) => request,
+        Err(err) => {
+            return Err(LetError::RpcRequestSerializeError { err });
+        },
+    };
+    request.push(b'\n');
+
+    // Write it, then drop stdin to close it
+    let mut stdin = match process.stdin.take() {
+        Some(stdin) => stdin,
+        None => {
+            return Err(LetError::ClosedStdin);
+        },
+    };
+    if let Err(err) = stdin.write_all(&request).await.and(stdin.flush().await) {
+        return Err(LetError::StdinWriteError { err });
+    }
+    Ok(())
+}
+
 /// **Edited: now returning LetErrors.**
 ///
 /// Creates a map with enviroment variables for the nested package based on the given arguments.
@@ -286,7 +372,7 @@ fn start(
 ///
 /// **Returns**  
 /// A new map with the environment on success, or a LetError on failure.
-fn construct_envs(variables: &Map<FullValue>) -> Result<Map<String>, LetError> {
+pub(crate) fn construct_envs(variables: &Map<FullValue>) -> Result<Map<String>, LetError> {
     // Simply add the values one-by-one
     let mut envs = Map::<String>::new();
     for (name, variable) in variables.iter() {
@@ -389,82 +475,282 @@ fn construct_envs(variables: &Map<FullValue>) -> Result<Map<String>, LetError> {
 
 
 /***** WAITING FOR RESULT *****/
-/// Waits for the given process to complete, then returns its result.
+/// Reads the CPU time that the process with the given PID has used so far.
 ///
 /// **Arguments**
-///  * `process`: The handle to the asynchronous tokio process.
-///  * `callback`: A Callback object to send heartbeats with.
+///  * `pid`: The PID of the process to read the CPU time of.
 ///
 /// **Returns**  
-/// The PackageReturnState describing how the call went on success, or a LetError on failure.
-async fn complete(
-    process: TokioChild,
-    // callback: &mut Option<&mut Callback>,
-) -> Result<PackageReturnState, LetError> {
-    let mut process = process;
+/// The CPU time in milliseconds, or [`None`] if we couldn't find it (e.g., because we're not on Linux or the process is gone).
+fn cpu_time_ms(pid: u32) -> Option<u64> {
+    // The fields after the command (which may contain spaces, but not a parenthesis) are fixed; utime and stime are 14th and 15th overall
+    let stat: String = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks: u64 = fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+    let ticks_per_sec: i64 = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_sec <= 0 {
+        return None;
+    }
+    Some(ticks * 1000 / ticks_per_sec as u64)
+}
 
-    // Handle waiting for the subprocess and doing heartbeats in a neat way, using select
-    let status = loop {
-        // Prepare the timer
-        let sleep = time::sleep(Duration::from_millis(HEARTBEAT_DELAY));
-        tokio::pin!(sleep);
-
-        // Wait for either the timer or the process
-        let status = tokio::select! {
-            status = process.wait() => {
-                // Process is finished!
-                Some(status)
-            },
-            _ = &mut sleep => {
-                // // Timeout occurred; send the heartbeat and continue
-                // if let Some(callback) = callback {
-                //     if let Err(err) = callback.heartbeat().await { warn!("Could not update driver on Heartbeat: {}", err); }
-                //     else { debug!("Sent Heartbeat to driver."); }
-                // }
-
-                // Stop without result
-                None
+/// Reads the resources used by the package once it has completed.
+///
+/// This covers all child processes that we (or tokio, on our behalf) waited for, which is only the package itself.
+///
+/// **Returns**  
+/// A [`ResourceUsage`] summarizing the CPU time, peak memory and disk I/O of the package, or an empty one if we failed to read it.
+fn resource_usage() -> ResourceUsage {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage) } != 0 {
+        warn!("Failed to read resource usage of package: {}", std::io::Error::last_os_error());
+        return ResourceUsage::default();
+    }
+
+    // Note that `ru_maxrss` is already in KiB, and blocks are always 512 bytes
+    let cpu_ms: i64 = (usage.ru_utime.tv_sec + usage.ru_stime.tv_sec) * 1000 + (usage.ru_utime.tv_usec + usage.ru_stime.tv_usec) / 1000;
+    ResourceUsage {
+        cpu_ms: Some(cpu_ms.max(0) as u64),
+        peak_rss_kib: Some(usage.ru_maxrss.max(0) as u64),
+        disk_read_bytes: Some(usage.ru_inblock.max(0) as u64 * 512),
+        disk_write_bytes: Some(usage.ru_oublock.max(0) as u64 * 512),
+        attempts: None,
+    }
+}
+
+/// Sends the given report to the worker by writing it to our stderr.
+///
+/// **Arguments**
+///  * `stderr`: Our stderr, which is shared with the forwarded stderr of the package.
+///  * `report`: The [`BraneletReport`] to send.
+pub(crate) async fn report(stderr: &Mutex<tio::Stderr>, report: BraneletReport) {
+    let mut stderr = stderr.lock().await;
+    if let Err(err) = stderr.write_all(report.to_line().as_bytes()).await {
+        warn!("Failed to send report to worker: {err}");
+    } else if let Err(err) = stderr.flush().await {
+        warn!("Failed to send report to worker: {err}");
+    }
+}
+
+/// Defines what to look for in the stdout of a package, besides forwarding it.
+struct StdoutWatch<'a> {
+    /// Our stderr, on which we report what we find.
+    stderr:   &'a Mutex<tio::Stderr>,
+    /// The readiness marker to look for, if any (and if not found yet).
+    ready:    Option<&'a str>,
+    /// Whether to interpret JSON-RPC messages (see [`crate::rpc`]).
+    rpc:      bool,
+    /// The JSON-RPC response of the package, once it has sent one.
+    response: Option<Message>,
+}
+impl<'a> StdoutWatch<'a> {
+    /// Handles a JSON-RPC message of the package, forwarding notifications to the worker and remembering its response.
+    ///
+    /// **Arguments**
+    ///  * `msg`: The [`Message`] to handle.
+    async fn handle(&mut self, msg: Message) {
+        let to_report: BraneletReport = match msg {
+            Message::Progress { fraction, message } => BraneletReport::Progress { fraction, message },
+            Message::Log { level, message } => BraneletReport::Log { level, message },
+            Message::Partial { value } => BraneletReport::Partial { value: serde_json::to_string(&value).unwrap() },
+            Message::Result { .. } | Message::Error { .. } => {
+                if self.response.is_some() {
+                    warn!("Package sent more than one JSON-RPC response; ignoring all but the first");
+                } else {
+                    self.response = Some(msg);
+                }
+                return;
             },
         };
+        report(self.stderr, to_report).await;
+    }
+}
 
-        // If we have a result, break from the main loop; otherwise, try again
-        if let Some(status) = status {
-            break status;
+/// Forwards the given output of the package line-by-line as it is produced, while also collecting it for when the package completes.
+///
+/// Every line is flushed before the next one is read, such that a slow reader of our output slows down the package instead of us buffering it.
+/// Lines longer than [`MAX_STREAMED_LINE_SIZE`] are truncated, and streaming stops altogether after [`MAX_STREAMED_SIZE`] bytes. The collected output
+/// is always complete.
+///
+/// **Arguments**
+///  * `source`: The package's output to read.
+///  * `target`: Our own output to forward it to.
+///  * `what`: Which output we're forwarding (used for debugging purposes).
+///  * `watch`: If given, things to look for in the output (see [`StdoutWatch`]).
+///
+/// **Returns**  
+/// All the raw output of the package (except for JSON-RPC messages), or an [`std::io::Error`] if we failed to read it.
+async fn stream_output<W: AsyncWrite + Unpin>(
+    source: impl AsyncRead + Unpin,
+    target: &Mutex<W>,
+    what: &str,
+    mut watch: Option<&mut StdoutWatch<'_>>,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut source = BufReader::new(source);
+    let mut text: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
+    let mut line: Vec<u8> = Vec::new();
+    let mut streamed: Option<usize> = Some(0);
+    loop {
+        // Read the next line
+        line.clear();
+        if source.read_until(b'\n', &mut line).await? == 0 {
+            break;
         }
-    };
 
-    // Match the status result
-    let status = match status {
-        Ok(status) => status,
-        Err(err) => {
-            return Err(LetError::PackageRunError { err });
-        },
-    };
+        // Handle JSON-RPC messages instead of treating them as output
+        if let Some(watch) = watch.as_mut().filter(|watch| watch.rpc) {
+            if let Some(msg) = Message::parse(&String::from_utf8_lossy(&line)) {
+                watch.handle(msg).await;
+                continue;
+            }
+        }
+        text.extend_from_slice(&line);
+
+        // Forward it if we're still doing that
+        if let Some(n_streamed) = streamed {
+            let mut target = target.lock().await;
+            let n: usize = line.len().min(MAX_STREAMED_LINE_SIZE);
+            let mut res: Result<(), std::io::Error> = target.write_all(&line[..n]).await;
+            if res.is_ok() && n < line.len() {
+                res = target.write_all(b" [truncated]\n").await;
+            } else if res.is_ok() && !line.ends_with(b"\n") {
+                // Always end with a newline, as whatever we write after the package should start on its own line
+                res = target.write_all(b"\n").await;
+            }
+            if res.is_ok() && n_streamed + n >= MAX_STREAMED_SIZE {
+                res = target.write_all(b"[output limit reached; not streaming any more output]\n").await;
+            }
+            if let Err(err) = res.and(target.flush().await) {
+                warn!("Failed to forward package {what}: {err} (not streaming any more output)");
+                streamed = None;
+            } else {
+                streamed = Some(n_streamed + n).filter(|n_streamed| *n_streamed < MAX_STREAMED_SIZE);
+            }
+        }
+
+        // Report readiness the first time the package says so
+        if let Some(watch) = watch.as_mut() {
+            if let Some(marker) = watch.ready.filter(|marker| String::from_utf8_lossy(&line).trim_end() == *marker) {
+                info!("Reached target 'Ready'");
+                report(watch.stderr, BraneletReport::Ready { marker: marker.into() }).await;
+                watch.ready = None;
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// Waits for the given process to complete, then returns its result.
+///
+/// While waiting, the output of the process is streamed to our own stdout and stderr, and the worker is periodically told that the process is still
+/// alive (see [`BraneletReport`]).
+///
+/// If the process exceeds its timeout, it is sent SIGTERM, and then SIGKILL if it's still running after [`TERMINATION_GRACE_PERIOD`].
+///
+/// **Arguments**
+///  * `process`: The handle to the asynchronous tokio process.
+///  * `ready`: The readiness marker that the package writes to its stdout once it is ready, if any.
+///  * `rpc`: Whether the package speaks JSON-RPC (see [`crate::rpc`]).
+///  * `timeout`: If given, the maximum time the process may run.
+///
+/// **Returns**  
+/// The PackageReturnState describing how the call went, together with the JSON-RPC response of the package (if any), on success, or a LetError on
+/// failure.
+async fn complete(
+    process: TokioChild,
+    ready: Option<&str>,
+    rpc: bool,
+    timeout: Option<Duration>,
+) -> Result<(PackageReturnState, Option<Message>), LetError> {
+    let mut process = process;
 
     // Try to get stdout and stderr readers
-    let mut stdout = match process.stdout {
+    let stdout = match process.stdout.take() {
         Some(stdout) => stdout,
         None => {
             return Err(LetError::ClosedStdout);
         },
     };
-    let mut stderr = match process.stderr {
+    let stderr = match process.stderr.take() {
         Some(stderr) => stderr,
         None => {
             return Err(LetError::ClosedStderr);
         },
     };
-    // Consume the readers into the raw text
-    let mut stdout_text: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
-    let _n_stdout = match stdout.read_to_end(&mut stdout_text).await {
-        Ok(n_stdout) => n_stdout,
+
+    // Our own outputs are shared between the forwarded package output and our reports
+    let our_stdout: Mutex<tio::Stdout> = Mutex::new(tio::stdout());
+    let our_stderr: Mutex<tio::Stderr> = Mutex::new(tio::stderr());
+
+    // Wait for the subprocess, reporting that it is still alive in the meantime (and terminating it if it takes too long)
+    let pid: Option<u32> = process.id();
+    let mut timed_out: bool = false;
+    let wait = async {
+        let mut interval = time::interval(LIVENESS_INTERVAL);
+        let deadline = time::sleep(timeout.unwrap_or_default());
+        tokio::pin!(deadline);
+        let mut killed: bool = false;
+        loop {
+            tokio::select! {
+                status = process.wait() => {
+                    // Process is finished!
+                    break status;
+                },
+                _ = &mut deadline, if timeout.is_some() && !killed => {
+                    if !timed_out {
+                        // Ask it nicely first...
+                        let timeout: Duration = timeout.unwrap();
+                        warn!("Package exceeded its timeout of {}s; terminating it", timeout.as_secs());
+                        report(&our_stderr, BraneletReport::TimedOut { timeout_s: timeout.as_secs() }).await;
+                        if let Some(pid) = pid {
+                            if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+                                warn!("Failed to send SIGTERM to package: {}", std::io::Error::last_os_error());
+                            }
+                        }
+                        timed_out = true;
+                        deadline.as_mut().reset(time::Instant::now() + TERMINATION_GRACE_PERIOD);
+                    } else {
+                        // ...then less nicely
+                        warn!("Package did not terminate within {}s; killing it", TERMINATION_GRACE_PERIOD.as_secs());
+                        if let Err(err) = process.start_kill() {
+                            warn!("Failed to kill package: {err}");
+                        }
+                        killed = true;
+                    }
+                },
+                _ = interval.tick() => {
+                    report(&our_stderr, BraneletReport::Alive { cpu_ms: pid.and_then(cpu_time_ms) }).await;
+                },
+            }
+        }
+    };
+
+    // Consume the readers into the raw text while the process runs, streaming it as we go
+    let mut watch: StdoutWatch = StdoutWatch { stderr: &our_stderr, ready, rpc, response: None };
+    let (status, stdout_text, stderr_text) =
+        tokio::join!(wait, stream_output(stdout, &our_stdout, "stdout", Some(&mut watch)), stream_output(stderr, &our_stderr, "stderr", None));
+    let response: Option<Message> = watch.response;
+
+    // Match the status result
+    let status = match status {
+        Ok(status) => status,
+        Err(err) => {
+            return Err(LetError::PackageRunError { err });
+        },
+    };
+
+    // Now that the package is done, tell the worker what it used
+    let usage: ResourceUsage = resource_usage();
+    info!("Package used: {usage}");
+    report(&our_stderr, BraneletReport::Usage(usage)).await;
+    let stdout_text: Vec<u8> = match stdout_text {
+        Ok(stdout_text) => stdout_text,
         Err(err) => {
             return Err(LetError::StdoutReadError { err });
         },
     };
-    let mut stderr_text: Vec<u8> = Vec::with_capacity(DEFAULT_STD_BUFFER_SIZE);
-    let _n_stderr = match stderr.read_to_end(&mut stderr_text).await {
-        Ok(n_stderr) => n_stderr,
+    let stderr_text: Vec<u8> = match stderr_text {
+        Ok(stderr_text) => stderr_text,
         Err(err) => {
             return Err(LetError::StderrReadError { err });
         },
@@ -477,17 +763,22 @@ async fn complete(
     debug!("Job stdout (unprocessed):\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), stdout, (0..80).map(|_| '-').collect::<String>());
     debug!("Job stderr (unprocessed):\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), stdout, (0..80).map(|_| '-').collect::<String>());
 
+    // If the process was terminated because it took too long, that's what we report
+    if timed_out {
+        return Ok((PackageReturnState::TimedOut { timeout: timeout.unwrap_or_default() }, response));
+    }
+
     // If the process failed, return it does
     if !status.success() {
         // Check if it was killed
         if status.signal().is_some() {
-            return Ok(PackageReturnState::Stopped { signal: status.signal().unwrap() });
+            return Ok((PackageReturnState::Stopped { signal: status.signal().unwrap() }, response));
         }
-        return Ok(PackageReturnState::Failed { code: status.code().unwrap_or(-1), stdout, stderr });
+        return Ok((PackageReturnState::Failed { code: status.code().unwrap_or(-1), stdout, stderr }, response));
     }
 
     // Otherwise, it was a success, so return it as such!
-    Ok(PackageReturnState::Finished { stdout })
+    Ok((PackageReturnState::Finished { stdout }, response))
 }
 
 /// **Edited: returns LetErrors + changed to accept string instead of split stuff.**
@@ -554,7 +845,7 @@ fn preprocess_stdout(stdout: String, mode: &Option<String>) -> String {
 ///
 /// **Returns**  
 /// The decoded return state as a PackageResult, or a LetError otherwise.
-fn decode(result: PackageReturnState, mode: &Option<String>) -> Result<PackageResult, LetError> {
+pub(crate) fn decode(result: PackageReturnState, mode: &Option<String>) -> Result<PackageResult, LetError> {
     // Match on the result
     match result {
         PackageReturnState::Finished { stdout } => {
@@ -593,6 +884,11 @@ fn decode(result: PackageReturnState, mode: &Option<String>) -> Result<PackageRe
             // Simply map the value
             Ok(PackageResult::Stopped { signal })
         },
+
+        PackageReturnState::TimedOut { timeout } => {
+            // Simply map the value
+            Ok(PackageResult::TimedOut { timeout })
+        },
     }
 }
 
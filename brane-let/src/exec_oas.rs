@@ -4,7 +4,7 @@
 //  Created:
 //    20 Sep 2022, 13:57:17
 //  Last edited:
-//    22 May 2023, 10:24:03
+//    15 Oct 2026, 20:47:32
 //  Auto updated?
 //    Yes
 //
@@ -284,6 +284,11 @@ fn decode(result: PackageReturnState) -> Result<PackageResult, LetError> {
             // Simply map the value
             Ok(PackageResult::Stopped { signal })
         },
+
+        PackageReturnState::TimedOut { timeout } => {
+            // Simply map the value
+            Ok(PackageResult::TimedOut { timeout })
+        },
     }
 }
 
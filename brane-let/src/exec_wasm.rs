@@ -0,0 +1,201 @@
+//  EXEC WASM.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 20:58:12
+//  Last edited:
+//    15 Oct 2026, 20:49:19
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Contains code that can execute WebAssembly packages (i.e., the
+//!   Wasm-type) in an embedded `wasmtime` runtime instead of as a separate
+//!   process.
+//
+
+use std::path::{Path, PathBuf};
+
+use brane_exe::FullValue;
+use log::{debug, info};
+use specifications::container::{ActionCommand, LocalContainerInfo};
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+use crate::common::{Map, PackageResult, PackageReturnState};
+use crate::errors::LetError;
+use crate::exec_ecu::{construct_envs, decode, initialize};
+use crate::retry::with_retries;
+
+
+/***** CONSTANTS *****/
+/// The maximum number of bytes of stdout and stderr (each) that we capture from the module.
+const MAX_OUTPUT_SIZE: usize = 16 * 1024 * 1024;
+/// The directories that we give the module access to (under the same path), if they exist. Note that the working directory is always given.
+const PREOPENED_DIRS: [&str; 2] = ["/data", "/result"];
+
+
+
+
+
+/***** ENTRYPOINT *****/
+/// Handles a package containing a WebAssembly module (WASM).
+///
+/// The module is expected to be a WASI command (i.e., export `_start`), and gets the same interface as an ECU package: it receives its arguments
+/// as environment variables, and writes its result to stdout as YAML (optionally captured as set in `container.yml`).
+///
+/// **Arguments**
+///  * `function`: The function name to execute in the package.
+///  * `arguments`: The arguments, as a map of argument name / value pairs.
+///  * `working_dir`: The wokring directory for this package.
+///
+/// **Returns**  
+/// The return state of the package call on success, or a LetError otherwise.
+pub async fn handle(function: String, arguments: Map<FullValue>, working_dir: PathBuf) -> Result<PackageResult, LetError> {
+    debug!("Executing '{}' (wasm) using arguments:\n{:#?}", function, arguments);
+
+    // Initialize the package just like an ECU package
+    let (container_info, function) = initialize(&function, &arguments, &working_dir)?;
+    info!("Reached target 'Initialized'");
+
+    // Prepare the module and its environment
+    let command =
+        function.command.clone().unwrap_or_else(|| ActionCommand { args: Default::default(), capture: None, ready: None, protocol: None });
+    let module_path: PathBuf = module_path(&container_info, &working_dir)?;
+    let envs: Map<String> = construct_envs(&arguments)?;
+    debug!("Using environment variables:\n{:#?}", envs);
+
+    // Run it, retrying it if its policy tells us to
+    with_retries(container_info.retry.as_ref(), || call(&module_path, &command, &envs, &working_dir)).await
+}
+
+/// Performs a single attempt at calling the package function.
+///
+/// **Arguments**
+///  * `module_path`: The path to the module to run.
+///  * `command`: The command of the function to call, which determines the arguments of the module and how to decode its output.
+///  * `envs`: The environment variables (i.e., the function arguments) to pass to the module.
+///  * `working_dir`: The wokring directory for this package.
+///
+/// **Returns**  
+/// The return state of the package call on success, or a LetError otherwise.
+async fn call(module_path: &Path, command: &ActionCommand, envs: &Map<String>, working_dir: &Path) -> Result<PackageResult, LetError> {
+    // Run it; wasmtime is synchronous, so we do so in a blocking task
+    info!("Reached target 'Started'");
+    let (module_path, args, envs, working_dir): (PathBuf, Vec<String>, Map<String>, PathBuf) =
+        (module_path.into(), command.args.clone(), envs.clone(), working_dir.into());
+    let result: PackageReturnState = match tokio::task::spawn_blocking(move || run(&module_path, &args, &envs, &working_dir)).await {
+        Ok(result) => result?,
+        Err(err) => {
+            return Err(LetError::WasmJoinError { err });
+        },
+    };
+    info!("Reached target 'Completed'");
+
+    // Convert the call to a PackageReturn value instead of state
+    let result = decode(result, &command.capture)?;
+    info!("Reached target 'Decode'");
+    Ok(result)
+}
+
+
+
+
+
+/***** EXECUTION *****/
+/// Resolves the path to the WebAssembly module of the package.
+///
+/// **Arguments**
+///  * `container_info`: The LocalContainerInfo representing the container.yml of this package, of which the entrypoint refers to the module.
+///  * `working_dir`: The working directory of the package.
+///
+/// **Returns**  
+/// The canonicalized path to the module, or a LetError if it didn't exist.
+fn module_path(container_info: &LocalContainerInfo, working_dir: &Path) -> Result<PathBuf, LetError> {
+    let path: PathBuf = working_dir.join(&container_info.entrypoint.exec);
+    match path.canonicalize() {
+        Ok(path) => Ok(path),
+        Err(err) => Err(LetError::EntrypointPathError { path, err }),
+    }
+}
+
+/// Runs the given WebAssembly module to completion.
+///
+/// **Arguments**
+///  * `path`: The path to the module to run.
+///  * `args`: The arguments to pass to the module.
+///  * `envs`: The environment variables (i.e., the function arguments) to pass to the module.
+///  * `working_dir`: The working directory of the package, which is made available to the module under the same path.
+///
+/// **Returns**  
+/// The PackageReturnState describing how the call went on success, or a LetError on failure.
+fn run(path: &Path, args: &[String], envs: &Map<String>, working_dir: &Path) -> Result<PackageReturnState, LetError> {
+    // Load the module
+    let engine: Engine = Engine::default();
+    let module: Module = match Module::from_file(&engine, path) {
+        Ok(module) => module,
+        Err(err) => {
+            return Err(LetError::WasmModuleError { path: path.into(), err });
+        },
+    };
+
+    // Prepare the WASI environment, capturing its output
+    let stdout: MemoryOutputPipe = MemoryOutputPipe::new(MAX_OUTPUT_SIZE);
+    let stderr: MemoryOutputPipe = MemoryOutputPipe::new(MAX_OUTPUT_SIZE);
+    let mut wasi: WasiCtxBuilder = WasiCtxBuilder::new();
+    wasi.arg(path.display().to_string()).args(args).stdout(stdout.clone()).stderr(stderr.clone());
+    for (name, value) in envs {
+        wasi.env(name, value);
+    }
+    let working_dir: String = working_dir.display().to_string();
+    for dir in std::iter::once(working_dir.as_str()).chain(PREOPENED_DIRS.into_iter().filter(|dir| Path::new(dir).is_dir())) {
+        if let Err(err) = wasi.preopened_dir(dir, dir, DirPerms::all(), FilePerms::all()) {
+            return Err(LetError::WasmEnvironmentError { err });
+        }
+    }
+    let mut store: Store<WasiP1Ctx> = Store::new(&engine, wasi.build_p1());
+    let mut linker: Linker<WasiP1Ctx> = Linker::new(&engine);
+    if let Err(err) = preview1::add_to_linker_sync(&mut linker, |ctx| ctx) {
+        return Err(LetError::WasmEnvironmentError { err });
+    }
+
+    // Instantiate it and call its entrypoint
+    let start = match linker.instantiate(&mut store, &module).and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, "_start")) {
+        Ok(start) => start,
+        Err(err) => {
+            return Err(LetError::WasmInstantiateError { path: path.into(), err });
+        },
+    };
+    let code: i32 = match start.call(&mut store, ()) {
+        Ok(()) => 0,
+        Err(err) => match err.downcast_ref::<I32Exit>() {
+            Some(I32Exit(code)) => *code,
+            None => {
+                return Err(LetError::WasmTrap { path: path.into(), err });
+            },
+        },
+    };
+
+    // Collect the output
+    drop(store);
+    let stdout: String = String::from_utf8_lossy(&stdout.contents()).to_string();
+    let stderr: String = String::from_utf8_lossy(&stderr.contents()).to_string();
+    debug!(
+        "Module stdout (unprocessed):\n{}\n{}\n{}\n\n",
+        (0..80).map(|_| '-').collect::<String>(),
+        stdout,
+        (0..80).map(|_| '-').collect::<String>()
+    );
+    debug!(
+        "Module stderr (unprocessed):\n{}\n{}\n{}\n\n",
+        (0..80).map(|_| '-').collect::<String>(),
+        stderr,
+        (0..80).map(|_| '-').collect::<String>()
+    );
+    if code != 0 {
+        return Ok(PackageReturnState::Failed { code, stdout, stderr });
+    }
+    Ok(PackageReturnState::Finished { stdout })
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    22 May 2023, 10:13:10
 //  Last edited:
-//    22 May 2023, 10:23:37
+//    15 Oct 2026, 20:49:19
 //  Auto updated?
 //    Yes
 //
@@ -19,3 +19,6 @@ pub mod errors;
 pub mod exec_ecu;
 pub mod exec_nop;
 pub mod exec_oas;
+pub mod exec_wasm;
+pub mod retry;
+pub mod rpc;
@@ -4,7 +4,7 @@
 //  Created:
 //    20 Sep 2022, 13:53:43
 //  Last edited:
-//    02 Oct 2023, 17:13:16
+//    16 Oct 2026, 00:33:35
 //  Auto updated?
 //    Yes
 //
@@ -15,14 +15,16 @@
 
 use std::path::PathBuf;
 use std::process;
+use std::time::Duration;
 
 use brane_let::common::PackageResult;
 use brane_let::errors::LetError;
-use brane_let::{exec_ecu, exec_nop, exec_oas};
+use brane_let::{exec_ecu, exec_nop, exec_oas, exec_wasm};
 use clap::Parser;
 use dotenvy::dotenv;
 use log::{debug, warn, LevelFilter};
 use serde::de::DeserializeOwned;
+use specifications::reporting::{BraneletReport, TIMEOUT_EXIT_CODE};
 
 
 /***** CONSTANTS *****/
@@ -51,6 +53,9 @@ struct Opts {
     proxy_address: Option<String>,
     #[clap(short, long, env = "BRANE_MOUNT_DFS")]
     mount_dfs: Option<String>,
+    /// If given, the maximum number of seconds the package may run before it is terminated (only for ECU packages)
+    #[clap(long, env = "BRANE_TIMEOUT")]
+    timeout: Option<u64>,
     /// Prints debug info
     #[clap(short, long, action, env = "DEBUG")]
     debug: bool,
@@ -83,6 +88,16 @@ enum SubCommand {
         #[clap(short, long, env = "BRANE_WORKDIR", default_value = "/opt/wd")]
         working_dir: PathBuf,
     },
+    /// Execute a WebAssembly module and return output
+    #[clap(name = "wasm")]
+    Wasm {
+        /// Function to execute
+        function:    String,
+        /// Input arguments (encoded, as Base64'ed JSON)
+        arguments:   String,
+        #[clap(short, long, env = "BRANE_WORKDIR", default_value = "/opt/wd")]
+        working_dir: PathBuf,
+    },
 }
 
 
@@ -94,7 +109,7 @@ enum SubCommand {
 async fn main() {
     // Parse the arguments
     dotenv().ok();
-    let Opts { proxy_address, debug, sub_command, .. } = Opts::parse();
+    let Opts { proxy_address, timeout, debug, sub_command, .. } = Opts::parse();
 
     // Configure logger.
     let mut logger = env_logger::builder();
@@ -145,7 +160,7 @@ async fn main() {
     // };
 
     // Wrap actual execution, so we can always log errors.
-    match run(sub_command).await {
+    match run(sub_command, timeout.map(Duration::from_secs)).await {
         Ok(code) => process::exit(code),
         Err(err) => {
             log::error!("{}", err);
@@ -160,12 +175,14 @@ async fn main() {
 ///
 /// **Arguments**
 ///  * `sub_command`: The subcommand to execute (is it code, oas or nop?)
+///  * `timeout`: If given, the maximum time the package may run before it is terminated.
 ///  * `callback`: The Callback future that asynchronously constructs a Callback instance.
 ///
 /// **Returns**  
 /// The exit code of the nested application on success, or a LetError otherwise.
 async fn run(
     sub_command: SubCommand,
+    timeout: Option<Duration>,
     // callback: Option<Callback>,
 ) -> Result<i32, LetError> {
     // // We've initialized!
@@ -175,8 +192,9 @@ async fn run(
 
     // Switch on the sub_command to do the actual work
     let output = match sub_command {
-        SubCommand::Code { function, arguments, working_dir } => exec_ecu::handle(function, decode_b64(arguments)?, working_dir).await,
+        SubCommand::Code { function, arguments, working_dir } => exec_ecu::handle(function, decode_b64(arguments)?, working_dir, timeout).await,
         SubCommand::WebApi { function, arguments, working_dir } => exec_oas::handle(function, decode_b64(arguments)?, working_dir).await,
+        SubCommand::Wasm { function, arguments, working_dir } => exec_wasm::handle(function, decode_b64(arguments)?, working_dir).await,
         SubCommand::NoOp {} => exec_nop::handle().await,
     };
 
@@ -246,9 +264,18 @@ async fn run(
             log::error!("Internal package call was forcefully stopped with signal {}", signal);
             // }
 
+            // Let the worker know which signal it was, since that doesn't survive our exit code
+            eprint!("{}", BraneletReport::Stopped { signal }.to_line());
+
             Ok(-1)
         },
 
+        Ok(PackageResult::TimedOut { timeout }) => {
+            // Print to stderr
+            log::error!("Internal package call exceeded its timeout of {}s and was terminated", timeout.as_secs());
+            Ok(TIMEOUT_EXIT_CODE)
+        },
+
         Err(err) => {
             // Just pass the error
             Err(err)
@@ -0,0 +1,92 @@
+//  RETRY.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:40:12
+//  Last edited:
+//    15 Oct 2026, 21:40:12
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements retrying package calls according to the retry policy in
+//!   their `container.yml`.
+//
+
+use std::future::Future;
+
+use log::{info, warn};
+use specifications::container::{RetryCondition, RetryPolicy};
+use specifications::reporting::BraneletReport;
+use tokio::io as tio;
+use tokio::sync::Mutex;
+
+use crate::common::PackageResult;
+use crate::errors::LetError;
+use crate::exec_ecu::report;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Determines why a package call failed, if it did.
+///
+/// **Arguments**
+///  * `result`: The PackageResult of the call.
+///
+/// **Returns**  
+/// The RetryCondition that the failure falls under and a human-readable reason, or [`None`] if the call succeeded.
+fn failure(result: &PackageResult) -> Option<(RetryCondition, String)> {
+    match result {
+        PackageResult::Finished { .. } => None,
+        PackageResult::Failed { code, .. } => Some((RetryCondition::NonzeroExit, format!("exit code {code}"))),
+        PackageResult::Stopped { signal } => Some((RetryCondition::NonzeroExit, format!("stopped by signal {signal}"))),
+        PackageResult::TimedOut { timeout } => Some((RetryCondition::Timeout, format!("timed out after {}s", timeout.as_secs()))),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Calls a package, retrying it according to its retry policy.
+///
+/// Errors of `branelet` itself (i.e., [`LetError`]s) are never retried, as these are not caused by the package.
+///
+/// **Arguments**
+///  * `policy`: The retry policy of the package, if any. If omitted, the package is called exactly once.
+///  * `attempt`: A closure that performs a single attempt at calling the package.
+///
+/// **Returns**  
+/// The PackageResult of the last attempt, or a LetError if any of them errored.
+pub async fn with_retries<F, Fut>(policy: Option<&RetryPolicy>, mut attempt: F) -> Result<PackageResult, LetError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<PackageResult, LetError>>,
+{
+    let policy: &RetryPolicy = match policy {
+        Some(policy) => policy,
+        None => return attempt().await,
+    };
+
+    let max: u32 = policy.max.saturating_add(1);
+    let mut i: u32 = 1;
+    loop {
+        let result: PackageResult = attempt().await?;
+        match failure(&result) {
+            Some((condition, reason)) if i < max && policy.on.contains(&condition) => {
+                warn!("Attempt {i}/{max} failed ({reason}); retrying in {:.1}s", policy.backoff.as_secs_f64());
+                report(&Mutex::new(tio::stderr()), BraneletReport::Retrying {
+                    attempt: i,
+                    max,
+                    reason,
+                    backoff_ms: policy.backoff.as_millis() as u64,
+                })
+                .await;
+                tokio::time::sleep(policy.backoff).await;
+                i += 1;
+                info!("Starting attempt {i}/{max}");
+            },
+            _ => return Ok(result),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//  RPC.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:10:37
+//  Last edited:
+//    15 Oct 2026, 21:10:37
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the JSON-RPC protocol that package code may use to talk to
+//!   `branelet` instead of only writing its result to stdout.
+//!
+//!   The protocol is enabled per action by setting `protocol: json_rpc` in
+//!   the action's `command` in `container.yml`. It then works as follows:
+//!   - The package still gets its arguments as environment variables, but
+//!     `branelet` also writes a single [JSON-RPC 2.0](https://www.jsonrpc.org/specification)
+//!     request to its stdin (followed by a newline, after which stdin is
+//!     closed):
+//!     ```json
+//!     {"jsonrpc": "2.0", "id": 1, "method": "run", "params": {"function": "<name>", "arguments": { ... }}}
+//!     ```
+//!   - The package writes JSON-RPC messages to its stdout, one per line.
+//!     Any line that isn't one is treated as normal output. It may send the
+//!     following notifications (i.e., without `id`) while running:
+//!     - `progress` with `{"fraction": <0.0-1.0>, "message": "<text>"}`
+//!       (both optional) to report how far along it is;
+//!     - `log` with `{"level": "<error|warn|info|debug>", "message": "<text>"}`
+//!       to write a structured log message; and
+//!     - `partial` with `{"value": <value>}` to report a partial result.
+//!   - Finally, it writes a response with `"id": 1` and either a `result`
+//!     (the value returned by the function, encoded as a `FullValue`) or an
+//!     `error` (`{"code": <int>, "message": "<text>"}`) that fails the task.
+//!     If the package exits without sending a response, its stdout is
+//!     decoded as usual.
+//!
+//!   All notifications are forwarded to the worker as
+//!   [`BraneletReport`](specifications::reporting::BraneletReport)s.
+//
+
+use brane_exe::FullValue;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::Map;
+
+
+/***** CONSTANTS *****/
+/// The JSON-RPC version we implement.
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// The ID of the one request that we send to the package.
+pub const RUN_REQUEST_ID: u64 = 1;
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Any JSON-RPC message as it is written by a package, before we know what it is.
+#[derive(Debug, Deserialize)]
+struct RawMessage {
+    /// The JSON-RPC version, which must be [`JSONRPC_VERSION`].
+    jsonrpc: String,
+    /// The ID of the request that this message is a response to, if any.
+    id:      Option<Value>,
+    /// The method of a notification.
+    method:  Option<String>,
+    /// The parameters of a notification.
+    #[serde(default)]
+    params:  Value,
+    /// The result of a successful response.
+    #[serde(default)]
+    result:  Value,
+    /// The error of a failed response.
+    error:   Option<RpcError>,
+}
+
+/// The parameters of a `progress`-notification.
+#[derive(Debug, Deserialize)]
+struct ProgressParams {
+    /// How far along the package is, as a fraction between 0 and 1.
+    fraction: Option<f64>,
+    /// What the package is currently doing.
+    message:  Option<String>,
+}
+
+/// The parameters of a `log`-notification.
+#[derive(Debug, Deserialize)]
+struct LogParams {
+    /// The level of the message.
+    #[serde(default = "default_log_level")]
+    level:   String,
+    /// The message itself.
+    message: String,
+}
+
+/// The parameters of a `partial`-notification.
+#[derive(Debug, Deserialize)]
+struct PartialParams {
+    /// The partial result.
+    value: FullValue,
+}
+
+/// The level of log messages that don't specify one.
+fn default_log_level() -> String { "info".into() }
+
+
+
+
+
+/***** LIBRARY *****/
+/// The request that `branelet` sends to the package.
+#[derive(Debug, Serialize)]
+pub struct RunRequest<'a> {
+    /// The JSON-RPC version, always [`JSONRPC_VERSION`].
+    pub jsonrpc: &'static str,
+    /// The ID of the request, always [`RUN_REQUEST_ID`].
+    pub id:      u64,
+    /// The method called, always `run`.
+    pub method:  &'static str,
+    /// The function to run and its arguments.
+    pub params:  RunParams<'a>,
+}
+impl<'a> RunRequest<'a> {
+    /// Constructor for the RunRequest.
+    ///
+    /// **Arguments**
+    ///  * `function`: The name of the function to run.
+    ///  * `arguments`: The arguments to run it with.
+    ///
+    /// **Returns**  
+    /// A new RunRequest, ready to be serialized.
+    #[inline]
+    pub fn new(function: &'a str, arguments: &'a Map<FullValue>) -> Self {
+        Self { jsonrpc: JSONRPC_VERSION, id: RUN_REQUEST_ID, method: "run", params: RunParams { function, arguments } }
+    }
+}
+
+/// The parameters of the [`RunRequest`].
+#[derive(Debug, Serialize)]
+pub struct RunParams<'a> {
+    /// The name of the function to run.
+    pub function:  &'a str,
+    /// The arguments to run it with.
+    pub arguments: &'a Map<FullValue>,
+}
+
+
+
+/// The error that a package may respond with.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RpcError {
+    /// Some code identifying the error, used as the exit code of the function.
+    pub code:    i64,
+    /// A message describing the error.
+    pub message: String,
+}
+
+
+
+/// Defines the messages that a package may send to `branelet`.
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// The package reports how far along it is.
+    Progress { fraction: Option<f64>, message: Option<String> },
+    /// The package writes a structured log message.
+    Log { level: String, message: String },
+    /// The package reports a partial result.
+    Partial { value: FullValue },
+    /// The package has completed and returned the given value.
+    Result { value: FullValue },
+    /// The package has failed with the given error.
+    Error { err: RpcError },
+}
+impl Message {
+    /// Parses a line of the package's stdout as a JSON-RPC message.
+    ///
+    /// **Arguments**
+    ///  * `line`: The line to parse.
+    ///
+    /// **Returns**  
+    /// The parsed Message, or [`None`] if the line isn't a (supported) JSON-RPC message and should be treated as normal output instead.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line: &str = line.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        let raw: RawMessage = serde_json::from_str(line).ok()?;
+        if raw.jsonrpc != JSONRPC_VERSION {
+            return None;
+        }
+
+        // Responses have an ID, notifications don't
+        if raw.id.is_some() {
+            return match raw.error {
+                Some(err) => Some(Self::Error { err }),
+                None => Some(Self::Result { value: serde_json::from_value(raw.result).ok()? }),
+            };
+        }
+        match raw.method?.as_str() {
+            "progress" => {
+                let ProgressParams { fraction, message } = serde_json::from_value(raw.params).ok()?;
+                Some(Self::Progress { fraction, message })
+            },
+            "log" => {
+                let LogParams { level, message } = serde_json::from_value(raw.params).ok()?;
+                Some(Self::Log { level, message })
+            },
+            "partial" => {
+                let PartialParams { value } = serde_json::from_value(raw.params).ok()?;
+                Some(Self::Partial { value })
+            },
+            _ => None,
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Feb 2024, 15:24:59
 //  Last edited:
-//    08 Feb 2024, 15:28:54
+//    16 Oct 2026, 00:18:28
 //  Auto updated?
 //    Yes
 //
@@ -12,7 +12,7 @@
 //!   Defines the shared context for all paths in the server.
 //
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::time::Instant;
 
@@ -21,6 +21,19 @@ use parking_lot::Mutex;
 
 
 /***** LIBRARY *****/
+/// The state of the snippets planned so far in one session.
+#[derive(Debug)]
+pub struct Session {
+    /// When a snippet was last planned in this session.
+    pub last_used: Instant,
+    /// The locations where the intermediate results of earlier snippets are available.
+    pub results:   HashMap<String, String>,
+    /// The residency zones that the intermediate results of earlier snippets inherited from their input.
+    pub residency: HashMap<String, HashSet<String>>,
+}
+
+
+
 /// The shared context for all paths in the planner server.
 #[derive(Debug)]
 pub struct Context {
@@ -30,5 +43,5 @@ pub struct Context {
     pub proxy: ProxyClient,
 
     /// A map of previously planned snippets.
-    pub state: Mutex<HashMap<String, (Instant, HashMap<String, String>)>>,
+    pub state: Mutex<HashMap<String, Session>>,
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 17:27:16
 //  Last edited:
-//    08 Feb 2024, 17:12:35
+//    16 Oct 2026, 02:07:43
 //  Auto updated?
 //    Yes
 //
@@ -29,19 +29,21 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{CentralConfig, NodeConfig};
-use brane_plr::context::Context;
+use brane_plr::context::{Context, Session};
 use brane_plr::planner;
 use brane_prx::client::ProxyClient;
+use brane_shr::logging::{self, LogFormat};
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::trace;
 use humanlog::{DebugMode, HumanLogger};
-use log::{debug, error, info, warn};
+use log::{debug, error, info, warn, LevelFilter};
 use parking_lot::Mutex;
+use specifications::telemetry::TRACEPARENT_HEADER;
 use tokio::signal::unix::{signal, Signal, SignalKind};
 use warp::Filter as _;
 
@@ -64,6 +66,15 @@ struct Opts {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
 }
 
 
@@ -78,8 +89,17 @@ async fn main() {
     let opts = Opts::parse();
 
     // Configure the logger.
-    if let Err(err) = HumanLogger::terminal(if opts.trace { DebugMode::Full } else { DebugMode::Debug }).init() {
-        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+    match opts.log_format {
+        LogFormat::Text => {
+            if let Err(err) = HumanLogger::terminal(if opts.trace { DebugMode::Full } else { DebugMode::Debug }).init() {
+                eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+            }
+        },
+        LogFormat::Json => {
+            if let Err(err) = logging::init("brane-plr", LogFormat::Json, if opts.trace { LevelFilter::Trace } else { LevelFilter::Debug }) {
+                eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+            }
+        },
     }
     info!("Initializing brane-plr v{}...", env!("CARGO_PKG_VERSION"));
 
@@ -92,6 +112,9 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    if let Some(tracing) = &node_config.tracing {
+        tracing.init("brane-plr");
+    }
     let central_cfg: CentralConfig = match node_config.node.try_into_central() {
         Some(config) => config,
         None => {
@@ -106,20 +129,31 @@ async fn main() {
         let proxy: ProxyClient = ProxyClient::new(central_cfg.services.prx.address());
 
         // The state of previously planned workflow snippets per-instance.
-        let state: Mutex<HashMap<String, (Instant, HashMap<String, String>)>> = Mutex::new(HashMap::new());
+        let state: Mutex<HashMap<String, Session>> = Mutex::new(HashMap::new());
 
         // Build the context
         Arc::new(Context { node_config_path: opts.node_config_path, proxy, state })
     };
 
     // Next, create the warp server
+    let what_if_context: Arc<Context> = context.clone();
     let plan = warp::post()
         .and(warp::path("plan"))
         .and(warp::path::end())
         .and(warp::any().map(move || context.clone()))
+        .and(warp::header::optional::<String>(TRACEPARENT_HEADER))
         .and(warp::body::json())
         .and_then(planner::handle);
-    let paths = plan;
+    let what_if = warp::post()
+        .and(warp::path("admin"))
+        .and(warp::path("what-if"))
+        .and(warp::path::end())
+        .and(warp::any().map(move || what_if_context.clone()))
+        .and(warp::body::json())
+        .and_then(planner::handle_what_if);
+    let health = warp::get().and(warp::path("health")).and(warp::path::end()).map(|| "OK!\n");
+    let version = warp::get().and(warp::path("version")).and(warp::path::end()).map(|| format!("v{}", env!("CARGO_PKG_VERSION")));
+    let paths = plan.or(what_if).or(health).or(version);
 
     // Launch it
     let handle = warp::serve(paths).try_bind_with_graceful_shutdown(central_cfg.services.plr.bind, async {
@@ -4,7 +4,7 @@
 //  Created:
 //    25 Oct 2022, 11:35:00
 //  Last edited:
-//    08 Feb 2024, 17:33:49
+//    16 Oct 2026, 01:54:00
 //  Auto updated?
 //    Yes
 //
@@ -28,7 +28,8 @@ use brane_cfg::info::Info as _;
 use brane_cfg::infra::{InfraFile, InfraLocation};
 use brane_cfg::node::{CentralConfig, NodeConfig};
 use brane_prx::client::ProxyClient;
-use brane_tsk::api::get_data_index;
+use brane_shr::suggest::suggest;
+use brane_tsk::api::{get_data_index, RemotePackageProvider};
 use brane_tsk::errors::PlanError;
 use error_trace::trace;
 use log::{debug, error, info};
@@ -37,14 +38,17 @@ use reqwest::StatusCode;
 use serde_json::Value;
 use specifications::address::Address;
 use specifications::data::{AccessKind, AvailabilityKind, DataIndex, DataName, PreprocessKind};
-use specifications::package::Capability;
-use specifications::planning::{PlanningDeniedReply, PlanningReply, PlanningRequest};
+use specifications::errors::{ErrorCode, ERROR_CODE_HEADER};
+use specifications::package::{Capability, PackageIndexProvider as _, PackageInfo, PackageResources};
+use specifications::planning::{Constraints, PlanningDeniedReply, PlanningReply, PlanningRequest, ResidencyViolation, WhatIfReply, WhatIfRequest};
 use specifications::profiling::ProfileReport;
+use specifications::telemetry::{Span, TraceContext};
 use specifications::working::{CheckReply, CheckWorkflowRequest, JobServiceClient};
+use warp::http::HeaderValue;
 use warp::reject::Rejection;
 use warp::reply::Response;
 
-use crate::context::Context;
+use crate::context::{Context, Session};
 
 
 /***** CONSTANTS *****/
@@ -56,13 +60,14 @@ const SESSION_TIMEOUT_S: u64 = 12 * 3600;
 
 
 /***** HELPER MACROS *****/
-/// Sends back an error as a Response.
+/// Sends back an error as a Response, with the [`ErrorCode`] that describes it in the [`ERROR_CODE_HEADER`].
 macro_rules! err_response {
-    (bad_request $(,)? $($msg:tt)* ) => {{
+    (bad_request $code:expr, $($msg:tt)* ) => {{
         let msg: String = format!($($msg)*);
         debug!("{msg}");
         let mut res: Response = Response::new(msg.into());
         *res.status_mut() = StatusCode::BAD_REQUEST;
+        res.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static($code.as_str()));
         Ok(res)
     }};
 
@@ -70,14 +75,53 @@ macro_rules! err_response {
         error!($($msg)*);
         let mut res: Response = Response::new("An internal server error has occurred".into());
         *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        res.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::Internal.as_str()));
         Ok(res)
     }};
 
     (unauthorized $(,)? $req:expr ) => {{
         let mut res: Response = Response::new($req);
         *res.status_mut() = StatusCode::UNAUTHORIZED;
+        res.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::PlrPolicyDenied.as_str()));
         Ok(res)
     }};
+
+    (residency $(,)? $violation:expr ) => {{
+        let violation: ResidencyViolation = $violation;
+        debug!("Task '{}' may not be planned on '{}' because of the residency of '{}'", violation.task, violation.location, violation.data);
+        let mut res: Response = Response::new(serde_json::to_string(&violation).unwrap().into());
+        *res.status_mut() = StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS;
+        res.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::PlrResidencyViolation.as_str()));
+        Ok(res)
+    }};
+}
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// The hypothetical environment in which the what-if sandbox plans a workflow.
+#[derive(Debug)]
+struct Sandbox {
+    /// If given, the infrastructure file (as JSON) that replaces the instance's own.
+    infra: Option<Value>,
+    /// The capabilities of locations that replace those reported by `brane-api`.
+    capabilities: HashMap<String, HashSet<Capability>>,
+}
+
+
+/// Keeps track of the placement constraints of a workflow while planning it.
+#[derive(Debug)]
+struct ConstraintState<'c> {
+    /// The constraints of the workflow.
+    constraints: &'c Constraints,
+    /// The index of the function of which we are planning the edges, or [`None`] if it's `<main>`.
+    func: Option<usize>,
+    /// The locations on which the co-location groups in the constraints have been planned so far.
+    placed: HashMap<usize, String>,
+    /// The residency zones that intermediate results inherited from the data they were computed from.
+    residency: HashMap<String, HashSet<String>>,
 }
 
 
@@ -85,6 +129,60 @@ macro_rules! err_response {
 
 
 /***** HELPER FUNCTIONS *****/
+/// Fetches the capabilities of a location from `brane-api`.
+///
+/// # Arguments
+/// - `api_addr`: The address where we can reach the `brane-api` service on.
+/// - `location`: The name of the location to fetch the capabilities of.
+///
+/// # Returns
+/// The set of [`Capability`]s that the location supports.
+///
+/// # Errors
+/// This function errors if we failed to reach `brane-api` or if it did not know the location.
+async fn fetch_capabilities(api_addr: &Address, location: &str) -> Result<HashSet<Capability>, PlanError> {
+    let address: String = format!("{api_addr}/infra/capabilities/{location}");
+    let res: reqwest::Response = match reqwest::get(&address).await {
+        Ok(req) => req,
+        Err(err) => {
+            return Err(PlanError::RequestError { address, err });
+        },
+    };
+    if !res.status().is_success() {
+        return Err(PlanError::RequestFailure { address, code: res.status(), err: res.text().await.ok() });
+    }
+    let capabilities: String = match res.text().await {
+        Ok(caps) => caps,
+        Err(err) => {
+            return Err(PlanError::RequestBodyError { address, err });
+        },
+    };
+    match serde_json::from_str(&capabilities) {
+        Ok(caps) => Ok(caps),
+        Err(err) => Err(PlanError::RequestParseError { address, raw: capabilities, err }),
+    }
+}
+
+/// Collects where the task calls in a planned workflow are planned.
+///
+/// # Arguments
+/// - `workflow`: The planned [`Workflow`].
+///
+/// # Returns
+/// A map of the locations of every task call, by their program counter (see [`Constraints::task_key()`]).
+fn placements(workflow: &Workflow) -> HashMap<String, String> {
+    let funcs = workflow.funcs.iter().map(|(idx, edges)| (Some(*idx), edges));
+    let mut placements: HashMap<String, String> = HashMap::new();
+    for (func, edges) in std::iter::once((None, &*workflow.graph)).chain(funcs) {
+        for (pc, edge) in edges.iter().enumerate() {
+            if let Edge::Node { at: Some(at), .. } = edge {
+                placements.insert(Constraints::task_key(func, pc), at.clone());
+            }
+        }
+    }
+    placements
+}
+
 /// Helper function that plans the given list of edges.
 ///
 /// # Arguments
@@ -93,6 +191,9 @@ macro_rules! err_response {
 /// - `api_addr`: The address where we can reach the `brane-api` service on. Used for asserting that the target domain supports what the package needs.
 /// - `dindex`: The DataIndex we use to resolve data references.
 /// - `infra`: The infrastructure to resolve locations.
+/// - `capabilities`: Capabilities of locations that override those reported by `brane-api` (used by the what-if sandbox).
+/// - `resources`: The minimum resources declared by the packages of the tasks in the `table`, by task index.
+/// - `cons`: The ConstraintState that tracks the placement constraints of the workflow.
 /// - `pc`: The initial value for the program counter. You should use '0' if you're calling this function.
 /// - `merge`: The number of the edge until which we will run. You should use 'None' if you're calling this function.
 /// - `deferred`: Whether or not to show errors when an intermediate result is not generated yet (false) or not (true).
@@ -111,6 +212,9 @@ async fn plan_edges(
     api_addr: &Address,
     dindex: &DataIndex,
     infra: &InfraFile,
+    capabilities: &HashMap<String, HashSet<Capability>>,
+    resources: &HashMap<usize, PackageResources>,
+    cons: &mut ConstraintState<'_>,
     pc: usize,
     merge: Option<usize>,
     deferred: bool,
@@ -126,10 +230,35 @@ async fn plan_edges(
         }
         done.insert(pc);
         match edge {
-            Edge::Node { task, locs, at, input, result, metadata: _, next } => {
+            Edge::Node { task, locs, at, input, result, metadata: _, cache: _, next } => {
                 // This is the node where it all revolves around, in the end
                 debug!("Planning task '{}' (edge {})...", table.tasks[*task].name(), pc);
 
+                // Narrow the locations down to those allowed by the workflow's placement constraints, if there are any for this call
+                let key: String = Constraints::task_key(cons.func, pc);
+                let group: Option<usize> = cons.constraints.group(&key);
+                if cons.constraints.constrains(&key, input.keys().map(DataName::name)) {
+                    let candidates: Vec<String> = match locs {
+                        Locations::All => infra.iter().map(|(name, _)| name.clone()).collect(),
+                        Locations::Restricted(locs) => locs.clone(),
+                    };
+                    let allowed: Vec<String> = candidates
+                        .into_iter()
+                        .filter(|name| {
+                            infra
+                                .get(name)
+                                .map(|info| cons.constraints.allows(&key, name, &info.labels, input.keys().map(DataName::name)))
+                                .unwrap_or(false)
+                        })
+                        .filter(|name| group.and_then(|group| cons.placed.get(&group)).map(|placed| placed == name).unwrap_or(true))
+                        .collect();
+                    debug!("Locations allowed for task '{}' by the placement constraints: {:?}", table.tasks[*task].name(), allowed);
+                    if allowed.is_empty() {
+                        return Err(PlanError::UnsatisfiableConstraints { name: table.tasks[*task].name().into(), key, locs: locs.clone() });
+                    }
+                    *locs = Locations::Restricted(allowed);
+                }
+
                 // If everything is allowed, we make it one easier for the planner by checking we happen to find only one occurrance based on the datasets
                 if locs.is_all() {
                     // Search all of the input to collect a list of possible locations
@@ -142,7 +271,7 @@ async fn plan_edges(
                                 // Simply add all locations where it lives
                                 data_locs.append(&mut info.access.keys().collect::<Vec<&String>>());
                             } else {
-                                return Err(PlanError::UnknownDataset { name: name.clone() });
+                                return Err(PlanError::UnknownDataset { name: name.clone(), suggestions: suggest(name, dindex.names()) });
                             }
                         }
                     }
@@ -153,34 +282,78 @@ async fn plan_edges(
                     }
                 }
 
+                // Find the residency zones that the input is restricted to, and prefer the locations that are part of them if we still have a choice
+                let residency: Vec<(String, HashSet<String>)> = input
+                    .keys()
+                    .filter_map(|name| match name {
+                        DataName::Data(dname) => dindex.get(dname).map(|info| (dname.clone(), info.residency.clone())),
+                        DataName::IntermediateResult(iname) => cons.residency.get(iname).map(|zones| (iname.clone(), zones.clone())),
+                    })
+                    .filter(|(_, zones)| !zones.is_empty())
+                    .collect();
+                if !residency.is_empty() && (!locs.is_restrictive() || locs.restricted().len() > 1) {
+                    let candidates: Vec<String> = match locs {
+                        Locations::All => infra.iter().map(|(name, _)| name.clone()).collect(),
+                        Locations::Restricted(locs) => locs.clone(),
+                    };
+                    let resident: Vec<String> = candidates
+                        .into_iter()
+                        .filter(|name| infra.get(name).map(|info| residency.iter().all(|(_, zones)| zones.is_subset(&info.zones))).unwrap_or(false))
+                        .collect();
+                    debug!("Locations in the residency zones of the input of task '{}': {:?}", table.tasks[*task].name(), resident);
+                    if !resident.is_empty() {
+                        *locs = Locations::Restricted(resident);
+                    }
+                }
+
+                // If we still have a choice, narrow it down to the locations that (may) satisfy the task's requirements according to the infrastructure file
+                if !locs.is_restrictive() || locs.restricted().len() > 1 {
+                    if let TaskDef::Compute(ComputeTaskDef { requirements, .. }) = &table.tasks[*task] {
+                        let candidates: Vec<String> = match locs {
+                            Locations::All => infra.iter().map(|(name, _)| name.clone()).collect(),
+                            Locations::Restricted(locs) => locs.clone(),
+                        };
+                        let suitable: Vec<String> = candidates
+                            .into_iter()
+                            .filter(|name| {
+                                infra.get(name).map(|info| info.capabilities.satisfies(requirements, resources.get(&*task))).unwrap_or(false)
+                            })
+                            .collect();
+                        debug!("Locations suitable for task '{}' based on their capabilities: {:?}", table.tasks[*task].name(), suitable);
+                        if !suitable.is_empty() {
+                            *locs = Locations::Restricted(suitable);
+                        }
+                    }
+                }
+
                 // We resolve all locations by collapsing them to the only possibility indicated by the user. More or less than zero? Error!
                 if !locs.is_restrictive() || locs.restricted().len() != 1 {
                     return Err(PlanError::AmbigiousLocationError { name: table.tasks[*task].name().into(), locs: locs.clone() });
                 }
                 let location: &str = &locs.restricted()[0];
 
-                // Fetch the list of capabilities supported by the planned location
-                let address: String = format!("{api_addr}/infra/capabilities/{location}");
-                let res: reqwest::Response = match reqwest::get(&address).await {
-                    Ok(req) => req,
-                    Err(err) => {
-                        return Err(PlanError::RequestError { address, err });
-                    },
-                };
-                if !res.status().is_success() {
-                    return Err(PlanError::RequestFailure { address, code: res.status(), err: res.text().await.ok() });
+                // Refuse to move any of the input outside of its residency zones
+                if let Some(info) = infra.get(location) {
+                    for (data, zones) in &residency {
+                        let mut missing: Vec<String> = zones.difference(&info.zones).cloned().collect();
+                        if !missing.is_empty() {
+                            missing.sort();
+                            return Err(PlanError::ResidencyViolation {
+                                violation: ResidencyViolation {
+                                    task:     table.tasks[*task].name().into(),
+                                    location: location.into(),
+                                    data:     data.clone(),
+                                    zones:    missing,
+                                },
+                            });
+                        }
+                    }
                 }
-                let capabilities: String = match res.text().await {
-                    Ok(caps) => caps,
-                    Err(err) => {
-                        return Err(PlanError::RequestBodyError { address, err });
-                    },
-                };
-                let capabilities: HashSet<Capability> = match serde_json::from_str(&capabilities) {
-                    Ok(caps) => caps,
-                    Err(err) => {
-                        return Err(PlanError::RequestParseError { address, raw: capabilities, err });
-                    },
+
+                // Fetch the list of capabilities supported by the planned location
+                let capabilities: HashSet<Capability> = match capabilities.get(location) {
+                    Some(capabilities) => capabilities.clone(),
+                    None => fetch_capabilities(api_addr, location).await?,
                 };
 
                 // Assert that this is what we need
@@ -199,6 +372,9 @@ async fn plan_edges(
 
                 // It checks out, plan it
                 *at = Some(location.into());
+                if let Some(group) = group {
+                    cons.placed.entry(group).or_insert_with(|| location.into());
+                }
                 debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), location);
 
                 // For all dataset/intermediate result inputs, we check if these are available on the planned location.
@@ -224,7 +400,7 @@ async fn plan_edges(
                                     });
                                 }
                             } else {
-                                return Err(PlanError::UnknownDataset { name: dname.clone() });
+                                return Err(PlanError::UnknownDataset { name: dname.clone(), suggestions: suggest(dname, dindex.names()) });
                             }
                         },
 
@@ -265,6 +441,14 @@ async fn plan_edges(
                     // Insert an entry in the list detailling where to access it and how
                     debug!("Making intermediate result '{}' accessible after execution of '{}' on '{}'", name, table.tasks[*task].name(), location);
                     table.results.insert(name.clone(), location.into());
+
+                    // It's restricted to the residency zones of the data it was computed from
+                    let zones: HashSet<String> = residency.into_iter().flat_map(|(_, zones)| zones).collect();
+                    if !zones.is_empty() {
+                        cons.residency.insert(name.clone(), zones);
+                    } else {
+                        cons.residency.remove(name.as_str());
+                    }
                 }
 
                 // Move to the one indicated by 'next'
@@ -286,10 +470,10 @@ async fn plan_edges(
                 let merge: Option<usize> = *merge;
 
                 // First analyse the true_next branch, until it reaches the merge (or quits)
-                plan_edges(table, edges, api_addr, dindex, infra, true_next, merge, deferred, done).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, capabilities, resources, cons, true_next, merge, deferred, done).await?;
                 // If there is a false branch, do that one too
                 if let Some(false_next) = false_next {
-                    plan_edges(table, edges, api_addr, dindex, infra, false_next, merge, deferred, done).await?;
+                    plan_edges(table, edges, api_addr, dindex, infra, capabilities, resources, cons, false_next, merge, deferred, done).await?;
                 }
 
                 // If there is a merge, continue there; otherwise, we can assume that we've returned fully in the branch
@@ -307,7 +491,7 @@ async fn plan_edges(
                 // Analyse any of the branches
                 for b in branches {
                     // No merge needed since we can be safe in assuming parallel branches end with returns
-                    plan_edges(table, edges, api_addr, dindex, infra, b, None, deferred, done).await?;
+                    plan_edges(table, edges, api_addr, dindex, infra, capabilities, resources, cons, b, None, deferred, done).await?;
                 }
 
                 // Continue at the merge
@@ -325,8 +509,8 @@ async fn plan_edges(
                 let next: Option<usize> = *next;
 
                 // Run the conditions and body in a first pass, with deferation enabled, to do as much as we can
-                plan_edges(table, edges, api_addr, dindex, infra, cond, Some(body), true, done).await?;
-                plan_edges(table, edges, api_addr, dindex, infra, body, Some(cond), true, done).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, capabilities, resources, cons, cond, Some(body), true, done).await?;
+                plan_edges(table, edges, api_addr, dindex, infra, capabilities, resources, cons, body, Some(cond), true, done).await?;
 
                 // Then we run through the condition and body again to resolve any unknown things
                 plan_deferred(table, edges, infra, cond, Some(body), &mut HashSet::new())?;
@@ -528,10 +712,17 @@ fn plan_deferred(
 /// - `splan`: An (already serialized) planned [`Workflow`] to validate.
 /// - `location`: The name of the location on which we're resolving (used for debugging purposes only).
 /// - `info`: The addresses where we find this location.
+/// - `trace`: The [`TraceContext`] of the planning request, which is propagated to the checker.
 ///
 /// # Errors
 /// This function errors if either we field to access any of the checkers, or they denied the workflow.
-async fn validate_workflow_with(proxy: &ProxyClient, splan: &str, location: &str, info: &InfraLocation) -> Result<(), PlanError> {
+async fn validate_workflow_with(
+    proxy: &ProxyClient,
+    splan: &str,
+    location: &str,
+    info: &InfraLocation,
+    trace: &TraceContext,
+) -> Result<(), PlanError> {
     debug!("Consulting checker of '{location}' for plan validity...");
 
     let message: CheckWorkflowRequest = CheckWorkflowRequest {
@@ -554,7 +745,9 @@ async fn validate_workflow_with(proxy: &ProxyClient, splan: &str, location: &str
     };
 
     // Send the request to the job node
-    let response: tonic::Response<CheckReply> = match client.check_workflow(message).await {
+    let mut request: tonic::Request<CheckWorkflowRequest> = tonic::Request::new(message);
+    trace.inject_grpc(&mut request);
+    let response: tonic::Response<CheckReply> = match client.check_workflow(request).await {
         Ok(response) => response,
         Err(err) => {
             return Err(PlanError::GrpcRequestError { what: "CheckRequest", endpoint: info.delegate.clone(), err });
@@ -575,23 +768,21 @@ async fn validate_workflow_with(proxy: &ProxyClient, splan: &str, location: &str
 
 
 
-
-
-/***** LIBRARY *****/
-/// This function hosts the actual planner, which uses an event monitor to receive plans which are then planned.
+/// Plans a workflow, either for real or in the what-if sandbox.
 ///
 /// # Arguments
 /// - `context`: The general context for this service itself.
+/// - `trace`: The `traceparent` header propagated by the driver, if any. Ignored if it's not a valid [`TraceContext`].
 /// - `body`: The body given in the planning request.
+/// - `sandbox`: If given, plans the workflow in the what-if sandbox with this hypothetical infrastructure and capabilities. The plan is then
+///   not validated by the checkers, and does not affect the state of the workflow's session.
 ///
 /// # Returns
-/// This function doesn't really return, unless the warp server closes.
+/// The response to send back to the client.
 ///
 /// # Errors
-/// This function only errors if we fail to listen for events. Otherwise, errors are logged to stderr using the `error!` macro.
-pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Response, Rejection> {
-    info!("Handling incoming request at '/plan' (i.e., plan new workflow)");
-
+/// This function doesn't typically error; failures are sent back to the client as error responses.
+async fn plan(context: Arc<Context>, trace: Option<String>, body: PlanningRequest, sandbox: Option<Sandbox>) -> Result<Response, Rejection> {
     // Start profiling
     let report = ProfileReport::auto_reporting_file("brane-plr plan::handle", "brane-drv_plan");
     let _total = report.time("Total");
@@ -600,10 +791,14 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
     let mut workflow: Workflow = match report.time_func("Request parsing", || serde_json::from_value(body.workflow)) {
         Ok(workflow) => workflow,
         Err(err) => {
-            return err_response!(bad_request "{}", trace!(("Failed to deserialize incoming workflow"), err));
+            return err_response!(bad_request ErrorCode::PlrInvalidWorkflow, "{}", trace!(("Failed to deserialize incoming workflow"), err));
         },
     };
     debug!("Planning workflow with ID '{}' in app '{}'", workflow.id, body.app_id);
+    report.set_trace_id(workflow.id.clone());
+    let trace: TraceContext = trace.and_then(|trace| trace.parse().ok()).unwrap_or_else(|| TraceContext::for_workflow(&workflow.id));
+    let mut span: Span = Span::child("brane-plr plan", &trace);
+    span.set_attribute("brane.app_id", &body.app_id);
 
     // Fetch the most recent NodeConfig
     let oh = report.time("Environment loading");
@@ -621,14 +816,25 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
         },
     };
 
-    // Load the infrastructure file
-    debug!("Loading infra.yml file '{}'...", central.paths.infra.display());
-    let infra: InfraFile = match InfraFile::from_path(&central.paths.infra) {
-        Ok(infra) => infra,
-        Err(err) => {
-            return err_response!(internal_error "Failed to load infrastructure file '{}': {}", central.paths.infra.display(), err);
+    // Load the infrastructure file (unless we imagine another one)
+    let infra: InfraFile = match sandbox.as_ref().and_then(|sandbox| sandbox.infra.clone()) {
+        Some(infra) => match serde_json::from_value(infra) {
+            Ok(infra) => infra,
+            Err(err) => {
+                return err_response!(bad_request ErrorCode::BadRequest, "{}", trace!(("Failed to deserialize hypothetical infrastructure"), err));
+            },
+        },
+        None => {
+            debug!("Loading infra.yml file '{}'...", central.paths.infra.display());
+            match InfraFile::from_path(&central.paths.infra) {
+                Ok(infra) => infra,
+                Err(err) => {
+                    return err_response!(internal_error "Failed to load infrastructure file '{}': {}", central.paths.infra.display(), err);
+                },
+            }
         },
     };
+    let capabilities: HashMap<String, HashSet<Capability>> = sandbox.as_ref().map(|sandbox| sandbox.capabilities.clone()).unwrap_or_default();
 
     // Fetch the data index
     let url: String = format!("{}/data/info", central.services.api.address);
@@ -639,6 +845,30 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
             return err_response!(internal_error "Failed to fetch DataIndex from '{}': {}", url, err);
         },
     };
+
+    // Fetch the minimum resources of the packages called by the workflow
+    let url: String = format!("{}/graphql", central.services.api.address);
+    let provider: RemotePackageProvider = RemotePackageProvider::new(&url);
+    let mut packages: HashMap<String, Vec<PackageInfo>> = HashMap::new();
+    let mut resources: HashMap<usize, PackageResources> = HashMap::new();
+    for (i, task) in workflow.table.tasks.iter().enumerate() {
+        if let TaskDef::Compute(ComputeTaskDef { package, version, .. }) = task {
+            if !packages.contains_key(package) {
+                debug!("Loading package '{package}' from '{url}'...");
+                match provider.packages(package).await {
+                    Ok(infos) => {
+                        packages.insert(package.clone(), infos);
+                    },
+                    Err(err) => {
+                        return err_response!(internal_error "Failed to fetch package '{}' from '{}': {}", package, url, err);
+                    },
+                }
+            }
+            if let Some(res) = packages[package].iter().find(|info| info.version == *version).and_then(|info| info.resources.clone()) {
+                resources.insert(i, res);
+            }
+        }
+    }
     oh.stop();
 
 
@@ -654,11 +884,19 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
         let mut table: SymTable = Arc::try_unwrap(table).unwrap();
 
         // Fetch any previous state for this table
-        if let Some(results) = context.state.lock().get_mut(&body.app_id) {
-            results.0 = Instant::now();
-            table.results.extend(results.1.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let mut residency: HashMap<String, HashSet<String>> = HashMap::new();
+        if sandbox.is_none() {
+            if let Some(session) = context.state.lock().get_mut(&body.app_id) {
+                session.last_used = Instant::now();
+                table.results.extend(session.results.iter().map(|(k, v)| (k.clone(), v.clone())));
+                residency.extend(session.residency.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
         }
 
+        // Prepare tracking the placement constraints
+        let constraints: Arc<Constraints> = workflow.constraints.clone();
+        let mut cons: ConstraintState = ConstraintState { constraints: &constraints, func: None, placed: HashMap::new(), residency };
+
         // Do the main edges first
         {
             // Start by getting a list of all the edges
@@ -671,11 +909,27 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
             if let Err(err) = alg
                 .time_fut(
                     "<<<main>>>",
-                    plan_edges(&mut table, &mut edges, &central.services.api.address, &dindex, &infra, 0, None, false, &mut HashSet::new()),
+                    plan_edges(
+                        &mut table,
+                        &mut edges,
+                        &central.services.api.address,
+                        &dindex,
+                        &infra,
+                        &capabilities,
+                        &resources,
+                        &mut cons,
+                        0,
+                        None,
+                        false,
+                        &mut HashSet::new(),
+                    ),
                 )
                 .await
             {
-                return err_response!(bad_request "{}", trace!(("Failed to plan main edges for workflow with ID '{}:{}'", body.app_id, workflow.id), err));
+                if let PlanError::ResidencyViolation { violation } = err {
+                    return err_response!(residency violation);
+                }
+                return err_response!(bad_request ErrorCode::PlrUnplannable, "{}", trace!(("Failed to plan main edges for workflow with ID '{}:{}'", body.app_id, workflow.id), err));
             };
 
             // Move the edges back
@@ -693,14 +947,31 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
             // Iterate through all of the edges
             for (idx, edges) in &mut funcs {
                 debug!("Planning '{}' edges...", table.funcs[*idx].name);
+                cons.func = Some(*idx);
                 if let Err(err) = alg
                     .time_fut(
                         workflow.table.funcs[*idx].name.to_string(),
-                        plan_edges(&mut table, edges, &central.services.api.address, &dindex, &infra, 0, None, false, &mut HashSet::new()),
+                        plan_edges(
+                            &mut table,
+                            edges,
+                            &central.services.api.address,
+                            &dindex,
+                            &infra,
+                            &capabilities,
+                            &resources,
+                            &mut cons,
+                            0,
+                            None,
+                            false,
+                            &mut HashSet::new(),
+                        ),
                     )
                     .await
                 {
-                    return err_response!(bad_request "{}", trace!(("Failed to plan function '{}' edges for workflow with correlation ID '{}:{}'", table.funcs[*idx].name, body.app_id, workflow.id), err));
+                    if let PlanError::ResidencyViolation { violation } = err {
+                        return err_response!(residency violation);
+                    }
+                    return err_response!(bad_request ErrorCode::PlrUnplannable, "{}", trace!(("Failed to plan function '{}' edges for workflow with correlation ID '{}:{}'", table.funcs[*idx].name, body.app_id, workflow.id), err));
                 }
             }
 
@@ -710,12 +981,13 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
         }
 
         // Write the results back for this session
-        context
-            .state
-            .lock()
-            .entry(body.app_id.clone())
-            .and_modify(|results| *results = (Instant::now(), table.results.clone()))
-            .or_insert_with(|| (Instant::now(), table.results.clone()));
+        if sandbox.is_none() {
+            context.state.lock().insert(body.app_id.clone(), Session {
+                last_used: Instant::now(),
+                results:   table.results.clone(),
+                residency: cons.residency.clone(),
+            });
+        }
 
         // Then, put the table back
         let mut table: Arc<SymTable> = Arc::new(table);
@@ -731,6 +1003,15 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
             return err_response!(internal_error "{}", trace!(("Failed to serialize plan"), err));
         },
     };
+    if sandbox.is_some() {
+        // The sandbox only shows what the plan would be; it's not checked, because the checkers only know the real instance
+        debug!("What-if planning of '{}' OK", workflow.id);
+        let reply: WhatIfReply = WhatIfReply { plan: vplan, placements: placements(&workflow) };
+        return match serde_json::to_string(&reply) {
+            Ok(sreply) => Ok(Response::new(sreply.into())),
+            Err(err) => err_response!(internal_error "{}", trace!(("Failed to serialize request result"), err)),
+        };
+    }
     let reply: PlanningReply = PlanningReply { plan: vplan };
     let sreply: String = match serde_json::to_string(&reply) {
         Ok(sreply) => sreply,
@@ -750,7 +1031,12 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
     debug!("Consulting {} checkers with plan validity...", infra.len());
     let val = report.nest("Policy validation");
     for (location, info) in infra.iter() {
-        match val.time_fut(format!("Domain '{}' ({})", location, info.registry), validate_workflow_with(&context.proxy, &splan, location, info)).await
+        match val
+            .time_fut(
+                format!("Domain '{}' ({})", location, info.registry),
+                validate_workflow_with(&context.proxy, &splan, location, info, span.context()),
+            )
+            .await
         {
             Ok(_) => {},
             Err(PlanError::CheckerDenied { domain, reasons }) => {
@@ -765,7 +1051,7 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
     if let Some(mut state) = context.state.try_lock() {
         let clean = report.time("Garbage collector");
         info!("Running garbage collector on old {} planning sessions...", state.len());
-        state.retain(|_, (last_used, _)| last_used.elapsed() < Duration::from_secs(SESSION_TIMEOUT_S));
+        state.retain(|_, session| session.last_used.elapsed() < Duration::from_secs(SESSION_TIMEOUT_S));
         debug!("{} planning sessions left after GC run", state.len());
         clean.stop();
     }
@@ -774,3 +1060,43 @@ pub async fn handle(context: Arc<Context>, body: PlanningRequest) -> Result<Resp
     debug!("Planning of '{}:{}' OK", body.app_id, workflow.id);
     Ok(Response::new(sreply.into()))
 }
+
+
+
+/***** LIBRARY *****/
+/// This function hosts the actual planner, which uses an event monitor to receive plans which are then planned.
+///
+/// # Arguments
+/// - `context`: The general context for this service itself.
+/// - `trace`: The `traceparent` header propagated by the driver, if any. Ignored if it's not a valid [`TraceContext`].
+/// - `body`: The body given in the planning request.
+///
+/// # Returns
+/// This function doesn't really return, unless the warp server closes.
+///
+/// # Errors
+/// This function only errors if we fail to listen for events. Otherwise, errors are logged to stderr using the `error!` macro.
+pub async fn handle(context: Arc<Context>, trace: Option<String>, body: PlanningRequest) -> Result<Response, Rejection> {
+    info!("Handling incoming request at '/plan' (i.e., plan new workflow)");
+    plan(context, trace, body, None).await
+}
+
+/// Handles the what-if sandbox, which tells administrators how a workflow would be planned on a hypothetical infrastructure.
+///
+/// This allows them to evaluate the impact of adding or removing a domain, or of changing its capabilities, before doing so for real. The
+/// workflow is planned as if it were submitted in a fresh session, and the plan is not validated by the checkers.
+///
+/// # Arguments
+/// - `context`: The general context for this service itself.
+/// - `body`: The [`WhatIfRequest`] with the workflow and the hypothetical infrastructure.
+///
+/// # Returns
+/// The response to send back to the client, which contains a [`WhatIfReply`] if the workflow could be planned.
+///
+/// # Errors
+/// This function doesn't typically error; failures are sent back to the client as error responses.
+pub async fn handle_what_if(context: Arc<Context>, body: WhatIfRequest) -> Result<Response, Rejection> {
+    info!("Handling incoming request at '/admin/what-if' (i.e., plan workflow on hypothetical infrastructure)");
+    let WhatIfRequest { workflow, infra, capabilities } = body;
+    plan(context, None, PlanningRequest { app_id: "<what-if>".into(), workflow }, Some(Sandbox { infra, capabilities })).await
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 11:43:56
 //  Last edited:
-//    03 Jan 2024, 14:55:04
+//    15 Oct 2026, 22:26:11
 //  Auto updated?
 //    Yes
 //
@@ -36,6 +36,8 @@ pub enum RedirectError {
     IllegalServerName { raw: String, err: rustls::client::InvalidDnsNameError },
     /// Failed to create a new tcp listener.
     ListenerCreateError { address: SocketAddr, err: std::io::Error },
+    /// Failed to resolve the credentials for the forwarding proxy.
+    ForwardCredentialsError { address: Address, err: brane_cfg::errors::SecretError },
     /// Failed to create a new socks5 client.
     Socks5CreateError { address: Address, err: anyhow::Error },
     /// Failed to create a new socks6 client.
@@ -50,6 +52,13 @@ pub enum RedirectError {
 
     /// The given port for an incoming path is in the outgoing path's range.
     PortInOutgoingRange { port: u16, range: RangeInclusive<u16> },
+
+    /// No port given in the given URL (and its scheme has no default one).
+    NoPort { raw: String },
+    /// Failed to resolve the host of a destination to check it against the destination rules.
+    DestinationResolveError { host: String, err: std::io::Error },
+    /// The destination of a new outgoing path is refused by the destination rules in the proxy config.
+    DestinationNotAllowed { address: String },
 }
 impl Display for RedirectError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -60,6 +69,7 @@ impl Display for RedirectError {
             TlsWithNonHostnameError { kind } => write!(f, "Got a request for TLS but with a non-hostname {kind} address provided"),
             IllegalServerName { raw, err } => write!(f, "Cannot parse '{raw}' as a valid server name: {err}"),
             ListenerCreateError { address, err } => write!(f, "Failed to create new TCP listener on '{address}': {err}"),
+            ForwardCredentialsError { address, err } => write!(f, "Failed to resolve credentials for forwarding proxy '{address}': {err}"),
             Socks5CreateError { address, err } => write!(f, "Failed to create new SOCKS5 client to '{address}': {err}"),
             Socks6CreateError { address, err } => write!(f, "Failed to create new SOCKS6 client to '{address}': {err}"),
 
@@ -75,6 +85,12 @@ impl Display for RedirectError {
                 range.start(),
                 range.end()
             ),
+
+            NoPort { raw } => write!(f, "No port found in '{raw}'"),
+            DestinationResolveError { host, err } => write!(f, "Failed to resolve host '{host}': {err}"),
+            DestinationNotAllowed { address } => {
+                write!(f, "Destination '{address}' is not allowed by this proxy (see 'destinations' in its proxy.yml)")
+            },
         }
     }
 }
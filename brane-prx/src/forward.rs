@@ -0,0 +1,203 @@
+//  FORWARD.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:53:18
+//  Last edited:
+//    15 Oct 2026, 22:53:18
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the bidirectional links that copy the bytes of a proxied
+//!   connection between the incoming and outgoing stream.
+//!
+//!   Links copy with large buffers that are allocated once per link. For
+//!   plain TCP on Linux, the bytes may optionally be moved through a pipe
+//!   with `splice(2)`, such that they never enter userspace at all.
+//
+
+use std::io;
+
+use log::warn;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+
+/***** CONSTANTS *****/
+/// The size of the buffers (in bytes) that a link copies with in each direction.
+///
+/// Tokio's default of 8 KiB means a syscall (and a TLS record) per 8 KiB, which makes dataset transfers CPU-bound in the proxy.
+pub const FORWARD_BUFFER_SIZE: usize = 256 * 1024;
+
+
+
+
+
+/***** SPLICING *****/
+/// Implements moving bytes between two sockets with `splice(2)`.
+#[cfg(target_os = "linux")]
+mod splice {
+    use std::io;
+    use std::os::fd::{AsRawFd as _, FromRawFd as _, OwnedFd, RawFd};
+
+    use tokio::io::Interest;
+    use tokio::net::TcpStream;
+
+    use super::FORWARD_BUFFER_SIZE;
+
+
+    /// A pipe through which the bytes of one direction of a link are spliced.
+    pub struct Pipe {
+        /// The end from which we splice into the destination socket.
+        read:  OwnedFd,
+        /// The end into which we splice from the source socket.
+        write: OwnedFd,
+    }
+
+    impl Pipe {
+        /// Creates a new, non-blocking pipe, enlarged to [`FORWARD_BUFFER_SIZE`] if the system allows it.
+        ///
+        /// # Errors
+        /// This function errors if we failed to create the pipe (e.g., because we ran out of file descriptors).
+        pub fn new() -> io::Result<Self> {
+            let mut fds: [libc::c_int; 2] = [-1; 2];
+            // SAFETY: `fds` is valid for two descriptors, as `pipe2()` requires.
+            if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: `pipe2()` just created these descriptors, so we are their only owner.
+            let (read, write): (OwnedFd, OwnedFd) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+
+            // Best effort; unprivileged processes may be limited by `/proc/sys/fs/pipe-max-size`, in which case the default (64 KiB) will do
+            // SAFETY: `F_SETPIPE_SZ` only reads its integer argument.
+            unsafe { libc::fcntl(write.as_raw_fd(), libc::F_SETPIPE_SZ, FORWARD_BUFFER_SIZE as libc::c_int) };
+            Ok(Self { read, write })
+        }
+    }
+
+
+
+    /// Moves at most `len` bytes from one descriptor to another without blocking.
+    ///
+    /// # Returns
+    /// The number of bytes moved, where 0 means the source reached EOF.
+    ///
+    /// # Errors
+    /// This function errors if `splice()` failed, including with [`io::ErrorKind::WouldBlock`] if either side is not ready.
+    fn splice(from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+        // SAFETY: Null offsets are allowed (and required) for pipes and sockets, and the descriptors are borrowed for the duration of the call.
+        let res: isize =
+            unsafe { libc::splice(from, std::ptr::null_mut(), to, std::ptr::null_mut(), len, libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK) };
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res as usize)
+        }
+    }
+
+    /// Splices one direction of a link until the source reaches EOF, after which the write half of the destination is shut down.
+    ///
+    /// # Arguments
+    /// - `from`: The socket to read from.
+    /// - `to`: The socket to write to.
+    /// - `pipe`: The [`Pipe`] to splice through. It is empty between chunks.
+    ///
+    /// # Returns
+    /// The number of bytes moved.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read from or write to either socket.
+    pub async fn one_way(from: &TcpStream, to: &TcpStream, pipe: &Pipe) -> io::Result<u64> {
+        let mut total: u64 = 0;
+        loop {
+            // The pipe is empty here, so only the socket can make us wait
+            let n: usize = from.async_io(Interest::READABLE, || splice(from.as_raw_fd(), pipe.write.as_raw_fd(), FORWARD_BUFFER_SIZE)).await?;
+            if n == 0 {
+                // SAFETY: `shutdown()` only acts on the (borrowed) descriptor.
+                if unsafe { libc::shutdown(to.as_raw_fd(), libc::SHUT_WR) } < 0 {
+                    let err: io::Error = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::NotConnected {
+                        return Err(err);
+                    }
+                }
+                return Ok(total);
+            }
+
+            // Drain the pipe into the destination
+            let mut left: usize = n;
+            while left > 0 {
+                let written: usize = to.async_io(Interest::WRITABLE, || splice(pipe.read.as_raw_fd(), to.as_raw_fd(), left)).await?;
+                left -= written;
+            }
+            total += n as u64;
+        }
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Prepares a socket that is part of a link.
+///
+/// This disables Nagle's algorithm, since the proxy should not add latency on top of whatever the endpoints already buffer.
+///
+/// # Arguments
+/// - `conn`: The socket to prepare.
+pub fn tune(conn: &TcpStream) {
+    if let Err(err) = conn.set_nodelay(true) {
+        warn!("Failed to set TCP_NODELAY on socket: {}", err);
+    }
+}
+
+
+
+/// Copies bytes both ways between two streams until both reach EOF.
+///
+/// # Arguments
+/// - `iconn`: The incoming stream.
+/// - `oconn`: The outgoing stream.
+///
+/// # Returns
+/// The number of bytes copied from `iconn` to `oconn` and from `oconn` to `iconn`, respectively.
+///
+/// # Errors
+/// This function errors if we failed to read from or write to either stream.
+pub async fn forward<I, O>(iconn: &mut I, oconn: &mut O) -> io::Result<(u64, u64)>
+where
+    I: AsyncRead + AsyncWrite + Unpin + ?Sized,
+    O: AsyncRead + AsyncWrite + Unpin + ?Sized,
+{
+    tokio::io::copy_bidirectional_with_sizes(iconn, oconn, FORWARD_BUFFER_SIZE, FORWARD_BUFFER_SIZE).await
+}
+
+/// Copies bytes both ways between two plain TCP sockets until both reach EOF.
+///
+/// # Arguments
+/// - `iconn`: The incoming socket.
+/// - `oconn`: The outgoing socket.
+/// - `use_splice`: Whether to move the bytes with `splice(2)` instead of copying them through userspace. Only has an effect on Linux; if we
+///   fail to create the pipes, we fall back to copying.
+///
+/// # Returns
+/// The number of bytes copied from `iconn` to `oconn` and from `oconn` to `iconn`, respectively.
+///
+/// # Errors
+/// This function errors if we failed to read from or write to either socket.
+pub async fn forward_tcp(iconn: &mut TcpStream, oconn: &mut TcpStream, use_splice: bool) -> io::Result<(u64, u64)> {
+    #[cfg(target_os = "linux")]
+    if use_splice {
+        match (splice::Pipe::new(), splice::Pipe::new()) {
+            (Ok(ipipe), Ok(opipe)) => {
+                return tokio::try_join!(splice::one_way(iconn, oconn, &ipipe), splice::one_way(oconn, iconn, &opipe));
+            },
+            (Err(err), _) | (_, Err(err)) => warn!("Failed to create pipe for splicing: {} (falling back to copying)", err),
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = use_splice;
+
+    forward(iconn, oconn).await
+}
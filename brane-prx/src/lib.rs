@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 10:34:23
 //  Last edited:
-//    23 May 2023, 15:29:39
+//    15 Oct 2026, 22:53:18
 //  Auto updated?
 //    Yes
 //
@@ -44,6 +44,7 @@
 // Declare modules
 pub mod client;
 pub mod errors;
+pub mod forward;
 pub mod manage;
 pub mod ports;
 pub mod redirect;
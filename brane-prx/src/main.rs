@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 10:52:33
 //  Last edited:
-//    14 Jun 2024, 15:14:24
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -18,12 +18,15 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use brane_cfg::certs;
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{NodeConfig, NodeSpecificConfig};
 use brane_cfg::proxy::ProxyConfig;
 use brane_prx::manage;
 use brane_prx::ports::PortAllocator;
 use brane_prx::spec::Context;
+use brane_shr::chaos::{self, ChaosConfig};
+use brane_shr::logging::{self, LogFormat};
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::trace;
@@ -50,6 +53,34 @@ struct Arguments {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
+
+    /// The faults to inject in chaos mode, if any.
+    #[clap(
+        long,
+        help = "If given, injects faults to test resilience, e.g., 'latency=250ms@0.5,drop=0.1,fail=0.05,seed=42' (see \
+                `brane_shr::chaos::ChaosConfig`). Only has an effect in debug builds.",
+        env = "BRANE_CHAOS"
+    )]
+    chaos: Option<ChaosConfig>,
+
+    /// Whether to splice plain TCP links.
+    #[clap(
+        long,
+        action,
+        help = "If given, moves the bytes of links without TLS between sockets using `splice(2)` instead of copying them through the proxy. Only \
+                has an effect on Linux.",
+        env = "SPLICE"
+    )]
+    splice: bool,
 }
 
 
@@ -63,15 +94,13 @@ async fn main() {
     let args: Arguments = Arguments::parse();
 
     // Configure logger.
-    let mut logger = env_logger::builder();
-    logger.format_module_path(false);
-
-    if args.debug {
-        logger.filter_level(LevelFilter::Debug).init();
-    } else {
-        logger.filter_level(LevelFilter::Info).init();
+    if let Err(err) = logging::init("brane-prx", args.log_format, if args.debug { LevelFilter::Debug } else { LevelFilter::Info }) {
+        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
     }
     info!("Initializing brane-prx v{}...", env!("CARGO_PKG_VERSION"));
+    if let Some(chaos) = args.chaos {
+        chaos::init("brane-prx", chaos);
+    }
 
     // Load the config, making sure it's a worker config
     debug!("Loading node.yml file '{}'...", args.node_config_path.display());
@@ -82,6 +111,9 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    if let Some(passphrase) = &node_config.key_passphrase {
+        certs::set_key_passphrase(passphrase.clone());
+    }
 
     // Load the proxy file
     let proxy_config: ProxyConfig = 'proxy: {
@@ -119,6 +151,7 @@ async fn main() {
         ports:  Mutex::new(PortAllocator::new(*proxy_config.outgoing_range.start(), *proxy_config.outgoing_range.end())),
         proxy:  proxy_config,
         opened: Mutex::new(HashMap::new()),
+        splice: args.splice,
     });
 
     // Spawn the incoming ports before we listen for new outgoing port requests
@@ -130,13 +163,16 @@ async fn main() {
 
     // Prepare the warp paths for management
     let context = warp::any().map(move || context.clone());
-    let filter = warp::post()
+    let outgoing = warp::post()
         .and(warp::path("outgoing"))
         .and(warp::path("new"))
         .and(warp::path::end())
         .and(warp::body::bytes())
         .and(context.clone())
         .and_then(manage::new_outgoing_path);
+    let health = warp::get().and(warp::path("health")).and(warp::path::end()).map(|| "OK!\n");
+    let version = warp::get().and(warp::path("version")).and(warp::path::end()).map(|| format!("v{}", env!("CARGO_PKG_VERSION")));
+    let filter = outgoing.or(health).or(version);
 
     // Extract the proxy address
     let bind_addr: SocketAddr = match node_config.node {
@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 11:07:05
 //  Last edited:
-//    12 Jan 2024, 10:43:44
+//    16 Oct 2026, 02:05:47
 //  Auto updated?
 //    Yes
 //
@@ -16,19 +16,22 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::str::FromStr as _;
 use std::sync::{Arc, MutexGuard};
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use specifications::address::Address;
 use tokio::net::{TcpListener, TcpStream};
+use url::Url;
 use warp::http::StatusCode;
 use warp::hyper::body::Bytes;
 use warp::hyper::{Body, Response};
 use warp::{Rejection, Reply};
 
 use crate::errors::RedirectError;
+use crate::forward;
 use crate::ports::PortAllocator;
-use crate::redirect::path_server_factory;
+use crate::redirect::{check_destination, path_server_factory};
 use crate::spec::{Context, NewPathRequest, NewPathRequestTlsOptions};
 
 
@@ -72,7 +75,8 @@ macro_rules! reject {
 /// # Returns
 /// A reponse with the following codes:
 /// - `200 OK` if the new path was successfully created. In the body, there is the (serialized) port number of the path to store.
-/// - `400 BAD REQUEST` if the given request body was not parseable as the desired JSON.
+/// - `400 BAD REQUEST` if the given request body was not parseable as the desired JSON, or if its address could not be checked against the destination rules.
+/// - `403 FORBIDDEN` if the destination rules in the proxy config do not allow the given address. The body explains why.
 /// - `507 INSUFFICIENT STORAGE` if the server is out of port ranges to allocate.
 ///
 /// # Errors
@@ -90,6 +94,25 @@ pub async fn new_outgoing_path(body: Bytes, context: Arc<Context>) -> Result<imp
         },
     };
 
+    // Check whether we are allowed to go there
+    if !context.proxy.destinations.is_empty() {
+        debug!("Checking destination '{}' against destination rules...", body.address);
+        let res: Result<Vec<SocketAddr>, RedirectError> = match Url::from_str(&body.address) {
+            Ok(url) => check_destination(&context.proxy.destinations, &url).await,
+            Err(err) => Err(RedirectError::IllegalUrl { raw: body.address.clone(), err }),
+        };
+        if let Err(err) = res {
+            let status: StatusCode = if matches!(err, RedirectError::DestinationNotAllowed { .. }) {
+                warn!("Refusing new outgoing path: {}", err);
+                StatusCode::FORBIDDEN
+            } else {
+                error!("Failed to check destination of new outgoing path: {}", err);
+                StatusCode::BAD_REQUEST
+            };
+            return Ok(Response::builder().status(status).body(Body::from(err.to_string())).unwrap());
+        }
+    }
+
     // If the port already exists, shortcut here
     {
         let opened: MutexGuard<HashMap<(String, Option<NewPathRequestTlsOptions>), u16>> = context.opened.lock().unwrap();
@@ -183,25 +206,38 @@ pub async fn new_incoming_path(port: u16, address: Address, context: Arc<Context
                 },
             };
             debug!(">{}->{}: Got new connection from '{}'", port, address, client_addr);
-
-            // Now we establish a new connection to the internal host
-            let addr: String = format!("{}:{}", address.domain(), address.port());
-            debug!("Connecting to '{}'...", addr);
-            let mut oconn: TcpStream = match TcpStream::connect(&addr).await {
-                Ok(oconn) => oconn,
-                Err(err) => {
-                    error!(">{}->{}: Failed to connect to internal '{}': {}", port, address, addr, err);
-                    continue;
-                },
-            };
-
-            // For the remainder of this session, simply copy the TCP stream both ways
-            debug!(">{}->{}: Bidirectional link started", port, address);
-            if let Err(err) = tokio::io::copy_bidirectional(&mut iconn, &mut oconn).await {
-                error!(">{}->{}: Bidirectional link failed: {}", port, address, err);
+            chaos::delay().await;
+            if chaos::should_drop() {
+                debug!(">{}->{}: Dropping connection from '{}' (chaos mode)", port, address, client_addr);
                 continue;
             }
-            debug!(">{}->{}: Bidirectional link completed", port, address);
+
+            // Serve it in the background, such that a long-running transfer does not hold up other connections
+            let address: Address = address.clone();
+            let splice: bool = context.splice;
+            tokio::spawn(async move {
+                // Now we establish a new connection to the internal host
+                let addr: String = format!("{}:{}", address.domain(), address.port());
+                debug!("Connecting to '{}'...", addr);
+                let mut oconn: TcpStream = match TcpStream::connect(&addr).await {
+                    Ok(oconn) => oconn,
+                    Err(err) => {
+                        error!(">{}->{}: Failed to connect to internal '{}': {}", port, address, addr, err);
+                        return;
+                    },
+                };
+                forward::tune(&iconn);
+                forward::tune(&oconn);
+
+                // For the remainder of this session, simply copy (or splice) the TCP stream both ways
+                debug!(">{}->{}: Bidirectional link started", port, address);
+                match forward::forward_tcp(&mut iconn, &mut oconn, splice).await {
+                    Ok((sent, received)) => {
+                        debug!(">{}->{}: Bidirectional link completed (sent {} bytes, received {} bytes)", port, address, sent, received)
+                    },
+                    Err(err) => error!(">{}->{}: Bidirectional link failed: {}", port, address, err),
+                }
+            });
         }
     });
 
@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 11:26:46
 //  Last edited:
-//    03 Jan 2024, 14:54:55
+//    16 Oct 2026, 02:05:47
 //  Auto updated?
 //    Yes
 //
@@ -13,7 +13,7 @@
 //
 
 use std::future::Future;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
@@ -21,19 +21,21 @@ use std::sync::Arc;
 use brane_cfg::certs::{load_certstore, load_identity};
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{NodeConfig, NodeSpecificConfig};
-use brane_cfg::proxy::ProxyProtocol;
+use brane_cfg::proxy::{DestinationRules, ProxyProtocol};
+use brane_shr::chaos;
 use log::{debug, error, info};
 use never_say_never::Never;
 use rustls::client::ClientConfig;
 use rustls::{Certificate, ConfigBuilder, PrivateKey, RootCertStore, ServerName};
-use socksx::{Socks5Client, Socks6Client};
+use socksx::{Credentials as SocksCredentials, Socks5Client, Socks6Client};
 use specifications::address::Address;
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::{lookup_host, TcpListener, TcpStream};
 use tokio_rustls::client::TlsStream;
 use tokio_rustls::TlsConnector;
 use url::Url;
 
 pub use crate::errors::RedirectError as Error;
+use crate::forward::{self, forward_tcp};
 use crate::spec::{Context, NewPathRequestTlsOptions};
 
 
@@ -88,7 +90,246 @@ impl RemoteClient {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Connects to the remote host at one of the given (checked) addresses, or at the host itself if there are none.
+///
+/// # Arguments
+/// - `client`: The proxied SOCKS-client / unproxied TcpClient to connect to remote hosts with.
+/// - `addr`: The `<host>:<port>` address of the remote host.
+/// - `pinned`: The addresses that the host resolved to and that were checked against the destination rules. If empty, connects to `addr` as-is.
+///
+/// # Returns
+/// A TcpStream that represents the connection.
+///
+/// # Errors
+/// This function errors if we failed to connect to any of the addresses. In that case, the error for the last one is returned.
+async fn connect_pinned(client: &RemoteClient, addr: &str, pinned: &[SocketAddr]) -> Result<TcpStream, Error> {
+    if pinned.is_empty() {
+        return client.connect(addr).await;
+    }
+    let mut last: Option<Error> = None;
+    for target in pinned {
+        debug!("Connecting to '{}' at '{}'...", addr, target);
+        match client.connect(target.to_string()).await {
+            Ok(conn) => return Ok(conn),
+            Err(err) => last = Some(err),
+        }
+    }
+    Err(last.unwrap())
+}
+
+/// Serves a single connection accepted by a path server, by connecting to the remote host and then linking both connections.
+///
+/// # Arguments
+/// - `node_config_path`: Path to the `node.yml` file where we pull the certificates location from.
+/// - `client`: The proxied SOCKS6-client / unproxied TcpClient to connect to remote hosts with.
+/// - `rules`: The [`DestinationRules`] that the remote host is checked against again before connecting to it.
+/// - `socket_addr`: The SocketAddress on which we serve (used for debugging).
+/// - `address`: The address to redirect the traffic to.
+/// - `tls`: If given, adds TLS encryption to the remote host with the given address.
+/// - `splice`: Whether to splice the link if it does not use TLS (see [`forward_tcp()`]).
+/// - `iconn`: The accepted connection.
+///
+/// # Errors
+/// This function does not error directly, but instead write errors to stderr (using the `log` crate) and then returns.
+async fn serve_link(
+    node_config_path: &Path,
+    client: &RemoteClient,
+    rules: &DestinationRules,
+    socket_addr: SocketAddr,
+    address: &Url,
+    tls: Option<&(ServerName, NewPathRequestTlsOptions)>,
+    splice: bool,
+    mut iconn: TcpStream,
+) {
+    // Check the destination again, and connect to exactly the addresses we checked; otherwise, the host may resolve to another address by now
+    let pinned: Vec<SocketAddr> = if rules.is_empty() {
+        vec![]
+    } else {
+        match check_destination(rules, address).await {
+            Ok(pinned) => pinned,
+            Err(err) => {
+                error!(":{}->{}: Refusing to connect: {}", socket_addr.port(), address, err);
+                return;
+            },
+        }
+    };
+
+    // Now we establish a new connection to the remote host
+    let addr: String = format!("{}:{}", address.host_str().unwrap(), address.port().unwrap());
+    debug!("Connecting to '{}'...", addr);
+    let mut oconn: TcpStream = match connect_pinned(client, &addr, &pinned).await {
+        Ok(oconn) => oconn,
+        Err(err) => {
+            error!(":{}->{}: Failed to connect to remote '{}': {}", socket_addr.port(), address, addr, err);
+            return;
+        },
+    };
+    forward::tune(&iconn);
+    forward::tune(&oconn);
+
+    // Now switch on whether we're using TLS or not.
+    if let Some((domain, tls)) = tls {
+        // Use TLS
+        debug!(":{}->{}: Setting up TLS for location '{}'...", socket_addr.port(), address, tls.location);
+
+        // Load the node config file
+        let node_config: NodeConfig = match NodeConfig::from_path(node_config_path) {
+            Ok(config) => config,
+            Err(err) => {
+                error!(":{}->{}: Failed to load NodeConfig file: {}", socket_addr.port(), address, err);
+                std::process::exit(1);
+            },
+        };
+
+        // Load the certificate path
+        let cert_path: &Path = match &node_config.node {
+            NodeSpecificConfig::Central(node) => &node.paths.certs,
+            NodeSpecificConfig::Worker(node) => &node.paths.certs,
+            NodeSpecificConfig::Proxy(node) => &node.paths.certs,
+        };
+
+        // Load the root CA certificate file
+        let ca_path: PathBuf = cert_path.join(&tls.location).join("ca.pem");
+        let ca: RootCertStore = match load_certstore(&ca_path) {
+            Ok(store) => store,
+            Err(err) => {
+                error!(
+                    ":{}->{}: Failed to load root certificate '{}' for location '{}': {}",
+                    socket_addr.port(),
+                    address,
+                    ca_path.display(),
+                    tls.location,
+                    err
+                );
+                return;
+            },
+        };
+
+        // If any, also load the client file
+        let client: Option<(PathBuf, Vec<Certificate>, PrivateKey)> = if tls.use_client_auth {
+            debug!(":{}->{}: Adding client certificate...", socket_addr.port(), address);
+            let client_path: PathBuf = cert_path.join(&tls.location).join("client-id.pem");
+            match load_identity(&client_path) {
+                Ok((certs, key)) => Some((client_path, certs, key)),
+                Err(err) => {
+                    error!(
+                        ":{}->{}: Failed to load client identity file '{}' for location '{}': {}",
+                        socket_addr.port(),
+                        address,
+                        client_path.display(),
+                        tls.location,
+                        err
+                    );
+                    return;
+                },
+            }
+        } else {
+            None
+        };
+
+        // Create a ClientConfig with that
+        let config: ConfigBuilder<_, _> = ClientConfig::builder().with_safe_defaults().with_root_certificates(ca);
+        let config: ClientConfig = if let Some((path, certs, key)) = client {
+            match config.with_client_auth_cert(certs, key) {
+                Ok(config) => config,
+                Err(err) => {
+                    error!(
+                        ":{}->{}: Failed to build client config from '{}' and '{}': {}",
+                        socket_addr.port(),
+                        address,
+                        ca_path.display(),
+                        path.display(),
+                        err
+                    );
+                    return;
+                },
+            }
+        } else {
+            config.with_no_client_auth()
+        };
+
+        // We can now wrap the outgoing stream in a TLS client stream.
+        debug!(":{}->{}: Negotiating TLS...", socket_addr.port(), address);
+        let connector: TlsConnector = TlsConnector::from(Arc::new(config));
+        let mut oconn: TlsStream<TcpStream> = match connector.connect(domain.clone(), oconn).await {
+            Ok(oconn) => oconn,
+            Err(err) => {
+                error!(":{}->{}: Failed to start a TLS connection with '{}': {}", socket_addr.port(), address, addr, err);
+                return;
+            },
+        };
+
+        // For the remainder of this session, simply copy the TCP stream both ways
+        debug!(":{}->{}: Bidirectional link started", socket_addr.port(), address);
+        match forward::forward(&mut iconn, &mut oconn).await {
+            Ok((sent, received)) => {
+                debug!(":{}->{}: Bidirectional link completed (sent {} bytes, received {} bytes)", socket_addr.port(), address, sent, received)
+            },
+            Err(err) => error!(":{}->{}: Bidirectional link failed: {}", socket_addr.port(), address, err),
+        }
+    } else {
+        // Simple TCP
+
+        // For the remainder of this session, simply copy (or splice) the TCP stream both ways
+        debug!(":{}->{}: Bidirectional link started", socket_addr.port(), address);
+        match forward_tcp(&mut iconn, &mut oconn, splice).await {
+            Ok((sent, received)) => {
+                debug!(":{}->{}: Bidirectional link completed (sent {} bytes, received {} bytes)", socket_addr.port(), address, sent, received)
+            },
+            Err(err) => error!(":{}->{}: Bidirectional link failed: {}", socket_addr.port(), address, err),
+        }
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
+/// Checks whether the given destination is allowed by the destination rules in the proxy config.
+///
+/// If the rules contain CIDRs, the host is resolved to check them. In that case, the allowed addresses it resolved to are returned, and
+/// connections should be made to those instead of to the host; resolving it again may give another address (i.e., DNS rebinding).
+///
+/// # Arguments
+/// - `rules`: The [`DestinationRules`] to check against.
+/// - `address`: The address of the destination, as given by the requester.
+///
+/// # Returns
+/// The addresses to connect to, or an empty list if the host was not resolved (and may thus be connected to directly).
+///
+/// # Errors
+/// This function errors if the destination is not allowed, or if we failed to parse or resolve its address.
+pub async fn check_destination(rules: &DestinationRules, address: &Url) -> Result<Vec<SocketAddr>, Error> {
+    let host: &str = match address.host_str() {
+        Some(host) => host,
+        None => return Err(Error::NoDomainName { raw: address.to_string() }),
+    };
+    let port: u16 = match address.port_or_known_default() {
+        Some(port) => port,
+        None => return Err(Error::NoPort { raw: address.to_string() }),
+    };
+
+    // Only resolve the host if there are CIDRs to match it against
+    let addrs: Vec<SocketAddr> = if rules.needs_resolve() {
+        match lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port)).await {
+            Ok(addrs) => addrs.collect(),
+            Err(err) => return Err(Error::DestinationResolveError { host: host.into(), err }),
+        }
+    } else {
+        vec![]
+    };
+    let ips: Vec<IpAddr> = addrs.iter().map(SocketAddr::ip).collect();
+
+    if rules.allows(host, &ips, port) {
+        // Only keep the addresses that are allowed by themselves
+        Ok(addrs.into_iter().filter(|addr| rules.allows(host, &[addr.ip()], port)).collect())
+    } else {
+        Err(Error::DestinationNotAllowed { address: address.to_string() })
+    }
+}
+
 /// Initializes a path server with the given port and address.
 ///
 /// # Arguments
@@ -115,7 +356,7 @@ pub async fn path_server_factory(
             return Err(Error::IllegalUrl { raw: remote_addr, err });
         },
     };
-    let hostname: &str = match remote_addr.domain() {
+    let hostname: &str = match remote_addr.host_str() {
         Some(hostname) => hostname,
         None => {
             return Err(Error::NoDomainName { raw: remote_addr.to_string() });
@@ -150,11 +391,22 @@ pub async fn path_server_factory(
 
     // Now match on what to do
     if let Some(proxy_cfg) = &context.proxy.forward {
+        // Resolve the credentials for the proxy, if any
+        let creds: Option<SocksCredentials> = match &proxy_cfg.credentials {
+            Some(creds) => match creds.password.resolve().await {
+                Ok(password) => Some(SocksCredentials::new(creds.username.as_bytes(), password.as_bytes())),
+                Err(err) => {
+                    return Err(Error::ForwardCredentialsError { address: proxy_cfg.address.clone(), err });
+                },
+            },
+            None => None,
+        };
+
         // Open the relevant client
         let client: RemoteClient = match proxy_cfg.protocol {
             ProxyProtocol::Socks5 => {
                 // Attempt to open the socks 5 client
-                match Socks5Client::new(proxy_cfg.address.to_string(), None).await {
+                match Socks5Client::new(proxy_cfg.address.to_string(), creds).await {
                     Ok(client) => RemoteClient::Socks5(client, proxy_cfg.address.clone()),
                     Err(err) => {
                         return Err(Error::Socks5CreateError { address: proxy_cfg.address.clone(), err });
@@ -164,7 +416,7 @@ pub async fn path_server_factory(
 
             ProxyProtocol::Socks6 => {
                 // Attempt to open the socks 6 client
-                match Socks6Client::new(proxy_cfg.address.to_string(), None).await {
+                match Socks6Client::new(proxy_cfg.address.to_string(), creds).await {
                     Ok(client) => RemoteClient::Socks6(client, proxy_cfg.address.clone()),
                     Err(err) => {
                         return Err(Error::Socks6CreateError { address: proxy_cfg.address.clone(), err });
@@ -174,10 +426,28 @@ pub async fn path_server_factory(
         };
 
         // If that was successfull, return the future
-        Ok(path_server(context.node_config_path.clone(), listener, client, socket_addr, remote_addr, tls))
+        Ok(path_server(
+            context.node_config_path.clone(),
+            listener,
+            client,
+            context.proxy.destinations.clone(),
+            socket_addr,
+            remote_addr,
+            tls,
+            context.splice,
+        ))
     } else {
         // Otherwise, just pass the address as 'to-be-connected'
-        Ok(path_server(context.node_config_path.clone(), listener, RemoteClient::Direct, socket_addr, remote_addr, tls))
+        Ok(path_server(
+            context.node_config_path.clone(),
+            listener,
+            RemoteClient::Direct,
+            context.proxy.destinations.clone(),
+            socket_addr,
+            remote_addr,
+            tls,
+            context.splice,
+        ))
     }
 }
 
@@ -185,32 +455,44 @@ pub async fn path_server_factory(
 
 /// Serves the proxying service on the given path, to the given address.
 ///
+/// Every accepted connection is served in its own task, such that a long-running transfer does not hold up other connections on the same path.
+///
 /// # Arguments
 /// - `node_config_path`: Path to the `node.yml` file where we pull the certificates location from.
 /// - `listener`: The TcpListener with which we serve.
 /// - `client`: The proxied SOCKS6-client / unproxied TcpClient to connect to remote hosts with.
+/// - `rules`: The [`DestinationRules`] that the remote host is checked against for every connection.
 /// - `socket_addr`: The SocketAddress on which to serve.
 /// - `address`: The address to redirect the traffic to.
 /// - `tls`: If given, adds TLS encryption to the remote host with the given address.
+/// - `splice`: Whether to splice links that do not use TLS (see [`forward_tcp()`]).
 ///
 /// # Returns
 /// Never, ideally.
 ///
 /// # Errors
 /// This function does not error directly, but instead write errors to stderr (using the `log` crate) and then returns.
+#[allow(clippy::too_many_arguments)]
 pub async fn path_server(
     node_config_path: PathBuf,
     listener: TcpListener,
     client: RemoteClient,
+    rules: DestinationRules,
     socket_addr: SocketAddr,
     address: Url,
     tls: Option<(ServerName, NewPathRequestTlsOptions)>,
+    splice: bool,
 ) -> Never {
     info!("Initiated new path ':{}' to '{}'", socket_addr, address);
+    let node_config_path: Arc<PathBuf> = Arc::new(node_config_path);
+    let client: Arc<RemoteClient> = Arc::new(client);
+    let rules: Arc<DestinationRules> = Arc::new(rules);
+    let address: Arc<Url> = Arc::new(address);
+    let tls: Arc<Option<(ServerName, NewPathRequestTlsOptions)>> = Arc::new(tls);
     loop {
         // Wait for the next connection
         debug!(":{}->{}: Ready for new connection", socket_addr.port(), address);
-        let (mut iconn, client_addr): (TcpStream, SocketAddr) = match listener.accept().await {
+        let (iconn, client_addr): (TcpStream, SocketAddr) = match listener.accept().await {
             Ok(res) => res,
             Err(err) => {
                 error!(":{}->{}: Failed to accept incoming request: {}", socket_addr.port(), address, err);
@@ -218,127 +500,16 @@ pub async fn path_server(
             },
         };
         debug!(":{}->{}: Got new connection from '{}'", socket_addr.port(), address, client_addr);
-
-        // Now we establish a new connection to the remote host
-        let addr: String = format!("{}:{}", address.domain().unwrap(), address.port().unwrap());
-        debug!("Connecting to '{}'...", addr);
-        let mut oconn: TcpStream = match client.connect(&addr).await {
-            Ok(oconn) => oconn,
-            Err(err) => {
-                error!(":{}->{}: Failed to connect to remote '{}': {}", socket_addr.port(), address, addr, err);
-                continue;
-            },
-        };
-
-        // Now switch on whether we're using TLS or not.
-        if let Some((domain, tls)) = &tls {
-            // Use TLS
-            debug!(":{}->{}: Setting up TLS for location '{}'...", socket_addr.port(), address, tls.location);
-
-            // Load the node config file
-            let node_config: NodeConfig = match NodeConfig::from_path(&node_config_path) {
-                Ok(config) => config,
-                Err(err) => {
-                    error!(":{}->{}: Failed to load NodeConfig file: {}", socket_addr.port(), address, err);
-                    std::process::exit(1);
-                },
-            };
-
-            // Load the certificate path
-            let cert_path: &Path = match &node_config.node {
-                NodeSpecificConfig::Central(node) => &node.paths.certs,
-                NodeSpecificConfig::Worker(node) => &node.paths.certs,
-                NodeSpecificConfig::Proxy(node) => &node.paths.certs,
-            };
-
-            // Load the root CA certificate file
-            let ca_path: PathBuf = cert_path.join(&tls.location).join("ca.pem");
-            let ca: RootCertStore = match load_certstore(&ca_path) {
-                Ok(store) => store,
-                Err(err) => {
-                    error!(
-                        ":{}->{}: Failed to load root certificate '{}' for location '{}': {}",
-                        socket_addr.port(),
-                        address,
-                        ca_path.display(),
-                        tls.location,
-                        err
-                    );
-                    continue;
-                },
-            };
-
-            // If any, also load the client file
-            let client: Option<(PathBuf, Vec<Certificate>, PrivateKey)> = if tls.use_client_auth {
-                debug!(":{}->{}: Adding client certificate...", socket_addr.port(), address);
-                let client_path: PathBuf = cert_path.join(&tls.location).join("client-id.pem");
-                match load_identity(&client_path) {
-                    Ok((certs, key)) => Some((client_path, certs, key)),
-                    Err(err) => {
-                        error!(
-                            ":{}->{}: Failed to load client identity file '{}' for location '{}': {}",
-                            socket_addr.port(),
-                            address,
-                            client_path.display(),
-                            tls.location,
-                            err
-                        );
-                        continue;
-                    },
-                }
-            } else {
-                None
-            };
-
-            // Create a ClientConfig with that
-            let config: ConfigBuilder<_, _> = ClientConfig::builder().with_safe_defaults().with_root_certificates(ca);
-            let config: ClientConfig = if let Some((path, certs, key)) = client {
-                match config.with_client_auth_cert(certs, key) {
-                    Ok(config) => config,
-                    Err(err) => {
-                        error!(
-                            ":{}->{}: Failed to build client config from '{}' and '{}': {}",
-                            socket_addr.port(),
-                            address,
-                            ca_path.display(),
-                            path.display(),
-                            err
-                        );
-                        continue;
-                    },
-                }
-            } else {
-                config.with_no_client_auth()
-            };
-
-            // We can now wrap the outgoing stream in a TLS client stream.
-            debug!(":{}->{}: Negotiating TLS...", socket_addr.port(), address);
-            let connector: TlsConnector = TlsConnector::from(Arc::new(config));
-            let mut oconn: TlsStream<TcpStream> = match connector.connect(domain.clone(), oconn).await {
-                Ok(oconn) => oconn,
-                Err(err) => {
-                    error!(":{}->{}: Failed to start a TLS connection with '{}': {}", socket_addr.port(), address, addr, err);
-                    continue;
-                },
-            };
-
-            // For the remainder of this session, simply copy the TCP stream both ways
-            debug!(":{}->{}: Bidirectional link started", socket_addr.port(), address);
-            if let Err(err) = tokio::io::copy_bidirectional(&mut iconn, &mut oconn).await {
-                error!(":{}->{}: Bidirectional link failed: {}", socket_addr.port(), address, err);
-                continue;
-            }
-            debug!(":{}->{}: Bidirectional link completed", socket_addr.port(), address);
-        } else {
-            // Simple TCP
-
-            // For the remainder of this session, simply copy the TCP stream both ways
-            debug!(":{}->{}: Bidirectional link started", socket_addr.port(), address);
-            if let Err(err) = tokio::io::copy_bidirectional(&mut iconn, &mut oconn).await {
-                error!(":{}->{}: Bidirectional link failed: {}", socket_addr.port(), address, err);
-                continue;
-            }
-            debug!(":{}->{}: Bidirectional link completed", socket_addr.port(), address);
+        chaos::delay().await;
+        if chaos::should_drop() {
+            debug!(":{}->{}: Dropping connection from '{}' (chaos mode)", socket_addr.port(), address, client_addr);
+            continue;
         }
+
+        // Serve it in the background
+        let (node_config_path, client, rules, address, tls) = (node_config_path.clone(), client.clone(), rules.clone(), address.clone(), tls.clone());
+        tokio::spawn(async move {
+            serve_link(&node_config_path, &client, &rules, socket_addr, &address, tls.as_ref().as_ref(), splice, iconn).await;
+        });
     }
 }
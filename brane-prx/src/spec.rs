@@ -4,7 +4,7 @@
 //  Created:
 //    23 Nov 2022, 11:02:54
 //  Last edited:
-//    09 Mar 2023, 16:12:19
+//    15 Oct 2026, 22:53:18
 //  Auto updated?
 //    Yes
 //
@@ -36,6 +36,8 @@ pub struct Context {
     pub opened: Mutex<HashMap<(String, Option<NewPathRequestTlsOptions>), u16>>,
     /// Specificies available path ports.
     pub ports:  Mutex<PortAllocator>,
+    /// Whether to move the bytes of plain TCP links with `splice(2)` (Linux only).
+    pub splice: bool,
 }
 
 
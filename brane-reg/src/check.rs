@@ -4,7 +4,7 @@
 //  Created:
 //    07 Feb 2024, 13:40:32
 //  Last edited:
-//    07 Feb 2024, 14:42:13
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -25,13 +25,14 @@ use enum_debug::EnumDebug as _;
 use error_trace::trace;
 use log::{debug, error, info};
 use specifications::data::DataName;
+use specifications::errors::ErrorCode;
 use specifications::profiling::ProfileReport;
 use specifications::registering::{CheckTransferReply, CheckTransferRequest};
 use warp::hyper::StatusCode;
 use warp::reject::Rejection;
 use warp::reply::{self, Reply, Response};
 
-use crate::data::assert_asset_permission;
+use crate::data::{assert_asset_permission, error_reply};
 use crate::spec::Context;
 
 
@@ -72,7 +73,7 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
         Ok(wf) => wf,
         Err(err) => {
             debug!("{}", trace!(("Given request has an invalid workflow"), err));
-            return Ok(warp::reply::with_status(Response::new("Invalid workflow".to_string().into()), StatusCode::BAD_REQUEST));
+            return Ok(error_reply("Invalid workflow", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
         },
     };
 
@@ -91,7 +92,7 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
                         workflow.graph.len()
                     );
                     debug!("{}", msg);
-                    return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+                    return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
                 },
             }
         } else {
@@ -106,25 +107,25 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
                             graph.len()
                         );
                         debug!("{}", msg);
-                        return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+                        return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
                     },
                 },
                 None => {
                     let msg: String = format!("Given request has an invalid workflow: unknown function ID '{}'", task.func_id);
                     debug!("{}", msg);
-                    return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+                    return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
                 },
             }
         };
 
         // Assert it is a Node that implies a transfer
-        if let Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, next: _ } = edge {
+        if let Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, cache: _, next: _ } = edge {
             // Ensure the requested dataset is the input of the request
             // NOTE: Might one day be extended to also check if we own that dataset, but only at that point
             if !input.contains_key(&name) {
                 let msg: String = format!("Bad request: requested dataset '{}' not part of input to node '{}'", name, task);
                 debug!("{}", msg);
-                return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+                return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
             }
 
             // Unwrap the 'at'
@@ -133,13 +134,13 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
                 None => {
                     let msg: String = format!("Given request has an invalid workflow: encountered unplanned Node '{}'", task);
                     debug!("{}", msg);
-                    return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+                    return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
                 },
             }
         } else {
             let msg: String = format!("Given task ID '{}' is invalid: expected Node, got {}", task, edge.variant());
             debug!("{}", msg);
-            return Ok(warp::reply::with_status(Response::new(msg.into()), StatusCode::BAD_REQUEST));
+            return Ok(error_reply(msg, StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
         }
     } else {
         // Instead, we use the workflow receiver and assume it's the last task
@@ -147,17 +148,17 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
             Some(user) => user.clone(),
             None => {
                 debug!("Given request has an invalid workflow: no task given and no final result receiver defined");
-                return Ok(warp::reply::with_status(
-                    Response::new("No task ID specified and no result receiver in workflow".to_string().into()),
-                    StatusCode::BAD_REQUEST,
-                ));
+                return Ok(error_reply("No task ID specified and no result receiver in workflow", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
             },
         }
     };
     prep.stop();
 
     // Attempt to parse the certificate to get the client's name (which tracks because it's already authenticated)
-    match report.time_fut("Checker", assert_asset_permission(&worker_config, &use_case, &workflow, &target, name.clone(), task)).await {
+    match report
+        .time_fut("Checker", assert_asset_permission(&worker_config, &context.verdicts, &use_case, &workflow, &target, name.clone(), task))
+        .await
+    {
         Ok(None) => {
             info!("Checker authorized transfer of dataset '{}' to '{}'", name, target);
 
@@ -166,10 +167,7 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
                 Ok(res) => res,
                 Err(err) => {
                     error!("{}", trace!(("Failed to serialize ChecKTransferReply"), err));
-                    return Ok(warp::reply::with_status(
-                        Response::new("Internal server error".to_string().into()),
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                    ));
+                    return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
                 },
             };
 
@@ -188,10 +186,7 @@ async fn check_data_or_result(name: DataName, body: CheckTransferRequest, contex
                 Ok(res) => res,
                 Err(err) => {
                     error!("{}", trace!(("Failed to serialize ChecKTransferReply"), err));
-                    return Ok(warp::reply::with_status(
-                        Response::new("Internal server error".to_string().into()),
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                    ));
+                    return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
                 },
             };
 
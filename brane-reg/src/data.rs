@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:40:40
 //  Last edited:
-//    07 Feb 2024, 14:19:12
+//    16 Oct 2026, 02:42:53
 //  Auto updated?
 //    Yes
 //
@@ -14,6 +14,7 @@
 //
 
 use std::collections::HashMap;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
@@ -21,27 +22,36 @@ use std::time::Duration;
 use brane_ast::ast::Edge;
 use brane_ast::func_id::FunctionId;
 use brane_ast::Workflow;
+use brane_cfg::approvals::{approval_id, ApprovalRequest, ApprovalStatus, ResultProvenance};
 use brane_cfg::certs::extract_client_name;
 use brane_cfg::info::Info as _;
-use brane_cfg::node::{NodeConfig, NodeSpecificConfig, WorkerConfig};
+use brane_cfg::node::{EgressApprovalConfig, NodeConfig, NodeSpecificConfig, WorkerConfig};
 use brane_exe::pc::ProgramCounter;
 use brane_shr::formatters::BlockFormatter;
-use brane_shr::fs::archive_async;
+use brane_shr::fs::{archive_async, archive_zstd_async};
+use brane_shr::remote::fetch_async;
+use brane_tsk::caches::{CheckQuestion, VerdictCache, VerdictKey};
 use brane_tsk::errors::AuthorizeError;
+use chrono::Utc;
 use deliberation::spec::Verdict;
 use enum_debug::EnumDebug as _;
 use error_trace::{trace, ErrorTrace as _};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use reqwest::header;
 use rustls::Certificate;
 use serde::{Deserialize, Serialize};
-use specifications::checking::DELIBERATION_API_TRANSFER_DATA;
+use specifications::checking::{ApprovalPending, PolicyDenial, DELIBERATION_API_TRANSFER_DATA};
 use specifications::data::{AccessKind, AssetInfo, DataName};
+use specifications::errors::{ErrorCode, ERROR_CODE_HEADER};
+use specifications::events::EventKind;
 use specifications::profiling::ProfileReport;
-use specifications::registering::DownloadAssetRequest;
+use specifications::registering::{ArchiveCompression, DownloadAssetRequest, ResultInfoReply};
+use specifications::telemetry::{Span, TraceContext};
 use tempfile::TempDir;
 use tokio::fs as tfs;
-use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt as _;
+use tokio_stream::StreamExt as _;
+use tokio_util::io::ReaderStream;
 use warp::http::HeaderValue;
 use warp::hyper::body::{Bytes, Sender};
 use warp::hyper::{Body, StatusCode};
@@ -54,11 +64,20 @@ use crate::spec::Context;
 use crate::store::Store;
 
 
+/***** CONSTANTS *****/
+/// The size of the chunks in which tarballs are read from disk and sent to clients.
+const ARCHIVE_CHUNK_SIZE: usize = 256 * 1024;
+
+
+
+
+
 /***** HELPER FUNCTIONS *****/
 /// Runs the do-be-done data transfer by the checker to assess if we're allowed to do it.
 ///
 /// # Arguments
 /// - `worker_cfg`: The configuration for this node's environment. For us, contains if and where we should proxy the request through and where we may find the checker.
+/// - `verdicts`: The [`VerdictCache`] with the checker's earlier verdicts, which is consulted before the checker itself.
 /// - `use_case`: A string denoting which use-case (registry) we're using.
 /// - `workflow`: The workflow to check.
 /// - `client_name`: The name as which the client is authenticated. Will be matched with the indicated task.
@@ -72,6 +91,7 @@ use crate::store::Store;
 /// This function errors if we failed to ask the checker. Clearly, that should be treated as permission denied.
 pub async fn assert_asset_permission(
     worker_cfg: &WorkerConfig,
+    verdicts: &VerdictCache,
     use_case: &str,
     workflow: &Workflow,
     client_name: &str,
@@ -90,7 +110,7 @@ pub async fn assert_asset_permission(
         // Extract the parts of the node we're interested in
         let (at, input): (&String, &HashMap<DataName, _>) = if pc.func_id.is_main() {
             match workflow.graph.get(pc.edge_idx) {
-                Some(Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, next: _ }) => {
+                Some(Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, cache: _, next: _ }) => {
                     if let Some(at) = at {
                         (at, input)
                     } else {
@@ -104,7 +124,7 @@ pub async fn assert_asset_permission(
         } else {
             match workflow.funcs.get(&pc.func_id.id()) {
                 Some(edges) => match edges.get(pc.edge_idx) {
-                    Some(Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, next: _ }) => {
+                    Some(Edge::Node { task: _, locs: _, at, input, result: _, metadata: _, cache: _, next: _ }) => {
                         if let Some(at) = at {
                             (at, input)
                         } else {
@@ -148,6 +168,26 @@ pub async fn assert_asset_permission(
         }
     }
 
+    // Re-use the checker's earlier verdict if it's still valid
+    let key: Option<VerdictKey> = verdicts
+        .key(
+            &worker_cfg.services.chk.address,
+            &worker_cfg.paths.policy_expert_secret,
+            use_case,
+            workflow,
+            CheckQuestion::Data(data_name.clone(), call),
+        )
+        .await;
+    if let Some(verdict) = key.as_ref().and_then(|key| verdicts.get(key)) {
+        info!(
+            "Checker {} data access of '{}'{} (cached)",
+            if verdict.is_none() { "ALLOWED" } else { "DENIED" },
+            data_name,
+            if let Some(call) = call { format!(" (in the context of {})", call) } else { String::new() },
+        );
+        return Ok(verdict);
+    }
+
     // Alrighty tighty, let's begin by building the request for the checker
     debug!("Constructing checker request...");
     let body: AccessDataRequest =
@@ -200,14 +240,14 @@ pub async fn assert_asset_permission(
     };
 
     // Now match the checker's response
-    match res {
+    let verdict: Option<Vec<String>> = match res {
         Verdict::Allow(_) => {
             info!(
                 "Checker ALLOWED data access of '{}'{}",
                 data_name,
                 if let Some(call) = call { format!(" (in the context of {})", call) } else { String::new() },
             );
-            Ok(None)
+            None
         },
 
         Verdict::Deny(verdict) => {
@@ -216,9 +256,302 @@ pub async fn assert_asset_permission(
                 data_name,
                 if let Some(call) = call { format!(" (in the context of {})", call) } else { String::new() },
             );
-            Ok(Some(verdict.reasons_for_denial.unwrap_or_else(Vec::new)))
+            Some(verdict.reasons_for_denial.unwrap_or_else(Vec::new))
         },
+    };
+    if let Some(key) = key {
+        verdicts.insert(key, verdict.clone());
     }
+    Ok(verdict)
+}
+
+/// Finds the name of the task called at the given program counter in a workflow.
+///
+/// # Arguments
+/// - `workflow`: The [`Workflow`] to search.
+/// - `pc`: The [`ProgramCounter`] pointing to the task call, if any.
+///
+/// # Returns
+/// The name of the task, or [`None`] if `pc` is [`None`] or does not point to a task call.
+fn task_name(workflow: &Workflow, pc: Option<ProgramCounter>) -> Option<String> {
+    let pc: ProgramCounter = pc?;
+    let edges: &[Edge] = if pc.func_id.is_main() { &workflow.graph } else { workflow.funcs.get(&pc.func_id.id())? };
+    match edges.get(pc.edge_idx) {
+        Some(Edge::Node { task, .. }) if *task < workflow.table.tasks.len() => Some(workflow.table.task(*task).name().into()),
+        _ => None,
+    }
+}
+
+/// Creates an error response that carries the given [`ErrorCode`] in the [`ERROR_CODE_HEADER`].
+///
+/// # Arguments
+/// - `body`: The body of the response (e.g., a human-readable message).
+/// - `status`: The HTTP status code of the response.
+/// - `code`: The [`ErrorCode`] that describes what went wrong.
+///
+/// # Returns
+/// A response with the given status code, body and error code.
+pub(crate) fn error_reply(body: impl Into<Body>, status: StatusCode, code: ErrorCode) -> reply::WithStatus<Response> {
+    let mut response = Response::new(body.into());
+    response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(code.as_str()));
+    reply::with_status(response, status)
+}
+
+/// Creates the response that tells the client the checker denied a download, explaining why.
+///
+/// # Arguments
+/// - `denial`: The [`PolicyDenial`] to send back.
+///
+/// # Returns
+/// A response with status code 403, the `denial` as JSON body and [`ErrorCode::RegPolicyDenied`] as error code.
+fn denied(denial: &PolicyDenial) -> reply::WithStatus<Response> {
+    let body: Vec<u8> = denial.to_bytes();
+    let body_len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body_len));
+    response.headers_mut().insert("Content-Type", HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::RegPolicyDenied.as_str()));
+    reply::with_status(response, StatusCode::FORBIDDEN)
+}
+
+/// Creates the response that tells the client its download awaits the approval of a data steward.
+///
+/// # Arguments
+/// - `pending`: The [`ApprovalPending`] to send back.
+///
+/// # Returns
+/// A response with status code 423, the `pending` as JSON body and [`ErrorCode::RegApprovalPending`] as error code.
+fn pending(pending: &ApprovalPending) -> reply::WithStatus<Response> {
+    let body: Vec<u8> = pending.to_bytes();
+    let body_len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body_len));
+    response.headers_mut().insert("Content-Type", HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(ERROR_CODE_HEADER, HeaderValue::from_static(ErrorCode::RegApprovalPending.as_str()));
+    reply::with_status(response, StatusCode::LOCKED)
+}
+
+/// Decides whether the download of an intermediate result may leave this domain or has to be approved by a data steward first.
+///
+/// Whether a result needs approval depends on the [`ResultProvenance`] that the worker recorded when it produced the result, never on the
+/// workflow of the client (which may describe the result however it likes). Results without a recorded provenance always need approval.
+///
+/// If it needs approval and was never requested before, it is parked as a new [`ApprovalRequest`]. Requests are identified by the fingerprint
+/// of the result, the client and the user, such that a decision applies to every run of the same workflow with the same arguments.
+///
+/// # Arguments
+/// - `config`: The [`EgressApprovalConfig`] of this domain.
+/// - `results`: The directory with the intermediate results of this domain (and their provenance).
+/// - `workflow`: The [`Workflow`] in whose context the result is downloaded.
+/// - `client_name`: The name as which the client is authenticated.
+/// - `name`: The name of the intermediate result.
+/// - `task`: The name of the task that needs the result, if any.
+///
+/// # Returns
+/// [`None`] if the download may proceed, or else the [`ApprovalRequest`] that is still pending or that was denied.
+///
+/// # Errors
+/// This function errors if we failed to read or write the request.
+fn egress_approval(
+    config: &EgressApprovalConfig,
+    results: &Path,
+    workflow: &Workflow,
+    client_name: &str,
+    name: &str,
+    task: Option<String>,
+) -> Result<Option<ApprovalRequest>, brane_cfg::approvals::Error> {
+    let (lineage, fingerprint, tags, sensitivity): (String, String, Vec<String>, u32) = match ResultProvenance::load(results, name)? {
+        Some(provenance) => {
+            let sensitivity: u32 = config.sensitivity(provenance.tags.iter().map(String::as_str));
+            if sensitivity < config.threshold {
+                debug!("Intermediate result '{}' has sensitivity {} (below threshold {}); no approval needed", name, sensitivity, config.threshold);
+                return Ok(None);
+            }
+            (provenance.lineage, provenance.fingerprint, provenance.tags, sensitivity)
+        },
+        None => {
+            // NOTE: Without provenance, the name of the result is the best we can do to identify it
+            warn!("Intermediate result '{}' has no recorded provenance; treating it as sensitive", name);
+            let lineage: String = format!("result:{name}");
+            (lineage.clone(), lineage, vec![], config.threshold)
+        },
+    };
+
+    // See if a data steward already decided on it (in this or an earlier run), parking it if not
+    let id: String = approval_id(&fingerprint, client_name, workflow.user.as_deref());
+    debug!("Submitting download of intermediate result '{}' (sensitivity {}) as request '{}'", name, sensitivity, id);
+    let request: ApprovalRequest = ApprovalRequest {
+        id,
+        result: name.into(),
+        lineage,
+        workflow: workflow.id.clone(),
+        user: (*workflow.user).clone(),
+        requester: client_name.into(),
+        task,
+        tags,
+        sensitivity,
+        requested: Utc::now(),
+        status: ApprovalStatus::Pending,
+        decided: None,
+        reason: None,
+    }
+    .submit(&config.path)?;
+    match request.status {
+        ApprovalStatus::Approved => Ok(None),
+        ApprovalStatus::Pending | ApprovalStatus::Denied => Ok(Some(request)),
+    }
+}
+
+/// Returns the file extension of tarballs compressed with the given compression.
+///
+/// # Arguments
+/// - `compression`: The [`ArchiveCompression`] of the tarball.
+///
+/// # Returns
+/// The extension, without leading dot.
+#[inline]
+fn archive_extension(compression: ArchiveCompression) -> &'static str {
+    match compression {
+        ArchiveCompression::Gzip => "tar.gz",
+        ArchiveCompression::Zstd => "tar.zst",
+    }
+}
+
+/// Archives the given dataset or result to a tarball that is compressed as the client asked.
+///
+/// # Arguments
+/// - `source`: The file or directory to archive.
+/// - `tmpdir`: The (temporary) directory to write the tarball to.
+/// - `compression`: The [`ArchiveCompression`] to compress the tarball with.
+///
+/// # Returns
+/// The path to the written tarball.
+///
+/// # Errors
+/// This function errors if we failed to write the archive.
+async fn archive(source: &Path, tmpdir: &Path, compression: ArchiveCompression) -> Result<PathBuf, brane_shr::fs::Error> {
+    let tar_path: PathBuf = tmpdir.join(format!("data.{}", archive_extension(compression)));
+    match compression {
+        ArchiveCompression::Gzip => archive_async(source, &tar_path, true).await?,
+        ArchiveCompression::Zstd => archive_zstd_async(source, &tar_path, true).await?,
+    }
+    Ok(tar_path)
+}
+
+/// Parses the start of a `Range`-header that asks for the remainder of a file (i.e., `bytes=<start>-`).
+///
+/// This is the only kind of range that workers send, when they resume an interrupted download.
+///
+/// # Arguments
+/// - `range`: The value of the `Range`-header.
+///
+/// # Returns
+/// The offset of the first byte to send, or [`None`] if the range is of any other form.
+fn range_start(range: &str) -> Option<u64> { range.trim().strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok() }
+
+/// Computes the size of a file or (recursively) of the files in a directory.
+///
+/// # Arguments
+/// - `path`: The file or directory to compute the size of.
+///
+/// # Returns
+/// The total size, in bytes.
+///
+/// # Errors
+/// This function errors if we failed to read (some of) the files or directories.
+fn disk_size(path: &Path) -> Result<u64, std::io::Error> {
+    let metadata: std::fs::Metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size: u64 = 0;
+    for entry in std::fs::read_dir(path)? {
+        size += disk_size(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+/// Sends the given tarball as the body of a reply.
+///
+/// The tarball is streamed from disk in large chunks that are handed to the body as-is, such that even multi-gigabyte datasets are never
+/// read into memory (or copied around) as a whole.
+///
+/// # Arguments
+/// - `tar_path`: The path of the tarball to send.
+/// - `tmpdir`: The temporary directory that the tarball lives in, if any. It is removed once the tarball has been sent.
+/// - `resumable`: Whether the tarball is kept between downloads, such that clients may resume an interrupted download of it.
+/// - `range`: The `Range`-header sent by the client, if any. Ignored if the tarball isn't `resumable`.
+/// - `report`: The [`ProfileReport`] to report the time it took to send the tarball to.
+///
+/// # Returns
+/// A response with the tarball (or the requested remainder of it) as its body.
+///
+/// # Errors
+/// This function errors if we failed to open the tarball.
+async fn send_archive(
+    tar_path: PathBuf,
+    tmpdir: Option<TempDir>,
+    resumable: bool,
+    range: Option<&str>,
+    report: ProfileReport,
+) -> Result<Response, Error> {
+    let mut handle: tfs::File = match tfs::File::open(&tar_path).await {
+        Ok(handle) => handle,
+        Err(err) => return Err(Error::TarOpenError { path: tar_path, err }),
+    };
+    let size: u64 = match handle.metadata().await {
+        Ok(metadata) => metadata.len(),
+        Err(err) => return Err(Error::TarMetadataError { path: tar_path, err }),
+    };
+
+    // Skip the part that the client already has, if it asked for a (sensible) remainder
+    let start: u64 = match range.filter(|_| resumable).and_then(range_start) {
+        Some(start) if start > 0 && start < size => {
+            if let Err(err) = handle.seek(SeekFrom::Start(start)).await {
+                return Err(Error::TarReadError { path: tar_path, err });
+            }
+            start
+        },
+        _ => 0,
+    };
+    debug!("Sending back reply with compressed archive ({} bytes, starting at {})...", size, start);
+
+    // Spawn a future that streams the file chunk-by-chunk
+    let (mut body_sender, body): (Sender, Body) = Body::channel();
+    tokio::spawn(async move {
+        let _upload = report.time("Uploading (file)");
+
+        // We move the temporary directory here just to keep it in scope
+        let _tmpdir: Option<TempDir> = tmpdir;
+
+        let mut chunks: ReaderStream<tfs::File> = ReaderStream::with_capacity(handle, ARCHIVE_CHUNK_SIZE);
+        while let Some(chunk) = chunks.next().await {
+            let chunk: Bytes = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    error!("{}", Error::TarReadError { path: tar_path, err }.trace());
+                    break;
+                },
+            };
+            if let Err(err) = body_sender.send_data(chunk).await {
+                // The client is gone, so there's no point in reading any further
+                error!("{}", Error::TarSendError { err }.trace());
+                break;
+            }
+        }
+    });
+
+    let mut response = Response::new(body);
+    response.headers_mut().insert("Content-Length", HeaderValue::from(size - start));
+    if resumable {
+        response.headers_mut().insert("Accept-Ranges", HeaderValue::from_static("bytes"));
+    }
+    if start > 0 {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        // Note: the header value is always valid ASCII
+        response.headers_mut().insert("Content-Range", HeaderValue::from_str(&format!("bytes {}-{}/{}", start, size - 1, size)).unwrap());
+    }
+    Ok(response)
 }
 
 
@@ -393,6 +726,77 @@ pub async fn get(name: String, context: Arc<Context>) -> Result<impl Reply, Reje
     Ok(response)
 }
 
+/// Handles a GET on a specific intermediate result in a child-path of the `/results`-path, which tells the client whether it (still) exists.
+///
+/// This is used by the driver to check whether a task has to be re-run when it resumes a workflow.
+///
+/// # Arguments
+/// - `name`: The name of the intermediate result to check.
+/// - `context`: The context that carries options and some shared structures between the warp paths.
+///
+/// # Returns
+/// The response that can be send back to the client. It is 200 OK with a [`ResultInfoReply`] as body if the result exists, or 404 NOT FOUND with
+/// an empty body otherwise.
+///
+/// # Errors
+/// This function may error (i.e., reject) if we failed to load the store or to compute the size of the result.
+pub async fn get_result(name: String, context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on `/results/info/{}` (i.e., check intermediate result)...", name);
+
+    // Load the config file
+    let node_config: NodeConfig = match NodeConfig::from_path(&context.node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            error!("{}", trace!(("Failed to load NodeConfig file"), err));
+            return Err(warp::reject::reject());
+        },
+    };
+    let worker_config: WorkerConfig = if let NodeSpecificConfig::Worker(worker) = node_config.node {
+        worker
+    } else {
+        error!("Given NodeConfig file '{}' does not have properties for a worker node.", context.node_config_path.display());
+        return Err(warp::reject::reject());
+    };
+
+    // Load the store and see if it has the result
+    let store: Store = match Store::from_dirs(&worker_config.paths.data, &worker_config.paths.results).await {
+        Ok(store) => store,
+        Err(err) => {
+            error!("{}", trace!(("Failed to load the store"), err));
+            return Err(warp::reject::reject());
+        },
+    };
+    let path: &PathBuf = match store.get_result(&name) {
+        Some(path) => path,
+        None => {
+            debug!("Unknown intermediate result '{}'", name);
+            return Ok(error_reply(Body::empty(), StatusCode::NOT_FOUND, ErrorCode::RegUnknownAsset));
+        },
+    };
+    debug!("Intermediate result '{}' exists", name);
+
+    // Tell the client how large it is
+    let size: u64 = match disk_size(path) {
+        Ok(size) => size,
+        Err(err) => {
+            error!("{}", trace!(("Failed to compute size of intermediate result '{}'", path.display()), err));
+            return Err(warp::reject::reject());
+        },
+    };
+    let body: String = match serde_json::to_string(&ResultInfoReply { size }) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", trace!(("Failed to serialize info of intermediate result '{name}'"), err));
+            return Err(warp::reject::reject());
+        },
+    };
+    let body_len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body_len));
+    response.headers_mut().insert("Content-Type", HeaderValue::from_static("application/json"));
+    Ok(reply::with_status(response, StatusCode::OK))
+}
+
 
 
 /// Handles a GET that downloads an entire dataset. This basically emulates a data transfer.
@@ -400,6 +804,8 @@ pub async fn get(name: String, context: Arc<Context>) -> Result<impl Reply, Reje
 /// # Arguments
 /// - `cert`: The client certificate by which we may extract some identity. Only clients that are authenticated by the local store may connect.
 /// - `name`: The name of the dataset to download.
+/// - `trace`: The `traceparent` header propagated by the worker that downloads it, if any. Ignored if it's not a valid [`TraceContext`].
+/// - `range`: The `Range` header sent by a worker that resumes an interrupted download, if any. Only honoured for immutable datasets whose archive is kept.
 /// - `body`: The body given with the request.
 /// - `context`: The context that carries options and some shared structures between the warp paths.
 ///
@@ -411,11 +817,15 @@ pub async fn get(name: String, context: Arc<Context>) -> Result<impl Reply, Reje
 pub async fn download_data(
     cert: Option<Certificate>,
     name: String,
+    trace: Option<String>,
+    range: Option<String>,
     body: DownloadAssetRequest,
     context: Arc<Context>,
 ) -> Result<impl Reply, Rejection> {
-    let DownloadAssetRequest { use_case, workflow, task: _ } = body;
+    let DownloadAssetRequest { use_case, workflow, task: _, compression } = body;
     info!("Handling GET on `/data/download/{}` (i.e., download dataset)...", name);
+    let mut span: Span = Span::child("brane-reg download_data", &trace.and_then(|trace| trace.parse::<TraceContext>().ok()).unwrap_or_default());
+    span.set_attribute("brane.dataset", &name);
 
     // Parse if a valid workflow is given
     debug!("Parsing workflow in request body...\n\nWorkflow:\n{}\n", BlockFormatter::new(serde_json::to_string_pretty(&workflow).unwrap()));
@@ -423,7 +833,7 @@ pub async fn download_data(
         Ok(wf) => wf,
         Err(err) => {
             debug!("{}", trace!(("Given request has an invalid workflow"), err));
-            return Ok(warp::reply::with_status(Response::new("Invalid workflow".to_string().into()), StatusCode::BAD_REQUEST));
+            return Ok(error_reply("Invalid workflow", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
         },
     };
 
@@ -462,7 +872,7 @@ pub async fn download_data(
         Some(info) => info,
         None => {
             error!("Unknown dataset '{}'", name);
-            return Err(warp::reject::not_found());
+            return Ok(error_reply(Body::empty(), StatusCode::NOT_FOUND, ErrorCode::RegUnknownAsset));
         },
     };
     loading.stop();
@@ -473,38 +883,47 @@ pub async fn download_data(
         Some(cert) => cert,
         None => {
             error!("Client did not specify a certificate (client unauthenticated)");
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(error_reply(Body::empty(), StatusCode::FORBIDDEN, ErrorCode::RegUnauthenticated));
         },
     };
     let client_name: String = match extract_client_name(cert) {
         Ok(name) => name,
         Err(err) => {
             error!("{} (client unauthenticated)", err);
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(error_reply(Body::empty(), StatusCode::FORBIDDEN, ErrorCode::RegUnauthenticated));
         },
     };
 
     // Before we continue, assert that this dataset may be downloaded by this person (uh-oh, how we gon' do that)
-    match assert_asset_permission(
-        &worker_config,
-        &use_case,
-        &workflow,
-        &client_name,
-        DataName::Data(name.clone()),
-        body.task.map(|t| ProgramCounter::new(if let Some(id) = t.0 { FunctionId::Func(id as usize) } else { FunctionId::Main }, t.1 as usize)),
-    )
-    .await
-    {
+    let pc: Option<ProgramCounter> =
+        body.task.map(|t| ProgramCounter::new(if let Some(id) = t.0 { FunctionId::Func(id as usize) } else { FunctionId::Main }, t.1 as usize));
+    match assert_asset_permission(&worker_config, &context.verdicts, &use_case, &workflow, &client_name, DataName::Data(name.clone()), pc).await {
         Ok(None) => {
             info!("Checker authorized download of dataset '{}' by '{}'", info.name, client_name);
+            context.events.publish(EventKind::DataAccessed {
+                workflow:  Some(workflow.id.clone()),
+                user:      (*workflow.user).clone(),
+                data:      name.clone(),
+                result:    false,
+                requester: Some(client_name.clone()),
+                allowed:   true,
+            });
         },
 
         Ok(Some(reasons)) => {
             info!("Checker denied download of dataset '{}' by '{}'", info.name, client_name);
+            context.events.publish(EventKind::DataAccessed {
+                workflow:  Some(workflow.id.clone()),
+                user:      (*workflow.user).clone(),
+                data:      name.clone(),
+                result:    false,
+                requester: Some(client_name.clone()),
+                allowed:   false,
+            });
             if !reasons.is_empty() {
-                debug!("Reasons:\n{}\n", reasons.into_iter().map(|r| format!(" - {r}")).collect::<Vec<String>>().join("\n"));
+                debug!("Reasons:\n{}\n", reasons.iter().map(|r| format!(" - {r}")).collect::<Vec<String>>().join("\n"));
             }
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(denied(&PolicyDenial { domain: worker_config.name.clone(), task: task_name(&workflow, pc), dataset: Some(name), reasons }));
         },
         Err(err) => {
             error!("{}", trace!(("Failed to consult the checker"), err));
@@ -513,82 +932,104 @@ pub async fn download_data(
     }
     auth.stop();
 
+    // Immutable datasets may have been archived by an earlier download already
+    let arch = report.time("Archiving");
+    let cached: Option<PathBuf> = match &context.archive_cache {
+        Some(cache) if info.immutable => {
+            let cached: PathBuf = cache.join(format!("{}.{}", name, archive_extension(compression)));
+            if cached.is_file() {
+                debug!("Using earlier archive '{}' of immutable dataset '{}'", cached.display(), name);
+                arch.stop();
+                return match send_archive(cached, None, true, range.as_deref(), report).await {
+                    Ok(response) => {
+                        let status: StatusCode = response.status();
+                        Ok(reply::with_status(response, status))
+                    },
+                    Err(err) => {
+                        error!("{}", err.trace());
+                        Err(warp::reject::custom(err))
+                    },
+                };
+            }
+            if let Err(err) = tfs::create_dir_all(cache).await {
+                let err = Error::ArchiveCacheCreateError { path: cache.clone(), err };
+                error!("{}", err.trace());
+                return Err(warp::reject::custom(err));
+            }
+            Some(cached)
+        },
+        _ => None,
+    };
+
+    // Otherwise, get a temporary directory (next to the archived datasets if we keep the archive, such that we can move it there)
+    let tmpdir: Result<TempDir, std::io::Error> = match &context.archive_cache {
+        Some(cache) if cached.is_some() => TempDir::new_in(cache),
+        _ => TempDir::new(),
+    };
+    let tmpdir: TempDir = match tmpdir {
+        Ok(tmpdir) => tmpdir,
+        Err(err) => {
+            let err = Error::TempDirCreateError { err };
+            error!("{}", err.trace());
+            return Err(warp::reject::custom(err));
+        },
+    };
+
     // Access the dataset in the way it likes to be accessed
-    match &info.access {
+    let path: PathBuf = match &info.access {
         AccessKind::File { path } => {
             debug!("Accessing file '{}' @ '{}' as AccessKind::File...", name, path.display());
             let path: PathBuf = worker_config.paths.data.join(&name).join(path);
             debug!("File can be found under: '{}'", path.display());
-
-            // First, get a temporary directory
-            let arch = report.time("Archiving (file)");
-            let tmpdir: TempDir = match TempDir::new() {
-                Ok(tmpdir) => tmpdir,
+            path
+        },
+        AccessKind::S3 { .. } | AccessKind::Url { .. } => {
+            // Fetch it first, such that we can send it like any other dataset
+            debug!("Fetching remote dataset '{}' @ '{}'...", name, info.access);
+            match fetch_async(&info.access, tmpdir.path().join("data")).await {
+                Ok(path) => path,
                 Err(err) => {
-                    let err = Error::TempDirCreateError { err };
+                    let err = Error::RemoteFetchError { name, err };
                     error!("{}", err.trace());
                     return Err(warp::reject::custom(err));
                 },
-            };
+            }
+        },
+    };
 
-            // Next, create an archive in the temporary directory
-            let tar_path: PathBuf = tmpdir.path().join("data.tar.gz");
-            if let Err(err) = archive_async(&path, &tar_path, true).await {
-                let err = Error::DataArchiveError { err };
+    // Next, create an archive in the temporary directory (and keep it, if the dataset is immutable)
+    let tar_path: PathBuf = match archive(&path, tmpdir.path(), compression).await {
+        Ok(tar_path) => tar_path,
+        Err(err) => {
+            let err = Error::DataArchiveError { err };
+            error!("{}", err.trace());
+            return Err(warp::reject::custom(err));
+        },
+    };
+    let resumable: bool = cached.is_some();
+    let tar_path: PathBuf = match cached {
+        Some(cached) => {
+            debug!("Keeping archive of immutable dataset '{}' as '{}'", name, cached.display());
+            if let Err(err) = tfs::rename(&tar_path, &cached).await {
+                let err = Error::ArchiveCacheMoveError { source: tar_path, target: cached, err };
                 error!("{}", err.trace());
                 return Err(warp::reject::custom(err));
             }
-            arch.stop();
-
-            // Now we send the tarball as a file in the reply
-            debug!("Sending back reply with compressed archive...");
-            let (mut body_sender, body): (Sender, Body) = Body::channel();
-
-            // Spawn a future that reads the file chunk-by-chunk (in case of large files)
-            tokio::spawn(async move {
-                let _upload = report.time("Uploading (file)");
-
-                // We move the temporary directory here just to keep it in scope
-                let _tmpdir: TempDir = tmpdir;
-
-                // Open the archive file to read
-                let mut handle: tfs::File = match tfs::File::open(&tar_path).await {
-                    Ok(handle) => handle,
-                    Err(err) => {
-                        let err = Error::TarOpenError { path: tar_path, err };
-                        error!("{}", err.trace());
-                        return Err(warp::reject::custom(err));
-                    },
-                };
-
-                // Read it chunk-by-chunk
-                // (The size of the buffer, like most of the code but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
-                let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
-                loop {
-                    // Read the chunk
-                    let bytes: usize = match handle.read(&mut buf).await {
-                        Ok(bytes) => bytes,
-                        Err(err) => {
-                            error!("{}", Error::TarReadError { path: tar_path, err }.trace());
-                            break;
-                        },
-                    };
-                    if bytes == 0 {
-                        break;
-                    }
-
-                    // Send that with the body
-                    if let Err(err) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
-                        error!("{}", Error::TarSendError { err }.trace());
-                    }
-                }
-
-                // Done
-                Ok(())
-            });
+            cached
+        },
+        None => tar_path,
+    };
+    arch.stop();
 
-            // We use the handle as a stream.
-            Ok(reply::with_status(Response::new(body), StatusCode::OK))
+    // Now we send the tarball as a file in the reply
+    match send_archive(tar_path, Some(tmpdir), resumable, range.as_deref(), report).await {
+        Ok(response) => {
+            let status: StatusCode = response.status();
+            Ok(reply::with_status(response, status))
+        },
+        Err(err) => {
+            error!("{}", err.trace());
+            Err(warp::reject::custom(err))
         },
     }
 }
@@ -598,6 +1039,7 @@ pub async fn download_data(
 /// # Arguments
 /// - `cert`: The client certificate by which we may extract some identity. Only clients that are authenticated by the local store may connect.
 /// - `name`: The name of the intermediate result to download.
+/// - `trace`: The `traceparent` header propagated by the worker that downloads it, if any. Ignored if it's not a valid [`TraceContext`].
 /// - `body`: The body given with the request.
 /// - `context`: The context that carries options and some shared structures between the warp paths.
 ///
@@ -609,11 +1051,14 @@ pub async fn download_data(
 pub async fn download_result(
     cert: Option<Certificate>,
     name: String,
+    trace: Option<String>,
     body: DownloadAssetRequest,
     context: Arc<Context>,
 ) -> Result<impl Reply, Rejection> {
-    let DownloadAssetRequest { use_case, workflow, task: _ } = body;
+    let DownloadAssetRequest { use_case, workflow, task: _, compression } = body;
     info!("Handling GET on `/results/download/{}` (i.e., download intermediate result)...", name);
+    let mut span: Span = Span::child("brane-reg download_result", &trace.and_then(|trace| trace.parse::<TraceContext>().ok()).unwrap_or_default());
+    span.set_attribute("brane.result", &name);
 
     // Parse if a valid workflow is given
     debug!("Parsing workflow in request body...\n\nWorkflow:\n{}\n", BlockFormatter::new(serde_json::to_string_pretty(&workflow).unwrap()));
@@ -621,7 +1066,7 @@ pub async fn download_result(
         Ok(wf) => wf,
         Err(err) => {
             debug!("{}", trace!(("Given request has an invalid workflow"), err));
-            return Ok(warp::reply::with_status(Response::new("Invalid workflow".to_string().into()), StatusCode::BAD_REQUEST));
+            return Ok(error_reply("Invalid workflow", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
         },
     };
 
@@ -662,7 +1107,7 @@ pub async fn download_result(
         Some(path) => path,
         None => {
             error!("Unknown intermediate result '{}'", name);
-            return Err(warp::reject::not_found());
+            return Ok(error_reply(Body::empty(), StatusCode::NOT_FOUND, ErrorCode::RegUnknownAsset));
         },
     };
     loading.stop();
@@ -673,25 +1118,28 @@ pub async fn download_result(
         Some(cert) => cert,
         None => {
             error!("Client did not specify a certificate (client unauthenticated)");
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(error_reply(Body::empty(), StatusCode::FORBIDDEN, ErrorCode::RegUnauthenticated));
         },
     };
     let client_name: String = match extract_client_name(cert) {
         Ok(name) => name,
         Err(err) => {
             error!("{} (client unauthenticated)", err);
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(error_reply(Body::empty(), StatusCode::FORBIDDEN, ErrorCode::RegUnauthenticated));
         },
     };
 
     // Before we continue, assert that this dataset may be downloaded by this person (uh-oh, how we gon' do that)
+    let pc: Option<ProgramCounter> =
+        body.task.map(|t| ProgramCounter::new(if let Some(id) = t.0 { FunctionId::Func(id as usize) } else { FunctionId::Main }, t.1 as usize));
     match assert_asset_permission(
         &worker_config,
+        &context.verdicts,
         &use_case,
         &workflow,
         &client_name,
         DataName::IntermediateResult(name.clone()),
-        body.task.map(|t| ProgramCounter::new(if let Some(id) = t.0 { FunctionId::Func(id as usize) } else { FunctionId::Main }, t.1 as usize)),
+        pc,
     )
     .await
     {
@@ -701,16 +1149,68 @@ pub async fn download_result(
 
         Ok(Some(reasons)) => {
             info!("Checker denied download of intermediate result '{}' by '{}'", name, client_name);
+            context.events.publish(EventKind::DataAccessed {
+                workflow:  Some(workflow.id.clone()),
+                user:      (*workflow.user).clone(),
+                data:      name.clone(),
+                result:    true,
+                requester: Some(client_name.clone()),
+                allowed:   false,
+            });
             if !reasons.is_empty() {
-                debug!("Reasons:\n{}\n", reasons.into_iter().map(|r| format!(" - {r}")).collect::<Vec<String>>().join("\n"));
+                debug!("Reasons:\n{}\n", reasons.iter().map(|r| format!(" - {r}")).collect::<Vec<String>>().join("\n"));
             }
-            return Ok(reply::with_status(Response::new(Body::empty()), StatusCode::FORBIDDEN));
+            return Ok(denied(&PolicyDenial { domain: worker_config.name.clone(), task: task_name(&workflow, pc), dataset: Some(name), reasons }));
         },
         Err(err) => {
             error!("{}", trace!(("Failed to consult the checker"), err));
             return Err(warp::reject::reject());
         },
     }
+
+    // Sensitive results may only leave the domain once the data steward approved it
+    if let Some(config) = &worker_config.egress_approval {
+        match egress_approval(config, &worker_config.paths.results, &workflow, &client_name, &name, task_name(&workflow, pc)) {
+            Ok(None) => {},
+            Ok(Some(request)) if request.status == ApprovalStatus::Denied => {
+                info!("Data steward denied download of intermediate result '{}' by '{}' (request '{}')", name, client_name, request.id);
+                context.events.publish(EventKind::DataAccessed {
+                    workflow:  Some(workflow.id.clone()),
+                    user:      (*workflow.user).clone(),
+                    data:      name.clone(),
+                    result:    true,
+                    requester: Some(client_name.clone()),
+                    allowed:   false,
+                });
+                let reason: String = match request.reason {
+                    Some(reason) => format!("The data steward denied the download: {reason}"),
+                    None => "The data steward denied the download".into(),
+                };
+                return Ok(denied(&PolicyDenial {
+                    domain:  worker_config.name.clone(),
+                    task:    task_name(&workflow, pc),
+                    dataset: Some(name),
+                    reasons: vec![reason],
+                }));
+            },
+            Ok(Some(request)) => {
+                info!("Download of intermediate result '{}' by '{}' awaits approval (request '{}')", name, client_name, request.id);
+                return Ok(pending(&ApprovalPending { domain: worker_config.name.clone(), id: request.id, result: name }));
+            },
+            Err(err) => {
+                error!("{}", trace!(("Failed to consult the approval of the download of intermediate result '{name}'"), err));
+                return Err(warp::reject::reject());
+            },
+        }
+    }
+    context.events.publish(EventKind::DataAccessed {
+        workflow:  Some(workflow.id.clone()),
+        user:      (*workflow.user).clone(),
+        data:      name.clone(),
+        result:    true,
+        requester: Some(client_name.clone()),
+        allowed:   true,
+    });
     auth.stop();
 
     // Start the upload; first, get a temporary directory
@@ -725,61 +1225,121 @@ pub async fn download_result(
     };
 
     // Next, create an archive in the temporary directory
-    let tar_path: PathBuf = tmpdir.path().join("data.tar.gz");
-    if let Err(err) = archive_async(&path, &tar_path, true).await {
-        let err = Error::DataArchiveError { err };
-        error!("{}", err.trace());
-        return Err(warp::reject::custom(err));
-    }
+    let tar_path: PathBuf = match archive(&path, tmpdir.path(), compression).await {
+        Ok(tar_path) => tar_path,
+        Err(err) => {
+            let err = Error::DataArchiveError { err };
+            error!("{}", err.trace());
+            return Err(warp::reject::custom(err));
+        },
+    };
     arch.stop();
 
     // Now we send the tarball as a file in the reply
-    debug!("Sending back reply with compressed archive...");
-    let (mut body_sender, body): (Sender, Body) = Body::channel();
+    match send_archive(tar_path, Some(tmpdir), false, None, report).await {
+        Ok(response) => Ok(reply::with_status(response, StatusCode::OK)),
+        Err(err) => {
+            error!("{}", err.trace());
+            Err(warp::reject::custom(err))
+        },
+    }
+}
 
-    // Spawn a future that reads the file chunk-by-chunk (in case of large files)
-    tokio::spawn(async move {
-        let _upload = report.time("Uploading (file)");
 
-        // We move the temporary directory here just to keep it in scope
-        let _tmpdir: TempDir = tmpdir;
 
-        // Open the archive file to read
-        let mut handle: tfs::File = match tfs::File::open(&tar_path).await {
-            Ok(handle) => handle,
-            Err(err) => {
-                let err = Error::TarOpenError { path: tar_path, err };
-                error!("{}", err.trace());
-                return Err(warp::reject::custom(err));
-            },
-        };
 
-        // Read it chunk-by-chunk
-        // (The size of the buffer, like most of the code but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
-        let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
-        loop {
-            // Read the chunk
-            let bytes: usize = match handle.read(&mut buf).await {
-                Ok(bytes) => bytes,
-                Err(err) => {
-                    error!("{}", Error::TarReadError { path: tar_path, err }.trace());
-                    break;
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{compile_program, ParserOptions};
+    use brane_shr::utilities::{create_data_index, create_package_index};
+    use specifications::data::DataIndex;
+    use specifications::package::PackageIndex;
+
+    use super::*;
+
+    /// Finds the result that `copy_result` downloads in the given workflow, and thus that needs approval.
+    fn downloaded(workflow: &Workflow) -> String {
+        workflow
+            .graph
+            .iter()
+            .chain(workflow.funcs.values().flatten())
+            .find_map(|edge| match edge {
+                Edge::Node { task, input, .. } if workflow.table.task(*task).name() == "copy_result" => {
+                    input.keys().find_map(|name| if let DataName::IntermediateResult(name) = name { Some(name.clone()) } else { None })
                 },
-            };
-            if bytes == 0 {
-                break;
-            }
+                _ => None,
+            })
+            .unwrap()
+    }
 
-            // Send that with the body
-            if let Err(err) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
-                error!("{}", Error::TarSendError { err }.trace());
-            }
+    /// Records the provenance of the given result as the worker would, with a call argument of the given value.
+    fn record(results: &Path, workflow: &Workflow, result: &str, arg: &str) {
+        let lineage: String = workflow.lineage(result).unwrap();
+        ResultProvenance {
+            result: result.into(),
+            workflow: workflow.id.clone(),
+            user: Some("amy".into()),
+            task: "epi[1.0.0]::local_compute".into(),
+            fingerprint: ResultProvenance::fingerprint(&lineage, [("input", arg)], []),
+            lineage,
+            tags: vec!["pii".into()],
+            recorded: Utc::now(),
         }
+        .save(results)
+        .unwrap();
+    }
 
-        // Done
-        Ok(())
-    });
+    /// Tests that a download that a data steward approved is still approved when the workflow is compiled (and thus named) anew, but not when
+    /// the result was computed with other arguments.
+    #[test]
+    fn approvals_survive_recompile() {
+        let (pindex, dindex): (PackageIndex, DataIndex) = (create_package_index(), create_data_index());
+        let code: &str = "import epi;\nimport copy_result;\nlet res := local_compute(new Data{ name := \"Test\" });\nreturn copy_result(res);";
+        let compile = || -> Workflow { compile_program(code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript()).workflow().0 };
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-reg-approvals-{}", std::process::id()));
+        let (results, approvals): (PathBuf, PathBuf) = (dir.join("results"), dir.join("approvals"));
+        std::fs::create_dir_all(&results).unwrap();
+        std::fs::create_dir_all(&approvals).unwrap();
+        let config: EgressApprovalConfig =
+            EgressApprovalConfig { path: approvals.clone(), levels: HashMap::from([("pii".into(), 2)]), threshold: 2 };
+        let download = |workflow: &Workflow| -> Option<ApprovalRequest> {
+            egress_approval(&config, &results, workflow, "hospital_b", &downloaded(workflow), Some("copy_result".into())).unwrap()
+        };
 
-    // We use the handle as a stream.
-    Ok(reply::with_status(Response::new(body), StatusCode::OK))
+        // The first download is parked...
+        let first: Workflow = compile();
+        record(&results, &first, &downloaded(&first), "1");
+        let mut request: ApprovalRequest = download(&first).unwrap();
+        assert_eq!(request.status, ApprovalStatus::Pending);
+        assert_eq!(request.lineage, "epi[1.0.0]::local_compute(data:Test)");
+        assert_eq!(request.tags, vec!["pii".to_string()]);
+
+        // ...until the data steward approves it...
+        request.status = ApprovalStatus::Approved;
+        request.decided = Some(Utc::now());
+        request.save(&approvals).unwrap();
+
+        // ...after which it succeeds in a new compile of the same workflow
+        let second: Workflow = compile();
+        assert_ne!(first.id, second.id);
+        assert_ne!(downloaded(&first), downloaded(&second));
+        record(&results, &second, &downloaded(&second), "1");
+        assert!(download(&second).is_none());
+
+        // A result computed with other arguments needs approval of its own
+        let third: Workflow = compile();
+        record(&results, &third, &downloaded(&third), "2");
+        assert_eq!(download(&third).unwrap().status, ApprovalStatus::Pending);
+
+        // And results without provenance always need approval, whatever the workflow of the client claims
+        let fourth: Workflow = compile();
+        let request: ApprovalRequest = download(&fourth).unwrap();
+        assert_eq!(request.status, ApprovalStatus::Pending);
+        assert_eq!(request.lineage, format!("result:{}", downloaded(&fourth)));
+        assert_eq!(ApprovalRequest::load_all(&approvals).unwrap().len(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,273 @@
+//  ENROLL.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:58:16
+//  Last edited:
+//    15 Oct 2026, 21:58:16
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the `/certs` paths, with which clients can obtain a
+//!   client certificate for this domain by presenting a one-time
+//!   enrollment token (see `branectl generate enrollment-token`).
+//
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use brane_cfg::enrollment::{EnrollmentTokensFile, PendingEnrollment, ENROLLMENT_TOKENS_FILE};
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{NodeConfig, NodeSpecificConfig, WorkerConfig};
+use error_trace::trace;
+use log::{debug, error, info};
+use openssl::asn1::{Asn1Integer, Asn1Time};
+use openssl::bn::{BigNum, MsbOption};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectKeyIdentifier};
+use openssl::x509::{X509Name, X509Req, X509};
+use specifications::errors::ErrorCode;
+use specifications::registering::{EnrollReply, EnrollRequest};
+use tokio::fs as tfs;
+use tokio::sync::Mutex;
+use warp::hyper::StatusCode;
+use warp::reject::Rejection;
+use warp::reply::{self, Reply, Response};
+
+use crate::data::error_reply;
+use crate::spec::Context;
+
+
+/***** CONSTANTS *****/
+/// The number of days that issued client certificates are valid.
+const CERT_VALIDITY_DAYS: u32 = 365;
+
+
+
+
+
+/***** GLOBALS *****/
+/// Serializes enrollments, such that two clients cannot redeem the same token at the same time.
+static ENROLL_LOCK: Mutex<()> = Mutex::const_new(());
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Loads the worker config from the node config.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file.
+///
+/// # Returns
+/// The [`WorkerConfig`] of this node, or [`None`] if we failed to load it (which is already logged).
+fn load_worker(node_config_path: &Path) -> Option<WorkerConfig> {
+    let node_config: NodeConfig = match NodeConfig::from_path(node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            error!("{}", trace!(("Failed to load NodeConfig file"), err));
+            return None;
+        },
+    };
+    match node_config.node {
+        NodeSpecificConfig::Worker(worker) => Some(worker),
+        _ => {
+            error!("Given NodeConfig file '{}' does not have properties for a worker node.", node_config_path.display());
+            None
+        },
+    }
+}
+
+/// Issues a client certificate for the public key in the given certificate signing request.
+///
+/// # Arguments
+/// - `csr`: The [`X509Req`] with the client's public key. Its subject is ignored.
+/// - `client`: The name of the client, which becomes the certificate's common name.
+/// - `ca_cert`: The certificate of the CA that signs the new certificate.
+/// - `ca_key`: The private key of the CA.
+///
+/// # Returns
+/// The new, signed client certificate.
+///
+/// # Errors
+/// This function errors if OpenSSL failed to build or sign the certificate.
+fn issue(csr: &X509Req, client: &str, ca_cert: &X509, ca_key: &PKey<Private>) -> Result<X509, ErrorStack> {
+    let mut subject = X509Name::builder()?;
+    subject.append_entry_by_text("CN", client)?;
+    let subject: X509Name = subject.build();
+
+    let mut serial: BigNum = BigNum::new()?;
+    serial.rand(159, MsbOption::MAYBE_ZERO, false)?;
+    let serial: Asn1Integer = serial.to_asn1_integer()?;
+
+    let mut cert = X509::builder()?;
+    cert.set_version(2)?;
+    cert.set_serial_number(&serial)?;
+    cert.set_subject_name(&subject)?;
+    cert.set_issuer_name(ca_cert.subject_name())?;
+    cert.set_pubkey(csr.public_key()?.as_ref())?;
+    cert.set_not_before(Asn1Time::days_from_now(0)?.as_ref())?;
+    cert.set_not_after(Asn1Time::days_from_now(CERT_VALIDITY_DAYS)?.as_ref())?;
+    cert.append_extension(BasicConstraints::new().critical().build()?)?;
+    cert.append_extension(KeyUsage::new().critical().digital_signature().key_encipherment().build()?)?;
+    cert.append_extension(ExtendedKeyUsage::new().client_auth().build()?)?;
+    let ski = SubjectKeyIdentifier::new().build(&cert.x509v3_context(Some(ca_cert), None))?;
+    cert.append_extension(ski)?;
+    let aki = AuthorityKeyIdentifier::new().keyid(true).build(&cert.x509v3_context(Some(ca_cert), None))?;
+    cert.append_extension(aki)?;
+    cert.sign(ca_key, MessageDigest::sha256())?;
+    Ok(cert.build())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Handles a GET on the `/certs/ca` path, returning the certificate of this domain's CA.
+///
+/// Clients fetch this before they have a certificate themselves, and check it against the fingerprint in their enrollment token.
+///
+/// # Arguments
+/// - `context`: The context that carries options.
+///
+/// # Returns
+/// The response that can be send back to the client. Contains the CA certificate as PEM.
+///
+/// # Errors
+/// This function errors (i.e., rejects) if we failed to read the CA certificate.
+pub async fn get_ca(context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on `/certs/ca` (i.e., get the domain's CA certificate)...");
+
+    let worker: WorkerConfig = match load_worker(&context.node_config_path) {
+        Some(worker) => worker,
+        None => return Err(warp::reject::reject()),
+    };
+    let ca_path: PathBuf = worker.paths.certs.join("ca.pem");
+    match tfs::read_to_string(&ca_path).await {
+        Ok(ca) => Ok(ca),
+        Err(err) => {
+            error!("{}", trace!(("Failed to read CA certificate '{}'", ca_path.display()), err));
+            Err(warp::reject::reject())
+        },
+    }
+}
+
+
+
+/// Handles a POST on the `/certs/enroll` path, issuing a client certificate in exchange for a one-time enrollment token.
+///
+/// # Arguments
+/// - `body`: The [`EnrollRequest`] with the token's secret and the client's certificate signing request.
+/// - `context`: The context that carries options.
+///
+/// # Returns
+/// The response that can be send back to the client. Contains an [`EnrollReply`] with the new certificate and the CA certificate, or an error if
+/// the token is invalid (403), this domain cannot issue certificates (503) or the signing request is malformed (400).
+///
+/// # Errors
+/// This function doesn't usually error; failures are sent back to the client as error responses.
+pub async fn enroll(body: EnrollRequest, context: Arc<Context>) -> Result<impl Reply, Rejection> {
+    info!("Handling POST on `/certs/enroll` (i.e., enroll a new client)...");
+    let EnrollRequest { secret, csr } = body;
+
+    let worker: WorkerConfig = match load_worker(&context.node_config_path) {
+        Some(worker) => worker,
+        None => return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal)),
+    };
+    let ca_path: PathBuf = worker.paths.certs.join("ca.pem");
+    let ca_key_path: PathBuf = worker.paths.certs.join("ca-key.pem");
+    if !ca_key_path.exists() {
+        error!("Cannot enroll clients: CA key '{}' not found", ca_key_path.display());
+        return Ok(error_reply("This domain does not issue certificates", StatusCode::SERVICE_UNAVAILABLE, ErrorCode::Unavailable));
+    }
+
+    // Parse the signing request before using up the token, so a typo on the client's end doesn't waste it
+    let csr: X509Req = match X509Req::from_pem(csr.as_bytes()) {
+        Ok(csr) => csr,
+        Err(err) => {
+            debug!("{}", trace!(("Failed to parse certificate signing request"), err));
+            return Ok(error_reply("Malformed certificate signing request", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
+        },
+    };
+    match csr.public_key().and_then(|key| csr.verify(&key)) {
+        Ok(true) => {},
+        Ok(false) | Err(_) => {
+            return Ok(error_reply("Certificate signing request has an invalid signature", StatusCode::BAD_REQUEST, ErrorCode::BadRequest));
+        },
+    }
+
+    // Redeem the token
+    let pending: PendingEnrollment = {
+        let _lock = ENROLL_LOCK.lock().await;
+        let tokens_path: PathBuf = worker.paths.certs.join(ENROLLMENT_TOKENS_FILE);
+        let mut tokens: EnrollmentTokensFile = if tokens_path.exists() {
+            match EnrollmentTokensFile::from_path(&tokens_path) {
+                Ok(tokens) => tokens,
+                Err(err) => {
+                    error!("{}", trace!(("Failed to load enrollment tokens file"), err));
+                    return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+                },
+            }
+        } else {
+            EnrollmentTokensFile::default()
+        };
+        let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let pending: Option<PendingEnrollment> = tokens.redeem(&secret, now);
+        if let Err(err) = tokens.to_path(&tokens_path) {
+            error!("{}", trace!(("Failed to write enrollment tokens file"), err));
+            return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+        }
+        match pending {
+            Some(pending) => pending,
+            None => {
+                info!("Refusing enrollment with an unknown, used or expired token");
+                return Ok(error_reply("Invalid or expired enrollment token", StatusCode::FORBIDDEN, ErrorCode::RegUnauthenticated));
+            },
+        }
+    };
+
+    // Load the CA and issue the certificate
+    let ca: String = match tfs::read_to_string(&ca_path).await {
+        Ok(ca) => ca,
+        Err(err) => {
+            error!("{}", trace!(("Failed to read CA certificate '{}'", ca_path.display()), err));
+            return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+        },
+    };
+    let ca_key: Vec<u8> = match tfs::read(&ca_key_path).await {
+        Ok(ca_key) => ca_key,
+        Err(err) => {
+            error!("{}", trace!(("Failed to read CA key '{}'", ca_key_path.display()), err));
+            return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+        },
+    };
+    let certificate: Vec<u8> = match X509::from_pem(ca.as_bytes())
+        .and_then(|ca_cert| PKey::private_key_from_pem(&ca_key).map(|ca_key| (ca_cert, ca_key)))
+        .and_then(|(ca_cert, ca_key)| issue(&csr, &pending.client, &ca_cert, &ca_key))
+        .and_then(|cert| cert.to_pem())
+    {
+        Ok(certificate) => certificate,
+        Err(err) => {
+            error!("{}", trace!(("Failed to issue certificate for client '{}'", pending.client), err));
+            return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+        },
+    };
+    info!("Enrolled client '{}'", pending.client);
+
+    // Send it back
+    let reply: EnrollReply = EnrollReply { certificate: String::from_utf8_lossy(&certificate).into(), ca };
+    let body: String = match serde_json::to_string(&reply) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("{}", trace!(("Failed to serialize enrollment reply"), err));
+            return Ok(error_reply("Internal server error", StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal));
+        },
+    };
+    Ok(reply::with_status(Response::new(body.into()), StatusCode::OK))
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:13:34
 //  Last edited:
-//    16 Jan 2024, 17:27:57
+//    15 Oct 2026, 22:50:46
 //  Auto updated?
 //    Yes
 //
@@ -121,10 +121,18 @@ pub enum DataError {
 
     /// Failed to create a temporary directory.
     TempDirCreateError { err: std::io::Error },
+    /// Failed to fetch a dataset that is stored remotely.
+    RemoteFetchError { name: String, err: brane_shr::remote::Error },
     /// Failed to archive the given dataset.
     DataArchiveError { err: brane_shr::fs::Error },
+    /// Failed to create the directory with archived datasets.
+    ArchiveCacheCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to move a freshly archived dataset into the directory with archived datasets.
+    ArchiveCacheMoveError { source: PathBuf, target: PathBuf, err: std::io::Error },
     /// Failed to re-open the tar file after compressing.
     TarOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to read the metadata of the tar file.
+    TarMetadataError { path: PathBuf, err: std::io::Error },
     /// Failed to read from the tar file.
     TarReadError { path: PathBuf, err: std::io::Error },
     /// Failed to send chunk of bytes on the body.
@@ -145,8 +153,14 @@ impl Display for DataError {
             AssetSerializeError { name, .. } => write!(f, "Failed to serialize dataset metadata for dataset '{name}'"),
 
             TempDirCreateError { .. } => write!(f, "Failed to create a temporary directory"),
+            RemoteFetchError { name, .. } => write!(f, "Failed to fetch remote dataset '{name}'"),
             DataArchiveError { .. } => write!(f, "Failed to archive data"),
+            ArchiveCacheCreateError { path, .. } => write!(f, "Failed to create archived datasets directory '{}'", path.display()),
+            ArchiveCacheMoveError { source, target, .. } => {
+                write!(f, "Failed to move archived dataset '{}' to '{}'", source.display(), target.display())
+            },
             TarOpenError { path, .. } => write!(f, "Failed to re-open tarball file '{}'", path.display()),
+            TarMetadataError { path, .. } => write!(f, "Failed to read metadata of tarball file '{}'", path.display()),
             TarReadError { path, .. } => write!(f, "Failed to read from tarball file '{}'", path.display()),
             TarSendError { .. } => write!(f, "Failed to send chunk of tarball file as body"),
             UnknownFileTypeError { path } => {
@@ -168,8 +182,12 @@ impl Error for DataError {
             AssetSerializeError { err, .. } => Some(err),
 
             TempDirCreateError { err } => Some(err),
+            RemoteFetchError { err, .. } => Some(err),
             DataArchiveError { err } => Some(err),
+            ArchiveCacheCreateError { err, .. } => Some(err),
+            ArchiveCacheMoveError { err, .. } => Some(err),
             TarOpenError { err, .. } => Some(err),
+            TarMetadataError { err, .. } => Some(err),
             TarReadError { err, .. } => Some(err),
             TarSendError { err, .. } => Some(err),
             UnknownFileTypeError { .. } => None,
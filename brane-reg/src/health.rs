@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:41:12
 //  Last edited:
-//    26 Sep 2022, 15:59:07
+//    16 Oct 2026, 02:08:06
 //  Auto updated?
 //    Yes
 //
@@ -13,27 +13,42 @@
 //!   `/health` path(s).
 //
 
+use std::sync::Arc;
+
 use log::debug;
-use warp::http::HeaderValue;
+use warp::http::{HeaderValue, StatusCode};
 use warp::hyper::Body;
 use warp::reply::Response;
 use warp::{Rejection, Reply};
 
+use crate::spec::Context;
+
 
 /***** LIBRARY *****/
 /// Handles a GET on the main `/health` path, returning that this service is alive.
 ///
+/// # Arguments
+/// - `context`: The context that carries options and the certificate expiry monitor.
+///
 /// # Returns
-/// The response that can be send back to the client. Simply contains the string "OK!\n".
+/// The response that can be send back to the client. Contains the string "OK!\n" if everything is fine, or "DEGRADED!\n" followed by the reasons if some certificates are about to expire.
+/// If any of them has already expired, the response has status code `503 Service Unavailable`.
 ///
 /// # Errors
 /// This function doesn't usually error.
-pub async fn get() -> Result<impl Reply, Rejection> {
+pub async fn get(context: Arc<Context>) -> Result<impl Reply, Rejection> {
     debug!("Handling GET on `/health` (i.e., confirming service is alive)...");
 
+    // Check if any certificates are about to expire (or have already)
+    let (expired, body): (bool, String) = context.cert_monitor.health();
+
     // Construct a response with the body and the content-length header
-    let mut response = Response::new(Body::from("OK!\n"));
-    response.headers_mut().insert("Content-Length", HeaderValue::from(4));
+    let len: usize = body.len();
+    let mut response = Response::new(Body::from(body));
+    response.headers_mut().insert("Content-Length", HeaderValue::from(len));
+    if expired {
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    }
 
     // Done
     Ok(response)
@@ -4,7 +4,7 @@
 //  Created:
 //    05 Jan 2023, 11:35:25
 //  Last edited:
-//    16 Jan 2024, 17:33:30
+//    15 Oct 2026, 18:33:17
 //  Auto updated?
 //    Yes
 //
@@ -64,7 +64,7 @@ pub async fn get_capabilities(context: Arc<Context>) -> Result<impl Reply, Rejec
     };
 
     // Serialize the capabilities
-    let capabilities: HashSet<Capability> = backend.capabilities.unwrap_or_default();
+    let capabilities: HashSet<Capability> = backend.all_capabilities();
     let capabilities: String = match serde_json::to_string(&capabilities) {
         Ok(capabilities) => capabilities,
         Err(err) => {
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:12:09
 //  Last edited:
-//    07 Feb 2024, 13:41:08
+//    15 Oct 2026, 21:58:16
 //  Auto updated?
 //    Yes
 //
@@ -16,6 +16,7 @@
 // Declare the modules
 pub mod check;
 pub mod data;
+pub mod enroll;
 pub mod errors;
 pub mod health;
 pub mod infra;
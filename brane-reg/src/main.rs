@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:11:44
 //  Last edited:
-//    07 Feb 2024, 14:42:42
+//    16 Oct 2026, 02:28:03
 //  Auto updated?
 //    Yes
 //
@@ -14,17 +14,23 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use brane_cfg::certs::{self, ExpiryMonitor};
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{NodeConfig, WorkerConfig};
 use brane_reg::server::serve_with_auth;
 use brane_reg::spec::Context;
-use brane_reg::{check, data, health, infra, version};
+use brane_reg::{check, data, enroll, health, infra, version};
+use brane_shr::events::EventPublisher;
+use brane_shr::logging::{self, LogFormat};
+use brane_tsk::caches::VerdictCache;
 use clap::Parser;
 use dotenvy::dotenv;
 use error_trace::{trace, ErrorTrace as _};
 use log::{debug, error, info, LevelFilter};
 use rustls::Certificate;
+use specifications::telemetry::TRACEPARENT_HEADER;
 use warp::Filter;
 
 
@@ -45,6 +51,33 @@ struct Args {
         env = "NODE_CONFIG_PATH"
     )]
     node_config_path: PathBuf,
+
+    /// The number of days before certificate expiry at which we start warning.
+    #[clap(
+        long,
+        default_value = "30",
+        help = "The number of days before a certificate expires at which the service starts warning about it and reports a degraded health.",
+        env = "CERT_EXPIRY_WINDOW"
+    )]
+    cert_expiry_window: u64,
+
+    /// Where to keep archived datasets.
+    #[clap(
+        long,
+        help = "If given, keeps archives of datasets marked as immutable in this directory, such that they are not archived again for every \
+                download.",
+        env = "ARCHIVE_CACHE"
+    )]
+    archive_cache: Option<PathBuf>,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
 }
 
 
@@ -59,12 +92,8 @@ async fn main() {
     let args = Args::parse();
 
     // Setup the logger according to the debug flag
-    let mut logger = env_logger::builder();
-    logger.format_module_path(false);
-    if args.debug {
-        logger.filter_level(LevelFilter::Debug).init();
-    } else {
-        logger.filter_level(LevelFilter::Info).init();
+    if let Err(err) = logging::init("brane-reg", args.log_format, if args.debug { LevelFilter::Debug } else { LevelFilter::Info }) {
+        eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
     }
     info!("Initializing brane-reg v{}...", env!("CARGO_PKG_VERSION"));
 
@@ -77,6 +106,12 @@ async fn main() {
             std::process::exit(1);
         },
     };
+    if let Some(tracing) = &node_config.tracing {
+        tracing.init("brane-reg");
+    }
+    if let Some(passphrase) = &node_config.key_passphrase {
+        certs::set_key_passphrase(passphrase.clone());
+    }
     if !node_config.node.is_worker() {
         error!("Given NodeConfig file '{}' does not have properties for a worker node.", args.node_config_path.display());
         std::process::exit(1);
@@ -84,8 +119,33 @@ async fn main() {
 
 
 
+    // Start monitoring the certificates for expiry
+    let cert_monitor: ExpiryMonitor = ExpiryMonitor::spawn(
+        node_config.node.worker().paths.certs.clone(),
+        Duration::from_secs(args.cert_expiry_window * 24 * 3600),
+        Duration::from_secs(12 * 3600),
+    );
+
+    // Connect to the event bus, if any
+    let events: EventPublisher = match &node_config.events {
+        Some(events) => match events.publisher(format!("brane-reg@{}", node_config.node.worker().name)).await {
+            Ok(events) => events,
+            Err(err) => {
+                error!("{}", trace!(("Failed to connect to event bus"), err));
+                std::process::exit(1);
+            },
+        },
+        None => EventPublisher::disabled(),
+    };
+
     // Put the path in a context
-    let context: Arc<Context> = Arc::new(Context { node_config_path: args.node_config_path });
+    let context: Arc<Context> = Arc::new(Context {
+        node_config_path: args.node_config_path,
+        archive_cache: args.archive_cache,
+        cert_monitor,
+        verdicts: Arc::new(VerdictCache::new()),
+        events,
+    });
     let context = warp::any().map(move || context.clone());
 
 
@@ -105,15 +165,25 @@ async fn main() {
         .and(warp::path("download"))
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>(TRACEPARENT_HEADER))
+        .and(warp::header::optional::<String>("range"))
         .and(warp::body::json())
         .and(context.clone())
         .and_then(data::download_data);
+    let get_result = warp::get()
+        .and(warp::path("results"))
+        .and(warp::path("info"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(context.clone())
+        .and_then(data::get_result);
     let download_result = warp::get()
         .and(warp::ext::get::<Option<Certificate>>())
         .and(warp::path("results"))
         .and(warp::path("download"))
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>(TRACEPARENT_HEADER))
         .and(warp::body::json())
         .and(context.clone())
         .and_then(data::download_result);
@@ -139,15 +209,26 @@ async fn main() {
         .and(warp::path::end())
         .and(context.clone())
         .and_then(infra::get_capabilities);
+    let certs_ca = warp::get().and(warp::path("certs")).and(warp::path("ca")).and(warp::path::end()).and(context.clone()).and_then(enroll::get_ca);
+    let certs_enroll = warp::post()
+        .and(warp::path("certs"))
+        .and(warp::path("enroll"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(context.clone())
+        .and_then(enroll::enroll);
     let version = warp::path("version").and(warp::path::end()).and_then(version::get);
-    let health = warp::path("health").and(warp::path::end()).and_then(health::get);
+    let health = warp::path("health").and(warp::path::end()).and(context.clone()).and_then(health::get);
     let filter = list_assets
         .or(get_asset)
         .or(download_asset)
+        .or(get_result)
         .or(download_result)
         .or(check_data)
         .or(check_result)
         .or(infra_capabilities)
+        .or(certs_ca)
+        .or(certs_enroll)
         .or(version)
         .or(health);
 
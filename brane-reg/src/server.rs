@@ -4,7 +4,7 @@
 //  Created:
 //    01 Nov 2022, 11:15:17
 //  Last edited:
-//    16 Jan 2024, 17:29:27
+//    15 Oct 2026, 17:22:07
 //  Auto updated?
 //    Yes
 //
@@ -18,11 +18,11 @@
 //
 
 use std::net::SocketAddr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
-use brane_cfg::certs::{load_certstore, load_keypair};
+use brane_cfg::certs::{load_certstore, load_keypair, previous_ca_deadline};
 use error_trace::trace;
 use log::{debug, error, info, warn};
 use rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, ServerConfig, ServerConnection};
@@ -38,9 +38,63 @@ use warp::{Filter, Reply};
 pub use crate::errors::ServerError as Error;
 
 
+/***** HELPER FUNCTIONS *****/
+/// Loads the TLS configuration of the server from disk.
+///
+/// # Arguments
+/// - `server_cert`: Path to the server's certificate file.
+/// - `server_key`: Path to the server's keyfile.
+/// - `ca_cert`: Path to the file that contains the root certificate by which all clients must have been signed.
+///
+/// # Returns
+/// A new [`ServerConfig`], together with the moment at which it should be reloaded because the grace period of a previous CA ends (if any).
+///
+/// # Errors
+/// This function errors if we failed to load any of the files or build the config.
+fn load_tls_config(server_cert: &Path, server_key: &Path, ca_cert: &Path) -> Result<(Arc<ServerConfig>, Option<SystemTime>), Error> {
+    // Load server key pair
+    let (certs, key): (Certificate, PrivateKey) = match load_keypair(server_cert, server_key) {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(Error::KeypairLoadError { err });
+        },
+    };
+
+    // Load the client certs
+    let client_roots: RootCertStore = match load_certstore(ca_cert) {
+        Ok(res) => res,
+        Err(err) => {
+            return Err(Error::StoreLoadError { err });
+        },
+    };
+    // See if we have to drop a previous CA later on
+    let deadline: Option<SystemTime> = match previous_ca_deadline(ca_cert) {
+        Ok(deadline) => deadline.filter(|deadline| *deadline > SystemTime::now()),
+        Err(err) => {
+            return Err(Error::StoreLoadError { err });
+        },
+    };
+
+    // Finally, create the config itself
+    match ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(Arc::new(AllowAnyAnonymousOrAuthenticatedClient::new(client_roots)))
+        .with_single_cert(vec![certs], key)
+    {
+        Ok(config) => Ok((Arc::new(config), deadline)),
+        Err(err) => Err(Error::ServerConfigError { err }),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Function that serves a warp server, but now by providing additional information about the authenticated client.
 ///
+/// The server's certificates are reloaded from disk when the process receives `SIGHUP`, and when the grace period of a previous (rotated) CA ends. Connections that are already established are not affected by this.
+///
 /// # Arguments
 /// - `server_cert`: Path to the server's certificate file.
 /// - `server_key`: Path to the server's keyfile.
@@ -54,9 +108,9 @@ pub use crate::errors::ServerError as Error;
 /// # Errors
 /// This function errors if we failed to serve properly.
 pub async fn serve_with_auth<F, E>(
-    server_cert: impl AsRef<Path>,
-    server_key: impl AsRef<Path>,
-    ca_cert: impl AsRef<Path>,
+    server_cert: impl Into<PathBuf>,
+    server_key: impl Into<PathBuf>,
+    ca_cert: impl Into<PathBuf>,
     filter: F,
     address: SocketAddr,
 ) -> Result<(), Error>
@@ -64,37 +118,11 @@ where
     F: 'static + Send + Sync + Clone + Filter<Extract = E, Error = warp::Rejection>,
     E: Reply,
 {
+    let (server_cert, server_key, ca_cert): (PathBuf, PathBuf, PathBuf) = (server_cert.into(), server_key.into(), ca_cert.into());
+
     // Load the TLS config first
     debug!("Loading cryptography...");
-    let tls_config: Arc<ServerConfig> = {
-        // Load server key pair
-        let (certs, key): (Certificate, PrivateKey) = match load_keypair(server_cert, server_key) {
-            Ok(res) => res,
-            Err(err) => {
-                return Err(Error::KeypairLoadError { err });
-            },
-        };
-
-        // Load the client certs
-        let client_roots: RootCertStore = match load_certstore(ca_cert) {
-            Ok(res) => res,
-            Err(err) => {
-                return Err(Error::StoreLoadError { err });
-            },
-        };
-
-        // Finally, create the config itself
-        match ServerConfig::builder()
-            .with_safe_defaults()
-            .with_client_cert_verifier(Arc::new(AllowAnyAnonymousOrAuthenticatedClient::new(client_roots)))
-            .with_single_cert(vec![certs], key)
-        {
-            Ok(config) => Arc::new(config),
-            Err(err) => {
-                return Err(Error::ServerConfigError { err });
-            },
-        }
-    };
+    let (tls_config, mut grace_deadline): (Arc<ServerConfig>, Option<SystemTime>) = load_tls_config(&server_cert, &server_key, &ca_cert)?;
 
     // Start a TCP listener
     debug!("Starting TCP server on '{}'...", address);
@@ -106,7 +134,7 @@ where
     };
 
     // Start a TLS acceptor.
-    let acceptor: TlsAcceptor = TlsAcceptor::from(tls_config);
+    let mut acceptor: TlsAcceptor = TlsAcceptor::from(tls_config);
 
     // Register a SIGTERM handler to be Docker-friendly
     let mut handler: Signal = match signal(SignalKind::terminate()) {
@@ -119,6 +147,17 @@ where
             }
         },
     };
+    // Register a SIGHUP handler to reload certificates
+    let mut reload_handler: Signal = match signal(SignalKind::hangup()) {
+        Ok(handler) => handler,
+        Err(err) => {
+            error!("{}", trace!(("Failed to register SIGHUP signal handler"), err));
+            warn!("Service will NOT reload certificates on SIGHUP");
+            loop {
+                tokio::time::sleep(Duration::from_secs(24 * 3600)).await;
+            }
+        },
+    };
 
 
 
@@ -179,6 +218,33 @@ where
                 info!("Received SIGTERM, shutting down gracefully...");
                 break Ok(());
             },
+
+            reason = async {
+                match grace_deadline.and_then(|deadline| deadline.duration_since(SystemTime::now()).ok()) {
+                    Some(timeout) => tokio::select! {
+                        _ = tokio::time::sleep(timeout) => "grace period of previous CA ended",
+                        _ = reload_handler.recv() => "received SIGHUP",
+                    },
+                    None => {
+                        reload_handler.recv().await;
+                        "received SIGHUP"
+                    },
+                }
+            } => {
+                // Only swap the acceptor if the new config is valid; in-flight connections keep using the old one
+                info!("Reloading certificates ({reason})...");
+                match load_tls_config(&server_cert, &server_key, &ca_cert) {
+                    Ok((tls_config, deadline)) => {
+                        acceptor = TlsAcceptor::from(tls_config);
+                        grace_deadline = deadline;
+                        info!("Reloaded certificates");
+                    },
+                    Err(err) => {
+                        error!("{}", trace!(("Failed to reload certificates; keeping old ones"), err));
+                        grace_deadline = None;
+                    },
+                }
+            },
         }
     }
 }
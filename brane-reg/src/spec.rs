@@ -4,7 +4,7 @@
 //  Created:
 //    06 Nov 2022, 17:05:19
 //  Last edited:
-//    06 Dec 2022, 11:19:22
+//    15 Oct 2026, 22:50:46
 //  Auto updated?
 //    Yes
 //
@@ -13,6 +13,11 @@
 //
 
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use brane_cfg::certs::ExpiryMonitor;
+use brane_shr::events::EventPublisher;
+use brane_tsk::caches::VerdictCache;
 
 
 /***** LIBRARY *****/
@@ -21,4 +26,12 @@ use std::path::PathBuf;
 pub struct Context {
     /// The path to the node config file.
     pub node_config_path: PathBuf,
+    /// The directory in which to keep archives of immutable datasets, if any.
+    pub archive_cache:    Option<PathBuf>,
+    /// Keeps track of any of our certificates that are about to expire.
+    pub cert_monitor:     ExpiryMonitor,
+    /// Remembers the checker's verdicts on data accesses, such that they need not be reasoned about again.
+    pub verdicts:         Arc<VerdictCache>,
+    /// Publishes audit events about who accessed which data.
+    pub events:           EventPublisher,
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 15:12:59
 //  Last edited:
-//    06 Nov 2022, 18:09:52
+//    15 Oct 2026, 18:31:48
 //  Auto updated?
 //    Yes
 //
@@ -199,8 +199,9 @@ impl Store {
                 };
 
                 // Match on directory or not
+                // (Hidden directories are skipped, as these contain the deduplicated results that the non-hidden ones link to)
                 let entry_path: PathBuf = entry.path();
-                if entry_path.is_dir() {
+                if entry_path.is_dir() && !entry.file_name().to_string_lossy().starts_with('.') {
                     // The name of the result is the name of the folder
                     let name: String = entry.file_name().to_string_lossy().to_string();
                     // The path path is simply the directory
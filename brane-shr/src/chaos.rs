@@ -0,0 +1,308 @@
+//  CHAOS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:34:35
+//  Last edited:
+//    15 Oct 2026, 21:34:35
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a chaos mode for the services, which injects artificial
+//!   latency, dropped connections and task failures at configurable rates
+//!   such that retries, heartbeats and the like can be exercised in
+//!   tests.
+//!
+//!   Chaos mode is only available in debug builds. In release builds,
+//!   [`init()`] refuses to enable it.
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::{debug, warn};
+use rand::rngs::StdRng;
+use rand::{Rng as _, SeedableRng as _};
+
+
+/***** GLOBALS *****/
+/// The chaos that this service injects, if chaos mode is enabled.
+static CHAOS: OnceLock<Chaos> = OnceLock::new();
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that occur when parsing a [`ChaosConfig`].
+#[derive(Debug)]
+pub enum ChaosConfigParseError {
+    /// A setting was not given as `key=value`.
+    MissingValue { raw: String },
+    /// A setting was given that we don't know.
+    UnknownSetting { key: String },
+    /// A rate was not a number between 0 and 1.
+    IllegalRate { raw: String },
+    /// A duration was not a number of milliseconds or seconds.
+    IllegalDuration { raw: String },
+    /// A seed was not a number.
+    IllegalSeed { raw: String, err: std::num::ParseIntError },
+}
+impl Display for ChaosConfigParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ChaosConfigParseError::*;
+        match self {
+            MissingValue { raw } => write!(f, "Chaos setting '{raw}' is not given as 'key=value'"),
+            UnknownSetting { key } => write!(f, "Unknown chaos setting '{key}' (expected 'latency', 'drop', 'fail' or 'seed')"),
+            IllegalRate { raw } => write!(f, "Illegal rate '{raw}' (expected a number between 0 and 1)"),
+            IllegalDuration { raw } => write!(f, "Illegal duration '{raw}' (expected a number followed by 'ms' or 's')"),
+            IllegalSeed { raw, .. } => write!(f, "Illegal seed '{raw}'"),
+        }
+    }
+}
+impl Error for ChaosConfigParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ChaosConfigParseError::*;
+        match self {
+            MissingValue { .. } => None,
+            UnknownSetting { .. } => None,
+            IllegalRate { .. } => None,
+            IllegalDuration { .. } => None,
+            IllegalSeed { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses a rate.
+///
+/// # Arguments
+/// - `raw`: The string to parse.
+///
+/// # Returns
+/// The parsed rate, which is guaranteed to be between 0 and 1.
+///
+/// # Errors
+/// This function errors if the string was not a number between 0 and 1.
+fn parse_rate(raw: &str) -> Result<f64, ChaosConfigParseError> {
+    match f64::from_str(raw) {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(ChaosConfigParseError::IllegalRate { raw: raw.into() }),
+    }
+}
+
+/// Parses a duration.
+///
+/// # Arguments
+/// - `raw`: The string to parse, e.g., `250ms` or `2s`.
+///
+/// # Returns
+/// The parsed duration.
+///
+/// # Errors
+/// This function errors if the string was not a number followed by `ms` or `s`.
+fn parse_duration(raw: &str) -> Result<Duration, ChaosConfigParseError> {
+    let (value, unit): (&str, u64) = if let Some(value) = raw.strip_suffix("ms") {
+        (value, 1)
+    } else if let Some(value) = raw.strip_suffix('s') {
+        (value, 1000)
+    } else {
+        return Err(ChaosConfigParseError::IllegalDuration { raw: raw.into() });
+    };
+    match u64::from_str(value) {
+        Ok(value) => Ok(Duration::from_millis(value * unit)),
+        Err(_) => Err(ChaosConfigParseError::IllegalDuration { raw: raw.into() }),
+    }
+}
+
+/// Decides whether to inject a fault.
+///
+/// # Arguments
+/// - `rate`: A function that selects the rate at which to inject the fault from the [`ChaosConfig`].
+///
+/// # Returns
+/// True if chaos mode is enabled and the fault should be injected this time, or false otherwise.
+fn roll(rate: impl FnOnce(&ChaosConfig) -> f64) -> bool {
+    let chaos: &Chaos = match CHAOS.get() {
+        Some(chaos) => chaos,
+        None => return false,
+    };
+    let rate: f64 = rate(&chaos.config);
+    if rate <= 0.0 {
+        return false;
+    }
+    // NOTE: The lock is only poisoned if another thread panicked while rolling, which never happens
+    chaos.rng.lock().map(|mut rng| rng.gen_bool(rate)).unwrap_or(false)
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The state of chaos mode once enabled.
+struct Chaos {
+    /// The faults to inject.
+    config: ChaosConfig,
+    /// The random generator that decides when to inject them.
+    rng:    Mutex<StdRng>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the faults that a service injects in chaos mode.
+///
+/// It is parsed from a comma-separated list of settings, e.g., `latency=250ms@0.5,drop=0.1,fail=0.05,seed=42`:
+/// - `latency=<DURATION>[@<RATE>]`: Delays requests and connections by the given duration (`ms` or `s`), at the given rate (or always).
+/// - `drop=<RATE>`: Drops requests and connections at the given rate.
+/// - `fail=<RATE>`: Fails tasks at the given rate.
+/// - `seed=<N>`: Seeds the random generator that decides when to inject faults, such that runs are reproducible.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChaosConfig {
+    /// The latency to inject.
+    pub latency:      Duration,
+    /// The rate at which to inject the latency.
+    pub latency_rate: f64,
+    /// The rate at which to drop requests and connections.
+    pub drop_rate:    f64,
+    /// The rate at which to fail tasks.
+    pub fail_rate:    f64,
+    /// The seed for the random generator, if any.
+    pub seed:         Option<u64>,
+}
+impl Display for ChaosConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "latency={}ms@{},drop={},fail={}", self.latency.as_millis(), self.latency_rate, self.drop_rate, self.fail_rate)?;
+        if let Some(seed) = self.seed {
+            write!(f, ",seed={seed}")?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for ChaosConfig {
+    type Err = ChaosConfigParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config: Self = Self::default();
+        for setting in s.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value): (&str, &str) = match setting.split_once('=') {
+                Some(pair) => pair,
+                None => return Err(ChaosConfigParseError::MissingValue { raw: setting.into() }),
+            };
+            match key {
+                "latency" => {
+                    let (latency, rate): (&str, Option<&str>) = match value.split_once('@') {
+                        Some((latency, rate)) => (latency, Some(rate)),
+                        None => (value, None),
+                    };
+                    config.latency = parse_duration(latency)?;
+                    config.latency_rate = if let Some(rate) = rate { parse_rate(rate)? } else { 1.0 };
+                },
+                "drop" => config.drop_rate = parse_rate(value)?,
+                "fail" => config.fail_rate = parse_rate(value)?,
+                "seed" => match u64::from_str(value) {
+                    Ok(seed) => config.seed = Some(seed),
+                    Err(err) => return Err(ChaosConfigParseError::IllegalSeed { raw: value.into(), err }),
+                },
+                key => return Err(ChaosConfigParseError::UnknownSetting { key: key.into() }),
+            }
+        }
+        Ok(config)
+    }
+}
+
+
+
+/// Enables chaos mode for this service.
+///
+/// Only has an effect in debug builds, and only the first time it is called.
+///
+/// # Arguments
+/// - `service`: The name of the calling service, used in the log.
+/// - `config`: The [`ChaosConfig`] that determines which faults to inject.
+pub fn init(service: &str, config: ChaosConfig) {
+    if !cfg!(debug_assertions) {
+        warn!("Chaos mode is only available in debug builds; not injecting any faults in {service}");
+        return;
+    }
+    warn!("Enabling chaos mode in {service} ({config}); expect things to go wrong");
+    let rng: StdRng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    if CHAOS.set(Chaos { config, rng: Mutex::new(rng) }).is_err() {
+        warn!("Chaos mode was already enabled; ignoring new configuration");
+    }
+}
+
+/// Waits for the configured latency if chaos mode decides to inject it.
+///
+/// Returns immediately if chaos mode is disabled.
+pub async fn delay() {
+    if roll(|config| if config.latency.is_zero() { 0.0 } else { config.latency_rate }) {
+        let latency: Duration = CHAOS.get().map(|chaos| chaos.config.latency).unwrap_or_default();
+        debug!("Injecting {}ms of latency (chaos mode)", latency.as_millis());
+        tokio::time::sleep(latency).await;
+    }
+}
+
+/// Decides whether to drop the current request or connection.
+///
+/// # Returns
+/// True if chaos mode is enabled and decides to drop it, or false otherwise.
+#[inline]
+pub fn should_drop() -> bool { roll(|config| config.drop_rate) }
+
+/// Decides whether to fail the current task.
+///
+/// # Returns
+/// True if chaos mode is enabled and decides to fail it, or false otherwise.
+#[inline]
+pub fn should_fail() -> bool { roll(|config| config.fail_rate) }
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests parsing all settings.
+    #[test]
+    fn chaosconfig_parse_all() {
+        let config: ChaosConfig = "latency=250ms@0.5, drop=0.1,fail=0.05,seed=42".parse().unwrap();
+        assert_eq!(config, ChaosConfig { latency: Duration::from_millis(250), latency_rate: 0.5, drop_rate: 0.1, fail_rate: 0.05, seed: Some(42) });
+        assert_eq!(config.to_string().parse::<ChaosConfig>().unwrap(), config);
+    }
+
+    /// Tests that latency without a rate is always injected.
+    #[test]
+    fn chaosconfig_parse_latency_without_rate() {
+        let config: ChaosConfig = "latency=2s".parse().unwrap();
+        assert_eq!(config.latency, Duration::from_secs(2));
+        assert_eq!(config.latency_rate, 1.0);
+    }
+
+    /// Tests that illegal settings are rejected.
+    #[test]
+    fn chaosconfig_parse_illegal() {
+        assert!(matches!("drop".parse::<ChaosConfig>(), Err(ChaosConfigParseError::MissingValue { .. })));
+        assert!(matches!("explode=0.5".parse::<ChaosConfig>(), Err(ChaosConfigParseError::UnknownSetting { .. })));
+        assert!(matches!("fail=1.5".parse::<ChaosConfig>(), Err(ChaosConfigParseError::IllegalRate { .. })));
+        assert!(matches!("latency=250".parse::<ChaosConfig>(), Err(ChaosConfigParseError::IllegalDuration { .. })));
+        assert!(matches!("seed=abc".parse::<ChaosConfig>(), Err(ChaosConfigParseError::IllegalSeed { .. })));
+    }
+}
@@ -0,0 +1,225 @@
+//  EVENTS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:29:21
+//  Last edited:
+//    15 Oct 2026, 21:29:21
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements publishing [`AuditEvent`]s on an event bus (Kafka or
+//!   NATS).
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::time::Duration;
+
+use chrono::Utc;
+use log::{debug, warn};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use sha2::{Digest as _, Sha256};
+use specifications::events::{AuditEvent, EventKind, GENESIS_HASH};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+
+/***** CONSTANTS *****/
+/// The time we wait for a Kafka broker to acknowledge an event.
+const KAFKA_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to publishing events.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to create a producer for the given Kafka brokers.
+    KafkaProducerError { brokers: String, err: rdkafka::error::KafkaError },
+    /// Failed to connect to the given NATS server.
+    NatsConnectError { address: String, err: async_nats::ConnectError },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            KafkaProducerError { brokers, .. } => write!(f, "Failed to create producer for Kafka brokers '{brokers}'"),
+            NatsConnectError { address, .. } => write!(f, "Failed to connect to NATS server '{address}'"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use Error::*;
+        match self {
+            KafkaProducerError { err, .. } => Some(err),
+            NatsConnectError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the hash of an event.
+///
+/// # Arguments
+/// - `event`: The [`AuditEvent`] to compute the hash of. Its own `hash` is ignored.
+///
+/// # Returns
+/// The hex-encoded SHA-256 hash of the event's JSON serialization with an empty `hash`.
+pub fn hash_event(event: &AuditEvent) -> String {
+    let event: AuditEvent = AuditEvent { hash: String::new(), ..event.clone() };
+    // NOTE: Serializing an event never fails, as it consists only of strings, numbers and booleans
+    hex::encode(Sha256::digest(serde_json::to_vec(&event).unwrap_or_default()))
+}
+
+/// Publishes events received on a channel, chaining them together, until all senders have been dropped.
+///
+/// # Arguments
+/// - `source`: The name of the service that publishes the events.
+/// - `backend`: The [`Backend`] to publish the events on.
+/// - `topic`: The topic (Kafka) or subject (NATS) to publish the events on.
+/// - `rx`: The receiving end of the channel that events are sent on.
+async fn publish(source: String, backend: Backend, topic: String, mut rx: UnboundedReceiver<EventKind>) {
+    let mut seq: u64 = 0;
+    let mut prev_hash: String = GENESIS_HASH.into();
+    while let Some(kind) = rx.recv().await {
+        let mut event: AuditEvent = AuditEvent { source: source.clone(), seq, timestamp: Utc::now(), kind, prev_hash, hash: String::new() };
+        event.hash = hash_event(&event);
+        seq += 1;
+        prev_hash = event.hash.clone();
+
+        // Send it
+        debug!("Publishing event {} on '{}'...", event.seq, topic);
+        let payload: Vec<u8> = serde_json::to_vec(&event).unwrap_or_default();
+        match &backend {
+            Backend::Kafka(producer) => {
+                if let Err((err, _)) = producer.send(FutureRecord::to(&topic).key(&source).payload(&payload), KAFKA_TIMEOUT).await {
+                    warn!("Failed to publish event {} on Kafka topic '{}': {}", event.seq, topic, err);
+                }
+            },
+            Backend::Nats(client) => {
+                if let Err(err) = client.publish(topic.clone(), payload.into()).await {
+                    warn!("Failed to publish event {} on NATS subject '{}': {}", event.seq, topic, err);
+                }
+            },
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// The event bus that events are published on.
+enum Backend {
+    /// A Kafka cluster.
+    Kafka(FutureProducer),
+    /// A NATS server.
+    Nats(async_nats::Client),
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Publishes [`AuditEvent`]s about what a service does on an event bus.
+///
+/// Events are chained together and published in the background, in the order in which they were given. Cloning the publisher gives another
+/// handle to the same chain.
+#[derive(Clone, Debug, Default)]
+pub struct EventPublisher {
+    /// The channel to send events to the background publisher on, or [`None`] if we don't publish.
+    tx: Option<UnboundedSender<EventKind>>,
+}
+impl EventPublisher {
+    /// Constructor for an EventPublisher that doesn't publish anything, for services without an event bus.
+    ///
+    /// # Returns
+    /// A new EventPublisher that ignores all events.
+    #[inline]
+    pub fn disabled() -> Self { Self { tx: None } }
+
+    /// Constructor for an EventPublisher that publishes on a Kafka cluster.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// - `source`: The name of the publishing service (e.g., `brane-job@hospital_a`).
+    /// - `brokers`: The (comma-separated list of) Kafka brokers to publish to.
+    /// - `topic`: The topic to publish on.
+    ///
+    /// # Returns
+    /// A new EventPublisher.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create a producer for the brokers.
+    pub fn kafka(source: impl Into<String>, brokers: impl Into<String>, topic: impl Into<String>) -> Result<Self, Error> {
+        let brokers: String = brokers.into();
+        let producer: FutureProducer = match ClientConfig::new().set("bootstrap.servers", &brokers).set("enable.idempotence", "true").create() {
+            Ok(producer) => producer,
+            Err(err) => return Err(Error::KafkaProducerError { brokers, err }),
+        };
+        Ok(Self::spawn(source.into(), Backend::Kafka(producer), topic.into()))
+    }
+
+    /// Constructor for an EventPublisher that publishes on a NATS server.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// - `source`: The name of the publishing service (e.g., `brane-job@hospital_a`).
+    /// - `address`: The address of the NATS server (e.g., `nats://nats:4222`).
+    /// - `subject`: The subject to publish on.
+    ///
+    /// # Returns
+    /// A new EventPublisher.
+    ///
+    /// # Errors
+    /// This function errors if we failed to connect to the server.
+    pub async fn nats(source: impl Into<String>, address: impl Into<String>, subject: impl Into<String>) -> Result<Self, Error> {
+        let address: String = address.into();
+        let client: async_nats::Client = match async_nats::connect(&address).await {
+            Ok(client) => client,
+            Err(err) => return Err(Error::NatsConnectError { address, err }),
+        };
+        Ok(Self::spawn(source.into(), Backend::Nats(client), subject.into()))
+    }
+
+    /// Spawns the background publisher for a backend.
+    ///
+    /// # Arguments
+    /// - `source`: The name of the publishing service.
+    /// - `backend`: The [`Backend`] to publish on.
+    /// - `topic`: The topic or subject to publish on.
+    ///
+    /// # Returns
+    /// A new EventPublisher that sends its events to the spawned publisher.
+    fn spawn(source: String, backend: Backend, topic: String) -> Self {
+        let (tx, rx): (UnboundedSender<EventKind>, UnboundedReceiver<EventKind>) = mpsc::unbounded_channel();
+        tokio::spawn(publish(source, backend, topic, rx));
+        Self { tx: Some(tx) }
+    }
+
+    /// Publishes an event.
+    ///
+    /// This doesn't wait until the event has been published; failures to publish it are logged instead.
+    ///
+    /// # Arguments
+    /// - `kind`: The [`EventKind`] that describes what happened.
+    #[inline]
+    pub fn publish(&self, kind: EventKind) {
+        if let Some(tx) = &self.tx {
+            // NOTE: Only fails if the publisher is gone, which only happens when the runtime shuts down
+            let _ = tx.send(kind);
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    30 Sep 2022, 16:21:24
 //  Last edited:
-//    01 May 2024, 10:44:25
+//    15 Oct 2026, 23:26:52
 //  Auto updated?
 //    Yes
 //
@@ -16,10 +16,16 @@
 //
 
 // Declare some modules
+pub mod chaos;
 pub mod errors;
+pub mod events;
 pub mod formatters;
 pub mod fs;
 pub mod input;
 pub mod jobs;
+pub mod logging;
+pub mod oci;
 // pub mod kafka;
+pub mod remote;
+pub mod suggest;
 pub mod utilities;
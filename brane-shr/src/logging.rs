@@ -0,0 +1,145 @@
+//  LOGGING.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:26:20
+//  Last edited:
+//    15 Oct 2026, 21:26:20
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements the logger setup shared by the Brane services, which can
+//!   log either human-readable text or one JSON object per line (for
+//!   ingestion by e.g. Loki or ELK).
+//
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::io::Write as _;
+use std::str::FromStr;
+
+use log::kv::{Key, Value as KvValue, VisitSource};
+use log::{LevelFilter, SetLoggerError};
+use serde_json::{json, Map, Value};
+use specifications::telemetry;
+
+
+/***** ERRORS *****/
+/// Defines errors that occur when parsing a [`LogFormat`].
+#[derive(Debug)]
+pub enum LogFormatParseError {
+    /// The given format is not one we know.
+    UnknownFormat { raw: String },
+}
+impl Display for LogFormatParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use LogFormatParseError::*;
+        match self {
+            UnknownFormat { raw } => write!(f, "Unknown log format '{raw}' (expected 'text' or 'json')"),
+        }
+    }
+}
+impl Error for LogFormatParseError {}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Collects the key/value pairs of a log record as JSON fields.
+struct FieldCollector(BTreeMap<String, Value>);
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: KvValue<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), Value::String(value.to_string()));
+        Ok(())
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the formats in which the services can write their logs.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LogFormat {
+    /// Human-readable lines of text.
+    #[default]
+    Text,
+    /// One JSON object per line, with the service, level, trace ID (if any), message and any additional fields.
+    Json,
+}
+impl Display for LogFormat {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+impl FromStr for LogFormat {
+    type Err = LogFormatParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(LogFormatParseError::UnknownFormat { raw: s.into() }),
+        }
+    }
+}
+
+
+
+/// Initializes the logger of a service.
+///
+/// In [`LogFormat::Json`], every line is a JSON object with the `timestamp`, `service`, `level`, `target` (module) and `message` of the log
+/// record. If the record is logged as part of a trace (either because it has a `trace_id` field or because it's logged within
+/// [`telemetry::in_trace()`]), the trace's ID is added as `trace_id`. Any other key/value pairs of the record are added in `fields`.
+///
+/// # Arguments
+/// - `service`: The name of the service that logs (e.g., `brane-drv`).
+/// - `format`: The [`LogFormat`] to log in.
+/// - `level`: The maximum level of the records to log.
+///
+/// # Errors
+/// This function errors if a logger has already been initialized.
+pub fn init(service: &'static str, format: LogFormat, level: LevelFilter) -> Result<(), SetLoggerError> {
+    let mut logger = env_logger::builder();
+    logger.filter_level(level);
+    match format {
+        LogFormat::Text => {
+            logger.format_module_path(false);
+        },
+        LogFormat::Json => {
+            logger.format(move |buf, record| {
+                // Collect any fields, taking the trace ID out if there is any
+                let mut fields: FieldCollector = FieldCollector(BTreeMap::new());
+                // NOTE: Our collector never fails
+                let _ = record.key_values().visit(&mut fields);
+                let trace_id: Option<Value> =
+                    fields.0.remove("trace_id").or_else(|| telemetry::current().map(|trace| json!(format!("{:032x}", trace.trace_id))));
+
+                // Write the line
+                let mut line: Map<String, Value> = Map::new();
+                line.insert("timestamp".into(), json!(buf.timestamp_millis().to_string()));
+                line.insert("service".into(), json!(service));
+                line.insert("level".into(), json!(record.level().as_str()));
+                line.insert("target".into(), json!(record.target()));
+                line.insert("message".into(), json!(record.args().to_string()));
+                if let Some(trace_id) = trace_id {
+                    line.insert("trace_id".into(), trace_id);
+                }
+                if !fields.0.is_empty() {
+                    line.insert("fields".into(), Value::Object(fields.0.into_iter().collect()));
+                }
+                writeln!(buf, "{}", Value::Object(line))
+            });
+        },
+    }
+    logger.try_init()
+}
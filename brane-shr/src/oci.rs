@@ -0,0 +1,1214 @@
+//  OCI.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 23:26:52
+//  Last edited:
+//    15 Oct 2026, 23:26:52
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a minimal client for the OCI distribution API, such that
+//!   packages can be pushed to and pulled from any OCI-compliant registry
+//!   (Docker Hub, Harbor, ECR, ...).
+//!
+//!   Packages are stored as ordinary OCI images, where the image manifest
+//!   carries the package's [`PackageInfo`] as an annotation. That means
+//!   that registry tooling (scanners, replication, retention policies)
+//!   works on them as on any other image.
+//
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use base64::Engine as _;
+use futures_util::StreamExt as _;
+use log::{debug, info};
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_LENGTH, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use reqwest::{Body, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use specifications::package::PackageInfo;
+use tempfile::TempDir;
+use tokio::fs as tfs;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+
+/***** CONSTANTS *****/
+/// The registry that references without an explicit registry resolve to.
+pub const DEFAULT_REGISTRY: &str = "registry-1.docker.io";
+
+/// The annotation on an image manifest that carries the serialized [`PackageInfo`].
+pub const PACKAGE_INFO_ANNOTATION: &str = "eu.epi-project.brane.package-info";
+
+/// The media type of OCI image manifests.
+const OCI_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+/// The media type of OCI image indices.
+const OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+/// The media type of Docker image manifests.
+const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+/// The media type of Docker manifest lists.
+const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+/// The media type of OCI image configs.
+const OCI_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+/// The media type of uncompressed OCI layers.
+const OCI_LAYER: &str = "application/vnd.oci.image.layer.v1.tar";
+/// The media type of gzip-compressed OCI layers.
+const OCI_LAYER_GZIP: &str = "application/vnd.oci.image.layer.v1.tar+gzip";
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to talking to OCI registries.
+#[derive(Debug)]
+pub enum Error {
+    /// The given string is not a valid image reference.
+    IllegalReference { raw: String, reason: &'static str },
+
+    /// Failed to read the Docker config file with registry credentials.
+    CredentialsReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the Docker config file with registry credentials.
+    CredentialsParseError { path: PathBuf, err: serde_json::Error },
+    /// An entry in the Docker config file has an `auth` field that is not base64-encoded `user:password`.
+    CredentialsIllegalAuth { path: PathBuf, registry: String },
+
+    /// Failed to build a request to a registry.
+    RequestBuildError { err: reqwest::Error },
+    /// Failed to send a request to a registry.
+    RequestError { address: String, err: reqwest::Error },
+    /// A registry answered a request with a non-success status code.
+    RequestFailure { address: String, status: StatusCode, message: Option<String> },
+    /// Failed to read the body of a response.
+    ResponseReadError { address: String, err: reqwest::Error },
+    /// A registry wants us to authenticate, but its challenge is not one we understand.
+    IllegalChallenge { registry: String, challenge: String },
+    /// A registry wants us to authenticate with a username and password, but we have none.
+    MissingCredentials { registry: String },
+    /// The token service of a registry returned something we did not understand.
+    TokenParseError { address: String, err: serde_json::Error },
+    /// A registry did not tell us where to upload a blob to.
+    MissingLocation { address: String },
+
+    /// Failed to create a temporary directory.
+    TempDirError { err: std::io::Error },
+    /// Failed to open the given image archive.
+    ArchiveOpenError { path: PathBuf, err: std::io::Error },
+    /// Failed to extract the given image archive.
+    ArchiveExtractError { path: PathBuf, err: std::io::Error },
+    /// Failed to parse the `manifest.json` in an image archive.
+    ArchiveManifestError { path: PathBuf, err: serde_json::Error },
+    /// The `manifest.json` in an image archive did not describe exactly one image.
+    ArchiveIllegalManifestNum { path: PathBuf, got: usize },
+    /// Failed to create the image archive to pull to.
+    ArchiveCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to write the image archive to pull to.
+    ArchiveWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a file.
+    FileReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to write a file.
+    FileWriteError { path: PathBuf, err: std::io::Error },
+
+    /// Failed to serialize a manifest or the package info.
+    SerializeError { what: &'static str, err: serde_json::Error },
+    /// Failed to parse a manifest.
+    ManifestParseError { reference: String, err: serde_json::Error },
+    /// A manifest has a media type we do not support.
+    UnsupportedMediaType { reference: String, media_type: String },
+    /// An index has no image for the requested platform.
+    NoMatchingPlatform { reference: String, arch: String },
+    /// The image has no package info annotation, i.e., it is not a Brane package.
+    NotAPackage { reference: String },
+    /// Failed to parse the package info annotation.
+    PackageInfoParseError { reference: String, err: serde_json::Error },
+    /// Something we downloaded did not have the digest we expected.
+    DigestMismatch { what: String, expected: String, got: String },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            IllegalReference { raw, reason } => write!(f, "Illegal image reference '{raw}': {reason}"),
+
+            CredentialsReadError { path, err } => write!(f, "Failed to read registry credentials file '{}': {}", path.display(), err),
+            CredentialsParseError { path, err } => write!(f, "Failed to parse registry credentials file '{}': {}", path.display(), err),
+            CredentialsIllegalAuth { path, registry } => {
+                write!(f, "Credentials for registry '{}' in '{}' are not base64-encoded 'user:password'", registry, path.display())
+            },
+
+            RequestBuildError { err } => write!(f, "Failed to build registry request: {err}"),
+            RequestError { address, err } => write!(f, "Failed to send request to '{address}': {err}"),
+            RequestFailure { address, status, message } => write!(
+                f,
+                "Request to '{}' failed with status {} ({}){}",
+                address,
+                status.as_u16(),
+                status.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
+            ResponseReadError { address, err } => write!(f, "Failed to read response from '{address}': {err}"),
+            IllegalChallenge { registry, challenge } => write!(f, "Registry '{registry}' sent an unsupported authentication challenge '{challenge}'"),
+            MissingCredentials { registry } => {
+                write!(f, "Registry '{registry}' requires credentials, but none were found (did you run 'docker login {registry}'?)")
+            },
+            TokenParseError { address, err } => write!(f, "Failed to parse token from '{address}': {err}"),
+            MissingLocation { address } => write!(f, "Response from '{address}' has no Location header to upload to"),
+
+            TempDirError { err } => write!(f, "Failed to create temporary directory: {err}"),
+            ArchiveOpenError { path, err } => write!(f, "Failed to open image archive '{}': {}", path.display(), err),
+            ArchiveExtractError { path, err } => write!(f, "Failed to extract image archive '{}': {}", path.display(), err),
+            ArchiveManifestError { path, err } => write!(f, "Failed to parse 'manifest.json' in image archive '{}': {}", path.display(), err),
+            ArchiveIllegalManifestNum { path, got } => {
+                write!(f, "Image archive '{}' contains {} images, but expected exactly 1", path.display(), got)
+            },
+            ArchiveCreateError { path, err } => write!(f, "Failed to create image archive '{}': {}", path.display(), err),
+            ArchiveWriteError { path, err } => write!(f, "Failed to write image archive '{}': {}", path.display(), err),
+            FileReadError { path, err } => write!(f, "Failed to read file '{}': {}", path.display(), err),
+            FileWriteError { path, err } => write!(f, "Failed to write file '{}': {}", path.display(), err),
+
+            SerializeError { what, err } => write!(f, "Failed to serialize {what}: {err}"),
+            ManifestParseError { reference, err } => write!(f, "Failed to parse manifest of '{reference}': {err}"),
+            UnsupportedMediaType { reference, media_type } => write!(f, "Manifest of '{reference}' has unsupported media type '{media_type}'"),
+            NoMatchingPlatform { reference, arch } => write!(f, "Image '{reference}' has no variant for platform 'linux/{arch}'"),
+            NotAPackage { reference } => write!(f, "Image '{reference}' is not a Brane package (it has no '{PACKAGE_INFO_ANNOTATION}' annotation)"),
+            PackageInfoParseError { reference, err } => write!(f, "Failed to parse package info of '{reference}': {err}"),
+            DigestMismatch { what, expected, got } => write!(f, "Digest of {what} does not match (expected '{expected}', got '{got}')"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use Error::*;
+        match self {
+            CredentialsReadError { err, .. } => Some(err),
+            CredentialsParseError { err, .. } => Some(err),
+            RequestBuildError { err } => Some(err),
+            RequestError { err, .. } => Some(err),
+            ResponseReadError { err, .. } => Some(err),
+            TokenParseError { err, .. } => Some(err),
+            TempDirError { err } => Some(err),
+            ArchiveOpenError { err, .. } => Some(err),
+            ArchiveExtractError { err, .. } => Some(err),
+            ArchiveManifestError { err, .. } => Some(err),
+            ArchiveCreateError { err, .. } => Some(err),
+            ArchiveWriteError { err, .. } => Some(err),
+            FileReadError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+            SerializeError { err, .. } => Some(err),
+            ManifestParseError { err, .. } => Some(err),
+            PackageInfoParseError { err, .. } => Some(err),
+
+            IllegalReference { .. }
+            | CredentialsIllegalAuth { .. }
+            | RequestFailure { .. }
+            | IllegalChallenge { .. }
+            | MissingCredentials { .. }
+            | MissingLocation { .. }
+            | ArchiveIllegalManifestNum { .. }
+            | UnsupportedMediaType { .. }
+            | NoMatchingPlatform { .. }
+            | NotAPackage { .. }
+            | DigestMismatch { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the name of the host's architecture as used in OCI platforms.
+#[inline]
+pub fn host_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        arch => arch,
+    }
+}
+
+/// Checks whether the given string is a `sha256:<hex>` digest.
+fn is_digest(raw: &str) -> bool {
+    match raw.strip_prefix("sha256:") {
+        Some(hex) => hex.len() == 64 && hex.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+        None => false,
+    }
+}
+
+/// Computes the `sha256:<hex>` digest of the given bytes.
+#[inline]
+fn digest_of(raw: &[u8]) -> String { format!("sha256:{}", hex::encode(Sha256::digest(raw))) }
+
+/// Computes the `sha256:<hex>` digest and size of the given file.
+///
+/// # Arguments
+/// - `path`: The path of the file to hash.
+///
+/// # Returns
+/// The digest and size of the file, and whether it starts with the gzip magic bytes, in that order.
+///
+/// # Errors
+/// This function errors if we failed to read the file.
+async fn digest_file(path: &Path) -> Result<(String, u64, bool), Error> {
+    let mut handle: tfs::File = match tfs::File::open(path).await {
+        Ok(handle) => handle,
+        Err(err) => return Err(Error::FileReadError { path: path.into(), err }),
+    };
+    let mut hasher: Sha256 = Sha256::new();
+    let mut size: u64 = 0;
+    let mut gzip: bool = false;
+    let mut buf: [u8; 65536] = [0; 65536];
+    loop {
+        let n: usize = match handle.read(&mut buf).await {
+            Ok(n) => n,
+            Err(err) => return Err(Error::FileReadError { path: path.into(), err }),
+        };
+        if n == 0 {
+            break;
+        }
+        if size == 0 && n >= 2 {
+            gzip = buf[0] == 0x1F && buf[1] == 0x8B;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((format!("sha256:{}", hex::encode(hasher.finalize())), size, gzip))
+}
+
+/// Parses the parameters of a `WWW-Authenticate` challenge.
+///
+/// # Arguments
+/// - `challenge`: The value of the header.
+///
+/// # Returns
+/// The (lowercase) scheme of the challenge and its parameters, or [`None`] if the challenge is empty.
+fn parse_challenge(challenge: &str) -> Option<(String, HashMap<String, String>)> {
+    let challenge: &str = challenge.trim();
+    let (scheme, mut rest): (&str, &str) = match challenge.split_once(' ') {
+        Some((scheme, rest)) => (scheme, rest.trim()),
+        None if !challenge.is_empty() => (challenge, ""),
+        None => return None,
+    };
+
+    // Parse the comma-separated key=value pairs, where values may be quoted
+    let mut params: HashMap<String, String> = HashMap::new();
+    while !rest.is_empty() {
+        let (key, after): (&str, &str) = match rest.split_once('=') {
+            Some((key, after)) => (key.trim(), after.trim_start()),
+            None => break,
+        };
+        let (value, after): (String, &str) = if let Some(quoted) = after.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (quoted[..end].into(), &quoted[end + 1..]),
+                None => (quoted.into(), ""),
+            }
+        } else {
+            match after.find(',') {
+                Some(end) => (after[..end].trim().into(), &after[end..]),
+                None => (after.trim().into(), ""),
+            }
+        };
+        params.insert(key.to_lowercase(), value);
+        rest = after.trim_start().trim_start_matches(',').trim_start();
+    }
+    Some((scheme.to_lowercase(), params))
+}
+
+/// Checks if the given response is successful, turning it into an error otherwise.
+///
+/// # Arguments
+/// - `address`: The address the request was sent to, for debugging.
+/// - `res`: The response to check.
+///
+/// # Returns
+/// The same response if it was successful.
+///
+/// # Errors
+/// This function errors with [`Error::RequestFailure`] if the response has a non-success status code.
+async fn check(address: &str, res: Response) -> Result<Response, Error> {
+    if res.status().is_success() {
+        Ok(res)
+    } else {
+        Err(Error::RequestFailure { address: address.into(), status: res.status(), message: res.text().await.ok().filter(|m| !m.is_empty()) })
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A reference to an image in an OCI registry, e.g., `harbor.example.com/brane/hello-world:1.0.0`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Reference {
+    /// The registry that hosts the image, as `host[:port]`.
+    pub registry:   String,
+    /// The repository of the image within the registry.
+    pub repository: String,
+    /// The tag of the image, if any.
+    pub tag:        Option<String>,
+    /// The digest of the image, if any. Takes precedence over the tag when resolving.
+    pub digest:     Option<String>,
+}
+
+impl Reference {
+    /// Returns a copy of this reference that is pinned to the given digest.
+    ///
+    /// # Arguments
+    /// - `digest`: The `sha256:<hex>` digest to pin to.
+    ///
+    /// # Returns
+    /// A new Reference with the same registry, repository and tag but the given digest.
+    #[inline]
+    pub fn pinned(&self, digest: impl Into<String>) -> Self { Self { digest: Some(digest.into()), ..self.clone() } }
+
+    /// Returns the tag or digest with which to ask for this image's manifest.
+    #[inline]
+    pub fn selector(&self) -> &str { self.digest.as_deref().or(self.tag.as_deref()).unwrap_or("latest") }
+
+    /// Returns the base address of the repository's API, e.g., `https://harbor.example.com/v2/brane/hello-world`.
+    ///
+    /// Registries on `localhost` are assumed to serve plain HTTP, as Docker does.
+    pub fn base(&self) -> String {
+        let host: &str = self.registry.split(':').next().unwrap_or(&self.registry);
+        let scheme: &str = if host == "localhost" || host == "127.0.0.1" { "http" } else { "https" };
+        format!("{}://{}/v2/{}", scheme, self.registry, self.repository)
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}/{}", self.registry, self.repository)?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{tag}")?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Reference {
+    type Err = Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let illegal = |reason: &'static str| Error::IllegalReference { raw: raw.into(), reason };
+
+        // Split off the digest, then the tag (which is only a tag if the colon comes after the last slash)
+        let (rest, digest): (&str, Option<String>) = match raw.split_once('@') {
+            Some((rest, digest)) => {
+                if !is_digest(digest) {
+                    return Err(illegal("digest is not of the form 'sha256:<64 hex characters>'"));
+                }
+                (rest, Some(digest.into()))
+            },
+            None => (raw, None),
+        };
+        let (rest, tag): (&str, Option<String>) = match rest.rfind(':') {
+            Some(pos) if pos > rest.rfind('/').unwrap_or(0) => {
+                let tag: &str = &rest[pos + 1..];
+                if tag.is_empty() || tag.len() > 128 || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-') {
+                    return Err(illegal("tag must be 1-128 alphanumeric characters, '_', '.' or '-'"));
+                }
+                (&rest[..pos], Some(tag.into()))
+            },
+            _ => (rest, None),
+        };
+
+        // The first component is a registry only if it looks like a hostname; otherwise, it's Docker Hub
+        let (registry, repository): (String, String) = match rest.split_once('/') {
+            Some((first, path)) if first.contains('.') || first.contains(':') || first == "localhost" => (first.into(), path.into()),
+            Some(_) => (DEFAULT_REGISTRY.into(), rest.into()),
+            None => (DEFAULT_REGISTRY.into(), format!("library/{rest}")),
+        };
+        if repository.is_empty()
+            || repository.split('/').any(|c| c.is_empty())
+            || !repository.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '/' || c == '.' || c == '_' || c == '-')
+        {
+            return Err(illegal("repository must be non-empty and consist of lowercase alphanumeric characters, '.', '_', '-' and '/'"));
+        }
+
+        Ok(Self { registry, repository, tag, digest })
+    }
+}
+
+
+
+/// The credentials to log in to registries with, as stored by `docker login`.
+#[derive(Clone, Debug, Default)]
+pub struct Credentials {
+    /// Maps (normalized) registry hostnames to usernames and passwords.
+    auths: HashMap<String, (String, String)>,
+}
+
+impl Credentials {
+    /// Normalizes a registry as written in a Docker config to a hostname.
+    fn normalize(registry: &str) -> String {
+        let registry: &str = registry.trim_start_matches("https://").trim_start_matches("http://");
+        let host: &str = registry.split('/').next().unwrap_or(registry);
+        match host {
+            "docker.io" | "registry-1.docker.io" => "index.docker.io".into(),
+            host => host.into(),
+        }
+    }
+
+    /// Loads the credentials from the Docker config file.
+    ///
+    /// The file is found at `$DOCKER_CONFIG/config.json`, or `~/.docker/config.json` if that variable is not set. Only credentials stored
+    /// inline (i.e., not in a credential helper) are used.
+    ///
+    /// # Returns
+    /// The credentials found, which are empty if there is no config file.
+    ///
+    /// # Errors
+    /// This function errors if the config file exists but could not be read or parsed.
+    pub fn from_docker_config() -> Result<Self, Error> {
+        let path: PathBuf = match std::env::var_os("DOCKER_CONFIG") {
+            Some(dir) => PathBuf::from(dir).join("config.json"),
+            None => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(".docker").join("config.json"),
+                None => return Ok(Self::default()),
+            },
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::from_path(path)
+    }
+
+    /// Loads the credentials from the given Docker config file.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the config file.
+    ///
+    /// # Returns
+    /// The credentials found in the file.
+    ///
+    /// # Errors
+    /// This function errors if the config file could not be read or parsed.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        /// The part of the Docker config file that we care about.
+        #[derive(Deserialize)]
+        struct DockerConfig {
+            #[serde(default)]
+            auths: HashMap<String, DockerAuth>,
+        }
+        /// A single entry in the `auths` of the Docker config file.
+        #[derive(Deserialize)]
+        struct DockerAuth {
+            #[serde(default)]
+            auth: Option<String>,
+        }
+
+        let path: &Path = path.as_ref();
+        let raw: String = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) => return Err(Error::CredentialsReadError { path: path.into(), err }),
+        };
+        let config: DockerConfig = match serde_json::from_str(&raw) {
+            Ok(config) => config,
+            Err(err) => return Err(Error::CredentialsParseError { path: path.into(), err }),
+        };
+
+        let mut auths: HashMap<String, (String, String)> = HashMap::with_capacity(config.auths.len());
+        for (registry, auth) in config.auths {
+            let auth: String = match auth.auth {
+                Some(auth) if !auth.is_empty() => auth,
+                _ => continue,
+            };
+            let decoded: Option<String> =
+                base64::engine::general_purpose::STANDARD.decode(auth.trim()).ok().and_then(|raw| String::from_utf8(raw).ok());
+            let decoded: String = match decoded {
+                Some(decoded) => decoded,
+                None => return Err(Error::CredentialsIllegalAuth { path: path.into(), registry }),
+            };
+            match decoded.split_once(':') {
+                Some((user, pass)) => {
+                    auths.insert(Self::normalize(&registry), (user.into(), pass.into()));
+                },
+                None => return Err(Error::CredentialsIllegalAuth { path: path.into(), registry }),
+            }
+        }
+        Ok(Self { auths })
+    }
+
+    /// Adds credentials for a registry, overriding any found in the config file.
+    ///
+    /// # Arguments
+    /// - `registry`: The registry to add credentials for.
+    /// - `user`: The username to log in with.
+    /// - `pass`: The password (or token) to log in with.
+    #[inline]
+    pub fn insert(&mut self, registry: &str, user: impl Into<String>, pass: impl Into<String>) {
+        self.auths.insert(Self::normalize(registry), (user.into(), pass.into()));
+    }
+
+    /// Returns the username and password for the given registry, if any.
+    #[inline]
+    pub fn get(&self, registry: &str) -> Option<&(String, String)> { self.auths.get(&Self::normalize(registry)) }
+}
+
+
+
+/// How we authenticate to a registry for a particular scope.
+#[derive(Clone, Debug)]
+enum Auth {
+    /// Send a bearer token obtained from the registry's token service.
+    Bearer(String),
+    /// Send the username and password directly.
+    Basic(String, String),
+}
+
+
+
+/// A descriptor of a blob or manifest in a registry.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    /// The media type of the described content.
+    media_type:  String,
+    /// The digest of the described content.
+    digest:      String,
+    /// The size of the described content, in bytes.
+    size:        u64,
+    /// The platform of the described image, in indices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    platform:    Option<Platform>,
+    /// Any annotations of the described content.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    annotations: HashMap<String, String>,
+}
+
+/// The platform of an image in an index.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Platform {
+    /// The CPU architecture, e.g., `amd64`.
+    architecture: String,
+    /// The operating system, e.g., `linux`.
+    os:           String,
+}
+
+/// An image manifest or index. Which of the two it is follows from whether `manifests` is populated.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// The version of the manifest schema (always 2).
+    schema_version: u32,
+    /// The media type of the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    media_type:     Option<String>,
+    /// The image config, for image manifests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    config:         Option<Descriptor>,
+    /// The layers of the image, for image manifests.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    layers:         Vec<Descriptor>,
+    /// The images in the index, for indices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    manifests:      Vec<Descriptor>,
+    /// Any annotations of the manifest.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    annotations:    HashMap<String, String>,
+}
+
+/// An entry in the `manifest.json` of a `docker save`/`docker load` archive.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct ArchiveManifest {
+    /// The path of the image config in the archive.
+    config:    String,
+    /// The tags to give the image when loaded.
+    #[serde(default)]
+    repo_tags: Option<Vec<String>>,
+    /// The paths of the layers in the archive.
+    layers:    Vec<String>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A client for pushing and pulling Brane packages to and from OCI registries.
+#[derive(Debug)]
+pub struct Client {
+    /// The HTTP client to send requests with.
+    http:   reqwest::Client,
+    /// The credentials to log in to registries with.
+    creds:  Credentials,
+    /// How to authenticate per `registry|scope`, as learned from earlier challenges.
+    tokens: Mutex<HashMap<String, Auth>>,
+}
+
+impl Client {
+    /// Constructor for the Client.
+    ///
+    /// # Arguments
+    /// - `creds`: The credentials to log in to registries with.
+    ///
+    /// # Returns
+    /// A new Client.
+    #[inline]
+    pub fn new(creds: Credentials) -> Self { Self { http: reqwest::Client::new(), creds, tokens: Mutex::new(HashMap::new()) } }
+
+    /// Sends a request to a registry, authenticating when the registry asks us to.
+    ///
+    /// # Arguments
+    /// - `reference`: The image the request is about, which determines the authentication scope.
+    /// - `push`: Whether the request needs push rights.
+    /// - `request`: A closure that builds the request. It is called again if we have to authenticate and retry.
+    ///
+    /// # Returns
+    /// The address the request was sent to and the registry's response, which may still have a non-success status code.
+    ///
+    /// # Errors
+    /// This function errors if we failed to send the request or to authenticate.
+    async fn send(
+        &self,
+        reference: &Reference,
+        push: bool,
+        request: impl Fn(&reqwest::Client) -> Result<RequestBuilder, Error>,
+    ) -> Result<(String, Response), Error> {
+        let scope: String = format!("repository:{}:{}", reference.repository, if push { "pull,push" } else { "pull" });
+        let key: String = format!("{}|{}", reference.registry, scope);
+        let mut authenticated: bool = false;
+        loop {
+            // Build the request with whatever authentication we have for this scope
+            let mut builder: RequestBuilder = request(&self.http)?;
+            let auth: Option<Auth> = self.tokens.lock().unwrap().get(&key).cloned();
+            match auth {
+                Some(Auth::Bearer(token)) => builder = builder.bearer_auth(token),
+                Some(Auth::Basic(user, pass)) => builder = builder.basic_auth(user, Some(pass)),
+                None => {},
+            }
+            let req: reqwest::Request = match builder.build() {
+                Ok(req) => req,
+                Err(err) => return Err(Error::RequestBuildError { err }),
+            };
+            let address: String = req.url().to_string();
+            debug!("Sending {} request to '{}'...", req.method(), address);
+            let res: Response = match self.http.execute(req).await {
+                Ok(res) => res,
+                Err(err) => return Err(Error::RequestError { address, err }),
+            };
+            if res.status() != StatusCode::UNAUTHORIZED || authenticated {
+                return Ok((address, res));
+            }
+
+            // Answer the challenge, then try again
+            let challenge: String = res.headers().get(WWW_AUTHENTICATE).and_then(|v| v.to_str().ok()).unwrap_or("").into();
+            let auth: Auth = self.authenticate(&reference.registry, &scope, &challenge).await?;
+            self.tokens.lock().unwrap().insert(key.clone(), auth);
+            authenticated = true;
+        }
+    }
+
+    /// Answers an authentication challenge from a registry.
+    ///
+    /// # Arguments
+    /// - `registry`: The registry that sent the challenge.
+    /// - `scope`: The scope we need access to.
+    /// - `challenge`: The value of the `WWW-Authenticate` header.
+    ///
+    /// # Returns
+    /// How to authenticate to the registry for this scope.
+    ///
+    /// # Errors
+    /// This function errors if the challenge is not supported, we have no credentials while we need them or the token service failed.
+    async fn authenticate(&self, registry: &str, scope: &str, challenge: &str) -> Result<Auth, Error> {
+        let (scheme, params): (String, HashMap<String, String>) = match parse_challenge(challenge) {
+            Some(parsed) => parsed,
+            None => return Err(Error::IllegalChallenge { registry: registry.into(), challenge: challenge.into() }),
+        };
+        let creds: Option<&(String, String)> = self.creds.get(registry);
+        match scheme.as_str() {
+            "basic" => match creds {
+                Some((user, pass)) => Ok(Auth::Basic(user.clone(), pass.clone())),
+                None => Err(Error::MissingCredentials { registry: registry.into() }),
+            },
+
+            "bearer" => {
+                let realm: &String = match params.get("realm") {
+                    Some(realm) => realm,
+                    None => return Err(Error::IllegalChallenge { registry: registry.into(), challenge: challenge.into() }),
+                };
+
+                // Ask the token service for a token, logging in if we can
+                let mut query: Vec<(&str, &str)> = vec![("scope", scope)];
+                if let Some(service) = params.get("service") {
+                    query.push(("service", service));
+                }
+                let mut builder: RequestBuilder = self.http.get(realm).query(&query);
+                if let Some((user, pass)) = creds {
+                    builder = builder.basic_auth(user, Some(pass));
+                }
+                debug!("Requesting token for '{}' from '{}'...", scope, realm);
+                let res: Response = match builder.send().await {
+                    Ok(res) => res,
+                    Err(err) => return Err(Error::RequestError { address: realm.clone(), err }),
+                };
+                let body: Vec<u8> = match check(realm, res).await?.bytes().await {
+                    Ok(body) => body.to_vec(),
+                    Err(err) => return Err(Error::ResponseReadError { address: realm.clone(), err }),
+                };
+
+                /// The response of a token service.
+                #[derive(Deserialize)]
+                struct TokenResponse {
+                    #[serde(default)]
+                    token: Option<String>,
+                    #[serde(default)]
+                    access_token: Option<String>,
+                }
+                let token: TokenResponse = match serde_json::from_slice(&body) {
+                    Ok(token) => token,
+                    Err(err) => return Err(Error::TokenParseError { address: realm.clone(), err }),
+                };
+                match token.token.or(token.access_token) {
+                    Some(token) => Ok(Auth::Bearer(token)),
+                    None => Err(Error::IllegalChallenge { registry: registry.into(), challenge: challenge.into() }),
+                }
+            },
+
+            _ => Err(Error::IllegalChallenge { registry: registry.into(), challenge: challenge.into() }),
+        }
+    }
+
+    /// Fetches a manifest from a registry.
+    ///
+    /// # Arguments
+    /// - `reference`: The image whose manifest to fetch.
+    /// - `selector`: The tag or digest of the manifest to fetch.
+    ///
+    /// # Returns
+    /// The parsed manifest and its digest.
+    ///
+    /// # Errors
+    /// This function errors if we failed to fetch or parse the manifest, or if it does not have the digest it was fetched by.
+    async fn get_manifest(&self, reference: &Reference, selector: &str) -> Result<(Manifest, String), Error> {
+        let address: String = format!("{}/manifests/{}", reference.base(), selector);
+        let accept: String = [OCI_MANIFEST, OCI_INDEX, DOCKER_MANIFEST, DOCKER_MANIFEST_LIST].join(", ");
+        let (address, res): (String, Response) = self.send(reference, false, |http| Ok(http.get(&address).header(ACCEPT, &accept))).await?;
+        let res: Response = check(&address, res).await?;
+        let media_type: Option<String> =
+            res.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(|v| v.split(';').next().unwrap_or(v).trim().into());
+        let body: Vec<u8> = match res.bytes().await {
+            Ok(body) => body.to_vec(),
+            Err(err) => return Err(Error::ResponseReadError { address, err }),
+        };
+
+        // Verify the digest if we asked by digest, since then it's supposed to be immutable
+        let digest: String = digest_of(&body);
+        if is_digest(selector) && digest != selector {
+            return Err(Error::DigestMismatch { what: format!("manifest of '{reference}'"), expected: selector.into(), got: digest });
+        }
+
+        let mut manifest: Manifest = match serde_json::from_slice(&body) {
+            Ok(manifest) => manifest,
+            Err(err) => return Err(Error::ManifestParseError { reference: reference.to_string(), err }),
+        };
+        if manifest.media_type.is_none() {
+            manifest.media_type = media_type;
+        }
+        match manifest.media_type.as_deref() {
+            Some(OCI_MANIFEST) | Some(DOCKER_MANIFEST) | Some(OCI_INDEX) | Some(DOCKER_MANIFEST_LIST) => Ok((manifest, digest)),
+            // Some registries leave the media type out entirely; fall back to the structure in that case
+            None => Ok((manifest, digest)),
+            Some(other) => Err(Error::UnsupportedMediaType { reference: reference.to_string(), media_type: other.into() }),
+        }
+    }
+
+    /// Resolves a reference to the image manifest for the given architecture, going through an index if necessary.
+    ///
+    /// # Arguments
+    /// - `reference`: The image to resolve.
+    /// - `arch`: The architecture (in OCI naming, e.g., `amd64`) to select from indices.
+    ///
+    /// # Returns
+    /// The digest of the top-level manifest, the annotations of the top-level manifest and the image manifest itself.
+    ///
+    /// # Errors
+    /// This function errors if we failed to fetch any manifest, or if an index has no image for the given architecture.
+    async fn resolve(&self, reference: &Reference, arch: &str) -> Result<(String, HashMap<String, String>, Manifest), Error> {
+        let (manifest, digest): (Manifest, String) = self.get_manifest(reference, reference.selector()).await?;
+        if manifest.manifests.is_empty() {
+            return Ok((digest, manifest.annotations.clone(), manifest));
+        }
+
+        // It's an index; find the image for our platform
+        let image: &Descriptor =
+            match manifest.manifests.iter().find(|d| d.platform.as_ref().map(|p| p.os == "linux" && p.architecture == arch).unwrap_or(false)) {
+                Some(image) => image,
+                None => return Err(Error::NoMatchingPlatform { reference: reference.to_string(), arch: arch.into() }),
+            };
+        let (image, _): (Manifest, String) = self.get_manifest(reference, &image.digest).await?;
+        Ok((digest, manifest.annotations, image))
+    }
+
+    /// Uploads a blob to a registry, unless the registry already has it.
+    ///
+    /// # Arguments
+    /// - `reference`: The image the blob belongs to.
+    /// - `path`: The path of the file with the blob's contents.
+    /// - `digest`: The digest of the blob.
+    /// - `size`: The size of the blob, in bytes.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the blob or to upload it.
+    async fn push_blob(&self, reference: &Reference, path: &Path, digest: &str, size: u64) -> Result<(), Error> {
+        // Don't upload what's already there
+        let address: String = format!("{}/blobs/{}", reference.base(), digest);
+        let (_, res): (String, Response) = self.send(reference, true, |http| Ok(http.head(&address))).await?;
+        if res.status().is_success() {
+            debug!("Registry already has blob '{}'", digest);
+            return Ok(());
+        }
+
+        // Start an upload session
+        let address: String = format!("{}/blobs/uploads/", reference.base());
+        let (address, res): (String, Response) =
+            self.send(reference, true, |http| Ok(http.post(&address).header(CONTENT_LENGTH, HeaderValue::from_static("0")))).await?;
+        let res: Response = check(&address, res).await?;
+        let location: String = match res.headers().get(LOCATION).and_then(|v| v.to_str().ok()) {
+            Some(location) => location.into(),
+            None => return Err(Error::MissingLocation { address }),
+        };
+        let location: String = if location.starts_with('/') {
+            // Relative to the registry
+            let base: String = reference.base();
+            let root: &str = &base[..base.find("/v2/").unwrap_or(base.len())];
+            format!("{root}{location}")
+        } else {
+            location
+        };
+        let address: String = format!("{}{}digest={}", location, if location.contains('?') { '&' } else { '?' }, digest);
+
+        // Upload the blob in one go
+        debug!("Uploading blob '{}' ({} bytes) from '{}'...", digest, size, path.display());
+        let (address, res): (String, Response) = self
+            .send(reference, true, |http| {
+                let handle: std::fs::File = match std::fs::File::open(path) {
+                    Ok(handle) => handle,
+                    Err(err) => return Err(Error::FileReadError { path: path.into(), err }),
+                };
+                Ok(http
+                    .put(&address)
+                    .header(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"))
+                    .header(CONTENT_LENGTH, size)
+                    .body(Body::from(tfs::File::from_std(handle))))
+            })
+            .await?;
+        check(&address, res).await?;
+        Ok(())
+    }
+
+    /// Downloads a blob from a registry to a file, verifying its digest.
+    ///
+    /// # Arguments
+    /// - `reference`: The image the blob belongs to.
+    /// - `digest`: The digest of the blob.
+    /// - `path`: The path of the file to write the blob to.
+    ///
+    /// # Errors
+    /// This function errors if we failed to download or write the blob, or if it does not have the expected digest.
+    async fn pull_blob(&self, reference: &Reference, digest: &str, path: &Path) -> Result<(), Error> {
+        let address: String = format!("{}/blobs/{}", reference.base(), digest);
+        let (address, res): (String, Response) = self.send(reference, false, |http| Ok(http.get(&address))).await?;
+        let res: Response = check(&address, res).await?;
+
+        debug!("Downloading blob '{}' to '{}'...", digest, path.display());
+        let mut handle: tfs::File = match tfs::File::create(path).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(Error::FileWriteError { path: path.into(), err }),
+        };
+        let mut hasher: Sha256 = Sha256::new();
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => return Err(Error::ResponseReadError { address, err }),
+            };
+            hasher.update(&chunk);
+            if let Err(err) = handle.write_all(&chunk).await {
+                return Err(Error::FileWriteError { path: path.into(), err });
+            }
+        }
+        if let Err(err) = handle.flush().await {
+            return Err(Error::FileWriteError { path: path.into(), err });
+        }
+
+        let got: String = format!("sha256:{}", hex::encode(hasher.finalize()));
+        if got != digest {
+            return Err(Error::DigestMismatch { what: format!("blob of '{reference}'"), expected: digest.into(), got });
+        }
+        Ok(())
+    }
+
+    /// Pushes a package image to a registry.
+    ///
+    /// The image is pushed as an OCI image manifest that carries the given package info as [`PACKAGE_INFO_ANNOTATION`], together with the
+    /// usual `org.opencontainers.image.*` annotations.
+    ///
+    /// # Arguments
+    /// - `image`: The path to the image archive as produced by `docker save` (i.e., a package's `image.tar`).
+    /// - `info`: The package info to attach to the image.
+    /// - `reference`: Where to push the image to. Its tag (or `latest`) is updated to point to the image.
+    ///
+    /// # Returns
+    /// The given reference, pinned to the digest of the pushed manifest.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read the archive or if the registry refused any part of the upload.
+    pub async fn push_archive(&self, image: impl AsRef<Path>, info: &PackageInfo, reference: &Reference) -> Result<Reference, Error> {
+        let image: &Path = image.as_ref();
+        info!("Pushing '{}' to '{}'...", image.display(), reference);
+
+        // Unpack the archive so we can hash and upload its files
+        let dir: TempDir = match TempDir::new() {
+            Ok(dir) => dir,
+            Err(err) => return Err(Error::TempDirError { err }),
+        };
+        let handle: tfs::File = match tfs::File::open(image).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(Error::ArchiveOpenError { path: image.into(), err }),
+        };
+        if let Err(err) = tokio_tar::Archive::new(handle).unpack(dir.path()).await {
+            return Err(Error::ArchiveExtractError { path: image.into(), err });
+        }
+        let manifest_path: PathBuf = dir.path().join("manifest.json");
+        let raw: Vec<u8> = match tfs::read(&manifest_path).await {
+            Ok(raw) => raw,
+            Err(err) => return Err(Error::FileReadError { path: manifest_path, err }),
+        };
+        let mut manifests: Vec<ArchiveManifest> = match serde_json::from_slice(&raw) {
+            Ok(manifests) => manifests,
+            Err(err) => return Err(Error::ArchiveManifestError { path: image.into(), err }),
+        };
+        if manifests.len() != 1 {
+            return Err(Error::ArchiveIllegalManifestNum { path: image.into(), got: manifests.len() });
+        }
+        let archive: ArchiveManifest = manifests.pop().unwrap();
+
+        // Upload the config and the layers
+        let config_path: PathBuf = dir.path().join(&archive.config);
+        let (digest, size, _): (String, u64, bool) = digest_file(&config_path).await?;
+        self.push_blob(reference, &config_path, &digest, size).await?;
+        let config: Descriptor = Descriptor { media_type: OCI_CONFIG.into(), digest, size, platform: None, annotations: HashMap::new() };
+        let mut layers: Vec<Descriptor> = Vec::with_capacity(archive.layers.len());
+        for layer in &archive.layers {
+            let layer_path: PathBuf = dir.path().join(layer);
+            let (digest, size, gzip): (String, u64, bool) = digest_file(&layer_path).await?;
+            self.push_blob(reference, &layer_path, &digest, size).await?;
+            layers.push(Descriptor {
+                media_type: if gzip { OCI_LAYER_GZIP } else { OCI_LAYER }.into(),
+                digest,
+                size,
+                platform: None,
+                annotations: HashMap::new(),
+            });
+        }
+
+        // Put the manifest with the package info attached
+        let info_json: String = match serde_json::to_string(info) {
+            Ok(info_json) => info_json,
+            Err(err) => return Err(Error::SerializeError { what: "package info", err }),
+        };
+        let mut annotations: HashMap<String, String> = HashMap::from([
+            ("org.opencontainers.image.title".into(), info.name.clone()),
+            ("org.opencontainers.image.version".into(), info.version.to_string()),
+            (PACKAGE_INFO_ANNOTATION.into(), info_json),
+        ]);
+        if !info.description.is_empty() {
+            annotations.insert("org.opencontainers.image.description".into(), info.description.clone());
+        }
+        let manifest: Manifest =
+            Manifest { schema_version: 2, media_type: Some(OCI_MANIFEST.into()), config: Some(config), layers, manifests: vec![], annotations };
+        let body: Vec<u8> = match serde_json::to_vec(&manifest) {
+            Ok(body) => body,
+            Err(err) => return Err(Error::SerializeError { what: "manifest", err }),
+        };
+        let digest: String = digest_of(&body);
+        let address: String = format!("{}/manifests/{}", reference.base(), reference.tag.as_deref().unwrap_or("latest"));
+        let (address, res): (String, Response) = self
+            .send(reference, true, |http| Ok(http.put(&address).header(CONTENT_TYPE, HeaderValue::from_static(OCI_MANIFEST)).body(body.clone())))
+            .await?;
+        check(&address, res).await?;
+
+        Ok(reference.pinned(digest))
+    }
+
+    /// Reads the package info attached to a package image in a registry.
+    ///
+    /// # Arguments
+    /// - `reference`: The image to read the package info of.
+    ///
+    /// # Returns
+    /// The package info and the given reference pinned to the digest of its manifest, such that later pulls get exactly this image.
+    ///
+    /// # Errors
+    /// This function errors if we failed to fetch the manifest or if it is not a Brane package.
+    pub async fn package_info(&self, reference: &Reference) -> Result<(PackageInfo, Reference), Error> {
+        let (digest, annotations, image): (String, HashMap<String, String>, Manifest) = self.resolve(reference, host_arch()).await?;
+        let raw: &String = match annotations.get(PACKAGE_INFO_ANNOTATION).or_else(|| image.annotations.get(PACKAGE_INFO_ANNOTATION)) {
+            Some(raw) => raw,
+            None => return Err(Error::NotAPackage { reference: reference.to_string() }),
+        };
+        let mut info: PackageInfo = match serde_json::from_str(raw) {
+            Ok(info) => info,
+            Err(err) => return Err(Error::PackageInfoParseError { reference: reference.to_string(), err }),
+        };
+        if info.digest.is_none() {
+            info.digest = image.config.as_ref().map(|c| c.digest.clone());
+        }
+        Ok((info, reference.pinned(digest)))
+    }
+
+    /// Pulls a package image from a registry into an archive that can be given to `docker load`.
+    ///
+    /// # Arguments
+    /// - `reference`: The image to pull.
+    /// - `arch`: The architecture (in OCI naming, e.g., `amd64`) to select from indices. Use [`host_arch()`] for the current machine.
+    /// - `target`: The path of the archive to write.
+    ///
+    /// # Errors
+    /// This function errors if we failed to fetch any part of the image, any part did not have the expected digest, or we failed to write the archive.
+    pub async fn pull_archive(&self, reference: &Reference, arch: &str, target: impl AsRef<Path>) -> Result<(), Error> {
+        let target: &Path = target.as_ref();
+        info!("Pulling '{}' to '{}'...", reference, target.display());
+        let (_, _, image): (String, HashMap<String, String>, Manifest) = self.resolve(reference, arch).await?;
+        let config: Descriptor = match image.config {
+            Some(config) => config,
+            None => return Err(Error::NotAPackage { reference: reference.to_string() }),
+        };
+
+        // Download the blobs using the same layout as `docker save` does, so the digest can be read from the archive again
+        let dir: TempDir = match TempDir::new() {
+            Ok(dir) => dir,
+            Err(err) => return Err(Error::TempDirError { err }),
+        };
+        let blobs: PathBuf = dir.path().join("blobs").join("sha256");
+        if let Err(err) = tfs::create_dir_all(&blobs).await {
+            return Err(Error::FileWriteError { path: blobs, err });
+        }
+        let mut names: Vec<String> = Vec::with_capacity(1 + image.layers.len());
+        let mut files: Vec<String> = Vec::with_capacity(1 + image.layers.len());
+        for desc in std::iter::once(&config).chain(image.layers.iter()) {
+            let hex: &str = desc.digest.strip_prefix("sha256:").unwrap_or(&desc.digest);
+            let name: String = format!("blobs/sha256/{hex}");
+            if !files.contains(&name) {
+                self.pull_blob(reference, &desc.digest, &dir.path().join(&name)).await?;
+                files.push(name.clone());
+            }
+            names.push(name);
+        }
+        let archive: Vec<ArchiveManifest> = vec![ArchiveManifest { config: names.remove(0), repo_tags: None, layers: names }];
+        let manifest_path: PathBuf = dir.path().join("manifest.json");
+        match serde_json::to_vec(&archive) {
+            Ok(raw) => {
+                if let Err(err) = tfs::write(&manifest_path, raw).await {
+                    return Err(Error::FileWriteError { path: manifest_path, err });
+                }
+            },
+            Err(err) => return Err(Error::SerializeError { what: "archive manifest", err }),
+        }
+
+        // Pack it all up
+        let handle: tfs::File = match tfs::File::create(target).await {
+            Ok(handle) => handle,
+            Err(err) => return Err(Error::ArchiveCreateError { path: target.into(), err }),
+        };
+        let mut builder: tokio_tar::Builder<tfs::File> = tokio_tar::Builder::new(handle);
+        for name in std::iter::once("manifest.json").chain(files.iter().map(String::as_str)) {
+            if let Err(err) = builder.append_path_with_name(dir.path().join(name), name).await {
+                return Err(Error::ArchiveWriteError { path: target.into(), err });
+            }
+        }
+        match builder.into_inner().await {
+            Ok(mut handle) => {
+                if let Err(err) = handle.flush().await {
+                    return Err(Error::ArchiveWriteError { path: target.into(), err });
+                }
+                Ok(())
+            },
+            Err(err) => Err(Error::ArchiveWriteError { path: target.into(), err }),
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_parse() {
+        let r: Reference = "harbor.example.com/brane/hello-world:1.0.0".parse().unwrap();
+        assert_eq!(r.registry, "harbor.example.com");
+        assert_eq!(r.repository, "brane/hello-world");
+        assert_eq!(r.tag.as_deref(), Some("1.0.0"));
+        assert_eq!(r.digest, None);
+        assert_eq!(r.base(), "https://harbor.example.com/v2/brane/hello-world");
+
+        let r: Reference = "localhost:5000/hello-world".parse().unwrap();
+        assert_eq!(r.registry, "localhost:5000");
+        assert_eq!(r.tag, None);
+        assert_eq!(r.selector(), "latest");
+        assert_eq!(r.base(), "http://localhost:5000/v2/hello-world");
+
+        let r: Reference = "ubuntu".parse().unwrap();
+        assert_eq!(r.registry, DEFAULT_REGISTRY);
+        assert_eq!(r.repository, "library/ubuntu");
+
+        let digest: String = format!("sha256:{}", "a".repeat(64));
+        let r: Reference = format!("epi/hello:1.0.0@{digest}").parse().unwrap();
+        assert_eq!(r.registry, DEFAULT_REGISTRY);
+        assert_eq!(r.repository, "epi/hello");
+        assert_eq!(r.selector(), digest);
+        assert_eq!(r.to_string(), format!("{DEFAULT_REGISTRY}/epi/hello:1.0.0@{digest}"));
+
+        assert!("harbor.example.com/Brane/hello".parse::<Reference>().is_err());
+        assert!("harbor.example.com/brane/hello:".parse::<Reference>().is_err());
+        assert!("harbor.example.com/brane/hello@sha256:abc".parse::<Reference>().is_err());
+        assert!("harbor.example.com//hello".parse::<Reference>().is_err());
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        let (scheme, params) =
+            parse_challenge(r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/ubuntu:pull""#)
+                .unwrap();
+        assert_eq!(scheme, "bearer");
+        assert_eq!(params.get("realm").map(String::as_str), Some("https://auth.docker.io/token"));
+        assert_eq!(params.get("service").map(String::as_str), Some("registry.docker.io"));
+        assert_eq!(params.get("scope").map(String::as_str), Some("repository:library/ubuntu:pull"));
+
+        let (scheme, params) = parse_challenge(r#"Basic realm="Harbor""#).unwrap();
+        assert_eq!(scheme, "basic");
+        assert_eq!(params.get("realm").map(String::as_str), Some("Harbor"));
+
+        assert!(parse_challenge("").is_none());
+    }
+
+    #[test]
+    fn test_credentials() {
+        let dir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join("config.json");
+        let auth: String = base64::engine::general_purpose::STANDARD.encode("user:pass");
+        std::fs::write(&path, format!(r#"{{ "auths": {{ "https://index.docker.io/v1/": {{ "auth": "{auth}" }}, "harbor.example.com": {{}} }} }}"#))
+            .unwrap();
+        let creds: Credentials = Credentials::from_path(&path).unwrap();
+        assert_eq!(creds.get(DEFAULT_REGISTRY), Some(&("user".into(), "pass".into())));
+        assert_eq!(creds.get("harbor.example.com"), None);
+    }
+}
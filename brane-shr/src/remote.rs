@@ -0,0 +1,232 @@
+//  REMOTE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:05:41
+//  Last edited:
+//    15 Oct 2026, 22:05:41
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements fetching datasets that don't live on a Brane-managed disk
+//!   (i.e., those accessed as [`AccessKind::S3`] or [`AccessKind::Url`]).
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::{Component, Path, PathBuf};
+
+use log::debug;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectMeta, ObjectStore as _};
+use reqwest::Url;
+use specifications::data::AccessKind;
+use tokio::fs as tfs;
+use tokio::io::AsyncWriteExt as _;
+use tokio_stream::StreamExt as _;
+
+use crate::fs::{download_file_async, DownloadSecurity};
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to fetching remote datasets.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to create the directory to fetch to.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to create a file to fetch an object to.
+    FileCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to write to a file we fetch an object to.
+    FileWriteError { path: PathBuf, err: std::io::Error },
+
+    /// Failed to download a file over HTTPS.
+    DownloadError { address: String, err: crate::fs::Error },
+
+    /// Failed to prepare the client for an S3 bucket.
+    S3ClientError { bucket: String, err: object_store::Error },
+    /// Failed to list the objects in an S3 bucket.
+    S3ListError { bucket: String, prefix: String, err: object_store::Error },
+    /// Failed to download an object from an S3 bucket.
+    S3GetError { bucket: String, key: String, err: object_store::Error },
+    /// An object in an S3 bucket has a key that would escape the directory we fetch to.
+    S3IllegalKey { bucket: String, key: String },
+    /// There are no objects under the given prefix in an S3 bucket.
+    S3NoObjects { bucket: String, prefix: String },
+}
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            DirCreateError { path, err } => write!(f, "Failed to create dataset directory '{}': {}", path.display(), err),
+            FileCreateError { path, err } => write!(f, "Failed to create dataset file '{}': {}", path.display(), err),
+            FileWriteError { path, err } => write!(f, "Failed to write to dataset file '{}': {}", path.display(), err),
+
+            DownloadError { address, err } => write!(f, "Failed to download dataset from '{address}': {err}"),
+
+            S3ClientError { bucket, err } => write!(f, "Failed to prepare client for S3 bucket '{bucket}': {err}"),
+            S3ListError { bucket, prefix, err } => write!(f, "Failed to list objects under '{prefix}' in S3 bucket '{bucket}': {err}"),
+            S3GetError { bucket, key, err } => write!(f, "Failed to download object '{key}' from S3 bucket '{bucket}': {err}"),
+            S3IllegalKey { bucket, key } => {
+                write!(f, "Object '{key}' in S3 bucket '{bucket}' has a key that cannot be used as a relative path (it contains '..' or is absolute)")
+            },
+            S3NoObjects { bucket, prefix } => write!(f, "There are no objects under '{prefix}' in S3 bucket '{bucket}'"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        use Error::*;
+        match self {
+            DirCreateError { err, .. } => Some(err),
+            FileCreateError { err, .. } => Some(err),
+            FileWriteError { err, .. } => Some(err),
+
+            DownloadError { err, .. } => Some(err),
+
+            S3ClientError { err, .. } => Some(err),
+            S3ListError { err, .. } => Some(err),
+            S3GetError { err, .. } => Some(err),
+            S3IllegalKey { .. } => None,
+            S3NoObjects { .. } => None,
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Downloads all objects under the given prefix in an S3 bucket to the given directory.
+///
+/// # Arguments
+/// - `bucket`: The name of the bucket.
+/// - `prefix`: The prefix of the objects to download. The objects are stored under their key relative to this prefix.
+/// - `region`: The region of the bucket.
+/// - `target`: The (existing) directory to download the objects to.
+///
+/// # Errors
+/// This function errors if we failed to list or download the objects, or if there weren't any.
+async fn fetch_s3(bucket: &str, prefix: &str, region: &str, target: &Path) -> Result<(), Error> {
+    let store: AmazonS3 = match AmazonS3Builder::from_env().with_bucket_name(bucket).with_region(region).build() {
+        Ok(store) => store,
+        Err(err) => {
+            return Err(Error::S3ClientError { bucket: bucket.into(), err });
+        },
+    };
+
+    // Download the objects one-by-one
+    let prefix: Option<ObjectPath> = if !prefix.is_empty() { Some(ObjectPath::from(prefix)) } else { None };
+    let prefix_str: String = prefix.as_ref().map(ToString::to_string).unwrap_or_default();
+    let mut objects = store.list(prefix.as_ref());
+    let mut n_objects: usize = 0;
+    while let Some(meta) = objects.next().await {
+        let meta: ObjectMeta = match meta {
+            Ok(meta) => meta,
+            Err(err) => {
+                return Err(Error::S3ListError { bucket: bucket.into(), prefix: prefix_str, err });
+            },
+        };
+
+        // Find where to put it, making sure it doesn't escape the target directory
+        let key: &str = meta.location.as_ref();
+        let rel: &str = key.strip_prefix(prefix_str.as_str()).unwrap_or(key).trim_start_matches('/');
+        let rel: PathBuf = PathBuf::from(if !rel.is_empty() { rel } else { meta.location.filename().unwrap_or("data") });
+        if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(Error::S3IllegalKey { bucket: bucket.into(), key: key.into() });
+        }
+        let path: PathBuf = target.join(rel);
+        if let Some(parent) = path.parent() {
+            if let Err(err) = tfs::create_dir_all(parent).await {
+                return Err(Error::DirCreateError { path: parent.into(), err });
+            }
+        }
+
+        // Stream the object to it
+        debug!("Downloading object '{}' from S3 bucket '{}' to '{}'...", key, bucket, path.display());
+        let mut handle: tfs::File = match tfs::File::create(&path).await {
+            Ok(handle) => handle,
+            Err(err) => {
+                return Err(Error::FileCreateError { path, err });
+            },
+        };
+        let mut chunks = match store.get(&meta.location).await {
+            Ok(res) => res.into_stream(),
+            Err(err) => {
+                return Err(Error::S3GetError { bucket: bucket.into(), key: key.into(), err });
+            },
+        };
+        while let Some(chunk) = chunks.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(err) => {
+                    return Err(Error::S3GetError { bucket: bucket.into(), key: key.into(), err });
+                },
+            };
+            if let Err(err) = handle.write_all(&chunk).await {
+                return Err(Error::FileWriteError { path, err });
+            }
+        }
+        if let Err(err) = handle.flush().await {
+            return Err(Error::FileWriteError { path, err });
+        }
+        n_objects += 1;
+    }
+
+    // Make sure there was something to download
+    if n_objects == 0 {
+        return Err(Error::S3NoObjects { bucket: bucket.into(), prefix: prefix_str });
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Fetches a dataset to the given directory if it is stored remotely.
+///
+/// # Arguments
+/// - `access`: The [`AccessKind`] that describes where the dataset is stored.
+/// - `target`: The directory to fetch the dataset to. Will be created if it does not exist yet.
+///
+/// # Returns
+/// The local path of the dataset. This is the path in `access` itself if it's an [`AccessKind::File`] (in which case nothing is fetched), the
+/// downloaded file for an [`AccessKind::Url`], or `target` for an [`AccessKind::S3`] (which contains the objects under their key relative to
+/// the prefix).
+///
+/// # Errors
+/// This function errors if we failed to create the target directory or failed to fetch the dataset.
+pub async fn fetch_async(access: &AccessKind, target: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let target: &Path = target.as_ref();
+    if let AccessKind::File { path } = access {
+        return Ok(path.clone());
+    }
+    debug!("Fetching remote dataset at '{}' to '{}'...", access, target.display());
+    if let Err(err) = tfs::create_dir_all(target).await {
+        return Err(Error::DirCreateError { path: target.into(), err });
+    }
+
+    match access {
+        AccessKind::File { .. } => unreachable!(),
+        AccessKind::S3 { bucket, prefix, region } => {
+            fetch_s3(bucket, prefix, region, target).await?;
+            Ok(target.into())
+        },
+        AccessKind::Url { https } => {
+            // Name the file after the last segment of the URL
+            let name: String = Url::parse(https)
+                .ok()
+                .and_then(|url| url.path_segments().and_then(|mut segments| segments.next_back().map(String::from)))
+                .filter(|name| !name.is_empty() && name != "." && name != "..")
+                .unwrap_or_else(|| "data".into());
+            let path: PathBuf = target.join(name);
+            if let Err(err) = download_file_async(https, &path, DownloadSecurity::https(), None).await {
+                return Err(Error::DownloadError { address: https.clone(), err });
+            }
+            Ok(path)
+        },
+    }
+}
@@ -0,0 +1,163 @@
+//  SUGGEST.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:55:10
+//  Last edited:
+//    15 Oct 2026, 22:55:10
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements "did you mean" suggestions for names that were not found
+//!   in an index (e.g., misspelled packages or datasets).
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+
+
+/***** CONSTANTS *****/
+/// The maximum number of suggestions returned by [`suggest()`].
+pub const MAX_SUGGESTIONS: usize = 3;
+
+
+
+
+
+/***** FORMATTERS *****/
+/// Formats a list of suggestions as a "did you mean"-postfix to an error message.
+///
+/// Writes nothing if there are no suggestions, such that it can be appended unconditionally.
+#[derive(Debug)]
+pub struct DidYouMean<'a> {
+    /// The suggestions to write.
+    suggestions: &'a [String],
+}
+impl<'a> DidYouMean<'a> {
+    /// Constructor for the DidYouMean.
+    ///
+    /// # Arguments
+    /// - `suggestions`: The suggestions to write, typically as returned by [`suggest()`].
+    ///
+    /// # Returns
+    /// A new instance of DidYouMean.
+    #[inline]
+    pub fn new(suggestions: &'a [String]) -> Self { Self { suggestions } }
+}
+impl<'a> Display for DidYouMean<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let (last, init): (&String, &[String]) = match self.suggestions.split_last() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+        write!(f, "; did you mean ")?;
+        for (i, suggestion) in init.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "'{suggestion}'")?;
+        }
+        if !init.is_empty() {
+            write!(f, " or ")?;
+        }
+        write!(f, "'{last}'?")
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Computes the Levenshtein distance between two strings, i.e., the number of single-character insertions, deletions and substitutions that
+/// turn one into the other.
+///
+/// # Arguments
+/// - `lhs`: The one string.
+/// - `rhs`: The other string.
+///
+/// # Returns
+/// The distance between the strings, counted in characters (not bytes).
+pub fn levenshtein(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+
+    // We only keep the previous row of the matrix around
+    let mut prev: Vec<usize> = (0..=rhs.len()).collect();
+    let mut next: Vec<usize> = vec![0; rhs.len() + 1];
+    for (i, l) in lhs.chars().enumerate() {
+        next[0] = i + 1;
+        for (j, r) in rhs.iter().enumerate() {
+            let cost: usize = if l == *r { 0 } else { 1 };
+            next[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(next[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut next);
+    }
+    prev[rhs.len()]
+}
+
+
+
+/// Finds the names closest to one that was not found.
+///
+/// Only names that are "close enough" are suggested, i.e., of which the distance is at most a third of the length of the given name (but at
+/// least 1). Differences in case are ignored.
+///
+/// # Arguments
+/// - `name`: The name that was not found.
+/// - `candidates`: The names that do exist. Duplicates and the given name itself are ignored.
+///
+/// # Returns
+/// At most [`MAX_SUGGESTIONS`] names, closest first. It is empty if none of the candidates is close enough.
+pub fn suggest<'c>(name: &str, candidates: impl IntoIterator<Item = &'c str>) -> Vec<String> {
+    let lower: String = name.to_lowercase();
+    let max_dist: usize = (lower.chars().count() / 3).max(1);
+
+    let mut close: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (levenshtein(&lower, &candidate.to_lowercase()), candidate))
+        .filter(|(dist, _)| *dist <= max_dist)
+        .collect();
+    close.sort_unstable();
+    close.dedup_by(|lhs, rhs| lhs.1 == rhs.1);
+    close.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate.into()).collect()
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test the distances themselves
+    #[test]
+    fn levenshtein_distances() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("covid_cases", "covid-cases"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("hëllo", "hello"), 1);
+    }
+
+    /// Test which names are suggested, and in what order
+    #[test]
+    fn suggest_closest() {
+        let names: [&str; 5] = ["covid-cases", "covid_deaths", "Covid_Cases", "hello_world", "covid-cases"];
+        assert_eq!(suggest("covid_cases", names), vec!["Covid_Cases".to_string(), "covid-cases".to_string()]);
+        assert_eq!(suggest("helloworld", names), vec!["hello_world".to_string()]);
+        assert!(suggest("epi", names).is_empty());
+        assert!(suggest("covid-cases", ["covid-cases"]).is_empty());
+    }
+
+    /// Test the formatting of suggestions
+    #[test]
+    fn didyoumean_format() {
+        assert_eq!(DidYouMean::new(&[]).to_string(), "");
+        assert_eq!(DidYouMean::new(&["a".into()]).to_string(), "; did you mean 'a'?");
+        assert_eq!(DidYouMean::new(&["a".into(), "b".into(), "c".into()]).to_string(), "; did you mean 'a', 'b' or 'c'?");
+    }
+}
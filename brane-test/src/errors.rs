@@ -0,0 +1,90 @@
+//  ERRORS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:33:01
+//  Last edited:
+//    15 Oct 2026, 21:33:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the errors that may occur in the `brane-test` crate.
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::time::Duration;
+
+
+/***** LIBRARY *****/
+/// Defines errors that relate to launching or using a [`TestInstance`](crate::instance::TestInstance).
+#[derive(Debug)]
+pub enum InstanceError {
+    /// Failed to create the temporary directory that contains the instance.
+    TempDirError { err: std::io::Error },
+    /// Failed to create a directory.
+    DirCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to read a directory.
+    DirReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to copy a file.
+    CopyError { source: PathBuf, target: PathBuf, err: std::io::Error },
+
+    /// Failed to spawn a command.
+    SpawnError { cmd: Command, err: std::io::Error },
+    /// A spawned command failed.
+    SpawnFailure { cmd: Command, status: ExitStatus, err: String },
+    /// A service did not come online in time.
+    ServiceTimeout { what: &'static str, port: u16, timeout: Duration },
+
+    /// The given fixture does not exist.
+    UnknownFixture { what: &'static str, path: PathBuf },
+    /// Failed to read the `container.yml` file of a package fixture.
+    ContainerInfoError { path: PathBuf, err: specifications::container::ContainerInfoError },
+}
+impl Display for InstanceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use InstanceError::*;
+        match self {
+            TempDirError { .. } => write!(f, "Failed to create temporary directory in system temp folder"),
+            DirCreateError { path, .. } => write!(f, "Failed to create directory '{}'", path.display()),
+            DirReadError { path, .. } => write!(f, "Failed to read directory '{}'", path.display()),
+            CopyError { source, target, .. } => write!(f, "Failed to copy file '{}' to '{}'", source.display(), target.display()),
+
+            SpawnError { cmd, .. } => write!(f, "Failed to run command '{cmd:?}'"),
+            SpawnFailure { cmd, status, err } => write!(
+                f,
+                "Command '{:?}' failed{}\n\nstderr:\n{}\n\n",
+                cmd,
+                if let Some(code) = status.code() { format!(" with exit code {code}") } else { String::new() },
+                err
+            ),
+            ServiceTimeout { what, port, timeout } => {
+                write!(f, "{} did not come online on port {} within {} seconds", what, port, timeout.as_secs())
+            },
+
+            UnknownFixture { what, path } => write!(f, "Unknown {} fixture '{}'", what, path.display()),
+            ContainerInfoError { path, .. } => write!(f, "Failed to read package fixture file '{}'", path.display()),
+        }
+    }
+}
+impl Error for InstanceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use InstanceError::*;
+        match self {
+            TempDirError { err } => Some(err),
+            DirCreateError { err, .. } => Some(err),
+            DirReadError { err, .. } => Some(err),
+            CopyError { err, .. } => Some(err),
+
+            SpawnError { err, .. } => Some(err),
+            SpawnFailure { .. } => None,
+            ServiceTimeout { .. } => None,
+
+            UnknownFixture { .. } => None,
+            ContainerInfoError { err, .. } => Some(err),
+        }
+    }
+}
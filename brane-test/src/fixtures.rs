@@ -0,0 +1,55 @@
+//  FIXTURES.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:33:01
+//  Last edited:
+//    15 Oct 2026, 21:33:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Locates the fixture packages, datasets and workflows in the `tests`
+//!   directory of the repository.
+//
+
+use std::path::PathBuf;
+
+
+/***** LIBRARY *****/
+/// Returns the directory that contains all fixtures, i.e., the `tests` directory at the root of the repository.
+///
+/// # Returns
+/// The path to the fixtures directory.
+#[inline]
+pub fn fixtures_dir() -> PathBuf { PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("tests") }
+
+/// Returns the directory of a fixture package.
+///
+/// # Arguments
+/// - `name`: The name of the package's directory in `tests/packages` (e.g., `hello-world`).
+///
+/// # Returns
+/// The path to the directory that contains the package's `container.yml`.
+#[inline]
+pub fn package(name: impl AsRef<str>) -> PathBuf { fixtures_dir().join("packages").join(name.as_ref()) }
+
+/// Returns the directory of a fixture dataset.
+///
+/// # Arguments
+/// - `name`: The name of the dataset's directory in `tests/data` (e.g., `numbers`).
+///
+/// # Returns
+/// The path to the directory that contains the dataset's `data.yml`.
+#[inline]
+pub fn dataset(name: impl AsRef<str>) -> PathBuf { fixtures_dir().join("data").join(name.as_ref()) }
+
+/// Returns the path of a fixture BraneScript workflow.
+///
+/// # Arguments
+/// - `name`: The name of the workflow in `tests/branescript`, without extension (e.g., `hello_world`).
+///
+/// # Returns
+/// The path to the workflow's `.bs` file.
+#[inline]
+pub fn workflow(name: impl AsRef<str>) -> PathBuf { fixtures_dir().join("branescript").join(format!("{}.bs", name.as_ref())) }
@@ -0,0 +1,578 @@
+//  INSTANCE.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:33:01
+//  Last edited:
+//    15 Oct 2026, 21:33:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a [`TestInstance`], which launches a central and a worker
+//!   node on the local machine for end-to-end tests.
+//!
+//!   The nodes are configured and started with `branectl`, exactly like
+//!   an administrator would, and the instance is registered with a `brane`
+//!   CLI that has its own configuration directory. As such, the harness
+//!   needs the `branectl` and `brane` executables, the service images
+//!   (e.g., as built by `make.py instance`) and a running Docker daemon.
+//
+
+use std::ffi::OsStr;
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+
+use log::{debug, info, warn};
+use specifications::container::ContainerInfo;
+
+pub use crate::errors::InstanceError as Error;
+use crate::fixtures;
+
+
+/***** CONSTANTS *****/
+/// The hostname under which the nodes reach each other.
+pub const DEFAULT_HOSTNAME: &str = "brane-test";
+/// The location ID of the worker node.
+pub const DEFAULT_LOCATION_ID: &str = "test";
+/// The name of the user as which the CLI authenticates itself.
+pub const DEFAULT_USER: &str = "test-user";
+/// The name under which the instance is registered with the CLI.
+const INSTANCE_NAME: &str = "brane-test";
+
+/// The interval at which we poll whether a service is online.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Runs a command to completion.
+///
+/// # Arguments
+/// - `cmd`: The [`Command`] to run.
+///
+/// # Returns
+/// The command's stdout.
+///
+/// # Errors
+/// This function errors if we failed to spawn the command or if it returned a non-zero exit code.
+fn run(mut cmd: Command) -> Result<String, Error> {
+    debug!("Running {cmd:?}...");
+    let output: Output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) => return Err(Error::SpawnError { cmd, err }),
+    };
+    if !output.status.success() {
+        return Err(Error::SpawnFailure { cmd, status: output.status, err: String::from_utf8_lossy(&output.stderr).into() });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into())
+}
+
+/// Creates a directory and all of its parents.
+///
+/// # Arguments
+/// - `path`: The path of the directory to create.
+///
+/// # Errors
+/// This function errors if we failed to create the directory.
+fn mkdir(path: impl Into<PathBuf>) -> Result<(), Error> {
+    let path: PathBuf = path.into();
+    match fs::create_dir_all(&path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::DirCreateError { path, err }),
+    }
+}
+
+/// Copies a file.
+///
+/// # Arguments
+/// - `source`: The file to copy.
+/// - `target`: The path to copy it to.
+///
+/// # Errors
+/// This function errors if we failed to copy the file.
+fn copy(source: impl Into<PathBuf>, target: impl Into<PathBuf>) -> Result<(), Error> {
+    let (source, target): (PathBuf, PathBuf) = (source.into(), target.into());
+    match fs::copy(&source, &target) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(Error::CopyError { source, target, err }),
+    }
+}
+
+/// Copies a directory and all of its contents.
+///
+/// # Arguments
+/// - `source`: The directory to copy.
+/// - `target`: The path to copy it to. Must not exist yet.
+///
+/// # Errors
+/// This function errors if we failed to read the source directory or to copy any of its contents.
+fn copy_dir(source: &Path, target: &Path) -> Result<(), Error> {
+    mkdir(target)?;
+    let entries: fs::ReadDir = match fs::read_dir(source) {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::DirReadError { path: source.into(), err }),
+    };
+    for entry in entries {
+        let entry: fs::DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err) => return Err(Error::DirReadError { path: source.into(), err }),
+        };
+        let entry_path: PathBuf = entry.path();
+        if entry_path.is_dir() {
+            copy_dir(&entry_path, &target.join(entry.file_name()))?;
+        } else {
+            copy(entry_path, target.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Waits until a service accepts connections on a port of the local machine.
+///
+/// # Arguments
+/// - `what`: Some name of the service to use in errors.
+/// - `port`: The port to connect to.
+/// - `timeout`: The maximum time to wait.
+///
+/// # Errors
+/// This function errors if the service did not accept a connection within `timeout`.
+fn wait_for(what: &'static str, port: u16, timeout: Duration) -> Result<(), Error> {
+    debug!("Waiting for {what} to come online on port {port}...");
+    let addr: SocketAddr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let start: Instant = Instant::now();
+    while TcpStream::connect_timeout(&addr, POLL_INTERVAL).is_err() {
+        if start.elapsed() >= timeout {
+            return Err(Error::ServiceTimeout { what, port, timeout });
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines how to launch a [`TestInstance`].
+#[derive(Clone, Debug)]
+pub struct TestInstanceOptions {
+    /// The `branectl` executable to configure and start the nodes with.
+    pub branectl:  PathBuf,
+    /// The `brane` executable to interact with the instance.
+    pub brane:     PathBuf,
+    /// The directory with the service images (`brane-api.tar`, `brane-drv.tar`, ...).
+    pub image_dir: PathBuf,
+
+    /// The hostname under which the nodes reach each other.
+    pub hostname:    String,
+    /// The IP address of the local machine as seen from within the containers, to which `hostname` resolves within them.
+    pub host_ip:     String,
+    /// The location ID of the worker node.
+    pub location_id: String,
+    /// The name of the user as which the CLI authenticates itself to the worker.
+    pub user:        String,
+    /// The first port used by the instance. The central node uses this port and the three after it, the worker node the four ports ten higher.
+    pub port_base:   u16,
+
+    /// How long to wait for the services to come online.
+    pub startup_timeout: Duration,
+    /// If true, does not stop the nodes nor remove their files when the instance is dropped (useful for debugging a failing test).
+    pub keep:            bool,
+}
+impl Default for TestInstanceOptions {
+    fn default() -> Self {
+        Self {
+            branectl:  "branectl".into(),
+            brane:     "brane".into(),
+            image_dir: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("target").join("release"),
+
+            hostname:    DEFAULT_HOSTNAME.into(),
+            host_ip:     "172.17.0.1".into(),
+            location_id: DEFAULT_LOCATION_ID.into(),
+            user:        DEFAULT_USER.into(),
+            port_base:   50050,
+
+            startup_timeout: Duration::from_secs(180),
+            keep:            false,
+        }
+    }
+}
+impl TestInstanceOptions {
+    /// Constructor for the TestInstanceOptions that takes the defaults, but overrides them with any of the following environment variables:
+    /// - `BRANECTL`: The path to the `branectl` executable.
+    /// - `BRANE`: The path to the `brane` executable.
+    /// - `BRANE_IMAGE_DIR`: The directory with the service images.
+    /// - `BRANE_TEST_HOST_IP`: The IP address of the local machine as seen from within the containers.
+    /// - `BRANE_TEST_KEEP`: If set (to anything), the instance is kept after the test.
+    ///
+    /// # Returns
+    /// A new TestInstanceOptions.
+    pub fn from_env() -> Self {
+        let mut opts: Self = Self::default();
+        if let Some(branectl) = std::env::var_os("BRANECTL") {
+            opts.branectl = branectl.into();
+        }
+        if let Some(brane) = std::env::var_os("BRANE") {
+            opts.brane = brane.into();
+        }
+        if let Some(image_dir) = std::env::var_os("BRANE_IMAGE_DIR") {
+            opts.image_dir = image_dir.into();
+        }
+        if let Ok(host_ip) = std::env::var("BRANE_TEST_HOST_IP") {
+            opts.host_ip = host_ip;
+        }
+        opts.keep = std::env::var_os("BRANE_TEST_KEEP").is_some();
+        opts
+    }
+}
+
+
+
+/// Runs a single-machine instance, consisting of a central node and a worker node, for the duration of a test.
+///
+/// Every instance lives in its own temporary directory, which contains the configuration and data of both nodes and of the CLI. The nodes are
+/// stopped and the directory is removed when the instance is dropped.
+///
+/// Note that the nodes bind to fixed ports (see [`TestInstanceOptions::port_base`]), so tests using a TestInstance should not run in parallel.
+/// Also note that the CLI connects to the central node on `localhost`, but to the worker node on [`TestInstanceOptions::hostname`]; tests that
+/// download datasets or results should thus run on a machine that resolves it.
+#[derive(Debug)]
+pub struct TestInstance {
+    /// The options with which we launched.
+    opts: TestInstanceOptions,
+    /// The temporary directory in which the instance lives.
+    dir:  PathBuf,
+}
+impl TestInstance {
+    /// Generates the configuration of both nodes, starts them and registers the instance with the CLI.
+    ///
+    /// # Arguments
+    /// - `opts`: The [`TestInstanceOptions`] that determine how to launch the instance.
+    ///
+    /// # Returns
+    /// A new TestInstance that is online.
+    ///
+    /// # Errors
+    /// This function errors if we failed to generate the configuration, if any of the services did not come online or if we failed to
+    /// register the instance with the CLI.
+    pub fn launch(opts: TestInstanceOptions) -> Result<Self, Error> {
+        let dir: PathBuf = match tempfile::Builder::new().prefix("brane-test-").tempdir() {
+            Ok(dir) => dir.into_path(),
+            Err(err) => return Err(Error::TempDirError { err }),
+        };
+        info!("Launching test instance in '{}'...", dir.display());
+        let this: Self = Self { opts, dir };
+
+        // Generate the nodes, worker first so the central node can trust its certificates
+        this.generate_worker()?;
+        this.generate_central()?;
+
+        // Start them
+        for node in ["worker", "central"] {
+            let mut cmd: Command = this.branectl(node);
+            cmd.arg("start").arg("--image-dir").arg(&this.opts.image_dir).arg(node);
+            run(cmd)?;
+        }
+        let base: u16 = this.opts.port_base;
+        wait_for("brane-api", base + 1, this.opts.startup_timeout)?;
+        wait_for("brane-plr", base + 2, this.opts.startup_timeout)?;
+        wait_for("brane-drv", base + 3, this.opts.startup_timeout)?;
+        wait_for("brane-reg", base + 11, this.opts.startup_timeout)?;
+        wait_for("brane-job", base + 12, this.opts.startup_timeout)?;
+
+        // Register it with the CLI
+        debug!("Registering instance with the CLI...");
+        let (api_port, drv_port): (String, String) = ((base + 1).to_string(), (base + 3).to_string());
+        let mut cmd: Command = this.brane();
+        cmd.args(["instance", "add", "localhost", "--api-port", &api_port, "--drv-port", &drv_port]);
+        cmd.args(["--name", INSTANCE_NAME, "--use", "--force"]);
+        run(cmd)?;
+        let certs: PathBuf = this.worker_dir().join("config").join("certs");
+        let mut cmd: Command = this.brane();
+        cmd.args(["certs", "add"]).arg(certs.join("ca.pem")).arg(certs.join("client-id.pem"));
+        cmd.args(["--instance", INSTANCE_NAME, "--domain", &this.opts.location_id, "--force"]);
+        run(cmd)?;
+
+        // Done
+        Ok(this)
+    }
+
+    /// Generates the configuration of the worker node.
+    ///
+    /// # Errors
+    /// This function errors if `branectl` failed to generate any of it.
+    fn generate_worker(&self) -> Result<(), Error> {
+        let dir: PathBuf = self.worker_dir();
+        let config: PathBuf = dir.join("config");
+        let certs: PathBuf = config.join("certs");
+        let base: u16 = self.opts.port_base + 10;
+
+        // Generate the certificates, both for the node itself and for the users that connect to it
+        let mut cmd: Command = self.branectl("worker");
+        cmd.args(["generate", "certs", "--fix-dirs", "--path"]).arg(&certs).arg("--temp-dir").arg(&self.dir).args([
+            "server",
+            &self.opts.location_id,
+            "--hostname",
+            &self.opts.hostname,
+        ]);
+        run(cmd)?;
+        let mut cmd: Command = self.branectl("worker");
+        cmd.args(["generate", "certs", "--fix-dirs", "--path"])
+            .arg(&certs)
+            .arg("--temp-dir")
+            .arg(&self.dir)
+            .args(["client", &self.opts.user, "--hostname", &self.opts.hostname, "--ca-cert"])
+            .arg(certs.join("ca.pem"))
+            .arg("--ca-key")
+            .arg(certs.join("ca-key.pem"));
+        run(cmd)?;
+
+        // Generate the other config files
+        let mut cmd: Command = self.branectl("worker");
+        cmd.args(["generate", "backend", "--fix-dirs", "--path"]).arg(config.join("backend.yml")).arg("local");
+        run(cmd)?;
+        let mut cmd: Command = self.branectl("worker");
+        // NOTE: The outgoing range is published on the host, so it must not overlap with that of the central node
+        cmd.args(["generate", "proxy", "--fix-dirs", "--path"]).arg(config.join("proxy.yml")).args(["--outgoing-range", "4300-4399"]);
+        run(cmd)?;
+        for secret in ["policy_deliberation_secret.json", "policy_expert_secret.json"] {
+            let mut cmd: Command = self.branectl("worker");
+            cmd.args(["generate", "policy_secret", "--fix-dirs", "--path"]).arg(config.join(secret));
+            run(cmd)?;
+        }
+        let mut cmd: Command = self.branectl("worker");
+        cmd.args(["generate", "policy_db", "--fix-dirs", "--path"]).arg(dir.join("policies.db"));
+        run(cmd)?;
+
+        // Finally, generate the node.yml itself
+        let mut cmd: Command = self.branectl("worker");
+        cmd.args(["generate", "node", "--fix-dirs", "--config-path"])
+            .arg(&config)
+            .args(["--hosts", &format!("{}:{}", self.opts.hostname, self.opts.host_ip)])
+            .args(["worker", &self.opts.hostname, &self.opts.location_id])
+            .args(["--use-cases", &format!("central=http://{}:{}", self.opts.hostname, self.opts.port_base + 1)])
+            .arg("--policy-database")
+            .arg(dir.join("policies.db"))
+            .arg("--packages")
+            .arg(dir.join("packages"))
+            .arg("--data")
+            .arg(dir.join("data"))
+            .arg("--results")
+            .arg(dir.join("results"))
+            .arg("--temp-data")
+            .arg(dir.join("tmp").join("data"))
+            .arg("--temp-results")
+            .arg(dir.join("tmp").join("results"))
+            .args(["--prx-port", &base.to_string()])
+            .args(["--reg-port", &(base + 1).to_string()])
+            .args(["--job-port", &(base + 2).to_string()])
+            .args(["--chk-port", &(base + 3).to_string()]);
+        run(cmd)?;
+        Ok(())
+    }
+
+    /// Generates the configuration of the central node.
+    ///
+    /// Assumes that the worker node has already been generated.
+    ///
+    /// # Errors
+    /// This function errors if `branectl` failed to generate any of it, or if we failed to copy the worker's certificates.
+    fn generate_central(&self) -> Result<(), Error> {
+        let dir: PathBuf = self.central_dir();
+        let config: PathBuf = dir.join("config");
+        let base: u16 = self.opts.port_base;
+
+        // Trust the worker, and let the central node authenticate as our user
+        let worker_certs: PathBuf = self.worker_dir().join("config").join("certs");
+        let certs: PathBuf = config.join("certs").join(&self.opts.location_id);
+        mkdir(&certs)?;
+        copy(worker_certs.join("ca.pem"), certs.join("ca.pem"))?;
+        copy(worker_certs.join("client-id.pem"), certs.join("client-id.pem"))?;
+
+        // Generate the other config files
+        let mut cmd: Command = self.branectl("central");
+        cmd.args(["generate", "infra", "--fix-dirs", "--path"]).arg(config.join("infra.yml")).args([
+            &format!("{}:{}", self.opts.location_id, self.opts.hostname),
+            "--reg-port",
+            &format!("{}={}", self.opts.location_id, base + 11),
+            "--job-port",
+            &format!("{}={}", self.opts.location_id, base + 12),
+        ]);
+        run(cmd)?;
+        let mut cmd: Command = self.branectl("central");
+        cmd.args(["generate", "proxy", "--fix-dirs", "--path"]).arg(config.join("proxy.yml"));
+        run(cmd)?;
+
+        // Finally, generate the node.yml itself
+        let mut cmd: Command = self.branectl("central");
+        cmd.args(["generate", "node", "--fix-dirs", "--config-path"])
+            .arg(&config)
+            .args(["--hosts", &format!("{}:{}", self.opts.hostname, self.opts.host_ip)])
+            .args(["central", &self.opts.hostname])
+            .arg("--packages")
+            .arg(dir.join("packages"))
+            .args(["--prx-port", &base.to_string()])
+            .args(["--api-port", &(base + 1).to_string()])
+            .args(["--plr-port", &(base + 2).to_string()])
+            .args(["--drv-port", &(base + 3).to_string()]);
+        run(cmd)?;
+        Ok(())
+    }
+
+    /// Registers a fixture package with the instance, by building it with the CLI and then pushing it.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the package's directory in `tests/packages` (e.g., `hello-world`).
+    ///
+    /// # Errors
+    /// This function errors if the fixture does not exist or if we failed to build or push it.
+    pub fn register_package(&self, name: impl AsRef<str>) -> Result<(), Error> {
+        let path: PathBuf = fixtures::package(name.as_ref()).join("container.yml");
+        if !path.is_file() {
+            return Err(Error::UnknownFixture { what: "package", path });
+        }
+        let info: ContainerInfo = match ContainerInfo::from_path(&path) {
+            Ok(info) => info,
+            Err(err) => return Err(Error::ContainerInfoError { path, err }),
+        };
+        info!("Registering package '{}' ({}) with test instance...", info.name, info.version);
+
+        let mut cmd: Command = self.brane();
+        cmd.args(["package", "build"]).arg(&path);
+        run(cmd)?;
+        self.run_brane(["package", "push", &format!("{}:{}", info.name, info.version)])?;
+        Ok(())
+    }
+
+    /// Registers a fixture dataset with the worker node, by copying it to the worker's data directory.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the dataset's directory in `tests/data` (e.g., `numbers`). It must contain a `data.yml` file.
+    ///
+    /// # Errors
+    /// This function errors if the fixture does not exist or if we failed to copy it.
+    pub fn register_dataset(&self, name: impl AsRef<str>) -> Result<(), Error> {
+        let name: &str = name.as_ref();
+        let path: PathBuf = fixtures::dataset(name);
+        if !path.join("data.yml").is_file() {
+            return Err(Error::UnknownFixture { what: "dataset", path });
+        }
+        info!("Registering dataset '{name}' with test instance...");
+        copy_dir(&path, &self.worker_dir().join("data").join(name))
+    }
+
+    /// Runs a workflow on the instance.
+    ///
+    /// # Arguments
+    /// - `path`: The path to the BraneScript file to run (see [`fixtures::workflow()`]).
+    ///
+    /// # Returns
+    /// Whatever the workflow printed.
+    ///
+    /// # Errors
+    /// This function errors if the workflow failed.
+    pub fn run_workflow(&self, path: impl AsRef<Path>) -> Result<String, Error> {
+        let mut cmd: Command = self.brane();
+        cmd.args(["run", "--remote"]).arg(path.as_ref());
+        run(cmd)
+    }
+
+    /// Returns a [`Command`] that runs `branectl` on one of the nodes.
+    ///
+    /// # Arguments
+    /// - `node`: Either `central` or `worker`.
+    ///
+    /// # Returns
+    /// A new Command, with the path to the node's `node.yml` already given.
+    pub fn branectl(&self, node: &str) -> Command {
+        let mut cmd: Command = Command::new(&self.opts.branectl);
+        cmd.arg("--node-config").arg(self.dir.join(node).join("node.yml"));
+        cmd
+    }
+
+    /// Returns a [`Command`] that runs the `brane` CLI connected to this instance.
+    ///
+    /// # Returns
+    /// A new Command that uses the instance's own CLI configuration (and thus doesn't touch that of the user running the tests).
+    pub fn brane(&self) -> Command {
+        let mut cmd: Command = Command::new(&self.opts.brane);
+        cmd.env("XDG_CONFIG_HOME", self.dir.join("cli").join("config")).env("XDG_DATA_HOME", self.dir.join("cli").join("data"));
+        cmd
+    }
+
+    /// Runs the `brane` CLI connected to this instance.
+    ///
+    /// # Arguments
+    /// - `args`: The arguments to give to the CLI.
+    ///
+    /// # Returns
+    /// The CLI's stdout.
+    ///
+    /// # Errors
+    /// This function errors if we failed to run the CLI or if it returned a non-zero exit code.
+    pub fn run_brane<I, S>(&self, args: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd: Command = self.brane();
+        cmd.args(args);
+        run(cmd)
+    }
+
+    /// Returns the address of the central node's API service.
+    #[inline]
+    pub fn api_address(&self) -> String { format!("http://localhost:{}", self.opts.port_base + 1) }
+
+    /// Returns the address of the central node's planner service.
+    #[inline]
+    pub fn planner_address(&self) -> String { format!("http://localhost:{}", self.opts.port_base + 2) }
+
+    /// Returns the address of the central node's driver service.
+    #[inline]
+    pub fn driver_address(&self) -> String { format!("grpc://localhost:{}", self.opts.port_base + 3) }
+
+    /// Returns the location ID of the worker node.
+    #[inline]
+    pub fn location_id(&self) -> &str { &self.opts.location_id }
+
+    /// Returns the directory that contains the central node's `node.yml` and other files.
+    #[inline]
+    pub fn central_dir(&self) -> PathBuf { self.dir.join("central") }
+
+    /// Returns the directory that contains the worker node's `node.yml` and other files.
+    #[inline]
+    pub fn worker_dir(&self) -> PathBuf { self.dir.join("worker") }
+}
+impl Drop for TestInstance {
+    fn drop(&mut self) {
+        if self.opts.keep {
+            info!("Keeping test instance in '{}'", self.dir.display());
+            return;
+        }
+
+        // Stop the nodes (if they were started at all)
+        for node in ["central", "worker"] {
+            if !self.dir.join(node).join("node.yml").exists() {
+                continue;
+            }
+            let mut cmd: Command = self.branectl(node);
+            cmd.arg("stop");
+            if let Err(err) = run(cmd) {
+                warn!("Failed to stop {node} node of test instance: {err}");
+            }
+        }
+
+        // Remove the files
+        if let Err(err) = fs::remove_dir_all(&self.dir) {
+            warn!("Failed to remove test instance directory '{}': {}", self.dir.display(), err);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:33:01
+//  Last edited:
+//    15 Oct 2026, 21:33:01
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   The `brane-test` crate provides a harness for end-to-end tests that
+//!   launches a full (central + worker) instance on the local machine.
+//
+
+// Declare modules
+pub mod errors;
+pub mod fixtures;
+pub mod instance;
@@ -0,0 +1,15 @@
+use brane_test::fixtures;
+use brane_test::instance::{TestInstance, TestInstanceOptions};
+
+#[test]
+#[ignore = "needs Docker, the service images and the `branectl` and `brane` executables"]
+fn hello_world_runs_on_worker() {
+    let instance = TestInstance::launch(TestInstanceOptions::from_env()).unwrap();
+    instance.register_package("hello-world").unwrap();
+
+    let output: String = instance.run_workflow(fixtures::workflow("hello_world")).unwrap();
+    assert!(output.contains("Hello, world!"), "unexpected workflow output: {output}");
+
+    // Without the package, the workflow may not even be planned
+    assert!(instance.run_workflow(fixtures::workflow("average")).is_err());
+}
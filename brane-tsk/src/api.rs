@@ -4,7 +4,7 @@
 //  Created:
 //    26 Sep 2022, 12:15:06
 //  Last edited:
-//    01 Mar 2023, 10:58:29
+//    16 Oct 2026, 00:40:59
 //  Auto updated?
 //    Yes
 //
@@ -16,12 +16,14 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use graphql_client::{GraphQLQuery, Response};
+use log::debug;
 use reqwest::Client;
 use specifications::common::{Function, Type};
 use specifications::data::{DataIndex, DataInfo};
-use specifications::package::{PackageIndex, PackageInfo, PackageKind};
+use specifications::package::{PackageIndex, PackageIndexProvider, PackageInfo, PackageKind, PackageResources};
 use specifications::version::Version;
 use uuid::Uuid;
 
@@ -36,29 +38,27 @@ pub type DateTimeUtc = DateTime<Utc>;
 
 
 
-/***** LIBRARY *****/
-/// Downloads the current package index from the Brane API service.
+/***** HELPER FUNCTIONS *****/
+/// Downloads (some of) the packages known to the Brane API service.
 ///
 /// # Arguments
 /// - `endpoint`: The endpoint to send the request to.
+/// - `name`: If given, only downloads the versions of the package with this name.
 ///
 /// # Returns
-/// The PackageIndex that represents the packages currently known to the instance at the time of the call.
+/// The PackageInfos of the packages currently known to the instance at the time of the call.
 ///
 /// # Errors
 /// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
-pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
+async fn get_packages(endpoint: &str, name: Option<&str>) -> Result<Vec<PackageInfo>, Error> {
     // Load up the query
     #[derive(GraphQLQuery)]
     #[graphql(schema_path = "graphql/api_schema.json", query_path = "graphql/get_packages.graphql", response_derives = "Debug")]
     pub struct GetPackages;
 
-    // Resolve &str-like to &str
-    let endpoint: &str = endpoint.as_ref();
-
-    // Start preparing the client to send the GraphQL request
+    // Start preparing the client to send the GraphQL request. Filter on the name as a search term too, such that the database does that work.
     let client = Client::new();
-    let variables = get_packages::Variables {};
+    let variables = get_packages::Variables { name: name.map(String::from), term: name.map(String::from) };
     let graphql_query = GetPackages::build_query(variables);
 
     // Request/response for GraphQL query.
@@ -95,6 +95,7 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
         // Parse some elements of the PackageInfo
         let functions: HashMap<String, Function> = p.functions_as_json.map(|f| serde_json::from_str(&f).unwrap()).unwrap_or_default();
         let types: HashMap<String, Type> = p.types_as_json.map(|t| serde_json::from_str(&t).unwrap()).unwrap_or_default();
+        let resources: Option<PackageResources> = p.resources_as_json.map(|r| serde_json::from_str(&r).unwrap());
         let kind: PackageKind = match PackageKind::from_str(&p.kind) {
             Ok(kind) => kind,
             Err(err) => {
@@ -113,20 +114,46 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
             created: p.created,
             id: p.id,
             digest: p.digest,
+            oci: p.oci,
 
             name: p.name,
             version,
             kind,
             owners: p.owners,
             description: p.description.unwrap_or_default(),
+            license: p.license,
+            maintainers: p.maintainers,
+            homepage: p.homepage,
+            resources,
+            tags: p.tags,
+            scan: None,
 
             detached: p.detached,
             functions,
             types,
         });
     }
+    Ok(infos)
+}
+
+
 
-    // Now parse it to an index
+
+
+/***** LIBRARY *****/
+/// Downloads the current package index from the Brane API service.
+///
+/// # Arguments
+/// - `endpoint`: The endpoint to send the request to.
+///
+/// # Returns
+/// The PackageIndex that represents the packages currently known to the instance at the time of the call.
+///
+/// # Errors
+/// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
+pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
+    let endpoint: &str = endpoint.as_ref();
+    let infos: Vec<PackageInfo> = get_packages(endpoint, None).await?;
     match PackageIndex::from_packages(infos) {
         Ok(index) => Ok(index),
         Err(err) => Err(Error::PackageIndexError { address: endpoint.into(), err }),
@@ -177,3 +204,39 @@ pub async fn get_data_index(endpoint: impl AsRef<str>) -> Result<DataIndex, Erro
         Err(err) => Err(Error::DataIndexError { address: endpoint.into(), err }),
     }
 }
+
+
+
+/// Resolves packages on demand from the Brane API service, instead of downloading all of them at once (see [`get_package_index()`]).
+///
+/// Use it with a [`PackageIndexCache`](crate::caches::PackageIndexCache) to only download the packages that a workflow imports.
+#[derive(Clone, Debug)]
+pub struct RemotePackageProvider {
+    /// The GraphQL endpoint of the Brane API service.
+    endpoint: String,
+}
+impl RemotePackageProvider {
+    /// Constructor for the RemotePackageProvider.
+    ///
+    /// # Arguments
+    /// - `endpoint`: The GraphQL endpoint (i.e., `<API>/graphql`) to send the requests to.
+    ///
+    /// # Returns
+    /// A new RemotePackageProvider.
+    #[inline]
+    pub fn new(endpoint: impl Into<String>) -> Self { Self { endpoint: endpoint.into() } }
+}
+#[async_trait]
+impl PackageIndexProvider for RemotePackageProvider {
+    type Error = Error;
+
+    async fn packages(&self, name: &str) -> Result<Vec<PackageInfo>, Self::Error> {
+        debug!("Fetching package '{}' from '{}'...", name, self.endpoint);
+        get_packages(&self.endpoint, Some(name)).await
+    }
+
+    async fn all_packages(&self) -> Result<Vec<PackageInfo>, Self::Error> {
+        debug!("Fetching all packages from '{}'...", self.endpoint);
+        get_packages(&self.endpoint, None).await
+    }
+}
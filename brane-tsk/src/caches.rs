@@ -4,7 +4,7 @@
 //  Created:
 //    31 Jan 2024, 11:45:19
 //  Last edited:
-//    31 Jan 2024, 14:24:26
+//    15 Oct 2026, 23:57:03
 //  Auto updated?
 //    Yes
 //
@@ -13,24 +13,43 @@
 //!   time.
 //
 
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use brane_ast::locations::Location;
+use brane_ast::{ParserOptions, Workflow};
+use brane_exe::pc::ProgramCounter;
+use brane_exe::FullValue;
 use brane_shr::formatters::BlockFormatter;
-use log::debug;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
 use num_traits::AsPrimitive;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
-use reqwest::{Response, StatusCode};
+use reqwest::{header, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest as _, Sha256};
 use specifications::address::Address;
+use specifications::checking::POLICY_API_GET_ACTIVE_VERSION;
+use specifications::data::DataName;
+use specifications::package::{PackageIndex, PackageIndexProvider, PackageInfo};
+use specifications::version::Version;
+use tokio::fs as tfs;
 
 
 /***** CONSTANTS *****/
 /// The default timeout (in seconds) of entries in the [`DomainRegistryCache`].
 pub const DEFAULT_DOMAIN_REGISTRY_CACHE_TIMEOUT: u64 = 6 * 3600;
 
+/// The default timeout (in seconds) of entries in the [`VerdictCache`].
+pub const DEFAULT_VERDICT_CACHE_TIMEOUT: u64 = 3600;
+
 
 
 
@@ -84,6 +103,141 @@ impl Error for DomainRegistryCacheError {
     }
 }
 
+/// Defines errors originating in the [`VerdictCache`].
+#[derive(Debug)]
+pub enum VerdictCacheError {
+    /// Failed to generate a token for the checker's management API.
+    TokenGenerate { secret: PathBuf, err: specifications::policy::Error },
+    /// Failed to send a request to the given URL.
+    RequestSend { kind: &'static str, url: String, err: reqwest::Error },
+    /// Failed to download the body of the given response.
+    ResponseDownload { url: String, err: reqwest::Error },
+    /// The response was not an OK
+    ResponseFailure { url: String, code: StatusCode, response: Option<String> },
+    /// Failed to parse the response of the checker.
+    ResponseParse { url: String, raw: String, err: serde_json::Error },
+}
+impl Display for VerdictCacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use VerdictCacheError::*;
+        match self {
+            TokenGenerate { secret, .. } => write!(f, "Failed to generate new JWT using secret '{}'", secret.display()),
+            RequestSend { kind, url, .. } => write!(f, "Failed to send {kind}-request to '{url}'"),
+            ResponseDownload { url, .. } => write!(f, "Failed to download body of response from '{url}'"),
+            ResponseFailure { url, code, response } => write!(
+                f,
+                "Request to '{}' failed with {} ({}){}",
+                url,
+                code.as_u16(),
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(response) = response { format!("\n\nResponse:\n{}\n", BlockFormatter::new(response)) } else { String::new() }
+            ),
+            ResponseParse { url, raw, .. } => {
+                write!(f, "Failed to parse response from '{}' as a policy\n\nResponse:\n{}\n", url, BlockFormatter::new(raw))
+            },
+        }
+    }
+}
+impl Error for VerdictCacheError {
+    fn source(&self) -> Option<&(dyn 'static + Error)> {
+        use VerdictCacheError::*;
+        match self {
+            TokenGenerate { err, .. } => Some(err),
+            RequestSend { err, .. } => Some(err),
+            ResponseDownload { err, .. } => Some(err),
+            ResponseFailure { .. } => None,
+            ResponseParse { err, .. } => Some(err),
+        }
+    }
+}
+
+/// Defines errors originating in the [`ResultCache`].
+#[derive(Debug)]
+pub enum ResultCacheError {
+    /// Failed to create the directory with the cache entries.
+    DirCreate { path: PathBuf, err: std::io::Error },
+    /// Failed to read a cache entry.
+    EntryRead { path: PathBuf, err: std::io::Error },
+    /// Failed to parse a cache entry.
+    EntryParse { path: PathBuf, err: serde_json::Error },
+    /// Failed to serialize a cache entry.
+    EntrySerialize { err: serde_json::Error },
+    /// Failed to write a cache entry.
+    EntryWrite { path: PathBuf, err: std::io::Error },
+    /// Failed to link a cached result to the place where it is expected.
+    ResultLink { path: PathBuf, target: PathBuf, err: std::io::Error },
+}
+impl Display for ResultCacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ResultCacheError::*;
+        match self {
+            DirCreate { path, .. } => write!(f, "Failed to create result cache directory '{}'", path.display()),
+            EntryRead { path, .. } => write!(f, "Failed to read result cache entry '{}'", path.display()),
+            EntryParse { path, .. } => write!(f, "Failed to parse result cache entry '{}'", path.display()),
+            EntrySerialize { .. } => write!(f, "Failed to serialize result cache entry"),
+            EntryWrite { path, .. } => write!(f, "Failed to write result cache entry '{}'", path.display()),
+            ResultLink { path, target, .. } => write!(f, "Failed to link cached result '{}' to '{}'", target.display(), path.display()),
+        }
+    }
+}
+impl Error for ResultCacheError {
+    fn source(&self) -> Option<&(dyn 'static + Error)> {
+        use ResultCacheError::*;
+        match self {
+            DirCreate { err, .. } => Some(err),
+            EntryRead { err, .. } => Some(err),
+            EntryParse { err, .. } => Some(err),
+            EntrySerialize { err } => Some(err),
+            EntryWrite { err, .. } => Some(err),
+            ResultLink { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// The parts of the active policy on a checker that we are interested in.
+#[derive(Debug, Deserialize)]
+struct ActivePolicy {
+    /// The version information of the policy.
+    version: ActivePolicyVersion,
+}
+
+/// The version information of the active policy on a checker.
+#[derive(Debug, Deserialize)]
+struct ActivePolicyVersion {
+    /// The version number of the policy.
+    version: Option<i64>,
+}
+
+/// An entry in the [`ResultCache`], as stored on disk.
+#[derive(Debug, Deserialize, Serialize)]
+struct ResultCacheEntry {
+    /// The moment the call that produced this entry finished.
+    created: DateTime<Utc>,
+    /// The value returned by the call.
+    value:   FullValue,
+    /// The stored object (see [`brane_shr::fs::deduplicate_dir_async()`]) with the intermediate result produced by the call, if any.
+    object:  Option<String>,
+}
+
+/// The verdicts in the [`VerdictCache`], together with when they were cached.
+type VerdictMap = HashMap<VerdictKey, (Option<Vec<String>>, Instant)>;
+
+/// The packages resolved by a [`PackageIndexCache`] so far.
+#[derive(Debug, Default)]
+struct ResolvedPackages {
+    /// The index with the resolved packages. It is shared with the compilations using it, and copied when new packages are added meanwhile.
+    index:    Arc<PackageIndex>,
+    /// The names of the packages that were resolved, including those that the provider did not know.
+    names:    HashSet<String>,
+    /// Whether all packages of the provider were resolved.
+    complete: bool,
+}
+
 
 
 
@@ -196,3 +350,459 @@ impl DomainRegistryCache {
         }
     }
 }
+
+
+
+/// Identifies the question asked to a checker about a workflow.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CheckQuestion {
+    /// May the workflow as a whole be executed?
+    Workflow,
+    /// May the task called at the given program counter be executed?
+    Task(ProgramCounter),
+    /// May the given dataset or intermediate result be accessed, in the context of the task called at the given program counter (if any)?
+    Data(DataName, Option<ProgramCounter>),
+}
+
+/// Identifies a verdict in the [`VerdictCache`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VerdictKey {
+    /// The hash of the workflow that the question is about, excluding its (per-execution) identifier.
+    workflow: u64,
+    /// The use-case in which the question was asked.
+    use_case: String,
+    /// The question asked.
+    question: CheckQuestion,
+    /// The version of the policy that was active when the question was asked, if any.
+    policy:   Option<i64>,
+}
+
+
+
+/// A cache for storing the verdicts of a checker, such that repeatedly executed workflows don't require the checker to reason about every task
+/// again.
+///
+/// Verdicts are keyed by the workflow, the question asked about it and the active policy version. The cache is cleared whenever it finds that
+/// another policy version has been activated on the checker.
+#[derive(Debug)]
+pub struct VerdictCache {
+    /// The timeout to that determines after how long entries in the map become stale.
+    timeout: u64,
+    /// The policy version for which the cached verdicts are valid, and the verdicts themselves.
+    ///
+    /// Verdicts are [`None`] if the checker allowed the question, or else carry the reasons for denial (if shared by the checker).
+    data:    RwLock<(Option<i64>, VerdictMap)>,
+}
+impl Default for VerdictCache {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+impl VerdictCache {
+    /// Constructor for the VerdictCache that uses the default timeout.
+    ///
+    /// See [`DEFAULT_VERDICT_CACHE_TIMEOUT`] to find what the current default is.
+    ///
+    /// # Returns
+    /// A new VerdictCache instance.
+    #[inline]
+    pub fn new() -> Self { Self::with_timeout(DEFAULT_VERDICT_CACHE_TIMEOUT) }
+
+    /// Constructor for the VerdictCache.
+    ///
+    /// # Arguments
+    /// - `timeout`: A timeout (in seconds) that determines after how long entries in the cache become stale.
+    ///
+    /// # Returns
+    /// A new VerdictCache instance.
+    #[inline]
+    pub fn with_timeout(timeout: impl AsPrimitive<u64>) -> Self {
+        Self { timeout: timeout.as_(), data: RwLock::new((None, HashMap::with_capacity(64))) }
+    }
+
+    /// Retrieves the currently active policy version from the checker, and clears the cache if it has changed since the last time.
+    ///
+    /// # Arguments
+    /// - `checker`: The address of the checker to query.
+    /// - `secret`: The path to the secret used for the checker's policy expert (management) API.
+    ///
+    /// # Returns
+    /// The currently active policy version, or [`None`] if no policy is active.
+    ///
+    /// # Errors
+    /// This function errors if we failed to query the checker.
+    pub async fn sync(&self, checker: &Address, secret: &Path) -> Result<Option<i64>, VerdictCacheError> {
+        // Fetch the active policy
+        let jwt: String = match specifications::policy::generate_policy_token("brane", "verdict-cache", Duration::from_secs(60), secret) {
+            Ok(jwt) => jwt,
+            Err(err) => return Err(VerdictCacheError::TokenGenerate { secret: secret.into(), err }),
+        };
+        let url: String = format!("{}/{}", checker, POLICY_API_GET_ACTIVE_VERSION.1);
+        debug!("Sending GET-request to '{url}'...");
+        let res: Response = match reqwest::Client::new()
+            .request(POLICY_API_GET_ACTIVE_VERSION.0, &url)
+            .header(header::AUTHORIZATION, format!("Bearer {jwt}"))
+            .send()
+            .await
+        {
+            Ok(res) => res,
+            Err(err) => return Err(VerdictCacheError::RequestSend { kind: "GET", url, err }),
+        };
+        let version: Option<i64> = match res.status() {
+            StatusCode::NOT_FOUND => None,
+            code if code.is_success() => {
+                let raw: String = match res.text().await {
+                    Ok(raw) => raw,
+                    Err(err) => return Err(VerdictCacheError::ResponseDownload { url, err }),
+                };
+                match serde_json::from_str::<ActivePolicy>(&raw) {
+                    Ok(policy) => policy.version.version,
+                    Err(err) => return Err(VerdictCacheError::ResponseParse { url, raw, err }),
+                }
+            },
+            code => return Err(VerdictCacheError::ResponseFailure { url, code, response: res.text().await.ok() }),
+        };
+
+        // Invalidate the cache if it's another version
+        let mut lock: RwLockWriteGuard<(Option<i64>, VerdictMap)> = self.data.write();
+        if lock.0 != version {
+            debug!(
+                "Active policy changed from {} to {}; invalidating {} cached verdict(s)",
+                lock.0.map(|v| v.to_string()).unwrap_or_else(|| "<none>".into()),
+                version.map(|v| v.to_string()).unwrap_or_else(|| "<none>".into()),
+                lock.1.len()
+            );
+            lock.0 = version;
+            lock.1.clear();
+        }
+        Ok(version)
+    }
+
+    /// Builds the key of a question under the policy that is currently active on the checker.
+    ///
+    /// This synchronizes the cache with the checker (see [`VerdictCache::sync()`]). If that fails, the cache is bypassed.
+    ///
+    /// # Arguments
+    /// - `checker`: The address of the checker to query.
+    /// - `secret`: The path to the secret used for the checker's policy expert (management) API.
+    /// - `use_case`: The use-case in which the question is asked.
+    /// - `workflow`: The [`Workflow`] that the question is about.
+    /// - `question`: The [`CheckQuestion`] asked.
+    ///
+    /// # Returns
+    /// A [`VerdictKey`] to use with [`VerdictCache::get()`] and [`VerdictCache::insert()`], or [`None`] if we failed to find the active policy
+    /// (in which case the checker should simply be asked).
+    pub async fn key(&self, checker: &Address, secret: &Path, use_case: &str, workflow: &Workflow, question: CheckQuestion) -> Option<VerdictKey> {
+        let policy: Option<i64> = match self.sync(checker, secret).await {
+            Ok(policy) => policy,
+            Err(err) => {
+                warn!("Not using cached checker verdicts: {err}");
+                return None;
+            },
+        };
+
+        // Hash the workflow without its ID, which differs between executions of the same workflow
+        let mut value: Value = match serde_json::to_value(workflow) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Not using cached checker verdicts: failed to serialize workflow: {err}");
+                return None;
+            },
+        };
+        if let Value::Object(map) = &mut value {
+            map.remove("id");
+        }
+        let mut hasher = DefaultHasher::new();
+        value.to_string().hash(&mut hasher);
+
+        Some(VerdictKey { workflow: hasher.finish(), use_case: use_case.into(), question, policy })
+    }
+
+    /// Returns a cached verdict.
+    ///
+    /// # Arguments
+    /// - `key`: The [`VerdictKey`] of the verdict to find.
+    ///
+    /// # Returns
+    /// [`None`] if there is no (valid) cached verdict, or else the verdict itself, which is [`None`] if the checker allowed the question and
+    /// otherwise carries the reasons for denial.
+    pub fn get(&self, key: &VerdictKey) -> Option<Option<Vec<String>>> {
+        let lock: RwLockReadGuard<(Option<i64>, VerdictMap)> = self.data.read();
+        match lock.1.get(key) {
+            Some((verdict, cached)) if cached.elapsed() < Duration::from_secs(self.timeout) => {
+                debug!("Found valid cached verdict for {:?}", key.question);
+                Some(verdict.clone())
+            },
+            _ => None,
+        }
+    }
+
+    /// Caches a verdict of the checker.
+    ///
+    /// The verdict is dropped if another policy has been activated in the meantime.
+    ///
+    /// # Arguments
+    /// - `key`: The [`VerdictKey`] of the question answered by the checker.
+    /// - `verdict`: The verdict itself, which is [`None`] if the checker allowed the question and otherwise carries the reasons for denial.
+    pub fn insert(&self, key: VerdictKey, verdict: Option<Vec<String>>) {
+        let mut lock: RwLockWriteGuard<(Option<i64>, VerdictMap)> = self.data.write();
+        if lock.0 != key.policy {
+            return;
+        }
+        let timeout: Duration = Duration::from_secs(self.timeout);
+        lock.1.retain(|_, (_, cached)| cached.elapsed() < timeout);
+        lock.1.insert(key, (verdict, Instant::now()));
+    }
+}
+
+
+
+/// A cache for the packages that a [`PackageIndexProvider`] resolves, such that clients only fetch the packages that their workflows import (and
+/// only once).
+#[derive(Debug)]
+pub struct PackageIndexCache<P> {
+    /// The provider to resolve unknown packages with.
+    provider: P,
+    /// The packages resolved so far.
+    data:     RwLock<ResolvedPackages>,
+}
+impl<P: PackageIndexProvider> PackageIndexCache<P> {
+    /// Constructor for the PackageIndexCache.
+    ///
+    /// # Arguments
+    /// - `provider`: The [`PackageIndexProvider`] to resolve packages with.
+    ///
+    /// # Returns
+    /// A new PackageIndexCache instance that has not resolved any packages yet.
+    #[inline]
+    pub fn new(provider: P) -> Self { Self { provider, data: RwLock::new(ResolvedPackages::default()) } }
+
+    /// Returns the packages resolved so far.
+    ///
+    /// # Returns
+    /// A [`PackageIndex`] with the resolved packages.
+    #[inline]
+    pub fn index(&self) -> Arc<PackageIndex> { self.data.read().index.clone() }
+
+    /// Resolves the packages imported by the given snippet, such that it can be compiled.
+    ///
+    /// Packages resolved before are not fetched again. If the imports of the snippet cannot be determined because it does not parse, nothing is
+    /// resolved; the compiler will report the error instead.
+    ///
+    /// # Arguments
+    /// - `snippet`: The source text of the snippet to compile.
+    /// - `options`: The ParserOptions with which the snippet will be compiled.
+    ///
+    /// # Returns
+    /// A [`PackageIndex`] with (at least) the packages imported by the snippet, as far as the provider knows them.
+    ///
+    /// # Errors
+    /// This function errors if the provider failed to resolve the imported packages.
+    pub async fn resolve(&self, snippet: &str, options: &ParserOptions) -> Result<Arc<PackageIndex>, P::Error> {
+        let imports: Option<HashSet<String>> = match brane_ast::package_imports(snippet, options) {
+            Ok(imports) => imports,
+            Err(err) => {
+                debug!("Not resolving any packages for unparseable snippet: {err}");
+                return Ok(self.index());
+            },
+        };
+
+        // Find the packages we haven't resolved yet
+        let missing: Option<Vec<String>> = {
+            let lock: RwLockReadGuard<ResolvedPackages> = self.data.read();
+            if lock.complete {
+                return Ok(lock.index.clone());
+            }
+            imports.map(|imports| imports.into_iter().filter(|name| !lock.names.contains(name)).collect())
+        };
+        let packages: Vec<PackageInfo> = match &missing {
+            Some(missing) if missing.is_empty() => return Ok(self.index()),
+            Some(missing) => {
+                debug!("Resolving {} new package(s)...", missing.len());
+                let mut packages: Vec<PackageInfo> = vec![];
+                for name in missing {
+                    packages.extend(self.provider.packages(name).await?);
+                }
+                packages
+            },
+            None => {
+                debug!("Resolving all packages...");
+                self.provider.all_packages().await?
+            },
+        };
+
+        // Add them to the index (skipping any that a concurrent call has added already)
+        let mut lock: RwLockWriteGuard<ResolvedPackages> = self.data.write();
+        let index: &mut PackageIndex = Arc::make_mut(&mut lock.index);
+        for package in packages {
+            if let Err(err) = index.insert(package) {
+                debug!("Not caching package: {err}");
+            }
+        }
+        match missing {
+            Some(missing) => lock.names.extend(missing),
+            None => lock.complete = true,
+        }
+        Ok(lock.index.clone())
+    }
+}
+
+
+
+/// A cache for the results of task calls on a worker, such that calls marked as cacheable (see the `#[cache(...)]`-attribute in BraneScript) can
+/// reuse the result of an earlier, identical call instead of running again.
+///
+/// Entries are kept on disk, so they survive restarts of the worker. They do not contain the intermediate results themselves, but instead refer to
+/// the deduplicated objects that those results are stored as.
+#[derive(Clone, Debug)]
+pub struct ResultCache {
+    /// The directory with the cache entries.
+    dir: PathBuf,
+}
+impl ResultCache {
+    /// Constructor for the ResultCache.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory with the cache entries. It is created when the first entry is inserted.
+    ///
+    /// # Returns
+    /// A new ResultCache instance.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self { Self { dir: dir.into() } }
+
+    /// Computes the key under which the result of a call is cached.
+    ///
+    /// Two calls have the same key if they call the same function in the same container with the same arguments and inputs.
+    ///
+    /// # Arguments
+    /// - `package`: The name of the package that the called function belongs to.
+    /// - `version`: The version of the package that the called function belongs to.
+    /// - `digest`: The digest of the container that implements the package, if known.
+    /// - `function`: The name of the called function.
+    /// - `args`: The arguments that the function is called with.
+    /// - `input`: The datasets/intermediate results that are input to the call.
+    ///
+    /// # Returns
+    /// The key of the call, as a hex-encoded hash.
+    pub fn key<'i>(
+        package: &str,
+        version: &Version,
+        digest: Option<&str>,
+        function: &str,
+        args: &HashMap<String, FullValue>,
+        input: impl IntoIterator<Item = &'i DataName>,
+    ) -> String {
+        let mut hasher: Sha256 = Sha256::new();
+        for part in [package, version.to_string().as_str(), digest.unwrap_or(""), function] {
+            hasher.update(part.as_bytes());
+            hasher.update(b"\0");
+        }
+
+        // Hash the arguments in a deterministic order
+        let mut args: Vec<(&String, &FullValue)> = args.iter().collect();
+        args.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        for (name, value) in args {
+            hasher.update(name.as_bytes());
+            hasher.update(b"=");
+            hasher.update(serde_json::to_string(value).unwrap_or_default().as_bytes());
+            hasher.update(b"\0");
+        }
+
+        // Then the inputs, also in a deterministic order
+        let mut input: Vec<String> = input.into_iter().map(|name| format!("{name:?}")).collect();
+        input.sort();
+        for name in input {
+            hasher.update(name.as_bytes());
+            hasher.update(b"\0");
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Retrieves the result of an earlier call from the cache, if it is still fresh.
+    ///
+    /// If the call produced an intermediate result, it is linked to the given path; entries whose stored object has disappeared are ignored.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the call (see [`ResultCache::key()`]).
+    /// - `ttl`: The number of seconds for which an earlier result may be reused.
+    /// - `result`: If the call produces an intermediate result, the path where it is expected together with the directory with stored objects.
+    ///
+    /// # Returns
+    /// The value returned by the earlier call, or [`None`] if there is no fresh result to reuse.
+    ///
+    /// # Errors
+    /// This function errors if we failed to read or parse the entry, or to link the intermediate result.
+    pub async fn get(&self, key: &str, ttl: u64, result: Option<(&Path, &Path)>) -> Result<Option<FullValue>, ResultCacheError> {
+        // Attempt to read the entry
+        let path: PathBuf = self.dir.join(format!("{key}.json"));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw: String = match tfs::read_to_string(&path).await {
+            Ok(raw) => raw,
+            Err(err) => return Err(ResultCacheError::EntryRead { path, err }),
+        };
+        let entry: ResultCacheEntry = match serde_json::from_str(&raw) {
+            Ok(entry) => entry,
+            Err(err) => return Err(ResultCacheError::EntryParse { path, err }),
+        };
+
+        // Check if it's fresh enough
+        let age: i64 = (Utc::now() - entry.created).num_seconds();
+        if age < 0 || age as u64 >= ttl {
+            debug!("Cached result '{}' is {}s old, which exceeds its lifetime of {}s", key, age, ttl);
+            return Ok(None);
+        }
+
+        // Restore the intermediate result, if any
+        if let Some((result, objects)) = result {
+            let object: &str = match &entry.object {
+                Some(object) if objects.join(object).is_dir() => object,
+                _ => {
+                    warn!("Cached result '{}' refers to a stored object that no longer exists; ignoring it", key);
+                    return Ok(None);
+                },
+            };
+            let target: PathBuf = match (result.parent(), objects.parent(), objects.file_name()) {
+                (Some(parent), Some(objects_parent), Some(objects_name)) if parent == objects_parent => PathBuf::from(objects_name).join(object),
+                _ => objects.join(object),
+            };
+            if let Err(err) = tfs::symlink(&target, result).await {
+                return Err(ResultCacheError::ResultLink { path: result.into(), target, err });
+            }
+        }
+        Ok(Some(entry.value))
+    }
+
+    /// Stores the result of a call in the cache, overwriting any earlier result of the same call.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the call (see [`ResultCache::key()`]).
+    /// - `value`: The value returned by the call.
+    /// - `object`: The stored object with the intermediate result produced by the call, if any.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the cache directory or to write the entry.
+    pub async fn insert(&self, key: &str, value: FullValue, object: Option<String>) -> Result<(), ResultCacheError> {
+        if !self.dir.exists() {
+            if let Err(err) = tfs::create_dir_all(&self.dir).await {
+                return Err(ResultCacheError::DirCreate { path: self.dir.clone(), err });
+            }
+        }
+
+        // Write it to a temporary file first, such that concurrent readers never see half an entry
+        let entry: ResultCacheEntry = ResultCacheEntry { created: Utc::now(), value, object };
+        let raw: String = match serde_json::to_string(&entry) {
+            Ok(raw) => raw,
+            Err(err) => return Err(ResultCacheError::EntrySerialize { err }),
+        };
+        let part: PathBuf = self.dir.join(format!("{key}.json.part"));
+        if let Err(err) = tfs::write(&part, raw).await {
+            return Err(ResultCacheError::EntryWrite { path: part, err });
+        }
+        let path: PathBuf = self.dir.join(format!("{key}.json"));
+        if let Err(err) = tfs::rename(&part, &path).await {
+            return Err(ResultCacheError::EntryWrite { path, err });
+        }
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    19 Sep 2022, 14:57:17
 //  Last edited:
-//    08 Feb 2024, 15:15:18
+//    15 Oct 2026, 22:48:55
 //  Auto updated?
 //    Yes
 //
@@ -14,6 +14,7 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -21,10 +22,13 @@ use base64ct::{Base64, Encoding};
 use bollard::container::{
     Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions, StartContainerOptions, WaitContainerOptions,
 };
+use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
 use bollard::image::{CreateImageOptions, ImportImageOptions, RemoveImageOptions, TagImageOptions};
 use bollard::models::{DeviceRequest, EndpointSettings, HostConfig};
 pub use bollard::{Docker, API_DEFAULT_VERSION};
+use brane_cfg::backend::{ContainerRuntime, SandboxConfig};
 use brane_exe::FullValue;
+use brane_shr::remote::fetch_async;
 use enum_debug::EnumDebug;
 use futures_util::stream::TryStreamExt as _;
 use futures_util::StreamExt as _;
@@ -37,6 +41,7 @@ use sha2::{Digest, Sha256};
 use specifications::container::{Image, VolumeBind};
 use specifications::data::{AccessKind, DataName};
 use specifications::package::Capability;
+use specifications::working::OutputChannel;
 use tokio::fs::{self as tfs, File as TFile};
 use tokio::io::{self as tio, AsyncReadExt as _, AsyncWriteExt as _};
 use tokio_tar::Archive;
@@ -55,6 +60,14 @@ pub(crate) const MANIFEST_CONFIG_PREFIX: &str = "blobs/sha256/";
 /// This one is actually used in saved images.
 pub(crate) const MANIFEST_CONFIG_POSTFIX: &str = ".json";
 
+/// Defines the unprivileged user (`nobody:nogroup`) that containers are run as when rootless execution is enabled.
+pub(crate) const ROOTLESS_USER: &str = "65534:65534";
+
+/// Defines the command that idle containers run instead of branelet, which does nothing but keep them alive until a task is executed in them.
+pub(crate) const IDLE_COMMAND: [&str; 3] = ["tail", "-f", "/dev/null"];
+/// Defines the path of branelet in package containers, which we call directly when executing a task in an idle container.
+pub(crate) const BRANELET_PATH: &str = "/branelet";
+
 
 
 
@@ -419,6 +432,8 @@ pub struct ExecuteInfo {
     pub capabilities: HashSet<Capability>,
     /// The netwok to connect the container to.
     pub network: Network,
+    /// How strongly to isolate the container from the host. Defaults to no additional isolation.
+    pub sandbox: SandboxConfig,
 }
 impl ExecuteInfo {
     /// Constructor for the ExecuteInfo.
@@ -433,7 +448,7 @@ impl ExecuteInfo {
     /// - `network`: The netwok to connect the container to.
     ///
     /// # Returns
-    /// A new ExecuteInfo instance populated with the given values.
+    /// A new ExecuteInfo instance populated with the given values, and without any additional sandboxing.
     #[inline]
     pub fn new(
         name: impl Into<String>,
@@ -444,7 +459,16 @@ impl ExecuteInfo {
         capabilities: HashSet<Capability>,
         network: Network,
     ) -> Self {
-        ExecuteInfo { name: name.into(), image: image.into(), image_source: image_source.into(), command, binds, capabilities, network }
+        ExecuteInfo {
+            name: name.into(),
+            image: image.into(),
+            image_source: image_source.into(),
+            command,
+            binds,
+            capabilities,
+            network,
+            sandbox: SandboxConfig::default(),
+        }
     }
 }
 
@@ -541,6 +565,11 @@ fn preprocess_arg(
             // Replace the argument
             *value = FullValue::String(dst_dir.to_string_lossy().to_string());
         },
+
+        // Remote data has to be fetched (and thus turned into a file) by the caller
+        AccessKind::S3 { .. } | AccessKind::Url { .. } => {
+            return Err(ExecuteError::UnfetchedData { name: data_name });
+        },
     }
 
     // OK
@@ -554,13 +583,14 @@ fn preprocess_arg(
 /// # Arguments
 /// - `docker`: The Docker instance to use for accessing the container.
 /// - `info`: The ExecuteInfo describing what to launch and how.
+/// - `idle`: If true, runs the [`IDLE_COMMAND`] instead of branelet (ignoring the command in `info`), such that tasks can be executed in it later.
 ///
 /// # Returns
 /// The name of the container such that it can be waited on later.
 ///
 /// # Errors
 /// This function may error for many reasons, which usually means that the container failed to be created or started (wow!).
-async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Result<String, Error> {
+async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo, idle: bool) -> Result<String, Error> {
     // Generate unique (temporary) container name
     let container_name: String = format!("{}-{}", info.name, &uuid::Uuid::new_v4().to_string()[..6]);
     let create_options = CreateContainerOptions { name: &container_name, platform: None };
@@ -598,12 +628,24 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
         network_mode: Some(info.network.clone().into()),
         privileged: Some(false),
         device_requests: Some(device_requests),
+        runtime: if info.sandbox.runtime != ContainerRuntime::Runc { Some(info.sandbox.runtime.name().into()) } else { None },
+        cap_drop: if info.sandbox.rootless { Some(vec!["ALL".into()]) } else { None },
+        security_opt: if info.sandbox.rootless { Some(vec!["no-new-privileges".into()]) } else { None },
         ..Default::default()
     };
+    if info.sandbox.runtime != ContainerRuntime::Runc || info.sandbox.rootless {
+        debug!("Sandboxing container (runtime: {}, rootless: {})", info.sandbox.runtime, info.sandbox.rootless);
+    }
 
     // Create the container confic
-    let create_config =
-        Config { image: Some(info.image.name()), cmd: Some(info.command.clone()), host_config: Some(host_config), ..Default::default() };
+    let create_config = Config {
+        image: Some(info.image.name()),
+        entrypoint: if idle { Some(IDLE_COMMAND.iter().map(|arg| arg.to_string()).collect()) } else { None },
+        cmd: if idle { None } else { Some(info.command.clone()) },
+        user: if info.sandbox.rootless { Some(ROOTLESS_USER.into()) } else { None },
+        host_config: Some(host_config),
+        ..Default::default()
+    };
 
     // Run it with that config
     debug!("Launching container with name '{}' (image: {})...", info.name, info.image.name());
@@ -625,42 +667,50 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
 /// # Arguments
 /// - `docker`: The Docker instance to use for accessing the container.
 /// - `name`: The name of the container to wait on.
-/// - `image`: The image that was run (used for debugging).
 /// - `keep_container`: Whether to keep the container around after it's finished or not.
+/// - `on_output`: A callback that is called with every chunk of output the container writes, as it writes it.
 ///
 /// # Returns
 /// The return code of the docker container, its stdout and its stderr (in that order).
 ///
 /// # Errors
 /// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
-async fn join_container(docker: &Docker, name: &str, keep_container: bool) -> Result<(i32, String, String), Error> {
-    // Wait for the container to complete
-    if let Err(reason) = docker.wait_container(name, None::<WaitContainerOptions<String>>).try_collect::<Vec<_>>().await {
-        return Err(Error::WaitError { name: name.into(), err: reason });
-    }
-
-    // Get stdout and stderr logs from container
-    let logs_options = Some(LogsOptions::<String> { stdout: true, stderr: true, ..Default::default() });
-    let log_outputs = match docker.logs(name, logs_options).try_collect::<Vec<LogOutput>>().await {
-        Ok(out) => out,
-        Err(reason) => {
-            return Err(Error::LogsError { name: name.into(), err: reason });
-        },
-    };
-
-    // Collect them in one string per output channel
+async fn join_container<F, Fut>(docker: &Docker, name: &str, keep_container: bool, mut on_output: F) -> Result<(i32, String, String), Error>
+where
+    F: FnMut(OutputChannel, String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    // Follow the stdout and stderr logs of the container until it completes, collecting them in one string per output channel
+    let logs_options = Some(LogsOptions::<String> { follow: true, stdout: true, stderr: true, ..Default::default() });
+    let mut logs = docker.logs(name, logs_options);
     let mut stderr = String::new();
     let mut stdout = String::new();
-    for log_output in log_outputs {
+    while let Some(log_output) = logs.next().await {
         match log_output {
-            LogOutput::StdErr { message } => stderr.push_str(String::from_utf8_lossy(&message).as_ref()),
-            LogOutput::StdOut { message } => stdout.push_str(String::from_utf8_lossy(&message).as_ref()),
-            _ => {
+            Ok(LogOutput::StdErr { message }) => {
+                let chunk: String = String::from_utf8_lossy(&message).into();
+                stderr.push_str(&chunk);
+                on_output(OutputChannel::Stderr, chunk).await;
+            },
+            Ok(LogOutput::StdOut { message }) => {
+                let chunk: String = String::from_utf8_lossy(&message).into();
+                stdout.push_str(&chunk);
+                on_output(OutputChannel::Stdout, chunk).await;
+            },
+            Ok(_) => {
                 continue;
             },
+            Err(reason) => {
+                return Err(Error::LogsError { name: name.into(), err: reason });
+            },
         }
     }
 
+    // Wait for the container to complete (which it should have already if the logs have ended)
+    if let Err(reason) = docker.wait_container(name, None::<WaitContainerOptions<String>>).try_collect::<Vec<_>>().await {
+        return Err(Error::WaitError { name: name.into(), err: reason });
+    }
+
     // Get the container's exit status by inspecting it
     let code = returncode_container(docker, name).await?;
 
@@ -673,6 +723,82 @@ async fn join_container(docker: &Docker, name: &str, keep_container: bool) -> Re
     Ok((code, stdout, stderr))
 }
 
+/// Executes branelet with the given arguments in an idle container, and waits for it to complete.
+///
+/// The container is unpaused for the duration of the call, and paused again once branelet completes.
+///
+/// # Arguments
+/// - `docker`: The Docker instance to use for accessing the container.
+/// - `name`: The name of the (paused) idle container to execute in.
+/// - `command`: The arguments to branelet.
+/// - `on_output`: A callback that is called with every chunk of output branelet writes, as it writes it.
+///
+/// # Returns
+/// The return code of branelet, its stdout and its stderr (in that order).
+///
+/// # Errors
+/// This function may error for many reasons, which usually means that the container is unknown, not paused or the Docker engine is unreachable.
+async fn exec_container<F, Fut>(docker: &Docker, name: &str, command: &[String], mut on_output: F) -> Result<(i32, String, String), Error>
+where
+    F: FnMut(OutputChannel, String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    if let Err(err) = docker.unpause_container(name).await {
+        return Err(Error::UnpauseError { name: name.into(), err });
+    }
+
+    // Create the call to branelet, which (like any other command) runs as the container's user
+    let mut cmd: Vec<String> = vec![BRANELET_PATH.into()];
+    cmd.extend(command.iter().cloned());
+    let exec_options = CreateExecOptions { cmd: Some(cmd), attach_stdout: Some(true), attach_stderr: Some(true), ..Default::default() };
+    let id: String = match docker.create_exec(name, exec_options).await {
+        Ok(res) => res.id,
+        Err(err) => return Err(Error::ExecCreateError { name: name.into(), err }),
+    };
+
+    // Run it, collecting its output in one string per output channel
+    let mut output = match docker.start_exec(&id, None::<StartExecOptions>).await {
+        Ok(StartExecResults::Attached { output, .. }) => output,
+        Ok(StartExecResults::Detached) => return Err(Error::ExecDetached { name: name.into() }),
+        Err(err) => return Err(Error::ExecStartError { name: name.into(), err }),
+    };
+    let mut stderr = String::new();
+    let mut stdout = String::new();
+    while let Some(log_output) = output.next().await {
+        match log_output {
+            Ok(LogOutput::StdErr { message }) => {
+                let chunk: String = String::from_utf8_lossy(&message).into();
+                stderr.push_str(&chunk);
+                on_output(OutputChannel::Stderr, chunk).await;
+            },
+            Ok(LogOutput::StdOut { message }) => {
+                let chunk: String = String::from_utf8_lossy(&message).into();
+                stdout.push_str(&chunk);
+                on_output(OutputChannel::Stdout, chunk).await;
+            },
+            Ok(_) => {
+                continue;
+            },
+            Err(err) => {
+                return Err(Error::ExecOutputError { name: name.into(), err });
+            },
+        }
+    }
+
+    // Get its exit status, then put the container to sleep again
+    let code: i32 = match docker.inspect_exec(&id).await {
+        Ok(info) => match info.exit_code {
+            Some(code) => code as i32,
+            None => return Err(Error::ExecNoExitCode { name: name.into() }),
+        },
+        Err(err) => return Err(Error::ExecInspectError { name: name.into(), err }),
+    };
+    if let Err(err) = docker.pause_container(name).await {
+        return Err(Error::PauseError { name: name.into(), err });
+    }
+    Ok((code, stdout, stderr))
+}
+
 /// Returns the exit code of a container is (hopefully) already stopped.
 ///
 /// # Arguments
@@ -841,6 +967,31 @@ pub fn connect_local(opts: impl AsRef<DockerOptions>) -> Result<Docker, Error> {
     compile_error!("Non-Unix, non-Windows OS not supported.");
 }
 
+/// Helps any VM aiming to use Docker by fetching the inputs of a task that are stored remotely (e.g., in S3), such that [`preprocess_args()`] can bind them like any other dataset.
+///
+/// # Arguments
+/// - `input`: A list of input datasets & intermediate results to the current task. Remote inputs are replaced by the local files they are fetched to.
+/// - `dir`: The directory to fetch the inputs to. Every input gets its own nested directory.
+///
+/// # Errors
+/// This function errors if we failed to fetch any of the inputs.
+pub async fn fetch_remote_inputs(input: &mut HashMap<DataName, AccessKind>, dir: impl AsRef<Path>) -> Result<(), ExecuteError> {
+    let dir: &Path = dir.as_ref();
+    for (name, access) in input.iter_mut() {
+        if access.is_remote() {
+            debug!("Fetching remote {} '{}' from '{}'...", name.variant(), name.name(), access);
+            let path: PathBuf = match fetch_async(access, dir.join(name.name())).await {
+                Ok(path) => path,
+                Err(err) => {
+                    return Err(ExecuteError::RemoteFetchError { name: name.clone(), err });
+                },
+            };
+            *access = AccessKind::File { path };
+        }
+    }
+    Ok(())
+}
+
 /// Helps any VM aiming to use Docker by preprocessing the given list of arguments and function result into a list of bindings (and resolving the the arguments while at it).
 ///
 /// # Arguments
@@ -1177,7 +1328,30 @@ pub async fn launch(opts: impl AsRef<DockerOptions>, exec: ExecuteInfo) -> Resul
     ensure_image(&docker, &exec.image, &exec.image_source).await?;
 
     // Start container, return immediately (propagating any errors that occurred)
-    create_and_start_container(&docker, &exec).await
+    create_and_start_container(&docker, &exec, false).await
+}
+
+/// Launches an idle container for the given job, in which tasks may be executed later using [`exec_streaming()`].
+///
+/// The container is paused until then, such that it does not use any CPU while it waits.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `exec`: The ExecuteInfo that describes the container to launch. Its command is ignored.
+///
+/// # Returns
+/// The name of the container such that tasks can be executed in it later.
+///
+/// # Errors
+/// This function errors for many reasons, some of which include not being able to connect to Docker or the container failing (to start).
+pub async fn launch_idle(opts: impl AsRef<DockerOptions>, exec: ExecuteInfo) -> Result<String, Error> {
+    let docker: Docker = connect_local(opts)?;
+    ensure_image(&docker, &exec.image, &exec.image_source).await?;
+    let name: String = create_and_start_container(&docker, &exec, true).await?;
+    match docker.pause_container(&name).await {
+        Ok(_) => Ok(name),
+        Err(err) => Err(Error::PauseError { name, err }),
+    }
 }
 
 /// Joins the container with the given name, i.e., waits for it to complete and returns its results.
@@ -1199,7 +1373,79 @@ pub async fn join(opts: impl AsRef<DockerOptions>, name: impl AsRef<str>, keep_c
     let docker: Docker = connect_local(opts)?;
 
     // And now wait for it
-    join_container(&docker, name, keep_container).await
+    join_container(&docker, name, keep_container, |_, _| async {}).await
+}
+
+/// Joins the container with the given name like [`join()`], but also reports its output while it is being written.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `name`: The name of the container to wait for.
+/// - `keep_container`: If true, then will not remove the container after it has been launched. This is very useful for debugging.
+/// - `on_output`: A callback that is called with every chunk of output the container writes, together with the channel it was written to.
+///
+/// # Returns
+/// The return code of the docker container, its stdout and its stderr (in that order).
+///
+/// # Errors
+/// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
+pub async fn join_streaming<F, Fut>(
+    opts: impl AsRef<DockerOptions>,
+    name: impl AsRef<str>,
+    keep_container: bool,
+    on_output: F,
+) -> Result<(i32, String, String), Error>
+where
+    F: FnMut(OutputChannel, String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let name: &str = name.as_ref();
+
+    // Connect to docker
+    let docker: Docker = connect_local(opts)?;
+
+    // And now wait for it
+    join_container(&docker, name, keep_container, on_output).await
+}
+
+/// Executes branelet in an idle container launched by [`launch_idle()`], and waits for it to complete while reporting its output.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `name`: The name of the idle container to execute in.
+/// - `command`: The arguments to branelet, i.e., the command that would be given to [`launch()`].
+/// - `on_output`: A callback that is called with every chunk of output branelet writes, together with the channel it was written to.
+///
+/// # Returns
+/// The return code of branelet, its stdout and its stderr (in that order).
+///
+/// # Errors
+/// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
+pub async fn exec_streaming<F, Fut>(
+    opts: impl AsRef<DockerOptions>,
+    name: impl AsRef<str>,
+    command: &[String],
+    on_output: F,
+) -> Result<(i32, String, String), Error>
+where
+    F: FnMut(OutputChannel, String) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let docker: Docker = connect_local(opts)?;
+    exec_container(&docker, name.as_ref(), command, on_output).await
+}
+
+/// Forcefully stops and removes the container with the given name, e.g., because it hung.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `name`: The name of the container to remove.
+///
+/// # Errors
+/// This function errors if we failed to connect to Docker or to remove the container.
+pub async fn remove(opts: impl AsRef<DockerOptions>, name: impl AsRef<str>) -> Result<(), Error> {
+    let docker: Docker = connect_local(opts)?;
+    remove_container(&docker, name).await
 }
 
 /// Launches the given container and waits until its completed.
@@ -1225,10 +1471,10 @@ pub async fn run_and_wait(opts: impl AsRef<DockerOptions>, exec: ExecuteInfo, ke
     ensure_image(&docker, &exec.image, &exec.image_source).await?;
 
     // Start container, return immediately (propagating any errors that occurred)
-    let name: String = create_and_start_container(&docker, &exec).await?;
+    let name: String = create_and_start_container(&docker, &exec, false).await?;
 
     // And now wait for it
-    join_container(&docker, &name, keep_container).await
+    join_container(&docker, &name, keep_container, |_, _| async {}).await
 }
 
 /// Tries to return the (IP-)address of the container with the given name.
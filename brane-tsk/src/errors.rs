@@ -4,7 +4,7 @@
 //  Created:
 //    24 Oct 2022, 15:27:26
 //  Last edited:
-//    08 Feb 2024, 16:47:05
+//    16 Oct 2026, 01:29:42
 //  Auto updated?
 //    Yes
 //
@@ -16,20 +16,26 @@ use std::collections::HashSet;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use bollard::ClientVersion;
 use brane_ast::func_id::FunctionId;
 use brane_ast::locations::{Location, Locations};
 use brane_exe::pc::ProgramCounter;
 use brane_shr::formatters::{BlockFormatter, Capitalizeable};
+use brane_shr::suggest::DidYouMean;
 use enum_debug::EnumDebug as _;
 use reqwest::StatusCode;
 use serde_json::Value;
 use specifications::address::Address;
+use specifications::checking::{ApprovalPending, PolicyDenial};
 use specifications::container::Image;
 use specifications::data::DataName;
 use specifications::driving::ExecuteReply;
+use specifications::errors::ErrorCode;
 use specifications::package::Capability;
+use specifications::planning::ResidencyViolation;
+use specifications::reporting::TaskFailure;
 use specifications::version::Version;
 // The TaskReply is here for legacy reasons; bad name
 use specifications::working::{ExecuteReply as TaskReply, TaskStatus};
@@ -90,6 +96,10 @@ pub enum PlanError {
 
     /// The user didn't specify the location (specifically enough).
     AmbigiousLocationError { name: String, locs: Locations },
+    /// None of the possible locations of a task satisfy the workflow's placement constraints.
+    UnsatisfiableConstraints { name: String, key: String, locs: Locations },
+    /// None of the locations to which a task is restricted are known.
+    UnknownLocationError { name: String, locs: Locations },
     /// Failed to send a request to the API service.
     RequestError { address: String, err: reqwest::Error },
     /// The request failed with a non-OK status code
@@ -100,8 +110,10 @@ pub enum PlanError {
     RequestParseError { address: String, raw: String, err: serde_json::Error },
     /// The planned domain does not support the task.
     UnsupportedCapabilities { task: String, loc: String, expected: HashSet<Capability>, got: HashSet<Capability> },
+    /// The planned domain is not part of the residency zones of (some of) the task's input.
+    ResidencyViolation { violation: ResidencyViolation },
     /// The given dataset was unknown to us.
-    UnknownDataset { name: String },
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// The given intermediate result was unknown to us.
     UnknownIntermediateResult { name: String },
     /// We failed to insert one of the dataset in the runtime set.
@@ -121,7 +133,7 @@ pub enum PlanError {
     /// Failed to send a request to plan at the planner.
     PlanningRequestSend { id: String, url: String, err: reqwest::Error },
     /// The server failed to plan.
-    PlanningFailure { id: String, url: String, code: StatusCode, response: Option<String> },
+    PlanningFailure { id: String, url: String, code: StatusCode, error_code: Option<ErrorCode>, response: Option<String> },
     /// Failed to download the server's response.
     PlanningResponseDownload { id: String, url: String, err: reqwest::Error },
     /// failed to parse the server's response.
@@ -154,6 +166,19 @@ impl Display for PlanError {
                     "all locations are possible, but you need to reduce that to only 1 (use On-structs for that)".into()
                 }
             ),
+            UnsatisfiableConstraints { name, key, locs } => write!(
+                f,
+                "None of the possible locations for task '{}' (call {}) satisfy the workflow's placement constraints{}",
+                name,
+                key,
+                if let Locations::Restricted(locs) = locs { format!(" (possible locations were {})", locs.join(", ")) } else { String::new() }
+            ),
+            UnknownLocationError { name, locs } => write!(
+                f,
+                "None of the possible locations for task '{}' are known{}",
+                name,
+                if let Locations::Restricted(locs) = locs { format!(" (possible locations were {})", locs.join(", ")) } else { String::new() }
+            ),
             RequestError { address, .. } => write!(f, "Failed to send GET-request to '{address}'"),
             RequestFailure { address, code, err } => write!(
                 f,
@@ -168,7 +193,16 @@ impl Display for PlanError {
             UnsupportedCapabilities { task, loc, expected, got } => {
                 write!(f, "Location '{loc}' only supports capabilities {got:?}, whereas task '{task}' requires capabilities {expected:?}")
             },
-            UnknownDataset { name } => write!(f, "Unknown dataset '{name}'"),
+            ResidencyViolation { violation } => write!(
+                f,
+                "Cannot plan task '{}' on location '{}', since its input '{}' may not leave residency zone(s) {} (which '{}' is not part of)",
+                violation.task,
+                violation.location,
+                violation.data,
+                violation.zones.iter().map(|z| format!("'{z}'")).collect::<Vec<String>>().join(", "),
+                violation.location
+            ),
+            UnknownDataset { name, suggestions } => write!(f, "Unknown dataset '{name}'{}", DidYouMean::new(suggestions)),
             UnknownIntermediateResult { name } => write!(f, "Unknown intermediate result '{name}'"),
             DataPlanError { .. } => write!(f, "Failed to plan dataset"),
             DatasetUnavailable { name, locs } => write!(
@@ -202,12 +236,13 @@ impl Display for PlanError {
             PlanningRequestSerialize { id, .. } => write!(f, "Failed to serialize planning request for workflow '{id}'"),
             PlanningRequest { id, url, .. } => write!(f, "Failed to create request to plan workflow '{id}' for '{url}'"),
             PlanningRequestSend { id, url, .. } => write!(f, "Failed to send request to plan workflow '{id}' to '{url}'"),
-            PlanningFailure { id, url, code, response } => write!(
+            PlanningFailure { id, url, code, error_code, response } => write!(
                 f,
-                "Planner failed to plan workflow '{}' (server at '{url}' returned {} ({})){}",
+                "Planner failed to plan workflow '{}' (server at '{url}' returned {} ({}){}){}",
                 id,
                 code.as_u16(),
                 code.canonical_reason().unwrap_or("???"),
+                if let Some(error_code) = error_code { format!(", {error_code}") } else { String::new() },
                 if let Some(res) = response { format!("\n\nResponse:\n{}\n", BlockFormatter::new(res)) } else { String::new() }
             ),
             PlanningResponseDownload { id, url, .. } => write!(f, "Failed to download response from '{url}' for workflow '{id}'"),
@@ -250,11 +285,14 @@ impl Error for PlanError {
             InfraFileLoadError { err } => Some(err),
 
             AmbigiousLocationError { .. } => None,
+            UnsatisfiableConstraints { .. } => None,
+            UnknownLocationError { .. } => None,
             RequestError { err, .. } => Some(err),
             RequestFailure { .. } => None,
             RequestBodyError { err, .. } => Some(err),
             RequestParseError { err, .. } => Some(err),
             UnsupportedCapabilities { .. } => None,
+            ResidencyViolation { .. } => None,
             UnknownDataset { .. } => None,
             UnknownIntermediateResult { .. } => None,
             DataPlanError { err } => Some(err),
@@ -285,6 +323,8 @@ impl Error for PlanError {
 pub enum PreprocessError {
     /// The dataset was _still_ unavailable after preprocessing
     UnavailableData { name: DataName },
+    /// Failed to simulate transferring a dataset to another domain.
+    SimulatedTransferError { name: DataName, err: brane_shr::fs::Error },
 
     // Instance only (client-side)
     /// Failed to load the node config file.
@@ -337,6 +377,10 @@ pub enum PreprocessError {
     DownloadRequestError { address: String, err: reqwest::Error },
     /// The given download request failed with a non-success status code.
     DownloadRequestFailure { address: String, code: StatusCode, message: Option<String> },
+    /// The checker of the domain that hosts the data denied its transfer, and explained why.
+    PolicyDenied { denial: PolicyDenial },
+    /// The domain that hosts the data only transfers it once its data steward approves.
+    ApprovalPending { pending: ApprovalPending },
     /// Failed to reach the next chunk of data.
     DownloadStreamError { address: String, err: reqwest::Error },
     /// Failed to create the file to which we write the download stream.
@@ -360,6 +404,7 @@ impl Display for PreprocessError {
         use self::PreprocessError::*;
         match self {
             UnavailableData { name } => write!(f, "{} '{}' is not available locally", name.variant(), name.name()),
+            SimulatedTransferError { name, .. } => write!(f, "Failed to simulate transfer of {} '{}'", name.variant(), name.name()),
 
             NodeConfigReadError { path, .. } => write!(f, "Failed to load node config file '{}'", path.display()),
             InfraReadError { path, .. } => write!(f, "Failed to load infrastructure file '{}'", path.display()),
@@ -395,6 +440,8 @@ impl Display for PreprocessError {
                 code.canonical_reason().unwrap_or("???"),
                 if let Some(message) = message { format!(": {message}") } else { String::new() }
             ),
+            PolicyDenied { denial } => write!(f, "{denial}"),
+            ApprovalPending { pending } => write!(f, "{pending}"),
             DownloadStreamError { address, .. } => write!(f, "Failed to get next chunk in download stream from '{address}'"),
             TarCreateError { path, .. } => write!(f, "Failed to create tarball file '{}'", path.display()),
             TarOpenError { path, .. } => write!(f, "Failed to re-open tarball file '{}'", path.display()),
@@ -412,6 +459,7 @@ impl Error for PreprocessError {
         use PreprocessError::*;
         match self {
             UnavailableData { .. } => None,
+            SimulatedTransferError { err, .. } => Some(err),
 
             NodeConfigReadError { err, .. } => Some(err),
             InfraReadError { err, .. } => Some(err),
@@ -433,6 +481,8 @@ impl Error for PreprocessError {
             ClientCreateError { err } => Some(err),
             DownloadRequestError { err, .. } => Some(err),
             DownloadRequestFailure { .. } => None,
+            PolicyDenied { .. } => None,
+            ApprovalPending { .. } => None,
             DownloadStreamError { err, .. } => Some(err),
             TarCreateError { err, .. } => Some(err),
             TarOpenError { err, .. } => Some(err),
@@ -458,10 +508,16 @@ pub enum ExecuteError {
     UnknownPackage { name: String, version: Version },
     /// We encountered a dataset/result that we didn't know.
     UnknownData { name: DataName },
+    /// We encountered a dataset/result that is stored remotely (e.g., in S3) but that wasn't fetched before the task was launched.
+    UnfetchedData { name: DataName },
+    /// Failed to create the temporary directory to fetch remote datasets/results to.
+    RemoteDirCreateError { err: std::io::Error },
+    /// Failed to fetch a dataset/result that is stored remotely.
+    RemoteFetchError { name: DataName, err: brane_shr::remote::Error },
     /// Failed to serialize task's input arguments
     ArgsEncodeError { err: serde_json::Error },
     /// The external call failed with a nonzero exit code and some stdout/stderr
-    ExternalCallFailed { name: String, image: Box<Image>, code: i32, stdout: String, stderr: String },
+    ExternalCallFailed { name: String, image: Box<Image>, failure: Box<TaskFailure> },
     /// Failed to decode the branelet output from base64 to raw bytes
     Base64DecodeError { raw: String, err: base64::DecodeError },
     /// Failed to decode the branelet output from raw bytes to an UTF-8 string
@@ -486,7 +542,7 @@ pub enum ExecuteError {
     StatusEmptyStringError { status: TaskStatus },
     /// Failed to parse the given value as a FullValue
     StatusValueParseError { status: TaskStatus, raw: String, err: serde_json::Error },
-    /// Failed to parse the given value as a return code/stdout/stderr triplet.
+    /// Failed to parse the given value as a task failure (or an older return code/stdout/stderr triplet).
     StatusTripletParseError { status: TaskStatus, raw: String, err: serde_json::Error },
     /// Failed to update the client of a status change.
     ClientUpdateError { status: TaskStatus, err: tokio::sync::mpsc::error::SendError<Result<TaskReply, Status>> },
@@ -504,6 +560,12 @@ pub enum ExecuteError {
     GrpcRequestError { what: &'static str, endpoint: Address, err: tonic::Status },
     /// Preprocessing failed with the following error.
     ExecuteError { endpoint: Address, name: String, status: TaskStatus, err: StringError },
+    /// The delegate stopped sending heartbeats for a running task (e.g., it crashed or is unreachable).
+    HeartbeatTimeout { endpoint: Address, name: String, status: TaskStatus, timeout: Duration },
+    /// The workflow exceeded its deadline while the task was still running, so it was cancelled.
+    DeadlineExceeded { name: String, location: String },
+    /// The task failed on the delegate, for the given reasons.
+    TaskFailed { name: String, location: String, failure: Box<TaskFailure> },
 
     // Instance-only (worker side)
     /// Failed to load the digest cache file
@@ -524,6 +586,8 @@ pub enum ExecuteError {
     ImageCreateError { path: PathBuf, err: std::io::Error },
     /// Failed to write to the file where we write the download stream.
     ImageWriteError { path: PathBuf, err: std::io::Error },
+    /// Failed to pull the image of a package from the OCI registry that distributes it.
+    OciPullError { reference: String, err: brane_shr::oci::Error },
     /// Failed to write to the file where we write the container ID.
     IdWriteError { path: PathBuf, err: std::io::Error },
     /// Failed to read from the file where we cached the container ID.
@@ -535,14 +599,16 @@ pub enum ExecuteError {
     /// Failed to read to the file where we cached the container hash.
     HashReadError { path: PathBuf, err: std::io::Error },
 
-    /// The checker rejected the workflow.
-    AuthorizationFailure { checker: Address },
+    /// The checker rejected the task, and explained why.
+    PolicyDenied { denial: PolicyDenial },
     /// The checker failed to check workflow authorization.
     AuthorizationError { checker: Address, err: AuthorizeError },
     /// Failed to get an up-to-date package index.
     PackageIndexError { endpoint: String, err: ApiError },
     /// Failed to load the backend file.
     BackendFileError { path: PathBuf, err: brane_cfg::backend::Error },
+    /// None of the backends in the backend file provide all capabilities required by a task.
+    NoSuitableBackend { name: String, requirements: HashSet<Capability> },
 }
 impl Display for ExecuteError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -550,16 +616,11 @@ impl Display for ExecuteError {
         match self {
             UnknownPackage { name, version } => write!(f, "Unknown package '{name}' (or it does not have version {version})"),
             UnknownData { name } => write!(f, "Unknown {} '{}'", name.variant(), name.name()),
+            UnfetchedData { name } => write!(f, "{} '{}' is stored remotely and has to be fetched first", name.variant(), name.name()),
+            RemoteDirCreateError { .. } => write!(f, "Failed to create temporary directory for remote data"),
+            RemoteFetchError { name, .. } => write!(f, "Failed to fetch remote {} '{}'", name.variant(), name.name()),
             ArgsEncodeError { .. } => write!(f, "Failed to serialize input arguments"),
-            ExternalCallFailed { name, image, code, stdout, stderr } => write!(
-                f,
-                "Task '{}' (image '{}') failed with exit code {}\n\n{}\n\n{}\n\n",
-                name,
-                image,
-                code,
-                BlockFormatter::new(stdout),
-                BlockFormatter::new(stderr)
-            ),
+            ExternalCallFailed { name, image, failure } => write!(f, "Task '{name}' (image '{image}') failed with {failure}"),
             Base64DecodeError { raw, .. } => {
                 write!(f, "Failed to decode the following task output as valid Base64:\n{}\n\n", BlockFormatter::new(raw))
             },
@@ -581,7 +642,7 @@ impl Display for ExecuteError {
                 write!(f, "Failed to parse '{raw}' as a FullValue in incoming status update {status:?}")
             },
             StatusTripletParseError { status, raw, .. } => {
-                write!(f, "Failed to parse '{raw}' as a return code/stdout/stderr triplet in incoming status update {status:?}")
+                write!(f, "Failed to parse '{raw}' as a task failure in incoming status update {status:?}")
             },
             ClientUpdateError { status, .. } => write!(f, "Failed to update client of status {status:?}"),
             NodeConfigReadError { path, .. } => write!(f, "Failed to load node config file '{}'", path.display()),
@@ -593,6 +654,16 @@ impl Display for ExecuteError {
             ExecuteError { endpoint, name, status, .. } => {
                 write!(f, "Remote delegate '{endpoint}' returned status '{status:?}' while executing task '{name}'")
             },
+            HeartbeatTimeout { endpoint, name, status, timeout } => write!(
+                f,
+                "Lost contact with remote delegate '{}' while executing task '{}' (no heartbeat for {}s; last known status '{:?}')",
+                endpoint,
+                name,
+                timeout.as_secs(),
+                status
+            ),
+            DeadlineExceeded { name, location } => write!(f, "Cancelled task '{name}' at '{location}' because the workflow exceeded its deadline"),
+            TaskFailed { name, location, failure } => write!(f, "Task '{name}' at '{location}' failed with {failure}"),
 
             DigestReadError { path, .. } => write!(f, "Failed to read cached digest in '{}'", path.display()),
             DigestError { path, .. } => write!(f, "Failed to read digest of image '{}'", path.display()),
@@ -610,16 +681,18 @@ impl Display for ExecuteError {
             DownloadStreamError { address, .. } => write!(f, "Failed to get next chunk in download stream from '{address}'"),
             ImageCreateError { path, .. } => write!(f, "Failed to create tarball file '{}'", path.display()),
             ImageWriteError { path, .. } => write!(f, "Failed to write to tarball file '{}'", path.display()),
+            OciPullError { reference, .. } => write!(f, "Failed to pull image '{reference}' from OCI registry"),
             IdWriteError { path, .. } => write!(f, "Failed to write image ID to file '{}'", path.display()),
             IdReadError { path, .. } => write!(f, "Failed to read image from file '{}'", path.display()),
             HashError { .. } => write!(f, "Failed to hash image"),
             HashWriteError { path, .. } => write!(f, "Failed to write image hash to file '{}'", path.display()),
             HashReadError { path, .. } => write!(f, "Failed to read image hash from file '{}'", path.display()),
 
-            AuthorizationFailure { checker: _ } => write!(f, "Checker rejected workflow"),
+            PolicyDenied { denial } => write!(f, "{denial}"),
             AuthorizationError { checker: _, .. } => write!(f, "Checker failed to authorize workflow"),
             PackageIndexError { endpoint, .. } => write!(f, "Failed to get PackageIndex from '{endpoint}'"),
             BackendFileError { path, .. } => write!(f, "Failed to load backend file '{}'", path.display()),
+            NoSuitableBackend { name, requirements } => write!(f, "No backend supports all capabilities {requirements:?} required by task '{name}'"),
         }
     }
 }
@@ -629,6 +702,9 @@ impl Error for ExecuteError {
         match self {
             UnknownPackage { .. } => None,
             UnknownData { .. } => None,
+            UnfetchedData { .. } => None,
+            RemoteDirCreateError { err } => Some(err),
+            RemoteFetchError { err, .. } => Some(err),
             ArgsEncodeError { err } => Some(err),
             ExternalCallFailed { .. } => None,
             Base64DecodeError { err, .. } => Some(err),
@@ -661,17 +737,22 @@ impl Error for ExecuteError {
             DownloadStreamError { err, .. } => Some(err),
             ImageCreateError { err, .. } => Some(err),
             ImageWriteError { err, .. } => Some(err),
+            OciPullError { err, .. } => Some(err),
             IdWriteError { err, .. } => Some(err),
             IdReadError { err, .. } => Some(err),
             HashError { err } => Some(err),
             HashWriteError { err, .. } => Some(err),
             HashReadError { err, .. } => Some(err),
 
-            AuthorizationFailure { .. } => None,
+            PolicyDenied { .. } => None,
             AuthorizationError { err, .. } => Some(err),
             PackageIndexError { err, .. } => Some(err),
             BackendFileError { err, .. } => Some(err),
+            NoSuitableBackend { .. } => None,
             ExecuteError { err, .. } => Some(err),
+            HeartbeatTimeout { .. } => None,
+            DeadlineExceeded { .. } => None,
+            TaskFailed { .. } => None,
         }
     }
 }
@@ -850,6 +931,8 @@ pub enum CommitError {
     DirRemoveError { path: PathBuf, err: std::io::Error },
     /// A given path is neither a file nor a directory.
     PathNotFileNotDir { path: PathBuf },
+    /// The dataset to overwrite is stored remotely (e.g., in S3), which we cannot write to.
+    RemoteDataset { name: String },
 }
 impl Display for CommitError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
@@ -888,6 +971,7 @@ impl Display for CommitError {
             FileRemoveError { path, .. } => write!(f, "Failed to remove file '{}'", path.display()),
             DirRemoveError { path, .. } => write!(f, "Failed to remove directory '{}'", path.display()),
             PathNotFileNotDir { path } => write!(f, "Given path '{}' neither points to a file nor a directory", path.display()),
+            RemoteDataset { name } => write!(f, "Dataset '{name}' is stored remotely and cannot be overwritten"),
         }
     }
 }
@@ -916,6 +1000,7 @@ impl Error for CommitError {
             FileRemoveError { err, .. } => Some(err),
             DirRemoveError { err, .. } => Some(err),
             PathNotFileNotDir { .. } => None,
+            RemoteDataset { .. } => None,
         }
     }
 }
@@ -980,6 +1065,23 @@ pub enum DockerError {
     /// Failed to remove the given container.
     ContainerRemoveError { name: String, err: bollard::errors::Error },
 
+    /// Failed to pause the given container.
+    PauseError { name: String, err: bollard::errors::Error },
+    /// Failed to unpause the given container.
+    UnpauseError { name: String, err: bollard::errors::Error },
+    /// Failed to create a command to execute in the given container.
+    ExecCreateError { name: String, err: bollard::errors::Error },
+    /// Failed to start a command in the given container.
+    ExecStartError { name: String, err: bollard::errors::Error },
+    /// A command in the given container started detached, so we cannot read its output.
+    ExecDetached { name: String },
+    /// Failed to read the output of a command in the given container.
+    ExecOutputError { name: String, err: bollard::errors::Error },
+    /// Failed to inspect a command in the given container.
+    ExecInspectError { name: String, err: bollard::errors::Error },
+    /// A command in the given container had no return code.
+    ExecNoExitCode { name: String },
+
     /// Failed to open the given image file.
     ImageFileOpenError { path: PathBuf, err: std::io::Error },
     /// Failed to import the given image file.
@@ -1047,6 +1149,15 @@ impl Display for DockerError {
 
             ContainerRemoveError { name, .. } => write!(f, "Fialed to remove Docker container with name '{name}'"),
 
+            PauseError { name, .. } => write!(f, "Failed to pause Docker container with name '{name}'"),
+            UnpauseError { name, .. } => write!(f, "Failed to unpause Docker container with name '{name}'"),
+            ExecCreateError { name, .. } => write!(f, "Failed to create command in Docker container with name '{name}'"),
+            ExecStartError { name, .. } => write!(f, "Failed to start command in Docker container with name '{name}'"),
+            ExecDetached { name } => write!(f, "Command in Docker container with name '{name}' was started detached"),
+            ExecOutputError { name, .. } => write!(f, "Failed to read output of command in Docker container with name '{name}'"),
+            ExecInspectError { name, .. } => write!(f, "Failed to inspect command in Docker container with name '{name}'"),
+            ExecNoExitCode { name } => write!(f, "Command in Docker container with name '{name}' has no return code"),
+
             ImageFileOpenError { path, .. } => write!(f, "Failed to open image file '{}'", path.display()),
             ImageImportError { path, .. } => write!(f, "Failed to import image file '{}' into Docker engine", path.display()),
             ImageFileCreateError { path, .. } => write!(f, "Failed to create image file '{}'", path.display()),
@@ -1115,6 +1226,15 @@ impl Error for DockerError {
 
             ContainerRemoveError { err, .. } => Some(err),
 
+            PauseError { err, .. } => Some(err),
+            UnpauseError { err, .. } => Some(err),
+            ExecCreateError { err, .. } => Some(err),
+            ExecStartError { err, .. } => Some(err),
+            ExecDetached { .. } => None,
+            ExecOutputError { err, .. } => Some(err),
+            ExecInspectError { err, .. } => Some(err),
+            ExecNoExitCode { .. } => None,
+
             ImageFileOpenError { err, .. } => Some(err),
             ImageImportError { err, .. } => Some(err),
             ImageFileCreateError { err, .. } => Some(err),
@@ -1144,6 +1264,63 @@ impl Error for DockerError {
 
 
 
+/// Collects errors that relate to the pool of idle containers.
+#[derive(Debug)]
+pub enum ContainerPoolError {
+    /// Failed to create the workspace of a container.
+    WorkspaceCreateError { path: PathBuf, err: std::io::Error },
+    /// Failed to bind the workspace of a container.
+    WorkspaceBindError { path: PathBuf, err: specifications::container::VolumeBindError },
+    /// Failed to read the contents of the workspace of a container.
+    WorkspaceReadError { path: PathBuf, err: std::io::Error },
+    /// Failed to clear the workspace of a container.
+    WorkspaceClearError { path: PathBuf, err: std::io::Error },
+    /// Failed to launch a new idle container.
+    LaunchError { image: Box<Image>, err: DockerError },
+    /// Failed to execute a task in an idle container.
+    ExecError { name: String, err: DockerError },
+    /// Failed to move (part of) a result out of the workspace of a container.
+    ResultMoveError { source: PathBuf, target: PathBuf, err: std::io::Error },
+    /// Failed to copy (part of) a result out of the workspace of a container.
+    ResultCopyError { source: PathBuf, target: PathBuf, err: brane_shr::fs::Error },
+}
+impl Display for ContainerPoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ContainerPoolError::*;
+        match self {
+            WorkspaceCreateError { path, .. } => write!(f, "Failed to create container workspace '{}'", path.display()),
+            WorkspaceBindError { path, .. } => write!(f, "Failed to bind container workspace '{}'", path.display()),
+            WorkspaceReadError { path, .. } => write!(f, "Failed to read container workspace '{}'", path.display()),
+            WorkspaceClearError { path, .. } => write!(f, "Failed to clear container workspace '{}'", path.display()),
+            LaunchError { image, .. } => write!(f, "Failed to launch idle container for image '{image}'"),
+            ExecError { name, .. } => write!(f, "Failed to execute task in idle container '{name}'"),
+            ResultMoveError { source, target, .. } => {
+                write!(f, "Failed to move result '{}' from container workspace to '{}'", source.display(), target.display())
+            },
+            ResultCopyError { source, target, .. } => {
+                write!(f, "Failed to copy result '{}' from container workspace to '{}'", source.display(), target.display())
+            },
+        }
+    }
+}
+impl Error for ContainerPoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ContainerPoolError::*;
+        match self {
+            WorkspaceCreateError { err, .. } => Some(err),
+            WorkspaceBindError { err, .. } => Some(err),
+            WorkspaceReadError { err, .. } => Some(err),
+            WorkspaceClearError { err, .. } => Some(err),
+            LaunchError { err, .. } => Some(err),
+            ExecError { err, .. } => Some(err),
+            ResultMoveError { err, .. } => Some(err),
+            ResultCopyError { err, .. } => Some(err),
+        }
+    }
+}
+
+
+
 /// Collects errors that relate to local index interaction.
 #[derive(Debug)]
 pub enum LocalError {
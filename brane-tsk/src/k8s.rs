@@ -4,7 +4,7 @@
 //  Created:
 //    08 May 2023, 13:01:23
 //  Last edited:
-//    02 Oct 2023, 17:21:24
+//    16 Oct 2026, 01:52:35
 //  Auto updated?
 //    Yes
 // 
@@ -640,6 +640,8 @@ pub struct K8sOptions {
     pub config           : PathBuf,
     /// The address of the Docker registry that we push container images to.
     pub registry_address : Address,
+    /// The credentials with which we push images to the registry and with which the cluster pulls them, if any.
+    pub registry_auth    : Option<RegistryAuth>,
 }
 
 /// Defines a struct that describes everything we need to know about a job for a Kubernetes task.
@@ -4,7 +4,7 @@
 //  Created:
 //    24 Oct 2022, 15:26:59
 //  Last edited:
-//    08 Feb 2024, 15:17:59
+//    15 Oct 2026, 22:48:55
 //  Auto updated?
 //    Yes
 //
@@ -22,6 +22,7 @@ pub mod errors;
 pub mod input;
 // pub mod k8s;
 pub mod local;
+pub mod pool;
 pub mod spec;
 pub mod tools;
 
@@ -4,7 +4,7 @@
 //  Created:
 //    18 Nov 2022, 14:46:51
 //  Last edited:
-//    22 May 2023, 13:39:32
+//    15 Oct 2026, 22:44:05
 //  Auto updated?
 //    Yes
 //
@@ -16,9 +16,11 @@ use std::fs::{self, DirEntry, File, ReadDir};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
+use async_trait::async_trait;
+use log::debug;
 use serde_json::json;
 use specifications::data::{DataIndex, DataInfo};
-use specifications::package::{PackageIndex, PackageInfo};
+use specifications::package::{PackageIndex, PackageIndexProvider, PackageInfo};
 use specifications::version::Version;
 
 pub use crate::errors::LocalError as Error;
@@ -81,6 +83,35 @@ pub fn get_package_versions(package_name: &str, package_dir: &Path) -> Result<Ve
     Ok(versions)
 }
 
+/// Reads the package infos of all versions in the given package directory.
+///
+/// # Arguments
+/// - `package_name`: The name of the package we read the infos of (used for debugging purposes).
+/// - `package_dir`: The package directory to read. This function assumes it already exists.
+///
+/// # Returns
+/// The PackageInfos of all versions of the package.
+///
+/// # Errors
+/// This function errors if we failed to read the package directory or any of its `package.yml` files.
+fn get_package_infos(package_name: &str, package_dir: &Path) -> Result<Vec<PackageInfo>, Error> {
+    let versions = get_package_versions(package_name, package_dir)?;
+    let mut infos: Vec<PackageInfo> = Vec::with_capacity(versions.len());
+    for version in versions {
+        // Try to read the propery package info
+        let package_file = package_dir.join(version.to_string()).join("package.yml");
+        match PackageInfo::from_path(package_file.clone()) {
+            Ok(package_info) => {
+                infos.push(package_info);
+            },
+            Err(err) => {
+                return Err(Error::InvalidPackageYml { package: package_name.to_string(), path: package_file, err });
+            },
+        }
+    }
+    Ok(infos)
+}
+
 
 
 
@@ -123,22 +154,7 @@ pub fn get_package_index(packages: impl AsRef<Path>) -> Result<PackageIndex, Err
 
         // Read the versions inside the package directory and add each of them separately
         let package_name = package_path.file_name().unwrap().to_string_lossy();
-        let versions = get_package_versions(&package_name, &package_path)?;
-        for version in versions {
-            // Get the path of this version
-            let version_path = package_path.join(version.to_string());
-
-            // Try to read the propery package info
-            let package_file = version_path.join("package.yml");
-            match PackageInfo::from_path(package_file.clone()) {
-                Ok(package_info) => {
-                    packages.push(package_info);
-                },
-                Err(err) => {
-                    return Err(Error::InvalidPackageYml { package: package_name.to_string(), path: package_file, err });
-                },
-            }
-        }
+        packages.extend(get_package_infos(&package_name, &package_path)?);
     }
 
     // Generate the package index from the collected list of packages
@@ -213,3 +229,43 @@ pub fn get_data_index(datasets_path: impl AsRef<Path>) -> Result<DataIndex, Erro
         Err(err) => Err(Error::DataIndexError { err }),
     }
 }
+
+
+
+/// Resolves packages on demand from the local packages directory, instead of reading all of them at once (see [`get_package_index()`]).
+///
+/// Use it with a [`PackageIndexCache`](crate::caches::PackageIndexCache) to only read the packages that a workflow imports.
+#[derive(Clone, Debug)]
+pub struct LocalPackageProvider {
+    /// The path to the directory that we read the packages from.
+    packages_path: PathBuf,
+}
+impl LocalPackageProvider {
+    /// Constructor for the LocalPackageProvider.
+    ///
+    /// # Arguments
+    /// - `packages_path`: The path to the directory that we read the packages from.
+    ///
+    /// # Returns
+    /// A new LocalPackageProvider.
+    #[inline]
+    pub fn new(packages_path: impl Into<PathBuf>) -> Self { Self { packages_path: packages_path.into() } }
+}
+#[async_trait]
+impl PackageIndexProvider for LocalPackageProvider {
+    type Error = Error;
+
+    async fn packages(&self, name: &str) -> Result<Vec<PackageInfo>, Self::Error> {
+        // Package names are identifiers, but let's not read outside of the packages directory if they aren't
+        let package_path: PathBuf = self.packages_path.join(name);
+        if name.contains(['/', '\\']) || name.starts_with('.') || !package_path.is_dir() {
+            debug!("Package '{}' not found in '{}'", name, self.packages_path.display());
+            return Ok(vec![]);
+        }
+        get_package_infos(name, &package_path)
+    }
+
+    async fn all_packages(&self) -> Result<Vec<PackageInfo>, Self::Error> {
+        Ok(get_package_index(&self.packages_path)?.packages.into_values().collect())
+    }
+}
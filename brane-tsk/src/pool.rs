@@ -0,0 +1,416 @@
+//  POOL.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 22:48:55
+//  Last edited:
+//    16 Oct 2026, 02:38:59
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a pool of idle containers, in which tasks can be executed
+//!   without having to create and start a new container for every call.
+//!
+//!   Idle containers are paused while they wait, and are only reused for
+//!   calls of the same user to the same package with the same datasets (as
+//!   these are bound when the container is created). Instead of binding the
+//!   result directory of a call, every container binds a workspace of its
+//!   own as `/result`, which is moved to the result directory and then
+//!   cleared after every call.
+//!
+//!   Note that only `/result` is reset between calls. Anything else a call
+//!   leaves behind in the container (e.g., files written to `/tmp` or
+//!   elsewhere in its filesystem, or background processes) is visible to
+//!   the next call in that container. This is why containers are never
+//!   shared between users; calls of the same user are not isolated from
+//!   each other, however. Workflows without a user share their containers
+//!   with each other.
+//
+
+use std::collections::HashMap;
+use std::fs::FileType;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use brane_cfg::node::ContainerPoolConfig;
+use brane_shr::fs::copy_dir_recursively_async;
+use log::{debug, warn};
+use specifications::container::VolumeBind;
+use specifications::working::OutputChannel;
+use tokio::fs as tfs;
+
+use crate::docker::{self, DockerOptions, ExecuteInfo};
+pub use crate::errors::ContainerPoolError as Error;
+
+
+/***** CONSTANTS *****/
+/// The path in the container where branelet writes the result of a task.
+const RESULT_PATH: &str = "/result";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the key under which the idle containers for the given call are pooled.
+///
+/// # Arguments
+/// - `info`: The [`ExecuteInfo`] describing the call.
+/// - `user`: The user on whose behalf the call is executed, if known.
+///
+/// # Returns
+/// A key that is the same for all calls that can be executed in the same container.
+fn pool_key(info: &ExecuteInfo, user: Option<&str>) -> String {
+    let mut inputs: Vec<String> =
+        info.binds.iter().filter(|bind| bind.container != Path::new(RESULT_PATH)).map(|bind| bind.docker().to_string()).collect();
+    inputs.sort();
+    let mut capabilities: Vec<String> = info.capabilities.iter().map(|capability| format!("{capability:?}")).collect();
+    capabilities.sort();
+    let network: String = info.network.clone().into();
+    format!("{:?}|{}|{}|{}|{:?}|{}", user, info.image.docker(), inputs.join(","), network, info.sandbox, capabilities.join(","))
+}
+
+/// Moves the contents of a workspace to the result directory of a call.
+///
+/// Entries are renamed if possible, and copied otherwise (e.g., because the workspace lives on another filesystem).
+///
+/// # Arguments
+/// - `workspace`: The workspace of the container that executed the call.
+/// - `result`: The (existing) result directory to move the contents to.
+///
+/// # Errors
+/// This function errors if we failed to read the workspace or to move any of its entries.
+async fn move_result(workspace: &Path, result: &Path) -> Result<(), Error> {
+    let mut entries: tfs::ReadDir = match tfs::read_dir(workspace).await {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::WorkspaceReadError { path: workspace.into(), err }),
+    };
+    loop {
+        let entry: tfs::DirEntry = match entries.next_entry().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(err) => return Err(Error::WorkspaceReadError { path: workspace.into(), err }),
+        };
+        let (source, target): (PathBuf, PathBuf) = (entry.path(), result.join(entry.file_name()));
+        if tfs::rename(&source, &target).await.is_ok() {
+            continue;
+        }
+
+        // Copy it instead, taking care not to follow any links the task made to outside of the workspace
+        let file_type: FileType = match entry.file_type().await {
+            Ok(file_type) => file_type,
+            Err(err) => return Err(Error::WorkspaceReadError { path: workspace.into(), err }),
+        };
+        if file_type.is_dir() {
+            if let Err(err) = copy_dir_recursively_async(&source, &target).await {
+                return Err(Error::ResultCopyError { source, target, err });
+            }
+        } else if file_type.is_file() {
+            if let Err(err) = tfs::copy(&source, &target).await {
+                return Err(Error::ResultMoveError { source, target, err });
+            }
+        } else {
+            warn!("Not copying '{}' from container workspace, as it is not a file or directory", source.display());
+        }
+    }
+    Ok(())
+}
+
+/// Removes everything in a workspace, but not the workspace itself (as it is still bound to its container).
+///
+/// Note that this does not touch the rest of the container's filesystem (see the module documentation).
+///
+/// # Arguments
+/// - `workspace`: The workspace to clear.
+///
+/// # Errors
+/// This function errors if we failed to read the workspace or to remove any of its entries.
+async fn clear_workspace(workspace: &Path) -> Result<(), Error> {
+    let mut entries: tfs::ReadDir = match tfs::read_dir(workspace).await {
+        Ok(entries) => entries,
+        Err(err) => return Err(Error::WorkspaceReadError { path: workspace.into(), err }),
+    };
+    loop {
+        let entry: tfs::DirEntry = match entries.next_entry().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(err) => return Err(Error::WorkspaceReadError { path: workspace.into(), err }),
+        };
+        let path: PathBuf = entry.path();
+        let res: Result<(), std::io::Error> = match entry.file_type().await {
+            Ok(file_type) if file_type.is_dir() => tfs::remove_dir_all(&path).await,
+            Ok(_) => tfs::remove_file(&path).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = res {
+            return Err(Error::WorkspaceClearError { path, err });
+        }
+    }
+    Ok(())
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a container that waits in the [`ContainerPool`].
+#[derive(Debug)]
+struct IdleContainer {
+    /// The name of the container.
+    name:      String,
+    /// The workspace that is bound as the container's result directory.
+    workspace: PathBuf,
+    /// The number of calls executed in the container so far.
+    uses:      usize,
+    /// The time since the container is idle.
+    since:     Instant,
+}
+
+/// Defines a container taken from the [`ContainerPool`] to execute a call in.
+///
+/// Give it back with [`ContainerPool::release()`] or [`ContainerPool::discard()`] when the call is done.
+#[derive(Debug)]
+pub struct PooledContainer {
+    /// The key under which the container is pooled.
+    key:       String,
+    /// The name of the container.
+    pub name:  String,
+    /// The workspace that is bound as the container's result directory.
+    workspace: PathBuf,
+    /// The number of calls executed in the container so far, including the current one.
+    uses:      usize,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Keeps idle containers around, such that calls of the same package do not have to create and start a new container every time.
+#[derive(Debug)]
+pub struct ContainerPool {
+    /// Determines how many containers we keep and for how long.
+    config:     ContainerPoolConfig,
+    /// The directory in which the workspaces of containers are created.
+    workspaces: PathBuf,
+    /// The idle containers, by [`pool_key()`]. The most recently used ones are at the end.
+    idle:       Mutex<HashMap<String, Vec<IdleContainer>>>,
+}
+
+impl ContainerPool {
+    /// Constructor for the ContainerPool.
+    ///
+    /// # Arguments
+    /// - `config`: The [`ContainerPoolConfig`] that determines how many containers we keep and for how long.
+    /// - `workspaces`: The directory in which to create the workspaces of containers. Preferably lives on the same filesystem as the results.
+    ///
+    /// # Returns
+    /// A new, empty ContainerPool.
+    #[inline]
+    pub fn new(config: ContainerPoolConfig, workspaces: impl Into<PathBuf>) -> Self {
+        Self { config, workspaces: workspaces.into(), idle: Mutex::new(HashMap::new()) }
+    }
+
+    /// Takes an idle container in which the given call can be executed, or launches a new one if there is none.
+    ///
+    /// Any containers that have been idle for too long are removed while at it.
+    ///
+    /// # Arguments
+    /// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+    /// - `info`: The [`ExecuteInfo`] describing the call.
+    /// - `user`: The user on whose behalf the call is executed, if known. Containers are never reused for calls of another user.
+    ///
+    /// # Returns
+    /// A [`PooledContainer`] to execute the call in using [`ContainerPool::exec_streaming()`].
+    ///
+    /// # Errors
+    /// This function errors if there was no idle container and we failed to launch a new one.
+    pub async fn acquire(&self, opts: &DockerOptions, info: &ExecuteInfo, user: Option<&str>) -> Result<PooledContainer, Error> {
+        let key: String = pool_key(info, user);
+
+        // Take the most recently used container, collecting any that expired
+        let timeout: Duration = Duration::from_secs(self.config.idle_timeout);
+        let (container, expired): (Option<IdleContainer>, Vec<IdleContainer>) = {
+            let mut idle = self.idle.lock().unwrap();
+            let mut expired: Vec<IdleContainer> = vec![];
+            for containers in idle.values_mut() {
+                let (old, fresh): (Vec<IdleContainer>, Vec<IdleContainer>) = containers.drain(..).partition(|c| c.since.elapsed() > timeout);
+                expired.extend(old);
+                *containers = fresh;
+            }
+            idle.retain(|_, containers| !containers.is_empty());
+            (idle.get_mut(&key).and_then(Vec::pop), expired)
+        };
+        for container in expired {
+            debug!("Idle container '{}' expired", container.name);
+            self.remove(opts, &container.name, &container.workspace).await;
+        }
+        if let Some(container) = container {
+            debug!("Reusing idle container '{}' (used {} time(s) before)", container.name, container.uses);
+            return Ok(PooledContainer { key, name: container.name, workspace: container.workspace, uses: container.uses + 1 });
+        }
+
+        // Otherwise, launch a new one with a workspace of its own
+        let workspace: PathBuf = self.workspaces.join(uuid::Uuid::new_v4().to_string());
+        if let Err(err) = tfs::create_dir_all(&workspace).await {
+            return Err(Error::WorkspaceCreateError { path: workspace, err });
+        }
+        let mut info: ExecuteInfo = info.clone();
+        info.binds.retain(|bind| bind.container != Path::new(RESULT_PATH));
+        info.binds.push(match VolumeBind::new_readwrite(&workspace, RESULT_PATH) {
+            Ok(bind) => bind,
+            Err(err) => return Err(Error::WorkspaceBindError { path: workspace, err }),
+        });
+        let image = Box::new(info.image.clone());
+        match docker::launch_idle(opts, info).await {
+            Ok(name) => {
+                debug!("Launched idle container '{}'", name);
+                Ok(PooledContainer { key, name, workspace, uses: 1 })
+            },
+            Err(err) => {
+                if let Err(err) = tfs::remove_dir_all(&workspace).await {
+                    warn!("Failed to remove container workspace '{}': {}", workspace.display(), err);
+                }
+                Err(Error::LaunchError { image, err })
+            },
+        }
+    }
+
+    /// Executes a call in a container taken from this pool, and waits for it to complete while reporting its output.
+    ///
+    /// # Arguments
+    /// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+    /// - `container`: The [`PooledContainer`] to execute the call in.
+    /// - `command`: The arguments to branelet, i.e., the command of the call's [`ExecuteInfo`].
+    /// - `on_output`: A callback that is called with every chunk of output the call writes, together with the channel it was written to.
+    ///
+    /// # Returns
+    /// The return code of the call, its stdout and its stderr (in that order).
+    ///
+    /// # Errors
+    /// This function errors if we failed to execute the call in the container.
+    pub async fn exec_streaming<F, Fut>(
+        &self,
+        opts: &DockerOptions,
+        container: &PooledContainer,
+        command: &[String],
+        on_output: F,
+    ) -> Result<(i32, String, String), Error>
+    where
+        F: FnMut(OutputChannel, String) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        match docker::exec_streaming(opts, &container.name, command, on_output).await {
+            Ok(res) => Ok(res),
+            Err(err) => Err(Error::ExecError { name: container.name.clone(), err }),
+        }
+    }
+
+    /// Gives a container back to the pool after a call completed in it.
+    ///
+    /// The result of the call is moved from the container's workspace to its result directory first. The container is removed instead if the
+    /// pool is full, it has been used too often or we failed to clear its workspace.
+    ///
+    /// # Arguments
+    /// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+    /// - `container`: The [`PooledContainer`] to give back.
+    /// - `result`: The (existing) directory where the result of the call should end up, if it has any.
+    ///
+    /// # Errors
+    /// This function errors if we failed to move the result of the call.
+    pub async fn release(&self, opts: &DockerOptions, container: PooledContainer, result: Option<&Path>) -> Result<(), Error> {
+        let res: Result<(), Error> = match result {
+            Some(result) => move_result(&container.workspace, result).await,
+            None => Ok(()),
+        };
+        let mut reuse: bool = res.is_ok() && container.uses < self.config.max_uses;
+        if reuse {
+            if let Err(err) = clear_workspace(&container.workspace).await {
+                warn!("{err} (removing container '{}')", container.name);
+                reuse = false;
+            }
+        }
+
+        // Put it back if there's room
+        if reuse {
+            let mut idle = self.idle.lock().unwrap();
+            let containers: &mut Vec<IdleContainer> = idle.entry(container.key).or_default();
+            if containers.len() < self.config.max_idle {
+                containers.push(IdleContainer {
+                    name:      container.name,
+                    workspace: container.workspace,
+                    uses:      container.uses,
+                    since:     Instant::now(),
+                });
+                return res;
+            }
+        }
+        self.remove(opts, &container.name, &container.workspace).await;
+        res
+    }
+
+    /// Removes a container taken from this pool instead of giving it back, e.g., because the call in it hung.
+    ///
+    /// # Arguments
+    /// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+    /// - `container`: The [`PooledContainer`] to remove.
+    #[inline]
+    pub async fn discard(&self, opts: &DockerOptions, container: PooledContainer) { self.remove(opts, &container.name, &container.workspace).await }
+
+    /// Removes a container and its workspace. Any errors are only logged, as the call in it has already completed.
+    ///
+    /// # Arguments
+    /// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+    /// - `name`: The name of the container to remove.
+    /// - `workspace`: The workspace of the container to remove.
+    async fn remove(&self, opts: &DockerOptions, name: &str, workspace: &Path) {
+        debug!("Removing pooled container '{}'...", name);
+        if let Err(err) = docker::remove(opts, name).await {
+            warn!("Failed to remove pooled container '{}': {}", name, err);
+        }
+        if let Err(err) = tfs::remove_dir_all(workspace).await {
+            warn!("Failed to remove container workspace '{}': {}", workspace.display(), err);
+        }
+    }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use specifications::container::Image;
+
+    use super::*;
+    use crate::docker::{ImageSource, Network};
+
+
+    #[test]
+    fn pool_key_separates_users() {
+        let info: ExecuteInfo = ExecuteInfo::new(
+            "test",
+            Image::new("hello_world", Some("1.0.0"), None::<&str>),
+            ImageSource::Registry("hello_world:1.0.0".into()),
+            vec![],
+            vec![VolumeBind::new_readwrite("/tmp/result", RESULT_PATH).unwrap()],
+            HashSet::new(),
+            Network::None,
+        );
+        assert_eq!(pool_key(&info, Some("amy")), pool_key(&info, Some("amy")));
+        assert_ne!(pool_key(&info, Some("amy")), pool_key(&info, Some("bob")));
+        assert_ne!(pool_key(&info, Some("amy")), pool_key(&info, None));
+
+        // The result directory differs per call, so it must not affect the key
+        let mut other: ExecuteInfo = info.clone();
+        other.binds = vec![VolumeBind::new_readwrite("/tmp/other", RESULT_PATH).unwrap()];
+        assert_eq!(pool_key(&info, Some("amy")), pool_key(&other, Some("amy")));
+    }
+}
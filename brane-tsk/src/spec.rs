@@ -4,7 +4,7 @@
 //  Created:
 //    24 Oct 2022, 16:42:17
 //  Last edited:
-//    12 Apr 2023, 12:57:54
+//    16 Oct 2026, 00:33:35
 //  Auto updated?
 //    Yes
 //
@@ -18,6 +18,8 @@ use std::str::FromStr;
 use brane_ast::Workflow;
 use brane_exe::FullValue;
 use log::warn;
+use specifications::checking::PolicyDenial;
+use specifications::reporting::TaskFailure;
 use specifications::working::TaskStatus;
 use uuid::Uuid;
 
@@ -54,13 +56,18 @@ macro_rules! return_status_val {
     }};
 }
 
-/// Defines a helper macro that parses a code, stdout, stderr triplet for a JobStatus before returning it.
+/// Defines a helper macro that parses a [`TaskFailure`] for a JobStatus before returning it.
+///
+/// Older workers send a code, stdout, stderr triplet instead, which is converted to one.
 macro_rules! return_status_failed {
     (JobStatus:: $status:ident, $str:ident) => {{
         if let Some(s) = $str {
-            match serde_json::from_str::<(i32, String, String)>(&s) {
-                Ok((code, stdout, stderr)) => Ok(JobStatus::$status(code, stdout, stderr)),
-                Err(err) => Err(ExecuteError::StatusTripletParseError { status: TaskStatus::$status, raw: s, err }),
+            match serde_json::from_str::<TaskFailure>(&s) {
+                Ok(failure) => Ok(JobStatus::$status(failure)),
+                Err(_) => match serde_json::from_str::<(i32, String, String)>(&s) {
+                    Ok((code, stdout, stderr)) => Ok(JobStatus::$status(TaskFailure::new(code, None, &stdout, &stderr, None))),
+                    Err(err) => Err(ExecuteError::StatusTripletParseError { status: TaskStatus::$status, raw: s, err }),
+                },
             }
         } else {
             Err(ExecuteError::StatusEmptyStringError { status: TaskStatus::$status })
@@ -203,8 +210,8 @@ pub enum JobStatus {
     // Checker events
     /// The job has been authorized by the job's checker(s).
     Authorized,
-    /// The job has been denied by the job's checker(s).
-    Denied,
+    /// The job has been denied by the job's checker(s), which explained why.
+    Denied(PolicyDenial),
     /// Authorization has failed.
     AuthorizationFailed(String),
 
@@ -241,8 +248,8 @@ pub enum JobStatus {
     Stopped,
     /// brane-let could not decode the output from the package call
     DecodingFailed(String),
-    /// The container has exited with a non-zero status code
-    Failed(i32, String, String),
+    /// The container has exited with a non-zero status code, for the given reasons
+    Failed(TaskFailure),
 }
 
 impl JobStatus {
@@ -273,7 +280,7 @@ impl JobStatus {
                 return_status!(JobStatus::Authorized, value)
             },
             Denied => {
-                return_status!(JobStatus::Denied, value)
+                return_status_val!(JobStatus::Denied, value)
             },
             AuthorizationFailed => {
                 return_status_str!(JobStatus::AuthorizationFailed, value)
@@ -343,7 +350,7 @@ impl JobStatus {
             Received => 1,
 
             Authorized => 2,
-            Denied => 2,
+            Denied(_) => 2,
             AuthorizationFailed(_) => 2,
 
             Created => 3,
@@ -383,7 +390,7 @@ impl From<&JobStatus> for TaskStatus {
             Received => Self::Received,
 
             Authorized => Self::Authorized,
-            Denied => Self::Denied,
+            Denied(_) => Self::Denied,
             AuthorizationFailed(_) => Self::AuthorizationFailed,
 
             Created => Self::Created,
@@ -402,7 +409,7 @@ impl From<&JobStatus> for TaskStatus {
             Finished(_) => Self::Finished,
             Stopped => Self::Stopped,
             DecodingFailed(_) => Self::DecodingFailed,
-            Failed(_, _, _) => Self::Failed,
+            Failed(_) => Self::Failed,
         }
     }
 }
@@ -419,7 +426,7 @@ impl From<&JobStatus> for (TaskStatus, Option<String>) {
             Received => (TaskStatus::Received, None),
 
             Authorized => (TaskStatus::Authorized, None),
-            Denied => (TaskStatus::Denied, None),
+            Denied(denial) => (TaskStatus::Denied, Some(serde_json::to_string(&denial).unwrap())),
             AuthorizationFailed(err) => (TaskStatus::AuthorizationFailed, Some(err.clone())),
 
             Created => (TaskStatus::Created, None),
@@ -438,7 +445,7 @@ impl From<&JobStatus> for (TaskStatus, Option<String>) {
             Finished(val) => (TaskStatus::Finished, Some(serde_json::to_string(&val).unwrap())),
             Stopped => (TaskStatus::Stopped, None),
             DecodingFailed(err) => (TaskStatus::DecodingFailed, Some(err.clone())),
-            Failed(code, stdout, stderr) => (TaskStatus::Failed, Some(serde_json::to_string(&(code, stdout, stderr)).unwrap())),
+            Failed(failure) => (TaskStatus::Failed, Some(serde_json::to_string(&failure).unwrap())),
         }
     }
 }
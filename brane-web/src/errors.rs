@@ -0,0 +1,81 @@
+//  ERRORS.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:32
+//  Last edited:
+//    16 Oct 2026, 01:02:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Contains errors used within the brane-web package only.
+//
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+
+use reqwest::StatusCode;
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to collecting the overview of the instance.
+#[derive(Debug)]
+pub enum OverviewError {
+    /// Failed to send a request to a service.
+    RequestError { what: &'static str, address: String, err: reqwest::Error },
+    /// Failed to send a request through the proxy service.
+    ProxyError { what: &'static str, address: String, err: brane_prx::errors::ClientError },
+    /// A service replied with a non-success status code.
+    RequestFailure { what: &'static str, address: String, code: StatusCode, message: Option<String> },
+    /// Failed to download the body of a reply.
+    ResponseDownloadError { what: &'static str, address: String, err: reqwest::Error },
+    /// Failed to parse the body of a reply.
+    ResponseParseError { what: &'static str, address: String, err: serde_json::Error },
+    /// The API replied with GraphQL errors.
+    GraphQlError { address: String, errors: Vec<String> },
+    /// Failed to connect to the driver.
+    DriverConnectError { address: String, err: specifications::driving::Error },
+    /// The driver failed to list the active sessions.
+    DriverListError { address: String, err: tonic::Status },
+}
+
+impl Display for OverviewError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use OverviewError::*;
+        match self {
+            RequestError { what, address, .. } => write!(f, "Failed to send request for {what} to '{address}'"),
+            ProxyError { what, address, .. } => write!(f, "Failed to send request for {what} to '{address}' through the proxy service"),
+            RequestFailure { what, address, code, message } => write!(
+                f,
+                "Request for {} to '{}' failed with status code {} ({}){}",
+                what,
+                address,
+                code.as_u16(),
+                code.canonical_reason().unwrap_or("???"),
+                if let Some(message) = message { format!(": {message}") } else { String::new() }
+            ),
+            ResponseDownloadError { what, address, .. } => write!(f, "Failed to download {what} from '{address}'"),
+            ResponseParseError { what, address, .. } => write!(f, "Failed to parse {what} from '{address}'"),
+            GraphQlError { address, errors } => write!(f, "Failed to query packages from '{}': {}", address, errors.join("; ")),
+            DriverConnectError { address, .. } => write!(f, "Failed to connect to driver '{address}'"),
+            DriverListError { address, err } => write!(f, "Failed to list active sessions of driver '{}': {}", address, err.message()),
+        }
+    }
+}
+
+impl Error for OverviewError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use OverviewError::*;
+        match self {
+            RequestError { err, .. } => Some(err),
+            ProxyError { err, .. } => Some(err),
+            RequestFailure { .. } => None,
+            ResponseDownloadError { err, .. } => Some(err),
+            ResponseParseError { err, .. } => Some(err),
+            GraphQlError { .. } => None,
+            DriverConnectError { err, .. } => Some(err),
+            DriverListError { err, .. } => Some(err),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//  LIB.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:32
+//  Last edited:
+//    16 Oct 2026, 01:02:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   The `brane-web` service is an optional, read-only dashboard for a
+//!   central node. It shows the packages, datasets, active workflows and
+//!   domain health of the instance in a browser.
+//
+
+// Declare modules
+pub mod errors;
+pub mod overview;
+pub mod page;
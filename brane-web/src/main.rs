@@ -0,0 +1,179 @@
+//  MAIN.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:32
+//  Last edited:
+//    16 Oct 2026, 01:39:25
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Entrypoint to the `brane-web` service.
+//
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use brane_cfg::info::Info as _;
+use brane_cfg::node::{CentralConfig, NodeConfig};
+use brane_prx::client::ProxyClient;
+use brane_shr::logging::{self, LogFormat};
+use brane_web::overview::{Context, Overview};
+use brane_web::page;
+use clap::Parser;
+use dotenvy::dotenv;
+use error_trace::trace;
+use humanlog::{DebugMode, HumanLogger};
+use log::{debug, error, info, warn, LevelFilter};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use warp::Filter as _;
+
+
+/***** ARGUMENTS *****/
+#[derive(Parser)]
+#[clap(version = env!("CARGO_PKG_VERSION"))]
+struct Opts {
+    /// Print debug info
+    #[clap(short, long, action, help = "If given, prints additional logging information.", env = "TRACE")]
+    trace: bool,
+
+    /// Node environment metadata store.
+    #[clap(
+        short,
+        long,
+        default_value = "/node.yml",
+        help = "The path to the node environment configuration. This defines things such as where local services may be found or where to store \
+                files, as wel as this service's service address.",
+        env = "NODE_CONFIG_PATH"
+    )]
+    node_config_path: PathBuf,
+
+    /// The format of the logs.
+    #[clap(
+        long,
+        default_value = "text",
+        help = "The format in which to write logs. Can be 'text' (human-readable) or 'json' (one JSON object per line, for log aggregators).",
+        env = "LOG_FORMAT"
+    )]
+    log_format: LogFormat,
+
+    /// The address on which to serve the dashboard.
+    #[clap(short, long, default_value = "0.0.0.0:50070", help = "The address on which to serve the dashboard.", env = "ADDRESS")]
+    address:   SocketAddr,
+    /// How often the page refreshes itself.
+    #[clap(
+        long,
+        default_value = "30",
+        help = "The number of seconds after which the dashboard page refreshes itself. Use '0' to disable refreshing.",
+        env = "REFRESH"
+    )]
+    refresh:   u64,
+    /// The API token with which to authenticate to the driver.
+    #[clap(
+        long,
+        help = "The API token with which to authenticate to `brane-drv`. Required if the driver runs with `--require-auth`, in which case the \
+                dashboard only shows the sessions of the token's user.",
+        env = "DRV_TOKEN"
+    )]
+    drv_token: Option<String>,
+}
+
+
+
+
+
+/***** ENTRYPOINT *****/
+#[tokio::main]
+async fn main() {
+    // Load arguments & environment stuff
+    dotenv().ok();
+    let opts = Opts::parse();
+
+    // Configure the logger.
+    match opts.log_format {
+        LogFormat::Text => {
+            if let Err(err) = HumanLogger::terminal(if opts.trace { DebugMode::Full } else { DebugMode::Debug }).init() {
+                eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+            }
+        },
+        LogFormat::Json => {
+            if let Err(err) = logging::init("brane-web", LogFormat::Json, if opts.trace { LevelFilter::Trace } else { LevelFilter::Debug }) {
+                eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
+            }
+        },
+    }
+    info!("Initializing brane-web v{}...", env!("CARGO_PKG_VERSION"));
+
+    // Load the config, making sure it's a central config
+    debug!("Loading node.yml file '{}'...", opts.node_config_path.display());
+    let node_config: NodeConfig = match NodeConfig::from_path(&opts.node_config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            error!("Failed to load NodeConfig file: {}", err);
+            std::process::exit(1);
+        },
+    };
+    let central_cfg: CentralConfig = match node_config.node.try_into_central() {
+        Some(config) => config,
+        None => {
+            error!("Presented with a non-central `node.yml` file (please adapt it to provide properties for a central node)");
+            std::process::exit(1);
+        },
+    };
+
+    // Create the context that tells us where to find the services
+    let context: Arc<Context> = Arc::new(Context {
+        api:   central_cfg.services.api.address,
+        drv:   central_cfg.services.drv.address,
+        token: opts.drv_token,
+        proxy: ProxyClient::new(central_cfg.services.prx.address()),
+    });
+    let context = warp::any().map(move || context.clone());
+
+    // Next, create the warp server
+    let refresh: u64 = opts.refresh;
+    let index = warp::get().and(warp::path::end()).and(context.clone()).and_then(move |context: Arc<Context>| async move {
+        debug!("Handling GET on `/` (i.e., render dashboard)...");
+        Ok::<_, Infallible>(warp::reply::html(page::render(&Overview::collect(&context).await, refresh)))
+    });
+    let overview = warp::get().and(warp::path("overview")).and(warp::path::end()).and(context).and_then(|context: Arc<Context>| async move {
+        debug!("Handling GET on `/overview` (i.e., collect overview)...");
+        Ok::<_, Infallible>(warp::reply::json(&Overview::collect(&context).await))
+    });
+    let health = warp::get().and(warp::path("health")).and(warp::path::end()).map(|| "OK!\n");
+    let paths = index.or(overview).or(health);
+
+    // Launch it
+    let handle = warp::serve(paths).try_bind_with_graceful_shutdown(opts.address, async {
+        // Register a SIGTERM handler to be Docker-friendly
+        let mut handler: Signal = match signal(SignalKind::terminate()) {
+            Ok(handler) => handler,
+            Err(err) => {
+                error!("{}", trace!(("Failed to register SIGTERM signal handler"), err));
+                warn!("Service will NOT shutdown gracefully on SIGTERM");
+                loop {
+                    tokio::time::sleep(Duration::from_secs(24 * 3600)).await;
+                }
+            },
+        };
+
+        // Wait until we receive such a signal after which we terminate the server
+        handler.recv().await;
+        info!("Received SIGTERM, shutting down gracefully...");
+    });
+
+    match handle {
+        Ok((addr, srv)) => {
+            info!("Now serving @ '{addr}'");
+            srv.await
+        },
+        Err(err) => {
+            error!("{}", trace!(("Failed to serve at '{}'", opts.address), err));
+            std::process::exit(1);
+        },
+    }
+}
@@ -0,0 +1,386 @@
+//  OVERVIEW.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:32
+//  Last edited:
+//    16 Oct 2026, 01:39:25
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Collects the overview of the instance shown by the dashboard by
+//!   querying `brane-api`, `brane-drv` and the registries of the domains.
+//!
+//!   Every part of the overview is collected independently, so that a
+//!   single unreachable service only blanks its own part of the page.
+//
+
+use std::collections::HashMap;
+
+use brane_prx::client::ProxyClient;
+use brane_prx::spec::NewPathRequestTlsOptions;
+use chrono::{DateTime, TimeZone as _, Utc};
+use error_trace::ErrorTrace as _;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specifications::address::Address;
+use specifications::data::DataInfo;
+use specifications::driving::{DriverServiceClient, ListSessionsRequest};
+
+pub use crate::errors::OverviewError as Error;
+
+
+/***** CONSTANTS *****/
+/// The GraphQL query with which we retrieve the packages from the API.
+const PACKAGES_QUERY: &str = "{ packages { name version kind description owners created } }";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Sends a GET-request to the given address and returns its body if the request succeeded.
+///
+/// # Arguments
+/// - `what`: A description of what we're retrieving, for use in errors.
+/// - `address`: The address to send the request to.
+///
+/// # Returns
+/// The body of the reply.
+///
+/// # Errors
+/// This function errors if we failed to send the request or the service replied with a non-success status code.
+async fn get_text(what: &'static str, address: String) -> Result<String, Error> {
+    let res: reqwest::Response = match reqwest::get(&address).await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestError { what, address, err }),
+    };
+    read_text(what, address, res).await
+}
+
+/// Reads the body of the given reply, failing if it has a non-success status code.
+///
+/// # Arguments
+/// - `what`: A description of what we're retrieving, for use in errors.
+/// - `address`: The address the request was sent to, for use in errors.
+/// - `res`: The [`reqwest::Response`] to read.
+///
+/// # Returns
+/// The body of the reply.
+///
+/// # Errors
+/// This function errors if the reply has a non-success status code or we failed to download its body.
+async fn read_text(what: &'static str, address: String, res: reqwest::Response) -> Result<String, Error> {
+    let code = res.status();
+    if !code.is_success() {
+        return Err(Error::RequestFailure { what, address, code, message: res.text().await.ok() });
+    }
+    match res.text().await {
+        Ok(body) => Ok(body),
+        Err(err) => Err(Error::ResponseDownloadError { what, address, err }),
+    }
+}
+
+
+/// Returns the list in the given result, or records its error and returns an empty list.
+///
+/// # Arguments
+/// - `res`: The result of collecting a part of the overview.
+/// - `errors`: The list of errors to record the error in, if any.
+///
+/// # Returns
+/// The collected list, or an empty one if collecting it failed.
+fn or_record<T>(res: Result<Vec<T>, Error>, errors: &mut Vec<String>) -> Vec<T> {
+    match res {
+        Ok(list) => list,
+        Err(err) => {
+            warn!("{}", err.trace());
+            errors.push(err.trace().to_string());
+            vec![]
+        },
+    }
+}
+
+
+
+/***** AUXILLARY *****/
+/// Describes a package in the [`Overview`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PackageSummary {
+    /// The name of the package.
+    pub name: String,
+    /// The version of the package.
+    pub version: String,
+    /// The kind of the package (e.g., `ecu`).
+    pub kind: String,
+    /// A (short) description of the package, if any.
+    pub description: Option<String>,
+    /// The owners of the package.
+    pub owners: Vec<String>,
+    /// When the package was uploaded.
+    pub created: DateTime<Utc>,
+}
+
+/// Describes a dataset in the [`Overview`].
+#[derive(Clone, Debug, Serialize)]
+pub struct DatasetSummary {
+    /// The name of the dataset.
+    pub name: String,
+    /// A (short) description of the dataset, if any.
+    pub description: Option<String>,
+    /// The domains that advertise the dataset.
+    pub locations: Vec<String>,
+    /// When the dataset was created.
+    pub created: DateTime<Utc>,
+}
+
+/// Describes an active workflow session in the [`Overview`].
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionSummary {
+    /// The identifier of the session.
+    pub id: String,
+    /// The user who created the session, if known.
+    pub user: Option<String>,
+    /// When the session was created.
+    pub created: Option<DateTime<Utc>>,
+    /// When a workflow was last executed in the session.
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// Describes the health of a domain in the [`Overview`].
+#[derive(Clone, Debug, Serialize)]
+pub struct DomainHealth {
+    /// The name of the domain.
+    pub name:     String,
+    /// The address of the registry of the domain.
+    pub registry: String,
+    /// The health reported by the registry (e.g., `OK`), or why we could not reach it.
+    pub status:   String,
+    /// Whether the registry reported itself to be fully healthy.
+    pub healthy:  bool,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines where to find the services that the overview is collected from.
+#[derive(Debug)]
+pub struct Context {
+    /// The address of the `brane-api` service.
+    pub api:   Address,
+    /// The address of the `brane-drv` service.
+    pub drv:   Address,
+    /// The API token with which to authenticate to the `brane-drv` service, if any.
+    pub token: Option<String>,
+    /// The client for the proxy service, used to reach the registries of the domains.
+    pub proxy: ProxyClient,
+}
+
+
+
+/// The overview of the instance shown by the dashboard.
+#[derive(Clone, Debug, Serialize)]
+pub struct Overview {
+    /// When this overview was collected.
+    pub generated: DateTime<Utc>,
+    /// The packages available in the instance, sorted by name and then version.
+    pub packages:  Vec<PackageSummary>,
+    /// The datasets available in the instance, sorted by name.
+    pub datasets:  Vec<DatasetSummary>,
+    /// The active workflow sessions, most recently active first.
+    pub sessions:  Vec<SessionSummary>,
+    /// The health of every domain in the instance, sorted by name.
+    pub domains:   Vec<DomainHealth>,
+    /// The parts of the overview that we failed to collect, and why.
+    pub errors:    Vec<String>,
+}
+
+impl Overview {
+    /// Collects the overview by querying the services in the given context.
+    ///
+    /// Parts that cannot be collected are left empty and the reason is recorded in `errors`.
+    ///
+    /// # Arguments
+    /// - `context`: The [`Context`] that defines where to find the services.
+    ///
+    /// # Returns
+    /// A new Overview.
+    pub async fn collect(context: &Context) -> Self {
+        let (packages, datasets, sessions, domains) = tokio::join!(
+            packages(&context.api),
+            datasets(&context.api),
+            sessions(&context.drv, context.token.clone()),
+            domains(&context.api, &context.proxy)
+        );
+
+        let mut errors: Vec<String> = vec![];
+        let packages: Vec<PackageSummary> = or_record(packages, &mut errors);
+        let datasets: Vec<DatasetSummary> = or_record(datasets, &mut errors);
+        let sessions: Vec<SessionSummary> = or_record(sessions, &mut errors);
+        let domains: Vec<DomainHealth> = or_record(domains, &mut errors);
+
+        Self { generated: Utc::now(), packages, datasets, sessions, domains, errors }
+    }
+}
+
+
+
+/// Retrieves the packages known to the API.
+///
+/// # Arguments
+/// - `api`: The address of the `brane-api` service.
+///
+/// # Returns
+/// The packages, sorted by name and then version.
+///
+/// # Errors
+/// This function errors if we failed to query the API.
+pub async fn packages(api: &Address) -> Result<Vec<PackageSummary>, Error> {
+    /// The reply to a GraphQL query.
+    #[derive(Deserialize)]
+    struct Reply {
+        data:   Option<Data>,
+        #[serde(default)]
+        errors: Vec<GraphQlError>,
+    }
+    /// The data in a reply to the [`PACKAGES_QUERY`].
+    #[derive(Deserialize)]
+    struct Data {
+        packages: Vec<PackageSummary>,
+    }
+    /// A single error in a reply to a GraphQL query.
+    #[derive(Deserialize)]
+    struct GraphQlError {
+        message: String,
+    }
+
+    let address: String = format!("{api}/graphql");
+    debug!("Retrieving packages from '{address}'...");
+    let res: reqwest::Response = match reqwest::Client::new().post(&address).json(&json!({ "query": PACKAGES_QUERY })).send().await {
+        Ok(res) => res,
+        Err(err) => return Err(Error::RequestError { what: "packages", address, err }),
+    };
+    let body: String = read_text("packages", address.clone(), res).await?;
+    let reply: Reply = match serde_json::from_str(&body) {
+        Ok(reply) => reply,
+        Err(err) => return Err(Error::ResponseParseError { what: "packages", address, err }),
+    };
+    if !reply.errors.is_empty() {
+        return Err(Error::GraphQlError { address, errors: reply.errors.into_iter().map(|err| err.message).collect() });
+    }
+
+    let mut packages: Vec<PackageSummary> = reply.data.map(|data| data.packages).unwrap_or_default();
+    packages.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name).then_with(|| lhs.version.cmp(&rhs.version)));
+    Ok(packages)
+}
+
+/// Retrieves the datasets known to the API.
+///
+/// # Arguments
+/// - `api`: The address of the `brane-api` service.
+///
+/// # Returns
+/// The datasets, sorted by name.
+///
+/// # Errors
+/// This function errors if we failed to query the API.
+pub async fn datasets(api: &Address) -> Result<Vec<DatasetSummary>, Error> {
+    let address: String = format!("{api}/data/info");
+    debug!("Retrieving datasets from '{address}'...");
+    let body: String = get_text("datasets", address.clone()).await?;
+    let index: HashMap<String, DataInfo> = match serde_json::from_str(&body) {
+        Ok(index) => index,
+        Err(err) => return Err(Error::ResponseParseError { what: "datasets", address, err }),
+    };
+
+    let mut datasets: Vec<DatasetSummary> = index
+        .into_values()
+        .map(|info| {
+            let mut locations: Vec<String> = info.access.into_keys().collect();
+            locations.sort();
+            DatasetSummary { name: info.name, description: info.description, locations, created: info.created }
+        })
+        .collect();
+    datasets.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+    Ok(datasets)
+}
+
+/// Retrieves the active workflow sessions of the driver.
+///
+/// # Arguments
+/// - `drv`: The address of the `brane-drv` service.
+/// - `token`: The API token with which to authenticate to the driver, if any. If given, only the sessions of its user are returned.
+///
+/// # Returns
+/// The sessions, most recently active first.
+///
+/// # Errors
+/// This function errors if we failed to connect to or query the driver.
+pub async fn sessions(drv: &Address, token: Option<String>) -> Result<Vec<SessionSummary>, Error> {
+    let address: String = drv.to_string();
+    debug!("Retrieving active sessions from '{address}'...");
+    let mut client: DriverServiceClient = match DriverServiceClient::connect(address.clone()).await {
+        Ok(client) => client.with_token(token),
+        Err(err) => return Err(Error::DriverConnectError { address, err }),
+    };
+    let reply = match client.list_sessions(ListSessionsRequest { user: None }).await {
+        Ok(reply) => reply.into_inner(),
+        Err(err) => return Err(Error::DriverListError { address, err }),
+    };
+
+    let time = |secs: u64| -> Option<DateTime<Utc>> { Utc.timestamp_opt(secs as i64, 0).single() };
+    Ok(reply
+        .sessions
+        .into_iter()
+        .map(|info| SessionSummary { id: info.uuid, user: info.user, created: time(info.created), last_activity: time(info.last_activity) })
+        .collect())
+}
+
+/// Retrieves the health of every domain by asking the API for their registries and then querying their `/health` endpoints.
+///
+/// Unreachable registries do not make this function fail, but are reported as unhealthy.
+///
+/// # Arguments
+/// - `api`: The address of the `brane-api` service.
+/// - `proxy`: The [`ProxyClient`] with which to reach the registries.
+///
+/// # Returns
+/// The health of the domains, sorted by name.
+///
+/// # Errors
+/// This function errors if we failed to retrieve the registries from the API.
+pub async fn domains(api: &Address, proxy: &ProxyClient) -> Result<Vec<DomainHealth>, Error> {
+    let address: String = format!("{api}/infra/registries");
+    debug!("Retrieving domain registries from '{address}'...");
+    let body: String = get_text("domain registries", address.clone()).await?;
+    let registries: HashMap<String, Address> = match serde_json::from_str(&body) {
+        Ok(registries) => registries,
+        Err(err) => return Err(Error::ResponseParseError { what: "domain registries", address, err }),
+    };
+
+    let mut domains: Vec<DomainHealth> = Vec::with_capacity(registries.len());
+    for (name, registry) in registries {
+        let address: String = format!("{registry}/health");
+        debug!("Retrieving health of domain '{name}' from '{address}'...");
+        let tls: NewPathRequestTlsOptions = NewPathRequestTlsOptions { location: name.clone(), use_client_auth: false };
+        let status: Result<String, Error> = match proxy.get(&address, Some(tls)).await {
+            Ok(Ok(res)) => read_text("domain health", address.clone(), res).await,
+            Ok(Err(err)) => Err(Error::RequestError { what: "domain health", address: address.clone(), err }),
+            Err(err) => Err(Error::ProxyError { what: "domain health", address: address.clone(), err }),
+        };
+        let (status, healthy): (String, bool) = match status {
+            Ok(body) => (body.trim().replace('\n', " "), body.trim() == "OK!"),
+            Err(err) => {
+                warn!("{err}");
+                (err.to_string(), false)
+            },
+        };
+        domains.push(DomainHealth { name, registry: registry.to_string(), status, healthy });
+    }
+    domains.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+    Ok(domains)
+}
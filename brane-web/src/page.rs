@@ -0,0 +1,238 @@
+//  PAGE.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 01:02:33
+//  Last edited:
+//    16 Oct 2026, 01:02:43
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Renders an [`Overview`] as the HTML page of the dashboard.
+//!
+//!   The page is fully rendered server-side (no JavaScript) and refreshes
+//!   itself periodically.
+//
+
+use std::fmt::Write as _;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::overview::Overview;
+
+
+/***** CONSTANTS *****/
+/// The stylesheet embedded in the page.
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0; }
+.generated { color: #777; margin-top: 0.2em; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { text-align: left; padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; }
+th { background: #f4f4f4; }
+.ok { color: #1a7f37; font-weight: bold; }
+.bad { color: #cf222e; font-weight: bold; }
+.errors { background: #fff0f0; border: 1px solid #cf222e; padding: 0.5em 1em; margin-bottom: 2em; }
+.empty { color: #777; font-style: italic; }";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes the given text such that it can be safely embedded in HTML.
+///
+/// # Arguments
+/// - `text`: The text to escape.
+///
+/// # Returns
+/// The escaped text.
+fn escape(text: &str) -> String {
+    let mut res: String = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Formats the given timestamp for display.
+///
+/// # Arguments
+/// - `time`: The timestamp to format, if known.
+///
+/// # Returns
+/// The formatted timestamp, or `-` if it is unknown.
+fn time(time: Option<&DateTime<Utc>>) -> String {
+    match time {
+        Some(time) => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => "-".into(),
+    }
+}
+
+/// Writes a table with the given header and rows to the given page.
+///
+/// # Arguments
+/// - `page`: The page to write to.
+/// - `title`: The title of the section the table is in.
+/// - `header`: The names of the columns.
+/// - `rows`: The cells of every row. Only [`Cell::Text`]s are escaped.
+fn table(page: &mut String, title: &str, header: &[&str], rows: Vec<Vec<Cell>>) {
+    let _ = writeln!(page, "<h2>{} ({})</h2>", escape(title), rows.len());
+    if rows.is_empty() {
+        page.push_str("<p class=\"empty\">None</p>\n");
+        return;
+    }
+    page.push_str("<table>\n<tr>");
+    for name in header {
+        let _ = write!(page, "<th>{}</th>", escape(name));
+    }
+    page.push_str("</tr>\n");
+    for row in rows {
+        page.push_str("<tr>");
+        for cell in row {
+            match cell {
+                Cell::Text(text) => {
+                    let _ = write!(page, "<td>{}</td>", escape(&text));
+                },
+                Cell::Html(html) => {
+                    let _ = write!(page, "<td>{html}</td>");
+                },
+            }
+        }
+        page.push_str("</tr>\n");
+    }
+    page.push_str("</table>\n");
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A single cell in a table on the page.
+enum Cell {
+    /// Text that is escaped before it is written.
+    Text(String),
+    /// HTML that is written as-is.
+    Html(String),
+}
+impl From<String> for Cell {
+    #[inline]
+    fn from(value: String) -> Self { Self::Text(value) }
+}
+impl From<&str> for Cell {
+    #[inline]
+    fn from(value: &str) -> Self { Self::Text(value.into()) }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Renders the given overview as an HTML page.
+///
+/// # Arguments
+/// - `overview`: The [`Overview`] to render.
+/// - `refresh`: The number of seconds after which the page refreshes itself, or `0` to never refresh.
+///
+/// # Returns
+/// The HTML page.
+pub fn render(overview: &Overview, refresh: u64) -> String {
+    let mut page: String = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Brane dashboard</title>\n");
+    if refresh > 0 {
+        let _ = writeln!(page, "<meta http-equiv=\"refresh\" content=\"{refresh}\">");
+    }
+    let _ = writeln!(page, "<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Brane dashboard</h1>");
+    let _ = writeln!(page, "<p class=\"generated\">Generated at {}</p>", time(Some(&overview.generated)));
+
+    // Show what we failed to collect first
+    if !overview.errors.is_empty() {
+        page.push_str("<div class=\"errors\"><p>Parts of this overview could not be collected:</p>\n<ul>\n");
+        for err in &overview.errors {
+            let _ = writeln!(page, "<li><pre>{}</pre></li>", escape(err));
+        }
+        page.push_str("</ul></div>\n");
+    }
+
+    table(
+        &mut page,
+        "Domains",
+        &["Name", "Registry", "Status"],
+        overview
+            .domains
+            .iter()
+            .map(|domain| {
+                let class: &str = if domain.healthy { "ok" } else { "bad" };
+                vec![
+                    domain.name.as_str().into(),
+                    domain.registry.as_str().into(),
+                    Cell::Html(format!("<span class=\"{}\">{}</span>", class, escape(&domain.status))),
+                ]
+            })
+            .collect(),
+    );
+    table(
+        &mut page,
+        "Active workflows",
+        &["Session", "User", "Created", "Last activity"],
+        overview
+            .sessions
+            .iter()
+            .map(|session| {
+                vec![
+                    session.id.as_str().into(),
+                    session.user.as_deref().unwrap_or("-").into(),
+                    time(session.created.as_ref()).into(),
+                    time(session.last_activity.as_ref()).into(),
+                ]
+            })
+            .collect(),
+    );
+    table(
+        &mut page,
+        "Packages",
+        &["Name", "Version", "Kind", "Owners", "Created", "Description"],
+        overview
+            .packages
+            .iter()
+            .map(|package| {
+                vec![
+                    package.name.as_str().into(),
+                    package.version.as_str().into(),
+                    package.kind.as_str().into(),
+                    package.owners.join(", ").into(),
+                    time(Some(&package.created)).into(),
+                    package.description.as_deref().unwrap_or("").into(),
+                ]
+            })
+            .collect(),
+    );
+    table(
+        &mut page,
+        "Datasets",
+        &["Name", "Domains", "Created", "Description"],
+        overview
+            .datasets
+            .iter()
+            .map(|dataset| {
+                vec![
+                    dataset.name.as_str().into(),
+                    dataset.locations.join(", ").into(),
+                    time(Some(&dataset.created)).into(),
+                    dataset.description.as_deref().unwrap_or("").into(),
+                ]
+            })
+            .collect(),
+    );
+
+    page.push_str("</body>\n</html>\n");
+    page
+}
@@ -35,6 +35,9 @@ CENTRAL_SERVICES = [ "prx", "api", "drv", "plr" ]
 # List of auxillary services in the control part of an instance
 # At least, the ones we have to build.
 AUX_CENTRAL_SERVICES = []
+# List of optional services in the control part of an instance
+# These are not part of the `instance`-target, but can be built separately (e.g., `web-image`).
+OPTIONAL_CENTRAL_SERVICES = [ "web" ]
 # List of services that live in a worker node in an instance
 WORKER_SERVICES = [ "prx", "job", "reg", "chk" ]
 # List of auxillary services in a worker node in an instance
@@ -3268,7 +3271,7 @@ debug: bool = False
 # A list of deduced sources
 instance_srcs = {
     f"{svc}" : deduce_toml_src_dirs(f"./brane-{svc}/Cargo.toml")
-    for svc in CENTRAL_SERVICES + WORKER_SERVICES
+    for svc in CENTRAL_SERVICES + OPTIONAL_CENTRAL_SERVICES + WORKER_SERVICES
     # Ignore services which we do not build from our source code
     if svc != "chk"
 }
@@ -3430,7 +3433,7 @@ targets = {
 }
 
 # Generate some really repetitive entries
-for svc in CENTRAL_SERVICES + WORKER_SERVICES:
+for svc in CENTRAL_SERVICES + OPTIONAL_CENTRAL_SERVICES + WORKER_SERVICES:
     # Generate the service binary targets for those that support it
     if svc != "chk":
         targets[f"{svc}-binary-dev"] = CrateTarget(f"{svc}-binary-dev",
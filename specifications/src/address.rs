@@ -4,7 +4,7 @@
 //  Created:
 //    26 Jan 2023, 09:41:51
 //  Last edited:
-//    12 Jan 2024, 11:51:07
+//    15 Oct 2026, 21:09:21
 //  Auto updated?
 //    Yes
 //
@@ -12,14 +12,15 @@
 //!   Defines the Address struct, which does something similar to the Url
 //!   struct in the `url` crate, except that it's much more lenient
 //!   towards defining URL schemes or not. Moreover, it does not contain
-//!   any paths.
+//!   any paths (except for Unix sockets).
 //
 
 use std::borrow::Cow;
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::net::{AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use enum_debug::EnumDebug;
@@ -27,6 +28,14 @@ use log::trace;
 use serde::de::{self, Deserializer, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
+use tokio::net::UnixStream;
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::service_fn;
+
+
+/***** CONSTANTS *****/
+/// The prefix of addresses that refer to Unix sockets.
+pub const UNIX_SCHEME: &str = "unix://";
 
 
 /***** ERRORS *****/
@@ -35,18 +44,24 @@ use serde::{Deserialize, Serialize};
 pub enum AddressError {
     /// Invalid port number.
     IllegalPortNumber { raw: String, err: std::num::ParseIntError },
+    /// An address in brackets was not a valid IPv6 address.
+    IllegalIpv6 { raw: String, err: AddrParseError },
     /// Missing the colon separator (':') in the address.
     MissingColon { raw: String },
     /// Port not found when translating an [`AddressOpt`] into an [`Address`].
     MissingPort { addr: AddressOpt },
+    /// A Unix-socket address did not have a path.
+    MissingUnixPath { raw: String },
 }
 impl Display for AddressError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use AddressError::*;
         match self {
             IllegalPortNumber { raw, .. } => write!(f, "Illegal port number '{raw}'"),
+            IllegalIpv6 { raw, .. } => write!(f, "Illegal IPv6 address '{raw}'"),
             MissingColon { raw } => write!(f, "Missing address/port separator ':' in '{raw}' (did you forget to define a port?)"),
             MissingPort { addr } => write!(f, "Address '{addr}' does not have a port"),
+            MissingUnixPath { raw } => write!(f, "Missing socket path in Unix-socket address '{raw}'"),
         }
     }
 }
@@ -55,8 +70,10 @@ impl Error for AddressError {
         use AddressError::*;
         match self {
             IllegalPortNumber { err, .. } => Some(err),
+            IllegalIpv6 { err, .. } => Some(err),
             MissingColon { .. } => None,
             MissingPort { .. } => None,
+            MissingUnixPath { .. } => None,
         }
     }
 }
@@ -65,6 +82,74 @@ impl Error for AddressError {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Splits the port from an address, taking care not to split inside an IPv6 address in brackets.
+///
+/// # Arguments
+/// - `raw`: The address to split.
+///
+/// # Returns
+/// A tuple of the address-part and the port-part (if any).
+fn split_port(raw: &str) -> (&str, Option<&str>) {
+    let start: usize = raw.rfind(']').map(|pos| pos + 1).unwrap_or(0);
+    match raw[start..].rfind(':') {
+        Some(pos) => (&raw[..start + pos], Some(&raw[start + pos + 1..])),
+        None => (raw, None),
+    }
+}
+
+/// Parses the address-part of an address (i.e., without port).
+///
+/// # Arguments
+/// - `raw`: The address-part to parse. May be an IP address (IPv6 optionally in brackets) or a hostname.
+///
+/// # Returns
+/// The parsed address as an [`AddressOpt`] without port.
+///
+/// # Errors
+/// This function errors if the address was in brackets but not a valid IPv6 address.
+fn parse_host(raw: &str) -> Result<AddressOpt, AddressError> {
+    // Addresses in brackets must be IPv6
+    if let Some(ip) = raw.strip_prefix('[').and_then(|raw| raw.strip_suffix(']')) {
+        return match Ipv6Addr::from_str(ip) {
+            Ok(ip) => Ok(AddressOpt::Ipv6(ip, None)),
+            Err(err) => Err(AddressError::IllegalIpv6 { raw: raw.into(), err }),
+        };
+    }
+
+    // Otherwise, it's any IP or a hostname
+    match IpAddr::from_str(raw) {
+        Ok(IpAddr::V4(ip)) => Ok(AddressOpt::Ipv4(ip, None)),
+        Ok(IpAddr::V6(ip)) => Ok(AddressOpt::Ipv6(ip, None)),
+        Err(err) => {
+            trace!("Parsing '{}' as a hostname, but might be an invalid IP address (parser feedback: {})", raw, err);
+            Ok(AddressOpt::Hostname(raw.into(), None))
+        },
+    }
+}
+
+/// Parses a Unix-socket address, if it is one.
+///
+/// # Arguments
+/// - `raw`: The address to parse.
+///
+/// # Returns
+/// The path of the socket, or [`None`] if the address is not a Unix-socket address (i.e., doesn't start with [`UNIX_SCHEME`]).
+///
+/// # Errors
+/// This function errors if it was a Unix-socket address without path.
+fn parse_unix(raw: &str) -> Result<Option<PathBuf>, AddressError> {
+    match raw.strip_prefix(UNIX_SCHEME) {
+        Some("") => Err(AddressError::MissingUnixPath { raw: raw.into() }),
+        Some(path) => Ok(Some(path.into())),
+        None => Ok(None),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Defines a more lenient alternative to a SocketAddr that also accepts hostnames.
 #[derive(Clone, Debug, EnumDebug)]
@@ -75,6 +160,8 @@ pub enum Address {
     Ipv6(Ipv6Addr, u16),
     /// It's a hostname.
     Hostname(String, u16),
+    /// It's the path of a Unix socket.
+    Unix(PathBuf),
 }
 impl Address {
     /// Constructor for the Address that initializes it for the given IPv4 address.
@@ -145,8 +232,20 @@ impl Address {
     #[inline]
     pub fn hostname(hostname: impl Into<String>, port: u16) -> Self { Self::Hostname(hostname.into(), port) }
 
+    /// Constructor for the Address that initializes it for the given Unix socket.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the socket.
+    ///
+    /// # Returns
+    /// A new Address instance.
+    #[inline]
+    pub fn unix(path: impl Into<PathBuf>) -> Self { Self::Unix(path.into()) }
+
     /// Returns the domain-part, as a (serialized) string version.
     ///
+    /// IPv6 addresses are wrapped in brackets, such that the result can be used in URLs. For Unix sockets, this is the path of the socket.
+    ///
     /// # Returns
     /// A `Cow<str>` that either contains a reference to the already String hostname, or else a newly created string that is the serialized version of an IP.
     #[inline]
@@ -154,15 +253,16 @@ impl Address {
         use Address::*;
         match self {
             Ipv4(addr, _) => format!("{addr}").into(),
-            Ipv6(addr, _) => format!("{addr}").into(),
+            Ipv6(addr, _) => format!("[{addr}]").into(),
             Hostname(addr, _) => addr.into(),
+            Unix(path) => path.to_string_lossy(),
         }
     }
 
     /// Returns the port-part, as a number.
     ///
     /// # Returns
-    /// A `u16` that is the port.
+    /// A `u16` that is the port. Unix sockets don't have one, in which case this is `0`.
     #[inline]
     pub fn port(&self) -> u16 {
         use Address::*;
@@ -170,6 +270,7 @@ impl Address {
             Ipv4(_, port) => *port,
             Ipv6(_, port) => *port,
             Hostname(_, port) => *port,
+            Unix(_) => 0,
         }
     }
 
@@ -177,6 +278,9 @@ impl Address {
     ///
     /// # Returns
     /// A mutable reference to the `u16` that is the port.
+    ///
+    /// # Panics
+    /// This function panics if this Address is an `Address::Unix`, since Unix sockets don't have a port. Use `Address::is_unix()` to query beforehand.
     #[inline]
     pub fn port_mut(&mut self) -> &mut u16 {
         use Address::*;
@@ -184,9 +288,29 @@ impl Address {
             Ipv4(_, port) => port,
             Ipv6(_, port) => port,
             Hostname(_, port) => port,
+            Unix(path) => panic!("Cannot get the port of Unix-socket address '{}'", path.display()),
+        }
+    }
+
+    /// Returns the path of the socket if this Address is an `Address::Unix`.
+    ///
+    /// # Returns
+    /// The path of the socket, or [`None`] if this Address is not a Unix socket.
+    #[inline]
+    pub fn unix_path(&self) -> Option<&Path> {
+        match self {
+            Self::Unix(path) => Some(path),
+            _ => None,
         }
     }
 
+    /// Returns if this Address is an `Address::Unix`.
+    ///
+    /// # Returns
+    /// True if it is, false if it isn't.
+    #[inline]
+    pub fn is_unix(&self) -> bool { matches!(self, Self::Unix(_)) }
+
     /// Returns if this Address is an `Address::Hostname`.
     ///
     /// # Returns
@@ -224,8 +348,9 @@ impl Display for Address {
         use Address::*;
         match self {
             Ipv4(addr, port) => write!(f, "{addr}:{port}"),
-            Ipv6(addr, port) => write!(f, "{addr}:{port}"),
+            Ipv6(addr, port) => write!(f, "[{addr}]:{port}"),
             Hostname(addr, port) => write!(f, "{addr}:{port}"),
+            Unix(path) => write!(f, "{UNIX_SCHEME}{}", path.display()),
         }
     }
 }
@@ -273,17 +398,19 @@ impl FromStr for Address {
     type Err = AddressError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Attempt to find the colon first
-        let colon_pos: usize = match s.rfind(':') {
-            Some(pos) => pos,
-            None => {
+        // Unix sockets are easy
+        if let Some(path) = parse_unix(s)? {
+            return Ok(Self::Unix(path));
+        }
+
+        // Otherwise, attempt to find the colon (that isn't part of an IPv6 address) and split on that
+        let (address, port): (&str, &str) = match split_port(s) {
+            (address, Some(port)) => (address, port),
+            (_, None) => {
                 return Err(AddressError::MissingColon { raw: s.into() });
             },
         };
 
-        // Split it on that
-        let (address, port): (&str, &str) = (&s[..colon_pos], &s[colon_pos + 1..]);
-
         // Parse the port
         let port: u16 = match u16::from_str(port) {
             Ok(port) => port,
@@ -293,16 +420,9 @@ impl FromStr for Address {
         };
 
         // Resolve the address to a new instance of ourselves
-        match IpAddr::from_str(address) {
-            Ok(address) => match address {
-                IpAddr::V4(ip) => Ok(Self::Ipv4(ip, port)),
-                IpAddr::V6(ip) => Ok(Self::Ipv6(ip, port)),
-            },
-            Err(err) => {
-                trace!("Parsing '{}' as a hostname, but might be an invalid IP address (parser feedback: {})", address, err);
-                Ok(Self::Hostname(address.into(), port))
-            },
-        }
+        let mut address: AddressOpt = parse_host(address)?;
+        *address.port_mut() = Some(port);
+        Self::try_from(address)
     }
 }
 impl AsRef<Address> for Address {
@@ -346,6 +466,8 @@ impl TryFrom<AddressOpt> for Address {
                     Err(AddressError::MissingPort { addr: AddressOpt::Hostname(host, None) })
                 }
             },
+
+            AddressOpt::Unix(path) => Ok(Self::Unix(path)),
         }
     }
 }
@@ -361,6 +483,8 @@ pub enum AddressOpt {
     Ipv6(Ipv6Addr, Option<u16>),
     /// It's a hostname.
     Hostname(String, Option<u16>),
+    /// It's the path of a Unix socket.
+    Unix(PathBuf),
 }
 impl AddressOpt {
     /// Constructor for the AddressOpt that initializes it for the given IPv4 address.
@@ -431,8 +555,20 @@ impl AddressOpt {
     #[inline]
     pub fn hostname(hostname: impl Into<String>, port: Option<u16>) -> Self { Self::Hostname(hostname.into(), port) }
 
+    /// Constructor for the AddressOpt that initializes it for the given Unix socket.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the socket.
+    ///
+    /// # Returns
+    /// A new AddressOpt instance.
+    #[inline]
+    pub fn unix(path: impl Into<PathBuf>) -> Self { Self::Unix(path.into()) }
+
     /// Returns the domain-part, as a (serialized) string version.
     ///
+    /// IPv6 addresses are wrapped in brackets, such that the result can be used in URLs. For Unix sockets, this is the path of the socket.
+    ///
     /// # Returns
     /// A `Cow<str>` that either contains a reference to the already String hostname, or else a newly created string that is the serialized version of an IP.
     #[inline]
@@ -440,15 +576,16 @@ impl AddressOpt {
         use AddressOpt::*;
         match self {
             Ipv4(addr, _) => format!("{addr}").into(),
-            Ipv6(addr, _) => format!("{addr}").into(),
+            Ipv6(addr, _) => format!("[{addr}]").into(),
             Hostname(addr, _) => addr.into(),
+            Unix(path) => path.to_string_lossy(),
         }
     }
 
     /// Returns the port-part, as a number.
     ///
     /// # Returns
-    /// A `u16` that is the port.
+    /// A `u16` that is the port. Unix sockets never have one.
     #[inline]
     pub fn port(&self) -> Option<u16> {
         use AddressOpt::*;
@@ -456,6 +593,7 @@ impl AddressOpt {
             Ipv4(_, port) => *port,
             Ipv6(_, port) => *port,
             Hostname(_, port) => *port,
+            Unix(_) => None,
         }
     }
 
@@ -463,6 +601,9 @@ impl AddressOpt {
     ///
     /// # Returns
     /// A mutable reference to the `u16` that is the port.
+    ///
+    /// # Panics
+    /// This function panics if this AddressOpt is an `AddressOpt::Unix`, since Unix sockets don't have a port. Use `AddressOpt::is_unix()` to query beforehand.
     #[inline]
     pub fn port_mut(&mut self) -> &mut Option<u16> {
         use AddressOpt::*;
@@ -470,9 +611,17 @@ impl AddressOpt {
             Ipv4(_, port) => port,
             Ipv6(_, port) => port,
             Hostname(_, port) => port,
+            Unix(path) => panic!("Cannot get the port of Unix-socket address '{}'", path.display()),
         }
     }
 
+    /// Returns if this AddressOpt is an `AddressOpt::Unix`.
+    ///
+    /// # Returns
+    /// True if it is, false if it isn't.
+    #[inline]
+    pub fn is_unix(&self) -> bool { matches!(self, Self::Unix(_)) }
+
     /// Returns if this AddressOpt is an `AddressOpt::Hostname`.
     ///
     /// # Returns
@@ -517,7 +666,7 @@ impl Display for AddressOpt {
                 Ok(())
             },
             Ipv6(addr, port) => {
-                write!(f, "{addr}")?;
+                write!(f, "[{addr}]")?;
                 if let Some(port) = port {
                     write!(f, ":{port}")?;
                 };
@@ -530,6 +679,7 @@ impl Display for AddressOpt {
                 };
                 Ok(())
             },
+            Unix(path) => write!(f, "{UNIX_SCHEME}{}", path.display()),
         }
     }
 }
@@ -577,26 +727,21 @@ impl FromStr for AddressOpt {
     type Err = AddressError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Attempt to find the colon first and split the string accordingly
-        let (address, port): (&str, Option<&str>) = match s.rfind(':') {
-            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
-            None => (s, None),
-        };
+        // Unix sockets are easy
+        if let Some(path) = parse_unix(s)? {
+            return Ok(Self::Unix(path));
+        }
+
+        // Otherwise, attempt to find the colon (that isn't part of an IPv6 address) and split the string accordingly
+        let (address, port): (&str, Option<&str>) = split_port(s);
 
         // Parse the port, if any
         let port: Option<u16> = port.map(|p| u16::from_str(p).map_err(|err| AddressError::IllegalPortNumber { raw: p.into(), err })).transpose()?;
 
         // Resolve the address to a new instance of ourselves
-        match IpAddr::from_str(address) {
-            Ok(address) => match address {
-                IpAddr::V4(ip) => Ok(Self::Ipv4(ip, port)),
-                IpAddr::V6(ip) => Ok(Self::Ipv6(ip, port)),
-            },
-            Err(err) => {
-                trace!("Parsing '{}' as a hostname, but might be an invalid IP address (parser feedback: {})", address, err);
-                Ok(Self::Hostname(address.into(), port))
-            },
-        }
+        let mut address: Self = parse_host(address)?;
+        *address.port_mut() = port;
+        Ok(address)
     }
 }
 impl AsRef<AddressOpt> for AddressOpt {
@@ -618,6 +763,56 @@ impl From<Address> for AddressOpt {
             Address::Ipv4(host, port) => Self::Ipv4(host, Some(port)),
             Address::Ipv6(host, port) => Self::Ipv6(host, Some(port)),
             Address::Hostname(host, port) => Self::Hostname(host, Some(port)),
+            Address::Unix(path) => Self::Unix(path),
         }
     }
 }
+
+
+
+
+
+/***** CONNECTIONS *****/
+/// Creates a gRPC [`Endpoint`] for the given address.
+///
+/// Unlike [`Endpoint::new()`], this also accepts Unix-socket addresses (i.e., `unix:///path/to/socket`), for which a placeholder endpoint is
+/// returned that must be connected to using [`connect_grpc()`].
+///
+/// # Arguments
+/// - `address`: The address of the gRPC service.
+///
+/// # Returns
+/// A new [`Endpoint`] for the address.
+///
+/// # Errors
+/// This function errors if the address was not a valid URI.
+pub fn grpc_endpoint(address: impl AsRef<str>) -> Result<Endpoint, tonic::transport::Error> {
+    let address: &str = address.as_ref();
+    if address.starts_with(UNIX_SCHEME) {
+        // The URI is ignored when connecting, but has to be valid all the same
+        Ok(Endpoint::from_static("http://[::]:50051"))
+    } else {
+        Endpoint::new(address.to_string())
+    }
+}
+
+/// Connects to the given gRPC [`Endpoint`], using a Unix socket if the address tells us to.
+///
+/// # Arguments
+/// - `endpoint`: The [`Endpoint`] to connect to, as returned by [`grpc_endpoint()`].
+/// - `address`: The address from which the `endpoint` was created.
+///
+/// # Returns
+/// A new [`Channel`] to the gRPC service.
+///
+/// # Errors
+/// This function errors if we failed to connect to the service.
+pub async fn connect_grpc(endpoint: Endpoint, address: impl AsRef<str>) -> Result<Channel, tonic::transport::Error> {
+    match address.as_ref().strip_prefix(UNIX_SCHEME) {
+        Some(path) => {
+            let path: PathBuf = path.into();
+            endpoint.connect_with_connector(service_fn(move |_: Uri| UnixStream::connect(path.clone()))).await
+        },
+        None => endpoint.connect().await,
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    07 Feb 2024, 11:54:14
 //  Last edited:
-//    06 Mar 2024, 14:03:32
+//    16 Oct 2026, 01:29:42
 //  Auto updated?
 //    Yes
 //
@@ -13,7 +13,10 @@
 //!   with the `policy-reasoner`.
 //
 
+use std::fmt::{Display, Formatter, Result as FResult};
+
 use reqwest::Method;
+use serde::{Deserialize, Serialize};
 
 
 /***** CONSTANTS *****/
@@ -23,10 +26,14 @@ pub const POLICY_API_LIST_POLICIES: (Method, &str) = (Method::GET, "v1/managemen
 pub const POLICY_API_GET_ACTIVE_VERSION: (Method, &str) = (Method::GET, "v1/management/policies/active");
 /// Defines the API path to update the currently active version on the checker.
 pub const POLICY_API_SET_ACTIVE_VERSION: (Method, &str) = (Method::PUT, "v1/management/policies/active");
+/// Defines the API path to deactivate the currently active version on the checker, such that no policy is active.
+pub const POLICY_API_DEACTIVATE_VERSION: (Method, &str) = (Method::DELETE, "v1/management/policies/active");
 /// Defines the API path to add a new policy version to the checker.
 pub const POLICY_API_ADD_VERSION: (Method, &str) = (Method::POST, "v1/management/policies");
 /// Defines the API path to fetch a policy's body from a checker.
 pub const POLICY_API_GET_VERSION: (Method, fn(i64) -> String) = (Method::GET, |version: i64| format!("v1/management/policies/{version}"));
+/// Defines the API path to remove a (non-active) policy version from a checker.
+pub const POLICY_API_REMOVE_VERSION: (Method, fn(i64) -> String) = (Method::DELETE, |version: i64| format!("v1/management/policies/{version}"));
 
 /// Defines the API path to check if a workflow as a whole is permitted to be executed.
 pub const DELIBERATION_API_WORKFLOW: (Method, &str) = (Method::POST, "v1/deliberation/execute-workflow");
@@ -34,3 +41,101 @@ pub const DELIBERATION_API_WORKFLOW: (Method, &str) = (Method::POST, "v1/deliber
 pub const DELIBERATION_API_EXECUTE_TASK: (Method, &str) = (Method::POST, "v1/deliberation/execute-task");
 /// Defines the API path to check if a dataset in a workflow is permitted to be transferred.
 pub const DELIBERATION_API_TRANSFER_DATA: (Method, &str) = (Method::POST, "v1/deliberation/access-data");
+
+
+
+
+
+/***** LIBRARY *****/
+/// Explains why a checker denied the execution of a task or the transfer of a dataset, such that it can be shown to users.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PolicyDenial {
+    /// The domain whose checker denied the request.
+    pub domain:  String,
+    /// The name of the task that was denied (or that needed the denied dataset), if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task:    Option<String>,
+    /// The name of the dataset or intermediate result of which the transfer was denied, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+    /// The reasons for denial given by the reasoner (i.e., which rules fired), if it shared any.
+    #[serde(default)]
+    pub reasons: Vec<String>,
+}
+impl PolicyDenial {
+    /// Attempts to parse a PolicyDenial from the details of a gRPC status or the body of an HTTP response.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw bytes to parse. Expected to be JSON.
+    ///
+    /// # Returns
+    /// The parsed PolicyDenial, or [`None`] if the bytes did not encode one.
+    #[inline]
+    pub fn from_bytes(raw: &[u8]) -> Option<Self> { serde_json::from_slice(raw).ok() }
+
+    /// Serializes the PolicyDenial such that it can be sent as the details of a gRPC status or the body of an HTTP response.
+    ///
+    /// # Returns
+    /// The serialized PolicyDenial, as JSON.
+    #[inline]
+    pub fn to_bytes(&self) -> Vec<u8> { serde_json::to_vec(self).unwrap_or_else(|err| panic!("Failed to serialize PolicyDenial: {err}")) }
+}
+impl Display for PolicyDenial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "Checker of domain '{}' denied ", self.domain)?;
+        match (&self.task, &self.dataset) {
+            (Some(task), Some(dataset)) => write!(f, "transfer of dataset '{dataset}' for task '{task}'")?,
+            (None, Some(dataset)) => write!(f, "transfer of dataset '{dataset}'")?,
+            (Some(task), None) => write!(f, "execution of task '{task}'")?,
+            (None, None) => write!(f, "execution of the workflow")?,
+        }
+        if !self.reasons.is_empty() {
+            write!(f, "\n\nReasons:")?;
+            for reason in &self.reasons {
+                write!(f, "\n - {reason}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+
+/// Tells a client that its download of an intermediate result was parked until the data steward of the domain approves it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApprovalPending {
+    /// The domain whose data steward needs to approve the download.
+    pub domain: String,
+    /// The identifier of the request, which the data steward approves with `branectl approvals approve <ID>`.
+    pub id:     String,
+    /// The name of the intermediate result to download.
+    pub result: String,
+}
+impl ApprovalPending {
+    /// Attempts to parse an ApprovalPending from the details of a gRPC status or the body of an HTTP response.
+    ///
+    /// # Arguments
+    /// - `raw`: The raw bytes to parse. Expected to be JSON.
+    ///
+    /// # Returns
+    /// The parsed ApprovalPending, or [`None`] if the bytes did not encode one.
+    #[inline]
+    pub fn from_bytes(raw: &[u8]) -> Option<Self> { serde_json::from_slice(raw).ok() }
+
+    /// Serializes the ApprovalPending such that it can be sent as the details of a gRPC status or the body of an HTTP response.
+    ///
+    /// # Returns
+    /// The serialized ApprovalPending, as JSON.
+    #[inline]
+    pub fn to_bytes(&self) -> Vec<u8> { serde_json::to_vec(self).unwrap_or_else(|err| panic!("Failed to serialize ApprovalPending: {err}")) }
+}
+impl Display for ApprovalPending {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(
+            f,
+            "Download of intermediate result '{}' awaits approval by the data steward of domain '{}' (request '{}'); try again once it is approved",
+            self.result, self.domain, self.id
+        )
+    }
+}
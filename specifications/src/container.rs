@@ -4,13 +4,14 @@ use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::common::{CallPattern, Parameter, Type};
-use crate::package::{Capability, PackageKind};
+use crate::package::{Capability, PackageKind, PackageResources};
 use crate::version::Version;
 
 
@@ -366,6 +367,9 @@ pub struct LocalContainerInfo {
     pub actions: Map<Action>,
     /// The list of types that are declared in this package.
     pub types: Map<Type>,
+    /// If given, how `branelet` retries the package when it fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
 }
 
 impl LocalContainerInfo {
@@ -466,6 +470,7 @@ impl From<ContainerInfo> for LocalContainerInfo {
             entrypoint: container_info.entrypoint,
             actions: container_info.actions,
             types: container_info.types.unwrap_or_default(),
+            retry: container_info.retry,
         }
     }
 }
@@ -477,6 +482,7 @@ impl From<&ContainerInfo> for LocalContainerInfo {
             entrypoint: container_info.entrypoint.clone(),
             actions: container_info.actions.clone(),
             types: container_info.types.as_ref().cloned().unwrap_or_default(),
+            retry: container_info.retry.clone(),
         }
     }
 }
@@ -498,6 +504,16 @@ pub struct ContainerInfo {
     pub owners: Option<Vec<String>>,
     /// A short description of the package.
     pub description: Option<String>,
+    /// The license of the package (e.g., an SPDX identifier like `Apache-2.0`).
+    pub license: Option<String>,
+    /// The list of people maintaining this package.
+    pub maintainers: Option<Vec<String>>,
+    /// A link to the homepage of the package.
+    pub homepage: Option<String>,
+    /// The minimum resources that the package needs to run.
+    pub resources: Option<PackageResources>,
+    /// A list of tags that help finding the package.
+    pub tags: Option<Vec<String>>,
 
     /// The functions that this package supports.
     pub actions:    Map<Action>,
@@ -505,6 +521,8 @@ pub struct ContainerInfo {
     pub entrypoint: Entrypoint,
     /// The types that this package adds.
     pub types:      Option<Map<Type>>,
+    /// How to retry the package when it fails, if at all.
+    pub retry:      Option<RetryPolicy>,
 
     /// The base image to use for the package image.
     pub base: Option<String>,
@@ -631,6 +649,88 @@ impl ContainerInfo {
 
 
 
+/// Defines the YAML of the retry policy of a package, e.g.:
+/// ```yaml
+/// retry:
+///   max: 3
+///   backoff: 10s
+///   on: [ nonzero_exit, timeout ]
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RetryPolicy {
+    /// The maximum number of times the package is retried (i.e., not counting the first attempt).
+    pub max:     u32,
+    /// How long to wait before every retry. Written as a number with a unit (`ms`, `s`, `m` or `h`), e.g., `10s`, or as a plain number of seconds.
+    #[serde(default, with = "backoff")]
+    pub backoff: Duration,
+    /// The failures on which the package is retried. Defaults to all of them.
+    #[serde(default = "RetryCondition::all")]
+    pub on:      Vec<RetryCondition>,
+}
+
+/// Defines the failures on which a package may be retried.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCondition {
+    /// The package returned a non-zero exit code (or was stopped by a signal, or responded with an error).
+    NonzeroExit,
+    /// The package exceeded the timeout of the worker.
+    Timeout,
+}
+impl RetryCondition {
+    /// Returns all RetryConditions, which is the default of [`RetryPolicy::on`].
+    #[inline]
+    pub fn all() -> Vec<Self> { vec![Self::NonzeroExit, Self::Timeout] }
+}
+
+/// Serializes and deserializes the [`RetryPolicy::backoff`] as a human-readable duration.
+mod backoff {
+    use std::time::Duration;
+
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::Serializer;
+
+    use super::{FResult, Formatter};
+
+    pub fn serialize<S: Serializer>(backoff: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        if backoff.subsec_millis() == 0 {
+            serializer.serialize_str(&format!("{}s", backoff.as_secs()))
+        } else {
+            serializer.serialize_str(&format!("{}ms", backoff.as_millis()))
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        /// Visits either a number of seconds or a string with a unit.
+        struct BackoffVisitor;
+        impl<'de> Visitor<'de> for BackoffVisitor {
+            type Value = Duration;
+
+            fn expecting(&self, f: &mut Formatter) -> FResult { write!(f, "a duration (e.g., '10s', '500ms' or '2m') or a number of seconds") }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> { Ok(Duration::from_secs(v)) }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let v: &str = v.trim();
+                let split: usize = v.find(|c: char| !c.is_ascii_digit()).unwrap_or(v.len());
+                let (value, unit): (&str, &str) = (&v[..split], v[split..].trim());
+                let value: u64 = value.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))?;
+                match unit {
+                    "ms" => Ok(Duration::from_millis(value)),
+                    "" | "s" => Ok(Duration::from_secs(value)),
+                    "m" => Ok(Duration::from_secs(value * 60)),
+                    "h" => Ok(Duration::from_secs(value * 3600)),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(BackoffVisitor)
+    }
+}
+
+
+
 /// Defines the YAML of an action in a package.
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -652,8 +752,24 @@ pub struct Action {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ActionCommand {
-    pub args:    Vec<String>,
-    pub capture: Option<String>,
+    pub args:     Vec<String>,
+    pub capture:  Option<String>,
+    /// A line that the package writes to its stdout once it is ready (e.g., has loaded its model), which `branelet` then reports to the worker.
+    pub ready:    Option<String>,
+    /// How the package code communicates with `branelet`. Defaults to [`CommandProtocol::Stdout`].
+    pub protocol: Option<CommandProtocol>,
+}
+
+/// Defines how package code communicates with `branelet`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandProtocol {
+    /// The package gets its arguments as environment variables and writes its result to stdout as YAML (see `capture`).
+    #[default]
+    Stdout,
+    /// Like [`CommandProtocol::Stdout`], but the package also gets a JSON-RPC 2.0 `run`-request on stdin and writes JSON-RPC messages to stdout
+    /// (progress updates, logs, partial results and the final response). See `brane_let::rpc` for the details.
+    JsonRpc,
 }
 
 
@@ -4,7 +4,7 @@
 //  Created:
 //    26 Aug 2022, 15:53:28
 //  Last edited:
-//    31 Jan 2024, 11:28:56
+//    16 Oct 2026, 01:42:13
 //  Auto updated?
 //    Yes
 //
@@ -13,7 +13,7 @@
 //!   registries and datasets.
 //
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::File;
@@ -316,6 +316,38 @@ pub enum AccessKind {
         /// The path to the file itself.
         path: PathBuf,
     },
+    /// By downloading all objects under some prefix in an S3(-compatible) bucket.
+    ///
+    /// Credentials are read from the standard `AWS_*` environment variables of the service downloading it (e.g., `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and, for S3-compatible stores, `AWS_ENDPOINT`).
+    S3 {
+        /// The name of the bucket.
+        bucket: String,
+        /// The prefix of the objects in the bucket that make up the dataset. May be empty to download the entire bucket.
+        #[serde(default)]
+        prefix: String,
+        /// The region of the bucket.
+        region: String,
+    },
+    /// By downloading a single file over HTTPS.
+    Url {
+        /// The HTTPS-address of the file.
+        https: String,
+    },
+}
+impl AccessKind {
+    /// Returns whether this AccessKind refers to data outside of the node (i.e., that has to be downloaded before it can be used).
+    #[inline]
+    pub fn is_remote(&self) -> bool { !matches!(self, Self::File { .. }) }
+}
+impl Display for AccessKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::File { path } => write!(f, "{}", path.display()),
+            Self::S3 { bucket, prefix, region } => write!(f, "s3://{bucket}/{prefix} ({region})"),
+            Self::Url { https } => write!(f, "{https}"),
+        }
+    }
 }
 
 /// Defines possible ways of downloading datasets to make them locally available.
@@ -442,6 +474,10 @@ impl DataIndex {
     #[inline]
     pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&DataInfo> { self.index.get(name.as_ref()) }
 
+    /// Returns an iterator over the identifiers of the datasets in this index.
+    #[inline]
+    pub fn names(&self) -> impl Iterator<Item = &str> { self.index.keys().map(String::as_str) }
+
     /// Returns an iterator over the internal DataIndices.
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = &DataInfo> { self.into_iter() }
@@ -572,6 +608,43 @@ impl Default for RuntimeDataIndex {
 
 
 
+/// Defines a single column in the schema of a (tabular) dataset.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DataColumn {
+    /// The name of the column.
+    pub name: String,
+    /// The type of the values in the column (e.g., `string`, `int` or `date`). This is not interpreted by Brane.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    /// A (short) description of the column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Sensitivity tags of the values in the column (e.g., `pii` or `pii:email`).
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub tags: HashSet<String>,
+}
+
+
+
+/// Describes where a dataset came from if it was promoted from the intermediate result of a workflow.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Provenance {
+    /// The identifier of the intermediate result that was promoted.
+    pub result:    String,
+    /// The task that produced the intermediate result, as `package[version]::function`, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task:      Option<String>,
+    /// The datasets and intermediate results that the result was computed from (directly, or also indirectly if known), closest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs:    Vec<String>,
+    /// The location where the intermediate result was produced.
+    pub location:  String,
+    /// The moment the intermediate result was promoted.
+    pub committed: DateTime<Utc>,
+}
+
+
+
 /// Defines a single DataInfo file that describes a dataset and how to access it.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DataInfo {
@@ -583,6 +656,19 @@ pub struct DataInfo {
     pub description: Option<String>,
     /// The created timestamp of the asset.
     pub created: DateTime<Utc>,
+    /// The column-level schema of the asset, if it's tabular and known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Vec<DataColumn>>,
+    /// Sensitivity tags that apply to the asset as a whole (e.g., `pii` or `medical`).
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub tags: HashSet<String>,
+    /// The residency zones that the asset is restricted to (e.g., `eu-only` or `on-prem`). It may only be processed on locations that are part of
+    /// all of them.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub residency: HashSet<String>,
+    /// Where the asset came from, if it was promoted from an intermediate result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 
     /// Defines how to access this DataInfo per location that advertises it.
     pub access: HashMap<Location, AccessKind>,
@@ -686,6 +772,16 @@ impl DataInfo {
             Err(err) => Err(DataInfoError::WriterWriteError { err }),
         }
     }
+
+    /// Returns all sensitivity tags of this asset, i.e., those of the asset itself and of any of its columns.
+    ///
+    /// This allows policies to reason about the kind of data a task touches without having to know about every dataset by name.
+    ///
+    /// # Returns
+    /// A set with references to the tags.
+    pub fn all_tags(&self) -> HashSet<&str> {
+        self.tags.iter().chain(self.schema.iter().flatten().flat_map(|column| column.tags.iter())).map(String::as_str).collect()
+    }
 }
 
 
@@ -702,6 +798,22 @@ pub struct AssetInfo {
     /// The created timestamp of the asset.
     #[serde(skip)]
     pub created: DateTime<Utc>,
+    /// The column-level schema of the asset, if it's tabular and known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Vec<DataColumn>>,
+    /// Sensitivity tags that apply to the asset as a whole (e.g., `pii` or `medical`).
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub tags: HashSet<String>,
+    /// The residency zones that the asset is restricted to (e.g., `eu-only` or `on-prem`). It may only be processed on locations that are part of
+    /// all of them.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub residency: HashSet<String>,
+    /// Whether the asset never changes, such that registries may keep it archived instead of archiving it again for every download.
+    #[serde(default)]
+    pub immutable: bool,
+    /// Where the asset came from, if it was promoted from an intermediate result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
 
     /// Defines the way how to access & distribute this asset to containers.
     pub access: AccessKind,
@@ -772,10 +884,22 @@ impl AssetInfo {
             owners: self.owners,
             description: self.description,
             created: self.created,
+            schema: self.schema,
+            tags: self.tags,
+            residency: self.residency,
+            provenance: self.provenance,
 
             access: HashMap::from([(location.into(), self.access)]),
         }
     }
+
+    /// Returns all sensitivity tags of this asset, i.e., those of the asset itself and of any of its columns.
+    ///
+    /// # Returns
+    /// A set with references to the tags.
+    pub fn all_tags(&self) -> HashSet<&str> {
+        self.tags.iter().chain(self.schema.iter().flatten().flat_map(|column| column.tags.iter())).map(String::as_str).collect()
+    }
 }
 
 impl From<AssetInfo> for DataInfo {
@@ -786,6 +910,10 @@ impl From<AssetInfo> for DataInfo {
             owners: value.owners,
             description: value.description,
             created: value.created,
+            schema: value.schema,
+            tags: value.tags,
+            residency: value.residency,
+            provenance: value.provenance,
 
             access: HashMap::from([("localhost".into(), value.access)]),
         }
@@ -4,7 +4,7 @@
 //  Created:
 //    06 Jan 2023, 14:43:35
 //  Last edited:
-//    08 Feb 2024, 17:01:30
+//    16 Oct 2026, 01:16:58
 //  Auto updated?
 //    Yes
 //
@@ -12,22 +12,37 @@
 //!   Defines the prost messages for interacting with the driver.
 //
 
+use std::collections::HashMap;
 use std::error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::Stream;
 use prost::Message;
+use serde::{Deserialize, Serialize};
 use tonic::body::{empty_body, BoxBody};
 use tonic::client::Grpc as GrpcClient;
 use tonic::codec::{ProstCodec, Streaming};
 use tonic::codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError};
+use tonic::metadata::MetadataValue;
 use tonic::server::{Grpc as GrpcServer, NamedService, ServerStreamingService, UnaryService};
-use tonic::transport::{Channel, Endpoint};
+use tonic::transport::Channel;
 use tonic::{Code, Request, Response, Status};
 pub use DriverServiceError as Error;
 
+use crate::address::{connect_grpc, grpc_endpoint};
+use crate::reporting::TaskFailure;
+
+
+/***** CONSTANTS *****/
+/// The gRPC metadata key that carries the API token of the client (as `Bearer <TOKEN>`).
+pub const AUTHORIZATION_METADATA: &str = "authorization";
+
+
+
+
 
 /***** ERRORS *****/
 /// Defines the errors occuring in the DriverServiceClient or DriverServiceServer.
@@ -54,10 +69,107 @@ impl error::Error for DriverServiceError {}
 
 
 
+/***** AUXILLARY *****/
+/// Defines the states that a task goes through while the driver executes a workflow.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// The task has not yet been reached.
+    Queued,
+    /// The workflow that the task is part of is being planned.
+    Planning,
+    /// The inputs of the task are being transferred to where it runs.
+    Transferring,
+    /// The task is running on its domain.
+    Running,
+    /// The task completed successfully.
+    Done,
+    /// The task failed.
+    Failed,
+    /// The task was cancelled because the workflow exceeded its deadline.
+    Cancelled,
+}
+impl Display for TaskState {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TaskState::*;
+        match self {
+            Queued => write!(f, "queued"),
+            Planning => write!(f, "planning"),
+            Transferring => write!(f, "transferring"),
+            Running => write!(f, "running"),
+            Done => write!(f, "done"),
+            Failed => write!(f, "failed"),
+            Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+
+
+/// Defines the progress updates that the driver sends alongside an [`ExecuteReply`] (JSON-encoded in its `event` field).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExecuteEvent {
+    /// The driver started planning the workflow.
+    Planning,
+    /// The driver planned the workflow.
+    Planned {
+        /// Maps the program counter of every task call (as `<func>:<edge>`) to the domain where it will run.
+        locations: HashMap<String, String>,
+    },
+    /// A task call changed state.
+    Task {
+        /// The program counter of the task call (as `<func>:<edge>`).
+        pc:      String,
+        /// The state it is now in.
+        state:   TaskState,
+        /// Some additional information, e.g., the reason why it failed.
+        message: Option<String>,
+    },
+    /// A task call failed on its domain. Sent after the [`ExecuteEvent::Task`] that reports its failed state.
+    TaskFailed {
+        /// The program counter of the task call (as `<func>:<edge>`).
+        pc:       String,
+        /// The name of the task.
+        name:     String,
+        /// The domain where it ran.
+        location: String,
+        /// Why it failed.
+        failure:  TaskFailure,
+    },
+    /// The workflow did not complete within its deadline, and its outstanding tasks were cancelled.
+    TimedOut {
+        /// The deadline that the workflow exceeded, in seconds.
+        deadline: u64,
+        /// Maps the program counter of every task call (as `<func>:<edge>`) that completed before the deadline to the value it returned (a
+        /// FullValue encoded as JSON).
+        partial:  HashMap<String, String>,
+    },
+    /// The workflow printed the text in the `stdout` field of the same [`ExecuteReply`]. Tells where and when it did, so that clients can
+    /// tag every line with it.
+    Printed {
+        /// The program counter of the call to `print` or `println` (as `<func>:<edge>`).
+        pc:        String,
+        /// The name of the function that printed it (`<main>` if it was printed outside of any function).
+        function:  String,
+        /// The moment it was printed.
+        timestamp: DateTime<Utc>,
+    },
+}
+
+
+
+
+
 /***** MESSAGES *****/
 /// Request for creating a new session.
 #[derive(Clone, Message)]
-pub struct CreateSessionRequest {}
+pub struct CreateSessionRequest {
+    /// The user who creates the session, if known. Used to list the user's sessions later on.
+    #[prost(tag = "1", optional, string)]
+    pub user: Option<String>,
+}
 
 /// The reply sent by the driver when a new session has been created.
 #[derive(Clone, Message)]
@@ -69,6 +181,104 @@ pub struct CreateSessionReply {
 
 
 
+/// Request for listing the active sessions.
+#[derive(Clone, Message)]
+pub struct ListSessionsRequest {
+    /// If given, only lists the sessions of this user.
+    #[prost(tag = "1", optional, string)]
+    pub user: Option<String>,
+}
+
+/// Describes a single active session in a [`ListSessionsReply`].
+#[derive(Clone, Message)]
+pub struct SessionInfo {
+    /// The UUID of the session.
+    #[prost(tag = "1", required, string)]
+    pub uuid: String,
+    /// The user who created the session, if known.
+    #[prost(tag = "2", optional, string)]
+    pub user: Option<String>,
+    /// When the session was created, as seconds since the Unix epoch.
+    #[prost(tag = "3", required, uint64)]
+    pub created: u64,
+    /// When a workflow was last executed in the session (or when it was created, if never), as seconds since the Unix epoch.
+    #[prost(tag = "4", required, uint64)]
+    pub last_activity: u64,
+}
+
+/// The reply sent by the driver with the active sessions.
+#[derive(Clone, Message)]
+pub struct ListSessionsReply {
+    /// The sessions that are active, ordered by most recent activity first.
+    #[prost(tag = "1", repeated, message)]
+    pub sessions: Vec<SessionInfo>,
+}
+
+
+
+/// Request for listing the intermediate results produced by an execution.
+#[derive(Clone, Message)]
+pub struct ListResultsRequest {
+    /// The identifier of the execution (i.e., of its workflow).
+    #[prost(tag = "1", required, string)]
+    pub execution_id: String,
+}
+
+/// Describes a single intermediate result in a [`ListResultsReply`].
+#[derive(Clone, Message)]
+pub struct ResultInfo {
+    /// The name of the intermediate result.
+    #[prost(tag = "1", required, string)]
+    pub name:     String,
+    /// The task that produced it (as `<package>[<version>]::<function>`), if known.
+    #[prost(tag = "2", optional, string)]
+    pub task:     Option<String>,
+    /// The location where the result lives.
+    #[prost(tag = "3", required, string)]
+    pub location: String,
+    /// The size of the result in bytes, or [`None`] if it no longer exists at its location.
+    #[prost(tag = "4", optional, uint64)]
+    pub size:     Option<u64>,
+    /// When the result was produced, as seconds since the Unix epoch.
+    #[prost(tag = "5", required, uint64)]
+    pub produced: u64,
+    /// When the driver forgets about the result, as seconds since the Unix epoch.
+    #[prost(tag = "6", required, uint64)]
+    pub expires:  u64,
+}
+
+/// The reply sent by the driver with the intermediate results of an execution.
+#[derive(Clone, Message)]
+pub struct ListResultsReply {
+    /// The intermediate results, in the order they were produced.
+    #[prost(tag = "1", repeated, message)]
+    pub results: Vec<ResultInfo>,
+}
+
+/// Request for promoting an intermediate result produced by an execution to a dataset.
+#[derive(Clone, Message)]
+pub struct PromoteResultRequest {
+    /// The identifier of the execution (i.e., of its workflow).
+    #[prost(tag = "1", required, string)]
+    pub execution_id: String,
+    /// The name of the intermediate result to promote.
+    #[prost(tag = "2", required, string)]
+    pub name:         String,
+    /// The name of the dataset to promote it to.
+    #[prost(tag = "3", required, string)]
+    pub dataset:      String,
+}
+
+/// The reply sent by the driver when an intermediate result has been promoted.
+#[derive(Clone, Message)]
+pub struct PromoteResultReply {
+    /// The location where the new dataset lives.
+    #[prost(tag = "1", required, string)]
+    pub location: String,
+}
+
+
+
 /// Request for checking the given workflow only.
 #[derive(Clone, Message)]
 pub struct CheckRequest {
@@ -102,10 +312,16 @@ pub struct CheckReply {
 pub struct ExecuteRequest {
     /// The session in which to execute the workflow.
     #[prost(tag = "1", required, string)]
-    pub uuid:  String,
+    pub uuid:     String,
     /// The input to the request, i.e., the workflow.
     #[prost(tag = "2", required, string)]
-    pub input: String,
+    pub input:    String,
+    /// If given, ignores `input` and instead resumes the interrupted workflow with this ID from its checkpoint.
+    #[prost(tag = "3", optional, string)]
+    pub resume:   Option<String>,
+    /// If given, the number of seconds that the workflow may take in total before the driver cancels it.
+    #[prost(tag = "4", optional, uint64)]
+    pub deadline: Option<u64>,
 }
 
 /// The reply sent by the driver when a workflow has been executed.
@@ -117,16 +333,22 @@ pub struct ExecuteReply {
 
     /// If given, then the driver has some debug information to show to the user.
     #[prost(tag = "2", optional, string)]
-    pub debug:  Option<String>,
+    pub debug:   Option<String>,
     /// If given, then the driver has stdout to write to the user.
     #[prost(tag = "3", optional, string)]
-    pub stdout: Option<String>,
+    pub stdout:  Option<String>,
     /// If given, then the driver has stderr to write to the user.
     #[prost(tag = "4", optional, string)]
-    pub stderr: Option<String>,
+    pub stderr:  Option<String>,
     /// If given, then the workflow has returned a value to use (FullValue encoded as JSON).
     #[prost(tag = "5", optional, string)]
-    pub value:  Option<String>,
+    pub value:   Option<String>,
+    /// If given, then the driver has profiling information (e.g., the resources used by a task) to show to the user if they asked for it.
+    #[prost(tag = "6", optional, string)]
+    pub profile: Option<String>,
+    /// If given, then the driver reports progress on the workflow (an [`ExecuteEvent`] encoded as JSON).
+    #[prost(tag = "7", optional, string)]
+    pub event:   Option<String>,
 }
 
 
@@ -139,6 +361,8 @@ pub struct ExecuteReply {
 pub struct DriverServiceClient {
     /// The client with which we actually do everything
     client: GrpcClient<Channel>,
+    /// The API token with which we authenticate ourselves to the driver, if any.
+    token:  Option<String>,
 }
 
 impl DriverServiceClient {
@@ -156,8 +380,8 @@ impl DriverServiceClient {
         let address: String = address.into();
 
         // Attempt to make the connection
-        let conn: Channel = match Endpoint::new(address.clone()) {
-            Ok(endpoint) => match endpoint.connect().await {
+        let conn: Channel = match grpc_endpoint(&address) {
+            Ok(endpoint) => match connect_grpc(endpoint, &address).await {
                 Ok(conn) => conn,
                 Err(err) => {
                     return Err(Error::ConnectError { address, err });
@@ -169,7 +393,38 @@ impl DriverServiceClient {
         };
 
         // Store it internally
-        Ok(Self { client: GrpcClient::new(conn) })
+        Ok(Self { client: GrpcClient::new(conn), token: None })
+    }
+
+    /// Makes this client authenticate itself with the given API token in every request.
+    ///
+    /// # Arguments
+    /// - `token`: The API token to present to the driver, or [`None`] to send requests anonymously.
+    ///
+    /// # Returns
+    /// The same client, for chaining.
+    #[inline]
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    /// Turns the given request into one that carries our API token, if any.
+    ///
+    /// # Arguments
+    /// - `request`: The request to prepare.
+    ///
+    /// # Returns
+    /// The request, with the token as `authorization` metadata.
+    fn authorize<T>(&self, request: impl tonic::IntoRequest<T>) -> Request<T> {
+        let mut request: Request<T> = request.into_request();
+        if let Some(token) = &self.token {
+            // A token that cannot be sent as metadata can never be valid, so we leave it out and let the driver decide
+            if let Ok(value) = MetadataValue::try_from(format!("Bearer {token}")) {
+                request.metadata_mut().insert(AUTHORIZATION_METADATA, value);
+            }
+        }
+        request
     }
 
     /// Send a CreateSessionRequest to the connected endpoint.
@@ -191,7 +446,73 @@ impl DriverServiceClient {
         // Set the default stuff
         let codec: ProstCodec<_, _> = ProstCodec::default();
         let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/CreateSession");
-        self.client.unary(request.into_request(), path, codec).await
+        self.client.unary(self.authorize(request), path, codec).await
+    }
+
+    /// Send a [`ListSessionsRequest`] to the connected endpoint.
+    ///
+    /// # Arguments
+    /// - `request`: The [`ListSessionsRequest`] to send to the endpoint.
+    ///
+    /// # Returns
+    /// The [`ListSessionsReply`] the endpoint returns.
+    ///
+    /// # Errors
+    /// This function errors if either we failed to send the request or the endpoint itself failed to process it.
+    pub async fn list_sessions(&mut self, request: impl tonic::IntoRequest<ListSessionsRequest>) -> Result<Response<ListSessionsReply>, Status> {
+        // Assert the client is ready to get the party started
+        if let Err(err) = self.client.ready().await {
+            return Err(Status::new(Code::Unknown, format!("Service was not ready: {err}")));
+        }
+
+        // Set the default stuff
+        let codec: ProstCodec<_, _> = ProstCodec::default();
+        let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/ListSessions");
+        self.client.unary(self.authorize(request), path, codec).await
+    }
+
+    /// Send a [`ListResultsRequest`] to the connected endpoint.
+    ///
+    /// # Arguments
+    /// - `request`: The [`ListResultsRequest`] to send to the endpoint.
+    ///
+    /// # Returns
+    /// The [`ListResultsReply`] the endpoint returns.
+    ///
+    /// # Errors
+    /// This function errors if either we failed to send the request or the endpoint itself failed to process it.
+    pub async fn list_results(&mut self, request: impl tonic::IntoRequest<ListResultsRequest>) -> Result<Response<ListResultsReply>, Status> {
+        // Assert the client is ready to get the party started
+        if let Err(err) = self.client.ready().await {
+            return Err(Status::new(Code::Unknown, format!("Service was not ready: {err}")));
+        }
+
+        // Set the default stuff
+        let codec: ProstCodec<_, _> = ProstCodec::default();
+        let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/ListResults");
+        self.client.unary(self.authorize(request), path, codec).await
+    }
+
+    /// Send a [`PromoteResultRequest`] to the connected endpoint.
+    ///
+    /// # Arguments
+    /// - `request`: The [`PromoteResultRequest`] to send to the endpoint.
+    ///
+    /// # Returns
+    /// The [`PromoteResultReply`] the endpoint returns.
+    ///
+    /// # Errors
+    /// This function errors if either we failed to send the request or the endpoint itself failed to process it.
+    pub async fn promote_result(&mut self, request: impl tonic::IntoRequest<PromoteResultRequest>) -> Result<Response<PromoteResultReply>, Status> {
+        // Assert the client is ready to get the party started
+        if let Err(err) = self.client.ready().await {
+            return Err(Status::new(Code::Unknown, format!("Service was not ready: {err}")));
+        }
+
+        // Set the default stuff
+        let codec: ProstCodec<_, _> = ProstCodec::default();
+        let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/PromoteResult");
+        self.client.unary(self.authorize(request), path, codec).await
     }
 
     /// Send a request to validate a workflow to the connected endpoint.
@@ -213,7 +534,7 @@ impl DriverServiceClient {
         // Set the default stuff
         let codec: ProstCodec<_, _> = ProstCodec::default();
         let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/Check");
-        self.client.unary(request.into_request(), path, codec).await
+        self.client.unary(self.authorize(request), path, codec).await
     }
 
     /// Send an ExecuteRequest to the connected endpoint.
@@ -235,7 +556,7 @@ impl DriverServiceClient {
         // Set the default stuff
         let codec: ProstCodec<_, _> = ProstCodec::default();
         let path: http::uri::PathAndQuery = http::uri::PathAndQuery::from_static("/driver.DriverService/Execute");
-        self.client.server_streaming(request.into_request(), path, codec).await
+        self.client.server_streaming(self.authorize(request), path, codec).await
     }
 }
 
@@ -263,6 +584,42 @@ pub trait DriverService: 'static + Send + Sync {
     /// This function may error (i.e., send back a `tonic::Status`) whenever it fails.
     async fn create_session(&self, request: Request<CreateSessionRequest>) -> Result<Response<CreateSessionReply>, Status>;
 
+    /// Handle for when a [`ListSessionsRequest`] comes in.
+    ///
+    /// # Arguments
+    /// - `request`: The ([`tonic::Request`]-wrapped) [`ListSessionsRequest`] containing the relevant details.
+    ///
+    /// # Returns
+    /// A [`ListSessionsReply`] for this request, wrapped in a [`tonic::Response`].
+    ///
+    /// # Errors
+    /// This function may error (i.e., send back a [`tonic::Status`]) whenever it fails.
+    async fn list_sessions(&self, request: Request<ListSessionsRequest>) -> Result<Response<ListSessionsReply>, Status>;
+
+    /// Handle for when a [`ListResultsRequest`] comes in.
+    ///
+    /// # Arguments
+    /// - `request`: The ([`tonic::Request`]-wrapped) [`ListResultsRequest`] containing the relevant details.
+    ///
+    /// # Returns
+    /// A [`ListResultsReply`] for this request, wrapped in a [`tonic::Response`].
+    ///
+    /// # Errors
+    /// This function may error (i.e., send back a [`tonic::Status`]) whenever it fails.
+    async fn list_results(&self, request: Request<ListResultsRequest>) -> Result<Response<ListResultsReply>, Status>;
+
+    /// Handle for when a [`PromoteResultRequest`] comes in.
+    ///
+    /// # Arguments
+    /// - `request`: The ([`tonic::Request`]-wrapped) [`PromoteResultRequest`] containing the relevant details.
+    ///
+    /// # Returns
+    /// A [`PromoteResultReply`] for this request, wrapped in a [`tonic::Response`].
+    ///
+    /// # Errors
+    /// This function may error (i.e., send back a [`tonic::Status`]) whenever it fails.
+    async fn promote_result(&self, request: Request<PromoteResultRequest>) -> Result<Response<PromoteResultReply>, Status>;
+
     /// Handle for when a [`CheckRequest`] comes in.
     ///
     /// # Arguments
@@ -348,6 +705,84 @@ where
                 })
             },
 
+            // Incoming ListSessionsRequest
+            "/driver.DriverService/ListSessions" => {
+                /// Helper struct for the given DriverService that focusses specifically on this request.
+                struct ListSessionsSvc<T>(Arc<T>);
+                impl<T: DriverService> UnaryService<ListSessionsRequest> for ListSessionsSvc<T> {
+                    type Future = BoxFuture<Response<Self::Response>, Status>;
+                    type Response = ListSessionsReply;
+
+                    fn call(&mut self, req: Request<ListSessionsRequest>) -> Self::Future {
+                        // Return the service function as the future to run
+                        let service = self.0.clone();
+                        let fut = async move { (*service).list_sessions(req).await };
+                        Box::pin(fut)
+                    }
+                }
+
+                // Create a future that creates the service
+                let service = self.service.clone();
+                Box::pin(async move {
+                    let method: ListSessionsSvc<T> = ListSessionsSvc(service);
+                    let codec: ProstCodec<_, _> = ProstCodec::default();
+                    let mut grpc: GrpcServer<ProstCodec<_, _>> = GrpcServer::new(codec);
+                    Ok(grpc.unary(method, req).await)
+                })
+            },
+
+            // Incoming ListResultsRequest
+            "/driver.DriverService/ListResults" => {
+                /// Helper struct for the given DriverService that focusses specifically on this request.
+                struct ListResultsSvc<T>(Arc<T>);
+                impl<T: DriverService> UnaryService<ListResultsRequest> for ListResultsSvc<T> {
+                    type Future = BoxFuture<Response<Self::Response>, Status>;
+                    type Response = ListResultsReply;
+
+                    fn call(&mut self, req: Request<ListResultsRequest>) -> Self::Future {
+                        // Return the service function as the future to run
+                        let service = self.0.clone();
+                        let fut = async move { (*service).list_results(req).await };
+                        Box::pin(fut)
+                    }
+                }
+
+                // Create a future that creates the service
+                let service = self.service.clone();
+                Box::pin(async move {
+                    let method: ListResultsSvc<T> = ListResultsSvc(service);
+                    let codec: ProstCodec<_, _> = ProstCodec::default();
+                    let mut grpc: GrpcServer<ProstCodec<_, _>> = GrpcServer::new(codec);
+                    Ok(grpc.unary(method, req).await)
+                })
+            },
+
+            // Incoming PromoteResultRequest
+            "/driver.DriverService/PromoteResult" => {
+                /// Helper struct for the given DriverService that focusses specifically on this request.
+                struct PromoteResultSvc<T>(Arc<T>);
+                impl<T: DriverService> UnaryService<PromoteResultRequest> for PromoteResultSvc<T> {
+                    type Future = BoxFuture<Response<Self::Response>, Status>;
+                    type Response = PromoteResultReply;
+
+                    fn call(&mut self, req: Request<PromoteResultRequest>) -> Self::Future {
+                        // Return the service function as the future to run
+                        let service = self.0.clone();
+                        let fut = async move { (*service).promote_result(req).await };
+                        Box::pin(fut)
+                    }
+                }
+
+                // Create a future that creates the service
+                let service = self.service.clone();
+                Box::pin(async move {
+                    let method: PromoteResultSvc<T> = PromoteResultSvc(service);
+                    let codec: ProstCodec<_, _> = ProstCodec::default();
+                    let mut grpc: GrpcServer<ProstCodec<_, _>> = GrpcServer::new(codec);
+                    Ok(grpc.unary(method, req).await)
+                })
+            },
+
             // Incoming CheckRequest
             "/driver.DriverService/Check" => {
                 /// Helper struct for the given DriverService that focusses specifically on this request.
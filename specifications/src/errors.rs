@@ -4,7 +4,7 @@
  * Created:
  *   28 Jan 2022, 13:50:37
  * Last edited:
- *   28 Jan 2022, 15:24:46
+ *   15 Oct 2026, 21:39:24
  * Auto updated?
  *   Yes
  *
@@ -77,3 +77,274 @@ impl std::fmt::Display for EncodeDecodeError {
 }
 
 impl std::error::Error for EncodeDecodeError {}
+
+
+
+/// Errors that relate to parsing [`ErrorCode`]s.
+#[derive(Debug)]
+pub struct ErrorCodeParseError {
+    /// The raw string that we failed to parse.
+    pub raw: String,
+}
+
+impl std::fmt::Display for ErrorCodeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "Unknown Brane error code '{}'", self.raw) }
+}
+
+impl std::error::Error for ErrorCodeParseError {}
+
+
+
+
+
+/***** ERROR CODES *****/
+/// The name of the gRPC metadata key and HTTP header that carries an [`ErrorCode`].
+pub const ERROR_CODE_HEADER: &str = "brane-error-code";
+
+
+
+/// Defines machine-readable codes for the errors that services return to their clients.
+///
+/// Codes are attached to gRPC statuses (as metadata) and HTTP error responses (as a header) under [`ERROR_CODE_HEADER`], such that clients can
+/// react to them without matching on error messages. They are serialized as `BRANE-<SERVICE>-<NUMBER>`, e.g., `BRANE-REG-403`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorCode {
+    // Generic
+    /// The request was malformed.
+    BadRequest,
+    /// The service failed internally.
+    Internal,
+    /// The service (or one it depends on) is unavailable.
+    Unavailable,
+
+    // API
+    /// The requested package does not exist.
+    ApiUnknownPackage,
+    /// The remote build of a package failed.
+    ApiBuildFailed,
+    /// The OCI registry that a package is distributed through could not be used.
+    ApiOciFailed,
+    /// The image of a new package has vulnerabilities that are too severe.
+    ApiVulnerablePackage,
+    /// The image of a new package could not be scanned for vulnerabilities.
+    ApiScanFailed,
+
+    // Driver
+    /// The submitted workflow was invalid.
+    DrvInvalidWorkflow,
+    /// The workflow was denied by the policy of one of the domains.
+    DrvPolicyDenied,
+    /// The given session does not exist.
+    DrvUnknownSession,
+    /// The given session is executing a workflow on another replica of the driver.
+    DrvSessionBusy,
+    /// The workflow failed while executing.
+    DrvExecutionFailed,
+    /// The workflow did not complete within its deadline.
+    DrvDeadlineExceeded,
+    /// The client did not present a (valid) API token.
+    DrvUnauthenticated,
+    /// The user embedded in the workflow (or owning the session) is not the authenticated client.
+    DrvUserMismatch,
+
+    // Planner
+    /// No plan exists for the workflow, e.g., because no domain can run one of its tasks.
+    PlrUnplannable,
+    /// The workflow to plan was invalid.
+    PlrInvalidWorkflow,
+    /// The planned workflow was denied by the policy of one of the domains.
+    PlrPolicyDenied,
+    /// Planning the workflow would move data outside of the residency zones it is restricted to.
+    PlrResidencyViolation,
+
+    // Worker
+    /// The request sent to the worker was invalid.
+    JobInvalidRequest,
+    /// The worker's policy denied the request.
+    JobPolicyDenied,
+
+    // Registry
+    /// The client did not present a (valid) certificate.
+    RegUnauthenticated,
+    /// The registry's policy denied access to the asset.
+    RegPolicyDenied,
+    /// The requested dataset or result does not exist.
+    RegUnknownAsset,
+    /// The download of the requested result awaits approval by the domain's data steward.
+    RegApprovalPending,
+}
+
+impl ErrorCode {
+    /// All error codes, for iterating.
+    pub const ALL: [Self; 26] = [
+        Self::BadRequest,
+        Self::Internal,
+        Self::Unavailable,
+        Self::ApiUnknownPackage,
+        Self::ApiBuildFailed,
+        Self::ApiOciFailed,
+        Self::ApiVulnerablePackage,
+        Self::ApiScanFailed,
+        Self::DrvInvalidWorkflow,
+        Self::DrvPolicyDenied,
+        Self::DrvUnknownSession,
+        Self::DrvSessionBusy,
+        Self::DrvExecutionFailed,
+        Self::DrvDeadlineExceeded,
+        Self::DrvUnauthenticated,
+        Self::DrvUserMismatch,
+        Self::PlrUnplannable,
+        Self::PlrInvalidWorkflow,
+        Self::PlrPolicyDenied,
+        Self::PlrResidencyViolation,
+        Self::JobInvalidRequest,
+        Self::JobPolicyDenied,
+        Self::RegUnauthenticated,
+        Self::RegPolicyDenied,
+        Self::RegUnknownAsset,
+        Self::RegApprovalPending,
+    ];
+
+    /// Returns the serialized form of this code.
+    ///
+    /// # Returns
+    /// A static string like `BRANE-PLR-001`.
+    pub const fn as_str(&self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            BadRequest => "BRANE-GEN-400",
+            Internal => "BRANE-GEN-500",
+            Unavailable => "BRANE-GEN-503",
+
+            ApiUnknownPackage => "BRANE-API-404",
+            ApiBuildFailed => "BRANE-API-422",
+            ApiOciFailed => "BRANE-API-502",
+            ApiVulnerablePackage => "BRANE-API-403",
+            ApiScanFailed => "BRANE-API-503",
+
+            DrvInvalidWorkflow => "BRANE-DRV-400",
+            DrvPolicyDenied => "BRANE-DRV-403",
+            DrvUnknownSession => "BRANE-DRV-404",
+            DrvSessionBusy => "BRANE-DRV-409",
+            DrvExecutionFailed => "BRANE-DRV-500",
+            DrvDeadlineExceeded => "BRANE-DRV-504",
+            DrvUnauthenticated => "BRANE-DRV-401",
+            DrvUserMismatch => "BRANE-DRV-422",
+
+            PlrUnplannable => "BRANE-PLR-001",
+            PlrInvalidWorkflow => "BRANE-PLR-400",
+            PlrPolicyDenied => "BRANE-PLR-403",
+            PlrResidencyViolation => "BRANE-PLR-451",
+
+            JobInvalidRequest => "BRANE-JOB-400",
+            JobPolicyDenied => "BRANE-JOB-403",
+
+            RegUnauthenticated => "BRANE-REG-401",
+            RegPolicyDenied => "BRANE-REG-403",
+            RegUnknownAsset => "BRANE-REG-404",
+            RegApprovalPending => "BRANE-REG-423",
+        }
+    }
+
+    /// Returns a short, human-readable description of this code.
+    ///
+    /// # Returns
+    /// A static string describing what went wrong.
+    pub const fn description(&self) -> &'static str {
+        use ErrorCode::*;
+        match self {
+            BadRequest => "bad request",
+            Internal => "internal error",
+            Unavailable => "service unavailable",
+
+            ApiUnknownPackage => "unknown package",
+            ApiBuildFailed => "build failed",
+            ApiOciFailed => "OCI registry failed",
+            ApiVulnerablePackage => "vulnerable package",
+            ApiScanFailed => "scan failed",
+
+            DrvInvalidWorkflow => "invalid workflow",
+            DrvPolicyDenied => "policy denied",
+            DrvUnknownSession => "unknown session",
+            DrvSessionBusy => "session busy",
+            DrvExecutionFailed => "execution failed",
+            DrvDeadlineExceeded => "deadline exceeded",
+            DrvUnauthenticated => "unauthenticated",
+            DrvUserMismatch => "user mismatch",
+
+            PlrUnplannable => "unplannable",
+            PlrInvalidWorkflow => "invalid workflow",
+            PlrPolicyDenied => "policy denied",
+            PlrResidencyViolation => "residency violation",
+
+            JobInvalidRequest => "invalid request",
+            JobPolicyDenied => "policy denied",
+
+            RegUnauthenticated => "unauthenticated",
+            RegPolicyDenied => "policy denied",
+            RegUnknownAsset => "unknown asset",
+            RegApprovalPending => "approval pending",
+        }
+    }
+
+    /// Returns whether this code means that some domain's policy denied the request.
+    ///
+    /// # Returns
+    /// True if this is one of the `*PolicyDenied` codes, or false otherwise.
+    #[inline]
+    pub const fn is_policy_denial(&self) -> bool {
+        matches!(self, Self::DrvPolicyDenied | Self::PlrPolicyDenied | Self::JobPolicyDenied | Self::RegPolicyDenied)
+    }
+
+    /// Attaches this code to a gRPC status.
+    ///
+    /// # Arguments
+    /// - `status`: The [`tonic::Status`] to attach this code to.
+    ///
+    /// # Returns
+    /// The same status, with this code in its metadata.
+    pub fn attach(self, mut status: tonic::Status) -> tonic::Status {
+        status.metadata_mut().insert(ERROR_CODE_HEADER, tonic::metadata::MetadataValue::from_static(self.as_str()));
+        status
+    }
+
+    /// Reads the code attached to a gRPC status.
+    ///
+    /// # Arguments
+    /// - `status`: The [`tonic::Status`] to read the code from.
+    ///
+    /// # Returns
+    /// The attached code, or [`None`] if the status has none (or one we don't know).
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        status.metadata().get(ERROR_CODE_HEADER).and_then(|code| code.to_str().ok()).and_then(|code| code.parse().ok())
+    }
+
+    /// Reads the code attached to an HTTP response.
+    ///
+    /// # Arguments
+    /// - `headers`: The [`reqwest::header::HeaderMap`] of the response to read the code from.
+    ///
+    /// # Returns
+    /// The attached code, or [`None`] if the response has none (or one we don't know).
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        headers.get(ERROR_CODE_HEADER).and_then(|code| code.to_str().ok()).and_then(|code| code.parse().ok())
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "{} {}", self.as_str(), self.description()) }
+}
+
+impl std::str::FromStr for ErrorCode {
+    type Err = ErrorCodeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Only the code itself matters, not any trailing description
+        let raw: &str = s.split_whitespace().next().unwrap_or("");
+        match Self::ALL.iter().find(|code| code.as_str().eq_ignore_ascii_case(raw)) {
+            Some(code) => Ok(*code),
+            None => Err(ErrorCodeParseError { raw: s.into() }),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//  EVENTS.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:29:21
+//  Last edited:
+//    16 Oct 2026, 01:16:58
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the audit events that the services publish on the event bus
+//!   (Kafka or NATS) about the workflows they run and the data they give
+//!   access to.
+//!
+//!   Every service publishes its events as a hash chain: each event
+//!   carries the hash of the event published before it by the same
+//!   source, such that a consumer can detect if events were altered,
+//!   removed or reordered after they were published.
+//
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+
+/***** CONSTANTS *****/
+/// The default topic (Kafka) or subject (NATS) on which events are published.
+pub const DEFAULT_EVENTS_TOPIC: &str = "brane-audit";
+
+/// The hash that the first event of every source refers to as its predecessor.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines a single event as published on the event bus.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuditEvent {
+    /// The service (and node) that published the event (e.g., `brane-job@hospital_a`).
+    pub source:    String,
+    /// The number of this event among those published by the same source, starting at 0 when the service starts.
+    pub seq:       u64,
+    /// The moment the event occurred.
+    pub timestamp: DateTime<Utc>,
+    /// What happened.
+    #[serde(flatten)]
+    pub kind:      EventKind,
+
+    /// The hash of the previous event published by the same source, or [`GENESIS_HASH`] if this is the first one.
+    pub prev_hash: String,
+    /// The hex-encoded SHA-256 hash of this event, computed over its JSON serialization with this field set to an empty string.
+    pub hash:      String,
+}
+
+
+
+/// Defines the things that may happen that are published as an [`AuditEvent`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    /// The driver started executing a workflow.
+    WorkflowStarted {
+        /// The ID of the workflow.
+        workflow: String,
+        /// The ID of the session in which it runs.
+        app_id:   String,
+        /// The user who submitted it, if known.
+        user:     Option<String>,
+    },
+    /// The driver finished executing a workflow.
+    WorkflowFinished {
+        /// The ID of the workflow.
+        workflow: String,
+        /// The ID of the session in which it ran.
+        app_id:   String,
+        /// The user who submitted it, if known.
+        user:     Option<String>,
+        /// Whether it completed successfully.
+        success:  bool,
+    },
+    /// The driver cancelled a workflow because it did not complete within its deadline.
+    WorkflowTimedOut {
+        /// The ID of the workflow.
+        workflow: String,
+        /// The ID of the session in which it ran.
+        app_id:   String,
+        /// The user who submitted it, if known.
+        user:     Option<String>,
+        /// The deadline that it exceeded, in seconds.
+        deadline: u64,
+    },
+
+    /// A worker started executing a task.
+    TaskStarted {
+        /// The ID of the workflow that the task is part of.
+        workflow: String,
+        /// The name of the task.
+        task:     String,
+        /// The call of the task in the workflow.
+        pc:       String,
+        /// The user on whose behalf it runs, if known.
+        user:     Option<String>,
+    },
+    /// A worker finished executing a task.
+    TaskFinished {
+        /// The ID of the workflow that the task is part of.
+        workflow: String,
+        /// The name of the task.
+        task:     String,
+        /// The call of the task in the workflow.
+        pc:       String,
+        /// The user on whose behalf it ran, if known.
+        user:     Option<String>,
+        /// Whether it completed successfully.
+        success:  bool,
+    },
+
+    /// A registry was asked to give access to a dataset or intermediate result.
+    DataAccessed {
+        /// The ID of the workflow in whose context access is asked, if known.
+        workflow:  Option<String>,
+        /// The user on whose behalf access is asked, if known.
+        user:      Option<String>,
+        /// The name of the dataset or intermediate result.
+        data:      String,
+        /// Whether it's an intermediate result (`true`) or a dataset (`false`).
+        result:    bool,
+        /// The domain that asked for access, if it identified itself.
+        requester: Option<String>,
+        /// Whether access was granted.
+        allowed:   bool,
+    },
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    12 Jun 2023, 17:13:25
 //  Last edited:
-//    28 Jun 2023, 09:37:30
+//    16 Oct 2026, 02:39:20
 //  Auto updated?
 //    Yes
 // 
@@ -439,7 +439,7 @@ impl<I: IndexInfo + Info<N>, N: Interface> Index<I, N> {
         self.infos.get(identifier.as_ref()).map(|versions| {
             let mut latest: Option<Version> = None;
             for version in versions.keys() {
-                if latest.is_none() || *version > latest.unwrap() { latest = Some(*version); }
+                if latest.is_none() || version.cmp_release(&latest.unwrap()).is_gt() { latest = Some(*version); }
             }
             latest
         }).flatten()
@@ -4,7 +4,7 @@
 //  Created:
 //    07 Jun 2023, 16:22:04
 //  Last edited:
-//    01 May 2024, 10:11:07
+//    16 Oct 2026, 00:29:09
 //  Auto updated?
 //    Yes
 //
@@ -24,11 +24,15 @@ pub mod container;
 pub mod data;
 pub mod driving;
 pub mod errors;
+pub mod events;
 pub mod os;
 pub mod package;
 pub mod planning;
 pub mod policy;
 pub mod profiling;
+pub mod reflection;
 pub mod registering;
+pub mod reporting;
+pub mod telemetry;
 pub mod version;
 pub mod working;
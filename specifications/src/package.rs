@@ -4,7 +4,7 @@
 //  Created:
 //    01 Mar 2023, 09:45:11
 //  Last edited:
-//    01 Mar 2023, 09:45:26
+//    16 Oct 2026, 02:28:32
 //  Auto updated?
 //    Yes
 //
@@ -18,6 +18,7 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use enum_debug::EnumDebug;
 // use semver::Version;
@@ -30,7 +31,7 @@ use uuid::Uuid;
 
 use crate::common::{Function, Type};
 use crate::container::ContainerInfo;
-use crate::version::Version;
+use crate::version::{Version, VersionRange};
 
 
 /***** CUSTOM TYPES *****/
@@ -92,6 +93,25 @@ impl std::fmt::Display for CapabilityParseError {
 }
 impl std::error::Error for CapabilityParseError {}
 
+/// Lists the error for parsing a Severity from a string.
+#[derive(Debug)]
+pub enum SeverityParseError {
+    /// An unknown severity was given.
+    UnknownSeverity { raw: String },
+}
+impl std::fmt::Display for SeverityParseError {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use SeverityParseError::*;
+        match self {
+            UnknownSeverity { raw } => {
+                write!(f, "Unknown severity '{raw}' (options are 'unknown', 'negligible', 'low', 'medium', 'high' or 'critical')")
+            },
+        }
+    }
+}
+impl std::error::Error for SeverityParseError {}
+
 
 /// Lists the errors that can occur for the PackageInfo struct
 #[derive(Debug)]
@@ -189,6 +209,9 @@ pub enum PackageKind {
     /// The package is an CWL job(?)
     #[serde(rename = "cwl")]
     Cwl,
+    /// The package is a WebAssembly module (run by `branelet` in an embedded runtime)
+    #[serde(rename = "wasm")]
+    Wasm,
 }
 
 impl PackageKind {
@@ -199,6 +222,7 @@ impl PackageKind {
             PackageKind::Oas => "Open API Standard package",
             PackageKind::Dsl => "BraneScript/Bakery package",
             PackageKind::Cwl => "CWL package",
+            PackageKind::Wasm => "WebAssembly package",
         }
     }
 }
@@ -216,6 +240,7 @@ impl std::str::FromStr for PackageKind {
             "oas" => Ok(PackageKind::Oas),
             "dsl" => Ok(PackageKind::Dsl),
             "cwl" => Ok(PackageKind::Cwl),
+            "wasm" => Ok(PackageKind::Wasm),
             _ => Err(PackageKindError::IllegalKind { skind: ls }),
         }
     }
@@ -232,6 +257,7 @@ impl std::convert::From<&PackageKind> for String {
             PackageKind::Oas => String::from("oas"),
             PackageKind::Dsl => String::from("dsl"),
             PackageKind::Cwl => String::from("cwl"),
+            PackageKind::Wasm => String::from("wasm"),
         }
     }
 }
@@ -278,9 +304,173 @@ impl FromStr for Capability {
 
 
 
+/// Defines the severity of a vulnerability found in the image of a package, ordered from least to most severe.
+#[derive(Clone, Copy, Debug, Deserialize, EnumDebug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The scanner could not determine the severity.
+    Unknown,
+    /// The vulnerability is not considered a practical risk.
+    Negligible,
+    /// The vulnerability has a low severity.
+    Low,
+    /// The vulnerability has a medium severity.
+    Medium,
+    /// The vulnerability has a high severity.
+    High,
+    /// The vulnerability has a critical severity.
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use Severity::*;
+        match self {
+            Unknown => write!(f, "unknown"),
+            Negligible => write!(f, "negligible"),
+            Low => write!(f, "low"),
+            Medium => write!(f, "medium"),
+            High => write!(f, "high"),
+            Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = SeverityParseError;
+
+    /// Parses a severity case-insensitively, such that the severities reported by scanners (e.g., `CRITICAL` or `Critical`) can be parsed as-is.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unknown" => Ok(Self::Unknown),
+            "negligible" => Ok(Self::Negligible),
+            "low" => Ok(Self::Low),
+            "medium" => Ok(Self::Medium),
+            "high" => Ok(Self::High),
+            "critical" => Ok(Self::Critical),
+
+            _ => Err(SeverityParseError::UnknownSeverity { raw: s.into() }),
+        }
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
+/// Defines the minimum resources that a package needs to run, as declared in its `container.yml`.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageResources {
+    /// The minimum number of CPU cores.
+    pub cpus:   Option<u32>,
+    /// The minimum amount of memory, in megabytes.
+    pub memory: Option<u64>,
+    /// The minimum amount of disk space, in megabytes.
+    pub disk:   Option<u64>,
+    /// The minimum number of GPUs.
+    pub gpus:   Option<u32>,
+
+    /// The kind of storage it needs for its datasets and results (e.g., `ssd`), which must match that of the location exactly.
+    #[serde(alias = "storage_class")]
+    pub storage_class: Option<String>,
+}
+impl std::fmt::Display for PackageResources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = Vec::with_capacity(5);
+        if let Some(cpus) = self.cpus {
+            parts.push(format!("{cpus} CPU(s)"));
+        }
+        if let Some(memory) = self.memory {
+            parts.push(format!("{memory} MB memory"));
+        }
+        if let Some(disk) = self.disk {
+            parts.push(format!("{disk} MB disk"));
+        }
+        if let Some(gpus) = self.gpus {
+            parts.push(format!("{gpus} GPU(s)"));
+        }
+        if let Some(storage_class) = &self.storage_class {
+            parts.push(format!("{storage_class} storage"));
+        }
+        if parts.is_empty() {
+            write!(f, "<unspecified>")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+
+
+/// Defines a single vulnerability that a scanner found in the image of a package.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Vulnerability {
+    /// The identifier of the vulnerability (e.g., `CVE-2023-1234`).
+    pub id:       String,
+    /// How severe the vulnerability is.
+    pub severity: Severity,
+    /// The name of the (OS or language) package in the image that is vulnerable.
+    pub package:  String,
+    /// The version of the vulnerable package that is installed in the image.
+    pub version:  String,
+    /// The version of the package that fixes the vulnerability, if any.
+    pub fixed_in: Option<String>,
+}
+
+/// Defines the findings of scanning the image of a package for known vulnerabilities, which are stored alongside the package.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    /// The name of the scanner that scanned the image (e.g., `trivy`).
+    pub scanner:  String,
+    /// When the image was scanned.
+    pub scanned:  DateTime<Utc>,
+    /// The vulnerabilities found.
+    pub findings: Vec<Vulnerability>,
+}
+impl ScanReport {
+    /// Returns the severity of the most severe vulnerability found.
+    ///
+    /// # Returns
+    /// The highest [`Severity`] among the findings, or [`None`] if nothing was found.
+    #[inline]
+    pub fn max_severity(&self) -> Option<Severity> { self.findings.iter().map(|v| v.severity).max() }
+
+    /// Returns the vulnerabilities that are at least as severe as the given one.
+    ///
+    /// # Arguments
+    /// - `threshold`: The [`Severity`] from which vulnerabilities are included.
+    ///
+    /// # Returns
+    /// An iterator over the matching [`Vulnerability`]s.
+    #[inline]
+    pub fn at_least(&self, threshold: Severity) -> impl Iterator<Item = &Vulnerability> {
+        self.findings.iter().filter(move |v| v.severity >= threshold)
+    }
+}
+impl std::fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.findings.is_empty() {
+            return write!(f, "no vulnerabilities found by {}", self.scanner);
+        }
+        let mut counts: Vec<String> = Vec::with_capacity(6);
+        for severity in [Severity::Critical, Severity::High, Severity::Medium, Severity::Low, Severity::Negligible, Severity::Unknown] {
+            let count: usize = self.findings.iter().filter(|v| v.severity == severity).count();
+            if count > 0 {
+                counts.push(format!("{count} {severity}"));
+            }
+        }
+        write!(f, "{} vulnerabilities found by {} ({})", self.findings.len(), self.scanner, counts.join(", "))
+    }
+}
+
+
+
 /// The PackageInfo struct, which might be used alongside a Docker container to define its metadata.
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -292,6 +482,8 @@ pub struct PackageInfo {
     pub id:      Uuid,
     /// The digest of the resulting image. As long as the image has not been generated, is None.
     pub digest:  Option<String>,
+    /// The OCI reference (pinned to a digest) the image can be pulled from, if it is distributed through an OCI registry.
+    pub oci:     Option<String>,
 
     /// The name/programming ID of this package.
     pub name: String,
@@ -303,6 +495,20 @@ pub struct PackageInfo {
     pub owners: Vec<String>,
     /// A short description of the package.
     pub description: String,
+    /// The license of the package (e.g., an SPDX identifier like `Apache-2.0`), if any.
+    pub license: Option<String>,
+    /// The list of people maintaining this package. Older (v1) manifests don't have this.
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+    /// A link to the homepage of the package, if any.
+    pub homepage: Option<String>,
+    /// The minimum resources that the package needs to run, if declared.
+    pub resources: Option<PackageResources>,
+    /// A list of tags that help finding the package. Older (v1) manifests don't have this.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The findings of scanning the image of the package for vulnerabilities, if the registry it was uploaded to did so.
+    pub scan: Option<ScanReport>,
 
     /// Whether or not the functions in this package run detached (i.e., asynchronous).
     pub detached:  bool,
@@ -316,6 +522,8 @@ pub struct PackageInfo {
 impl PackageInfo {
     /// Constructor for the PackageInfo.
     ///
+    /// Note that the fields added in manifest v2 (the license, maintainers, homepage, resources and tags) and the scan report are left empty.
+    ///
     /// **Arguments**
     ///  * `name`: The name/programming ID of this package.
     ///  * `version`: The version of this package.
@@ -341,7 +549,26 @@ impl PackageInfo {
         let created = Utc::now();
 
         // Return the package
-        PackageInfo { created, id, digest: None, name, version, kind, owners, description, detached, functions, types }
+        PackageInfo {
+            created,
+            id,
+            digest: None,
+            oci: None,
+            name,
+            version,
+            kind,
+            owners,
+            description,
+            license: None,
+            maintainers: Vec::new(),
+            homepage: None,
+            resources: None,
+            tags: Vec::new(),
+            scan: None,
+            detached,
+            functions,
+            types,
+        }
     }
 
     /// **Edited: changed to return appropriate errors. Also added docstring.**
@@ -454,16 +681,23 @@ impl From<ContainerInfo> for PackageInfo {
         }
 
         // Put it an other values in the new instance
-        PackageInfo::new(
-            container.name,
-            container.version,
-            container.kind,
-            container.owners.unwrap_or_default(),
-            container.description.unwrap_or_default(),
-            container.entrypoint.kind == *"service",
-            functions,
-            container.types.unwrap_or_default(),
-        )
+        PackageInfo {
+            license: container.license,
+            maintainers: container.maintainers.unwrap_or_default(),
+            homepage: container.homepage,
+            resources: container.resources,
+            tags: container.tags.unwrap_or_default(),
+            ..PackageInfo::new(
+                container.name,
+                container.version,
+                container.kind,
+                container.owners.unwrap_or_default(),
+                container.description.unwrap_or_default(),
+                container.entrypoint.kind == *"service",
+                functions,
+                container.types.unwrap_or_default(),
+            )
+        }
     }
 }
 
@@ -489,25 +723,32 @@ impl From<&ContainerInfo> for PackageInfo {
         }
 
         // Put it and other clones in the new instance
-        PackageInfo::new(
-            container.name.clone(),
-            container.version,
-            container.kind,
-            match container.owners.as_ref() {
-                Some(owners) => owners.clone(),
-                None => Vec::new(),
-            },
-            match container.description.as_ref() {
-                Some(description) => description.clone(),
-                None => String::new(),
-            },
-            container.entrypoint.kind == *"service",
-            functions,
-            match container.types.as_ref() {
-                Some(types) => types.clone(),
-                None => Map::new(),
-            },
-        )
+        PackageInfo {
+            license: container.license.clone(),
+            maintainers: container.maintainers.clone().unwrap_or_default(),
+            homepage: container.homepage.clone(),
+            resources: container.resources.clone(),
+            tags: container.tags.clone().unwrap_or_default(),
+            ..PackageInfo::new(
+                container.name.clone(),
+                container.version,
+                container.kind,
+                match container.owners.as_ref() {
+                    Some(owners) => owners.clone(),
+                    None => Vec::new(),
+                },
+                match container.description.as_ref() {
+                    Some(description) => description.clone(),
+                    None => String::new(),
+                },
+                container.entrypoint.kind == *"service",
+                functions,
+                match container.types.as_ref() {
+                    Some(types) => types.clone(),
+                    None => Map::new(),
+                },
+            )
+        }
     }
 }
 
@@ -541,9 +782,9 @@ impl PackageIndex {
                 continue;
             }
 
-            // Check if the existing version is later or not
+            // Check if the existing version is later or not (where normal releases beat pre-releases)
             let latest_package: &mut (Version, String) = latest.get_mut(&package.name).unwrap();
-            if package.version >= latest_package.0 {
+            if package.version.cmp_release(&latest_package.0).is_ge() {
                 // It is; update the version to point to the latest version of this package
                 latest_package.0 = package.version;
                 latest_package.1.clone_from(key);
@@ -680,6 +921,35 @@ impl PackageIndex {
         Ok(PackageIndex::new(packages))
     }
 
+    /// Adds a single package to the PackageIndex.
+    ///
+    /// **Arguments**
+    ///  * `package`: The PackageInfo to add.
+    ///
+    /// **Returns**  
+    /// Nothing if the package was added, or a PackageIndexError if this version of the package was already known.
+    pub fn insert(&mut self, package: PackageInfo) -> Result<(), PackageIndexError> {
+        let key = format!("{}-{}", package.name, package.version);
+        if self.packages.contains_key(&key) {
+            return Err(PackageIndexError::DuplicatePackage { name: package.name.clone(), version: package.version.to_string() });
+        }
+
+        // Update the latest version cache if this one is newer
+        match self.latest.get_mut(&package.name) {
+            Some(latest_package) => {
+                if package.version.cmp_release(&latest_package.0).is_ge() {
+                    latest_package.0 = package.version;
+                    latest_package.1.clone_from(&key);
+                }
+            },
+            None => {
+                self.latest.insert(package.name.clone(), (package.version, key.clone()));
+            },
+        }
+        self.packages.insert(key, package);
+        Ok(())
+    }
+
     /// Returns the package with the given name and (optional) version.
     ///
     /// **Arguments**
@@ -715,6 +985,22 @@ impl PackageIndex {
         self.packages.get(&format!("{name}-{version}"))
     }
 
+    /// Returns the package with the given name and the highest version in the given range.
+    ///
+    /// **Arguments**
+    ///  * `name`: The name of the package.
+    ///  * `range`: The VersionRange that the version of the package must be in.
+    ///
+    /// **Returns**  
+    /// An (immuteable) reference to the package if it exists and has a version in range, or else None.
+    pub fn get_range(&self, name: &str, range: &VersionRange) -> Option<&PackageInfo> {
+        // Find the highest version in range
+        let version: Version = range.highest_match(self.packages.values().filter(|p| p.name == name).map(|p| p.version))?;
+
+        // Return the package info matching to this name/version pair
+        self.packages.get(&format!("{name}-{version}"))
+    }
+
     /// Returns the latest version of the given package.
     ///
     /// **Arguments**
@@ -725,3 +1011,30 @@ impl PackageIndex {
     #[inline]
     fn get_latest_version(&self, name: &str) -> Option<&Version> { self.latest.get(name).map(|(version, _)| version) }
 }
+
+
+
+/// Resolves packages on demand, such that a client does not have to download the full [`PackageIndex`] of an instance before it can compile a
+/// workflow that only imports a few of them.
+#[async_trait]
+pub trait PackageIndexProvider: Send + Sync {
+    /// The error returned when the provider fails to resolve packages.
+    type Error: 'static + Send + Sync + std::error::Error;
+
+    /// Resolves all versions of the package with the given name.
+    ///
+    /// **Arguments**
+    ///  * `name`: The name of the package to resolve.
+    ///
+    /// **Returns**  
+    /// The PackageInfos of all known versions of the package, which is empty if the package is unknown.
+    async fn packages(&self, name: &str) -> Result<Vec<PackageInfo>, Self::Error>;
+
+    /// Resolves all packages known to the provider.
+    ///
+    /// This is needed when the packages that a workflow uses cannot be derived from its imports (e.g., for Bakery patterns).
+    ///
+    /// **Returns**  
+    /// The PackageInfos of all versions of all known packages.
+    async fn all_packages(&self) -> Result<Vec<PackageInfo>, Self::Error>;
+}
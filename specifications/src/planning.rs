@@ -4,18 +4,57 @@
 //  Created:
 //    28 Sep 2022, 10:33:37
 //  Last edited:
-//    08 Feb 2024, 17:24:07
+//    16 Oct 2026, 00:18:28
 //  Auto updated?
 //    Yes
 //
 //  Description:
 //!   Defines Kafka network messages used by `brane-drv` <-> `brane-job`
-//!   <-> `brane-plr` interaction.
+//!   <-> `brane-plr` interaction, and the placement constraints that are
+//!   sent along with workflows.
 //
 
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FResult};
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::package::Capability;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether a residency tag matches the given labels of a location.
+///
+/// # Arguments
+/// - `tag`: The tag to match. Either `key=value` (the location must have label `key` with value `value`) or `key` (the location must have label `key`).
+/// - `labels`: The labels of the location.
+///
+/// # Returns
+/// True if the tag matches, or false otherwise.
+fn tag_matches(tag: &str, labels: &HashMap<String, String>) -> bool {
+    match tag.split_once('=') {
+        Some((key, value)) => labels.get(key.trim()).map(|v| v == value.trim()).unwrap_or(false),
+        None => labels.contains_key(tag.trim()),
+    }
+}
+
+/// Formats a set of strings as a sorted, comma-separated list.
+///
+/// # Arguments
+/// - `set`: The set to format.
+///
+/// # Returns
+/// The formatted list.
+fn sorted_list<'s>(set: impl IntoIterator<Item = &'s String>) -> String {
+    let mut list: Vec<&str> = set.into_iter().map(String::as_str).collect();
+    list.sort_unstable();
+    list.join(", ")
+}
+
+
+
+
 
 /***** NETWORKING *****/
 /// Defines a message that carries an _unplanned_ workflow. It is destined to be intercepted by the planner.
@@ -46,3 +85,199 @@ pub struct PlanningDeniedReply {
     /// A list of reasons given by the domain. May be empty.
     pub reasons: Vec<String>,
 }
+
+/// Defines the reply of the planner if planning a task would move data outside of the residency zones it is restricted to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResidencyViolation {
+    /// The name of the task that could not be planned.
+    pub task:     String,
+    /// The location where the task would have been planned.
+    pub location: String,
+    /// The name of the dataset (or intermediate result derived from it) that may not go there.
+    pub data:     String,
+    /// The residency zones of the data that the location is not part of.
+    pub zones:    Vec<String>,
+}
+
+
+
+/// Defines a message that asks the planner how it _would_ plan a workflow on a hypothetical infrastructure, without running it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WhatIfRequest {
+    /// The raw workflow, as JSON, to plan.
+    ///
+    /// Note that, to avoid cyclic dependency on `brane-ast`, we define it as an abstract JSON [`Value`].
+    pub workflow: Value,
+    /// If given, replaces the instance's infrastructure file while planning. It has the same layout as `infra.yml`.
+    ///
+    /// Note that, to avoid cyclic dependency on `brane-cfg`, we define it as an abstract JSON [`Value`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub infra: Option<Value>,
+    /// Replaces the capabilities of the given locations while planning. Locations that are not part of the instance must be given here.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub capabilities: HashMap<String, HashSet<Capability>>,
+}
+
+/// Defines the reply of the planner to a [`WhatIfRequest`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WhatIfReply {
+    /// The workflow after planning.
+    ///
+    /// Note that, to avoid cyclic dependency on `brane-ast`, we define it as an abstract JSON [`Value`].
+    pub plan: Value,
+    /// The location that every task call was planned on, by its program counter (see [`Constraints::task_key()`]).
+    pub placements: HashMap<String, String>,
+}
+
+
+
+
+
+/***** CONSTRAINTS *****/
+/// Defines the placement constraints on a single task call in a workflow.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TaskConstraints {
+    /// If given, the task may only be executed on one of these locations. Otherwise, any location is allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<HashSet<String>>,
+    /// The task may never be executed on any of these locations, even if they are `allowed`.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub denied:  HashSet<String>,
+}
+impl TaskConstraints {
+    /// Checks whether the given location is allowed by these constraints.
+    ///
+    /// # Arguments
+    /// - `location`: The name of the location to check.
+    ///
+    /// # Returns
+    /// True if the task may be executed there, or false otherwise.
+    #[inline]
+    pub fn allows(&self, location: &str) -> bool {
+        self.allowed.as_ref().map(|allowed| allowed.contains(location)).unwrap_or(true) && !self.denied.contains(location)
+    }
+}
+
+
+
+/// Defines the placement constraints of a workflow.
+///
+/// These are serialized as part of the workflow, such that the compiler (which derives them from attributes), the planner (which has to
+/// satisfy them) and the checkers (which may verify them) all use the same definition.
+///
+/// Task calls are referred to by their program counter, i.e., `<func>:<edge>`, where `<func>` is either `<main>` or the index of the function
+/// in the workflow's table and `<edge>` is the index of the call's edge in that function (see [`Constraints::task_key()`]).
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Constraints {
+    /// The constraints on individual task calls.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tasks:     HashMap<String, TaskConstraints>,
+    /// Groups of task calls that must all be executed on the same location.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub colocate:  Vec<HashSet<String>>,
+    /// Residency tags of datasets and intermediate results (by name). Task calls that take such data as input may only be executed on
+    /// locations with labels that match all of its tags, where a tag is either `key=value` (the label must have that value) or `key` (the label
+    /// must exist).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub residency: HashMap<String, HashSet<String>>,
+}
+impl Constraints {
+    /// Returns the key by which a task call is referred to in the constraints.
+    ///
+    /// # Arguments
+    /// - `func`: The index of the function in which the call is made, or [`None`] if it's made in `<main>`.
+    /// - `edge`: The index of the call's edge in that function.
+    ///
+    /// # Returns
+    /// The key of the call, i.e., its program counter.
+    #[inline]
+    pub fn task_key(func: Option<usize>, edge: usize) -> String {
+        match func {
+            Some(func) => format!("{func}:{edge}"),
+            None => format!("<main>:{edge}"),
+        }
+    }
+
+    /// Returns whether there are any constraints at all.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.tasks.is_empty() && self.colocate.is_empty() && self.residency.is_empty() }
+
+    /// Returns the constraints on the given task call, adding empty ones if it had none yet.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the task call (see [`Constraints::task_key()`]).
+    ///
+    /// # Returns
+    /// A mutable reference to the call's [`TaskConstraints`].
+    #[inline]
+    pub fn task_mut(&mut self, key: impl Into<String>) -> &mut TaskConstraints { self.tasks.entry(key.into()).or_default() }
+
+    /// Returns the co-location group that the given task call is a part of.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the task call (see [`Constraints::task_key()`]).
+    ///
+    /// # Returns
+    /// The index of the group in [`Constraints::colocate`], or [`None`] if the call isn't part of any.
+    #[inline]
+    pub fn group(&self, key: &str) -> Option<usize> { self.colocate.iter().position(|group| group.contains(key)) }
+
+    /// Returns whether the given task call is constrained at all.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the task call (see [`Constraints::task_key()`]).
+    /// - `inputs`: The names of the datasets and intermediate results that the call takes as input.
+    ///
+    /// # Returns
+    /// True if there are constraints on the call itself, if it's part of a co-location group or if any of its inputs have residency tags.
+    pub fn constrains<'i>(&self, key: &str, inputs: impl IntoIterator<Item = &'i str>) -> bool {
+        self.tasks.contains_key(key) || self.group(key).is_some() || inputs.into_iter().any(|name| self.residency.contains_key(name))
+    }
+
+    /// Checks whether the given task call may be executed on the given location.
+    ///
+    /// Note that this does not consider co-location groups, as those depend on where the other calls in the group are executed.
+    ///
+    /// # Arguments
+    /// - `key`: The key of the task call (see [`Constraints::task_key()`]).
+    /// - `location`: The name of the location to check.
+    /// - `labels`: The labels of that location (as given in the infrastructure file).
+    /// - `inputs`: The names of the datasets and intermediate results that the call takes as input.
+    ///
+    /// # Returns
+    /// True if the call may be executed there, or false otherwise.
+    pub fn allows<'i>(&self, key: &str, location: &str, labels: &HashMap<String, String>, inputs: impl IntoIterator<Item = &'i str>) -> bool {
+        if let Some(task) = self.tasks.get(key) {
+            if !task.allows(location) {
+                return false;
+            }
+        }
+        inputs.into_iter().filter_map(|name| self.residency.get(name)).flatten().all(|tag| tag_matches(tag, labels))
+    }
+}
+impl Display for Constraints {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let mut keys: Vec<&String> = self.tasks.keys().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let task: &TaskConstraints = &self.tasks[key];
+            write!(f, "task {key}")?;
+            if let Some(allowed) = &task.allowed {
+                write!(f, " allowed [{}]", sorted_list(allowed))?;
+            }
+            if !task.denied.is_empty() {
+                write!(f, " denied [{}]", sorted_list(&task.denied))?;
+            }
+            writeln!(f)?;
+        }
+        for group in &self.colocate {
+            writeln!(f, "colocate [{}]", sorted_list(group))?;
+        }
+        let mut names: Vec<&String> = self.residency.keys().collect();
+        names.sort_unstable();
+        for name in names {
+            writeln!(f, "residency '{}' [{}]", name, sorted_list(&self.residency[name]))?;
+        }
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    01 Feb 2023, 09:54:51
 //  Last edited:
-//    06 Feb 2024, 12:53:50
+//    15 Oct 2026, 21:24:42
 //  Auto updated?
 //    Yes
 //
@@ -27,13 +27,17 @@ use std::ops::Deref;
 use std::path::PathBuf;
 use std::str;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use chrono::{DateTime, Local};
 use enum_debug::EnumDebug;
 use log::warn;
 use parking_lot::{Mutex, MutexGuard};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::telemetry::TraceContext;
 
 
 /***** HELPER MACROS *****/
@@ -93,6 +97,96 @@ impl ProfileTiming {
 
 
 
+/***** HELPER STRUCTS *****/
+/// Defines a single span in an exported trace, i.e., a timing or scope of which we know when it started.
+#[derive(Debug)]
+struct Span {
+    /// The name of the timing or scope.
+    name:   String,
+    /// The start of the span, in nanoseconds since the Unix epoch.
+    start:  u64,
+    /// The end of the span, in nanoseconds since the Unix epoch.
+    end:    u64,
+    /// The index of the span's parent in the list of spans, if any.
+    parent: Option<usize>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// The offset basis of the 64-bit FNV-1a hash.
+pub(crate) const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+
+/// Returns the current time in nanoseconds since the Unix epoch.
+///
+/// # Returns
+/// The current time, or [`None`] if the system clock is not between the Unix epoch and the year 2554.
+#[inline]
+fn unix_nanos() -> Option<u64> { SystemTime::now().duration_since(UNIX_EPOCH).ok().and_then(|d| u64::try_from(d.as_nanos()).ok()) }
+
+/// Hashes the given bytes with the 64-bit FNV-1a hash, which (unlike the standard hasher) is stable across services.
+///
+/// # Arguments
+/// - `data`: The bytes to hash.
+/// - `offset`: The offset basis to start with. Use [`FNV_OFFSET`] for the standard hash.
+///
+/// # Returns
+/// The hash of the bytes.
+#[inline]
+pub(crate) fn fnv1a(data: &[u8], offset: u64) -> u64 { data.iter().fold(offset, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001B3)) }
+
+/// Collects the given scope and everything nested in it as a list of spans.
+///
+/// Scopes get the time spanned by their children, and are omitted if none of their children have a known start.
+///
+/// # Arguments
+/// - `scope`: The ProfileScope to collect.
+/// - `parent`: The index of the span of the scope's parent, if any.
+/// - `spans`: The list of spans to add the collected spans to.
+///
+/// # Returns
+/// The start and end of the span of the scope, or [`None`] if it has been omitted.
+fn collect_spans(scope: &ProfileScope, parent: Option<usize>, spans: &mut Vec<Span>) -> Option<(u64, u64)> {
+    // Add the scope itself first, such that its children can refer to it
+    let index: usize = spans.len();
+    spans.push(Span { name: scope.name.clone(), start: 0, end: 0, parent });
+
+    // Add its children, keeping track of the time they span
+    let mut range: Option<(u64, u64)> = None;
+    for t in scope.timings.lock().iter() {
+        let child: Option<(u64, u64)> = match t {
+            ProfileTiming::Timing(name, timing) => {
+                let timing: Timing = *timing.lock();
+                timing.start.map(|start| {
+                    let end: u64 = start.saturating_add(u64::try_from(timing.nanos).unwrap_or(u64::MAX));
+                    spans.push(Span { name: name.clone(), start, end, parent: Some(index) });
+                    (start, end)
+                })
+            },
+            ProfileTiming::Scope(scope) => collect_spans(scope, Some(index), spans),
+        };
+        if let Some((start, end)) = child {
+            range = Some(range.map(|(rstart, rend)| (rstart.min(start), rend.max(end))).unwrap_or((start, end)));
+        }
+    }
+
+    // Set the time of the scope, or remove it if there wasn't any (in which case nothing has been added after it)
+    match range {
+        Some((start, end)) => {
+            spans[index].start = start;
+            spans[index].end = end;
+        },
+        None => spans.truncate(index),
+    }
+    range
+}
+
+
+
+
+
 /***** FORMATTERS *****/
 /// Formats the giving Timing to show a (hopefully) sensible scale to the given formatter.
 #[derive(Debug)]
@@ -179,6 +273,9 @@ impl<'s> Display for ProfileScopeFormatter<'s> {
 pub struct Timing {
     /// The amount of nanoseconds that have passed.
     nanos: u128,
+    /// When the timing was started, in nanoseconds since the Unix epoch. Is [`None`] if unknown (e.g., when created from a [`Duration`]).
+    #[serde(default)]
+    start: Option<u64>,
 }
 
 impl Timing {
@@ -187,7 +284,25 @@ impl Timing {
     /// # Returns
     /// A new Timing instance, for which all `Timing::elapsed_XX()` functions will return 0.
     #[inline]
-    pub const fn none() -> Self { Self { nanos: 0 } }
+    pub const fn none() -> Self { Self { nanos: 0, start: None } }
+
+    /// Returns a Timing for the time that has passed since the given moment.
+    ///
+    /// # Arguments
+    /// - `start`: The Instant at which the timing started.
+    /// - `start_unix`: The same moment as `start`, but in nanoseconds since the Unix epoch (see [`unix_nanos()`]).
+    ///
+    /// # Returns
+    /// A new Timing instance that remembers when it was started.
+    #[inline]
+    fn since(start: Instant, start_unix: Option<u64>) -> Self { Self { nanos: start.elapsed().as_nanos(), start: start_unix } }
+
+    /// Returns when this Timing was started.
+    ///
+    /// # Returns
+    /// The start of the timing in nanoseconds since the Unix epoch, or [`None`] if this is unknown.
+    #[inline]
+    pub const fn start_unix_ns(&self) -> Option<u64> { self.start }
 
     /// Writes a human-readable representation of the elapsed time in this Timing.
     ///
@@ -242,15 +357,15 @@ impl From<&mut Timing> for Timing {
 
 impl From<Duration> for Timing {
     #[inline]
-    fn from(value: Duration) -> Self { Timing { nanos: value.as_nanos() } }
+    fn from(value: Duration) -> Self { Timing { nanos: value.as_nanos(), start: None } }
 }
 impl From<&Duration> for Timing {
     #[inline]
-    fn from(value: &Duration) -> Self { Timing { nanos: value.as_nanos() } }
+    fn from(value: &Duration) -> Self { Timing { nanos: value.as_nanos(), start: None } }
 }
 impl From<&mut Duration> for Timing {
     #[inline]
-    fn from(value: &mut Duration) -> Self { Timing { nanos: value.as_nanos() } }
+    fn from(value: &mut Duration) -> Self { Timing { nanos: value.as_nanos(), start: None } }
 }
 
 
@@ -259,11 +374,13 @@ impl From<&mut Duration> for Timing {
 #[derive(Debug)]
 pub struct TimerGuard<'s> {
     /// The start of the timing.
-    start:     Instant,
+    start:      Instant,
+    /// The start of the timing, in nanoseconds since the Unix epoch.
+    start_unix: Option<u64>,
     /// The timing to populate.
-    timing:    Arc<Mutex<Timing>>,
+    timing:     Arc<Mutex<Timing>>,
     /// We mark the phantom lifetime because the above is a weak reference
-    _lifetime: PhantomData<&'s ()>,
+    _lifetime:  PhantomData<&'s ()>,
 }
 impl<'s> TimerGuard<'s> {
     /// Early stop the timer. This effectively just janks the guard out-of-scope by taking ownership of it.
@@ -274,7 +391,7 @@ impl<'s> Drop for TimerGuard<'s> {
     fn drop(&mut self) {
         // Set it, done
         let mut lock: MutexGuard<Timing> = self.timing.lock();
-        *lock = self.start.elapsed().into();
+        *lock = Timing::since(self.start, self.start_unix);
     }
 }
 
@@ -344,14 +461,22 @@ impl<'s> From<ProfileScopeHandle<'s>> for ProfileScopeHandleOwned {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ProfileReport<W: Write> {
     /// The writer that we wrap.
-    writer: Option<W>,
+    writer:   Option<W>,
     /// The toplevel scope that we wrap. NOTE: Is [`Option`] to extract it at [`ProfileReport::into_scope()`].
-    scope:  Option<ProfileScope>,
+    scope:    Option<ProfileScope>,
+    /// If given, the path (without extension) to which we also export the report as traces when it goes out-of-scope.
+    export:   Option<PathBuf>,
+    /// Some identifier of the trace that this report is a part of (see [`ProfileReport::set_trace_id()`]).
+    #[serde(default)]
+    trace_id: Mutex<Option<String>>,
 }
 
 impl ProfileReport<File> {
     /// Constructor for the ProfileReport that will write it to a file in a default location (`/logs/profile`) with a default name (date & time of the profile state) when it goes out-of-scope.
     ///
+    /// Next to the textual report (`.txt`), it is also exported as a Chrome trace (`.trace.json`) and as OTLP/JSON spans (`.otlp.json`) (see
+    /// [`ProfileScope::to_chrome_trace()`] and [`ProfileScope::to_otlp()`]). These use the first word of `name` as the name of the service.
+    ///
     /// # Arguments
     /// - `name`: The name for the toplevel scope in this report.
     /// - `filename`: A more snake-case-like filename for the file.
@@ -361,8 +486,9 @@ impl ProfileReport<File> {
     pub fn auto_reporting_file(name: impl Into<String>, file_name: impl Into<String>) -> Self {
         // Define the target path
         let now: DateTime<Local> = Local::now();
-        let path: PathBuf =
-            PathBuf::from("/logs").join("profile").join(format!("profile_{}_{}.txt", file_name.into(), now.format("%Y-%m-%d_%H-%M-%s")));
+        let export: PathBuf =
+            PathBuf::from("/logs").join("profile").join(format!("profile_{}_{}", file_name.into(), now.format("%Y-%m-%d_%H-%M-%s")));
+        let path: PathBuf = export.with_extension("txt");
 
         // Attempt to open the file
         let handle: Option<File> = match File::create(&path) {
@@ -374,7 +500,7 @@ impl ProfileReport<File> {
         };
 
         // Run the thing
-        Self { writer: handle, scope: Some(ProfileScope::new(name)) }
+        Self { writer: handle, scope: Some(ProfileScope::new(name)), export: Some(export), trace_id: Mutex::new(None) }
     }
 }
 impl<W: Write> ProfileReport<W> {
@@ -388,9 +514,19 @@ impl<W: Write> ProfileReport<W> {
     /// A new ProfileReport instance.
     #[inline]
     pub fn auto_reporting(name: impl Into<String>, writer: impl Into<W>) -> Self {
-        Self { writer: Some(writer.into()), scope: Some(ProfileScope::new(name)) }
+        Self { writer: Some(writer.into()), scope: Some(ProfileScope::new(name)), export: None, trace_id: Mutex::new(None) }
     }
 
+    /// Sets the identifier of the trace that this report is a part of when it's exported as OTLP spans.
+    ///
+    /// Reports of different services that are given the same identifier (e.g., the ID of the workflow they process) are combined in the same
+    /// trace, such that they can be inspected together.
+    ///
+    /// # Arguments
+    /// - `trace_id`: The identifier of the trace. Can be any string.
+    #[inline]
+    pub fn set_trace_id(&self, trace_id: impl Into<String>) { *self.trace_id.lock() = Some(trace_id.into()); }
+
     /// Returns the inner scope that can be used without worrying about auto-reporting.
     ///
     /// # Returns
@@ -418,6 +554,19 @@ impl<W: Write> Drop for ProfileReport<W> {
                 warn!("Failed to auto-report ProfileReport '{}': {}", scope.name, err);
             };
         }
+
+        // Also export it as traces, if told to do so
+        if let Some(export) = &self.export {
+            let scope: &ProfileScope = self.scope.as_ref().unwrap();
+            let service: &str = scope.name.split_whitespace().next().unwrap_or("brane");
+            let trace_id: Option<String> = self.trace_id.lock().clone();
+            for (ext, trace) in [("trace.json", scope.to_chrome_trace(service)), ("otlp.json", scope.to_otlp(service, trace_id.as_deref()))] {
+                let path: PathBuf = export.with_extension(ext);
+                if let Err(err) = File::create(&path).map_err(serde_json::Error::io).and_then(|handle| serde_json::to_writer(handle, &trace)) {
+                    warn!("Failed to export ProfileReport '{}' to '{}': {}", scope.name, path.display(), err);
+                }
+            }
+        }
     }
 }
 
@@ -465,7 +614,7 @@ impl ProfileScope {
 
         // Create a TimerGuard around that timing.
         let timing: Arc<Mutex<Timing>> = lock.last().unwrap().timing().clone();
-        TimerGuard { start: Instant::now(), timing, _lifetime: Default::default() }
+        TimerGuard { start: Instant::now(), start_unix: unix_nanos(), timing, _lifetime: Default::default() }
     }
 
     /// Profiles the given function and adds its timing under the given name.
@@ -478,9 +627,9 @@ impl ProfileScope {
     /// The result of the function, if any.
     pub fn time_func<R>(&self, name: impl Into<String>, func: impl FnOnce() -> R) -> R {
         // Time the function
-        let start: Instant = Instant::now();
+        let (start, start_unix): (Instant, Option<u64>) = (Instant::now(), unix_nanos());
         let res: R = func();
-        let end: Timing = start.elapsed().into();
+        let end: Timing = Timing::since(start, start_unix);
 
         // Add the timing internally
         let mut lock: MutexGuard<Vec<ProfileTiming>> = self.timings.lock();
@@ -511,9 +660,9 @@ impl ProfileScope {
         // Now profile the future
         async move {
             // Time the future
-            let start: Instant = Instant::now();
+            let (start, start_unix): (Instant, Option<u64>) = (Instant::now(), unix_nanos());
             let res: R = fut.await;
-            let end: Timing = start.elapsed().into();
+            let end: Timing = Timing::since(start, start_unix);
 
             // Add the timing internally
             let mut lock: MutexGuard<Timing> = timing.lock();
@@ -565,9 +714,9 @@ impl ProfileScope {
         };
 
         // Time the function
-        let start: Instant = Instant::now();
+        let (start, start_unix): (Instant, Option<u64>) = (Instant::now(), unix_nanos());
         let res: R = func(scope);
-        let end: Timing = start.elapsed().into();
+        let end: Timing = Timing::since(start, start_unix);
 
         // Set that time
         let mut lock: MutexGuard<Timing> = timing.lock();
@@ -607,9 +756,9 @@ impl ProfileScope {
         // Now profile the future
         async move {
             // Time the future
-            let start: Instant = Instant::now();
+            let (start, start_unix): (Instant, Option<u64>) = (Instant::now(), unix_nanos());
             let res: F::Output = fut(scope).await;
-            let end: Timing = start.elapsed().into();
+            let end: Timing = Timing::since(start, start_unix);
 
             // Add the timing internally
             let mut lock: MutexGuard<Timing> = timing.lock();
@@ -620,6 +769,85 @@ impl ProfileScope {
         }
     }
 
+    /// Exports the timings in this scope in the [Chrome trace-event format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+    /// which can be inspected in, e.g., [Perfetto](https://ui.perfetto.dev).
+    ///
+    /// Timings of which the start is unknown are omitted. The traces of multiple services can be combined by concatenating their `traceEvents`.
+    ///
+    /// # Arguments
+    /// - `service`: The name of the service that took the timings (e.g., `brane-drv`), which is used as the name of the process.
+    ///
+    /// # Returns
+    /// The trace as a JSON object.
+    pub fn to_chrome_trace(&self, service: &str) -> Value {
+        let mut spans: Vec<Span> = vec![];
+        collect_spans(self, None, &mut spans);
+
+        // Derive the process ID from the service, such that it's unique when traces of different services are combined
+        let pid: u64 = fnv1a(service.as_bytes(), FNV_OFFSET) % (1 << 31);
+        let mut events: Vec<Value> = Vec::with_capacity(1 + spans.len());
+        events.push(json!({ "name": "process_name", "ph": "M", "pid": pid, "tid": 0, "args": { "name": service } }));
+        for span in spans {
+            events.push(json!({
+                "name": span.name,
+                "cat": "brane",
+                "ph": "X",
+                "ts": span.start as f64 / 1000.0,
+                "dur": (span.end - span.start) as f64 / 1000.0,
+                "pid": pid,
+                "tid": 0,
+            }));
+        }
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    /// Exports the timings in this scope as [OpenTelemetry](https://opentelemetry.io) spans, encoded as an OTLP/JSON `ExportTraceServiceRequest`.
+    ///
+    /// This can be sent as-is to the `/v1/traces` endpoint of any OTLP/HTTP collector (e.g., Jaeger) to inspect it. Timings of which the start
+    /// is unknown are omitted.
+    ///
+    /// # Arguments
+    /// - `service`: The name of the service that took the timings (e.g., `brane-drv`), which is used as the `service.name` of the spans.
+    /// - `trace_id`: Some identifier of the trace that the spans are part of (e.g., a workflow ID). Spans of different services with the same identifier end up in the same trace. If omitted, a random trace is used.
+    ///
+    /// # Returns
+    /// The request as a JSON object.
+    pub fn to_otlp(&self, service: &str, trace_id: Option<&str>) -> Value {
+        let mut spans: Vec<Span> = vec![];
+        collect_spans(self, None, &mut spans);
+
+        // Generate the IDs of the trace and the spans
+        let trace_id: String = match trace_id {
+            Some(id) => format!("{:032x}", TraceContext::for_workflow(id).trace_id),
+            None => Uuid::new_v4().simple().to_string(),
+        };
+        let span_ids: Vec<String> = spans.iter().map(|_| Uuid::new_v4().simple().to_string()[..16].to_string()).collect();
+
+        // Write them
+        let spans: Vec<Value> = spans
+            .iter()
+            .zip(&span_ids)
+            .map(|(span, span_id)| {
+                let mut res: Value = json!({
+                    "traceId": trace_id,
+                    "spanId": span_id,
+                    "name": span.name,
+                    "kind": 1,
+                    "startTimeUnixNano": span.start.to_string(),
+                    "endTimeUnixNano": span.end.to_string(),
+                });
+                if let Some(parent) = span.parent {
+                    res["parentSpanId"] = json!(span_ids[parent]);
+                }
+                res
+            })
+            .collect();
+        json!({ "resourceSpans": [{
+            "resource": { "attributes": [{ "key": "service.name", "value": { "stringValue": service } }] },
+            "scopeSpans": [{ "scope": { "name": "brane" }, "spans": spans }],
+        }] })
+    }
+
     /// Returns a formatter that neatly displays the results of this scope.
     ///
     /// Note that this does _not_ end with a newline, so typically you want to call `writeln!()`/`println!()` on this.
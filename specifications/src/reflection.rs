@@ -0,0 +1,353 @@
+//  REFLECTION.rs
+//    by Lut99
+//
+//  Created:
+//    16 Oct 2026, 00:28:45
+//  Last edited:
+//    16 Oct 2026, 00:55:26
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Describes the gRPC protocols in [`crate::driving`] and
+//!   [`crate::working`] as protobuf file descriptors, such that services
+//!   can offer them through gRPC server reflection.
+//!
+//!   Since the messages are written by hand instead of generated from
+//!   `.proto` files, so are their descriptors. Keep them in sync when
+//!   changing the messages.
+//
+
+use std::convert::TryFrom;
+use std::fmt::Debug;
+
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    MethodDescriptorProto, OneofDescriptorProto, ServiceDescriptorProto,
+};
+
+use crate::working::{OutputChannel, TaskStatus};
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_descriptor_types_resolve() {
+        let set: FileDescriptorSet = descriptor_set();
+
+        // Collect all the types defined in the set
+        let mut defined: HashSet<String> = HashSet::new();
+        for file in &set.file {
+            let package: &str = file.package();
+            defined.extend(file.message_type.iter().map(|msg| format!(".{}.{}", package, msg.name())));
+            defined.extend(file.enum_type.iter().map(|enm| format!(".{}.{}", package, enm.name())));
+        }
+
+        // Check that everything referenced is among them
+        for file in &set.file {
+            for msg in &file.message_type {
+                for field in msg.field.iter().filter(|field| field.type_name.is_some()) {
+                    assert!(
+                        defined.contains(field.type_name()),
+                        "Field '{}.{}' refers to undefined type '{}'",
+                        msg.name(),
+                        field.name(),
+                        field.type_name()
+                    );
+                }
+            }
+            for service in &file.service {
+                for method in &service.method {
+                    assert!(
+                        defined.contains(method.input_type()),
+                        "Method '{}.{}' refers to undefined type '{}'",
+                        service.name(),
+                        method.name(),
+                        method.input_type()
+                    );
+                    assert!(
+                        defined.contains(method.output_type()),
+                        "Method '{}.{}' refers to undefined type '{}'",
+                        service.name(),
+                        method.name(),
+                        method.output_type()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_descriptor_services() {
+        let set: FileDescriptorSet = descriptor_set();
+        let services: Vec<String> =
+            set.file.iter().flat_map(|file| file.service.iter().map(move |service| format!("{}.{}", file.package(), service.name()))).collect();
+        assert_eq!(services, vec!["driver.DriverService".to_string(), "job.JobService".to_string()]);
+    }
+
+    #[test]
+    fn test_enum_values() {
+        let values: Vec<EnumValueDescriptorProto> = enum_values::<OutputChannel>();
+        assert_eq!(values.iter().map(|value| (value.name(), value.number())).collect::<Vec<_>>(), vec![("STDOUT", 0), ("STDERR", 1)]);
+        let values: Vec<EnumValueDescriptorProto> = enum_values::<TaskStatus>();
+        assert_eq!(values.last().map(|value| value.name()), Some("FAILED"));
+        assert_eq!(values.iter().find(|value| value.number() == 4).map(|value| value.name()), Some("AUTHORIZATION_FAILED"));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Describes a field of a message.
+///
+/// # Arguments
+/// - `name`: The name of the field.
+/// - `number`: The tag of the field.
+/// - `label`: Whether the field is required, optional or repeated.
+/// - `ty`: The type of the field.
+/// - `type_name`: The fully qualified name of the message or enum that is the type of the field, if any (e.g., `.job.ProgramCounter`).
+///
+/// # Returns
+/// A new [`FieldDescriptorProto`].
+fn field(name: &str, number: i32, label: Label, ty: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.into()),
+        number: Some(number),
+        label: Some(label as i32),
+        r#type: Some(ty as i32),
+        type_name: type_name.map(String::from),
+        ..Default::default()
+    }
+}
+
+/// Describes a message.
+///
+/// # Arguments
+/// - `name`: The name of the message.
+/// - `fields`: The fields of the message.
+///
+/// # Returns
+/// A new [`DescriptorProto`].
+fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+    DescriptorProto { name: Some(name.into()), field: fields, ..Default::default() }
+}
+
+/// Describes the values of an enum by trying all integers from zero until one is not a variant.
+///
+/// The names of the values are those of the variants in `SCREAMING_SNAKE_CASE`, as is custom in protobuf.
+///
+/// # Returns
+/// A list of [`EnumValueDescriptorProto`]s, one per variant.
+fn enum_values<E: Debug + TryFrom<i32>>() -> Vec<EnumValueDescriptorProto> {
+    (0..)
+        .map_while(|number: i32| E::try_from(number).ok().map(|value| (number, value)))
+        .map(|(number, value)| {
+            let mut name: String = String::new();
+            for (i, c) in format!("{value:?}").chars().enumerate() {
+                if i > 0 && c.is_uppercase() {
+                    name.push('_');
+                }
+                name.push(c.to_ascii_uppercase());
+            }
+            EnumValueDescriptorProto { name: Some(name), number: Some(number), options: None }
+        })
+        .collect()
+}
+
+/// Describes a method of a service.
+///
+/// # Arguments
+/// - `name`: The name of the method.
+/// - `input`: The fully qualified name of the request message.
+/// - `output`: The fully qualified name of the reply message.
+/// - `streaming`: Whether the service replies with a stream of messages.
+///
+/// # Returns
+/// A new [`MethodDescriptorProto`].
+fn method(name: &str, input: &str, output: &str, streaming: bool) -> MethodDescriptorProto {
+    MethodDescriptorProto {
+        name: Some(name.into()),
+        input_type: Some(input.into()),
+        output_type: Some(output.into()),
+        options: None,
+        client_streaming: Some(false),
+        server_streaming: Some(streaming),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Describes the protocol of the driver service (see [`crate::driving`]).
+///
+/// # Returns
+/// A [`FileDescriptorProto`] for the `driver` package.
+pub fn driver_descriptor() -> FileDescriptorProto {
+    use Label::{Optional, Repeated, Required};
+    use Type::{Bool, Message, String, Uint64};
+
+    FileDescriptorProto {
+        name: Some("driver.proto".into()),
+        package: Some("driver".into()),
+        syntax: Some("proto2".into()),
+        message_type: vec![
+            message("CreateSessionRequest", vec![field("user", 1, Optional, String, None)]),
+            message("CreateSessionReply", vec![field("uuid", 1, Required, String, None)]),
+            message("ListSessionsRequest", vec![field("user", 1, Optional, String, None)]),
+            message("SessionInfo", vec![
+                field("uuid", 1, Required, String, None),
+                field("user", 2, Optional, String, None),
+                field("created", 3, Required, Uint64, None),
+                field("last_activity", 4, Required, Uint64, None),
+            ]),
+            message("ListSessionsReply", vec![field("sessions", 1, Repeated, Message, Some(".driver.SessionInfo"))]),
+            message("ListResultsRequest", vec![field("execution_id", 1, Required, String, None)]),
+            message("ResultInfo", vec![
+                field("name", 1, Required, String, None),
+                field("task", 2, Optional, String, None),
+                field("location", 3, Required, String, None),
+                field("size", 4, Optional, Uint64, None),
+                field("produced", 5, Required, Uint64, None),
+                field("expires", 6, Required, Uint64, None),
+            ]),
+            message("ListResultsReply", vec![field("results", 1, Repeated, Message, Some(".driver.ResultInfo"))]),
+            message("PromoteResultRequest", vec![
+                field("execution_id", 1, Required, String, None),
+                field("name", 2, Required, String, None),
+                field("dataset", 3, Required, String, None),
+            ]),
+            message("PromoteResultReply", vec![field("location", 1, Required, String, None)]),
+            message("CheckRequest", vec![field("workflow", 1, Required, String, None)]),
+            message("CheckReply", vec![
+                field("verdict", 1, Required, Bool, None),
+                field("who", 2, Optional, String, None),
+                field("reasons", 3, Repeated, String, None),
+                field("profile", 4, Optional, String, None),
+            ]),
+            message("ExecuteRequest", vec![
+                field("uuid", 1, Required, String, None),
+                field("input", 2, Required, String, None),
+                field("resume", 3, Optional, String, None),
+                field("deadline", 4, Optional, Uint64, None),
+            ]),
+            message("ExecuteReply", vec![
+                field("close", 1, Required, Bool, None),
+                field("debug", 2, Optional, String, None),
+                field("stdout", 3, Optional, String, None),
+                field("stderr", 4, Optional, String, None),
+                field("value", 5, Optional, String, None),
+                field("profile", 6, Optional, String, None),
+                field("event", 7, Optional, String, None),
+            ]),
+        ],
+        service: vec![ServiceDescriptorProto {
+            name:    Some("DriverService".into()),
+            method:  vec![
+                method("CreateSession", ".driver.CreateSessionRequest", ".driver.CreateSessionReply", false),
+                method("ListSessions", ".driver.ListSessionsRequest", ".driver.ListSessionsReply", false),
+                method("ListResults", ".driver.ListResultsRequest", ".driver.ListResultsReply", false),
+                method("PromoteResult", ".driver.PromoteResultRequest", ".driver.PromoteResultReply", false),
+                method("Check", ".driver.CheckRequest", ".driver.CheckReply", false),
+                method("Execute", ".driver.ExecuteRequest", ".driver.ExecuteReply", true),
+            ],
+            options: None,
+        }],
+        ..Default::default()
+    }
+}
+
+/// Describes the protocol of the job service (see [`crate::working`]).
+///
+/// # Returns
+/// A [`FileDescriptorProto`] for the `job` package.
+pub fn job_descriptor() -> FileDescriptorProto {
+    use Label::{Optional, Repeated, Required};
+    use Type::{Bool, Enum, Message, String, Uint64};
+
+    // The data name is a oneof in the TransferRegistryTar
+    let mut transfer_registry_tar: DescriptorProto = message("TransferRegistryTar", vec![
+        field("location", 1, Required, String, None),
+        FieldDescriptorProto { oneof_index: Some(0), ..field("data", 2, Optional, String, None) },
+        FieldDescriptorProto { oneof_index: Some(0), ..field("intermediate_result", 3, Optional, String, None) },
+    ]);
+    transfer_registry_tar.oneof_decl.push(OneofDescriptorProto { name: Some("dataname".into()), options: None });
+
+    FileDescriptorProto {
+        name: Some("job.proto".into()),
+        package: Some("job".into()),
+        syntax: Some("proto2".into()),
+        enum_type: vec![
+            EnumDescriptorProto { name: Some("TaskStatus".into()), value: enum_values::<TaskStatus>(), ..Default::default() },
+            EnumDescriptorProto { name: Some("OutputChannel".into()), value: enum_values::<OutputChannel>(), ..Default::default() },
+        ],
+        message_type: vec![
+            transfer_registry_tar,
+            message("CheckWorkflowRequest", vec![field("use_case", 1, Required, String, None), field("workflow", 2, Required, String, None)]),
+            message("CheckTaskRequest", vec![
+                field("use_case", 1, Required, String, None),
+                field("workflow", 2, Required, String, None),
+                field("task_id", 3, Required, String, None),
+            ]),
+            message("CheckReply", vec![field("verdict", 1, Required, Bool, None), field("reasons", 2, Repeated, String, None)]),
+            message("PreprocessRequest", vec![
+                field("use_case", 1, Required, String, None),
+                field("kind", 2, Required, Message, Some(".job.TransferRegistryTar")),
+                field("workflow", 3, Required, String, None),
+                field("pc", 4, Optional, Message, Some(".job.ProgramCounter")),
+            ]),
+            message("ProgramCounter", vec![field("func_id", 1, Required, Uint64, None), field("edge_idx", 2, Required, Uint64, None)]),
+            message("PreprocessReply", vec![field("access", 1, Required, String, None)]),
+            message("ExecuteRequest", vec![
+                field("use_case", 1, Required, String, None),
+                field("workflow", 2, Required, String, None),
+                field("call_pc", 3, Required, Message, Some(".job.ProgramCounter")),
+                field("task_def", 4, Required, Uint64, None),
+                field("input", 5, Required, String, None),
+                field("result", 6, Optional, String, None),
+                field("args", 7, Required, String, None),
+                field("stream_output", 8, Optional, Bool, None),
+            ]),
+            message("ExecuteReply", vec![
+                field("status", 1, Required, Enum, Some(".job.TaskStatus")),
+                field("value", 2, Optional, String, None),
+                field("output", 3, Optional, Message, Some(".job.OutputChunk")),
+                field("usage", 4, Optional, String, None),
+            ]),
+            message("OutputChunk", vec![field("channel", 1, Required, Enum, Some(".job.OutputChannel")), field("data", 2, Required, String, None)]),
+            message("CommitRequest", vec![
+                field("result_name", 1, Optional, String, None),
+                field("data_name", 2, Optional, String, None),
+                field("provenance", 3, Optional, String, None),
+            ]),
+            message("CommitReply", vec![]),
+        ],
+        service: vec![ServiceDescriptorProto {
+            name:    Some("JobService".into()),
+            method:  vec![
+                method("CheckWorkflow", ".job.CheckWorkflowRequest", ".job.CheckReply", false),
+                method("CheckTask", ".job.CheckTaskRequest", ".job.CheckReply", false),
+                method("Preprocess", ".job.PreprocessRequest", ".job.PreprocessReply", false),
+                method("Execute", ".job.ExecuteRequest", ".job.ExecuteReply", true),
+                method("Commit", ".job.CommitRequest", ".job.CommitReply", false),
+            ],
+            options: None,
+        }],
+        ..Default::default()
+    }
+}
+
+/// Describes the protocols of both the driver and job services.
+///
+/// # Returns
+/// A [`FileDescriptorSet`] that can be registered with a gRPC reflection service.
+pub fn descriptor_set() -> FileDescriptorSet { FileDescriptorSet { file: vec![driver_descriptor(), job_descriptor()] } }
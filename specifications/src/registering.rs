@@ -4,7 +4,7 @@
 //  Created:
 //    15 Jan 2024, 14:32:30
 //  Last edited:
-//    07 Feb 2024, 13:49:33
+//    16 Oct 2026, 00:55:26
 //  Auto updated?
 //    Yes
 //
@@ -18,6 +18,17 @@ use serde_json::Value;
 
 
 /***** LIBRARY *****/
+/// Defines how the archives that are sent by the registry are compressed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveCompression {
+    /// The archive is a `.tar.gz`. This is the default, as all clients understand it.
+    #[default]
+    Gzip,
+    /// The archive is a `.tar.zst`, which is considerably faster to (de)compress.
+    Zstd,
+}
+
 /// Defines the input in the body of a request to download an asset.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DownloadAssetRequest {
@@ -29,7 +40,10 @@ pub struct DownloadAssetRequest {
     pub workflow: Value,
     /// The task within the workflow that acts as the context in which the download occurs. If omitted, then it should be interpreted as the data being accessed to download the final result of the workflow.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub task:     Option<(Option<u64>, u64)>,
+    pub task: Option<(Option<u64>, u64)>,
+    /// How the client would like the archive to be compressed. Registries that don't know of this field always send gzip.
+    #[serde(default)]
+    pub compression: ArchiveCompression,
 }
 
 
@@ -57,3 +71,49 @@ pub struct CheckTransferReply {
     #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
     pub reasons: Vec<String>,
 }
+
+
+
+/// Defines the output for a request to check an intermediate result (see `/results/info/<NAME>`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResultInfoReply {
+    /// The size of the intermediate result on disk, in bytes.
+    pub size: u64,
+}
+
+
+
+/// Defines the input for a request to enroll at a domain, i.e., to have its registry issue a client certificate.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EnrollRequest {
+    /// The secret part of the one-time enrollment token that the domain's administrator minted.
+    pub secret: String,
+    /// The certificate signing request for the client's key, as PEM. Only its public key is used; the registry decides on the subject.
+    pub csr:    String,
+}
+
+/// Defines the output for a request to enroll at a domain.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EnrollReply {
+    /// The issued client certificate, as PEM.
+    pub certificate: String,
+    /// The certificate(s) of the domain's CA, as PEM.
+    pub ca:          String,
+}
+
+
+
+/// Defines a package in the federated view of a central registry (see `/federation/packages`), i.e., a package of one of its peer instances.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FederatedPackage {
+    /// The name of the peer instance that has the package.
+    pub instance:    String,
+    /// The name of the package.
+    pub name:        String,
+    /// The version of the package.
+    pub version:     String,
+    /// The kind of the package.
+    pub kind:        String,
+    /// The description of the package, if any.
+    pub description: Option<String>,
+}
@@ -0,0 +1,338 @@
+//  REPORTING.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 19:12:40
+//  Last edited:
+//    16 Oct 2026, 00:33:35
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Defines the reports that `branelet` writes to its stderr to let the
+//!   worker know that the package it runs is still alive (and, if the
+//!   package says so, ready), and how many resources it used.
+//
+
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_failure_tail() {
+        let stdout: String = (0..FAILURE_LOG_LINES + 5).map(|i| format!("line {i}\n")).collect();
+        let failure: TaskFailure = TaskFailure::new(1, None, &stdout, "oops\n", None);
+        assert_eq!(failure.stdout.len(), FAILURE_LOG_LINES);
+        assert_eq!(failure.stdout[0], "line 5");
+        assert_eq!(failure.stderr, vec!["oops".to_string()]);
+        assert!(failure.truncated);
+
+        let failure: TaskFailure = TaskFailure::new(1, None, "", "oops", None);
+        assert!(failure.stdout.is_empty());
+        assert!(!failure.truncated);
+    }
+
+    #[test]
+    fn test_task_failure_from_output() {
+        let usage: ResourceUsage = ResourceUsage { cpu_ms: Some(1500), ..Default::default() };
+        let stderr: String = format!(
+            "starting\n{}{}{}",
+            BraneletReport::Alive { cpu_ms: None }.to_line(),
+            BraneletReport::Usage(usage).to_line(),
+            BraneletReport::Stopped { signal: 9 }.to_line()
+        );
+        let failure: TaskFailure = TaskFailure::from_output(255, "", &stderr);
+        assert_eq!(failure.code, 255);
+        assert_eq!(failure.signal, Some(9));
+        assert_eq!(failure.usage, Some(usage));
+        assert_eq!(failure.stderr, vec!["starting".to_string()]);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The prefix that marks a line on `branelet`'s stderr as a [`BraneletReport`].
+pub const REPORT_PREFIX: &str = "~~>branelet ";
+
+/// The interval in which `branelet` sends a [`BraneletReport::Alive`] while the package runs.
+pub const LIVENESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The time after which the worker considers a task hung if its `branelet` stops sending [`BraneletReport::Alive`]s.
+///
+/// Only enforced once a `branelet` has sent at least one report, such that older `branelet`s aren't timed out.
+pub const LIVENESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The exit code with which `branelet` exits if the package exceeded its timeout (the same as coreutils' `timeout`).
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// The number of lines at the end of a failed task's stdout and stderr that are kept in its [`TaskFailure`].
+pub const FAILURE_LOG_LINES: usize = 20;
+
+
+
+
+
+/***** LIBRARY *****/
+/// Summarizes the resources that a package used while it ran.
+///
+/// Any of the fields may be missing if `branelet` could not measure them (e.g., because it doesn't run on Linux).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    /// The CPU time (user + system, in milliseconds) the package used.
+    pub cpu_ms: Option<u64>,
+    /// The peak resident set size (in KiB) of the package.
+    pub peak_rss_kib: Option<u64>,
+    /// The number of bytes the package read from disk.
+    pub disk_read_bytes: Option<u64>,
+    /// The number of bytes the package wrote to disk.
+    pub disk_write_bytes: Option<u64>,
+    /// How many times the package was run, if it was retried (see the `retry` policy in its `container.yml`). Filled in by the worker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+}
+impl Display for ResourceUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        fn opt(f: &mut Formatter<'_>, value: Option<u64>, scale: f64, unit: &str) -> FResult {
+            match value {
+                Some(value) => write!(f, "{:.2}{unit}", value as f64 / scale),
+                None => write!(f, "?"),
+            }
+        }
+
+        write!(f, "CPU ")?;
+        opt(f, self.cpu_ms, 1000.0, "s")?;
+        write!(f, ", peak RSS ")?;
+        opt(f, self.peak_rss_kib, 1024.0, "MiB")?;
+        write!(f, ", disk read ")?;
+        opt(f, self.disk_read_bytes, 1024.0 * 1024.0, "MiB")?;
+        write!(f, ", disk written ")?;
+        opt(f, self.disk_write_bytes, 1024.0 * 1024.0, "MiB")?;
+        if let Some(attempts) = self.attempts {
+            write!(f, " (over {attempts} attempts)")?;
+        }
+        Ok(())
+    }
+}
+
+
+
+/// Defines the reports that `branelet` sends to the worker.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BraneletReport {
+    /// The package is still running.
+    Alive {
+        /// The CPU time (in milliseconds) the package has used so far, if `branelet` could find it. If this stops increasing, the package is idle.
+        cpu_ms: Option<u64>,
+    },
+    /// The package wrote the readiness marker declared in its `container.yml` to its stdout.
+    Ready {
+        /// The marker that the package wrote.
+        marker: String,
+    },
+    /// The package has completed, and used the given resources to do so.
+    Usage(ResourceUsage),
+    /// The package was stopped by a signal (e.g., because it ran out of memory).
+    Stopped {
+        /// The signal that stopped the package.
+        signal: i32,
+    },
+    /// The package exceeded its timeout, and is being terminated.
+    TimedOut {
+        /// The timeout (in seconds) that the package exceeded.
+        timeout_s: u64,
+    },
+    /// The package failed, and will be retried according to its retry policy.
+    Retrying {
+        /// The number of the attempt that failed (starting at 1).
+        attempt: u32,
+        /// The maximum number of attempts.
+        max: u32,
+        /// Why the attempt failed.
+        reason: String,
+        /// How long (in milliseconds) `branelet` waits before the next attempt.
+        backoff_ms: u64,
+    },
+
+    /// The package reported how far along it is (only for packages using the JSON-RPC protocol).
+    Progress {
+        /// How far along the package is, as a fraction between 0 and 1.
+        fraction: Option<f64>,
+        /// What the package is currently doing.
+        message:  Option<String>,
+    },
+    /// The package wrote a structured log message (only for packages using the JSON-RPC protocol).
+    Log {
+        /// The level of the message (e.g., `info`).
+        level:   String,
+        /// The message itself.
+        message: String,
+    },
+    /// The package reported a partial result (only for packages using the JSON-RPC protocol).
+    Partial {
+        /// The partial result, as a JSON-encoded `FullValue`.
+        value: String,
+    },
+}
+impl BraneletReport {
+    /// Parses a report from a line of `branelet`'s stderr.
+    ///
+    /// # Arguments
+    /// - `line`: The line to parse, with or without its trailing newline.
+    ///
+    /// # Returns
+    /// The parsed [`BraneletReport`], or [`None`] if the line isn't one.
+    pub fn from_line(line: &str) -> Option<Self> { serde_json::from_str(line.strip_prefix(REPORT_PREFIX)?.trim_end()).ok() }
+
+    /// Returns a human-readable version of the report if it carries information for the user.
+    ///
+    /// # Returns
+    /// A single line (without trailing newline) describing the report, or [`None`] if it's only relevant for the worker.
+    pub fn user_message(&self) -> Option<String> {
+        match self {
+            Self::Alive { .. } | Self::Ready { .. } | Self::Usage(_) => None,
+            Self::Stopped { signal } => Some(format!("[stopped] Task was stopped by signal {signal}")),
+            Self::TimedOut { timeout_s } => Some(format!("[timeout] Task exceeded its timeout of {timeout_s}s; terminating it")),
+            Self::Retrying { attempt, max, reason, backoff_ms } => {
+                Some(format!("[retry] Attempt {attempt}/{max} failed ({reason}); retrying in {:.1}s", *backoff_ms as f64 / 1000.0))
+            },
+            Self::Progress { fraction, message } => Some(match (fraction, message) {
+                (Some(fraction), Some(message)) => format!("[progress {:.0}%] {}", fraction * 100.0, message),
+                (Some(fraction), None) => format!("[progress {:.0}%]", fraction * 100.0),
+                (None, Some(message)) => format!("[progress] {message}"),
+                (None, None) => "[progress]".into(),
+            }),
+            Self::Log { level, message } => Some(format!("[{level}] {message}")),
+            Self::Partial { value } => Some(format!("[partial result] {value}")),
+        }
+    }
+
+    /// Serializes the report as a line that can be written to `branelet`'s stderr.
+    ///
+    /// # Returns
+    /// The report as a single line, including its trailing newline.
+    pub fn to_line(&self) -> String { format!("{}{}\n", REPORT_PREFIX, serde_json::to_string(self).unwrap()) }
+}
+
+
+
+/// Describes why a task failed, such that users see what went wrong without having to dig through the logs of the worker.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TaskFailure {
+    /// The exit code of the task's container.
+    pub code:      i32,
+    /// The signal that stopped the package, if it was stopped by one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal:    Option<i32>,
+    /// The last (at most [`FAILURE_LOG_LINES`]) lines that the task wrote to its stdout.
+    pub stdout:    Vec<String>,
+    /// The last (at most [`FAILURE_LOG_LINES`]) lines that the task wrote to its stderr, without [`BraneletReport`]s.
+    pub stderr:    Vec<String>,
+    /// Whether earlier lines were left out of `stdout` or `stderr`.
+    #[serde(default)]
+    pub truncated: bool,
+    /// The resources the task used, if `branelet` measured them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage:     Option<ResourceUsage>,
+}
+impl TaskFailure {
+    /// Constructor for the TaskFailure that only keeps the last [`FAILURE_LOG_LINES`] lines of the task's output.
+    ///
+    /// # Arguments
+    /// - `code`: The exit code of the task's container.
+    /// - `signal`: The signal that stopped the package, if any.
+    /// - `stdout`: Everything the task wrote to its stdout.
+    /// - `stderr`: Everything the task wrote to its stderr, without [`BraneletReport`]s.
+    /// - `usage`: The resources the task used, if known.
+    ///
+    /// # Returns
+    /// A new TaskFailure instance.
+    pub fn new(code: i32, signal: Option<i32>, stdout: &str, stderr: &str, usage: Option<ResourceUsage>) -> Self {
+        fn tail(text: &str, truncated: &mut bool) -> Vec<String> {
+            let lines: Vec<&str> = text.lines().collect();
+            let skip: usize = lines.len().saturating_sub(FAILURE_LOG_LINES);
+            *truncated |= skip > 0;
+            lines[skip..].iter().map(|line| line.to_string()).collect()
+        }
+
+        let mut truncated: bool = false;
+        let stdout: Vec<String> = tail(stdout, &mut truncated);
+        let stderr: Vec<String> = tail(stderr, &mut truncated);
+        Self { code, signal, stdout, stderr, truncated, usage }
+    }
+
+    /// Constructor for the TaskFailure that collects the signal and resource usage from the [`BraneletReport`]s in the task's stderr.
+    ///
+    /// Use this if the reports haven't been filtered out of the stderr yet (e.g., when running a task locally).
+    ///
+    /// # Arguments
+    /// - `code`: The exit code of the task's container.
+    /// - `stdout`: Everything the task wrote to its stdout.
+    /// - `stderr`: Everything the task wrote to its stderr, including `branelet`'s reports.
+    ///
+    /// # Returns
+    /// A new TaskFailure instance.
+    pub fn from_output(code: i32, stdout: &str, stderr: &str) -> Self {
+        let mut signal: Option<i32> = None;
+        let mut usage: Option<ResourceUsage> = None;
+        let mut filtered: String = String::with_capacity(stderr.len());
+        for line in stderr.split_inclusive('\n') {
+            match BraneletReport::from_line(line) {
+                Some(BraneletReport::Stopped { signal: sig }) => signal = Some(sig),
+                Some(BraneletReport::Usage(used)) => usage = Some(used),
+                Some(report) => {
+                    if let Some(msg) = report.user_message() {
+                        filtered.push_str(&msg);
+                        filtered.push('\n');
+                    }
+                },
+                None => filtered.push_str(line),
+            }
+        }
+        Self::new(code, signal, stdout, &filtered, usage)
+    }
+
+    /// Summarizes how the task exited, without its output.
+    ///
+    /// # Returns
+    /// A single line like `exit code 137 (stopped by signal 9)`.
+    pub fn summary(&self) -> String {
+        match self.signal {
+            Some(signal) => format!("exit code {} (stopped by signal {})", self.code, signal),
+            None => format!("exit code {}", self.code),
+        }
+    }
+}
+impl Display for TaskFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        fn block(f: &mut Formatter<'_>, name: &str, lines: &[String], truncated: bool) -> FResult {
+            if lines.is_empty() {
+                return writeln!(f, "\n{name}: <empty>");
+            }
+            writeln!(f, "\n{}{}:", name, if truncated { format!(" (last {} lines)", lines.len()) } else { String::new() })?;
+            let divider: String = (0..80).map(|_| '-').collect();
+            writeln!(f, "{divider}")?;
+            for line in lines {
+                writeln!(f, "{line}")?;
+            }
+            writeln!(f, "{divider}")
+        }
+
+        writeln!(f, "{}", self.summary())?;
+        if let Some(usage) = &self.usage {
+            writeln!(f, "Resources used: {usage}")?;
+        }
+        block(f, "stdout", &self.stdout, self.truncated && self.stdout.len() == FAILURE_LOG_LINES)?;
+        block(f, "stderr", &self.stderr, self.truncated && self.stderr.len() == FAILURE_LOG_LINES)
+    }
+}
@@ -0,0 +1,400 @@
+//  TELEMETRY.rs
+//    by Lut99
+//
+//  Created:
+//    15 Oct 2026, 21:24:42
+//  Last edited:
+//    15 Oct 2026, 21:26:20
+//  Auto updated?
+//    Yes
+//
+//  Description:
+//!   Implements a small, shared tracing layer that follows a workflow
+//!   across services.
+//!
+//!   Every service that handles (part of) a workflow records what it does
+//!   as [`Span`]s, which are part of a trace identified by a
+//!   [`TraceContext`]. This context is propagated between services as a
+//!   [W3C `traceparent`](https://www.w3.org/TR/trace-context/) header,
+//!   both in gRPC metadata and in HTTP headers, such that the spans of the
+//!   driver, planner, workers, registries and proxies can be combined in a
+//!   single trace. If configured (see [`init()`]), spans are exported in
+//!   batches to an [OpenTelemetry](https://opentelemetry.io) collector
+//!   using OTLP/HTTP.
+//
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+use serde_json::{json, Value};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use uuid::Uuid;
+
+use crate::profiling::{fnv1a, FNV_OFFSET};
+
+
+/***** CONSTANTS *****/
+/// The name of the header (or gRPC metadata key) that carries the [`TraceContext`].
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The maximum number of spans that are exported in one request.
+const MAX_BATCH_SIZE: usize = 512;
+/// The maximum time that spans wait before they are exported.
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+
+
+
+
+/***** GLOBALS *****/
+/// The exporter that spans are sent to when they end, if any.
+static EXPORTER: OnceLock<Exporter> = OnceLock::new();
+
+tokio::task_local! {
+    /// The trace context of the task that is currently running, if it runs as part of a trace (see [`in_trace()`]).
+    static CURRENT: TraceContext;
+}
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that occur when parsing a [`TraceContext`] from a `traceparent` header.
+#[derive(Debug)]
+pub enum TraceContextParseError {
+    /// The header did not consist of four dash-separated fields.
+    IllegalFormat { raw: String },
+    /// The header has a version we don't support.
+    UnsupportedVersion { raw: String, version: String },
+    /// One of the fields was not valid hexadecimal of the right length.
+    IllegalField { raw: String, what: &'static str },
+}
+impl Display for TraceContextParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use TraceContextParseError::*;
+        match self {
+            IllegalFormat { raw } => write!(f, "Trace context '{raw}' is not of the form '<version>-<trace id>-<span id>-<flags>'"),
+            UnsupportedVersion { raw, version } => write!(f, "Trace context '{raw}' has unsupported version '{version}' (expected '00')"),
+            IllegalField { raw, what } => write!(f, "Trace context '{raw}' has an illegal {what}"),
+        }
+    }
+}
+impl Error for TraceContextParseError {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the current time in nanoseconds since the Unix epoch.
+///
+/// # Returns
+/// The current time, or 0 if the system clock is before the Unix epoch.
+#[inline]
+fn unix_nanos() -> u64 { SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0) }
+
+/// Generates a new, random span identifier.
+///
+/// # Returns
+/// A non-zero span identifier.
+#[inline]
+fn random_span_id() -> u64 { (Uuid::new_v4().as_u128() as u64).max(1) }
+
+/// Exports batches of spans until all senders have been dropped.
+///
+/// # Arguments
+/// - `service`: The name of the service that records the spans, used as its `service.name`.
+/// - `endpoint`: The base address of the OTLP/HTTP collector (e.g., `http://jaeger:4318`).
+/// - `headers`: Any additional headers to send along (e.g., for authentication).
+/// - `rx`: The receiving end of the channel that spans are sent on.
+async fn export(service: String, endpoint: String, headers: HashMap<String, String>, mut rx: UnboundedReceiver<Value>) {
+    let url: String = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    let client: reqwest::Client = reqwest::Client::new();
+    loop {
+        // Wait for the first span, and then for a few more to make it worthwhile
+        let mut spans: Vec<Value> = match rx.recv().await {
+            Some(span) => vec![span],
+            None => return,
+        };
+        let deadline = tokio::time::Instant::now() + EXPORT_INTERVAL;
+        while spans.len() < MAX_BATCH_SIZE {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(span)) => spans.push(span),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        // Send them
+        debug!("Exporting {} span(s) to '{}'...", spans.len(), url);
+        let body: Value = json!({ "resourceSpans": [{
+            "resource": { "attributes": [{ "key": "service.name", "value": { "stringValue": service } }] },
+            "scopeSpans": [{ "scope": { "name": "brane" }, "spans": spans }],
+        }] });
+        let mut req: reqwest::RequestBuilder = client.post(&url).json(&body);
+        for (name, value) in &headers {
+            req = req.header(name, value);
+        }
+        match req.send().await {
+            Ok(res) if !res.status().is_success() => warn!("Collector '{}' rejected spans with status {}", url, res.status()),
+            Ok(_) => {},
+            Err(err) => warn!("Failed to export spans to collector '{url}': {err}"),
+        }
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Sends ended spans to the background task that exports them.
+#[derive(Debug)]
+struct Exporter {
+    /// The channel to send spans on.
+    tx: UnboundedSender<Value>,
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Initializes exporting spans to an OpenTelemetry collector over OTLP/HTTP.
+///
+/// Until this is called, spans are recorded but dropped when they end. Must be called from within a Tokio runtime, and only has effect the
+/// first time it is called.
+///
+/// # Arguments
+/// - `service`: The name of this service (e.g., `brane-drv`), used as `service.name` of its spans.
+/// - `endpoint`: The base address of the OTLP/HTTP collector (e.g., `http://jaeger:4318`). Spans are sent to its `/v1/traces` path.
+/// - `headers`: Any additional headers to send along with the spans (e.g., for authentication).
+pub fn init(service: impl Into<String>, endpoint: impl Into<String>, headers: HashMap<String, String>) {
+    let (service, endpoint): (String, String) = (service.into(), endpoint.into());
+    let (tx, rx): (UnboundedSender<Value>, UnboundedReceiver<Value>) = mpsc::unbounded_channel();
+    if EXPORTER.set(Exporter { tx }).is_ok() {
+        debug!("Exporting spans of '{service}' to collector '{endpoint}'");
+        tokio::spawn(export(service, endpoint, headers, rx));
+    }
+}
+
+
+
+/// Runs the given future as part of the given trace.
+///
+/// While it runs, [`current()`] returns the given context (e.g., such that its logs can be related to the trace).
+///
+/// # Arguments
+/// - `context`: The [`TraceContext`] of the trace to run in.
+/// - `fut`: The future to run.
+///
+/// # Returns
+/// The output of `fut`.
+#[inline]
+pub async fn in_trace<F: Future>(context: TraceContext, fut: F) -> F::Output { CURRENT.scope(context, fut).await }
+
+/// Returns the context of the trace that the calling task runs in, if any.
+///
+/// # Returns
+/// The [`TraceContext`] given to the [`in_trace()`] that we're running in, or [`None`] if we're not.
+#[inline]
+pub fn current() -> Option<TraceContext> { CURRENT.try_with(|context| *context).ok() }
+
+
+
+/// Identifies a trace and the span within it that is currently active, as propagated between services.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TraceContext {
+    /// The identifier of the trace.
+    pub trace_id: u128,
+    /// The identifier of the span that is the parent of any spans started with this context.
+    pub span_id:  u64,
+    /// Whether the trace is sampled (i.e., its spans are recorded).
+    pub sampled:  bool,
+}
+impl TraceContext {
+    /// Constructor for the TraceContext that starts a new, random trace.
+    ///
+    /// # Returns
+    /// A new TraceContext.
+    #[inline]
+    pub fn new() -> Self { Self { trace_id: Uuid::new_v4().as_u128(), span_id: random_span_id(), sampled: true } }
+
+    /// Constructor for the TraceContext that refers to the root span of the trace of a workflow.
+    ///
+    /// Both identifiers are derived from the workflow's ID, such that any service handling the workflow ends up in the same trace (even if the
+    /// context was not propagated to it). The trace is also the one that profiling reports of the workflow are exported to (see
+    /// [`ProfileReport::set_trace_id()`](crate::profiling::ProfileReport::set_trace_id())).
+    ///
+    /// # Arguments
+    /// - `id`: The ID of the workflow.
+    ///
+    /// # Returns
+    /// A new TraceContext.
+    pub fn for_workflow(id: &str) -> Self {
+        let trace_id: u128 = ((fnv1a(id.as_bytes(), FNV_OFFSET) as u128) << 64) | fnv1a(id.as_bytes(), !FNV_OFFSET) as u128;
+        Self { trace_id, span_id: fnv1a(id.as_bytes(), FNV_OFFSET.rotate_left(32)).max(1), sampled: true }
+    }
+
+    /// Injects this context in the metadata of an outgoing gRPC request.
+    ///
+    /// # Arguments
+    /// - `request`: The [`tonic::Request`] to inject the context in.
+    #[inline]
+    pub fn inject_grpc<T>(&self, request: &mut tonic::Request<T>) {
+        // NOTE: Always succeeds, as the traceparent is only hexadecimals and dashes
+        if let Ok(value) = self.to_string().parse() {
+            request.metadata_mut().insert(TRACEPARENT_HEADER, value);
+        }
+    }
+
+    /// Extracts a context from the metadata of an incoming gRPC request.
+    ///
+    /// # Arguments
+    /// - `request`: The [`tonic::Request`] to extract the context from.
+    ///
+    /// # Returns
+    /// The context, or [`None`] if the request did not carry a (valid) one.
+    #[inline]
+    pub fn extract_grpc<T>(request: &tonic::Request<T>) -> Option<Self> {
+        request.metadata().get(TRACEPARENT_HEADER).and_then(|value| value.to_str().ok()).and_then(|value| value.parse().ok())
+    }
+
+    /// Injects this context in the headers of an outgoing HTTP request.
+    ///
+    /// # Arguments
+    /// - `request`: The [`reqwest::RequestBuilder`] of the request.
+    ///
+    /// # Returns
+    /// The same `request`, but now with the context as header.
+    #[inline]
+    pub fn inject_http(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder { request.header(TRACEPARENT_HEADER, self.to_string()) }
+}
+impl Default for TraceContext {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+impl Display for TraceContext {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "00-{:032x}-{:016x}-{:02x}", self.trace_id, self.span_id, self.sampled as u8) }
+}
+impl FromStr for TraceContext {
+    type Err = TraceContextParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split('-').collect();
+        if fields.len() != 4 {
+            return Err(TraceContextParseError::IllegalFormat { raw: s.into() });
+        }
+        if fields[0] != "00" {
+            return Err(TraceContextParseError::UnsupportedVersion { raw: s.into(), version: fields[0].into() });
+        }
+
+        // Parse the fields, which may not be all zeroes
+        let trace_id: u128 = match u128::from_str_radix(fields[1], 16) {
+            Ok(id) if fields[1].len() == 32 && id != 0 => id,
+            _ => return Err(TraceContextParseError::IllegalField { raw: s.into(), what: "trace ID" }),
+        };
+        let span_id: u64 = match u64::from_str_radix(fields[2], 16) {
+            Ok(id) if fields[2].len() == 16 && id != 0 => id,
+            _ => return Err(TraceContextParseError::IllegalField { raw: s.into(), what: "span ID" }),
+        };
+        let flags: u8 = match u8::from_str_radix(fields[3], 16) {
+            Ok(flags) if fields[3].len() == 2 => flags,
+            _ => return Err(TraceContextParseError::IllegalField { raw: s.into(), what: "flags" }),
+        };
+        Ok(Self { trace_id, span_id, sampled: flags & 0x01 != 0 })
+    }
+}
+
+
+
+/// Records a single operation of a service as part of a trace.
+///
+/// The span ends when it is dropped, after which it is exported if [`init()`] has been called.
+#[derive(Debug)]
+pub struct Span {
+    /// The name of the operation.
+    name: String,
+    /// The context of this span, which can be propagated to make other spans its children.
+    context: TraceContext,
+    /// The span ID of this span's parent, if any.
+    parent: Option<u64>,
+    /// The start of the span, in nanoseconds since the Unix epoch.
+    start: u64,
+    /// Any attributes that describe the operation further.
+    attributes: Vec<(String, String)>,
+}
+impl Span {
+    /// Starts a new span that is the root of its trace.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the operation.
+    /// - `context`: The context of the span itself (e.g., [`TraceContext::for_workflow()`]).
+    ///
+    /// # Returns
+    /// A new Span.
+    #[inline]
+    pub fn root(name: impl Into<String>, context: TraceContext) -> Self {
+        Self { name: name.into(), context, parent: None, start: unix_nanos(), attributes: vec![] }
+    }
+
+    /// Starts a new span as a child of the given context.
+    ///
+    /// # Arguments
+    /// - `name`: The name of the operation.
+    /// - `parent`: The context of the parent span (e.g., as extracted from an incoming request).
+    ///
+    /// # Returns
+    /// A new Span.
+    #[inline]
+    pub fn child(name: impl Into<String>, parent: &TraceContext) -> Self {
+        Self {
+            name: name.into(),
+            context: TraceContext { span_id: random_span_id(), ..*parent },
+            parent: Some(parent.span_id),
+            start: unix_nanos(),
+            attributes: vec![],
+        }
+    }
+
+    /// Adds an attribute that describes the operation further.
+    ///
+    /// # Arguments
+    /// - `key`: The name of the attribute (e.g., `brane.workflow`).
+    /// - `value`: The value of the attribute.
+    #[inline]
+    pub fn set_attribute(&mut self, key: impl Into<String>, value: impl Display) { self.attributes.push((key.into(), value.to_string())); }
+
+    /// Returns the context of this span, to propagate to other services or to start child spans with.
+    #[inline]
+    pub fn context(&self) -> &TraceContext { &self.context }
+}
+impl Drop for Span {
+    fn drop(&mut self) {
+        let exporter: &Exporter = match EXPORTER.get() {
+            Some(exporter) if self.context.sampled => exporter,
+            _ => return,
+        };
+        let mut span: Value = json!({
+            "traceId": format!("{:032x}", self.context.trace_id),
+            "spanId": format!("{:016x}", self.context.span_id),
+            "name": self.name,
+            "kind": if self.parent.is_some() { 2 } else { 1 },
+            "startTimeUnixNano": self.start.to_string(),
+            "endTimeUnixNano": unix_nanos().to_string(),
+            "attributes": self.attributes.iter().map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } })).collect::<Vec<Value>>(),
+        });
+        if let Some(parent) = self.parent {
+            span["parentSpanId"] = json!(format!("{parent:016x}"));
+        }
+        // NOTE: Only fails if the exporter is gone, in which case there's nothing to do anyway
+        let _ = exporter.tx.send(span);
+    }
+}
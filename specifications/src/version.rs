@@ -4,13 +4,16 @@
 //  Created:
 //    23 Mar 2022, 15:15:12
 //  Last edited:
-//    10 Apr 2023, 11:28:06
+//    16 Oct 2026, 01:54:32
 //  Auto updated?
 //    Yes
 //
 //  Description:
 //!   Implements a new Version struct, which is like semver's Version but
 //!   with
+//!
+//!   Also implements a VersionRange, which describes a range of Versions
+//!   (e.g., `^1.2`) in which the highest one may be found.
 //
 
 use std::cmp::Ordering;
@@ -91,6 +94,48 @@ mod tests {
         let mut latest = Version::latest();
         let versions = vec![];
         assert_eq!(latest.resolve_latest(versions), Err(ResolveError::NoVersions));
+
+        // Normal releases take precedence over (newer) pre-releases
+        let mut latest = Version::latest();
+        let versions = vec![Version::new(1, 0, 0), Version::new(2, 0, 0).with_prerelease("rc.1").unwrap(), Version::new(1, 1, 0)];
+        assert!(latest.resolve_latest(versions).is_ok());
+        assert_eq!(latest, Version::new(1, 1, 0));
+        let mut latest = Version::latest();
+        let versions = vec![Version::new(2, 0, 0).with_prerelease("rc.1").unwrap(), Version::new(2, 0, 0).with_prerelease("rc.2").unwrap()];
+        assert!(latest.resolve_latest(versions).is_ok());
+        assert_eq!(latest, Version::new(2, 0, 0).with_prerelease("rc.2").unwrap());
+    }
+
+    #[test]
+    fn test_prerelease() {
+        // Test if it can parse and display labels
+        let version = Version::from_str("1.0.0-alpha.1+build.42").unwrap();
+        assert_eq!(version.pre.as_str(), "alpha.1");
+        assert_eq!(version.build.as_str(), "build.42");
+        assert_eq!(version.to_string(), "1.0.0-alpha.1+build.42");
+        assert_eq!(Version::from_str("v1.2-rc.1").unwrap().to_string(), "1.2.0-rc.1");
+        assert_eq!(Version::from_str("1.2.3+20261015").unwrap().to_string(), "1.2.3+20261015");
+        assert!(!Version::from_str("1.2.3+0042").unwrap().is_prerelease());
+
+        // Test the ordering as given by the SemVer spec, ignoring build metadata
+        let ordered = ["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0"];
+        for window in ordered.windows(2) {
+            assert!(Version::from_str(window[0]).unwrap() < Version::from_str(window[1]).unwrap(), "{} < {}", window[0], window[1]);
+        }
+        assert_eq!(Version::from_str("1.0.0-rc.1+a").unwrap(), Version::from_str("1.0.0-rc.1+b").unwrap());
+        assert!(Version::from_str("1.0.0-rc.1").unwrap() > Version::new(0, 9, 9));
+        assert!(Version::from_str("1.0.0-rc.1").unwrap().cmp_release(&Version::new(0, 9, 9)).is_lt());
+
+        // Test if it fails properly too
+        assert_eq!(Version::from_str("1.0.0-"), Err(ParseError::IllegalLabel { what: "pre-release", raw: String::new() }));
+        assert_eq!(Version::from_str("1.0.0-rc..1"), Err(ParseError::IllegalLabel { what: "pre-release", raw: String::from("rc..1") }));
+        assert_eq!(Version::from_str("1.0.0+b_1"), Err(ParseError::IllegalLabel { what: "build metadata", raw: String::from("b_1") }));
+        assert_eq!(
+            Version::from_str("1.0.0-rc.01"),
+            Err(ParseError::LabelLeadingZero { raw: String::from("rc.01"), identifier: String::from("01") })
+        );
+        let long: String = "a".repeat(MAX_LABEL_LEN + 1);
+        assert_eq!(Version::from_str(&format!("1.0.0-{long}")), Err(ParseError::LabelTooLong { what: "pre-release", raw: long }));
     }
 
 
@@ -99,18 +144,26 @@ mod tests {
     fn test_semver() {
         // Make sure the from (consuming) makes sense
         let semversion = semver::Version::new(42, 21, 10);
-        let version = Version::from(semversion.clone());
+        let version = Version::try_from(semversion.clone()).unwrap();
         assert_eq!(semversion.major, version.major);
         assert_eq!(semversion.minor, version.minor);
         assert_eq!(semversion.patch, version.patch);
 
         // Make sure the from (reference) makes sense
         let semversion = semver::Version::new(10, 21, 42);
-        let version = Version::from(&semversion);
+        let version = Version::try_from(&semversion).unwrap();
         assert_eq!(semversion.major, version.major);
         assert_eq!(semversion.minor, version.minor);
         assert_eq!(semversion.patch, version.patch);
 
+        // Make sure labels survive, and are never silently dropped
+        let semversion = semver::Version::parse("1.2.3-rc.1+git.0123456789abcdef0123456789abcdef01234567").unwrap();
+        let version = Version::try_from(&semversion).unwrap();
+        assert_eq!(version.to_string(), semversion.to_string());
+        let long: String = "a".repeat(MAX_LABEL_LEN + 1);
+        let semversion = semver::Version::parse(&format!("1.2.3+{long}")).unwrap();
+        assert_eq!(Version::try_from(&semversion), Err(ParseError::LabelTooLong { what: "build metadata", raw: long }));
+
         // Check the eq
         assert_eq!(Version::new(42, 21, 10), semver::Version::new(42, 21, 10));
         assert_ne!(Version::latest(), semver::Version::new(u64::MAX, u64::MAX, u64::MAX));
@@ -126,6 +179,90 @@ mod tests {
 
 
 
+    #[test]
+    fn test_range_parse() {
+        // Test the various kinds of comparators
+        let range = |min: Option<Version>, max: Option<Version>| VersionRange { min, max };
+        assert_eq!(VersionRange::from_str("latest"), Ok(VersionRange::any()));
+        assert_eq!(VersionRange::from_str("*"), Ok(VersionRange::any()));
+        assert_eq!(VersionRange::from_str("1.2.3"), Ok(VersionRange::exact(Version::new(1, 2, 3))));
+        assert_eq!(VersionRange::from_str("=1.2"), Ok(range(Some(Version::new(1, 2, 0)), Some(Version::new(1, 3, 0)))));
+        assert_eq!(VersionRange::from_str("1.x"), Ok(range(Some(Version::new(1, 0, 0)), Some(Version::new(2, 0, 0)))));
+        assert_eq!(VersionRange::from_str("^1.2"), Ok(range(Some(Version::new(1, 2, 0)), Some(Version::new(2, 0, 0)))));
+        assert_eq!(VersionRange::from_str("^0.2.3"), Ok(range(Some(Version::new(0, 2, 3)), Some(Version::new(0, 3, 0)))));
+        assert_eq!(VersionRange::from_str("^0.0.3"), Ok(VersionRange::exact(Version::new(0, 0, 3))));
+        assert_eq!(VersionRange::from_str("~1.2.3"), Ok(range(Some(Version::new(1, 2, 3)), Some(Version::new(1, 3, 0)))));
+        assert_eq!(VersionRange::from_str("~1"), Ok(range(Some(Version::new(1, 0, 0)), Some(Version::new(2, 0, 0)))));
+        assert_eq!(VersionRange::from_str(">1.2"), Ok(range(Some(Version::new(1, 3, 0)), None)));
+        assert_eq!(VersionRange::from_str("<=1.2.3"), Ok(range(None, Some(Version::new(1, 2, 4)))));
+        assert_eq!(VersionRange::from_str(">=1.2.0, <2"), Ok(range(Some(Version::new(1, 2, 0)), Some(Version::new(2, 0, 0)))));
+
+        // Test if it fails properly too
+        assert_eq!(VersionRange::from_str(""), Err(ParseError::EmptyRange { raw: String::new() }));
+        assert_eq!(VersionRange::from_str("^"), Err(ParseError::EmptyRange { raw: String::from("^") }));
+        assert_eq!(VersionRange::from_str("1.2.3.4"), Err(ParseError::TooManyNumbers { raw: String::from("1.2.3.4"), got: 4 }));
+        assert_eq!(VersionRange::from_str("1.x.3"), Err(ParseError::NumberAfterWildcard { raw: String::from("1.x.3") }));
+        assert_eq!(VersionRange::from_str("^1.a"), Err(ParseError::MinorParseError { raw: String::from("a"), err: u64::from_str("a").unwrap_err() }));
+        assert_eq!(VersionRange::from_str("^1.2-rc.1"), Err(ParseError::PartialPrerelease { raw: String::from("^1.2-rc.1") }));
+    }
+
+    #[test]
+    fn test_range_prerelease() {
+        let rc1 = Version::from_str("1.0.0-rc.1").unwrap();
+        let rc2 = Version::from_str("1.0.0-rc.2").unwrap();
+
+        // Pre-releases only match if asked for explicitly
+        assert!(!VersionRange::any().matches(&rc1));
+        assert!(!VersionRange::from_str("^0.9").unwrap().matches(&rc1));
+        assert!(!VersionRange::from_str("<1.0.0").unwrap().matches(&rc1));
+        assert!(VersionRange::from_str("1.0.0-rc.1").unwrap().matches(&rc1));
+        assert!(VersionRange::from_str("1.0.0-rc.1+build.42").unwrap().matches(&Version::from_str("1.0.0-rc.1+build.7").unwrap()));
+        assert!(!VersionRange::from_str("1.0.0-rc.1").unwrap().matches(&rc2));
+        assert!(!VersionRange::from_str("1.0.0-rc.1").unwrap().matches(&Version::new(1, 0, 0)));
+        assert!(VersionRange::from_str("^1.0.0-rc.1").unwrap().matches(&rc2));
+        assert!(VersionRange::from_str("^1.0.0-rc.1").unwrap().matches(&Version::new(1, 4, 0)));
+        assert!(!VersionRange::from_str("^1.0.0-rc.1").unwrap().matches(&Version::from_str("1.1.0-rc.1").unwrap()));
+        assert!(VersionRange::from_str(">1.0.0-rc.1").unwrap().matches(&rc2));
+        assert!(!VersionRange::from_str(">1.0.0-rc.1").unwrap().matches(&rc1));
+
+        // Exact pre-release ranges display as the version
+        assert_eq!(VersionRange::exact(rc1), VersionRange::from_str("=1.0.0-rc.1").unwrap());
+        assert_eq!(VersionRange::exact(rc1).as_exact(), Some(rc1));
+        assert_eq!(VersionRange::exact(rc1).to_string(), "1.0.0-rc.1");
+    }
+
+    #[test]
+    fn test_range_match() {
+        // Test matching single versions
+        let range = VersionRange::from_str("^1.2").unwrap();
+        assert!(range.matches(&Version::new(1, 2, 0)));
+        assert!(range.matches(&Version::new(1, 9, 9)));
+        assert!(!range.matches(&Version::new(1, 1, 9)));
+        assert!(!range.matches(&Version::new(2, 0, 0)));
+        assert!(!range.matches(&Version::latest()));
+        assert!(!VersionRange::any().matches(&Version::latest()));
+
+        // Test finding the highest match
+        let versions = vec![Version::new(1, 1, 0), Version::new(1, 4, 2), Version::new(1, 3, 7), Version::new(2, 0, 0)];
+        assert_eq!(range.highest_match(versions.clone()), Some(Version::new(1, 4, 2)));
+        assert_eq!(VersionRange::any().highest_match(versions.clone()), Some(Version::new(2, 0, 0)));
+        assert_eq!(VersionRange::from_str("^3").unwrap().highest_match(versions), None);
+    }
+
+    #[test]
+    fn test_range_display() {
+        // Test if ranges are displayed in a way that parses to the same range
+        for raw in ["*", "1.2.3", "^1.2", "~0.3.1", ">=1.0.0", "<2", ">=1.2.0, <1.5.0"] {
+            let range = VersionRange::from_str(raw).unwrap();
+            assert_eq!(VersionRange::from_str(&range.to_string()), Ok(range));
+        }
+        assert_eq!(VersionRange::from_str("=1.2.3").unwrap().to_string(), "1.2.3");
+        assert_eq!(VersionRange::from_str("^1.2").unwrap().to_string(), ">=1.2.0, <2.0.0");
+        assert_eq!(VersionRange::from_str("latest").unwrap().to_string(), "*");
+    }
+
+
+
     #[test]
     fn test_serde_serialize() {
         // Try to convert some versions to serde tokens
@@ -207,11 +344,26 @@ pub enum ParseError {
     MinorParseError { raw: String, err: std::num::ParseIntError },
     /// Could not parse the patch version number
     PatchParseError { raw: String, err: std::num::ParseIntError },
+    /// A pre-release or build metadata label was not a dot-separated list of non-empty identifiers with only ASCII letters, digits and hyphens.
+    IllegalLabel { what: &'static str, raw: String },
+    /// A numeric identifier in a pre-release label had a leading zero.
+    LabelLeadingZero { raw: String, identifier: String },
+    /// A pre-release or build metadata label was longer than [`MAX_LABEL_LEN`].
+    LabelTooLong { what: &'static str, raw: String },
 
     /// Got a NAME:VERSION pair with too many colons
     TooManyColons { raw: String, got: usize },
     /// Could not parse the Version in a given NAME:VERSION pair.
     IllegalVersion { raw: String, raw_version: String, err: Box<Self> },
+
+    /// A version range (or one of its comma-separated comparators) was empty.
+    EmptyRange { raw: String },
+    /// A comparator in a version range had more than three version numbers.
+    TooManyNumbers { raw: String, got: usize },
+    /// A comparator in a version range had a version number after a wildcard (e.g., `1.x.3`).
+    NumberAfterWildcard { raw: String },
+    /// A comparator in a version range had a pre-release label, but not all three version numbers (e.g., `1.2-rc.1`).
+    PartialPrerelease { raw: String },
 }
 
 impl Display for ParseError {
@@ -224,9 +376,20 @@ impl Display for ParseError {
             MajorParseError { raw, err } => write!(f, "Could not parse major version number '{raw}': {err}"),
             MinorParseError { raw, err } => write!(f, "Could not parse minor version number '{raw}': {err}"),
             PatchParseError { raw, err } => write!(f, "Could not parse patch version number '{raw}': {err}"),
+            IllegalLabel { what, raw } => write!(
+                f,
+                "Illegal {what} '{raw}': expected dot-separated, non-empty identifiers consisting of ASCII letters, digits and hyphens only"
+            ),
+            LabelLeadingZero { raw, identifier } => write!(f, "Illegal pre-release '{raw}': numeric identifier '{identifier}' has a leading zero"),
+            LabelTooLong { what, raw } => write!(f, "The {what} '{raw}' is too long (got {} bytes, expected at most {MAX_LABEL_LEN})", raw.len()),
 
             TooManyColons { raw, got } => write!(f, "Given 'NAME[:VERSION]' pair '{raw}' has too many colons (got {got}, expected at most 1)"),
             IllegalVersion { raw, raw_version, err } => write!(f, "Could not parse version '{raw_version}' in '{raw}': {err}"),
+
+            EmptyRange { raw } => write!(f, "Version range '{raw}' is (partially) empty"),
+            TooManyNumbers { raw, got } => write!(f, "Version '{raw}' in range has too many numbers (got {got}, expected at most 3)"),
+            NumberAfterWildcard { raw } => write!(f, "Version '{raw}' in range has a number after a wildcard"),
+            PartialPrerelease { raw } => write!(f, "Version '{raw}' in range has a pre-release label but not all three version numbers"),
         }
     }
 }
@@ -255,12 +418,167 @@ impl<'de> Visitor<'de> for VersionVisitor {
     }
 }
 
+/// Implements a Visitor for the VersionRange.
+struct VersionRangeVisitor;
+
+impl<'de> Visitor<'de> for VersionRangeVisitor {
+    type Value = VersionRange;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> FResult { formatter.write_str("a semantic version range") }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        // Parse the value with the VersionRange parser
+        VersionRange::from_str(value).map_err(|err| E::custom(format!("{err}")))
+    }
+}
+
+
+
+
+
+/***** LABEL *****/
+/// The maximum length (in bytes) of the pre-release label or build metadata of a [`Version`].
+///
+/// This fits, e.g., build metadata with a full Git commit hash (`git.<40 hexadecimal digits>`).
+pub const MAX_LABEL_LEN: usize = 64;
+
+
+
+/// Implements the pre-release label (e.g., `alpha.1`) or build metadata (e.g., `build.42`) of a [`Version`].
+///
+/// The label is stored inline, such that Versions remain `Copy`; this is why it is limited to [`MAX_LABEL_LEN`] bytes.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Label {
+    /// The number of bytes in `bytes` that are part of the label.
+    len:   u8,
+    /// The label itself. Has some spare room for [`Label::successor()`].
+    bytes: [u8; MAX_LABEL_LEN + 2],
+}
+
+impl Label {
+    /// Constructor for the Label that creates an empty one, i.e., the absence of a pre-release or build metadata.
+    #[inline]
+    pub const fn empty() -> Self { Self { len: 0, bytes: [0; MAX_LABEL_LEN + 2] } }
+
+    /// Parses a Label from a string.
+    ///
+    /// **Arguments**
+    ///  * `what`: What the label is (i.e., `"pre-release"` or `"build metadata"`), for use in errors.
+    ///  * `raw`: The label to parse, without the leading `-` or `+`.
+    ///  * `prerelease`: Whether this is a pre-release label, in which numeric identifiers may not have leading zeroes.
+    ///
+    /// **Returns**  
+    /// The parsed Label, or a ParseError if it is not a valid label according to SemVer.
+    pub fn parse(what: &'static str, raw: &str, prerelease: bool) -> Result<Self, ParseError> {
+        for identifier in raw.split('.') {
+            if identifier.is_empty() || !identifier.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+                return Err(ParseError::IllegalLabel { what, raw: raw.into() });
+            }
+            if prerelease && identifier.len() > 1 && identifier.starts_with('0') && identifier.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseError::LabelLeadingZero { raw: raw.into(), identifier: identifier.into() });
+            }
+        }
+        if raw.len() > MAX_LABEL_LEN {
+            return Err(ParseError::LabelTooLong { what, raw: raw.into() });
+        }
+
+        let mut bytes: [u8; MAX_LABEL_LEN + 2] = [0; MAX_LABEL_LEN + 2];
+        bytes[..raw.len()].copy_from_slice(raw.as_bytes());
+        Ok(Self { len: raw.len() as u8, bytes })
+    }
+
+    /// Returns the Label that directly follows this one in SemVer precedence, which is this one with an extra `.0` identifier.
+    ///
+    /// This is used to express "exactly this pre-release" as a half-open interval.
+    ///
+    /// **Returns**  
+    /// The successor of this label. If this label is empty or there is no room for the extra identifier, returns this label instead.
+    fn successor(&self) -> Self {
+        let len: usize = self.len as usize;
+        if len == 0 || len + 2 > self.bytes.len() {
+            return *self;
+        }
+        let mut res: Self = *self;
+        res.bytes[len..len + 2].copy_from_slice(b".0");
+        res.len += 2;
+        res
+    }
+
+    /// Returns the Label as a string.
+    #[inline]
+    pub fn as_str(&self) -> &str { std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default() }
+
+    /// Returns whether this Label is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool { self.len == 0 }
+}
+
+impl Debug for Label {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{:?}", self.as_str()) }
+}
+
+impl Display for Label {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}", self.as_str()) }
+}
+
+
+
+/// Compares two pre-release labels by their SemVer precedence.
+///
+/// The absence of a label takes precedence over any label (i.e., `1.0.0-rc.1 < 1.0.0`). Otherwise, labels are compared identifier by
+/// identifier, where numeric identifiers are compared numerically and have lower precedence than alphanumeric ones, which are compared
+/// lexically. If all identifiers are equal, the label with more identifiers takes precedence.
+///
+/// **Arguments**
+///  * `lhs`: The first label.
+///  * `rhs`: The second label.
+///
+/// **Returns**  
+/// How `lhs` compares to `rhs`.
+fn cmp_prerelease(lhs: &Label, rhs: &Label) -> Ordering {
+    match (lhs.is_empty(), rhs.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {},
+    }
+
+    let is_numeric = |identifier: &str| identifier.bytes().all(|b| b.is_ascii_digit());
+    let mut lhs = lhs.as_str().split('.');
+    let mut rhs = rhs.as_str().split('.');
+    loop {
+        let order: Ordering = match (lhs.next(), rhs.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            // Numeric identifiers have no leading zeroes, so the longer one is the larger one
+            (Some(l), Some(r)) => match (is_numeric(l), is_numeric(r)) {
+                (true, true) => l.len().cmp(&r.len()).then_with(|| l.cmp(r)),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => l.cmp(r),
+            },
+        };
+        if order.is_ne() {
+            return order;
+        }
+    }
+}
+
 
 
 
 
 /***** VERSION *****/
 /// Implements the Version, which is used to keep track of package versions.
+///
+/// Besides the three version numbers, a Version may have a pre-release label (e.g., `1.0.0-rc.1`) and build metadata (e.g., `1.0.0+build.42`)
+/// as defined by [SemVer](https://semver.org). Build metadata is ignored when comparing versions.
 #[derive(Clone, Copy, Debug, Eq)]
 pub struct Version {
     /// The major version number. If all three are set to u64::MAX, is interpreted as an unresolved 'latest' version number.
@@ -269,6 +587,10 @@ pub struct Version {
     pub minor: u64,
     /// The patch version number. If all three are set to u64::MAX, is interpreted as an unresolved 'latest' version number.
     pub patch: u64,
+    /// The pre-release label, which is empty for normal releases.
+    pub pre:   Label,
+    /// The build metadata, which is empty if there is none.
+    pub build: Label,
 }
 
 impl Version {
@@ -281,7 +603,7 @@ impl Version {
     ///  * `patch`: The patch version number.
     pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
         // Create the version
-        let result = Self { major, minor, patch };
+        let result = Self::from_numbers(major, minor, patch);
 
         // If it's latest, panic; otherwise, return
         if result.is_latest() {
@@ -294,7 +616,42 @@ impl Version {
 
     /// Constructor for the Version that sets it to an (unresolved) 'latest' version.
     #[inline]
-    pub const fn latest() -> Self { Self { major: u64::MAX, minor: u64::MAX, patch: u64::MAX } }
+    pub const fn latest() -> Self { Self::from_numbers(u64::MAX, u64::MAX, u64::MAX) }
+
+    /// Constructor for the Version that does not check whether it's 'latest'.
+    ///
+    /// **Arguments**
+    ///  * `major`: The major version number.
+    ///  * `minor`: The minor version number.
+    ///  * `patch`: The patch version number.
+    #[inline]
+    const fn from_numbers(major: u64, minor: u64, patch: u64) -> Self { Self { major, minor, patch, pre: Label::empty(), build: Label::empty() } }
+
+    /// Returns this Version with the given pre-release label.
+    ///
+    /// **Arguments**
+    ///  * `pre`: The pre-release label, without the leading `-` (e.g., `alpha.1`).
+    ///
+    /// **Returns**  
+    /// The updated Version, or a ParseError if the label is invalid.
+    #[inline]
+    pub fn with_prerelease(mut self, pre: &str) -> Result<Self, ParseError> {
+        self.pre = Label::parse("pre-release", pre, true)?;
+        Ok(self)
+    }
+
+    /// Returns this Version with the given build metadata.
+    ///
+    /// **Arguments**
+    ///  * `build`: The build metadata, without the leading `+` (e.g., `build.42`).
+    ///
+    /// **Returns**  
+    /// The updated Version, or a ParseError if the metadata is invalid.
+    #[inline]
+    pub fn with_build(mut self, build: &str) -> Result<Self, ParseError> {
+        self.build = Label::parse("build metadata", build, false)?;
+        Ok(self)
+    }
 
     /// Special factory method that creates a package name and a version from a `NAME[:VERSION]` pair.
     ///
@@ -360,8 +717,8 @@ impl Version {
 
             // Then, check if we saw a version before
             if let Some(lversion) = &last_version {
-                // Update if this version is newer
-                if &version > lversion {
+                // Update if this version is newer (where pre-releases only count if there are no normal releases)
+                if version.cmp_release(lversion).is_gt() {
                     last_version = Some(version);
                 }
             } else {
@@ -382,6 +739,32 @@ impl Version {
     /// Returns whether or not this Version represents a 'latest' version.
     #[inline]
     pub const fn is_latest(&self) -> bool { self.major == u64::MAX && self.minor == u64::MAX && self.patch == u64::MAX }
+
+    /// Returns whether or not this Version is a pre-release (e.g., `1.0.0-rc.1`).
+    #[inline]
+    pub const fn is_prerelease(&self) -> bool { !self.pre.is_empty() }
+
+    /// Returns whether this Version has the same version numbers as the given one, ignoring any labels.
+    #[inline]
+    pub const fn same_numbers(&self, other: &Self) -> bool { self.major == other.major && self.minor == other.minor && self.patch == other.patch }
+
+    /// Compares this Version to another one to decide which one is the 'latest'.
+    ///
+    /// This is like [`Ord::cmp()`], except that normal releases always take precedence over pre-releases. That way, publishing a release
+    /// candidate does not make it the default for everyone who asks for the latest version.
+    ///
+    /// **Arguments**
+    ///  * `other`: The Version to compare with.
+    ///
+    /// **Returns**  
+    /// How this Version compares to `other`.
+    pub fn cmp_release(&self, other: &Self) -> Ordering {
+        match (self.is_prerelease(), other.is_prerelease()) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            _ => self.cmp(other),
+        }
+    }
 }
 
 impl Default for Version {
@@ -392,7 +775,7 @@ impl Default for Version {
 
 impl PartialEq for Version {
     #[inline]
-    fn eq(&self, other: &Self) -> bool { self.major == other.major && self.minor == other.minor && self.patch == other.patch }
+    fn eq(&self, other: &Self) -> bool { self.same_numbers(other) && self.pre == other.pre }
 }
 
 impl Ord for Version {
@@ -410,7 +793,13 @@ impl Ord for Version {
         }
 
         // Compare the patch
-        self.patch.cmp(&other.patch)
+        let order = self.patch.cmp(&other.patch);
+        if order.is_ne() {
+            return order;
+        }
+
+        // Compare the pre-release (but never the build metadata)
+        cmp_prerelease(&self.pre, &other.pre)
     }
 }
 
@@ -428,6 +817,16 @@ impl FromStr for Version {
             return Ok(Self::latest());
         }
 
+        // Split off the build metadata and then the pre-release label, if any
+        let (s, build): (&str, Label) = match s.split_once('+') {
+            Some((s, build)) => (s, Label::parse("build metadata", build, false)?),
+            None => (s, Label::empty()),
+        };
+        let (s, pre): (&str, Label) = match s.split_once('-') {
+            Some((s, pre)) => (s, Label::parse("pre-release", pre, true)?),
+            None => (s, Label::empty()),
+        };
+
         // Otherwise, see if we can split the string into multiple slices
         // Compute the possible dot posses first
         let dot1 = s.find('.');
@@ -487,7 +886,7 @@ impl FromStr for Version {
         };
 
         // Put them together in a Version
-        let result = Self { major, minor, patch };
+        let result = Self { major, minor, patch, pre, build };
 
         // If this version is latest, then error
         if result.is_latest() {
@@ -499,7 +898,18 @@ impl FromStr for Version {
 
 impl Display for Version {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-        if self.is_latest() { write!(f, "latest") } else { write!(f, "{}.{}.{}", self.major, self.minor, self.patch) }
+        if self.is_latest() {
+            write!(f, "latest")
+        } else {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+            if !self.pre.is_empty() {
+                write!(f, "-{}", self.pre)?;
+            }
+            if !self.build.is_empty() {
+                write!(f, "+{}", self.build)?;
+            }
+            Ok(())
+        }
     }
 }
 
@@ -521,7 +931,11 @@ impl From<&mut Version> for Version {
 impl PartialEq<semver::Version> for Version {
     #[inline]
     fn eq(&self, other: &semver::Version) -> bool {
-        !self.is_latest() && self.major == other.major && self.minor == other.minor && self.patch == other.patch
+        !self.is_latest()
+            && self.major == other.major
+            && self.minor == other.minor
+            && self.patch == other.patch
+            && self.pre.as_str() == other.pre.as_str()
     }
 }
 
@@ -532,32 +946,33 @@ impl PartialOrd<semver::Version> for Version {
         if self.is_latest() {
             return None;
         }
-
-        // Compare the major number
-        let order = self.major.cmp(&other.major);
-        if order.is_ne() {
-            return Some(order);
-        }
-
-        // Compare the minor number
-        let order = self.minor.cmp(&other.minor);
-        if order.is_ne() {
-            return Some(order);
-        }
-
-        // Compare the patch
-        Some(self.patch.cmp(&other.patch))
+        Version::try_from(other).ok().map(|other| self.cmp(&other))
     }
 }
 
-impl From<semver::Version> for Version {
+impl TryFrom<semver::Version> for Version {
+    type Error = ParseError;
+
     #[inline]
-    fn from(version: semver::Version) -> Self { Self { major: version.major, minor: version.minor, patch: version.patch } }
+    fn try_from(version: semver::Version) -> Result<Self, Self::Error> { Self::try_from(&version) }
 }
 
-impl From<&semver::Version> for Version {
-    #[inline]
-    fn from(version: &semver::Version) -> Self { Self { major: version.major, minor: version.minor, patch: version.patch } }
+impl TryFrom<&semver::Version> for Version {
+    type Error = ParseError;
+
+    /// Converts a semver Version to ours.
+    ///
+    /// # Errors
+    /// This function errors if the version's pre-release label or build metadata is longer than [`MAX_LABEL_LEN`], as it cannot be represented.
+    fn try_from(version: &semver::Version) -> Result<Self, Self::Error> {
+        Ok(Self {
+            major: version.major,
+            minor: version.minor,
+            patch: version.patch,
+            pre:   if version.pre.is_empty() { Label::empty() } else { Label::parse("pre-release", version.pre.as_str(), true)? },
+            build: if version.build.is_empty() { Label::empty() } else { Label::parse("build metadata", version.build.as_str(), false)? },
+        })
+    }
 }
 
 
@@ -591,3 +1006,286 @@ impl<'de> Deserialize<'de> for Version {
         deserializer.deserialize_str(VersionVisitor)
     }
 }
+
+
+
+
+
+/***** VERSION RANGE *****/
+/// Implements a range of [`Version`]s, which is used to refer to a package without pinning it to a single version.
+///
+/// A range consists of one or more comma-separated comparators, all of which a version must satisfy. Supported are:
+/// - `latest` or `*`: Any version.
+/// - `1.2.3` or `=1.2.3`: Exactly the given version. Omitted or wildcard numbers (e.g., `1.2`, `1.2.x` or `1.*`) match anything.
+/// - `^1.2.3`: Any version compatible with the given one, i.e., that doesn't change the left-most non-zero number (`>=1.2.3, <2.0.0`).
+/// - `~1.2.3`: Any version with the same major and minor number (`>=1.2.3, <1.3.0`), or the same major number only if the minor is omitted.
+/// - `>1.2.3`, `>=1.2.3`, `<1.2.3` or `<=1.2.3`: Any version newer or older than the given one.
+///
+/// Internally, it is represented as a half-open interval of versions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VersionRange {
+    /// The oldest version in the range (inclusive), or [`None`] if there isn't any.
+    pub min: Option<Version>,
+    /// The first version after the range (exclusive), or [`None`] if there isn't any.
+    pub max: Option<Version>,
+}
+
+impl VersionRange {
+    /// Constructor for the VersionRange that matches any version.
+    #[inline]
+    pub const fn any() -> Self { Self { min: None, max: None } }
+
+    /// Constructor for the VersionRange that matches exactly the given version.
+    ///
+    /// **Arguments**
+    ///  * `version`: The Version to match. If it's a 'latest' version, matches any version instead. Its build metadata is ignored.
+    #[inline]
+    pub fn exact(version: Version) -> Self {
+        if version.is_latest() {
+            return Self::any();
+        }
+        if version.is_prerelease() {
+            let min: Version = Version { build: Label::empty(), ..version };
+            return Self { min: Some(min), max: Some(Version { pre: version.pre.successor(), ..min }) };
+        }
+        let (min, max): (Version, Version) = bounds(version.major, Some(version.minor), Some(version.patch));
+        Self { min: Some(min), max: Some(max) }
+    }
+
+    /// Returns whether the given version is in this range.
+    ///
+    /// Like Cargo, pre-releases are only matched if one of the bounds of the range is a pre-release of the same version numbers (e.g.,
+    /// `>=1.0.0-rc.1` matches `1.0.0-rc.2` but `>=0.9.0` does not).
+    ///
+    /// **Arguments**
+    ///  * `version`: The Version to check. Note that a 'latest' version is never in any range.
+    ///
+    /// **Returns**  
+    /// True if it is, or false otherwise.
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.is_latest() {
+            return false;
+        }
+        if version.is_prerelease()
+            && !self.min.map(|min| min.is_prerelease() && min.same_numbers(version)).unwrap_or(false)
+            && !self.max.map(|max| max.is_prerelease() && max.same_numbers(version)).unwrap_or(false)
+        {
+            return false;
+        }
+        self.min.map(|min| *version >= min).unwrap_or(true) && self.max.map(|max| *version < max).unwrap_or(true)
+    }
+
+    /// Finds the highest version in this range among the given ones.
+    ///
+    /// **Generic types**
+    ///  * `I`: The type of the iterator passed to this function.
+    ///
+    /// **Arguments**
+    ///  * `iter`: An iterator over (resolved) versions.
+    ///
+    /// **Returns**  
+    /// The highest version that is in this range, or [`None`] if none of them are.
+    #[inline]
+    pub fn highest_match<I: IntoIterator<Item = Version>>(&self, iter: I) -> Option<Version> { iter.into_iter().filter(|v| self.matches(v)).max() }
+
+    /// Returns whether this VersionRange matches any version.
+    #[inline]
+    pub const fn is_any(&self) -> bool { self.min.is_none() && self.max.is_none() }
+
+    /// Returns the only version in this VersionRange if it matches exactly one version.
+    #[inline]
+    pub fn as_exact(&self) -> Option<Version> {
+        let min: Version = self.min?;
+        if Self::exact(min) == *self {
+            Some(min)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for VersionRange {
+    /// Default constructor for the VersionRange, which initializes it to match any version.
+    #[inline]
+    fn default() -> Self { Self::any() }
+}
+
+impl FromStr for VersionRange {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Parse every comparator separately and intersect them
+        let mut result: Self = Self::any();
+        for comp in s.split(',') {
+            let (min, max): (Option<Version>, Option<Version>) = parse_comparator(comp.trim())?;
+            if let Some(min) = min {
+                result.min = Some(result.min.map(|rmin| rmin.max(min)).unwrap_or(min));
+            }
+            if let Some(max) = max {
+                result.max = Some(result.max.map(|rmax| rmax.min(max)).unwrap_or(max));
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        if let Some(version) = self.as_exact() {
+            return write!(f, "{version}");
+        }
+        match (self.min, self.max) {
+            (None, None) => write!(f, "*"),
+            (Some(min), None) => write!(f, ">={min}"),
+            (None, Some(max)) => write!(f, "<{max}"),
+            (Some(min), Some(max)) => write!(f, ">={min}, <{max}"),
+        }
+    }
+}
+
+impl From<Version> for VersionRange {
+    #[inline]
+    fn from(value: Version) -> Self { Self::exact(value) }
+}
+impl From<&Version> for VersionRange {
+    #[inline]
+    fn from(value: &Version) -> Self { Self::exact(*value) }
+}
+
+
+
+impl Serialize for VersionRange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(VersionRangeVisitor)
+    }
+}
+
+
+
+/// Computes the interval of versions matching the given, possibly partial, version.
+///
+/// **Arguments**
+///  * `major`: The major version number.
+///  * `minor`: The minor version number, or [`None`] if it may be anything.
+///  * `patch`: The patch version number, or [`None`] if it may be anything.
+///
+/// **Returns**  
+/// The oldest matching version (inclusive) and the first version after it that doesn't (exclusive).
+fn bounds(major: u64, minor: Option<u64>, patch: Option<u64>) -> (Version, Version) {
+    match (minor, patch) {
+        (Some(minor), Some(patch)) => (Version::from_numbers(major, minor, patch), Version::from_numbers(major, minor, patch.saturating_add(1))),
+        (Some(minor), None) => (Version::from_numbers(major, minor, 0), Version::from_numbers(major, minor.saturating_add(1), 0)),
+        (None, _) => (Version::from_numbers(major, 0, 0), Version::from_numbers(major.saturating_add(1), 0, 0)),
+    }
+}
+
+/// Parses a single comparator of a [`VersionRange`] (e.g., `^1.2` or `<2.0.0`).
+///
+/// **Arguments**
+///  * `raw`: The comparator to parse, without surrounding whitespace.
+///
+/// **Returns**  
+/// The oldest version (inclusive) and first version after (exclusive) the comparator, either of which may be [`None`] if it's unbounded.
+fn parse_comparator(raw: &str) -> Result<(Option<Version>, Option<Version>), ParseError> {
+    if raw.is_empty() {
+        return Err(ParseError::EmptyRange { raw: raw.into() });
+    }
+    if raw.to_lowercase() == "latest" {
+        return Ok((None, None));
+    }
+
+    // Split the operator off the version
+    let (op, version): (&str, &str) = match [">=", "<=", ">", "<", "=", "^", "~"].into_iter().find(|op| raw.starts_with(*op)) {
+        Some(op) => (op, raw[op.len()..].trim()),
+        None => ("", raw),
+    };
+    let version: &str = version.strip_prefix('v').unwrap_or(version);
+    if version.is_empty() {
+        return Err(ParseError::EmptyRange { raw: raw.into() });
+    }
+
+    // Build metadata is irrelevant for ranges, but a pre-release label is not
+    let version: &str = version.split_once('+').map(|(version, _)| version).unwrap_or(version);
+    let (version, pre): (&str, Label) = match version.split_once('-') {
+        Some((version, pre)) => (version, Label::parse("pre-release", pre, true)?),
+        None => (version, Label::empty()),
+    };
+
+    // Parse the (possibly partial) version numbers
+    let parts: Vec<&str> = version.split('.').collect();
+    if parts.len() > 3 {
+        return Err(ParseError::TooManyNumbers { raw: version.into(), got: parts.len() });
+    }
+    let mut numbers: [Option<u64>; 3] = [None; 3];
+    for (i, part) in parts.iter().enumerate() {
+        if *part == "*" || *part == "x" || *part == "X" {
+            continue;
+        }
+        if i > 0 && numbers[i - 1].is_none() {
+            return Err(ParseError::NumberAfterWildcard { raw: version.into() });
+        }
+        numbers[i] = match u64::from_str(part) {
+            Ok(number) => Some(number),
+            Err(err) => {
+                return Err(match i {
+                    0 => ParseError::MajorParseError { raw: part.to_string(), err },
+                    1 => ParseError::MinorParseError { raw: part.to_string(), err },
+                    _ => ParseError::PatchParseError { raw: part.to_string(), err },
+                });
+            },
+        };
+    }
+    let [major, minor, patch] = numbers;
+    let major: u64 = match major {
+        Some(major) => major,
+        // A wildcard major number matches anything
+        None => return Ok((None, None)),
+    };
+    if !pre.is_empty() && (minor.is_none() || patch.is_none()) {
+        return Err(ParseError::PartialPrerelease { raw: raw.into() });
+    }
+    let (numeric_lower, numeric_upper): (Version, Version) = bounds(major, minor, patch);
+    // With a pre-release, the interval only contains exactly that pre-release
+    let (lower, upper): (Version, Version) = if pre.is_empty() {
+        (numeric_lower, numeric_upper)
+    } else {
+        (Version { pre, ..numeric_lower }, Version { pre: pre.successor(), ..numeric_lower })
+    };
+
+    // Compute the range based on the operator
+    match op {
+        "" | "=" => Ok((Some(lower), Some(upper))),
+        "^" => {
+            let upper: Version = match (minor, patch) {
+                (Some(minor), _) if major == 0 && minor > 0 => Version::from_numbers(0, minor.saturating_add(1), 0),
+                (Some(0), _) if major == 0 => numeric_upper,
+                _ => Version::from_numbers(major.saturating_add(1), 0, 0),
+            };
+            Ok((Some(lower), Some(upper)))
+        },
+        "~" => {
+            let upper: Version = match minor {
+                Some(minor) => Version::from_numbers(major, minor.saturating_add(1), 0),
+                None => numeric_upper,
+            };
+            Ok((Some(lower), Some(upper)))
+        },
+        ">=" => Ok((Some(lower), None)),
+        ">" => Ok((Some(upper), None)),
+        "<" => Ok((None, Some(lower))),
+        "<=" => Ok((None, Some(upper))),
+        _ => unreachable!(),
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    06 Jan 2023, 15:01:17
 //  Last edited:
-//    07 Mar 2024, 11:58:09
+//    15 Oct 2026, 23:52:12
 //  Auto updated?
 //    Yes
 //
@@ -17,6 +17,7 @@ use std::convert::TryFrom;
 use std::error;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::Stream;
@@ -26,10 +27,25 @@ use tonic::client::Grpc as GrpcClient;
 use tonic::codec::{ProstCodec, Streaming};
 use tonic::codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError};
 use tonic::server::{Grpc as GrpcServer, NamedService, ServerStreamingService, UnaryService};
-use tonic::transport::{Channel, Endpoint};
+use tonic::transport::Channel;
 use tonic::{Code, Request, Response, Status};
 pub use JobServiceError as Error;
 
+use crate::address::{connect_grpc, grpc_endpoint};
+
+
+/***** CONSTANTS *****/
+/// The interval in which workers send a [`TaskStatus::Heartbeat`] for every task they are executing (or have queued).
+pub const TASK_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The time after which the driver considers a task lost if it stops receiving heartbeats for it.
+///
+/// Only enforced once a worker has sent at least one heartbeat, such that workers that don't send them aren't timed out.
+pub const TASK_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+
+
+
+
 
 /***** ERRORS *****/
 /// Defines the errors occurring when juggling [`PreprocessKind`]s.
@@ -227,6 +243,16 @@ pub enum TaskStatus {
     Failed = 18,
 }
 
+/// Auxillary enum that defines the output channels of a task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum OutputChannel {
+    /// The task wrote to its stdout.
+    Stdout = 0,
+    /// The task wrote to its stderr.
+    Stderr = 1,
+}
+
 
 
 
@@ -341,6 +367,10 @@ pub struct ExecuteRequest {
     /// The arguments to run the request with. Given as a JSON-encoded map of names to FullValues.
     #[prost(tag = "7", required, string)]
     pub args:   String,
+
+    /// If true, asks the worker to send the task's output as it is written (see [`ExecuteReply::output`]) instead of only on failure.
+    #[prost(tag = "8", optional, bool)]
+    pub stream_output: Option<bool>,
 }
 
 /// The reply sent by the worker while a task has executing.
@@ -352,6 +382,27 @@ pub struct ExecuteReply {
     /// An optional value that may be carried along with some of the statusses. See the `TaskStatus` enum for more information.
     #[prost(tag = "2", optional, string)]
     pub value:  Option<String>,
+    /// A chunk of output written by the task, if the client asked for it with [`ExecuteRequest::stream_output`].
+    ///
+    /// Such replies carry [`TaskStatus::Heartbeat`] as status, so clients unaware of this field simply ignore them.
+    #[prost(tag = "3", optional, message)]
+    pub output: Option<OutputChunk>,
+    /// The resources used by the task once it has completed, as a JSON-encoded [`ResourceUsage`](crate::reporting::ResourceUsage).
+    ///
+    /// Like [`ExecuteReply::output`], such replies carry [`TaskStatus::Heartbeat`] as status.
+    #[prost(tag = "4", optional, string)]
+    pub usage:  Option<String>,
+}
+
+/// A chunk of output written by a task while it executes.
+#[derive(Clone, Message)]
+pub struct OutputChunk {
+    /// The channel to which the task wrote the chunk.
+    #[prost(tag = "1", required, enumeration = "OutputChannel")]
+    pub channel: i32,
+    /// The chunk itself.
+    #[prost(tag = "2", required, string)]
+    pub data:    String,
 }
 
 
@@ -365,6 +416,9 @@ pub struct CommitRequest {
     /// The name that the result should have once it is committed.
     #[prost(tag = "2", string)]
     pub data_name:   String,
+    /// Describes where the result came from, as a JSON-encoded [`Provenance`](crate::data::Provenance).
+    #[prost(tag = "3", optional, string)]
+    pub provenance:  Option<String>,
 }
 
 /// The reply sent by the worker when the comittation was successfull.
@@ -398,8 +452,8 @@ impl JobServiceClient {
         let address: String = address.into();
 
         // Attempt to make the connection
-        let conn: Channel = match Endpoint::new(address.clone()) {
-            Ok(endpoint) => match endpoint.connect().await {
+        let conn: Channel = match grpc_endpoint(&address) {
+            Ok(endpoint) => match connect_grpc(endpoint, &address).await {
                 Ok(conn) => conn,
                 Err(err) => {
                     return Err(Error::ConnectError { address, err });